@@ -226,11 +226,19 @@ pub trait AssetTypeProvider {
 /// Trait defining the data for a specific type of asset
 pub trait AssetData {
     fn asset_type_guid(&self) -> Guid;
+
+    /// Get the amount of memory used by the asset data, in bytes.
+    /// 
+    /// This is used to track memory usage and enforce memory budgets, so should include any memory owned by the asset, e.g. pixel data of a texture.
+    fn memory_usage(&self) -> usize {
+        0
+    }
 }
 
 pub struct Asset {
-    metadata: Metadata,
-    data:     Box<dyn AssetData>,
+    metadata:     Metadata,
+    data:         Box<dyn AssetData>,
+    memory_usage: usize,
 }
 
 impl Asset {
@@ -238,7 +246,8 @@ impl Asset {
         T: AssetData + AssetTypeProvider + 'static
     {
         metadata.type_guid = T::GUID;
-        Self { metadata, data }
+        let memory_usage = data.memory_usage();
+        Self { metadata, data, memory_usage }
     }
 
     pub fn get_data<T>(&self) -> Option<&T> where
@@ -268,5 +277,21 @@ impl Asset {
     pub fn metadata(&self) -> &Metadata {
         &self.metadata
     }
+
+    /// Get the tracked memory usage of the asset, in bytes.
+    /// 
+    /// This is the memory usage reported by the asset data when it was added, or when it was last updated using [`AssetSystem::update_asset_memory_usage`].
+    /// 
+    /// [`AssetSystem::update_asset_memory_usage`]: crate::AssetSystem::update_asset_memory_usage
+    pub fn memory_usage(&self) -> usize {
+        self.memory_usage
+    }
+
+    /// Re-query the memory usage of the asset data, returning the previous and new memory usage.
+    pub(crate) fn refresh_memory_usage(&mut self) -> (usize, usize) {
+        let old = self.memory_usage;
+        self.memory_usage = self.data.memory_usage();
+        (old, self.memory_usage)
+    }
 }
 
@@ -0,0 +1,271 @@
+use std::collections::HashMap;
+
+use onca_common::{index_handle::IndexHandle16, prelude::*};
+use onca_logging::{log_info, log_warning, LogCategory};
+
+const LOG_CAT: LogCategory = LogCategory::new_with_sub("Asset System", "Memory Budget");
+
+//--------------------------------------------------------------
+// MEMORY USAGE
+//--------------------------------------------------------------
+
+/// Memory usage of all assets of a single asset type.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct AssetTypeMemoryUsage {
+    /// Number of resident assets of the type.
+    pub asset_count: usize,
+    /// Current memory usage in bytes.
+    pub current:     usize,
+    /// Peak memory usage in bytes.
+    pub peak:        usize,
+}
+
+impl AssetTypeMemoryUsage {
+    fn add(&mut self, size: usize) {
+        self.asset_count += 1;
+        self.current += size;
+        self.peak = self.peak.max(self.current);
+    }
+
+    fn remove(&mut self, size: usize) {
+        self.asset_count = self.asset_count.saturating_sub(1);
+        self.current = self.current.saturating_sub(size);
+    }
+}
+
+//--------------------------------------------------------------
+// BUDGETS
+//--------------------------------------------------------------
+
+pub const MEMORY_BUDGET_ID_BITS: usize = 8;
+type MemoryBudgetIndexHandle = IndexHandle16<MEMORY_BUDGET_ID_BITS>;
+
+/// Handle to a memory budget category.
+///
+/// Supports a maximum of 256 simultaneous budget categories.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MemoryBudget(MemoryBudgetIndexHandle);
+
+/// Error returned when registering a memory budget category.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MemoryBudgetRegisterError {
+    /// The same asset type was passed more than once.
+    DuplicateType(Guid),
+    /// The asset type is already covered by another budget category.
+    /// 
+    /// Gives a handle to the existing budget category.
+    TypeAlreadyCovered(Guid, MemoryBudget),
+}
+
+/// Info passed to an eviction callback when a budget has been exceeded.
+pub struct MemoryBudgetExceeded<'a> {
+    /// Name of the budget category.
+    pub name:       &'a str,
+    /// Budget in bytes.
+    pub budget:     usize,
+    /// Current memory usage of the category in bytes.
+    pub usage:      usize,
+    /// Resident assets in the budget category that can be evicted, together with their memory usage in bytes.
+    /// 
+    /// The asset that caused the budget to be exceeded is never a candidate.
+    pub candidates: &'a [(Guid, usize)],
+}
+
+impl MemoryBudgetExceeded<'_> {
+    /// Number of bytes that need to be freed to get back within the budget.
+    pub fn overshoot(&self) -> usize {
+        self.usage.saturating_sub(self.budget)
+    }
+}
+
+/// Callback that is called when a budget category has been exceeded.
+///
+/// The callback returns the [`Guid`]s of the assets that should be evicted from the asset system.
+pub type MemoryBudgetEvictionCallback = Box<dyn FnMut(&MemoryBudgetExceeded) -> Vec<Guid>>;
+
+/// Memory budget category, covering one or more asset types.
+pub struct MemoryBudgetInfo {
+    /// Name of the budget category, e.g. "Textures".
+    pub name:       String,
+    /// Budget in bytes.
+    pub budget:     usize,
+    /// Asset types covered by this budget.
+    pub type_guids: Vec<Guid>,
+    /// Current memory usage of the category in bytes.
+    pub usage:      usize,
+    /// Peak memory usage of the category in bytes.
+    pub peak:       usize,
+    eviction:       Option<MemoryBudgetEvictionCallback>,
+}
+
+/// Tracks the memory usage per asset type and enforces memory budgets.
+pub struct AssetMemoryTracker {
+    type_usage: HashMap<Guid, AssetTypeMemoryUsage>,
+    budgets:    Vec<(u8, Option<MemoryBudgetInfo>)>,
+    free_ids:   Vec<u16>,
+}
+
+impl AssetMemoryTracker {
+    pub fn new() -> Self {
+        Self {
+            type_usage: HashMap::new(),
+            budgets: Vec::new(),
+            free_ids: Vec::new(),
+        }
+    }
+
+    /// Register a new budget category covering the given asset types.
+    /// 
+    /// Each asset type can only be covered by a single budget category.
+    pub fn register_budget(&mut self, name: &str, budget: usize, type_guids: &[Guid], eviction: Option<MemoryBudgetEvictionCallback>) -> Result<MemoryBudget, MemoryBudgetRegisterError> {
+        for (idx, type_guid) in type_guids.iter().enumerate() {
+            if type_guids[..idx].contains(type_guid) {
+                return Err(MemoryBudgetRegisterError::DuplicateType(*type_guid));
+            }
+            if let Some(existing) = self.get_type_budget(*type_guid) {
+                return Err(MemoryBudgetRegisterError::TypeAlreadyCovered(*type_guid, existing));
+            }
+        }
+
+        let usage = type_guids.iter().map(|guid| self.get_type_usage(*guid).current).sum();
+        let info = MemoryBudgetInfo {
+            name: name.to_string(),
+            budget,
+            type_guids: type_guids.to_vec(),
+            usage,
+            peak: usage,
+            eviction,
+        };
+
+        if let Some(id) = self.free_ids.pop() {
+            let lifetime = self.budgets[id as usize].0;
+            self.budgets[id as usize].1 = Some(info);
+            Ok(MemoryBudget(IndexHandle16::new(id, lifetime as u16)))
+        } else {
+            let id = self.budgets.len() as u16;
+            self.budgets.push((0, Some(info)));
+            Ok(MemoryBudget(IndexHandle16::new(id, 0)))
+        }
+    }
+
+    /// Get the budget category covering an asset type, if there is one.
+    pub fn get_type_budget(&self, type_guid: Guid) -> Option<MemoryBudget> {
+        self.budgets.iter().enumerate().find_map(|(idx, (lifetime, budget))| match budget {
+            Some(budget) if budget.type_guids.contains(&type_guid) => Some(MemoryBudget(IndexHandle16::new(idx as u16, *lifetime as u16))),
+            _ => None,
+        })
+    }
+
+    /// Unregister a budget category.
+    pub fn unregister_budget(&mut self, budget: MemoryBudget) -> bool {
+        if !self.is_budget_valid(budget) {
+            return false;
+        }
+
+        let idx = budget.0.index() as usize;
+        let (lifetime, info) = &mut self.budgets[idx];
+        *lifetime = lifetime.wrapping_add(1) & MemoryBudgetIndexHandle::MAX_LIFETIME as u8;
+        *info = None;
+        self.free_ids.push(idx as u16);
+        true
+    }
+
+    /// Check if a budget handle is valid.
+    pub fn is_budget_valid(&self, budget: MemoryBudget) -> bool {
+        let idx = budget.0.index() as usize;
+        match self.budgets.get(idx) {
+            Some((lifetime, Some(_))) => budget.0.lifetime() == *lifetime as u16,
+            _ => false,
+        }
+    }
+
+    /// Get the info of a budget category.
+    pub fn get_budget_info(&self, budget: MemoryBudget) -> Option<&MemoryBudgetInfo> {
+        if !self.is_budget_valid(budget) {
+            return None;
+        }
+        self.budgets[budget.0.index() as usize].1.as_ref()
+    }
+
+    /// Change the budget of a category.
+    pub fn set_budget(&mut self, budget: MemoryBudget, bytes: usize) -> bool {
+        if !self.is_budget_valid(budget) {
+            return false;
+        }
+        self.budgets[budget.0.index() as usize].1.as_mut().unwrap().budget = bytes;
+        true
+    }
+
+    /// Get the memory usage of an asset type.
+    pub fn get_type_usage(&self, type_guid: Guid) -> AssetTypeMemoryUsage {
+        self.type_usage.get(&type_guid).copied().unwrap_or_default()
+    }
+
+    /// Get the total memory usage of all assets.
+    pub fn total_usage(&self) -> usize {
+        self.type_usage.values().map(|usage| usage.current).sum()
+    }
+
+    /// Iterate over the memory usage of all asset types.
+    pub fn iter_type_usage(&self) -> impl Iterator<Item = (Guid, AssetTypeMemoryUsage)> + '_ {
+        self.type_usage.iter().map(|(guid, usage)| (*guid, *usage))
+    }
+
+    pub(crate) fn track_add(&mut self, type_guid: Guid, size: usize) {
+        self.type_usage.entry(type_guid).or_default().add(size);
+        for (_, budget) in &mut self.budgets {
+            if let Some(budget) = budget && budget.type_guids.contains(&type_guid) {
+                budget.usage += size;
+                budget.peak = budget.peak.max(budget.usage);
+            }
+        }
+    }
+
+    pub(crate) fn track_remove(&mut self, type_guid: Guid, size: usize) {
+        if let Some(usage) = self.type_usage.get_mut(&type_guid) {
+            usage.remove(size);
+        }
+        for (_, budget) in &mut self.budgets {
+            if let Some(budget) = budget && budget.type_guids.contains(&type_guid) {
+                budget.usage = budget.usage.saturating_sub(size);
+            }
+        }
+    }
+
+    /// Get the budgets that currently exceed their limit, and that cover the given asset type.
+    pub(crate) fn get_exceeded_budgets(&self, type_guid: Guid) -> Vec<MemoryBudget> {
+        self.budgets.iter().enumerate().filter_map(|(idx, (lifetime, budget))| match budget {
+            Some(budget) if budget.usage > budget.budget && budget.type_guids.contains(&type_guid) =>
+                Some(MemoryBudget(IndexHandle16::new(idx as u16, *lifetime as u16))),
+            _ => None,
+        }).collect()
+    }
+
+    /// Run the eviction callback of an exceeded budget, returning the assets that should be evicted.
+    pub(crate) fn run_eviction(&mut self, budget: MemoryBudget, candidates: &[(Guid, usize)]) -> Vec<Guid> {
+        let Some(info) = self.budgets[budget.0.index() as usize].1.as_mut() else { return Vec::new() };
+
+        log_warning!(LOG_CAT, "Memory budget '{}' exceeded: {} / {} bytes", info.name, info.usage, info.budget);
+
+        let exceeded = MemoryBudgetExceeded {
+            name: &info.name,
+            budget: info.budget,
+            usage: info.usage,
+            candidates,
+        };
+        match &mut info.eviction {
+            Some(eviction) => eviction(&exceeded),
+            None => Vec::new(),
+        }
+    }
+
+    /// Log the current memory usage of all budgets.
+    pub fn log_usage(&self) {
+        for (_, budget) in &self.budgets {
+            if let Some(budget) = budget {
+                log_info!(LOG_CAT, "'{}': {} / {} bytes (peak: {} bytes)", budget.name, budget.usage, budget.budget, budget.peak);
+            }
+        }
+        log_info!(LOG_CAT, "Total asset memory usage: {} bytes", self.total_usage());
+    }
+}
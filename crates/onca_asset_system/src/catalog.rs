@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use onca_common::{prelude::*, io};
+use onca_fs::{Path, PathBuf};
+
+use crate::{AssetRedirectError, AssetRedirectTable};
+
+//--------------------------------------------------------------
+// CATALOG
+//--------------------------------------------------------------
+
+/// Magic number at the start of a serialized catalog.
+const CATALOG_MAGIC: [u8; 4] = *b"OCAT";
+/// Current version of the serialized catalog.
+const CATALOG_VERSION: u32 = 1;
+
+/// Entry in the asset catalog, describing where an asset can be loaded from.
+#[derive(Clone, Debug)]
+pub struct AssetCatalogEntry {
+    /// Type GUID of the asset.
+    pub type_guid: Guid,
+    /// Path to the asset.
+    pub path:      PathBuf,
+}
+
+/// Catalog of all assets known to the asset system, together with the redirects of deleted or renamed assets.
+///
+/// The catalog allows assets to be loaded by their [`Guid`], including assets that have been redirected to a replacement.
+pub struct AssetCatalog {
+    entries:   HashMap<Guid, AssetCatalogEntry>,
+    redirects: AssetRedirectTable,
+}
+
+impl AssetCatalog {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+            redirects: AssetRedirectTable::new(),
+        }
+    }
+
+    /// Add an asset to the catalog, replacing any existing entry for the same [`Guid`].
+    pub fn add_entry(&mut self, guid: Guid, type_guid: Guid, path: &Path) {
+        self.entries.insert(guid, AssetCatalogEntry { type_guid, path: path.to_path_buf() });
+    }
+
+    /// Remove an asset from the catalog.
+    ///
+    /// Returns the removed entry, if there was one.
+    pub fn remove_entry(&mut self, guid: Guid) -> Option<AssetCatalogEntry> {
+        self.entries.remove(&guid)
+    }
+
+    /// Get the entry of an asset, without following any redirects.
+    pub fn get_entry(&self, guid: Guid) -> Option<&AssetCatalogEntry> {
+        self.entries.get(&guid)
+    }
+
+    /// Iterate over all entries in the catalog.
+    pub fn entries(&self) -> impl Iterator<Item = (Guid, &AssetCatalogEntry)> + '_ {
+        self.entries.iter().map(|(guid, entry)| (*guid, entry))
+    }
+
+    /// Add a redirect from `from` to `to`, replacing any existing redirect for `from`.
+    ///
+    /// Returns the previous target of `from`, if there was one.
+    pub fn add_redirect(&mut self, from: Guid, to: Guid) -> Result<Option<Guid>, AssetRedirectError> {
+        self.redirects.add(from, to)
+    }
+
+    /// Remove the redirect for `from`.
+    ///
+    /// Returns the target of the removed redirect, if there was one.
+    pub fn remove_redirect(&mut self, from: Guid) -> Option<Guid> {
+        self.redirects.remove(from)
+    }
+
+    /// Resolve a [`Guid`] to its final replacement, following all chained redirects.
+    pub fn resolve(&self, guid: Guid) -> Guid {
+        self.redirects.resolve(guid)
+    }
+
+    /// Get the redirect table.
+    pub fn redirects(&self) -> &AssetRedirectTable {
+        &self.redirects
+    }
+
+    /// Write the catalog to a writer.
+    pub fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&CATALOG_MAGIC)?;
+        writer.write_all(&CATALOG_VERSION.to_le_bytes())?;
+
+        writer.write_all(&(self.entries.len() as u32).to_le_bytes())?;
+        for (guid, entry) in &self.entries {
+            let path = entry.path.as_str().trim_end_matches('\0');
+            writer.write_all(&guid.as_u128().to_be_bytes())?;
+            writer.write_all(&entry.type_guid.as_u128().to_be_bytes())?;
+            writer.write_all(&(path.len() as u32).to_le_bytes())?;
+            writer.write_all(path.as_bytes())?;
+        }
+
+        self.redirects.write_to(writer)
+    }
+
+    /// Read a catalog from a reader, merging it into the current catalog.
+    ///
+    /// Entries and redirects that are read will override any existing ones with the same [`Guid`].
+    pub fn read_from<R: io::Read>(&mut self, reader: &mut R) -> io::Result<()> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != CATALOG_MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid asset catalog magic number"));
+        }
+
+        let mut u32_buf = [0u8; 4];
+        reader.read_exact(&mut u32_buf)?;
+        let version = u32::from_le_bytes(u32_buf);
+        if version != CATALOG_VERSION {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Unsupported asset catalog version"));
+        }
+
+        reader.read_exact(&mut u32_buf)?;
+        let count = u32::from_le_bytes(u32_buf);
+
+        let mut guid_buf = [0u8; 16];
+        for _ in 0..count {
+            reader.read_exact(&mut guid_buf)?;
+            let guid = Guid::new(guid_buf);
+            reader.read_exact(&mut guid_buf)?;
+            let type_guid = Guid::new(guid_buf);
+
+            reader.read_exact(&mut u32_buf)?;
+            let mut path = vec![0u8; u32::from_le_bytes(u32_buf) as usize];
+            reader.read_exact(&mut path)?;
+            let path = PathBuf::from_utf8_lossy(&path).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid path for asset {guid} in catalog")))?;
+
+            self.entries.insert(guid, AssetCatalogEntry { type_guid, path });
+        }
+
+        self.redirects.read_from(reader)
+    }
+}
@@ -0,0 +1,60 @@
+use core::fmt;
+use onca_common::{prelude::*, io};
+
+/// Asset system error
+#[derive(Debug)]
+pub enum AssetError {
+    /// An asset with the same [`Guid`] has already been added.
+    DuplicateGuid(Guid),
+    /// The asset type has not been registered with the asset system.
+    UnknownType(Guid),
+    /// The asset is not loaded and cannot be found in the catalog.
+    UnknownAsset(Guid),
+    /// No more asset loaders can be registered.
+    LoaderLimit,
+    /// No loader is available for the asset.
+    NoLoader,
+    /// The asset loader does not support saving assets.
+    SaveUnsupported,
+    /// An I/O error occured.
+    IO(io::Error),
+    /// An asset loader failed to load or save an asset.
+    LoaderFailed{ loader: String, message: String },
+}
+
+impl AssetError {
+    /// Create a new [`AssetError::LoaderFailed`] error.
+    pub fn loader_failed(loader: &str, message: impl Into<String>) -> Self {
+        Self::LoaderFailed { loader: loader.to_string(), message: message.into() }
+    }
+}
+
+impl From<io::Error> for AssetError {
+    fn from(err: io::Error) -> Self {
+        Self::IO(err)
+    }
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetError::DuplicateGuid(guid)             => f.write_fmt(format_args!("An asset with guid '{guid}' already exists")),
+            AssetError::UnknownType(guid)               => f.write_fmt(format_args!("Unknown asset type with guid '{guid}'")),
+            AssetError::UnknownAsset(guid)              => f.write_fmt(format_args!("Unknown asset with guid '{guid}'")),
+            AssetError::LoaderLimit                     => f.write_str("Maximum number of asset loaders has been reached"),
+            AssetError::NoLoader                        => f.write_str("No loader is available for the asset"),
+            AssetError::SaveUnsupported                 => f.write_str("The asset loader does not support saving assets"),
+            AssetError::IO(err)                         => f.write_fmt(format_args!("I/O error: {err}")),
+            AssetError::LoaderFailed { loader, message } => f.write_fmt(format_args!("Asset loader '{loader}' failed: {message}")),
+        }
+    }
+}
+
+impl std::error::Error for AssetError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AssetError::IO(err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -9,7 +9,20 @@ pub use asset::*;
 
 mod loader;
 pub use loader::*;
-use onca_fs::{File, FileAccessFlags, Path, Permission};
+
+mod redirect;
+pub use redirect::*;
+
+mod catalog;
+pub use catalog::*;
+
+mod budget;
+pub use budget::*;
+
+mod error;
+pub use error::*;
+
+use onca_fs::{File, FileAccessFlags, FileCreateFlags, OpenMode, Path, Permission};
 
 //--------------------------------------------------------------
 // TAGS
@@ -104,7 +117,7 @@ impl AssetStore {
 		}
 	}
 
-	fn add_asset<T>(&mut self, mut metadata: Metadata, data: Box<dyn AssetData>) -> Result<AssetHandle, ()> where
+	fn add_asset<T>(&mut self, mut metadata: Metadata, data: Box<dyn AssetData>) -> Result<AssetHandle, AssetError> where
 		T: AssetData + AssetTypeProvider + 'static
 	{
 		if !metadata.guid.is_valid() {
@@ -113,7 +126,7 @@ impl AssetStore {
 
 		// Make sure an asset with the guid doesn't already exist
 		if self.guid_asset_mapping.contains_key(&metadata.guid) {
-			return Err(());
+			return Err(AssetError::DuplicateGuid(metadata.guid));
 		}		
 		
 		let guid = metadata.guid;
@@ -151,6 +164,26 @@ impl AssetStore {
 		}
 	}
 
+	fn get_handle(&self, guid: Guid) -> Option<AssetHandle> {
+		let idx = *self.guid_asset_mapping.get(&guid)?;
+		let lifetime = self.assets[idx as usize].0;
+		Some(AssetHandle(IndexHandle32::new(idx, lifetime as u32)))
+	}
+
+	fn get(&self, handle: AssetHandle) -> Option<&Asset> {
+		if !self.is_valid(handle) {
+			return None;
+		}
+		self.assets[handle.0.index() as usize].1.as_ref()
+	}
+
+	fn get_mut(&mut self, handle: AssetHandle) -> Option<&mut Asset> {
+		if !self.is_valid(handle) {
+			return None;
+		}
+		self.assets[handle.0.index() as usize].1.as_mut()
+	}
+
 	fn is_valid(&self, handle: AssetHandle) -> bool {
 		let idx = handle.0.index() as usize;
 		if idx >= self.assets.len() {
@@ -162,8 +195,8 @@ impl AssetStore {
 	}
 
 	/// Iterate over all existing assets.
-	fn for_each<F>(&self, f: F) where
-		F: Fn(&Asset)
+	fn for_each<F>(&self, mut f: F) where
+		F: FnMut(&Asset)
 	{
 		for (_, asset) in &self.assets {
 			if let Some(asset) = asset {
@@ -215,6 +248,7 @@ const ASSET_HANDLE_BITS: usize = 20;
 type AssetHandleIndexHandle = IndexHandle32<ASSET_HANDLE_BITS>;
 
 /// A handle to an asset
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct AssetHandle(AssetHandleIndexHandle);
 
 pub struct AssetSystem {
@@ -222,6 +256,8 @@ pub struct AssetSystem {
 	type_registry: AssetTypeRegistry,
 	assets:        AssetStore,
 	loaders:       AssetLoaderManager,
+	catalog:       AssetCatalog,
+	memory:        AssetMemoryTracker,
 }
 
 impl AssetSystem {
@@ -231,6 +267,8 @@ impl AssetSystem {
 			type_registry: AssetTypeRegistry::new(),
 			assets: AssetStore::new(),
 			loaders: AssetLoaderManager::new(),
+			catalog: AssetCatalog::new(),
+			memory: AssetMemoryTracker::new(),
 		}
 	}
 
@@ -310,31 +348,73 @@ impl AssetSystem {
 
 	/// Add a new asset to the asset system.
 	/// 
-	/// Returns an error if the asset already exists.
+	/// Returns an error if the asset already exists, or if the asset type has not been registered.
 	/// 
 	/// # Note
 	/// 
 	/// Adding assets manually should be avoided when possible.
 	/// The main use for this function is mainly to allow asset loaders to import a new asset to the asset system,
 	/// or to load an already existing asset.
-	pub fn add_asset<T>(&mut self, metadata: Metadata, data: Box<T>) -> Result<AssetHandle, ()> where
+	pub fn add_asset<T>(&mut self, metadata: Metadata, data: Box<T>) -> Result<AssetHandle, AssetError> where
 		T: AssetData + AssetTypeProvider + 'static
 	{
-		self.assets.add_asset::<T>(metadata, data)
+		if self.type_registry.from_guid(T::GUID).is_none() {
+			return Err(AssetError::UnknownType(T::GUID));
+		}
+
+		let handle = self.assets.add_asset::<T>(metadata, data)?;
+		self.on_asset_added(handle);
+		Ok(handle)
 	}
 
 	/// Load an asset from a file
+	/// 
+	/// If the loaded asset has been redirected, its replacement will be loaded instead.
 	// TODO: Support more file options
-	pub fn load_asset<T>(&mut self, path: &Path, settings: &LoadSettings) -> Result<AssetHandle, LoadResult> where
+	pub fn load_asset<T>(&mut self, path: &Path, settings: &LoadSettings) -> Result<AssetHandle, AssetError> where
 		T: AssetData + AssetTypeProvider + 'static
 	{
-		let file = File::open(path, Permission::Read, Permission::None, FileAccessFlags::None).map_err(|err| LoadResult::IO(err))?;
+		if self.type_registry.from_guid(T::GUID).is_none() {
+			return Err(AssetError::UnknownType(T::GUID));
+		}
+
+		let file = File::open(path, Permission::Read, Permission::None, FileAccessFlags::None)?;
 		let (metadata, data) = self.loaders.load(file, settings)?;
-		self.assets.add_asset::<T>(metadata, data).map_err(|_| LoadResult::DuplicateGuid)
+
+		// If the loaded asset was replaced by another asset, the replacement is used instead, so the redirected asset is never added
+		let resolved = self.catalog.resolve(metadata.guid);
+		if resolved != metadata.guid {
+			return self.load_asset_by_guid::<T>(resolved, settings);
+		}
+
+		let guid = metadata.guid;
+		let handle = self.assets.add_asset::<T>(metadata, data)?;
+		self.catalog.add_entry(guid, T::GUID, path);
+		self.on_asset_added(handle);
+		Ok(handle)
+	}
+
+	/// Load an asset from its [`Guid`], using the path stored in the catalog.
+	/// 
+	/// If the [`Guid`] has been redirected, the replacement asset will be loaded instead.
+	/// If the asset is already loaded, the handle to the loaded asset will be returned.
+	pub fn load_asset_by_guid<T>(&mut self, guid: Guid, settings: &LoadSettings) -> Result<AssetHandle, AssetError> where
+		T: AssetData + AssetTypeProvider + 'static
+	{
+		let resolved = self.catalog.resolve(guid);
+		if let Some(handle) = self.assets.get_handle(resolved) {
+			return Ok(handle);
+		}
+
+		let Some(entry) = self.catalog.get_entry(resolved) else {
+			return Err(AssetError::UnknownAsset(resolved));
+		};
+		let path = entry.path.clone();
+		self.load_asset::<T>(&path, settings)
 	}
 
 	// TODO
-	// pub fn save_asset(&self, asset: AssetHandle, path: &Path, settings: &SaveSettings) -> Result<(), SaveResult> {
+	// pub fn save_asset(&self, asset: AssetHandle, path: &Path, settings: &SaveSettings) -> Result<(), AssetError> {
 	// 	let file = File::open(path, Permission::Write, Permission::None, FileAccessFlags::None);
 	// 	self.loaders.save(file, settings)
 	// }
@@ -343,7 +423,21 @@ impl AssetSystem {
 	/// 
 	/// Returns the removed asset, if the handle points to a valid asset
 	pub fn remove_asset(&mut self, handle: AssetHandle) -> Option<Asset> {
-		self.assets.remove_asset(handle)
+		let asset = self.assets.remove_asset(handle)?;
+		self.memory.track_remove(asset.metadata().type_guid, asset.memory_usage());
+		Some(asset)
+	}
+
+	/// Get a handle to a loaded asset from its [`Guid`].
+	/// 
+	/// If the [`Guid`] has been redirected, the handle to the replacement asset will be returned.
+	pub fn get_asset_handle(&self, guid: Guid) -> Option<AssetHandle> {
+		self.assets.get_handle(self.catalog.resolve(guid))
+	}
+
+	/// Get an asset from its handle.
+	pub fn get_asset(&self, handle: AssetHandle) -> Option<&Asset> {
+		self.assets.get(handle)
 	}
 
 	/// Check if an asset handle is valid.
@@ -388,7 +482,7 @@ impl AssetSystem {
 	/// Register an asset loader with the asset system.
 	/// 
 	/// Return an error if too many loaders have been registered.
-	pub fn register_loader<T>(&mut self, loader: Box<T>) -> Result<AssetLoaderHandle, ()> where
+	pub fn register_loader<T>(&mut self, loader: Box<T>) -> Result<AssetLoaderHandle, AssetError> where
 		T: AssetLoader + 'static
 	{
 		self.loaders.register(loader)
@@ -400,4 +494,141 @@ impl AssetSystem {
 	pub fn unregister_loader(&mut self, handle: AssetLoaderHandle) -> Box<dyn AssetLoader> {
 		self.loaders.unregister(handle)
 	}
-}
\ No newline at end of file
+
+	//------------------------------
+	// MEMORY BUDGETS
+	//------------------------------
+
+	/// Register a memory budget category covering the given asset types.
+	/// 
+	/// When adding an asset causes the budget to be exceeded, the eviction callback is called to select which assets should be evicted from the asset system.
+	/// 
+	/// Returns an error if an asset type is passed more than once, or is already covered by another budget category.
+	pub fn register_memory_budget(&mut self, name: &str, budget: usize, type_guids: &[Guid], eviction: Option<MemoryBudgetEvictionCallback>) -> Result<MemoryBudget, MemoryBudgetRegisterError> {
+		self.memory.register_budget(name, budget, type_guids, eviction)
+	}
+
+	/// Unregister a memory budget category.
+	pub fn unregister_memory_budget(&mut self, budget: MemoryBudget) -> bool {
+		self.memory.unregister_budget(budget)
+	}
+
+	/// Get the memory tracker, containing the memory usage per asset type and budget category.
+	pub fn memory_tracker(&self) -> &AssetMemoryTracker {
+		&self.memory
+	}
+
+	/// Change the budget of a memory budget category.
+	/// 
+	/// Lowering a budget will not immediately evict any assets, eviction will only happen when the next asset covered by the budget is added.
+	pub fn set_memory_budget(&mut self, budget: MemoryBudget, bytes: usize) -> bool {
+		self.memory.set_budget(budget, bytes)
+	}
+
+	/// Re-query the memory usage of an asset, e.g. after its data has been modified or streamed in.
+	pub fn update_asset_memory_usage(&mut self, handle: AssetHandle) {
+		let Some(asset) = self.assets.get_mut(handle) else { return };
+		let type_guid = asset.metadata().type_guid;
+		let guid = asset.metadata().guid;
+		let (old, new) = asset.refresh_memory_usage();
+		if old != new {
+			self.memory.track_remove(type_guid, old);
+			self.memory.track_add(type_guid, new);
+			self.enforce_memory_budgets(type_guid, guid);
+		}
+	}
+
+	fn on_asset_added(&mut self, handle: AssetHandle) {
+		let Some(asset) = self.assets.get(handle) else { return };
+		let metadata = asset.metadata();
+		let (type_guid, guid) = (metadata.type_guid, metadata.guid);
+		self.memory.track_add(type_guid, asset.memory_usage());
+		self.enforce_memory_budgets(type_guid, guid);
+	}
+
+	/// Evict assets from any budget covering the asset type that is exceeded.
+	/// 
+	/// The asset that caused the budget to be exceeded is never evicted, as its handle is still being used by the caller.
+	fn enforce_memory_budgets(&mut self, type_guid: Guid, keep: Guid) {
+		for budget in self.memory.get_exceeded_budgets(type_guid) {
+			let Some(info) = self.memory.get_budget_info(budget) else { continue };
+
+			let mut candidates = Vec::new();
+			self.assets.for_each(|asset| {
+				let metadata = asset.metadata();
+				if metadata.guid != keep && info.type_guids.contains(&metadata.type_guid) {
+					candidates.push((metadata.guid, asset.memory_usage()));
+				}
+			});
+
+			for guid in self.memory.run_eviction(budget, &candidates) {
+				if guid == keep {
+					continue;
+				}
+				if let Some(handle) = self.assets.get_handle(guid) {
+					self.remove_asset(handle);
+				}
+			}
+		}
+	}
+
+	//------------------------------
+	// CATALOG
+	//------------------------------
+
+	/// Get the asset catalog, containing all known assets and redirects.
+	pub fn catalog(&self) -> &AssetCatalog {
+		&self.catalog
+	}
+
+	/// Remove an asset from the catalog, so it can no longer be loaded by its [`Guid`].
+	/// 
+	/// This does not remove the asset from the asset system if it is loaded.
+	pub fn remove_from_catalog(&mut self, guid: Guid) -> Option<AssetCatalogEntry> {
+		self.catalog.remove_entry(guid)
+	}
+
+	/// Save the catalog, including the redirect table, to a file.
+	pub fn save_catalog(&self, path: &Path) -> Result<(), AssetError> {
+		let mut file = File::create(path, OpenMode::CreateAlways, Permission::Write, Permission::None, FileCreateFlags::None, FileAccessFlags::None)?;
+		Ok(self.catalog.write_to(&mut file)?)
+	}
+
+	/// Load a catalog from a file, merging it into the current catalog.
+	pub fn load_catalog(&mut self, path: &Path) -> Result<(), AssetError> {
+		let mut file = File::open(path, Permission::Read, Permission::None, FileAccessFlags::None)?;
+		Ok(self.catalog.read_from(&mut file)?)
+	}
+
+	//------------------------------
+	// REDIRECTS
+	//------------------------------
+
+	/// Redirect an asset [`Guid`] to the [`Guid`] of a replacement asset.
+	/// 
+	/// This allows references to deleted or renamed assets to keep working, as the redirect is resolved transparently when getting or loading an asset.
+	/// 
+	/// Returns the previous target of the redirect, if there was one.
+	pub fn add_asset_redirect(&mut self, from: Guid, to: Guid) -> Result<Option<Guid>, AssetRedirectError> {
+		self.catalog.add_redirect(from, to)
+	}
+
+	/// Remove the redirect for an asset [`Guid`].
+	/// 
+	/// Returns the target of the removed redirect, if there was one.
+	pub fn remove_asset_redirect(&mut self, from: Guid) -> Option<Guid> {
+		self.catalog.remove_redirect(from)
+	}
+
+	/// Resolve an asset [`Guid`] to the [`Guid`] of its final replacement.
+	/// 
+	/// If the [`Guid`] is not redirected, it will be returned as is.
+	pub fn resolve_asset_guid(&self, guid: Guid) -> Guid {
+		self.catalog.resolve(guid)
+	}
+
+	/// Get the redirect table.
+	pub fn redirects(&self) -> &AssetRedirectTable {
+		self.catalog.redirects()
+	}
+}
@@ -3,11 +3,15 @@ use std::{collections::HashMap, io::{Read, Seek}};
 use onca_common::{prelude::*, io};
 use onca_fs::File;
 
-use crate::{AssetData, Metadata};
+use crate::{AssetData, AssetError, Metadata};
 
 
 /// Asset loader info
 pub struct AssetLoaderInfo<'a> {
+    /// Name of the loader.
+    /// 
+    /// Used to identify the loader in errors and logs.
+    pub name:           &'a str,
     /// Default extensions for the asset loader.
     /// 
     /// This will only be used for the fast path when looking for an initial loader to use.
@@ -28,29 +32,11 @@ pub struct LoadSettings {
 
 }
 
-pub enum LoadResult {
-    /// No loader is available for this asset.
-    Unavailable,
-    /// An asset with the same [`Guid`] has already been added.
-    DuplicateGuid,    
-    /// An I/O error occured when loading
-    IO(io::Error),
-}
-
 // TODO
 pub struct SaveSettings {
     
 }
 
-pub enum SaveResult {
-    /// No loader is available for this asset.
-    Unavailable,
-    /// The asset loader does not support saving assets.
-    Unsupported,
-    /// An I/O error occured when saving
-    IO(io::Error),
-}
-
 
 /// An object used to load/save an asset from and to a file.
 /// 
@@ -64,12 +50,14 @@ pub trait AssetLoader {
     
     /// Load an asset from a file
     // TODO: Support for complex assets, like GLTF or USD, which have multiple assets
-    fn load(&mut self, file: File, settings: &LoadSettings) -> Result<(Metadata, Box<dyn AssetData>), LoadResult>;
+    /// 
+    /// Errors specific to the loader should be returned as [`AssetError::LoaderFailed`].
+    fn load(&mut self, file: File, settings: &LoadSettings) -> Result<(Metadata, Box<dyn AssetData>), AssetError>;
 
     /// Store an asset to a file
     // TODO: Support for complex assets, that embed other assets
-    fn save(&mut self, _file: File, _settings: &SaveSettings) -> Result<(), SaveResult> {
-        Err(SaveResult::Unsupported)
+    fn save(&mut self, _file: File, _settings: &SaveSettings) -> Result<(), AssetError> {
+        Err(AssetError::SaveUnsupported)
     }
 }
 
@@ -89,8 +77,8 @@ impl AssetLoaderManager {
         }
     }
 
-    pub(crate) fn register(&mut self, loader: Box<dyn AssetLoader>) -> Result<AssetLoaderHandle, ()> {
-        let free_slot = self.loaders.iter().enumerate().find_map(|(idx, val)| val.as_ref().map(|_| idx));
+    pub(crate) fn register(&mut self, loader: Box<dyn AssetLoader>) -> Result<AssetLoaderHandle, AssetError> {
+        let free_slot = self.loaders.iter().position(|val| val.is_none());
         let idx = if let Some(slot) = free_slot {
             self.loaders[slot] = Some(loader);
             slot
@@ -99,7 +87,7 @@ impl AssetLoaderManager {
 
             // Only 65536 loader supported, should be enough for pretty much everything
             if idx >= u16::MAX as usize {
-                return Err(());
+                return Err(AssetError::LoaderLimit);
             }
 
             self.loaders.push(Some(loader));
@@ -122,7 +110,7 @@ impl AssetLoaderManager {
         core::mem::take(&mut self.loaders[idx]).unwrap()
     }
 
-    pub(crate) fn load(&mut self, mut file: File, settings: &LoadSettings) -> Result<(Metadata, Box<dyn AssetData>), LoadResult> {
+    pub(crate) fn load(&mut self, mut file: File, settings: &LoadSettings) -> Result<(Metadata, Box<dyn AssetData>), AssetError> {
         let ext = file.path().extension().unwrap_or("");
         if let Some(fast_path_index) = self.fast_path_mapping.get(ext) {
             let loader = self.loaders[*fast_path_index as usize].as_mut().unwrap();
@@ -141,7 +129,7 @@ impl AssetLoaderManager {
                 }
             }
         }
-        Err(LoadResult::Unavailable)
+        Err(AssetError::NoLoader)
     }
 
     fn check_file_or_magic(file: &mut File, magic: &[u8], offset: u64) -> bool {
@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use onca_common::{prelude::*, io};
+
+//--------------------------------------------------------------
+// REDIRECTS
+//--------------------------------------------------------------
+
+/// Error returned when adding a redirect.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AssetRedirectError {
+    /// Either the source or the target [`Guid`] is invalid.
+    InvalidGuid,
+    /// The redirect would point an asset to itself, either directly or through a chain of redirects.
+    Cycle,
+}
+
+/// Table mapping the [`Guid`] of a deleted or renamed asset to the [`Guid`] of its replacement.
+///
+/// Redirects can be chained, i.e. `A -> B -> C`, in which case resolving `A` will result in `C`.
+/// 
+/// The redirect table is persisted as part of the [`AssetCatalog`](crate::AssetCatalog).
+pub struct AssetRedirectTable {
+    redirects: HashMap<Guid, Guid>,
+}
+
+impl AssetRedirectTable {
+    pub fn new() -> Self {
+        Self {
+            redirects: HashMap::new(),
+        }
+    }
+
+    /// Add a redirect from `from` to `to`, replacing any existing redirect for `from`.
+    ///
+    /// Returns the previous target of `from`, if there was one.
+    pub fn add(&mut self, from: Guid, to: Guid) -> Result<Option<Guid>, AssetRedirectError> {
+        if !from.is_valid() || !to.is_valid() {
+            return Err(AssetRedirectError::InvalidGuid);
+        }
+        // The table is acyclic before the redirect is added, so walking the chain from `to` will always terminate.
+        // Any existing redirect from `from` gets replaced, so the redirect only creates a cycle if the chain reaches `from`.
+        let mut guid = to;
+        loop {
+            if guid == from {
+                return Err(AssetRedirectError::Cycle);
+            }
+            match self.redirects.get(&guid) {
+                Some(target) => guid = *target,
+                None => break,
+            }
+        }
+        Ok(self.redirects.insert(from, to))
+    }
+
+    /// Remove the redirect for `from`.
+    ///
+    /// Returns the target of the removed redirect, if there was one.
+    pub fn remove(&mut self, from: Guid) -> Option<Guid> {
+        self.redirects.remove(&from)
+    }
+
+    /// Get the direct target of a redirect, without following any chained redirects.
+    pub fn get(&self, from: Guid) -> Option<Guid> {
+        self.redirects.get(&from).copied()
+    }
+
+    /// Check if a [`Guid`] is redirected.
+    pub fn is_redirected(&self, guid: Guid) -> bool {
+        self.redirects.contains_key(&guid)
+    }
+
+    /// Resolve a [`Guid`] to its final replacement, following all chained redirects.
+    ///
+    /// If the [`Guid`] is not redirected, it will be returned as is.
+    pub fn resolve(&self, mut guid: Guid) -> Guid {
+        // Cycles are prevented when adding redirects, so this will always terminate
+        while let Some(target) = self.redirects.get(&guid) {
+            guid = *target;
+        }
+        guid
+    }
+
+    /// Get the number of redirects in the table.
+    pub fn len(&self) -> usize {
+        self.redirects.len()
+    }
+
+    /// Check if the table does not contain any redirects.
+    pub fn is_empty(&self) -> bool {
+        self.redirects.is_empty()
+    }
+
+    /// Remove all redirects.
+    pub fn clear(&mut self) {
+        self.redirects.clear();
+    }
+
+    /// Iterate over all redirects, as `(from, to)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (Guid, Guid)> + '_ {
+        self.redirects.iter().map(|(from, to)| (*from, *to))
+    }
+
+    /// Write the redirects to a writer, the header is written by the catalog.
+    pub(crate) fn write_to<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&(self.redirects.len() as u32).to_le_bytes())?;
+        for (from, to) in &self.redirects {
+            writer.write_all(&from.as_u128().to_be_bytes())?;
+            writer.write_all(&to.as_u128().to_be_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Read redirects from a reader, merging them into the current table.
+    ///
+    /// Redirects that are read will override any existing redirect with the same source.
+    pub(crate) fn read_from<R: io::Read>(&mut self, reader: &mut R) -> io::Result<()> {
+        let mut u32_buf = [0u8; 4];
+        reader.read_exact(&mut u32_buf)?;
+        let count = u32::from_le_bytes(u32_buf);
+
+        let mut guid_buf = [0u8; 16];
+        for _ in 0..count {
+            reader.read_exact(&mut guid_buf)?;
+            let from = Guid::new(guid_buf);
+            reader.read_exact(&mut guid_buf)?;
+            let to = Guid::new(guid_buf);
+
+            self.add(from, to).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("Invalid asset redirect {from} -> {to}: {err:?}")))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reject_direct_cycle() {
+        let a = Guid::new_u128(1);
+        let b = Guid::new_u128(2);
+
+        let mut table = AssetRedirectTable::new();
+        assert_eq!(table.add(a, a), Err(AssetRedirectError::Cycle));
+        assert_eq!(table.add(a, b), Ok(None));
+        assert_eq!(table.add(b, a), Err(AssetRedirectError::Cycle));
+    }
+
+    #[test]
+    fn reject_cycle_in_middle_of_chain() {
+        let a = Guid::new_u128(1);
+        let b = Guid::new_u128(2);
+        let c = Guid::new_u128(3);
+
+        let mut table = AssetRedirectTable::new();
+        assert_eq!(table.add(a, b), Ok(None));
+        assert_eq!(table.add(b, c), Ok(None));
+
+        // Replacing `b -> c` with `b -> a` would create `a -> b -> a`
+        assert_eq!(table.add(b, a), Err(AssetRedirectError::Cycle));
+        assert_eq!(table.add(c, a), Err(AssetRedirectError::Cycle));
+        assert_eq!(table.get(b), Some(c));
+        assert_eq!(table.resolve(a), c);
+    }
+
+    #[test]
+    fn allow_redirect_retarget() {
+        let a = Guid::new_u128(1);
+        let b = Guid::new_u128(2);
+        let c = Guid::new_u128(3);
+
+        let mut table = AssetRedirectTable::new();
+        assert_eq!(table.add(a, b), Ok(None));
+        assert_eq!(table.add(b, c), Ok(None));
+        assert_eq!(table.add(a, c), Ok(Some(b)));
+        assert_eq!(table.resolve(a), c);
+    }
+}
@@ -21,6 +21,15 @@ impl DynLib {
         os::load(&path).map(|handle| DynLib { handle })
     }
 
+    /// Get a dynamic library that is already loaded into the process, without loading it when it isn't
+    pub fn get_loaded(path: &str) -> Option<DynLib> {
+        scoped_alloc!(AllocId::TlsTemp);
+        // Go via a `String` to make sure it is null-terminated
+        let mut path = path.to_string();
+        path.null_terminate();
+        os::get_loaded(&path).map(|handle| DynLib { handle })
+    }
+
     /// Close a dynamic library, this has the same result as dropping the dynamic library, except that it has a return value
     /// 
     /// # Error
@@ -0,0 +1,386 @@
+use std::hash::Hasher;
+
+use super::Hasher256;
+
+const IV: [u32; 8] = [0x6A09E667, 0xBB67AE85, 0x3C6EF372, 0xA54FF53A, 0x510E527F, 0x9B05688C, 0x1F83D9AB, 0x5BE0CD19];
+const MSG_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+
+/// BLAKE3 hash
+///
+/// Info can be found at: https://github.com/BLAKE3-team/BLAKE3-specs/blob/master/blake3.pdf
+///
+/// # Note
+///
+/// This implementation only supports the default hash mode, not the keyed hash or key derivation modes.
+///
+/// As BLAKE3 is a tree hash, large inputs can be hashed in parallel,
+/// by hashing subtrees with [`BLAKE3::new_subtree`] and merging them using [`BLAKE3::merge_subtrees`] and [`BLAKE3::merge_subtrees_root`].
+pub struct BLAKE3 {
+    chunk:        ChunkState,
+    cv_stack:     [[u32; 8]; BLAKE3::MAX_DEPTH],
+    cv_stack_len: u8,
+}
+
+impl BLAKE3 {
+    /// Size of a chunk, the leaves of the hash tree.
+    pub const CHUNK_LEN: usize = 1024;
+    const BLOCK_LEN: usize = 64;
+    const MAX_DEPTH: usize = 54;
+
+    pub fn new() -> Self {
+        Self {
+            chunk: ChunkState::new(0),
+            cv_stack: [[0; 8]; Self::MAX_DEPTH],
+            cv_stack_len: 0,
+        }
+    }
+
+    /// Hash `bytes` in one go.
+    pub fn hash(bytes: &[u8]) -> [u8; 32] {
+        let mut hasher = Self::new();
+        hasher.write(bytes);
+        hasher.finish256()
+    }
+
+    /// Create a hasher for a subtree of a larger input, which starts at `offset` bytes in the input.
+    ///
+    /// A subtree needs to contain `2^n` chunks and start at a multiple of its size, except for the last subtree in the input, which may be smaller.
+    /// Use [`BLAKE3::left_subtree_len`] to split an input into valid subtrees.
+    ///
+    /// The result of the subtree needs to be retrieved using [`BLAKE3::finish_subtree`].
+    pub fn new_subtree(offset: u64) -> Self {
+        debug_assert!(offset % Self::CHUNK_LEN as u64 == 0, "A subtree needs to start at a chunk boundary");
+        Self {
+            chunk: ChunkState::new(offset / Self::CHUNK_LEN as u64),
+            cv_stack: [[0; 8]; Self::MAX_DEPTH],
+            cv_stack_len: 0,
+        }
+    }
+
+    /// Get the length of the left subtree, when an input of `len` bytes is split into 2 subtrees.
+    ///
+    /// The left subtree contains the largest power of 2 chunks that still leaves at least a single byte for the right subtree, `len` needs to be larger than [`BLAKE3::CHUNK_LEN`].
+    pub fn left_subtree_len(len: u64) -> u64 {
+        debug_assert!(len > Self::CHUNK_LEN as u64, "An input of a single chunk cannot be split");
+        let full_chunks = (len - 1) / Self::CHUNK_LEN as u64;
+        (1 << (63 - full_chunks.leading_zeros())) * Self::CHUNK_LEN as u64
+    }
+
+    /// Get the chaining value of a subtree, which can be merged with other subtrees.
+    pub fn finish_subtree(&self) -> [u8; 32] {
+        words_to_bytes(&self.final_output().chaining_value())
+    }
+
+    /// Merge the chaining values of 2 subtrees into the chaining value of their parent.
+    pub fn merge_subtrees(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        words_to_bytes(&parent_output(&bytes_to_words(left), &bytes_to_words(right)).chaining_value())
+    }
+
+    /// Merge the chaining values of the 2 subtrees at the root of the tree into the final hash.
+    pub fn merge_subtrees_root(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+        parent_output(&bytes_to_words(left), &bytes_to_words(right)).root_hash()
+    }
+
+    fn push_chunk_cv(&mut self, mut cv: [u32; 8], mut total_chunks: u64) {
+        // Each completed subtree is merged with its left sibling, which is at the top of the stack
+        while total_chunks & 1 == 0 {
+            self.cv_stack_len -= 1;
+            cv = parent_output(&self.cv_stack[self.cv_stack_len as usize], &cv).chaining_value();
+            total_chunks >>= 1;
+        }
+        self.cv_stack[self.cv_stack_len as usize] = cv;
+        self.cv_stack_len += 1;
+    }
+
+    fn final_output(&self) -> Output {
+        // The last chunk is never merged while writing, as it's unknown if it's the root, so merge all subtrees from right to left
+        let mut output = self.chunk.output();
+        for cv in self.cv_stack[..self.cv_stack_len as usize].iter().rev() {
+            output = parent_output(cv, &output.chaining_value());
+        }
+        output
+    }
+}
+
+impl Hasher for BLAKE3 {
+    fn finish(&self) -> u64 {
+        let hash = self.finish256();
+        u64::from_le_bytes(hash[..8].try_into().unwrap())
+    }
+
+    fn write(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            // A chunk is only completed once more data comes in, as the final chunk needs to be handled differently
+            if self.chunk.len() == Self::CHUNK_LEN {
+                let cv = self.chunk.output().chaining_value();
+                let total_chunks = self.chunk.counter + 1;
+                self.push_chunk_cv(cv, total_chunks);
+                self.chunk = ChunkState::new(total_chunks);
+            }
+
+            let len = (Self::CHUNK_LEN - self.chunk.len()).min(bytes.len());
+            self.chunk.write(&bytes[..len]);
+            bytes = &bytes[len..];
+        }
+    }
+}
+
+impl Hasher256 for BLAKE3 {
+    fn finish256(&self) -> [u8; 32] {
+        self.final_output().root_hash()
+    }
+}
+
+impl Default for BLAKE3 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//------------------------------
+
+/// State of the chunk that is currently being hashed.
+struct ChunkState {
+    cv:                [u32; 8],
+    counter:           u64,
+    block:             [u8; BLAKE3::BLOCK_LEN],
+    block_len:         u8,
+    blocks_compressed: u8,
+}
+
+impl ChunkState {
+    fn new(counter: u64) -> Self {
+        Self {
+            cv: IV,
+            counter,
+            block: [0; BLAKE3::BLOCK_LEN],
+            block_len: 0,
+            blocks_compressed: 0,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.blocks_compressed as usize * BLAKE3::BLOCK_LEN + self.block_len as usize
+    }
+
+    fn start_flag(&self) -> u32 {
+        if self.blocks_compressed == 0 { CHUNK_START } else { 0 }
+    }
+
+    fn write(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            // Like chunks, a block is only compressed once more data comes in, as the final block is flagged as the end of the chunk
+            if self.block_len as usize == BLAKE3::BLOCK_LEN {
+                let block = bytes_to_block(&self.block);
+                self.cv = first_8_words(compress(&self.cv, &block, self.counter, BLAKE3::BLOCK_LEN as u32, self.start_flag()));
+                self.blocks_compressed += 1;
+                self.block = [0; BLAKE3::BLOCK_LEN];
+                self.block_len = 0;
+            }
+
+            let len = (BLAKE3::BLOCK_LEN - self.block_len as usize).min(bytes.len());
+            self.block[self.block_len as usize..self.block_len as usize + len].copy_from_slice(&bytes[..len]);
+            self.block_len += len as u8;
+            bytes = &bytes[len..];
+        }
+    }
+
+    fn output(&self) -> Output {
+        Output {
+            cv: self.cv,
+            block: bytes_to_block(&self.block),
+            counter: self.counter,
+            block_len: self.block_len as u32,
+            flags: self.start_flag() | CHUNK_END,
+        }
+    }
+}
+
+/// Output of a chunk or parent, which is either used as the chaining value for its parent, or as the root hash.
+struct Output {
+    cv:        [u32; 8],
+    block:     [u32; 16],
+    counter:   u64,
+    block_len: u32,
+    flags:     u32,
+}
+
+impl Output {
+    fn chaining_value(&self) -> [u32; 8] {
+        first_8_words(compress(&self.cv, &self.block, self.counter, self.block_len, self.flags))
+    }
+
+    fn root_hash(&self) -> [u8; 32] {
+        // The root is the only output that can be extended past 32 bytes, which would use the counter for the output block
+        let words = compress(&self.cv, &self.block, 0, self.block_len, self.flags | ROOT);
+        words_to_bytes(&first_8_words(words))
+    }
+}
+
+fn parent_output(left: &[u32; 8], right: &[u32; 8]) -> Output {
+    let mut block = [0; 16];
+    block[..8].copy_from_slice(left);
+    block[8..].copy_from_slice(right);
+    Output {
+        cv: IV,
+        block,
+        counter: 0,
+        block_len: BLAKE3::BLOCK_LEN as u32,
+        flags: PARENT,
+    }
+}
+
+fn g(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize, mx: u32, my: u32) {
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(mx);
+    state[d] = (state[d] ^ state[a]).rotate_right(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(12);
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(my);
+    state[d] = (state[d] ^ state[a]).rotate_right(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(7);
+}
+
+fn round(state: &mut [u32; 16], m: &[u32; 16]) {
+    // Mix the columns
+    g(state, 0, 4, 8, 12, m[0], m[1]);
+    g(state, 1, 5, 9, 13, m[2], m[3]);
+    g(state, 2, 6, 10, 14, m[4], m[5]);
+    g(state, 3, 7, 11, 15, m[6], m[7]);
+    // Mix the diagonals
+    g(state, 0, 5, 10, 15, m[8], m[9]);
+    g(state, 1, 6, 11, 12, m[10], m[11]);
+    g(state, 2, 7, 8, 13, m[12], m[13]);
+    g(state, 3, 4, 9, 14, m[14], m[15]);
+}
+
+fn compress(cv: &[u32; 8], block: &[u32; 16], counter: u64, block_len: u32, flags: u32) -> [u32; 16] {
+    let mut state = [
+        cv[0], cv[1], cv[2], cv[3],
+        cv[4], cv[5], cv[6], cv[7],
+        IV[0], IV[1], IV[2], IV[3],
+        counter as u32, (counter >> 32) as u32, block_len, flags,
+    ];
+
+    let mut block = *block;
+    for i in 0..7 {
+        round(&mut state, &block);
+        if i != 6 {
+            block = MSG_PERMUTATION.map(|idx| block[idx]);
+        }
+    }
+
+    for i in 0..8 {
+        state[i] ^= state[i + 8];
+        state[i + 8] ^= cv[i];
+    }
+    state
+}
+
+fn first_8_words(words: [u32; 16]) -> [u32; 8] {
+    words[..8].try_into().unwrap()
+}
+
+fn bytes_to_block(bytes: &[u8; BLAKE3::BLOCK_LEN]) -> [u32; 16] {
+    std::array::from_fn(|i| u32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap()))
+}
+
+fn bytes_to_words(bytes: &[u8; 32]) -> [u32; 8] {
+    std::array::from_fn(|i| u32::from_le_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap()))
+}
+
+fn words_to_bytes(words: &[u32; 8]) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    for (i, word) in words.iter().enumerate() {
+        bytes[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::Hasher;
+
+    use crate::hashing::Hasher256;
+
+    use super::BLAKE3;
+
+    fn generate_data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    pub fn blake3() {
+        let expected = [
+            0xaf, 0x13, 0x49, 0xb9, 0xf5, 0xf9, 0xa1, 0xa6, 0xa0, 0x40, 0x4d, 0xea, 0x36, 0xdc, 0xc9, 0x49,
+            0x9b, 0xcb, 0x25, 0xc9, 0xad, 0xc1, 0x12, 0xb7, 0xcc, 0x9a, 0x93, 0xca, 0xe4, 0x1f, 0x32, 0x62,
+        ];
+        assert_eq!(BLAKE3::hash(b""), expected);
+
+        let expected = [
+            0x64, 0x37, 0xb3, 0xac, 0x38, 0x46, 0x51, 0x33, 0xff, 0xb6, 0x3b, 0x75, 0x27, 0x3a, 0x8d, 0xb5,
+            0x48, 0xc5, 0x58, 0x46, 0x5d, 0x79, 0xdb, 0x03, 0xfd, 0x35, 0x9c, 0x6c, 0xd5, 0xbd, 0x9d, 0x85,
+        ];
+        assert_eq!(BLAKE3::hash(b"abc"), expected);
+
+        // 43 bytes
+        let expected = [
+            0x2f, 0x15, 0x14, 0x18, 0x1a, 0xad, 0xcc, 0xd9, 0x13, 0xab, 0xd9, 0x4c, 0xfa, 0x59, 0x27, 0x01,
+            0xa5, 0x68, 0x6a, 0xb2, 0x3f, 0x8d, 0xf1, 0xdf, 0xf1, 0xb7, 0x47, 0x10, 0xfe, 0xbc, 0x6d, 0x4a,
+        ];
+        assert_eq!(BLAKE3::hash(b"The quick brown fox jumps over the lazy dog"), expected);
+
+        // Exactly a single chunk
+        let expected = [
+            0x42, 0x21, 0x47, 0x39, 0xf0, 0x95, 0xa4, 0x06, 0xf3, 0xfc, 0x83, 0xde, 0xb8, 0x89, 0x74, 0x4a,
+            0xc0, 0x0d, 0xf8, 0x31, 0xc1, 0x0d, 0xaa, 0x55, 0x18, 0x9b, 0x5d, 0x12, 0x1c, 0x85, 0x5a, 0xf7,
+        ];
+        assert_eq!(BLAKE3::hash(&generate_data(1024)), expected);
+
+        let expected = [
+            0xd0, 0x02, 0x78, 0xae, 0x47, 0xeb, 0x27, 0xb3, 0x4f, 0xae, 0xcf, 0x67, 0xb4, 0xfe, 0x26, 0x3f,
+            0x82, 0xd5, 0x41, 0x29, 0x16, 0xc1, 0xff, 0xd9, 0x7c, 0x8c, 0xb7, 0xfb, 0x81, 0x4b, 0x84, 0x44,
+        ];
+        assert_eq!(BLAKE3::hash(&generate_data(1025)), expected);
+
+        let expected = [
+            0xd9, 0x3c, 0x23, 0xee, 0xda, 0xf1, 0x65, 0xa7, 0xe0, 0xbe, 0x90, 0x8b, 0xa8, 0x6f, 0x1a, 0x7a,
+            0x52, 0x0d, 0x56, 0x8d, 0x2d, 0x13, 0xcd, 0xe7, 0x87, 0xc8, 0x58, 0x0c, 0x5c, 0x72, 0xcc, 0x54,
+        ];
+        assert_eq!(BLAKE3::hash(&generate_data(100000)), expected);
+    }
+
+    #[test]
+    pub fn blake3_streaming() {
+        let data = generate_data(5000);
+        let expected = BLAKE3::hash(&data);
+        for step in [1, 7, 64, 200, 1024, 1025] {
+            let mut blake3 = BLAKE3::new();
+            for chunk in data.chunks(step) {
+                blake3.write(chunk);
+            }
+            assert_eq!(blake3.finish256(), expected);
+        }
+    }
+
+    #[test]
+    pub fn blake3_subtrees() {
+        let data = generate_data(100000);
+
+        let left_len = BLAKE3::left_subtree_len(data.len() as u64) as usize;
+        assert_eq!(left_len, 65536);
+
+        let mut left = BLAKE3::new_subtree(0);
+        left.write(&data[..left_len]);
+        let mut right = BLAKE3::new_subtree(left_len as u64);
+        right.write(&data[left_len..]);
+
+        let hash = BLAKE3::merge_subtrees_root(&left.finish_subtree(), &right.finish_subtree());
+        assert_eq!(hash, BLAKE3::hash(&data));
+    }
+}
@@ -11,6 +11,12 @@ pub use md5::*;
 mod sha1;
 pub use sha1::*;
 
+mod xxh3;
+pub use xxh3::*;
+
+mod blake3;
+pub use blake3::*;
+
 
 
 
@@ -30,4 +36,13 @@ pub trait Hasher160: Hasher {
     /// Additinal `write`s will continue from the current value.
     /// If you need to start a fresh hash value, you will have to create a new hasher. 
     fn finish160(&self) -> [u8; 20];
+}
+
+pub trait Hasher256: Hasher {
+    /// returns the hash value for the values written so far.
+    /// 
+    /// Depsite its name, the method does not reset the hasher's internal state.
+    /// Additinal `write`s will continue from the current value.
+    /// If you need to start a fresh hash value, you will have to create a new hasher. 
+    fn finish256(&self) -> [u8; 32];
 }
\ No newline at end of file
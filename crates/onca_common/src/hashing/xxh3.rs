@@ -0,0 +1,316 @@
+use std::hash::Hasher;
+
+const PRIME32_1: u64 = 0x9E3779B1;
+const PRIME32_2: u64 = 0x85EBCA77;
+const PRIME32_3: u64 = 0xC2B2AE3D;
+const PRIME64_1: u64 = 0x9E3779B185EBCA87;
+const PRIME64_2: u64 = 0xC2B2AE3D27D4EB4F;
+const PRIME64_3: u64 = 0x165667B19E3779F9;
+const PRIME64_4: u64 = 0x85EBCA77C2B2AE63;
+const PRIME64_5: u64 = 0x27D4EB2F165667C5;
+const PRIME_MX1: u64 = 0x165667919E3779F9;
+const PRIME_MX2: u64 = 0x9FB21C651E98DF25;
+
+/// Default secret, used to scramble the input.
+const SECRET: [u8; 192] = [
+    0xb8, 0xfe, 0x6c, 0x39, 0x23, 0xa4, 0x4b, 0xbe, 0x7c, 0x01, 0x81, 0x2c, 0xf7, 0x21, 0xad, 0x1c,
+    0xde, 0xd4, 0x6d, 0xe9, 0x83, 0x90, 0x97, 0xdb, 0x72, 0x40, 0xa4, 0xa4, 0xb7, 0xb3, 0x67, 0x1f,
+    0xcb, 0x79, 0xe6, 0x4e, 0xcc, 0xc0, 0xe5, 0x78, 0x82, 0x5a, 0xd0, 0x7d, 0xcc, 0xff, 0x72, 0x21,
+    0xb8, 0x08, 0x46, 0x74, 0xf7, 0x43, 0x24, 0x8e, 0xe0, 0x35, 0x90, 0xe6, 0x81, 0x3a, 0x26, 0x4c,
+    0x3c, 0x28, 0x52, 0xbb, 0x91, 0xc3, 0x00, 0xcb, 0x88, 0xd0, 0x65, 0x8b, 0x1b, 0x53, 0x2e, 0xa3,
+    0x71, 0x64, 0x48, 0x97, 0xa2, 0x0d, 0xf9, 0x4e, 0x38, 0x19, 0xef, 0x46, 0xa9, 0xde, 0xac, 0xd8,
+    0xa8, 0xfa, 0x76, 0x3f, 0xe3, 0x9c, 0x34, 0x3f, 0xf9, 0xdc, 0xbb, 0xc7, 0xc7, 0x0b, 0x4f, 0x1d,
+    0x8a, 0x51, 0xe0, 0x4b, 0xcd, 0xb4, 0x59, 0x31, 0xc8, 0x9f, 0x7e, 0xc9, 0xd9, 0x78, 0x73, 0x64,
+    0xea, 0xc5, 0xac, 0x83, 0x34, 0xd3, 0xeb, 0xc3, 0xc5, 0x81, 0xa0, 0xff, 0xfa, 0x13, 0x63, 0xeb,
+    0x17, 0x0d, 0xdd, 0x51, 0xb7, 0xf0, 0xda, 0x49, 0xd3, 0x16, 0x55, 0x26, 0x29, 0xd4, 0x68, 0x9e,
+    0x2b, 0x16, 0xbe, 0x58, 0x7d, 0x47, 0xa1, 0xfc, 0x8f, 0xf8, 0xb8, 0xd1, 0x7a, 0xd0, 0x31, 0xce,
+    0x45, 0xcb, 0x3a, 0x8f, 0x95, 0x16, 0x04, 0x28, 0xaf, 0xd7, 0xfb, 0xca, 0xbb, 0x4b, 0x40, 0x7e,
+];
+
+/// 64-bit XXH3 hash
+///
+/// Info can be found at: https://github.com/Cyan4973/xxHash/blob/dev/doc/xxhash_spec.md
+///
+/// # Note
+///
+/// This implementation only supports the default secret and a seed of 0, and produces the same hash as `XXH3_64bits`.
+pub struct XXH3 {
+    acc:         [u64; Self::ACC_SIZE],
+    /// Buffered input, the last bytes are always kept in the buffer, as the final stripe needs to be processed differently.
+    buffer:      [u8; Self::BUFFER_SIZE],
+    buffer_len:  usize,
+    /// Number of stripes processed in the current block.
+    num_stripes: usize,
+    total_len:   u64,
+}
+
+impl XXH3 {
+    const ACC_SIZE: usize = 8;
+    const STRIPE_LEN: usize = 64;
+    const BUFFER_SIZE: usize = 256;
+    const SECRET_CONSUME_RATE: usize = 8;
+    const STRIPES_PER_BLOCK: usize = (SECRET.len() - Self::STRIPE_LEN) / Self::SECRET_CONSUME_RATE;
+    const SECRET_LIMIT: usize = SECRET.len() - Self::STRIPE_LEN;
+    const SECRET_LAST_ACC_START: usize = 7;
+    const SECRET_MERGE_ACCS_START: usize = 11;
+    const MID_SIZE_MAX: usize = 240;
+    const MID_SIZE_START_OFFSET: usize = 3;
+    const MID_SIZE_LAST_OFFSET: usize = 17;
+    const SECRET_SIZE_MIN: usize = 136;
+
+    pub fn new() -> Self {
+        Self {
+            acc: [PRIME32_3, PRIME64_1, PRIME64_2, PRIME64_3, PRIME64_4, PRIME32_2, PRIME64_5, PRIME32_1],
+            buffer: [0; Self::BUFFER_SIZE],
+            buffer_len: 0,
+            num_stripes: 0,
+            total_len: 0,
+        }
+    }
+
+    /// Hash `bytes` in one go.
+    pub fn hash(bytes: &[u8]) -> u64 {
+        let mut hasher = Self::new();
+        hasher.write(bytes);
+        hasher.finish()
+    }
+
+    fn accumulate_stripe(acc: &mut [u64; Self::ACC_SIZE], stripe: &[u8], secret: &[u8]) {
+        for i in 0..Self::ACC_SIZE {
+            let data = read_u64(stripe, i * 8);
+            let key = data ^ read_u64(secret, i * 8);
+            acc[i ^ 1] = acc[i ^ 1].wrapping_add(data);
+            acc[i] = acc[i].wrapping_add((key & 0xFFFF_FFFF).wrapping_mul(key >> 32));
+        }
+    }
+
+    fn scramble(acc: &mut [u64; Self::ACC_SIZE]) {
+        for (i, val) in acc.iter_mut().enumerate() {
+            let key = read_u64(&SECRET, Self::SECRET_LIMIT + i * 8);
+            *val ^= *val >> 47;
+            *val ^= key;
+            *val = val.wrapping_mul(PRIME32_1);
+        }
+    }
+
+    /// Process whole stripes, scrambling the accumulators at the end of each block.
+    fn consume_stripes(acc: &mut [u64; Self::ACC_SIZE], num_stripes_in_block: &mut usize, mut stripes: &[u8]) {
+        while !stripes.is_empty() {
+            let count = (stripes.len() / Self::STRIPE_LEN).min(Self::STRIPES_PER_BLOCK - *num_stripes_in_block);
+            for i in 0..count {
+                let secret = &SECRET[(*num_stripes_in_block + i) * Self::SECRET_CONSUME_RATE..];
+                Self::accumulate_stripe(acc, &stripes[i * Self::STRIPE_LEN..], secret);
+            }
+            *num_stripes_in_block += count;
+            stripes = &stripes[count * Self::STRIPE_LEN..];
+
+            if *num_stripes_in_block == Self::STRIPES_PER_BLOCK {
+                Self::scramble(acc);
+                *num_stripes_in_block = 0;
+            }
+        }
+    }
+
+    fn finish_long(&self) -> u64 {
+        let mut acc = self.acc;
+        let mut num_stripes = self.num_stripes;
+
+        let mut last_stripe = [0; Self::STRIPE_LEN];
+        if self.buffer_len >= Self::STRIPE_LEN {
+            // The stripe containing the last byte is always processed as the last stripe, even when it is a whole stripe
+            let stripes_len = (self.buffer_len - 1) / Self::STRIPE_LEN * Self::STRIPE_LEN;
+            Self::consume_stripes(&mut acc, &mut num_stripes, &self.buffer[..stripes_len]);
+            last_stripe.copy_from_slice(&self.buffer[self.buffer_len - Self::STRIPE_LEN..self.buffer_len]);
+        } else {
+            // The last stripe overlaps with data that has already been processed, which is kept at the end of the buffer
+            let prev_len = Self::STRIPE_LEN - self.buffer_len;
+            last_stripe[..prev_len].copy_from_slice(&self.buffer[Self::BUFFER_SIZE - prev_len..]);
+            last_stripe[prev_len..].copy_from_slice(&self.buffer[..self.buffer_len]);
+        }
+        Self::accumulate_stripe(&mut acc, &last_stripe, &SECRET[Self::SECRET_LIMIT - Self::SECRET_LAST_ACC_START..]);
+
+        let mut res = self.total_len.wrapping_mul(PRIME64_1);
+        for i in 0..Self::ACC_SIZE / 2 {
+            let secret = Self::SECRET_MERGE_ACCS_START + i * 16;
+            res = res.wrapping_add(mul128_fold64(acc[i * 2] ^ read_u64(&SECRET, secret), acc[i * 2 + 1] ^ read_u64(&SECRET, secret + 8)));
+        }
+        avalanche(res)
+    }
+
+    fn hash_short(bytes: &[u8]) -> u64 {
+        let len = bytes.len();
+        match len {
+            0 => xxh64_avalanche(read_u64(&SECRET, 56) ^ read_u64(&SECRET, 64)),
+            1..=3 => {
+                let combined = ((bytes[0] as u32) << 16) | ((bytes[len >> 1] as u32) << 24) | (bytes[len - 1] as u32) | ((len as u32) << 8);
+                let bitflip = (read_u32(&SECRET, 0) ^ read_u32(&SECRET, 4)) as u64;
+                xxh64_avalanche(combined as u64 ^ bitflip)
+            },
+            4..=8 => {
+                let first = read_u32(bytes, 0) as u64;
+                let last = read_u32(bytes, len - 4) as u64;
+                let bitflip = read_u64(&SECRET, 8) ^ read_u64(&SECRET, 16);
+                rrmxmx((last + (first << 32)) ^ bitflip, len as u64)
+            },
+            9..=16 => {
+                let input_lo = read_u64(bytes, 0) ^ (read_u64(&SECRET, 24) ^ read_u64(&SECRET, 32));
+                let input_hi = read_u64(bytes, len - 8) ^ (read_u64(&SECRET, 40) ^ read_u64(&SECRET, 48));
+                let acc = (len as u64).wrapping_add(input_lo.swap_bytes()).wrapping_add(input_hi).wrapping_add(mul128_fold64(input_lo, input_hi));
+                avalanche(acc)
+            },
+            17..=128 => {
+                let mut acc = (len as u64).wrapping_mul(PRIME64_1);
+                let num_pairs = (len - 1) / 32 + 1;
+                for i in 0..num_pairs {
+                    acc = acc.wrapping_add(mix16(&bytes[i * 16..], &SECRET[i * 32..]));
+                    acc = acc.wrapping_add(mix16(&bytes[len - (i + 1) * 16..], &SECRET[i * 32 + 16..]));
+                }
+                avalanche(acc)
+            },
+            _ => {
+                let mut acc = (len as u64).wrapping_mul(PRIME64_1);
+                for i in 0..8 {
+                    acc = acc.wrapping_add(mix16(&bytes[i * 16..], &SECRET[i * 16..]));
+                }
+                acc = avalanche(acc);
+
+                for i in 8..len / 16 {
+                    acc = acc.wrapping_add(mix16(&bytes[i * 16..], &SECRET[(i - 8) * 16 + Self::MID_SIZE_START_OFFSET..]));
+                }
+                acc = acc.wrapping_add(mix16(&bytes[len - 16..], &SECRET[Self::SECRET_SIZE_MIN - Self::MID_SIZE_LAST_OFFSET..]));
+                avalanche(acc)
+            },
+        }
+    }
+}
+
+impl Hasher for XXH3 {
+    fn finish(&self) -> u64 {
+        if self.total_len <= Self::MID_SIZE_MAX as u64 {
+            Self::hash_short(&self.buffer[..self.buffer_len])
+        } else {
+            self.finish_long()
+        }
+    }
+
+    fn write(&mut self, mut bytes: &[u8]) {
+        self.total_len += bytes.len() as u64;
+
+        // The buffer is only processed once more data comes in, as the last stripe needs to be handled when finishing the hash
+        if bytes.len() <= Self::BUFFER_SIZE - self.buffer_len {
+            self.buffer[self.buffer_len..self.buffer_len + bytes.len()].copy_from_slice(bytes);
+            self.buffer_len += bytes.len();
+            return;
+        }
+
+        if self.buffer_len != 0 {
+            let fill_len = Self::BUFFER_SIZE - self.buffer_len;
+            self.buffer[self.buffer_len..].copy_from_slice(&bytes[..fill_len]);
+            bytes = &bytes[fill_len..];
+            Self::consume_stripes(&mut self.acc, &mut self.num_stripes, &self.buffer);
+            self.buffer_len = 0;
+        }
+
+        if bytes.len() > Self::BUFFER_SIZE {
+            let stripes_len = (bytes.len() - 1) / Self::STRIPE_LEN * Self::STRIPE_LEN;
+            Self::consume_stripes(&mut self.acc, &mut self.num_stripes, &bytes[..stripes_len]);
+
+            // Keep the last processed stripe, as the final stripe may overlap with it
+            self.buffer[Self::BUFFER_SIZE - Self::STRIPE_LEN..].copy_from_slice(&bytes[stripes_len - Self::STRIPE_LEN..stripes_len]);
+            bytes = &bytes[stripes_len..];
+        }
+
+        self.buffer[..bytes.len()].copy_from_slice(bytes);
+        self.buffer_len = bytes.len();
+    }
+}
+
+impl Default for XXH3 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+fn mul128_fold64(a: u64, b: u64) -> u64 {
+    let res = a as u128 * b as u128;
+    res as u64 ^ (res >> 64) as u64
+}
+
+fn mix16(bytes: &[u8], secret: &[u8]) -> u64 {
+    mul128_fold64(read_u64(bytes, 0) ^ read_u64(secret, 0), read_u64(bytes, 8) ^ read_u64(secret, 8))
+}
+
+fn avalanche(mut hash: u64) -> u64 {
+    hash ^= hash >> 37;
+    hash = hash.wrapping_mul(PRIME_MX1);
+    hash ^ (hash >> 32)
+}
+
+fn xxh64_avalanche(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(PRIME64_2);
+    hash ^= hash >> 29;
+    hash = hash.wrapping_mul(PRIME64_3);
+    hash ^ (hash >> 32)
+}
+
+fn rrmxmx(mut hash: u64, len: u64) -> u64 {
+    hash ^= hash.rotate_left(49) ^ hash.rotate_left(24);
+    hash = hash.wrapping_mul(PRIME_MX2);
+    hash ^= (hash >> 35).wrapping_add(len);
+    hash = hash.wrapping_mul(PRIME_MX2);
+    hash ^ (hash >> 28)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hash::Hasher;
+
+    use super::XXH3;
+
+    const LOREM_IPSUM_96: &str = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. Donec vel nisi magna. Duis aliquam leo.";
+    const LOREM_IPSUM_256: &str = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. Aenean vel elit justo. Ut quis felis vitae nisi malesuada malesuada nec eu massa. Suspendisse fringilla nulla id tristique commodo. Pellentesque nec nisi ut elit pretium tincidunt quis non dolor dui.";
+
+    fn generate_data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    pub fn xxh3() {
+        assert_eq!(XXH3::hash(b""), 0x2d06800538d394c2);
+        // 1-3 bytes
+        assert_eq!(XXH3::hash(b"a"), 0xe6c632b61e964e1f);
+        assert_eq!(XXH3::hash(b"abc"), 0x78af5f94892f3950);
+        // 17-128 bytes
+        assert_eq!(XXH3::hash(b"The quick brown fox"), 0xf8b92649fd8122b4);
+        assert_eq!(XXH3::hash(b"The quick brown fox jumps over the lazy dog"), 0xce7d19a5418fb365);
+        assert_eq!(XXH3::hash(LOREM_IPSUM_96.as_bytes()), 0xa8ba8164fb048a01);
+        // Long input
+        assert_eq!(XXH3::hash(LOREM_IPSUM_256.as_bytes()), 0xef5d905e344c3a4d);
+        // Exactly a single block
+        assert_eq!(XXH3::hash(&generate_data(1024)), 0xe5d78bafa45b2aa5);
+        assert_eq!(XXH3::hash(&generate_data(1025)), 0xe95c42288f28186e);
+        assert_eq!(XXH3::hash(&generate_data(5000)), 0xb418500fc42320ee);
+        assert_eq!(XXH3::hash(&generate_data(100000)), 0x42c23aeead96750d);
+    }
+
+    #[test]
+    pub fn xxh3_streaming() {
+        let data = generate_data(5000);
+        for step in [1, 7, 64, 200, 256, 257, 1000] {
+            let mut xxh3 = XXH3::new();
+            for chunk in data.chunks(step) {
+                xxh3.write(chunk);
+            }
+            assert_eq!(xxh3.finish(), 0xb418500fc42320ee);
+        }
+    }
+}
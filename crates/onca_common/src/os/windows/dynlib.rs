@@ -2,7 +2,7 @@ use windows::{
     core::PCSTR,
     Win32::{
         Foundation::{HMODULE, GetLastError, FreeLibrary},
-        System::LibraryLoader::{LoadLibraryA, GetProcAddress},
+        System::LibraryLoader::{LoadLibraryA, GetModuleHandleExA, GetProcAddress},
     },
 };
 
@@ -27,6 +27,17 @@ pub(crate) fn load(s: &str) -> Result<DynLibHandle, i32> {
     }
 }
 
+pub(crate) fn get_loaded(s: &str) -> Option<DynLibHandle> {
+    unsafe {
+        let mut handle = HMODULE::default();
+        // Default flags increment the reference count of the module, so it can be closed like a loaded library
+        match GetModuleHandleExA(0, PCSTR(s.as_ptr()), &mut handle) {
+            Ok(_) => Some(DynLibHandle(handle)),
+            Err(_) => None,
+        }
+    }
+}
+
 pub(crate) fn close(handle: DynLibHandle) -> Result<(), i32> {
     unsafe {
         match FreeLibrary(handle.0) {
@@ -0,0 +1,941 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    num::NonZeroU64,
+    sync::Arc,
+};
+
+use flate2::read::DeflateDecoder;
+use onca_common::{io::{self, Read, Seek}, sync::Mutex};
+
+use crate::*;
+
+const ZIP_LOCAL_HEADER_SIGNATURE:   u32 = 0x04034B50;
+const ZIP_CENTRAL_HEADER_SIGNATURE: u32 = 0x02014B50;
+const ZIP_EOCD_SIGNATURE:           u32 = 0x06054B50;
+const ZIP64_EOCD_LOCATOR_SIGNATURE: u32 = 0x07064B50;
+const ZIP64_EOCD_SIGNATURE:         u32 = 0x06064B50;
+
+const ZIP_LOCAL_HEADER_SIZE:   usize = 30;
+const ZIP_EOCD_SIZE:           usize = 22;
+const ZIP64_EOCD_LOCATOR_SIZE: usize = 20;
+const ZIP64_EOCD_SIZE:         usize = 56;
+
+/// Magic at the start of an opak archive.
+pub const OPAK_MAGIC: [u8; 4] = *b"OPAK";
+/// Current version of the opak format.
+pub const OPAK_VERSION: u16 = 1;
+const OPAK_HEADER_SIZE: usize = 24;
+
+/// Archive format.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArchiveFormat {
+    /// Zip archive, including zip64 archives.
+    ///
+    /// Only stored and deflated files are supported, encrypted files cannot be opened.
+    Zip,
+    /// Onca package.
+    ///
+    /// All values are stored in little endian.
+    ///
+    /// The archive starts with a 24 byte header:
+    /// - magic: `OPAK`
+    /// - version: u16
+    /// - flags: u16, reserved and should be 0
+    /// - entry count: u32
+    /// - table of contents size: u32
+    /// - table of contents offset: u64
+    ///
+    /// The table of contents contains an entry for each file:
+    /// - data offset: u64
+    /// - compressed size: u64
+    /// - uncompressed size: u64
+    /// - compression: u8, 0 for stored, 1 for raw deflate
+    /// - reserved: u8
+    /// - path length: u16
+    /// - path: UTF-8 path relative to the root of the archive, using `/` as separator
+    ///
+    /// Directories are not stored, but are derived from the paths of the files.
+    Opak,
+}
+
+impl ArchiveFormat {
+    /// Detect the format of an archive based on its magic.
+    ///
+    /// The cursor of the file will be moved.
+    ///
+    /// Returns [`None`] if the file is not a supported archive.
+    pub fn detect(file: &mut File) -> io::Result<Option<ArchiveFormat>> {
+        let mut magic = [0; 4];
+        file.seek(io::SeekFrom::Start(0))?;
+        match file.read_exact(&mut magic) {
+            Ok(_) => {},
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err),
+        }
+
+        let signature = u32::from_le_bytes(magic);
+        Ok(if signature == ZIP_LOCAL_HEADER_SIGNATURE || signature == ZIP_EOCD_SIGNATURE {
+            Some(ArchiveFormat::Zip)
+        } else if magic == OPAK_MAGIC {
+            Some(ArchiveFormat::Opak)
+        } else {
+            None
+        })
+    }
+}
+
+/// Compression method of a file in an archive.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ArchiveCompression {
+    /// The file is stored without compression.
+    Stored,
+    /// The file is compressed using raw deflate.
+    Deflate,
+}
+
+/// Info about a file in an archive, as stored in the central directory of the archive.
+#[derive(Clone, Copy, Debug)]
+struct ArchiveFileInfo {
+    /// `None` if the file uses an unsupported compression method or is encrypted.
+    compression:     Option<ArchiveCompression>,
+    /// Offset of the local header for zip archives, offset of the data for opak archives
+    offset:          u64,
+    compressed_size: u64,
+    size:            u64,
+}
+
+/// Node in an archive
+enum ArchiveNode {
+    Directory,
+    File(ArchiveFileInfo),
+}
+
+/// Read-only file system over an archive.
+///
+/// The central directory of the archive is read and cached when the archive is opened,
+/// file data is only read and decompressed when a file is read, so content can be accessed without extracting the archive.
+///
+/// To make archives transparently accessible via the [`VirtualFileSystem`], use [`ArchiveFileSystem::register`],
+/// or mount an archive directly using [`MountProvider::SubSystem`].
+///
+/// All paths are relative to the root of the archive.
+pub struct ArchiveFileSystem {
+    path:   PathBuf,
+    format: ArchiveFormat,
+    source: Arc<Mutex<File>>,
+    /// Nodes, indexed by their path using `/` as separator
+    nodes:  BTreeMap<String, ArchiveNode>,
+}
+
+impl ArchiveFileSystem {
+    /// Open an archive from a file, the format is detected from the content of the file.
+    ///
+    /// # Error
+    ///
+    /// Returns an error if the file is not a supported archive, or if the central directory of the archive could not be read.
+    pub fn new(mut file: File) -> io::Result<Arc<Self>> {
+        let Some(format) = ArchiveFormat::detect(&mut file)? else {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("`{}` is not a supported archive", file.path())));
+        };
+
+        let files = match format {
+            ArchiveFormat::Zip  => Self::read_zip_central_directory(&mut file)?,
+            ArchiveFormat::Opak => Self::read_opak_table_of_contents(&mut file)?,
+        };
+
+        let mut nodes = BTreeMap::new();
+        for (key, node) in files {
+            let key = key.replace('\\', "/").trim_matches('/').to_string();
+            if key.is_empty() {
+                continue;
+            }
+
+            let mut parent = Self::get_parent_key(&key);
+            while !parent.is_empty() && !nodes.contains_key(parent) {
+                nodes.insert(parent.to_string(), ArchiveNode::Directory);
+                parent = Self::get_parent_key(parent);
+            }
+            nodes.insert(key, node);
+        }
+
+        Ok(Arc::new(Self {
+            path: file.path().to_path_buf(),
+            format,
+            source: Arc::new(Mutex::new(file)),
+            nodes,
+        }))
+    }
+
+    /// Register sub-systems for `.zip` and `.opak` archives, allowing any path going through an archive to be resolved by the VFS.
+    pub fn register(vfs: &VirtualFileSystem) {
+        vfs.register_sub_system("zip",
+            |file| Ok(ArchiveFormat::detect(file)? == Some(ArchiveFormat::Zip)),
+            |file| Self::new(file).map(|archive| archive as VirtualSubSystemHandle)
+        );
+        vfs.register_sub_system("opak",
+            |file| Ok(ArchiveFormat::detect(file)? == Some(ArchiveFormat::Opak)),
+            |file| Self::new(file).map(|archive| archive as VirtualSubSystemHandle)
+        );
+    }
+
+    /// Get the format of the archive.
+    #[must_use]
+    pub fn format(&self) -> ArchiveFormat {
+        self.format
+    }
+
+    /// Open a decompression stream to a file in the archive.
+    ///
+    /// # Error
+    ///
+    /// Returns an error if the file does not exist, or uses an unsupported compression method.
+    pub fn open_stream(&self, path: &Path) -> io::Result<ArchiveFileStream> {
+        let info = match self.nodes.get(&Self::get_key(path)) {
+            Some(ArchiveNode::File(info)) => *info,
+            _ => return Err(io::Error::new(io::ErrorKind::NotFound, format!("`{path}` is not a file in archive `{}`", self.path))),
+        };
+
+        let Some(compression) = info.compression else {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, format!("`{path}` in archive `{}` is encrypted or uses an unsupported compression method", self.path)));
+        };
+
+        let data_offset = match self.format {
+            ArchiveFormat::Zip  => Self::get_zip_data_offset(&mut self.source.lock(), info.offset)?,
+            ArchiveFormat::Opak => info.offset,
+        };
+
+        let raw = ArchiveRawReader { source: self.source.clone(), offset: data_offset, size: info.compressed_size, pos: 0 };
+        let reader = match compression {
+            ArchiveCompression::Stored  => ArchiveStreamReader::Stored(raw),
+            ArchiveCompression::Deflate => ArchiveStreamReader::Deflate(DeflateDecoder::new(raw)),
+        };
+        Ok(ArchiveFileStream { reader, size: info.size, pos: 0 })
+    }
+
+    /// Get the key of a path into the node map
+    fn get_key(path: &Path) -> String {
+        path.as_str().replace('\\', "/").trim_matches('/').to_string()
+    }
+
+    /// Get the key of the parent of a node
+    fn get_parent_key(key: &str) -> &str {
+        key.rsplit_once('/').map_or("", |(parent, _)| parent)
+    }
+
+    fn is_dir(&self, key: &str) -> bool {
+        key.is_empty() || matches!(self.nodes.get(key), Some(ArchiveNode::Directory))
+    }
+
+    fn create_entry(key: &str, node: &ArchiveNode) -> (Box<dyn EntryHandle>, EntryType) {
+        let (entry_type, info) = match node {
+            ArchiveNode::Directory  => (EntryType::Directory, None),
+            ArchiveNode::File(info) => (EntryType::File, Some(*info)),
+        };
+        (Box::new(ArchiveEntryHandle { path: PathBuf::from(key.to_string()), entry_type, info }), entry_type)
+    }
+
+    fn read_zip_central_directory(file: &mut File) -> io::Result<Vec<(String, ArchiveNode)>> {
+        let file_len = file.seek(io::SeekFrom::End(0))?;
+
+        // The end of central directory record is followed by a comment of at most u16::MAX bytes
+        let tail_len = file_len.min((ZIP_EOCD_SIZE + u16::MAX as usize) as u64) as usize;
+        let tail_offset = file_len - tail_len as u64;
+        let mut tail = vec![0; tail_len];
+        read_exact_at(file, tail_offset, &mut tail)?;
+
+        let eocd_pos = (0..=tail_len.saturating_sub(ZIP_EOCD_SIZE)).rev()
+            .find(|&pos| tail.len() >= pos + ZIP_EOCD_SIZE && tail[pos..pos + 4] == ZIP_EOCD_SIGNATURE.to_le_bytes())
+            .ok_or_else(|| invalid_archive("Zip archive is missing its end of central directory record"))?;
+
+        let mut eocd = ByteReader::new(&tail[eocd_pos + 4..]);
+        eocd.skip(6)?;
+        let mut num_entries = eocd.u16()? as u64;
+        let mut cd_size = eocd.u32()? as u64;
+        let mut cd_offset = eocd.u32()? as u64;
+
+        if num_entries == u16::MAX as u64 || cd_size == u32::MAX as u64 || cd_offset == u32::MAX as u64 {
+            let eocd_offset = tail_offset + eocd_pos as u64;
+            if eocd_offset < ZIP64_EOCD_LOCATOR_SIZE as u64 {
+                return Err(invalid_archive("Zip64 archive is missing its end of central directory locator"));
+            }
+
+            let mut locator = [0; ZIP64_EOCD_LOCATOR_SIZE];
+            read_exact_at(file, eocd_offset - ZIP64_EOCD_LOCATOR_SIZE as u64, &mut locator)?;
+            let mut locator = ByteReader::new(&locator);
+            if locator.u32()? != ZIP64_EOCD_LOCATOR_SIGNATURE {
+                return Err(invalid_archive("Zip64 archive is missing its end of central directory locator"));
+            }
+            locator.skip(4)?;
+            let zip64_eocd_offset = locator.u64()?;
+
+            let mut zip64_eocd = [0; ZIP64_EOCD_SIZE];
+            read_exact_at(file, zip64_eocd_offset, &mut zip64_eocd)?;
+            let mut zip64_eocd = ByteReader::new(&zip64_eocd);
+            if zip64_eocd.u32()? != ZIP64_EOCD_SIGNATURE {
+                return Err(invalid_archive("Zip64 archive has an invalid end of central directory record"));
+            }
+            zip64_eocd.skip(28)?;
+            num_entries = zip64_eocd.u64()?;
+            cd_size = zip64_eocd.u64()?;
+            cd_offset = zip64_eocd.u64()?;
+        }
+
+        if cd_offset.checked_add(cd_size).map_or(true, |end| end > file_len) {
+            return Err(invalid_archive("Zip archive central directory is out of bounds"));
+        }
+
+        let mut central_directory = vec![0; cd_size as usize];
+        read_exact_at(file, cd_offset, &mut central_directory)?;
+        let mut reader = ByteReader::new(&central_directory);
+
+        let mut files = Vec::with_capacity(num_entries.min(u16::MAX as u64) as usize);
+        for _ in 0..num_entries {
+            if reader.u32()? != ZIP_CENTRAL_HEADER_SIGNATURE {
+                return Err(invalid_archive("Zip archive has an invalid central directory header"));
+            }
+            reader.skip(4)?;
+            let flags = reader.u16()?;
+            let method = reader.u16()?;
+            reader.skip(8)?;
+            let mut compressed_size = reader.u32()? as u64;
+            let mut size = reader.u32()? as u64;
+            let name_len = reader.u16()? as usize;
+            let extra_len = reader.u16()? as usize;
+            let comment_len = reader.u16()? as usize;
+            reader.skip(8)?;
+            let mut offset = reader.u32()? as u64;
+            let name = String::from_utf8_lossy(reader.bytes(name_len)?).into_owned();
+
+            // Values that do not fit in the header are stored in the zip64 extended information, in the order of the header
+            let mut extra = ByteReader::new(reader.bytes(extra_len)?);
+            while extra.remaining() >= 4 {
+                let id = extra.u16()?;
+                let len = extra.u16()? as usize;
+                let mut data = ByteReader::new(extra.bytes(len)?);
+                if id == 0x0001 {
+                    if size == u32::MAX as u64 {
+                        size = data.u64()?;
+                    }
+                    if compressed_size == u32::MAX as u64 {
+                        compressed_size = data.u64()?;
+                    }
+                    if offset == u32::MAX as u64 {
+                        offset = data.u64()?;
+                    }
+                }
+            }
+            reader.skip(comment_len)?;
+
+            if name.ends_with('/') {
+                files.push((name, ArchiveNode::Directory));
+                continue;
+            }
+
+            let encrypted = flags & 0x1 != 0;
+            let compression = match method {
+                _ if encrypted => None,
+                0 => Some(ArchiveCompression::Stored),
+                8 => Some(ArchiveCompression::Deflate),
+                _ => None,
+            };
+            files.push((name, ArchiveNode::File(ArchiveFileInfo { compression, offset, compressed_size, size })));
+        }
+        Ok(files)
+    }
+
+    /// Get the offset of the data of a zip file, which is located after its local header
+    fn get_zip_data_offset(file: &mut File, header_offset: u64) -> io::Result<u64> {
+        let mut header = [0; ZIP_LOCAL_HEADER_SIZE];
+        read_exact_at(file, header_offset, &mut header)?;
+        let mut header = ByteReader::new(&header);
+        if header.u32()? != ZIP_LOCAL_HEADER_SIGNATURE {
+            return Err(invalid_archive("Zip archive has an invalid local file header"));
+        }
+        header.skip(22)?;
+        let name_len = header.u16()? as u64;
+        let extra_len = header.u16()? as u64;
+        Ok(header_offset + ZIP_LOCAL_HEADER_SIZE as u64 + name_len + extra_len)
+    }
+
+    fn read_opak_table_of_contents(file: &mut File) -> io::Result<Vec<(String, ArchiveNode)>> {
+        let mut header = [0; OPAK_HEADER_SIZE];
+        read_exact_at(file, 0, &mut header)?;
+        let mut header = ByteReader::new(&header);
+        header.skip(4)?;
+        let version = header.u16()?;
+        if version != OPAK_VERSION {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, format!("Unsupported opak version {version}, expected version {OPAK_VERSION}")));
+        }
+        header.skip(2)?;
+        let num_entries = header.u32()?;
+        let toc_size = header.u32()?;
+        let toc_offset = header.u64()?;
+
+        let mut toc = vec![0; toc_size as usize];
+        read_exact_at(file, toc_offset, &mut toc)?;
+        let mut reader = ByteReader::new(&toc);
+
+        let mut files = Vec::with_capacity(num_entries.min(u16::MAX as u32) as usize);
+        for _ in 0..num_entries {
+            let offset = reader.u64()?;
+            let compressed_size = reader.u64()?;
+            let size = reader.u64()?;
+            let compression = match reader.u8()? {
+                0 => Some(ArchiveCompression::Stored),
+                1 => Some(ArchiveCompression::Deflate),
+                _ => None,
+            };
+            reader.skip(1)?;
+            let path_len = reader.u16()? as usize;
+            let path = std::str::from_utf8(reader.bytes(path_len)?).map_err(|_| invalid_archive("Opak archive contains a path that is not valid UTF-8"))?;
+            files.push((path.to_string(), ArchiveNode::File(ArchiveFileInfo { compression, offset, compressed_size, size })));
+        }
+        Ok(files)
+    }
+}
+
+impl SubSystem for ArchiveFileSystem {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn get_support(&self) -> SubSystemSupport {
+        SubSystemSupport::None
+    }
+
+    fn entry(&self, path: &Path) -> io::Result<Entry> {
+        let key = Self::get_key(path);
+        if key.is_empty() {
+            let (handle, entry_type) = Self::create_entry(&key, &ArchiveNode::Directory);
+            return Ok(Entry::from_raw(handle, entry_type));
+        }
+
+        match self.nodes.get(&key) {
+            Some(node) => {
+                let (handle, entry_type) = Self::create_entry(&key, node);
+                Ok(Entry::from_raw(handle, entry_type))
+            },
+            None => Err(io::Error::new(io::ErrorKind::NotFound, format!("`{path}` does not exist in archive `{}`", self.path))),
+        }
+    }
+
+    fn directory_exists(&self, path: &Path) -> io::Result<bool> {
+        Ok(self.is_dir(&Self::get_key(path)))
+    }
+
+    fn read_directory(&self, path: &Path) -> io::Result<EntryIter> {
+        let key = Self::get_key(path);
+        if !self.is_dir(&key) {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("`{path}` is not a directory in archive `{}`", self.path)));
+        }
+
+        let entries = self.nodes.iter()
+            .filter(|(node_key, _)| *node_key != &key && Self::get_parent_key(node_key) == key)
+            .map(|(key, node)| Self::create_entry(key, node))
+            .collect();
+        // The entry iterator stops when it is given an empty path, so make sure that reading the root does not end the iteration
+        let iter_path = if key.is_empty() { self.path.clone() } else { PathBuf::from(key) };
+        Ok(unsafe { EntryIter::from_raw(iter_path, Box::new(ArchiveEntrySearchHandle { entries })) })
+    }
+
+    fn create_directory(&self, _path: &Path, _recursively: bool) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    fn remove_directory(&self, _path: &Path) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    fn remove_directory_all(&self, _path: &Path) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    fn create_file(
+        &self,
+        path: &Path,
+        open_mode: OpenMode,
+        access_perms: Permission,
+        _shared_access_perms: Permission,
+        _create_flags: FileCreateFlags,
+        _access_flags: FileAccessFlags,
+    ) -> io::Result<File> {
+        if open_mode != OpenMode::OpenExisting || access_perms.intersects(Permission::Append | Permission::Write | Permission::Delete) {
+            return Err(read_only_error());
+        }
+
+        let key = Self::get_key(path);
+        let stream = self.open_stream(path)?;
+        let handle = Box::new(ArchiveFileHandle { path: PathBuf::from(key.clone()), stream });
+        Ok(unsafe { File::from_raw(handle, PathBuf::from(key), access_perms) })
+    }
+
+    fn delete_file(&self, _path: &Path) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    fn watch_files(&self, _path: &Path, _watch_subtree: bool, _filter: FileWatcherFilter, _name_filter: Option<&str>) -> io::Result<Filewatcher> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "Archives do not support file watchers"))
+    }
+}
+
+//------------------------------
+
+/// Reader over the raw, possibly compressed, data of a file in an archive
+struct ArchiveRawReader {
+    source: Arc<Mutex<File>>,
+    offset: u64,
+    size:   u64,
+    pos:    u64,
+}
+
+impl io::Read for ArchiveRawReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = (buf.len() as u64).min(self.size.saturating_sub(self.pos)) as usize;
+        if len == 0 {
+            return Ok(0);
+        }
+
+        // The archive file is shared between all streams, so the cursor needs to be set for every read
+        let mut source = self.source.lock();
+        source.seek(io::SeekFrom::Start(self.offset + self.pos))?;
+        let read = source.read(&mut buf[..len])?;
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+enum ArchiveStreamReader {
+    Stored(ArchiveRawReader),
+    Deflate(DeflateDecoder<ArchiveRawReader>),
+}
+
+/// Decompression stream over a file in an archive.
+///
+/// Seeking in a stored file is free, seeking in a compressed file requires the data up to the new position to be decompressed,
+/// which means that seeking backwards will restart decompression from the start of the file.
+pub struct ArchiveFileStream {
+    reader: ArchiveStreamReader,
+    size:   u64,
+    pos:    u64,
+}
+
+impl ArchiveFileStream {
+    /// Get the uncompressed size of the file.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.size
+    }
+
+    /// Check if the file is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+}
+
+impl io::Read for ArchiveFileStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = (buf.len() as u64).min(self.size.saturating_sub(self.pos)) as usize;
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let read = match &mut self.reader {
+            ArchiveStreamReader::Stored(reader)  => reader.read(&mut buf[..len])?,
+            ArchiveStreamReader::Deflate(reader) => reader.read(&mut buf[..len])?,
+        };
+        if read == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Archive file data ended before the expected size was read"));
+        }
+        self.pos += read as u64;
+        Ok(read)
+    }
+}
+
+impl io::Seek for ArchiveFileStream {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match pos {
+            io::SeekFrom::Start(pos)   => (0, pos as i64),
+            io::SeekFrom::End(pos)     => (self.size, pos),
+            io::SeekFrom::Current(pos) => (self.pos, pos),
+        };
+        let Some(target) = base.checked_add_signed(offset) else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid seek to a negative or overflowing position"));
+        };
+
+        match &mut self.reader {
+            ArchiveStreamReader::Stored(reader) => {
+                reader.pos = target;
+                self.pos = target;
+                return Ok(target);
+            },
+            ArchiveStreamReader::Deflate(decoder) => if target < self.pos {
+                let raw = decoder.get_ref();
+                let raw = ArchiveRawReader { source: raw.source.clone(), offset: raw.offset, size: raw.size, pos: 0 };
+                decoder.reset(raw);
+                self.pos = 0;
+            },
+        }
+
+        // Skip over the data by decompressing it, anything past the end can just be ignored
+        let to_skip = target.min(self.size).saturating_sub(self.pos);
+        io::copy(&mut Read::by_ref(self).take(to_skip), &mut io::sink())?;
+        self.pos = target;
+        Ok(target)
+    }
+}
+
+//------------------------------
+
+struct ArchiveEntryHandle {
+    path:       PathBuf,
+    entry_type: EntryType,
+    info:       Option<ArchiveFileInfo>,
+}
+
+impl EntryHandle for ArchiveEntryHandle {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn fully_qualified_path(&self) -> io::Result<PathBuf> {
+        Ok(self.path.clone())
+    }
+
+    fn metadata(&self) -> io::Result<MetaData> {
+        let (file_size, alloc_size, compressed_size) = match self.info {
+            Some(info) => {
+                let compressed_size = if info.compression == Some(ArchiveCompression::Stored) { None } else { NonZeroU64::new(info.compressed_size) };
+                (info.size, info.compressed_size, compressed_size)
+            },
+            None => (0, 0, None),
+        };
+        Ok(MetaData { entry_type: self.entry_type, file_size, alloc_size, compressed_size, ..Default::default() })
+    }
+
+    fn permissions(&self) -> io::Result<Permission> {
+        Ok(Permission::Read)
+    }
+}
+
+struct ArchiveEntrySearchHandle {
+    entries: VecDeque<(Box<dyn EntryHandle>, EntryType)>,
+}
+
+impl EntrySearchHandle for ArchiveEntrySearchHandle {
+    fn next(&mut self, path: PathBuf) -> Option<(Box<dyn EntryHandle>, EntryType, PathBuf)> {
+        let (handle, entry_type) = self.entries.pop_front()?;
+        Some((handle, entry_type, path))
+    }
+}
+
+//------------------------------
+
+struct ArchiveFileHandle {
+    path:   PathBuf,
+    stream: ArchiveFileStream,
+}
+
+impl FileHandle for ArchiveFileHandle {
+    fn flush_data(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn flush_all(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn cancel_all_thread_async_io(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn cancel_all_async_io(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_len(&mut self, _len: u64) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    fn set_times(&mut self, _times: FileTimes) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    fn set_permissions(&mut self, _permissions: Permission) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    fn set_readonly(&mut self, _readonly: bool) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    fn set_hidden(&mut self, _hidden: bool) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    fn set_content_indexed(&mut self, _content_indexed: bool) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    fn get_metadata(&mut self) -> io::Result<MetaData> {
+        let compressed_size = match &self.stream.reader {
+            ArchiveStreamReader::Stored(_)        => None,
+            ArchiveStreamReader::Deflate(decoder) => NonZeroU64::new(decoder.get_ref().size),
+        };
+        let alloc_size = compressed_size.map_or(self.stream.size, NonZeroU64::get);
+        Ok(MetaData { entry_type: EntryType::File, file_size: self.stream.size, alloc_size, compressed_size, ..Default::default() })
+    }
+
+    // Archive files are read-only, so there is nothing to protect the file from
+
+    fn lock(&mut self, _kind: FileLockKind, _blocking: bool) -> io::Result<bool> {
+        Ok(true)
+    }
+
+    fn unlock(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn read_ahead(&mut self, _offset: u64, _len: u64) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream.read(buf)
+    }
+
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(read_only_error())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.stream.seek(pos)
+    }
+
+    // Archive files are read synchronously, as the data might need to be decompressed
+
+    fn read_async(&mut self, bytes_to_read: u64) -> io::Result<FileAsyncReadResult> {
+        let mut buf = Vec::new();
+        Read::by_ref(&mut self.stream).take(bytes_to_read).read_to_end(&mut buf)?;
+        Ok(Box::new(CompletedAsyncResult(Some(Ok(buf)))))
+    }
+
+    fn write_async(&mut self, _buf: Vec<u8>) -> io::Result<FileAsyncWriteResult> {
+        Err(read_only_error())
+    }
+
+    fn map_memory(&mut self, _mapped_size: Option<u64>) -> io::Result<Box<dyn MemoryMappedFileHandle>> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, format!("`{}` is in an archive and cannot be memory mapped", self.path)))
+    }
+}
+
+//------------------------------
+
+/// Little endian reader over a buffer, returning an error when reading past the end of the buffer
+struct ByteReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len()
+    }
+
+    fn bytes(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.data.len() < len {
+            return Err(invalid_archive("Unexpected end of archive data"));
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, len: usize) -> io::Result<()> {
+        self.bytes(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> io::Result<u16> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+}
+
+fn read_exact_at(file: &mut File, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+    file.seek(io::SeekFrom::Start(offset))?;
+    file.read_exact(buf)
+}
+
+fn invalid_archive(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn read_only_error() -> io::Error {
+    io::Error::new(io::ErrorKind::PermissionDenied, "Archives are read-only")
+}
+
+//--------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{write::DeflateEncoder, Compression};
+
+    use super::*;
+
+    const STORED_CONTENT: &[u8] = b"stored file content";
+
+    fn deflated_content() -> Vec<u8> {
+        (0..4096u32).flat_map(|i| (i % 251).to_le_bytes()).collect()
+    }
+
+    fn deflate(data: &[u8]) -> Vec<u8> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn create_zip() -> Vec<u8> {
+        let content = deflated_content();
+        let files: [(&str, u16, Vec<u8>, usize); 2] = [
+            ("stored.txt", 0, STORED_CONTENT.to_vec(), STORED_CONTENT.len()),
+            ("dir/sub/deflated.bin", 8, deflate(&content), content.len()),
+        ];
+
+        let mut data = Vec::new();
+        let mut central_directory = Vec::new();
+        for (name, method, compressed, size) in &files {
+            let offset = data.len() as u32;
+            data.extend_from_slice(&ZIP_LOCAL_HEADER_SIGNATURE.to_le_bytes());
+            data.extend_from_slice(&[20, 0, 0, 0]);
+            data.extend_from_slice(&method.to_le_bytes());
+            data.extend_from_slice(&[0; 8]);
+            data.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(*size as u32).to_le_bytes());
+            data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            data.extend_from_slice(&0u16.to_le_bytes());
+            data.extend_from_slice(name.as_bytes());
+            data.extend_from_slice(compressed);
+
+            central_directory.extend_from_slice(&ZIP_CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+            central_directory.extend_from_slice(&[20, 0, 20, 0, 0, 0]);
+            central_directory.extend_from_slice(&method.to_le_bytes());
+            central_directory.extend_from_slice(&[0; 8]);
+            central_directory.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+            central_directory.extend_from_slice(&(*size as u32).to_le_bytes());
+            central_directory.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            central_directory.extend_from_slice(&[0; 12]);
+            central_directory.extend_from_slice(&offset.to_le_bytes());
+            central_directory.extend_from_slice(name.as_bytes());
+        }
+
+        let cd_offset = data.len() as u32;
+        data.extend_from_slice(&central_directory);
+        data.extend_from_slice(&ZIP_EOCD_SIGNATURE.to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(central_directory.len() as u32).to_le_bytes());
+        data.extend_from_slice(&cd_offset.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data
+    }
+
+    fn create_opak() -> Vec<u8> {
+        let content = deflated_content();
+        let files: [(&str, u8, Vec<u8>, usize); 2] = [
+            ("stored.txt", 0, STORED_CONTENT.to_vec(), STORED_CONTENT.len()),
+            ("dir/sub/deflated.bin", 1, deflate(&content), content.len()),
+        ];
+
+        let mut data = vec![0; OPAK_HEADER_SIZE];
+        let mut toc = Vec::new();
+        for (path, compression, compressed, size) in &files {
+            toc.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            toc.extend_from_slice(&(compressed.len() as u64).to_le_bytes());
+            toc.extend_from_slice(&(*size as u64).to_le_bytes());
+            toc.extend_from_slice(&[*compression, 0]);
+            toc.extend_from_slice(&(path.len() as u16).to_le_bytes());
+            toc.extend_from_slice(path.as_bytes());
+            data.extend_from_slice(compressed);
+        }
+
+        let toc_offset = data.len() as u64;
+        data.extend_from_slice(&toc);
+
+        let mut header = Vec::with_capacity(OPAK_HEADER_SIZE);
+        header.extend_from_slice(&OPAK_MAGIC);
+        header.extend_from_slice(&OPAK_VERSION.to_le_bytes());
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(&(files.len() as u32).to_le_bytes());
+        header.extend_from_slice(&(toc.len() as u32).to_le_bytes());
+        header.extend_from_slice(&toc_offset.to_le_bytes());
+        data[..OPAK_HEADER_SIZE].copy_from_slice(&header);
+        data
+    }
+
+    fn open_archive(name: &str, data: &[u8]) -> Arc<ArchiveFileSystem> {
+        let memory_fs = MemoryFileSystem::new("memory");
+        let mut file = memory_fs.create_file(Path::new(name).unwrap(), OpenMode::CreateAlways, Permission::Read | Permission::Write, Permission::None, FileCreateFlags::None, FileAccessFlags::None).unwrap();
+        file.write_all(data).unwrap();
+        ArchiveFileSystem::new(file).unwrap()
+    }
+
+    fn check_archive(archive: &ArchiveFileSystem) {
+        assert!(archive.directory_exists(Path::new("dir/sub").unwrap()).unwrap());
+        let root_entries: Vec<_> = archive.read_directory(Path::new("/").unwrap()).unwrap().map(|entry| entry.file_name().to_string()).collect();
+        assert_eq!(root_entries, ["dir", "stored.txt"]);
+
+        let mut stored = String::new();
+        archive.create_file(Path::new("stored.txt").unwrap(), OpenMode::OpenExisting, Permission::Read, Permission::None, FileCreateFlags::None, FileAccessFlags::None).unwrap()
+            .read_to_string(&mut stored).unwrap();
+        assert_eq!(stored.as_bytes(), STORED_CONTENT);
+
+        let content = deflated_content();
+        let mut stream = archive.open_stream(Path::new("dir/sub/deflated.bin").unwrap()).unwrap();
+        let mut buf = Vec::new();
+        stream.read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, content);
+
+        // Seeking backwards restarts decompression
+        let mut buf = [0; 16];
+        stream.seek(io::SeekFrom::Start(1000)).unwrap();
+        stream.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, content[1000..1016]);
+
+        assert!(archive.create_file(Path::new("stored.txt").unwrap(), OpenMode::OpenExisting, Permission::Write, Permission::None, FileCreateFlags::None, FileAccessFlags::None).is_err());
+    }
+
+    #[test]
+    pub fn zip_archive() {
+        let archive = open_archive("test.zip", &create_zip());
+        assert_eq!(archive.format(), ArchiveFormat::Zip);
+        check_archive(&archive);
+    }
+
+    #[test]
+    pub fn opak_archive() {
+        let archive = open_archive("test.opak", &create_opak());
+        assert_eq!(archive.format(), ArchiveFormat::Opak);
+        check_archive(&archive);
+    }
+}
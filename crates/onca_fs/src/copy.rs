@@ -0,0 +1,358 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use onca_common::io::{self, Read, Write};
+
+use crate::{directory, os::os_imp, Entry, File, FileAccessFlags, FileCreateFlags, FileTimes, MetaData, OpenMode, Path, Permission, SymlinkPolicy};
+
+/// Default size of the chunks in which the content of a file is copied (1 MiB).
+pub const DEFAULT_COPY_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Token to cancel a copy or move, which can be shared with other threads.
+///
+/// Cancellation is checked in between chunks, so a copy stops at most a single chunk after the token is cancelled.
+#[derive(Clone, Default, Debug)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a new cancellation token.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel any copy or move using this token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Check if the token has been cancelled.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Progress of a copy or move.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct CopyProgress {
+    /// Number of bytes copied so far.
+    pub copied_bytes: u64,
+    /// Total number of bytes to copy.
+    pub total_bytes:  u64,
+    /// Number of files copied so far.
+    pub copied_files: u64,
+    /// Total number of files to copy.
+    pub total_files:  u64,
+}
+
+impl CopyProgress {
+    /// Get the progress as a fraction in the range `[0, 1]`.
+    #[must_use]
+    pub fn fraction(&self) -> f32 {
+        if self.total_bytes == 0 {
+            if self.total_files == 0 { 1.0 } else { self.copied_files as f32 / self.total_files as f32 }
+        } else {
+            (self.copied_bytes as f64 / self.total_bytes as f64) as f32
+        }
+    }
+}
+
+/// Options for a copy or move.
+#[derive(Clone, Debug)]
+pub struct CopyOptions {
+    /// Size of the chunks in which the content of a file is copied, progress is reported after each chunk.
+    pub chunk_size:     usize,
+    /// Overwrite files that already exist at the destination, otherwise an error is returned when a file already exists.
+    pub overwrite:      bool,
+    /// Preserve the creation, access and modification times of the copied files and directories.
+    pub preserve_times: bool,
+    /// Token to cancel the copy or move.
+    pub cancel:         Option<CancellationToken>,
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_COPY_CHUNK_SIZE,
+            overwrite: false,
+            preserve_times: true,
+            cancel: None,
+        }
+    }
+}
+
+/// Copy a file or directory, calling `callback` with the current progress after every copied chunk.
+///
+/// See [`copy_with_options`] for more info.
+///
+/// # Error
+///
+/// Returns an error if any file or directory could not be copied.
+pub fn copy_with_progress<P, Q, F>(src: P, dst: Q, callback: F) -> io::Result<u64>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+    F: FnMut(CopyProgress),
+{
+    copy_with_options(src, dst, &CopyOptions::default(), callback)
+}
+
+/// Copy a file or directory using the given options, calling `callback` with the current progress after every copied chunk.
+///
+/// Directories are copied recursively, following any symbolic link, and the content of the destination directory is merged with the content of the source.
+/// The readonly state of each file is copied, as are the times when [`CopyOptions::preserve_times`] is set.
+///
+/// Returns the total number of bytes copied.
+///
+/// # Error
+///
+/// Returns an error if any file or directory could not be copied, or an error of kind [`io::ErrorKind::Interrupted`] when the copy was cancelled.
+/// The file that was being copied when the error occured is removed, but any file or directory copied before is kept.
+pub fn copy_with_options<P, Q, F>(src: P, dst: Q, options: &CopyOptions, mut callback: F) -> io::Result<u64>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+    F: FnMut(CopyProgress),
+{
+    copy_internal(src.as_ref(), dst.as_ref(), options, &mut callback)
+}
+
+/// Move a file or directory.
+///
+/// See [`move_with_options`] for more info.
+///
+/// # Error
+///
+/// Returns an error if the file or directory could not be moved.
+pub fn move_item<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> io::Result<()> {
+    move_with_options(src, dst, &CopyOptions::default(), |_| ())
+}
+
+/// Move a file or directory using the given options.
+///
+/// When the source and destination are on the same volume, the entry is renamed and `callback` is never called.
+/// Otherwise the entry is copied, reporting progress like [`copy_with_options`], after which the source is removed.
+///
+/// # Error
+///
+/// Returns an error if the file or directory could not be moved, or an error of kind [`io::ErrorKind::Interrupted`] when the move was cancelled.
+/// If the move fails while copying to another volume, the source is left untouched.
+pub fn move_with_options<P, Q, F>(src: P, dst: Q, options: &CopyOptions, mut callback: F) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+    F: FnMut(CopyProgress),
+{
+    move_internal(src.as_ref(), dst.as_ref(), options, &mut callback, os_imp::file::rename)
+}
+
+//------------------------------
+
+/// Move using `rename`, falling back to a copy and delete when `rename` fails because the destination is on another volume
+fn move_internal<R>(src: &Path, dst: &Path, options: &CopyOptions, callback: &mut dyn FnMut(CopyProgress), rename: R) -> io::Result<()>
+where
+    R: FnOnce(&Path, &Path, bool) -> io::Result<()>,
+{
+    // Not all OSes can prevent a rename from replacing the destination
+    if !options.overwrite && Entry::new(dst).is_ok() {
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, "The destination of the move already exists"));
+    }
+
+    match rename(src, dst, options.overwrite) {
+        Ok(()) => return Ok(()),
+        Err(err) if err.raw_os_error() != Some(os_imp::file::CROSS_VOLUME_ERROR) => return Err(err),
+        Err(_) => (),
+    }
+
+    let entry = Entry::new(src)?;
+    copy_internal(src, dst, options, callback)?;
+
+    if entry.entry_type().is_dir() {
+        directory::remove_all(src)
+    } else {
+        // A readonly file cannot be deleted
+        if entry.metadata().map_or(false, |metadata| metadata.is_readonly()) {
+            crate::set_readonly(src, false)?;
+        }
+        crate::file::delete(src)
+    }
+}
+
+fn cancelled_error() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "The copy was cancelled")
+}
+
+fn times_from_metadata(metadata: &MetaData) -> FileTimes {
+    FileTimes {
+        created: Some(metadata.creation_time),
+        accessed: Some(metadata.last_access_time),
+        modified: Some(metadata.last_write_time),
+    }
+}
+
+fn copy_internal(src: &Path, dst: &Path, options: &CopyOptions, callback: &mut dyn FnMut(CopyProgress)) -> io::Result<u64> {
+    let entry = Entry::new(src)?;
+    let mut buffer = vec![0; options.chunk_size.max(1)];
+
+    if !entry.entry_type().is_dir() {
+        let metadata = entry.metadata()?;
+        let mut progress = CopyProgress { total_bytes: metadata.file_size, total_files: 1, ..Default::default() };
+        callback(progress);
+        copy_file(src, dst, options, &mut buffer, &mut progress, callback)?;
+        return Ok(progress.copied_bytes);
+    }
+
+    // Gather everything up front, so the total size is known when the first progress is reported
+    let mut dirs = vec![(dst.to_path_buf(), entry.metadata()?)];
+    let mut files = Vec::new();
+    let mut progress = CopyProgress::default();
+    for walk_entry in directory::walk(src)?.symlinks(SymlinkPolicy::Follow) {
+        let walk_entry = walk_entry?;
+        let dst_path = dst.join(walk_entry.relative_path());
+        if walk_entry.entry_type().is_dir() {
+            dirs.push((dst_path, *walk_entry.metadata()));
+        } else {
+            progress.total_bytes += walk_entry.metadata().file_size;
+            progress.total_files += 1;
+            files.push((walk_entry.path().to_path_buf(), dst_path));
+        }
+    }
+    callback(progress);
+
+    // Parent directories are always returned before their content
+    for (dst_dir, _) in &dirs {
+        if !directory::exists(dst_dir) {
+            directory::create(dst_dir, false)?;
+        }
+    }
+
+    for (src_file, dst_file) in &files {
+        copy_file(src_file, dst_file, options, &mut buffer, &mut progress, callback)?;
+    }
+
+    // Copying files into a directory updates its times, so they can only be set once everything has been copied
+    if options.preserve_times {
+        for (dst_dir, metadata) in dirs.iter().rev() {
+            crate::set_times(dst_dir, times_from_metadata(metadata))?;
+        }
+    }
+
+    Ok(progress.copied_bytes)
+}
+
+fn copy_file(src: &Path, dst: &Path, options: &CopyOptions, buffer: &mut [u8], progress: &mut CopyProgress, callback: &mut dyn FnMut(CopyProgress)) -> io::Result<()> {
+    let mut src_file = File::open(src, Permission::Read, Permission::Read, FileAccessFlags::SequentialAccess)?;
+    let open_mode = if options.overwrite { OpenMode::CreateAlways } else { OpenMode::CreateNonExisting };
+    let mut dst_file = File::create(dst, open_mode, Permission::Read | Permission::Write, Permission::None, FileCreateFlags::None, FileAccessFlags::SequentialAccess)?;
+
+    let start_bytes = progress.copied_bytes;
+    let res = copy_file_content(&mut src_file, &mut dst_file, options, buffer, progress, callback);
+    if res.is_err() {
+        // Don't leave a partially copied file behind
+        drop(dst_file);
+        _ = crate::file::delete(dst);
+        progress.copied_bytes = start_bytes;
+        return res;
+    }
+
+    progress.copied_files += 1;
+    callback(*progress);
+    Ok(())
+}
+
+fn copy_file_content(src: &mut File, dst: &mut File, options: &CopyOptions, buffer: &mut [u8], progress: &mut CopyProgress, callback: &mut dyn FnMut(CopyProgress)) -> io::Result<()> {
+    loop {
+        if options.cancel.as_ref().map_or(false, |cancel| cancel.is_cancelled()) {
+            return Err(cancelled_error());
+        }
+
+        let read = match src.read(buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        dst.write_all(&buffer[..read])?;
+
+        progress.copied_bytes += read as u64;
+        callback(*progress);
+    }
+    dst.flush()?;
+
+    let metadata = src.get_metadata()?;
+    if options.preserve_times {
+        dst.set_times(times_from_metadata(&metadata))?;
+    }
+    if metadata.is_readonly() {
+        dst.set_readonly(true)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_file(path: &Path, content: &[u8]) {
+        let mut file = File::create(path, OpenMode::CreateAlways, Permission::Write, Permission::None, FileCreateFlags::None, FileAccessFlags::None).unwrap();
+        file.write_all(content).unwrap();
+    }
+
+    fn read_file(path: &Path) -> Vec<u8> {
+        let mut file = File::open(path, Permission::Read, Permission::Read, FileAccessFlags::None).unwrap();
+        let mut content = Vec::new();
+        file.read_to_end(&mut content).unwrap();
+        content
+    }
+
+    fn cross_volume_rename(_: &Path, _: &Path, _: bool) -> io::Result<()> {
+        Err(io::Error::from_raw_os_error(os_imp::file::CROSS_VOLUME_ERROR))
+    }
+
+    #[test]
+    fn move_file_across_volumes() {
+        let dir = crate::tempdir().unwrap();
+        let src = dir.path().join("src.txt");
+        let dst = dir.path().join("dst.txt");
+        write_file(&src, b"moved content");
+
+        let mut last_progress = CopyProgress::default();
+        move_internal(&src, &dst, &CopyOptions::default(), &mut |progress| last_progress = progress, cross_volume_rename).unwrap();
+
+        assert!(Entry::new(&src).is_err());
+        assert_eq!(read_file(&dst), b"moved content");
+        assert_eq!(last_progress.copied_files, 1);
+        assert_eq!(last_progress.copied_bytes, 13);
+    }
+
+    #[test]
+    fn move_directory_across_volumes() {
+        let dir = crate::tempdir().unwrap();
+        let src = dir.path().join("src");
+        let dst = dir.path().join("dst");
+        directory::create(src.join("sub"), true).unwrap();
+        write_file(&src.join("sub/file.txt"), b"nested");
+
+        move_internal(&src, &dst, &CopyOptions::default(), &mut |_| (), cross_volume_rename).unwrap();
+
+        assert!(!directory::exists(&src));
+        assert_eq!(read_file(&dst.join("sub/file.txt")), b"nested");
+    }
+
+    #[test]
+    fn move_only_falls_back_across_volumes() {
+        let dir = crate::tempdir().unwrap();
+        let src = dir.path().join("src.txt");
+        let dst = dir.path().join("dst.txt");
+        write_file(&src, b"content");
+
+        let res = move_internal(&src, &dst, &CopyOptions::default(), &mut |_| (), |_, _, _| Err(io::Error::new(io::ErrorKind::PermissionDenied, "denied")));
+
+        assert_eq!(res.unwrap_err().kind(), io::ErrorKind::PermissionDenied);
+        assert_eq!(read_file(&src), b"content");
+        assert!(Entry::new(&dst).is_err());
+    }
+}
@@ -3,7 +3,7 @@ use onca_common::{
     io
 };
 
-use crate::{os::os_imp, Path, Entry, EntryIter, EntryType};
+use crate::{os::os_imp, Path, Entry, EntryIter, EntryType, WatchHandle, FileWatcherFilter, Walk};
 
 /// Check if the given path is valid and points to a directory.
 #[must_use]
@@ -25,6 +25,18 @@ pub fn read<P: AsRef<Path>>(path: P) -> io::Result<EntryIter> {
     Ok(unsafe { EntryIter::from_raw(path, handle) })
 }
 
+/// Recursively walks the content of a directory and returns an iterator over all entries, including their metadata.
+/// 
+/// The returned [`Walk`] can be configured with a depth range, a symlink policy and a glob or regex filter.
+/// 
+/// # Errors
+/// 
+/// Returns an error if the directory could not be read.
+#[must_use]
+pub fn walk<P: AsRef<Path>>(path: P) -> io::Result<Walk> {
+    Walk::new(path.as_ref())
+}
+
 /// Creates a directory with the given path.
 /// 
 /// If the directory is created recursively, parent directories that do not exists will also be created.
@@ -72,8 +84,23 @@ pub fn remove_all<P: AsRef<Path>>(path: P) -> io::Result<()> {
             EntryType::File             => crate::file::delete(entry.path())?,
             EntryType::Directory        => remove_all(entry.path())?,
             EntryType::SymlinkFile      => crate::file::delete(entry.path())?,
-            EntryType::SymlinkDirectory => remove_all(entry.path())?,
+            // Only remove the link, not the content of the directory it points to
+            EntryType::SymlinkDirectory => remove(entry.path())?,
         }
     }
     remove(path)
+}
+
+/// Watch a directory for changes.
+/// 
+/// The returned handle reports files and directories being created, modified, deleted or renamed to its registered listeners.
+/// When `recursive` is set, changes in all subdirectories are reported as well.
+/// 
+/// Paths in the reported changes are relative to the watched directory.
+/// 
+/// # Errors
+/// 
+/// Returns an error if the directory could not be opened for watching, or `Unsupported` on platforms other than Windows and Linux.
+pub fn watch<P: AsRef<Path>>(path: P, recursive: bool) -> io::Result<WatchHandle> {
+    WatchHandle::new(path, recursive, FileWatcherFilter::all(), None)
 }
\ No newline at end of file
@@ -55,6 +55,66 @@ pub struct DriveInfo {
     pub free_clusters:       u64,
 }
 
+impl DriveInfo {
+    /// Get the size of a cluster/block (in bytes), i.e. the smallest amount of space a non-empty file can take up on the drive.
+    #[must_use]
+    pub fn cluster_size(&self) -> u32 {
+        self.sector_size * self.sectors_per_cluster
+    }
+}
+
+/// Disk space of the volume containing a path.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct DiskSpace {
+    /// Total size of the volume (in bytes).
+    pub total:             u64,
+    /// Size of the available space on the volume (in bytes).
+    pub available:         u64,
+    /// Size of the available space on the volume that the user can use (in bytes).
+    /// 
+    /// This value may be smaller than the total available space on an OS that supports per-user quotas.
+    pub available_to_user: u64,
+}
+
+impl DiskSpace {
+    /// Get the size of the used space on the volume (in bytes).
+    #[must_use]
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+
+    /// Check if `size` bytes can be written by the user, while still leaving at least `reserve` bytes available.
+    #[must_use]
+    pub fn can_fit(&self, size: u64, reserve: u64) -> bool {
+        size.checked_add(reserve).map_or(false, |needed| needed <= self.available_to_user)
+    }
+}
+
+/// Capabilities of the storage device backing a volume.
+/// 
+/// Each capability is `None` when the OS or the device driver does not report it, which is common for network and virtual drives.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct DeviceCapabilities {
+    /// Does the device incur a seek penalty, i.e. is random access noticeably slower than sequential access, as is the case for a rotational drive.
+    pub seek_penalty: Option<bool>,
+    /// Does the device support TRIM (or an equivalent discard command).
+    pub trim:         Option<bool>,
+}
+
+impl DeviceCapabilities {
+    /// Check if the device is a solid state drive.
+    /// 
+    /// Returns `None` if this cannot be determined.
+    #[must_use]
+    pub fn is_ssd(&self) -> Option<bool> {
+        match (self.seek_penalty, self.trim) {
+            (Some(seek_penalty), _) => Some(!seek_penalty),
+            (None, Some(true))      => Some(true),
+            _                       => None,
+        }
+    }
+}
+
 /// File system flags associated with a volume.
 #[flags]
 pub enum FilesystemFlags {
@@ -137,6 +197,71 @@ pub fn get_volume_info<P: AsRef<Path>>(path: &P) -> io::Result<VolumeInfo> {
     os_imp::drive_volume::get_volume_info(path.as_ref())
 }
 
+/// Retrieve the root of the volume containing the given path, e.g. `C:/` for `C:/some/file.txt`.
+/// 
+/// The path does not need to exist, as long as the volume it would reside on does.
+/// 
+/// # Error
+/// 
+/// Returns an error if the volume root could not be determined.
+#[must_use]
+pub fn get_volume_root<P: AsRef<Path>>(path: &P) -> io::Result<PathBuf> {
+    os_imp::drive_volume::get_volume_root(path.as_ref())
+}
+
+/// Retrieve the volume info for the volume containing the given path.
+/// 
+/// # Error
+/// 
+/// Returns an error if the volume containing the path could not be determined, or its info could not be retrieved.
+#[must_use]
+pub fn get_volume_info_for_path<P: AsRef<Path>>(path: &P) -> io::Result<VolumeInfo> {
+    let root = get_volume_root(path)?;
+    os_imp::drive_volume::get_volume_info(&root)
+}
+
+/// Retrieve the name of the file system of the volume containing the given path, e.g. `NTFS` or `ext4`.
+/// 
+/// # Error
+/// 
+/// Returns an error if the volume containing the path could not be determined, or its info could not be retrieved.
+#[must_use]
+pub fn get_filesystem_name<P: AsRef<Path>>(path: &P) -> io::Result<String> {
+    get_volume_info_for_path(path).map(|info| info.fs_name)
+}
+
+/// Retrieve the disk space of the volume containing the given path.
+/// 
+/// This should be checked before writing large amounts of data, as writes will fail when the volume runs out of space.
+/// 
+/// # Error
+/// 
+/// Returns an error if the volume containing the path could not be determined, or its disk space could not be retrieved.
+#[must_use]
+pub fn get_disk_space<P: AsRef<Path>>(path: &P) -> io::Result<DiskSpace> {
+    os_imp::drive_volume::get_disk_space(path.as_ref())
+}
+
+/// Retrieve the cluster/block size (in bytes) of the volume containing the given path.
+/// 
+/// # Error
+/// 
+/// Returns an error if the volume containing the path could not be determined, or its cluster size could not be retrieved.
+#[must_use]
+pub fn get_cluster_size<P: AsRef<Path>>(path: &P) -> io::Result<u32> {
+    os_imp::drive_volume::get_cluster_size(path.as_ref())
+}
+
+/// Retrieve the capabilities of the storage device backing the volume containing the given path.
+/// 
+/// # Error
+/// 
+/// Returns an error if the device backing the volume could not be opened, capabilities not reported by the device are set to `None` instead.
+#[must_use]
+pub fn get_device_capabilities<P: AsRef<Path>>(path: &P) -> io::Result<DeviceCapabilities> {
+    os_imp::drive_volume::get_device_capabilities(path.as_ref())
+}
+
 /// Retrieve th evolume info for all available volumes.
 /// 
 /// Returns an error if not all volume infos could be retrieved
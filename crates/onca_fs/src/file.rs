@@ -1,9 +1,9 @@
-use std::{num::NonZeroU64, sync::Arc};
+use std::{num::NonZeroU64, sync::Arc, ops::{Deref, DerefMut}, mem};
 
 use onca_common::io;
 use onca_common_macros::flags;
 
-use crate::{Path, os::os_imp, Permission, PathBuf, MetaData};
+use crate::{Path, os::os_imp, Permission, PathBuf, MetaData, FileTime, FileTimes, VirtualFileSystem, get_global_vfs_path};
 
 /// File open mode.
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
@@ -71,6 +71,14 @@ pub enum FileAccessFlags {
     MemoryMappable,
 }
 
+/// File lock kind.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FileLockKind {
+    /// Shared lock, the file can be locked by multiple shared locks at the same time, but not while an exclusive lock is held.
+    Shared,
+    /// Exclusive lock, the file cannot be locked by any other lock while an exclusive lock is held.
+    Exclusive,
+}
 
 //------------------------------
 
@@ -80,7 +88,7 @@ pub type FileAsyncReadResult = Box<dyn io::AsyncIOResult<Output = io::Result<Vec
 /// Result type of an asycn write operation
 pub type FileAsyncWriteResult = Box<dyn io::AsyncIOResult<Output = io::Result<u64>>>;
 
-pub trait FileHandle {
+pub trait FileHandle: Send {
     /// Write all data that is currently cached.
     /// 
     /// # Note
@@ -106,12 +114,15 @@ pub trait FileHandle {
     /// After this operation, the cursor will still be at the same location as before the call, meaning that it can be located passed the new file lenght.
     fn set_len(&mut self, len: u64) -> io::Result<()>;
 
-    /// Set the modification time of the file
-    fn set_modified(&mut self, time: u64) -> io::Result<()>;
+    /// Set the times of the file, any time that is `None` is left unchanged.
+    fn set_times(&mut self, times: FileTimes) -> io::Result<()>;
 
     /// Set the file permissions.
     fn set_permissions(&mut self, permissions: Permission) -> io::Result<()>;
 
+    /// Set if the file is read-only.
+    fn set_readonly(&mut self, readonly: bool) -> io::Result<()>;
+
     /// Set if the file should is hidden in a file explorer.
     /// 
     /// This may be a no-op if the underlying filesystem does not support this.
@@ -125,6 +136,19 @@ pub trait FileHandle {
     /// Gets the metadata for the file
     fn get_metadata(&mut self) -> io::Result<MetaData>;
 
+    /// Lock the entire file.
+    /// 
+    /// If `blocking` is `false`, `Ok(false)` is returned when the file is already locked by another handle, instead of waiting for the lock to be released.
+    fn lock(&mut self, kind: FileLockKind, blocking: bool) -> io::Result<bool>;
+
+    /// Release the lock held by this handle.
+    fn unlock(&mut self) -> io::Result<()>;
+
+    /// Hint to the OS that `len` bytes, starting at `offset`, will be read soon, so it can start loading them into its cache.
+    /// 
+    /// This may be a no-op if the underlying filesystem does not support this.
+    fn read_ahead(&mut self, offset: u64, len: u64) -> io::Result<()>;
+
     /// Read bytes from the file, returning the number of bytes read.
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>;
 
@@ -173,7 +197,8 @@ impl File {
     /// 
     /// # Note
     /// 
-    /// Only works for path on the native filesystem, for vfs support, use ['VirtualFileSystem::create_file']
+    /// Only works for path on the native filesystem or `vfs://<mount point>/<path>` paths, which are resolved using [`VirtualFileSystem::global`].
+    /// For other vfs support, use ['VirtualFileSystem::create_file']
     /// 
     /// # Error
     /// 
@@ -187,6 +212,10 @@ impl File {
         create_flags: FileCreateFlags,
         access_flags: FileAccessFlags
     ) -> io::Result<File> {
+        if let Some(vfs_path) = get_global_vfs_path(path.as_ref()) {
+            return VirtualFileSystem::global().create_file(vfs_path, open_mode, access_perms, shared_access_perms, create_flags, access_flags);
+        }
+
         os_imp::file::FileHandle::create(path.as_ref(), open_mode, access_perms, shared_access_perms, create_flags, access_flags, false, false)
             .map(|(handle, path_buf)| File { handle, path: path_buf, permissions: access_perms })
     }
@@ -195,7 +224,8 @@ impl File {
     /// 
     /// # Note
     /// 
-    /// Only works for path on the native filesystem, for vfs support, use ['VirtualFileSystem::create_file']
+    /// Only works for path on the native filesystem or `vfs://<mount point>/<path>` paths, which are resolved using [`VirtualFileSystem::global`].
+    /// For other vfs support, use ['VirtualFileSystem::open_file']
     /// 
     /// # Error
     /// 
@@ -206,6 +236,10 @@ impl File {
         shared_access_perms: Permission,
         access_flags: FileAccessFlags,
     ) -> io::Result<File> {
+        if let Some(vfs_path) = get_global_vfs_path(path.as_ref()) {
+            return VirtualFileSystem::global().open_file(vfs_path, access_perms, shared_access_perms, access_flags);
+        }
+
         os_imp::file::FileHandle::create(path.as_ref(), OpenMode::OpenExisting, access_perms, shared_access_perms, FileCreateFlags::None, access_flags, false, false)
             .map(|(handle, path_buf)| File { handle, path: path_buf, permissions: access_perms })
     }
@@ -232,25 +266,38 @@ impl File {
         .map(|(handle, path_buf)| File { handle, path: path_buf, permissions: access_perms })
     }
 
-    /// Create/open a temporary file, in the folder given by `path`.
+    /// Create a uniquely named temporary file, in the folder given by `dir`.
+    /// 
+    /// The OS is hinted to keep the content of the file in memory where possible, but the file is not deleted automatically.
+    /// For a file that is deleted when it goes out of scope, use [`TempFile`](crate::TempFile).
     /// 
     /// # Note
     /// 
-    /// Only works for path on the native filesystem, for vfs support, use ['VirtualFileSystem::create_temp']
+    /// Only works for path on the native filesystem.
     /// 
     /// #  Error
     /// 
     /// Returns an error when the temporary file could not be created.
     #[must_use]
     pub fn create_temp<P: AsRef<Path>>(
-        path: P,
-        open_mode: OpenMode,
+        dir: P,
         access_perms: Permission,
         shared_access_perms: Permission,
         create_flags: FileCreateFlags,
         access_flags: FileAccessFlags
     ) -> io::Result<File> {
-        os_imp::file::FileHandle::create(path.as_ref(), open_mode, access_perms, shared_access_perms, create_flags, access_flags, false, false)
+        crate::temp::create_unique(dir.as_ref(), "", ".tmp", |path| Self::create_temp_at(path, access_perms, shared_access_perms, create_flags, access_flags))
+    }
+
+    /// Create a new temporary file at the given path.
+    pub(crate) fn create_temp_at(
+        path: &Path,
+        access_perms: Permission,
+        shared_access_perms: Permission,
+        create_flags: FileCreateFlags,
+        access_flags: FileAccessFlags
+    ) -> io::Result<File> {
+        os_imp::file::FileHandle::create(path, OpenMode::CreateNonExisting, access_perms, shared_access_perms, create_flags, access_flags, false, true)
         .map(|(handle, path_buf)| File { handle, path: path_buf, permissions: access_perms })
     }
 
@@ -329,8 +376,38 @@ impl File {
     /// 
     /// Returns an error when the modification time could not be set.
     #[must_use]
-    pub fn set_modified(&mut self, time: u64) -> io::Result<()> {
-        self.handle.set_modified(time)
+    pub fn set_modified(&mut self, time: FileTime) -> io::Result<()> {
+        self.handle.set_times(FileTimes { modified: Some(time), ..Default::default() })
+    }
+
+    /// Set the last access time of the file.
+    /// 
+    /// # Error
+    /// 
+    /// Returns an error when the access time could not be set.
+    #[must_use]
+    pub fn set_accessed(&mut self, time: FileTime) -> io::Result<()> {
+        self.handle.set_times(FileTimes { accessed: Some(time), ..Default::default() })
+    }
+
+    /// Set the times of the file, any time that is `None` is left unchanged.
+    /// 
+    /// # Error
+    /// 
+    /// Returns an error when the times could not be set.
+    #[must_use]
+    pub fn set_times(&mut self, times: FileTimes) -> io::Result<()> {
+        self.handle.set_times(times)
+    }
+
+    /// Set if the file is read-only.
+    /// 
+    /// # Error
+    /// 
+    /// Returns an error when the file could not be set as read-only/writable.
+    #[must_use]
+    pub fn set_readonly(&mut self, readonly: bool) -> io::Result<()> {
+        self.handle.set_readonly(readonly)
     }
 
     /// Set the file permissions.
@@ -370,6 +447,107 @@ impl File {
         self.handle.get_metadata()
     }
 
+    /// Lock the file with a shared lock, waiting until any exclusive lock on the file is released.
+    /// 
+    /// The lock is released when the returned guard is dropped.
+    /// 
+    /// See [`File::lock`] for more info.
+    /// 
+    /// # Error
+    /// 
+    /// Returns an error if the file could not be locked.
+    pub fn lock_shared(&mut self) -> io::Result<FileLockGuard<'_>> {
+        self.lock(FileLockKind::Shared)
+    }
+
+    /// Lock the file with an exclusive lock, waiting until any other lock on the file is released.
+    /// 
+    /// The lock is released when the returned guard is dropped.
+    /// 
+    /// See [`File::lock`] for more info.
+    /// 
+    /// # Error
+    /// 
+    /// Returns an error if the file could not be locked.
+    pub fn lock_exclusive(&mut self) -> io::Result<FileLockGuard<'_>> {
+        self.lock(FileLockKind::Exclusive)
+    }
+
+    /// Try to lock the file with a shared lock, returns `None` if the file is exclusively locked by another handle.
+    /// 
+    /// The lock is released when the returned guard is dropped.
+    /// 
+    /// See [`File::lock`] for more info.
+    /// 
+    /// # Error
+    /// 
+    /// Returns an error if the file could not be locked for another reason than it already being locked.
+    pub fn try_lock_shared(&mut self) -> io::Result<Option<FileLockGuard<'_>>> {
+        self.try_lock(FileLockKind::Shared)
+    }
+
+    /// Try to lock the file with an exclusive lock, returns `None` if the file is locked by another handle.
+    /// 
+    /// The lock is released when the returned guard is dropped.
+    /// 
+    /// See [`File::lock`] for more info.
+    /// 
+    /// # Error
+    /// 
+    /// Returns an error if the file could not be locked for another reason than it already being locked.
+    pub fn try_lock_exclusive(&mut self) -> io::Result<Option<FileLockGuard<'_>>> {
+        self.try_lock(FileLockKind::Exclusive)
+    }
+
+    /// Lock the entire file, waiting until the lock can be acquired.
+    /// 
+    /// The lock is released when the returned guard is dropped.
+    /// 
+    /// # Note
+    /// 
+    /// Locks are held per file handle, so opening the same file multiple times within the same process will result in locks that conflict with each other.
+    /// 
+    /// On Windows, locks are mandatory, meaning that other handles cannot write to a locked file (or read from it while it's exclusively locked).
+    /// On other platforms, locks are advisory, meaning that they only prevent other handles from locking the file, and not from accessing it.
+    /// 
+    /// Files in a [`VirtualFileSystem`] sub-system, like a [`MemoryFileSystem`](crate::MemoryFileSystem), only live within the current process, so locking them always succeeds.
+    /// 
+    /// # Error
+    /// 
+    /// Returns an error if the file could not be locked.
+    pub fn lock(&mut self, kind: FileLockKind) -> io::Result<FileLockGuard<'_>> {
+        self.handle.lock(kind, true)?;
+        Ok(FileLockGuard { file: self, kind })
+    }
+
+    /// Try to lock the entire file, returns `None` if the lock is held by another handle.
+    /// 
+    /// The lock is released when the returned guard is dropped.
+    /// 
+    /// See [`File::lock`] for more info.
+    /// 
+    /// # Error
+    /// 
+    /// Returns an error if the file could not be locked for another reason than it already being locked.
+    pub fn try_lock(&mut self, kind: FileLockKind) -> io::Result<Option<FileLockGuard<'_>>> {
+        if self.handle.lock(kind, false)? {
+            Ok(Some(FileLockGuard { file: self, kind }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Hint to the OS that `len` bytes, starting at `offset`, will be read soon, so it can start loading them into its cache.
+    /// 
+    /// This does not move the cursor and may be a no-op if the underlying filesystem does not support it.
+    /// 
+    /// # Error
+    /// 
+    /// Returns an error if the hint could not be given to the OS.
+    pub fn read_ahead(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        self.handle.read_ahead(offset, len)
+    }
+
     /// Map a file into memory.
     /// 
     /// `mapped_size` represents the size in memory for a file with write permissions,
@@ -439,6 +617,53 @@ impl io::AsyncWrite for File {
         self.handle.write_async(buf).map(|inner| AsyncWriteResult(inner))
     }
 }
+
+/// Guard of a lock on a file, the lock is released when the guard is dropped.
+/// 
+/// The locked file can be accessed via the guard.
+pub struct FileLockGuard<'a> {
+    file: &'a mut File,
+    kind: FileLockKind,
+}
+
+impl FileLockGuard<'_> {
+    /// Get the kind of lock that is held.
+    #[must_use]
+    pub fn kind(&self) -> FileLockKind {
+        self.kind
+    }
+
+    /// Release the lock.
+    /// 
+    /// # Error
+    /// 
+    /// Returns an error if the lock could not be released, dropping the guard will ignore this error.
+    pub fn unlock(self) -> io::Result<()> {
+        let res = self.file.handle.unlock();
+        mem::forget(self);
+        res
+    }
+}
+
+impl Deref for FileLockGuard<'_> {
+    type Target = File;
+
+    fn deref(&self) -> &Self::Target {
+        self.file
+    }
+}
+
+impl DerefMut for FileLockGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.file
+    }
+}
+
+impl Drop for FileLockGuard<'_> {
+    fn drop(&mut self) {
+        _ = self.file.handle.unlock();
+    }
+}
  
 //--------------------------------------------------------------
 
@@ -549,7 +774,7 @@ pub fn delete<P: AsRef<Path>>(path: P) -> io::Result<()> {
 //--------------------------------------------------------------
 
 /// Asynchronous read result
-pub struct AsyncReadResult(Box<dyn io::AsyncIOResult<Output = <Self as io::AsyncIOResult>::Output>>);
+pub struct AsyncReadResult(pub(crate) Box<dyn io::AsyncIOResult<Output = <Self as io::AsyncIOResult>::Output>>);
 
 impl io::AsyncIOResult for AsyncReadResult {
     type Output = io::Result<Vec<u8>>;
@@ -568,7 +793,7 @@ impl io::AsyncIOResult for AsyncReadResult {
 }
 
 /// Asynchronous write result
-pub struct AsyncWriteResult(Box<dyn io::AsyncIOResult<Output = <Self as io::AsyncIOResult>::Output>>);
+pub struct AsyncWriteResult(pub(crate) Box<dyn io::AsyncIOResult<Output = <Self as io::AsyncIOResult>::Output>>);
 
 impl io::AsyncIOResult for AsyncWriteResult {
     type Output = io::Result<u64>;
@@ -0,0 +1,349 @@
+use std::{task::Poll, time::Instant};
+
+use onca_common::io;
+
+use crate::{Path, os::os_imp, Permission, PathBuf, OpenMode, FileCreateFlags, FileAccessFlags, FileAsyncReadResult, FileAsyncWriteResult, AsyncReadResult, AsyncWriteResult};
+
+/// Timeout value that will cause an asynchronous wait to never time out
+pub const ASYNC_WAIT_INFINITE: u32 = u32::MAX;
+
+/// Range in a file to read.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct FileReadRange {
+    /// Offset in the file to start reading at.
+    pub offset: u64,
+    /// Number of bytes to read.
+    pub size:   u64,
+}
+
+//------------------------------
+
+/// Completion-based asynchronous file handle.
+pub trait AsyncFileHandle {
+    /// Read bytes asynchronously from the file, starting at `offset`.
+    fn read_at(&self, offset: u64, bytes_to_read: u64) -> io::Result<FileAsyncReadResult>;
+
+    /// Write bytes asynchronously to the file, starting at `offset`.
+    fn write_at(&self, offset: u64, buf: Vec<u8>) -> io::Result<FileAsyncWriteResult>;
+
+    /// Cancel all async I/O in flight for this file.
+    fn cancel_all_async_io(&self) -> io::Result<()>;
+
+    /// Get the current length of the file.
+    fn len(&self) -> io::Result<u64>;
+
+    /// Write all data that is currently cached.
+    fn flush(&self) -> io::Result<()>;
+}
+
+//------------------------------
+
+/// File dedicated to completion-based asynchronous I/O.
+///
+/// Each operation is issued at an explicit offset and is completed by the OS (IOCP on Windows, io_uring on Linux),
+/// so any number of reads and writes can be in flight at the same time, without the need for dedicated blocking threads.
+///
+/// The `AsyncRead` and `AsyncWrite` implementations use an internal cursor, which is advanced by the requested size when the operation is issued.
+///
+/// # Note
+///
+/// Dropping the file may cause the thread to sleep for a short amount of time while all in-flight I/O is being cancelled.
+pub struct AsyncFile {
+    handle: Box<dyn AsyncFileHandle>,
+    path:   PathBuf,
+    cursor: u64,
+}
+
+impl AsyncFile {
+    /// Create an async file from a handle and path
+    pub unsafe fn from_raw(handle: Box<dyn AsyncFileHandle>, path: PathBuf) -> Self {
+        Self { handle, path, cursor: 0 }
+    }
+
+    /// Create/open a file for asynchronous I/O.
+    ///
+    /// # Note
+    ///
+    /// Only works for path on the native filesystem.
+    ///
+    /// `FileAccessFlags::SupportAsync` is implied and does not need to be passed.
+    ///
+    /// # Error
+    ///
+    /// Returns an error when the file could not be created or opened, or `Unsupported` on platforms other than Windows and Linux.
+    #[must_use]
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        open_mode: OpenMode,
+        access_perms: Permission,
+        shared_access_perms: Permission,
+        create_flags: FileCreateFlags,
+        access_flags: FileAccessFlags
+    ) -> io::Result<AsyncFile> {
+        os_imp::file_async::AsyncFileHandle::create(path.as_ref(), open_mode, access_perms, shared_access_perms, create_flags, access_flags | FileAccessFlags::SupportAsync)
+            .map(|(handle, path)| AsyncFile { handle, path, cursor: 0 })
+    }
+
+    /// Open an existing file for asynchronous I/O.
+    ///
+    /// # Note
+    ///
+    /// Only works for path on the native filesystem.
+    ///
+    /// `FileAccessFlags::SupportAsync` is implied and does not need to be passed.
+    ///
+    /// # Error
+    ///
+    /// Returns an error when the file could not be opened.
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+        access_perms: Permission,
+        shared_access_perms: Permission,
+        access_flags: FileAccessFlags,
+    ) -> io::Result<AsyncFile> {
+        Self::create(path, OpenMode::OpenExisting, access_perms, shared_access_perms, FileCreateFlags::None, access_flags)
+    }
+
+    /// Get the file path.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the current length of the file.
+    ///
+    /// # Error
+    ///
+    /// Returns an error when the length could not be retrieved.
+    pub fn len(&self) -> io::Result<u64> {
+        self.handle.len()
+    }
+
+    /// Write all data that is currently cached by the OS.
+    ///
+    /// # Note
+    ///
+    /// This only includes writes that have already completed.
+    ///
+    /// # Error
+    ///
+    /// Returns an error when the data could not be flushed.
+    pub fn flush(&self) -> io::Result<()> {
+        self.handle.flush()
+    }
+
+    /// Read `bytes_to_read` bytes asynchronously, starting at `offset`.
+    ///
+    /// This does not use or modify the internal cursor.
+    ///
+    /// # Error
+    ///
+    /// Returns an error when the read could not be issued.
+    pub fn read_at(&self, offset: u64, bytes_to_read: u64) -> io::Result<AsyncReadResult> {
+        self.handle.read_at(offset, bytes_to_read).map(|inner| AsyncReadResult(inner))
+    }
+
+    /// Read multiple ranges of the file asynchronously, each into their own buffer.
+    ///
+    /// All reads are in flight at the same time and the result only completes once all reads have completed.
+    ///
+    /// # Error
+    ///
+    /// Returns an error when any of the reads could not be issued, in which case all reads that were already issued will be cancelled.
+    pub fn read_scatter(&self, ranges: &[FileReadRange]) -> io::Result<AsyncScatterReadResult> {
+        let mut results = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match self.handle.read_at(range.offset, range.size) {
+                Ok(res) => results.push(res),
+                Err(err) => {
+                    results.iter_mut().for_each(|res| _ = res.cancel());
+                    return Err(err);
+                }
+            }
+        }
+        Ok(AsyncBatchResult::new(results))
+    }
+
+    /// Write `buf` asynchronously, starting at `offset`.
+    ///
+    /// This does not use or modify the internal cursor.
+    ///
+    /// # Error
+    ///
+    /// Returns an error when the write could not be issued.
+    pub fn write_at(&self, offset: u64, buf: Vec<u8>) -> io::Result<AsyncWriteResult> {
+        self.handle.write_at(offset, buf).map(|inner| AsyncWriteResult(inner))
+    }
+
+    /// Write multiple buffers asynchronously, consecutively starting at `offset`.
+    ///
+    /// All writes are in flight at the same time and the result only completes once all writes have completed.
+    ///
+    /// # Error
+    ///
+    /// Returns an error when any of the writes could not be issued, in which case all writes that were already issued will be cancelled.
+    pub fn write_gather(&self, offset: u64, bufs: Vec<Vec<u8>>) -> io::Result<AsyncGatherWriteResult> {
+        let mut results = Vec::with_capacity(bufs.len());
+        let mut offset = offset;
+        for buf in bufs {
+            let size = buf.len() as u64;
+            match self.handle.write_at(offset, buf) {
+                Ok(res) => results.push(res),
+                Err(err) => {
+                    results.iter_mut().for_each(|res| _ = res.cancel());
+                    return Err(err);
+                }
+            }
+            offset += size;
+        }
+        Ok(AsyncBatchResult::new(results))
+    }
+
+    /// Cancel all async I/O for this file.
+    ///
+    /// # Note
+    ///
+    /// This function is also useful when calling it some time (i.e. 1 or more frames) before dropping the file,
+    /// allowing the file to be destroyed without having to wait for all I/O cancellation in the drop function.
+    ///
+    /// # Error
+    ///
+    /// Returns an error when all async I/O could not be cancelled.
+    pub fn cancel_all_async_io(&self) -> io::Result<()> {
+        self.handle.cancel_all_async_io()
+    }
+}
+
+impl io::Seek for AsyncFile {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match pos {
+            io::SeekFrom::Start(pos)   => (0, pos as i64),
+            io::SeekFrom::End(pos)     => (self.handle.len()?, pos),
+            io::SeekFrom::Current(pos) => (self.cursor, pos),
+        };
+
+        match base.checked_add_signed(offset) {
+            Some(cursor) => {
+                self.cursor = cursor;
+                Ok(cursor)
+            },
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid seek to a negative or overflowing position")),
+        }
+    }
+}
+
+impl io::AsyncRead for AsyncFile {
+    type AsyncResult = AsyncReadResult;
+
+    fn read_async(&mut self, bytes_to_read: u64) -> io::Result<Self::AsyncResult> {
+        let res = self.read_at(self.cursor, bytes_to_read)?;
+        self.cursor += bytes_to_read;
+        Ok(res)
+    }
+}
+
+impl io::AsyncWrite for AsyncFile {
+    type AsyncResult = AsyncWriteResult;
+
+    fn write_async(&mut self, buf: Vec<u8>) -> io::Result<Self::AsyncResult> {
+        let size = buf.len() as u64;
+        let res = self.write_at(self.cursor, buf)?;
+        self.cursor += size;
+        Ok(res)
+    }
+}
+
+//--------------------------------------------------------------
+
+/// Asynchronous scatter read result, returning a buffer per read range
+pub type AsyncScatterReadResult = AsyncBatchResult<Vec<u8>>;
+
+/// Asynchronous gather write result, returning the number of bytes written per buffer
+pub type AsyncGatherWriteResult = AsyncBatchResult<u64>;
+
+/// Result of multiple asynchronous operations, which completes once all operations have completed.
+///
+/// If any operation fails, all remaining operations will be cancelled and the error will be returned.
+pub struct AsyncBatchResult<T> {
+    results:   Vec<Box<dyn io::AsyncIOResult<Output = io::Result<T>>>>,
+    outputs:   Vec<Option<T>>,
+    exhausted: bool,
+}
+
+impl<T> AsyncBatchResult<T> {
+    fn new(results: Vec<Box<dyn io::AsyncIOResult<Output = io::Result<T>>>>) -> Self {
+        let outputs = results.iter().map(|_| None).collect();
+        Self { results, outputs, exhausted: false }
+    }
+
+    fn poll_or_wait(&mut self, timeout: Option<u32>) -> Poll<io::Result<Vec<T>>> {
+        if self.exhausted {
+            return Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, "Data was already taken from this result")));
+        }
+
+        let start = Instant::now();
+        let mut pending = false;
+        let mut error = None;
+        for (result, output) in self.results.iter_mut().zip(self.outputs.iter_mut()) {
+            if output.is_some() {
+                continue;
+            }
+
+            let res = match timeout {
+                Some(timeout) => result.wait(get_remaining_timeout(start, timeout)),
+                None => result.poll(),
+            };
+            match res {
+                Poll::Ready(Ok(val)) => *output = Some(val),
+                Poll::Ready(Err(err)) => {
+                    error = Some(err);
+                    break;
+                },
+                Poll::Pending => pending = true,
+            }
+        }
+
+        if let Some(err) = error {
+            _ = self.cancel();
+            self.exhausted = true;
+            return Poll::Ready(Err(err));
+        }
+        if pending {
+            return Poll::Pending;
+        }
+
+        self.exhausted = true;
+        Poll::Ready(Ok(self.outputs.drain(..).map(|output| output.unwrap()).collect()))
+    }
+}
+
+impl<T> io::AsyncIOResult for AsyncBatchResult<T> {
+    type Output = io::Result<Vec<T>>;
+
+    fn poll(&mut self) -> Poll<Self::Output> {
+        self.poll_or_wait(None)
+    }
+
+    fn wait(&mut self, timeout: u32) -> Poll<Self::Output> {
+        self.poll_or_wait(Some(timeout))
+    }
+
+    fn cancel(&mut self) -> io::Result<()> {
+        let mut res = Ok(());
+        for (result, _) in self.results.iter_mut().zip(self.outputs.iter()).filter(|(_, output)| output.is_none()) {
+            if let Err(err) = result.cancel() {
+                res = Err(err);
+            }
+        }
+        res
+    }
+}
+
+/// Get the timeout remaining from `timeout`, since `start`.
+pub(crate) fn get_remaining_timeout(start: Instant, timeout: u32) -> u32 {
+    if timeout == ASYNC_WAIT_INFINITE {
+        timeout
+    } else {
+        timeout.saturating_sub(start.elapsed().as_millis().min(u32::MAX as u128) as u32)
+    }
+}
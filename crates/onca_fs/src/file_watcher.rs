@@ -188,6 +188,10 @@ impl Filewatcher {
     }
 
     /// Create a new file watcher for a directory on the native file system
+    /// 
+    /// # Error
+    /// 
+    /// Returns an error when the directory could not be watched, or `Unsupported` on platforms other than Windows and Linux.
     pub fn new<P: AsRef<Path>>(path: P, watch_subtree: bool, filter: FileWatcherFilter, name_filter: Option<&str>) -> io::Result<Self> {
         let path = path.as_ref().to_path_buf();
         let handle = os_imp::file_watcher::FileWatcher::new(&path, watch_subtree, filter)?;
@@ -212,6 +216,10 @@ impl Filewatcher {
     }
 
     /// Tick the file watcher and dispatch any notification if needed
+    /// 
+    /// # Note
+    /// 
+    /// On Windows, changes are collected by the thread that created the file watcher, so it should also be ticked from that thread.
     pub fn tick(&self) {
         const METADATA_FILTERS: FileWatcherFilter = FileWatcherFilter::Attributes
             .bitor(FileWatcherFilter::Size)
@@ -233,7 +241,7 @@ impl Filewatcher {
                     self.filter.contains(FileWatcherFilter::FileRename) &&
                     (self.name_filter.filter(old.as_str()) || self.name_filter.filter(new.as_str())),
                 FileChangeInfo::FileModified { path, .. } =>
-                    self.filter.contains(METADATA_FILTERS) &&
+                    self.filter.intersects(METADATA_FILTERS) &&
                     self.name_filter.filter(path.as_str()),
                 FileChangeInfo::DirAdded(path) =>
                     self.filter.contains(FileWatcherFilter::DirAdded) &&
@@ -245,7 +253,7 @@ impl Filewatcher {
                     self.filter.contains(FileWatcherFilter::DirRename) &&
                     (self.name_filter.filter(old.as_str()) || self.name_filter.filter(new.as_str())),
                 FileChangeInfo::DirModified { path, .. } =>
-                    self.filter.contains(METADATA_FILTERS) &&
+                    self.filter.intersects(METADATA_FILTERS) &&
                     self.name_filter.filter(path.as_str()),
             };
 
@@ -282,3 +290,8 @@ impl Filewatcher {
         self.filter
     }
 }
+
+/// Handle to a directory watch, returned by `directory::watch`.
+/// 
+/// Changes are dispatched to the registered listeners when the handle is ticked, the watch stops when the handle is dropped.
+pub type WatchHandle = Filewatcher;
@@ -0,0 +1,298 @@
+use std::{
+    fmt,
+    hash::Hasher,
+    num::NonZeroUsize,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use onca_common::{
+    hashing::{Hasher256, BLAKE3, XXH3},
+    io::{self, BufRead, Read, Seek},
+};
+
+use crate::{File, FileAccessFlags, Path, Permission, PrefetchReader};
+
+/// Default size of the chunks in which the content of a file is read while hashing (1 MiB).
+pub const DEFAULT_HASH_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Default minimum size of a file, before it is hashed in parallel (64 MiB).
+pub const DEFAULT_PARALLEL_HASH_THRESHOLD: u64 = 64 * 1024 * 1024;
+
+/// Minimum size of the part of a file hashed by a single task when hashing in parallel (1 MiB), this needs to be a power of 2 multiple of [`BLAKE3::CHUNK_LEN`].
+const MIN_PARALLEL_SUBTREE_LEN: u64 = 1024 * 1024;
+
+/// Algorithm used to hash the content of a file.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum HashAlgo {
+    /// 64-bit XXH3, a fast non-cryptographic hash, e.g. to detect changes to a file.
+    XXH3,
+    /// 256-bit BLAKE3, a cryptographic hash, e.g. for content addressing or to verify a download.
+    ///
+    /// This is the only algorithm that supports hashing a file in parallel.
+    BLAKE3,
+}
+
+/// Hash of the content of a file.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum FileHash {
+    /// 64-bit XXH3 hash.
+    XXH3(u64),
+    /// 256-bit BLAKE3 hash.
+    BLAKE3([u8; 32]),
+}
+
+impl FileHash {
+    /// Get the algorithm used to create the hash.
+    #[must_use]
+    pub fn algo(&self) -> HashAlgo {
+        match self {
+            FileHash::XXH3(_) => HashAlgo::XXH3,
+            FileHash::BLAKE3(_) => HashAlgo::BLAKE3,
+        }
+    }
+
+    /// Get the bytes of the hash in their canonical order, which for XXH3 is big endian.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            FileHash::XXH3(hash) => hash.to_be_bytes().to_vec(),
+            FileHash::BLAKE3(hash) => hash.to_vec(),
+        }
+    }
+}
+
+/// Formats the hash as lowercase hex, in the canonical byte order of the algorithm.
+impl fmt::Display for FileHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.to_bytes() {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Options for hashing a file.
+#[derive(Clone, Debug)]
+pub struct HashOptions {
+    /// Size of the chunks in which the content of a file is read.
+    pub chunk_size:         usize,
+    /// Hash large files on multiple threads, each reading a different part of the file.
+    ///
+    /// This is only supported by [`HashAlgo::BLAKE3`], as other algorithms need to process the file in order.
+    pub parallel:           bool,
+    /// Minimum size of a file, before it is hashed in parallel.
+    pub parallel_threshold: u64,
+    /// Maximum number of threads used to hash a file in parallel, or `None` to use the available parallelism.
+    pub max_threads:        Option<NonZeroUsize>,
+}
+
+impl Default for HashOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: DEFAULT_HASH_CHUNK_SIZE,
+            parallel: true,
+            parallel_threshold: DEFAULT_PARALLEL_HASH_THRESHOLD,
+            max_threads: None,
+        }
+    }
+}
+
+/// Hash the content of a file.
+///
+/// See [`hash_file_with_options`] for more info.
+///
+/// # Error
+///
+/// Returns an error if the file could not be read.
+pub fn hash_file<P: AsRef<Path>>(path: P, algo: HashAlgo) -> io::Result<FileHash> {
+    hash_file_with_options(path, algo, &HashOptions::default())
+}
+
+/// Hash the content of a file using the given options.
+///
+/// The file is read in chunks, where the next chunk is prefetched while the current one is being hashed, see [`PrefetchReader`].
+/// Large files are hashed in parallel when the algorithm supports it, see [`HashOptions::parallel`].
+///
+/// The resulting hash is the same as when hashing the content in memory using [`hash_bytes`], whichever way the file was hashed.
+///
+/// # Error
+///
+/// Returns an error if the file could not be read.
+pub fn hash_file_with_options<P: AsRef<Path>>(path: P, algo: HashAlgo, options: &HashOptions) -> io::Result<FileHash> {
+    let path = path.as_ref();
+    let chunk_size = options.chunk_size.max(1);
+
+    let mut file = File::open(path, Permission::Read, Permission::Read, FileAccessFlags::SequentialAccess)?;
+    let len = file.get_metadata()?.file_size;
+
+    if algo == HashAlgo::BLAKE3 && options.parallel && len >= options.parallel_threshold {
+        let threads = options.max_threads.map_or_else(|| thread::available_parallelism().map_or(1, |threads| threads.get()), |threads| threads.get());
+        let subtree_len = parallel_subtree_len(len, threads);
+        if threads > 1 && len > subtree_len {
+            // Each thread opens its own handle, so they don't share a cursor
+            drop(file);
+            return hash_parallel(path, len, subtree_len, threads, chunk_size).map(FileHash::BLAKE3);
+        }
+    }
+
+    // Prefetching a file that fits in a single chunk would only add the overhead of the prefetch thread
+    if len <= chunk_size as u64 {
+        return hash_reader_with_chunk_size(&mut file, algo, chunk_size);
+    }
+
+    let mut reader = PrefetchReader::with_chunk_size(file, chunk_size)?;
+    let mut hasher = ContentHasher::new(algo);
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        hasher.write(buf);
+        let len = buf.len();
+        reader.consume(len);
+    }
+    Ok(hasher.finish())
+}
+
+/// Hash all remaining content of a reader.
+///
+/// This can be used to hash content that is not stored in a file on disk, like a file inside an [`Archive`](crate::Archive).
+///
+/// # Error
+///
+/// Returns an error if the content could not be read.
+pub fn hash_reader<R: Read>(reader: &mut R, algo: HashAlgo) -> io::Result<FileHash> {
+    hash_reader_with_chunk_size(reader, algo, DEFAULT_HASH_CHUNK_SIZE)
+}
+
+/// Hash content that is already in memory, resulting in the same hash as [`hash_file`] would for a file with the same content.
+#[must_use]
+pub fn hash_bytes(bytes: &[u8], algo: HashAlgo) -> FileHash {
+    let mut hasher = ContentHasher::new(algo);
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+//------------------------------
+
+enum ContentHasher {
+    // Both hashers buffer their input, which makes them too large to move around on the stack
+    XXH3(Box<XXH3>),
+    BLAKE3(Box<BLAKE3>),
+}
+
+impl ContentHasher {
+    fn new(algo: HashAlgo) -> Self {
+        match algo {
+            HashAlgo::XXH3 => ContentHasher::XXH3(Box::new(XXH3::new())),
+            HashAlgo::BLAKE3 => ContentHasher::BLAKE3(Box::new(BLAKE3::new())),
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        match self {
+            ContentHasher::XXH3(hasher) => hasher.write(bytes),
+            ContentHasher::BLAKE3(hasher) => hasher.write(bytes),
+        }
+    }
+
+    fn finish(&self) -> FileHash {
+        match self {
+            ContentHasher::XXH3(hasher) => FileHash::XXH3(hasher.finish()),
+            ContentHasher::BLAKE3(hasher) => FileHash::BLAKE3(hasher.finish256()),
+        }
+    }
+}
+
+fn hash_reader_with_chunk_size<R: Read>(reader: &mut R, algo: HashAlgo, chunk_size: usize) -> io::Result<FileHash> {
+    let mut hasher = ContentHasher::new(algo);
+    let mut buffer = vec![0; chunk_size];
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        hasher.write(&buffer[..read]);
+    }
+    Ok(hasher.finish())
+}
+
+/// Get the size of the parts a file is split in when it's hashed in parallel.
+///
+/// Each part is a BLAKE3 subtree, so it needs to contain a power of 2 chunks, the file is split in a few parts per thread, so the threads stay busy when some parts take longer to read.
+fn parallel_subtree_len(len: u64, threads: usize) -> u64 {
+    let chunks = len / (threads as u64 * 4) / BLAKE3::CHUNK_LEN as u64;
+    (chunks.max(1).next_power_of_two() * BLAKE3::CHUNK_LEN as u64).max(MIN_PARALLEL_SUBTREE_LEN)
+}
+
+fn hash_parallel(path: &Path, len: u64, subtree_len: u64, threads: usize, chunk_size: usize) -> io::Result<[u8; 32]> {
+    let num_subtrees = len.div_ceil(subtree_len) as usize;
+    let next_subtree = AtomicUsize::new(0);
+
+    let hash_subtrees = || -> io::Result<Vec<(usize, [u8; 32])>> {
+        let mut file = File::open(path, Permission::Read, Permission::Read, FileAccessFlags::SequentialAccess)?;
+        let mut buffer = vec![0; chunk_size.min(subtree_len as usize)];
+        let mut cvs = Vec::new();
+        loop {
+            let idx = next_subtree.fetch_add(1, Ordering::Relaxed);
+            if idx >= num_subtrees {
+                return Ok(cvs);
+            }
+
+            let offset = idx as u64 * subtree_len;
+            let mut remaining = subtree_len.min(len - offset);
+            file.seek(io::SeekFrom::Start(offset))?;
+            _ = file.read_ahead(offset, remaining);
+
+            let mut hasher = BLAKE3::new_subtree(offset);
+            while remaining > 0 {
+                let to_read = remaining.min(buffer.len() as u64) as usize;
+                file.read_exact(&mut buffer[..to_read])?;
+                hasher.write(&buffer[..to_read]);
+                remaining -= to_read as u64;
+            }
+            cvs.push((idx, hasher.finish_subtree()));
+        }
+    };
+
+    let mut cvs = vec![[0; 32]; num_subtrees];
+    thread::scope(|scope| {
+        let handles = (0..threads.min(num_subtrees)).map(|_| scope.spawn(|| {
+            let res = hash_subtrees();
+            if res.is_err() {
+                // Stop the other threads, as the hash cannot be completed anymore
+                next_subtree.store(num_subtrees, Ordering::Relaxed);
+            }
+            res
+        })).collect::<Vec<_>>();
+
+        for handle in handles {
+            let thread_cvs = handle.join().unwrap_or_else(|err| std::panic::resume_unwind(err))?;
+            for (idx, cv) in thread_cvs {
+                cvs[idx] = cv;
+            }
+        }
+        Ok::<_, io::Error>(())
+    })?;
+
+    let left_len = largest_power_of_2_below(cvs.len());
+    Ok(BLAKE3::merge_subtrees_root(&merge_subtrees(&cvs[..left_len]), &merge_subtrees(&cvs[left_len..])))
+}
+
+/// Merge the chaining values of equally sized subtrees, of which only the last one may be smaller, into the chaining value of their parent.
+fn merge_subtrees(cvs: &[[u8; 32]]) -> [u8; 32] {
+    if cvs.len() == 1 {
+        return cvs[0];
+    }
+
+    // This results in the same tree as splitting the input at `BLAKE3::left_subtree_len`, as the subtrees contain a power of 2 chunks
+    let left_len = largest_power_of_2_below(cvs.len());
+    BLAKE3::merge_subtrees(&merge_subtrees(&cvs[..left_len]), &merge_subtrees(&cvs[left_len..]))
+}
+
+fn largest_power_of_2_below(val: usize) -> usize {
+    1 << (usize::BITS - 1 - (val - 1).leading_zeros())
+}
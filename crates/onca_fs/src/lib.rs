@@ -19,15 +19,42 @@ pub mod link;
 mod file;
 pub use file::*;
 
+mod file_async;
+pub use file_async::*;
+
+mod safe_write;
+pub use safe_write::*;
+
+mod copy;
+pub use copy::*;
+
+mod temp;
+pub use temp::*;
+
+mod prefetch;
+pub use prefetch::*;
+
+mod hash;
+pub use hash::*;
+
 mod entry;
 pub use entry::*;
 
+mod walk;
+pub use walk::*;
+
 mod file_watcher;
 pub use file_watcher::*;
 
 mod vfs;
 pub use vfs::*;
 
+mod memory_fs;
+pub use memory_fs::*;
+
+mod archive;
+pub use archive::*;
+
 mod os;
 
 pub fn get_working_dir() -> io::Result<PathBuf> {
@@ -0,0 +1,409 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Arc,
+    task::Poll,
+};
+
+use onca_common::{io, sync::RwLock};
+
+use crate::*;
+
+/// Node in a memory file system
+enum MemoryNode {
+    Directory,
+    File(Arc<RwLock<Vec<u8>>>),
+}
+
+/// File system that only lives in memory.
+///
+/// The memory file system is meant to be mounted in the [`VirtualFileSystem`], e.g. to provide generated or downloaded content,
+/// or to override files of other mount point entries without touching the disk.
+///
+/// All paths are relative to the root of the memory file system.
+pub struct MemoryFileSystem {
+    path:  PathBuf,
+    /// Nodes, indexed by their path using `/` as separator
+    nodes: RwLock<BTreeMap<String, MemoryNode>>,
+}
+
+impl MemoryFileSystem {
+    /// Create a new empty memory file system, `name` is used as the path of the file system.
+    pub fn new(name: &str) -> Arc<Self> {
+        Arc::new(Self {
+            path: PathBuf::from(name.to_string()),
+            nodes: RwLock::new(BTreeMap::new()),
+        })
+    }
+
+    /// Get the key of a path into the node map
+    fn get_key(path: &Path) -> String {
+        path.as_str().replace('\\', "/").trim_matches('/').to_string()
+    }
+
+    /// Get the key of the parent of a node
+    fn get_parent_key(key: &str) -> &str {
+        key.rsplit_once('/').map_or("", |(parent, _)| parent)
+    }
+
+    fn is_dir(nodes: &BTreeMap<String, MemoryNode>, key: &str) -> bool {
+        key.is_empty() || matches!(nodes.get(key), Some(MemoryNode::Directory))
+    }
+
+    fn get_children<'a>(nodes: &'a BTreeMap<String, MemoryNode>, key: &'a str) -> impl Iterator<Item = (&'a String, &'a MemoryNode)> + 'a {
+        nodes.iter().filter(move |(node_key, _)| Self::get_parent_key(node_key) == key)
+    }
+
+    fn create_entry(key: &str, node: &MemoryNode) -> (Box<dyn EntryHandle>, EntryType) {
+        let (entry_type, data) = match node {
+            MemoryNode::Directory  => (EntryType::Directory, None),
+            MemoryNode::File(data) => (EntryType::File, Some(data.clone())),
+        };
+        (Box::new(MemoryEntryHandle { path: PathBuf::from(key.to_string()), entry_type, data }), entry_type)
+    }
+}
+
+impl SubSystem for MemoryFileSystem {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn get_support(&self) -> SubSystemSupport {
+        SubSystemSupport::CreateDeleteDirectory | SubSystemSupport::CreateDeleteFile
+    }
+
+    fn entry(&self, path: &Path) -> io::Result<Entry> {
+        let key = Self::get_key(path);
+        let nodes = self.nodes.read();
+        match nodes.get(&key) {
+            Some(node) => {
+                let (handle, entry_type) = Self::create_entry(&key, node);
+                Ok(Entry::from_raw(handle, entry_type))
+            },
+            None => Err(io::Error::new(io::ErrorKind::NotFound, format!("`{path}` does not exist in the memory file system"))),
+        }
+    }
+
+    fn directory_exists(&self, path: &Path) -> io::Result<bool> {
+        Ok(Self::is_dir(&self.nodes.read(), &Self::get_key(path)))
+    }
+
+    fn read_directory(&self, path: &Path) -> io::Result<EntryIter> {
+        let key = Self::get_key(path);
+        let nodes = self.nodes.read();
+        if !Self::is_dir(&nodes, &key) {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("`{path}` is not a directory in the memory file system")));
+        }
+
+        let entries = Self::get_children(&nodes, &key).map(|(key, node)| Self::create_entry(key, node)).collect();
+        // The entry iterator stops when it is given an empty path, so make sure that reading the root does not end the iteration
+        let iter_path = if key.is_empty() { self.path.clone() } else { PathBuf::from(key) };
+        Ok(unsafe { EntryIter::from_raw(iter_path, Box::new(MemoryEntrySearchHandle { entries })) })
+    }
+
+    fn create_directory(&self, path: &Path, recursively: bool) -> io::Result<()> {
+        let key = Self::get_key(path);
+        let mut nodes = self.nodes.write();
+
+        let parent = Self::get_parent_key(&key);
+        if !Self::is_dir(&nodes, parent) {
+            if !recursively {
+                return Err(io::Error::new(io::ErrorKind::NotFound, format!("Parent directory of `{path}` does not exist in the memory file system")));
+            }
+
+            let mut cur = String::with_capacity(parent.len());
+            for comp in parent.split('/') {
+                if !cur.is_empty() {
+                    cur.push('/');
+                }
+                cur.push_str(comp);
+                match nodes.get(&cur) {
+                    Some(MemoryNode::File(_)) => return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("`{cur}` is a file in the memory file system"))),
+                    Some(MemoryNode::Directory) => {},
+                    None => _ = nodes.insert(cur.clone(), MemoryNode::Directory),
+                }
+            }
+        }
+
+        match nodes.get(&key) {
+            Some(MemoryNode::Directory) => Ok(()),
+            Some(MemoryNode::File(_)) => Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("`{path}` is a file in the memory file system"))),
+            None => {
+                nodes.insert(key, MemoryNode::Directory);
+                Ok(())
+            }
+        }
+    }
+
+    fn remove_directory(&self, path: &Path) -> io::Result<()> {
+        let key = Self::get_key(path);
+        let mut nodes = self.nodes.write();
+        if key.is_empty() || !Self::is_dir(&nodes, &key) {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("`{path}` is not a directory in the memory file system")));
+        }
+        if Self::get_children(&nodes, &key).next().is_some() {
+            return Err(io::Error::other(format!("`{path}` is not empty")));
+        }
+        nodes.remove(&key);
+        Ok(())
+    }
+
+    fn remove_directory_all(&self, path: &Path) -> io::Result<()> {
+        let key = Self::get_key(path);
+        let mut nodes = self.nodes.write();
+        if !Self::is_dir(&nodes, &key) {
+            return Err(io::Error::new(io::ErrorKind::NotFound, format!("`{path}` is not a directory in the memory file system")));
+        }
+
+        if key.is_empty() {
+            nodes.clear();
+        } else {
+            let prefix = format!("{key}/");
+            nodes.retain(|node_key, _| *node_key != key && !node_key.starts_with(&prefix));
+        }
+        Ok(())
+    }
+
+    fn create_file(
+        &self,
+        path: &Path,
+        open_mode: OpenMode,
+        access_perms: Permission,
+        _shared_access_perms: Permission,
+        _create_flags: FileCreateFlags,
+        _access_flags: FileAccessFlags,
+    ) -> io::Result<File> {
+        let key = Self::get_key(path);
+        let mut nodes = self.nodes.write();
+
+        let existing = match nodes.get(&key) {
+            Some(MemoryNode::Directory) => return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("`{path}` is a directory in the memory file system"))),
+            Some(MemoryNode::File(data)) => Some(data.clone()),
+            None => None,
+        };
+
+        let data = match (existing, open_mode) {
+            (Some(_), OpenMode::CreateNonExisting) => return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("`{path}` already exists in the memory file system"))),
+            (Some(data), OpenMode::CreateAlways | OpenMode::TruncateExisting) => {
+                data.write().clear();
+                data
+            },
+            (Some(data), _) => data,
+            (None, OpenMode::OpenExisting | OpenMode::TruncateExisting) => return Err(io::Error::new(io::ErrorKind::NotFound, format!("`{path}` does not exist in the memory file system"))),
+            (None, _) => {
+                if !Self::is_dir(&nodes, Self::get_parent_key(&key)) {
+                    return Err(io::Error::new(io::ErrorKind::NotFound, format!("Parent directory of `{path}` does not exist in the memory file system")));
+                }
+                let data = Arc::new(RwLock::new(Vec::new()));
+                nodes.insert(key.clone(), MemoryNode::File(data.clone()));
+                data
+            },
+        };
+
+        let handle = Box::new(MemoryFileHandle { path: PathBuf::from(key.clone()), data, cursor: 0 });
+        Ok(unsafe { File::from_raw(handle, PathBuf::from(key), access_perms) })
+    }
+
+    fn delete_file(&self, path: &Path) -> io::Result<()> {
+        let key = Self::get_key(path);
+        let mut nodes = self.nodes.write();
+        match nodes.get(&key) {
+            Some(MemoryNode::File(_)) => {
+                nodes.remove(&key);
+                Ok(())
+            },
+            _ => Err(io::Error::new(io::ErrorKind::NotFound, format!("`{path}` is not a file in the memory file system"))),
+        }
+    }
+
+    fn watch_files(&self, _path: &Path, _watch_subtree: bool, _filter: FileWatcherFilter, _name_filter: Option<&str>) -> io::Result<Filewatcher> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "The memory file system does not support file watchers"))
+    }
+}
+
+//------------------------------
+
+struct MemoryEntryHandle {
+    path:       PathBuf,
+    entry_type: EntryType,
+    data:       Option<Arc<RwLock<Vec<u8>>>>,
+}
+
+impl EntryHandle for MemoryEntryHandle {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn fully_qualified_path(&self) -> io::Result<PathBuf> {
+        Ok(self.path.clone())
+    }
+
+    fn metadata(&self) -> io::Result<MetaData> {
+        let file_size = self.data.as_ref().map_or(0, |data| data.read().len() as u64);
+        Ok(MetaData { entry_type: self.entry_type, file_size, alloc_size: file_size, ..Default::default() })
+    }
+
+    fn permissions(&self) -> io::Result<Permission> {
+        Ok(Permission::Read | Permission::Write | Permission::Delete)
+    }
+}
+
+struct MemoryEntrySearchHandle {
+    entries: VecDeque<(Box<dyn EntryHandle>, EntryType)>,
+}
+
+impl EntrySearchHandle for MemoryEntrySearchHandle {
+    fn next(&mut self, path: PathBuf) -> Option<(Box<dyn EntryHandle>, EntryType, PathBuf)> {
+        let (handle, entry_type) = self.entries.pop_front()?;
+        Some((handle, entry_type, path))
+    }
+}
+
+//------------------------------
+
+struct MemoryFileHandle {
+    path:   PathBuf,
+    data:   Arc<RwLock<Vec<u8>>>,
+    cursor: u64,
+}
+
+impl FileHandle for MemoryFileHandle {
+    fn flush_data(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn flush_all(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn cancel_all_thread_async_io(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn cancel_all_async_io(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        self.data.write().resize(len as usize, 0);
+        Ok(())
+    }
+
+    fn set_times(&mut self, _times: FileTimes) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_permissions(&mut self, _permissions: Permission) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_readonly(&mut self, _readonly: bool) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_hidden(&mut self, _hidden: bool) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_content_indexed(&mut self, _content_indexed: bool) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn get_metadata(&mut self) -> io::Result<MetaData> {
+        MemoryEntryHandle { path: self.path.clone(), entry_type: EntryType::File, data: Some(self.data.clone()) }.metadata()
+    }
+
+    // Memory files only live within the current process, so there are no other processes to protect the file from
+
+    fn lock(&mut self, _kind: FileLockKind, _blocking: bool) -> io::Result<bool> {
+        Ok(true)
+    }
+
+    fn unlock(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn read_ahead(&mut self, _offset: u64, _len: u64) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let data = self.data.read();
+        let start = (self.cursor as usize).min(data.len());
+        let len = buf.len().min(data.len() - start);
+        buf[..len].copy_from_slice(&data[start..start + len]);
+        self.cursor += len as u64;
+        Ok(len)
+    }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut data = self.data.write();
+        let start = self.cursor as usize;
+        let end = start + buf.len();
+        if data.len() < end {
+            data.resize(end, 0);
+        }
+        data[start..end].copy_from_slice(buf);
+        self.cursor = end as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match pos {
+            io::SeekFrom::Start(pos)   => (0, pos as i64),
+            io::SeekFrom::End(pos)     => (self.data.read().len() as u64, pos),
+            io::SeekFrom::Current(pos) => (self.cursor, pos),
+        };
+
+        match base.checked_add_signed(offset) {
+            Some(cursor) => {
+                self.cursor = cursor;
+                Ok(cursor)
+            },
+            None => Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid seek to a negative or overflowing position")),
+        }
+    }
+
+    // Memory files are always immediately available, so async I/O completes synchronously
+
+    fn read_async(&mut self, bytes_to_read: u64) -> io::Result<FileAsyncReadResult> {
+        let mut buf = vec![0; bytes_to_read as usize];
+        let read = self.read(&mut buf)?;
+        buf.truncate(read);
+        Ok(Box::new(CompletedAsyncResult(Some(Ok(buf)))))
+    }
+
+    fn write_async(&mut self, buf: Vec<u8>) -> io::Result<FileAsyncWriteResult> {
+        let written = self.write(&buf)?;
+        Ok(Box::new(CompletedAsyncResult(Some(Ok(written as u64)))))
+    }
+
+    fn map_memory(&mut self, _mapped_size: Option<u64>) -> io::Result<Box<dyn MemoryMappedFileHandle>> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "Files in a memory file system cannot be memory mapped"))
+    }
+}
+
+/// Async result that has already completed
+pub(crate) struct CompletedAsyncResult<T>(pub(crate) Option<io::Result<T>>);
+
+impl<T> io::AsyncIOResult for CompletedAsyncResult<T> {
+    type Output = io::Result<T>;
+
+    fn poll(&mut self) -> Poll<Self::Output> {
+        match self.0.take() {
+            Some(res) => Poll::Ready(res),
+            None => Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, "Data was already taken from this result"))),
+        }
+    }
+
+    fn wait(&mut self, _timeout: u32) -> Poll<Self::Output> {
+        self.poll()
+    }
+
+    fn cancel(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
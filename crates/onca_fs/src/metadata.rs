@@ -1,8 +1,13 @@
-use std::num::{NonZeroU32, NonZeroU64};
+use std::{
+    num::{NonZeroU32, NonZeroU64},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-use onca_common::guid::Guid;
+use onca_common::{guid::Guid, io};
 use onca_common_macros::{flags, EnumDisplay};
 
+use crate::{os::os_imp, Path};
+
 /// File system entry type.
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Default, EnumDisplay)]
 pub enum EntryType {
@@ -136,9 +141,70 @@ pub enum FileLinkCount {
 }
 
 /// File time.
+/// 
+/// The time is stored as the number of 100ns intervals since 1601-01-01 (UTC), independent of the OS.
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Debug)]
 pub struct FileTime(pub(crate) u64);
 
+impl FileTime {
+    /// Number of 100ns intervals in a second.
+    const INTERVALS_PER_SEC: u64 = 10_000_000;
+    /// Number of 100ns intervals between 1601-01-01 and the unix epoch (1970-01-01).
+    const UNIX_EPOCH_INTERVALS: u64 = 11_644_473_600 * Self::INTERVALS_PER_SEC;
+
+    /// Create a file time from the number of 100ns intervals since 1601-01-01 (UTC).
+    #[must_use]
+    pub const fn from_raw(intervals: u64) -> Self {
+        Self(intervals)
+    }
+
+    /// Get the number of 100ns intervals since 1601-01-01 (UTC).
+    #[must_use]
+    pub const fn as_raw(self) -> u64 {
+        self.0
+    }
+
+    /// Get the current time.
+    #[must_use]
+    pub fn now() -> Self {
+        SystemTime::now().into()
+    }
+}
+
+impl From<SystemTime> for FileTime {
+    fn from(time: SystemTime) -> Self {
+        let to_intervals = |dur: Duration| dur.as_secs() * Self::INTERVALS_PER_SEC + dur.subsec_nanos() as u64 / 100;
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(dur) => Self(Self::UNIX_EPOCH_INTERVALS.saturating_add(to_intervals(dur))),
+            Err(err) => Self(Self::UNIX_EPOCH_INTERVALS.saturating_sub(to_intervals(err.duration()))),
+        }
+    }
+}
+
+impl From<FileTime> for SystemTime {
+    fn from(time: FileTime) -> Self {
+        let to_duration = |intervals: u64| Duration::new(intervals / FileTime::INTERVALS_PER_SEC, (intervals % FileTime::INTERVALS_PER_SEC) as u32 * 100);
+        if time.0 >= FileTime::UNIX_EPOCH_INTERVALS {
+            UNIX_EPOCH + to_duration(time.0 - FileTime::UNIX_EPOCH_INTERVALS)
+        } else {
+            UNIX_EPOCH - to_duration(FileTime::UNIX_EPOCH_INTERVALS - time.0)
+        }
+    }
+}
+
+/// Times to set on a file or directory.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct FileTimes {
+    /// Creation time, or `None` to leave it unchanged.
+    /// 
+    /// Not all platforms support setting the creation time, in which case it will be ignored.
+    pub created:  Option<FileTime>,
+    /// Last access time, or `None` to leave it unchanged.
+    pub accessed: Option<FileTime>,
+    /// Last write time, or `None` to leave it unchanged.
+    pub modified: Option<FileTime>,
+}
+
 /// Storage flags
 #[flags]
 pub enum StorageFlags {
@@ -204,4 +270,90 @@ pub struct MetaData {
     pub volume_file_id:   VolumeFileId,
     /// File storage info
     pub storage_info:     Option<StorageInfo>,
+}
+
+impl MetaData {
+    /// Check if the entry is read-only.
+    #[must_use]
+    pub fn is_readonly(&self) -> bool {
+        self.flags.contains(EntryFlags::ReadOnly)
+    }
+
+    /// Check if the entry is hidden.
+    #[must_use]
+    pub fn is_hidden(&self) -> bool {
+        self.flags.contains(EntryFlags::Hidden)
+    }
+
+    /// Check if the entry is compressed by the file system.
+    #[must_use]
+    pub fn is_compressed(&self) -> bool {
+        self.flags.contains(EntryFlags::Compressed)
+    }
+
+    /// Check if the entry is a sparse file.
+    #[must_use]
+    pub fn is_sparse(&self) -> bool {
+        self.flags.contains(EntryFlags::Sparse)
+    }
+
+    /// Get the number of bytes the entry actually takes up on disk.
+    /// 
+    /// For compressed or sparse files, this is the compressed size, which can be smaller than the file size,
+    /// otherwise this is the allocation size, which is commonly rounded up to a multiple of the cluster size.
+    #[must_use]
+    pub fn size_on_disk(&self) -> u64 {
+        self.compressed_size.map_or(self.alloc_size, NonZeroU64::get)
+    }
+}
+
+//------------------------------
+
+/// Set the times of a file or directory, any time that is `None` is left unchanged.
+/// 
+/// # Error
+/// 
+/// Returns an error if the times could not be set.
+pub fn set_times<P: AsRef<Path>>(path: P, times: FileTimes) -> io::Result<()> {
+    os_imp::entry::set_times(path.as_ref(), times)
+}
+
+/// Set the modification time of a file or directory.
+/// 
+/// # Error
+/// 
+/// Returns an error if the modification time could not be set.
+pub fn set_modified<P: AsRef<Path>>(path: P, time: FileTime) -> io::Result<()> {
+    set_times(path, FileTimes { modified: Some(time), ..Default::default() })
+}
+
+/// Set the last access time of a file or directory.
+/// 
+/// # Error
+/// 
+/// Returns an error if the access time could not be set.
+pub fn set_accessed<P: AsRef<Path>>(path: P, time: FileTime) -> io::Result<()> {
+    set_times(path, FileTimes { accessed: Some(time), ..Default::default() })
+}
+
+/// Set if a file or directory is read-only.
+/// 
+/// This does not require the file to be opened with write permissions, so can also be used to make a read-only file writable again.
+/// 
+/// # Error
+/// 
+/// Returns an error if the read-only flag could not be set.
+pub fn set_readonly<P: AsRef<Path>>(path: P, readonly: bool) -> io::Result<()> {
+    os_imp::entry::set_readonly(path.as_ref(), readonly)
+}
+
+/// Set if a file or directory is hidden in a file explorer.
+/// 
+/// This may be a no-op if the underlying file system does not support this, e.g. when files are hidden based on their name.
+/// 
+/// # Error
+/// 
+/// Returns an error if the hidden flag could not be set.
+pub fn set_hidden<P: AsRef<Path>>(path: P, hidden: bool) -> io::Result<()> {
+    os_imp::entry::set_hidden(path.as_ref(), hidden)
 }
\ No newline at end of file
@@ -0,0 +1,61 @@
+use onca_common::{
+    prelude::*,
+    io,
+};
+use crate::Path;
+
+pub(crate) fn create(path: &Path) -> io::Result<()> {
+    scoped_alloc!(AllocId::TlsTemp);
+
+    let path = path.to_path_buf();
+    create_dir(path.as_ptr() as *const libc::c_char)
+}
+
+pub(crate) fn create_recursive(path: &Path) -> io::Result<()> {
+    scoped_alloc!(AllocId::TlsTemp);
+
+    let mut parent_paths = Vec::new();
+    for ancestor in path.ancestors() {
+        parent_paths.push(ancestor);
+    }
+
+    for cur_dir in parent_paths.into_iter().rev() {
+        let path = cur_dir.to_path_buf();
+        create_dir(path.as_ptr() as *const libc::c_char)?;
+    }
+    Ok(())
+}
+
+/// Create a new directory for temporary files, which is only accessible by the current user
+/// 
+/// Unlike `create`, this fails with `io::ErrorKind::AlreadyExists` when the directory already exists
+pub(crate) fn create_temp(path: &Path) -> io::Result<()> {
+    scoped_alloc!(AllocId::TlsTemp);
+
+    let path = path.to_path_buf();
+    if unsafe { libc::mkdir(path.as_ptr() as *const libc::c_char, 0o700) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn create_dir(path: *const libc::c_char) -> io::Result<()> {
+    // The permissions are still limited by the umask of the process
+    if unsafe { libc::mkdir(path, 0o777) } != 0 {
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() != Some(libc::EEXIST) {
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn remove(path: &Path) -> io::Result<()> {
+    scoped_alloc!(AllocId::TlsTemp);
+
+    let path = path.to_path_buf();
+    if unsafe { libc::rmdir(path.as_ptr() as *const libc::c_char) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
@@ -0,0 +1,340 @@
+use std::{
+    mem,
+    num::NonZeroU32,
+};
+use cfg_if::cfg_if;
+use onca_common::{
+    prelude::*,
+    io,
+};
+use crate::{PathBuf, DriveInfo, DriveType, VolumeInfo, FilesystemFlags, Path, DiskSpace, DeviceCapabilities};
+
+use super::entry::realpath;
+
+/// Entry in the mount table
+struct MountEntry {
+    device:      String,
+    mount_point: PathBuf,
+    fs_type:     String,
+}
+
+pub fn get_drive_info(path: &Path) -> io::Result<DriveInfo> {
+    let mount = find_mount_for_root(path)?;
+    get_drive_info_internal(&mount)
+}
+
+pub fn get_volume_root(path: &Path) -> io::Result<PathBuf> {
+    find_mount(path).map(|mount| mount.mount_point)
+}
+
+pub fn get_disk_space(path: &Path) -> io::Result<DiskSpace> {
+    let root = get_volume_root(path)?;
+    let stat = statvfs(&root)?;
+    let block_size = block_size(&stat);
+    Ok(DiskSpace {
+        total: stat.f_blocks as u64 * block_size,
+        available: stat.f_bfree as u64 * block_size,
+        available_to_user: stat.f_bavail as u64 * block_size,
+    })
+}
+
+pub fn get_cluster_size(path: &Path) -> io::Result<u32> {
+    let root = get_volume_root(path)?;
+    statvfs(&root).map(|stat| block_size(&stat) as u32)
+}
+
+pub fn get_device_capabilities(path: &Path) -> io::Result<DeviceCapabilities> {
+    let root = get_volume_root(path)?;
+    let device = stat(&root)?.st_dev;
+
+    cfg_if!{
+        if #[cfg(target_os = "linux")] {
+            Ok(DeviceCapabilities {
+                seek_penalty: read_block_device_attribute(device, "queue/rotational").map(|rotational| rotational != 0),
+                trim: read_block_device_attribute(device, "queue/discard_max_bytes").map(|max_bytes| max_bytes != 0),
+            })
+        } else {
+            // TODO: macOS needs IOKit to query the device properties
+            _ = device;
+            Ok(DeviceCapabilities::default())
+        }
+    }
+}
+
+pub fn get_drive_type(path: &Path) -> DriveType {
+    scoped_alloc!(AllocId::TlsTemp);
+
+    match find_mount_for_root(path) {
+        Ok(mount) => get_mount_drive_type(&mount),
+        Err(_) => DriveType::NoRootDir,
+    }
+}
+
+pub fn get_all_drive_info() -> io::Result<Vec<DriveInfo>> {
+    let mut infos = Vec::new();
+    for mount in get_mounts()? {
+        // Pseudo file systems, like `proc` and `sysfs`, don't have any storage
+        match get_drive_info_internal(&mount) {
+            Ok(info) if info.total_size > 0 => infos.push(info),
+            _ => (),
+        }
+    }
+    Ok(infos)
+}
+
+fn get_drive_info_internal(mount: &MountEntry) -> io::Result<DriveInfo> {
+    let stat = statvfs(&mount.mount_point)?;
+    let block_size = block_size(&stat);
+
+    // The sector size is not reported by `statvfs`, so assume the most common sector size, unless the blocks are smaller
+    let sector_size = if block_size % 512 == 0 { 512 } else { block_size };
+
+    Ok(DriveInfo {
+        root: mount.mount_point.clone(),
+        drive_type: get_mount_drive_type(mount),
+        total_size: stat.f_blocks as u64 * block_size,
+        available_size: stat.f_bfree as u64 * block_size,
+        available_to_user: stat.f_bavail as u64 * block_size,
+        sector_size: sector_size as u32,
+        sectors_per_cluster: (block_size / sector_size) as u32,
+        total_clusters: stat.f_blocks as u64,
+        free_clusters: stat.f_bavail as u64,
+    })
+}
+
+fn get_mount_drive_type(mount: &MountEntry) -> DriveType {
+    match mount.fs_type.as_str() {
+        "tmpfs" | "ramfs" => DriveType::RamDisk,
+        "nfs" | "nfs4" | "cifs" | "smb3" | "smbfs" | "afpfs" | "webdav" | "9p" | "fuse.sshfs" => DriveType::Remote,
+        "iso9660" | "udf" | "cd9660" => DriveType::Disk,
+        _ if mount.device.starts_with("/dev/") => if is_removable(mount) { DriveType::Removable } else { DriveType::Fixed },
+        _ => DriveType::Unknown,
+    }
+}
+
+fn is_removable(mount: &MountEntry) -> bool {
+    cfg_if!{
+        if #[cfg(target_os = "linux")] {
+            stat(&mount.mount_point).ok()
+                .and_then(|stat| read_block_device_attribute(stat.st_dev, "removable"))
+                .map_or(false, |removable| removable != 0)
+        } else {
+            _ = mount;
+            false
+        }
+    }
+}
+
+pub fn get_volume_info(path: &Path) -> io::Result<VolumeInfo> {
+    let mount = find_mount_for_root(path)?;
+    get_volume_info_internal(&mount)
+}
+
+pub fn get_all_volume_info() -> io::Result<Vec<VolumeInfo>> {
+    let mut infos = Vec::<VolumeInfo>::new();
+    for mount in get_mounts()? {
+        // Pseudo file systems, like `proc` and `sysfs`, don't have any storage
+        if statvfs(&mount.mount_point).map_or(true, |stat| stat.f_blocks == 0) {
+            continue;
+        }
+        let Ok(info) = get_volume_info_internal(&mount) else { continue; };
+
+        // A device can be mounted in multiple locations
+        match infos.iter_mut().find(|other| other.name == info.name && other.serial == info.serial) {
+            Some(other) => other.roots.extend(info.roots),
+            None => infos.push(info),
+        }
+    }
+    Ok(infos)
+}
+
+fn get_volume_info_internal(mount: &MountEntry) -> io::Result<VolumeInfo> {
+    let stat = statvfs(&mount.mount_point)?;
+
+    let mut fs_flags = FilesystemFlags::CasePreservedNames | FilesystemFlags::UnicodePaths;
+    fs_flags.set(FilesystemFlags::CaseSensitiveSearch, is_case_sensitive(mount));
+    fs_flags.set(FilesystemFlags::ReadOnly, stat.f_flag & libc::ST_RDONLY != 0);
+
+    Ok(VolumeInfo {
+        roots: vec![mount.mount_point.clone()],
+        name: mount.device.clone(),
+        serial: NonZeroU32::new(stat.f_fsid as u32),
+        max_comp_len: stat.f_namemax as u32,
+        fs_flags,
+        fs_name: mount.fs_type.clone(),
+    })
+}
+
+fn is_case_sensitive(mount: &MountEntry) -> bool {
+    cfg_if!{
+        if #[cfg(target_os = "macos")] {
+            // APFS and HFS+ are case-insensitive by default, but can be formatted as case-sensitive
+            unsafe { libc::pathconf(mount.mount_point.as_ptr() as *const libc::c_char, libc::_PC_CASE_SENSITIVE) == 1 }
+        } else {
+            !matches!(mount.fs_type.as_str(), "vfat" | "msdos" | "exfat" | "ntfs" | "ntfs3" | "fuseblk" | "cifs" | "smb3" | "iso9660")
+        }
+    }
+}
+
+//------------------------------
+
+/// Find the mount containing the given path
+fn find_mount(path: &Path) -> io::Result<MountEntry> {
+    // The path does not need to exist, so use the closest ancestor that does, relative paths are relative to the working directory
+    let real_path = path.ancestors()
+        .find_map(|ancestor| realpath(&ancestor.to_path_buf()).ok())
+        .map_or_else(|| realpath(&PathBuf::from_str(".").unwrap()), Ok)?;
+
+    // The last mount is used when multiple mounts have the same mount point, as it hides the earlier ones
+    get_mounts()?.into_iter()
+        .filter(|mount| real_path.starts_with(&mount.mount_point))
+        .max_by_key(|mount| mount.mount_point.len())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not find the volume containing the path"))
+}
+
+/// Find the mount with the given path as its mount point
+fn find_mount_for_root(path: &Path) -> io::Result<MountEntry> {
+    let real_path = realpath(&path.to_path_buf())?;
+    get_mounts()?.into_iter()
+        .rev()
+        .find(|mount| mount.mount_point == real_path)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "The path is not the root of a volume"))
+}
+
+cfg_if!{
+    if #[cfg(target_os = "linux")] {
+        fn get_mounts() -> io::Result<Vec<MountEntry>> {
+            let content = read_to_string("/proc/self/mounts")?;
+
+            let mut mounts = Vec::new();
+            for line in content.lines() {
+                // Each line has the format: `<device> <mount point> <fs type> <options> <dump> <pass>`
+                let mut fields = line.split(' ');
+                let (Some(device), Some(mount_point), Some(fs_type)) = (fields.next(), fields.next(), fields.next()) else { continue; };
+
+                // Mount points that cannot be represented by a path are skipped
+                let Ok(mount_point) = PathBuf::from_str(&unescape_mount_field(mount_point)) else { continue; };
+                mounts.push(MountEntry {
+                    device: unescape_mount_field(device),
+                    mount_point,
+                    fs_type: fs_type.to_string(),
+                });
+            }
+            Ok(mounts)
+        }
+
+        /// Spaces, tabs, newlines and backslashes are escaped as 3 octal digits, e.g. `\040` for a space
+        fn unescape_mount_field(field: &str) -> String {
+            let bytes = field.as_bytes();
+            let mut unescaped = Vec::with_capacity(bytes.len());
+            let mut idx = 0;
+            while idx < bytes.len() {
+                if bytes[idx] == b'\\' && idx + 4 <= bytes.len() {
+                    if let Ok(c) = u8::from_str_radix(&field[idx + 1..idx + 4], 8) {
+                        unescaped.push(c);
+                        idx += 4;
+                        continue;
+                    }
+                }
+                unescaped.push(bytes[idx]);
+                idx += 1;
+            }
+            String::from_utf8_lossy(&unescaped).into_owned()
+        }
+
+        /// Read an attribute of a block device from sysfs, e.g. `queue/rotational`
+        fn read_block_device_attribute(device: libc::dev_t, attribute: &str) -> Option<u64> {
+            // Same encoding as glibc's `major` and `minor`
+            let device = device as u64;
+            let major = ((device >> 8) & 0xFFF) | ((device >> 32) & !0xFFF);
+            let minor = (device & 0xFF) | ((device >> 12) & !0xFF);
+
+            // Partitions don't have all attributes, so fall back to the disk containing the partition
+            ["", "../"].iter().find_map(|prefix| {
+                read_to_string(&format!("/sys/dev/block/{major}:{minor}/{prefix}{attribute}")).ok()
+                    .and_then(|content| content.trim().parse().ok())
+            })
+        }
+
+        /// Read a small file, `Path` cannot be used, as these paths may contain characters that are not allowed in a path, like ':'
+        fn read_to_string(path: &str) -> io::Result<String> {
+            let path = std::ffi::CString::new(path).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Path contains a null character"))?;
+            let fd = unsafe { libc::open(path.as_ptr(), libc::O_RDONLY | libc::O_CLOEXEC) };
+            if fd < 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            // Files in procfs and sysfs report a size of 0, so read until the end of the file
+            let mut content = Vec::new();
+            let mut buf = [0u8; 4096];
+            loop {
+                let read = unsafe { libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+                if read > 0 {
+                    content.extend_from_slice(&buf[..read as usize]);
+                } else if read == 0 {
+                    break;
+                } else {
+                    let err = io::Error::last_os_error();
+                    if err.raw_os_error() != Some(libc::EINTR) {
+                        unsafe { libc::close(fd) };
+                        return Err(err);
+                    }
+                }
+            }
+            unsafe { libc::close(fd) };
+
+            String::from_utf8(content).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "File does not contain valid utf-8"))
+        }
+    } else if #[cfg(target_os = "macos")] {
+        fn get_mounts() -> io::Result<Vec<MountEntry>> {
+            // The returned buffer is owned by the OS and reused on the next call
+            let mut buf = std::ptr::null_mut::<libc::statfs>();
+            let count = unsafe { libc::getmntinfo(&mut buf, libc::MNT_NOWAIT) };
+            if count <= 0 {
+                return Err(io::Error::last_os_error());
+            }
+
+            let to_string = |chars: &[libc::c_char]| {
+                let bytes = unsafe { core::slice::from_raw_parts(chars.as_ptr() as *const u8, chars.len()) };
+                String::from_utf8_lossy(onca_common::utils::null_terminate_slice(bytes)).into_owned()
+            };
+
+            let mut mounts = Vec::new();
+            for statfs in unsafe { core::slice::from_raw_parts(buf, count as usize) } {
+                // Mount points that cannot be represented by a path are skipped
+                let Ok(mount_point) = PathBuf::from_str(&to_string(&statfs.f_mntonname)) else { continue; };
+                mounts.push(MountEntry {
+                    device: to_string(&statfs.f_mntfromname),
+                    mount_point,
+                    fs_type: to_string(&statfs.f_fstypename),
+                });
+            }
+            Ok(mounts)
+        }
+    } else {
+        fn get_mounts() -> io::Result<Vec<MountEntry>> {
+            Err(io::Error::new(io::ErrorKind::Unsupported, "Retrieving mounted volumes is not supported on this platform"))
+        }
+    }
+}
+
+fn stat(path: &PathBuf) -> io::Result<libc::stat> {
+    let mut stat = unsafe { mem::zeroed::<libc::stat>() };
+    if unsafe { libc::stat(path.as_ptr() as *const libc::c_char, &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(stat)
+}
+
+fn statvfs(path: &PathBuf) -> io::Result<libc::statvfs> {
+    let mut stat = unsafe { mem::zeroed::<libc::statvfs>() };
+    if unsafe { libc::statvfs(path.as_ptr() as *const libc::c_char, &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(stat)
+}
+
+/// Get the size of the blocks that the block counts are expressed in
+fn block_size(stat: &libc::statvfs) -> u64 {
+    if stat.f_frsize != 0 { stat.f_frsize as u64 } else { stat.f_bsize as u64 }
+}
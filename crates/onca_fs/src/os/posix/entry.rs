@@ -0,0 +1,316 @@
+use std::{
+    ffi::CStr,
+    mem,
+    num::{NonZeroU32, NonZeroU64},
+    ptr::null_mut,
+};
+use cfg_if::cfg_if;
+use onca_common::{
+    prelude::*,
+    io,
+    guid::Guid,
+};
+
+use crate::{MetaData, EntryType, EntryFlags, Permission, Path, PathBuf, VolumeFileId, FileLinkCount, EntryHandle, EntrySearchHandle, FileTime, FileTimes};
+use super::{file::readonly_mode, file_time_to_timespec, mode_to_flags, unix_to_file_time};
+
+//------------------------------
+
+pub(crate) struct NativeEntryHandle {
+    path: PathBuf
+}
+
+impl NativeEntryHandle {
+    pub(crate) fn new(path: &Path) -> io::Result<(Box<Self>, EntryType)> {
+        let entry = Self { path: path.to_path_buf() };
+        let entry_type = entry.entry_type()?;
+        Ok((Box::new(entry), entry_type))
+    }
+
+    fn entry_type(&self) -> io::Result<EntryType> {
+        let stat = stat(&self.path, false)?;
+        Ok(match stat.st_mode & libc::S_IFMT {
+            libc::S_IFDIR => EntryType::Directory,
+            // A link that does not point to a valid entry is treated as a link to a file
+            libc::S_IFLNK => match stat(&self.path, true) {
+                Ok(target) if target.st_mode & libc::S_IFMT == libc::S_IFDIR => EntryType::SymlinkDirectory,
+                _ => EntryType::SymlinkFile,
+            },
+            _ => EntryType::File,
+        })
+    }
+}
+
+impl crate::entry::EntryHandle for NativeEntryHandle {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn fully_qualified_path(&self) -> io::Result<PathBuf> {
+        realpath(&self.path)
+    }
+
+    fn metadata(&self) -> io::Result<MetaData> {
+        // Symlinks are followed, unless they don't point to a valid entry
+        let stat = stat(&self.path, true).or_else(|_| stat(&self.path, false))?;
+        let birth_time = get_birth_time(libc::AT_FDCWD, self.path.as_ptr() as *const libc::c_char, &stat);
+        Ok(get_metadata(&stat, birth_time, self.path.file_name()))
+    }
+
+    fn permissions(&self) -> io::Result<Permission> {
+        // Make sure that the entry exists, as `access` does not report it
+        stat(&self.path, false)?;
+
+        let mut permissions = Permission::None;
+        permissions.set(Permission::Read, access(&self.path, libc::R_OK));
+        permissions.set(Permission::Write, access(&self.path, libc::W_OK));
+        permissions.set(Permission::Append, access(&self.path, libc::W_OK));
+        permissions.set(Permission::Execute, access(&self.path, libc::X_OK));
+
+        // Deleting an entry modifies the directory containing it, not the entry itself
+        let parent = match self.path.parent() {
+            Some(parent) => parent.to_path_buf(),
+            None => PathBuf::from_str(".").unwrap(),
+        };
+        permissions.set(Permission::Delete, access(&parent, libc::W_OK | libc::X_OK));
+        Ok(permissions)
+    }
+}
+
+//------------------------------
+
+/// Get the absolute path of an entry, with all symlinks resolved
+pub(crate) fn realpath(path: &PathBuf) -> io::Result<PathBuf> {
+    let ptr = unsafe { libc::realpath(path.as_ptr() as *const libc::c_char, null_mut()) };
+    if ptr.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+
+    let path = PathBuf::from_utf8_lossy(unsafe { CStr::from_ptr(ptr) }.to_bytes());
+    unsafe { libc::free(ptr as *mut libc::c_void) };
+    path.map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid fully qualified path"))
+}
+
+fn stat(path: &PathBuf, follow: bool) -> io::Result<libc::stat> {
+    let mut stat = unsafe { mem::zeroed::<libc::stat>() };
+    let res = if follow {
+        unsafe { libc::stat(path.as_ptr() as *const libc::c_char, &mut stat) }
+    } else {
+        unsafe { libc::lstat(path.as_ptr() as *const libc::c_char, &mut stat) }
+    };
+    if res != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(stat)
+}
+
+fn access(path: &PathBuf, mode: libc::c_int) -> bool {
+    unsafe { libc::access(path.as_ptr() as *const libc::c_char, mode) == 0 }
+}
+
+/// Get the creation time of an entry, if the file system keeps track of it.
+///
+/// `path` is relative to `dirfd`, if `path` is empty, the creation time of `dirfd` itself is returned.
+pub(crate) fn get_birth_time(dirfd: libc::c_int, path: *const libc::c_char, stat: &libc::stat) -> Option<FileTime> {
+    cfg_if!{
+        if #[cfg(all(target_os = "linux", target_env = "gnu"))] {
+            _ = stat;
+            let flags = if unsafe { *path } == 0 { libc::AT_EMPTY_PATH } else { 0 };
+            let mut statx = unsafe { mem::zeroed::<libc::statx>() };
+            if unsafe { libc::statx(dirfd, path, flags, libc::STATX_BTIME, &mut statx) } != 0 || statx.stx_mask & libc::STATX_BTIME == 0 {
+                return None;
+            }
+            Some(unix_to_file_time(statx.stx_btime.tv_sec, statx.stx_btime.tv_nsec as i64))
+        } else if #[cfg(target_os = "macos")] {
+            _ = (dirfd, path);
+            Some(unix_to_file_time(stat.st_birthtime as i64, stat.st_birthtime_nsec as i64))
+        } else {
+            _ = (dirfd, path, stat);
+            None
+        }
+    }
+}
+
+pub(crate) fn get_metadata(stat: &libc::stat, birth_time: Option<FileTime>, file_name: Option<&str>) -> MetaData {
+    let mut flags = mode_to_flags(stat.st_mode);
+    let entry_type = match stat.st_mode & libc::S_IFMT {
+        libc::S_IFDIR => EntryType::Directory,
+        libc::S_IFLNK => EntryType::SymlinkFile,
+        _             => EntryType::File,
+    };
+
+    // Entries starting with a '.' are hidden by convention
+    if file_name.map_or(false, |name| name.starts_with('.') && name != "." && name != "..") {
+        flags |= EntryFlags::Hidden;
+    }
+    #[cfg(target_os = "macos")]
+    {
+        if stat.st_flags & libc::UF_HIDDEN != 0     { flags |= EntryFlags::Hidden; }
+        if stat.st_flags & libc::UF_COMPRESSED != 0 { flags |= EntryFlags::Compressed; }
+        if stat.st_flags & libc::UF_APPEND != 0     { flags |= EntryFlags::AppendOnly; }
+    }
+
+    // `st_blocks` is always in 512-byte units, independent of the block size of the file system
+    let file_size = stat.st_size as u64;
+    let alloc_size = stat.st_blocks as u64 * 512;
+    if entry_type == EntryType::File && alloc_size < file_size {
+        flags |= EntryFlags::Sparse;
+    }
+
+    // The compressed size is the actual size on disk for both compressed and sparse files
+    let compressed_size = if flags.intersects(EntryFlags::Compressed | EntryFlags::Sparse) {
+        NonZeroU64::new(alloc_size)
+    } else {
+        None
+    };
+
+    let last_write_time = unix_to_file_time(stat.st_mtime as i64, stat.st_mtime_nsec as i64);
+
+    // The inode number uniquely identifies the entry on its device
+    let mut file_id = [0; 16];
+    file_id[..8].copy_from_slice(&(stat.st_ino as u64).to_le_bytes());
+    let volume_file_id = VolumeFileId {
+        volume_id: stat.st_dev as u64,
+        file_id: unsafe { Guid::from_raw(file_id) },
+    };
+
+    MetaData {
+        entry_type,
+        flags,
+        // Fall back to the last write time when the creation time is unknown, as it's the closest time that is known
+        creation_time: birth_time.unwrap_or(last_write_time),
+        last_access_time: unix_to_file_time(stat.st_atime as i64, stat.st_atime_nsec as i64),
+        last_write_time,
+        last_change_time: unix_to_file_time(stat.st_ctime as i64, stat.st_ctime_nsec as i64),
+        file_size,
+        alloc_size,
+        compressed_size,
+        num_links: NonZeroU32::new(stat.st_nlink as u32).map_or(FileLinkCount::Unknown, |count| FileLinkCount::Known(count)),
+        // Unbuffered I/O alignment is not reported by the file system
+        min_align: 0,
+        volume_file_id,
+        storage_info: None,
+    }
+}
+
+pub(crate) fn set_times(path: &Path, times: FileTimes) -> io::Result<()> {
+    scoped_alloc!(AllocId::TlsTemp);
+
+    // The creation time cannot be set
+    let path = path.to_path_buf();
+    let timespecs = [file_time_to_timespec(times.accessed), file_time_to_timespec(times.modified)];
+    if unsafe { libc::utimensat(libc::AT_FDCWD, path.as_ptr() as *const libc::c_char, timespecs.as_ptr(), 0) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+pub(crate) fn set_readonly(path: &Path, readonly: bool) -> io::Result<()> {
+    scoped_alloc!(AllocId::TlsTemp);
+
+    let path = path.to_path_buf();
+    let mode = stat(&path, true)?.st_mode;
+    if unsafe { libc::chmod(path.as_ptr() as *const libc::c_char, readonly_mode(mode, readonly)) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+pub(crate) fn set_hidden(path: &Path, hidden: bool) -> io::Result<()> {
+    cfg_if!{
+        if #[cfg(target_os = "macos")] {
+            scoped_alloc!(AllocId::TlsTemp);
+
+            let path = path.to_path_buf();
+            let mut flags = stat(&path, true)?.st_flags;
+            if hidden {
+                flags |= libc::UF_HIDDEN;
+            } else {
+                flags &= !libc::UF_HIDDEN;
+            }
+            if unsafe { libc::chflags(path.as_ptr() as *const libc::c_char, flags) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        } else {
+            // Entries are hidden based on their name, so only check that the entry exists
+            _ = hidden;
+            stat(&path.to_path_buf(), false).map(|_| ())
+        }
+    }
+}
+
+//------------------------------
+
+pub(crate) struct NativeEntrySearchHandle(*mut libc::DIR);
+
+impl NativeEntrySearchHandle {
+    pub(crate) fn new(path: &Path) -> io::Result<(Box<NativeEntrySearchHandle>, PathBuf)> {
+        let path_buf = path.to_path_buf();
+        let dir = unsafe { libc::opendir(path_buf.as_ptr() as *const libc::c_char) };
+        if dir.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut handle = Box::new(NativeEntrySearchHandle(dir));
+        // An empty path ends the iteration, so an empty directory will not return any entries
+        let path = match handle.next_name() {
+            Some(name) => path_buf.join(name),
+            None => PathBuf::new(),
+        };
+        Ok((handle, path))
+    }
+
+    /// Get the name of the next entry, skipping "." and ".."
+    fn next_name(&mut self) -> Option<String> {
+        loop {
+            // Reaching the end of the directory and an error both return null
+            let dirent = unsafe { libc::readdir(self.0) };
+            if dirent.is_null() {
+                return None;
+            }
+
+            let name = unsafe { CStr::from_ptr((*dirent).d_name.as_ptr()) }.to_bytes();
+            if name == b"." || name == b".." {
+                continue;
+            }
+
+            // Skip any entry with a name that cannot be represented by a path
+            match std::str::from_utf8(name) {
+                Ok(name) if Path::new(name).is_ok() => return Some(name.to_string()),
+                _ => continue,
+            }
+        }
+    }
+}
+
+impl EntrySearchHandle for NativeEntrySearchHandle {
+    fn next(&mut self, mut path: PathBuf) -> Option<(Box<dyn EntryHandle>, EntryType, PathBuf)> {
+        loop {
+            let entry = NativeEntryHandle::new(&path);
+            let next_path = match self.next_name() {
+                Some(name) => {
+                    let mut next_path = path.clone();
+                    next_path.set_file_name(name);
+                    next_path
+                },
+                None => PathBuf::new(),
+            };
+
+            match entry {
+                Ok((entry, entry_type)) => return Some((entry, entry_type, next_path)),
+                // The entry was removed while iterating, so go to the next one
+                Err(_) if !next_path.as_str().is_empty() => path = next_path,
+                Err(_) => return None,
+            }
+        }
+    }
+}
+
+impl Drop for NativeEntrySearchHandle {
+    fn drop(&mut self) {
+        if unsafe { libc::closedir(self.0) } != 0 {
+            debug_assert!(false, "Failed to properly close search handle")
+        }
+    }
+}
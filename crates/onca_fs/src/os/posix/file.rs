@@ -0,0 +1,588 @@
+use std::{
+    mem,
+    num::NonZeroU64,
+    ptr::null_mut,
+};
+
+use cfg_if::cfg_if;
+use onca_common::{
+    prelude::*,
+    io,
+};
+
+use crate::{
+    Path, PathBuf, Permission, OpenMode, FileCreateFlags, FileAccessFlags, FileLockKind, FileTimes, MetaData,
+    FileAsyncReadResult, FileAsyncWriteResult, MemoryMappedFileHandle, MemoryMappedViewHandle, MappedViewAccess, CompletedAsyncResult,
+};
+
+use super::{entry, file_time_to_timespec};
+
+pub(crate) fn delete(path: &Path) -> io::Result<()> {
+    scoped_alloc!(AllocId::TlsTemp);
+
+    let path = path.to_path_buf();
+    if unsafe { libc::unlink(path.as_ptr() as *const libc::c_char) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+pub(crate) fn replace(source: &Path, dest: &Path) -> io::Result<()> {
+    scoped_alloc!(AllocId::TlsTemp);
+
+    let source_buf = source.to_path_buf();
+    let dest_buf = dest.to_path_buf();
+
+    // The renamed file keeps its own permissions, so give it the permissions of the file it replaces
+    let mut stat = unsafe { mem::zeroed::<libc::stat>() };
+    if unsafe { libc::stat(dest_buf.as_ptr() as *const libc::c_char, &mut stat) } == 0 {
+        if unsafe { libc::chmod(source_buf.as_ptr() as *const libc::c_char, stat.st_mode & 0o7777) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    } else {
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::NotFound {
+            return Err(err);
+        }
+    }
+
+    // `rename` atomically replaces the destination when both are on the same filesystem
+    if unsafe { libc::rename(source_buf.as_ptr() as *const libc::c_char, dest_buf.as_ptr() as *const libc::c_char) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Make a `replace` durable, this is called after `replace` has succeeded, so the destination already contains the new file when this fails
+pub(crate) fn sync_replaced(dest: &Path) -> io::Result<()> {
+    scoped_alloc!(AllocId::TlsTemp);
+
+    // The rename itself is only durable once the directory containing it has been synced
+    let Some(parent) = dest.parent() else { return Ok(()); };
+    let parent = if parent.as_str().is_empty() { Path::new(".").unwrap() } else { parent };
+    let parent = parent.to_path_buf();
+
+    let fd = unsafe { libc::open(parent.as_ptr() as *const libc::c_char, libc::O_RDONLY | libc::O_DIRECTORY | libc::O_CLOEXEC) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let res = unsafe { libc::fsync(fd) };
+    unsafe { libc::close(fd) };
+    if res != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Error returned when a rename crosses volumes
+pub(crate) const CROSS_VOLUME_ERROR: i32 = libc::EXDEV;
+
+/// `rename` always replaces the destination, so the caller needs to check if it exists when it should not be replaced
+pub(crate) fn rename(source: &Path, dest: &Path, _replace: bool) -> io::Result<()> {
+    scoped_alloc!(AllocId::TlsTemp);
+
+    let source = source.to_path_buf();
+    let dest = dest.to_path_buf();
+    if unsafe { libc::rename(source.as_ptr() as *const libc::c_char, dest.as_ptr() as *const libc::c_char) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Lock the entire file using an advisory lock.
+/// 
+/// Returns `false` if the file is already locked and `blocking` is `false`.
+pub(crate) fn lock(fd: libc::c_int, kind: FileLockKind, blocking: bool) -> io::Result<bool> {
+    let mut op = match kind {
+        FileLockKind::Shared    => libc::LOCK_SH,
+        FileLockKind::Exclusive => libc::LOCK_EX,
+    };
+    if !blocking {
+        op |= libc::LOCK_NB;
+    }
+
+    loop {
+        if unsafe { libc::flock(fd, op) } == 0 {
+            return Ok(true);
+        }
+
+        let err = io::Error::last_os_error();
+        match err.raw_os_error() {
+            Some(libc::EWOULDBLOCK) => return Ok(false),
+            // Interrupted by a signal while waiting on the lock
+            Some(libc::EINTR) => continue,
+            _ => return Err(err),
+        }
+    }
+}
+
+/// Release an advisory lock on a file.
+pub(crate) fn unlock(fd: libc::c_int) -> io::Result<()> {
+    if unsafe { libc::flock(fd, libc::LOCK_UN) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+//--------------------------------------------------------------
+
+pub struct FileHandle {
+    fd:          libc::c_int,
+    path:        PathBuf,
+    permissions: Permission,
+}
+
+impl crate::file::FileHandle for FileHandle {
+    fn flush_data(&mut self) -> io::Result<()> {
+        cfg_if!{
+            // macOS has no `fdatasync`
+            if #[cfg(target_os = "linux")] {
+                let res = unsafe { libc::fdatasync(self.fd) };
+            } else {
+                let res = unsafe { libc::fsync(self.fd) };
+            }
+        }
+        if res != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn flush_all(&mut self) -> io::Result<()> {
+        self.flush()
+    }
+
+    // All I/O is completed synchronously, so there is nothing to cancel
+    fn cancel_all_thread_async_io(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn cancel_all_async_io(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn set_len(&mut self, len: u64) -> io::Result<()> {
+        if unsafe { libc::ftruncate(self.fd, len as libc::off_t) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn set_times(&mut self, times: FileTimes) -> io::Result<()> {
+        // The creation time cannot be set
+        let timespecs = [file_time_to_timespec(times.accessed), file_time_to_timespec(times.modified)];
+        if unsafe { libc::futimens(self.fd, timespecs.as_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn set_permissions(&mut self, permissions: Permission) -> io::Result<()> {
+        // Only the permissions of the owner are changed
+        let mut mode = self.fstat()?.st_mode & !(libc::S_IFMT | libc::S_IRWXU);
+        if permissions.contains(Permission::Read)                        { mode |= libc::S_IRUSR; }
+        if permissions.intersects(Permission::Write | Permission::Append) { mode |= libc::S_IWUSR; }
+        if permissions.contains(Permission::Execute)                     { mode |= libc::S_IXUSR; }
+        self.fchmod(mode)
+    }
+
+    fn set_readonly(&mut self, readonly: bool) -> io::Result<()> {
+        let mode = self.fstat()?.st_mode;
+        self.fchmod(readonly_mode(mode, readonly))
+    }
+
+    fn set_hidden(&mut self, hidden: bool) -> io::Result<()> {
+        set_hidden(self.fd, hidden)
+    }
+
+    fn set_content_indexed(&mut self, _content_indexed: bool) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn get_metadata(&mut self) -> io::Result<MetaData> {
+        let stat = self.fstat()?;
+        let birth_time = entry::get_birth_time(self.fd, b"\0".as_ptr() as *const libc::c_char, &stat);
+        Ok(entry::get_metadata(&stat, birth_time, self.path.file_name()))
+    }
+
+    fn lock(&mut self, kind: FileLockKind, blocking: bool) -> io::Result<bool> {
+        lock(self.fd, kind, blocking)
+    }
+
+    fn unlock(&mut self) -> io::Result<()> {
+        unlock(self.fd)
+    }
+
+    fn read_ahead(&mut self, offset: u64, len: u64) -> io::Result<()> {
+        read_ahead(self.fd, offset, len)
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            // A single read may return less bytes than requested, which will be handled by the caller
+            let read = unsafe { libc::read(self.fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len().min(isize::MAX as usize)) };
+            if read >= 0 {
+                return Ok(read as usize);
+            }
+
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() != Some(libc::EINTR) {
+                return Err(err);
+            }
+        }
+    }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        loop {
+            // A single write may write less bytes than requested, which will be handled by the caller
+            let written = unsafe { libc::write(self.fd, buf.as_ptr() as *const libc::c_void, buf.len().min(isize::MAX as usize)) };
+            if written >= 0 {
+                return Ok(written as usize);
+            }
+
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() != Some(libc::EINTR) {
+                return Err(err);
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if unsafe { libc::fsync(self.fd) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let (offset, whence) = match pos {
+            io::SeekFrom::Start(pos)   => (pos as i64, libc::SEEK_SET),
+            io::SeekFrom::End(pos)     => (pos, libc::SEEK_END),
+            io::SeekFrom::Current(pos) => (pos, libc::SEEK_CUR),
+        };
+        let pos = unsafe { libc::lseek(self.fd, offset as libc::off_t, whence) };
+        if pos < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(pos as u64)
+    }
+
+    // Real async I/O is done using an `AsyncFile`, so this completes the I/O immediately, at the current cursor, without moving the cursor.
+    fn read_async(&mut self, bytes_to_read: u64) -> io::Result<FileAsyncReadResult> {
+        let offset = self.seek(io::SeekFrom::Current(0))?;
+
+        let mut buffer = vec![0; bytes_to_read as usize];
+        let mut total_read = 0;
+        while total_read < buffer.len() {
+            let to_read = (buffer.len() - total_read).min(isize::MAX as usize);
+            let read = unsafe { libc::pread(self.fd, buffer[total_read..].as_mut_ptr() as *mut libc::c_void, to_read, (offset + total_read as u64) as libc::off_t) };
+            match read {
+                0 => break,
+                read if read > 0 => total_read += read as usize,
+                _ => {
+                    let err = io::Error::last_os_error();
+                    if err.raw_os_error() != Some(libc::EINTR) {
+                        return Ok(Box::new(CompletedAsyncResult(Some(Err(err)))));
+                    }
+                },
+            }
+        }
+        buffer.truncate(total_read);
+        Ok(Box::new(CompletedAsyncResult(Some(Ok(buffer)))))
+    }
+
+    fn write_async(&mut self, buf: Vec<u8>) -> io::Result<FileAsyncWriteResult> {
+        let offset = self.seek(io::SeekFrom::Current(0))?;
+
+        let mut total_written = 0;
+        while total_written < buf.len() {
+            let to_write = (buf.len() - total_written).min(isize::MAX as usize);
+            let written = unsafe { libc::pwrite(self.fd, buf[total_written..].as_ptr() as *const libc::c_void, to_write, (offset + total_written as u64) as libc::off_t) };
+            if written >= 0 {
+                total_written += written as usize;
+                continue;
+            }
+
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() != Some(libc::EINTR) {
+                return Ok(Box::new(CompletedAsyncResult(Some(Err(err)))));
+            }
+        }
+        Ok(Box::new(CompletedAsyncResult(Some(Ok(total_written as u64)))))
+    }
+
+    fn map_memory(&mut self, mapped_size: Option<u64>) -> io::Result<Box<dyn MemoryMappedFileHandle>> {
+        let is_write = self.permissions.intersects(Permission::Write | Permission::Append);
+        let is_exec = self.permissions.contains(Permission::Execute);
+
+        let file_size = self.fstat()?.st_size as u64;
+        let size = match mapped_size {
+            Some(mapped_size) if is_write => {
+                // Unlike on Windows, the file is not resized when it's mapped, so resize it here
+                if mapped_size > file_size {
+                    crate::file::FileHandle::set_len(self, mapped_size)?;
+                }
+                mapped_size
+            },
+            _ => file_size,
+        };
+
+        // The mapping should stay valid independently of the lifetime of this handle
+        let fd = unsafe { libc::dup(self.fd) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as u64;
+        Ok(Box::new(MemoryMappedFile { fd, size, is_exec, page_size }))
+    }
+}
+
+impl FileHandle {
+    pub(crate) fn create(
+        path: &Path,
+        open_mode: OpenMode,
+        access_perms: Permission,
+        _shared_access_perms: Permission,
+        create_flags: FileCreateFlags,
+        access_flags: FileAccessFlags,
+        open_link: bool,
+        temporary: bool
+    ) -> io::Result<(Box<dyn crate::FileHandle>, PathBuf)> {
+        let path_buf = path.to_path_buf();
+
+        let is_read = access_perms.intersects(Permission::Read | Permission::Execute);
+        let is_write = access_perms.intersects(Permission::Write | Permission::Append);
+        let mut flags = match (is_read, is_write) {
+            (true, true) => libc::O_RDWR,
+            (false, true) => libc::O_WRONLY,
+            _ => libc::O_RDONLY,
+        };
+        flags |= libc::O_CLOEXEC;
+        flags |= match open_mode {
+            OpenMode::OpenOrCreate      => libc::O_CREAT,
+            OpenMode::OpenExisting      => 0,
+            OpenMode::CreateNonExisting => libc::O_CREAT | libc::O_EXCL,
+            OpenMode::CreateAlways      => libc::O_CREAT | libc::O_TRUNC,
+            OpenMode::TruncateExisting  => libc::O_TRUNC,
+        };
+        if access_perms.contains(Permission::Append) && !access_perms.contains(Permission::Write) {
+            flags |= libc::O_APPEND;
+        }
+        if access_flags.contains(FileAccessFlags::WriteThrough) { flags |= libc::O_DSYNC; }
+        #[cfg(target_os = "linux")]
+        if access_flags.contains(FileAccessFlags::NoBuffering)  { flags |= libc::O_DIRECT; }
+        // A symlink itself cannot be opened for I/O, so fail instead of silently opening the file it points to
+        if open_link { flags |= libc::O_NOFOLLOW; }
+
+        // Temporary files are often created in a shared directory, so they are only accessible by the current user
+        let mode: libc::mode_t = match (temporary, create_flags.contains(FileCreateFlags::ReadOnly)) {
+            (true, true)   => 0o400,
+            (true, false)  => 0o600,
+            (false, true)  => 0o444,
+            (false, false) => 0o666,
+        };
+
+        let fd = unsafe { libc::open(path_buf.as_ptr() as *const libc::c_char, flags, mode as libc::c_uint) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        cfg_if!{
+            if #[cfg(target_os = "linux")] {
+                if !access_flags.contains(FileAccessFlags::NoBuffering) {
+                    if access_flags.contains(FileAccessFlags::RandomAccess) {
+                        unsafe { libc::posix_fadvise(fd, 0, 0, libc::POSIX_FADV_RANDOM) };
+                    } else if access_flags.contains(FileAccessFlags::SequentialAccess) {
+                        unsafe { libc::posix_fadvise(fd, 0, 0, libc::POSIX_FADV_SEQUENTIAL) };
+                    }
+                }
+            } else if #[cfg(target_os = "macos")] {
+                if access_flags.contains(FileAccessFlags::NoBuffering) {
+                    unsafe { libc::fcntl(fd, libc::F_NOCACHE, 1) };
+                } else if access_flags.contains(FileAccessFlags::RandomAccess) {
+                    unsafe { libc::fcntl(fd, libc::F_RDAHEAD, 0) };
+                }
+            }
+        }
+
+        if create_flags.contains(FileCreateFlags::Hidden) {
+            if let Err(err) = set_hidden(fd, true) {
+                unsafe { libc::close(fd) };
+                return Err(err);
+            }
+        }
+
+        // The file will keep existing until the descriptor has been closed
+        if create_flags.contains(FileCreateFlags::DeleteOnClose) {
+            unsafe { libc::unlink(path_buf.as_ptr() as *const libc::c_char) };
+        }
+
+        Ok((Box::new(FileHandle { fd, path: path_buf.clone(), permissions: access_perms }), path_buf))
+    }
+
+    fn fstat(&self) -> io::Result<libc::stat> {
+        fstat(self.fd)
+    }
+
+    fn fchmod(&mut self, mode: libc::mode_t) -> io::Result<()> {
+        if unsafe { libc::fchmod(self.fd, mode) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for FileHandle {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+fn fstat(fd: libc::c_int) -> io::Result<libc::stat> {
+    let mut stat = unsafe { mem::zeroed::<libc::stat>() };
+    if unsafe { libc::fstat(fd, &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(stat)
+}
+
+fn set_hidden(fd: libc::c_int, hidden: bool) -> io::Result<()> {
+    cfg_if!{
+        if #[cfg(target_os = "macos")] {
+            let mut flags = fstat(fd)?.st_flags;
+            if hidden {
+                flags |= libc::UF_HIDDEN;
+            } else {
+                flags &= !libc::UF_HIDDEN;
+            }
+            if unsafe { libc::fchflags(fd, flags) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        } else {
+            // Files are hidden based on their name, which cannot be changed through a descriptor
+            _ = (fd, hidden);
+            Ok(())
+        }
+    }
+}
+
+fn read_ahead(fd: libc::c_int, offset: u64, len: u64) -> io::Result<()> {
+    cfg_if!{
+        if #[cfg(target_os = "linux")] {
+            // `posix_fadvise` returns the error, instead of setting `errno`
+            let res = unsafe { libc::posix_fadvise(fd, offset as libc::off_t, len.min(libc::off_t::MAX as u64) as libc::off_t, libc::POSIX_FADV_WILLNEED) };
+            if res != 0 {
+                return Err(io::Error::from_raw_os_error(res));
+            }
+            Ok(())
+        } else if #[cfg(target_os = "macos")] {
+            let advisory = libc::radvisory { ra_offset: offset as libc::off_t, ra_count: len.min(libc::c_int::MAX as u64) as libc::c_int };
+            if unsafe { libc::fcntl(fd, libc::F_RDADVISE, &advisory) } == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        } else {
+            _ = (fd, offset, len);
+            Ok(())
+        }
+    }
+}
+
+/// Get the mode with the read-only state changed.
+/// 
+/// Making an entry read-only removes all write permissions, while making it writable only gives write permission to the owner.
+pub(crate) fn readonly_mode(mode: libc::mode_t, readonly: bool) -> libc::mode_t {
+    let mode = mode & !libc::S_IFMT;
+    if readonly {
+        mode & !(libc::S_IWUSR | libc::S_IWGRP | libc::S_IWOTH)
+    } else {
+        mode | libc::S_IWUSR
+    }
+}
+
+//--------------------------------------------------------------
+
+pub struct MemoryMappedFile {
+    fd:        libc::c_int,
+    size:      u64,
+    is_exec:   bool,
+    page_size: u64,
+}
+
+impl MemoryMappedFileHandle for MemoryMappedFile {
+    fn create_view(&self, access: MappedViewAccess, offset: u64, size: Option<NonZeroU64>) -> io::Result<Box<dyn MemoryMappedViewHandle>> {
+        let mut prot = match access {
+            MappedViewAccess::Read      => libc::PROT_READ,
+            MappedViewAccess::ReadWrite => libc::PROT_READ | libc::PROT_WRITE,
+        };
+        if self.is_exec {
+            prot |= libc::PROT_EXEC;
+        }
+
+        let size = match size {
+            Some(size) => size.get(),
+            None => self.size.saturating_sub(offset),
+        };
+        if size == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Cannot create an empty view"));
+        }
+
+        // The offset of the mapping needs to be a multiple of the page size
+        let diff = offset % self.page_size;
+        let map_offset = offset - diff;
+        let map_len = (size + diff) as usize;
+
+        let addr = unsafe { libc::mmap(null_mut(), map_len, prot, libc::MAP_SHARED, self.fd, map_offset as libc::off_t) };
+        if addr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Box::new(MemoryMappedView {
+            addr,
+            map_len,
+            offset: diff as usize,
+            size: size as usize,
+        }))
+    }
+}
+
+impl Drop for MemoryMappedFile {
+    fn drop(&mut self) {
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+//--------------------------------------------------------------
+
+pub struct MemoryMappedView {
+    addr:    *mut libc::c_void,
+    map_len: usize,
+    /// Offset of the view within the mapping, as the mapping needs to start on a page boundary
+    offset:  usize,
+    size:    usize,
+}
+
+impl MemoryMappedViewHandle for MemoryMappedView {
+    fn get_slice(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts((self.addr as *const u8).add(self.offset), self.size) }
+    }
+
+    fn get_mut_slice(&self) -> &mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut((self.addr as *mut u8).add(self.offset), self.size) }
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        if unsafe { libc::msync(self.addr, self.map_len, libc::MS_SYNC) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MemoryMappedView {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.addr, self.map_len) };
+    }
+}
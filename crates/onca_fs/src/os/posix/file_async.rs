@@ -0,0 +1,465 @@
+use std::{
+    collections::HashSet,
+    mem,
+    ptr,
+    sync::Arc,
+    task::Poll,
+    time::Instant,
+};
+
+use io_uring::{IoUring, opcode, types, squeue};
+use onca_common::{io, sync::Mutex};
+
+use crate::{Path, PathBuf, Permission, OpenMode, FileCreateFlags, FileAccessFlags, FileAsyncReadResult, FileAsyncWriteResult, get_remaining_timeout};
+
+/// Async IO completion state
+#[derive(Clone, Copy)]
+enum AsyncIOCompletionState {
+    /// Async operation is still in flight
+    InFlight,
+    /// Async operation has completed successfully
+    Completed(u64),
+    /// Async operation has completed with an error
+    Unsuccessful(i32),
+    /// Async opeartion has completed successfully, but the buffer is already returned
+    Exhausted,
+}
+
+/// Async operation, which is kept alive by the ring until it has completed.
+struct AsyncOp {
+    // The kernel writes directly into the buffer's memory, so the buffer needs to live as long as the operation
+    buffer: Mutex<Vec<u8>>,
+    state:  Mutex<AsyncIOCompletionState>,
+}
+
+impl AsyncOp {
+    fn new(buffer: Vec<u8>) -> Arc<Self> {
+        Arc::new(Self {
+            buffer: Mutex::new(buffer),
+            state: Mutex::new(AsyncIOCompletionState::InFlight),
+        })
+    }
+
+    fn user_data(&self) -> u64 {
+        self as *const _ as u64
+    }
+}
+
+//------------------------------
+
+struct RingState {
+    ring:      IoUring,
+    /// User data of all operations that are currently in flight
+    in_flight: HashSet<u64>,
+}
+
+/// io_uring instance, owned by a single file.
+///
+/// # Note
+///
+/// The ring is locked while waiting for completions, so issuing new operations will block until the wait has finished.
+struct Ring {
+    state: Mutex<RingState>,
+}
+
+impl Ring {
+    /// Number of submission queue entries
+    const NUM_ENTRIES: u32 = 64;
+    /// User data used for cancellation requests, this can never be a valid operation
+    const CANCEL_USER_DATA: u64 = 0;
+
+    fn new() -> io::Result<Self> {
+        Ok(Self {
+            state: Mutex::new(RingState {
+                ring: IoUring::new(Self::NUM_ENTRIES)?,
+                in_flight: HashSet::new(),
+            })
+        })
+    }
+
+    /// Issue an operation
+    fn issue(&self, op: &Arc<AsyncOp>, entry: squeue::Entry) -> io::Result<()> {
+        let mut state = self.state.lock();
+
+        // The ring holds a reference to the operation, which will be released once it completes
+        let user_data = Arc::into_raw(op.clone()) as u64;
+        if let Err(err) = Self::push(&mut state, entry.user_data(user_data)) {
+            unsafe { drop(Arc::from_raw(user_data as *const AsyncOp)) };
+            return Err(err);
+        }
+        state.in_flight.insert(user_data);
+        Ok(())
+    }
+
+    /// Cancel an operation, if it is still in flight
+    fn cancel(&self, user_data: u64) -> io::Result<()> {
+        let mut state = self.state.lock();
+        if !state.in_flight.contains(&user_data) {
+            return Ok(());
+        }
+        Self::push(&mut state, opcode::AsyncCancel::new(user_data).build().user_data(Self::CANCEL_USER_DATA))
+    }
+
+    /// Cancel all operations that are in flight
+    fn cancel_all(&self) -> io::Result<()> {
+        let mut state = self.state.lock();
+        let in_flight = state.in_flight.iter().copied().collect::<Vec<_>>();
+        for user_data in in_flight {
+            Self::push(&mut state, opcode::AsyncCancel::new(user_data).build().user_data(Self::CANCEL_USER_DATA))?;
+        }
+        Ok(())
+    }
+
+    fn push(state: &mut RingState, entry: squeue::Entry) -> io::Result<()> {
+        // SAFETY: All memory referenced by the entry is owned by an operation, which is kept alive until it has completed
+        while unsafe { state.ring.submission().push(&entry) }.is_err() {
+            // The submission queue is full, so submit the queued entries to make space
+            state.ring.submit()?;
+        }
+        state.ring.submit()?;
+        Ok(())
+    }
+
+    /// Process all completions currently in the ring, waiting up to `timeout` ms for at least 1 completion
+    fn process_completions(&self, timeout: u32) {
+        let mut state = self.state.lock();
+
+        if timeout > 0 && state.ring.completion().is_empty() {
+            let timespec = types::Timespec::new()
+                .sec((timeout / 1000) as u64)
+                .nsec((timeout % 1000) * 1_000_000);
+            let args = types::SubmitArgs::new().timespec(&timespec);
+            // Reaching the timeout returns an error, which just means that there is nothing to process
+            _ = state.ring.submitter().submit_with_args(1, &args);
+        }
+
+        let completions = state.ring.completion().map(|entry| (entry.user_data(), entry.result())).collect::<Vec<_>>();
+        for (user_data, result) in completions {
+            if user_data == Self::CANCEL_USER_DATA {
+                continue;
+            }
+
+            state.in_flight.remove(&user_data);
+            // SAFETY: Every operation in the ring was created by `issue`, which leaked a reference to it
+            let op = unsafe { Arc::from_raw(user_data as *const AsyncOp) };
+            *op.state.lock() = if result >= 0 {
+                AsyncIOCompletionState::Completed(result as u64)
+            } else {
+                AsyncIOCompletionState::Unsuccessful(-result)
+            };
+        }
+    }
+
+    fn has_in_flight(&self) -> bool {
+        !self.state.lock().in_flight.is_empty()
+    }
+
+    /// Poll the state of an operation, processing any available completions
+    fn poll(&self, op: &AsyncOp) -> AsyncIOCompletionState {
+        let state = *op.state.lock();
+        if let AsyncIOCompletionState::InFlight = state {
+            self.process_completions(0);
+            *op.state.lock()
+        } else {
+            state
+        }
+    }
+
+    /// Wait for an operation to complete, until the timeout has been reached
+    fn wait(&self, op: &AsyncOp, timeout: u32) -> AsyncIOCompletionState {
+        let start = Instant::now();
+        loop {
+            let state = *op.state.lock();
+            let remaining = get_remaining_timeout(start, timeout);
+            match state {
+                AsyncIOCompletionState::InFlight if remaining > 0 => self.process_completions(remaining),
+                AsyncIOCompletionState::InFlight => return self.poll(op),
+                state => return state,
+            }
+        }
+    }
+}
+
+//------------------------------
+
+pub struct AsyncFileHandle {
+    fd:              libc::c_int,
+    ring:            Arc<Ring>,
+    /// Alignment of the buffer, offset and length of a read, only set when the file uses direct I/O
+    direct_io_align: Option<u64>,
+}
+
+impl AsyncFileHandle {
+    pub(crate) fn create(
+        path: &Path,
+        open_mode: OpenMode,
+        access_perms: Permission,
+        _shared_access_perms: Permission,
+        create_flags: FileCreateFlags,
+        access_flags: FileAccessFlags,
+    ) -> io::Result<(Box<dyn crate::AsyncFileHandle>, PathBuf)> {
+        let path_buf = path.to_path_buf();
+
+        // Every access is done at an explicit offset, so `O_APPEND` cannot be used, as it would ignore the offset
+        let is_read = access_perms.contains(Permission::Read);
+        let is_write = access_perms.intersects(Permission::Write | Permission::Append);
+        let mut flags = match (is_read, is_write) {
+            (true, true) => libc::O_RDWR,
+            (false, true) => libc::O_WRONLY,
+            _ => libc::O_RDONLY,
+        };
+        flags |= libc::O_CLOEXEC;
+        flags |= match open_mode {
+            OpenMode::OpenOrCreate      => libc::O_CREAT,
+            OpenMode::OpenExisting      => 0,
+            OpenMode::CreateNonExisting => libc::O_CREAT | libc::O_EXCL,
+            OpenMode::CreateAlways      => libc::O_CREAT | libc::O_TRUNC,
+            OpenMode::TruncateExisting  => libc::O_TRUNC,
+        };
+        if access_flags.contains(FileAccessFlags::NoBuffering)  { flags |= libc::O_DIRECT; }
+        if access_flags.contains(FileAccessFlags::WriteThrough) { flags |= libc::O_DSYNC; }
+
+        let mode: libc::mode_t = if create_flags.contains(FileCreateFlags::ReadOnly) { 0o444 } else { 0o666 };
+
+        let fd = unsafe { libc::open(path_buf.as_ptr() as *const libc::c_char, flags, mode as libc::c_uint) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        if !access_flags.contains(FileAccessFlags::NoBuffering) {
+            if access_flags.contains(FileAccessFlags::RandomAccess) {
+                unsafe { libc::posix_fadvise(fd, 0, 0, libc::POSIX_FADV_RANDOM) };
+            } else if access_flags.contains(FileAccessFlags::SequentialAccess) {
+                unsafe { libc::posix_fadvise(fd, 0, 0, libc::POSIX_FADV_SEQUENTIAL) };
+            }
+        }
+
+        // The file will keep existing until the descriptor has been closed
+        if create_flags.contains(FileCreateFlags::DeleteOnClose) {
+            unsafe { libc::unlink(path_buf.as_ptr() as *const libc::c_char) };
+        }
+
+        // The logical block size is not reported, so use the preferred I/O block size, which is always a multiple of it
+        let direct_io_align = if access_flags.contains(FileAccessFlags::NoBuffering) {
+            let mut stat = unsafe { mem::zeroed::<libc::stat>() };
+            if unsafe { libc::fstat(fd, &mut stat) } != 0 {
+                let err = io::Error::last_os_error();
+                unsafe { libc::close(fd) };
+                return Err(err);
+            }
+            Some((stat.st_blksize as u64).max(512).next_power_of_two())
+        } else {
+            None
+        };
+
+        let ring = match Ring::new() {
+            Ok(ring) => ring,
+            Err(err) => {
+                unsafe { libc::close(fd) };
+                return Err(err);
+            }
+        };
+
+        Ok((Box::new(AsyncFileHandle { fd, ring: Arc::new(ring), direct_io_align }), path_buf))
+    }
+}
+
+impl crate::AsyncFileHandle for AsyncFileHandle {
+    fn read_at(&self, offset: u64, bytes_to_read: u64) -> io::Result<FileAsyncReadResult> {
+        if bytes_to_read > u32::MAX as u64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Cannot read more than u32::MAX bytes in a single async operation"));
+        }
+
+        // Direct I/O requires the offset and length to be aligned, so read the aligned range containing the requested bytes
+        let (read_offset, read_len, align) = match self.direct_io_align {
+            Some(align) => {
+                let start = offset & !(align - 1);
+                let end = (offset + bytes_to_read + align - 1) & !(align - 1);
+                (start, end - start, align as usize)
+            },
+            None => (offset, bytes_to_read, 1),
+        };
+        if read_len > u32::MAX as u64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Aligned read for direct I/O exceeds u32::MAX bytes"));
+        }
+
+        // The buffer is only initialized by the read, so its length is only set once the read has completed.
+        // Over-allocate so the start of the read can be aligned for direct I/O.
+        let mut buffer = Vec::with_capacity(read_len as usize + align - 1);
+        let read_start = buffer.as_ptr().align_offset(align);
+        // The heap allocation does not move when the buffer is moved into the operation
+        let buffer_ptr = unsafe { buffer.as_mut_ptr().add(read_start) };
+        let op = AsyncOp::new(buffer);
+
+        let entry = opcode::Read::new(types::Fd(self.fd), buffer_ptr, read_len as u32)
+            .offset(read_offset)
+            .build();
+        self.ring.issue(&op, entry)?;
+        Ok(Box::new(AsyncReadResult {
+            op,
+            ring: self.ring.clone(),
+            read_start,
+            data_start: read_start + (offset - read_offset) as usize,
+            bytes_to_read: bytes_to_read as usize,
+        }))
+    }
+
+    fn write_at(&self, offset: u64, buf: Vec<u8>) -> io::Result<FileAsyncWriteResult> {
+        if buf.len() > u32::MAX as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Cannot write more than u32::MAX bytes in a single async operation"));
+        }
+
+        let buffer_len = buf.len() as u32;
+        // Direct I/O requires the buffer, offset and length to be aligned. Only the buffer can be fixed up, as the offset and length would need the surrounding data to be read first
+        let (buf, data_start) = match self.direct_io_align {
+            Some(align) => {
+                if offset % align != 0 || buf.len() as u64 % align != 0 {
+                    return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Writes to a file opened with `NoBuffering` need an offset and length aligned to {align} bytes")));
+                }
+                align_write_buffer(buf, align as usize)
+            },
+            None => (buf, 0),
+        };
+        // The heap allocation does not move when the buffer is moved into the operation
+        let buffer_ptr = unsafe { buf.as_ptr().add(data_start) };
+        let op = AsyncOp::new(buf);
+
+        let entry = opcode::Write::new(types::Fd(self.fd), buffer_ptr, buffer_len)
+            .offset(offset)
+            .build();
+        self.ring.issue(&op, entry)?;
+        Ok(Box::new(AsyncWriteResult { op, ring: self.ring.clone() }))
+    }
+
+    fn cancel_all_async_io(&self) -> io::Result<()> {
+        self.ring.cancel_all()
+    }
+
+    fn len(&self) -> io::Result<u64> {
+        let mut stat = unsafe { mem::zeroed::<libc::stat>() };
+        if unsafe { libc::fstat(self.fd, &mut stat) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(stat.st_size as u64)
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        if unsafe { libc::fsync(self.fd) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+/// Get a buffer with the data starting at an aligned address, and the index of the data in it, the data is copied to an aligned bounce buffer when it isn't aligned yet
+fn align_write_buffer(buf: Vec<u8>, align: usize) -> (Vec<u8>, usize) {
+    if buf.as_ptr().align_offset(align) == 0 {
+        return (buf, 0);
+    }
+
+    // Over-allocate so the start of the data can be aligned, the buffer is never reallocated, as it has enough capacity
+    let mut bounce = Vec::with_capacity(buf.len() + align - 1);
+    let data_start = bounce.as_ptr().align_offset(align);
+    bounce.resize(data_start, 0);
+    bounce.extend_from_slice(&buf);
+    (bounce, data_start)
+}
+
+impl Drop for AsyncFileHandle {
+    fn drop(&mut self) {
+        _ = self.ring.cancel_all();
+        // All operations need to be completed, otherwise their memory would be leaked
+        while self.ring.has_in_flight() {
+            self.ring.process_completions(1);
+        }
+
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+//--------------------------------------------------------------
+
+pub(crate) struct AsyncReadResult {
+    op:            Arc<AsyncOp>,
+    ring:          Arc<Ring>,
+    /// Index in the buffer the read started writing to
+    read_start:    usize,
+    /// Index in the buffer of the first requested byte, this is after `read_start` when an aligned read was done
+    data_start:    usize,
+    bytes_to_read: usize,
+}
+
+impl AsyncReadResult {
+    fn handle_state(&mut self, state: AsyncIOCompletionState) -> Poll<io::Result<Vec<u8>>> {
+        match state {
+            AsyncIOCompletionState::InFlight              => Poll::Pending,
+            AsyncIOCompletionState::Completed(bytes_read) => Poll::Ready(Ok(self.take_buffer_and_exhaust(bytes_read))),
+            AsyncIOCompletionState::Unsuccessful(err)     => Poll::Ready(Err(io::Error::from_raw_os_error(err))),
+            AsyncIOCompletionState::Exhausted             => Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, "Data was already taken from this result")))
+        }
+    }
+
+    fn take_buffer_and_exhaust(&mut self, bytes_read: u64) -> Vec<u8> {
+        *self.op.state.lock() = AsyncIOCompletionState::Exhausted;
+        let mut buffer = mem::take(&mut *self.op.buffer.lock());
+
+        // Only the bytes written by the read are initialized, which may not cover all requested bytes when the end of the file was reached
+        let initialized_end = self.read_start + bytes_read as usize;
+        let len = initialized_end.saturating_sub(self.data_start).min(self.bytes_to_read);
+        unsafe {
+            ptr::copy(buffer.as_ptr().add(self.data_start), buffer.as_mut_ptr(), len);
+            buffer.set_len(len);
+        }
+        buffer
+    }
+}
+
+impl io::AsyncIOResult for AsyncReadResult {
+    type Output = io::Result<Vec<u8>>;
+
+    fn poll(&mut self) -> Poll<Self::Output> {
+        let state = self.ring.poll(&self.op);
+        self.handle_state(state)
+    }
+
+    fn wait(&mut self, timeout: u32) -> Poll<Self::Output> {
+        let state = self.ring.wait(&self.op, timeout);
+        self.handle_state(state)
+    }
+
+    fn cancel(&mut self) -> io::Result<()> {
+        self.ring.cancel(self.op.user_data())
+    }
+}
+
+pub(crate) struct AsyncWriteResult {
+    op:   Arc<AsyncOp>,
+    ring: Arc<Ring>,
+}
+
+impl AsyncWriteResult {
+    fn handle_state(state: AsyncIOCompletionState) -> Poll<io::Result<u64>> {
+        match state {
+            AsyncIOCompletionState::InFlight                 => Poll::Pending,
+            AsyncIOCompletionState::Completed(bytes_written) => Poll::Ready(Ok(bytes_written)),
+            AsyncIOCompletionState::Unsuccessful(err)        => Poll::Ready(Err(io::Error::from_raw_os_error(err))),
+            AsyncIOCompletionState::Exhausted                => Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, "Data was already taken from this result")))
+        }
+    }
+}
+
+impl io::AsyncIOResult for AsyncWriteResult {
+    type Output = io::Result<u64>;
+
+    fn poll(&mut self) -> Poll<Self::Output> {
+        Self::handle_state(self.ring.poll(&self.op))
+    }
+
+    fn wait(&mut self, timeout: u32) -> Poll<Self::Output> {
+        Self::handle_state(self.ring.wait(&self.op, timeout))
+    }
+
+    fn cancel(&mut self) -> io::Result<()> {
+        self.ring.cancel(self.op.user_data())
+    }
+}
@@ -0,0 +1,287 @@
+use std::{
+    task::Poll,
+    collections::{VecDeque, HashMap},
+    ffi::CStr,
+};
+
+use onca_common::{
+    prelude::*,
+    io, sync::Mutex,
+};
+
+use crate::{FileWatcherHandle, FileWatcherFilter, PathBuf, Path, FileChangeInfo, FileTime, FileChangeMetadata, EntryType, EntryFlags};
+
+struct WatchState {
+    /// Watch descriptor to directory path, relative to the root of the watcher
+    watches:         HashMap<libc::c_int, PathBuf>,
+    /// Pending `IN_MOVED_FROM` events, waiting for a matching `IN_MOVED_TO` with the same cookie
+    pending_renames: HashMap<u32, (PathBuf, bool)>,
+    changes:         VecDeque<FileChangeInfo>,
+}
+
+//------------------------------
+
+pub struct FileWatcher {
+    fd:            libc::c_int,
+    root:          PathBuf,
+    watch_subtree: bool,
+    mask:          u32,
+    state:         Mutex<WatchState>,
+}
+
+impl FileWatcher {
+    /// Size of buffer to retrieve result in
+    const BUFFER_SIZE: usize = KiB(4);
+
+    pub fn new(path: &PathBuf, watch_subtree: bool, filter: FileWatcherFilter) -> io::Result<Box<Self>> {
+        let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let watcher = Box::new(Self {
+            fd,
+            root: path.clone(),
+            watch_subtree,
+            mask: get_inotify_mask(filter),
+            state: Mutex::new(WatchState {
+                watches: HashMap::new(),
+                pending_renames: HashMap::new(),
+                changes: VecDeque::new(),
+            }),
+        });
+
+        // inotify only watches a single directory, so when watching a subtree, each subdirectory needs its own watch
+        let mut state = watcher.state.lock();
+        watcher.add_watch(&mut state, PathBuf::new())?;
+        drop(state);
+
+        Ok(watcher)
+    }
+
+    fn add_watch(&self, state: &mut WatchState, rel_path: PathBuf) -> io::Result<()> {
+        let full_path = self.root.join(&rel_path);
+        let wd = unsafe { libc::inotify_add_watch(self.fd, full_path.as_ptr() as *const libc::c_char, self.mask) };
+        if wd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        state.watches.insert(wd, rel_path.clone());
+
+        if self.watch_subtree {
+            for name in read_sub_directories(&full_path)? {
+                self.add_watch(state, rel_path.join(&name))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read_events(&self, state: &mut WatchState) {
+        let mut buffer = [0u8; Self::BUFFER_SIZE];
+        loop {
+            let read = unsafe { libc::read(self.fd, buffer.as_mut_ptr() as *mut libc::c_void, buffer.len()) };
+            // Either an error occurred, or no events are available (EAGAIN), as the descriptor is non-blocking
+            if read <= 0 {
+                break;
+            }
+
+            let mut offset = 0;
+            while offset < read as usize {
+                let event = unsafe { &*(buffer.as_ptr().add(offset) as *const libc::inotify_event) };
+                let name = if event.len > 0 {
+                    let name_ptr = unsafe { buffer.as_ptr().add(offset + core::mem::size_of::<libc::inotify_event>()) };
+                    Some(unsafe { CStr::from_ptr(name_ptr as *const libc::c_char) }.to_string_lossy().into_owned())
+                } else {
+                    None
+                };
+                offset += core::mem::size_of::<libc::inotify_event>() + event.len as usize;
+
+                self.process_event(state, event, name);
+            }
+        }
+
+        // Any move without a matching destination was moved out of the watched directory
+        for (_, (path, is_dir)) in state.pending_renames.drain() {
+            if is_dir {
+                state.changes.push_back(FileChangeInfo::DirDeleted(path));
+            } else {
+                state.changes.push_back(FileChangeInfo::FileDeleted(path));
+            }
+        }
+    }
+
+    fn process_event(&self, state: &mut WatchState, event: &libc::inotify_event, name: Option<String>) {
+        if event.mask & libc::IN_IGNORED != 0 {
+            state.watches.remove(&event.wd);
+            return;
+        }
+
+        let (Some(dir), Some(name)) = (state.watches.get(&event.wd), name) else { return; };
+        let path = dir.join(&name);
+        let is_dir = event.mask & libc::IN_ISDIR != 0;
+
+        if event.mask & libc::IN_CREATE != 0 {
+            if is_dir {
+                if self.watch_subtree {
+                    _ = self.add_watch(state, path.clone());
+                }
+                state.changes.push_back(FileChangeInfo::DirAdded(path));
+            } else {
+                state.changes.push_back(FileChangeInfo::FileAdded(path));
+            }
+        } else if event.mask & libc::IN_DELETE != 0 {
+            if is_dir {
+                state.changes.push_back(FileChangeInfo::DirDeleted(path));
+            } else {
+                state.changes.push_back(FileChangeInfo::FileDeleted(path));
+            }
+        } else if event.mask & (libc::IN_MODIFY | libc::IN_ATTRIB) != 0 {
+            let metadata = get_change_metadata(&self.root.join(&path));
+            if is_dir {
+                state.changes.push_back(FileChangeInfo::DirModified { path, metadata });
+            } else {
+                state.changes.push_back(FileChangeInfo::FileModified { path, metadata });
+            }
+        } else if event.mask & libc::IN_MOVED_FROM != 0 {
+            state.pending_renames.insert(event.cookie, (path, is_dir));
+        } else if event.mask & libc::IN_MOVED_TO != 0 {
+            match state.pending_renames.remove(&event.cookie) {
+                Some((old, _)) => if is_dir {
+                    // Keep the paths of any watched subdirectories up to date
+                    for watch_path in state.watches.values_mut() {
+                        if let Ok(sub_path) = watch_path.strip_prefix(&old) {
+                            let new_path = path.join(sub_path);
+                            *watch_path = new_path;
+                        }
+                    }
+                    state.changes.push_back(FileChangeInfo::DirRenamed { old, new: path });
+                } else {
+                    state.changes.push_back(FileChangeInfo::FileRenamed { old, new: path });
+                },
+                // Moved in from outside of the watched directory
+                None => if is_dir {
+                    if self.watch_subtree {
+                        _ = self.add_watch(state, path.clone());
+                    }
+                    state.changes.push_back(FileChangeInfo::DirAdded(path));
+                } else {
+                    state.changes.push_back(FileChangeInfo::FileAdded(path));
+                },
+            }
+        }
+    }
+}
+
+impl FileWatcherHandle for FileWatcher {
+    fn poll(&self) -> Poll<FileChangeInfo> {
+        let mut state = self.state.lock();
+        if state.changes.is_empty() {
+            self.read_events(&mut state);
+        }
+
+        match state.changes.pop_front() {
+            Some(change) => Poll::Ready(change),
+            None => Poll::Pending,
+        }
+    }
+
+    fn cancel(&self) -> io::Result<()> {
+        let mut state = self.state.lock();
+        for (wd, _) in state.watches.drain() {
+            unsafe { libc::inotify_rm_watch(self.fd, wd) };
+        }
+        Ok(())
+    }
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        // Closing the descriptor also removes all watches
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+//--------------------------------------------------------------
+
+fn get_inotify_mask(filter: FileWatcherFilter) -> u32 {
+    let mut mask = 0;
+    if filter.intersects(FileWatcherFilter::FileAdded | FileWatcherFilter::DirAdded) {
+        mask |= libc::IN_CREATE;
+    }
+    if filter.intersects(FileWatcherFilter::FileDeleted | FileWatcherFilter::DirDeleted) {
+        mask |= libc::IN_DELETE;
+    }
+    if filter.intersects(FileWatcherFilter::FileRename | FileWatcherFilter::DirRename) {
+        mask |= libc::IN_MOVED_FROM | libc::IN_MOVED_TO;
+    }
+    if filter.intersects(FileWatcherFilter::Size | FileWatcherFilter::LastWrite) {
+        mask |= libc::IN_MODIFY;
+    }
+    if filter.intersects(FileWatcherFilter::Attributes | FileWatcherFilter::LastAccess | FileWatcherFilter::Creation) {
+        mask |= libc::IN_ATTRIB;
+    }
+    // Directories created or moved into the watched directory always need to be known, so subtree watches can be kept up to date.
+    // Any change that does not match the filter is filtered out by the `Filewatcher` itself.
+    mask | libc::IN_CREATE | libc::IN_MOVED_TO | libc::IN_ONLYDIR
+}
+
+fn read_sub_directories(path: &Path) -> io::Result<Vec<String>> {
+    scoped_alloc!(AllocId::TlsTemp);
+
+    let path = path.to_path_buf();
+    let dir = unsafe { libc::opendir(path.as_ptr() as *const libc::c_char) };
+    if dir.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut sub_dirs = Vec::new();
+    loop {
+        let entry = unsafe { libc::readdir(dir) };
+        if entry.is_null() {
+            break;
+        }
+
+        let entry = unsafe { &*entry };
+        let name = unsafe { CStr::from_ptr(entry.d_name.as_ptr()) }.to_string_lossy();
+        if entry.d_type == libc::DT_DIR && name != "." && name != ".." {
+            sub_dirs.push(name.into_owned());
+        }
+    }
+    unsafe { libc::closedir(dir) };
+    Ok(sub_dirs)
+}
+
+fn get_change_metadata(path: &Path) -> FileChangeMetadata {
+    let path = path.to_path_buf();
+    let mut stat = unsafe { core::mem::zeroed::<libc::stat>() };
+    if unsafe { libc::lstat(path.as_ptr() as *const libc::c_char, &mut stat) } != 0 {
+        return FileChangeMetadata::default();
+    }
+
+    let file_type = stat.st_mode & libc::S_IFMT;
+    let (entry_type, mut flags) = match file_type {
+        libc::S_IFDIR => (EntryType::Directory, EntryFlags::Directory),
+        libc::S_IFLNK => (EntryType::SymlinkFile, EntryFlags::ReparsePoint),
+        libc::S_IFREG => (EntryType::File, EntryFlags::None),
+        _             => (EntryType::Unknown, EntryFlags::Device),
+    };
+    if stat.st_mode & 0o222 == 0 {
+        flags |= EntryFlags::ReadOnly;
+    }
+
+    FileChangeMetadata {
+        entry_type,
+        flags,
+        // Linux does not track creation time in `stat`, so use the last status change instead
+        creation_time: unix_time_to_file_time(stat.st_ctime, stat.st_ctime_nsec),
+        last_access_time: unix_time_to_file_time(stat.st_atime, stat.st_atime_nsec),
+        last_write_time: unix_time_to_file_time(stat.st_mtime, stat.st_mtime_nsec),
+        file_size: stat.st_size as u64,
+        alloc_size: stat.st_blocks as u64 * 512,
+    }
+}
+
+/// Convert a unix timestamp to a `FileTime`, which uses 100ns intervals since 1601-01-01
+fn unix_time_to_file_time(secs: libc::time_t, nsecs: libc::c_long) -> FileTime {
+    const UNIX_EPOCH_OFFSET: u64 = 11_644_473_600;
+    FileTime((secs as u64 + UNIX_EPOCH_OFFSET) * 10_000_000 + nsecs as u64 / 100)
+}
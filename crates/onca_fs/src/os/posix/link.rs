@@ -0,0 +1,37 @@
+use onca_common::{
+    prelude::*,
+    io,
+};
+
+use crate::Path;
+
+pub fn hard_link(source: &Path, dest: &Path) -> io::Result<()> {
+    scoped_alloc!(AllocId::TlsTemp);
+    let source = source.to_path_buf();
+    let dest = dest.to_path_buf();
+
+    if unsafe { libc::link(source.as_ptr() as *const libc::c_char, dest.as_ptr() as *const libc::c_char) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+pub fn symlink_file(source: &Path, dest: &Path) -> io::Result<()> {
+    symlink(source, dest)
+}
+
+// Symlinks to files and directories are the same kind of entry
+pub fn symlink_dir(source: &Path, dest: &Path) -> io::Result<()> {
+    symlink(source, dest)
+}
+
+fn symlink(source: &Path, dest: &Path) -> io::Result<()> {
+    scoped_alloc!(AllocId::TlsTemp);
+    let source = source.to_path_buf();
+    let dest = dest.to_path_buf();
+
+    if unsafe { libc::symlink(source.as_ptr() as *const libc::c_char, dest.as_ptr() as *const libc::c_char) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
@@ -0,0 +1,126 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use cfg_if::cfg_if;
+
+use onca_common::{
+    io,
+    utils::{self, is_flag_set},
+};
+
+use crate::{EntryFlags, FileTime, PathBuf};
+
+pub(crate) mod entry;
+pub(crate) mod drive_volume;
+pub(crate) mod file;
+pub(crate) mod directory;
+pub(crate) mod link;
+
+cfg_if!{
+    if #[cfg(target_os = "linux")] {
+        pub(crate) mod file_watcher;
+        pub(crate) mod file_async;
+    } else {
+        // TODO: macOS needs an FSEvents based file watcher and a kqueue/dispatch_io based async file
+        pub(crate) mod file_watcher {
+            use onca_common::io;
+            use crate::{FileWatcherHandle, FileWatcherFilter, PathBuf};
+
+            pub struct FileWatcher;
+
+            impl FileWatcher {
+                pub fn new(_path: &PathBuf, _watch_subtree: bool, _filter: FileWatcherFilter) -> io::Result<Box<dyn FileWatcherHandle>> {
+                    Err(io::Error::new(io::ErrorKind::Unsupported, "File watchers are not supported on this platform"))
+                }
+            }
+        }
+
+        pub(crate) mod file_async {
+            use onca_common::io;
+            use crate::{Path, PathBuf, Permission, OpenMode, FileCreateFlags, FileAccessFlags};
+
+            pub struct AsyncFileHandle;
+
+            impl AsyncFileHandle {
+                pub(crate) fn create(
+                    _path: &Path,
+                    _open_mode: OpenMode,
+                    _access_perms: Permission,
+                    _shared_access_perms: Permission,
+                    _create_flags: FileCreateFlags,
+                    _access_flags: FileAccessFlags,
+                ) -> io::Result<(Box<dyn crate::AsyncFileHandle>, PathBuf)> {
+                    Err(io::Error::new(io::ErrorKind::Unsupported, "Async files are not supported on this platform"))
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn get_working_dir() -> io::Result<PathBuf> {
+    let mut buf = vec![0u8; 256];
+    loop {
+        if !unsafe { libc::getcwd(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) }.is_null() {
+            return PathBuf::from_utf8_lossy(utils::null_terminate_slice(&buf)).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid working directory path"));
+        }
+
+        // `getcwd` fails with `ERANGE` when the buffer is too small to hold the path
+        let err = io::Error::last_os_error();
+        if err.raw_os_error() != Some(libc::ERANGE) {
+            return Err(err);
+        }
+        let new_len = buf.len() * 2;
+        buf.resize(new_len, 0);
+    }
+}
+
+pub(crate) fn get_temp_dir() -> io::Result<PathBuf> {
+    let dir = std::env::var("TMPDIR").ok().filter(|dir| !dir.is_empty()).unwrap_or_else(|| "/tmp".to_string());
+    PathBuf::from_str(&dir).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid temporary directory path"))
+}
+
+//------------------------------------------------------------------------------------------------------------------------------
+
+/// Convert a unix timestamp, split in seconds and nanoseconds, to a file time
+fn unix_to_file_time(sec: i64, nsec: i64) -> FileTime {
+    let time = if sec >= 0 {
+        UNIX_EPOCH + Duration::new(sec as u64, nsec as u32)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(sec.unsigned_abs()) + Duration::from_nanos(nsec as u64)
+    };
+    time.into()
+}
+
+/// Convert a file time to a `timespec`, a time of `None` is converted to `UTIME_OMIT`, so it is left unchanged by `utimensat` and `futimens`
+fn file_time_to_timespec(time: Option<FileTime>) -> libc::timespec {
+    // `timespec` may contain padding on some targets, so it cannot be constructed directly
+    let mut timespec = unsafe { std::mem::zeroed::<libc::timespec>() };
+    match time.map(|time| SystemTime::from(time).duration_since(UNIX_EPOCH)) {
+        Some(Ok(dur)) => {
+            timespec.tv_sec = dur.as_secs() as libc::time_t;
+            timespec.tv_nsec = dur.subsec_nanos() as _;
+        },
+        // Times before the epoch have negative seconds, but the nanoseconds are always positive
+        Some(Err(err)) => {
+            let dur = err.duration();
+            let (sec, nsec) = match dur.subsec_nanos() {
+                0 => (-(dur.as_secs() as i64), 0),
+                nsec => (-(dur.as_secs() as i64) - 1, 1_000_000_000 - nsec),
+            };
+            timespec.tv_sec = sec as libc::time_t;
+            timespec.tv_nsec = nsec as _;
+        },
+        None => timespec.tv_nsec = libc::UTIME_OMIT as _,
+    }
+    timespec
+}
+
+fn mode_to_flags(mode: libc::mode_t) -> EntryFlags {
+    let mut flags = EntryFlags::None;
+    let file_type = mode & libc::S_IFMT;
+    // Read-only files have no write permission for their owner, see `set_readonly`
+    if !is_flag_set(mode, libc::S_IWUSR)                          { flags |= EntryFlags::ReadOnly; }
+    if file_type == libc::S_IFDIR                                 { flags |= EntryFlags::Directory; }
+    if file_type == libc::S_IFCHR || file_type == libc::S_IFBLK { flags |= EntryFlags::Device; }
+    if file_type == libc::S_IFLNK                                 { flags |= EntryFlags::ReparsePoint; }
+    flags
+}
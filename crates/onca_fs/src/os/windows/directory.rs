@@ -34,6 +34,20 @@ pub(crate) fn create_recursive(path: &Path) -> io::Result<()> {
     Ok(())
 }
 
+/// Create a new directory for temporary files
+/// 
+/// Unlike `create`, this fails with `io::ErrorKind::AlreadyExists` when the directory already exists
+pub(crate) fn create_temp(path: &Path) -> io::Result<()> {
+    scoped_alloc!(AllocId::TlsTemp);
+
+    let path = path.to_path_buf();
+    unsafe { CreateDirectoryA(PCSTR(path.as_ptr()), None) }.map_err(|err| if err.code() == ERROR_ALREADY_EXISTS.to_hresult() {
+        Error::from(io::ErrorKind::AlreadyExists)
+    } else {
+        Error::from_raw_os_error(err.code().0)
+    })
+}
+
 fn create_dir(pcstr: PCSTR) -> io::Result<()> {
     match unsafe { CreateDirectoryA(pcstr, None) } {
         Err(err) if err.code().0 as u32 != ERROR_ALREADY_EXISTS.0 =>
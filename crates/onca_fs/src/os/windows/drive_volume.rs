@@ -1,6 +1,7 @@
 use std::{
-    mem,
-    num::NonZeroU32
+    mem::{self, size_of},
+    num::NonZeroU32,
+    ffi::c_void,
 };
 use onca_common::{
     prelude::*,
@@ -16,24 +17,114 @@ use windows::{
             GetLogicalDriveStringsA, 
             GetVolumeInformationA,
             FindFirstVolumeA, FindNextVolumeA, FindVolumeClose,
-            GetVolumePathNamesForVolumeNameA
+            GetVolumePathNamesForVolumeNameA,
+            GetVolumePathNameA, GetVolumeNameForVolumeMountPointA,
+            CreateFileA, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING, FILE_FLAGS_AND_ATTRIBUTES,
         },
         System::WindowsProgramming::DRIVE_UNKNOWN,
     },
     Win32::{
-        Foundation::{ERROR_MORE_DATA, ERROR_NO_MORE_FILES, MAX_PATH},
+        Foundation::{ERROR_MORE_DATA, ERROR_NO_MORE_FILES, MAX_PATH, HANDLE, CloseHandle},
         System::{
+            IO::DeviceIoControl,
+            Ioctl::{
+                IOCTL_STORAGE_QUERY_PROPERTY, STORAGE_PROPERTY_QUERY, STORAGE_PROPERTY_ID, PropertyStandardQuery,
+                StorageDeviceSeekPenaltyProperty, StorageDeviceTrimProperty, DEVICE_SEEK_PENALTY_DESCRIPTOR, DEVICE_TRIM_DESCRIPTOR,
+            },
             WindowsProgramming::{DRIVE_NO_ROOT_DIR, DRIVE_REMOVABLE, DRIVE_FIXED, DRIVE_REMOTE, DRIVE_CDROM, DRIVE_RAMDISK},
             SystemServices::{FILE_CASE_SENSITIVE_SEARCH, FILE_CASE_PRESERVED_NAMES, FILE_UNICODE_ON_DISK, FILE_FILE_COMPRESSION, FILE_VOLUME_QUOTAS, FILE_SUPPORTS_SPARSE_FILES, FILE_SUPPORTS_REPARSE_POINTS, FILE_VOLUME_IS_COMPRESSED, FILE_SUPPORTS_ENCRYPTION, FILE_READ_ONLY_VOLUME}
         }
     },
 };
-use crate::{PathBuf, DriveInfo, DriveType, VolumeInfo, FilesystemFlags, Path};
+use crate::{PathBuf, DriveInfo, DriveType, VolumeInfo, FilesystemFlags, Path, DiskSpace, DeviceCapabilities};
 
 pub fn get_drive_info(path: &Path) -> io::Result<DriveInfo> {
     get_drive_info_internal(path)
 }
 
+pub fn get_volume_root(path: &Path) -> io::Result<PathBuf> {
+    let path = path.to_path_buf();
+    let mut root = [0u8; MAX_PATH as usize + 1];
+    unsafe { GetVolumePathNameA(PCSTR(path.as_ptr()), &mut root) }.map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
+    PathBuf::from_utf8_lossy(utils::null_terminate_slice(&root)).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid volume root"))
+}
+
+pub fn get_disk_space(path: &Path) -> io::Result<DiskSpace> {
+    // `GetDiskFreeSpaceExA` only accepts directories, so use the root, as the path may point to a file
+    let root = get_volume_root(path)?;
+
+    let mut space = DiskSpace::default();
+    unsafe { GetDiskFreeSpaceExA(PCSTR(root.as_ptr()), Some(&mut space.available_to_user), Some(&mut space.total), Some(&mut space.available)) }
+        .map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
+    Ok(space)
+}
+
+pub fn get_cluster_size(path: &Path) -> io::Result<u32> {
+    let root = get_volume_root(path)?;
+
+    let mut sectors_per_cluster = 0;
+    let mut bytes_per_sector = 0;
+    unsafe { GetDiskFreeSpaceA(PCSTR(root.as_ptr()), Some(&mut sectors_per_cluster), Some(&mut bytes_per_sector), None, None) }
+        .map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
+    Ok(sectors_per_cluster * bytes_per_sector)
+}
+
+pub fn get_device_capabilities(path: &Path) -> io::Result<DeviceCapabilities> {
+    let root = get_volume_root(path)?;
+
+    // The device needs to be opened using the volume GUID path without the trailing separator, otherwise the root directory is opened instead
+    let mut volume_name = [0u8; MAX_PATH as usize + 1];
+    unsafe { GetVolumeNameForVolumeMountPointA(PCSTR(root.as_ptr()), &mut volume_name) }.map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
+    let len = utils::null_terminate_slice(&volume_name).len();
+    if len > 0 && volume_name[len - 1] == b'\\' {
+        volume_name[len - 1] = 0;
+    }
+
+    // No access rights are needed to query the device properties
+    let handle = unsafe { CreateFileA(
+        PCSTR(volume_name.as_ptr()),
+        0,
+        FILE_SHARE_READ | FILE_SHARE_WRITE,
+        None,
+        OPEN_EXISTING,
+        FILE_FLAGS_AND_ATTRIBUTES(0),
+        HANDLE::default()
+    ) }.map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
+
+    let capabilities = DeviceCapabilities {
+        seek_penalty: query_storage_property::<DEVICE_SEEK_PENALTY_DESCRIPTOR>(handle, StorageDeviceSeekPenaltyProperty).map(|desc| desc.IncursSeekPenalty.0 != 0),
+        trim: query_storage_property::<DEVICE_TRIM_DESCRIPTOR>(handle, StorageDeviceTrimProperty).map(|desc| desc.TrimEnabled.0 != 0),
+    };
+
+    unsafe { CloseHandle(handle) }.map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
+    Ok(capabilities)
+}
+
+/// Query a storage property, returns `None` if the device does not support the property.
+fn query_storage_property<T>(handle: HANDLE, property: STORAGE_PROPERTY_ID) -> Option<T> {
+    let query = STORAGE_PROPERTY_QUERY {
+        PropertyId: property,
+        QueryType: PropertyStandardQuery,
+        AdditionalParameters: [0],
+    };
+
+    let mut desc = mem::MaybeUninit::<T>::zeroed();
+    let mut returned = 0;
+    unsafe { DeviceIoControl(
+        handle,
+        IOCTL_STORAGE_QUERY_PROPERTY,
+        Some(&query as *const _ as *const c_void),
+        size_of::<STORAGE_PROPERTY_QUERY>() as u32,
+        Some(desc.as_mut_ptr() as *mut c_void),
+        size_of::<T>() as u32,
+        Some(&mut returned),
+        None
+    ) }.ok()?;
+
+    // SAFETY: The descriptor was zero-initialized and is only returned when it was fully written
+    (returned as usize >= size_of::<T>()).then(|| unsafe { desc.assume_init() })
+}
+
 pub fn get_drive_type(path: &Path) -> DriveType {
     scoped_alloc!(AllocId::TlsTemp);
         
@@ -91,7 +182,7 @@ fn get_drive_info_internal(path: &Path) -> io::Result<DriveInfo> {
     let pcstr = PCSTR(path.as_ptr());
     
     unsafe {
-        GetDiskFreeSpaceExA(pcstr, Some(&mut available_bytes), Some(&mut total_bytes), Some(&mut available_to_user))
+        GetDiskFreeSpaceExA(pcstr, Some(&mut available_to_user), Some(&mut total_bytes), Some(&mut available_bytes))
             .map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
         
         GetDiskFreeSpaceA(pcstr,
@@ -10,7 +10,7 @@ use onca_common::{
 };
 use windows::{
     Win32::{
-        Foundation::{HANDLE, ERROR_INSUFFICIENT_BUFFER, PSID, LUID, CloseHandle},
+        Foundation::{HANDLE, ERROR_INSUFFICIENT_BUFFER, ERROR_NO_MORE_FILES, PSID, LUID, CloseHandle},
         Storage::FileSystem::*, 
         Security::{
             GetFileSecurityA, LookupAccountNameA, SID, DACL_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR, SID_NAME_USE, GROUP_SECURITY_INFORMATION, OWNER_SECURITY_INFORMATION,
@@ -27,7 +27,7 @@ use windows::{
     core::{PCSTR, PSTR, PCWSTR}
 };
 
-use crate::{MetaData, EntryType, EntryFlags, Permission, Path, PathBuf, VolumeFileId, FileLinkCount, EntryHandle, EntrySearchHandle, FileTime, StorageInfo, StorageFlags};
+use crate::{MetaData, EntryType, EntryFlags, Permission, Path, PathBuf, VolumeFileId, FileLinkCount, EntryHandle, EntrySearchHandle, FileTime, FileTimes, StorageInfo, StorageFlags};
 use super::dword_to_flags;
 
 //------------------------------
@@ -65,37 +65,21 @@ impl crate::entry::EntryHandle for NativeEntryHandle {
     }
 
     fn fully_qualified_path(&self) -> io::Result<PathBuf> {
+        // Only the attributes are needed to get the final path, and the entry should never be created
         let handle = unsafe { CreateFileA(
             PCSTR(self.path.as_ptr()),
-            FILE_GENERIC_READ.0,
-            FILE_SHARE_READ,
+            FILE_READ_ATTRIBUTES.0,
+            FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
             None,
-            OPEN_ALWAYS,
-            FILE_ATTRIBUTE_NORMAL,
+            OPEN_EXISTING,
+            // Directories can only be opened using backup semantics
+            FILE_FLAG_BACKUP_SEMANTICS,
             HANDLE::default()
         ) }.map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
 
-        let needed = unsafe { GetFinalPathNameByHandleA(handle, &mut [], FILE_NAME_NORMALIZED) } as usize;
-        let mut path = if needed == 0 {
-            return Err(io::Error::last_os_error())
-        } else {
-            let mut path = String::with_capacity(needed);
-            unsafe { path.as_mut_vec().set_len(needed) };
-            path
-        };
-
-        let written = unsafe { GetFinalPathNameByHandleA(handle, &mut path.as_mut_vec(), FILE_NAME_NORMALIZED) } as usize;
-        if written == 0 {
-            return Err(io::Error::last_os_error())
-        } else {
-            unsafe { path.as_mut_vec().set_len(written) };
-        }
-
-        // Path returned starts with `//?/`, so strip it
-        path.drain(..=3);
-
+        let path = get_final_path(handle);
         unsafe { CloseHandle(handle) }.map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
-        Ok(PathBuf::from_str(&path).unwrap())
+        path
     }
 
     fn metadata(&self) -> io::Result<MetaData> {
@@ -106,7 +90,8 @@ impl crate::entry::EntryHandle for NativeEntryHandle {
             FILE_SHARE_READ,
             None,
             OPEN_EXISTING,
-            FILE_ATTRIBUTE_NORMAL,
+            // Directories can only be opened using backup semantics
+            FILE_FLAG_BACKUP_SEMANTICS,
             HANDLE::default()
         )}.map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
 
@@ -138,19 +123,26 @@ pub(crate) fn get_metadata(handle: HANDLE) -> io::Result<MetaData> {
     };
 
     let mut standard_info = FILE_STANDARD_INFO::default();
-    unsafe { GetFileInformationByHandleEx(handle, FileStandardInfo, &mut standard_info as *mut _ as *mut _, size_of::<FILE_BASIC_INFO>() as u32) }
+    unsafe { GetFileInformationByHandleEx(handle, FileStandardInfo, &mut standard_info as *mut _ as *mut _, size_of::<FILE_STANDARD_INFO>() as u32) }
         .map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
 
-    let mut compression_info = FILE_COMPRESSION_INFO::default();
-    unsafe { GetFileInformationByHandleEx(handle, FileCompressionInfo, &mut compression_info as *mut _ as *mut _, size_of::<FILE_BASIC_INFO>() as u32) }
-        .map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
+    // The compressed size is the actual size on disk for both compressed and sparse files, for any other file, it's the same as the file size.
+    // Not all file systems support compression, so ignore the compressed size when it cannot be retrieved.
+    let compressed_size = if flags.intersects(EntryFlags::Compressed | EntryFlags::Sparse) {
+        let mut compression_info = FILE_COMPRESSION_INFO::default();
+        unsafe { GetFileInformationByHandleEx(handle, FileCompressionInfo, &mut compression_info as *mut _ as *mut _, size_of::<FILE_COMPRESSION_INFO>() as u32) }
+            .ok()
+            .and_then(|_| NonZeroU64::new(compression_info.CompressedFileSize as u64))
+    } else {
+        None
+    };
 
     let mut align_info = FILE_ALIGNMENT_INFO::default();
-    unsafe { GetFileInformationByHandleEx(handle, FileAlignmentInfo, &mut align_info as *mut _ as *mut _, size_of::<FILE_BASIC_INFO>() as u32) }
+    unsafe { GetFileInformationByHandleEx(handle, FileAlignmentInfo, &mut align_info as *mut _ as *mut _, size_of::<FILE_ALIGNMENT_INFO>() as u32) }
         .map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
 
     let mut storage_info = FILE_STORAGE_INFO::default();
-    unsafe { GetFileInformationByHandleEx(handle, FileStorageInfo, &mut storage_info as *mut _ as *mut _, size_of::<FILE_BASIC_INFO>() as u32) }
+    unsafe { GetFileInformationByHandleEx(handle, FileStorageInfo, &mut storage_info as *mut _ as *mut _, size_of::<FILE_STORAGE_INFO>() as u32) }
         .map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
 
     let mut storage_flags = StorageFlags::None;
@@ -169,7 +161,7 @@ pub(crate) fn get_metadata(handle: HANDLE) -> io::Result<MetaData> {
 
 
     let mut id_info = FILE_ID_INFO::default();
-    unsafe { GetFileInformationByHandleEx(handle, FileIdInfo, &mut id_info as *mut _ as *mut _, size_of::<FILE_BASIC_INFO>() as u32) }
+    unsafe { GetFileInformationByHandleEx(handle, FileIdInfo, &mut id_info as *mut _ as *mut _, size_of::<FILE_ID_INFO>() as u32) }
         .map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
 
     let volume_file_id = VolumeFileId {
@@ -191,7 +183,7 @@ pub(crate) fn get_metadata(handle: HANDLE) -> io::Result<MetaData> {
         last_change_time: FileTime(basic_info.ChangeTime as u64),
         file_size: standard_info.EndOfFile as u64,
         alloc_size: standard_info.AllocationSize as u64,
-        compressed_size: NonZeroU64::new(compression_info.CompressedFileSize as u64),
+        compressed_size,
         num_links,
         min_align: align_info.AlignmentRequirement,
         volume_file_id,
@@ -199,6 +191,56 @@ pub(crate) fn get_metadata(handle: HANDLE) -> io::Result<MetaData> {
     })
 }
 
+pub(crate) fn set_times(path: &Path, times: FileTimes) -> io::Result<()> {
+    scoped_alloc!(AllocId::TlsTemp);
+
+    let path = path.to_path_buf();
+    let handle = unsafe { CreateFileA(
+        PCSTR(path.as_ptr()),
+        FILE_WRITE_ATTRIBUTES.0,
+        FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+        None,
+        OPEN_EXISTING,
+        // Directories can only be opened using backup semantics
+        FILE_FLAG_BACKUP_SEMANTICS,
+        HANDLE::default()
+    )}.map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
+
+    let res = super::file::set_times(handle, times);
+    unsafe { CloseHandle(handle) }.map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
+    res
+}
+
+pub(crate) fn set_readonly(path: &Path, readonly: bool) -> io::Result<()> {
+    set_attrib(path, FILE_ATTRIBUTE_READONLY, readonly)
+}
+
+pub(crate) fn set_hidden(path: &Path, hidden: bool) -> io::Result<()> {
+    set_attrib(path, FILE_ATTRIBUTE_HIDDEN, hidden)
+}
+
+fn set_attrib(path: &Path, attrib: FILE_FLAGS_AND_ATTRIBUTES, set: bool) -> io::Result<()> {
+    scoped_alloc!(AllocId::TlsTemp);
+
+    let path = path.to_path_buf();
+    let mut attribs = unsafe { GetFileAttributesA(PCSTR(path.as_ptr())) };
+    if attribs == INVALID_FILE_ATTRIBUTES {
+        return Err(io::Error::last_os_error());
+    }
+
+    if set {
+        attribs |= attrib.0;
+    } else {
+        attribs &= !attrib.0;
+    }
+    // An attribute value of 0 is not allowed
+    if attribs == 0 {
+        attribs = FILE_ATTRIBUTE_NORMAL.0;
+    }
+
+    unsafe { SetFileAttributesA(PCSTR(path.as_ptr()), FILE_FLAGS_AND_ATTRIBUTES(attribs)) }.map_err(|err| io::Error::from_raw_os_error(err.code().0))
+}
+
 pub(crate) fn get_permissions_pcstr(pcstr: PCSTR) -> io::Result<Permission> {
     scoped_alloc!(AllocId::TlsTemp);
 
@@ -313,32 +355,37 @@ impl NativeEntrySearchHandle {
         let mut find_data = WIN32_FIND_DATAA::default();
         let handle = unsafe{ FindFirstFileA(pcwstr, &mut find_data) }
             .map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
+        let search_handle = Box::new(NativeEntrySearchHandle(handle));
 
         // Skip both "." and ".."
         while matches!(find_data.cFileName[..3], [b'.', 0, _] | [b'.', b'.', 0])
         {
-            unsafe { FindNextFileA(handle, &mut find_data) }.map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
+            match unsafe { FindNextFileA(handle, &mut find_data) } {
+                Ok(_) => (),
+                // An empty path ends the iteration, so an empty directory will not return any entries
+                Err(err) if err.code() == ERROR_NO_MORE_FILES.to_hresult() => return Ok((search_handle, PathBuf::new())),
+                Err(err) => return Err(io::Error::from_raw_os_error(err.code().0)),
+            }
         }
 
         let mut path = path.to_path_buf();
         // SAFETY: Windows should always return a valid file name
         path.push(unsafe { PathBuf::from_utf8_lossy(utils::null_terminate_slice(&find_data.cFileName)).unwrap_unchecked() });
-        Ok((Box::new(NativeEntrySearchHandle(handle)), path))
+        Ok((search_handle, path))
     }
 }
 
 impl EntrySearchHandle for NativeEntrySearchHandle {
     fn next(&mut self, mut path: PathBuf) -> Option<(Box<dyn EntryHandle>, EntryType, PathBuf)> {
         let mut find_data = WIN32_FIND_DATAA::default();
+        let (entry, entry_type) = NativeEntryHandle::new(&path).map_or(None, |val| Some(val))?;
 
+        // The current entry still needs to be returned when there is no next entry, the empty path will end the iteration
         match unsafe { FindNextFileA(self.0, &mut find_data) } {
-            Ok(_) => {
-                let (entry, entry_type) = NativeEntryHandle::new(&path).map_or(None, |val| Some(val))?;
-                path.set_file_name(utils::null_terminated_arr_to_str_unchecked(&find_data.cFileName));
-                Some((entry, entry_type, path))
-            },
-            Err(_) => None,
+            Ok(_) => path.set_file_name(utils::null_terminated_arr_to_str_unchecked(&find_data.cFileName)),
+            Err(_) => path = PathBuf::new(),
         }
+        Some((entry, entry_type, path))
     }
 }
 
@@ -351,3 +398,30 @@ impl Drop for NativeEntrySearchHandle {
         }
     }
 }
+
+fn get_final_path(handle: HANDLE) -> io::Result<PathBuf> {
+    let needed = unsafe { GetFinalPathNameByHandleA(handle, &mut [], FILE_NAME_NORMALIZED) } as usize;
+    let mut path = if needed == 0 {
+        return Err(io::Error::last_os_error())
+    } else {
+        let mut path = String::with_capacity(needed);
+        unsafe { path.as_mut_vec().set_len(needed) };
+        path
+    };
+
+    let written = unsafe { GetFinalPathNameByHandleA(handle, &mut path.as_mut_vec(), FILE_NAME_NORMALIZED) } as usize;
+    if written == 0 {
+        return Err(io::Error::last_os_error())
+    } else {
+        unsafe { path.as_mut_vec().set_len(written) };
+    }
+
+    // Path returned starts with `\\?\`, or `\\?\UNC\` for a network share, so strip it
+    if path.starts_with("\\\\?\\UNC\\") {
+        path.replace_range(..8, "//");
+    } else if path.starts_with("\\\\?\\") {
+        path.drain(..4);
+    }
+
+    PathBuf::from_str(&path).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid fully qualified path"))
+}
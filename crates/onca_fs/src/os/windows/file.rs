@@ -3,7 +3,7 @@ use std::{
     mem::{size_of, self},
     num::NonZeroU64,
     sync::{
-        atomic::{AtomicU32, Ordering, AtomicUsize},
+        atomic::{Ordering, AtomicUsize},
         Arc,
     },
     task::Poll,
@@ -16,10 +16,10 @@ use onca_common::{
 use windows::{
     Win32::{
         Storage::FileSystem::*, 
-        Foundation::{GetLastError, HANDLE, CloseHandle, FILETIME, ERROR_SUCCESS, ERROR_TIMEOUT, WAIT_EVENT, BOOL, MAX_PATH},
+        Foundation::{HANDLE, CloseHandle, ERROR_SUCCESS, ERROR_TIMEOUT, ERROR_FILE_NOT_FOUND, ERROR_IO_PENDING, ERROR_LOCK_VIOLATION, ERROR_NOT_SAME_DEVICE, WAIT_EVENT, BOOL},
         System::{
-            IO::{OVERLAPPED, CancelIoEx, CancelIo},
-            Threading::{WaitForSingleObjectEx, SleepEx},
+            IO::{OVERLAPPED, CancelIoEx, CancelIo, GetOverlappedResult},
+            Threading::{WaitForSingleObjectEx, SleepEx, CreateEventA},
             Memory::{CreateFileMappingA, PAGE_READONLY, PAGE_PROTECTION_FLAGS, PAGE_READWRITE, PAGE_EXECUTE_READ, PAGE_EXECUTE_READWRITE, MapViewOfFile, FILE_MAP_READ, FILE_MAP_WRITE, MEMORY_MAPPED_VIEW_ADDRESS, UnmapViewOfFile, FlushViewOfFile},
             SystemInformation::{GetSystemInfo, SYSTEM_INFO},
         }, 
@@ -27,9 +27,9 @@ use windows::{
     core::PCSTR,
 };
 
-use crate::{Path, Permission, OpenMode, FileCreateFlags, PathBuf, FileAsyncWriteResult, FileAsyncReadResult, FileAccessFlags, MemoryMappedFileHandle, MappedViewAccess, MemoryMappedViewHandle};
+use crate::{Path, Permission, OpenMode, FileCreateFlags, PathBuf, FileAsyncWriteResult, FileAsyncReadResult, FileAccessFlags, MemoryMappedFileHandle, MappedViewAccess, MemoryMappedViewHandle, FileLockKind, FileTimes};
 
-use super::{entry, high_low_to_u64};
+use super::{entry, high_low_to_u64, win32_io_error};
 
 pub(crate) fn delete(path: &Path) -> io::Result<()> {
     scoped_alloc!(AllocId::TlsTemp);
@@ -39,6 +39,57 @@ pub(crate) fn delete(path: &Path) -> io::Result<()> {
     unsafe { DeleteFileA(PCSTR(path.as_ptr())) }.map_err(|err| io::Error::from_raw_os_error(err.code().0))
 }
 
+pub(crate) fn replace(source: &Path, dest: &Path) -> io::Result<()> {
+    scoped_alloc!(AllocId::TlsTemp);
+
+    let source = source.to_path_buf();
+    let dest = dest.to_path_buf();
+
+    // `ReplaceFile` keeps the attributes, ACLs and alternate streams of the replaced file, but requires it to exist
+    match unsafe { ReplaceFileA(PCSTR(dest.as_ptr()), PCSTR(source.as_ptr()), PCSTR::null(), REPLACEFILE_IGNORE_MERGE_ERRORS, None, None) } {
+        Ok(_) => Ok(()),
+        Err(err) if err.code() == ERROR_FILE_NOT_FOUND.to_hresult() => {
+            unsafe { MoveFileExA(PCSTR(source.as_ptr()), PCSTR(dest.as_ptr()), MOVEFILE_REPLACE_EXISTING | MOVEFILE_WRITE_THROUGH) }
+                .map_err(win32_io_error)
+        },
+        Err(err) => Err(io::Error::from_raw_os_error(err.code().0)),
+    }
+}
+
+/// Make a `replace` durable, `ReplaceFile` and `MOVEFILE_WRITE_THROUGH` already do this before returning
+pub(crate) fn sync_replaced(_dest: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Error returned when a rename crosses volumes
+pub(crate) const CROSS_VOLUME_ERROR: i32 = ERROR_NOT_SAME_DEVICE.0 as i32;
+
+pub(crate) fn rename(source: &Path, dest: &Path, replace: bool) -> io::Result<()> {
+    scoped_alloc!(AllocId::TlsTemp);
+
+    let source = source.to_path_buf();
+    let dest = dest.to_path_buf();
+
+    // Without `MOVEFILE_COPY_ALLOWED`, moving to another volume fails with `ERROR_NOT_SAME_DEVICE`
+    let flags = if replace { MOVEFILE_REPLACE_EXISTING } else { MOVEFILE_FLAGS(0) };
+    unsafe { MoveFileExA(PCSTR(source.as_ptr()), PCSTR(dest.as_ptr()), flags) }
+        .map_err(win32_io_error)
+}
+
+/// Set the times of a file or directory, any time that is `None` is left unchanged
+pub(crate) fn set_times(handle: HANDLE, times: FileTimes) -> io::Result<()> {
+    // Times with a value of 0 are left unchanged, as are attributes with a value of 0
+    let file_info = FILE_BASIC_INFO {
+        CreationTime: times.created.map_or(0, |time| time.as_raw() as i64),
+        LastAccessTime: times.accessed.map_or(0, |time| time.as_raw() as i64),
+        LastWriteTime: times.modified.map_or(0, |time| time.as_raw() as i64),
+        ChangeTime: 0,
+        FileAttributes: 0,
+    };
+    unsafe { SetFileInformationByHandle(handle, FileBasicInfo, &file_info as *const _ as *const c_void, size_of::<FILE_BASIC_INFO>() as u32) }
+        .map_err(|err| io::Error::from_raw_os_error(err.code().0))
+}
+
 pub struct FileHandle{
     pub(crate) handle: HANDLE,
     pub(crate) async_op_count: Arc<AtomicUsize>,
@@ -70,18 +121,18 @@ impl crate::file::FileHandle for FileHandle {
             .map_err(|err| io::Error::from_raw_os_error(err.code().0))
     }
 
-    fn set_modified(&mut self, time: u64) -> io::Result<()> {
-        let mut file_time = FILETIME::default();
-        file_time.dwLowDateTime = time as u32;
-        file_time.dwHighDateTime = (time >> 32) as u32;
-
-        unsafe { SetFileTime(self.handle, None, None, Some(&file_time)) }.map_err(|err| io::Error::from_raw_os_error(err.code().0))
+    fn set_times(&mut self, times: FileTimes) -> io::Result<()> {
+        set_times(self.handle, times)
     }
 
     fn set_permissions(&mut self, permissions: Permission) -> io::Result<()> {
         self.set_attrib(FILE_ATTRIBUTE_READONLY, !permissions.contains(Permission::Write))
     }
 
+    fn set_readonly(&mut self, readonly: bool) -> io::Result<()> {
+        self.set_attrib(FILE_ATTRIBUTE_READONLY, readonly)
+    }
+
     fn set_hidden(&mut self, hidden: bool) -> io::Result<()> {
         self.set_attrib(FILE_ATTRIBUTE_HIDDEN, hidden)
     }
@@ -94,6 +145,45 @@ impl crate::file::FileHandle for FileHandle {
         entry::get_metadata(self.handle)
     }
 
+    fn lock(&mut self, kind: FileLockKind, blocking: bool) -> io::Result<bool> {
+        let mut flags = LOCK_FILE_FLAGS(0);
+        if kind == FileLockKind::Exclusive {
+            flags |= LOCKFILE_EXCLUSIVE_LOCK;
+        }
+        if !blocking {
+            flags |= LOCKFILE_FAIL_IMMEDIATELY;
+        }
+
+        // If the file was opened for async I/O, the lock may complete asynchronously, so we need an event to wait on
+        let event = unsafe { CreateEventA(None, true, false, PCSTR::null()) }.map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
+        let mut overlapped = OVERLAPPED::default();
+        overlapped.hEvent = event;
+
+        let res = match unsafe { LockFileEx(self.handle, flags, 0, u32::MAX, u32::MAX, &mut overlapped) } {
+            Ok(_) => Ok(true),
+            Err(err) if err.code() == ERROR_IO_PENDING.to_hresult() => {
+                let mut bytes_transferred = 0;
+                unsafe { GetOverlappedResult(self.handle, &overlapped, &mut bytes_transferred, true) }
+                    .map_or_else(|err| Err(io::Error::from_raw_os_error(err.code().0)), |_| Ok(true))
+            },
+            Err(err) if err.code() == ERROR_LOCK_VIOLATION.to_hresult() => Ok(false),
+            Err(err) => Err(io::Error::from_raw_os_error(err.code().0)),
+        };
+        _ = unsafe { CloseHandle(event) };
+        res
+    }
+
+    fn unlock(&mut self) -> io::Result<()> {
+        let mut overlapped = OVERLAPPED::default();
+        unsafe { UnlockFileEx(self.handle, 0, u32::MAX, u32::MAX, &mut overlapped) }.map_err(|err| io::Error::from_raw_os_error(err.code().0))
+    }
+
+    fn read_ahead(&mut self, _offset: u64, _len: u64) -> io::Result<()> {
+        // Windows has no read-ahead hint for a range of a file, the cache manager only reads ahead based on the access pattern,
+        // which is more aggressive for files opened with `FILE_FLAG_SEQUENTIAL_SCAN`
+        Ok(())
+    }
+
     fn read(&mut self, mut buf: &mut [u8]) -> io::Result<usize> {
         fn read_impl(handle: HANDLE, arr: &mut [u8]) -> io::Result<usize> {
             let mut bytes_read = 0;
@@ -261,28 +351,23 @@ impl FileHandle {
         open_link: bool,
         temporary: bool
     ) -> io::Result<(Box<dyn crate::FileHandle>, PathBuf)> {
-        let mut path_buf = path.to_path_buf();
-
-        if temporary {
-            let mut file_name = [0u8; MAX_PATH as usize];
-            let path_name = ['.' as u16, 0];
-            let prefix_string = ['O' as u16, 'N' as u16, 'C' as u16, 'A' as u16, 0];
-            static UUNIQUE : AtomicU32 = AtomicU32::new(1);
-            let unique = UUNIQUE.fetch_add(1, Ordering::AcqRel);
-            let res = unsafe { GetTempFileNameA(PCSTR(path_name.as_ptr() as *const _), PCSTR(prefix_string.as_ptr() as *const _), unique, &mut file_name) };
-            if res != 0 {
-                unsafe { GetLastError() }.map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
-            }
+        let (handle, path_buf) = Self::create_raw(path, open_mode, access_perms, shared_access_perms, create_flags, access_flags, open_link, temporary)?;
+        Ok((Box::new(FileHandle{ handle, permissions: access_perms, async_op_count: Arc::new(AtomicUsize::new(0)) }), path_buf))
+    }
+
+    /// Create/open a native file handle, without wrapping it in a `FileHandle`
+    pub(crate) fn create_raw(
+        path: &Path,
+        open_mode: OpenMode,
+        access_perms: Permission,
+        shared_access_perms: Permission,
+        create_flags: FileCreateFlags,
+        access_flags: FileAccessFlags,
+        open_link: bool,
+        temporary: bool
+    ) -> io::Result<(HANDLE, PathBuf)> {
+        let path_buf = path.to_path_buf();
 
-            let temp_end = file_name.iter().position(|&c| c == 0).unwrap_or_default();
-            if temp_end > 0 {
-                let _scope_alloc = ScopedAlloc::new(AllocId::TlsTemp);
-                // SAFETY: file_name will always be valid
-                let file_name = unsafe { Path::new_unchecked(std::str::from_utf8_unchecked(&file_name[..temp_end])) };
-                path_buf.push(file_name)
-            }
-        }
-        
         let mut win32_access = FILE_ACCESS_RIGHTS(0);
         if access_perms.contains(Permission::Read)    {  win32_access |= FILE_GENERIC_READ; }
         if access_perms.contains(Permission::Write)   { win32_access |= FILE_GENERIC_WRITE; }
@@ -298,6 +383,9 @@ impl FileHandle {
             if shared_access_perms.contains(Permission::Write) || shared_access_perms.contains(Permission::Append) {
                 win32_access_share |= FILE_SHARE_WRITE.0;
             }
+            if shared_access_perms.contains(Permission::Delete) {
+                win32_access_share |= FILE_SHARE_DELETE.0;
+            }
             // Do this is an assert, as the user should never pass Execute here
             assert!(!is_flag_set(shared_access_perms, Permission::Execute), "Cannot share file execute permission");
         }
@@ -323,6 +411,7 @@ impl FileHandle {
         if access_flags.contains(FileAccessFlags::WriteThrough)     { win32_flags |= FILE_FLAG_WRITE_THROUGH.0; }
         if create_flags.contains(FileCreateFlags::AllowBackup)      { win32_flags |= FILE_FLAG_BACKUP_SEMANTICS.0; }
         if open_link {  win32_flags |= FILE_FLAG_OPEN_REPARSE_POINT.0; }
+        // Hint the OS to keep the file in memory, as it is expected to be deleted soon
         if temporary {  win32_flags |= FILE_ATTRIBUTE_TEMPORARY.0; }
         
         let handle = unsafe { CreateFileA(
@@ -335,7 +424,7 @@ impl FileHandle {
             HANDLE::default()
         ) };
         match handle {
-            Ok(handle) => Ok((Box::new(FileHandle{ handle, permissions: access_perms, async_op_count: Arc::new(AtomicUsize::new(0)) }), path_buf)),
+            Ok(handle) => Ok((handle, path_buf)),
             Err(err) => Err(io::Error::from_raw_os_error(err.code().0))
         }
     }
@@ -347,17 +436,28 @@ impl FileHandle {
     }
 
     fn set_attrib(&mut self, attrib: FILE_FLAGS_AND_ATTRIBUTES, set: bool) -> io::Result<()> {
-            let mut file_info = FILE_BASIC_INFO::default();
-            unsafe { GetFileInformationByHandleEx(self.handle, FileBasicInfo, &mut file_info as *mut _ as *mut c_void, size_of::<FILE_BASIC_INFO>() as u32) }
-                .map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
-
-            if set {
-                file_info.FileAttributes &= !attrib.0;
-            } else {
-                file_info.FileAttributes |= attrib.0;
-            }
-            unsafe { SetFileInformationByHandle(self.handle, FileBasicInfo, &file_info as *const _ as *const c_void, size_of::<FILE_BASIC_INFO>() as u32) }
-                .map_err(|err| io::Error::from_raw_os_error(err.code().0))
+        let mut file_info = FILE_BASIC_INFO::default();
+        unsafe { GetFileInformationByHandleEx(self.handle, FileBasicInfo, &mut file_info as *mut _ as *mut c_void, size_of::<FILE_BASIC_INFO>() as u32) }
+            .map_err(|err| io::Error::from_raw_os_error(err.code().0))?;
+
+        if set {
+            file_info.FileAttributes |= attrib.0;
+        } else {
+            file_info.FileAttributes &= !attrib.0;
+        }
+        // An attribute value of 0 would leave the attributes unchanged
+        if file_info.FileAttributes == 0 {
+            file_info.FileAttributes = FILE_ATTRIBUTE_NORMAL.0;
+        }
+
+        // Times with a value of 0 are left unchanged, so the attribute change does not touch any times
+        file_info.CreationTime = 0;
+        file_info.LastAccessTime = 0;
+        file_info.LastWriteTime = 0;
+        file_info.ChangeTime = 0;
+
+        unsafe { SetFileInformationByHandle(self.handle, FileBasicInfo, &file_info as *const _ as *const c_void, size_of::<FILE_BASIC_INFO>() as u32) }
+            .map_err(|err| io::Error::from_raw_os_error(err.code().0))
     }
 }
 
@@ -609,3 +709,16 @@ impl io::AsyncIOResult for AsyncWriteResult {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use windows::core::Error;
+
+    use super::*;
+
+    #[test]
+    fn rename_reports_cross_volume_error() {
+        // `move_with_options` only falls back to copying when the rename error matches `CROSS_VOLUME_ERROR`
+        let err = win32_io_error(Error::from(ERROR_NOT_SAME_DEVICE.to_hresult()));
+        assert_eq!(err.raw_os_error(), Some(CROSS_VOLUME_ERROR));
+    }
+}
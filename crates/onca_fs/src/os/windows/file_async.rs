@@ -1,147 +1,391 @@
 use std::{
-    task::Poll,
+    cell::UnsafeCell,
     mem,
+    ptr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::Poll,
+    time::Instant,
 };
 
-use onca_common::io::{SeekFrom, self};
+use onca_common::{io, sync::Mutex};
 use windows::Win32::{
-    Storage::FileSystem::{ReadFileEx, WriteFileEx},
-    System::{
-        IO::{OVERLAPPED, CancelIo, CancelIoEx},
-        Threading::WaitForSingleObjectEx
-    },
-    Foundation::{ERROR_SUCCESS, HANDLE, BOOL, ERROR_TIMEOUT, WAIT_EVENT}
+    Storage::FileSystem::{ReadFile, WriteFile, GetFileSizeEx, FlushFileBuffers, GetFileInformationByHandleEx, FileStorageInfo, FILE_STORAGE_INFO},
+    System::IO::{OVERLAPPED, OVERLAPPED_ENTRY, CancelIoEx, CreateIoCompletionPort, GetQueuedCompletionStatusEx, GetOverlappedResult},
+    Foundation::{HANDLE, BOOL, CloseHandle, ERROR_IO_PENDING},
 };
 
+use crate::{Path, PathBuf, Permission, OpenMode, FileCreateFlags, FileAccessFlags, FileAsyncReadResult, FileAsyncWriteResult, get_remaining_timeout};
+
 use super::file::FileHandle;
 
-/// Windoows async IO completion state
+/// Async IO completion state
+#[derive(Clone, Copy)]
 enum AsyncIOCompletionState {
     /// Async operation is still in flight
     InFlight,
     /// Async operation has completed successfully
     Completed(u64),
     /// Async operation has completed with an error
-    Unsuccessful(u32),
+    Unsuccessful(i32),
     /// Async opeartion has completed successfully, but the buffer is already returned
     Exhausted,
 }
 
-unsafe extern "system" fn io_completion_callback(error_code: u32, bytes_transfered: u32, overlapped: *mut OVERLAPPED) {
-    let completion_data : &mut AsyncIOCompletionData = mem::transmute((*overlapped).hEvent);
-    if error_code == ERROR_SUCCESS.0 {
-        completion_data.state = AsyncIOCompletionState::Completed(bytes_transfered as u64);
-    } else {
-        completion_data.state = AsyncIOCompletionState::Unsuccessful(error_code);
+/// Async operation, which is kept alive by the completion port until it has completed.
+#[repr(C)]
+struct AsyncOp {
+    // Needs to be the first field, so the `OVERLAPPED` returned by the completion port can be cast back to the operation
+    overlapped:  UnsafeCell<OVERLAPPED>,
+    file_handle: HANDLE,
+    // The kernel writes directly into the buffer's memory, so the buffer needs to live as long as the operation
+    buffer:      Mutex<Vec<u8>>,
+    state:       Mutex<AsyncIOCompletionState>,
+}
+
+impl AsyncOp {
+    fn new(file_handle: HANDLE, offset: u64, buffer: Vec<u8>) -> Arc<Self> {
+        let mut overlapped = OVERLAPPED::default();
+        overlapped.Anonymous.Anonymous.Offset = offset as u32;
+        overlapped.Anonymous.Anonymous.OffsetHigh = (offset >> 32) as u32;
+
+        Arc::new(Self {
+            overlapped: UnsafeCell::new(overlapped),
+            file_handle,
+            buffer: Mutex::new(buffer),
+            state: Mutex::new(AsyncIOCompletionState::InFlight),
+        })
     }
 
-    if let Some(waker) = completion_data.waker.take() {
-        waker.wake();
+    fn overlapped(&self) -> *mut OVERLAPPED {
+        self.overlapped.get()
     }
 }
 
-struct AsyncIOCompletionData {
-    state : AsyncIOCompletionState,
-    waker : Option<core::task::Waker>
+//------------------------------
+
+/// I/O completion port, owned by a single file.
+struct CompletionPort {
+    handle:    HANDLE,
+    in_flight: AtomicUsize,
 }
 
-impl AsyncIOCompletionData {
-    fn new() -> AsyncIOCompletionData {
-        AsyncIOCompletionData { state: AsyncIOCompletionState::InFlight, waker: None }
+impl CompletionPort {
+    /// Maximum number of completions to dequeue at once
+    const MAX_ENTRIES: usize = 32;
+
+    /// Issue an operation, `issue` should return `ERROR_IO_PENDING` or success when the operation was successfully queued.
+    fn issue<F>(&self, op: &Arc<AsyncOp>, issue: F) -> io::Result<()> where
+        F: FnOnce(*mut OVERLAPPED) -> windows::core::Result<()>
+    {
+        // The completion port holds a reference to the operation, which will be released once it completes
+        let raw = Arc::into_raw(op.clone());
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+
+        match issue(op.overlapped()) {
+            Ok(_) => Ok(()),
+            Err(err) if err.code() == ERROR_IO_PENDING.to_hresult() => Ok(()),
+            Err(err) => {
+                // The operation was never queued, so no completion will be posted for it
+                self.in_flight.fetch_sub(1, Ordering::AcqRel);
+                unsafe { drop(Arc::from_raw(raw)) };
+                Err(io::Error::from_raw_os_error(err.code().0))
+            }
+        }
+    }
+
+    /// Process all completions currently in the port, waiting up to `timeout` ms for at least 1 completion
+    fn process_completions(&self, timeout: u32) {
+        let mut entries = [OVERLAPPED_ENTRY::default(); Self::MAX_ENTRIES];
+        let mut num_removed = 0;
+        // If no completion was dequeued before the timeout, this returns an error, which just means that there is nothing to process
+        if unsafe { GetQueuedCompletionStatusEx(self.handle, &mut entries, &mut num_removed, timeout, BOOL(0)) }.is_err() {
+            return;
+        }
+
+        for entry in &entries[..num_removed as usize] {
+            // SAFETY: Every overlapped queued on this port was created by `issue`, which leaked a reference to its operation
+            let op = unsafe { Arc::from_raw(entry.lpOverlapped as *const AsyncOp) };
+
+            let mut bytes_transfered = 0;
+            let state = match unsafe { GetOverlappedResult(op.file_handle, op.overlapped(), &mut bytes_transfered, BOOL(0)) } {
+                Ok(_)    => AsyncIOCompletionState::Completed(bytes_transfered as u64),
+                Err(err) => AsyncIOCompletionState::Unsuccessful(err.code().0),
+            };
+            *op.state.lock() = state;
+            self.in_flight.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+
+    /// Poll the state of an operation, processing any available completions
+    fn poll(&self, op: &AsyncOp) -> AsyncIOCompletionState {
+        let state = *op.state.lock();
+        if let AsyncIOCompletionState::InFlight = state {
+            self.process_completions(0);
+            *op.state.lock()
+        } else {
+            state
+        }
+    }
+
+    /// Wait for an operation to complete, until the timeout has been reached
+    fn wait(&self, op: &AsyncOp, timeout: u32) -> AsyncIOCompletionState {
+        let start = Instant::now();
+        loop {
+            let state = *op.state.lock();
+            let remaining = get_remaining_timeout(start, timeout);
+            match state {
+                AsyncIOCompletionState::InFlight if remaining > 0 => self.process_completions(remaining),
+                AsyncIOCompletionState::InFlight => return self.poll(op),
+                state => return state,
+            }
+        }
     }
 }
 
-pub(crate) struct AsyncReadResult {
-    file_handle     : HANDLE, 
-    buffer          : Vec<u8>,
-    overlapped      : Box<OVERLAPPED>,
-    completion_data : Box<AsyncIOCompletionData>,
+impl Drop for CompletionPort {
+    fn drop(&mut self) {
+        unsafe { _ = CloseHandle(self.handle) };
+    }
 }
 
-impl AsyncReadResult {
-    pub fn poll(&mut self, cx: &mut core::task::Context<'_>) -> Poll<io::Result<Vec<u8>>> {
-        match self.completion_data.state {
-            AsyncIOCompletionState::InFlight => {
-                self.completion_data.waker = Some(cx.waker().clone());
-                Poll::Pending
-            } 
-            AsyncIOCompletionState::Completed(bytes_read) => Poll::Ready(Ok(self.take_buffer_and_exhaust(bytes_read))),
-            AsyncIOCompletionState::Unsuccessful(err)     => Poll::Ready(Err(io::Error::from_raw_os_error(err as i32))),
-            AsyncIOCompletionState::Exhausted             => Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, "Data was already taken from this result")))
+//------------------------------
+
+pub struct AsyncFileHandle {
+    handle:          HANDLE,
+    port:            Arc<CompletionPort>,
+    /// Alignment of the buffer, offset and length of an operation, only set when the file uses unbuffered I/O
+    direct_io_align: Option<u64>,
+}
+
+impl AsyncFileHandle {
+    pub(crate) fn create(
+        path: &Path,
+        open_mode: OpenMode,
+        access_perms: Permission,
+        shared_access_perms: Permission,
+        create_flags: FileCreateFlags,
+        access_flags: FileAccessFlags,
+    ) -> io::Result<(Box<dyn crate::AsyncFileHandle>, PathBuf)> {
+        let (handle, path_buf) = FileHandle::create_raw(path, open_mode, access_perms, shared_access_perms, create_flags, access_flags, false, false)?;
+
+        // Unbuffered I/O needs to be aligned to the sector size, the buffer needs to be aligned to the physical sector size, while the offset and length need to be aligned to the logical sector size
+        let direct_io_align = if access_flags.contains(FileAccessFlags::NoBuffering) {
+            let mut storage_info = FILE_STORAGE_INFO::default();
+            if let Err(err) = unsafe { GetFileInformationByHandleEx(handle, FileStorageInfo, &mut storage_info as *mut _ as *mut _, mem::size_of::<FILE_STORAGE_INFO>() as u32) } {
+                unsafe { _ = CloseHandle(handle) };
+                return Err(io::Error::from_raw_os_error(err.code().0));
+            }
+            Some((storage_info.LogicalBytesPerSector.max(storage_info.PhysicalBytesPerSectorForAtomicity) as u64).max(512).next_power_of_two())
+        } else {
+            None
+        };
+
+        let port = match unsafe { CreateIoCompletionPort(handle, HANDLE::default(), 0, 0) } {
+            Ok(port) => port,
+            Err(err) => {
+                unsafe { _ = CloseHandle(handle) };
+                return Err(io::Error::from_raw_os_error(err.code().0));
+            }
+        };
+
+        Ok((Box::new(AsyncFileHandle{ handle, port: Arc::new(CompletionPort { handle: port, in_flight: AtomicUsize::new(0) }), direct_io_align }), path_buf))
+    }
+}
+
+impl crate::AsyncFileHandle for AsyncFileHandle {
+    fn read_at(&self, offset: u64, bytes_to_read: u64) -> io::Result<FileAsyncReadResult> {
+        if bytes_to_read > u32::MAX as u64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Cannot read more than u32::MAX bytes in a single async operation"));
         }
+
+        // Unbuffered I/O requires the offset and length to be aligned, so read the aligned range containing the requested bytes
+        let (read_offset, read_len, align) = match self.direct_io_align {
+            Some(align) => {
+                let start = offset & !(align - 1);
+                let end = (offset + bytes_to_read + align - 1) & !(align - 1);
+                (start, end - start, align as usize)
+            },
+            None => (offset, bytes_to_read, 1),
+        };
+        if read_len > u32::MAX as u64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Aligned read for unbuffered I/O exceeds u32::MAX bytes"));
+        }
+
+        // `ReadFile` takes an initialized slice, so the buffer is zeroed, it is truncated to the requested bytes that were read once completed.
+        // Over-allocate so the start of the read can be aligned for unbuffered I/O.
+        let mut buffer = vec![0u8; read_len as usize + align - 1];
+        let read_start = buffer.as_ptr().align_offset(align);
+        // The heap allocation does not move when the buffer is moved into the operation
+        let buffer_ptr = unsafe { buffer.as_mut_ptr().add(read_start) };
+        let op = AsyncOp::new(self.handle, read_offset, buffer);
+
+        self.port.issue(&op, |overlapped| unsafe {
+            ReadFile(self.handle, Some(core::slice::from_raw_parts_mut(buffer_ptr, read_len as usize)), None, Some(overlapped))
+        })?;
+        Ok(Box::new(AsyncReadResult {
+            op,
+            port: self.port.clone(),
+            read_start,
+            data_start: read_start + (offset - read_offset) as usize,
+            bytes_to_read: bytes_to_read as usize,
+        }))
     }
 
-    pub fn wait(&mut self, timeout: u32) -> Poll<io::Result<Vec<u8>>> {
-        const SUCCESS: WAIT_EVENT = WAIT_EVENT(ERROR_SUCCESS.0);
-        const TIMEOUT: WAIT_EVENT = WAIT_EVENT(ERROR_TIMEOUT.0);
+    fn write_at(&self, offset: u64, buf: Vec<u8>) -> io::Result<FileAsyncWriteResult> {
+        if buf.len() > u32::MAX as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Cannot write more than u32::MAX bytes in a single async operation"));
+        }
 
-        match unsafe { WaitForSingleObjectEx(self.file_handle, timeout, BOOL(1)) } {
-            SUCCESS |
-            TIMEOUT => {
-                match self.completion_data.state {
-                    AsyncIOCompletionState::InFlight              => Poll::Pending,
-                    AsyncIOCompletionState::Completed(bytes_read) => Poll::Ready(Ok(self.take_buffer_and_exhaust(bytes_read))),
-                    AsyncIOCompletionState::Unsuccessful(err)     => Poll::Ready(Err(io::Error::from_raw_os_error(err as i32))),
-                    AsyncIOCompletionState::Exhausted             => Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, "Data was already taken from this result")))
+        let buffer_len = buf.len();
+        // Unbuffered I/O requires the buffer, offset and length to be aligned. Only the buffer can be fixed up, as the offset and length would need the surrounding data to be read first
+        let (buf, data_start) = match self.direct_io_align {
+            Some(align) => {
+                if offset % align != 0 || buf.len() as u64 % align != 0 {
+                    return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Writes to a file opened with `NoBuffering` need an offset and length aligned to {align} bytes")));
                 }
+                align_write_buffer(buf, align as usize)
             },
-            res => Poll::Ready(Err(io::Error::from_raw_os_error(res.0 as i32))),
+            None => (buf, 0),
+        };
+        // The heap allocation does not move when the buffer is moved into the operation
+        let buffer_ptr = unsafe { buf.as_ptr().add(data_start) };
+        let op = AsyncOp::new(self.handle, offset, buf);
+
+        self.port.issue(&op, |overlapped| unsafe {
+            WriteFile(self.handle, Some(core::slice::from_raw_parts(buffer_ptr, buffer_len)), None, Some(overlapped))
+        })?;
+        Ok(Box::new(AsyncWriteResult { op, port: self.port.clone() }))
+    }
+
+    fn cancel_all_async_io(&self) -> io::Result<()> {
+        unsafe { CancelIoEx(self.handle, None) }.map_err(|err| io::Error::from_raw_os_error(err.code().0))
+    }
+
+    fn len(&self) -> io::Result<u64> {
+        let mut size = 0;
+        unsafe { GetFileSizeEx(self.handle, &mut size) }
+            .map_or_else(|err| Err(io::Error::from_raw_os_error(err.code().0)), |_| Ok(size as u64))
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        unsafe { FlushFileBuffers(self.handle) }.map_err(|err| io::Error::from_raw_os_error(err.code().0))
+    }
+}
+
+/// Get a buffer with the data starting at an aligned address, and the index of the data in it, the data is copied to an aligned bounce buffer when it isn't aligned yet
+fn align_write_buffer(buf: Vec<u8>, align: usize) -> (Vec<u8>, usize) {
+    if buf.as_ptr().align_offset(align) == 0 {
+        return (buf, 0);
+    }
+
+    // Over-allocate so the start of the data can be aligned, the buffer is never reallocated, as it has enough capacity
+    let mut bounce = Vec::with_capacity(buf.len() + align - 1);
+    let data_start = bounce.as_ptr().align_offset(align);
+    bounce.resize(data_start, 0);
+    bounce.extend_from_slice(&buf);
+    (bounce, data_start)
+}
+
+impl Drop for AsyncFileHandle {
+    fn drop(&mut self) {
+        if !self.handle.is_invalid() {
+            _ = unsafe { CancelIoEx(self.handle, None) };
+            // All operations need to be dequeued, otherwise their memory would be leaked
+            while self.port.in_flight.load(Ordering::Acquire) != 0 {
+                self.port.process_completions(1);
+            }
+
+            unsafe { _ = CloseHandle(self.handle); }
         }
     }
+}
 
-    pub fn cancel(&mut self) -> io::Result<()> {
-        unsafe { CancelIoEx(self.file_handle, Some(&*self.overlapped)) }
-            .map_err(|err| io::Error::from_raw_os_error(err.code().0))
+//--------------------------------------------------------------
+
+pub(crate) struct AsyncReadResult {
+    op:            Arc<AsyncOp>,
+    port:          Arc<CompletionPort>,
+    /// Index in the buffer the read started writing to
+    read_start:    usize,
+    /// Index in the buffer of the first requested byte, this is after `read_start` when an aligned read was done
+    data_start:    usize,
+    bytes_to_read: usize,
+}
+
+impl AsyncReadResult {
+    fn handle_state(&mut self, state: AsyncIOCompletionState) -> Poll<io::Result<Vec<u8>>> {
+        match state {
+            AsyncIOCompletionState::InFlight              => Poll::Pending,
+            AsyncIOCompletionState::Completed(bytes_read) => Poll::Ready(Ok(self.take_buffer_and_exhaust(bytes_read))),
+            AsyncIOCompletionState::Unsuccessful(err)     => Poll::Ready(Err(io::Error::from_raw_os_error(err))),
+            AsyncIOCompletionState::Exhausted             => Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, "Data was already taken from this result")))
+        }
     }
 
     fn take_buffer_and_exhaust(&mut self, bytes_read: u64) -> Vec<u8> {
-        self.completion_data.state = AsyncIOCompletionState::Exhausted;
-        let mut buffer = mem::take(&mut self.buffer);
-        unsafe { buffer.set_len(bytes_read as usize) };
+        *self.op.state.lock() = AsyncIOCompletionState::Exhausted;
+        let mut buffer = mem::take(&mut *self.op.buffer.lock());
+
+        // The read may not cover all requested bytes when the end of the file was reached
+        let read_end = self.read_start + bytes_read as usize;
+        let len = read_end.saturating_sub(self.data_start).min(self.bytes_to_read);
+        unsafe { ptr::copy(buffer.as_ptr().add(self.data_start), buffer.as_mut_ptr(), len) };
+        buffer.truncate(len);
         buffer
     }
 }
 
+impl io::AsyncIOResult for AsyncReadResult {
+    type Output = io::Result<Vec<u8>>;
+
+    fn poll(&mut self) -> Poll<Self::Output> {
+        let state = self.port.poll(&self.op);
+        self.handle_state(state)
+    }
+
+    fn wait(&mut self, timeout: u32) -> Poll<Self::Output> {
+        let state = self.port.wait(&self.op, timeout);
+        self.handle_state(state)
+    }
+
+    fn cancel(&mut self) -> io::Result<()> {
+        unsafe { CancelIoEx(self.op.file_handle, Some(self.op.overlapped() as *const _)) }
+            .map_err(|err| io::Error::from_raw_os_error(err.code().0))
+    }
+}
+
 pub(crate) struct AsyncWriteResult {
-    file_handle : HANDLE,
-    _buffer     : Vec<u8>,
-    overlapped  : Box<OVERLAPPED>,
-    completion_data : Box<AsyncIOCompletionData>,
+    op:   Arc<AsyncOp>,
+    port: Arc<CompletionPort>,
 }
 
 impl AsyncWriteResult {
-    pub fn poll(&mut self, cx: &mut core::task::Context<'_>) -> Poll<io::Result<u64>> {
-        match self.completion_data.state {
-            AsyncIOCompletionState::InFlight => {
-                self.completion_data.waker = Some(cx.waker().clone());
-                Poll::Pending
-            } 
-            AsyncIOCompletionState::Completed(bytes_read) => Poll::Ready(Ok(bytes_read)),
-            AsyncIOCompletionState::Unsuccessful(err) => Poll::Ready(Err(io::Error::from_raw_os_error(err as i32))),
-            AsyncIOCompletionState::Exhausted => Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, "Data was already taken from this result")))
+    fn handle_state(state: AsyncIOCompletionState) -> Poll<io::Result<u64>> {
+        match state {
+            AsyncIOCompletionState::InFlight                 => Poll::Pending,
+            AsyncIOCompletionState::Completed(bytes_written) => Poll::Ready(Ok(bytes_written)),
+            AsyncIOCompletionState::Unsuccessful(err)        => Poll::Ready(Err(io::Error::from_raw_os_error(err))),
+            AsyncIOCompletionState::Exhausted                => Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, "Data was already taken from this result")))
         }
     }
+}
 
-    pub fn wait(&mut self, timeout: u32) -> Poll<io::Result<u64>> {
-        const SUCCESS: WAIT_EVENT = WAIT_EVENT(ERROR_SUCCESS.0);
-        const TIMEOUT: WAIT_EVENT = WAIT_EVENT(ERROR_TIMEOUT.0);
+impl io::AsyncIOResult for AsyncWriteResult {
+    type Output = io::Result<u64>;
 
-        match unsafe { WaitForSingleObjectEx(self.file_handle, timeout, BOOL(1)) } {
-            SUCCESS |
-            TIMEOUT => {
-                match self.completion_data.state {
-                    AsyncIOCompletionState::InFlight              => Poll::Pending,
-                    AsyncIOCompletionState::Completed(bytes_read) => Poll::Ready(Ok(bytes_read)),
-                    AsyncIOCompletionState::Unsuccessful(err)     => Poll::Ready(Err(io::Error::from_raw_os_error(err as i32))),
-                    AsyncIOCompletionState::Exhausted             => Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, "Data was already taken from this result")))
-                }
-            },
-            res => Poll::Ready(Err(io::Error::from_raw_os_error(res.0 as i32))),
-        }
+    fn poll(&mut self) -> Poll<Self::Output> {
+        Self::handle_state(self.port.poll(&self.op))
     }
 
-    pub fn cancel(&mut self) -> io::Result<()> {
-        unsafe { CancelIoEx(self.file_handle, Some(&*self.overlapped)) }
+    fn wait(&mut self, timeout: u32) -> Poll<Self::Output> {
+        Self::handle_state(self.port.wait(&self.op, timeout))
+    }
+
+    fn cancel(&mut self) -> io::Result<()> {
+        unsafe { CancelIoEx(self.op.file_handle, Some(self.op.overlapped() as *const _)) }
             .map_err(|err| io::Error::from_raw_os_error(err.code().0))
     }
-}
\ No newline at end of file
+}
@@ -34,7 +34,8 @@ unsafe extern "system" fn io_completion_callback(err_code: u32, number_of_bytes_
             let notify_info = unsafe { &*(completion_data.buffer.as_ptr().add(offset) as *const FILE_NOTIFY_EXTENDED_INFORMATION) };
             
             let path = unsafe {
-                let utf16_slice = std::slice::from_raw_parts(notify_info.FileName.as_ptr(), notify_info.FileNameLength as usize);
+                // `FileNameLength` is in bytes, not in characters
+                let utf16_slice = std::slice::from_raw_parts(notify_info.FileName.as_ptr(), notify_info.FileNameLength as usize / 2);
                 PathBuf::from_raw(String::from_utf16_lossy(utf16_slice))
             };
 
@@ -149,9 +150,9 @@ impl FileWatcher {
             PCSTR(path.as_ptr()),
             FILE_LIST_DIRECTORY.0,
             // Set all shared access value, or we won't be able to read/write/delete directories/files in the directory
-            FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_WRITE,
+            FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
             None,
-            OPEN_ALWAYS,
+            OPEN_EXISTING,
             // Backup semantics are required to get a handle to a directory
             FILE_FLAG_BACKUP_SEMANTICS | FILE_FLAG_OVERLAPPED,
             None
@@ -205,6 +206,9 @@ impl FileWatcher {
 
 impl FileWatcherHandle for FileWatcher {
     fn poll(&self) -> Poll<FileChangeInfo> {
+        // The completion routine is only called when the thread that started the watch is in an alertable state
+        unsafe { SleepEx(0, true) };
+
         match self.completion_data.changes.lock().pop_front() {
             Some(change) => Poll::Ready(change),
             None => Poll::Pending,
@@ -233,10 +237,10 @@ impl Drop for FileWatcher {
 
 fn get_file_notify_change_and_info_class(filter: FileWatcherFilter) -> FILE_NOTIFY_CHANGE {
     let mut notify_filter = FILE_NOTIFY_CHANGE(0);
-    if filter.contains(FileWatcherFilter::FileAdded | FileWatcherFilter::FileDeleted | FileWatcherFilter::FileRename) {
+    if filter.intersects(FileWatcherFilter::FileAdded | FileWatcherFilter::FileDeleted | FileWatcherFilter::FileRename) {
         notify_filter |= FILE_NOTIFY_CHANGE_FILE_NAME;
     }
-    if filter.contains(FileWatcherFilter::DirAdded | FileWatcherFilter::DirDeleted | FileWatcherFilter::DirRename) {
+    if filter.intersects(FileWatcherFilter::DirAdded | FileWatcherFilter::DirDeleted | FileWatcherFilter::DirRename) {
         notify_filter |= FILE_NOTIFY_CHANGE_DIR_NAME;
     }
     if filter.contains(FileWatcherFilter::Attributes) {
@@ -14,7 +14,8 @@ pub fn hard_link(source: &Path, dest: &Path) -> io::Result<()> {
     let source = source.to_path_buf();
     let dest = dest.to_path_buf();
 
-    unsafe { CreateHardLinkA(PCSTR(source.as_ptr()), PCSTR(dest.as_ptr()), None) }
+    // The new link is the first argument, the existing file the second
+    unsafe { CreateHardLinkA(PCSTR(dest.as_ptr()), PCSTR(source.as_ptr()), None) }
         .map_err(|err| io::Error::from_raw_os_error(err.code().0))
 }
 
@@ -23,7 +24,7 @@ pub fn symlink_file(source: &Path, dest: &Path) -> io::Result<()> {
     let source = source.to_path_buf();
     let dest = dest.to_path_buf();
 
-    let res = unsafe { CreateSymbolicLinkA(PCSTR(source.as_ptr()), PCSTR(dest.as_ptr()), SYMBOLIC_LINK_FLAGS(0)) }.as_bool();
+    let res = unsafe { CreateSymbolicLinkA(PCSTR(dest.as_ptr()), PCSTR(source.as_ptr()), SYMBOLIC_LINK_FLAGS(0)) }.as_bool();
     if res {
         Ok(())
     } else {
@@ -36,7 +37,7 @@ pub fn symlink_dir(source: &Path, dest: &Path) -> io::Result<()> {
     let source = source.to_path_buf();
     let dest = dest.to_path_buf();
 
-    let res = unsafe { CreateSymbolicLinkA(PCSTR(source.as_ptr()), PCSTR(dest.as_ptr()), SYMBOLIC_LINK_FLAG_DIRECTORY).as_bool() };
+    let res = unsafe { CreateSymbolicLinkA(PCSTR(dest.as_ptr()), PCSTR(source.as_ptr()), SYMBOLIC_LINK_FLAG_DIRECTORY).as_bool() };
     if res {
         Ok(())
     } else {
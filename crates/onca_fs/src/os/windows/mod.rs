@@ -21,6 +21,7 @@ use windows::Win32::{
         FILE_ATTRIBUTE_VIRTUAL,
         FILE_ATTRIBUTE_RECALL_ON_OPEN,
         FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS,
+        GetTempPathA,
     },
     System::Environment::GetCurrentDirectoryA,
 };
@@ -28,6 +29,7 @@ use windows::Win32::{
 pub(crate) mod entry;
 pub(crate) mod drive_volume;
 pub(crate) mod file;
+pub(crate) mod file_async;
 pub(crate) mod directory;
 pub(crate) mod link;
 pub(crate) mod file_watcher;
@@ -49,8 +51,36 @@ pub(crate) fn get_working_dir() -> io::Result<PathBuf> {
     }
 }
 
+pub(crate) fn get_temp_dir() -> io::Result<PathBuf> {
+    let expected_len = unsafe { GetTempPathA(None) } as usize;
+    if expected_len == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut buf = vec![0; expected_len];
+
+    // `GetTempPathA` returns the length of the string without the null-terminator
+    let len = unsafe { GetTempPathA(Some(&mut *buf)) } as usize;
+    if len == 0 || len >= expected_len {
+        return Err(io::Error::last_os_error());
+    }
+    PathBuf::from_utf8_lossy(&buf[..len]).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid temporary directory path"))
+}
+
 //------------------------------------------------------------------------------------------------------------------------------
 
+/// Convert an error returned by a Win32 function to an `io::Error` with the original Win32 error code.
+///
+/// The `windows` crate wraps Win32 error codes in an `HRESULT`, which would not match any `ERROR_*` code.
+fn win32_io_error(err: windows::core::Error) -> io::Error {
+    let hr = err.code().0 as u32;
+    // `HRESULT_FROM_WIN32` stores the error code in the low 16 bits, with `FACILITY_WIN32` as the facility
+    if hr & 0xFFFF_0000 == 0x8007_0000 {
+        io::Error::from_raw_os_error((hr & 0xFFFF) as i32)
+    } else {
+        io::Error::from_raw_os_error(err.code().0)
+    }
+}
+
 fn high_low_to_u64(high: u32, low: u32) -> u64 {
     ((high as u64) << 32) | low as u64
 }
@@ -28,6 +28,30 @@ fn is_sep(c: char) -> bool {
     c == '/' || c == '\\'
 }
 
+/// Are paths on the current platform case-sensitive?
+/// 
+/// Windows and macOS file systems are case-insensitive by default, while other platforms are case-sensitive.
+pub const PLATFORM_CASE_SENSITIVE: bool = cfg!(not(any(windows, target_os = "macos")));
+
+fn str_eq_ignore_case(a: &str, b: &str) -> bool {
+    a.chars().flat_map(char::to_lowercase).eq(b.chars().flat_map(char::to_lowercase))
+}
+
+fn component_eq_ignore_case(a: Component<'_>, b: Component<'_>) -> bool {
+    match (a, b) {
+        (Component::Root(a), Component::Root(b)) => match (a.kind(), b.kind()) {
+            (Root::Drive(a), Root::Drive(b)) => a.eq_ignore_ascii_case(&b),
+            (Root::WinShare { server: a_server, share: a_share }, Root::WinShare { server: b_server, share: b_share }) =>
+                str_eq_ignore_case(a_server, b_server) && str_eq_ignore_case(a_share, b_share),
+            (a, b) => a == b,
+        },
+        (Component::Normal(a), Component::Normal(b))     => str_eq_ignore_case(a, b),
+        (Component::Wildcard(a), Component::Wildcard(b)) => str_eq_ignore_case(a, b),
+        (Component::VFS(a), Component::VFS(b))           => str_eq_ignore_case(a.as_str(), b.as_str()),
+        (a, b) => a == b,
+    }
+}
+
 fn iter_after<'a, 'b, I, J>(mut iter: I, mut prefix: J) -> Option<I> where
     I: Iterator<Item = Component<'a>> + Clone,
     J: Iterator<Item = Component<'b>>
@@ -927,7 +951,8 @@ impl PathBuf {
 
     /// Normalize the path (in this case, a fully qualified path will be returned).
     /// 
-    /// This cannot handle paths that are handled by the VFS
+    /// This cannot handle paths that are handled by the VFS.
+    /// To only resolve `.` and `..` components, without making the path absolute, use [`Path::normalize_lexically`].
     /// 
     /// # Error
     /// 
@@ -1437,6 +1462,135 @@ impl Path {
         iter_after(self.components().rev(), child.components().rev()).is_some()
     }
 
+    /// Compare the path with `other` component-wise, ignoring case.
+    #[must_use]
+    pub fn eq_ignore_case<P: AsRef<Path>>(&self, other: P) -> bool {
+        self._eq_ignore_case(other.as_ref())
+    }
+
+    fn _eq_ignore_case(&self, other: &Path) -> bool {
+        let mut comps = self.components();
+        let mut other_comps = other.components();
+        loop {
+            match (comps.next(), other_comps.next()) {
+                (Some(a), Some(b)) if component_eq_ignore_case(a, b) => (),
+                (None, None) => return true,
+                _ => return false,
+            }
+        }
+    }
+
+    /// Compare the path with `other` component-wise, following the case-sensitivity of the current platform.
+    /// 
+    /// See [`PLATFORM_CASE_SENSITIVE`] for more info.
+    #[must_use]
+    pub fn eq_platform<P: AsRef<Path>>(&self, other: P) -> bool {
+        if PLATFORM_CASE_SENSITIVE {
+            self == other.as_ref()
+        } else {
+            self._eq_ignore_case(other.as_ref())
+        }
+    }
+
+    /// Canonicalize the path, i.e. get the fully qualified path with all symbolic links resolved.
+    /// 
+    /// This cannot handle paths that are handled by the VFS.
+    /// 
+    /// # Error
+    /// 
+    /// Returns an error if the path does not exist, or the fully qualified path could not be retrieved.
+    pub fn canonicalize(&self) -> io::Result<PathBuf> {
+        Entry::new(self)?.fully_qualified_path()
+    }
+
+    /// Normalize the path lexically, resolving any `.` and `..` components without accessing the file system.
+    /// 
+    /// Unlike [`PathBuf::normalize`], the path is not made absolute, so any leading `..` components of a relative path are kept.
+    /// A `..` directly following a root is removed, as the parent of a root is the root itself.
+    /// 
+    /// # Note
+    /// 
+    /// As symbolic links are not resolved, the resulting path may point to a different location if the path contains a symbolic link followed by `..`.
+    #[must_use]
+    pub fn normalize_lexically(&self) -> PathBuf {
+        let mut comps = Vec::new();
+        for comp in self.components() {
+            match comp {
+                Component::CurDir => (),
+                Component::ParentDir => match comps.last().copied() {
+                    Some(Component::Normal(_) | Component::Wildcard(_)) => _ = comps.pop(),
+                    Some(Component::Root(_)) => (),
+                    // A macro can expand to multiple components, so it cannot be removed
+                    _ => comps.push(comp),
+                },
+                _ => comps.push(comp),
+            }
+        }
+
+        if comps.is_empty() {
+            return PathBuf(".".to_string());
+        }
+
+        let mut buf = String::with_capacity(self.0.len());
+        for comp in comps {
+            if !buf.is_empty() && !buf.ends_with(is_sep) {
+                buf.push('/');
+            }
+            buf.push_str(comp.as_str());
+            // The raw root does not always include the trailing separator
+            if matches!(comp, Component::Root(_)) && !buf.ends_with(is_sep) {
+                buf.push('/');
+            }
+        }
+        PathBuf(buf)
+    }
+
+    /// Get the path relative to `base`, i.e. the path that when joined onto `base`, points to the same location as `self`.
+    /// 
+    /// Both paths are normalized lexically, see [`Path::normalize_lexically`], and are compared following the case-sensitivity of the current platform.
+    /// 
+    /// Returns [`None`] if only one of the paths has a root, when the roots differ,
+    /// or if `base` contains a `..` or macro that is not shared with `self`, as it is unknown which directory it refers to.
+    #[must_use]
+    pub fn relative_to<P: AsRef<Path>>(&self, base: P) -> Option<PathBuf> {
+        self._relative_to(base.as_ref())
+    }
+
+    fn _relative_to(&self, base: &Path) -> Option<PathBuf> {
+        let path = self.normalize_lexically();
+        let base = base.normalize_lexically();
+
+        let mut comps = path.components().peekable();
+        let mut base_comps = base.components().peekable();
+        while let (Some(&a), Some(&b)) = (comps.peek(), base_comps.peek()) {
+            let is_eq = if PLATFORM_CASE_SENSITIVE { a == b } else { component_eq_ignore_case(a, b) };
+            if !is_eq {
+                break;
+            }
+            comps.next();
+            base_comps.next();
+        }
+
+        if matches!(comps.peek(), Some(Component::Root(_))) || matches!(base_comps.peek(), Some(Component::Root(_))) {
+            return None;
+        }
+
+        let mut buf = PathBuf::new();
+        for comp in base_comps {
+            match comp {
+                Component::Normal(_) | Component::Wildcard(_) => buf.push(Component::ParentDir),
+                Component::CurDir => (),
+                _ => return None,
+            }
+        }
+        buf.extend(comps.filter(|comp| *comp != Component::CurDir));
+
+        if buf.as_str().is_empty() {
+            buf = PathBuf(".".to_string());
+        }
+        Some(buf)
+    }
+
     /// Extract teh stem (non-extension) potion of [`Path::filename`].
     /// 
     /// The stem is:
@@ -2270,4 +2424,39 @@ mod test {
         let mut pathbuf = PathBuf::from_path(&path);
         pathbuf.set_extension("png");
     }
+
+    #[test]
+    fn normalize_lexically() {
+        let normalize = |s: &str| Path::new(s).unwrap().normalize_lexically();
+
+        assert_eq!(normalize("a/./b/../c").as_str(), "a/c");
+        assert_eq!(normalize("../a/../../b").as_str(), "../../b");
+        assert_eq!(normalize("a/..").as_str(), ".");
+        assert_eq!(normalize("C:/..").as_str(), "C:/");
+        assert_eq!(normalize("/a/b/../../..").as_str(), "/");
+        assert_eq!(normalize("//server/share/a/../b").as_str(), "//server/share/b");
+    }
+
+    #[test]
+    fn relative_to() {
+        let relative = |path: &str, base: &str| Path::new(path).unwrap().relative_to(Path::new(base).unwrap());
+
+        assert_eq!(relative("C:/a/b/c", "C:/a/d").unwrap().as_str(), "../b/c");
+        assert_eq!(relative("a/b", "a/./b/c/..").unwrap().as_str(), ".");
+        assert_eq!(relative("a/b/c", "a").unwrap().as_str(), "b/c");
+        assert_eq!(relative("../a", "b").unwrap().as_str(), "../../a");
+        assert!(relative("/a", "C:/a").is_none());
+        assert!(relative("a", "/a").is_none());
+        assert!(relative("a", "../b").is_none());
+    }
+
+    #[test]
+    fn eq_ignore_case() {
+        let path = Path::new("C:/Assets/Textures/Grass.png").unwrap();
+        assert!(path.eq_ignore_case(Path::new("c:/assets/textures/GRASS.PNG").unwrap()));
+        assert!(path.eq_ignore_case(Path::new("C:\\Assets\\Textures\\Grass.png").unwrap()));
+        assert!(!path.eq_ignore_case(Path::new("C:/Assets/Textures").unwrap()));
+        assert!(!path.eq_ignore_case(Path::new("D:/Assets/Textures/Grass.png").unwrap()));
+        assert_eq!(path.eq_platform(Path::new("c:/assets/textures/grass.png").unwrap()), !PLATFORM_CASE_SENSITIVE);
+    }
 }
\ No newline at end of file
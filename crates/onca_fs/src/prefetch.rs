@@ -0,0 +1,502 @@
+use std::{
+    sync::mpsc::{self, Receiver, Sender, SyncSender, TryRecvError},
+    thread::{self, JoinHandle},
+};
+
+use onca_common::io::{self, Read, Seek};
+
+use crate::{File, FileAccessFlags, Path, PathBuf, Permission};
+
+/// Default size of the chunks read by a [`PrefetchReader`] (1 MiB).
+pub const DEFAULT_PREFETCH_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Command sent from the reader to the prefetch thread.
+enum Command {
+    /// Continue reading at the given offset, tagging any following chunk with the generation.
+    Seek { offset: u64, generation: u64 },
+    /// Return a consumed buffer, so it can be reused for the next chunk.
+    Recycle(Vec<u8>),
+}
+
+/// Chunk read by the prefetch thread.
+struct Chunk {
+    generation: u64,
+    offset:     u64,
+    data:       io::Result<Vec<u8>>,
+}
+
+/// Reader that prefetches the content of a file on a helper thread, while the previous chunk is being consumed.
+///
+/// The file is read sequentially in chunks, the helper thread reads the next chunk while the current one is being consumed,
+/// and the OS is hinted to read ahead the chunk after that, so the drive is kept busy at all times.
+/// Windows has no read-ahead hint for a range of a file, so there the reader relies on the cache manager's own read-ahead,
+/// which is why the file should be opened with [`FileAccessFlags::SequentialAccess`].
+/// This is mainly useful for loading large assets from drives with a high latency, like spinning disks or network shares,
+/// for which a [`BufReader`](std::io::BufReader) would wait on the drive each time its buffer runs empty.
+///
+/// Seeking within the current chunk is cheap, while seeking outside of it discards any prefetched data.
+///
+/// # Note
+///
+/// The file should not be modified while it is being read, as the size of the file is only queried when the reader is created.
+pub struct PrefetchReader {
+    path:       PathBuf,
+    len:        u64,
+    /// Current chunk.
+    buf:        Vec<u8>,
+    /// Offset of the current chunk in the file.
+    buf_offset: u64,
+    /// Position within the current chunk.
+    buf_pos:    usize,
+    /// Generation of the chunks that are expected, any chunk with an older generation was prefetched before the last seek.
+    generation: u64,
+    /// Whether the end of the file was reached, in which case the prefetch thread waits for a seek.
+    at_end:     bool,
+    commands:   Sender<Command>,
+    chunks:     Receiver<Chunk>,
+    thread:     Option<JoinHandle<()>>,
+}
+
+impl PrefetchReader {
+    /// Open the file at `path` for prefetched reading, using [`DEFAULT_PREFETCH_CHUNK_SIZE`].
+    ///
+    /// # Error
+    ///
+    /// Returns an error if the file could not be opened or the prefetch thread could not be started.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path, Permission::Read, Permission::Read, FileAccessFlags::SequentialAccess)?;
+        Self::new(file)
+    }
+
+    /// Create a prefetch reader for `file`, using [`DEFAULT_PREFETCH_CHUNK_SIZE`].
+    ///
+    /// # Error
+    ///
+    /// Returns an error if the prefetch thread could not be started.
+    pub fn new(file: File) -> io::Result<Self> {
+        Self::with_chunk_size(file, DEFAULT_PREFETCH_CHUNK_SIZE)
+    }
+
+    /// Create a prefetch reader for `file`, which reads the file in chunks of `chunk_size` bytes.
+    ///
+    /// Reading starts at the current cursor of the file.
+    /// For the best performance, the file should be opened with [`FileAccessFlags::SequentialAccess`].
+    ///
+    /// # Error
+    ///
+    /// Returns an error if the prefetch thread could not be started.
+    pub fn with_chunk_size(mut file: File, chunk_size: usize) -> io::Result<Self> {
+        let chunk_size = chunk_size.max(1);
+        let path = file.path().to_path_buf();
+        let len = file.get_metadata()?.file_size;
+        let offset = file.stream_position()?;
+
+        // Get the OS started on the first chunks, before the thread is even running
+        _ = file.read_ahead(offset, chunk_size as u64 * 2);
+
+        let (commands, command_receiver) = mpsc::channel();
+        // The prefetch thread blocks on sending a chunk until the previous one is consumed, so at most 2 chunks are in flight at any time
+        let (chunk_sender, chunks) = mpsc::sync_channel(0);
+        let thread = thread::Builder::new()
+            .name("onca_fs prefetch".to_string())
+            .spawn(move || prefetch_thread(file, offset, chunk_size, command_receiver, chunk_sender))?;
+
+        Ok(Self {
+            path,
+            len,
+            buf: Vec::new(),
+            buf_offset: offset,
+            buf_pos: 0,
+            generation: 0,
+            at_end: false,
+            commands,
+            chunks,
+            thread: Some(thread),
+        })
+    }
+
+    /// Get the path of the file that is being read.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the size of the file, at the time the reader was created.
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Check if the file is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Get the current position in the file.
+    #[must_use]
+    pub fn position(&self) -> u64 {
+        self.buf_offset + self.buf_pos as u64
+    }
+
+    /// Get the prefetched data that has not been consumed yet, without waiting for the next chunk.
+    #[must_use]
+    pub fn buffer(&self) -> &[u8] {
+        &self.buf[self.buf_pos..]
+    }
+
+    /// Replace the current chunk with the next chunk of the current generation, returns `false` if the end of the file was reached.
+    fn next_chunk(&mut self) -> io::Result<bool> {
+        if self.at_end {
+            return Ok(false);
+        }
+
+        let next_offset = self.position();
+        self.recycle();
+        self.buf_offset = next_offset;
+        loop {
+            let chunk = self.chunks.recv().map_err(|_| io::Error::other("The prefetch thread stopped unexpectedly"))?;
+            if chunk.generation != self.generation {
+                if let Ok(data) = chunk.data {
+                    _ = self.commands.send(Command::Recycle(data));
+                }
+                continue;
+            }
+
+            match chunk.data {
+                Ok(data) => {
+                    self.at_end = data.is_empty();
+                    self.buf = data;
+                    self.buf_offset = chunk.offset;
+                    return Ok(!self.at_end);
+                },
+                Err(err) => {
+                    // The thread waits after an error, so restart it at the same position, allowing the caller to retry the read
+                    self.restart_at(next_offset);
+                    return Err(err);
+                },
+            }
+        }
+    }
+
+    /// Discard all prefetched data and let the prefetch thread continue at `offset`.
+    fn restart_at(&mut self, offset: u64) {
+        self.recycle();
+        self.generation += 1;
+        self.buf_offset = offset;
+        self.at_end = false;
+        _ = self.commands.send(Command::Seek { offset, generation: self.generation });
+    }
+
+    /// Return the current chunk to the prefetch thread.
+    fn recycle(&mut self) {
+        let buf = std::mem::take(&mut self.buf);
+        if buf.capacity() != 0 {
+            _ = self.commands.send(Command::Recycle(buf));
+        }
+        self.buf_pos = 0;
+    }
+}
+
+impl io::Read for PrefetchReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.buf_pos == self.buf.len() && !self.next_chunk()? {
+            return Ok(0);
+        }
+
+        let available = &self.buf[self.buf_pos..];
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.buf_pos += len;
+        Ok(len)
+    }
+}
+
+impl io::BufRead for PrefetchReader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.buf_pos == self.buf.len() {
+            self.next_chunk()?;
+        }
+        Ok(&self.buf[self.buf_pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.buf_pos = (self.buf_pos + amt).min(self.buf.len());
+    }
+}
+
+impl io::Seek for PrefetchReader {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            io::SeekFrom::Start(offset) => Some(offset),
+            io::SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            io::SeekFrom::Current(offset) => self.position().checked_add_signed(offset),
+        };
+        let Some(target) = target else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Cannot seek before the start of the file"));
+        };
+
+        // Seeking within the current chunk does not require any data to be read again
+        if target >= self.buf_offset && target <= self.buf_offset + self.buf.len() as u64 {
+            self.buf_pos = (target - self.buf_offset) as usize;
+        } else {
+            self.restart_at(target);
+        }
+        Ok(target)
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.position())
+    }
+}
+
+impl Drop for PrefetchReader {
+    fn drop(&mut self) {
+        // Dropping the channels wakes up the thread, whether it's waiting to send a chunk or waiting for a command
+        let (commands, _) = mpsc::channel();
+        let (_, chunks) = mpsc::sync_channel(0);
+        drop(std::mem::replace(&mut self.commands, commands));
+        drop(std::mem::replace(&mut self.chunks, chunks));
+        if let Some(thread) = self.thread.take() {
+            _ = thread.join();
+        }
+    }
+}
+
+//------------------------------
+
+fn prefetch_thread(mut file: File, mut offset: u64, chunk_size: usize, commands: Receiver<Command>, chunks: SyncSender<Chunk>) {
+    let mut generation = 0;
+    let mut free_bufs = Vec::new();
+    let mut seek_to = None;
+    let mut waiting = false;
+
+    loop {
+        // Once the end of the file is reached or an error occured, nothing is read until the reader seeks
+        let command = if waiting {
+            match commands.recv() {
+                Ok(command) => Some(command),
+                Err(_) => return,
+            }
+        } else {
+            match commands.try_recv() {
+                Ok(command) => Some(command),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => return,
+            }
+        };
+
+        match command {
+            Some(Command::Seek { offset: new_offset, generation: new_generation }) => {
+                offset = new_offset;
+                generation = new_generation;
+                seek_to = Some(new_offset);
+                waiting = false;
+                continue;
+            },
+            Some(Command::Recycle(buf)) => {
+                free_bufs.push(buf);
+                continue;
+            },
+            None => (),
+        }
+
+        let data = match seek_to.take() {
+            Some(seek_offset) => file.seek(io::SeekFrom::Start(seek_offset)).and_then(|_| {
+                _ = file.read_ahead(seek_offset, chunk_size as u64 * 2);
+                read_chunk(&mut file, free_bufs.pop(), chunk_size)
+            }),
+            None => read_chunk(&mut file, free_bufs.pop(), chunk_size),
+        };
+
+        let read = match &data {
+            Ok(data) if !data.is_empty() => {
+                // The next chunk is being read by this thread, so the OS can already start on the one after that
+                _ = file.read_ahead(offset + data.len() as u64 + chunk_size as u64, chunk_size as u64);
+                data.len()
+            },
+            _ => {
+                waiting = true;
+                0
+            },
+        };
+
+        if chunks.send(Chunk { generation, offset, data }).is_err() {
+            return;
+        }
+        offset += read as u64;
+    }
+}
+
+/// Read a full chunk from the file, only returning less data when the end of the file is reached.
+fn read_chunk(file: &mut File, buf: Option<Vec<u8>>, chunk_size: usize) -> io::Result<Vec<u8>> {
+    let mut buf = buf.unwrap_or_default();
+    buf.resize(chunk_size, 0);
+
+    let mut len = 0;
+    while len < chunk_size {
+        match file.read(&mut buf[len..]) {
+            Ok(0) => break,
+            Ok(read) => len += read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    buf.truncate(len);
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use onca_common::io::{BufRead, Write};
+
+    use super::*;
+    use crate::{FileCreateFlags, OpenMode};
+
+    const CHUNK_SIZE: usize = 64;
+
+    fn create_file(path: &Path, len: usize) -> Vec<u8> {
+        let content = (0..len).map(|idx| (idx % 251) as u8).collect::<Vec<_>>();
+        let mut file = File::create(path, OpenMode::CreateAlways, Permission::Write, Permission::None, FileCreateFlags::None, FileAccessFlags::None).unwrap();
+        file.write_all(&content).unwrap();
+        content
+    }
+
+    fn open_reader(path: &Path) -> PrefetchReader {
+        let file = File::open(path, Permission::Read, Permission::Read, FileAccessFlags::SequentialAccess).unwrap();
+        PrefetchReader::with_chunk_size(file, CHUNK_SIZE).unwrap()
+    }
+
+    fn read_exact_at(reader: &mut PrefetchReader, offset: u64, len: usize) -> Vec<u8> {
+        assert_eq!(reader.seek(io::SeekFrom::Start(offset)).unwrap(), offset);
+        let mut buf = vec![0; len];
+        reader.read_exact(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn sequential_read() {
+        let dir = crate::tempdir().unwrap();
+        let path = dir.path().join("sequential.bin");
+        let content = create_file(&path, CHUNK_SIZE * 10 + 13);
+
+        let mut reader = open_reader(&path);
+        assert_eq!(reader.len(), content.len() as u64);
+
+        // Odd sized reads, so reads regularly cross chunk boundaries
+        let mut read = Vec::new();
+        let mut buf = [0; 7];
+        loop {
+            let len = reader.read(&mut buf).unwrap();
+            if len == 0 {
+                break;
+            }
+            assert!(len <= CHUNK_SIZE);
+            read.extend_from_slice(&buf[..len]);
+        }
+        assert_eq!(read, content);
+        assert_eq!(reader.position(), content.len() as u64);
+    }
+
+    #[test]
+    fn buffered_read() {
+        let dir = crate::tempdir().unwrap();
+        let path = dir.path().join("buffered.bin");
+        let content = create_file(&path, CHUNK_SIZE * 3 + 1);
+
+        let mut reader = open_reader(&path);
+        let mut read = Vec::new();
+        loop {
+            let buf = reader.fill_buf().unwrap();
+            if buf.is_empty() {
+                break;
+            }
+            assert!(buf.len() <= CHUNK_SIZE);
+            let len = buf.len();
+            read.extend_from_slice(buf);
+            reader.consume(len);
+        }
+        assert_eq!(read, content);
+    }
+
+    #[test]
+    fn seek_within_chunk() {
+        let dir = crate::tempdir().unwrap();
+        let path = dir.path().join("seek_within.bin");
+        let content = create_file(&path, CHUNK_SIZE * 4);
+
+        let mut reader = open_reader(&path);
+        assert_eq!(read_exact_at(&mut reader, 0, 16), &content[..16]);
+
+        // Backwards and forwards within the chunk that is currently buffered
+        assert_eq!(read_exact_at(&mut reader, 4, 8), &content[4..12]);
+        assert_eq!(reader.seek(io::SeekFrom::Current(20)).unwrap(), 32);
+        assert_eq!(reader.buffer(), &content[32..CHUNK_SIZE]);
+
+        // The end of the chunk is still within the chunk, the next read continues with the next chunk
+        assert_eq!(read_exact_at(&mut reader, CHUNK_SIZE as u64, 8), &content[CHUNK_SIZE..CHUNK_SIZE + 8]);
+    }
+
+    #[test]
+    fn seek_outside_chunk() {
+        let dir = crate::tempdir().unwrap();
+        let path = dir.path().join("seek_outside.bin");
+        let content = create_file(&path, CHUNK_SIZE * 8 + 5);
+
+        let mut reader = open_reader(&path);
+        assert_eq!(read_exact_at(&mut reader, 0, 8), &content[..8]);
+
+        // Forwards past any prefetched chunk, and back to an already consumed chunk
+        assert_eq!(read_exact_at(&mut reader, CHUNK_SIZE as u64 * 5 + 3, CHUNK_SIZE), &content[CHUNK_SIZE * 5 + 3..CHUNK_SIZE * 6 + 3]);
+        assert_eq!(read_exact_at(&mut reader, CHUNK_SIZE as u64 + 1, 10), &content[CHUNK_SIZE + 1..CHUNK_SIZE + 11]);
+
+        // Reading continues sequentially after a seek
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, &content[CHUNK_SIZE + 11..]);
+
+        assert_eq!(reader.seek(io::SeekFrom::End(-10)).unwrap(), content.len() as u64 - 10);
+        let mut tail = Vec::new();
+        reader.read_to_end(&mut tail).unwrap();
+        assert_eq!(tail, &content[content.len() - 10..]);
+
+        assert!(reader.seek(io::SeekFrom::Current(-(content.len() as i64) - 1)).is_err());
+    }
+
+    #[test]
+    fn end_of_file() {
+        let dir = crate::tempdir().unwrap();
+        let path = dir.path().join("eof.bin");
+        let content = create_file(&path, CHUNK_SIZE * 2);
+
+        let mut reader = open_reader(&path);
+        let mut read = Vec::new();
+        reader.read_to_end(&mut read).unwrap();
+        assert_eq!(read, content);
+
+        // Reading at the end keeps returning 0 bytes
+        let mut buf = [0; 8];
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+
+        // Seeking past the end is allowed, but there is nothing to read
+        assert_eq!(reader.seek(io::SeekFrom::Start(content.len() as u64 + 100)).unwrap(), content.len() as u64 + 100);
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+
+        // The reader can be restarted after the end was reached
+        assert_eq!(read_exact_at(&mut reader, 3, 5), &content[3..8]);
+    }
+
+    #[test]
+    fn empty_file() {
+        let dir = crate::tempdir().unwrap();
+        let path = dir.path().join("empty.bin");
+        create_file(&path, 0);
+
+        let mut reader = open_reader(&path);
+        assert!(reader.is_empty());
+        let mut buf = [0; 8];
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+        assert!(reader.fill_buf().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use onca_common::io::{self, Write};
+
+use crate::{os::os_imp, File, Path, PathBuf, OpenMode, Permission, FileCreateFlags, FileAccessFlags};
+
+/// Atomically write `bytes` to a file.
+///
+/// The data is first written to a temporary file next to `path`, which is synced to disk and then moved over `path`,
+/// so the file will either contain the old or the new content, even if the process crashes or the system loses power mid-write.
+///
+/// See [`SafeWriter`] for more info.
+///
+/// # Error
+///
+/// Returns an error if the data could not be written, in which case the original file is left untouched.
+pub fn write_atomic<P: AsRef<Path>>(path: P, bytes: &[u8]) -> io::Result<()> {
+    let mut writer = SafeWriter::new(path)?;
+    writer.write_all(bytes)?;
+    writer.commit()
+}
+
+/// Writer that safely replaces the content of a file.
+///
+/// All data is written to a temporary file in the same directory as the target,
+/// which replaces the target once [`SafeWriter::commit`] is called, after all data has been synced to disk.
+/// If the writer is dropped without committing, the temporary file is deleted and the target is left untouched.
+///
+/// On Windows, the target is replaced using `ReplaceFile` semantics, so the attributes and ACLs of an existing target are kept.
+/// On other platforms, the temporary file gets the permissions of an existing target and is renamed over it, after which the directory is synced.
+pub struct SafeWriter {
+    file:      Option<File>,
+    temp_path: PathBuf,
+    target:    PathBuf,
+}
+
+impl SafeWriter {
+    /// Create a new safe writer for the file at `path`.
+    ///
+    /// The target file does not need to exist.
+    ///
+    /// # Error
+    ///
+    /// Returns an error if the temporary file could not be created.
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        static UNIQUE: AtomicU32 = AtomicU32::new(0);
+
+        let target = path.as_ref().to_path_buf();
+        let Some(file_name) = target.file_name() else {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "A safe writer requires a path to a file"));
+        };
+
+        // The temporary file needs to be on the same volume as the target, to allow it to be moved atomically
+        let unique = UNIQUE.fetch_add(1, Ordering::Relaxed);
+        let temp_path = target.with_file_name(format!(".{file_name}.{}-{unique}.tmp", std::process::id()));
+
+        let file = File::create(&temp_path, OpenMode::CreateNonExisting, Permission::Read | Permission::Write, Permission::None, FileCreateFlags::None, FileAccessFlags::None)?;
+        Ok(Self { file: Some(file), temp_path, target })
+    }
+
+    /// Get the path of the file that will be replaced.
+    #[must_use]
+    pub fn target(&self) -> &Path {
+        &self.target
+    }
+
+    /// Sync all written data to disk and replace the target file.
+    ///
+    /// # Error
+    ///
+    /// Returns an error if the data could not be synced or the target could not be replaced, in which case the target is left untouched.
+    ///
+    /// An error is also returned if the target was replaced, but the replacement could not be made durable,
+    /// in which case the target already contains the new data, but may still contain the old data after the system loses power.
+    pub fn commit(mut self) -> io::Result<()> {
+        let file = self.file.as_mut().unwrap();
+        file.flush()?;
+        file.flush_all()?;
+
+        // The file needs to be closed before it can be moved
+        drop(self.file.take());
+
+        if let Err(err) = os_imp::file::replace(&self.temp_path, &self.target) {
+            _ = crate::file::delete(&self.temp_path);
+            return Err(err);
+        }
+
+        // The temporary file now is the target, so it must not be deleted when the replacement cannot be made durable
+        os_imp::file::sync_replaced(&self.target)
+    }
+
+    /// Discard all written data and delete the temporary file, leaving the target untouched.
+    pub fn abort(self) {
+        // Cleanup is handled in `drop`
+    }
+}
+
+impl io::Write for SafeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.as_mut().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.as_mut().unwrap().flush()
+    }
+}
+
+impl Drop for SafeWriter {
+    fn drop(&mut self) {
+        if let Some(file) = self.file.take() {
+            drop(file);
+            _ = crate::file::delete(&self.temp_path);
+        }
+    }
+}
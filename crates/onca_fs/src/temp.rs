@@ -0,0 +1,314 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    ops::{Deref, DerefMut},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use onca_common::io::{self, Read, Seek, Write};
+
+use crate::{directory, os::os_imp, File, FileAccessFlags, FileCreateFlags, Path, PathBuf, Permission};
+
+/// Maximum number of names that are tried before giving up on creating a unique temporary file or directory.
+const MAX_UNIQUE_ATTEMPTS: u32 = 64;
+
+/// Get the directory for temporary files of the current user.
+///
+/// # Error
+///
+/// Returns an error if the temporary directory could not be retrieved.
+pub fn temp_dir() -> io::Result<PathBuf> {
+    os_imp::get_temp_dir()
+}
+
+/// Create a temporary file in the temporary directory, which is deleted when it goes out of scope.
+///
+/// See [`TempBuilder`] for more options.
+///
+/// # Error
+///
+/// Returns an error if the temporary file could not be created.
+pub fn tempfile() -> io::Result<TempFile> {
+    TempBuilder::new().tempfile()
+}
+
+/// Create a temporary directory in the temporary directory, which is deleted together with its content when it goes out of scope.
+///
+/// See [`TempBuilder`] for more options.
+///
+/// # Error
+///
+/// Returns an error if the temporary directory could not be created.
+pub fn tempdir() -> io::Result<TempDir> {
+    TempBuilder::new().tempdir()
+}
+
+/// Generate a name that is unique to this process and highly unlikely to be used by another process.
+fn unique_name(prefix: &str, suffix: &str) -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    // `RandomState` is seeded randomly, so the name cannot be predicted by another process
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    format!("{prefix}{:x}-{:016x}{suffix}", std::process::id(), hasher.finish())
+}
+
+/// Call `create` with a unique path in `dir`, until it does not fail because the path already exists.
+pub(crate) fn create_unique<T, F>(dir: &Path, prefix: &str, suffix: &str, mut create: F) -> io::Result<T>
+where
+    F: FnMut(&Path) -> io::Result<T>
+{
+    for _ in 0..MAX_UNIQUE_ATTEMPTS {
+        let name = unique_name(prefix, suffix);
+        let name = Path::new(&name).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "The temporary name contains invalid characters"))?;
+        match create(&dir.join(name)) {
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            res => return res,
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::AlreadyExists, "Could not find a unique name for a temporary file or directory"))
+}
+
+//------------------------------
+
+/// Builder for temporary files and directories.
+pub struct TempBuilder {
+    prefix:          String,
+    suffix:          String,
+    dir:             Option<PathBuf>,
+    delete_on_close: bool,
+}
+
+impl TempBuilder {
+    /// Create a new builder, creating entries in the temporary directory, with a `tmp-` prefix.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            prefix: "tmp-".to_string(),
+            suffix: String::new(),
+            dir: None,
+            delete_on_close: false,
+        }
+    }
+
+    /// Set the prefix of the generated name.
+    #[must_use]
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Set the suffix of the generated name, e.g. the extension of a file.
+    #[must_use]
+    pub fn suffix(mut self, suffix: &str) -> Self {
+        self.suffix = suffix.to_string();
+        self
+    }
+
+    /// Create the entry in the given directory, instead of the temporary directory.
+    ///
+    /// Use this when the file needs to be moved on the same volume afterwards.
+    #[must_use]
+    pub fn in_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Let the OS delete a temporary file when its last handle is closed, instead of deleting it when the [`TempFile`] is dropped.
+    ///
+    /// This guarantees that the file is deleted, even when the process crashes, but prevents the file from being kept.
+    /// This has no effect on temporary directories.
+    ///
+    /// # Note
+    ///
+    /// On POSIX platforms, the file is unlinked as soon as it is created, so [`TempFile::path`] does not point to an existing file,
+    /// and the file cannot be opened again by path. The open handle can still be used to read from and write to the file.
+    #[must_use]
+    pub fn delete_on_close(mut self, delete_on_close: bool) -> Self {
+        self.delete_on_close = delete_on_close;
+        self
+    }
+
+    /// Create a temporary file.
+    ///
+    /// The file is opened for reading and writing, other handles may only read or delete the file.
+    ///
+    /// # Error
+    ///
+    /// Returns an error if the temporary file could not be created.
+    pub fn tempfile(&self) -> io::Result<TempFile> {
+        let dir = self.get_dir()?;
+
+        let (access_perms, create_flags) = if self.delete_on_close {
+            (Permission::Read | Permission::Write | Permission::Delete, FileCreateFlags::DeleteOnClose)
+        } else {
+            (Permission::Read | Permission::Write, FileCreateFlags::None)
+        };
+        let shared_perms = Permission::Read | Permission::Delete;
+
+        let file = create_unique(&dir, &self.prefix, &self.suffix, |path| File::create_temp_at(path, access_perms, shared_perms, create_flags, FileAccessFlags::None))?;
+        Ok(TempFile { file: Some(file), delete_on_close: self.delete_on_close })
+    }
+
+    /// Create a temporary directory.
+    ///
+    /// # Error
+    ///
+    /// Returns an error if the temporary directory could not be created.
+    pub fn tempdir(&self) -> io::Result<TempDir> {
+        let dir = self.get_dir()?;
+        let path = create_unique(&dir, &self.prefix, &self.suffix, |path| os_imp::directory::create_temp(path).map(|_| path.to_path_buf()))?;
+        Ok(TempDir { path: Some(path) })
+    }
+
+    fn get_dir(&self) -> io::Result<PathBuf> {
+        match &self.dir {
+            Some(dir) => Ok(dir.clone()),
+            None => temp_dir(),
+        }
+    }
+}
+
+impl Default for TempBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+//------------------------------
+
+/// Temporary file, which is deleted when it goes out of scope.
+///
+/// The underlying [`File`] can be accessed by dereferencing the temporary file.
+pub struct TempFile {
+    file:            Option<File>,
+    delete_on_close: bool,
+}
+
+impl TempFile {
+    /// Get the path of the temporary file.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        self.file.as_ref().unwrap().path()
+    }
+
+    /// Keep the temporary file, so it is not deleted when the file is closed.
+    ///
+    /// # Error
+    ///
+    /// Returns an error if the file was created with [`TempBuilder::delete_on_close`], as the OS will delete it regardless.
+    pub fn keep(mut self) -> Result<File, (io::Error, TempFile)> {
+        if self.delete_on_close {
+            return Err((io::Error::new(io::ErrorKind::Unsupported, "A temporary file that is deleted on close cannot be kept"), self));
+        }
+        Ok(self.file.take().unwrap())
+    }
+
+    /// Close and delete the temporary file.
+    ///
+    /// # Error
+    ///
+    /// Returns an error if the file could not be deleted.
+    pub fn close(mut self) -> io::Result<()> {
+        let file = self.file.take().unwrap();
+        let path = file.path().to_path_buf();
+        drop(file);
+        if self.delete_on_close {
+            Ok(())
+        } else {
+            crate::file::delete(&path)
+        }
+    }
+}
+
+impl Deref for TempFile {
+    type Target = File;
+
+    fn deref(&self) -> &Self::Target {
+        self.file.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for TempFile {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.file.as_mut().unwrap()
+    }
+}
+
+impl io::Read for TempFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.file.as_mut().unwrap().read(buf)
+    }
+}
+
+impl io::Write for TempFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.as_mut().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.as_mut().unwrap().flush()
+    }
+}
+
+impl io::Seek for TempFile {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.file.as_mut().unwrap().seek(pos)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if let Some(file) = self.file.take() {
+            let path = file.path().to_path_buf();
+            drop(file);
+            if !self.delete_on_close {
+                _ = crate::file::delete(&path);
+            }
+        }
+    }
+}
+
+//------------------------------
+
+/// Temporary directory, which is deleted together with its content when it goes out of scope.
+pub struct TempDir {
+    path: Option<PathBuf>,
+}
+
+impl TempDir {
+    /// Get the path of the temporary directory.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        self.path.as_ref().unwrap()
+    }
+
+    /// Keep the temporary directory, so it is not deleted, and return its path.
+    #[must_use]
+    pub fn keep(mut self) -> PathBuf {
+        self.path.take().unwrap()
+    }
+
+    /// Delete the temporary directory and its content.
+    ///
+    /// # Error
+    ///
+    /// Returns an error if the directory or any of its content could not be deleted.
+    pub fn close(mut self) -> io::Result<()> {
+        directory::remove_all(self.path.take().unwrap())
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        self.path()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.take() {
+            _ = directory::remove_all(path);
+        }
+    }
+}
@@ -1,10 +1,10 @@
 use std::{
     collections::{HashMap, BTreeMap, VecDeque, HashSet},
     fmt,
-    sync::Arc, io::Seek,
+    sync::{Arc, OnceLock}, io::Seek,
 };
 
-use onca_common::sync::{RwLock, MappedRwLockReadGuard, RwLockReadGuard};
+use onca_common::sync::{RwLock, MappedRwLockReadGuard, RwLockReadGuard, RwLockUpgradableReadGuard};
 use onca_common_macros::flags;
 
 use crate::*;
@@ -26,8 +26,66 @@ impl fmt::Display for VfsMultiRootError {
 impl std::error::Error for VfsMultiRootError {
 }
 
+/// Prefix of paths that are resolved using the global virtual file system, i.e. `vfs://<mount point>/<path>`.
+pub const VFS_PATH_PREFIX: &str = "vfs://";
+
+/// Convert a `vfs://<mount point>/<path>` path to a `<mount point>:/<path>` path.
+/// 
+/// Returns [`None`] if the path does not start with [`VFS_PATH_PREFIX`].
+pub(crate) fn get_global_vfs_path(path: &Path) -> Option<PathBuf> {
+    let path = path.as_str().strip_prefix(VFS_PATH_PREFIX)?;
+    let (mount_point, sub_path) = path.split_once(|c| c == '/' || c == '\\').unwrap_or((path, ""));
+    Some(PathBuf::from(format!("{mount_point}:/{sub_path}")))
+}
+
+/// Provider backing an entry of a mount point.
+pub enum MountProvider {
+    /// Directory on the native file system.
+    /// 
+    /// Files in the directory can themselves be opened as a sub-system, e.g. an archive, when a path goes through them.
+    Directory(PathBuf),
+    /// Sub-system, e.g. a read-only archive or a [`MemoryFileSystem`].
+    SubSystem(VirtualSubSystemHandle),
+}
+
+impl MountProvider {
+    /// Get the path of the provider.
+    pub fn path(&self) -> &Path {
+        match self {
+            MountProvider::Directory(path)     => path,
+            MountProvider::SubSystem(sub_sys) => sub_sys.path(),
+        }
+    }
+}
+
+/// Mount options for an entry of a mount point.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MountOptions {
+    /// Priority of the entry, entries with a higher priority are searched first.
+    /// 
+    /// The priority needs to be unique within a single mount point.
+    pub priority:       u16,
+    /// Should paths be matched case-sensitively?
+    /// 
+    /// When `false`, a path that cannot be found as-is will be matched to entries in the provider ignoring case.
+    /// When `true`, paths are passed to the provider as-is, meaning that a case-insensitive provider (e.g. NTFS) will still ignore case.
+    pub case_sensitive: bool,
+}
+
+impl MountOptions {
+    /// Create mount options with a given priority, matching paths case-sensitively.
+    pub fn new(priority: u16) -> Self {
+        Self { priority, case_sensitive: true }
+    }
+}
+
+struct MountEntry {
+    provider:       MountProvider,
+    case_sensitive: bool,
+}
+
 struct MountPoint {
-    entries: BTreeMap<u16, PathBuf>
+    entries: BTreeMap<u16, MountEntry>
 }
 
 impl MountPoint {
@@ -38,11 +96,11 @@ impl MountPoint {
     }
 
     /// Mount an entry
-    fn mount_point(&mut self, priority: u16, path: &Path) -> Result<(), ()> {
-        if self.entries.contains_key(&priority) {
+    fn mount_point(&mut self, provider: MountProvider, options: MountOptions) -> Result<(), ()> {
+        if self.entries.contains_key(&options.priority) {
             Err(())
         } else {
-            self.entries.insert(priority, path.to_path_buf());
+            self.entries.insert(options.priority, MountEntry { provider, case_sensitive: options.case_sensitive });
             Ok(())
         }
 
@@ -52,7 +110,7 @@ impl MountPoint {
     fn unmount_point(&mut self, path: &Path) -> bool {
         // NOTE: We could use BTreeMap<T>::extract_if once it's stable
         // We need the `&idx` binding to derefence the value, since otherwise we would still be borrowing `points` via `pair` while tryin to mutable borrow it.
-        if let Some((&idx, _)) =  self.entries.iter().find(|(_, val)| val == &path){
+        if let Some((&idx, _)) =  self.entries.iter().find(|(_, val)| val.provider.path() == path){
             self.entries.remove(&idx);
             true
         } else {
@@ -60,26 +118,44 @@ impl MountPoint {
         }
     }
 
+    /// Unmount the entry with the given priority
+    fn unmount_priority(&mut self, priority: u16) -> bool {
+        self.entries.remove(&priority).is_some()
+    }
+
     /// Get the number of entries in the mount point
     fn num_entries(&self) -> usize {
         self.entries.len()
     }
 
-    /// Go through each entry in the mount point, as call a closure, passing the current entry path
+    /// Go through each entry in the mount point, from highest to lowest priority, and call a closure, passing the current entry
     fn for_each<T, F>(&self, mut f: F) -> io::Result<T> where
-        F: FnMut(u16, &Path) -> io::Result<T>
+        F: FnMut(u16, &MountEntry) -> io::Result<T>
     {
         let mut errors = Vec::with_capacity(self.num_entries());
-        for (&priority, path) in self.entries.iter().rev() {
-            match f(priority, path) {
+        for (&priority, entry) in self.entries.iter().rev() {
+            match f(priority, entry) {
                 Ok(val) => return Ok(val),
-                Err(err) => errors.push((path.to_path_buf(), err)),
+                Err(err) => errors.push((entry.provider.path().to_path_buf(), err)),
             }
         }
         Err(io::Error::other(VfsMultiRootError(errors)))
     }
 }
 
+/// Resolve `path` by matching each of its components, ignoring case, to the entries returned by `read_dir`.
+fn resolve_case_insensitive<F>(path: &Path, read_dir: F) -> Option<PathBuf> where
+    F: Fn(&Path) -> io::Result<EntryIter>
+{
+    let mut resolved = PathBuf::new();
+    for comp in path.components() {
+        let name = comp.as_str().to_lowercase();
+        let entry = read_dir(&resolved).ok()?.find(|entry| entry.file_name().to_lowercase() == name)?;
+        resolved.push(entry.file_name());
+    }
+    Some(resolved)
+}
+
 //------------------------------
 
 struct MultiRootEntrySearchHandle {
@@ -125,9 +201,9 @@ impl EntrySearchHandle for MultiRootEntrySearchHandle {
 /// 
 /// The VFS has internal thread safety.
 pub struct VirtualFileSystem {
-    mount_points:         RwLock<HashMap<String, MountPoint>>,
+    mount_points:        RwLock<HashMap<String, MountPoint>>,
     macros:              RwLock<HashMap<String, PathBuf>>,
-    sub_system_creators: RwLock<HashMap<String, (Box<dyn Fn(&mut File) -> io::Result<bool>>, Box<dyn Fn(File) -> VirtualSubSystemHandle>)>>,
+    sub_system_creators: RwLock<HashMap<String, (Box<dyn Fn(&mut File) -> io::Result<bool> + Send + Sync>, Box<dyn Fn(File) -> io::Result<VirtualSubSystemHandle> + Send + Sync>)>>,
     cached_sub_systems:  RwLock<HashMap<PathBuf, Arc<dyn SubSystem>>>,
 }
 
@@ -142,6 +218,14 @@ impl VirtualFileSystem {
         }
     }
 
+    /// Get the global virtual file system.
+    /// 
+    /// The global virtual file system is used to resolve any `vfs://<mount point>/<path>` path passed to [`File::create`] and [`File::open`].
+    pub fn global() -> &'static VirtualFileSystem {
+        static GLOBAL_VFS: OnceLock<VirtualFileSystem> = OnceLock::new();
+        GLOBAL_VFS.get_or_init(VirtualFileSystem::new)
+    }
+
     // Mount points
     //------------------------------
 
@@ -152,12 +236,24 @@ impl VirtualFileSystem {
     /// 
     /// This function will return an error if an entry with the same `priority` for `mount_point` already exists.
     pub fn mount(&self, mount_point: &str, priority: u16, path: &Path) -> Result<(), ()> {
+        self.mount_provider(mount_point, MountProvider::Directory(path.to_path_buf()), MountOptions::new(priority))
+    }
+
+    /// Mount a provider as an entry of a mount point.
+    /// 
+    /// Multiple providers can be stacked in a single mount point, e.g. a memory file system with loose directory overrides on top of a read-only archive.
+    /// When reading, the providers are searched from the highest to the lowest priority, modifications are only allowed when the mount point has a single entry.
+    /// 
+    /// # Error
+    /// 
+    /// This function will return an error if an entry with the same priority for `mount_point` already exists.
+    pub fn mount_provider(&self, mount_point: &str, provider: MountProvider, options: MountOptions) -> Result<(), ()> {
         let mut mount_points = self.mount_points.write();
         if let Some(mount) = mount_points.get_mut(mount_point) {
-            mount.mount_point(priority, path)
+            mount.mount_point(provider, options)
         } else {
             let mut mount = MountPoint::new();
-            mount.mount_point(priority, path)?;
+            mount.mount_point(provider, options)?;
             mount_points.insert(mount_point.to_string(), mount);
             Ok(())
         }
@@ -175,6 +271,18 @@ impl VirtualFileSystem {
         }
     }
 
+    /// Unmount the mount point entry with the given priority.
+    /// 
+    /// Returns if the mount point was removed, `false` means that there was no mount point or entry with the given name or priority.
+    pub fn unmount_priority(&self, mount_point: &str, priority: u16) -> bool {
+        let mut mount_points = self.mount_points.write();
+        if let Some(mount) = mount_points.get_mut(mount_point) {
+            mount.unmount_priority(priority)
+        } else {
+            false
+        }
+    }
+
     /// Check is a mount point exists
     pub fn is_mounted(&self, mount_point: &str) -> bool {
         self.mount_points.read().contains_key(mount_point)
@@ -292,14 +400,16 @@ impl VirtualFileSystem {
     /// Register a sub-system creation closure.
     /// 
     /// Both the file `extension` and `magic_chk` will be used to find the correct sub-system.
-    /// 1) At first, the sub-system will be 
-    /// 2) b
+    /// 1) At first, the sub-system registered for the extension of the file will be checked using its `magic_chk`.
+    /// 2) If that fails, the `magic_chk` of all registered sub-systems will be tried, to find one that can handle the file.
+    /// 
+    /// Once found, `creation_closure` is called to create the sub-system, which will be cached until the VFS is destroyed.
     /// 
     /// Returns whether the sub-system creation closure was registered successfully,
     /// a value of `false` indicates another sub-system was registerd for the given extension
     pub fn register_sub_system<F0, F1>(&self, extension: &str, magic_chk: F0, creation_closure: F1) -> bool where
-        F0: Fn(&mut File) -> io::Result<bool> + 'static,
-        F1: Fn(File) -> VirtualSubSystemHandle + 'static
+        F0: Fn(&mut File) -> io::Result<bool> + Send + Sync + 'static,
+        F1: Fn(File) -> io::Result<VirtualSubSystemHandle> + Send + Sync + 'static
     {
         let mut creators = self.sub_system_creators.write();
         if creators.contains_key(extension) {
@@ -329,6 +439,13 @@ impl VirtualFileSystem {
         // Open the file, as it should exist if we get here
         let mut file = open_file(path)?;
 
+        let sub_system = self.create_subsystem(file)?;
+        let mut sub_systems = RwLockUpgradableReadGuard::upgrade(sub_systems);
+        sub_systems.insert(path.to_path_buf(), sub_system.clone());
+        Ok(sub_system)
+    }
+
+    fn create_subsystem(&self, mut file: File) -> io::Result<Arc<dyn SubSystem>> {
         let creators = self.sub_system_creators.read();
 
         // Try to shortcut via the extension (handles both files with magic numbers, and files without magic number)
@@ -337,16 +454,17 @@ impl VirtualFileSystem {
                 // check if magic is correct
                 if let Ok(true) = creator.0(&mut file) {
                     file.seek(io::SeekFrom::Start(0))?;
-                    return Ok(creator.1(file));
+                    return creator.1(file);
                 }
             }
         }
         
         // We didn't find the file type using a extenstion, so go over all creators and try to find one that works, based on the magic number
         for create in &*creators {
+            file.seek(io::SeekFrom::Start(0))?;
             if let Ok(true) = create.1.0(&mut file) {
                 file.seek(io::SeekFrom::Start(0))?;
-                return Ok(create.1.1(file));
+                return create.1.1(file);
             }
         }
         Err(io::Error::other(format!("Cannot create a virtual file system for {}", file.path())))
@@ -370,19 +488,49 @@ impl VirtualFileSystem {
 
         match root.kind() {
             Root::VFS(vfs) => if let Some(mount) = self.mount_points.read().get(vfs) {
-                if needed_support.is_any() || mount.num_entries() == 1 {
-                    mount.for_each(|_, root| self.query_from_root(root, comps.as_path(), needed_support, native_func, sub_sys_func))
+                if !needed_support.modifies_filesystem() || mount.num_entries() == 1 {
+                    mount.for_each(|_, entry| self.query_from_mount_entry(entry, comps.as_path(), needed_support, native_func, sub_sys_func))
                 } else {
                     Err(io::Error::other(format!("vfs mount point `{vfs}` is a multi-entry mount point, and can therefore not do any modification to the filesystem itself")))
                 }
 
             } else {
-                Err(io::Error::other("vfs mount point '{vfs}' does not exist"))
+                Err(io::Error::other(format!("vfs mount point '{vfs}' does not exist")))
             },
             _ => self.query_from_root(root.as_path(), comps.as_path(), needed_support, native_func, sub_sys_func)
         }
     }
 
+    fn query_from_mount_entry<F0, F1, T>(&self, entry: &MountEntry, path: &Path, needed_support: SubSystemSupport, native_func: F0, sub_sys_func: F1) -> io::Result<T> where
+        F0: Fn(&Path) -> io::Result<T> + Copy,
+        F1: Fn(&Arc<dyn SubSystem>, &Path) -> io::Result<T> + Copy
+    {
+        match &entry.provider {
+            MountProvider::Directory(root) => match self.query_from_root(root, path, needed_support, native_func, sub_sys_func) {
+                Ok(val) => Ok(val),
+                Err(err) if !entry.case_sensitive => match resolve_case_insensitive(path, |sub_path| directory::read(root.join(sub_path))) {
+                    Some(resolved) => self.query_from_root(root, &resolved, needed_support, native_func, sub_sys_func),
+                    None => Err(err),
+                },
+                Err(err) => Err(err),
+            },
+            MountProvider::SubSystem(sub_system) => {
+                if !sub_system.get_support().contains(needed_support) {
+                    return Err(io::Error::other(format!("The virtual file sub-system for `{}` does not support the `{}` flag", sub_system.path(), needed_support)))
+                }
+
+                match self.recursive_sub_system(sub_system.clone(), path, needed_support, sub_sys_func) {
+                    Ok(val) => Ok(val),
+                    Err(err) if !entry.case_sensitive => match resolve_case_insensitive(path, |sub_path| sub_system.read_directory(sub_path)) {
+                        Some(resolved) => self.recursive_sub_system(sub_system.clone(), &resolved, needed_support, sub_sys_func),
+                        None => Err(err),
+                    },
+                    Err(err) => Err(err),
+                }
+            },
+        }
+    }
+
     fn query_from_root<F0, F1, T>(&self, root: &Path, path: &Path, needed_support: SubSystemSupport, native_func: F0, sub_sys_func: F1) -> io::Result<T> where
         F0: Fn(&Path) -> io::Result<T>,
         F1: Fn(&Arc<dyn SubSystem>, &Path) -> io::Result<T>
@@ -403,7 +551,7 @@ impl VirtualFileSystem {
 
         // We did't find it via the native system, it might be in a sub-system, so try to find the first sub-entry that exists, 
         // and if it's a file, try to access the sub-path, do this until we hit the root
-        while path.len() > root_len {
+        while full_path.len() > root_len {
             full_path.pop();
             comps.next_back();
 
@@ -414,12 +562,12 @@ impl VirtualFileSystem {
                         // create new sub-system
                         let sub_system = self.get_or_create_subsystem(&full_path, |path| File::open(
                             path,
-                            Permission::Read | Permission::Write,
+                            Permission::Read,
                             Permission::None,
                             FileAccessFlags::None
                         ))?;
 
-                        if sub_system.get_support().contains(needed_support) {
+                        if !sub_system.get_support().contains(needed_support) {
                             return Err(io::Error::other(format!("The virtual file sub-system for `{}` does not support the `{}` flag", sub_system.path(), needed_support)))
                         }
 
@@ -454,17 +602,18 @@ impl VirtualFileSystem {
                 Ok(entry) => {
                     // We hit the first sub-entry, go down this, trying to see if it's another sub-system
                     if entry.entry_type().is_file() {
-                        // create new sub-system
-                        let sub_system = self.get_or_create_subsystem(cur_path, |path| sub_system.create_file(
-                            path,
+                        // create new sub-system, nested sub-systems are cached using their path within their parent sub-system
+                        let cache_path = sub_system.path().join(cur_path);
+                        let sub_system = self.get_or_create_subsystem(&cache_path, |_| sub_system.create_file(
+                            cur_path,
                             OpenMode::OpenExisting,
-                            Permission::Read | Permission::Write,
+                            Permission::Read,
                             Permission::None,
                             FileCreateFlags::None,
                             FileAccessFlags::None
                         ))?;
 
-                        if sub_system.get_support().contains(needed_support) {
+                        if !sub_system.get_support().contains(needed_support) {
                             return Err(io::Error::other(format!("The virtual file sub-system for `{}` does not support the `{}` flag", sub_system.path(), needed_support)))
                         }
 
@@ -524,8 +673,8 @@ impl VirtualFileSystem {
         match root.kind() {
             Root::VFS(vfs) => if let Some(mount) = self.mount_points.read().get(vfs) {
                 let mut iters = VecDeque::with_capacity(mount.num_entries());
-                _ = mount.for_each(|_, root| {
-                    match self.query_from_root(root, comps.as_path(), SubSystemSupport::None, native_func, sub_sys_func) {
+                _ = mount.for_each(|_, entry| {
+                    match self.query_from_mount_entry(entry, comps.as_path(), SubSystemSupport::None, native_func, sub_sys_func) {
                         Ok(iter) => iters.push_back(iter),
                         Err(_) => {},
                     }
@@ -540,7 +689,7 @@ impl VirtualFileSystem {
                 }
 
             } else {
-                Err(io::Error::other("vfs mount point '{vfs}' does not exist"))
+                Err(io::Error::other(format!("vfs mount point '{vfs}' does not exist")))
             },
             _ => self.query_from_root(root.as_path(), comps.as_path(), SubSystemSupport::None, native_func, sub_sys_func)
         }
@@ -609,7 +758,7 @@ impl VirtualFileSystem {
     ) -> io::Result<File> {
         self.query(
             path.as_ref(),
-            if open_mode != OpenMode::OpenExisting { SubSystemSupport::None } else { SubSystemSupport::CreateDeleteFile },
+            if open_mode == OpenMode::OpenExisting { SubSystemSupport::None } else { SubSystemSupport::CreateDeleteFile },
             |path| file::File::create(path, open_mode, access_perms, shared_access_perms, create_flags, access_flags),
             |sub_sys, path| sub_sys.create_file(path, open_mode, access_perms, shared_access_perms, create_flags, access_flags)
         )
@@ -673,7 +822,7 @@ pub enum SubSystemSupport {
 
 impl SubSystemSupport {
     pub fn modifies_filesystem(self) -> bool {
-        self.contains(SubSystemSupport::CreateDeleteDirectory | SubSystemSupport::CreateDeleteFile)
+        self.intersects(SubSystemSupport::CreateDeleteDirectory | SubSystemSupport::CreateDeleteFile)
     }
 }
 
@@ -685,7 +834,7 @@ impl SubSystemSupport {
 /// with the special case of "" refering to a file that's directly nested in the sub-system, e.g. a .tar file inside of a .tar.gz.
 /// 
 /// Sub-systems also do not support symlinks.
-pub trait SubSystem {
+pub trait SubSystem: Send + Sync {
     /// Get the path to the virtual sub-system
     fn path(&self) -> &Path;
     
@@ -793,4 +942,43 @@ mod tests {
         );
         assert_eq!(pathbuf, expected);
     }
+
+    #[test]
+    pub fn stacked_memory_mounts() {
+        use std::io::{Read, Write};
+
+        let vfs = VirtualFileSystem::new();
+        let low = MemoryFileSystem::new("low");
+        let high = MemoryFileSystem::new("high");
+        vfs.mount_provider("data", MountProvider::SubSystem(low.clone()), MountOptions::new(0)).unwrap();
+        vfs.mount_provider("data", MountProvider::SubSystem(high.clone()), MountOptions { priority: 1, case_sensitive: false }).unwrap();
+
+        let write = |sub_sys: &MemoryFileSystem, path: &str, content: &str| {
+            let mut file = sub_sys.create_file(Path::new(path).unwrap(), OpenMode::CreateAlways, Permission::Write, Permission::None, FileCreateFlags::None, FileAccessFlags::None).unwrap();
+            file.write_all(content.as_bytes()).unwrap();
+        };
+        write(&low, "a.txt", "low");
+        write(&low, "b.txt", "low");
+        write(&high, "a.txt", "high");
+
+        let read = |path: &str| {
+            let mut file = vfs.open_file(Path::new(path).unwrap(), Permission::Read, Permission::None, FileAccessFlags::None).unwrap();
+            let mut content = String::new();
+            file.read_to_string(&mut content).unwrap();
+            content
+        };
+        assert_eq!(read("data:/a.txt"), "high");
+        assert_eq!(read("data:/b.txt"), "low");
+        assert_eq!(read("data:/A.TXT"), "high");
+
+        // Modifications are not allowed on a mount point with multiple entries
+        assert!(vfs.delete_file(Path::new("data:/a.txt").unwrap()).is_err());
+    }
+
+    #[test]
+    pub fn global_vfs_path() {
+        let path = get_global_vfs_path(Path::new("vfs://assets/textures/a.png").unwrap()).unwrap();
+        assert_eq!(path, Path::new("assets:/textures/a.png").unwrap());
+        assert!(get_global_vfs_path(Path::new("C:/assets/textures/a.png").unwrap()).is_none());
+    }
 }
\ No newline at end of file
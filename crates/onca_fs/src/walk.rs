@@ -0,0 +1,244 @@
+use onca_common::io;
+use onca_regex::{Regex, RegexError};
+
+use crate::{directory, Entry, EntryIter, EntryType, MetaData, Path, PathBuf};
+
+/// How symbolic links are handled during a directory walk.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SymlinkPolicy {
+    /// Symbolic links are skipped and not returned.
+    Skip,
+    /// Symbolic links are returned, but symbolic links to directories are not descended into.
+    #[default]
+    Report,
+    /// Symbolic links are returned and symbolic links to directories are descended into.
+    ///
+    /// A link pointing to one of the directories that is currently being walked is not descended into, to prevent infinite cycles.
+    Follow,
+}
+
+/// Entry returned by a directory walk.
+pub struct WalkEntry {
+    entry:         Entry,
+    relative_path: PathBuf,
+    depth:         usize,
+    metadata:      MetaData,
+}
+
+impl WalkEntry {
+    /// Get the underlying entry.
+    #[must_use]
+    pub fn entry(&self) -> &Entry {
+        &self.entry
+    }
+
+    /// Get the underlying entry, consuming the walk entry.
+    #[must_use]
+    pub fn into_entry(self) -> Entry {
+        self.entry
+    }
+
+    /// Get the path of the entry.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        self.entry.path()
+    }
+
+    /// Get the path of the entry, relative to the root of the walk.
+    #[must_use]
+    pub fn relative_path(&self) -> &Path {
+        &self.relative_path
+    }
+
+    /// Get the file name of the entry.
+    #[must_use]
+    pub fn file_name(&self) -> &str {
+        self.entry.file_name()
+    }
+
+    /// Get the type of the entry.
+    #[must_use]
+    pub fn entry_type(&self) -> EntryType {
+        self.entry.entry_type()
+    }
+
+    /// Get the depth of the entry, entries directly inside of the root of the walk have a depth of 1.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Get the metadata of the entry, which was collected while walking.
+    #[must_use]
+    pub fn metadata(&self) -> &MetaData {
+        &self.metadata
+    }
+}
+
+//------------------------------
+
+/// Directory currently being walked.
+struct WalkDir {
+    iter:    EntryIter,
+    path:    PathBuf,
+    /// Depth of the entries in the directory
+    depth:   usize,
+    /// Fully qualified path, only retrieved when needed to check for symlink cycles
+    fq_path: Option<PathBuf>,
+}
+
+/// Recursive, depth-first iterator over the contents of a directory.
+///
+/// A directory is returned before its content.
+///
+/// The filter only decides which entries are returned, directories that do not match the filter are still descended into,
+/// so `**/*.png` returns all png files in any subdirectory.
+///
+/// Any error while walking (e.g. a subdirectory that could not be read) is returned as an item, after which walking continues.
+pub struct Walk {
+    root:      PathBuf,
+    stack:     Vec<WalkDir>,
+    min_depth: usize,
+    max_depth: usize,
+    symlinks:  SymlinkPolicy,
+    filter:    Option<Regex>,
+    pending:   Option<io::Error>,
+}
+
+impl Walk {
+    pub(crate) fn new(root: &Path) -> io::Result<Self> {
+        let iter = directory::read(root)?;
+        Ok(Self {
+            root: root.to_path_buf(),
+            stack: vec![WalkDir { iter, path: root.to_path_buf(), depth: 1, fq_path: None }],
+            min_depth: 1,
+            max_depth: usize::MAX,
+            symlinks: SymlinkPolicy::default(),
+            filter: None,
+            pending: None,
+        })
+    }
+
+    /// Only return entries with at least the given depth, shallower directories will still be walked.
+    #[must_use]
+    pub fn min_depth(mut self, depth: usize) -> Self {
+        self.min_depth = depth;
+        self
+    }
+
+    /// Only walk up to the given depth, a depth of 1 only returns the direct contents of the root.
+    #[must_use]
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Set how symbolic links are handled.
+    #[must_use]
+    pub fn symlinks(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlinks = policy;
+        self
+    }
+
+    /// Only return entries whose path, relative to the root, matches the glob, e.g. `textures/**/*.{png,jpg}`.
+    ///
+    /// The glob always uses `/` as separator, independent of the OS.
+    ///
+    /// # Error
+    ///
+    /// Returns an error if the glob is invalid.
+    pub fn glob(self, glob: &str) -> Result<Self, RegexError> {
+        Ok(self.regex(Regex::from_glob(glob)?))
+    }
+
+    /// Only return entries whose path, relative to the root, matches the regex.
+    ///
+    /// The path matched against always uses `/` as separator, independent of the OS.
+    #[must_use]
+    pub fn regex(mut self, regex: Regex) -> Self {
+        self.filter = Some(regex);
+        self
+    }
+
+    fn should_descend(&mut self, entry: &Entry, depth: usize) -> bool {
+        if depth >= self.max_depth {
+            return false;
+        }
+
+        match entry.entry_type() {
+            EntryType::Directory        => true,
+            EntryType::SymlinkDirectory => self.symlinks == SymlinkPolicy::Follow && !self.is_cycle(entry),
+            _                           => false,
+        }
+    }
+
+    /// Check if a symlink points to any directory that is currently being walked.
+    fn is_cycle(&mut self, entry: &Entry) -> bool {
+        let Ok(target) = entry.fully_qualified_path() else { return true; };
+        for dir in &mut self.stack {
+            if dir.fq_path.is_none() {
+                dir.fq_path = Entry::new(&dir.path).and_then(|entry| entry.fully_qualified_path()).ok();
+            }
+            if dir.fq_path.as_ref().map_or(false, |fq_path| *fq_path == target) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn matches_filter(&self, relative_path: &Path) -> bool {
+        match &self.filter {
+            Some(filter) => filter.is_match(&relative_path.as_str().replace('\\', "/")).is_some(),
+            None => true,
+        }
+    }
+}
+
+impl Iterator for Walk {
+    type Item = io::Result<WalkEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.pending.take() {
+            return Some(Err(err));
+        }
+
+        loop {
+            let dir = self.stack.last_mut()?;
+            let depth = dir.depth;
+            let Some(entry) = dir.iter.next() else {
+                self.stack.pop();
+                continue;
+            };
+
+            if entry.entry_type().is_symlink() && self.symlinks == SymlinkPolicy::Skip {
+                continue;
+            }
+
+            let relative_path = match entry.path().strip_prefix(&self.root) {
+                Ok(path) => path.to_path_buf(),
+                Err(_) => PathBuf::from(entry.file_name().to_string()),
+            };
+
+            if self.should_descend(&entry, depth) {
+                match directory::read(entry.path()) {
+                    Ok(iter) => self.stack.push(WalkDir { iter, path: entry.path().to_path_buf(), depth: depth + 1, fq_path: None }),
+                    // Return the entry first, the error will be returned on the next call
+                    Err(err) => self.pending = Some(err),
+                }
+            }
+
+            if depth < self.min_depth || !self.matches_filter(&relative_path) {
+                if let Some(err) = self.pending.take() {
+                    return Some(Err(err));
+                }
+                continue;
+            }
+
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(err) => return Some(Err(err)),
+            };
+            return Some(Ok(WalkEntry { entry, relative_path, depth, metadata }));
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use std::sync::Arc;
+
+use onca_common::sync::Mutex;
+use onca_logging::log_error;
+use onca_toml as toml;
+
+use crate::{
+    AxisId, AxisType, AxisValue, AxisDefinition, DeviceType, DeviceTypeMatchSupport, Handle, InputDevice, LOG_INPUT_CAT,
+    axis_type_to_str, axis_type_from_str, device_type_to_string, device_type_from_str,
+};
+
+/// Filter deciding which input can be captured as a binding.
+#[derive(Clone, Debug)]
+pub struct BindingCaptureFilter {
+    /// Only capture input from devices of these types, input from any device is captured when empty.
+    pub device_types:     Vec<DeviceType>,
+    /// Only capture input from the devices assigned to this user, input from any device is captured when `None`.
+    pub user_idx:         Option<u8>,
+    /// Allow analog axes, e.g. sticks and triggers, to be captured.
+    pub allow_analog:     bool,
+    /// How far an analog axis needs to move away from its resting value before it is captured, relative to half of the axis range.
+    /// 
+    /// This makes sure that noise or drift of a stick does not get captured.
+    pub analog_threshold: f32,
+    /// Axes that will never be captured, e.g. the key used to cancel the capture.
+    pub ignored_axes:     Vec<AxisId>,
+}
+
+impl Default for BindingCaptureFilter {
+    fn default() -> Self {
+        Self {
+            device_types: Vec::new(),
+            user_idx: None,
+            allow_analog: true,
+            analog_threshold: 0.5,
+            ignored_axes: Vec::new(),
+        }
+    }
+}
+
+/// Binding that is already bound to a captured input.
+#[derive(Clone, Debug)]
+pub struct BindingConflict {
+    /// Identifier of the mapping context containing the binding.
+    pub context:     String,
+    /// Name of the action the binding belongs to.
+    pub action:      String,
+    /// Rebind name of the binding, if the binding can be rebound.
+    pub rebind_name: Option<String>,
+}
+
+/// Binding descriptor of a captured input.
+#[derive(Clone, Debug)]
+pub struct CapturedBinding {
+    /// Captured input axis.
+    pub axis:        AxisId,
+    /// Type of the device the input was captured from.
+    pub device_type: DeviceType,
+    /// Type of the captured axis.
+    pub axis_type:   AxisType,
+    /// Bindings in the active mapping contexts that are already bound to the captured input.
+    pub conflicts:   Vec<BindingConflict>,
+}
+
+impl CapturedBinding {
+    /// Convert the captured binding to a toml table.
+    /// 
+    /// Conflicts are only relevant at the time of capture, so they are not serialized.
+    pub fn to_toml_table(&self) -> toml::Table {
+        let mut table = toml::Table::new();
+        table.push("axis".to_string(), toml::Item::String(self.axis.as_string()));
+        table.push("device_type".to_string(), toml::Item::String(device_type_to_string(&self.device_type)));
+        table.push("axis_type".to_string(), toml::Item::String(axis_type_to_str(self.axis_type).to_string()));
+        table
+    }
+
+    /// Create a captured binding from a toml table.
+    pub fn from_toml_table(table: &toml::Table) -> Option<Self> {
+        let Some(axis) = table.get::<String>("axis") else {
+            log_error!(LOG_INPUT_CAT, "A captured binding requires an axis to be specified");
+            return None;
+        };
+        let Some(device_type) = table.get::<String>("device_type").and_then(|s| device_type_from_str(s)) else {
+            log_error!(LOG_INPUT_CAT, "Invalid or missing device type for captured binding '{axis}'");
+            return None;
+        };
+        let Some(axis_type) = table.get::<String>("axis_type").and_then(|s| axis_type_from_str(s)) else {
+            log_error!(LOG_INPUT_CAT, "Invalid or missing axis type for captured binding '{axis}'");
+            return None;
+        };
+        Some(Self { axis: AxisId::new(axis), device_type, axis_type, conflicts: Vec::new() })
+    }
+}
+
+/// State of a binding capture.
+#[derive(Clone, Debug)]
+pub enum BindingCaptureState {
+    /// Still waiting for input.
+    Capturing,
+    /// An input was captured.
+    Captured(CapturedBinding),
+    /// The capture was cancelled, either explicitly or by starting a new capture.
+    Cancelled,
+}
+
+pub(crate) struct BindingCapture {
+    filter:   BindingCaptureFilter,
+    /// Resting values of the axes of each device, a device's axes are only captured when they move away from these.
+    baseline: Vec<(Handle, AxisId, AxisValue)>,
+    state:    BindingCaptureState,
+}
+
+/// Handle to a binding capture, started with [`InputManager::start_binding_capture`](crate::InputManager::start_binding_capture).
+#[derive(Clone)]
+pub struct BindingCaptureHandle(pub(crate) Arc<Mutex<BindingCapture>>);
+
+impl BindingCaptureHandle {
+    pub(crate) fn new(filter: BindingCaptureFilter) -> Self {
+        Self(Arc::new(Mutex::new(BindingCapture { filter, baseline: Vec::new(), state: BindingCaptureState::Capturing })))
+    }
+
+    /// Get the current state of the capture.
+    pub fn state(&self) -> BindingCaptureState {
+        self.0.lock().state.clone()
+    }
+
+    /// Check if the capture is still waiting for input.
+    pub fn is_capturing(&self) -> bool {
+        matches!(self.0.lock().state, BindingCaptureState::Capturing)
+    }
+
+    /// Get the captured binding, if an input was captured.
+    pub fn result(&self) -> Option<CapturedBinding> {
+        match &self.0.lock().state {
+            BindingCaptureState::Captured(binding) => Some(binding.clone()),
+            _ => None,
+        }
+    }
+
+    /// Cancel the capture, if it hasn't captured any input yet.
+    pub fn cancel(&self) {
+        let mut capture = self.0.lock();
+        if let BindingCaptureState::Capturing = capture.state {
+            capture.state = BindingCaptureState::Cancelled;
+        }
+    }
+}
+
+impl BindingCapture {
+    pub(crate) fn filter(&self) -> &BindingCaptureFilter {
+        &self.filter
+    }
+
+    pub(crate) fn is_capturing(&self) -> bool {
+        matches!(self.state, BindingCaptureState::Capturing)
+    }
+
+    pub(crate) fn set_captured(&mut self, binding: CapturedBinding) {
+        self.state = BindingCaptureState::Captured(binding);
+    }
+
+    /// Check the axes of a device, and return the first rebindable axis that moved away from its resting value.
+    /// 
+    /// All ids of the axis are returned, the first id is the one that should be bound.
+    pub(crate) fn check_device(&mut self, handle: Handle, dev: &dyn InputDevice) -> Option<(&'static [AxisId], DeviceType, AxisType)> {
+        if !self.filter.device_types.is_empty() && !self.filter.device_types.iter().any(|dev_type| dev.get_device_type().match_or_supports(dev_type) != DeviceTypeMatchSupport::None) {
+            return None;
+        }
+
+        let mut captured = None;
+        for def in dev.get_axes() {
+            if !def.can_rebind || def.ids.is_empty() || def.ids.iter().any(|id| self.filter.ignored_axes.contains(id)) {
+                continue;
+            }
+            if !self.filter.allow_analog && !matches!(def.axis, AxisDefinition::Digital) {
+                continue;
+            }
+
+            let id = &def.ids[0];
+            let Some(value) = dev.get_axis_value(id) else { continue };
+
+            // The first time a device's axis is seen, its current value is its resting value
+            let Some(idx) = self.baseline.iter().position(|(cur_handle, cur_id, _)| *cur_handle == handle && cur_id == id) else {
+                self.baseline.push((handle, id.clone(), value));
+                continue;
+            };
+
+            let baseline = &mut self.baseline[idx].2;
+            let (activation, base_activation) = (Self::get_activation(&def.axis, value), Self::get_activation(&def.axis, *baseline));
+            if activation < base_activation {
+                // Input that was held when the capture started needs to be released before it can be captured
+                *baseline = value;
+            } else if captured.is_none() && Self::has_moved(&def.axis, value, *baseline, self.filter.analog_threshold) {
+                captured = Some((def.ids, def.dev_type.clone(), def.axis.axis_type()));
+            }
+        }
+        captured
+    }
+
+    /// Get how far an axis is activated, relative to the center of its range.
+    fn get_activation(def: &AxisDefinition, value: AxisValue) -> f32 {
+        match (def, value) {
+            (AxisDefinition::Digital, AxisValue::Digital(val)) => if val { 1.0 } else { 0.0 },
+            (AxisDefinition::Int(min, max), AxisValue::Int(val)) => {
+                let center = (*min as f32 + *max as f32) * 0.5;
+                (val as f32 - center).abs()
+            },
+            (AxisDefinition::Axis(min, max), AxisValue::Axis(val)) => (val - (min + max) * 0.5).abs(),
+            (AxisDefinition::Axis2D(min, max), AxisValue::Axis2D(val)) => (val - (*min + *max) * 0.5).len(),
+            (AxisDefinition::Axis3D(min, max), AxisValue::Axis3D(val)) => (val - (*min + *max) * 0.5).len(),
+            _ => 0.0,
+        }
+    }
+
+    /// Check if the axis moved far enough away from its resting value to be captured.
+    fn has_moved(def: &AxisDefinition, value: AxisValue, baseline: AxisValue, threshold: f32) -> bool {
+        match (def, value, baseline) {
+            (AxisDefinition::Digital, AxisValue::Digital(val), AxisValue::Digital(base)) => val && !base,
+            (AxisDefinition::Int(_, _), AxisValue::Int(val), AxisValue::Int(base)) => val != base,
+            (AxisDefinition::Axis(min, max), AxisValue::Axis(val), AxisValue::Axis(base)) => (val - base).abs() > (max - min) * 0.5 * threshold,
+            (AxisDefinition::Axis2D(min, max), AxisValue::Axis2D(val), AxisValue::Axis2D(base)) => (val - base).len() > (*max - *min).len() * 0.5 * threshold,
+            (AxisDefinition::Axis3D(min, max), AxisValue::Axis3D(val), AxisValue::Axis3D(base)) => (val - base).len() > (*max - *min).len() * 0.5 * threshold,
+            _ => false,
+        }
+    }
+}
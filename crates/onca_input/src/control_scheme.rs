@@ -1,8 +1,9 @@
 use core::num::NonZeroUsize;
 use onca_common::prelude::*;
-use onca_logging::log_warning;
+use onca_logging::{log_error, log_warning};
+use onca_toml as toml;
 
-use crate::{DeviceType, LOG_INPUT_CAT, DeviceTypeMatchSupport, Handle};
+use crate::{DeviceType, LOG_INPUT_CAT, DeviceTypeMatchSupport, Handle, GamepadFeatures};
 
 pub enum SchemeItem {
     /// The device is required for this mapping
@@ -23,10 +24,90 @@ impl SchemeItem {
     }
 }
 
+const GAMEPAD_FEATURE_NAMES: [(GamepadFeatures, &str); 4] = [
+    (GamepadFeatures::Touch, "touch"),
+    (GamepadFeatures::Keyboard, "keyboard"),
+    (GamepadFeatures::Gyro, "gyro"),
+    (GamepadFeatures::Accel, "accel"),
+];
+
+/// Convert a device type to a string, e.g. `gamepad:touch|gyro` or `other:my_device`.
+pub(crate) fn device_type_to_string(dev_type: &DeviceType) -> String {
+    match dev_type {
+        DeviceType::Mouse             => "mouse".to_string(),
+        DeviceType::Keyboard          => "keyboard".to_string(),
+        DeviceType::Gamepad(features) => if features.is_none() {
+            "gamepad".to_string()
+        } else {
+            let names = GAMEPAD_FEATURE_NAMES.iter()
+                .filter(|(feature, _)| features.contains(*feature))
+                .map(|(_, name)| *name)
+                .collect::<Vec<_>>();
+            format!("gamepad:{}", names.join("|"))
+        },
+        DeviceType::Touch             => "touch".to_string(),
+        DeviceType::Pen               => "pen".to_string(),
+        DeviceType::ArcadeStick       => "arcade_stick".to_string(),
+        DeviceType::FlightStick       => "flight_stick".to_string(),
+        DeviceType::RacingWheel       => "racing_wheel".to_string(),
+        DeviceType::Other(name)       => format!("other:{name}"),
+    }
+}
+
+pub(crate) fn device_type_from_str(s: &str) -> Option<DeviceType> {
+    let (name, args) = match s.split_once(':') {
+        Some((name, args)) => (name, Some(args)),
+        None => (s, None),
+    };
+
+    match (name, args) {
+        ("mouse", None)           => Some(DeviceType::Mouse),
+        ("keyboard", None)        => Some(DeviceType::Keyboard),
+        ("gamepad", None)         => Some(DeviceType::Gamepad(GamepadFeatures::None)),
+        ("gamepad", Some(args))   => {
+            let mut features = GamepadFeatures::None;
+            for feature in args.split('|').map(|val| val.trim()) {
+                features |= GAMEPAD_FEATURE_NAMES.iter().find(|(_, name)| *name == feature)?.0;
+            }
+            Some(DeviceType::Gamepad(features))
+        },
+        ("touch", None)           => Some(DeviceType::Touch),
+        ("pen", None)             => Some(DeviceType::Pen),
+        ("arcade_stick", None)    => Some(DeviceType::ArcadeStick),
+        ("flight_stick", None)    => Some(DeviceType::FlightStick),
+        ("racing_wheel", None)    => Some(DeviceType::RacingWheel),
+        ("other", Some(name))     => Some(DeviceType::Other(name.to_string())),
+        _                         => None,
+    }
+}
+
+fn device_type_from_item(item: &toml::Item) -> Option<DeviceType> {
+    let dev_type = match item {
+        toml::Item::String(s) => device_type_from_str(s),
+        _                     => None,
+    };
+    if dev_type.is_none() {
+        log_error!(LOG_INPUT_CAT, "Invalid device type in control scheme: {item:?}");
+    }
+    dev_type
+}
+
 // TODO: Interned string
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct ControlSchemeID(String);
 
+impl ControlSchemeID {
+    /// Create a new control scheme identifier.
+    pub fn new(id: &str) -> Self {
+        Self(id.to_string())
+    }
+
+    /// Get the identifier as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
 impl Default for ControlSchemeID {
     fn default() -> Self {
         Self(Default::default())
@@ -114,6 +195,65 @@ impl ControlScheme {
     pub fn identifier(&self) -> &ControlSchemeID {
         &self.identifier
     }
+
+    /// Get the items in the control scheme.
+    pub fn items(&self) -> &[SchemeItem] {
+        &self.items
+    }
+
+    /// Convert the control scheme to a toml table.
+    pub fn to_toml_table(&self) -> toml::Table {
+        let mut table = toml::Table::new();
+        table.push("identifier".to_string(), toml::Item::String(self.identifier.0.clone()));
+
+        let items = self.items.iter().map(|item| {
+            let mut item_table = toml::Table::new();
+            match item {
+                SchemeItem::Required(dev_type) => item_table.push("required".to_string(), toml::Item::String(device_type_to_string(dev_type))),
+                SchemeItem::Either(dev_types)  => item_table.push("either".to_string(), toml::Item::Array(dev_types.iter().map(|dev_type| toml::Item::String(device_type_to_string(dev_type))).collect())),
+                SchemeItem::Optional(dev_type) => item_table.push("optional".to_string(), toml::Item::String(device_type_to_string(dev_type))),
+            };
+            toml::Item::Table(item_table)
+        }).collect();
+        table.push("items".to_string(), toml::Item::Array(items));
+        table
+    }
+
+    /// Create a control scheme from a toml table.
+    pub fn from_toml_table(table: &toml::Table) -> Option<Self> {
+        let Some(identifier) = table.get::<String>("identifier") else {
+            log_error!(LOG_INPUT_CAT, "A control scheme requires an identifier to be specified");
+            return None;
+        };
+
+        let mut items = Vec::new();
+        for item in table.get::<Vec<toml::Item>>("items").map_or(&[][..], |items| items.as_slice()) {
+            let toml::Item::Table(item) = item else {
+                log_error!(LOG_INPUT_CAT, "Invalid item in control scheme '{identifier}', expected a table");
+                return None;
+            };
+
+            let scheme_item = if let Some(dev_type) = item.get_item("required") {
+                SchemeItem::Required(device_type_from_item(dev_type)?)
+            } else if let Some(dev_types) = item.get::<Vec<toml::Item>>("either") {
+                SchemeItem::Either(dev_types.iter().map(device_type_from_item).collect::<Option<_>>()?)
+            } else if let Some(dev_type) = item.get_item("optional") {
+                SchemeItem::Optional(device_type_from_item(dev_type)?)
+            } else {
+                log_error!(LOG_INPUT_CAT, "Invalid item in control scheme '{identifier}', expected 'required', 'either', or 'optional'");
+                return None;
+            };
+            items.push(scheme_item);
+        }
+
+        match Self::new(ControlSchemeID(identifier.clone()), items) {
+            Ok(scheme) => Some(scheme),
+            Err(_) => {
+                log_error!(LOG_INPUT_CAT, "Failed to create control scheme '{identifier}' from toml");
+                None
+            }
+        }
+    }
 }
 
 /// Control set containing device handles for current layout
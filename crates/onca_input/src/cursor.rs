@@ -0,0 +1,64 @@
+use onca_window::CursorGrabMode;
+
+/// Cursor state requested by a part of the application.
+/// 
+/// Requests are stored on a stack, with the most recent request being applied, e.g. a pause menu can request a free cursor on top of the game's relative mode request.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CursorRequest {
+    /// How the cursor is grabbed by the window.
+    pub grab:    CursorGrabMode,
+    /// Is the cursor visible.
+    pub visible: bool,
+}
+
+impl CursorRequest {
+    /// Free and visible cursor, e.g. for menu navigation.
+    pub const FREE: CursorRequest = CursorRequest { grab: CursorGrabMode::None, visible: true };
+    /// Visible cursor that is confined to the window, e.g. for strategy games scrolling at the edge of the screen.
+    pub const CONFINED: CursorRequest = CursorRequest { grab: CursorGrabMode::Confined, visible: true };
+    /// Hidden cursor that is locked in place, only raw relative motion is used, e.g. for first person camera control.
+    pub const RELATIVE: CursorRequest = CursorRequest { grab: CursorGrabMode::Locked, visible: false };
+
+    /// Check if the request is for raw relative motion.
+    pub fn is_relative(&self) -> bool {
+        self.grab == CursorGrabMode::Locked
+    }
+}
+
+/// Identifier of a cursor request.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CursorRequestId(u32);
+
+pub(crate) struct CursorRequestStack {
+    requests: Vec<(CursorRequestId, CursorRequest)>,
+    next_id:  u32,
+}
+
+impl CursorRequestStack {
+    pub(crate) fn new() -> Self {
+        Self { requests: Vec::new(), next_id: 0 }
+    }
+
+    pub(crate) fn push(&mut self, request: CursorRequest) -> CursorRequestId {
+        let id = CursorRequestId(self.next_id);
+        self.next_id = self.next_id.wrapping_add(1);
+        self.requests.push((id, request));
+        id
+    }
+
+    /// Remove a request, requests don't need to be removed in order.
+    pub(crate) fn remove(&mut self, id: CursorRequestId) -> bool {
+        match self.requests.iter().position(|(cur_id, _)| *cur_id == id) {
+            Some(idx) => {
+                self.requests.remove(idx);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Get the active request, when there are no requests, the cursor is free.
+    pub(crate) fn current(&self) -> CursorRequest {
+        self.requests.last().map_or(CursorRequest::FREE, |(_, request)| *request)
+    }
+}
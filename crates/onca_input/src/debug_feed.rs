@@ -0,0 +1,116 @@
+use crate::{AxisId, AxisValue, TriggerResult, TriggerState, TriggerType};
+
+/// Modifier that was applied to a value.
+#[derive(Clone, Debug)]
+pub struct ModifierDebugInfo {
+    /// Name of the modifier.
+    pub name:   &'static str,
+    /// Value before the modifier was applied.
+    pub input:  AxisValue,
+    /// Value after the modifier was applied.
+    pub output: AxisValue,
+}
+
+/// Trigger that was checked.
+#[derive(Clone, Debug)]
+pub struct TriggerDebugInfo {
+    /// Name of the trigger.
+    pub name:         &'static str,
+    /// Type of the trigger.
+    pub trigger_type: TriggerType,
+    /// Result of the trigger this frame.
+    pub result:       TriggerResult,
+}
+
+/// Binding of an action that was processed.
+#[derive(Clone, Debug)]
+pub struct BindingDebugInfo {
+    /// Input axis the binding is bound to.
+    pub input_axis:  AxisId,
+    /// Raw input value, `None` if the input was consumed.
+    pub raw_value:   Option<AxisValue>,
+    /// Value after the binding's modifiers were applied, `None` if the input was consumed.
+    pub value:       Option<AxisValue>,
+    /// Name of the action that consumed the input before this binding could use it.
+    pub consumed_by: Option<String>,
+    /// Modifiers applied to the binding.
+    pub modifiers:   Vec<ModifierDebugInfo>,
+    /// Triggers of the binding.
+    pub triggers:    Vec<TriggerDebugInfo>,
+}
+
+/// Outcome of processing an action in a mapping context.
+#[derive(Clone, Debug)]
+pub enum ActionDebugOutcome {
+    /// The action was processed in this context.
+    Processed,
+    /// The action was already processed by a mapping in another context, which won because it has a higher priority or comes earlier.
+    Shadowed {
+        /// Context that processed the action.
+        winning_context: String,
+    },
+}
+
+/// Debug info of a single action mapping in a single frame.
+#[derive(Clone, Debug)]
+pub struct ActionDebugInfo {
+    /// Name of the action.
+    pub action:         String,
+    /// Identifier of the mapping context the mapping is in.
+    pub context:        String,
+    /// User the action was processed for.
+    pub user_idx:       u8,
+    /// Outcome of processing the mapping.
+    pub outcome:        ActionDebugOutcome,
+    /// Does the action consume its inputs.
+    pub consume_input:  bool,
+    /// Bindings of the mapping.
+    pub bindings:       Vec<BindingDebugInfo>,
+    /// Mapping modifiers, applied to the combined value of all bindings.
+    pub modifiers:      Vec<ModifierDebugInfo>,
+    /// Mapping triggers.
+    pub triggers:       Vec<TriggerDebugInfo>,
+    /// Final trigger result.
+    pub result:         TriggerResult,
+    /// Trigger state, the action is only dispatched when this is not idle.
+    pub state:          TriggerState,
+    /// Final value.
+    pub value:          AxisValue,
+}
+
+impl ActionDebugInfo {
+    pub(crate) fn shadowed(action: String, context: String, user_idx: u8, winning_context: String) -> Self {
+        Self {
+            action,
+            context,
+            user_idx,
+            outcome: ActionDebugOutcome::Shadowed { winning_context },
+            consume_input: false,
+            bindings: Vec::new(),
+            modifiers: Vec::new(),
+            triggers: Vec::new(),
+            result: TriggerResult::Idle,
+            state: TriggerState::Idle,
+            value: AxisValue::Digital(false),
+        }
+    }
+}
+
+/// Debug info of the mapping pipeline for a single frame.
+/// 
+/// Contains an entry for every mapping that was visited, in processing order, which can be displayed by a debug overlay to find out why an action did or did not fire.
+#[derive(Clone, Debug, Default)]
+pub struct InputDebugFrame {
+    /// Frame index, matches the frame of the [`InputSnapshot`](crate::InputSnapshot) of the same tick.
+    pub frame:   u64,
+    /// Processed mappings.
+    pub actions: Vec<ActionDebugInfo>,
+}
+
+impl InputDebugFrame {
+    /// Get all entries for an action with the given name.
+    pub fn action(&self, name: &str) -> impl Iterator<Item = &ActionDebugInfo> {
+        let name = name.to_string();
+        self.actions.iter().filter(move |info| info.action == name)
+    }
+}
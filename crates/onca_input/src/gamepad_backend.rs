@@ -0,0 +1,198 @@
+use onca_hid as hid;
+use onca_logging::log_warning;
+
+use crate::{GamepadFeatures, InputDevice, NativeDeviceHandle, LOG_INPUT_CAT};
+
+/// Controller type, identified by the vendor and product of the device.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ControllerType {
+    /// Xbox 360 controller.
+    Xbox360,
+    /// Xbox One or Xbox Series controller.
+    XboxOne,
+    /// Playstation DualShock 4 controller.
+    DualShock4,
+    /// Playstation DualSense controller.
+    DualSense,
+    /// Playstation DualSense Edge controller.
+    DualSenseEdge,
+    /// Nintendo Switch Pro controller.
+    SwitchPro,
+    /// Left Nintendo Switch Joy-Con.
+    JoyConLeft,
+    /// Right Nintendo Switch Joy-Con.
+    JoyConRight,
+    /// Controller that could not be identified.
+    Unknown,
+}
+
+impl ControllerType {
+    const MICROSOFT_VENDOR: u16 = 0x045E;
+    const SONY_VENDOR:      u16 = 0x054C;
+    const NINTENDO_VENDOR:  u16 = 0x057E;
+
+    /// Identify the controller type from a hid identifier and its unique identifier.
+    pub fn from_identifier(iden: &hid::Identifier, unique_id: &str) -> Self {
+        let vendor = iden.vendor_device.vendor.as_u16();
+        let product = iden.vendor_device.device.as_u16();
+        match (vendor, product) {
+            (Self::MICROSOFT_VENDOR, 0x028E | 0x028F | 0x0719)                            => ControllerType::Xbox360,
+            (Self::MICROSOFT_VENDOR, 0x02D1 | 0x02DD | 0x02E3 | 0x02EA | 0x0B00 | 0x0B12) => ControllerType::XboxOne,
+            (Self::SONY_VENDOR     , 0x05C4 | 0x09CC | 0x0BA0)                            => ControllerType::DualShock4,
+            (Self::SONY_VENDOR     , 0x0CE6)                                              => ControllerType::DualSense,
+            (Self::SONY_VENDOR     , 0x0DF2)                                              => ControllerType::DualSenseEdge,
+            (Self::NINTENDO_VENDOR , 0x2009)                                              => ControllerType::SwitchPro,
+            (Self::NINTENDO_VENDOR , 0x2006)                                              => ControllerType::JoyConLeft,
+            (Self::NINTENDO_VENDOR , 0x2007)                                              => ControllerType::JoyConRight,
+            // Any other XInput compatible device behaves like an xbox controller
+            _ if unique_id.contains("IG_")                                                => ControllerType::XboxOne,
+            _                                                                             => ControllerType::Unknown,
+        }
+    }
+
+    /// Get the features the controller supports, when it is read with a backend that exposes them.
+    pub fn features(self) -> GamepadFeatures {
+        match self {
+            ControllerType::Xbox360 |
+            ControllerType::XboxOne |
+            ControllerType::Unknown       => GamepadFeatures::None,
+            ControllerType::DualShock4 |
+            ControllerType::DualSense |
+            ControllerType::DualSenseEdge => GamepadFeatures::Touch | GamepadFeatures::Gyro | GamepadFeatures::Accel,
+            ControllerType::SwitchPro |
+            ControllerType::JoyConLeft |
+            ControllerType::JoyConRight   => GamepadFeatures::Gyro | GamepadFeatures::Accel,
+        }
+    }
+}
+
+// TODO: Steam Input backend, this needs the Steamworks SDK
+/// Kind of gamepad backend.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GamepadBackendKind {
+    /// Windows GameInput, only available when the GameInput redistributable is installed.
+    GameInput,
+    /// XInput, only supports xbox compatible controllers, without gyro or touch support.
+    XInput,
+    /// Raw HID, devices created by the product and usage device creators.
+    RawHid,
+}
+
+impl GamepadBackendKind {
+    /// Get the default priority of the backend, backends with a higher priority are tried first.
+    /// 
+    /// GameInput > XInput > raw HID, as GameInput supports trigger rumble, and both GameInput and XInput can read the triggers of xbox controllers independently, which raw HID can't.
+    pub fn default_priority(self) -> u32 {
+        match self {
+            GamepadBackendKind::GameInput => 300,
+            GamepadBackendKind::XInput    => 200,
+            GamepadBackendKind::RawHid    => 100,
+        }
+    }
+}
+
+/// Platform gamepad backend.
+/// 
+/// Backends are tried in order of priority when a gamepad is connected, if no backend can create a device, the raw HID device creators will be used instead.
+pub trait GamepadBackend {
+    /// Get the kind of backend.
+    fn kind(&self) -> GamepadBackendKind;
+
+    /// Check if the backend is available, e.g. if its runtime library could be loaded.
+    fn is_available(&self) -> bool;
+
+    /// Check if the backend can handle the device.
+    fn supports(&self, iden: &hid::Identifier, unique_id: &str, controller_type: ControllerType) -> bool;
+
+    /// Create an input device for the native device.
+    /// 
+    /// If the device could not be created, the native handle needs to be returned, so it can be passed to the next backend.
+    fn create_device(&mut self, handle: NativeDeviceHandle, controller_type: ControllerType) -> Result<Box<dyn InputDevice>, NativeDeviceHandle>;
+
+    /// Update the backend, called once each tick before any device is updated.
+    fn tick(&mut self) {}
+}
+
+struct BackendEntry {
+    priority: u32,
+    enabled:  bool,
+    backend:  Box<dyn GamepadBackend>,
+}
+
+pub(crate) struct GamepadBackends {
+    backends: Vec<BackendEntry>,
+}
+
+impl GamepadBackends {
+    pub(crate) fn new() -> Self {
+        Self { backends: Vec::new() }
+    }
+
+    pub(crate) fn register(&mut self, priority: u32, backend: Box<dyn GamepadBackend>) {
+        let kind = backend.kind();
+        if self.backends.iter().any(|entry| entry.backend.kind() == kind) {
+            log_warning!(LOG_INPUT_CAT, "A gamepad backend has already been registered for {kind:?}");
+            return;
+        }
+        self.backends.push(BackendEntry { priority, enabled: true, backend });
+        self.sort();
+    }
+
+    pub(crate) fn set_priority(&mut self, kind: GamepadBackendKind, priority: u32) -> bool {
+        match self.backends.iter_mut().find(|entry| entry.backend.kind() == kind) {
+            Some(entry) => {
+                entry.priority = priority;
+                self.sort();
+                true
+            },
+            None => false,
+        }
+    }
+
+    pub(crate) fn set_enabled(&mut self, kind: GamepadBackendKind, enabled: bool) -> bool {
+        match self.backends.iter_mut().find(|entry| entry.backend.kind() == kind) {
+            Some(entry) => {
+                entry.enabled = enabled;
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Get all enabled and available backends, in order of priority.
+    pub(crate) fn active_backends(&self) -> Vec<GamepadBackendKind> {
+        self.backends.iter()
+            .filter(|entry| entry.enabled && entry.backend.is_available())
+            .map(|entry| entry.backend.kind())
+            .collect()
+    }
+
+    pub(crate) fn tick(&mut self) {
+        for entry in &mut self.backends {
+            if entry.enabled {
+                entry.backend.tick();
+            }
+        }
+    }
+
+    /// Try to create a device with the highest priority backend that supports it.
+    pub(crate) fn create_device(&mut self, mut handle: NativeDeviceHandle) -> Result<(Box<dyn InputDevice>, GamepadBackendKind), NativeDeviceHandle> {
+        let controller_type = ControllerType::from_identifier(handle.get_hid_identifier(), handle.get_unique_identifier());
+        for entry in &mut self.backends {
+            if !entry.enabled || !entry.backend.is_available() || !entry.backend.supports(handle.get_hid_identifier(), handle.get_unique_identifier(), controller_type) {
+                continue;
+            }
+
+            handle = match entry.backend.create_device(handle, controller_type) {
+                Ok(dev) => return Ok((dev, entry.backend.kind())),
+                Err(handle) => handle,
+            };
+        }
+        Err(handle)
+    }
+
+    fn sort(&mut self) {
+        // Stable sort, so backends with the same priority are tried in order of registration
+        self.backends.sort_by(|a, b| b.priority.cmp(&a.priority));
+    }
+}
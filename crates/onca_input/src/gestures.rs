@@ -0,0 +1,249 @@
+use onca_math::*;
+
+use crate::{AxisId, AxisValue, Handle};
+
+/// Settings used to recognize gestures.
+/// 
+/// All distances are in units of a touch surface, i.e. the full width or height of a touch surface is 1 unit.
+#[derive(Clone, Copy, Debug)]
+pub struct GestureSettings {
+    /// Maximum time a pointer can be down to be recognized as a tap, in seconds.
+    pub tap_max_duration:        f32,
+    /// Maximum time between 2 taps to be recognized as a double tap, in seconds.
+    pub double_tap_max_interval: f32,
+    /// Minimum time a pointer needs to be down without moving to be recognized as a hold, in seconds.
+    pub hold_min_duration:       f32,
+    /// Distance a pointer needs to move away from where it went down to be recognized as a drag.
+    pub move_threshold:          f32,
+    /// Minimum velocity a pointer needs to be released with to be recognized as a swipe, in units per second.
+    pub swipe_min_velocity:      f32,
+    /// Number of mouse pixels that correspond to the size of a touch surface, used to convert mouse movement into touch surface units.
+    pub mouse_surface_size:      f32,
+}
+
+impl Default for GestureSettings {
+    fn default() -> Self {
+        Self {
+            tap_max_duration: 0.25,
+            double_tap_max_interval: 0.3,
+            hold_min_duration: 0.5,
+            move_threshold: 0.02,
+            swipe_min_velocity: 1.5,
+            mouse_surface_size: 1000.0,
+        }
+    }
+}
+
+/// Sample of a pointer that is used to recognize gestures, either a touch contact, or a mouse with its left button held.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PointerSample {
+    /// Device and contact id of the pointer.
+    pub key:      (Handle, u32),
+    /// Position in touch surface units.
+    pub position: f32v2,
+    /// Is the pointer still down, a pointer that is not down anymore is released.
+    pub down:     bool,
+}
+
+struct Pointer {
+    key:         (Handle, u32),
+    start_pos:   f32v2,
+    start_time:  f32,
+    position:    f32v2,
+    delta:       f32v2,
+    velocity:    f32v2,
+    /// The pointer moved further than the move threshold.
+    moved:       bool,
+    /// The pointer was part of a multi-pointer gesture, so it cannot be used for a single pointer gesture anymore.
+    multi:       bool,
+}
+
+/// Per-user gesture recognizer.
+/// 
+/// Gestures are recognized from all touch devices and mice in the user's control set, and are exposed as axes, so they can be bound to actions like any other input.
+pub struct GestureRecognizer {
+    settings:       GestureSettings,
+    pointers:       Vec<Pointer>,
+    time:           f32,
+    last_tap:       Option<(f32, f32v2)>,
+    pinch_distance: Option<f32>,
+
+    tap:            bool,
+    double_tap:     bool,
+    hold:           bool,
+    position:       f32v2,
+    drag:           f32v2,
+    pinch:          f32,
+    swipe:          f32v2,
+}
+
+impl GestureRecognizer {
+    pub const TAP:         AxisId = AxisId::new("Gesture Tap");
+    pub const DOUBLE_TAP:  AxisId = AxisId::new("Gesture Double Tap");
+    pub const HOLD:        AxisId = AxisId::new("Gesture Hold");
+    pub const POSITION:    AxisId = AxisId::new("Gesture Position");
+    pub const DRAGGING:    AxisId = AxisId::new("Gesture Dragging");
+    pub const DRAG:        AxisId = AxisId::new("Gesture Drag");
+    pub const PINCH:       AxisId = AxisId::new("Gesture Pinch");
+    pub const SWIPE:       AxisId = AxisId::new("Gesture Swipe");
+    pub const SWIPE_LEFT:  AxisId = AxisId::new("Gesture Swipe Left");
+    pub const SWIPE_RIGHT: AxisId = AxisId::new("Gesture Swipe Right");
+    pub const SWIPE_UP:    AxisId = AxisId::new("Gesture Swipe Up");
+    pub const SWIPE_DOWN:  AxisId = AxisId::new("Gesture Swipe Down");
+
+    pub fn new() -> Self {
+        Self {
+            settings: GestureSettings::default(),
+            pointers: Vec::new(),
+            time: 0.0,
+            last_tap: None,
+            pinch_distance: None,
+            tap: false,
+            double_tap: false,
+            hold: false,
+            position: f32v2::zero(),
+            drag: f32v2::zero(),
+            pinch: 0.0,
+            swipe: f32v2::zero(),
+        }
+    }
+
+    /// Set the settings used to recognize gestures.
+    pub fn set_settings(&mut self, settings: GestureSettings) {
+        self.settings = settings;
+    }
+
+    /// Get the settings used to recognize gestures.
+    pub fn settings(&self) -> &GestureSettings {
+        &self.settings
+    }
+
+    /// Get the number of pointers that are currently down.
+    pub fn pointer_count(&self) -> usize {
+        self.pointers.len()
+    }
+
+    /// Get the value of a gesture axis, `None` is returned if the axis is not a gesture.
+    /// 
+    /// - `TAP`, `DOUBLE_TAP`: on for the frame the gesture was recognized.
+    /// - `HOLD`: on while a single pointer is held without moving.
+    /// - `POSITION`: position of the pointer used for the last single pointer gesture.
+    /// - `DRAGGING`: on while a single pointer is being dragged, `DRAG` contains the movement of the pointer this frame.
+    /// - `PINCH`: relative change of the distance between 2 pointers this frame, i.e. `0.1` means the pinch has grown by 10%.
+    /// - `SWIPE`: velocity of the pointer at the moment it was released, `SWIPE_LEFT`, etc contain the speed when the swipe is mainly in that direction.
+    pub fn get_axis_value(&self, axis: &AxisId) -> Option<AxisValue> {
+        let horizontal = self.swipe.x.abs() >= self.swipe.y.abs();
+        match *axis {
+            Self::TAP         => Some(AxisValue::Digital(self.tap)),
+            Self::DOUBLE_TAP  => Some(AxisValue::Digital(self.double_tap)),
+            Self::HOLD        => Some(AxisValue::Digital(self.hold)),
+            Self::POSITION    => Some(AxisValue::Axis2D( self.position)),
+            Self::DRAGGING    => Some(AxisValue::Digital(self.pointers.len() == 1 && self.pointers[0].moved && !self.pointers[0].multi)),
+            Self::DRAG        => Some(AxisValue::Axis2D( self.drag)),
+            Self::PINCH       => Some(AxisValue::Axis(   self.pinch)),
+            Self::SWIPE       => Some(AxisValue::Axis2D( self.swipe)),
+            Self::SWIPE_LEFT  => Some(AxisValue::Axis(   if horizontal { (-self.swipe.x).max(0.0) } else { 0.0 })),
+            Self::SWIPE_RIGHT => Some(AxisValue::Axis(   if horizontal { self.swipe.x.max(0.0) } else { 0.0 })),
+            Self::SWIPE_UP    => Some(AxisValue::Axis(   if horizontal { 0.0 } else { (-self.swipe.y).max(0.0) })),
+            Self::SWIPE_DOWN  => Some(AxisValue::Axis(   if horizontal { 0.0 } else { self.swipe.y.max(0.0) })),
+            _ => None
+        }
+    }
+
+    /// Update the recognizer with the pointers for this frame.
+    /// 
+    /// Pointers that were down last frame, but don't have a sample anymore are considered lost and will not produce any gesture.
+    pub(crate) fn update(&mut self, dt: f32, samples: &[PointerSample]) {
+        self.time += dt;
+        self.tap = false;
+        self.double_tap = false;
+        self.drag = f32v2::zero();
+        self.pinch = 0.0;
+        self.swipe = f32v2::zero();
+
+        self.pointers.retain(|pointer| samples.iter().any(|sample| sample.key == pointer.key));
+
+        for sample in samples {
+            let idx = self.pointers.iter().position(|pointer| pointer.key == sample.key);
+            match (idx, sample.down) {
+                (Some(idx), true) => {
+                    let pointer = &mut self.pointers[idx];
+                    pointer.delta = sample.position - pointer.position;
+                    if dt > 0.0 {
+                        // Smooth the velocity, as a single frame can be very noisy
+                        pointer.velocity = (pointer.velocity + pointer.delta / dt) * 0.5;
+                    }
+                    pointer.position = sample.position;
+                    pointer.moved |= pointer.start_pos.dist(sample.position) > self.settings.move_threshold;
+                },
+                (Some(idx), false) => {
+                    let pointer = self.pointers.remove(idx);
+                    self.release(pointer, sample.position);
+                },
+                (None, true) => self.pointers.push(Pointer {
+                    key: sample.key,
+                    start_pos: sample.position,
+                    start_time: self.time,
+                    position: sample.position,
+                    delta: f32v2::zero(),
+                    velocity: f32v2::zero(),
+                    moved: false,
+                    multi: false,
+                }),
+                (None, false) => {},
+            }
+        }
+
+        match self.pointers.len() {
+            1 => {
+                let pointer = &self.pointers[0];
+                self.hold = !pointer.moved && !pointer.multi && self.time - pointer.start_time >= self.settings.hold_min_duration;
+                if pointer.moved && !pointer.multi {
+                    self.drag = pointer.delta;
+                }
+                if !pointer.multi {
+                    self.position = pointer.position;
+                }
+                self.pinch_distance = None;
+            },
+            0 => {
+                self.hold = false;
+                self.pinch_distance = None;
+            },
+            _ => {
+                self.hold = false;
+                for pointer in &mut self.pointers {
+                    pointer.multi = true;
+                }
+
+                let distance = self.pointers[0].position.dist(self.pointers[1].position);
+                if let Some(prev_distance) = self.pinch_distance {
+                    if prev_distance > 0.0 {
+                        self.pinch = distance / prev_distance - 1.0;
+                    }
+                }
+                self.pinch_distance = Some(distance);
+            },
+        }
+    }
+
+    fn release(&mut self, pointer: Pointer, position: f32v2) {
+        if pointer.multi {
+            return;
+        }
+
+        self.position = position;
+        if !pointer.moved {
+            if self.time - pointer.start_time <= self.settings.tap_max_duration {
+                self.tap = true;
+                self.double_tap = self.last_tap.map_or(false, |(time, pos)|
+                    self.time - time <= self.settings.double_tap_max_interval && pos.dist(position) <= self.settings.move_threshold
+                );
+                // A double tap should not be the start of another double tap
+                self.last_tap = if self.double_tap { None } else { Some((self.time, position)) };
+            }
+        } else if pointer.velocity.len() >= self.settings.swipe_min_velocity {
+            self.swipe = pointer.velocity;
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use crate::{RumbleState, TriggerFeedback};
+
+/// Handle to a rumble effect playing on a [`HapticsMixer`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RumbleHandle(u32);
+
+struct RumbleEffect {
+    handle:    RumbleHandle,
+    rumble:    RumbleState,
+    /// Remaining time in seconds, `None` if the effect plays until it is stopped.
+    remaining: Option<f32>,
+}
+
+/// Per-user haptics mixer.
+/// 
+/// Multiple rumble effects can play at the same time, e.g. an explosion while driving over rough terrain.
+/// The mixer combines all active effects by taking the strongest intensity for each motor, so an effect will never be drowned out by a weaker one.
+pub struct HapticsMixer {
+    effects:          Vec<RumbleEffect>,
+    next_handle:      u32,
+    intensity:        f32,
+    trigger_feedback: [TriggerFeedback; 2],
+    last_rumble:      Option<RumbleState>,
+    feedback_dirty:   bool,
+}
+
+impl HapticsMixer {
+    pub fn new() -> Self {
+        Self {
+            effects: Vec::new(),
+            next_handle: 0,
+            intensity: 1.0,
+            trigger_feedback: [TriggerFeedback::Off; 2],
+            last_rumble: None,
+            feedback_dirty: false,
+        }
+    }
+
+    /// Start playing a rumble effect.
+    /// 
+    /// If a `duration` (in seconds) is given, the effect is automatically stopped when it runs out, otherwise it plays until [`HapticsMixer::stop`] is called.
+    pub fn play(&mut self, rumble: RumbleState, duration: Option<f32>) -> RumbleHandle {
+        let handle = RumbleHandle(self.next_handle);
+        self.next_handle = self.next_handle.wrapping_add(1);
+        self.effects.push(RumbleEffect { handle, rumble, remaining: duration });
+        handle
+    }
+
+    /// Stop a rumble effect.
+    pub fn stop(&mut self, handle: RumbleHandle) {
+        self.effects.retain(|effect| effect.handle != handle);
+    }
+
+    /// Stop all rumble effects.
+    pub fn stop_all(&mut self) {
+        self.effects.clear();
+    }
+
+    /// Check if a rumble effect is still playing.
+    pub fn is_playing(&self, handle: RumbleHandle) -> bool {
+        self.effects.iter().any(|effect| effect.handle == handle)
+    }
+
+    /// Set the global intensity (0-1) all rumble effects are scaled by, e.g. from a user's vibration strength setting.
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity.clamp(0.0, 1.0);
+    }
+
+    /// Get the global intensity all rumble effects are scaled by.
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    /// Set the trigger feedback, this is only applied to devices that support trigger feedback.
+    pub fn set_trigger_feedback(&mut self, right_trigger: bool, trigger_feedback: TriggerFeedback) {
+        self.trigger_feedback[right_trigger as usize] = trigger_feedback;
+        self.feedback_dirty = true;
+    }
+
+    /// Get the currently mixed rumble state.
+    pub fn mixed_rumble(&self) -> RumbleState {
+        let mut mixed = RumbleState::default();
+        for effect in &self.effects {
+            mixed.low_frequency = mixed.low_frequency.max(effect.rumble.low_frequency);
+            mixed.high_frequency = mixed.high_frequency.max(effect.rumble.high_frequency);
+            mixed.left_trigger = mixed.left_trigger.max(effect.rumble.left_trigger);
+            mixed.right_trigger = mixed.right_trigger.max(effect.rumble.right_trigger);
+        }
+
+        RumbleState {
+            low_frequency: mixed.low_frequency.clamp(0.0, 1.0) * self.intensity,
+            high_frequency: mixed.high_frequency.clamp(0.0, 1.0) * self.intensity,
+            left_trigger: mixed.left_trigger.clamp(0.0, 1.0) * self.intensity,
+            right_trigger: mixed.right_trigger.clamp(0.0, 1.0) * self.intensity,
+        }
+    }
+
+    /// Mix the current rumble state with another rumble state, taking the strongest intensity for each motor.
+    pub fn mix_with(&self, rumble: RumbleState) -> RumbleState {
+        let mixed = self.mixed_rumble();
+        RumbleState {
+            low_frequency: mixed.low_frequency.max(rumble.low_frequency),
+            high_frequency: mixed.high_frequency.max(rumble.high_frequency),
+            left_trigger: mixed.left_trigger.max(rumble.left_trigger),
+            right_trigger: mixed.right_trigger.max(rumble.right_trigger),
+        }
+    }
+
+    /// Update the effects, and return the rumble state when it changed since the last update.
+    pub(crate) fn tick(&mut self, dt: f32) -> Option<RumbleState> {
+        self.effects.retain_mut(|effect| match &mut effect.remaining {
+            Some(remaining) => {
+                *remaining -= dt;
+                *remaining > 0.0
+            },
+            None => true,
+        });
+
+        let rumble = self.mixed_rumble();
+        let changed = self.last_rumble.map_or(true, |last|
+            last.low_frequency != rumble.low_frequency ||
+            last.high_frequency != rumble.high_frequency ||
+            last.left_trigger != rumble.left_trigger ||
+            last.right_trigger != rumble.right_trigger
+        );
+
+        if changed {
+            self.last_rumble = Some(rumble);
+            Some(rumble)
+        } else {
+            None
+        }
+    }
+
+    /// Get the trigger feedback when it changed since the last time it was retrieved.
+    pub(crate) fn take_trigger_feedback(&mut self) -> Option<[TriggerFeedback; 2]> {
+        if self.feedback_dirty {
+            self.feedback_dirty = false;
+            Some(self.trigger_feedback)
+        } else {
+            None
+        }
+    }
+
+    /// Force the current state to be resent on the next update, e.g. when the devices of the user changed.
+    pub(crate) fn invalidate(&mut self) {
+        self.last_rumble = None;
+        self.feedback_dirty = true;
+    }
+}
@@ -219,9 +219,6 @@ impl CalibrartionData {
     const GYRO_RES_PER_DEGREE: f32 = 1024.0;
     const ACCEL_RES_PER_G: f32 = 8192.0;
 
-    // Standard gravity
-    const STANDARD_GRAVITY: f32 = 9.80665;
-
     pub fn from_raw_data(raw: RawCalibrationData) -> Self {
         let numerator = (raw.gyro_speed_plus + raw.gyro_speed_min) as f32 * Self::GYRO_RES_PER_DEGREE;
 
@@ -270,7 +267,7 @@ impl CalibrartionData {
             // Convert to newtons
             CalibrationMode::AccelX |
             CalibrationMode::AccelY |
-            CalibrationMode::AccelZ => value / Self::ACCEL_RES_PER_G * Self::STANDARD_GRAVITY,
+            CalibrationMode::AccelZ => value / Self::ACCEL_RES_PER_G * STANDARD_GRAVITY,
         }
     }
 }
@@ -614,6 +611,8 @@ pub struct DualsenseInputState {
     touch:    [Option<TouchState>; 2],
     angular:  f32v3,
     accel:    f32v3,
+    orientation_delta: f32v3,
+    gravity:  f32v3,
     battery:  u8,
 }
 
@@ -627,6 +626,8 @@ impl DualsenseInputState {
             touch: Default::default(),
             angular: Default::default(),
             accel: Default::default(),
+            orientation_delta: Default::default(),
+            gravity: Default::default(),
             battery: Default::default(),
         }
     }
@@ -664,6 +665,7 @@ pub struct DualSense {
     state:         RwLock<DualsenseInputState>,
     changes:       Mutex<DualsenseChangeState>,
     button_timers: [f32; DualsenseButton::COUNT],
+    motion:        MotionFusion,
     
     // Output
     out_state:     Mutex<DualsenseOutputState>,
@@ -718,11 +720,13 @@ impl DualSense {
     pub const GYRO_PITCH:    AxisId = AxisId::new("Dualsense Gyro Pitch");
     pub const GYRO_YAW:      AxisId = AxisId::new("Dualsense Gyro Yaw");
     pub const GYRO_ROLL:     AxisId = AxisId::new("Dualsense Gyro Roll");
+    pub const ORIENTATION_DELTA: AxisId = AxisId::new("Dualsense Orientation Delta");
 
     pub const ACCEL:         AxisId = AxisId::new("Dualsense Accel");
     pub const ACCEL_X:       AxisId = AxisId::new("Dualsense Accel X");
     pub const ACCEL_Y:       AxisId = AxisId::new("Dualsense Accel Y");
     pub const ACCEL_Z:       AxisId = AxisId::new("Dualsense Accel Z");
+    pub const GRAVITY:       AxisId = AxisId::new("Dualsense Gravity");
 
     // Output
     pub const OUT_PLAYER_INDICATOR: AxisId = AxisId::new("Dualsense Player Indicator");
@@ -745,6 +749,7 @@ impl DualSense {
             state: RwLock::new(DualsenseInputState::new()),
             changes: Mutex::new(DualsenseChangeState::new()),
             button_timers: [0.0; DualsenseButton::COUNT],
+            motion: MotionFusion::default(),
             out_state: Mutex::new(DualsenseOutputState::new()),
             calibration,
         })
@@ -895,6 +900,10 @@ impl DualSense {
             log_verbose!(LOG_INPUT_CAT, "Acceleration: ({:+07.3}, {:+07.3}, {:+07.3})", changes.accel.x, changes.accel.y, changes.accel.z);
         }
         state.accel = changes.accel;
+
+        self.motion.update(state.angular, state.accel, dt);
+        state.orientation_delta = self.motion.orientation_delta();
+        state.gravity = self.motion.up();
     }
 
     fn update_ouput(&mut self) {
@@ -1099,8 +1108,8 @@ impl InputDevice for DualSense {
         }
 
         let angular_x = self.calibration.apply(CalibrationMode::GyroPitch, raw_state.gyro_pitch);
-        let angular_y = self.calibration.apply(CalibrationMode::GyroPitch, raw_state.gyro_yaw);
-        let angular_z = self.calibration.apply(CalibrationMode::GyroPitch, raw_state.gyro_roll);
+        let angular_y = self.calibration.apply(CalibrationMode::GyroYaw, raw_state.gyro_yaw);
+        let angular_z = self.calibration.apply(CalibrationMode::GyroRoll, raw_state.gyro_roll);
 
         self.set_angular_velocity(f32v3::new(angular_x, angular_y, angular_z));
         
@@ -1166,6 +1175,18 @@ impl InputDevice for DualSense {
             /*Touch::TOUCH_1_X |*/ Self::TOUCH_1_X => self.state.read().touch[1].map(|val| AxisValue::Axis(val.pos.x)),
             /*Touch::TOUCH_1_Y |*/ Self::TOUCH_1_Y => self.state.read().touch[1].map(|val| AxisValue::Axis(val.pos.y)),
 
+            Gyro::GYRO                    | Self::GYRO              => Some(AxisValue::Axis3D (self.state.read().angular)),
+            Gyro::GYRO_PITCH              | Self::GYRO_PITCH        => Some(AxisValue::Axis   (self.state.read().angular.x)),
+            Gyro::GYRO_YAW                | Self::GYRO_YAW          => Some(AxisValue::Axis   (self.state.read().angular.y)),
+            Gyro::GYRO_ROLL               | Self::GYRO_ROLL         => Some(AxisValue::Axis   (self.state.read().angular.z)),
+            Gyro::ORIENTATION_DELTA       | Self::ORIENTATION_DELTA => Some(AxisValue::Axis3D (self.state.read().orientation_delta)),
+
+            Accel::ACCEL                  | Self::ACCEL             => Some(AxisValue::Axis3D (self.state.read().accel)),
+            Accel::ACCEL_X                | Self::ACCEL_X           => Some(AxisValue::Axis   (self.state.read().accel.x)),
+            Accel::ACCEL_Y                | Self::ACCEL_Y           => Some(AxisValue::Axis   (self.state.read().accel.y)),
+            Accel::ACCEL_Z                | Self::ACCEL_Z           => Some(AxisValue::Axis   (self.state.read().accel.z)),
+            Accel::GRAVITY                | Self::GRAVITY           => Some(AxisValue::Axis3D (self.state.read().gravity)),
+
             _ => None
         }
     }
@@ -1174,7 +1195,9 @@ impl InputDevice for DualSense {
         const ZERO_V2: f32v2 = f32v2{ x: 0.0, y: 0.0 };
         const ONE_V2:  f32v2 = f32v2{ x: 1.0, y: 1.0 };
         const MONE_V2: f32v2 = f32v2{ x: -1.0, y: -1.0 };
-        const MIN_V3:  f32v3 = f32v3{ x: f32::MIN, y: f32::MAX, z: f32::MAX };
+        const MIN_V3:  f32v3 = f32v3{ x: f32::MIN, y: f32::MIN, z: f32::MIN };
+        const MONE_V3: f32v3 = f32v3{ x: -1.0, y: -1.0, z: -1.0 };
+        const ONE_V3:  f32v3 = f32v3{ x: 1.0, y: 1.0, z: 1.0 };
         const MAX_V3:  f32v3 = f32v3{ x: f32::MAX, y: f32::MAX, z: f32::MAX };
 
         &[
@@ -1217,15 +1240,17 @@ impl InputDevice for DualSense {
             InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Touch), ids: &[/*Touch::TOUCH_1_X,*/         Self::TOUCH_1_X]    , axis: AxisDefinition::Axis  (0.0     , 1.0)     , can_rebind: true },
             InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Touch), ids: &[/*Touch::TOUCH_1_Y,*/         Self::TOUCH_1_Y]    , axis: AxisDefinition::Axis  (0.0     , 1.0)     , can_rebind: true },
 
-            InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Gyro), ids: &[/*Gryo::GYRO,*/                Self::GYRO]         , axis: AxisDefinition::Axis3D(MIN_V3  , MAX_V3)  , can_rebind: true },
-            InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Gyro), ids: &[/*Gryo::GYRO_PITCH,*/          Self::GYRO_PITCH]   , axis: AxisDefinition::Axis  (f32::MIN, f32::MAX), can_rebind: true },
-            InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Gyro), ids: &[/*Gryo::GYRO_YAW,*/            Self::GYRO_YAW]     , axis: AxisDefinition::Axis  (f32::MIN, f32::MAX), can_rebind: true },
-            InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Gyro), ids: &[/*Gryo::GYRO_YROLL*/           Self::GYRO_ROLL]    , axis: AxisDefinition::Axis  (f32::MIN, f32::MAX), can_rebind: true },
-
-            InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Accel), ids: &[/*Accel::ACCEL,*/             Self::ACCEL]        , axis: AxisDefinition::Axis3D(MIN_V3  , MAX_V3)  , can_rebind: true },
-            InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Accel), ids: &[/*Accel::ACCEL_X,*/           Self::ACCEL_X]      , axis: AxisDefinition::Axis  (f32::MIN, f32::MAX), can_rebind: true },
-            InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Accel), ids: &[/*Accel::ACCEL_Y,*/           Self::ACCEL_Y]      , axis: AxisDefinition::Axis  (f32::MIN, f32::MAX), can_rebind: true },
-            InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Accel), ids: &[/*Accel::ACCEL_Z*/            Self::ACCEL_Z]      , axis: AxisDefinition::Axis  (f32::MIN, f32::MAX), can_rebind: true },
+            InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Gyro), ids: &[Gyro::GYRO                  , Self::GYRO]         , axis: AxisDefinition::Axis3D(MIN_V3  , MAX_V3)  , can_rebind: true },
+            InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Gyro), ids: &[Gyro::GYRO_PITCH            , Self::GYRO_PITCH]   , axis: AxisDefinition::Axis  (f32::MIN, f32::MAX), can_rebind: true },
+            InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Gyro), ids: &[Gyro::GYRO_YAW              , Self::GYRO_YAW]     , axis: AxisDefinition::Axis  (f32::MIN, f32::MAX), can_rebind: true },
+            InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Gyro), ids: &[Gyro::GYRO_ROLL             , Self::GYRO_ROLL]    , axis: AxisDefinition::Axis  (f32::MIN, f32::MAX), can_rebind: true },
+            InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Gyro), ids: &[Gyro::ORIENTATION_DELTA, Self::ORIENTATION_DELTA], axis: AxisDefinition::Axis3D(MIN_V3, MAX_V3), can_rebind: true },
+
+            InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Accel), ids: &[Accel::ACCEL               , Self::ACCEL]        , axis: AxisDefinition::Axis3D(MIN_V3  , MAX_V3)  , can_rebind: true },
+            InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Accel), ids: &[Accel::ACCEL_X             , Self::ACCEL_X]      , axis: AxisDefinition::Axis  (f32::MIN, f32::MAX), can_rebind: true },
+            InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Accel), ids: &[Accel::ACCEL_Y             , Self::ACCEL_Y]      , axis: AxisDefinition::Axis  (f32::MIN, f32::MAX), can_rebind: true },
+            InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Accel), ids: &[Accel::ACCEL_Z             , Self::ACCEL_Z]      , axis: AxisDefinition::Axis  (f32::MIN, f32::MAX), can_rebind: true },
+            InputAxisDefinition{ dev_type: DeviceType::Gamepad(GamepadFeatures::Accel), ids: &[Accel::GRAVITY, Self::GRAVITY], axis: AxisDefinition::Axis3D(MONE_V3, ONE_V3), can_rebind: true },
         ]
     }
 
@@ -16,12 +16,21 @@ pub use mouse::*;
 mod gamepad;
 pub use gamepad::*;
 
+mod touch;
+pub use touch::*;
+
+mod pen;
+pub use pen::*;
+
 mod generic;
 pub use generic::*;
 
 mod definitions;
 pub use definitions::*;
 
+mod motion;
+pub use motion::*;
+
 // TODO: Move into plugin once plugin system is added
 mod dualsense;
 pub use dualsense::*;
@@ -62,6 +71,8 @@ pub enum DeviceType {
     Gamepad(GamepadFeatures),
     //// Touch device.
     Touch,
+    /// Pen or stylus.
+    Pen,
     /// Arcade stick.
     ArcadeStick,
     /// Flight stick.
@@ -96,6 +107,7 @@ impl DeviceType {
                 _ => false
             },
             DeviceType::Touch => matches!(self, DeviceType::Touch),
+            DeviceType::Pen => matches!(self, DeviceType::Pen),
             DeviceType::ArcadeStick => matches!(self, DeviceType::ArcadeStick),
             DeviceType::FlightStick => matches!(self, DeviceType::FlightStick),
             DeviceType::RacingWheel => matches!(self, DeviceType::RacingWheel),
@@ -207,6 +219,13 @@ pub trait InputDevice {
 
     /// Set the value of an output axis.
     fn set_output_axis(&self, axis: AxisId, value: AxisValue);
+
+    /// Get all touch contacts that are tracked by the device.
+    /// 
+    /// Devices without touch support don't have any contacts.
+    fn get_touch_contacts(&self) -> Vec<TouchContact> {
+        Vec::new()
+    }
 }
 
 pub trait NativeDeviceHandleT {
@@ -0,0 +1,266 @@
+use onca_math::*;
+
+use crate::AxisId;
+
+/// Standard gravity in m/s^2.
+pub const STANDARD_GRAVITY: f32 = 9.80665;
+
+/// Generic gyroscope axes.
+/// 
+/// Angular velocity is in radians per second, with x being pitch, y being yaw and z being roll, relative to the controller.
+pub struct Gyro;
+
+impl Gyro {
+    pub const GYRO:              AxisId = AxisId::new("Gyro");
+    pub const GYRO_PITCH:        AxisId = AxisId::new("Gyro Pitch");
+    pub const GYRO_YAW:          AxisId = AxisId::new("Gyro Yaw");
+    pub const GYRO_ROLL:         AxisId = AxisId::new("Gyro Roll");
+    /// Fused change in orientation since the last frame, in radians.
+    /// 
+    /// The yaw is relative to the world (around gravity), instead of relative to the controller, so turning works the same independent of how the controller is held.
+    pub const ORIENTATION_DELTA: AxisId = AxisId::new("Gyro Orientation Delta");
+}
+
+/// Generic accelerometer axes.
+/// 
+/// Acceleration is in m/s^2, relative to the controller.
+pub struct Accel;
+
+impl Accel {
+    pub const ACCEL:   AxisId = AxisId::new("Accel");
+    pub const ACCEL_X: AxisId = AxisId::new("Accel X");
+    pub const ACCEL_Y: AxisId = AxisId::new("Accel Y");
+    pub const ACCEL_Z: AxisId = AxisId::new("Accel Z");
+    /// Estimated direction of gravity (pointing up), relative to the controller.
+    pub const GRAVITY: AxisId = AxisId::new("Accel Gravity");
+}
+
+//==============================================================================================================================
+
+/// Settings for the motion sensor fusion.
+#[derive(Clone, Copy, Debug)]
+pub struct MotionFusionSettings {
+    /// How fast the gravity estimate is pulled towards the accelerometer, per second.
+    pub correction_rate:       f32,
+    /// Maximum difference between the measured acceleration and standard gravity for the accelerometer to be trusted, in m/s^2.
+    pub accel_tolerance:       f32,
+    /// Maximum angular velocity for the controller to be considered still, in radians per second.
+    pub still_threshold:       f32,
+    /// Time the controller needs to be still before the gyro bias is calibrated, in seconds.
+    pub still_time:            f32,
+    /// How fast the gyro bias is pulled towards the measured angular velocity while the controller is still, per second.
+    pub bias_rate:             f32,
+}
+
+impl Default for MotionFusionSettings {
+    fn default() -> Self {
+        Self {
+            correction_rate: 1.0,
+            accel_tolerance: 1.0,
+            still_threshold: 0.05,
+            still_time: 1.0,
+            bias_rate: 0.5,
+        }
+    }
+}
+
+/// Complementary filter fusing gyroscope and accelerometer data.
+/// 
+/// The gyroscope is used to track the orientation of the controller, while the accelerometer slowly corrects the drift of the gravity estimate.
+/// When the controller is held still, the gyro bias is also calibrated.
+pub struct MotionFusion {
+    settings:   MotionFusionSettings,
+    up:         Option<f32v3>,
+    bias:       f32v3,
+    still_time: f32,
+    delta:      f32v3,
+}
+
+impl MotionFusion {
+    /// Create a new motion fusion.
+    pub fn new(settings: MotionFusionSettings) -> Self {
+        Self {
+            settings,
+            up: None,
+            bias: f32v3::zero(),
+            still_time: 0.0,
+            delta: f32v3::zero(),
+        }
+    }
+
+    /// Update the fusion with the angular velocity (rad/s) and acceleration (m/s^2) of the current frame.
+    pub fn update(&mut self, angular: f32v3, accel: f32v3, dt: f32) {
+        let accel_len = accel.len();
+        let accel_valid = accel_len > 0.0 && (accel_len - STANDARD_GRAVITY).abs() <= self.settings.accel_tolerance;
+
+        // Gyro bias calibration
+        if accel_valid && (angular - self.bias).len() <= self.settings.still_threshold {
+            self.still_time += dt;
+            if self.still_time >= self.settings.still_time {
+                self.bias = self.bias.lerp(angular, (self.settings.bias_rate * dt).min(1.0));
+            }
+        } else {
+            self.still_time = 0.0;
+        }
+        let angular = angular - self.bias;
+
+        let up = match self.up {
+            Some(up) => up,
+            None if accel_len > 0.0 => accel / accel_len,
+            None => {
+                self.delta = f32v3::zero();
+                return;
+            },
+        };
+
+        // Gravity is fixed in the world, so rotate it in the opposite direction of the controller
+        let rot = angular * dt;
+        let mut new_up = up - rot.cross(up);
+        if accel_valid {
+            new_up = new_up.lerp(accel / accel_len, (self.settings.correction_rate * dt).min(1.0));
+        }
+        let new_up = new_up.normalize_or(up);
+        self.up = Some(new_up);
+
+        // Yaw is the rotation around gravity, so it is independent of how the controller is held
+        self.delta = f32v3::new(rot.x, rot.dot(new_up), rot.z);
+    }
+
+    /// Get the change in orientation (pitch, world yaw, roll) of the last update, in radians.
+    pub fn orientation_delta(&self) -> f32v3 {
+        self.delta
+    }
+
+    /// Get the estimated up direction, relative to the controller.
+    pub fn up(&self) -> f32v3 {
+        self.up.unwrap_or(f32v3::zero())
+    }
+
+    /// Get the estimated gyro bias, in radians per second.
+    pub fn gyro_bias(&self) -> f32v3 {
+        self.bias
+    }
+
+    /// Reset the fusion, including the calibrated gyro bias.
+    pub fn reset(&mut self) {
+        self.up = None;
+        self.bias = f32v3::zero();
+        self.still_time = 0.0;
+        self.delta = f32v3::zero();
+    }
+}
+
+impl Default for MotionFusion {
+    fn default() -> Self {
+        Self::new(MotionFusionSettings::default())
+    }
+}
+
+//==============================================================================================================================
+
+/// Settings for a flick stick.
+#[derive(Clone, Copy, Debug)]
+pub struct FlickStickSettings {
+    /// Stick deflection needed to start a flick.
+    pub threshold:  f32,
+    /// Time a flick takes to complete, in seconds, 0 will flick instantly.
+    pub flick_time: f32,
+}
+
+impl Default for FlickStickSettings {
+    fn default() -> Self {
+        Self {
+            threshold: 0.9,
+            flick_time: 0.1,
+        }
+    }
+}
+
+/// Flick stick helper.
+/// 
+/// Pushing the stick past the threshold turns the camera towards the direction of the stick (forward being no turn), after which rotating the stick turns the camera by the same angle.
+/// The output is a yaw delta in radians, which is intended to be combined with the yaw of the gyro.
+pub struct FlickStick {
+    settings:     FlickStickSettings,
+    active:       bool,
+    prev_angle:   f32,
+    flick_target: f32,
+    flick_time:   f32,
+}
+
+impl FlickStick {
+    /// Create a new flick stick.
+    pub fn new(settings: FlickStickSettings) -> Self {
+        Self {
+            settings,
+            active: false,
+            prev_angle: 0.0,
+            flick_target: 0.0,
+            flick_time: 0.0,
+        }
+    }
+
+    /// Update the flick stick with the current stick position and return the yaw delta for this frame, in radians.
+    pub fn update(&mut self, stick: f32v2, dt: f32) -> f32 {
+        let mut delta = 0.0;
+
+        if stick.len() >= self.settings.threshold {
+            let angle = stick.x.atan2(stick.y);
+            if self.active {
+                delta += wrap_angle(angle - self.prev_angle);
+            } else {
+                self.active = true;
+                self.flick_target = angle;
+                self.flick_time = 0.0;
+            }
+            self.prev_angle = angle;
+        } else {
+            self.active = false;
+        }
+
+        if self.flick_target != 0.0 {
+            if self.settings.flick_time <= 0.0 {
+                delta += self.flick_target;
+                self.flick_target = 0.0;
+            } else {
+                let prev = flick_ease(self.flick_time / self.settings.flick_time);
+                self.flick_time += dt;
+                let cur = flick_ease(self.flick_time / self.settings.flick_time);
+                delta += self.flick_target * (cur - prev);
+
+                if self.flick_time >= self.settings.flick_time {
+                    self.flick_target = 0.0;
+                }
+            }
+        }
+
+        delta
+    }
+
+    /// Is the stick currently past the flick threshold.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Cancel any flick in progress.
+    pub fn reset(&mut self) {
+        self.active = false;
+        self.flick_target = 0.0;
+        self.flick_time = 0.0;
+    }
+}
+
+impl Default for FlickStick {
+    fn default() -> Self {
+        Self::new(FlickStickSettings::default())
+    }
+}
+
+fn wrap_angle(angle: f32) -> f32 {
+    (angle + f32::PI).rem_euclid(f32::TWO_PI) - f32::PI
+}
+
+fn flick_ease(t: f32) -> f32 {
+    let t = 1.0 - t.min(1.0);
+    1.0 - t * t
+}
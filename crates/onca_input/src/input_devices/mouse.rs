@@ -151,6 +151,9 @@ impl Mouse {
     pub const XY:            AxisId = AxisId::new("Mouse XY");
     pub const X:             AxisId = AxisId::new("Mouse X");
     pub const Y:             AxisId = AxisId::new("Mouse Y");
+    pub const DELTA:         AxisId = AxisId::new("Mouse Delta");
+    pub const DELTA_X:       AxisId = AxisId::new("Mouse Delta X");
+    pub const DELTA_Y:       AxisId = AxisId::new("Mouse Delta Y");
     pub const WHEEL:         AxisId = AxisId::new("Mouse Wheel Axis");
     pub const WHEEL_UP:      AxisId = AxisId::new("Mouse Wheel Up");
     pub const WHEEL_DOWN:    AxisId = AxisId::new("Mouse Wheel Down");
@@ -211,7 +214,9 @@ impl Mouse {
         self.state.read().position
     }
     
-    /// Get the mouse delta
+    /// Get the mouse delta.
+    /// 
+    /// The delta comes from the raw mouse input, so it is not affected by the OS' pointer acceleration and keeps updating while the cursor is locked.
     pub fn get_mouse_delta(&self) -> MouseDelta {
         self.state.read().delta
     }
@@ -323,6 +328,9 @@ impl InputDevice for Mouse {
             Self::XY            => Some(AxisValue::Axis2D( self.get_mouse_position().cast())),
             Self::X             => Some(AxisValue::Axis(   self.get_mouse_position().x as f32)),
             Self::Y             => Some(AxisValue::Axis(   self.get_mouse_position().y as f32)),
+            Self::DELTA         => Some(AxisValue::Axis2D( self.get_mouse_delta().cast())),
+            Self::DELTA_X       => Some(AxisValue::Axis(   self.get_mouse_delta().x as f32)),
+            Self::DELTA_Y       => Some(AxisValue::Axis(   self.get_mouse_delta().y as f32)),
             Self::WHEEL         => Some(AxisValue::Axis(   self.get_mouse_wheel().y)),
             Self::WHEEL_UP      => Some(AxisValue::Axis(   self.get_mouse_wheel().y.max(0f32))),
             Self::WHEEL_DOWN    => Some(AxisValue::Axis(  -self.get_mouse_wheel().y.min(0f32))),
@@ -357,6 +365,9 @@ impl InputDevice for Mouse {
             InputAxisDefinition { dev_type: DeviceType::Mouse, ids: &[Self::RIGHT_BUTTON] , axis: AxisDefinition::Digital                   , can_rebind: true },
             InputAxisDefinition { dev_type: DeviceType::Mouse, ids: &[Self::SIDE0_BUTTON] , axis: AxisDefinition::Digital                   , can_rebind: true },
             InputAxisDefinition { dev_type: DeviceType::Mouse, ids: &[Self::SIDE1_BUTTON] , axis: AxisDefinition::Digital                   , can_rebind: true },
+            InputAxisDefinition { dev_type: DeviceType::Mouse, ids: &[Self::DELTA]        , axis: AxisDefinition::Axis2D(MIN_V2  , MAX_V2)  , can_rebind: false},
+            InputAxisDefinition { dev_type: DeviceType::Mouse, ids: &[Self::DELTA_X]      , axis: AxisDefinition::Axis  (f32::MIN, f32::MAX), can_rebind: false},
+            InputAxisDefinition { dev_type: DeviceType::Mouse, ids: &[Self::DELTA_Y]      , axis: AxisDefinition::Axis  (f32::MIN, f32::MAX), can_rebind: false},
         ]
     }
 
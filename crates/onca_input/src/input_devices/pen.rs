@@ -0,0 +1,227 @@
+use onca_common::sync::{RwLock, Mutex};
+use onca_hid as hid;
+#[cfg(feature = "raw_input_logging")]
+use onca_logging::log_verbose;
+use onca_math::*;
+
+use crate::{get_digitizer_value, AxisDefinition, AxisId, AxisValue, DeviceType, InputAxisDefinition, InputDevice, NativeDeviceHandle, OutputInfo, Rebinder, RumbleSupport, DIGITIZER_PAGE, USAGE_TIP_PRESSURE, USAGE_TIP_SWITCH, USAGE_X, USAGE_Y};
+#[cfg(feature = "raw_input_logging")]
+use crate::LOG_INPUT_CAT;
+
+const USAGE_IN_RANGE:      hid::UsageId = hid::UsageId::new(0x32);
+const USAGE_INVERT:        hid::UsageId = hid::UsageId::new(0x3C);
+const USAGE_BARREL_SWITCH: hid::UsageId = hid::UsageId::new(0x44);
+const USAGE_ERASER:        hid::UsageId = hid::UsageId::new(0x45);
+const USAGE_X_TILT:        hid::Usage = hid::Usage::from_u16(0x0D, 0x3D);
+const USAGE_Y_TILT:        hid::Usage = hid::Usage::from_u16(0x0D, 0x3E);
+
+/// Maximum tilt of a pen, in degrees.
+const MAX_TILT: f32 = 90.0;
+
+/// State of a pen.
+#[derive(Clone, Copy, Debug)]
+pub struct PenState {
+    /// The pen is close enough to the surface to be tracked.
+    pub in_range: bool,
+    /// The tip of the pen is touching the surface.
+    pub tip:      bool,
+    /// The barrel button of the pen is pressed.
+    pub barrel:   bool,
+    /// The eraser of the pen is touching the surface.
+    pub eraser:   bool,
+    /// The pen is inverted, i.e. the eraser end is pointing towards the surface.
+    pub inverted: bool,
+    /// Position of the pen, normalized to the surface, i.e. in the range `[0, 1]`.
+    pub position: f32v2,
+    /// Pressure of the pen in the range `[0, 1]`.
+    pub pressure: f32,
+    /// Tilt of the pen along the x and y axis of the surface, in degrees.
+    pub tilt:     f32v2,
+}
+
+impl PenState {
+    fn new() -> Self {
+        Self {
+            in_range: false,
+            tip: false,
+            barrel: false,
+            eraser: false,
+            inverted: false,
+            position: f32v2::zero(),
+            pressure: 0.0,
+            tilt: f32v2::zero(),
+        }
+    }
+}
+
+/// Pen or stylus, e.g. on a drawing tablet or touch screen laptop.
+pub struct PenDevice {
+    handle:  Option<NativeDeviceHandle>,
+    state:   RwLock<PenState>,
+    changes: Mutex<Option<PenState>>,
+}
+
+impl PenDevice {
+    pub const IN_RANGE: AxisId = AxisId::new("Pen In Range");
+    pub const TIP:      AxisId = AxisId::new("Pen Tip");
+    pub const BARREL:   AxisId = AxisId::new("Pen Barrel Button");
+    pub const ERASER:   AxisId = AxisId::new("Pen Eraser");
+    pub const POSITION: AxisId = AxisId::new("Pen Position");
+    pub const X:        AxisId = AxisId::new("Pen X");
+    pub const Y:        AxisId = AxisId::new("Pen Y");
+    pub const PRESSURE: AxisId = AxisId::new("Pen Pressure");
+    pub const TILT:     AxisId = AxisId::new("Pen Tilt");
+
+    /// Create a new pen device.
+    pub fn new(handle: NativeDeviceHandle) -> Result<Self, NativeDeviceHandle> {
+        if handle.hid_dev.is_none() {
+            return Err(handle);
+        }
+
+        Ok(Self {
+            handle: Some(handle),
+            state: RwLock::new(PenState::new()),
+            changes: Mutex::new(None),
+        })
+    }
+
+    /// Emulate a change in the pen's state.
+    pub fn set_state(&self, state: PenState) {
+        *self.changes.lock() = Some(state);
+    }
+
+    /// Get the current state of the pen.
+    pub fn get_state(&self) -> PenState {
+        *self.state.read()
+    }
+}
+
+impl InputDevice for PenDevice {
+    fn tick(&mut self, _dt: f32, rebinder: &mut Rebinder) {
+        let Some(change) = self.changes.lock().take() else { return };
+        let mut state = self.state.write();
+
+        if change.tip && !state.tip {
+            rebinder.notify(&[Self::TIP]);
+            #[cfg(feature = "raw_input_logging")]
+            log_verbose!(LOG_INPUT_CAT, "Pen tip has touched the surface at ({}, {})", change.position.x, change.position.y);
+        }
+        if change.barrel && !state.barrel {
+            rebinder.notify(&[Self::BARREL]);
+        }
+        if change.eraser && !state.eraser {
+            rebinder.notify(&[Self::ERASER]);
+        }
+        *state = change;
+    }
+
+    fn handle_hid_input(&mut self, input_report: &[u8]) {
+        let hid_dev = match unsafe { &self.handle.as_ref().unwrap_unchecked().hid_dev } {
+            Some(hid_dev) => hid_dev,
+            None => return,
+        };
+        let input_report = unsafe { hid::InputReport::from_raw_slice(input_report, hid_dev) };
+
+        let buttons = input_report.get_buttons_for_page(DIGITIZER_PAGE).unwrap_or_default();
+        let x = get_digitizer_value(hid_dev, &input_report, USAGE_X, 0).unwrap_or_default();
+        let y = get_digitizer_value(hid_dev, &input_report, USAGE_Y, 0).unwrap_or_default();
+        let x_tilt = get_digitizer_value(hid_dev, &input_report, USAGE_X_TILT, 0).unwrap_or_default();
+        let y_tilt = get_digitizer_value(hid_dev, &input_report, USAGE_Y_TILT, 0).unwrap_or_default();
+
+        let tip = buttons.contains(&USAGE_TIP_SWITCH);
+        let eraser = buttons.contains(&USAGE_ERASER);
+        let pressure = get_digitizer_value(hid_dev, &input_report, USAGE_TIP_PRESSURE, 0)
+            .unwrap_or(if tip || eraser { 1.0 } else { 0.0 });
+
+        self.set_state(PenState {
+            in_range: buttons.contains(&USAGE_IN_RANGE),
+            tip,
+            barrel: buttons.contains(&USAGE_BARREL_SWITCH),
+            eraser,
+            inverted: buttons.contains(&USAGE_INVERT),
+            position: f32v2::new(x, y),
+            pressure,
+            tilt: f32v2::new(x_tilt, y_tilt) * MAX_TILT,
+        });
+    }
+
+    fn handle_native_input(&mut self, _native_data: *const std::ffi::c_void) {
+        // Nothing to do here, pen input is received via HID reports
+    }
+
+    fn get_native_handle(&self) -> &NativeDeviceHandle {
+        self.handle.as_ref().unwrap()
+    }
+
+    fn get_axis_value(&self, axis: &AxisId) -> Option<AxisValue> {
+        let state = self.state.read();
+        match *axis {
+            Self::IN_RANGE => Some(AxisValue::Digital(state.in_range)),
+            Self::TIP      => Some(AxisValue::Digital(state.tip)),
+            Self::BARREL   => Some(AxisValue::Digital(state.barrel)),
+            Self::ERASER   => Some(AxisValue::Digital(state.eraser)),
+            Self::POSITION => Some(AxisValue::Axis2D( state.position)),
+            Self::X        => Some(AxisValue::Axis(   state.position.x)),
+            Self::Y        => Some(AxisValue::Axis(   state.position.y)),
+            Self::PRESSURE => Some(AxisValue::Axis(   state.pressure)),
+            Self::TILT     => Some(AxisValue::Axis2D( state.tilt)),
+            _ => None
+        }
+    }
+
+    fn get_axes(&self) -> &[InputAxisDefinition] {
+        const MIN_V2:      f32v2 = f32v2{ x: 0.0, y: 0.0 };
+        const MAX_V2:      f32v2 = f32v2{ x: 1.0, y: 1.0 };
+        const MIN_TILT_V2: f32v2 = f32v2{ x: -MAX_TILT, y: -MAX_TILT };
+        const MAX_TILT_V2: f32v2 = f32v2{ x: MAX_TILT, y: MAX_TILT };
+
+        &[
+            InputAxisDefinition { dev_type: DeviceType::Pen, ids: &[Self::IN_RANGE], axis: AxisDefinition::Digital                           , can_rebind: false },
+            InputAxisDefinition { dev_type: DeviceType::Pen, ids: &[Self::TIP]     , axis: AxisDefinition::Digital                           , can_rebind: true  },
+            InputAxisDefinition { dev_type: DeviceType::Pen, ids: &[Self::BARREL]  , axis: AxisDefinition::Digital                           , can_rebind: true  },
+            InputAxisDefinition { dev_type: DeviceType::Pen, ids: &[Self::ERASER]  , axis: AxisDefinition::Digital                           , can_rebind: true  },
+            InputAxisDefinition { dev_type: DeviceType::Pen, ids: &[Self::POSITION], axis: AxisDefinition::Axis2D(MIN_V2, MAX_V2)            , can_rebind: false },
+            InputAxisDefinition { dev_type: DeviceType::Pen, ids: &[Self::X]       , axis: AxisDefinition::Axis  (0.0, 1.0)                  , can_rebind: false },
+            InputAxisDefinition { dev_type: DeviceType::Pen, ids: &[Self::Y]       , axis: AxisDefinition::Axis  (0.0, 1.0)                  , can_rebind: false },
+            InputAxisDefinition { dev_type: DeviceType::Pen, ids: &[Self::PRESSURE], axis: AxisDefinition::Axis  (0.0, 1.0)                  , can_rebind: false },
+            InputAxisDefinition { dev_type: DeviceType::Pen, ids: &[Self::TILT]    , axis: AxisDefinition::Axis2D(MIN_TILT_V2, MAX_TILT_V2)  , can_rebind: false },
+        ]
+    }
+
+    fn get_device_type(&self) -> DeviceType {
+        DeviceType::Pen
+    }
+
+    fn take_native_handle(&mut self) -> NativeDeviceHandle {
+        core::mem::take(&mut self.handle).unwrap()
+    }
+
+    fn get_battery_info(&self) -> Option<crate::BatteryInfo> {
+        None
+    }
+
+    fn get_output_info<'a>(&'a self) -> &'a OutputInfo<'a> {
+        &OutputInfo {
+            rumble: RumbleSupport::None,
+            trigger_feedback: None,
+            led_support: &[],
+            output_axes: &[]
+        }
+    }
+
+    fn set_rumble(&self, _rumble: crate::RumbleState) {
+        // Nothing to do here, as we don't support output
+    }
+
+    fn set_trigger_feedback(&self, _right_trigger: bool, _trigger_feedback: crate::TriggerFeedback) {
+        // Nothing to do here, as we don't support output
+    }
+
+    fn set_led_state(&self, _index: u16, _state: crate::LedState) {
+        // Nothing to do here, as we don't support output
+    }
+
+    fn set_output_axis(&self, _axis: AxisId, _value: AxisValue) {
+        // Nothing to do here, as we don't support output
+    }
+}
@@ -0,0 +1,322 @@
+use onca_common::sync::{RwLock, Mutex};
+use onca_hid as hid;
+use onca_logging::log_warning;
+#[cfg(feature = "raw_input_logging")]
+use onca_logging::log_verbose;
+use onca_math::*;
+
+use crate::{AxisDefinition, AxisId, AxisValue, DeviceType, InputAxisDefinition, InputDevice, NativeDeviceHandle, OutputInfo, Rebinder, RumbleSupport, LOG_INPUT_CAT};
+
+/// HID usage page containing all digitizer usages.
+pub(crate) const DIGITIZER_PAGE: hid::UsagePageId = hid::UsagePageId::new(0x0D);
+
+pub(crate) const USAGE_X:            hid::Usage = hid::Usage::from_u16(0x01, 0x30);
+pub(crate) const USAGE_Y:            hid::Usage = hid::Usage::from_u16(0x01, 0x31);
+pub(crate) const USAGE_TIP_PRESSURE: hid::Usage = hid::Usage::from_u16(0x0D, 0x30);
+pub(crate) const USAGE_TIP_SWITCH:   hid::UsageId = hid::UsageId::new(0x42);
+const USAGE_CONTACT_ID:              hid::Usage = hid::Usage::from_u16(0x0D, 0x51);
+const USAGE_CONTACT_COUNT:           hid::Usage = hid::Usage::from_u16(0x0D, 0x54);
+
+/// Time after which a contact that did not receive any updates is cancelled, in seconds.
+const CONTACT_TIMEOUT: f32 = 0.25;
+
+/// Get a digitizer value, normalized to its logical range.
+/// 
+/// The value is in the range `[0, 1]` for unsigned values, and `[-1, 1]` for values with a symmetric signed range, e.g. tilt.
+pub(crate) fn get_digitizer_value(hid_dev: &hid::Device, input_report: &hid::InputReport, usage: hid::Usage, collection_id: u16) -> Option<f32> {
+    let raw = input_report.get_raw_value(usage, Some(collection_id))?.get_value(0);
+    let caps = hid_dev.get_value_capabilities_for_usage(hid::ReportType::Input, usage, Some(collection_id))?;
+
+    let range = &caps.logical_range;
+    if range.end <= range.start {
+        return None;
+    }
+
+    if range.start < 0 {
+        // Sign-extend the raw value
+        let shift = 32 - caps.bit_size as u32;
+        let val = ((raw << shift) as i32) >> shift;
+        Some(val as f32 / range.end.max(-range.start) as f32)
+    } else {
+        Some((raw as i32 - range.start) as f32 / (range.end - range.start) as f32)
+    }
+}
+
+/// Phase of a touch contact.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TouchPhase {
+    /// The contact started touching the surface this frame.
+    Began,
+    /// The contact moved this frame.
+    Moved,
+    /// The contact is touching the surface, but did not move this frame.
+    Stationary,
+    /// The contact stopped touching the surface this frame.
+    Ended,
+    /// The contact was lost without being released, e.g. because the device stopped sending updates for it.
+    Cancelled,
+}
+
+/// Single contact on a touch surface.
+#[derive(Clone, Copy, Debug)]
+pub struct TouchContact {
+    /// Identifier of the contact, this is unique for as long as the contact touches the surface.
+    pub id:       u32,
+    /// Position of the contact, normalized to the touch surface, i.e. in the range `[0, 1]`.
+    pub position: f32v2,
+    /// Movement of the contact since the previous frame.
+    pub delta:    f32v2,
+    /// Pressure of the contact in the range `[0, 1]`, this is `1` when the device does not report pressure.
+    pub pressure: f32,
+    /// Phase of the contact.
+    pub phase:    TouchPhase,
+}
+
+struct ContactState {
+    contact:   TouchContact,
+    idle_time: f32,
+}
+
+struct ContactChange {
+    id:       u32,
+    position: f32v2,
+    pressure: f32,
+    touching: bool,
+}
+
+/// Multi-touch device, e.g. a touch screen.
+/// 
+/// Contacts are tracked over multiple frames, allowing them to be used to recognize gestures.
+pub struct TouchDevice {
+    handle:      Option<NativeDeviceHandle>,
+    /// Collections containing the data of a single contact.
+    collections: Vec<u16>,
+    contacts:    RwLock<Vec<ContactState>>,
+    changes:     Mutex<Vec<ContactChange>>,
+}
+
+impl TouchDevice {
+    pub const TOUCH:              AxisId = AxisId::new("Touch");
+    pub const POSITION:           AxisId = AxisId::new("Touch Position");
+    pub const X:                  AxisId = AxisId::new("Touch X");
+    pub const Y:                  AxisId = AxisId::new("Touch Y");
+    pub const PRESSURE:           AxisId = AxisId::new("Touch Pressure");
+    pub const SECONDARY_POSITION: AxisId = AxisId::new("Touch Secondary Position");
+    pub const CONTACT_COUNT:      AxisId = AxisId::new("Touch Contact Count");
+
+    /// Create a new touch device.
+    pub fn new(handle: NativeDeviceHandle) -> Result<Self, NativeDeviceHandle> {
+        let Some(hid_dev) = &handle.hid_dev else { return Err(handle) };
+
+        // Each contact is reported in its own collection, which contains its position
+        let mut collections = hid_dev.get_value_capabilities(hid::ReportType::Input).iter()
+            .filter(|caps| caps.usage_page == USAGE_X.page && caps.usage.contains(&USAGE_X.usage))
+            .map(|caps| caps.collection_id)
+            .collect::<Vec<_>>();
+        collections.sort();
+        collections.dedup();
+
+        if collections.is_empty() {
+            log_warning!(LOG_INPUT_CAT, "Touch device '{}' does not report any contact positions", hid_dev.identifier());
+            return Err(handle);
+        }
+
+        Ok(Self {
+            handle: Some(handle),
+            collections,
+            contacts: RwLock::new(Vec::new()),
+            changes: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Emulate a contact touching the surface or moving, `position` is normalized to the touch surface.
+    pub fn touch(&self, id: u32, position: f32v2, pressure: f32) {
+        self.changes.lock().push(ContactChange { id, position, pressure, touching: true });
+    }
+
+    /// Emulate a contact being released.
+    pub fn release(&self, id: u32) {
+        self.changes.lock().push(ContactChange { id, position: f32v2::zero(), pressure: 0.0, touching: false });
+    }
+
+    /// Get all contacts that are currently tracked, including the contacts that ended this frame, ordered from oldest to newest.
+    pub fn get_contacts(&self) -> Vec<TouchContact> {
+        self.contacts.read().iter().map(|state| state.contact).collect()
+    }
+
+    /// Get the contact with the given id.
+    pub fn get_contact(&self, id: u32) -> Option<TouchContact> {
+        self.contacts.read().iter().find(|state| state.contact.id == id).map(|state| state.contact)
+    }
+
+    /// Get the n-th oldest contact that is still touching the surface.
+    fn get_active_contact(&self, idx: usize) -> Option<TouchContact> {
+        self.contacts.read().iter()
+            .filter(|state| !matches!(state.contact.phase, TouchPhase::Ended | TouchPhase::Cancelled))
+            .nth(idx)
+            .map(|state| state.contact)
+    }
+
+    fn get_active_contact_count(&self) -> usize {
+        self.contacts.read().iter().filter(|state| !matches!(state.contact.phase, TouchPhase::Ended | TouchPhase::Cancelled)).count()
+    }
+}
+
+impl InputDevice for TouchDevice {
+    fn tick(&mut self, dt: f32, rebinder: &mut Rebinder) {
+        let mut changes = self.changes.lock();
+        let mut contacts = self.contacts.write();
+
+        // Contacts that ended last frame are not tracked anymore
+        contacts.retain(|state| !matches!(state.contact.phase, TouchPhase::Ended | TouchPhase::Cancelled));
+        for state in contacts.iter_mut() {
+            state.contact.phase = TouchPhase::Stationary;
+            state.contact.delta = f32v2::zero();
+            state.idle_time += dt;
+        }
+
+        for change in changes.drain(..) {
+            match contacts.iter_mut().find(|state| state.contact.id == change.id) {
+                Some(state) => {
+                    state.idle_time = 0.0;
+                    if change.touching {
+                        let delta = change.position - state.contact.position;
+                        state.contact.delta += delta;
+                        state.contact.position = change.position;
+                        state.contact.pressure = change.pressure;
+                        if state.contact.phase == TouchPhase::Stationary && !delta.is_zero() {
+                            state.contact.phase = TouchPhase::Moved;
+                        }
+                    } else {
+                        state.contact.phase = TouchPhase::Ended;
+                        #[cfg(feature = "raw_input_logging")]
+                        log_verbose!(LOG_INPUT_CAT, "Touch contact {} has ended", change.id);
+                    }
+                },
+                None => if change.touching {
+                    contacts.push(ContactState {
+                        contact: TouchContact {
+                            id: change.id,
+                            position: change.position,
+                            delta: f32v2::zero(),
+                            pressure: change.pressure,
+                            phase: TouchPhase::Began,
+                        },
+                        idle_time: 0.0,
+                    });
+                    rebinder.notify(&[Self::TOUCH]);
+                    #[cfg(feature = "raw_input_logging")]
+                    log_verbose!(LOG_INPUT_CAT, "Touch contact {} has began at ({}, {})", change.id, change.position.x, change.position.y);
+                },
+            }
+        }
+
+        for state in contacts.iter_mut() {
+            if state.idle_time > CONTACT_TIMEOUT && state.contact.phase == TouchPhase::Stationary {
+                state.contact.phase = TouchPhase::Cancelled;
+            }
+        }
+    }
+
+    fn handle_hid_input(&mut self, input_report: &[u8]) {
+        let hid_dev = match unsafe { &self.handle.as_ref().unwrap_unchecked().hid_dev } {
+            Some(hid_dev) => hid_dev,
+            None => return,
+        };
+        let input_report = unsafe { hid::InputReport::from_raw_slice(input_report, hid_dev) };
+
+        // When a device reports contacts over multiple reports (hybrid mode), only the first `contact count` collections contain valid data
+        let contact_count = input_report.get_raw_value(USAGE_CONTACT_COUNT, None)
+            .map_or(self.collections.len(), |val| val.get_value(0) as usize)
+            .min(self.collections.len());
+
+        let mut changes = self.changes.lock();
+        for collection in &self.collections[..contact_count] {
+            let Some(id) = input_report.get_raw_value(USAGE_CONTACT_ID, Some(*collection)).map(|val| val.get_value(0)) else { continue };
+            let touching = input_report.get_buttons_for_page_and_collection(DIGITIZER_PAGE, *collection)
+                .map_or(false, |buttons| buttons.contains(&USAGE_TIP_SWITCH));
+
+            let x = get_digitizer_value(hid_dev, &input_report, USAGE_X, *collection).unwrap_or_default();
+            let y = get_digitizer_value(hid_dev, &input_report, USAGE_Y, *collection).unwrap_or_default();
+            let pressure = get_digitizer_value(hid_dev, &input_report, USAGE_TIP_PRESSURE, *collection).unwrap_or(1.0);
+            changes.push(ContactChange { id, position: f32v2::new(x, y), pressure, touching });
+        }
+    }
+
+    fn handle_native_input(&mut self, _native_data: *const std::ffi::c_void) {
+        // Nothing to do here, touch input is received via HID reports
+    }
+
+    fn get_native_handle(&self) -> &NativeDeviceHandle {
+        self.handle.as_ref().unwrap()
+    }
+
+    fn get_axis_value(&self, axis: &AxisId) -> Option<AxisValue> {
+        match *axis {
+            Self::TOUCH              => Some(AxisValue::Digital(self.get_active_contact(0).is_some())),
+            Self::POSITION           => Some(AxisValue::Axis2D(self.get_active_contact(0).map_or(f32v2::zero(), |contact| contact.position))),
+            Self::X                  => Some(AxisValue::Axis(  self.get_active_contact(0).map_or(0.0, |contact| contact.position.x))),
+            Self::Y                  => Some(AxisValue::Axis(  self.get_active_contact(0).map_or(0.0, |contact| contact.position.y))),
+            Self::PRESSURE           => Some(AxisValue::Axis(  self.get_active_contact(0).map_or(0.0, |contact| contact.pressure))),
+            Self::SECONDARY_POSITION => Some(AxisValue::Axis2D(self.get_active_contact(1).map_or(f32v2::zero(), |contact| contact.position))),
+            Self::CONTACT_COUNT      => Some(AxisValue::Int(   self.get_active_contact_count() as i32)),
+            _ => None
+        }
+    }
+
+    fn get_axes(&self) -> &[InputAxisDefinition] {
+        const MIN_V2: f32v2 = f32v2{ x: 0.0, y: 0.0 };
+        const MAX_V2: f32v2 = f32v2{ x: 1.0, y: 1.0 };
+
+        &[
+            InputAxisDefinition { dev_type: DeviceType::Touch, ids: &[Self::TOUCH]             , axis: AxisDefinition::Digital                 , can_rebind: true  },
+            InputAxisDefinition { dev_type: DeviceType::Touch, ids: &[Self::POSITION]          , axis: AxisDefinition::Axis2D(MIN_V2, MAX_V2)  , can_rebind: false },
+            InputAxisDefinition { dev_type: DeviceType::Touch, ids: &[Self::X]                 , axis: AxisDefinition::Axis  (0.0, 1.0)        , can_rebind: false },
+            InputAxisDefinition { dev_type: DeviceType::Touch, ids: &[Self::Y]                 , axis: AxisDefinition::Axis  (0.0, 1.0)        , can_rebind: false },
+            InputAxisDefinition { dev_type: DeviceType::Touch, ids: &[Self::PRESSURE]          , axis: AxisDefinition::Axis  (0.0, 1.0)        , can_rebind: false },
+            InputAxisDefinition { dev_type: DeviceType::Touch, ids: &[Self::SECONDARY_POSITION], axis: AxisDefinition::Axis2D(MIN_V2, MAX_V2)  , can_rebind: false },
+            InputAxisDefinition { dev_type: DeviceType::Touch, ids: &[Self::CONTACT_COUNT]     , axis: AxisDefinition::Int   (0, i32::MAX)     , can_rebind: false },
+        ]
+    }
+
+    fn get_device_type(&self) -> DeviceType {
+        DeviceType::Touch
+    }
+
+    fn take_native_handle(&mut self) -> NativeDeviceHandle {
+        core::mem::take(&mut self.handle).unwrap()
+    }
+
+    fn get_battery_info(&self) -> Option<crate::BatteryInfo> {
+        None
+    }
+
+    fn get_output_info<'a>(&'a self) -> &'a OutputInfo<'a> {
+        &OutputInfo {
+            rumble: RumbleSupport::None,
+            trigger_feedback: None,
+            led_support: &[],
+            output_axes: &[]
+        }
+    }
+
+    fn set_rumble(&self, _rumble: crate::RumbleState) {
+        // Nothing to do here, as we don't support output
+    }
+
+    fn set_trigger_feedback(&self, _right_trigger: bool, _trigger_feedback: crate::TriggerFeedback) {
+        // Nothing to do here, as we don't support output
+    }
+
+    fn set_led_state(&self, _index: u16, _state: crate::LedState) {
+        // Nothing to do here, as we don't support output
+    }
+
+    fn set_output_axis(&self, _axis: AxisId, _value: AxisValue) {
+        // Nothing to do here, as we don't support output
+    }
+
+    fn get_touch_contacts(&self) -> Vec<TouchContact> {
+        self.get_contacts()
+    }
+}
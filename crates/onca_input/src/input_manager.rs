@@ -1,22 +1,26 @@
 use core::num::NonZeroU8;
-use std::{collections::HashMap, sync::{Arc, atomic::{AtomicBool, Ordering}}, ffi::c_void};
+use std::{collections::HashMap, sync::{Arc, atomic::{AtomicBool, Ordering}}, ffi::c_void, time::Instant};
 
 use onca_common::{
     prelude::*,
     sync::{Mutex, RwLock, MutexGuard},
-    event_listener::EventListener,
+    event_listener::{EventListener, DynEventListenerArray, DynEventListenerRef},
     time::DeltaTime,
     sys,
 };
 use onca_hid as hid;
 use onca_logging::{log_verbose, log_error, log_warning, log_info};
 use onca_toml::Toml;
-use onca_window::WindowManager;
+use onca_window::{WindowManager, Window};
 
 use crate::{
     os::{self, OSInput},
     input_devices::{Keyboard, InputDevice},
-    LOG_INPUT_CAT, Mouse, Gamepad, ControlScheme, User, DeviceType, AxisValue, ControlSchemeID, AxisId, MappingContext, NativeDeviceHandle, Handle, parse_definitions, GenericDevice, DualSense
+    LOG_INPUT_CAT, Mouse, Gamepad, ControlScheme, User, DeviceType, AxisValue, ControlSchemeID, AxisId, MappingContext, NativeDeviceHandle, Handle, parse_definitions, GenericDevice, DualSense,
+    InputRecorder, InputRecording, InputPlayer, RecordedEventKind, RumbleState, RumbleHandle, TriggerFeedback, HapticsMixer, DeviceAssignmentEvent, AxisDefinition,
+    TextInputEvent, TextInputListener, TouchDevice, PenDevice, TouchPhase, GestureSettings, PointerSample, LatencyProbe, LatencyStats,
+    BindingCaptureFilter, BindingCaptureHandle, BindingConflict, CapturedBinding, CursorRequest, CursorRequestId, CursorRequestStack,
+    GamepadBackend, GamepadBackendKind, GamepadBackends, ControllerType, InputSnapshot, InputDebugFrame,
 };
 
 
@@ -93,6 +97,10 @@ impl DeviceStorage {
         }
     }
 
+    fn iter(&self) -> impl Iterator<Item = (Handle, &dyn InputDevice)> {
+        self.devices.iter().enumerate().filter_map(|(idx, (lifetime, opt))| opt.as_ref().map(|dev| (Handle { id: idx as u8, lifetime: *lifetime }, dev.as_ref())))
+    }
+
     fn tick(&mut self, dt: f32, rebinder: &mut Rebinder) {
         for (_, opt) in &mut self.devices {
             if let Some(dev) = opt {
@@ -109,9 +117,9 @@ impl DeviceStorage {
         }
     }
 
-    fn handle_native_input(&mut self, handle: Handle, native_data: *const c_void) {
+    fn handle_native_input(&mut self, handle: Handle, native_data: &[u8]) {
         if let Some(dev) = self.get_device_mut(handle) {
-            dev.handle_native_input(native_data);
+            dev.handle_native_input(native_data.as_ptr() as *const c_void);
         } else {
             log_error!(LOG_INPUT_CAT, "Failed to find device to process hid report")
         }
@@ -167,6 +175,8 @@ pub struct InputManager {
     device_custom_creators:  Mutex<Vec<(Box<dyn Fn(&hid::Identifier, &str) -> bool>, CreateDevicePtr)>>,
     device_product_creators: Mutex<HashMap<hid::VendorProduct, CreateDevicePtr>>,
     device_usage_creators:   Mutex<HashMap<hid::Usage, CreateDevicePtr>>,
+    gamepad_backends:        Mutex<GamepadBackends>,
+    device_backends:         Mutex<Vec<(Handle, GamepadBackendKind)>>,
 
     mapping_contexts:        Mutex<Vec<MappingContext>>,
 
@@ -175,9 +185,26 @@ pub struct InputManager {
 
     unused_devices:          Mutex<Vec<Handle>>,
     has_init_devices:        AtomicBool,
+    device_claiming:         AtomicBool,
+    assignment_listeners:    Mutex<DynEventListenerArray<DeviceAssignmentEvent>>,
+    device_haptics:          Mutex<Vec<(Handle, HapticsMixer)>>,
 
     rebind_context:          Mutex<Option<RebindContext>>,
     rebinder:                Mutex<Rebinder>,
+    binding_capture:         Mutex<Option<BindingCaptureHandle>>,
+
+    text_input:              Arc<Mutex<TextInputListener>>,
+    text_input_passthrough:  Mutex<Vec<AxisId>>,
+
+    cursor_requests:         Mutex<CursorRequestStack>,
+
+    recorder:                Mutex<Option<InputRecorder>>,
+    player:                  Mutex<Option<InputPlayer>>,
+
+    latency:                 Mutex<LatencyProbe>,
+    snapshot:                RwLock<InputSnapshot>,
+    debug_feed_enabled:      AtomicBool,
+    debug_frame:             RwLock<Option<Arc<InputDebugFrame>>>,
 }
 
 impl InputManager {
@@ -200,14 +227,29 @@ impl InputManager {
             device_product_creators: Mutex::new(HashMap::new()),
             device_custom_creators: Mutex::new(Vec::new()),
             device_usage_creators: Mutex::new(HashMap::new()),
+            gamepad_backends: Mutex::new(GamepadBackends::new()),
+            device_backends: Mutex::new(Vec::new()),
             mapping_contexts: Mutex::new(Vec::new()),
             control_schemes: RwLock::new(Vec::new()),
             // Make sure that there is 1 user
             users: RwLock::new(vec![User::new()]),
             has_init_devices: AtomicBool::new(false),
             unused_devices: Mutex::new(Vec::new()),
+            device_claiming: AtomicBool::new(false),
+            assignment_listeners: Mutex::new(DynEventListenerArray::new()),
+            device_haptics: Mutex::new(Vec::new()),
             rebind_context: Mutex::new(None),
             rebinder: Mutex::new(Rebinder::new()),
+            binding_capture: Mutex::new(None),
+            text_input: Arc::new(Mutex::new(TextInputListener::new())),
+            text_input_passthrough: Mutex::new(Vec::new()),
+            cursor_requests: Mutex::new(CursorRequestStack::new()),
+            recorder: Mutex::new(None),
+            player: Mutex::new(None),
+            latency: Mutex::new(LatencyProbe::new()),
+            snapshot: RwLock::new(InputSnapshot::default()),
+            debug_feed_enabled: AtomicBool::new(false),
+            debug_frame: RwLock::new(None),
         });
         ptr.raw_input_listener.lock().init(&ptr);
         window_manager.register_raw_input_listener(ptr.raw_input_listener.clone());
@@ -262,6 +304,45 @@ impl InputManager {
         self.os_input.lock().register_device_usages(&[usage]);
     }
 
+    /// Register a platform gamepad backend with a given priority, backends with a higher priority are tried first.
+    /// 
+    /// Only a single backend can be registered for each kind of backend.
+    pub fn register_gamepad_backend(&self, priority: u32, backend: Box<dyn GamepadBackend>) {
+        self.gamepad_backends.lock().register(priority, backend);
+    }
+
+    /// Change the priority of a gamepad backend, this only affects gamepads that are connected afterwards.
+    pub fn set_gamepad_backend_priority(&self, kind: GamepadBackendKind, priority: u32) {
+        if !self.gamepad_backends.lock().set_priority(kind, priority) {
+            log_warning!(LOG_INPUT_CAT, "Trying to set the priority of an unregistered gamepad backend: {kind:?}");
+        }
+    }
+
+    /// Enable or disable a gamepad backend, this only affects gamepads that are connected afterwards.
+    pub fn set_gamepad_backend_enabled(&self, kind: GamepadBackendKind, enabled: bool) {
+        if !self.gamepad_backends.lock().set_enabled(kind, enabled) {
+            log_warning!(LOG_INPUT_CAT, "Trying to {} an unregistered gamepad backend: {kind:?}", if enabled { "enable" } else { "disable" });
+        }
+    }
+
+    /// Get all enabled and available gamepad backends, in order of priority.
+    pub fn get_active_gamepad_backends(&self) -> Vec<GamepadBackendKind> {
+        self.gamepad_backends.lock().active_backends()
+    }
+
+    /// Get the backend that is used to read a gamepad, `None` if the device is not a gamepad.
+    pub fn get_gamepad_backend(&self, handle: Handle) -> Option<GamepadBackendKind> {
+        self.device_backends.lock().iter().find(|(cur, _)| *cur == handle).map(|(_, kind)| *kind)
+    }
+
+    /// Get the controller type of a gamepad, `None` if the device is not a gamepad.
+    pub fn get_controller_type(&self, handle: Handle) -> Option<ControllerType> {
+        self.get_gamepad_backend(handle)?;
+        let store = self.device_store.read();
+        let dev = store.get_device(handle)?;
+        Some(ControllerType::from_identifier(dev.get_hid_identifier(), dev.get_unique_identifier()))
+    }
+
     pub fn register_generic_hid_definitions(&self, toml: &Toml) {
         let defs = parse_definitions(toml);
         for def in defs {
@@ -351,6 +432,32 @@ impl InputManager {
         })
     }
   
+    /// Start capturing the next meaningful input that passes the filter, e.g. for a "press a key to bind" menu.
+    /// 
+    /// Any capture that is still in progress will be cancelled.
+    /// The returned handle can be polled for the captured binding, which will also contain any conflicts with the bindings in the active mapping contexts.
+    pub fn start_binding_capture(&self, filter: BindingCaptureFilter) -> BindingCaptureHandle {
+        let handle = BindingCaptureHandle::new(filter);
+        if let Some(prev) = self.binding_capture.lock().replace(handle.clone()) {
+            prev.cancel();
+        }
+        handle
+    }
+
+    /// Find all bindings in the active mapping contexts that are bound to the given axis.
+    /// 
+    /// When a user is given, only the mapping contexts of that user are checked.
+    pub fn find_binding_conflicts(&self, axis: &AxisId, user_idx: Option<u8>) -> Vec<BindingConflict> {
+        let mut conflicts = Vec::new();
+        let users = self.users.read();
+        for (idx, user) in users.iter().enumerate() {
+            if user_idx.map_or(true, |user_idx| user_idx as usize == idx) {
+                user.find_binding_conflicts(core::slice::from_ref(axis), &mut conflicts);
+            }
+        }
+        conflicts
+    }
+
     pub fn tick(&self, dt: DeltaTime) {
         assert!(sys::is_on_main_thread(), "The input manager should only be ticked on the main thread");
 
@@ -363,6 +470,13 @@ impl InputManager {
 
         // Update OS input
         self.os_input.lock().tick();
+        self.gamepad_backends.lock().tick();
+
+        // All events since the last tick belong to this frame
+        let dt = self.play_frame(dt);
+        if let Some(recorder) = &mut *self.recorder.lock() {
+            recorder.end_frame(dt.get_dt());
+        }
         
         // Update devices
         let mut rebinder = self.rebinder.lock();
@@ -374,27 +488,378 @@ impl InputManager {
         
         let device_store = self.device_store.read();
         let mut users = self.users.write();
+        let mut assignment_events = Vec::new();
+
+        Self::update_gestures(&users, &device_store, dt.get_dt());
+        self.update_binding_capture(&users, &device_store);
+
+        let mut latency = self.latency.lock();
+        let mut action_snapshots = Vec::new();
+        let mut debug_actions = self.debug_feed_enabled.load(Ordering::Relaxed).then(Vec::new);
+
+        // While text entry is active, keyboard input is meant for the text, not for any action
+        let text_passthrough = self.text_input_passthrough.lock();
+        let text_passthrough = if self.is_text_input_active() { Some(text_passthrough.as_slice()) } else { None };
+
         if users.len() != 1 {
             let schemes = self.control_schemes.read();
             let mut unused_devices = self.unused_devices.lock();
-            // If there are users that don't have a control set, try to generate them
+            let device_claiming = self.device_claiming.load(Ordering::SeqCst);
+            if device_claiming {
+                Self::claim_devices(&mut users, &mut unused_devices, &device_store, &mut assignment_events);
+            }
+
+            // If there are users that don't have a control set, try to generate them.
+            // While device claiming is enabled, devices are only assigned when they are claimed, otherwise a scheme would grab the devices before they could be claimed.
             for (user_idx, user) in users.iter_mut().enumerate() {
-                if !unused_devices.is_empty() && user.control_set().is_none() {
+                if !device_claiming && !unused_devices.is_empty() && user.control_set().is_none() {
                     for scheme in &*schemes {
                         // The first control scheme that can be created will be used
                         if let Some(control_set) = scheme.create_control_set(&unused_devices, |handle| device_store.get_device_types(handle)) {
                             unused_devices.retain(|handle| !control_set.devices().contains(handle));
+                            assignment_events.extend(control_set.devices().iter().map(|handle| DeviceAssignmentEvent::Assigned { user_idx: user_idx as u8, handle: *handle }));
                             user.set_control_set(control_set);
                             break;
                         }
                     }
                 }
-                user.process_input(dt, user_idx as u8, |user, axis| Self::get_input_for_user(user, axis, &device_store));
+                let input_timestamp = latency.latest_event_time(user.control_set().map(|set| set.devices().as_slice()));
+                user.process_input(dt, user_idx as u8, input_timestamp, &mut action_snapshots, debug_actions.as_mut(), |user, axis| Self::get_input_for_user(user, axis, &device_store, text_passthrough));
             }
         } else {
             assert!(users.len() == 1);
-            users[0].process_input(dt, 0, |_, axis| self.get_input_for_any(axis, &device_store));
+            users[0].process_input(dt, 0, latency.latest_event_time(None), &mut action_snapshots, debug_actions.as_mut(), |_, axis| self.get_input_for_any(axis, &device_store, text_passthrough));
+        }
+
+        self.update_haptics(&users, &device_store, dt.get_dt());
+
+        {
+            let mut snapshot = self.snapshot.write();
+            let frame = snapshot.frame() + 1;
+            *snapshot = InputSnapshot::new(frame, action_snapshots);
+            *self.debug_frame.write() = debug_actions.map(|actions| Arc::new(InputDebugFrame { frame, actions }));
+        }
+
+        // All events received since the last tick have now been processed
+        latency.end_frame(Instant::now());
+        drop(latency);
+
+        // Listeners may call back into the input manager, so release all locks first
+        drop(users);
+        drop(device_store);
+        self.notify_assignment_events(&assignment_events);
+    }
+
+    /// Assign a device to a user, unassigning it from the user that currently holds it.
+    /// 
+    /// When a user does not have a control set yet, assigning a device will create one that is not associated with any control scheme,
+    /// so no control scheme will automatically be created for that user anymore.
+    /// 
+    /// Device assignment only has an effect when there is more than 1 user, as a single user will always receive input from all devices.
+    pub fn assign_device(&self, user_idx: u8, handle: Handle) -> bool {
+        if !self.has_device(handle) {
+            log_warning!(LOG_INPUT_CAT, "Trying to assign a device that does not exist");
+            return false;
+        }
+
+        let mut events = Vec::new();
+        {
+            let mut users = self.users.write();
+            if user_idx as usize >= users.len() {
+                log_warning!(LOG_INPUT_CAT, "Trying to assign a device to a user that cannot exists");
+                return false;
+            }
+
+            if let Some(cur_user_idx) = users.iter().position(|user| user.has_device(handle)) {
+                if cur_user_idx == user_idx as usize {
+                    return true;
+                }
+                users[cur_user_idx].unassign_device(handle);
+                events.push(DeviceAssignmentEvent::Unassigned { user_idx: cur_user_idx as u8, handle });
+            } else {
+                self.unused_devices.lock().retain(|val| *val != handle);
+            }
+
+            users[user_idx as usize].assign_device(handle);
+            events.push(DeviceAssignmentEvent::Assigned { user_idx, handle });
+        }
+        self.notify_assignment_events(&events);
+        true
+    }
+
+    /// Unassign a device from the user that currently holds it, making it available to be assigned to another user.
+    pub fn unassign_device(&self, handle: Handle) {
+        let user_idx = {
+            let mut users = self.users.write();
+            let Some(user_idx) = users.iter().position(|user| user.has_device(handle)) else { return };
+            users[user_idx].unassign_device(handle);
+            self.unused_devices.lock().push(handle);
+            user_idx as u8
+        };
+        self.notify_assignment_events(&[DeviceAssignmentEvent::Unassigned { user_idx, handle }]);
+    }
+
+    /// Get the index of the user a device is assigned to.
+    pub fn get_device_user(&self, handle: Handle) -> Option<u8> {
+        self.users.read().iter().position(|user| user.has_device(handle)).map(|idx| idx as u8)
+    }
+
+    /// Enable or disable device claiming.
+    /// 
+    /// When enabled, an unassigned device will be assigned to the first user without any devices as soon as one of its buttons is pressed,
+    /// e.g. for "press any button to join" in split-screen games.
+    /// 
+    /// While enabled, no control sets are automatically created from the registered control schemes.
+    pub fn set_device_claiming(&self, enabled: bool) {
+        self.device_claiming.store(enabled, Ordering::SeqCst);
+    }
+
+    /// Register a listener that is notified when devices are assigned to or unassigned from a user.
+    pub fn register_device_assignment_listener(&self, listener: DynEventListenerRef<DeviceAssignmentEvent>) {
+        self.assignment_listeners.lock().push(listener);
+    }
+
+    /// Unregister a device assignment listener.
+    pub fn unregister_device_assignment_listener(&self, listener: &DynEventListenerRef<DeviceAssignmentEvent>) {
+        self.assignment_listeners.lock().remove(listener);
+    }
+
+    /// Start text entry for a window, e.g. when a text field in the UI receives focus.
+    /// 
+    /// While text entry is active, committed text and IME composition events of the window are sent to all text input listeners,
+    /// and keyboard input will not trigger any actions, except for keys set using [`InputManager::set_text_input_passthrough`].
+    /// 
+    /// Text entry can only be active for a single window at a time, [`InputManager::stop_text_input`] needs to be called before starting it for another window.
+    pub fn start_text_input(&self, window: &mut Window) {
+        let mut text_input = self.text_input.lock();
+        if let Some(window_id) = text_input.window() {
+            if window_id != window.id() {
+                log_warning!(LOG_INPUT_CAT, "Cannot start text input for window '{}' while it is still active for window '{window_id}'", window.id());
+            }
+            return;
+        }
+        window.register_window_listener(self.text_input.clone());
+        text_input.start(window.id());
+        window.set_ime_allowed(true);
+    }
+
+    /// Stop text entry for a window.
+    /// 
+    /// If an IME composition is still in progress, text input listeners will receive a `CompositionEnded` event.
+    pub fn stop_text_input(&self, window: &mut Window) {
+        let mut text_input = self.text_input.lock();
+        if text_input.window() != Some(window.id()) {
+            return;
+        }
+        text_input.stop();
+        window.set_ime_allowed(false);
+        drop(text_input);
+
+        window.unregister_window_listener(self.text_input.clone());
+    }
+
+    /// Check if text entry is currently active.
+    pub fn is_text_input_active(&self) -> bool {
+        self.text_input.lock().window().is_some()
+    }
+
+    /// Set the keyboard keys that can still trigger actions while text entry is active, e.g. a key to close a chat window.
+    pub fn set_text_input_passthrough(&self, axes: &[AxisId]) {
+        *self.text_input_passthrough.lock() = axes.to_vec();
+    }
+
+    /// Register a listener that receives text and IME composition events while text entry is active.
+    /// 
+    /// Listeners are notified while window messages are processed, so they are not allowed to start or stop text entry from within the callback.
+    pub fn register_text_input_listener(&self, listener: DynEventListenerRef<TextInputEvent>) {
+        self.text_input.lock().register(listener);
+    }
+
+    /// Unregister a text input listener.
+    pub fn unregister_text_input_listener(&self, listener: &DynEventListenerRef<TextInputEvent>) {
+        self.text_input.lock().unregister(listener);
+    }
+
+    /// Push a cursor request on top of the request stack and apply it to the window.
+    /// 
+    /// The returned id is used to remove the request again, e.g. the game requests relative mode for its camera, while a menu requests a free cursor while it is open.
+    pub fn push_cursor_request(&self, window: &mut Window, request: CursorRequest) -> CursorRequestId {
+        let mut requests = self.cursor_requests.lock();
+        let id = requests.push(request);
+        Self::apply_cursor_request(window, requests.current());
+        id
+    }
+
+    /// Remove a cursor request and apply the request that is now on top of the stack to the window.
+    pub fn remove_cursor_request(&self, window: &mut Window, id: CursorRequestId) {
+        let mut requests = self.cursor_requests.lock();
+        if !requests.remove(id) {
+            log_warning!(LOG_INPUT_CAT, "Trying to remove a cursor request that does not exist");
+            return;
+        }
+        Self::apply_cursor_request(window, requests.current());
+    }
+
+    /// Get the cursor request that is currently applied.
+    pub fn current_cursor_request(&self) -> CursorRequest {
+        self.cursor_requests.lock().current()
+    }
+
+    /// Play a rumble effect on all devices of a user.
+    /// 
+    /// If a `duration` (in seconds) is given, the effect is automatically stopped when it runs out, otherwise it plays until [`InputManager::stop_rumble`] is called.
+    /// Effects that play at the same time are mixed by the user's [`HapticsMixer`].
+    pub fn play_rumble(&self, user_idx: u8, rumble: RumbleState, duration: Option<f32>) -> Option<RumbleHandle> {
+        let users = self.users.read();
+        match users.get(user_idx as usize) {
+            Some(user) => Some(user.haptics().lock().play(rumble, duration)),
+            None => {
+                log_warning!(LOG_INPUT_CAT, "Trying to play a rumble effect for a user that cannot exists");
+                None
+            }
+        }
+    }
+
+    /// Set the settings used to recognize gestures for a user.
+    pub fn set_gesture_settings(&self, user_idx: u8, settings: GestureSettings) {
+        let users = self.users.read();
+        match users.get(user_idx as usize) {
+            Some(user) => user.gestures().lock().set_settings(settings),
+            None => log_warning!(LOG_INPUT_CAT, "Trying to set the gesture settings for a user that cannot exists"),
+        }
+    }
+
+    /// Rumble the low and high frequency motors of all devices of a user for `duration` seconds.
+    pub fn set_rumble(&self, user_idx: u8, low_freq: f32, high_freq: f32, duration: f32) -> Option<RumbleHandle> {
+        self.play_rumble(user_idx, RumbleState { low_frequency: low_freq, high_frequency: high_freq, ..Default::default() }, Some(duration))
+    }
+
+    /// Stop a rumble effect that is playing for a user.
+    pub fn stop_rumble(&self, user_idx: u8, handle: RumbleHandle) {
+        if let Some(user) = self.users.read().get(user_idx as usize) {
+            user.haptics().lock().stop(handle);
+        }
+    }
+
+    /// Rumble the low and high frequency motors of a single device for `duration` seconds.
+    /// 
+    /// The effect is mixed with the effects playing for the user the device is assigned to, so both can be felt at the same time.
+    pub fn set_device_rumble(&self, handle: Handle, low_freq: f32, high_freq: f32, duration: f32) -> Option<RumbleHandle> {
+        if !self.has_device(handle) {
+            log_warning!(LOG_INPUT_CAT, "Trying to play a rumble effect on a device that does not exist");
+            return None;
         }
+
+        let rumble = RumbleState { low_frequency: low_freq, high_frequency: high_freq, ..Default::default() };
+        let mut device_haptics = self.device_haptics.lock();
+        let idx = match device_haptics.iter().position(|(dev_handle, _)| *dev_handle == handle) {
+            Some(idx) => idx,
+            None => {
+                device_haptics.push((handle, HapticsMixer::new()));
+                device_haptics.len() - 1
+            },
+        };
+        Some(device_haptics[idx].1.play(rumble, Some(duration)))
+    }
+
+    /// Stop a rumble effect that is playing on a single device.
+    pub fn stop_device_rumble(&self, handle: Handle, rumble_handle: RumbleHandle) {
+        if let Some((_, mixer)) = self.device_haptics.lock().iter_mut().find(|(dev_handle, _)| *dev_handle == handle) {
+            mixer.stop(rumble_handle);
+        }
+    }
+
+    /// Set the trigger feedback for all devices of a user that support it.
+    pub fn set_trigger_feedback(&self, user_idx: u8, right_trigger: bool, trigger_feedback: TriggerFeedback) {
+        match self.users.read().get(user_idx as usize) {
+            Some(user) => user.haptics().lock().set_trigger_feedback(right_trigger, trigger_feedback),
+            None => log_warning!(LOG_INPUT_CAT, "Trying to set the trigger feedback for a user that cannot exists"),
+        }
+    }
+
+    /// Start recording all device events, replacing any recording that is currently in progress.
+    pub fn start_recording(&self, recorder: InputRecorder) {
+        let mut cur_recorder = self.recorder.lock();
+        if cur_recorder.is_some() {
+            log_warning!(LOG_INPUT_CAT, "Starting a new input recording while another recording is in progress, the previous recording will be discarded");
+        }
+        *cur_recorder = Some(recorder);
+    }
+
+    /// Stop recording and return the recording, if a recording was in progress.
+    pub fn stop_recording(&self) -> Option<InputRecording> {
+        self.recorder.lock().take().map(|recorder| recorder.finish())
+    }
+
+    /// Check if device events are currently being recorded.
+    pub fn is_recording(&self) -> bool {
+        self.recorder.lock().is_some()
+    }
+
+    /// Start playing back a recording, which will replace all live device input until the playback is finished or stopped.
+    /// 
+    /// Devices in the recording are matched to connected devices with the same HID identifier, events for devices that can't be matched are skipped.
+    pub fn start_playback(&self, mut player: InputPlayer) {
+        let missing = {
+            let device_store = self.device_store.read();
+            let connected = device_store.devices.iter()
+                .enumerate()
+                .filter_map(|(idx, (lifetime, dev))| dev.as_ref().map(|dev| (Handle { id: idx as u8, lifetime: *lifetime }, dev.get_hid_identifier())));
+            player.map_devices(connected)
+        };
+        for iden in missing {
+            log_warning!(LOG_INPUT_CAT, "No connected device found for recorded device '{iden}', its input will be skipped during playback");
+        }
+        *self.player.lock() = Some(player);
+    }
+
+    /// Stop the current playback and return the player, if a recording was being played back.
+    pub fn stop_playback(&self) -> Option<InputPlayer> {
+        self.player.lock().take()
+    }
+
+    /// Check if a recording is currently being played back.
+    pub fn is_playing_back(&self) -> bool {
+        self.player.lock().is_some()
+    }
+
+    /// Get the event-to-process latency statistics of each device that has received input, for diagnosing input lag.
+    pub fn latency_stats(&self) -> Vec<(Handle, LatencyStats)> {
+        self.latency.lock().stats()
+    }
+
+    /// Reset the latency statistics of all devices.
+    pub fn reset_latency_stats(&self) {
+        self.latency.lock().reset();
+    }
+
+    /// Get a snapshot of the state of all actions at the end of the last tick.
+    /// 
+    /// The snapshot is immutable and cheap to clone, so it can be handed to jobs that run on other threads.
+    pub fn snapshot(&self) -> InputSnapshot {
+        self.snapshot.read().clone()
+    }
+
+    /// Enable or disable the debug feed of the mapping pipeline.
+    /// 
+    /// Collecting the debug feed has a cost, so it should only be enabled while it is being displayed.
+    pub fn set_debug_feed_enabled(&self, enabled: bool) {
+        self.debug_feed_enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            *self.debug_frame.write() = None;
+        }
+    }
+
+    /// Check if the debug feed of the mapping pipeline is enabled.
+    pub fn is_debug_feed_enabled(&self) -> bool {
+        self.debug_feed_enabled.load(Ordering::Relaxed)
+    }
+
+    /// Get the debug info of the mapping pipeline of the last tick, `None` if the debug feed is not enabled.
+    /// 
+    /// The info contains the raw inputs, applied modifiers and trigger results of each mapping, as well as which context processed an action, to help debug why an action did or did not fire.
+    pub fn debug_frame(&self) -> Option<Arc<InputDebugFrame>> {
+        self.debug_frame.read().clone()
     }
 
     /// Set the maximum number of users that can be created.4
@@ -434,7 +899,7 @@ impl InputManager {
     pub(crate) fn add_device(&self, iden: hid::Identifier, native_handle: NativeDeviceHandle) -> Option<Handle> {
         //log_info!(LOG_INPUT_CAT, "Trying to add device {device}");
 
-        if let Some(input_dev) = self.create_input_device_for(iden, native_handle) {
+        if let Some((input_dev, backend)) = self.create_input_device_for(iden, native_handle) {
             
             let usage = iden.usage;
             let vendor = input_dev.get_native_handle().hid_dev.as_ref().map_or_else(
@@ -457,6 +922,14 @@ impl InputManager {
 
             let dev = store.get_device(handle).unwrap();
             self.os_input.lock().notify_device_added(handle, dev.get_native_handle());
+            if let Some(backend) = backend {
+                self.device_backends.lock().push((handle, backend));
+            }
+
+            // Make sure the new device gets the current haptics state
+            for user in &*self.users.read() {
+                user.haptics().lock().invalidate();
+            }
 
             // First check if any user had this device disconnected, if so, give it the device and try to create the control scheme
             for user in &mut *self.users.write() {
@@ -472,12 +945,66 @@ impl InputManager {
         }
     }
 
-    pub(crate) fn handle_hid_input(&self, handle: Handle, raw_report: &[u8]) {
-        self.device_store.write().handle_hid_input(handle, raw_report);
+    /// Handle a hid report, the timestamp is the time at which the OS received the input.
+    pub(crate) fn handle_hid_input(&self, handle: Handle, raw_report: &[u8], timestamp: Instant) {
+        // Live input is ignored during playback
+        if self.player.lock().is_some() {
+            return;
+        }
+        self.latency.lock().add_event(handle, timestamp);
+
+        let mut device_store = self.device_store.write();
+        if let Some(recorder) = &mut *self.recorder.lock() {
+            recorder.record(handle, RecordedEventKind::Hid, raw_report, || device_store.get_device(handle).map(|dev| *dev.get_hid_identifier()));
+        }
+        device_store.handle_hid_input(handle, raw_report);
+    }
+
+    /// Handle native input, the data is the native OS input struct for the device, e.g. a `RAWKEYBOARD` on windows.
+    /// 
+    /// The timestamp is the time at which the OS received the input.
+    pub (crate) fn handle_native_input(&self, handle: Handle, native_data: &[u8], timestamp: Instant) {
+        // Live input is ignored during playback
+        if self.player.lock().is_some() {
+            return;
+        }
+        self.latency.lock().add_event(handle, timestamp);
+
+        let mut device_store = self.device_store.write();
+        if let Some(recorder) = &mut *self.recorder.lock() {
+            recorder.record(handle, RecordedEventKind::Native, native_data, || device_store.get_device(handle).map(|dev| *dev.get_hid_identifier()));
+        }
+        device_store.handle_native_input(handle, native_data);
     }
 
-    pub (crate) fn handle_native_input(&self, handle: Handle, native_data: *const c_void) {
-        self.device_store.write().handle_native_input(handle, native_data);
+    /// Inject the events of the next frame of the playback, and return the delta time to use for the frame.
+    fn play_frame(&self, dt: DeltaTime) -> DeltaTime {
+        let mut player = self.player.lock();
+        let Some(cur_player) = &mut *player else { return dt };
+
+        let Some((frame, handles)) = cur_player.next_frame() else {
+            log_info!(LOG_INPUT_CAT, "Input playback finished");
+            *player = None;
+            return dt;
+        };
+
+        let mut device_store = self.device_store.write();
+        for event in &frame.events {
+            let Some(handle) = handles[event.device as usize] else { continue };
+            match event.kind {
+                RecordedEventKind::Hid => device_store.handle_hid_input(handle, &event.data),
+                RecordedEventKind::Native => {
+                    // Native data is read as the OS struct, so it needs to be correctly aligned
+                    let mut aligned = vec![0u64; event.data.len().div_ceil(8)];
+                    // SAFETY: The buffer is at least as large as the data
+                    unsafe { core::ptr::copy_nonoverlapping(event.data.as_ptr(), aligned.as_mut_ptr() as *mut u8, event.data.len()) };
+                    // SAFETY: Any bit pattern is a valid u8, and the slice covers only initialized memory of the buffer
+                    let data = unsafe { core::slice::from_raw_parts(aligned.as_ptr() as *const u8, event.data.len()) };
+                    device_store.handle_native_input(handle, data);
+                },
+            }
+        }
+        DeltaTime::with_dilation(frame.dt, dt.get_dilation())
     }
 
     pub(crate) fn remove_device(&self, handle: Handle) {
@@ -505,6 +1032,9 @@ impl InputManager {
             Some(handle) => handle,
             None => return,
         };
+        self.unused_devices.lock().retain(|val| *val != handle);
+        self.latency.lock().remove_device(handle);
+        self.device_backends.lock().retain(|(cur, _)| *cur != handle);
 
         let mut users = self.users.write();
         for (idx, user) in users.iter_mut().enumerate() {
@@ -578,11 +1108,11 @@ impl InputManager {
             //hid::Usage::from_u16(1, 7),
 
             // - External Pen Device
-            //hid::Usage::from_u16(13, 1),
+            hid::Usage::from_u16(13, 1),
             // - Integrated Pen Device
-            //hid::Usage::from_u16(13, 2),
+            hid::Usage::from_u16(13, 2),
             // - Touchscreen
-            //hid::Usage::from_u16(13, 4),
+            hid::Usage::from_u16(13, 4),
             // - Precision Touchpad
             //hid::Usage::from_u16(13, 5),
         ];
@@ -604,6 +1134,21 @@ impl InputManager {
             res
         }));
 
+        for usage in [hid::Usage::from_u16(13, 1), hid::Usage::from_u16(13, 2)] {
+            self.register_usage_creator_device(usage, |handle| PenDevice::new(handle).map(|x| {
+                // We need to get around rust not realizing that `Box` could `CoerseUnsized` directly in a return statement
+                // This could be one of those "std::boxed::Box is special" cases, as the first line clearly shows that it works
+                let res: Box<dyn InputDevice> = Box::new(x);
+                res
+            }));
+        }
+        self.register_usage_creator_device(hid::Usage::from_u16(13, 4), |handle| TouchDevice::new(handle).map(|x| {
+            // We need to get around rust not realizing that `Box` could `CoerseUnsized` directly in a return statement
+            // This could be one of those "std::boxed::Box is special" cases, as the first line clearly shows that it works
+            let res: Box<dyn InputDevice> = Box::new(x);
+            res
+        }));
+
         let gamepad_usage = hid::Usage::from_u16(1, 5);
         self.register_usage_creator_device(gamepad_usage, |handle| Gamepad::new(handle).map(|x| {
             // We need to get around rust not realizing that `Box` could `CoerseUnsized` directly in a return statement
@@ -629,7 +1174,8 @@ impl InputManager {
         self.has_init_devices.store(true, Ordering::SeqCst);
     }
 
-    fn create_input_device_for(&self, ident: hid::Identifier, mut handle: NativeDeviceHandle) -> Option<Box<dyn InputDevice>> {
+    /// Create an input device for a native device, also returning the backend used when the device is a gamepad.
+    fn create_input_device_for(&self, ident: hid::Identifier, mut handle: NativeDeviceHandle) -> Option<(Box<dyn InputDevice>, Option<GamepadBackendKind>)> {
         // Find the best fitting, registered device
         //
         // 1) any device that matches custom logic
@@ -638,23 +1184,33 @@ impl InputManager {
 
         if let Some(create) = self.device_custom_creators.lock().iter().find(|(fun, _)| fun(hid_iden, unique_iden)) {
             handle = match create.1(handle) {
-                Ok(ptr) => return Some(ptr),
+                Ok(ptr) => return Some((ptr, None)),
                 Err(handle) => handle,
             };
         }
 
-        // 2) any device that matches the specific vendor and product
+        // 2) gamepads are handled by the highest priority platform backend that supports it
+        let is_gamepad = ident.usage == hid::Usage::from_u16(1, 4) || ident.usage == hid::Usage::from_u16(1, 5);
+        if is_gamepad {
+            handle = match self.gamepad_backends.lock().create_device(handle) {
+                Ok((ptr, backend)) => return Some((ptr, Some(backend))),
+                Err(handle) => handle,
+            };
+        }
+        let raw_hid_backend = if is_gamepad { Some(GamepadBackendKind::RawHid) } else { None };
+
+        // 3) any device that matches the specific vendor and product
         if let Some(create) = self.device_product_creators.lock().get(&ident.vendor_device) {
             handle = match create(handle) {
-                Ok(ptr) => return Some(ptr),
+                Ok(ptr) => return Some((ptr, raw_hid_backend)),
                 Err(handle) => handle,
             };
         }
 
-        // 3) any device that matches the usage
+        // 4) any device that matches the usage
         if let Some(create) = self.device_usage_creators.lock().get(&ident.usage) {
             _ = match create(handle) {
-                Ok(ptr) => return Some(ptr),
+                Ok(ptr) => return Some((ptr, raw_hid_backend)),
                 Err(handle) => handle,
             };
         }
@@ -664,9 +1220,202 @@ impl InputManager {
         None
     }
     
-    fn get_input_for_any(&self, axis_path: &AxisId, device_store: &DeviceStorage) -> AxisValue {
+    fn claim_devices(users: &mut [User], unused_devices: &mut Vec<Handle>, device_store: &DeviceStorage, events: &mut Vec<DeviceAssignmentEvent>) {
+        unused_devices.retain(|handle| {
+            let Some(dev) = device_store.get_device(*handle) else { return true };
+            let pressed = dev.get_axes().iter()
+                .filter(|axis| matches!(axis.axis, AxisDefinition::Digital))
+                .any(|axis| axis.ids.iter().any(|id| matches!(dev.get_axis_value(id), Some(AxisValue::Digital(true)))));
+            if !pressed {
+                return true;
+            }
+
+            match users.iter().position(|user| user.control_set().is_none()) {
+                Some(user_idx) => {
+                    users[user_idx].assign_device(*handle);
+                    events.push(DeviceAssignmentEvent::Assigned { user_idx: user_idx as u8, handle: *handle });
+                    false
+                },
+                None => true,
+            }
+        });
+    }
+
+    fn notify_assignment_events(&self, events: &[DeviceAssignmentEvent]) {
+        if events.is_empty() {
+            return;
+        }
+
+        let mut listeners = self.assignment_listeners.lock();
+        for event in events {
+            listeners.notify(event);
+        }
+    }
+
+    fn update_haptics(&self, users: &[User], device_store: &DeviceStorage, dt: f32) {
+        let mut device_haptics = self.device_haptics.lock();
+        let mut changed_devices = Vec::new();
+        device_haptics.retain_mut(|(handle, mixer)| {
+            if !device_store.has_device(*handle) {
+                return false;
+            }
+            if mixer.tick(dt).is_some() {
+                changed_devices.push(*handle);
+            }
+            true
+        });
+
+        // Device effects are mixed with the effects of the user the device belongs to
+        let device_rumble = |handle: Handle, user_rumble: RumbleState| match device_haptics.iter().find(|(dev_handle, _)| *dev_handle == handle) {
+            Some((_, mixer)) => mixer.mix_with(user_rumble),
+            None        => user_rumble,
+        };
+
+        for user in users {
+            let mut haptics = user.haptics().lock();
+            let user_changed = haptics.tick(dt).is_some();
+            let user_rumble = haptics.mixed_rumble();
+            let trigger_feedback = haptics.take_trigger_feedback();
+            if !user_changed && trigger_feedback.is_none() && changed_devices.is_empty() {
+                continue;
+            }
+
+            let apply = |handle: Handle, dev: &dyn InputDevice| {
+                let output_info = dev.get_output_info();
+                if (user_changed || changed_devices.contains(&handle)) && output_info.rumble.is_any() {
+                    dev.set_rumble(device_rumble(handle, user_rumble));
+                }
+                if let Some([left, right]) = trigger_feedback && output_info.trigger_feedback.is_some() {
+                    dev.set_trigger_feedback(false, left);
+                    dev.set_trigger_feedback(true, right);
+                }
+            };
+
+            // With only a single user, all devices belong to that user
+            if users.len() == 1 {
+                device_store.iter().for_each(|(handle, dev)| apply(handle, dev));
+            } else if let Some(control_set) = user.control_set() {
+                control_set.devices().iter().filter_map(|handle| device_store.get_device(*handle).map(|dev| (*handle, dev))).for_each(|(handle, dev)| apply(handle, dev));
+            }
+        }
+
+        // Devices that are not assigned to any user only play their own effects
+        if users.len() != 1 {
+            for handle in changed_devices {
+                if users.iter().any(|user| user.has_device(handle)) {
+                    continue;
+                }
+                if let Some(dev) = device_store.get_device(handle) && dev.get_output_info().rumble.is_any() {
+                    dev.set_rumble(device_rumble(handle, RumbleState::default()));
+                }
+            }
+        }
+    }
+
+    /// Feed device input to the active binding capture, and complete it once an axis is captured, limited to the devices of the user being captured for, if any.
+    fn update_binding_capture(&self, users: &[User], device_store: &DeviceStorage) {
+        let mut binding_capture = self.binding_capture.lock();
+        let Some(handle) = &*binding_capture else { return };
+        let mut capture = handle.0.lock();
+        if !capture.is_capturing() {
+            drop(capture);
+            *binding_capture = None;
+            return;
+        }
+
+        // With a single user, all devices belong to that user
+        let user = capture.filter().user_idx.filter(|_| users.len() != 1).map(|idx| users.get(idx as usize));
+        let user_devices = match user {
+            Some(Some(user)) => Some(user.control_set().map_or(&[][..], |set| set.devices().as_slice())),
+            Some(None) => {
+                log_warning!(LOG_INPUT_CAT, "Trying to capture a binding for a user that does not exist");
+                return;
+            },
+            None => None,
+        };
+
+        let mut captured = None;
+        for (dev_handle, dev) in device_store.iter() {
+            if user_devices.map_or(false, |devices| !devices.contains(&dev_handle)) {
+                continue;
+            }
+            // All devices are checked, so each device's resting values are known before any input gets captured
+            if let Some(res) = capture.check_device(dev_handle, dev) && captured.is_none() {
+                captured = Some(res);
+            }
+        }
+
+        if let Some((ids, device_type, axis_type)) = captured {
+            let mut conflicts = Vec::new();
+            for (idx, user) in users.iter().enumerate() {
+                if capture.filter().user_idx.map_or(true, |user_idx| user_idx as usize == idx) {
+                    user.find_binding_conflicts(ids, &mut conflicts);
+                }
+            }
+            capture.set_captured(CapturedBinding { axis: ids[0].clone(), device_type, axis_type, conflicts });
+            drop(capture);
+            *binding_capture = None;
+        }
+    }
+
+    fn apply_cursor_request(window: &mut Window, request: CursorRequest) {
+        window.set_cursor_grab(request.grab);
+        window.set_cursor_visible(request.visible);
+    }
+
+    /// Check if input of a device is suppressed because text entry is active, `text_passthrough` contains the keys that are still allowed to trigger actions.
+    fn is_text_input_suppressed(dev: &dyn InputDevice, axis_path: &AxisId, text_passthrough: Option<&[AxisId]>) -> bool {
+        match text_passthrough {
+            Some(passthrough) => dev.get_device_type() == DeviceType::Keyboard && !passthrough.contains(axis_path),
+            None => false,
+        }
+    }
+
+    fn update_gestures(users: &[User], device_store: &DeviceStorage, dt: f32) {
+        for user in users {
+            let mut gestures = user.gestures().lock();
+            let mouse_surface_size = gestures.settings().mouse_surface_size;
+
+            let mut samples = Vec::new();
+            let mut add_samples = |handle: Handle, dev: &dyn InputDevice| {
+                if dev.get_device_type() == DeviceType::Mouse {
+                    // The left mouse button emulates a single touch contact
+                    if let (Some(AxisValue::Digital(down)), Some(AxisValue::Axis2D(pos))) = (dev.get_axis_value(&Mouse::LEFT_BUTTON), dev.get_axis_value(&Mouse::XY)) {
+                        samples.push(PointerSample { key: (handle, 0), position: pos / mouse_surface_size, down });
+                    }
+                } else {
+                    for contact in dev.get_touch_contacts() {
+                        if contact.phase != TouchPhase::Cancelled {
+                            samples.push(PointerSample { key: (handle, contact.id), position: contact.position, down: contact.phase != TouchPhase::Ended });
+                        }
+                    }
+                }
+            };
+
+            // With only a single user, all devices belong to that user
+            if users.len() == 1 {
+                for (idx, (lifetime, dev)) in device_store.devices.iter().enumerate() {
+                    if let Some(dev) = dev {
+                        add_samples(Handle { id: idx as u8, lifetime: *lifetime }, dev.as_ref());
+                    }
+                }
+            } else if let Some(control_set) = user.control_set() {
+                for handle in control_set.devices() {
+                    if let Some(dev) = device_store.get_device(*handle) {
+                        add_samples(*handle, dev);
+                    }
+                }
+            }
+            gestures.update(dt, &samples);
+        }
+    }
+
+    fn get_input_for_any(&self, axis_path: &AxisId, device_store: &DeviceStorage, text_passthrough: Option<&[AxisId]>) -> AxisValue {
         for opt in &device_store.devices {
             if let (_, Some(dev)) = opt {
+                if Self::is_text_input_suppressed(dev.as_ref(), axis_path, text_passthrough) {
+                    continue;
+                }
                 if let Some(val) = dev.get_axis_value(axis_path) {
                     return val;
                 }
@@ -676,12 +1425,13 @@ impl InputManager {
         AxisValue::Digital(false)
     }
 
-    fn get_input_for_user(user: &User, axis_path: &AxisId, device_store: &DeviceStorage) -> AxisValue {
+    fn get_input_for_user(user: &User, axis_path: &AxisId, device_store: &DeviceStorage, text_passthrough: Option<&[AxisId]>) -> AxisValue {
         match user.control_set() {
             Some(control_set) => {
                 for handle in control_set.devices() {
                     let store = device_store;
-                    if let Some(val) = store.get_device(*handle).and_then(|dev| dev.get_axis_value(axis_path)) {
+                    let dev = store.get_device(*handle).filter(|dev| !Self::is_text_input_suppressed(*dev, axis_path, text_passthrough));
+                    if let Some(val) = dev.and_then(|dev| dev.get_axis_value(axis_path)) {
                         return val;
                     }
                 }
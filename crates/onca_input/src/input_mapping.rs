@@ -1,5 +1,5 @@
 use core::{num::NonZeroU32, ops::BitOr};
-use std::sync::{Weak, Arc};
+use std::{sync::{Weak, Arc}, time::Instant};
 
 use onca_common::{
     prelude::*,
@@ -8,9 +8,14 @@ use onca_common::{
     event_listener::*,
 };
 use onca_common_macros::flags;
+use onca_logging::{log_error, log_warning};
 use onca_math::{f32v2, f32v3, Swizzle};
+use onca_toml::{self as toml, Toml};
 
-use crate::{AxisValue, AxisType, User, InputProcessContext, AxisId};
+use crate::{
+    AxisValue, AxisType, User, InputProcessContext, AxisId, ControlScheme, ControlSchemeID, ActionSnapshot, ActionKey, LOG_INPUT_CAT,
+    ActionDebugInfo, ActionDebugOutcome, BindingDebugInfo, ModifierDebugInfo, TriggerDebugInfo,
+};
 
 //------------------------------------------------------------------------------------------------------------------------------
 // MODIFIERS
@@ -50,11 +55,52 @@ pub enum Modifier {
     TimeScale(bool),
     /// Swizzle the axes.
     Swizzle(Swizzle, Swizzle, Swizzle),
+    /// Apply a response curve, raising the value to the given exponent while keeping its sign, e.g. an exponent of 2 gives finer control around the center of a stick.
+    /// 
+    /// 2D and 3D axes are curved based on their length, so their direction is preserved.
+    ResponseCurve(f32),
+    /// Smooth the value using an exponential low-pass filter, a larger time constant results in smoother, but less responsive input.
+    /// 
+    /// Use [`Modifier::smoothing`] to create this modifier.
+    Smoothing {
+        /// Time constant of the filter, in seconds.
+        time_constant: f32,
+        /// Previous filtered value.
+        prev:          Option<AxisValue>,
+    },
     /// Custom modifier.
     Custom(Box<dyn CustomModifier>)
 }
 
 impl Modifier {
+    /// Create a smoothing modifier with a given time constant, in seconds.
+    pub fn smoothing(time_constant: f32) -> Self {
+        Modifier::Smoothing { time_constant, prev: None }
+    }
+
+    /// Get the name of the modifier, as used when serializing it.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Modifier::Deadzone { .. }    => "deadzone",
+            Modifier::Negate(..)         => "negate",
+            Modifier::Scale(..)          => "scale",
+            Modifier::TimeScale(_)       => "time_scale",
+            Modifier::Swizzle(..)        => "swizzle",
+            Modifier::ResponseCurve(_)   => "response_curve",
+            Modifier::Smoothing { .. }   => "smoothing",
+            Modifier::Custom(_)          => "custom",
+        }
+    }
+
+    /// Apply the modifier, recording it in `debug` if debug info is requested.
+    fn apply_debug(&mut self, value: AxisValue, dt: DeltaTime, debug: Option<&mut Vec<ModifierDebugInfo>>) -> AxisValue {
+        let output = self.apply(value, dt);
+        if let Some(debug) = debug {
+            debug.push(ModifierDebugInfo { name: self.name(), input: value, output });
+        }
+        output
+    }
+
     fn apply(&mut self, value: AxisValue, dt: DeltaTime) -> AxisValue {
         match self {
             Modifier::Deadzone { lower_bound, upper_bound, deadzone_type } => Self::apply_deadzone(value, *lower_bound, *upper_bound, *deadzone_type),
@@ -62,6 +108,8 @@ impl Modifier {
             Modifier::Scale(x, y, z)                                       => Self::apply_scale(value, *x, *y, *z),
             Modifier::TimeScale(use_dilation)                              => Self::apply_time_scale(value, dt, *use_dilation),
             Modifier::Swizzle(x, y, z)                                     => Self::apply_swizzle(value, *x, *y, *z),
+            Modifier::ResponseCurve(exponent)                              => Self::apply_response_curve(value, *exponent),
+            Modifier::Smoothing { time_constant, prev }                    => Self::apply_smoothing(value, dt, *time_constant, prev),
             Modifier::Custom(custom)                                       => custom.apply(value),
         }
     }
@@ -146,6 +194,39 @@ impl Modifier {
             AxisValue::Axis3D(val) => AxisValue::Axis3D(val.swizzle(x, y, z)),
         }
     }
+
+    fn apply_response_curve(value: AxisValue, exponent: f32) -> AxisValue {
+        let curve_1d = |val: f32| val.abs().powf(exponent).copysign(val);
+
+        match value {
+            AxisValue::Digital(val) => AxisValue::Digital(val),
+            AxisValue::Int(val)     => AxisValue::Int(val),
+            AxisValue::Axis(val)    => AxisValue::Axis(curve_1d(val)),
+            AxisValue::Axis2D(val)  => {
+                let len = val.len();
+                if len == 0f32 { AxisValue::Axis2D(val) } else { AxisValue::Axis2D(val / len * curve_1d(len)) }
+            },
+            AxisValue::Axis3D(val)  => {
+                let len = val.len();
+                if len == 0f32 { AxisValue::Axis3D(val) } else { AxisValue::Axis3D(val / len * curve_1d(len)) }
+            },
+        }
+    }
+
+    fn apply_smoothing(value: AxisValue, dt: DeltaTime, time_constant: f32, prev: &mut Option<AxisValue>) -> AxisValue {
+        // Smoothing should feel the same regardless of time dilation
+        let alpha = if time_constant <= 0f32 { 1f32 } else { 1f32 - (-dt.get(false) / time_constant).exp() };
+
+        let smoothed = match (value, *prev) {
+            (AxisValue::Axis(val), Some(AxisValue::Axis(prev)))     => AxisValue::Axis(prev + (val - prev) * alpha),
+            (AxisValue::Axis2D(val), Some(AxisValue::Axis2D(prev))) => AxisValue::Axis2D(prev + (val - prev) * alpha),
+            (AxisValue::Axis3D(val), Some(AxisValue::Axis3D(prev))) => AxisValue::Axis3D(prev + (val - prev) * alpha),
+            // Digital and integer values can't be smoothed, and the first value (or a value of a different type) is used as is
+            _ => value,
+        };
+        *prev = Some(smoothed);
+        smoothed
+    }
 }
 
 impl Clone for Modifier {
@@ -156,6 +237,8 @@ impl Clone for Modifier {
             Self::Scale(arg0, arg1, arg2)                              => Self::Scale(*arg0, *arg1, *arg2),
             Self::TimeScale(arg0)                                      => Self::TimeScale(*arg0),
             Self::Swizzle(arg0, arg1, arg2)                            => Self::Swizzle(*arg0, *arg1, *arg2),
+            Self::ResponseCurve(arg0)                                  => Self::ResponseCurve(*arg0),
+            Self::Smoothing { time_constant, prev }                    => Self::Smoothing { time_constant: *time_constant, prev: *prev },
             Self::Custom(arg0)                                         => Self::Custom(arg0.clone_modifier()),
         }
     }
@@ -229,10 +312,41 @@ pub enum Trigger {
     },
     /// Chorded trigger (other action needs to be triggered).
     Chord(Weak<Mutex<Action>>),
+    /// Triggers when the given actions are triggered in order, followed by the value passing the given threshold, e.g. `Ctrl+K, Ctrl+C` or a fighting game combo.
+    /// 
+    /// As with chorded triggers, the actions in the sequence need to be processed before the action owning this trigger, i.e. be part of a mapping that comes earlier or has a higher priority.
+    Sequence {
+        /// Actions that need to be triggered in order.
+        actions:       Vec<Weak<Mutex<Action>>>,
+        /// Maximum amount of time between 2 consecutive steps of the sequence, after which the sequence is reset.
+        max_step_time: f32,
+        /// Whether the step time is affected by time dialation.
+        time_dilation: bool,
+        /// Actuation treshold for the final step (actuates when greater or equal).
+        threshold:     f32
+    },
     /// Custom trigger.
     Custom(Box<dyn CustomTrigger>)
 }
 
+impl Trigger {
+    /// Get the name of the trigger, as used when serializing it.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Trigger::Down(_)               => "down",
+            Trigger::Pressed(_)            => "pressed",
+            Trigger::Released(_)           => "released",
+            Trigger::Hold { .. }           => "hold",
+            Trigger::HoldAndRelease { .. } => "hold_and_release",
+            Trigger::Pulse { .. }          => "pulse",
+            Trigger::Tap { .. }            => "tap",
+            Trigger::Chord(_)              => "chord",
+            Trigger::Sequence { .. }       => "sequence",
+            Trigger::Custom(_)             => "custom",
+        }
+    }
+}
+
 impl Clone for Trigger {
     fn clone(&self) -> Self {
         match self {
@@ -244,6 +358,7 @@ impl Clone for Trigger {
             Self::Pulse { trigger_on_start, interval, trigger_limit, time_dilation, threshold } => Self::Pulse { trigger_on_start: *trigger_on_start, interval: *interval, trigger_limit: *trigger_limit, time_dilation: *time_dilation, threshold: *threshold },
             Self::Tap { release_time_threshold, time_dilation, threshold }                      => Self::Tap { release_time_threshold: *release_time_threshold, time_dilation: *time_dilation, threshold: *threshold },
             Self::Chord(action)                                                                 => Self::Chord(action.clone()),
+            Self::Sequence { actions, max_step_time, time_dilation, threshold }                 => Self::Sequence { actions: actions.clone(), max_step_time: *max_step_time, time_dilation: *time_dilation, threshold: *threshold },
             Self::Custom(arg0)                                                                  => Self::Custom(arg0.clone_trigger()),
         }
     }
@@ -372,6 +487,7 @@ pub struct TriggerContext {
     prev_result: TriggerResult,
     timer:       f32,
     misc:        u32,
+    held:        bool,
 }
 
 impl Default for TriggerContext {
@@ -380,7 +496,8 @@ impl Default for TriggerContext {
             prev_value: AxisValue::Digital(false),
             prev_result: TriggerResult::Idle,
             timer: 0f32,
-            misc: 0
+            misc: 0,
+            held: false
         }
     }
 }
@@ -388,6 +505,7 @@ impl Default for TriggerContext {
 /// Trigger kind.
 /// 
 /// A action can be triggered if any `Any` trigger, all `Required` triggers, and no `Blocker` triggers are triggered.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum TriggerType {
     /// Any trigger of this type needs to be triggered for the action to happen.
     Any,
@@ -423,6 +541,8 @@ impl TriggerData {
                 } else {
                     TriggerResult::Idle
                 },
+            Trigger::Sequence { actions, max_step_time, time_dilation, threshold }                 =>
+                Self::check_sequence(&mut self.context, value, dt, context, actions, *max_step_time, *time_dilation, *threshold),
             Trigger::Custom(custom)                                                                => custom.check(value, &mut self.context),
         };
         self.context.prev_value = value;
@@ -430,6 +550,15 @@ impl TriggerData {
         res
     }
 
+    /// Check the trigger, recording it in `debug` if debug info is requested.
+    fn check_debug(&mut self, value: AxisValue, dt: DeltaTime, context: &mut InputProcessContext, debug: Option<&mut Vec<TriggerDebugInfo>>) -> TriggerResult {
+        let res = self.check(value, dt, context);
+        if let Some(debug) = debug {
+            debug.push(TriggerDebugInfo { name: self.trigger.name(), trigger_type: self.trigger_type(), result: res });
+        }
+        res
+    }
+
     fn trigger_type(&self) -> TriggerType {
         match &self.trigger {
             Trigger::Down(_)               => TriggerType::Any,
@@ -440,6 +569,7 @@ impl TriggerData {
             Trigger::Pulse { .. }          => TriggerType::Any,
             Trigger::Tap { .. }            => TriggerType::Any,
             Trigger::Chord(_)              => TriggerType::Required,
+            Trigger::Sequence { .. }       => TriggerType::Any,
             Trigger::Custom(custom)        => custom.trigger_type(),
         }
     }
@@ -554,6 +684,36 @@ impl TriggerData {
     }
 }
 
+impl TriggerData {
+    fn check_sequence(ctx: &mut TriggerContext, value: AxisValue, dt: DeltaTime, context: &InputProcessContext, actions: &[Weak<Mutex<Action>>], max_step_time: f32, time_dilation: bool, threshold: f32) -> TriggerResult {
+        // Reset the sequence when the next step took too long
+        if ctx.misc > 0 {
+            ctx.timer += dt.get(time_dilation);
+            if ctx.timer > max_step_time {
+                ctx.misc = 0;
+            }
+        }
+
+        let step = ctx.misc as usize;
+        if step < actions.len() {
+            let triggered = context.triggered_actions.iter().any(|action| Weak::ptr_eq(&Arc::downgrade(action), &actions[step]));
+            // Only advance on a new trigger, so holding an action can't complete multiple steps using the same action
+            if triggered && !ctx.held {
+                ctx.misc += 1;
+                ctx.timer = 0f32;
+            }
+            ctx.held = triggered;
+
+            if ctx.misc > 0 { TriggerResult::Ongoing } else { TriggerResult::Idle }
+        } else if Self::check_pressed(ctx.prev_value, value, threshold) == TriggerResult::Triggered {
+            ctx.misc = 0;
+            TriggerResult::Triggered
+        } else {
+            TriggerResult::Ongoing
+        }
+    }
+}
+
 impl From<Trigger> for TriggerData {
     fn from(trigger: Trigger) -> Self {
         Self { trigger, context: TriggerContext::default() }
@@ -568,6 +728,9 @@ impl From<Trigger> for TriggerData {
 /// 
 /// If the action is present twice for a user, only the first occurance of the action will be processed
 pub struct Action {
+    /// Name of the action, used to refer to the action when serializing mappings
+    // TODO: Name should be something associated to the asset
+    pub name:                String,
     /// Localized description of this action
     // TODO: Localized string
     pub description:         (),
@@ -587,17 +750,21 @@ pub struct Action {
     pub modifiers:           Vec<Modifier>,
     /// Action event listeners
     pub listeners:           DynEventListenerArray<(TriggerState, AxisValue, u8)>,
+    /// Timestamp of the most recent input event when the action was last dispatched, can be used to measure the delay between the input and its effect.
+    pub last_input_time:     Option<Instant>,
 }
 
 impl Action {
-    pub fn new(consume_input: bool, axis_type: AxisType) -> Self {
+    pub fn new(name: String, consume_input: bool, axis_type: AxisType) -> Self {
         Self {
+            name,
             description: (),
             consume_input,
             axis_type,
             triggers: Vec::new(),
             modifiers: Vec::new(),
-            listeners: DynEventListenerArray::new()
+            listeners: DynEventListenerArray::new(),
+            last_input_time: None,
         }
     }
 
@@ -613,7 +780,8 @@ impl Action {
         self.listeners.push(listener);
     }
 
-    pub fn dispatch(&mut self, trigger_res: TriggerState, value: AxisValue, user_idx: u8) {
+    pub fn dispatch(&mut self, trigger_res: TriggerState, value: AxisValue, user_idx: u8, input_time: Option<Instant>) {
+        self.last_input_time = input_time;
         self.listeners.notify(&(trigger_res, value.convert_to(self.axis_type), user_idx));
     }
 }
@@ -639,6 +807,10 @@ pub struct Binding {
     pub triggers:       Vec<TriggerData>,
     /// Binding specific modifiers
     pub modifiers:      Vec<Modifier>,
+    /// Modifiers that are only applied when the user's active control scheme matches, after the binding specific modifiers.
+    /// 
+    /// This allows e.g. camera sensitivity or deadzones to be tuned differently for a gamepad and a flight stick.
+    pub scheme_modifiers: Vec<(ControlSchemeID, Vec<Modifier>)>,
     /// Binding rebind options
     pub rebind_options: Option<RebindOptions>
 }
@@ -649,6 +821,7 @@ impl Binding {
             input_axis,
             triggers: Vec::new(),
             modifiers: Vec::new(),
+            scheme_modifiers: Vec::new(),
             rebind_options: None
         }
     }
@@ -661,15 +834,29 @@ impl Binding {
         self.modifiers.push(modifier);
     }
 
-    pub(crate) fn apply_modifiers(&mut self, value: &mut AxisValue, dt: DeltaTime) {
+    /// Add a modifier that is only applied when the given control scheme is active.
+    pub fn add_scheme_modifier(&mut self, scheme: ControlSchemeID, modifier: Modifier) {
+        match self.scheme_modifiers.iter_mut().find(|(id, _)| *id == scheme) {
+            Some((_, modifiers)) => modifiers.push(modifier),
+            None => self.scheme_modifiers.push((scheme, vec![modifier])),
+        }
+    }
+
+    pub(crate) fn apply_modifiers(&mut self, value: &mut AxisValue, dt: DeltaTime, scheme: Option<&ControlSchemeID>, mut debug: Option<&mut Vec<ModifierDebugInfo>>) {
         for modifier in &mut self.modifiers {
-            *value = modifier.apply(*value, dt);
+            *value = modifier.apply_debug(*value, dt, debug.as_deref_mut());
+        }
+
+        if let Some(scheme) = scheme && let Some((_, modifiers)) = self.scheme_modifiers.iter_mut().find(|(id, _)| id == scheme) {
+            for modifier in modifiers {
+                *value = modifier.apply_debug(*value, dt, debug.as_deref_mut());
+            }
         }
     }
 
-    pub(crate) fn process_triggers(&mut self, value: AxisValue, dt: DeltaTime, context: &mut InputProcessContext, final_res: &mut FinalTriggerResult) {
+    pub(crate) fn process_triggers(&mut self, value: AxisValue, dt: DeltaTime, context: &mut InputProcessContext, final_res: &mut FinalTriggerResult, mut debug: Option<&mut Vec<TriggerDebugInfo>>) {
         for trigger in &mut self.triggers {
-            let res = trigger.check(value, dt, context);
+            let res = trigger.check_debug(value, dt, context, debug.as_deref_mut());
             let trigger_type = trigger.trigger_type();
             final_res.update(trigger_type, res);
         }
@@ -711,60 +898,131 @@ impl Mapping {
         self.bindings.push(binding);
     }
 
-    pub(crate) fn apply_modifiers(&mut self, value: &mut AxisValue, dt: DeltaTime) {
+    pub(crate) fn apply_modifiers(&mut self, value: &mut AxisValue, dt: DeltaTime, mut debug: Option<&mut Vec<ModifierDebugInfo>>) {
         for modifier in &mut self.modifiers {
-            *value = modifier.apply(*value, dt);
+            *value = modifier.apply_debug(*value, dt, debug.as_deref_mut());
         }
     }
 
-    pub(crate) fn process_triggers(&mut self, value: AxisValue, dt: DeltaTime, context: &mut InputProcessContext, final_res: &mut FinalTriggerResult) {
+    pub(crate) fn process_triggers(&mut self, value: AxisValue, dt: DeltaTime, context: &mut InputProcessContext, final_res: &mut FinalTriggerResult, mut debug: Option<&mut Vec<TriggerDebugInfo>>) {
         for trigger in &mut self.triggers {
-            let res = trigger.check(value, dt, context);
+            let res = trigger.check_debug(value, dt, context, debug.as_deref_mut());
             let trigger_type = trigger.trigger_type();
             final_res.update(trigger_type, res);
         }
     }
 
-    pub(crate) fn process<F>(&mut self, dt: DeltaTime, user: &User, user_idx: u8, context: &mut InputProcessContext, get_input: &F) where
+    pub(crate) fn process<F>(&mut self, dt: DeltaTime, user: &User, user_idx: u8, context_identifier: &str, context: &mut InputProcessContext, get_input: &F) where
         F: Fn(&User, &AxisId) -> AxisValue
     {
         // Only the first occurance of an action is processed, so check that first
         if context.processed_actions.iter().any(|action| Arc::ptr_eq(action, &self.action)) {
+            if let Some(debug) = &mut context.debug {
+                let name = self.action.lock().name.clone();
+                let winning_context = debug.iter()
+                    .find(|info| info.action == name && info.user_idx == user_idx && matches!(info.outcome, ActionDebugOutcome::Processed))
+                    .map_or(String::new(), |info| info.context.clone());
+                debug.push(ActionDebugInfo::shadowed(name, context_identifier.to_string(), user_idx, winning_context));
+            }
             return;
         }
 
         let mut value = AxisValue::Digital(false);
         let mut trigger_res = FinalTriggerResult::new();
-        let consume_inputs = self.action.lock().consume_input;
+        let (consume_inputs, action_name) = {
+            let action = self.action.lock();
+            (action.consume_input, action.name.clone())
+        };
+
+        let is_debugging = context.debug.is_some();
+        let mut binding_debug = Vec::new();
 
         for binding in &mut self.bindings {
             // Skip bindings with consumed inputs
             if context.consumed_inputs.contains(&binding.input_axis) {
+                if is_debugging {
+                    binding_debug.push(BindingDebugInfo {
+                        input_axis: binding.input_axis.clone(),
+                        raw_value: None,
+                        value: None,
+                        consumed_by: context.consumed_by.iter().find(|(axis, _)| *axis == binding.input_axis).map(|(_, name)| name.clone()),
+                        modifiers: Vec::new(),
+                        triggers: Vec::new(),
+                    });
+                }
                 continue;
             }
 
             let mut binding_value = get_input(user, &binding.input_axis);
-            binding.apply_modifiers(&mut binding_value, dt);
-            binding.process_triggers(binding_value, dt, context, &mut trigger_res);
+            let raw_value = binding_value;
+            let mut modifier_debug = Vec::new();
+            let mut trigger_debug = Vec::new();
+            binding.apply_modifiers(&mut binding_value, dt, user.control_set().map(|set| set.scheme_identifier()), is_debugging.then_some(&mut modifier_debug));
+            binding.process_triggers(binding_value, dt, context, &mut trigger_res, is_debugging.then_some(&mut trigger_debug));
 
             value = value + binding_value;
 
             if consume_inputs {
                 context.consumed_inputs.insert(binding.input_axis.clone());
+                if is_debugging {
+                    context.consumed_by.push((binding.input_axis.clone(), action_name.clone()));
+                }
+            }
+
+            if is_debugging {
+                binding_debug.push(BindingDebugInfo {
+                    input_axis: binding.input_axis.clone(),
+                    raw_value: Some(raw_value),
+                    value: Some(binding_value),
+                    consumed_by: None,
+                    modifiers: modifier_debug,
+                    triggers: trigger_debug,
+                });
             }
         }
 
-        self.apply_modifiers(&mut value, dt);
-        self.process_triggers(value, dt, context, &mut trigger_res);
+        let mut modifier_debug = Vec::new();
+        let mut trigger_debug = Vec::new();
+        self.apply_modifiers(&mut value, dt, is_debugging.then_some(&mut modifier_debug));
+        self.process_triggers(value, dt, context, &mut trigger_res, is_debugging.then_some(&mut trigger_debug));
 
         let result = trigger_res.to_result();
         let prev_res = user.get_previous_action_trigger_result(&self.action);
         context.trigger_states.push((Arc::downgrade(&self.action), result));
         
         let trigger_state = TriggerState::from_results(prev_res, result);
-        // Only notify if the state is not idle
-        if trigger_state != TriggerState::Idle {
-            self.action.lock().dispatch(trigger_state, value, user_idx);
+        if let Some(debug) = &mut context.debug {
+            debug.push(ActionDebugInfo {
+                action: action_name,
+                context: context_identifier.to_string(),
+                user_idx,
+                outcome: ActionDebugOutcome::Processed,
+                consume_input: consume_inputs,
+                bindings: binding_debug,
+                modifiers: modifier_debug,
+                triggers: trigger_debug,
+                result,
+                state: trigger_state,
+                value,
+            });
+        }
+
+        {
+            let mut action = self.action.lock();
+            context.action_snapshots.push(ActionSnapshot {
+                key: ActionKey::of(&self.action),
+                name: action.name.clone(),
+                user_idx,
+                prev_result: prev_res,
+                result,
+                state: trigger_state,
+                value: value.convert_to(action.axis_type),
+            });
+
+            // Only notify if the state is not idle
+            if trigger_state != TriggerState::Idle {
+                action.dispatch(trigger_state, value, user_idx, context.input_timestamp);
+            }
         }
         context.processed_actions.push(self.action.clone());
 
@@ -808,4 +1066,591 @@ impl MappingContext {
             mapping.rebind(binding_name, input.clone());
         }
     }
-}
\ No newline at end of file
+}
+
+//------------------------------------------------------------------------------------------------------------------------------
+// SERIALIZATION
+//------------------------------------------------------------------------------------------------------------------------------
+
+fn push_item(table: &mut toml::Table, key: &str, item: toml::Item) {
+    table.push(key.to_string(), item);
+}
+
+fn push_str(table: &mut toml::Table, key: &str, val: &str) {
+    push_item(table, key, toml::Item::String(val.to_string()));
+}
+
+fn push_f32(table: &mut toml::Table, key: &str, val: f32) {
+    push_item(table, key, toml::Item::Float(val as f64));
+}
+
+fn push_bool(table: &mut toml::Table, key: &str, val: bool) {
+    push_item(table, key, toml::Item::Boolean(val));
+}
+
+/// Get a float, integers are also accepted, as `1` is often written instead of `1.0`.
+fn get_f32(table: &toml::Table, key: &str) -> Option<f32> {
+    match table.get_item(key)? {
+        toml::Item::Float(val)   => Some(*val as f32),
+        toml::Item::Integer(val) => Some(*val as f32),
+        _                        => None,
+    }
+}
+
+fn get_bool(table: &toml::Table, key: &str, default: bool) -> bool {
+    table.get::<bool>(key).copied().unwrap_or(default)
+}
+
+/// Get an array of tables, non-table items are skipped with a warning.
+fn get_tables<'a>(table: &'a toml::Table, key: &str) -> Vec<&'a toml::Table> {
+    let mut tables = Vec::new();
+    if let Some(items) = table.get::<Vec<toml::Item>>(key) {
+        for item in items {
+            match item {
+                toml::Item::Table(table) => tables.push(table),
+                _ => log_warning!(LOG_INPUT_CAT, "Skipping non-table item in '{key}'"),
+            }
+        }
+    }
+    tables
+}
+
+pub(crate) fn axis_type_to_str(axis_type: AxisType) -> &'static str {
+    match axis_type {
+        AxisType::Digital => "digital",
+        AxisType::Int     => "int",
+        AxisType::Axis    => "axis",
+        AxisType::Axis2D  => "axis2d",
+        AxisType::Axis3D  => "axis3d",
+    }
+}
+
+pub(crate) fn axis_type_from_str(name: &str) -> Option<AxisType> {
+    match name {
+        "digital" => Some(AxisType::Digital),
+        "int"     => Some(AxisType::Int),
+        "axis"    => Some(AxisType::Axis),
+        "axis2d"  => Some(AxisType::Axis2D),
+        "axis3d"  => Some(AxisType::Axis3D),
+        _         => None,
+    }
+}
+
+fn swizzle_to_str(swizzle: Swizzle) -> &'static str {
+    match swizzle {
+        Swizzle::X => "x",
+        Swizzle::Y => "y",
+        Swizzle::Z => "z",
+        Swizzle::W => "w",
+    }
+}
+
+fn swizzle_from_str(name: &str) -> Option<Swizzle> {
+    match name {
+        "x" => Some(Swizzle::X),
+        "y" => Some(Swizzle::Y),
+        "z" => Some(Swizzle::Z),
+        "w" => Some(Swizzle::W),
+        _   => None,
+    }
+}
+
+fn find_action(actions: &[Arc<Mutex<Action>>], name: &str) -> Option<Arc<Mutex<Action>>> {
+    actions.iter().find(|action| action.lock().name == name).cloned()
+}
+
+fn modifiers_to_toml(modifiers: &[Modifier]) -> toml::Item {
+    toml::Item::Array(modifiers.iter().filter_map(|modifier| modifier.to_toml_table().map(toml::Item::Table)).collect())
+}
+
+fn modifiers_from_toml(table: &toml::Table) -> Vec<Modifier> {
+    get_tables(table, "modifiers").into_iter().filter_map(Modifier::from_toml_table).collect()
+}
+
+fn triggers_to_toml(triggers: &[TriggerData]) -> toml::Item {
+    toml::Item::Array(triggers.iter().filter_map(|trigger| trigger.trigger.to_toml_table().map(toml::Item::Table)).collect())
+}
+
+fn triggers_from_toml(table: &toml::Table, actions: &[Arc<Mutex<Action>>]) -> Vec<TriggerData> {
+    get_tables(table, "triggers").into_iter().filter_map(|table| Trigger::from_toml_table(table, actions)).map(TriggerData::from).collect()
+}
+
+impl Modifier {
+    /// Convert the modifier to a toml table.
+    /// 
+    /// Custom modifiers cannot be serialized, so `None` is returned for them.
+    pub fn to_toml_table(&self) -> Option<toml::Table> {
+        let mut table = toml::Table::new();
+        match self {
+            Modifier::Deadzone { lower_bound, upper_bound, deadzone_type } => {
+                push_str(&mut table, "type", "deadzone");
+                push_f32(&mut table, "lower_bound", *lower_bound);
+                push_f32(&mut table, "upper_bound", *upper_bound);
+                push_str(&mut table, "deadzone_type", match deadzone_type {
+                    DeadzoneType::Axial  => "axial",
+                    DeadzoneType::Radial => "radial",
+                });
+            },
+            Modifier::Negate(x, y, z) => {
+                push_str(&mut table, "type", "negate");
+                push_bool(&mut table, "x", *x);
+                push_bool(&mut table, "y", *y);
+                push_bool(&mut table, "z", *z);
+            },
+            Modifier::Scale(x, y, z) => {
+                push_str(&mut table, "type", "scale");
+                push_f32(&mut table, "x", *x);
+                push_f32(&mut table, "y", *y);
+                push_f32(&mut table, "z", *z);
+            },
+            Modifier::TimeScale(time_dilation) => {
+                push_str(&mut table, "type", "time_scale");
+                push_bool(&mut table, "time_dilation", *time_dilation);
+            },
+            Modifier::Swizzle(x, y, z) => {
+                push_str(&mut table, "type", "swizzle");
+                push_str(&mut table, "x", swizzle_to_str(*x));
+                push_str(&mut table, "y", swizzle_to_str(*y));
+                push_str(&mut table, "z", swizzle_to_str(*z));
+            },
+            Modifier::ResponseCurve(exponent) => {
+                push_str(&mut table, "type", "response_curve");
+                push_f32(&mut table, "exponent", *exponent);
+            },
+            Modifier::Smoothing { time_constant, .. } => {
+                push_str(&mut table, "type", "smoothing");
+                push_f32(&mut table, "time_constant", *time_constant);
+            },
+            Modifier::Custom(_) => {
+                log_warning!(LOG_INPUT_CAT, "Custom modifiers cannot be serialized, skipping modifier");
+                return None;
+            },
+        }
+        Some(table)
+    }
+
+    /// Create a modifier from a toml table.
+    pub fn from_toml_table(table: &toml::Table) -> Option<Self> {
+        let Some(ty) = table.get::<String>("type") else {
+            log_error!(LOG_INPUT_CAT, "A modifier requires a type to be specified");
+            return None;
+        };
+
+        let modifier = match ty.as_str() {
+            "deadzone" => {
+                let deadzone_type = match table.get::<String>("deadzone_type").map(|s| s.as_str()) {
+                    None | Some("axial") => DeadzoneType::Axial,
+                    Some("radial")       => DeadzoneType::Radial,
+                    Some(name)           => {
+                        log_error!(LOG_INPUT_CAT, "Unknown deadzone type '{name}'");
+                        return None;
+                    }
+                };
+                Modifier::Deadzone {
+                    lower_bound: get_f32(table, "lower_bound").unwrap_or(0f32),
+                    upper_bound: get_f32(table, "upper_bound").unwrap_or(1f32),
+                    deadzone_type
+                }
+            },
+            "negate"     => Modifier::Negate(get_bool(table, "x", false), get_bool(table, "y", false), get_bool(table, "z", false)),
+            "scale"      => Modifier::Scale(get_f32(table, "x").unwrap_or(1f32), get_f32(table, "y").unwrap_or(1f32), get_f32(table, "z").unwrap_or(1f32)),
+            "time_scale" => Modifier::TimeScale(get_bool(table, "time_dilation", true)),
+            "response_curve" => Modifier::ResponseCurve(get_f32(table, "exponent").unwrap_or(1f32)),
+            "smoothing"  => Modifier::smoothing(get_f32(table, "time_constant").unwrap_or(0f32)),
+            "swizzle"    => {
+                let get_swizzle = |key: &str, default: Swizzle| match table.get::<String>(key) {
+                    Some(name) => swizzle_from_str(name),
+                    None       => Some(default),
+                };
+                match (get_swizzle("x", Swizzle::X), get_swizzle("y", Swizzle::Y), get_swizzle("z", Swizzle::Z)) {
+                    (Some(x), Some(y), Some(z)) => Modifier::Swizzle(x, y, z),
+                    _ => {
+                        log_error!(LOG_INPUT_CAT, "Invalid swizzle modifier, components need to be one of 'x', 'y', 'z', or 'w'");
+                        return None;
+                    }
+                }
+            },
+            _ => {
+                log_error!(LOG_INPUT_CAT, "Unknown modifier type '{ty}'");
+                return None;
+            }
+        };
+        Some(modifier)
+    }
+}
+
+impl Trigger {
+    /// Convert the trigger to a toml table.
+    /// 
+    /// Custom triggers cannot be serialized, so `None` is returned for them, as is the case for chorded and sequence triggers whose actions no longer exist.
+    pub fn to_toml_table(&self) -> Option<toml::Table> {
+        let mut table = toml::Table::new();
+        match self {
+            Trigger::Down(threshold) => {
+                push_str(&mut table, "type", "down");
+                push_f32(&mut table, "threshold", *threshold);
+            },
+            Trigger::Pressed(threshold) => {
+                push_str(&mut table, "type", "pressed");
+                push_f32(&mut table, "threshold", *threshold);
+            },
+            Trigger::Released(threshold) => {
+                push_str(&mut table, "type", "released");
+                push_f32(&mut table, "threshold", *threshold);
+            },
+            Trigger::Hold { hold_time, one_shot, time_dilation, threshold } => {
+                push_str(&mut table, "type", "hold");
+                push_f32(&mut table, "hold_time", *hold_time);
+                push_bool(&mut table, "one_shot", *one_shot);
+                push_bool(&mut table, "time_dilation", *time_dilation);
+                push_f32(&mut table, "threshold", *threshold);
+            },
+            Trigger::HoldAndRelease { hold_time, time_dilation, threshold } => {
+                push_str(&mut table, "type", "hold_and_release");
+                push_f32(&mut table, "hold_time", *hold_time);
+                push_bool(&mut table, "time_dilation", *time_dilation);
+                push_f32(&mut table, "threshold", *threshold);
+            },
+            Trigger::Pulse { trigger_on_start, interval, trigger_limit, time_dilation, threshold } => {
+                push_str(&mut table, "type", "pulse");
+                push_bool(&mut table, "trigger_on_start", *trigger_on_start);
+                push_f32(&mut table, "interval", *interval);
+                if let Some(limit) = trigger_limit {
+                    push_item(&mut table, "trigger_limit", toml::Item::Integer(limit.get() as i64));
+                }
+                push_bool(&mut table, "time_dilation", *time_dilation);
+                push_f32(&mut table, "threshold", *threshold);
+            },
+            Trigger::Tap { release_time_threshold, time_dilation, threshold } => {
+                push_str(&mut table, "type", "tap");
+                push_f32(&mut table, "release_time_threshold", *release_time_threshold);
+                push_bool(&mut table, "time_dilation", *time_dilation);
+                push_f32(&mut table, "threshold", *threshold);
+            },
+            Trigger::Chord(action) => {
+                let Some(action) = action.upgrade() else {
+                    log_warning!(LOG_INPUT_CAT, "Chorded action no longer exists, skipping trigger");
+                    return None;
+                };
+                push_str(&mut table, "type", "chord");
+                push_str(&mut table, "action", &action.lock().name);
+            },
+            Trigger::Sequence { actions, max_step_time, time_dilation, threshold } => {
+                let mut names = Vec::with_capacity(actions.len());
+                for action in actions {
+                    let Some(action) = action.upgrade() else {
+                        log_warning!(LOG_INPUT_CAT, "Action in sequence no longer exists, skipping trigger");
+                        return None;
+                    };
+                    names.push(toml::Item::String(action.lock().name.clone()));
+                }
+                push_str(&mut table, "type", "sequence");
+                push_item(&mut table, "actions", toml::Item::Array(names));
+                push_f32(&mut table, "max_step_time", *max_step_time);
+                push_bool(&mut table, "time_dilation", *time_dilation);
+                push_f32(&mut table, "threshold", *threshold);
+            },
+            Trigger::Custom(_) => {
+                log_warning!(LOG_INPUT_CAT, "Custom triggers cannot be serialized, skipping trigger");
+                return None;
+            },
+        }
+        Some(table)
+    }
+
+    /// Create a trigger from a toml table.
+    /// 
+    /// `actions` is used to look up the actions of chorded and sequence triggers.
+    pub fn from_toml_table(table: &toml::Table, actions: &[Arc<Mutex<Action>>]) -> Option<Self> {
+        let Some(ty) = table.get::<String>("type") else {
+            log_error!(LOG_INPUT_CAT, "A trigger requires a type to be specified");
+            return None;
+        };
+
+        let threshold = get_f32(table, "threshold").unwrap_or(0.5f32);
+        let time_dilation = get_bool(table, "time_dilation", true);
+        let trigger = match ty.as_str() {
+            "down"             => Trigger::Down(threshold),
+            "pressed"          => Trigger::Pressed(threshold),
+            "released"         => Trigger::Released(threshold),
+            "hold"             => Trigger::Hold {
+                hold_time: get_f32(table, "hold_time").unwrap_or(0f32),
+                one_shot: get_bool(table, "one_shot", false),
+                time_dilation,
+                threshold
+            },
+            "hold_and_release" => Trigger::HoldAndRelease {
+                hold_time: get_f32(table, "hold_time").unwrap_or(0f32),
+                time_dilation,
+                threshold
+            },
+            "pulse"            => Trigger::Pulse {
+                trigger_on_start: get_bool(table, "trigger_on_start", false),
+                interval: get_f32(table, "interval").unwrap_or(0f32),
+                trigger_limit: table.get::<i64>("trigger_limit").and_then(|limit| NonZeroU32::new(*limit as u32)),
+                time_dilation,
+                threshold
+            },
+            "tap"              => Trigger::Tap {
+                release_time_threshold: get_f32(table, "release_time_threshold").unwrap_or(0f32),
+                time_dilation,
+                threshold
+            },
+            "chord"            => {
+                let Some(name) = table.get::<String>("action") else {
+                    log_error!(LOG_INPUT_CAT, "A chord trigger requires an action to be specified");
+                    return None;
+                };
+                let Some(action) = find_action(actions, name) else {
+                    log_error!(LOG_INPUT_CAT, "Unknown chorded action '{name}'");
+                    return None;
+                };
+                Trigger::Chord(Arc::downgrade(&action))
+            },
+            "sequence"         => {
+                let mut sequence = Vec::new();
+                for item in table.get::<Vec<toml::Item>>("actions").map_or(&[][..], |items| items.as_slice()) {
+                    let toml::Item::String(name) = item else {
+                        log_error!(LOG_INPUT_CAT, "Actions in a sequence trigger need to be strings");
+                        return None;
+                    };
+                    let Some(action) = find_action(actions, name) else {
+                        log_error!(LOG_INPUT_CAT, "Unknown action '{name}' in sequence trigger");
+                        return None;
+                    };
+                    sequence.push(Arc::downgrade(&action));
+                }
+                Trigger::Sequence {
+                    actions: sequence,
+                    max_step_time: get_f32(table, "max_step_time").unwrap_or(0.5f32),
+                    time_dilation,
+                    threshold
+                }
+            },
+            _ => {
+                log_error!(LOG_INPUT_CAT, "Unknown trigger type '{ty}'");
+                return None;
+            }
+        };
+        Some(trigger)
+    }
+}
+
+impl Action {
+    /// Convert the action to a toml table.
+    pub fn to_toml_table(&self) -> toml::Table {
+        let mut table = toml::Table::new();
+        push_str(&mut table, "name", &self.name);
+        push_bool(&mut table, "consume_input", self.consume_input);
+        push_str(&mut table, "axis_type", axis_type_to_str(self.axis_type));
+        push_item(&mut table, "triggers", triggers_to_toml(&self.triggers));
+        push_item(&mut table, "modifiers", modifiers_to_toml(&self.modifiers));
+        table
+    }
+}
+
+impl Binding {
+    /// Convert the binding to a toml table.
+    /// 
+    /// Returns `None` if the name of the input axis is unknown.
+    pub fn to_toml_table(&self) -> Option<toml::Table> {
+        let axis = self.input_axis.as_string();
+        if axis.is_empty() {
+            log_warning!(LOG_INPUT_CAT, "Input axis name is unknown, skipping binding");
+            return None;
+        }
+
+        let mut table = toml::Table::new();
+        push_str(&mut table, "axis", &axis);
+        if let Some(rebind_options) = &self.rebind_options {
+            push_str(&mut table, "rebind_name", &rebind_options.name);
+        }
+        push_item(&mut table, "triggers", triggers_to_toml(&self.triggers));
+        push_item(&mut table, "modifiers", modifiers_to_toml(&self.modifiers));
+
+        let scheme_modifiers = self.scheme_modifiers.iter().map(|(scheme, modifiers)| {
+            let mut scheme_table = toml::Table::new();
+            push_str(&mut scheme_table, "scheme", scheme.as_str());
+            push_item(&mut scheme_table, "modifiers", modifiers_to_toml(modifiers));
+            toml::Item::Table(scheme_table)
+        }).collect();
+        push_item(&mut table, "scheme_modifiers", toml::Item::Array(scheme_modifiers));
+        Some(table)
+    }
+
+    /// Create a binding from a toml table.
+    /// 
+    /// `actions` is used to look up the action of chorded triggers.
+    pub fn from_toml_table(table: &toml::Table, actions: &[Arc<Mutex<Action>>]) -> Option<Self> {
+        let Some(axis) = table.get::<String>("axis") else {
+            log_error!(LOG_INPUT_CAT, "A binding requires an input axis to be specified");
+            return None;
+        };
+
+        // Make sure the axis name can be retrieved when serializing the binding again
+        InternedString::new(axis);
+        let mut binding = Binding::new(AxisId::new(axis));
+        binding.triggers = triggers_from_toml(table, actions);
+        binding.modifiers = modifiers_from_toml(table);
+        for scheme_table in get_tables(table, "scheme_modifiers") {
+            match scheme_table.get::<String>("scheme") {
+                Some(scheme) => binding.scheme_modifiers.push((ControlSchemeID::new(scheme), modifiers_from_toml(scheme_table))),
+                None => log_warning!(LOG_INPUT_CAT, "Skipping scheme modifiers without a control scheme for binding '{axis}'"),
+            }
+        }
+        binding.rebind_options = table.get::<String>("rebind_name").map(|name| RebindOptions { display_name: (), display_category: (), name: name.clone() });
+        Some(binding)
+    }
+}
+
+impl Mapping {
+    /// Convert the mapping to a toml table.
+    pub fn to_toml_table(&self) -> toml::Table {
+        let mut table = toml::Table::new();
+        push_str(&mut table, "action", &self.action.lock().name);
+        push_item(&mut table, "triggers", triggers_to_toml(&self.triggers));
+        push_item(&mut table, "modifiers", modifiers_to_toml(&self.modifiers));
+        push_item(&mut table, "bindings", toml::Item::Array(self.bindings.iter().filter_map(|binding| binding.to_toml_table().map(toml::Item::Table)).collect()));
+        table
+    }
+
+    /// Create a mapping from a toml table.
+    /// 
+    /// If the table contains no triggers or modifiers, the triggers and modifiers of the action will be used.
+    pub fn from_toml_table(table: &toml::Table, actions: &[Arc<Mutex<Action>>]) -> Option<Self> {
+        let Some(name) = table.get::<String>("action") else {
+            log_error!(LOG_INPUT_CAT, "A mapping requires an action to be specified");
+            return None;
+        };
+        let Some(action) = find_action(actions, name) else {
+            log_error!(LOG_INPUT_CAT, "Unknown action '{name}' in mapping");
+            return None;
+        };
+
+        let mut mapping = Mapping::new(action);
+        if table.get_item("triggers").is_some() {
+            mapping.triggers = triggers_from_toml(table, actions);
+        }
+        if table.get_item("modifiers").is_some() {
+            mapping.modifiers = modifiers_from_toml(table);
+        }
+        for binding in get_tables(table, "bindings") {
+            if let Some(binding) = Binding::from_toml_table(binding, actions) {
+                mapping.add_binding(binding);
+            }
+        }
+        Some(mapping)
+    }
+}
+
+impl MappingContext {
+    /// Serialize the mapping context to toml, together with the actions it uses and the given control schemes.
+    /// 
+    /// Custom triggers and modifiers cannot be serialized and will be skipped.
+    pub fn to_toml(&self, control_schemes: &[ControlScheme]) -> Toml {
+        let mut actions: Vec<Arc<Mutex<Action>>> = Vec::new();
+        for mapping in &self.mappings {
+            if !actions.iter().any(|action| Arc::ptr_eq(action, &mapping.action)) {
+                actions.push(mapping.action.clone());
+            }
+        }
+
+        let mut toml = Toml::new();
+        toml.push("identifier".to_string(), toml::Item::String(self.identifier.clone()));
+        toml.push("actions".to_string(), toml::Item::Array(actions.iter().map(|action| toml::Item::Table(action.lock().to_toml_table())).collect()));
+        toml.push("mappings".to_string(), toml::Item::Array(self.mappings.iter().map(|mapping| toml::Item::Table(mapping.to_toml_table())).collect()));
+        toml.push("control_schemes".to_string(), toml::Item::Array(control_schemes.iter().map(|scheme| toml::Item::Table(scheme.to_toml_table())).collect()));
+        toml
+    }
+
+    /// Deserialize a mapping context and its control schemes from toml.
+    /// 
+    /// Actions are looked up by name in `actions`, so any listeners registered on an existing action stay attached,
+    /// while its settings, triggers, and modifiers are replaced by those in the toml. Actions that don't exist yet are created and added to `actions`.
+    /// 
+    /// Invalid entries are skipped, `None` is only returned when the toml does not contain an identifier.
+    pub fn from_toml(toml: &Toml, actions: &mut Vec<Arc<Mutex<Action>>>) -> Option<(Self, Vec<ControlScheme>)> {
+        let Some(toml::Item::String(identifier)) = toml.get("identifier") else {
+            log_error!(LOG_INPUT_CAT, "A mapping context requires an identifier to be specified");
+            return None;
+        };
+
+        let action_tables: Vec<&toml::Table> = match toml.get("actions") {
+            Some(toml::Item::Array(items)) => items.iter().filter_map(|item| match item {
+                toml::Item::Table(table) => Some(table),
+                _ => None,
+            }).collect(),
+            _ => Vec::new(),
+        };
+
+        // Create all actions first, so chorded triggers can reference actions that are defined later on
+        let mut parsed_actions = Vec::new();
+        for table in action_tables {
+            let Some(name) = table.get::<String>("name") else {
+                log_error!(LOG_INPUT_CAT, "An action requires a name to be specified");
+                continue;
+            };
+            let axis_type = match table.get::<String>("axis_type") {
+                Some(axis_type) => match axis_type_from_str(axis_type) {
+                    Some(axis_type) => axis_type,
+                    None => {
+                        log_error!(LOG_INPUT_CAT, "Unknown axis type '{axis_type}' for action '{name}'");
+                        continue;
+                    }
+                },
+                None => AxisType::Digital,
+            };
+            let consume_input = get_bool(table, "consume_input", false);
+
+            let action = match find_action(actions, name) {
+                Some(action) => {
+                    let mut locked = action.lock();
+                    locked.consume_input = consume_input;
+                    locked.axis_type = axis_type;
+                    drop(locked);
+                    action
+                },
+                None => {
+                    let action = Arc::new(Mutex::new(Action::new(name.clone(), consume_input, axis_type)));
+                    actions.push(action.clone());
+                    action
+                }
+            };
+            parsed_actions.push((table, action));
+        }
+
+        for (table, action) in parsed_actions {
+            let triggers = triggers_from_toml(table, actions);
+            let modifiers = modifiers_from_toml(table);
+            let mut action = action.lock();
+            action.triggers = triggers;
+            action.modifiers = modifiers;
+        }
+
+        let mut context = MappingContext::new(identifier.clone());
+        if let Some(toml::Item::Array(items)) = toml.get("mappings") {
+            for item in items {
+                match item {
+                    toml::Item::Table(table) => if let Some(mapping) = Mapping::from_toml_table(table, actions) {
+                        context.add_mapping(mapping);
+                    },
+                    _ => log_warning!(LOG_INPUT_CAT, "Skipping non-table item in 'mappings'"),
+                }
+            }
+        }
+
+        let mut control_schemes = Vec::new();
+        if let Some(toml::Item::Array(items)) = toml.get("control_schemes") {
+            for item in items {
+                match item {
+                    toml::Item::Table(table) => if let Some(scheme) = ControlScheme::from_toml_table(table) {
+                        control_schemes.push(scheme);
+                    },
+                    _ => log_warning!(LOG_INPUT_CAT, "Skipping non-table item in 'control_schemes'"),
+                }
+            }
+        }
+
+        Some((context, control_schemes))
+    }
+}
@@ -0,0 +1,86 @@
+use std::time::{Duration, Instant};
+
+use crate::Handle;
+
+/// Event-to-process latency statistics for a single device.
+/// 
+/// The latency is measured from the moment the OS received the input event, until the input manager processed it during its tick.
+#[derive(Clone, Copy, Debug)]
+pub struct LatencyStats {
+    /// Smallest measured latency.
+    pub min:   Duration,
+    /// Average latency.
+    pub avg:   Duration,
+    /// Largest measured latency.
+    pub max:   Duration,
+    /// Number of events that were measured.
+    pub count: u32,
+}
+
+struct DeviceLatency {
+    handle: Handle,
+    min:    Duration,
+    max:    Duration,
+    total:  Duration,
+    count:  u32,
+}
+
+/// Latency probe, collecting the event to process delays of all devices.
+pub(crate) struct LatencyProbe {
+    /// Timestamps of the events that were received since the last tick.
+    pending: Vec<(Handle, Instant)>,
+    devices: Vec<DeviceLatency>,
+}
+
+impl LatencyProbe {
+    pub(crate) fn new() -> Self {
+        Self { pending: Vec::new(), devices: Vec::new() }
+    }
+
+    /// Register that an event was received for a device at the given time.
+    pub(crate) fn add_event(&mut self, handle: Handle, timestamp: Instant) {
+        self.pending.push((handle, timestamp));
+    }
+
+    /// Get the timestamp of the most recent event since the last tick, for any of the given devices, or for any device when `handles` is `None`.
+    pub(crate) fn latest_event_time(&self, handles: Option<&[Handle]>) -> Option<Instant> {
+        self.pending.iter()
+            .filter(|(handle, _)| handles.map_or(true, |handles| handles.contains(handle)))
+            .map(|(_, timestamp)| *timestamp)
+            .max()
+    }
+
+    /// Record the latency of all pending events, as they have now been processed.
+    pub(crate) fn end_frame(&mut self, process_time: Instant) {
+        for (handle, timestamp) in self.pending.drain(..) {
+            let delay = process_time.saturating_duration_since(timestamp);
+            match self.devices.iter_mut().find(|dev| dev.handle == handle) {
+                Some(dev) => {
+                    dev.min = dev.min.min(delay);
+                    dev.max = dev.max.max(delay);
+                    dev.total += delay;
+                    dev.count += 1;
+                },
+                None => self.devices.push(DeviceLatency { handle, min: delay, max: delay, total: delay, count: 1 }),
+            }
+        }
+    }
+
+    pub(crate) fn remove_device(&mut self, handle: Handle) {
+        self.pending.retain(|(cur, _)| *cur != handle);
+        self.devices.retain(|dev| dev.handle != handle);
+    }
+
+    pub(crate) fn stats(&self) -> Vec<(Handle, LatencyStats)> {
+        self.devices.iter().map(|dev| (dev.handle, LatencyStats {
+            min: dev.min,
+            avg: dev.total / dev.count,
+            max: dev.max,
+            count: dev.count,
+        })).collect()
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.devices.clear();
+    }
+}
@@ -8,7 +8,7 @@ mod os;
 
 mod input_manager;
 use core::ops::Add;
-use std::{collections::HashSet, sync::{Arc, Weak}};
+use std::{collections::HashSet, sync::{Arc, Weak}, time::Instant};
 
 pub use input_manager::*;
 
@@ -25,6 +25,36 @@ mod user;
 use onca_common::sync::Mutex;
 pub use user::*;
 
+mod recording;
+pub use recording::*;
+
+mod haptics;
+pub use haptics::*;
+
+mod text_input;
+pub use text_input::*;
+
+mod gestures;
+pub use gestures::*;
+
+mod latency;
+pub use latency::*;
+
+mod binding_capture;
+pub use binding_capture::*;
+
+mod cursor;
+pub use cursor::*;
+
+mod gamepad_backend;
+pub use gamepad_backend::*;
+
+mod snapshot;
+pub use snapshot::*;
+
+mod debug_feed;
+pub use debug_feed::*;
+
 use onca_logging::LogCategory;
 use onca_math::{f32v2, f32v3};
 
@@ -193,10 +223,27 @@ pub(crate) struct InputProcessContext {
     triggered_actions : Vec<Arc<Mutex<Action>>>,
     consumed_inputs   : HashSet<AxisId>,
     trigger_states    : Vec<(Weak<Mutex<Action>>, TriggerResult)>,
+    /// Timestamp of the most recent input event that is processed
+    input_timestamp   : Option<Instant>,
+    /// State of all processed actions, used to build the frame's snapshot
+    action_snapshots  : Vec<ActionSnapshot>,
+    /// Debug info of all visited mappings, only collected when the debug feed is enabled
+    debug             : Option<Vec<ActionDebugInfo>>,
+    /// Actions that consumed each input, only collected when the debug feed is enabled
+    consumed_by       : Vec<(AxisId, String)>,
 }
 
 impl InputProcessContext {
-    pub fn new() -> Self {
-        Self { processed_actions: Vec::new(), triggered_actions: Vec::new(), consumed_inputs: HashSet::new(), trigger_states: Vec::new() }
+    pub fn new(input_timestamp: Option<Instant>, collect_debug: bool) -> Self {
+        Self {
+            processed_actions: Vec::new(),
+            triggered_actions: Vec::new(),
+            consumed_inputs: HashSet::new(),
+            trigger_states: Vec::new(),
+            input_timestamp,
+            action_snapshots: Vec::new(),
+            debug: collect_debug.then(Vec::new),
+            consumed_by: Vec::new(),
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,436 @@
+use std::{ffi::c_void, sync::Arc};
+
+use onca_common::{dynlib::DynLib, sync::Mutex};
+use onca_hid as hid;
+use onca_logging::log_info;
+use onca_math::Vec2;
+use windows::core::HRESULT;
+
+use crate::{
+    AxisId, AxisValue, Gamepad, GamepadButton, HatSwitch, InputDevice, NativeDeviceHandle, OutputInfo, Rebinder, ReleaseCurve, RumbleState, RumbleSupport,
+    GamepadBackend, GamepadBackendKind, ControllerType, LOG_INPUT_CAT,
+};
+
+// The GameInput interfaces are not part of the windows crate, so only the parts that are used are declared here, following `GameInput.h` of the GDK.
+// Functions that are not used are declared as opaque pointers, so the offsets of the used functions stay correct.
+
+const GAME_INPUT_KIND_GAMEPAD: u32 = 0x0004_0000;
+
+const GAME_INPUT_GAMEPAD_MENU:             u32 = 0x0000_0001;
+const GAME_INPUT_GAMEPAD_VIEW:             u32 = 0x0000_0002;
+const GAME_INPUT_GAMEPAD_A:                u32 = 0x0000_0004;
+const GAME_INPUT_GAMEPAD_B:                u32 = 0x0000_0008;
+const GAME_INPUT_GAMEPAD_X:                u32 = 0x0000_0010;
+const GAME_INPUT_GAMEPAD_Y:                u32 = 0x0000_0020;
+const GAME_INPUT_GAMEPAD_DPAD_UP:          u32 = 0x0000_0040;
+const GAME_INPUT_GAMEPAD_DPAD_DOWN:        u32 = 0x0000_0080;
+const GAME_INPUT_GAMEPAD_DPAD_LEFT:        u32 = 0x0000_0100;
+const GAME_INPUT_GAMEPAD_DPAD_RIGHT:       u32 = 0x0000_0200;
+const GAME_INPUT_GAMEPAD_LEFT_SHOULDER:    u32 = 0x0000_0400;
+const GAME_INPUT_GAMEPAD_RIGHT_SHOULDER:   u32 = 0x0000_0800;
+const GAME_INPUT_GAMEPAD_LEFT_THUMBSTICK:  u32 = 0x0000_1000;
+const GAME_INPUT_GAMEPAD_RIGHT_THUMBSTICK: u32 = 0x0000_2000;
+
+type Unused = *const c_void;
+
+#[repr(C)]
+struct IUnknownVtbl {
+    query_interface: Unused,
+    add_ref:         unsafe extern "system" fn(*mut c_void) -> u32,
+    release:         unsafe extern "system" fn(*mut c_void) -> u32,
+}
+
+#[repr(C)]
+struct IGameInputVtbl {
+    base:                            IUnknownVtbl,
+    get_current_timestamp:           Unused,
+    get_current_reading:             unsafe extern "system" fn(*mut c_void, u32, *mut c_void, *mut *mut c_void) -> HRESULT,
+    get_next_reading:                Unused,
+    get_previous_reading:            Unused,
+    get_temporal_reading:            Unused,
+    register_reading_callback:       Unused,
+    register_device_callback:        Unused,
+    register_system_button_callback: Unused,
+    register_keyboard_layout_callback: Unused,
+    stop_callback:                   Unused,
+    unregister_callback:             Unused,
+    create_dispatcher:               Unused,
+    create_aggregate_device:         Unused,
+    find_device_from_id:             Unused,
+    find_device_from_object:         Unused,
+    find_device_from_platform_handle: Unused,
+    find_device_from_platform_string: unsafe extern "system" fn(*mut c_void, *const u16, *mut *mut c_void) -> HRESULT,
+}
+
+#[repr(C)]
+struct IGameInputReadingVtbl {
+    base:                          IUnknownVtbl,
+    get_input_kind:                Unused,
+    get_sequence_number:           unsafe extern "system" fn(*mut c_void, u32) -> u64,
+    get_timestamp:                 Unused,
+    get_device:                    Unused,
+    get_raw_report:                Unused,
+    get_controller_axis_count:     Unused,
+    get_controller_axis_state:     Unused,
+    get_controller_button_count:   Unused,
+    get_controller_button_state:   Unused,
+    get_controller_switch_count:   Unused,
+    get_controller_switch_state:   Unused,
+    get_key_count:                 Unused,
+    get_key_state:                 Unused,
+    get_mouse_state:               Unused,
+    get_touch_count:               Unused,
+    get_touch_state:               Unused,
+    get_motion_state:              Unused,
+    get_arcade_stick_state:        Unused,
+    get_flight_stick_state:        Unused,
+    get_gamepad_state:             unsafe extern "system" fn(*mut c_void, *mut GameInputGamepadState) -> bool,
+}
+
+#[repr(C)]
+struct IGameInputDeviceVtbl {
+    base:                              IUnknownVtbl,
+    get_device_info:                   unsafe extern "system" fn(*mut c_void) -> *const GameInputDeviceInfo,
+    get_device_status:                 Unused,
+    get_battery_state:                 Unused,
+    create_force_feedback_effect:      Unused,
+    is_force_feedback_motor_powered_on: Unused,
+    set_force_feedback_motor_gain:     Unused,
+    set_haptic_motor_state:            Unused,
+    set_rumble_state:                  unsafe extern "system" fn(*mut c_void, *const GameInputRumbleParams),
+}
+
+/// Start of `GameInputDeviceInfo`, up to the input the device supports.
+#[allow(dead_code)]
+#[repr(C)]
+struct GameInputDeviceInfo {
+    info_size:         u32,
+    vendor_id:         u16,
+    product_id:        u16,
+    revision_number:   u16,
+    interface_number:  u8,
+    collection_number: u8,
+    usage:             [u16; 2],
+    hardware_version:  [u16; 4],
+    firmware_version:  [u16; 4],
+    device_id:         [u8; 32],
+    device_root_id:    [u8; 32],
+    device_family:     u32,
+    capabilities:      u32,
+    supported_input:   u32,
+}
+
+#[derive(Clone, Copy, Default)]
+#[repr(C)]
+struct GameInputGamepadState {
+    buttons:            u32,
+    left_trigger:       f32,
+    right_trigger:      f32,
+    left_thumbstick_x:  f32,
+    left_thumbstick_y:  f32,
+    right_thumbstick_x: f32,
+    right_thumbstick_y: f32,
+}
+
+#[repr(C)]
+struct GameInputRumbleParams {
+    low_frequency:  f32,
+    high_frequency: f32,
+    left_trigger:   f32,
+    right_trigger:  f32,
+}
+
+/// Owned reference to a GameInput interface, which is released when dropped.
+struct ComPtr<T> {
+    ptr: *mut c_void,
+    _phantom: core::marker::PhantomData<T>,
+}
+
+impl<T> ComPtr<T> {
+    /// Take ownership of an interface pointer, returning `None` when the pointer is null.
+    unsafe fn from_raw(ptr: *mut c_void) -> Option<Self> {
+        if ptr.is_null() {
+            None
+        } else {
+            Some(Self { ptr, _phantom: core::marker::PhantomData })
+        }
+    }
+
+    fn vtbl(&self) -> &T {
+        // SAFETY: The first member of every interface is a pointer to its vtable
+        unsafe { &**(self.ptr as *const *const T) }
+    }
+}
+
+impl<T> Clone for ComPtr<T> {
+    fn clone(&self) -> Self {
+        // SAFETY: Every vtable starts with the `IUnknown` functions
+        unsafe {
+            let vtbl = &**(self.ptr as *const *const IUnknownVtbl);
+            (vtbl.add_ref)(self.ptr);
+        }
+        Self { ptr: self.ptr, _phantom: core::marker::PhantomData }
+    }
+}
+
+impl<T> Drop for ComPtr<T> {
+    fn drop(&mut self) {
+        // SAFETY: Every vtable starts with the `IUnknown` functions
+        unsafe {
+            let vtbl = &**(self.ptr as *const *const IUnknownVtbl);
+            (vtbl.release)(self.ptr);
+        }
+    }
+}
+
+// SAFETY: All GameInput interfaces are free-threaded
+unsafe impl<T> Send for ComPtr<T> {}
+unsafe impl<T> Sync for ComPtr<T> {}
+
+/// GameInput gamepad backend.
+pub(crate) struct GameInputBackend {
+    // The interface needs to be released before the library is unloaded, so it's declared first
+    game_input: Option<ComPtr<IGameInputVtbl>>,
+    dynlib:     Option<Arc<DynLib>>,
+}
+
+impl GameInputBackend {
+    pub fn new() -> Self {
+        // GameInput is only available when its redistributable is installed, if it isn't, the backend is reported as unavailable
+        let Ok(dynlib) = DynLib::load("GameInput.dll") else {
+            log_info!(LOG_INPUT_CAT, "GameInput is not installed, the GameInput gamepad backend is unavailable");
+            return Self { game_input: None, dynlib: None };
+        };
+
+        let game_input = dynlib.get::<unsafe extern "system" fn(*mut *mut c_void) -> HRESULT>("GameInputCreate").and_then(|game_input_create| unsafe {
+            let mut game_input = core::ptr::null_mut();
+            let hres = game_input_create(&mut game_input);
+            if hres.is_ok() { ComPtr::from_raw(game_input) } else { None }
+        });
+        if game_input.is_none() {
+            log_info!(LOG_INPUT_CAT, "Failed to create the GameInput interface, the GameInput gamepad backend is unavailable");
+        }
+
+        Self { game_input, dynlib: Some(Arc::new(dynlib)) }
+    }
+
+    /// Find the GameInput device for the native device, if GameInput can read it as a gamepad.
+    fn find_gamepad(&self, unique_id: &str) -> Option<ComPtr<IGameInputDeviceVtbl>> {
+        let game_input = self.game_input.as_ref()?;
+
+        // The unique identifier is the device interface path, which is the platform string GameInput uses
+        let platform_string = unique_id.encode_utf16().chain(core::iter::once(0)).collect::<Vec<_>>();
+        let mut device = core::ptr::null_mut();
+        let device = unsafe {
+            let hres = (game_input.vtbl().find_device_from_platform_string)(game_input.ptr, platform_string.as_ptr(), &mut device);
+            if hres.is_err() {
+                return None;
+            }
+            ComPtr::<IGameInputDeviceVtbl>::from_raw(device)?
+        };
+
+        let info = unsafe { (device.vtbl().get_device_info)(device.ptr).as_ref()? };
+        if info.supported_input & GAME_INPUT_KIND_GAMEPAD != 0 {
+            Some(device)
+        } else {
+            None
+        }
+    }
+}
+
+impl GamepadBackend for GameInputBackend {
+    fn kind(&self) -> GamepadBackendKind {
+        GamepadBackendKind::GameInput
+    }
+
+    fn is_available(&self) -> bool {
+        self.game_input.is_some()
+    }
+
+    fn supports(&self, _iden: &hid::Identifier, unique_id: &str, _controller_type: ControllerType) -> bool {
+        self.find_gamepad(unique_id).is_some()
+    }
+
+    fn create_device(&mut self, handle: NativeDeviceHandle, _controller_type: ControllerType) -> Result<Box<dyn InputDevice>, NativeDeviceHandle> {
+        let (Some(game_input), Some(dynlib)) = (&self.game_input, &self.dynlib) else { return Err(handle) };
+        let Some(device) = self.find_gamepad(handle.get_unique_identifier()) else { return Err(handle) };
+
+        // The gamepad keeps its own reference to the GameInput interface, so it can get the readings of the device, and keeps the library loaded while it does
+        GameInputGamepad::new(game_input.clone(), device, dynlib.clone(), handle).map(|x| {
+            let res: Box<dyn InputDevice> = Box::new(x);
+            res
+        })
+    }
+}
+
+pub struct GameInputGamepad {
+    gamepad:       Gamepad,
+    game_input:    ComPtr<IGameInputVtbl>,
+    device:        ComPtr<IGameInputDeviceVtbl>,
+    cur_sequence:  u64,
+    rumble_state:  Mutex<RumbleState>,
+    _dynlib:       Arc<DynLib>,
+}
+
+impl GameInputGamepad {
+    fn new(game_input: ComPtr<IGameInputVtbl>, device: ComPtr<IGameInputDeviceVtbl>, dynlib: Arc<DynLib>, handle: NativeDeviceHandle) -> Result<Self, NativeDeviceHandle> {
+        Ok(Self {
+            gamepad: Gamepad::new(handle)?,
+            game_input,
+            device,
+            cur_sequence: 0,
+            rumble_state: Mutex::new(RumbleState::default()),
+            _dynlib: dynlib,
+        })
+    }
+
+    /// Get the latest gamepad state of the device, when it has changed since the last reading.
+    fn get_new_state(&mut self) -> Option<GameInputGamepadState> {
+        let reading = unsafe {
+            let mut reading = core::ptr::null_mut();
+            let hres = (self.game_input.vtbl().get_current_reading)(self.game_input.ptr, GAME_INPUT_KIND_GAMEPAD, self.device.ptr, &mut reading);
+            if hres.is_err() {
+                return None;
+            }
+            ComPtr::<IGameInputReadingVtbl>::from_raw(reading)?
+        };
+
+        let sequence = unsafe { (reading.vtbl().get_sequence_number)(reading.ptr, GAME_INPUT_KIND_GAMEPAD) };
+        if sequence == self.cur_sequence {
+            return None;
+        }
+        self.cur_sequence = sequence;
+
+        let mut state = GameInputGamepadState::default();
+        if unsafe { (reading.vtbl().get_gamepad_state)(reading.ptr, &mut state) } {
+            Some(state)
+        } else {
+            None
+        }
+    }
+}
+
+impl InputDevice for GameInputGamepad {
+    fn tick(&mut self, dt: f32, rebinder: &mut Rebinder) {
+        if let Some(state) = self.get_new_state() {
+            let pressed = |button: u32| state.buttons & button != 0;
+
+            // DPad
+            let up = pressed(GAME_INPUT_GAMEPAD_DPAD_UP);
+            let down = pressed(GAME_INPUT_GAMEPAD_DPAD_DOWN);
+            let left = pressed(GAME_INPUT_GAMEPAD_DPAD_LEFT);
+            let right = pressed(GAME_INPUT_GAMEPAD_DPAD_RIGHT);
+
+            self.gamepad.move_dpad(HatSwitch::from_4_button(up, down, left, right), f32::MAX);
+
+            if up { rebinder.notify(&[Gamepad::DPAD_UP]); }
+            if down { rebinder.notify(&[Gamepad::DPAD_DOWN]); }
+            if left { rebinder.notify(&[Gamepad::DPAD_LEFT]); }
+            if right { rebinder.notify(&[Gamepad::DPAD_RIGHT]); }
+
+            // Buttons
+            const BUTTONS: [(u32, GamepadButton, AxisId); 10] = [
+                (GAME_INPUT_GAMEPAD_A,                GamepadButton::FaceBottom,       Gamepad::FACE_BOTTOM),
+                (GAME_INPUT_GAMEPAD_B,                GamepadButton::FaceRight,        Gamepad::FACE_RIGHT),
+                (GAME_INPUT_GAMEPAD_X,                GamepadButton::FaceLeft,         Gamepad::FACE_LEFT),
+                (GAME_INPUT_GAMEPAD_Y,                GamepadButton::FaceTop,          Gamepad::FACE_TOP),
+                (GAME_INPUT_GAMEPAD_LEFT_SHOULDER,    GamepadButton::LeftBumper,       Gamepad::LEFT_BUMPER),
+                (GAME_INPUT_GAMEPAD_RIGHT_SHOULDER,   GamepadButton::RightBumper,      Gamepad::RIGHT_BUMPER),
+                (GAME_INPUT_GAMEPAD_VIEW,             GamepadButton::LeftMenu,         Gamepad::LEFT_MENU),
+                (GAME_INPUT_GAMEPAD_MENU,             GamepadButton::RightMenu,        Gamepad::RIGHT_MENU),
+                (GAME_INPUT_GAMEPAD_LEFT_THUMBSTICK,  GamepadButton::LeftThumbstick,   Gamepad::LEFT_THUMB_BUTTON),
+                (GAME_INPUT_GAMEPAD_RIGHT_THUMBSTICK, GamepadButton::RightThumbsstick, Gamepad::RIGHT_THUMB_BUTTON),
+            ];
+            for (flag, button, axis) in BUTTONS {
+                let is_pressed = pressed(flag);
+                self.gamepad.set_button(button, f32::MAX, is_pressed);
+                if is_pressed { rebinder.notify(&[axis]); }
+            }
+
+            // Thumbsticks, GameInput already reports these in the range [-1, 1]
+            self.gamepad.move_stick(false, Vec2::new(state.left_thumbstick_x, state.left_thumbstick_y), f32::MAX, ReleaseCurve::Instant);
+
+            if state.left_thumbstick_x.abs() > 0.5 { rebinder.notify(&[Gamepad::LEFT_THUMB_X]); }
+            if state.left_thumbstick_y.abs() > 0.5 { rebinder.notify(&[Gamepad::LEFT_THUMB_Y]); }
+
+            self.gamepad.move_stick(true, Vec2::new(state.right_thumbstick_x, state.right_thumbstick_y), f32::MAX, ReleaseCurve::Instant);
+
+            if state.right_thumbstick_x.abs() > 0.5 { rebinder.notify(&[Gamepad::RIGHT_THUMB_X]); }
+            if state.right_thumbstick_y.abs() > 0.5 { rebinder.notify(&[Gamepad::RIGHT_THUMB_Y]); }
+
+            // Triggers, in the range [0, 1]
+            self.gamepad.move_trigger(false, state.left_trigger, f32::MAX, ReleaseCurve::Instant);
+            self.gamepad.move_trigger(true, state.right_trigger, f32::MAX, ReleaseCurve::Instant);
+
+            if state.left_trigger > 0.5 { rebinder.notify(&[Gamepad::LEFT_TRIGGER]) };
+            if state.right_trigger > 0.5 { rebinder.notify(&[Gamepad::RIGHT_TRIGGER]) };
+        }
+        self.gamepad.tick(dt, rebinder);
+
+        let rumble_state = self.rumble_state.lock();
+        let params = GameInputRumbleParams {
+            low_frequency: rumble_state.low_frequency,
+            high_frequency: rumble_state.high_frequency,
+            left_trigger: rumble_state.left_trigger,
+            right_trigger: rumble_state.right_trigger,
+        };
+        unsafe { (self.device.vtbl().set_rumble_state)(self.device.ptr, &params) };
+    }
+
+    fn handle_hid_input(&mut self, _input_report: &[u8]) {
+        // Nothing to do here
+    }
+
+    fn handle_native_input(&mut self, _native_data: *const c_void) {
+        // Nothing to do here
+    }
+
+    fn get_native_handle(&self) -> &NativeDeviceHandle {
+        self.gamepad.get_native_handle()
+    }
+
+    fn get_axis_value(&self, axis: &crate::AxisId) -> Option<crate::AxisValue> {
+        self.gamepad.get_axis_value(axis)
+    }
+
+    fn get_axes(&self) -> &[crate::InputAxisDefinition] {
+        self.gamepad.get_axes()
+    }
+
+    fn get_device_type(&self) -> crate::DeviceType {
+        self.gamepad.get_device_type()
+    }
+
+    fn take_native_handle(&mut self) -> NativeDeviceHandle {
+        self.gamepad.take_native_handle()
+    }
+
+    fn get_battery_info(&self) -> Option<crate::BatteryInfo> {
+        None
+    }
+    fn get_output_info<'a>(&'a self) -> &'a OutputInfo<'a> {
+        const INFO: OutputInfo = OutputInfo {
+            rumble: RumbleSupport::LowFrequecy.bitor(RumbleSupport::HighFrequency).bitor(RumbleSupport::LeftTrigger).bitor(RumbleSupport::RightTrigger),
+            trigger_feedback: None,
+            led_support: &[],
+            output_axes: &[]
+        };
+        &INFO
+    }
+
+    fn set_rumble(&self, rumble: crate::RumbleState) {
+        *self.rumble_state.lock() = rumble;
+    }
+
+    fn set_trigger_feedback(&self, _right_trigger: bool, _trigger_feedback: crate::TriggerFeedback) {
+        // Nothing to do here
+    }
+
+    fn set_led_state(&self, _index: u16, _state: crate::LedState) {
+        // Nothing to do here
+    }
+
+    fn set_output_axis(&self, _axis: AxisId, _value: AxisValue) {
+        // Nothing to do here
+    }
+}
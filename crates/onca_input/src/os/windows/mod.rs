@@ -2,22 +2,23 @@ use core::{
     mem,
     ffi::c_void,
 };
-use std::{mem::size_of, collections::HashMap, sync::Arc};
+use std::{mem::size_of, collections::HashMap, sync::Arc, time::{Instant, Duration}};
 use onca_common::prelude::*;
 use onca_hid as hid;
 use onca_window::Window;
 use windows::{Win32::{
     UI::{
-        WindowsAndMessaging::{RIM_INPUT, GIDC_ARRIVAL, GIDC_REMOVAL},
+        WindowsAndMessaging::{RIM_INPUT, GIDC_ARRIVAL, GIDC_REMOVAL, GetMessageTime},
         Input::{GetRawInputData, HRAWINPUT, RAWINPUTHEADER, RID_INPUT, RAWINPUT, RIM_TYPEMOUSE, RIM_TYPEKEYBOARD, RIM_TYPEHID, GetRawInputDeviceInfoA, RIDI_DEVICENAME, RID_DEVICE_INFO, RIDI_DEVICEINFO, RAWINPUTDEVICE, RegisterRawInputDevices, RIDEV_DEVNOTIFY}
     },
-    Foundation::{WPARAM, LPARAM, GetLastError, HANDLE, HWND}
+    Foundation::{WPARAM, LPARAM, GetLastError, HANDLE, HWND},
+    System::SystemInformation::GetTickCount,
 }, core::HRESULT};
 use onca_logging::{log_warning, log_error};
 #[cfg(feature = "raw_input_logging")]
 use onca_logging::log_verbose;
 use crate::{
-    InputManager, LOG_EVENT_CAT, LOG_INPUT_CAT, NativeDeviceHandleT, NativeDeviceHandle, Handle, InputDevice,
+    InputManager, LOG_EVENT_CAT, LOG_INPUT_CAT, NativeDeviceHandleT, NativeDeviceHandle, Handle, GamepadBackendKind,
 };
 
 pub(crate) mod keyboard;
@@ -26,39 +27,38 @@ pub(crate) use keyboard::OSKeyboard;
 pub(crate) mod mouse;
 pub(crate) use mouse::OSMouse;
 
-use self::xinput::{XInputContext, XInputGamepad};
+use self::{gameinput::GameInputBackend, xinput::XInputBackend};
 
+pub(crate) mod gameinput;
 pub(crate) mod xinput;
 
 const RIMTYPE_MOUSE    : u32 = RIM_TYPEMOUSE.0;
 const RIMTYPE_KEYBOARD : u32 = RIM_TYPEKEYBOARD.0;
 const RIMTYPE_HID      : u32 = RIM_TYPEHID.0;
 
-pub fn register_input_devices(manager: &Arc<InputManager>) {
-    let manager_ptr = manager.clone();
-
-    manager.register_custom_create_device(
-        |_, unique_id| unique_id.contains("IG_"), // XInput device identifier
-        &[],
-        move |handle| {
-            XInputGamepad::new(&mut manager_ptr.get_os_input().xinput_ctx, handle).map(|x| {
-                // We need to get around rust not realizing that `Box` could `CoerseUnsized` directly in a return statement
-                // This could be one of those "std::boxed::Box is special" cases, as the first line clearly shows that it works
-                let res: Box<dyn InputDevice> = Box::new(x);
-                res
-            })
-
-        }
-    );
-
+/// Get the bytes of a native input struct.
+fn as_bytes<T>(val: &T) -> &[u8] {
+    // SAFETY: The native input structs are plain data
+    unsafe { core::slice::from_raw_parts(val as *const T as *const u8, core::mem::size_of::<T>()) }
+}
 
+/// Get the time at which the OS posted the message that is currently being processed.
+fn get_message_timestamp() -> Instant {
+    let now = Instant::now();
+    // Both times are in milliseconds since system start, and wrap around every ~49.7 days
+    let (tick_count, message_time) = unsafe { (GetTickCount(), GetMessageTime() as u32) };
+    let age = Duration::from_millis(tick_count.wrapping_sub(message_time) as u64);
+    now.checked_sub(age).unwrap_or(now)
+}
 
+pub fn register_input_devices(manager: &Arc<InputManager>) {
+    manager.register_gamepad_backend(GamepadBackendKind::GameInput.default_priority(), Box::new(GameInputBackend::new()));
+    manager.register_gamepad_backend(GamepadBackendKind::XInput.default_priority(), Box::new(XInputBackend::new()));
 }
 
 pub(crate) struct OSInput {
     handle_mapping: HashMap<isize, Handle>,
     main_window:    HWND,
-    xinput_ctx:     XInputContext,
 }
 
 impl OSInput {
@@ -66,7 +66,6 @@ impl OSInput {
         Ok(Self {
             handle_mapping: HashMap::new(),
             main_window: main_window.os_handle().hwnd(),
-            xinput_ctx: XInputContext::new(),
         })
     }
 
@@ -126,12 +125,13 @@ impl OSInput {
                     return;
                 }
                 
+                let timestamp = get_message_timestamp();
                 match rawinput.header.dwType {
-                    RIMTYPE_MOUSE    => manager.handle_native_input(handle, &rawinput.data.mouse as *const _ as *const _),
-                    RIMTYPE_KEYBOARD => manager.handle_native_input(handle, &rawinput.data.keyboard as *const _ as *const _),
+                    RIMTYPE_MOUSE    => manager.handle_native_input(handle, as_bytes(&rawinput.data.mouse), timestamp),
+                    RIMTYPE_KEYBOARD => manager.handle_native_input(handle, as_bytes(&rawinput.data.keyboard), timestamp),
                     RIMTYPE_HID => {
                         let raw_report = core::slice::from_raw_parts(rawinput.data.hid.bRawData.as_ptr(), rawinput.data.hid.dwSizeHid as usize);
-                        manager.handle_hid_input(handle, raw_report);
+                        manager.handle_hid_input(handle, raw_report, timestamp);
                     },
                     invalid => log_error!(LOG_EVENT_CAT, "Received an invalid RAWINPUT type: {invalid}"),
                 }
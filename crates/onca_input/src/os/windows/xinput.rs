@@ -5,7 +5,10 @@ use onca_hid as hid;
 use onca_math::Vec2;
 use windows::Win32::UI::Input::XboxController::*;
 
-use crate::{AxisId, AxisValue, Gamepad, GamepadButton, HatSwitch, InputDevice, NativeDeviceHandle, OutputInfo, Rebinder, ReleaseCurve, RumbleState, RumbleSupport};
+use crate::{
+    AxisId, AxisValue, Gamepad, GamepadButton, HatSwitch, InputDevice, NativeDeviceHandle, OutputInfo, Rebinder, ReleaseCurve, RumbleState, RumbleSupport,
+    GamepadBackend, GamepadBackendKind, ControllerType,
+};
 
 #[derive(Clone, Copy, Debug)]
 #[repr(C)]
@@ -58,6 +61,41 @@ impl XInputContext {
     }
 }
 
+/// XInput gamepad backend.
+pub(crate) struct XInputBackend {
+    ctx: XInputContext,
+}
+
+impl XInputBackend {
+    pub fn new() -> Self {
+        Self { ctx: XInputContext::new() }
+    }
+}
+
+impl GamepadBackend for XInputBackend {
+    fn kind(&self) -> GamepadBackendKind {
+        GamepadBackendKind::XInput
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn supports(&self, _iden: &hid::Identifier, unique_id: &str, _controller_type: ControllerType) -> bool {
+        // XInput device identifier
+        unique_id.contains("IG_")
+    }
+
+    fn create_device(&mut self, handle: NativeDeviceHandle, _controller_type: ControllerType) -> Result<Box<dyn InputDevice>, NativeDeviceHandle> {
+        XInputGamepad::new(&mut self.ctx, handle).map(|x| {
+            // We need to get around rust not realizing that `Box` could `CoerseUnsized` directly in a return statement
+            // This could be one of those "std::boxed::Box is special" cases, as the first line clearly shows that it works
+            let res: Box<dyn InputDevice> = Box::new(x);
+            res
+        })
+    }
+}
+
 pub struct XInputGamepad {
     gamepad:       Gamepad,
     xinput_idx:    u32,
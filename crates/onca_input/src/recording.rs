@@ -0,0 +1,290 @@
+use std::io::{self, Read, Write};
+
+use onca_hid as hid;
+
+use crate::Handle;
+
+const RECORDING_MAGIC: [u8; 4] = *b"OIRC";
+const RECORDING_VERSION: u16 = 1;
+
+const EVENT_KIND_HID: u8 = 0;
+const EVENT_KIND_NATIVE: u8 = 1;
+
+/// Kind of data contained in a recorded event.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RecordedEventKind {
+    /// Raw HID input report.
+    Hid,
+    /// Native OS input data, e.g. a `RAWMOUSE` or `RAWKEYBOARD` on windows.
+    Native,
+}
+
+/// Single device event in a recording.
+#[derive(Clone, Debug)]
+pub struct RecordedEvent {
+    /// Index of the device in [`InputRecording::devices`].
+    pub device: u8,
+    /// Kind of data.
+    pub kind:   RecordedEventKind,
+    /// Event data.
+    pub data:   Vec<u8>,
+}
+
+/// All device events that were received during a single frame.
+#[derive(Clone, Debug, Default)]
+pub struct RecordedFrame {
+    /// Delta time of the frame, in seconds.
+    pub dt:     f32,
+    /// Events in the order they were received.
+    pub events: Vec<RecordedEvent>,
+}
+
+/// Recorded input stream, which can be replayed using an [`InputPlayer`].
+///
+/// Devices are stored using their HID identifier, so the recording can be replayed on another machine, as long as a device with the same identifier is connected.
+/// Input of devices that are polled instead of sending events, e.g. XInput gamepads, is not part of a recording.
+#[derive(Clone, Debug, Default)]
+pub struct InputRecording {
+    /// Identifiers of all devices that sent events during the recording.
+    pub devices: Vec<hid::Identifier>,
+    /// Recorded frames.
+    pub frames:  Vec<RecordedFrame>,
+}
+
+impl InputRecording {
+    /// Get the total duration of the recording, in seconds.
+    pub fn duration(&self) -> f32 {
+        self.frames.iter().map(|frame| frame.dt).sum()
+    }
+
+    /// Write the recording to a compact binary stream.
+    ///
+    /// A recording can contain at most 255 devices.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        if self.devices.len() > u8::MAX as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Input recording contains more than 255 devices"));
+        }
+
+        writer.write_all(&RECORDING_MAGIC)?;
+        writer.write_all(&RECORDING_VERSION.to_le_bytes())?;
+
+        writer.write_all(&[self.devices.len() as u8])?;
+        for iden in &self.devices {
+            for val in [iden.vendor_device.vendor.as_u16(), iden.vendor_device.device.as_u16(), iden.version, iden.usage.page.as_u16(), iden.usage.usage.as_u16()] {
+                writer.write_all(&val.to_le_bytes())?;
+            }
+        }
+
+        writer.write_all(&(self.frames.len() as u32).to_le_bytes())?;
+        for frame in &self.frames {
+            writer.write_all(&frame.dt.to_le_bytes())?;
+            writer.write_all(&(frame.events.len() as u32).to_le_bytes())?;
+            for event in &frame.events {
+                let kind = match event.kind {
+                    RecordedEventKind::Hid    => EVENT_KIND_HID,
+                    RecordedEventKind::Native => EVENT_KIND_NATIVE,
+                };
+                writer.write_all(&[event.device, kind])?;
+                writer.write_all(&(event.data.len() as u16).to_le_bytes())?;
+                writer.write_all(&event.data)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a recording from a binary stream written by [`InputRecording::write_to`].
+    pub fn read_from<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != RECORDING_MAGIC {
+            return Err(invalid_data("Invalid input recording: magic number does not match"));
+        }
+        let version = read_u16(&mut reader)?;
+        if version != RECORDING_VERSION {
+            return Err(invalid_data("Unsupported input recording version"));
+        }
+
+        let device_count = read_u8(&mut reader)?;
+        let mut devices = Vec::with_capacity(device_count as usize);
+        for _ in 0..device_count {
+            let vendor = read_u16(&mut reader)?;
+            let device = read_u16(&mut reader)?;
+            let version = read_u16(&mut reader)?;
+            let page = read_u16(&mut reader)?;
+            let usage = read_u16(&mut reader)?;
+            devices.push(hid::Identifier {
+                vendor_device: hid::VendorProduct::from_u16(vendor, device),
+                version,
+                usage: hid::Usage::from_u16(page, usage),
+            });
+        }
+
+        let frame_count = read_u32(&mut reader)?;
+        let mut frames = Vec::new();
+        for _ in 0..frame_count {
+            let dt = f32::from_bits(read_u32(&mut reader)?);
+            let event_count = read_u32(&mut reader)?;
+            let mut events = Vec::new();
+            for _ in 0..event_count {
+                let device = read_u8(&mut reader)?;
+                if device as usize >= devices.len() {
+                    return Err(invalid_data("Invalid input recording: event references an unknown device"));
+                }
+                let kind = match read_u8(&mut reader)? {
+                    EVENT_KIND_HID    => RecordedEventKind::Hid,
+                    EVENT_KIND_NATIVE => RecordedEventKind::Native,
+                    _ => return Err(invalid_data("Invalid input recording: unknown event kind")),
+                };
+                let len = read_u16(&mut reader)?;
+                let mut data = vec![0; len as usize];
+                reader.read_exact(&mut data)?;
+                events.push(RecordedEvent { device, kind, data });
+            }
+            frames.push(RecordedFrame { dt, events });
+        }
+        Ok(Self { devices, frames })
+    }
+}
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut bytes = [0; 1];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes[0])
+}
+
+fn read_u16<R: Read>(reader: &mut R) -> io::Result<u16> {
+    let mut bytes = [0; 2];
+    reader.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// Recorder that captures all device events received by the [`InputManager`](crate::InputManager), together with the delta time of each frame.
+///
+/// Start a recording using [`InputManager::start_recording`](crate::InputManager::start_recording).
+#[derive(Default)]
+pub struct InputRecorder {
+    handles:   Vec<Handle>,
+    recording: InputRecording,
+    current:   Vec<RecordedEvent>,
+}
+
+impl InputRecorder {
+    pub fn new() -> Self {
+        Self { handles: Vec::new(), recording: InputRecording::default(), current: Vec::new() }
+    }
+
+    /// Get the number of frames that have been recorded.
+    pub fn frame_count(&self) -> usize {
+        self.recording.frames.len()
+    }
+
+    /// Record an event for a device, `get_iden` is used to get the identifier the first time a device sends an event.
+    pub(crate) fn record<F>(&mut self, handle: Handle, kind: RecordedEventKind, data: &[u8], get_iden: F)
+    where
+        F: FnOnce() -> Option<hid::Identifier>
+    {
+        // The length of an event is stored as a u16, larger events are not expected from any device
+        if data.len() > u16::MAX as usize {
+            return;
+        }
+
+        let device = match self.handles.iter().position(|h| *h == handle) {
+            Some(idx) => idx,
+            None => {
+                // The device count is stored as a u8, so only 255 devices can be referenced by a recording
+                if self.handles.len() >= u8::MAX as usize {
+                    return;
+                }
+                let Some(iden) = get_iden() else { return };
+                self.handles.push(handle);
+                self.recording.devices.push(iden);
+                self.handles.len() - 1
+            },
+        };
+        self.current.push(RecordedEvent { device: device as u8, kind, data: data.to_vec() });
+    }
+
+    /// End the current frame, storing all events received since the previous frame.
+    pub(crate) fn end_frame(&mut self, dt: f32) {
+        self.recording.frames.push(RecordedFrame { dt, events: core::mem::take(&mut self.current) });
+    }
+
+    /// Finish the recording.
+    pub(crate) fn finish(mut self) -> InputRecording {
+        if !self.current.is_empty() {
+            self.end_frame(0.0);
+        }
+        self.recording
+    }
+}
+
+/// Player that re-injects a recording into the [`InputManager`](crate::InputManager), frame by frame.
+///
+/// While a recording is playing, live input from devices is ignored and the recorded delta time replaces the delta time passed to the input manager,
+/// so the replay is deterministic.
+///
+/// Start playback using [`InputManager::start_playback`](crate::InputManager::start_playback).
+pub struct InputPlayer {
+    recording: InputRecording,
+    handles:   Vec<Option<Handle>>,
+    frame_idx: usize,
+}
+
+impl InputPlayer {
+    pub fn new(recording: InputRecording) -> Self {
+        Self { recording, handles: Vec::new(), frame_idx: 0 }
+    }
+
+    /// Get the recording being played.
+    pub fn recording(&self) -> &InputRecording {
+        &self.recording
+    }
+
+    /// Get the index of the next frame that will be played.
+    pub fn frame_idx(&self) -> usize {
+        self.frame_idx
+    }
+
+    /// Check if all frames have been played.
+    pub fn is_finished(&self) -> bool {
+        self.frame_idx >= self.recording.frames.len()
+    }
+
+    /// Map each device in the recording to a connected device with the same identifier.
+    ///
+    /// Returns the identifiers of recorded devices that could not be mapped.
+    pub(crate) fn map_devices<'a, I>(&mut self, connected: I) -> Vec<hid::Identifier>
+    where
+        I: Iterator<Item = (Handle, &'a hid::Identifier)> + Clone
+    {
+        let mut missing = Vec::new();
+        self.handles.clear();
+        for iden in &self.recording.devices {
+            let handle = connected.clone()
+                .find(|(handle, dev_iden)| *dev_iden == iden && !self.handles.contains(&Some(*handle)))
+                .map(|(handle, _)| handle);
+            if handle.is_none() {
+                missing.push(*iden);
+            }
+            self.handles.push(handle);
+        }
+        missing
+    }
+
+    /// Get the next frame to play, and the handles of the devices its events need to be sent to.
+    pub(crate) fn next_frame(&mut self) -> Option<(&RecordedFrame, &[Option<Handle>])> {
+        let frame = self.recording.frames.get(self.frame_idx)?;
+        self.frame_idx += 1;
+        Some((frame, &self.handles))
+    }
+}
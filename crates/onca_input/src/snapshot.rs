@@ -0,0 +1,129 @@
+use std::sync::Arc;
+
+use onca_common::sync::Mutex;
+
+use crate::{Action, AxisValue, TriggerResult, TriggerState};
+
+/// Identifier of an action within a snapshot, based on the identity of the action, not its name.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ActionKey(usize);
+
+impl ActionKey {
+    /// Get the key of an action.
+    pub fn of(action: &Arc<Mutex<Action>>) -> Self {
+        Self(Arc::as_ptr(action) as *const () as usize)
+    }
+}
+
+/// State of a single action for a single user, at the end of a frame.
+#[derive(Clone, Debug)]
+pub struct ActionSnapshot {
+    /// Key of the action.
+    pub key:         ActionKey,
+    /// Name of the action.
+    pub name:        String,
+    /// User the action was processed for.
+    pub user_idx:    u8,
+    /// Trigger result of the previous frame.
+    pub prev_result: TriggerResult,
+    /// Trigger result of this frame.
+    pub result:      TriggerResult,
+    /// Trigger state this frame.
+    pub state:       TriggerState,
+    /// Value of the action this frame.
+    pub value:       AxisValue,
+}
+
+impl ActionSnapshot {
+    /// Is the action currently triggered.
+    pub fn is_down(&self) -> bool {
+        self.result == TriggerResult::Triggered
+    }
+
+    /// Did the action start triggering this frame.
+    pub fn was_pressed(&self) -> bool {
+        self.result == TriggerResult::Triggered && self.prev_result != TriggerResult::Triggered
+    }
+
+    /// Did the action stop triggering this frame.
+    pub fn was_released(&self) -> bool {
+        self.result != TriggerResult::Triggered && self.prev_result == TriggerResult::Triggered
+    }
+}
+
+struct SnapshotData {
+    frame:   u64,
+    actions: Vec<ActionSnapshot>,
+}
+
+/// Immutable snapshot of the state of all actions at the end of a frame.
+/// 
+/// Snapshots are cheap to clone and can be read from any thread, so gameplay code that prefers polling can read the input state without needing to listen to action events.
+/// When an action is processed for multiple users, the queries that do not take a user return the combined state of all users.
+#[derive(Clone)]
+pub struct InputSnapshot {
+    data: Arc<SnapshotData>,
+}
+
+impl InputSnapshot {
+    pub(crate) fn new(frame: u64, actions: Vec<ActionSnapshot>) -> Self {
+        Self { data: Arc::new(SnapshotData { frame, actions }) }
+    }
+
+    /// Get the frame index the snapshot was taken on.
+    pub fn frame(&self) -> u64 {
+        self.data.frame
+    }
+
+    /// Get the state of all actions that were processed during the frame.
+    pub fn actions(&self) -> &[ActionSnapshot] {
+        &self.data.actions
+    }
+
+    /// Get the state of an action for a given user.
+    pub fn get(&self, action: &Arc<Mutex<Action>>, user_idx: u8) -> Option<&ActionSnapshot> {
+        let key = ActionKey::of(action);
+        self.data.actions.iter().find(|snapshot| snapshot.key == key && snapshot.user_idx == user_idx)
+    }
+
+    /// Get the state of an action with the given name for a given user.
+    pub fn get_by_name(&self, name: &str, user_idx: u8) -> Option<&ActionSnapshot> {
+        self.data.actions.iter().find(|snapshot| snapshot.name == name && snapshot.user_idx == user_idx)
+    }
+
+    /// Check if an action is currently triggered for any user.
+    pub fn is_action_down(&self, action: &Arc<Mutex<Action>>) -> bool {
+        self.iter_action(action).any(|snapshot| snapshot.is_down())
+    }
+
+    /// Check if an action started triggering this frame for any user.
+    pub fn was_pressed_this_frame(&self, action: &Arc<Mutex<Action>>) -> bool {
+        self.iter_action(action).any(|snapshot| snapshot.was_pressed())
+    }
+
+    /// Check if an action stopped triggering this frame for any user.
+    pub fn was_released_this_frame(&self, action: &Arc<Mutex<Action>>) -> bool {
+        self.iter_action(action).any(|snapshot| snapshot.was_released())
+    }
+
+    /// Get the value of an action, converted to the axis type of the action.
+    /// 
+    /// The value of the first user that triggered the action is returned, otherwise the value of the first user that processed it.
+    pub fn axis_value(&self, action: &Arc<Mutex<Action>>) -> Option<AxisValue> {
+        self.iter_action(action)
+            .find(|snapshot| snapshot.is_down())
+            .or_else(|| self.iter_action(action).next())
+            .map(|snapshot| snapshot.value)
+    }
+
+    fn iter_action(&self, action: &Arc<Mutex<Action>>) -> impl Iterator<Item = &ActionSnapshot> {
+        let key = ActionKey::of(action);
+        self.data.actions.iter().filter(move |snapshot| snapshot.key == key)
+    }
+}
+
+impl Default for InputSnapshot {
+    fn default() -> Self {
+        Self::new(0, Vec::new())
+    }
+}
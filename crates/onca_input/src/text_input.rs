@@ -0,0 +1,81 @@
+use onca_common::event_listener::{EventListener, DynEventListenerArray, DynEventListenerRef};
+use onca_window::{WindowEvent, WindowId};
+
+/// Text input event, sent while text input is active.
+#[derive(Clone, Debug)]
+pub enum TextInputEvent {
+    /// Text has been committed, either typed directly, or as the result of an IME composition.
+    Text(String),
+    /// An IME composition has started.
+    CompositionStarted,
+    /// The IME composition has been updated.
+    /// 
+    /// Contains the current composition string and the byte offset of the cursor within it.
+    /// UI widgets are expected to draw the composition string, and can place the IME candidate window using [`Window::set_ime_candidate_rect`](onca_window::Window::set_ime_candidate_rect).
+    Composition{ text: String, cursor: usize },
+    /// The IME composition has ended, any resulting text will be sent using a `Text` event.
+    CompositionEnded,
+}
+
+/// Window listener forwarding text and IME events to the text input listeners.
+pub(crate) struct TextInputListener {
+    window:    Option<WindowId>,
+    composing: bool,
+    listeners: DynEventListenerArray<TextInputEvent>,
+}
+
+impl TextInputListener {
+    pub(crate) fn new() -> Self {
+        Self { window: None, composing: false, listeners: DynEventListenerArray::new() }
+    }
+
+    /// Get the window that text input is active for.
+    pub(crate) fn window(&self) -> Option<WindowId> {
+        self.window
+    }
+
+    pub(crate) fn start(&mut self, window: WindowId) {
+        self.window = Some(window);
+    }
+
+    pub(crate) fn stop(&mut self) {
+        // Make sure a UI widget does not keep waiting on a composition that will never be finished
+        if self.composing {
+            self.composing = false;
+            self.listeners.notify(&TextInputEvent::CompositionEnded);
+        }
+        self.window = None;
+    }
+
+    pub(crate) fn register(&mut self, listener: DynEventListenerRef<TextInputEvent>) {
+        self.listeners.push(listener);
+    }
+
+    pub(crate) fn unregister(&mut self, listener: &DynEventListenerRef<TextInputEvent>) {
+        self.listeners.remove(listener);
+    }
+}
+
+impl<'a> EventListener<(WindowId, WindowEvent<'a>)> for TextInputListener {
+    fn notify(&mut self, event: &(WindowId, WindowEvent<'a>)) {
+        let (window_id, event) = event;
+        if self.window != Some(*window_id) {
+            return;
+        }
+
+        let event = match event {
+            WindowEvent::Text(text)                      => TextInputEvent::Text(text.to_string()),
+            WindowEvent::ImeCompositionStarted           => {
+                self.composing = true;
+                TextInputEvent::CompositionStarted
+            },
+            WindowEvent::ImeComposition { text, cursor } => TextInputEvent::Composition { text: text.to_string(), cursor: *cursor },
+            WindowEvent::ImeCompositionEnded             => {
+                self.composing = false;
+                TextInputEvent::CompositionEnded
+            },
+            _ => return,
+        };
+        self.listeners.notify(&event);
+    }
+}
@@ -1,12 +1,26 @@
 use core::mem::take;
-use std::sync::{Weak, Arc};
+use std::{sync::{Weak, Arc}, time::Instant};
 use onca_common::{
     prelude::*,
     sync::Mutex,
     time::DeltaTime
 };
-use crate::{MappingContext, ControlSet, AxisValue, ControlSchemeID, InputProcessContext, Action, TriggerResult, AxisId, Handle, NativeDeviceHandle};
-
+use crate::{MappingContext, ControlSet, AxisValue, ControlSchemeID, InputProcessContext, Action, TriggerResult, AxisId, Handle, NativeDeviceHandle, HapticsMixer, GestureRecognizer, BindingConflict, ActionSnapshot, ActionDebugInfo};
+
+/// Change in the devices assigned to a user.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DeviceAssignmentEvent {
+    /// A device was assigned to the user.
+    Assigned {
+        user_idx: u8,
+        handle:   Handle,
+    },
+    /// A device was unassigned from the user.
+    Unassigned {
+        user_idx: u8,
+        handle:   Handle,
+    },
+}
 
 pub struct User {
     /// Per-user mapping contexts
@@ -23,6 +37,10 @@ pub struct User {
     cur_held_devs       : Vec<Handle>,
     /// Disconnected device that could reconnect to this user
     disconnected_devs   : Vec<NativeDeviceHandle>,
+    /// Haptics mixer for the user's devices
+    haptics             : Mutex<HapticsMixer>,
+    /// Gesture recognizer for the user's touch devices and mice
+    gestures            : Mutex<GestureRecognizer>,
 }
 
 
@@ -35,7 +53,9 @@ impl User {
             prev_trigger_res: Vec::new(),
             disconnected_scheme: ControlSchemeID::default(),
             cur_held_devs: Vec::new(),
-            disconnected_devs: Vec::new()
+            disconnected_devs: Vec::new(),
+            haptics: Mutex::new(HapticsMixer::new()),
+            gestures: Mutex::new(GestureRecognizer::new()),
         }
     }   
 
@@ -51,6 +71,21 @@ impl User {
         self.control_set.as_ref()
     }
 
+    /// Check if a device is assigned to the user.
+    pub fn has_device(&self, handle: Handle) -> bool {
+        self.control_set.as_ref().map_or(false, |control_set| control_set.devices().contains(&handle))
+    }
+
+    /// Get the haptics mixer, which controls the rumble and trigger feedback of all devices in the user's control set.
+    pub fn haptics(&self) -> &Mutex<HapticsMixer> {
+        &self.haptics
+    }
+
+    /// Get the gesture recognizer, which recognizes gestures from all touch devices and mice in the user's control set.
+    pub fn gestures(&self) -> &Mutex<GestureRecognizer> {
+        &self.gestures
+    }
+
     pub(crate) fn notify_scheme_removed(&mut self, identifier: &ControlSchemeID) {
         if let Some(set) = &self.control_set && set.scheme_identifier() == identifier {
             self.control_set = None;
@@ -102,22 +137,53 @@ impl User {
         };
     }
 
+    /// Assign a device to the user, if the user has no control set yet, a control set that is not associated with any control scheme will be created.
+    pub(crate) fn assign_device(&mut self, handle: Handle) {
+        match &mut self.control_set {
+            Some(control_set) => control_set.devices.push(handle),
+            None => self.control_set = Some(ControlSet { scheme: ControlSchemeID::default(), devices: vec![handle] }),
+        }
+        self.haptics.lock().invalidate();
+    }
+
+    /// Unassign a device from the user, returns `false` if the device wasn't assigned to the user.
+    pub(crate) fn unassign_device(&mut self, handle: Handle) -> bool {
+        let Some(control_set) = &mut self.control_set else { return false };
+        let Some(idx) = control_set.devices.iter().position(|val| *val == handle) else { return false };
+
+        control_set.devices.remove(idx);
+        if control_set.devices.is_empty() {
+            self.control_set = None;
+        }
+        true
+    }
+
     pub(crate) fn set_control_set(&mut self, control_set: ControlSet) {
         self.control_set = Some(control_set);
         self.disconnected_devs.clear();
+        self.haptics.lock().invalidate();
     }
 
-    pub(crate) fn process_input<F>(&mut self, dt: DeltaTime, user_idx: u8, get_input: F)
+    pub(crate) fn process_input<F>(&mut self, dt: DeltaTime, user_idx: u8, input_timestamp: Option<Instant>, snapshots: &mut Vec<ActionSnapshot>, debug: Option<&mut Vec<ActionDebugInfo>>, get_input: F)
     where
         F : Fn(&User, &AxisId) -> AxisValue
     {
-        let mut context = InputProcessContext::new();
-        for mapping_ctx in &mut *self.mappings_contexts.lock() {
-            for mapping in &mut mapping_ctx.1.mappings {
-                mapping.process(dt, self, user_idx, &mut context, &get_input);
+        // Gestures are exposed as axes, so they can be bound like any other input
+        let gestures = self.gestures.lock();
+        let get_input = |user: &User, axis: &AxisId| gestures.get_axis_value(axis).unwrap_or_else(|| get_input(user, axis));
+
+        let mut context = InputProcessContext::new(input_timestamp, debug.is_some());
+        for (_, mapping_ctx) in &mut *self.mappings_contexts.lock() {
+            for mapping in &mut mapping_ctx.mappings {
+                mapping.process(dt, self, user_idx, &mapping_ctx.identifier, &mut context, &get_input);
             }
         }
+        drop(gestures);
         self.prev_trigger_res = context.trigger_states;
+        snapshots.append(&mut context.action_snapshots);
+        if let Some(debug) = debug && let Some(mut context_debug) = context.debug {
+            debug.append(&mut context_debug);
+        }
     }
 
     pub(crate) fn get_currently_held_devices(&self) -> &Vec<Handle> {
@@ -134,6 +200,7 @@ impl User {
         self.cur_held_devs.push(handle);
         if self.disconnected_devs.is_empty() {
             self.control_set = Some(ControlSet{ scheme: take(&mut self.disconnected_scheme), devices: take(&mut self.cur_held_devs) });
+            self.haptics.lock().invalidate();
         }
         true
     }
@@ -152,5 +219,20 @@ impl User {
             }
         }
     }
+
+    /// Find all bindings in the user's mapping contexts that are bound to any of the given axes.
+    pub(crate) fn find_binding_conflicts(&self, axes: &[AxisId], conflicts: &mut Vec<BindingConflict>) {
+        for (_, mapping_ctx) in &*self.mappings_contexts.lock() {
+            for mapping in &mapping_ctx.mappings {
+                for binding in mapping.bindings.iter().filter(|binding| axes.contains(&binding.input_axis)) {
+                    conflicts.push(BindingConflict {
+                        context: mapping_ctx.identifier.clone(),
+                        action: mapping.action.lock().name.clone(),
+                        rebind_name: binding.rebind_options.as_ref().map(|options| options.name.clone()),
+                    });
+                }
+            }
+        }
+    }
 }
 
@@ -9,7 +9,7 @@ pub struct SubmitBatch<'a> {
 
 /// Info returned by RAL implementations with resulting values:
 /// - Clamped width, height, and num backbuffers
-/// - Chosen format
+/// - Chosen format and color space
 pub struct SwapChainResultInfo {
     /// Backbuffer handles and rtv handles
     pub backbuffers:       Vec<TextureInterfaceHandle>,
@@ -25,6 +25,8 @@ pub struct SwapChainResultInfo {
     pub backbuffer_usages: TextureUsage,
     /// Present mode
     pub present_mode:      PresentMode,
+    /// Color space
+    pub color_space:       SwapChainColorSpace,
 }
 
 pub struct SwapChainChangeParams {
@@ -35,9 +37,20 @@ pub struct SwapChainChangeParams {
     pub backbuffer_usages: TextureUsage,
     pub present_mode:      PresentMode,
     pub alpha_mode:        SwapChainAlphaMode,
+    pub color_space:       SwapChainColorSpace,
     pub queue:             CommandQueueHandle
 }
 
+/// Present statistics returned by RAL implementations, these are used to fill in `PresentStatistics`
+pub struct PresentStatisticsResultInfo {
+    /// Number of presents that have been displayed
+    pub present_count:         u32,
+    /// Number of vertical blanks that happened before the last present was displayed
+    pub present_refresh_count: u32,
+    /// CPU timestamp at which the last present was displayed, in ticks of the platform's high resolution clock
+    pub present_time:          u64,
+}
+
 pub struct SwapChainResizeResultInfo {
     /// Backbuffer handles and rtv handles
     pub backbuffers:       Vec<TextureInterfaceHandle>,
@@ -0,0 +1,223 @@
+//! Bindless descriptors
+//! 
+//! A bindless table manages a shader visible descriptor heap, where each descriptor is identified by a `ResourceIndex`.
+//! Shaders can then directly index the heap using the index, instead of requiring descriptor tables to be rebound, e.g. to index material textures by ID.
+//! 
+//! To access the heap from a shader, the pipeline layout needs to be created with `PipelineLayoutFlags::Bindless`, and the heap must be bound using `bind_descriptor_heaps`
+use onca_common::sync::Mutex;
+
+use crate::*;
+
+/// Index of a descriptor in a bindless table
+/// 
+/// The index can be passed to shaders, e.g. via constants or a buffer, and is used to index the descriptor heap:
+/// - DX12: `ResourceDescriptorHeap[index]` or `SamplerDescriptorHeap[index]`
+/// - Vulkan: the unbounded descriptor array in the bindless descriptor set of the pipeline layout
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ResourceIndex(u32);
+
+impl ResourceIndex {
+    /// Get the index in the descriptor heap
+    pub fn index(self) -> u32 {
+        self.0
+    }
+}
+
+#[cfg(feature = "validation")]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum BindlessSlot {
+    Free,
+    Sampler,
+    Resource(DescriptorType),
+}
+
+struct BindlessAllocator {
+    free:  Vec<u32>,
+    next:  u32,
+    #[cfg(feature = "validation")]
+    slots: Vec<BindlessSlot>,
+}
+
+/// Bindless descriptor table
+pub struct BindlessTable {
+    heap:      DescriptorHeapHandle,
+    allocator: Mutex<BindlessAllocator>,
+}
+
+impl BindlessTable {
+    /// Create a new bindless table
+    /// 
+    /// ## Validation
+    /// 
+    /// A validation error will occur when `max_descriptors` exceeds `constants::MAX_BINDLESS_RESOURCE_DESCRIPTORS` or `constants::MAX_BINDLESS_SAMPLER_DESCRIPTORS`, depending on the heap type
+    pub fn new(device: &DeviceHandle, heap_type: DescriptorHeapType, max_descriptors: u32) -> Result<Self> {
+        #[cfg(feature = "validation")]
+        {
+            let max = match heap_type {
+                DescriptorHeapType::Resources => constants::MAX_BINDLESS_RESOURCE_DESCRIPTORS,
+                DescriptorHeapType::Samplers  => constants::MAX_BINDLESS_SAMPLER_DESCRIPTORS,
+            };
+            if max_descriptors > max {
+                return Err(Error::InvalidParameter(format!("Bindless {heap_type} table cannot contain more than {max} descriptors, requested {max_descriptors}")));
+            }
+        }
+
+        let heap = device.create_descriptor_heap(&DescriptorHeapDesc {
+            heap_type,
+            max_descriptors,
+            shader_visible: true,
+        })?;
+
+        Ok(Self {
+            heap,
+            allocator: Mutex::new(BindlessAllocator {
+                free: Vec::new(),
+                next: 0,
+                #[cfg(feature = "validation")]
+                slots: vec![BindlessSlot::Free; max_descriptors as usize],
+            }),
+        })
+    }
+
+    /// Get the descriptor heap used by the table
+    pub fn heap(&self) -> &DescriptorHeapHandle {
+        &self.heap
+    }
+
+    /// Get the number of descriptors currently in the table
+    pub fn len(&self) -> u32 {
+        let allocator = self.allocator.lock();
+        allocator.next - allocator.free.len() as u32
+    }
+
+    /// Check if the table does not contain any descriptors
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add a sampler to the table
+    pub fn add_sampler(&self, sampler: &SamplerHandle) -> Result<ResourceIndex> {
+        self.add(DescriptorHeapType::Samplers, None, |index| self.heap.write_sampler(index, sampler))
+    }
+
+    /// Add a sampled texture to the table
+    pub fn add_sampled_texture(&self, view: &SampledTextureViewHandle) -> Result<ResourceIndex> {
+        self.add(DescriptorHeapType::Resources, Some(DescriptorType::SampledTexture), |index| self.heap.write_sampled_texture(index, view))
+    }
+
+    /// Add a storage texture to the table
+    pub fn add_storage_texture(&self, view: &StorageTextureViewHandle) -> Result<ResourceIndex> {
+        self.add(DescriptorHeapType::Resources, Some(DescriptorType::StorageTexture), |index| self.heap.write_storage_texture(index, view))
+    }
+
+    /// Add a constant buffer to the table
+    pub fn add_constant_buffer(&self, buffer: &BufferHandle, range: BufferRange) -> Result<ResourceIndex> {
+        self.add(DescriptorHeapType::Resources, Some(DescriptorType::ConstantBuffer), |index| self.heap.write_constant_buffer(index, buffer, range))
+    }
+
+    /// Add a readonly structured buffer to the table
+    pub fn add_ro_structured_buffer(&self, buffer: &BufferHandle, desc: StructuredBufferViewDesc) -> Result<ResourceIndex> {
+        self.add(DescriptorHeapType::Resources, Some(DescriptorType::StorageBuffer), |index| self.heap.write_ro_structured_buffer(index, buffer, desc))
+    }
+
+    /// Add a read/write structured buffer to the table
+    pub fn add_rw_structured_buffer(&self, buffer: &BufferHandle, desc: StructuredBufferViewDesc) -> Result<ResourceIndex> {
+        self.add(DescriptorHeapType::Resources, Some(DescriptorType::StorageBuffer), |index| self.heap.write_rw_structured_buffer(index, buffer, desc))
+    }
+
+    /// Add a readonly raw buffer to the table
+    pub fn add_ro_raw_buffer(&self, buffer: &BufferHandle, range: BufferRange) -> Result<ResourceIndex> {
+        self.add(DescriptorHeapType::Resources, Some(DescriptorType::StorageBuffer), |index| self.heap.write_ro_raw_buffer(index, buffer, range))
+    }
+
+    /// Add a read/write raw buffer to the table
+    pub fn add_rw_raw_buffer(&self, buffer: &BufferHandle, range: BufferRange) -> Result<ResourceIndex> {
+        self.add(DescriptorHeapType::Resources, Some(DescriptorType::StorageBuffer), |index| self.heap.write_rw_raw_buffer(index, buffer, range))
+    }
+
+    /// Add a readonly texel buffer to the table
+    pub fn add_ro_texel_buffer(&self, buffer: &BufferHandle, desc: TexelBufferViewDesc) -> Result<ResourceIndex> {
+        self.add(DescriptorHeapType::Resources, Some(DescriptorType::ConstantTexelBuffer), |index| self.heap.write_ro_texel_buffer(index, buffer, desc))
+    }
+
+    /// Add a read/write texel buffer to the table
+    pub fn add_rw_texel_buffer(&self, buffer: &BufferHandle, desc: TexelBufferViewDesc) -> Result<ResourceIndex> {
+        self.add(DescriptorHeapType::Resources, Some(DescriptorType::StorageTexelBuffer), |index| self.heap.write_rw_texel_buffer(index, buffer, desc))
+    }
+
+    /// Remove a descriptor from the table
+    /// 
+    /// The user needs to make sure that the GPU has finished using the descriptor before removing it, as the index may be reused by the next added descriptor
+    /// 
+    /// ## Validation
+    /// 
+    /// A validation error will occur when the index is not in use
+    pub fn remove(&self, index: ResourceIndex) -> Result<()> {
+        let mut allocator = self.allocator.lock();
+
+        #[cfg(feature = "validation")]
+        {
+            match allocator.slots.get_mut(index.0 as usize) {
+                Some(slot) if *slot != BindlessSlot::Free => *slot = BindlessSlot::Free,
+                _ => return Err(Error::InvalidParameter(format!("Cannot remove bindless index {}, as it is not in use", index.0))),
+            }
+        }
+
+        allocator.free.push(index.0);
+        Ok(())
+    }
+
+    /// Validate that an index refers to a descriptor of the given type
+    /// 
+    /// For sampler tables, `descriptor_type` is ignored
+    pub fn validate_index(&self, index: ResourceIndex, descriptor_type: DescriptorType) -> Result<()> {
+        #[cfg(feature = "validation")]
+        {
+            let allocator = self.allocator.lock();
+            match allocator.slots.get(index.0 as usize) {
+                None => return Err(Error::DescriptorOutOfRange { index: index.0, max: self.heap.max_descriptors() }),
+                Some(BindlessSlot::Free) => return Err(Error::InvalidParameter(format!("Bindless index {} is not in use", index.0))),
+                Some(BindlessSlot::Sampler) => {},
+                Some(BindlessSlot::Resource(slot_type)) => if *slot_type != descriptor_type {
+                    return Err(Error::InvalidParameter(format!("Bindless index {} contains a {slot_type:?} descriptor, expected a {descriptor_type:?} descriptor", index.0)));
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn add<F>(&self, heap_type: DescriptorHeapType, _descriptor_type: Option<DescriptorType>, write: F) -> Result<ResourceIndex> where
+        F: FnOnce(u32) -> Result<()>
+    {
+        if self.heap.heap_type() != heap_type {
+            return Err(Error::InvalidParameter(format!("Cannot add a descriptor for a {heap_type} heap to a bindless {} table", self.heap.heap_type())));
+        }
+
+        let mut allocator = self.allocator.lock();
+        let index = match allocator.free.pop() {
+            Some(index) => index,
+            None => {
+                if allocator.next >= self.heap.max_descriptors() {
+                    return Err(Error::DescriptorOutOfRange { index: allocator.next, max: self.heap.max_descriptors() });
+                }
+                allocator.next += 1;
+                allocator.next - 1
+            },
+        };
+
+        if let Err(err) = write(index) {
+            allocator.free.push(index);
+            return Err(err);
+        }
+
+        #[cfg(feature = "validation")]
+        {
+            allocator.slots[index as usize] = match _descriptor_type {
+                Some(descriptor_type) => BindlessSlot::Resource(descriptor_type),
+                None => BindlessSlot::Sampler,
+            };
+        }
+
+        Ok(ResourceIndex(index))
+    }
+}
@@ -1,6 +1,6 @@
 use core::{mem::ManuallyDrop, num::NonZeroU64};
 
-use onca_common::{prelude::*, sync::Mutex};
+use onca_common::{prelude::*, sync::{Mutex, RwLock}};
 use onca_common_macros::flags;
 use onca_logging::log_error;
 
@@ -30,6 +30,12 @@ pub enum BufferUsage {
     IndirectBuffer,
     /// Buffer can be used as a conditional rendering buffer
     ConditionalRendering,
+    /// Buffer can be used as storage for acceleration structures
+    AccelerationStructure,
+    /// Buffer can be used as input when building an acceleration structure (vertices, indices, transforms, AABBs, or instances)
+    AccelerationStructureInput,
+    /// Buffer can be used as a shader binding table
+    ShaderBindingTable,
 }
 
 /// Buffer description
@@ -53,6 +59,9 @@ pub trait BufferInterface {
 
     /// Unmap mapped memory
     unsafe fn unmap(&self, allocation: &GpuAllocation, memory: MappedMemory);
+
+    /// Set the debug name of the buffer
+    unsafe fn set_debug_name(&self, name: &str);
 }
 
 pub type BufferInterfaceHandle = InterfaceHandle<dyn BufferInterface>;
@@ -78,8 +87,9 @@ pub struct Buffer {
     address:    GpuAddress,
     desc:       BufferDesc,
     validation: Mutex<ValidationData>,
+    debug_name: RwLock<String>,
 }
-create_ral_handle!(BufferHandle, Buffer, BufferInterfaceHandle);
+create_ral_handle!(BufferHandle, Buffer, BufferInterfaceHandle, debug_name);
 
 impl BufferHandle {
     pub(crate) fn create(device: &DeviceHandle, handle: BufferInterfaceHandle, allocation: GpuAllocation, address: GpuAddress, desc: BufferDesc) -> Self {
@@ -90,6 +100,7 @@ impl BufferHandle {
             address,
             desc,
             validation: Mutex::new(ValidationData::new()),
+            debug_name: RwLock::new(String::new()),
         })
     }
     
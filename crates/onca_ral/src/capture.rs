@@ -0,0 +1,74 @@
+use core::{ffi::c_void, ptr::null_mut};
+
+use onca_common::dynlib::DynLib;
+
+#[cfg(windows)]
+const RENDERDOC_LIBRARY: &str = "renderdoc.dll";
+#[cfg(not(windows))]
+const RENDERDOC_LIBRARY: &str = "librenderdoc.so";
+
+/// RenderDoc API version 1.1.2
+const RENDERDOC_API_VERSION: u32 = 10102;
+
+type FnGetApi = unsafe extern "C" fn(version: u32, out_api: *mut *mut c_void) -> i32;
+type FnTriggerCapture = unsafe extern "C" fn();
+type FnStartFrameCapture = unsafe extern "C" fn(device: *mut c_void, window: *mut c_void);
+type FnIsFrameCapturing = unsafe extern "C" fn() -> u32;
+type FnEndFrameCapture = unsafe extern "C" fn(device: *mut c_void, window: *mut c_void) -> u32;
+
+/// Function table of the RenderDoc 1.1.2 API, only containing the functions used by the RAL
+#[repr(C)]
+struct RenderDocApiTable {
+    _unused0:                     [*const c_void; 15],
+    trigger_capture:              FnTriggerCapture,
+    _unused1:                     [*const c_void; 3],
+    start_frame_capture:          FnStartFrameCapture,
+    is_frame_capturing:           FnIsFrameCapturing,
+    end_frame_capture:            FnEndFrameCapture,
+}
+
+/// RenderDoc in-application API
+/// 
+/// The API is only available when RenderDoc was injected into the process before the RAL was created
+pub(crate) struct RenderDoc {
+    api:     *const RenderDocApiTable,
+    _dynlib: DynLib,
+}
+
+impl RenderDoc {
+    /// Get the RenderDoc API if RenderDoc is injected into the process
+    pub(crate) fn load() -> Option<Self> {
+        let dynlib = DynLib::get_loaded(RENDERDOC_LIBRARY)?;
+        let get_api = dynlib.get::<FnGetApi>("RENDERDOC_GetAPI")?;
+
+        let mut api = null_mut();
+        if unsafe { get_api(RENDERDOC_API_VERSION, &mut api) } != 1 || api.is_null() {
+            return None;
+        }
+        Some(Self { api: api as *const RenderDocApiTable, _dynlib: dynlib })
+    }
+
+    /// Capture the next presented frame
+    pub(crate) fn trigger_capture(&self) {
+        unsafe { ((*self.api).trigger_capture)() }
+    }
+
+    /// Start a capture, this will capture all devices and windows
+    pub(crate) fn start_frame_capture(&self) {
+        unsafe { ((*self.api).start_frame_capture)(null_mut(), null_mut()) }
+    }
+
+    /// Check if a capture is currently in progress
+    pub(crate) fn is_frame_capturing(&self) -> bool {
+        unsafe { ((*self.api).is_frame_capturing)() != 0 }
+    }
+
+    /// End the current capture, returns `false` if the capture failed
+    pub(crate) fn end_frame_capture(&self) -> bool {
+        unsafe { ((*self.api).end_frame_capture)(null_mut(), null_mut()) != 0 }
+    }
+}
+
+// The API table is static for the lifetime of the library and RenderDoc's API is thread-safe
+unsafe impl Send for RenderDoc {}
+unsafe impl Sync for RenderDoc {}
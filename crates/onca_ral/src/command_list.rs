@@ -1,58 +1,65 @@
 //! Not all comnmand lists support all features, below is a table of supported features per command list type:
 //! 
-//!  Method                       | Graphics | Compute | Copy | Bundle | In renderpass
-//! ------------------------------|----------|---------|------|--------|---------------
-//! begin_conditional_rendering   | X        |         |      |        | X
-//! begin_query                   | X        |         |      |        | X
-//! begin_render_pass             | X        |         |      |        |  
-//! bind_compute_pipeline_layout  | X        | X       |      | X      | X
-//! bind_compute_pipeline         | X        | X       |      | X      | X
-//! bind_graphics_pipeline_layout | X        |         |      | X      | X
-//! bind_graphics_pipeline        | X        |         |      | X      | X
-//! build_acceleration_structure  | X        | X       |      |        |  
-//! clear_attachments             | X        |         |      |        |  
-//! clear_depth_stencil           | X        |         |      |        |  
-//! clear_pipeline                | X        | X       |      |        |  
-//! clear_render_target           | X        |         |      |        |  
-//! clear_texture                 | X        |         |      |        | X
-//! copy_acceleration_structure   | X        | X       |      |        |  
-//! copy_buffer                   | X        | X       | X    |        |  
-//! copy_buffer_to_texture        | X        | X       | X    |        |  
-//! copy_texture                  | X        | X       | X    |        |  
-//! copy_texture_to_buffer        | X        | X       | X    |        |  
-//! draw                          | X        |         |      | X      | X
-//! draw_indexed                  | X        |         |      | X      | X
-//! draw_indexed_indirect         | X        |         |      | X      | X
-//! draw_indirect                 | X        |         |      | X      | X
-//! draw_mesh_tasks               | X        |         |      | X      | X
-//! draw_mesh_tasks_indirect      | X        |         |      | X      | X
-//! dispatch                      | X        | X       |      | X      |  
-//! dispatch_indirect             | X        | X       |      | X      |  
-//! end_conditional_rendering     | X        |         |      |        | X
-//! end_query                     | X        |         |      |        | X
-//! end_render_pass               | X        |         |      |        |  
-//! execute_bundle                | X        |         |      |        | X
-//! multi_draw                    | X        |         |      | X      | X
-//! mutli_draw_indexed            | X        |         |      | X      | X
-//! query_acceleration_structure  | X        | X       |      |        |  
-//! reset_query_ppol              | X        | X       | X    |        |  
-//! resolve_query                 | X        | X       | X    |        |  
-//! resolve_texture               | X        |         |      |        |  
-//! set_blend_factor              | X        |         |      | X      | X
-//! set_index_buffer              | X        |         |      | X      | X
-//! set_depth_bounds              | X        |         |      | X      | X
-//! set_primitive_topology        | X        |         |      | X      | X
-//! set_sample_locations          | X        |         |      | X      | X
-//! set_shading_rate              | X        |         |      | X      | X
-//! set_shading_rate_image        | X        |         |      | X      | X
-//! set_scissor_rects             | X        |         |      | X      | X
-//! set_stencil_ref               | X        |         |      | X      | X
-//! set_vertex_buffer             | X        |         |      | X      | X
-//! set_viewports                 | X        |         |      |        | X
-//! trace_rays                    | X        |         |      |        | X
-//! update_acceleration_structure | X        | X       |      |        |  
-//! write_timestamp               | X        | X       | X    |        | X
-//! write_buffer                  | X        | X       | X    | X      | X
+//!  Method                                     | Graphics | Compute | Copy | Bundle | In renderpass
+//! --------------------------------------------|----------|---------|------|--------|---------------
+//! begin_predication                           | X        |         |      |        | X
+//! begin_query                                 | X        |         |      |        | X
+//! begin_render_pass                           | X        |         |      |        |  
+//! bind_compute_pipeline_layout                | X        | X       |      | X      | X
+//! bind_compute_pipeline                       | X        | X       |      | X      | X
+//! bind_graphics_pipeline_layout               | X        |         |      | X      | X
+//! bind_graphics_pipeline                      | X        |         |      | X      | X
+//! bind_raytracing_pipeline                    | X        | X       |      |        |  
+//! build_acceleration_structure                | X        | X       |      |        |  
+//! clear_attachments                           | X        |         |      |        |  
+//! clear_depth_stencil                         | X        |         |      |        |  
+//! clear_pipeline                              | X        | X       |      |        |  
+//! clear_render_target                         | X        |         |      |        |  
+//! clear_texture                               | X        |         |      |        | X
+//! copy_acceleration_structure                 | X        | X       |      |        |  
+//! copy_buffer                                 | X        | X       | X    |        |  
+//! copy_buffer_to_texture                      | X        | X       | X    |        |  
+//! copy_texture                                | X        | X       | X    |        |  
+//! copy_texture_to_buffer                      | X        | X       | X    |        |  
+//! draw                                        | X        |         |      | X      | X
+//! draw_indexed                                | X        |         |      | X      | X
+//! draw_indexed_indirect                       | X        |         |      | X      | X
+//! draw_indirect                               | X        |         |      | X      | X
+//! dispatch                                    | X        | X       |      | X      |  
+//! dispatch_indirect                           | X        | X       |      | X      |  
+//! dispatch_mesh                               | X        |         |      | X      | X
+//! dispatch_mesh_indirect                      | X        |         |      | X      | X
+//! end_predication                             | X        |         |      |        | X
+//! end_query                                   | X        |         |      |        | X
+//! end_render_pass                             | X        |         |      |        |  
+//! execute_bundle                              | X        |         |      |        | X
+//! multi_draw                                  | X        |         |      | X      | X
+//! mutli_draw_indexed                          | X        |         |      | X      | X
+//! multi_draw_indexed_indirect                 | X        |         |      | X      | X
+//! multi_draw_indexed_indirect_count           | X        |         |      | X      | X
+//! multi_draw_indirect                         | X        |         |      | X      | X
+//! multi_draw_indirect_count                   | X        |         |      | X      | X
+//! reset_query_ppol                            | X        | X       | X    |        |  
+//! resolve_query                               | X        | X       | X    |        |  
+//! resolve_texture                             | X        |         |      |        |  
+//! set_blend_factor                            | X        |         |      | X      | X
+//! set_compute_constants                       | X        | X       |      | X      | X
+//! set_index_buffer                            | X        |         |      | X      | X
+//! set_depth_bounds                            | X        |         |      | X      | X
+//! set_graphics_constants                      | X        |         |      | X      | X
+//! set_primitive_topology                      | X        |         |      | X      | X
+//! set_sample_locations                        | X        |         |      | X      | X
+//! set_shading_rate                            | X        |         |      | X      | X
+//! set_shading_rate_image                      | X        |         |      | X      | X
+//! set_scissor_rects                           | X        |         |      | X      | X
+//! set_stencil_ref                             | X        |         |      | X      | X
+//! set_vertex_buffer                           | X        |         |      | X      | X
+//! set_viewports                               | X        |         |      |        | X
+//! trace_rays                                  | X        | X       |      |        |  
+//! update_acceleration_structure               | X        | X       |      |        |  
+//! write_acceleration_structure_compacted_size | X        | X       |      |        |  
+//! write_timestamp                             | X        | X       | X    |        | X
+//! write_buffer                                | X        | X       | X    | X      | X
 //! 
 //! The above table is currently incomplete while part of the API are still being figured out
 
@@ -63,8 +70,12 @@ use onca_common::{
     sync::{RwLock, Mutex}
 };
 use onca_common_macros::flags;
+#[cfg(feature = "validation")]
+use onca_logging::log_error;
 
 use crate::{*, handle::InterfaceHandle};
+#[cfg(feature = "validation")]
+use crate::validation::{ResourceTracker, display_name};
 
 //==============================================================================================================================
 // COMMAND POOL
@@ -75,6 +86,9 @@ pub trait CommandPoolInterface {
     unsafe fn reset(&self) -> Result<()>;
     unsafe fn allocate(&self, list_type: CommandListType) -> Result<CommandListInterfaceHandle>;
     unsafe fn free(&self, list: &CommandListInterfaceHandle);
+
+    /// Set the debug name of the command pool
+    unsafe fn set_debug_name(&self, name: &str);
 }
 
 pub type CommandPoolInterfaceHandle = InterfaceHandle<dyn CommandPoolInterface>;
@@ -128,7 +142,8 @@ impl CommandPoolHandle {
             queue_idx: self.queue_idx,
             pool: Handle::downgrade(self),
             dynamic: RwLock::new(CommandListDynamic::new()),
-            validation: Mutex::new(CommandListValidation::new())
+            validation: Mutex::new(CommandListValidation::new()),
+            debug_name: RwLock::new(String::new()),
         }))
     }
 
@@ -144,6 +159,11 @@ impl CommandPoolHandle {
     fn unmark_command_list_recording(&self) {
         self.is_recording.store(false, atomic::Ordering::Release);
     }
+
+    /// Set the debug name, which will show up in graphics debuggers and validation messages
+    pub fn set_debug_name(&self, name: &str) {
+        unsafe { self.handle.set_debug_name(name) }
+    }
 }
 
 impl HandleImpl for CommandPool {
@@ -188,6 +208,11 @@ impl GraphicsCommandPool {
     pub fn allocate(&self) -> Result<GraphicsCommandListHandle> {
         Ok(GraphicsCommandList { handle: self.handle.allocate()? })
     }
+
+    /// Set the debug name, which will show up in graphics debuggers and validation messages
+    pub fn set_debug_name(&self, name: &str) {
+        self.handle.set_debug_name(name);
+    }
 }
 
 //==============================================================
@@ -223,6 +248,11 @@ impl ComputeCommandPool {
     pub fn allocate(&self) -> Result<ComputeCommandListHandle> {
         Ok(ComputeCommandList { handle: self.handle.allocate()? })
     }
+
+    /// Set the debug name, which will show up in graphics debuggers and validation messages
+    pub fn set_debug_name(&self, name: &str) {
+        self.handle.set_debug_name(name);
+    }
 }
 
 //==============================================================
@@ -258,6 +288,11 @@ impl CopyCommandPool {
     pub fn allocate(&self) -> Result<CopyCommandListHandle> {
         Ok(CopyCommandList{ handle: self.handle.allocate()? })
     }
+
+    /// Set the debug name, which will show up in graphics debuggers and validation messages
+    pub fn set_debug_name(&self, name: &str) {
+        self.handle.set_debug_name(name);
+    }
 }
 
 //==============================================================
@@ -293,6 +328,11 @@ impl BundleCommandPool {
     pub fn allocate(&self) -> Result<BundleCommandListHandle> {
         Ok(BundleCommandList{ handle: self.handle.allocate()? })
     }
+
+    /// Set the debug name, which will show up in graphics debuggers and validation messages
+    pub fn set_debug_name(&self, name: &str) {
+        self.handle.set_debug_name(name);
+    }
 }
 
 //==============================================================================================================================
@@ -312,6 +352,16 @@ pub trait CommandListInterface {
     /// Closes the command list
     unsafe fn close(&self) -> Result<()>; 
 
+    //==============================================================
+    // Debug functionality
+
+    /// Begin a named debug event, events can be nested
+    unsafe fn begin_event(&self, name: &str, color: [f32; 4]);
+    /// End the last debug event
+    unsafe fn end_event(&self);
+    /// Insert a named debug marker
+    unsafe fn set_marker(&self, name: &str, color: [f32; 4]);
+
     //==============================================================
     // Copy functionality
     
@@ -347,6 +397,27 @@ pub trait CommandListInterface {
 
     /// Bind the first entry in the descriptor table at 'index' in the current bound pipeline
     unsafe fn set_compute_descriptor_table(&self, index: u32, descriptor: GpuDescriptor, layout: &PipelineLayoutHandle);
+    /// Set 32-bit constants in the constant range at 'index' in the current bound pipeline, starting at `offset` (in 32-bit values)
+    unsafe fn set_compute_constants(&self, index: u32, offset: u32, data: &[u32], layout: &PipelineLayoutHandle);
+
+    /// Dispatch compute workgroups
+    unsafe fn dispatch(&self, group_count_x: u32, group_count_y: u32, group_count_z: u32);
+    /// Dispatch compute workgroups, with the workgroup counts read from `buffer` at `offset`
+    unsafe fn dispatch_indirect(&self, buffer: &BufferHandle, offset: u64);
+
+    //==============================================================
+    // Raytracing functionality
+
+    /// Build an acceleration structure, when `src` is set, `src` will be updated into the destination of the build info
+    unsafe fn build_acceleration_structure(&self, info: &AccelerationStructureBuildInfo, src: Option<&AccelerationStructureHandle>);
+    /// Copy an acceleration structure
+    unsafe fn copy_acceleration_structure(&self, dst: &AccelerationStructureHandle, src: &AccelerationStructureHandle, mode: AccelerationStructureCopyMode);
+    /// Write the compacted size of an acceleration structure as a `u64` to `dst` at `dst_offset`
+    unsafe fn write_acceleration_structure_compacted_size(&self, src: &AccelerationStructureHandle, dst: &BufferHandle, dst_offset: u64);
+    /// Bind a raytracing pipeline
+    unsafe fn bind_raytracing_pipeline(&self, pipeline: &PipelineHandle);
+    /// Trace rays
+    unsafe fn trace_rays(&self, regions: &ShaderBindingTableRegions, width: u32, height: u32, depth: u32);
 
     //==============================================================
     // Graphics functionality
@@ -358,6 +429,8 @@ pub trait CommandListInterface {
 
     /// Bind the first entry in the descriptor table at 'index' in the current bound pipeline
     unsafe fn set_graphics_descriptor_table(&self, index: u32, descriptor: GpuDescriptor, layout: &PipelineLayoutHandle);
+    /// Set 32-bit constants in the constant range at 'index' in the current bound pipeline, starting at `offset` (in 32-bit values)
+    unsafe fn set_graphics_constants(&self, index: u32, offset: u32, data: &[u32], layout: &PipelineLayoutHandle);
 
     /// Bind a vertex buffer
     unsafe fn bind_vertex_buffer(&self, view: VertexBufferView);
@@ -368,17 +441,55 @@ pub trait CommandListInterface {
     unsafe fn begin_rendering(&self, rendering_info: &RenderingInfo);
     /// Ends rendering and manually resolves RTs and/or depth/stencil if needed
     unsafe fn end_rendering(&self);
+    /// Resolve a subresource of a multisampled texture into a subresource of a single sampled texture
+    unsafe fn resolve_texture(&self, src: &TextureHandle, src_subresource: TextureSubresourceIndex, dst: &TextureHandle, dst_subresource: TextureSubresourceIndex);
     /// Set the viewport(s)
     unsafe fn set_viewports(&self, viewports: &[Viewport]);
     /// Set the scissor(s)
     unsafe fn set_scissors(&self, scissors: &[ScissorRect]);
     /// Set the primitive topology
     unsafe fn set_primitive_topology(&self, topology: PrimitiveTopology);
+    /// Set the per-draw shading rate, and the combiners used to combine it with the per-primitive and attachment shading rates
+    unsafe fn set_shading_rate(&self, rate: ShadingRate, combiners: [ShadingRateCombiner; 2]);
 
     /// Draw without indices
     unsafe fn draw_instanced(&self, vertex_count: u32, instance_count: u32, start_vertex: u32, start_instance: u32);  
     /// Draw with indices
     unsafe fn draw_indexed_instanced(&self, index_count: u32, instance_count: u32, start_index: u32, vertex_offset: i32, start_instance: u32);
+    /// Draw without indices, with `draw_count` draws read from `buffer` at `offset`
+    unsafe fn draw_indirect(&self, buffer: &BufferHandle, offset: u64, draw_count: u32);
+    /// Draw with indices, with `draw_count` draws read from `buffer` at `offset`
+    unsafe fn draw_indexed_indirect(&self, buffer: &BufferHandle, offset: u64, draw_count: u32);
+    /// Draw without indices, with draws read from `buffer` at `offset` and the number of draws read from `count_buffer` at `count_offset`
+    unsafe fn draw_indirect_count(&self, buffer: &BufferHandle, offset: u64, count_buffer: &BufferHandle, count_offset: u64, max_draw_count: u32);
+    /// Draw with indices, with draws read from `buffer` at `offset` and the number of draws read from `count_buffer` at `count_offset`
+    unsafe fn draw_indexed_indirect_count(&self, buffer: &BufferHandle, offset: u64, count_buffer: &BufferHandle, count_offset: u64, max_draw_count: u32);
+
+    /// Dispatch mesh (or task) shader workgroups
+    unsafe fn dispatch_mesh(&self, group_count_x: u32, group_count_y: u32, group_count_z: u32);
+    /// Dispatch mesh (or task) shader workgroups, with the workgroup counts read from `buffer` at `offset`
+    unsafe fn dispatch_mesh_indirect(&self, buffer: &BufferHandle, offset: u64);
+
+    //==============================================================
+    // Query functionality
+
+    /// Write a GPU timestamp to the query at `index` in the query pool
+    unsafe fn write_timestamp(&self, query_pool: &QueryPoolHandle, index: u32);
+    /// Begin the query at `index` in the query pool
+    unsafe fn begin_query(&self, query_pool: &QueryPoolHandle, index: u32);
+    /// End the query at `index` in the query pool
+    unsafe fn end_query(&self, query_pool: &QueryPoolHandle, index: u32);
+    /// Reset `count` queries, starting at `first_query`
+    unsafe fn reset_queries(&self, query_pool: &QueryPoolHandle, first_query: u32, count: u32);
+    /// Resolve `count` queries, starting at `first_query`, into `dst` at `dst_offset`
+    unsafe fn resolve_queries(&self, query_pool: &QueryPoolHandle, first_query: u32, count: u32, dst: &BufferHandle, dst_offset: u64);
+    /// Begin predication, using the predicate in `buffer` at `offset`
+    unsafe fn begin_predication(&self, buffer: &BufferHandle, offset: u64, op: PredicationOp);
+    /// End predication
+    unsafe fn end_predication(&self);
+
+    /// Set the debug name of the command list
+    unsafe fn set_debug_name(&self, name: &str);
 }
 
 pub type CommandListInterfaceHandle = InterfaceHandle<dyn CommandListInterface>;
@@ -407,6 +518,8 @@ pub(crate) enum CommandListValidationFlags {
     Rendering,
     /// A compute pipeline/layout is bound, if this flag is not set, a graphics pipeline is assumed
     ComputePipeline,
+    /// Are we between `begin_predication` and `end_predication` calls
+    Predication,
 
     // BUNDLE FLAGS
     /// The bundle is relying on the calling command list to have its pipeline layout set
@@ -456,6 +569,10 @@ pub struct CommandListValidation {
     pub(crate) flags:          CommandListValidationFlags,
                error:          Option<Error>,
                pipeline_state: CommandListPipelineStateFlags,
+               event_depth:    u32,
+    /// Resource tracking of the RAL validation layer
+    #[cfg(feature = "validation")]
+    pub(crate) tracker:        ResourceTracker,
 }
 
 impl CommandListValidation {
@@ -466,6 +583,9 @@ impl CommandListValidation {
             flags: CommandListValidationFlags::None,
             error: None,
             pipeline_state: CommandListPipelineStateFlags::None,
+            event_depth: 0,
+            #[cfg(feature = "validation")]
+            tracker: ResourceTracker::new(),
         }
     }
 
@@ -484,6 +604,7 @@ pub struct CommandList {
     pub(crate) dynamic:   RwLock<CommandListDynamic>,
     #[cfg(feature = "validation")]
     pub(crate) validation: Mutex<CommandListValidation>,
+               debug_name: RwLock<String>,
 }
 type CommandListHandle = Handle<CommandList>;
 
@@ -505,6 +626,15 @@ macro_rules! validate_during_recording {
     };
 }
 
+/// Report an issue found by the RAL validation layer, these don't put the command list in an error state, as resource states can only be partially tracked
+macro_rules! track_resource {
+    ($list:expr, $call:literal, $tracked:expr) => {
+        if let Err(msg) = $tracked {
+            $list.report_validation_issue($call, &msg);
+        }
+    };
+}
+
 // TODO: add threading guard
 impl CommandListHandle {
     /// Reset the command list
@@ -539,6 +669,7 @@ impl CommandListHandle {
             unsafe { self.handle.begin(flags)? };
             validation.state = CommandListState::Recording;
             validation.begin_flags = flags;
+            validation.tracker = ResourceTracker::new();
             Ok(())
         }
         #[cfg(not(feature = "validation"))]
@@ -564,6 +695,7 @@ impl CommandListHandle {
             let mut validation = self.validation.lock();
             validation.state = CommandListState::Recording;
             validation.begin_flags = flags;
+            validation.tracker = ResourceTracker::new();
         }
         unsafe { self.handle.reset_and_begin(flags)?; }
         *self.dynamic.write() = CommandListDynamic::new();
@@ -590,6 +722,12 @@ impl CommandListHandle {
             if validation.state != CommandListState::Recording {
                 return Err(Error::CommandList("Can only close a command list that is recording"));
             }
+            if validation.event_depth != 0 {
+                return Err(Error::CommandList("Cannot close a command list with debug events that have not been ended"));
+            }
+            if validation.flags.contains(CommandListValidationFlags::Predication) {
+                return Err(Error::CommandList("Cannot close a command list while predication is active"));
+            }
 
             unsafe { self.handle.close() }?;
             validation.state = CommandListState::Closed;
@@ -607,14 +745,68 @@ impl CommandListHandle {
 
     //==============================================================================================================================
 
+    /// Begin a named debug event, which will show up in graphics debuggers, events can be nested
+    fn begin_event(&self, name: &str, color: [f32; 4]) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+            validation.event_depth += 1;
+        }
+        unsafe { self.handle.begin_event(name, color) };
+    }
+
+    /// End the last debug event
+    fn end_event(&self) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+            validate_parameter_recording!(validation, validation.event_depth > 0, "Trying to end a debug event, but no debug event has been begun");
+            validation.event_depth -= 1;
+        }
+        unsafe { self.handle.end_event() };
+    }
+
+    /// Insert a named debug marker, which will show up in graphics debuggers
+    fn set_marker(&self, name: &str, color: [f32; 4]) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            if self.validation.lock().state == CommandListState::Error {
+                return;
+            }
+        }
+        unsafe { self.handle.set_marker(name, color) };
+    }
+
+    /// Set the debug name, which will show up in graphics debuggers and validation messages
+    fn set_debug_name(&self, name: &str) {
+        *self.debug_name.write() = name.to_string();
+        unsafe { self.handle.set_debug_name(name) }
+    }
+
+    //==============================================================================================================================
+
     /// Insert barriers into the command list
     fn barrier(&self, barriers: &[Barrier]) {
         #[cfg(feature = "validation")]
         {
             self.check_recording();
-            if self.validation.lock().state == CommandListState::Error {
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
                 return;
             }
+
+            for barrier in barriers {
+                track_resource!(self, "barrier", validation.tracker.barrier(barrier));
+            }
         }
         unsafe { self.handle.barrier(barriers, self.queue_idx); }
     }
@@ -649,6 +841,9 @@ impl CommandListHandle {
                 validate_parameter_recording!(validation, region.dst_offset < dst_size, "Buffer copy region {idx} destination offset out of range: {}, buffer size: {src_size}", region.src_offset);
                 validate_parameter_recording!(validation, region.dst_offset + region.size <= dst_size, "Buffer copy region {idx} size will go out of range in the destination buffer, offset + size: {}, buffer size: {src_size}", region.src_offset + region.size);
             }
+
+            track_resource!(self, "copy_buffer_regions", validation.tracker.use_buffer(src, Access::CopyRead | Access::MemoryRead));
+            track_resource!(self, "copy_buffer_regions", validation.tracker.use_buffer(dst, Access::CopyWrite | Access::MemoryWrite));
         }
         unsafe { self.handle.copy_buffer_regions(src, dst, regions); }
     }
@@ -669,6 +864,9 @@ impl CommandListHandle {
             let src_size = src.size();
             let dst_size = dst.size();
             validate_parameter_recording!(validation, src_size == dst_size, "Buffer size must match to copy the entire buffer, src size: {src_size}, dst size: {dst_size}");
+
+            track_resource!(self, "copy_buffer", validation.tracker.use_buffer(src, Access::CopyRead | Access::MemoryRead));
+            track_resource!(self, "copy_buffer", validation.tracker.use_buffer(dst, Access::CopyWrite | Access::MemoryWrite));
         }
         unsafe { self.handle.copy_buffer(src, dst); }
     }
@@ -697,6 +895,9 @@ impl CommandListHandle {
             for region in regions {
                 validate_during_recording!(validation, region.validate(src, dst));
             }
+
+            track_resource!(self, "copy_texture_regions", validation.tracker.use_texture(src, Access::CopyRead | Access::MemoryRead, &[TextureLayout::CopySrc, TextureLayout::Common]));
+            track_resource!(self, "copy_texture_regions", validation.tracker.use_texture(dst, Access::CopyWrite | Access::MemoryWrite, &[TextureLayout::CopyDst, TextureLayout::Common]));
         }
         unsafe { self.handle.copy_texture_regions(src, dst, regions) }
     }
@@ -724,6 +925,9 @@ impl CommandListHandle {
 
             validate_parameter_recording!(validation, src.format().components() == src.format().components(), "Cannot copy between textures with different format components");
             validate_parameter_recording!(validation, src.mip_levels() == dst.mip_levels(), "Cannot copy between textures with a different number of mip levels");
+
+            track_resource!(self, "copy_texture", validation.tracker.use_texture(src, Access::CopyRead | Access::MemoryRead, &[TextureLayout::CopySrc, TextureLayout::Common]));
+            track_resource!(self, "copy_texture", validation.tracker.use_texture(dst, Access::CopyWrite | Access::MemoryWrite, &[TextureLayout::CopyDst, TextureLayout::Common]));
         }
         unsafe { self.handle.copy_texture(src, dst) }
     }
@@ -742,6 +946,9 @@ impl CommandListHandle {
             for region in regions {
                 validate_during_recording!(validation, region.validate(src, dst, false));
             }
+
+            track_resource!(self, "copy_buffer_to_texture", validation.tracker.use_buffer(src, Access::CopyRead | Access::MemoryRead));
+            track_resource!(self, "copy_buffer_to_texture", validation.tracker.use_texture(dst, Access::CopyWrite | Access::MemoryWrite, &[TextureLayout::CopyDst, TextureLayout::Common]));
         }
         unsafe { self.handle.copy_buffer_to_texture(src, dst, regions) };
     }
@@ -760,6 +967,9 @@ impl CommandListHandle {
             for region in regions {
                 validate_during_recording!(validation, region.validate(dst, src, true));
             }
+
+            track_resource!(self, "copy_texture_to_buffer", validation.tracker.use_texture(src, Access::CopyRead | Access::MemoryRead, &[TextureLayout::CopySrc, TextureLayout::Common]));
+            track_resource!(self, "copy_texture_to_buffer", validation.tracker.use_buffer(dst, Access::CopyWrite | Access::MemoryWrite));
         }
         unsafe { self.handle.copy_texture_to_buffer(src, dst, regions) };
     }
@@ -784,6 +994,7 @@ impl CommandListHandle {
                 }
 
                 validate_parameter_recording!(validation, heap.heap_type() == DescriptorHeapType::Resources, "Cannot bind a non-resource descriptor heap as resource descriptor heap");
+                validation.tracker.use_descriptor_heap(heap);
             }
             if let Some(heap) = sampler_heap {
                 if !heap.is_shader_visible() {
@@ -792,6 +1003,7 @@ impl CommandListHandle {
                 }
 
                 validate_parameter_recording!(validation, heap.heap_type() == DescriptorHeapType::Samplers, "Cannot bind a non-resource descriptor heap as resource descriptor heap");
+                validation.tracker.use_descriptor_heap(heap);
             }
         }
 
@@ -814,6 +1026,12 @@ impl CommandListHandle {
                 return;
             }
 
+            if pipeline_layout.flags().contains(PipelineLayoutFlags::Bindless) {
+                let dynamic = self.dynamic.read();
+                validate_parameter_recording!(validation, dynamic.resource_descriptor_heap.is_some() && dynamic.sampler_descriptor_heap.is_some(),
+                    "Both a resource and sampler descriptor heap need to be bound before binding a bindless pipeline layout");
+            }
+
             validation.flags.enable(CommandListValidationFlags::ComputePipeline);
             validation.pipeline_state.enable(CommandListPipelineStateFlags::PipelineLayout);
         }
@@ -869,8 +1087,8 @@ impl CommandListHandle {
             }
             let dynamic = self.dynamic.read();
 
-            validate_parameter_recording!(validation, dynamic.pipeline.is_some(), "Trying to set a descriptor table with no pipeline layout bound");
-            validate_parameter_recording!(validation, !validation.flags.contains(CommandListValidationFlags::ComputePipeline), "Cannot set a graphics descriptor table when a compute pipeline is bound");
+            validate_parameter_recording!(validation, dynamic.pipeline_layout.is_some(), "Trying to set a descriptor table with no pipeline layout bound");
+            validate_parameter_recording!(validation, validation.flags.contains(CommandListValidationFlags::ComputePipeline), "Cannot set a compute descriptor table when a graphics pipeline is bound");
 
             let heap = match WeakHandle::upgrade(descriptor.heap()) {
                 Some(heap) => heap,
@@ -899,6 +1117,17 @@ impl CommandListHandle {
                     }
                 },
             }
+
+            match dynamic.pipeline_layout.as_ref().and_then(|layout| layout.desc().descriptor_tables.as_ref()).and_then(|tables| tables.get(index as usize)) {
+                Some(table) => {
+                    let end = descriptor.index() as u64 + table.num_descriptors() as u64;
+                    validate_parameter_recording!(validation, end <= heap.max_descriptors() as u64, "Descriptor table {index} goes out of bounds of the descriptor heap, first descriptor + table size: {end}, heap size: {}", heap.max_descriptors());
+                },
+                None => {
+                    validation.set_error(Error::InvalidParameter(format!("The bound pipeline layout does not have a descriptor table at index {index}")));
+                    return;
+                },
+            }
         }
 
         let dynamic = self.dynamic.read();
@@ -906,31 +1135,8 @@ impl CommandListHandle {
         unsafe { self.handle.set_compute_descriptor_table(index, descriptor, pipeline_layout) };
     }
 
-    //==============================================================================================================================
-
-    /// Bind a graphics pipeline layout
-    fn bind_graphics_pipeline_layout(&self, pipeline_layout: &PipelineLayoutHandle) {
-        #[cfg(feature = "validation")]
-        {
-            self.check_recording();
-            let mut validation = self.validation.lock();
-            if validation.state == CommandListState::Error {
-                return;
-            }
-
-            validation.flags.disable(CommandListValidationFlags::ComputePipeline);
-            validation.pipeline_state.enable(CommandListPipelineStateFlags::PipelineLayout);
-        }
-
-        let mut dynamic = self.dynamic.write();
-        dynamic.pipeline_layout = Some(pipeline_layout.clone());
-        dynamic.pipeline = None;
-
-        unsafe { self.handle.bind_graphics_pipeline_layout(pipeline_layout); }
-    }
-
-    /// Bind a graphics pipeline
-    fn bind_graphics_pipeline(&self, pipeline: &PipelineHandle) {
+    /// Set 32-bit constants in the constant range at 'index' in the current bound pipeline, starting at `offset` (in 32-bit values)
+    fn set_compute_constants(&self, index: u32, offset: u32, data: &[u32]) {
         #[cfg(feature = "validation")]
         {
             self.check_recording();
@@ -939,119 +1145,91 @@ impl CommandListHandle {
             if validation.state == CommandListState::Error {
                 return;
             }
+            let dynamic = self.dynamic.read();
 
-            validate_parameter_recording!(validation, !validation.flags.contains(CommandListValidationFlags::ComputePipeline),"Trying to bind a graphics pipeline when a compute layout is bound");
-
-            let dynamic = self.dynamic.write();
+            validate_parameter_recording!(validation, validation.flags.contains(CommandListValidationFlags::ComputePipeline), "Cannot set compute constants when a graphics pipeline is bound");
             match &dynamic.pipeline_layout {
-                Some(pipeline_layout) => {
-                    validate_parameter_recording!(validation, Handle::ptr_eq(pipeline_layout, pipeline.layout()), "Cannot bind pipeline, as the layout does not match the bound pipeline layout");
-                },
+                Some(pipeline_layout) => validate_during_recording!(validation, pipeline_layout.desc().validate_constants(index, offset, data.len())),
                 None => {
-                    validation.set_error(Error::InvalidParameter("Cannot bind pipeline when no pipeline layout has been bound".to_string()));
+                    validation.set_error(Error::InvalidParameter("Trying to set constants with no pipeline layout bound".to_string()));
                     return;
                 },
             }
-
-            validation.pipeline_state.enable(CommandListPipelineStateFlags::Pipeline);
         }
 
-        let mut dynamic = self.dynamic.write();
-        dynamic.pipeline = Some(pipeline.clone());
-        unsafe { self.handle.bind_graphics_pipeline(pipeline) };
+        let dynamic = self.dynamic.read();
+        let pipeline_layout = dynamic.pipeline_layout.as_ref().unwrap();
+        unsafe { self.handle.set_compute_constants(index, offset, data, pipeline_layout) };
     }
 
-    /// Bind the first entry in the descriptor table at 'index' in the current bound pipeline
-    fn set_graphics_descriptor_table(&self, index: u32, descriptor: GpuDescriptor) {
+    /// Dispatch compute workgroups
+    fn dispatch(&self, group_count_x: u32, group_count_y: u32, group_count_z: u32) {
         #[cfg(feature = "validation")]
         {
             self.check_recording();
+            self.check_dispatch_state();
 
             let mut validation = self.validation.lock();
             if validation.state == CommandListState::Error {
                 return;
             }
 
-            validate_parameter_recording!(validation, !validation.flags.contains(CommandListValidationFlags::ComputePipeline), "Cannot set a graphics descriptor table when a compute pipeline is bound");
-
-            let heap = match WeakHandle::upgrade(descriptor.heap()) {
-                Some(heap) => heap,
-                None => {
-                    validation.set_error(Error::ExpiredHandle("Descriptor heap owning GPU descriptor"));
-                    return
-                },
-            };
-
-            let dynamic = self.dynamic.read();
-            match heap.heap_type() {
-                DescriptorHeapType::Resources => {
-                    if let Some(cur_heap) = &dynamic.resource_descriptor_heap {
-                        validate_parameter_recording!(validation, Handle::ptr_eq(cur_heap, &heap), "Cannot set a descriptor table with a descriptor that's not in the currently bound descriptor heap");
-                    } else {
-                        validation.set_error(Error::InvalidParameter("Trying to set a descriptor table with no descriptor heap assigned".to_string()));
-                        return;
-                    }
-                },
-                DescriptorHeapType::Samplers => {
-                    if let Some(cur_heap) = &dynamic.sampler_descriptor_heap {
-                        validate_parameter_recording!(validation, Handle::ptr_eq(cur_heap, &heap), "Cannot set a descriptor table with a descriptor that's not in the currently bound descriptor heap");
-                    } else {
-                        validation.set_error(Error::InvalidParameter("Trying to set a descriptor table with no descriptor heap assigned".to_string()));
-                        return;
-                    }
-                },
-            }
-
-            validate_parameter_recording!(validation, descriptor.index() % constants::MIN_DESCRIPTOR_TABLE_OFFSET_ALIGNMENT == 0, "Descriptors need to be align to a multiple of 4 descriptors to bind as a descriptor table")
+            let [max_x, max_y, max_z] = constants::MAX_COMPUTE_WORKGROUP_COUNT_PER_DIMENSION;
+            validate_parameter_recording!(validation, group_count_x <= max_x, "Dispatch workgroup count in the x dimension ({group_count_x}) exceeds the maximum of {max_x}");
+            validate_parameter_recording!(validation, group_count_y <= max_y, "Dispatch workgroup count in the y dimension ({group_count_y}) exceeds the maximum of {max_y}");
+            validate_parameter_recording!(validation, group_count_z <= max_z, "Dispatch workgroup count in the z dimension ({group_count_z}) exceeds the maximum of {max_z}");
         }
 
-        let dynamic = self.dynamic.read();
-        let pipeline_layout = dynamic.pipeline_layout.as_ref().unwrap();
-        unsafe { self.handle.set_graphics_descriptor_table(index, descriptor, pipeline_layout) };
+        unsafe { self.handle.dispatch(group_count_x, group_count_y, group_count_z) };
     }
 
-    /// Bind a vertex buffer to the pipeline
-    fn bind_vertex_buffer(&self, mut view: VertexBufferView) {
+    /// Dispatch compute workgroups, with the workgroup counts read from `buffer` at `offset`
+    fn dispatch_indirect(&self, buffer: &BufferHandle, offset: u64) {
         #[cfg(feature = "validation")]
         {
             self.check_recording();
-            self.check_in_renderpass();
+            self.check_dispatch_state();
+
             let mut validation = self.validation.lock();
             if validation.state == CommandListState::Error {
                 return;
             }
 
-            validate_during_recording!(validation, view.validate());
-            validation.pipeline_state.enable(CommandListPipelineStateFlags::VertexBuffer);
+            let args_size = core::mem::size_of::<DispatchIndirectArgs>() as u64;
+            let buffer_size = buffer.size();
+            let offset_align = constants::INDIRECT_ARGUMENT_OFFSET_ALIGN.alignment();
+            validate_parameter_recording!(validation, buffer.usages().contains(BufferUsage::IndirectBuffer), "Indirect dispatch buffer must have the `BufferUsage::IndirectBuffer` usage");
+            validate_parameter_recording!(validation, offset % offset_align == 0, "Indirect dispatch offset ({offset}) needs to be a multiple of {offset_align}");
+            validate_parameter_recording!(validation, offset + args_size <= buffer_size, "Indirect dispatch arguments will go out of range of the buffer, offset + size: {}, buffer size: {buffer_size}", offset + args_size);
+
+            track_resource!(self, "dispatch_indirect", validation.tracker.use_buffer(buffer, Access::Indirect | Access::MemoryRead));
         }
 
-        // Make sure to clamp the size, as implementation are allowed to use the size value directly without comparing it to the buffer size
-        view.size = view.size.min(view.buffer.size());
-        unsafe { self.handle.bind_vertex_buffer(view); }
+        unsafe { self.handle.dispatch_indirect(buffer, offset) };
     }
 
-    fn bind_index_buffer(&self, mut view: IndexBufferView) {
+    //==============================================================================================================================
+
+    /// Build an acceleration structure
+    fn build_acceleration_structure(&self, info: &AccelerationStructureBuildInfo) {
         #[cfg(feature = "validation")]
         {
             self.check_recording();
-            self.check_in_renderpass();
-            
+
             let mut validation = self.validation.lock();
             if validation.state == CommandListState::Error {
                 return;
             }
 
-            validate_during_recording!(validation, view.validate());
-            validation.pipeline_state.enable(CommandListPipelineStateFlags::IndexBuffer);
+            validate_parameter_recording!(validation, !validation.flags.contains(CommandListValidationFlags::Rendering), "Cannot build an acceleration structure inside of a render pass");
+            validate_during_recording!(validation, info.validate());
         }
 
-        // Make sure to clamp the size, as implementation are allowed to use the size value directly without comparing it to the buffer size
-        view.size = view.size.min(view.buffer.size());
-        unsafe { self.handle.bind_index_buffer(view); }
+        unsafe { self.handle.build_acceleration_structure(info, None) };
     }
 
-    /// Begin rendering
-    fn begin_rendering(&self, rendering_info: &RenderingInfo) {
+    /// Update `src` into the destination of the build info
+    fn update_acceleration_structure(&self, src: &AccelerationStructureHandle, info: &AccelerationStructureBuildInfo) {
         #[cfg(feature = "validation")]
         {
             self.check_recording();
@@ -1061,104 +1239,479 @@ impl CommandListHandle {
                 return;
             }
 
-            validation.flags.enable(CommandListValidationFlags::Rendering);
+            validate_parameter_recording!(validation, !validation.flags.contains(CommandListValidationFlags::Rendering), "Cannot update an acceleration structure inside of a render pass");
+            validate_parameter_recording!(validation, info.flags.contains(AccelerationStructureBuildFlags::AllowUpdate), "Cannot update an acceleration structure that was not built with `AccelerationStructureBuildFlags::AllowUpdate`");
+            validate_parameter_recording!(validation, src.ty() == info.dst.ty(), "Cannot update a {:?} acceleration structure into a {:?} acceleration structure", src.ty(), info.dst.ty());
+            validate_parameter_recording!(validation, info.dst.size() >= src.size(), "Acceleration structure update destination ({}) is smaller than the source ({})", info.dst.size(), src.size());
+            validate_during_recording!(validation, info.validate());
         }
-        unsafe { self.handle.begin_rendering(rendering_info) };
+
+        unsafe { self.handle.build_acceleration_structure(info, Some(src)) };
     }
 
-    /// End rendering
-    fn end_rendering(&self) {
+    /// Copy an acceleration structure
+    fn copy_acceleration_structure(&self, dst: &AccelerationStructureHandle, src: &AccelerationStructureHandle, mode: AccelerationStructureCopyMode) {
         #[cfg(feature = "validation")]
         {
             self.check_recording();
+
             let mut validation = self.validation.lock();
             if validation.state == CommandListState::Error {
                 return;
             }
 
-            validate_parameter_recording!(validation, validation.flags.contains(CommandListValidationFlags::Rendering), "Cannot end rendering, as `begin_rendering` was never called")
-        }
-        unsafe { self.handle.end_rendering() };
-        #[cfg(feature = "validation")]
-        {
-            self.validation.lock().flags.disable(CommandListValidationFlags::Rendering);
+            validate_parameter_recording!(validation, !validation.flags.contains(CommandListValidationFlags::Rendering), "Cannot copy an acceleration structure inside of a render pass");
+            validate_parameter_recording!(validation, src.ty() == dst.ty(), "Cannot copy a {:?} acceleration structure into a {:?} acceleration structure", src.ty(), dst.ty());
+            if mode == AccelerationStructureCopyMode::Clone {
+                validate_parameter_recording!(validation, dst.size() >= src.size(), "Acceleration structure copy destination ({}) is smaller than the source ({})", dst.size(), src.size());
+            }
         }
+
+        unsafe { self.handle.copy_acceleration_structure(dst, src, mode) };
     }
 
-    /// Set the viewports
-    fn set_viewports(&self, viewports: &[Viewport]) {
+    /// Write the compacted size of an acceleration structure as a `u64` to `dst` at `dst_offset`
+    /// 
+    /// The acceleration structure needs to be built with `AccelerationStructureBuildFlags::AllowCompaction`
+    fn write_acceleration_structure_compacted_size(&self, src: &AccelerationStructureHandle, dst: &BufferHandle, dst_offset: u64) {
         #[cfg(feature = "validation")]
         {
             self.check_recording();
-            self.check_in_renderpass();
+
             let mut validation = self.validation.lock();
             if validation.state == CommandListState::Error {
                 return;
             }
 
-            validation.pipeline_state.enable(CommandListPipelineStateFlags::Viewport);
+            let dst_size = dst.size();
+            validate_parameter_recording!(validation, !validation.flags.contains(CommandListValidationFlags::Rendering), "Cannot write an acceleration structure's compacted size inside of a render pass");
+            validate_parameter_recording!(validation, dst.usages().contains(BufferUsage::CopyDst), "Acceleration structure compacted size destination must have the `BufferUsage::CopyDst` usage");
+            validate_parameter_recording!(validation, dst.usages().contains(BufferUsage::StorageBuffer), "Acceleration structure compacted size destination must have the `BufferUsage::StorageBuffer` usage");
+            validate_parameter_recording!(validation, dst_offset % 8 == 0, "Acceleration structure compacted size destination offset ({dst_offset}) needs to be a multiple of 8");
+            validate_parameter_recording!(validation, dst_offset + 8 <= dst_size, "Acceleration structure compacted size will go out of range of the destination buffer, offset + size: {}, buffer size: {dst_size}", dst_offset + 8);
         }
-        
-        unsafe { self.handle.set_viewports(viewports); }
+
+        unsafe { self.handle.write_acceleration_structure_compacted_size(src, dst, dst_offset) };
     }
-    
-    /// Set the scissor rects
-    fn set_scissors(&self, scissors: &[ScissorRect]) {
+
+    /// Bind a raytracing pipeline
+    /// 
+    /// Raytracing pipelines use the compute pipeline layout
+    fn bind_raytracing_pipeline(&self, pipeline: &PipelineHandle) {
         #[cfg(feature = "validation")]
         {
             self.check_recording();
-            self.check_in_renderpass();
+
             let mut validation = self.validation.lock();
             if validation.state == CommandListState::Error {
                 return;
             }
 
-            validation.pipeline_state.enable(CommandListPipelineStateFlags::Scissor);
+            validate_parameter_recording!(validation, validation.flags.contains(CommandListValidationFlags::ComputePipeline),"Trying to bind a raytracing pipeline when a graphics layout is bound");
+
+            let dynamic = self.dynamic.write();
+            match &dynamic.pipeline_layout {
+                Some(pipeline_layout) => {
+                    validate_parameter_recording!(validation, Handle::ptr_eq(pipeline_layout, pipeline.layout()),"Cannot bind pipeline, as the layout does not match the bound pipeline layout");        
+                },
+                None => {
+                    validation.set_error(Error::InvalidParameter("Cannot bind pipeline when no pipeline layout has been bound".to_string()));
+                    return;
+                },
+            }
+
+            validation.pipeline_state.enable(CommandListPipelineStateFlags::Pipeline);
         }
-        
-        unsafe { self.handle.set_scissors(scissors); }
+
+        let mut dynamic = self.dynamic.write();
+        dynamic.pipeline = Some(pipeline.clone());
+        unsafe { self.handle.bind_raytracing_pipeline(pipeline); };
     }
 
-    /// Set the primitive topology to be used
-    fn set_primitive_topology(&self, topology: PrimitiveTopology) {
+    /// Trace rays
+    fn trace_rays(&self, regions: &ShaderBindingTableRegions, width: u32, height: u32, depth: u32) {
         #[cfg(feature = "validation")]
         {
             self.check_recording();
-            self.check_in_renderpass();
+            self.check_dispatch_state();
+
             let mut validation = self.validation.lock();
             if validation.state == CommandListState::Error {
                 return;
             }
 
-            validation.pipeline_state.enable(CommandListPipelineStateFlags::Topology);
-        }
+            let invocations = width as u64 * height as u64 * depth as u64;
+            validate_parameter_recording!(validation, invocations <= constants::MAX_RAYTRACE_INVOCATIONS as u64, "Trace rays invocation count ({invocations}) exceeds the maximum of {}", constants::MAX_RAYTRACE_INVOCATIONS);
+            validate_parameter_recording!(validation, regions.raygen.size != 0, "Trace rays requires a raygen shader record");
 
-        unsafe { self.handle.set_primitive_topology(topology); }
-    }
-    
-    /// Draw instanced
-    fn draw_instanced(&self, vertex_count: u32, instance_count: u32, start_vertex: u32, start_instance: u32) {
-        #[cfg(feature = "validation")]
-        {
-            self.check_recording();
-            self.check_in_renderpass();
-            self.check_draw_state();
-            if self.validation.lock().state == CommandListState::Error {
-                return;
+            let base_align = constants::MIN_RAYTRACE_HITGROUP_BASE_ALIGNMENT.alignment();
+            let stride_align = constants::MIN_RAYTRACE_HITGROUP_HANDLE_ALIGNMENT.alignment();
+            for (name, region) in [("raygen", &regions.raygen), ("miss", &regions.miss), ("hit group", &regions.hit_group), ("callable", &regions.callable)] {
+                validate_parameter_recording!(validation, region.address.as_raw() % base_align == 0, "Shader binding table {name} region address needs to be aligned to {base_align} bytes");
+                validate_parameter_recording!(validation, region.stride % stride_align == 0, "Shader binding table {name} region stride ({}) needs to be a multiple of {stride_align}", region.stride);
+                validate_parameter_recording!(validation, region.stride <= constants::MAX_RAYTRACE_HITGROUP_STRIDE as u64, "Shader binding table {name} region stride ({}) exceeds the maximum of {}", region.stride, constants::MAX_RAYTRACE_HITGROUP_STRIDE);
             }
         }
 
-        unsafe { self.handle.draw_instanced(vertex_count, instance_count, start_vertex, start_instance); };
+        unsafe { self.handle.trace_rays(regions, width, height, depth) };
     }
 
-    /// Draw indexed instance
-    fn draw_indexed_instanced(&self, index_count: u32, instance_count: u32, start_index: u32, vertex_offset: i32, start_instance: u32) {
+    //==============================================================================================================================
+
+    /// Bind a graphics pipeline layout
+    fn bind_graphics_pipeline_layout(&self, pipeline_layout: &PipelineLayoutHandle) {
         #[cfg(feature = "validation")]
         {
             self.check_recording();
-            self.check_in_renderpass();
-            self.check_draw_state();
-
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            if pipeline_layout.flags().contains(PipelineLayoutFlags::Bindless) {
+                let dynamic = self.dynamic.read();
+                validate_parameter_recording!(validation, dynamic.resource_descriptor_heap.is_some() && dynamic.sampler_descriptor_heap.is_some(),
+                    "Both a resource and sampler descriptor heap need to be bound before binding a bindless pipeline layout");
+            }
+
+            validation.flags.disable(CommandListValidationFlags::ComputePipeline);
+            validation.pipeline_state.enable(CommandListPipelineStateFlags::PipelineLayout);
+        }
+
+        let mut dynamic = self.dynamic.write();
+        dynamic.pipeline_layout = Some(pipeline_layout.clone());
+        dynamic.pipeline = None;
+
+        unsafe { self.handle.bind_graphics_pipeline_layout(pipeline_layout); }
+    }
+
+    /// Bind a graphics pipeline
+    fn bind_graphics_pipeline(&self, pipeline: &PipelineHandle) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            validate_parameter_recording!(validation, !validation.flags.contains(CommandListValidationFlags::ComputePipeline),"Trying to bind a graphics pipeline when a compute layout is bound");
+
+            let dynamic = self.dynamic.write();
+            match &dynamic.pipeline_layout {
+                Some(pipeline_layout) => {
+                    validate_parameter_recording!(validation, Handle::ptr_eq(pipeline_layout, pipeline.layout()), "Cannot bind pipeline, as the layout does not match the bound pipeline layout");
+                },
+                None => {
+                    validation.set_error(Error::InvalidParameter("Cannot bind pipeline when no pipeline layout has been bound".to_string()));
+                    return;
+                },
+            }
+
+            validation.pipeline_state.enable(CommandListPipelineStateFlags::Pipeline);
+        }
+
+        let mut dynamic = self.dynamic.write();
+        dynamic.pipeline = Some(pipeline.clone());
+        unsafe { self.handle.bind_graphics_pipeline(pipeline) };
+    }
+
+    /// Bind the first entry in the descriptor table at 'index' in the current bound pipeline
+    fn set_graphics_descriptor_table(&self, index: u32, descriptor: GpuDescriptor) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            validate_parameter_recording!(validation, !validation.flags.contains(CommandListValidationFlags::ComputePipeline), "Cannot set a graphics descriptor table when a compute pipeline is bound");
+
+            let heap = match WeakHandle::upgrade(descriptor.heap()) {
+                Some(heap) => heap,
+                None => {
+                    validation.set_error(Error::ExpiredHandle("Descriptor heap owning GPU descriptor"));
+                    return
+                },
+            };
+
+            let dynamic = self.dynamic.read();
+            match heap.heap_type() {
+                DescriptorHeapType::Resources => {
+                    if let Some(cur_heap) = &dynamic.resource_descriptor_heap {
+                        validate_parameter_recording!(validation, Handle::ptr_eq(cur_heap, &heap), "Cannot set a descriptor table with a descriptor that's not in the currently bound descriptor heap");
+                    } else {
+                        validation.set_error(Error::InvalidParameter("Trying to set a descriptor table with no descriptor heap assigned".to_string()));
+                        return;
+                    }
+                },
+                DescriptorHeapType::Samplers => {
+                    if let Some(cur_heap) = &dynamic.sampler_descriptor_heap {
+                        validate_parameter_recording!(validation, Handle::ptr_eq(cur_heap, &heap), "Cannot set a descriptor table with a descriptor that's not in the currently bound descriptor heap");
+                    } else {
+                        validation.set_error(Error::InvalidParameter("Trying to set a descriptor table with no descriptor heap assigned".to_string()));
+                        return;
+                    }
+                },
+            }
+
+            match dynamic.pipeline_layout.as_ref().and_then(|layout| layout.desc().descriptor_tables.as_ref()).and_then(|tables| tables.get(index as usize)) {
+                Some(table) => {
+                    let end = descriptor.index() as u64 + table.num_descriptors() as u64;
+                    validate_parameter_recording!(validation, end <= heap.max_descriptors() as u64, "Descriptor table {index} goes out of bounds of the descriptor heap, first descriptor + table size: {end}, heap size: {}", heap.max_descriptors());
+                },
+                None => {
+                    validation.set_error(Error::InvalidParameter(format!("The bound pipeline layout does not have a descriptor table at index {index}")));
+                    return;
+                },
+            }
+
+            validate_parameter_recording!(validation, descriptor.index() % constants::MIN_DESCRIPTOR_TABLE_OFFSET_ALIGNMENT == 0, "Descriptors need to be align to a multiple of 4 descriptors to bind as a descriptor table")
+        }
+
+        let dynamic = self.dynamic.read();
+        let pipeline_layout = dynamic.pipeline_layout.as_ref().unwrap();
+        unsafe { self.handle.set_graphics_descriptor_table(index, descriptor, pipeline_layout) };
+    }
+
+    /// Set 32-bit constants in the constant range at 'index' in the current bound pipeline, starting at `offset` (in 32-bit values)
+    fn set_graphics_constants(&self, index: u32, offset: u32, data: &[u32]) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+            let dynamic = self.dynamic.read();
+
+            validate_parameter_recording!(validation, !validation.flags.contains(CommandListValidationFlags::ComputePipeline), "Cannot set graphics constants when a compute pipeline is bound");
+            match &dynamic.pipeline_layout {
+                Some(pipeline_layout) => validate_during_recording!(validation, pipeline_layout.desc().validate_constants(index, offset, data.len())),
+                None => {
+                    validation.set_error(Error::InvalidParameter("Trying to set constants with no pipeline layout bound".to_string()));
+                    return;
+                },
+            }
+        }
+
+        let dynamic = self.dynamic.read();
+        let pipeline_layout = dynamic.pipeline_layout.as_ref().unwrap();
+        unsafe { self.handle.set_graphics_constants(index, offset, data, pipeline_layout) };
+    }
+
+    /// Bind a vertex buffer to the pipeline
+    fn bind_vertex_buffer(&self, mut view: VertexBufferView) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            self.check_in_renderpass();
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            validate_during_recording!(validation, view.validate());
+            validation.pipeline_state.enable(CommandListPipelineStateFlags::VertexBuffer);
+
+            track_resource!(self, "bind_vertex_buffer", validation.tracker.use_buffer(&view.buffer, Access::VertexBuffer | Access::MemoryRead));
+        }
+
+        // Make sure to clamp the size, as implementation are allowed to use the size value directly without comparing it to the buffer size
+        view.size = view.size.min(view.buffer.size());
+        unsafe { self.handle.bind_vertex_buffer(view); }
+    }
+
+    fn bind_index_buffer(&self, mut view: IndexBufferView) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            self.check_in_renderpass();
+            
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            validate_during_recording!(validation, view.validate());
+            validation.pipeline_state.enable(CommandListPipelineStateFlags::IndexBuffer);
+
+            track_resource!(self, "bind_index_buffer", validation.tracker.use_buffer(&view.buffer, Access::IndexBuffer | Access::MemoryRead));
+        }
+
+        // Make sure to clamp the size, as implementation are allowed to use the size value directly without comparing it to the buffer size
+        view.size = view.size.min(view.buffer.size());
+        unsafe { self.handle.bind_index_buffer(view); }
+    }
+
+    /// Begin rendering
+    fn begin_rendering(&self, rendering_info: &RenderingInfo) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            validation.flags.enable(CommandListValidationFlags::Rendering);
+
+            for render_target in rendering_info.render_targets {
+                match WeakHandle::upgrade(render_target.rtv.texture()) {
+                    Some(texture) => track_resource!(self, "begin_rendering", validation.tracker.use_texture(&texture,
+                        Access::RenderTargetRead | Access::RenderTargetWrite | Access::MemoryRead | Access::MemoryWrite,
+                        &[render_target.layout, TextureLayout::Attachment])),
+                    None => self.report_validation_issue("begin_rendering", "Render target view refers to a texture that has been dropped"),
+                }
+            }
+        }
+        unsafe { self.handle.begin_rendering(rendering_info) };
+    }
+
+    /// End rendering
+    fn end_rendering(&self) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            validate_parameter_recording!(validation, validation.flags.contains(CommandListValidationFlags::Rendering), "Cannot end rendering, as `begin_rendering` was never called")
+        }
+        unsafe { self.handle.end_rendering() };
+        #[cfg(feature = "validation")]
+        {
+            self.validation.lock().flags.disable(CommandListValidationFlags::Rendering);
+        }
+    }
+
+    /// Resolve a multisampled texture
+    fn resolve_texture(&self, src: &TextureHandle, src_subresource: TextureSubresourceIndex, dst: &TextureHandle, dst_subresource: TextureSubresourceIndex) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            validate_parameter_recording!(validation, !validation.flags.contains(CommandListValidationFlags::Rendering), "Cannot resolve a texture inside of a render pass");
+            validate_parameter_recording!(validation, src.samples().is_multisampled(), "Resolve source needs to be multisampled");
+            validate_parameter_recording!(validation, !dst.samples().is_multisampled(), "Resolve destination cannot be multisampled, found {} samples", dst.samples().get());
+            validate_parameter_recording!(validation, src.format() == dst.format(), "Resolve source and destination need to have the same format, source: {}, destination: {}", src.format(), dst.format());
+            validate_parameter_recording!(validation, src.format().aspect() == TextureAspect::Color, "Only color textures can be resolved, found {}", src.format());
+
+            let (src_mip, src_layer) = subresource_mip_and_layer(src_subresource);
+            let (dst_mip, dst_layer) = subresource_mip_and_layer(dst_subresource);
+            let (src_width, src_height, _, src_layers) = src.size().as_tuple();
+            let (dst_width, dst_height, _, dst_layers) = dst.size().as_tuple();
+            let (dst_width, dst_height) = ((dst_width >> dst_mip).max(1), (dst_height >> dst_mip).max(1));
+
+            validate_parameter_recording!(validation, src_mip == 0, "Resolve source mip level needs to be 0, found {src_mip}");
+            validate_parameter_recording!(validation, src_layer < src_layers, "Resolve source layer {src_layer} is out of range, number of layers: {src_layers}");
+            validate_parameter_recording!(validation, dst_mip < dst.mip_levels(), "Resolve destination mip level {dst_mip} is out of range, number of mip levels: {}", dst.mip_levels());
+            validate_parameter_recording!(validation, dst_layer < dst_layers, "Resolve destination layer {dst_layer} is out of range, number of layers: {dst_layers}");
+            validate_parameter_recording!(validation, src_width == dst_width && src_height == dst_height, "Resolve source and destination size mismatch, source: {src_width}x{src_height}, destination: {dst_width}x{dst_height}");
+
+            // The `Common` layout is not accepted, as the backends record the resolve with the textures in the resolve layouts
+            track_resource!(self, "resolve_texture", validation.tracker.use_texture(src, Access::ResolveRead | Access::MemoryRead, &[TextureLayout::ResolveSrc]));
+            track_resource!(self, "resolve_texture", validation.tracker.use_texture(dst, Access::ResolveWrite | Access::MemoryWrite, &[TextureLayout::ResolveDst]));
+        }
+        unsafe { self.handle.resolve_texture(src, src_subresource, dst, dst_subresource) };
+    }
+
+    /// Set the viewports
+    fn set_viewports(&self, viewports: &[Viewport]) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            self.check_in_renderpass();
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            validation.pipeline_state.enable(CommandListPipelineStateFlags::Viewport);
+        }
+        
+        unsafe { self.handle.set_viewports(viewports); }
+    }
+    
+    /// Set the scissor rects
+    fn set_scissors(&self, scissors: &[ScissorRect]) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            self.check_in_renderpass();
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            validation.pipeline_state.enable(CommandListPipelineStateFlags::Scissor);
+        }
+        
+        unsafe { self.handle.set_scissors(scissors); }
+    }
+
+    /// Set the primitive topology to be used
+    fn set_primitive_topology(&self, topology: PrimitiveTopology) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            self.check_in_renderpass();
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            validation.pipeline_state.enable(CommandListPipelineStateFlags::Topology);
+        }
+
+        unsafe { self.handle.set_primitive_topology(topology); }
+    }
+
+    /// Set the per-draw shading rate and shading rate combiners
+    fn set_shading_rate(&self, rate: ShadingRate, combiners: [ShadingRateCombiner; 2]) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            self.check_in_renderpass();
+        }
+
+        unsafe { self.handle.set_shading_rate(rate, combiners); }
+    }
+    
+    /// Draw instanced
+    fn draw_instanced(&self, vertex_count: u32, instance_count: u32, start_vertex: u32, start_instance: u32) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            self.check_in_renderpass();
+            self.check_draw_state();
+            if self.validation.lock().state == CommandListState::Error {
+                return;
+            }
+        }
+
+        unsafe { self.handle.draw_instanced(vertex_count, instance_count, start_vertex, start_instance); };
+    }
+
+    /// Draw indexed instance
+    fn draw_indexed_instanced(&self, index_count: u32, instance_count: u32, start_index: u32, vertex_offset: i32, start_instance: u32) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            self.check_in_renderpass();
+            self.check_draw_state();
+
             let mut validation = self.validation.lock();
             if validation.state == CommandListState::Error {
                 return;
@@ -1167,12 +1720,292 @@ impl CommandListHandle {
             validate_parameter_recording!(validation, validation.pipeline_state.contains(CommandListPipelineStateFlags::IndexBuffer), "Trying to draw indexed, but no index buffer has been set");
         }
 
-        unsafe { self.handle.draw_indexed_instanced(index_count, instance_count, start_index, vertex_offset, start_instance); }
+        unsafe { self.handle.draw_indexed_instanced(index_count, instance_count, start_index, vertex_offset, start_instance); }
+    }
+
+    /// Draw instanced, with `draw_count` draws read from `buffer` at `offset`
+    fn draw_indirect(&self, buffer: &BufferHandle, offset: u64, draw_count: u32) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            self.check_in_renderpass();
+            self.check_draw_state();
+            self.check_indirect_draw_args(buffer, offset, core::mem::size_of::<DrawIndirectArgs>() as u64, draw_count, None);
+            if self.validation.lock().state == CommandListState::Error {
+                return;
+            }
+        }
+
+        unsafe { self.handle.draw_indirect(buffer, offset, draw_count) };
+    }
+
+    /// Draw indexed instanced, with `draw_count` draws read from `buffer` at `offset`
+    fn draw_indexed_indirect(&self, buffer: &BufferHandle, offset: u64, draw_count: u32) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            self.check_in_renderpass();
+            self.check_draw_state();
+            self.check_indirect_draw_args(buffer, offset, core::mem::size_of::<DrawIndexedIndirectArgs>() as u64, draw_count, None);
+
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            validate_parameter_recording!(validation, validation.pipeline_state.contains(CommandListPipelineStateFlags::IndexBuffer), "Trying to draw indexed, but no index buffer has been set");
+        }
+
+        unsafe { self.handle.draw_indexed_indirect(buffer, offset, draw_count) };
+    }
+
+    /// Draw instanced, with draws read from `buffer` at `offset`, and the number of draws read from `count_buffer` at `count_offset`
+    fn draw_indirect_count(&self, buffer: &BufferHandle, offset: u64, count_buffer: &BufferHandle, count_offset: u64, max_draw_count: u32) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            self.check_in_renderpass();
+            self.check_draw_state();
+            self.check_indirect_draw_args(buffer, offset, core::mem::size_of::<DrawIndirectArgs>() as u64, max_draw_count, Some((count_buffer, count_offset)));
+            if self.validation.lock().state == CommandListState::Error {
+                return;
+            }
+        }
+
+        unsafe { self.handle.draw_indirect_count(buffer, offset, count_buffer, count_offset, max_draw_count) };
+    }
+
+    /// Draw indexed instanced, with draws read from `buffer` at `offset`, and the number of draws read from `count_buffer` at `count_offset`
+    fn draw_indexed_indirect_count(&self, buffer: &BufferHandle, offset: u64, count_buffer: &BufferHandle, count_offset: u64, max_draw_count: u32) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            self.check_in_renderpass();
+            self.check_draw_state();
+            self.check_indirect_draw_args(buffer, offset, core::mem::size_of::<DrawIndexedIndirectArgs>() as u64, max_draw_count, Some((count_buffer, count_offset)));
+
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            validate_parameter_recording!(validation, validation.pipeline_state.contains(CommandListPipelineStateFlags::IndexBuffer), "Trying to draw indexed, but no index buffer has been set");
+        }
+
+        unsafe { self.handle.draw_indexed_indirect_count(buffer, offset, count_buffer, count_offset, max_draw_count) };
+    }
+
+    /// Dispatch mesh (or task) shader workgroups
+    fn dispatch_mesh(&self, group_count_x: u32, group_count_y: u32, group_count_z: u32) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            self.check_in_renderpass();
+            self.check_mesh_draw_state();
+
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            // Task shader limits are equal to the mesh shader limits, so we can validate against either
+            let [max_x, max_y, max_z] = constants::MAX_MESH_WORKGROUP_COUNT_PER_DIMENSION;
+            validate_parameter_recording!(validation, group_count_x <= max_x, "Mesh dispatch workgroup count in the x dimension ({group_count_x}) exceeds the maximum of {max_x}");
+            validate_parameter_recording!(validation, group_count_y <= max_y, "Mesh dispatch workgroup count in the y dimension ({group_count_y}) exceeds the maximum of {max_y}");
+            validate_parameter_recording!(validation, group_count_z <= max_z, "Mesh dispatch workgroup count in the z dimension ({group_count_z}) exceeds the maximum of {max_z}");
+
+            let total = group_count_x as u64 * group_count_y as u64 * group_count_z as u64;
+            let max_total = constants::MAX_MESH_WORKGROUP_COUNT;
+            validate_parameter_recording!(validation, total <= max_total as u64, "Mesh dispatch total workgroup count ({total}) exceeds the maximum of {max_total}");
+        }
+
+        unsafe { self.handle.dispatch_mesh(group_count_x, group_count_y, group_count_z) };
+    }
+
+    /// Dispatch mesh (or task) shader workgroups, with the workgroup counts read from `buffer` at `offset`
+    fn dispatch_mesh_indirect(&self, buffer: &BufferHandle, offset: u64) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+            self.check_in_renderpass();
+            self.check_mesh_draw_state();
+
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            let args_size = core::mem::size_of::<DispatchIndirectArgs>() as u64;
+            let buffer_size = buffer.size();
+            let offset_align = constants::INDIRECT_ARGUMENT_OFFSET_ALIGN.alignment();
+            validate_parameter_recording!(validation, buffer.usages().contains(BufferUsage::IndirectBuffer), "Indirect mesh dispatch buffer must have the `BufferUsage::IndirectBuffer` usage");
+            validate_parameter_recording!(validation, offset % offset_align == 0, "Indirect mesh dispatch offset ({offset}) needs to be a multiple of {offset_align}");
+            validate_parameter_recording!(validation, offset + args_size <= buffer_size, "Indirect mesh dispatch arguments will go out of range of the buffer, offset + size: {}, buffer size: {buffer_size}", offset + args_size);
+        }
+
+        unsafe { self.handle.dispatch_mesh_indirect(buffer, offset) };
+    }
+
+    //==============================================================================================================================
+
+    /// Write a GPU timestamp to the query at `index` in the query pool
+    fn write_timestamp(&self, query_pool: &QueryPoolHandle, index: u32) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            // Copy queues write timestamps to a different kind of query heap on DX12, so they need their own query type
+            let is_copy_list = WeakHandle::upgrade(&self.pool).map_or(false, |pool| pool.pool_type == CommandListType::Copy);
+            let expected_type = if is_copy_list { QueryType::CopyQueueTimestamp } else { QueryType::Timestamp };
+            validate_parameter_recording!(validation, query_pool.query_type() == expected_type, "Cannot write a timestamp to a query pool of type `{}`, expected a query pool of type `{expected_type}`", query_pool.query_type());
+            validate_parameter_recording!(validation, index < query_pool.count(), "Query index out of range: {index}, query pool count: {}", query_pool.count());
+        }
+
+        unsafe { self.handle.write_timestamp(query_pool, index) };
+    }
+
+    /// Begin the query at `index` in the query pool
+    fn begin_query(&self, query_pool: &QueryPoolHandle, index: u32) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            validate_parameter_recording!(validation, !query_pool.query_type().is_timestamp(), "Cannot begin a timestamp query, use `write_timestamp` instead");
+            validate_parameter_recording!(validation, index < query_pool.count(), "Query index out of range: {index}, query pool count: {}", query_pool.count());
+        }
+
+        unsafe { self.handle.begin_query(query_pool, index) };
+    }
+
+    /// End the query at `index` in the query pool
+    fn end_query(&self, query_pool: &QueryPoolHandle, index: u32) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            validate_parameter_recording!(validation, !query_pool.query_type().is_timestamp(), "Cannot end a timestamp query, use `write_timestamp` instead");
+            validate_parameter_recording!(validation, index < query_pool.count(), "Query index out of range: {index}, query pool count: {}", query_pool.count());
+        }
+
+        unsafe { self.handle.end_query(query_pool, index) };
+    }
+
+    /// Reset `count` queries, starting at `first_query`
+    /// 
+    /// Queries need to be reset before they can be written to
+    fn reset_queries(&self, query_pool: &QueryPoolHandle, first_query: u32, count: u32) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            validate_parameter_recording!(validation, !validation.flags.contains(CommandListValidationFlags::Rendering), "Cannot reset queries inside of a render pass");
+            validate_parameter_recording!(validation, first_query as u64 + count as u64 <= query_pool.count() as u64, "Query range out of range, first + count: {}, query pool count: {}", first_query as u64 + count as u64, query_pool.count());
+        }
+
+        unsafe { self.handle.reset_queries(query_pool, first_query, count) };
+    }
+
+    /// Resolve `count` queries, starting at `first_query`, into `dst` at `dst_offset`
+    /// 
+    /// Each query is resolved to the size returned by `QueryType::result_size`, which is tightly packed in the destination buffer
+    fn resolve_queries(&self, query_pool: &QueryPoolHandle, first_query: u32, count: u32, dst: &BufferHandle, dst_offset: u64) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            let resolve_size = count as u64 * query_pool.query_type().result_size();
+            let dst_size = dst.size();
+            validate_parameter_recording!(validation, !validation.flags.contains(CommandListValidationFlags::Rendering), "Cannot resolve queries inside of a render pass");
+            validate_parameter_recording!(validation, first_query as u64 + count as u64 <= query_pool.count() as u64, "Query range out of range, first + count: {}, query pool count: {}", first_query as u64 + count as u64, query_pool.count());
+            validate_parameter_recording!(validation, dst.usages().contains(BufferUsage::CopyDst), "Query resolve destination must have the `BufferUsage::CopyDst` usage");
+            validate_parameter_recording!(validation, dst_offset % 8 == 0, "Query resolve destination offset ({dst_offset}) needs to be a multiple of 8");
+            validate_parameter_recording!(validation, dst_offset + resolve_size <= dst_size, "Query resolve will go out of range of the destination buffer, offset + size: {}, buffer size: {dst_size}", dst_offset + resolve_size);
+
+            track_resource!(self, "resolve_queries", validation.tracker.use_buffer(dst, Access::CopyWrite | Access::MemoryWrite));
+        }
+
+        unsafe { self.handle.resolve_queries(query_pool, first_query, count, dst, dst_offset) };
+    }
+
+    /// Begin predication, using the 64-bit predicate in `buffer` at `offset`
+    /// 
+    /// While predication is active, draws, dispatches and clears are skipped depending on the predicate and `op`.
+    /// The buffer needs to be in the `ResourceState::CONDITIONAL_RENDERING` state when it is used
+    fn begin_predication(&self, buffer: &BufferHandle, offset: u64, op: PredicationOp) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            let buffer_size = buffer.size();
+            validate_parameter_recording!(validation, !validation.flags.contains(CommandListValidationFlags::Predication), "Cannot begin predication, as predication is already active");
+            validate_parameter_recording!(validation, buffer.usages().contains(BufferUsage::ConditionalRendering), "Predication buffer must have the `BufferUsage::ConditionalRendering` usage");
+            validate_parameter_recording!(validation, offset % 8 == 0, "Predication offset ({offset}) needs to be a multiple of 8");
+            validate_parameter_recording!(validation, offset + 8 <= buffer_size, "Predicate will go out of range of the buffer, offset + size: {}, buffer size: {buffer_size}", offset + 8);
+
+            track_resource!(self, "begin_predication", validation.tracker.use_buffer(buffer, Access::Conditional | Access::MemoryRead));
+            validation.flags.enable(CommandListValidationFlags::Predication);
+        }
+
+        unsafe { self.handle.begin_predication(buffer, offset, op) };
+    }
+
+    /// End predication
+    fn end_predication(&self) {
+        #[cfg(feature = "validation")]
+        {
+            self.check_recording();
+
+            let mut validation = self.validation.lock();
+            if validation.state == CommandListState::Error {
+                return;
+            }
+
+            validate_parameter_recording!(validation, validation.flags.contains(CommandListValidationFlags::Predication), "Cannot end predication, as `begin_predication` was never called");
+            validation.flags.disable(CommandListValidationFlags::Predication);
+        }
+
+        unsafe { self.handle.end_predication() };
     }
 
     //==============================================================================================================================
     // HELPERS
 
+    /// Report an issue found by the RAL validation layer to the log
+    #[cfg(feature = "validation")]
+    pub(crate) fn report_validation_issue(&self, call: &str, msg: &str) {
+        log_error!(LOG_CAT, "RAL validation: `{call}` on command list '{}': {msg}", display_name(&self.debug_name.read()));
+    }
+
     #[cfg(feature = "validation")]
     fn check_recording(&self) {
         let mut validation = self.validation.lock();
@@ -1193,6 +2026,33 @@ impl CommandListHandle {
         validate_parameter_recording!(validation, validation.flags.contains(CommandListValidationFlags::Rendering), "Cannot record rendering command, as command list is not in a render pass");
     }
 
+    #[cfg(feature = "validation")]
+    fn check_dispatch_state(&self) {
+        let mut validation = self.validation.lock();
+        if validation.state == CommandListState::Error {
+            return;
+        }
+
+        validate_parameter_recording!(validation, !validation.flags.contains(CommandListValidationFlags::Rendering), "Cannot dispatch inside of a render pass");
+        validate_parameter_recording!(validation, validation.flags.contains(CommandListValidationFlags::ComputePipeline), "Trying to dispatch, but no compute pipeline layout has been set");
+        validate_parameter_recording!(validation, validation.pipeline_state.contains(CommandListPipelineStateFlags::PipelineLayout), "Trying to dispatch, but no pipeline layout has been set");
+        validate_parameter_recording!(validation, validation.pipeline_state.contains(CommandListPipelineStateFlags::Pipeline), "Trying to dispatch, but no pipeline has been set");
+    }
+
+    #[cfg(feature = "validation")]
+    fn check_mesh_draw_state(&self) {
+        let mut validation = self.validation.lock();
+        if validation.state == CommandListState::Error {
+            return;
+        }
+
+        validate_parameter_recording!(validation, !validation.flags.contains(CommandListValidationFlags::ComputePipeline), "Trying to dispatch mesh shaders, but a compute pipeline layout is bound");
+        validate_parameter_recording!(validation, validation.pipeline_state.contains(CommandListPipelineStateFlags::PipelineLayout), "Trying to dispatch mesh shaders, but no pipeline layout has been set");
+        validate_parameter_recording!(validation, validation.pipeline_state.contains(CommandListPipelineStateFlags::Pipeline), "Trying to dispatch mesh shaders, but no pipeline has been set");
+        validate_parameter_recording!(validation, validation.pipeline_state.contains(CommandListPipelineStateFlags::Viewport), "Trying to dispatch mesh shaders, but no viewports has been set");
+        validate_parameter_recording!(validation, validation.pipeline_state.contains(CommandListPipelineStateFlags::Scissor), "Trying to dispatch mesh shaders, but no scissors has been set");
+    }
+
     #[cfg(feature = "validation")]
     fn check_draw_state(&self) {
         let mut validation = self.validation.lock();
@@ -1216,6 +2076,33 @@ impl CommandListHandle {
             return;
         }
     }
+
+    #[cfg(feature = "validation")]
+    fn check_indirect_draw_args(&self, buffer: &BufferHandle, offset: u64, args_size: u64, draw_count: u32, count: Option<(&BufferHandle, u64)>) {
+        let mut validation = self.validation.lock();
+        if validation.state == CommandListState::Error {
+            return;
+        }
+
+        let buffer_size = buffer.size();
+        let offset_align = constants::INDIRECT_ARGUMENT_OFFSET_ALIGN.alignment();
+        let args_end = offset + args_size * draw_count as u64;
+        validate_parameter_recording!(validation, buffer.usages().contains(BufferUsage::IndirectBuffer), "Indirect draw buffer must have the `BufferUsage::IndirectBuffer` usage");
+        validate_parameter_recording!(validation, offset % offset_align == 0, "Indirect draw offset ({offset}) needs to be a multiple of {offset_align}");
+        validate_parameter_recording!(validation, draw_count <= constants::MAX_DRAW_INDIRECT_COUNT, "Indirect draw count ({draw_count}) exceeds the maximum of {}", constants::MAX_DRAW_INDIRECT_COUNT);
+        validate_parameter_recording!(validation, args_end <= buffer_size, "Indirect draw arguments will go out of range of the buffer, offset + size: {args_end}, buffer size: {buffer_size}");
+
+        if let Some((count_buffer, count_offset)) = count {
+            let count_buffer_size = count_buffer.size();
+            let count_align = constants::INDIRECT_COUNT_OFFSET_ALIGN.alignment();
+            validate_parameter_recording!(validation, count_buffer.usages().contains(BufferUsage::IndirectBuffer), "Indirect draw count buffer must have the `BufferUsage::IndirectBuffer` usage");
+            validate_parameter_recording!(validation, count_offset % count_align == 0, "Indirect draw count offset ({count_offset}) needs to be a multiple of {count_align}");
+            validate_parameter_recording!(validation, count_offset + 4 <= count_buffer_size, "Indirect draw count will go out of range of the buffer, offset + size: {}, buffer size: {count_buffer_size}", count_offset + 4);
+            track_resource!(self, "draw_indirect_count", validation.tracker.use_buffer(count_buffer, Access::Indirect | Access::MemoryRead));
+        }
+
+        track_resource!(self, "draw_indirect", validation.tracker.use_buffer(buffer, Access::Indirect | Access::MemoryRead));
+    }
 }
 
 impl HandleImpl for CommandList {
@@ -1271,6 +2158,28 @@ impl GraphicsCommandList {
         self.handle.close()
     }
 
+    /// Set the debug name, which will show up in graphics debuggers and validation messages
+    pub fn set_debug_name(&self, name: &str) {
+        self.handle.set_debug_name(name);
+    }
+
+    //==============================================================
+
+    /// Begin a named debug event, which will show up in graphics debuggers, events can be nested
+    pub fn begin_event(&self, name: &str, color: [f32; 4]) {
+        self.handle.begin_event(name, color);
+    }
+
+    /// End the last debug event
+    pub fn end_event(&self) {
+        self.handle.end_event();
+    }
+
+    /// Insert a named debug marker, which will show up in graphics debuggers
+    pub fn set_marker(&self, name: &str, color: [f32; 4]) {
+        self.handle.set_marker(name, color);
+    }
+
     //==============================================================
 
     /// Insert barriers into the command list
@@ -1290,6 +2199,23 @@ impl GraphicsCommandList {
 
     //==============================================================
 
+    /// Write a GPU timestamp to the query at `index` in the query pool
+    pub fn write_timestamp(&self, query_pool: &QueryPoolHandle, index: u32) {
+        self.handle.write_timestamp(query_pool, index);
+    }
+
+    /// Reset `count` queries, starting at `first_query`
+    pub fn reset_queries(&self, query_pool: &QueryPoolHandle, first_query: u32, count: u32) {
+        self.handle.reset_queries(query_pool, first_query, count);
+    }
+
+    /// Resolve `count` queries, starting at `first_query`, into `dst` at `dst_offset`
+    pub fn resolve_queries(&self, query_pool: &QueryPoolHandle, first_query: u32, count: u32, dst: &BufferHandle, dst_offset: u64) {
+        self.handle.resolve_queries(query_pool, first_query, count, dst, dst_offset);
+    }
+
+    //==============================================================
+
     /// Bind resourece and sampler descriptor heaps
     pub fn bind_descriptor_heaps(&self, resource_heap: Option<&DescriptorHeapHandle>, sampler_heap: Option<&DescriptorHeapHandle>) {
         self.handle.bind_descriptor_heaps(resource_heap, sampler_heap);
@@ -1311,6 +2237,53 @@ impl GraphicsCommandList {
     pub fn set_compute_descriptor_table(&self, index: u32, descriptor: GpuDescriptor) {
         self.handle.set_compute_descriptor_table(index, descriptor)
     }
+
+    /// Set 32-bit constants in the constant range at 'index' in the current bound pipeline, starting at `offset` (in 32-bit values)
+    pub fn set_compute_constants(&self, index: u32, offset: u32, data: &[u32]) {
+        self.handle.set_compute_constants(index, offset, data)
+    }
+
+    /// Dispatch `group_count_x * group_count_y * group_count_z` compute workgroups
+    pub fn dispatch(&self, group_count_x: u32, group_count_y: u32, group_count_z: u32) {
+        self.handle.dispatch(group_count_x, group_count_y, group_count_z)
+    }
+
+    /// Dispatch compute workgroups, with the workgroup counts read from `buffer` at `offset`, laid out as `DispatchIndirectArgs`
+    pub fn dispatch_indirect(&self, buffer: &BufferHandle, offset: u64) {
+        self.handle.dispatch_indirect(buffer, offset)
+    }
+
+    //==============================================================
+
+    /// Build an acceleration structure
+    pub fn build_acceleration_structure(&self, info: &AccelerationStructureBuildInfo) {
+        self.handle.build_acceleration_structure(info)
+    }
+
+    /// Update `src` into the destination of the build info
+    pub fn update_acceleration_structure(&self, src: &AccelerationStructureHandle, info: &AccelerationStructureBuildInfo) {
+        self.handle.update_acceleration_structure(src, info)
+    }
+
+    /// Copy an acceleration structure
+    pub fn copy_acceleration_structure(&self, dst: &AccelerationStructureHandle, src: &AccelerationStructureHandle, mode: AccelerationStructureCopyMode) {
+        self.handle.copy_acceleration_structure(dst, src, mode)
+    }
+
+    /// Write the compacted size of an acceleration structure as a `u64` to `dst` at `dst_offset`
+    pub fn write_acceleration_structure_compacted_size(&self, src: &AccelerationStructureHandle, dst: &BufferHandle, dst_offset: u64) {
+        self.handle.write_acceleration_structure_compacted_size(src, dst, dst_offset)
+    }
+
+    /// Bind a raytracing pipeline
+    pub fn bind_raytracing_pipeline(&self, pipeline: &PipelineHandle) {
+        self.handle.bind_raytracing_pipeline(pipeline)
+    }
+
+    /// Trace rays
+    pub fn trace_rays(&self, regions: &ShaderBindingTableRegions, width: u32, height: u32, depth: u32) {
+        self.handle.trace_rays(regions, width, height, depth)
+    }
     
     //==============================================================
 
@@ -1328,6 +2301,11 @@ impl GraphicsCommandList {
     pub fn set_graphics_descriptor_table(&self, index: u32, descriptor: GpuDescriptor) {
         self.handle.set_graphics_descriptor_table(index, descriptor)
     }
+
+    /// Set 32-bit constants in the constant range at 'index' in the current bound pipeline, starting at `offset` (in 32-bit values)
+    pub fn set_graphics_constants(&self, index: u32, offset: u32, data: &[u32]) {
+        self.handle.set_graphics_constants(index, offset, data)
+    }
     
     /// Bind a vertex buffer to the pipeline
     pub fn bind_vertex_buffer(&self, view: VertexBufferView) {
@@ -1349,6 +2327,13 @@ impl GraphicsCommandList {
         self.handle.end_rendering();
     }
 
+    /// Resolve a subresource of a multisampled texture into a subresource of a single sampled texture
+    /// 
+    /// Both textures need to have the same color format, the source texture needs to be in the `ResolveSrc` layout and the destination texture needs to be in the `ResolveDst` layout
+    pub fn resolve_texture(&self, src: &TextureHandle, src_subresource: TextureSubresourceIndex, dst: &TextureHandle, dst_subresource: TextureSubresourceIndex) {
+        self.handle.resolve_texture(src, src_subresource, dst, dst_subresource);
+    }
+
     /// Set the viewports to use
     pub fn set_viewport(&self, viewports: &[Viewport]) {
         self.handle.set_viewports(viewports);
@@ -1364,6 +2349,13 @@ impl GraphicsCommandList {
         self.handle.set_primitive_topology(topology);
     }
 
+    /// Set the per-draw shading rate
+    /// 
+    /// `combiners[0]` combines the per-draw rate with the per-primitive rate, `combiners[1]` combines that result with the rate of the shading rate attachment
+    pub fn set_shading_rate(&self, rate: ShadingRate, combiners: [ShadingRateCombiner; 2]) {
+        self.handle.set_shading_rate(rate, combiners);
+    }
+
     /// Draw `vertex_count` vertices, with the first vertex starting at `start_vertex`
     pub fn draw(&self, vertex_count: u32, start_vertex: u32) {
         self.handle.draw_instanced(vertex_count, 1, start_vertex, 0);
@@ -1384,6 +2376,70 @@ impl GraphicsCommandList {
         self.handle.draw_indexed_instanced(index_count, instance_count, start_index, vertex_offset, start_instance);
     }
 
+    /// Draw using the arguments read from `buffer` at `offset`, laid out as `DrawIndirectArgs`
+    pub fn draw_indirect(&self, buffer: &BufferHandle, offset: u64) {
+        self.handle.draw_indirect(buffer, offset, 1);
+    }
+
+    /// Draw `draw_count` times, using the arguments read from `buffer` at `offset`, laid out as a tightly packed array of `DrawIndirectArgs`
+    pub fn multi_draw_indirect(&self, buffer: &BufferHandle, offset: u64, draw_count: u32) {
+        self.handle.draw_indirect(buffer, offset, draw_count);
+    }
+
+    /// Draw using the arguments read from `buffer` at `offset`, laid out as a tightly packed array of `DrawIndirectArgs`,
+    /// with the number of draws read as a `u32` from `count_buffer` at `count_offset`, clamped to `max_draw_count`
+    pub fn multi_draw_indirect_count(&self, buffer: &BufferHandle, offset: u64, count_buffer: &BufferHandle, count_offset: u64, max_draw_count: u32) {
+        self.handle.draw_indirect_count(buffer, offset, count_buffer, count_offset, max_draw_count);
+    }
+
+    /// Draw indexed using the arguments read from `buffer` at `offset`, laid out as `DrawIndexedIndirectArgs`
+    pub fn draw_indexed_indirect(&self, buffer: &BufferHandle, offset: u64) {
+        self.handle.draw_indexed_indirect(buffer, offset, 1);
+    }
+
+    /// Draw indexed `draw_count` times, using the arguments read from `buffer` at `offset`, laid out as a tightly packed array of `DrawIndexedIndirectArgs`
+    pub fn multi_draw_indexed_indirect(&self, buffer: &BufferHandle, offset: u64, draw_count: u32) {
+        self.handle.draw_indexed_indirect(buffer, offset, draw_count);
+    }
+
+    /// Draw indexed using the arguments read from `buffer` at `offset`, laid out as a tightly packed array of `DrawIndexedIndirectArgs`,
+    /// with the number of draws read as a `u32` from `count_buffer` at `count_offset`, clamped to `max_draw_count`
+    pub fn multi_draw_indexed_indirect_count(&self, buffer: &BufferHandle, offset: u64, count_buffer: &BufferHandle, count_offset: u64, max_draw_count: u32) {
+        self.handle.draw_indexed_indirect_count(buffer, offset, count_buffer, count_offset, max_draw_count);
+    }
+
+    /// Dispatch `group_count_x * group_count_y * group_count_z` mesh shader workgroups, or task shader workgroups if the pipeline contains a task shader
+    pub fn dispatch_mesh(&self, group_count_x: u32, group_count_y: u32, group_count_z: u32) {
+        self.handle.dispatch_mesh(group_count_x, group_count_y, group_count_z)
+    }
+
+    /// Dispatch mesh (or task) shader workgroups, with the workgroup counts read from `buffer` at `offset`, laid out as `DispatchIndirectArgs`
+    pub fn dispatch_mesh_indirect(&self, buffer: &BufferHandle, offset: u64) {
+        self.handle.dispatch_mesh_indirect(buffer, offset)
+    }
+
+    /// Begin the query at `index` in the query pool
+    pub fn begin_query(&self, query_pool: &QueryPoolHandle, index: u32) {
+        self.handle.begin_query(query_pool, index);
+    }
+
+    /// End the query at `index` in the query pool
+    pub fn end_query(&self, query_pool: &QueryPoolHandle, index: u32) {
+        self.handle.end_query(query_pool, index);
+    }
+
+    /// Begin predication, using the 64-bit predicate in `buffer` at `offset`
+    /// 
+    /// This is mainly used to skip rendering based on the resolved result of an occlusion query
+    pub fn begin_predication(&self, buffer: &BufferHandle, offset: u64, op: PredicationOp) {
+        self.handle.begin_predication(buffer, offset, op);
+    }
+
+    /// End predication
+    pub fn end_predication(&self) {
+        self.handle.end_predication();
+    }
+
 }
 
 impl AsRef<Handle<CommandList>> for GraphicsCommandList {
@@ -1429,6 +2485,28 @@ impl ComputeCommandList {
         self.handle.close()
     }
 
+    /// Set the debug name, which will show up in graphics debuggers and validation messages
+    pub fn set_debug_name(&self, name: &str) {
+        self.handle.set_debug_name(name);
+    }
+
+    //==============================================================
+
+    /// Begin a named debug event, which will show up in graphics debuggers, events can be nested
+    pub fn begin_event(&self, name: &str, color: [f32; 4]) {
+        self.handle.begin_event(name, color);
+    }
+
+    /// End the last debug event
+    pub fn end_event(&self) {
+        self.handle.end_event();
+    }
+
+    /// Insert a named debug marker, which will show up in graphics debuggers
+    pub fn set_marker(&self, name: &str, color: [f32; 4]) {
+        self.handle.set_marker(name, color);
+    }
+
     //==============================================================
 
     /// Insert barriers into the command list
@@ -1448,6 +2526,23 @@ impl ComputeCommandList {
 
     //==============================================================
 
+    /// Write a GPU timestamp to the query at `index` in the query pool
+    pub fn write_timestamp(&self, query_pool: &QueryPoolHandle, index: u32) {
+        self.handle.write_timestamp(query_pool, index);
+    }
+
+    /// Reset `count` queries, starting at `first_query`
+    pub fn reset_queries(&self, query_pool: &QueryPoolHandle, first_query: u32, count: u32) {
+        self.handle.reset_queries(query_pool, first_query, count);
+    }
+
+    /// Resolve `count` queries, starting at `first_query`, into `dst` at `dst_offset`
+    pub fn resolve_queries(&self, query_pool: &QueryPoolHandle, first_query: u32, count: u32, dst: &BufferHandle, dst_offset: u64) {
+        self.handle.resolve_queries(query_pool, first_query, count, dst, dst_offset);
+    }
+
+    //==============================================================
+
     /// Bind resourece and sampler descriptor heaps
     pub fn bind_descriptor_heaps(&self, resource_heap: Option<&DescriptorHeapHandle>, sampler_heap: Option<&DescriptorHeapHandle>) {
         self.handle.bind_descriptor_heaps(resource_heap, sampler_heap);
@@ -1469,6 +2564,53 @@ impl ComputeCommandList {
     pub fn set_compute_descriptor_table(&self, index: u32, descriptor: GpuDescriptor) {
         self.handle.set_compute_descriptor_table(index, descriptor)
     }
+
+    /// Set 32-bit constants in the constant range at 'index' in the current bound pipeline, starting at `offset` (in 32-bit values)
+    pub fn set_compute_constants(&self, index: u32, offset: u32, data: &[u32]) {
+        self.handle.set_compute_constants(index, offset, data)
+    }
+
+    /// Dispatch `group_count_x * group_count_y * group_count_z` compute workgroups
+    pub fn dispatch(&self, group_count_x: u32, group_count_y: u32, group_count_z: u32) {
+        self.handle.dispatch(group_count_x, group_count_y, group_count_z)
+    }
+
+    /// Dispatch compute workgroups, with the workgroup counts read from `buffer` at `offset`, laid out as `DispatchIndirectArgs`
+    pub fn dispatch_indirect(&self, buffer: &BufferHandle, offset: u64) {
+        self.handle.dispatch_indirect(buffer, offset)
+    }
+
+    //==============================================================
+
+    /// Build an acceleration structure
+    pub fn build_acceleration_structure(&self, info: &AccelerationStructureBuildInfo) {
+        self.handle.build_acceleration_structure(info)
+    }
+
+    /// Update `src` into the destination of the build info
+    pub fn update_acceleration_structure(&self, src: &AccelerationStructureHandle, info: &AccelerationStructureBuildInfo) {
+        self.handle.update_acceleration_structure(src, info)
+    }
+
+    /// Copy an acceleration structure
+    pub fn copy_acceleration_structure(&self, dst: &AccelerationStructureHandle, src: &AccelerationStructureHandle, mode: AccelerationStructureCopyMode) {
+        self.handle.copy_acceleration_structure(dst, src, mode)
+    }
+
+    /// Write the compacted size of an acceleration structure as a `u64` to `dst` at `dst_offset`
+    pub fn write_acceleration_structure_compacted_size(&self, src: &AccelerationStructureHandle, dst: &BufferHandle, dst_offset: u64) {
+        self.handle.write_acceleration_structure_compacted_size(src, dst, dst_offset)
+    }
+
+    /// Bind a raytracing pipeline
+    pub fn bind_raytracing_pipeline(&self, pipeline: &PipelineHandle) {
+        self.handle.bind_raytracing_pipeline(pipeline)
+    }
+
+    /// Trace rays
+    pub fn trace_rays(&self, regions: &ShaderBindingTableRegions, width: u32, height: u32, depth: u32) {
+        self.handle.trace_rays(regions, width, height, depth)
+    }
     
 }
 
@@ -1515,6 +2657,28 @@ impl CopyCommandList {
         self.handle.close()
     }
 
+    /// Set the debug name, which will show up in graphics debuggers and validation messages
+    pub fn set_debug_name(&self, name: &str) {
+        self.handle.set_debug_name(name);
+    }
+
+    //==============================================================
+
+    /// Begin a named debug event, which will show up in graphics debuggers, events can be nested
+    pub fn begin_event(&self, name: &str, color: [f32; 4]) {
+        self.handle.begin_event(name, color);
+    }
+
+    /// End the last debug event
+    pub fn end_event(&self) {
+        self.handle.end_event();
+    }
+
+    /// Insert a named debug marker, which will show up in graphics debuggers
+    pub fn set_marker(&self, name: &str, color: [f32; 4]) {
+        self.handle.set_marker(name, color);
+    }
+
     //==============================================================
 
     /// Insert barriers into the command list
@@ -1532,6 +2696,25 @@ impl CopyCommandList {
         self.handle.copy_buffer(src, dst);
     }
 
+    //==============================================================
+
+    /// Write a GPU timestamp to the query at `index` in the query pool
+    /// 
+    /// The query pool needs to be a `QueryType::CopyQueueTimestamp` query pool
+    pub fn write_timestamp(&self, query_pool: &QueryPoolHandle, index: u32) {
+        self.handle.write_timestamp(query_pool, index);
+    }
+
+    /// Reset `count` queries, starting at `first_query`
+    pub fn reset_queries(&self, query_pool: &QueryPoolHandle, first_query: u32, count: u32) {
+        self.handle.reset_queries(query_pool, first_query, count);
+    }
+
+    /// Resolve `count` queries, starting at `first_query`, into `dst` at `dst_offset`
+    pub fn resolve_queries(&self, query_pool: &QueryPoolHandle, first_query: u32, count: u32, dst: &BufferHandle, dst_offset: u64) {
+        self.handle.resolve_queries(query_pool, first_query, count, dst, dst_offset);
+    }
+
 }
 
 impl AsRef<Handle<CommandList>> for CopyCommandList {
@@ -1577,6 +2760,28 @@ impl BundleCommandList {
         self.handle.close()
     }
 
+    /// Set the debug name, which will show up in graphics debuggers and validation messages
+    pub fn set_debug_name(&self, name: &str) {
+        self.handle.set_debug_name(name);
+    }
+
+    //==============================================================
+
+    /// Begin a named debug event, which will show up in graphics debuggers, events can be nested
+    pub fn begin_event(&self, name: &str, color: [f32; 4]) {
+        self.handle.begin_event(name, color);
+    }
+
+    /// End the last debug event
+    pub fn end_event(&self) {
+        self.handle.end_event();
+    }
+
+    /// Insert a named debug marker, which will show up in graphics debuggers
+    pub fn set_marker(&self, name: &str, color: [f32; 4]) {
+        self.handle.set_marker(name, color);
+    }
+
     //==============================================================
 
     //==============================================================
@@ -1591,6 +2796,21 @@ impl BundleCommandList {
         self.handle.bind_compute_pipeline(pipeline)
     }
 
+    /// Set 32-bit constants in the constant range at 'index' in the current bound pipeline, starting at `offset` (in 32-bit values)
+    pub fn set_compute_constants(&self, index: u32, offset: u32, data: &[u32]) {
+        self.handle.set_compute_constants(index, offset, data)
+    }
+
+    /// Dispatch `group_count_x * group_count_y * group_count_z` compute workgroups
+    pub fn dispatch(&self, group_count_x: u32, group_count_y: u32, group_count_z: u32) {
+        self.handle.dispatch(group_count_x, group_count_y, group_count_z)
+    }
+
+    /// Dispatch compute workgroups, with the workgroup counts read from `buffer` at `offset`, laid out as `DispatchIndirectArgs`
+    pub fn dispatch_indirect(&self, buffer: &BufferHandle, offset: u64) {
+        self.handle.dispatch_indirect(buffer, offset)
+    }
+
     //==============================================================
     
     /// Bind a graphics pipeline layout
@@ -1602,6 +2822,11 @@ impl BundleCommandList {
     pub fn bind_graphics_pipeline(&self, pipeline: &PipelineHandle) {
         self.handle.bind_graphics_pipeline(pipeline)
     }
+
+    /// Set 32-bit constants in the constant range at 'index' in the current bound pipeline, starting at `offset` (in 32-bit values)
+    pub fn set_graphics_constants(&self, index: u32, offset: u32, data: &[u32]) {
+        self.handle.set_graphics_constants(index, offset, data)
+    }
     
     /// Bind a vertex buffer to the pipeline
     pub fn bind_vertex_buffer(&self, view: VertexBufferView) {
@@ -1628,6 +2853,13 @@ impl BundleCommandList {
         self.handle.set_primitive_topology(topology);
     }
 
+    /// Set the per-draw shading rate
+    /// 
+    /// `combiners[0]` combines the per-draw rate with the per-primitive rate, `combiners[1]` combines that result with the rate of the shading rate attachment
+    pub fn set_shading_rate(&self, rate: ShadingRate, combiners: [ShadingRateCombiner; 2]) {
+        self.handle.set_shading_rate(rate, combiners);
+    }
+
     /// Draw `vertex_count` vertices, with the first vertex starting at `start_vertex`
     pub fn draw(&self, vertex_count: u32, start_vertex: u32) {
         self.handle.draw_instanced(vertex_count, 1, start_vertex, 0);
@@ -1637,6 +2869,48 @@ impl BundleCommandList {
     pub fn draw_instanced(&self, vertex_count: u32, instance_count: u32, start_vertex: u32, start_instance: u32) {
         self.handle.draw_instanced(vertex_count, instance_count, start_vertex, start_instance);
     }
+
+    /// Draw using the arguments read from `buffer` at `offset`, laid out as `DrawIndirectArgs`
+    pub fn draw_indirect(&self, buffer: &BufferHandle, offset: u64) {
+        self.handle.draw_indirect(buffer, offset, 1);
+    }
+
+    /// Draw `draw_count` times, using the arguments read from `buffer` at `offset`, laid out as a tightly packed array of `DrawIndirectArgs`
+    pub fn multi_draw_indirect(&self, buffer: &BufferHandle, offset: u64, draw_count: u32) {
+        self.handle.draw_indirect(buffer, offset, draw_count);
+    }
+
+    /// Draw using the arguments read from `buffer` at `offset`, laid out as a tightly packed array of `DrawIndirectArgs`,
+    /// with the number of draws read as a `u32` from `count_buffer` at `count_offset`, clamped to `max_draw_count`
+    pub fn multi_draw_indirect_count(&self, buffer: &BufferHandle, offset: u64, count_buffer: &BufferHandle, count_offset: u64, max_draw_count: u32) {
+        self.handle.draw_indirect_count(buffer, offset, count_buffer, count_offset, max_draw_count);
+    }
+
+    /// Draw indexed using the arguments read from `buffer` at `offset`, laid out as `DrawIndexedIndirectArgs`
+    pub fn draw_indexed_indirect(&self, buffer: &BufferHandle, offset: u64) {
+        self.handle.draw_indexed_indirect(buffer, offset, 1);
+    }
+
+    /// Draw indexed `draw_count` times, using the arguments read from `buffer` at `offset`, laid out as a tightly packed array of `DrawIndexedIndirectArgs`
+    pub fn multi_draw_indexed_indirect(&self, buffer: &BufferHandle, offset: u64, draw_count: u32) {
+        self.handle.draw_indexed_indirect(buffer, offset, draw_count);
+    }
+
+    /// Draw indexed using the arguments read from `buffer` at `offset`, laid out as a tightly packed array of `DrawIndexedIndirectArgs`,
+    /// with the number of draws read as a `u32` from `count_buffer` at `count_offset`, clamped to `max_draw_count`
+    pub fn multi_draw_indexed_indirect_count(&self, buffer: &BufferHandle, offset: u64, count_buffer: &BufferHandle, count_offset: u64, max_draw_count: u32) {
+        self.handle.draw_indexed_indirect_count(buffer, offset, count_buffer, count_offset, max_draw_count);
+    }
+
+    /// Dispatch `group_count_x * group_count_y * group_count_z` mesh shader workgroups, or task shader workgroups if the pipeline contains a task shader
+    pub fn dispatch_mesh(&self, group_count_x: u32, group_count_y: u32, group_count_z: u32) {
+        self.handle.dispatch_mesh(group_count_x, group_count_y, group_count_z)
+    }
+
+    /// Dispatch mesh (or task) shader workgroups, with the workgroup counts read from `buffer` at `offset`, laid out as `DispatchIndirectArgs`
+    pub fn dispatch_mesh_indirect(&self, buffer: &BufferHandle, offset: u64) {
+        self.handle.dispatch_mesh_indirect(buffer, offset)
+    }
 }
 
 impl AsRef<Handle<CommandList>> for BundleCommandList {
@@ -1,7 +1,11 @@
-use core::fmt;
-use onca_common::prelude::*;
+use core::{fmt, sync::atomic::{AtomicU64, Ordering}};
+use onca_common::{prelude::*, time::Duration};
 
-use crate::{handle::{InterfaceHandle, create_ral_handle}, Handle, Result, CommandList, Error, CommandListSubmitInfo, api, HandleImpl, CommandListState};
+use crate::{
+    handle::{InterfaceHandle, create_ral_handle},
+    Handle, WeakHandle, Result, CommandList, Error, CommandListSubmitInfo, api, HandleImpl, CommandListState, TimestampCalibration, Device, track_device_lost,
+    DeviceHandle, FenceHandle, FenceWaitSubmitInfo, FenceSignalSubmitInfo, SyncPoint, SparseTexture, SparseTileMapping,
+};
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct QueueIndex(u8);
@@ -28,6 +32,15 @@ pub trait CommandQueueInterface {
 
     /// Submit a command list and execute it, all wait fences need to be signalled to the correct value to proceed, and all signal fences will be signalled on submit
     unsafe fn submit(&self, batches: &[api::SubmitBatch]) -> Result<()>;
+
+    /// Update the tile mappings of a sparse texture
+    unsafe fn update_tile_mappings(&self, texture: &SparseTexture, mappings: &[SparseTileMapping]) -> Result<()>;
+
+    /// Get a pair of correlated CPU and GPU timestamps, together with the frequency of both clocks
+    unsafe fn get_timestamp_calibration(&self) -> Result<TimestampCalibration>;
+
+    /// Set the debug name of the command queue
+    unsafe fn set_debug_name(&self, name: &str);
 }
 
 pub type CommandQueueInterfaceHandle = InterfaceHandle<dyn CommandQueueInterface>;
@@ -35,22 +48,27 @@ pub type CommandQueueInterfaceHandle = InterfaceHandle<dyn CommandQueueInterface
 pub struct CommandQueue {
     pub handle: CommandQueueInterfaceHandle,
     pub index:  QueueIndex,
+    device:     WeakHandle<Device>,
 }
 create_ral_handle!(CommandQueueHandle, CommandQueue, CommandQueueInterfaceHandle);
 
 impl CommandQueueHandle {
+    pub(crate) fn create(handle: CommandQueueInterfaceHandle, index: QueueIndex, device: WeakHandle<Device>) -> Self {
+        Self::new(CommandQueue { handle, index, device })
+    }
+
     /// Flush all work on this queue
     /// 
     /// Prefer synchronization using fences
     pub fn flush(&self) -> Result<()> {
-        unsafe { self.handle.flush() }
+        track_device_lost(&self.device, unsafe { self.handle.flush() })
     }
 
     pub fn submit<T: AsRef<Handle<CommandList>>>(&self, submit_info: &CommandListSubmitInfo<'_, T>) -> Result<()> {
         scoped_alloc!(AllocId::TlsTemp);
 
         let batch = submit_info_to_batch_and_validate(submit_info, self.index)?;
-        unsafe { self.handle.submit(&[batch]) }
+        track_device_lost(&self.device, unsafe { self.handle.submit(&[batch]) })
     }
 
     /// Submit multiple batches of command lists
@@ -65,7 +83,119 @@ impl CommandQueueHandle {
             submit_batches.push(submit_info_to_batch_and_validate(submit_info, self.index)?)
         }
 
-        unsafe { self.handle.submit(&submit_batches) }
+        track_device_lost(&self.device, unsafe { self.handle.submit(&submit_batches) })
+    }
+
+    /// Submit command lists which only start executing after all `wait_for` sync points are reached, and signal a new sync point on `timeline` once they finish
+    /// 
+    /// This is a convenience over `submit` for cross-queue dependencies, e.g. graphics work waiting on a texture upload on the copy queue
+    pub fn submit_with_dependencies<T: AsRef<Handle<CommandList>>>(&self, command_lists: &[T], wait_for: &[QueueSyncPoint], timeline: &QueueTimeline) -> Result<QueueSyncPoint> {
+        scoped_alloc!(AllocId::TlsTemp);
+
+        let wait_fences = wait_for.iter().map(|sync_point| sync_point.wait_info(SyncPoint::All)).collect::<Vec<_>>();
+        let sync_point = timeline.next_sync_point();
+        let signal_fences = [sync_point.signal_info(SyncPoint::All)];
+
+        self.submit(&CommandListSubmitInfo {
+            command_lists,
+            wait_fences: Some(&wait_fences[..]),
+            signal_fences: Some(&signal_fences[..]),
+        })?;
+        Ok(sync_point)
+    }
+
+    /// Map or unmap tiles of a sparse texture to memory
+    /// 
+    /// The update is queued on the GPU and this call does not wait for it to finish. The update starts after all work previously submitted to this queue has finished,
+    /// and work submitted to this queue afterwards only starts once the update has finished, so it can access the newly mapped tiles.
+    /// Work on other queues is not ordered with the update, and needs to be synchronized using fences.
+    /// 
+    /// The residency of the texture, as reported by `SparseTexture`, is updated when this call returns, so it already includes tiles that are still being mapped on the GPU.
+    pub fn update_tile_mappings(&self, texture: &SparseTexture, mappings: &[SparseTileMapping]) -> Result<()> {
+        #[cfg(feature = "validation")]
+        {
+            for mapping in mappings {
+                mapping.validate(texture)?;
+            }
+        }
+
+        track_device_lost(&self.device, unsafe { self.handle.update_tile_mappings(texture, mappings) })?;
+        texture.update_residency(mappings);
+        Ok(())
+    }
+
+    /// Get a pair of correlated CPU and GPU timestamps, which can be used to convert resolved timestamp queries into CPU time
+    /// 
+    /// Calibration should be re-queried periodically, as the CPU and GPU clocks may drift apart
+    pub fn get_timestamp_calibration(&self) -> Result<TimestampCalibration> {
+        unsafe { self.handle.get_timestamp_calibration() }
+    }
+}
+
+/// Timeline used to express dependencies between work submitted to different queues
+/// 
+/// The queue producing a result signals a sync point, while the queue consuming it waits for that sync point,
+/// this allows e.g. texture uploads on the copy queue or post-processing on the async compute queue to overlap with graphics work.
+pub struct QueueTimeline {
+    fence: FenceHandle,
+    value: AtomicU64,
+}
+
+impl QueueTimeline {
+    /// Create a new queue timeline
+    pub fn new(device: &DeviceHandle) -> Result<Self> {
+        let fence = device.create_fence()?;
+        let value = fence.get_value()?;
+        Ok(Self { fence, value: AtomicU64::new(value) })
+    }
+
+    /// Get the next sync point on the timeline, which needs to be signalled by exactly 1 submit
+    /// 
+    /// Sync points need to be signalled in the order they were created
+    pub fn next_sync_point(&self) -> QueueSyncPoint {
+        let value = self.value.fetch_add(1, Ordering::AcqRel) + 1;
+        QueueSyncPoint { fence: self.fence.clone(), value }
+    }
+
+    /// Get the value of the last sync point that was reached
+    pub fn completed_value(&self) -> Result<u64> {
+        self.fence.get_value()
+    }
+}
+
+/// Point on a `QueueTimeline` that is signalled by 1 queue and can be waited on by other queues or the CPU
+#[derive(Clone)]
+pub struct QueueSyncPoint {
+    fence: FenceHandle,
+    value: u64,
+}
+
+impl QueueSyncPoint {
+    /// Get the timeline value of the sync point
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// Get the fence signal info to signal this sync point when submitting
+    pub fn signal_info(&self, sync_point: SyncPoint) -> FenceSignalSubmitInfo {
+        FenceSignalSubmitInfo { fence: self.fence.clone(), value: self.value, sync_point }
+    }
+
+    /// Get the fence wait info to wait for this sync point when submitting
+    pub fn wait_info(&self, sync_point: SyncPoint) -> FenceWaitSubmitInfo {
+        FenceWaitSubmitInfo { fence: self.fence.clone(), value: self.value, sync_point }
+    }
+
+    /// Check if the sync point was reached
+    pub fn is_complete(&self) -> Result<bool> {
+        Ok(self.fence.get_value()? >= self.value)
+    }
+
+    /// Wait on the CPU until the sync point is reached
+    /// 
+    /// Returns result with `Ok(true)` when the sync point was reached, and `Ok(false)` if the wait hit a timeout
+    pub fn wait(&self, timeout: Duration) -> Result<bool> {
+        self.fence.wait(self.value, timeout)
     }
 }
 
@@ -79,6 +209,10 @@ fn submit_info_to_batch_and_validate<'a, T: AsRef<Handle<CommandList>>>(submit_i
             if validation.state != CommandListState::Closed {
                 return Err(Error::CommandList("Cannot submit a command buffer that isn't closed"));
             }
+            if let Err(msg) = validation.tracker.check_lifetimes() {
+                list.report_validation_issue("submit", &msg);
+                return Err(Error::ExpiredHandle("Resource used by the submitted command list"));
+            }
             validation.state = CommandListState::Submitted;
         }
     }
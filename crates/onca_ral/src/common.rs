@@ -218,6 +218,124 @@ pub enum SwapChainAlphaMode {
     Unspecified,
 }
 
+/// Swap-chain color space, i.e. how the output will interpret the values in the backbuffer
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, EnumDisplay)]
+pub enum SwapChainColorSpace {
+    /// sRGB primaries with the sRGB transfer function (SDR)
+    #[default]
+    SrgbNonLinear,
+    /// sRGB primaries with a linear transfer function, where values above 1.0 represent HDR values (scRGB)
+    /// 
+    /// Commonly used with a `Format::R16G16B16A16SFloat` backbuffer
+    ExtendedSrgbLinear,
+    /// BT.2020 primaries with the ST.2084 perceptual quantizer transfer function (HDR10)
+    /// 
+    /// Commonly used with a `Format::R10G10B10A2UNorm` backbuffer
+    Hdr10St2084,
+}
+
+impl SwapChainColorSpace {
+    /// Check if the color space can be used to output HDR content
+    pub fn is_hdr(self) -> bool {
+        !matches!(self, SwapChainColorSpace::SrgbNonLinear)
+    }
+}
+
+/// HDR metadata, describing the display the content was mastered on and the light levels of the content
+/// 
+/// Chromaticity coordinates are in CIE 1931 xy, luminance values are in nits (cd/m^2)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct HdrMetadata {
+    /// Chromaticity of the red primary of the mastering display
+    pub red_primary:                   [f32; 2],
+    /// Chromaticity of the green primary of the mastering display
+    pub green_primary:                 [f32; 2],
+    /// Chromaticity of the blue primary of the mastering display
+    pub blue_primary:                  [f32; 2],
+    /// Chromaticity of the white point of the mastering display
+    pub white_point:                   [f32; 2],
+    /// Maximum luminance of the mastering display
+    pub max_mastering_luminance:       f32,
+    /// Minimum luminance of the mastering display
+    pub min_mastering_luminance:       f32,
+    /// Maximum content light level (MaxCLL)
+    pub max_content_light_level:       f32,
+    /// Maximum frame average light level (MaxFALL)
+    pub max_frame_average_light_level: f32,
+}
+
+impl HdrMetadata {
+    /// Create HDR metadata using BT.2020 primaries and a D65 white point, with the given luminance range
+    pub fn bt2020(max_luminance: f32, min_luminance: f32) -> Self {
+        Self {
+            red_primary: [0.708, 0.292],
+            green_primary: [0.170, 0.797],
+            blue_primary: [0.131, 0.046],
+            white_point: [0.3127, 0.3290],
+            max_mastering_luminance: max_luminance,
+            min_mastering_luminance: min_luminance,
+            max_content_light_level: max_luminance,
+            max_frame_average_light_level: max_luminance,
+        }
+    }
+}
+
+/// HDR capabilities of an output (monitor)
+/// 
+/// Chromaticity coordinates are in CIE 1931 xy, luminance values are in nits (cd/m^2)
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct OutputHdrInfo {
+    /// Chromaticity of the red primary of the output
+    pub red_primary:              [f32; 2],
+    /// Chromaticity of the green primary of the output
+    pub green_primary:            [f32; 2],
+    /// Chromaticity of the blue primary of the output
+    pub blue_primary:             [f32; 2],
+    /// Chromaticity of the white point of the output
+    pub white_point:              [f32; 2],
+    /// Minimum luminance of the output
+    pub min_luminance:            f32,
+    /// Maximum luminance of the output, only achievable for a small area of the output
+    pub max_luminance:            f32,
+    /// Maximum luminance the output can sustain for a full frame
+    pub max_full_frame_luminance: f32,
+}
+
+/// Capabilities of the output (monitor) a swap-chain is presented to
+#[derive(Clone, PartialEq, Debug)]
+pub struct SwapChainOutputInfo {
+    /// Color spaces that can be presented to the output
+    pub color_spaces:   Vec<SwapChainColorSpace>,
+    /// Number of bits per color component the output supports, 0 if unknown
+    pub bits_per_color: u8,
+    /// HDR capabilities of the output, `None` if the output does not support HDR, or if the API cannot query them
+    pub hdr:            Option<OutputHdrInfo>,
+}
+
+impl SwapChainOutputInfo {
+    /// Check if the output supports a given color space
+    pub fn supports_color_space(&self, color_space: SwapChainColorSpace) -> bool {
+        self.color_spaces.contains(&color_space)
+    }
+}
+
+/// Present statistics of a swap-chain
+/// 
+/// The present time is a CPU timestamp in ticks of the platform's high resolution clock on all APIs, which is the same unit as `TimestampCalibration::cpu_timestamp`
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct PresentStatistics {
+    /// Number of presents that have been displayed
+    pub present_count:         u32,
+    /// Number of vertical blanks that happened before the last present was displayed
+    pub present_refresh_count: u32,
+    /// CPU timestamp at which the last present was displayed
+    pub present_time:          u64,
+    /// Number of vertical blanks a frame is displayed for, 0 when frames are presented immediately
+    pub sync_interval:         u32,
+    /// Number of frames that were displayed later than expected, since the swap-chain was created or resized
+    pub dropped_frames:        u32,
+}
+
 /// Present scroll rectangle
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct PresentScrollRect {
@@ -296,11 +414,25 @@ pub struct MemoryBudgetValue {
 }
 
 /// Memory info for current state of memory
+#[derive(Clone, Copy, Debug, Default)]
 pub struct MemoryBudgetInfo {
 	pub budgets: [MemoryBudgetValue; MemoryHeapType::COUNT],
 	pub total:   MemoryBudgetValue,
 }
 
+impl MemoryBudgetInfo {
+	/// Check if the memory in use on a heap exceeds its budget
+	pub fn is_over_budget(&self, heap_type: MemoryHeapType) -> bool {
+		let value = &self.budgets[heap_type as usize];
+		value.in_use > value.budget
+	}
+
+	/// Check if the budget of any heap differs from the budget in `other`, or if any heap went over or back under its budget
+	pub fn has_budget_changed(&self, other: &MemoryBudgetInfo) -> bool {
+		self.budgets.iter().zip(other.budgets.iter()).any(|(a, b)| a.budget != b.budget || (a.in_use > a.budget) != (b.in_use > b.budget))
+	}
+}
+
 /// Memory flags
 #[flags]
 pub enum MemoryTypeFlags {
@@ -510,6 +642,78 @@ pub struct BufferCopyRegion {
 // TEXTURES
 //==============================================================================================================================
 
+/// Number of samples per pixel
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default, EnumDisplay)]
+pub enum SampleCount {
+    /// 1 sample per pixel, i.e. not multisampled
+    #[default]
+    Sample1  = 1,
+    /// 2 samples per pixel
+    Sample2  = 2,
+    /// 4 samples per pixel
+    Sample4  = 4,
+    /// 8 samples per pixel
+    Sample8  = 8,
+    /// 16 samples per pixel
+    Sample16 = 16,
+}
+
+impl SampleCount {
+    /// Get the number of samples
+    pub fn get(self) -> u8 {
+        self as u8
+    }
+
+    /// Check if the sample count represents a multisampled resource
+    pub fn is_multisampled(self) -> bool {
+        self != SampleCount::Sample1
+    }
+}
+
+/// Supported sample counts
+#[flags]
+pub enum SampleCountSupport {
+    /// 1 sample per pixel is supported
+    Sample1,
+    /// 2 samples per pixel are supported
+    Sample2,
+    /// 4 samples per pixel are supported
+    Sample4,
+    /// 8 samples per pixel are supported
+    Sample8,
+    /// 16 samples per pixel are supported
+    Sample16,
+}
+
+impl SampleCountSupport {
+    /// Check if a given sample count is supported
+    pub fn supports(self, samples: SampleCount) -> bool {
+        let flag = match samples {
+            SampleCount::Sample1  => SampleCountSupport::Sample1,
+            SampleCount::Sample2  => SampleCountSupport::Sample2,
+            SampleCount::Sample4  => SampleCountSupport::Sample4,
+            SampleCount::Sample8  => SampleCountSupport::Sample8,
+            SampleCount::Sample16 => SampleCountSupport::Sample16,
+        };
+        self.contains(flag)
+    }
+
+    /// Get the highest supported sample count
+    pub fn max_sample_count(self) -> SampleCount {
+        if self.contains(SampleCountSupport::Sample16) {
+            SampleCount::Sample16
+        } else if self.contains(SampleCountSupport::Sample8) {
+            SampleCount::Sample8
+        } else if self.contains(SampleCountSupport::Sample4) {
+            SampleCount::Sample4
+        } else if self.contains(SampleCountSupport::Sample2) {
+            SampleCount::Sample2
+        } else {
+            SampleCount::Sample1
+        }
+    }
+}
+
 /// View into a region of a texture to copy
 #[derive(Clone, Copy, Debug)]
 pub struct TextureCopyView {
@@ -751,8 +955,8 @@ impl BufferTextureRegion {
                 let texel_align_mask = !(texel_align as u64 - 1);
                 check_invalid_parameter!(pitch % texel_align_mask != 0, "The buffer row pitch needs to be a power of 2");
 
-                let tex_height = texture.size().height();
-                check_invalid_parameter!(height >= tex_height as u64, "Buffer height, i.e. num rows per 2D slice, must be greater or equal to the the heigh of the texture, buffer height: {height}, texture height {tex_height}");
+                let copy_height = self.texture_view.extent.height.get();
+                check_invalid_parameter!(height >= copy_height as u64, "Buffer height, i.e. num rows per 2D slice, must be greater or equal to the the height of the copied region, buffer height: {height}, region height {copy_height}");
             }
         }
         Ok(())
@@ -879,6 +1083,8 @@ pub enum ShaderType {
     Vertex,
     // Pixel/fragment shader
     Pixel,
+    // Compute shader
+    Compute,
     // Task shader
     Task,
     // Mesh shader
@@ -904,6 +1110,8 @@ pub enum ShaderTypeMask {
     Vertex,
     // Pixel/fragment shader
     Pixel,
+    // Compute shader
+    Compute,
     // Task/amplification shader
     Task,
     // Mesh shaders
@@ -1918,6 +2126,34 @@ impl Default for InputLayout {
     }
 }
 
+/// Multisample state
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MultisampleState {
+    /// Number of samples per pixel, this needs to match the sample count of the render targets and depth stencil the pipeline renders to
+    pub samples:           SampleCount,
+    /// Mask of samples that can be written, each bit represents a sample
+    pub sample_mask:       u32,
+    /// Use the alpha of the first render target as a coverage mask
+    pub alpha_to_coverage: bool,
+}
+
+impl MultisampleState {
+    /// Create a multisample state with a given sample count, writing all samples
+    pub fn new(samples: SampleCount) -> Self {
+        Self {
+            samples,
+            sample_mask: u32::MAX,
+            alpha_to_coverage: false,
+        }
+    }
+}
+
+impl Default for MultisampleState {
+    fn default() -> Self {
+        Self::new(SampleCount::Sample1)
+    }
+}
+
 /// Primitive restart
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
 pub enum PrimitiveRestart {
@@ -1945,6 +2181,8 @@ pub struct GraphicsPipelineDesc {
     pub depth_stencil_state:  DepthStencilState,
     /// Blend state
     pub blend_state:          BlendState,
+    /// Multisample state
+    pub multisample_state:    MultisampleState,
     /// Input state
     pub input_layout:         Option<InputLayout>,
     /// Render targer formats
@@ -1974,6 +2212,9 @@ impl GraphicsPipelineDesc {
                 
                 input_layout.validate()?;
             }
+            if self.multisample_state.alpha_to_coverage {
+                check_invalid_parameter!(self.rendertarget_formats[0].is_some(), "Alpha to coverage requires the pipeline to have a render target at index 0");
+            }
         }
         Ok(())
     }
@@ -1986,6 +2227,7 @@ impl PartialEq for GraphicsPipelineDesc {
         self.rasterizer_state == other.rasterizer_state &&
         self.depth_stencil_state == other.depth_stencil_state &&
         self.blend_state == other.blend_state &&
+        self.multisample_state == other.multisample_state &&
         self.input_layout == other.input_layout &&
         Handle::ptr_eq(&self.vertex_shader, &other.vertex_shader) &&
         Handle::ptr_eq(&self.pixel_shader, &other.pixel_shader)
@@ -1994,13 +2236,15 @@ impl PartialEq for GraphicsPipelineDesc {
 
 /// Mesh graphics pipeline description
 #[derive(Clone)]
-pub struct MeshPipelineDescription {
+pub struct MeshPipelineDesc {
     /// Rasterizer state
     pub rasterizer_state:     RasterizerState,
     /// Depth stencil state
     pub depth_stencil_state:  DepthStencilState,
     /// Blend state
     pub blend_state:          BlendState,
+    /// Multisample state
+    pub multisample_state:    MultisampleState,
     /// Render targer formats
     pub rendertarget_formats: [Option<Format>; constants::MAX_RENDERTARGETS as usize],
     /// Depth stencil formats
@@ -2017,15 +2261,73 @@ pub struct MeshPipelineDescription {
     pub pipeline_layout:      PipelineLayoutHandle,
 }
 
+impl MeshPipelineDesc {
+    pub fn validate(&self) -> Result<()> {
+        #[cfg(feature = "validation")]
+        {
+            if let Some(task_shader) = &self.task_shader {
+                let shader_type = task_shader.shader_type();
+                if shader_type != ShaderType::Task {
+                    return Err(Error::InvalidParameter(format!("Mesh pipeline requires the task shader to be a task shader, found a '{shader_type:?}' shader")));
+                }
+            }
+
+            let shader_type = self.mesh_shader.shader_type();
+            if shader_type != ShaderType::Mesh {
+                return Err(Error::InvalidParameter(format!("Mesh pipeline requires a mesh shader, found a '{shader_type:?}' shader")));
+            }
 
-impl PartialEq for MeshPipelineDescription {
+            let shader_type = self.pixel_shader.shader_type();
+            if shader_type != ShaderType::Pixel {
+                return Err(Error::InvalidParameter(format!("Mesh pipeline requires a pixel shader, found a '{shader_type:?}' shader")));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for MeshPipelineDesc {
     fn eq(&self, other: &Self) -> bool {
         self.rasterizer_state == other.rasterizer_state &&
         self.depth_stencil_state == other.depth_stencil_state &&
         self.blend_state == other.blend_state &&
+        self.multisample_state == other.multisample_state &&
         Handle::ptr_eq(&self.mesh_shader, &other.mesh_shader) &&
         Handle::ptr_eq(&self.pixel_shader, &other.pixel_shader) &&
-        self.task_shader.as_ref().map_or(false, |task0| other.task_shader.as_ref().map_or(false, |task1| Handle::ptr_eq(task0, task1)))
+        match (&self.task_shader, &other.task_shader) {
+            (Some(task0), Some(task1)) => Handle::ptr_eq(task0, task1),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Compute pipeline description
+#[derive(Clone)]
+pub struct ComputePipelineDesc {
+    /// Compute shader
+    pub compute_shader:  ShaderHandle,
+    /// Pipeline layout
+    pub pipeline_layout: PipelineLayoutHandle,
+}
+
+impl ComputePipelineDesc {
+    pub fn validate(&self) -> Result<()> {
+        #[cfg(feature = "validation")]
+        {
+            let shader_type = self.compute_shader.shader_type();
+            if shader_type != ShaderType::Compute {
+                return Err(Error::InvalidParameter(format!("Compute pipeline requires a compute shader, found a '{shader_type:?}' shader")));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl PartialEq for ComputePipelineDesc {
+    fn eq(&self, other: &Self) -> bool {
+        Handle::ptr_eq(&self.compute_shader, &other.compute_shader) &&
+        Handle::ptr_eq(&self.pipeline_layout, &other.pipeline_layout)
     }
 }
 
@@ -2587,6 +2889,8 @@ impl ResourceState {
     
     // Texture resource states with limited access, sync point, and layout combinations
     
+    /// Undefined texture resource state, the content of the texture is discarded when transitioning from this state
+    pub const UNDEFINED_TEX : ResourceState = ResourceState::new_tex(Access::None, SyncPoint::Top, TextureLayout::Undefined);
     /// Render target read resource state
     pub const RENDER_TARGET_READ : ResourceState = ResourceState::new_tex(Access::RenderTargetRead, SyncPoint::RenderTarget, TextureLayout::RenderTarget);
     /// Render target write resource state
@@ -2644,6 +2948,7 @@ impl BitOrAssign for ResourceState {
 }
 
 /// Queue transfer operation
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum BarrierQueueTransferOp {
     /// No queue transfer operation
     None, 
@@ -2705,6 +3010,28 @@ impl Barrier {
         }
     }
 
+    /// Create the pair of barriers needed to transfer ownership of a range in a `Buffer` from 1 queue to another
+    /// 
+    /// The first barrier releases the buffer and needs to be recorded on a command list submitted to `src_queue`,
+    /// the second barrier acquires the buffer and needs to be recorded on a command list submitted to `dst_queue`, after the release has finished (e.g. using a `QueueSyncPoint`)
+    pub fn buffer_queue_transfer(before: ResourceState, after: ResourceState, buffer: BufferHandle, offset: u64, size: u64, src_queue: QueueIndex, dst_queue: QueueIndex) -> [Barrier; 2] {
+        [
+            Barrier::Buffer { before, after, buffer: buffer.clone(), offset, size, queue_transfer_op: BarrierQueueTransferOp::To(dst_queue) },
+            Barrier::Buffer { before, after, buffer, offset, size, queue_transfer_op: BarrierQueueTransferOp::From(src_queue) },
+        ]
+    }
+
+    /// Create the pair of barriers needed to transfer ownership of a `Texture` from 1 queue to another
+    /// 
+    /// The first barrier releases the texture and needs to be recorded on a command list submitted to `src_queue`,
+    /// the second barrier acquires the texture and needs to be recorded on a command list submitted to `dst_queue`, after the release has finished (e.g. using a `QueueSyncPoint`)
+    pub fn texture_queue_transfer(before: ResourceState, after: ResourceState, texture: TextureHandle, subresource_range: Option<TextureSubresourceRange>, src_queue: QueueIndex, dst_queue: QueueIndex) -> [Barrier; 2] {
+        [
+            Barrier::Texture { before, after, texture: texture.clone(), subresource_range, queue_transfer_op: BarrierQueueTransferOp::To(dst_queue) },
+            Barrier::Texture { before, after, texture, subresource_range, queue_transfer_op: BarrierQueueTransferOp::From(src_queue) },
+        ]
+    }
+
     /// Validate the resource barrier
     pub fn validate(&self, list_type: CommandListType, check_for_redudant_barriers: bool) -> Result<()> {
         #[cfg(feature = "validation")]
@@ -2800,6 +3127,50 @@ impl<'a> From<&'a WorkGroupSize> for &'a [u32; 3] {
     }
 }
 
+/// Arguments of an indirect dispatch, as they are laid out in an indirect argument buffer
+/// 
+/// Indirect mesh dispatches use the same layout
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct DispatchIndirectArgs {
+    /// Number of workgroups in the x dimension
+    pub x: u32,
+    /// Number of workgroups in the y dimension
+    pub y: u32,
+    /// Number of workgroups in the z dimension
+    pub z: u32,
+}
+
+/// Arguments of an indirect draw, as they are laid out in an indirect argument buffer
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct DrawIndirectArgs {
+    /// Number of vertices to draw
+    pub vertex_count:   u32,
+    /// Number of instances to draw
+    pub instance_count: u32,
+    /// Index of the first vertex
+    pub start_vertex:   u32,
+    /// Index of the first instance
+    pub start_instance: u32,
+}
+
+/// Arguments of an indexed indirect draw, as they are laid out in an indirect argument buffer
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct DrawIndexedIndirectArgs {
+    /// Number of indices to draw
+    pub index_count:    u32,
+    /// Number of instances to draw
+    pub instance_count: u32,
+    /// Index of the first index
+    pub start_index:    u32,
+    /// Value added to each index before indexing into the vertex buffer
+    pub vertex_offset:  i32,
+    /// Index of the first instance
+    pub start_instance: u32,
+}
+
 //==============================================================================================================================
 // RENDER PASSES
 //==============================================================================================================================
@@ -2963,6 +3334,8 @@ pub struct RenderingInfo<'a> {
     pub render_targets: &'a [RenderTargetAttachmentDesc],
     /// Depth stencil attachments
     pub depth_stencil:  Option<DepthStencilAttachmentDesc>,
+    /// Shading rate attachment, used to vary the shading rate per screen-space tile
+    pub shading_rate:   Option<ShadingRateAttachmentDesc>,
 }
 
 impl RenderingInfo<'_> {
@@ -2979,6 +3352,9 @@ impl RenderingInfo<'_> {
             if let Some(dsv) = &self.depth_stencil {
                 dsv.validate()?;
             }
+            if let Some(shading_rate) = &self.shading_rate {
+                shading_rate.validate()?;
+            }
         }
         Ok(())
     }
@@ -3012,6 +3388,7 @@ pub struct FrameBufferRenderTargetDesc {
 /// Shading rate
 /// 
 /// definced as `X`x`Y`, where `X` represent the coarse pixel width, and `Y` represent the coarse pixel height
+#[derive(Clone, Copy, PartialEq, Eq, Debug, EnumDisplay)]
 pub enum ShadingRate {
     Rate1x1 = 0b00_00,
     Rate1x2 = 0b00_01,
@@ -3022,6 +3399,65 @@ pub enum ShadingRate {
     Rate4x4 = 0b10_10,
 }
 
+impl ShadingRate {
+    /// Get the size of a coarse pixel, as `(width, height)`
+    pub fn coarse_pixel_size(self) -> (u32, u32) {
+        let val = self as u32;
+        (1 << (val >> 2), 1 << (val & 0b11))
+    }
+
+    /// Check if the shading rate is one of the large shading rates, i.e. 2x4, 4x2, or 4x4
+    pub fn is_large(self) -> bool {
+        matches!(self, ShadingRate::Rate2x4 | ShadingRate::Rate4x2 | ShadingRate::Rate4x4)
+    }
+}
+
+/// Shading rate combiner
+/// 
+/// The final shading rate is calculated as `combiner[1](combiner[0](per-draw rate, per-primitive rate), attachment rate)`
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, EnumDisplay)]
+pub enum ShadingRateCombiner {
+    /// Keep the incoming shading rate, ignoring the new rate
+    #[default]
+    Passthrough,
+    /// Override the incoming shading rate with the new rate
+    Override,
+    /// Use the minimum of the incoming and new shading rate
+    Min,
+    /// Use the maximum of the incoming and new shading rate
+    Max,
+    /// Sum the incoming and new shading rate (in log2 space, meaning that the coarse pixel sizes are multiplied)
+    Sum,
+}
+
+/// Shading rate attachment description
+/// 
+/// Each texel in the attachment defines the shading rate of a tile in the render area, with the tile size defined by `VariableRateShadingSupport::attachment_tile_size`
+#[derive(Clone)]
+pub struct ShadingRateAttachmentDesc {
+    /// View to the shading rate texture, which needs to be created with `TextureUsage::ShadingRate`
+    pub view:   SampledTextureViewHandle,
+    /// Shading rate texture layout
+    pub layout: TextureLayout,
+}
+
+impl ShadingRateAttachmentDesc {
+    pub fn validate(&self) -> Result<()> {
+        #[cfg(feature = "validation")]
+        {
+            if self.layout != TextureLayout::ShadingRate {
+                return Err(Error::InvalidParameter(format!("A shading rate attachment needs to be in the `ShadingRate` layout, found `{}`", self.layout)));
+            }
+            if let Some(texture) = WeakHandle::upgrade(self.view.texture()) {
+                if !texture.usages().contains(TextureUsage::ShadingRate) {
+                    return Err(Error::InvalidParameter("A shading rate attachment needs to be created with `TextureUsage::ShadingRate`".to_string()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 
 //==============================================================================================================================
 // RAYTRACING
@@ -94,6 +94,36 @@ pub enum FormatComponents {
     BC6H,
     /// BC7 block compression
     BC7,
+    /// ASTC 4x4 block compression
+    ///
+    /// ASTC formats are optional, and are only supported when `Capabilities::TextureCompressionASTC` is supported by the physical device
+    ASTC4x4,
+    /// ASTC 5x4 block compression
+    ASTC5x4,
+    /// ASTC 5x5 block compression
+    ASTC5x5,
+    /// ASTC 6x5 block compression
+    ASTC6x5,
+    /// ASTC 6x6 block compression
+    ASTC6x6,
+    /// ASTC 8x5 block compression
+    ASTC8x5,
+    /// ASTC 8x6 block compression
+    ASTC8x6,
+    /// ASTC 8x8 block compression
+    ASTC8x8,
+    /// ASTC 10x5 block compression
+    ASTC10x5,
+    /// ASTC 10x6 block compression
+    ASTC10x6,
+    /// ASTC 10x8 block compression
+    ASTC10x8,
+    /// ASTC 10x10 block compression
+    ASTC10x10,
+    /// ASTC 12x10 block compression
+    ASTC12x10,
+    /// ASTC 12x12 block compression
+    ASTC12x12,
 
     // Special types
     /// Sampler feedback min mip opaque
@@ -101,7 +131,7 @@ pub enum FormatComponents {
     /// Sampler feedback mip region used opaque
     SamplerFeedbackMipRegionUsed
 
-    // NOTE: Currently there are no plans to support ETC2 or ASTC
+    // NOTE: Currently there are no plans to support ETC2 or ASTC HDR
 }
 
 impl FormatComponents {
@@ -125,11 +155,20 @@ impl FormatComponents {
         self.is_block_compressed()
     }
 
-    /// Is the format components a block compression (BC) format?
+    /// Is the format components a block compression (BC or ASTC) format?
     pub fn is_block_compressed(self) -> bool {
         FORMAT_COMPONENTS_INFO[self as usize].is_block_compressed
     }
 
+    /// Is the format components an ASTC format?
+    pub fn is_astc(self) -> bool {
+        matches!(self,
+            FormatComponents::ASTC4x4  | FormatComponents::ASTC5x4   | FormatComponents::ASTC5x5   | FormatComponents::ASTC6x5   | FormatComponents::ASTC6x6 |
+            FormatComponents::ASTC8x5  | FormatComponents::ASTC8x6   | FormatComponents::ASTC8x8   | FormatComponents::ASTC10x5  | FormatComponents::ASTC10x6 |
+            FormatComponents::ASTC10x8 | FormatComponents::ASTC10x10 | FormatComponents::ASTC12x10 | FormatComponents::ASTC12x12
+        )
+    }
+
     /// Is the format components a video format?
     pub fn is_video_format(self) -> bool {
         FORMAT_COMPONENTS_INFO[self as usize].is_video_format
@@ -145,7 +184,7 @@ impl FormatComponents {
         FORMAT_COMPONENTS_INFO[self as usize].bits_per_pixel
     }
 
-    /// Get the size of an element in bytes
+    /// Get the size of an element in bytes, for block compressed formats, this is the size of a single block
     pub fn unit_byte_size(self) -> u8 {
         FORMAT_COMPONENTS_INFO[self as usize].unit_byte_size
     }
@@ -174,7 +213,8 @@ impl FormatComponents {
     /// Does the format support 3D textures
     pub fn supports_3d(self) -> bool {
         let aspect = self.aspect();
-        !aspect.contains(TextureAspect::Depth) && !aspect.contains(TextureAspect::Stencil) && !self.is_video_format()
+        // 3D ASTC textures require sliced 3D support, which is currently not exposed
+        !aspect.contains(TextureAspect::Depth) && !aspect.contains(TextureAspect::Stencil) && !self.is_video_format() && !self.is_astc()
     }
 
     /// Does the format suport cubemap textures
@@ -304,7 +344,7 @@ struct FormatComponentsInfo {
     is_video_format:     bool,
     is_planar:           bool,
     bits_per_pixel:      u16,
-    /// This is different to `bits / 8`, as it returns 0 for formats that can't store individual pixels (i.e. compressed formats)
+    /// This is different to `bits / 8`, as it contains the size of a block for formats that can't store individual pixels (i.e. compressed formats)
     unit_byte_size:      u8,
     num_planes:          u8,
     min_mip_size:        (u8, u8),
@@ -422,6 +462,36 @@ pub enum Format {
     BC7UNorm,
     BC7Srgb,
 
+    // ASTC
+    ASTC4x4UNorm,
+    ASTC4x4Srgb,
+    ASTC5x4UNorm,
+    ASTC5x4Srgb,
+    ASTC5x5UNorm,
+    ASTC5x5Srgb,
+    ASTC6x5UNorm,
+    ASTC6x5Srgb,
+    ASTC6x6UNorm,
+    ASTC6x6Srgb,
+    ASTC8x5UNorm,
+    ASTC8x5Srgb,
+    ASTC8x6UNorm,
+    ASTC8x6Srgb,
+    ASTC8x8UNorm,
+    ASTC8x8Srgb,
+    ASTC10x5UNorm,
+    ASTC10x5Srgb,
+    ASTC10x6UNorm,
+    ASTC10x6Srgb,
+    ASTC10x8UNorm,
+    ASTC10x8Srgb,
+    ASTC10x10UNorm,
+    ASTC10x10Srgb,
+    ASTC12x10UNorm,
+    ASTC12x10Srgb,
+    ASTC12x12UNorm,
+    ASTC12x12Srgb,
+
     // Special types
     SamplerFeedbackMinMipOpaque,
     SamplerFeedbackMipRegionOpaque,
@@ -453,11 +523,16 @@ impl Format {
         self.is_block_compressed()
     }
 
-    /// Is the format a block compression (BC) format?
+    /// Is the format a block compression (BC or ASTC) format?
     pub fn is_block_compressed(self) -> bool {
         self.components().is_block_compressed()
     }
 
+    /// Is the format an ASTC format?
+    pub fn is_astc(self) -> bool {
+        self.components().is_astc()
+    }
+
     /// Is the format a video format?
     pub fn is_video_format(self) -> bool {
         self.components().is_video_format()
@@ -682,6 +757,34 @@ const FORMAT_INFO: [FormatInfo; Format::COUNT] = [
     /* BC7Typeless                    */ FormatInfo::new(FormatComponents::BC7                         , FormatDataType::Typeless, FormatSupport::None                                                  ),
     /* BC7UNorm                       */ FormatInfo::new(FormatComponents::BC7                         , FormatDataType::UNorm   , FormatSupport::Sampled                                               ),
     /* BC7Srgb                        */ FormatInfo::new(FormatComponents::BC7                         , FormatDataType::Srgb    , FormatSupport::Sampled                                               ),
+    /* ASTC4x4UNorm                   */ FormatInfo::new(FormatComponents::ASTC4x4                     , FormatDataType::UNorm   , FormatSupport::Sampled                                               ),
+    /* ASTC4x4Srgb                    */ FormatInfo::new(FormatComponents::ASTC4x4                     , FormatDataType::Srgb    , FormatSupport::Sampled                                               ),
+    /* ASTC5x4UNorm                   */ FormatInfo::new(FormatComponents::ASTC5x4                     , FormatDataType::UNorm   , FormatSupport::Sampled                                               ),
+    /* ASTC5x4Srgb                    */ FormatInfo::new(FormatComponents::ASTC5x4                     , FormatDataType::Srgb    , FormatSupport::Sampled                                               ),
+    /* ASTC5x5UNorm                   */ FormatInfo::new(FormatComponents::ASTC5x5                     , FormatDataType::UNorm   , FormatSupport::Sampled                                               ),
+    /* ASTC5x5Srgb                    */ FormatInfo::new(FormatComponents::ASTC5x5                     , FormatDataType::Srgb    , FormatSupport::Sampled                                               ),
+    /* ASTC6x5UNorm                   */ FormatInfo::new(FormatComponents::ASTC6x5                     , FormatDataType::UNorm   , FormatSupport::Sampled                                               ),
+    /* ASTC6x5Srgb                    */ FormatInfo::new(FormatComponents::ASTC6x5                     , FormatDataType::Srgb    , FormatSupport::Sampled                                               ),
+    /* ASTC6x6UNorm                   */ FormatInfo::new(FormatComponents::ASTC6x6                     , FormatDataType::UNorm   , FormatSupport::Sampled                                               ),
+    /* ASTC6x6Srgb                    */ FormatInfo::new(FormatComponents::ASTC6x6                     , FormatDataType::Srgb    , FormatSupport::Sampled                                               ),
+    /* ASTC8x5UNorm                   */ FormatInfo::new(FormatComponents::ASTC8x5                     , FormatDataType::UNorm   , FormatSupport::Sampled                                               ),
+    /* ASTC8x5Srgb                    */ FormatInfo::new(FormatComponents::ASTC8x5                     , FormatDataType::Srgb    , FormatSupport::Sampled                                               ),
+    /* ASTC8x6UNorm                   */ FormatInfo::new(FormatComponents::ASTC8x6                     , FormatDataType::UNorm   , FormatSupport::Sampled                                               ),
+    /* ASTC8x6Srgb                    */ FormatInfo::new(FormatComponents::ASTC8x6                     , FormatDataType::Srgb    , FormatSupport::Sampled                                               ),
+    /* ASTC8x8UNorm                   */ FormatInfo::new(FormatComponents::ASTC8x8                     , FormatDataType::UNorm   , FormatSupport::Sampled                                               ),
+    /* ASTC8x8Srgb                    */ FormatInfo::new(FormatComponents::ASTC8x8                     , FormatDataType::Srgb    , FormatSupport::Sampled                                               ),
+    /* ASTC10x5UNorm                  */ FormatInfo::new(FormatComponents::ASTC10x5                    , FormatDataType::UNorm   , FormatSupport::Sampled                                               ),
+    /* ASTC10x5Srgb                   */ FormatInfo::new(FormatComponents::ASTC10x5                    , FormatDataType::Srgb    , FormatSupport::Sampled                                               ),
+    /* ASTC10x6UNorm                  */ FormatInfo::new(FormatComponents::ASTC10x6                    , FormatDataType::UNorm   , FormatSupport::Sampled                                               ),
+    /* ASTC10x6Srgb                   */ FormatInfo::new(FormatComponents::ASTC10x6                    , FormatDataType::Srgb    , FormatSupport::Sampled                                               ),
+    /* ASTC10x8UNorm                  */ FormatInfo::new(FormatComponents::ASTC10x8                    , FormatDataType::UNorm   , FormatSupport::Sampled                                               ),
+    /* ASTC10x8Srgb                   */ FormatInfo::new(FormatComponents::ASTC10x8                    , FormatDataType::Srgb    , FormatSupport::Sampled                                               ),
+    /* ASTC10x10UNorm                 */ FormatInfo::new(FormatComponents::ASTC10x10                   , FormatDataType::UNorm   , FormatSupport::Sampled                                               ),
+    /* ASTC10x10Srgb                  */ FormatInfo::new(FormatComponents::ASTC10x10                   , FormatDataType::Srgb    , FormatSupport::Sampled                                               ),
+    /* ASTC12x10UNorm                 */ FormatInfo::new(FormatComponents::ASTC12x10                   , FormatDataType::UNorm   , FormatSupport::Sampled                                               ),
+    /* ASTC12x10Srgb                  */ FormatInfo::new(FormatComponents::ASTC12x10                   , FormatDataType::Srgb    , FormatSupport::Sampled                                               ),
+    /* ASTC12x12UNorm                 */ FormatInfo::new(FormatComponents::ASTC12x12                   , FormatDataType::UNorm   , FormatSupport::Sampled                                               ),
+    /* ASTC12x12Srgb                  */ FormatInfo::new(FormatComponents::ASTC12x12                   , FormatDataType::Srgb    , FormatSupport::Sampled                                               ),
     /* SamplerFeedbackMinMipOpaque    */ FormatInfo::new(FormatComponents::SamplerFeedbackMinMip       , FormatDataType::Typeless, FormatSupport::None                                                  ),
     /* SamplerFeedbackMipRegionOpaque */ FormatInfo::new(FormatComponents::SamplerFeedbackMipRegionUsed, FormatDataType::Typeless, FormatSupport::None                                                  ),
 ];
@@ -704,13 +807,27 @@ const FORMAT_COMPONENTS_INFO: [FormatComponentsInfo; FormatComponents::COUNT] =
     /* D32                                */ FormatComponentsInfo { aspect: TextureAspect::Depth       , is_block_compressed: false, is_video_format: false, bits_per_pixel:  32, unit_byte_size:  4, num_planes: 1, min_mip_size: (1, 1), is_planar: false, swizzle: FormatSwizzle::R                                                                         },
     /* D32S8                              */ FormatComponentsInfo { aspect: TextureAspect::DepthStencil, is_block_compressed: false, is_video_format: false, bits_per_pixel:  40, unit_byte_size:  5, num_planes: 2, min_mip_size: (1, 1), is_planar: false, swizzle: FormatSwizzle::R.bitor(FormatSwizzle::G)                                                 },
     /* S8                                 */ FormatComponentsInfo { aspect: TextureAspect::Stencil     , is_block_compressed: false, is_video_format: false, bits_per_pixel:   8, unit_byte_size:  1, num_planes: 2, min_mip_size: (1, 1), is_planar: false, swizzle: FormatSwizzle::R                                                                         },
-    /* BC1                                */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   4, unit_byte_size:  4, num_planes: 1, min_mip_size: (4, 4), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
-    /* BC2                                */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   8, unit_byte_size:  8, num_planes: 1, min_mip_size: (4, 4), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
-    /* BC3                                */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   8, unit_byte_size:  8, num_planes: 1, min_mip_size: (4, 4), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
-    /* BC4                                */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   4, unit_byte_size:  4, num_planes: 1, min_mip_size: (4, 4), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
-    /* BC5                                */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   8, unit_byte_size:  8, num_planes: 1, min_mip_size: (4, 4), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
-    /* BC6H                               */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   8, unit_byte_size:  8, num_planes: 1, min_mip_size: (4, 4), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
-    /* BC7                                */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   8, unit_byte_size:  8, num_planes: 1, min_mip_size: (4, 4), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* BC1                                */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   4, unit_byte_size:  8, num_planes: 1, min_mip_size: (4, 4), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* BC2                                */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   8, unit_byte_size: 16, num_planes: 1, min_mip_size: (4, 4), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* BC3                                */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   8, unit_byte_size: 16, num_planes: 1, min_mip_size: (4, 4), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* BC4                                */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   4, unit_byte_size:  8, num_planes: 1, min_mip_size: (4, 4), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* BC5                                */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   8, unit_byte_size: 16, num_planes: 1, min_mip_size: (4, 4), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* BC6H                               */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   8, unit_byte_size: 16, num_planes: 1, min_mip_size: (4, 4), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* BC7                                */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   8, unit_byte_size: 16, num_planes: 1, min_mip_size: (4, 4), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* ASTC4x4                            */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   8, unit_byte_size: 16, num_planes: 1, min_mip_size: (4, 4), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* ASTC5x4                            */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   7, unit_byte_size: 16, num_planes: 1, min_mip_size: (5, 4), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* ASTC5x5                            */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   6, unit_byte_size: 16, num_planes: 1, min_mip_size: (5, 5), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* ASTC6x5                            */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   5, unit_byte_size: 16, num_planes: 1, min_mip_size: (6, 5), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* ASTC6x6                            */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   4, unit_byte_size: 16, num_planes: 1, min_mip_size: (6, 6), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* ASTC8x5                            */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   4, unit_byte_size: 16, num_planes: 1, min_mip_size: (8, 5), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* ASTC8x6                            */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   3, unit_byte_size: 16, num_planes: 1, min_mip_size: (8, 6), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* ASTC8x8                            */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   2, unit_byte_size: 16, num_planes: 1, min_mip_size: (8, 8), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* ASTC10x5                           */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   3, unit_byte_size: 16, num_planes: 1, min_mip_size: (10, 5), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* ASTC10x6                           */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   3, unit_byte_size: 16, num_planes: 1, min_mip_size: (10, 6), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* ASTC10x8                           */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   2, unit_byte_size: 16, num_planes: 1, min_mip_size: (10, 8), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* ASTC10x10                          */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   2, unit_byte_size: 16, num_planes: 1, min_mip_size: (10, 10), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* ASTC12x10                          */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   2, unit_byte_size: 16, num_planes: 1, min_mip_size: (12, 10), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
+    /* ASTC12x12                          */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: true , is_video_format: false, bits_per_pixel:   1, unit_byte_size: 16, num_planes: 1, min_mip_size: (12, 12), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
     /* SamplerFeedbackMinMipOpaque        */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: false, is_video_format: false, bits_per_pixel:   0, unit_byte_size:  0, num_planes: 1, min_mip_size: (1, 1), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
     /* SamplerFeedbackMipRegionUsedOpaque */ FormatComponentsInfo { aspect: TextureAspect::Color       , is_block_compressed: false, is_video_format: false, bits_per_pixel:   0, unit_byte_size:  0, num_planes: 1, min_mip_size: (1, 1), is_planar: false, swizzle: FormatSwizzle::None                                                                      },
     ];
@@ -739,7 +856,21 @@ const FORMAT_COMPONENTS_INFO: [FormatComponentsInfo; FormatComponents::COUNT] =
     /* BC4                                */ [Some(Format::BC4Typeless)                   , None                         , None                            , None                          , None                          , Some(Format::BC4UNorm)         , Some(Format::BC4SNorm)         , None                      ],
     /* BC5                                */ [Some(Format::BC5Typeless)                   , None                         , None                            , None                          , None                          , Some(Format::BC5UNorm)         , Some(Format::BC5SNorm)         , None                      ],
     /* BC6H                               */ [Some(Format::BC6HTypeless)                  , Some(Format::BC6HUFloat)     , Some(Format::BC6HSFloat)        , None                          , None                          , None                           , None                           , None                      ],
-    /* BC7                                */ [Some(Format::BC7Typeless)                   , None                         , None                            , None                          , None                          , None                           , None                           , Some(Format::BC7Srgb)     ],
+    /* BC7                                */ [Some(Format::BC7Typeless)                   , None                         , None                            , None                          , None                          , Some(Format::BC7UNorm)         , None                           , Some(Format::BC7Srgb)     ],
+    /* ASTC4x4                            */ [None                                        , None                         , None                            , None                          , None                          , Some(Format::ASTC4x4UNorm)     , None                           , Some(Format::ASTC4x4Srgb) ],
+    /* ASTC5x4                            */ [None                                        , None                         , None                            , None                          , None                          , Some(Format::ASTC5x4UNorm)     , None                           , Some(Format::ASTC5x4Srgb) ],
+    /* ASTC5x5                            */ [None                                        , None                         , None                            , None                          , None                          , Some(Format::ASTC5x5UNorm)     , None                           , Some(Format::ASTC5x5Srgb) ],
+    /* ASTC6x5                            */ [None                                        , None                         , None                            , None                          , None                          , Some(Format::ASTC6x5UNorm)     , None                           , Some(Format::ASTC6x5Srgb) ],
+    /* ASTC6x6                            */ [None                                        , None                         , None                            , None                          , None                          , Some(Format::ASTC6x6UNorm)     , None                           , Some(Format::ASTC6x6Srgb) ],
+    /* ASTC8x5                            */ [None                                        , None                         , None                            , None                          , None                          , Some(Format::ASTC8x5UNorm)     , None                           , Some(Format::ASTC8x5Srgb) ],
+    /* ASTC8x6                            */ [None                                        , None                         , None                            , None                          , None                          , Some(Format::ASTC8x6UNorm)     , None                           , Some(Format::ASTC8x6Srgb) ],
+    /* ASTC8x8                            */ [None                                        , None                         , None                            , None                          , None                          , Some(Format::ASTC8x8UNorm)     , None                           , Some(Format::ASTC8x8Srgb) ],
+    /* ASTC10x5                           */ [None                                        , None                         , None                            , None                          , None                          , Some(Format::ASTC10x5UNorm)    , None                           , Some(Format::ASTC10x5Srgb)],
+    /* ASTC10x6                           */ [None                                        , None                         , None                            , None                          , None                          , Some(Format::ASTC10x6UNorm)    , None                           , Some(Format::ASTC10x6Srgb)],
+    /* ASTC10x8                           */ [None                                        , None                         , None                            , None                          , None                          , Some(Format::ASTC10x8UNorm)    , None                           , Some(Format::ASTC10x8Srgb)],
+    /* ASTC10x10                          */ [None                                        , None                         , None                            , None                          , None                          , Some(Format::ASTC10x10UNorm)   , None                           , Some(Format::ASTC10x10Srgb)],
+    /* ASTC12x10                          */ [None                                        , None                         , None                            , None                          , None                          , Some(Format::ASTC12x10UNorm)   , None                           , Some(Format::ASTC12x10Srgb)],
+    /* ASTC12x12                          */ [None                                        , None                         , None                            , None                          , None                          , Some(Format::ASTC12x12UNorm)   , None                           , Some(Format::ASTC12x12Srgb)],
     /* SamplerFeedbackMinMipOpaque        */ [Some(Format::SamplerFeedbackMinMipOpaque)   , None                         , None                            , None                          , None                          , None                           , None                           , None                      ],
     /* SamplerFeedbackMipRegionUsedOpaque */ [Some(Format::SamplerFeedbackMipRegionOpaque), None                         , None                            , None                          , None                          , None                           , None                           , None                      ],
 ];
@@ -768,6 +899,20 @@ const FORMAT_COMPONENT_VALID_DATATYPES: [&[FormatDataType]; FormatComponents::CO
     /* BC5                                */ &[FormatDataType::Typeless,                                                                                             FormatDataType::UNorm, FormatDataType::SNorm                      ],
     /* BC6H                               */ &[FormatDataType::Typeless, FormatDataType::UFloat, FormatDataType::SFloat                                                                                                                ],
     /* BC7                                */ &[FormatDataType::Typeless,                                                                                             FormatDataType::UNorm,                        FormatDataType::Srgb],
+    /* ASTC4x4                            */ &[                                                                                                                      FormatDataType::UNorm,                        FormatDataType::Srgb],
+    /* ASTC5x4                            */ &[                                                                                                                      FormatDataType::UNorm,                        FormatDataType::Srgb],
+    /* ASTC5x5                            */ &[                                                                                                                      FormatDataType::UNorm,                        FormatDataType::Srgb],
+    /* ASTC6x5                            */ &[                                                                                                                      FormatDataType::UNorm,                        FormatDataType::Srgb],
+    /* ASTC6x6                            */ &[                                                                                                                      FormatDataType::UNorm,                        FormatDataType::Srgb],
+    /* ASTC8x5                            */ &[                                                                                                                      FormatDataType::UNorm,                        FormatDataType::Srgb],
+    /* ASTC8x6                            */ &[                                                                                                                      FormatDataType::UNorm,                        FormatDataType::Srgb],
+    /* ASTC8x8                            */ &[                                                                                                                      FormatDataType::UNorm,                        FormatDataType::Srgb],
+    /* ASTC10x5                           */ &[                                                                                                                      FormatDataType::UNorm,                        FormatDataType::Srgb],
+    /* ASTC10x6                           */ &[                                                                                                                      FormatDataType::UNorm,                        FormatDataType::Srgb],
+    /* ASTC10x8                           */ &[                                                                                                                      FormatDataType::UNorm,                        FormatDataType::Srgb],
+    /* ASTC10x10                          */ &[                                                                                                                      FormatDataType::UNorm,                        FormatDataType::Srgb],
+    /* ASTC12x10                          */ &[                                                                                                                      FormatDataType::UNorm,                        FormatDataType::Srgb],
+    /* ASTC12x12                          */ &[                                                                                                                      FormatDataType::UNorm,                        FormatDataType::Srgb],
     /* SamplerFeedbackMinMipOpaque        */ &[FormatDataType::Typeless                                                                                                                                                                ],
     /* SamplerFeedbackMipRegionUsedOpaque */ &[FormatDataType::Typeless                                                                                                                                                                ],
 ];
\ No newline at end of file
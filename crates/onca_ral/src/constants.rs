@@ -53,12 +53,18 @@ pub const MIN_CONSTANT_TEXEL_BUFFER_OFFSET_ALIGNMENT : u64 = 64;
 pub const MIN_STORAGE_TEXEL_BUFFER_OFFSET_ALIGNMENT : u64 = 64;
 /// Maximum sparse memory address space
 pub const MAX_SPARSE_ADDRESS_SPACE_SIZE: u64 = GiB(1024) as u64 - 1;
+/// Size of a single sparse tile
+pub const SPARSE_TILE_SIZE: u64 = KiB(64) as u64;
 /// Aligment of constant buffer size (size needs to be a multiple of this value)
 pub const CONSTANT_BUFFER_SIZE_ALIGN: MemAlign = MemAlign::new(256);
 /// Optimal texture/buffer copy offset alignment
 pub const OPTIMAL_COPY_OFFSET_ALIGNMENT: MemAlign = MemAlign::new(512);
 /// Optimal texture/buffer copy row pitch alignment
 pub const OPTIMAL_COPY_ROW_PITCH_ALIGNMENT: MemAlign = MemAlign::new(256);
+/// Alignment of the offset of arguments in an indirect argument buffer
+pub const INDIRECT_ARGUMENT_OFFSET_ALIGN: MemAlign = MemAlign::new(4);
+/// Alignment of the offset of the draw count in an indirect count buffer
+pub const INDIRECT_COUNT_OFFSET_ALIGN: MemAlign = MemAlign::new(4);
 
 //==============================================================================================================================
 // PER STAGE LIMITS
@@ -88,6 +94,10 @@ pub const MAX_DESCRIPTOR_ARRAY_SIZE:        u32 = 16;
 /// 
 /// Value is arbitrarily chosen, so can be increased in the future
 pub const MAX_BINDLESS_ARRAY_SIZE:          u32 = 1024;
+/// Maximum number of resource descriptors in a bindless table
+pub const MAX_BINDLESS_RESOURCE_DESCRIPTORS: u32 = 1_000_000;
+/// Maximum number of sampler descriptors in a bindless table
+pub const MAX_BINDLESS_SAMPLER_DESCRIPTORS: u32 = MAX_PIPELINE_DESCRIPTOR_SAMPLERS;
 
 //==============================================================================================================================
 // PIPELINE LIMITS
@@ -117,7 +127,7 @@ pub const MAX_PIPELINE_INLINE_DESCRIPTOR_TOTAL_BLOCK_SIZE : u32 = 3584;
 pub const MAX_PIPELINE_INLINE_DESCRIPTORS:                   u32 = 4;
 /// Maximum number of total descriptors that can be bound to a single pipeline.
 pub const MAX_PIPELINE_BOUND_DESCRIPTORS:                   u32 = 32;
-/// Maximu size of push constants, in bytes
+/// Minimum guaranteed size of push constants, in bytes, see `Properties::max_pipeline_constant_size` for the actual limit of a device
 pub const MAX_PIPELINE_PUSH_CONSTANT_SIZE:                  u32 = 128;
 /// Minimum descriptor table offset alignment (in descriptors)
 pub const MIN_DESCRIPTOR_TABLE_OFFSET_ALIGNMENT:            u32 = 4;
@@ -311,6 +321,10 @@ pub const MAX_RAYTRACE_HIT_ATTRIBUTE_SIZE : u32 = 32;
 
 /// Minimum acceleration structure scratch buffer alignment
 pub const MIN_RAYTRACE_ACCELERATION_STRUCTURE_SCRATCH_ALIGNMENT : MemAlign = MemAlign::new(256);
+/// Alignment of the offset of an acceleration structure in its buffer
+pub const RAYTRACE_ACCELERATION_STRUCTURE_OFFSET_ALIGNMENT : MemAlign = MemAlign::new(256);
+/// Alignment of the instance data used to build a TLAS
+pub const RAYTRACE_INSTANCE_ALIGNMENT : MemAlign = MemAlign::new(16);
 
 /// Maximum stride of a hitgroup
 pub const MAX_RAYTRACE_HITGROUP_STRIDE : u32 = 4096;
@@ -353,4 +367,8 @@ pub const MAX_RENDER_TARGET_VIEWS: u16 = 2048;
 /// Maximum amount of depth stencil views that can exist at any time
 /// 
 /// This value is arbitrarily chosen.
-pub const MAX_DEPTH_STENCIL_VIEWS: u16 = 256;
\ No newline at end of file
+pub const MAX_DEPTH_STENCIL_VIEWS: u16 = 256;
+/// Maximum number of frames that can be queued for presentation on a swap-chain
+/// 
+/// This value matches the maximum frame latency supported by DXGI.
+pub const MAX_FRAME_LATENCY: u8 = 16;
\ No newline at end of file
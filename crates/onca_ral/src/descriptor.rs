@@ -1,6 +1,6 @@
 use core::{num::NonZeroU32, mem::ManuallyDrop};
 
-use onca_common::prelude::*;
+use onca_common::{prelude::*, sync::RwLock};
 use onca_common_macros::{EnumDisplay, EnumCount};
 
 use crate::{
@@ -246,7 +246,8 @@ impl DescriptorTableDesc {
 }
 
 pub trait DescriptorTableLayoutInterface {
-
+    /// Set the debug name of the descriptor table layout
+    unsafe fn set_debug_name(&self, name: &str);
 }
 pub type DescriptorTableLayoutInterfaceHandle = InterfaceHandle<dyn DescriptorTableLayoutInterface>;
 
@@ -328,6 +329,9 @@ pub trait DescriptorHeapInterface {
     unsafe fn write_ro_texel_buffer(&self, index: u32, buffer: &BufferHandle, desc: TexelBufferViewDesc);
     /// Write a read/write texel buffer to a given descriptor
     unsafe fn write_rw_texel_buffer(&self, index: u32, buffer: &BufferHandle, desc: TexelBufferViewDesc);
+
+    /// Set the debug name of the descriptor heap
+    unsafe fn set_debug_name(&self, name: &str);
 }
 
 pub type DescriptorHeapInterfaceHandle = InterfaceHandle<dyn DescriptorHeapInterface>;
@@ -337,8 +341,9 @@ pub struct DescriptorHeap {
     handle:     ManuallyDrop<DescriptorHeapInterfaceHandle>,
     allocation: Option<ManuallyDrop<GpuAllocation>>,
     desc:       DescriptorHeapDesc,
+    debug_name: RwLock<String>,
 }
-create_ral_handle!(DescriptorHeapHandle, DescriptorHeap, DescriptorHeapInterfaceHandle);
+create_ral_handle!(DescriptorHeapHandle, DescriptorHeap, DescriptorHeapInterfaceHandle, debug_name);
 
 impl DescriptorHeapHandle {
     pub(crate) fn create(device: &DeviceHandle, handle: DescriptorHeapInterfaceHandle, allocation: Option<GpuAllocation>, desc: DescriptorHeapDesc) -> Self {
@@ -347,6 +352,7 @@ impl DescriptorHeapHandle {
             handle: ManuallyDrop::new(handle),
             allocation: allocation.map(|val| ManuallyDrop::new(val)),
             desc,
+            debug_name: RwLock::new(String::new()),
         })
     }
 
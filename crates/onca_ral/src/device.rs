@@ -1,9 +1,18 @@
-use onca_common::prelude::*;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use onca_common::{
+    prelude::*,
+    sync::{Mutex, RwLock},
+    time::Duration,
+    event_listener::{DynEventListenerArray, DynEventListenerRef, EventListener},
+};
+use onca_logging::{log_warning, log_error};
 
 use crate::{
     *,
     handle::{InterfaceHandle, create_ral_handle},
-    api::SwapChainResultInfo
+    api::SwapChainResultInfo,
+    capture::RenderDoc,
 };
 
 pub trait DeviceInterface {
@@ -12,12 +21,29 @@ pub trait DeviceInterface {
     unsafe fn create_fence(&self) -> Result<FenceInterfaceHandle>;
 
     unsafe fn create_buffer(&self, desc: &BufferDesc, alloc: &GpuAllocator) -> Result<(BufferInterfaceHandle, GpuAllocation, GpuAddress)>;
+    unsafe fn create_texture(&self, desc: &TextureDesc, alloc: &GpuAllocator) -> Result<(TextureInterfaceHandle, GpuAllocation)>;
+    /// Create a sparse texture without any memory bound to it, and return a tuple with the handle and the tiling info of the texture
+    unsafe fn create_sparse_texture(&self, desc: &SparseTextureDesc) -> Result<(TextureInterfaceHandle, SparseTextureInfo)>;
+    /// Create a texture placed at `offset` in `heap`, the texture does not own the memory it's placed in
+    unsafe fn create_placed_texture(&self, desc: &TextureDesc, heap: &MemoryHeapHandle, offset: u64) -> Result<TextureInterfaceHandle>;
+    /// Get the size and alignment of the memory needed to place a texture in a memory heap
+    unsafe fn get_texture_allocation_info(&self, desc: &TextureDesc) -> ResourceAllocationInfo;
 
     unsafe fn create_shader(&self, code: &[u8], shader_type: ShaderType) -> Result<ShaderInterfaceHandle>;
     unsafe fn create_static_sampler(&self, desc: &StaticSamplerDesc) -> Result<StaticSamplerInterfaceHandle>;
     unsafe fn create_sampler(&self, desc: &SamplerDesc) -> Result<SamplerInterfaceHandle>;
     unsafe fn create_pipeline_layout(&self, desc: &PipelineLayoutDesc) -> Result<PipelineLayoutInterfaceHandle>;
-    unsafe fn create_graphics_pipeline(&self, desc: &GraphicsPipelineDesc) -> Result<PipelineInterfaceHandle>;
+    unsafe fn create_graphics_pipeline(&self, desc: &GraphicsPipelineDesc, cache: Option<&PipelineCacheHandle>) -> Result<PipelineInterfaceHandle>;
+    unsafe fn create_compute_pipeline(&self, desc: &ComputePipelineDesc, cache: Option<&PipelineCacheHandle>) -> Result<PipelineInterfaceHandle>;
+    unsafe fn create_mesh_pipeline(&self, desc: &MeshPipelineDesc, cache: Option<&PipelineCacheHandle>) -> Result<PipelineInterfaceHandle>;
+    /// Create a pipeline cache from driver data, an empty `data` slice creates an empty pipeline cache
+    unsafe fn create_pipeline_cache(&self, data: &[u8]) -> Result<PipelineCacheInterfaceHandle>;
+    unsafe fn create_query_pool(&self, desc: &QueryPoolDesc) -> Result<QueryPoolInterfaceHandle>;
+    unsafe fn create_acceleration_structure(&self, desc: &AccelerationStructureDesc) -> Result<(AccelerationStructureInterfaceHandle, GpuAddress)>;
+    unsafe fn get_acceleration_structure_build_sizes(&self, inputs: &AccelerationStructureInputs, flags: AccelerationStructureBuildFlags) -> Result<AccelerationStructureBuildSizes>;
+    unsafe fn create_raytracing_pipeline(&self, desc: &RaytracingPipelineDesc, cache: Option<&PipelineCacheHandle>) -> Result<PipelineInterfaceHandle>;
+    /// Get the shader group handles of `count` groups, starting at `first_group`, each handle is `constants::RAYTRACE_HITGROUP_HANDLE_SIZE` bytes
+    unsafe fn get_raytracing_shader_group_handles(&self, pipeline: &PipelineHandle, first_group: u32, count: u32) -> Result<Vec<u8>>;
 
     /// Create a descriptor table layout and return a tuple with the handle, the number of descriptors, and the size of the table in bytes
     unsafe fn create_descriptor_table_layout(&self, desc: &DescriptorTableDesc) -> Result<(DescriptorTableLayoutInterfaceHandle, u32, u32)>;
@@ -33,10 +59,38 @@ pub trait DeviceInterface {
     /// - 4MiB: Supports all resources (including MSAA)
     unsafe fn allocate_heap(&self, size: u64, alignment: u64, memory_type: MemoryType, mem_info: &MemoryInfo) -> Result<MemoryHeapInterfaceHandle>;
     unsafe fn free_heap(&self, heap: MemoryHeapHandle);
+
+    /// Begin a capture using the API specific capture tool, returns `false` if no capture tool is attached
+    unsafe fn begin_capture(&self) -> bool;
+    /// End a capture using the API specific capture tool, returns `false` if no capture tool is attached
+    unsafe fn end_capture(&self) -> bool;
+
+    /// Get information about why the device was lost, only called after a call returned `Error::DeviceLost`
+    unsafe fn get_device_lost_info(&self) -> DeviceLostInfo;
+
+    /// Set the debug name of the device
+    unsafe fn set_debug_name(&self, name: &str);
 }
 
 pub type DeviceInterfaceHandle = InterfaceHandle<dyn DeviceInterface>;
 
+/// Information about why a device was lost
+#[derive(Clone, Debug)]
+pub struct DeviceLostInfo {
+    /// Reason the device was lost
+    pub reason:  String,
+    /// Additional API specific information, e.g. DRED breadcrumbs and page faults for DX12, or the device fault info for Vulkan
+    pub details: Vec<String>,
+}
+
+/// Event send to device lost listeners
+pub enum DeviceLostEvent {
+    /// The device was lost, all objects created from the device are invalid and should be released
+    Lost(DeviceLostInfo),
+    /// The device was recreated using `Ral::recreate_device`, objects can be recreated using the new device
+    Recreated(DeviceHandle),
+}
+
 pub struct Device {
     /// Device handle
     handle:         DeviceInterfaceHandle,
@@ -46,24 +100,58 @@ pub struct Device {
     command_queues: [[CommandQueueHandle; QueuePriority::COUNT]; QueueType::COUNT],
     /// GpuAllocator
     gpu_allocator:  GpuAllocator,
+    /// Last queried memory budget
+    memory_budget:  Mutex<Option<MemoryBudgetInfo>>,
+    /// Memory budget listeners
+    budget_listeners: Mutex<DynEventListenerArray<MemoryBudgetEvent>>,
+    /// Pipeline cache used when creating pipelines
+    pipeline_cache: RwLock<Option<PipelineCacheHandle>>,
+    /// RenderDoc API, if RenderDoc is attached
+    renderdoc:      Option<RenderDoc>,
+    /// Was the device lost
+    lost:           AtomicBool,
+    /// Device lost listeners
+    lost_listeners: Mutex<DynEventListenerArray<DeviceLostEvent>>,
+    /// Was the device created in headless mode
+    headless:       bool,
 
     cpu_alloc:      AllocId
 }
 create_ral_handle!(DeviceHandle, Device, DeviceInterfaceHandle);
 
+/// Track if a result indicates that the device was lost, for objects that only hold a weak reference to their device
+pub(crate) fn track_device_lost<T>(device: &WeakHandle<Device>, result: Result<T>) -> Result<T> {
+    match WeakHandle::upgrade(device) {
+        Some(device) => device.track_device_lost(result),
+        None => result,
+    }
+}
+
 impl DeviceHandle {
     /// Create a new device
-    pub(crate) fn create(handle: DeviceInterfaceHandle, phys_dev: PhysicalDevice, command_queues: [[CommandQueueHandle; QueuePriority::COUNT]; QueueType::COUNT], alloc_impl: GpuAllocatorImpl, cpu_alloc: AllocId) -> Self {
+    pub(crate) fn create(handle: DeviceInterfaceHandle, phys_dev: PhysicalDevice, command_queue_handles: [[(CommandQueueInterfaceHandle, QueueIndex); QueuePriority::COUNT]; QueueType::COUNT], alloc_impl: GpuAllocatorImpl, headless: bool, cpu_alloc: AllocId) -> Self {
         let mem_info = phys_dev.memory_info.clone();
         Handle::new_cyclic(|weak| Device {
             handle,
             phys_dev,
-            command_queues,
+            command_queues: command_queue_handles.map(|arr| arr.map(|(handle, index)| CommandQueueHandle::create(handle, index, weak.clone()))),
             gpu_allocator: GpuAllocator::new(weak, mem_info, alloc_impl),
+            memory_budget: Mutex::new(None),
+            budget_listeners: Mutex::new(DynEventListenerArray::new()),
+            pipeline_cache: RwLock::new(None),
+            renderdoc: RenderDoc::load(),
+            lost: AtomicBool::new(false),
+            lost_listeners: Mutex::new(DynEventListenerArray::new()),
+            headless,
             cpu_alloc,
         })
     }
 
+    /// Check if the device was created in headless mode, in which case no swap chains can be created
+    pub fn is_headless(&self) -> bool {
+        self.headless
+    }
+
     /// Get the physical device that is used by this device
     pub fn get_physical_device(&self) -> &PhysicalDevice {
         &self.phys_dev
@@ -75,8 +163,26 @@ impl DeviceHandle {
         self.command_queues[queue_type as usize][priority as usize].clone()
     }
 
+    /// Get the async compute queue, which can execute compute work in parallel with the graphics queue
+    pub fn get_async_compute_queue(&self, priority: QueuePriority) -> CommandQueueHandle {
+        self.get_queue(QueueType::Compute, priority)
+    }
+
+    /// Get the async copy queue, which can execute transfers in parallel with the graphics and compute queues
+    pub fn get_async_copy_queue(&self, priority: QueuePriority) -> CommandQueueHandle {
+        self.get_queue(QueueType::Copy, priority)
+    }
+
+    /// Check if a queue type maps to a different hardware queue than the graphics queue, meaning work submitted to it can actually overlap graphics work
+    pub fn has_dedicated_queue(&self, queue_type: QueueType) -> bool {
+        self.command_queues[queue_type as usize][0].index != self.command_queues[QueueType::Graphics as usize][0].index
+    }
+
     /// Create a swap chain
     pub fn create_swap_chain(&self, create_info: SwapChainDesc) -> Result<SwapChainHandle> {
+        if self.headless {
+            return Err(Error::InvalidParameter("Cannot create a swap chain on a headless device".to_string()));
+        }
         scoped_alloc!(self.cpu_alloc);
         let (handle, result_info) = unsafe { self.handle.create_swap_chain(&self.phys_dev, &create_info)? };
         SwapChain::new(self, create_info, handle, result_info)
@@ -118,7 +224,7 @@ impl DeviceHandle {
     pub fn create_fence(&self) -> Result<FenceHandle> {
         scoped_alloc!(self.cpu_alloc);
         let handle = unsafe { self.handle.create_fence()? };
-        Ok(FenceHandle::create(handle))
+        Ok(FenceHandle::create(handle, Handle::downgrade(self)))
     }
 
     /// Create a buffer
@@ -128,6 +234,142 @@ impl DeviceHandle {
         Ok(BufferHandle::create(self, handle, allocation, address, desc.clone()))
     }
 
+    /// Create a texture
+    /// 
+    /// The texture will be in the `TextureLayout::Undefined` layout after creation
+    pub fn create_texture(&self, desc: &TextureDesc) -> Result<TextureHandle> {
+        #[cfg(feature = "validation")]
+        {
+            desc.validate()?;
+            let supported_samples = self.phys_dev.get_supported_sample_counts(desc.format);
+            check_invalid_parameter!(supported_samples.supports(desc.samples), "Format {} does not support {} samples per pixel, supported sample counts: {supported_samples}", desc.format, desc.samples.get());
+        }
+        scoped_alloc!(self.cpu_alloc);
+        let (handle, allocation) = unsafe { self.handle.create_texture(desc, &self.gpu_allocator)? };
+        Ok(unsafe { TextureHandle::create_allocated(self, handle, allocation, desc) })
+    }
+
+    /// Create a texture placed at `offset` in `heap`, this allows the memory of multiple textures to be aliased
+    /// 
+    /// The texture keeps the heap alive, but it won't free it. The texture will be in the `TextureLayout::Undefined` layout after creation
+    /// 
+    /// # Safety
+    /// 
+    /// The user is responsible to make sure that textures sharing memory are never used at the same time,
+    /// and that the texture is transitioned from the `TextureLayout::Undefined` layout when it starts using memory previously used by another texture
+    pub unsafe fn create_placed_texture(&self, desc: &TextureDesc, heap: &MemoryHeapHandle, offset: u64) -> Result<TextureHandle> {
+        #[cfg(feature = "validation")]
+        {
+            desc.validate()?;
+            let supported_samples = self.phys_dev.get_supported_sample_counts(desc.format);
+            check_invalid_parameter!(supported_samples.supports(desc.samples), "Format {} does not support {} samples per pixel, supported sample counts: {supported_samples}", desc.format, desc.samples.get());
+            check_invalid_parameter!(heap.memory_type() == MemoryType::Gpu, "Textures can only be placed in GPU memory, found a {} heap", heap.memory_type());
+            check_invalid_parameter!(!desc.samples.is_multisampled() || heap.has_msaa_support(), "Multisampled textures can only be placed in a heap with MSAA support");
+
+            let alloc_info = self.handle.get_texture_allocation_info(desc);
+            check_invalid_parameter!(offset % alloc_info.alignment == 0, "Texture needs to be placed at an offset aligned to {} bytes, found offset {offset}", alloc_info.alignment);
+            check_invalid_parameter!(offset + alloc_info.size <= heap.size(), "Texture of {} bytes at offset {offset} does not fit in a heap of {} bytes", alloc_info.size, heap.size());
+        }
+        scoped_alloc!(self.cpu_alloc);
+        let handle = self.handle.create_placed_texture(desc, heap, offset)?;
+        Ok(TextureHandle::create_placed(self, handle, heap.clone(), desc))
+    }
+
+    /// Get the size and alignment of the memory needed to place a texture in a memory heap
+    pub fn get_texture_allocation_info(&self, desc: &TextureDesc) -> ResourceAllocationInfo {
+        unsafe { self.handle.get_texture_allocation_info(desc) }
+    }
+
+    /// Create a texture and upload its initial data
+    /// 
+    /// All subresources in `data` are uploaded using a one-time graphics command list, after which the remaining mips are generated when a `mip_generator` is provided.
+    /// This waits until the upload has finished, and the texture will be in `final_state` afterwards
+    pub fn create_texture_with_data(&self, desc: &TextureDesc, data: &TextureInitData, mip_generator: Option<&MipGenerator>, final_state: ResourceState) -> Result<TextureHandle> {
+        let generate_mips = mip_generator.is_some() && data.mip_levels < desc.mip_levels;
+        #[cfg(feature = "validation")]
+        {
+            check_invalid_parameter!(desc.usage.contains(TextureUsage::CopyDst), "Texture needs the `TextureUsage::CopyDst` usage to upload its initial data");
+            check_invalid_parameter!(final_state.layout.is_some(), "Final texture state needs to contain a layout");
+            check_invalid_parameter!(data.mip_levels <= desc.mip_levels, "Initial data contains more mips ({}) than the texture ({})", data.mip_levels, desc.mip_levels);
+            check_invalid_parameter!(!desc.format.components().is_planar(), "Initial data cannot be uploaded to textures with a planar format, found {}", desc.format);
+            check_invalid_parameter!(!desc.samples.is_multisampled(), "Initial data cannot be uploaded to multisampled textures");
+        }
+
+        let (copies, buffer_size) = data.get_subresource_copies(desc)?;
+
+        let buffer = self.create_buffer(&BufferDesc {
+            size: buffer_size,
+            usage: BufferUsage::CopySrc,
+            alloc_desc: GpuAllocationDesc {
+                memory_type: MemoryType::Upload,
+                flags: MemoryAllocationFlags::None,
+            },
+        })?;
+
+        let mut padded = vec![0; buffer_size as usize];
+        for copy in &copies {
+            for row in 0..copy.num_rows {
+                let src = &data.data[(copy.data_offset + row * copy.row_size) as usize..][..copy.row_size as usize];
+                padded[(copy.region.buffer_offset + row * copy.row_pitch) as usize..][..copy.row_size as usize].copy_from_slice(src);
+            }
+        }
+        let mut mapped = buffer.map(0, buffer_size)?;
+        mapped.write(&padded);
+        buffer.unmap(mapped);
+
+        let texture = self.create_texture(desc)?;
+
+        let command_pool = self.create_graphics_command_pool(CommandPoolFlags::Transient)?;
+        let command_list = command_pool.allocate()?;
+        command_list.begin(CommandListBeginFlags::OneTimeSubmit)?;
+
+        command_list.barrier(&[Barrier::new_basic_texture(ResourceState::UNDEFINED_TEX, ResourceState::COPY_WRITE_TEX, texture.clone())]);
+        let regions = copies.into_iter().map(|copy| copy.region).collect::<Vec<_>>();
+        command_list.as_ref().copy_buffer_to_texture(&buffer, &texture, &regions);
+
+        let _mip_resources = match mip_generator {
+            Some(mip_generator) if generate_mips => {
+                command_list.barrier(&[Barrier::new_basic_texture(ResourceState::COPY_WRITE_TEX, MipGenerator::TEXTURE_STATE, texture.clone())]);
+                let resources = mip_generator.record(self, &command_list, &texture, data.mip_levels.get())?;
+                command_list.barrier(&[Barrier::new_basic_texture(MipGenerator::TEXTURE_STATE, final_state, texture.clone())]);
+                Some(resources)
+            },
+            _ => {
+                command_list.barrier(&[Barrier::new_basic_texture(ResourceState::COPY_WRITE_TEX, final_state, texture.clone())]);
+                None
+            },
+        };
+        command_list.close()?;
+
+        let fence = self.create_fence()?;
+        let fence_value = fence.get_value()? + 1;
+        let queue = self.get_queue(QueueType::Graphics, QueuePriority::Normal);
+        queue.submit(&CommandListSubmitInfo {
+            command_lists: &[command_list],
+            wait_fences: None,
+            signal_fences: Some(&[FenceSignalSubmitInfo { fence: fence.clone(), value: fence_value, sync_point: SyncPoint::All }]),
+        })?;
+        if !fence.wait(fence_value, Duration::MAX)? {
+            return Err(Error::Timeout);
+        }
+
+        Ok(texture)
+    }
+
+    /// Create a sparse texture
+    /// 
+    /// The texture will not have any memory mapped to it, tiles need to be mapped using `CommandQueueHandle::update_tile_mappings` before they can be accessed
+    pub fn create_sparse_texture(&self, desc: &SparseTextureDesc) -> Result<SparseTexture> {
+        #[cfg(feature = "validation")]
+        {
+            desc.validate(&self.phys_dev)?;
+        }
+        scoped_alloc!(self.cpu_alloc);
+        let (handle, info) = unsafe { self.handle.create_sparse_texture(desc)? };
+        let texture = unsafe { TextureHandle::create(Handle::downgrade(self), handle, TextureFlags::Sparse, desc.size, desc.format, desc.mip_levels.get(), desc.usage) };
+        Ok(SparseTexture::new(texture, info))
+    }
+
     /// Create a shader from a binary blob and a type
     pub fn create_shader(&self, code: &[u8], shader_type: ShaderType) -> Result<ShaderHandle> {
         scoped_alloc!(self.cpu_alloc);
@@ -151,6 +393,10 @@ impl DeviceHandle {
 
     /// Create a pipeline layout
     pub fn create_pipeline_layout(&self, desc: &PipelineLayoutDesc) -> Result<PipelineLayoutHandle> {
+        #[cfg(feature = "validation")]
+        {
+            desc.validate(&self.phys_dev)?;
+        }
         scoped_alloc!(self.cpu_alloc);
         let handle = unsafe { self.handle.create_pipeline_layout(desc)? };
         let static_samplers = desc.static_samplers.as_ref().map_or(Vec::new(), |arr| arr.clone());
@@ -159,11 +405,129 @@ impl DeviceHandle {
 
     /// Create a graphics pipeline (vertex)
     pub fn create_graphics_pipeline(&self, desc: &GraphicsPipelineDesc) -> Result<PipelineHandle> {
+        #[cfg(feature = "validation")]
+        {
+            if let Some(input_layout) = &desc.input_layout {
+                desc.vertex_shader.reflect()?.validate_input_layout(input_layout)?;
+            }
+        }
+        scoped_alloc!(self.cpu_alloc);
+        let handle = unsafe { self.handle.create_graphics_pipeline(desc, self.pipeline_cache.read().as_ref())? };
+        Ok(PipelineHandle::create(handle, desc.pipeline_layout.clone()))
+    }
+
+    /// Create a compute pipeline
+    pub fn create_compute_pipeline(&self, desc: &ComputePipelineDesc) -> Result<PipelineHandle> {
+        #[cfg(feature = "validation")]
+        {
+            desc.validate()?;
+        }
+        scoped_alloc!(self.cpu_alloc);
+        let handle = unsafe { self.handle.create_compute_pipeline(desc, self.pipeline_cache.read().as_ref())? };
+        Ok(PipelineHandle::create(handle, desc.pipeline_layout.clone()))
+    }
+
+    /// Create a query pool
+    pub fn create_query_pool(&self, desc: &QueryPoolDesc) -> Result<QueryPoolHandle> {
+        #[cfg(feature = "validation")]
+        {
+            if desc.count == 0 {
+                return Err(Error::InvalidParameter("A query pool needs to contain at least 1 query".to_string()));
+            }
+        }
+        scoped_alloc!(self.cpu_alloc);
+        let handle = unsafe { self.handle.create_query_pool(desc)? };
+        Ok(QueryPoolHandle::create(handle, *desc))
+    }
+
+    /// Create a mesh pipeline
+    pub fn create_mesh_pipeline(&self, desc: &MeshPipelineDesc) -> Result<PipelineHandle> {
+        #[cfg(feature = "validation")]
+        {
+            desc.validate()?;
+        }
+        scoped_alloc!(self.cpu_alloc);
+        let handle = unsafe { self.handle.create_mesh_pipeline(desc, self.pipeline_cache.read().as_ref())? };
+        Ok(PipelineHandle::create(handle, desc.pipeline_layout.clone()))
+    }
+
+    /// Create an acceleration structure
+    pub fn create_acceleration_structure(&self, desc: &AccelerationStructureDesc) -> Result<AccelerationStructureHandle> {
+        #[cfg(feature = "validation")]
+        {
+            desc.validate()?;
+        }
+        scoped_alloc!(self.cpu_alloc);
+        let (handle, address) = unsafe { self.handle.create_acceleration_structure(desc)? };
+        Ok(AccelerationStructureHandle::create(handle, desc, address))
+    }
+
+    /// Get the memory requirements to build an acceleration structure
+    pub fn get_acceleration_structure_build_sizes(&self, inputs: &AccelerationStructureInputs, flags: AccelerationStructureBuildFlags) -> Result<AccelerationStructureBuildSizes> {
+        #[cfg(feature = "validation")]
+        {
+            inputs.validate()?;
+        }
         scoped_alloc!(self.cpu_alloc);
-        let handle = unsafe { self.handle.create_graphics_pipeline(desc)? };
+        unsafe { self.handle.get_acceleration_structure_build_sizes(inputs, flags) }
+    }
+
+    /// Create a raytracing pipeline
+    pub fn create_raytracing_pipeline(&self, desc: &RaytracingPipelineDesc) -> Result<PipelineHandle> {
+        #[cfg(feature = "validation")]
+        {
+            desc.validate()?;
+
+            let max_recursion_depth = self.phys_dev.raytracing.max_recursion_depth;
+            if desc.max_recursion_depth > max_recursion_depth {
+                return Err(Error::InvalidParameter(format!("Raytracing pipeline recursion depth ({}) exceeds the maximum of {max_recursion_depth}", desc.max_recursion_depth)));
+            }
+        }
+        scoped_alloc!(self.cpu_alloc);
+        let handle = unsafe { self.handle.create_raytracing_pipeline(desc, self.pipeline_cache.read().as_ref())? };
         Ok(PipelineHandle::create(handle, desc.pipeline_layout.clone()))
     }
 
+    /// Create a pipeline cache
+    /// 
+    /// `data` is expected to be data created by `PipelineCacheHandle::serialize`.
+    /// If no data is provided, or the data was created for another device or driver, an empty pipeline cache will be created
+    pub fn create_pipeline_cache(&self, data: Option<&[u8]>) -> Result<PipelineCacheHandle> {
+        scoped_alloc!(self.cpu_alloc);
+        let key = PipelineCacheKey::from_physical_device(&self.phys_dev);
+        let driver_data = match data {
+            Some(data) => match key.get_driver_data(data) {
+                Some(driver_data) => driver_data,
+                None => {
+                    log_warning!(LOG_CAT, "Pipeline cache data was created for a different device or driver, starting with an empty cache");
+                    &[]
+                },
+            },
+            None => &[],
+        };
+
+        let handle = unsafe { self.handle.create_pipeline_cache(driver_data)? };
+        Ok(PipelineCacheHandle::create(handle, key))
+    }
+
+    /// Set the pipeline cache that will be used when creating pipelines
+    pub fn set_pipeline_cache(&self, cache: Option<PipelineCacheHandle>) {
+        *self.pipeline_cache.write() = cache;
+    }
+
+    /// Get the pipeline cache that is used when creating pipelines
+    pub fn pipeline_cache(&self) -> Option<PipelineCacheHandle> {
+        self.pipeline_cache.read().clone()
+    }
+
+    /// Get the shader group handles of `count` groups in a raytracing pipeline, starting at `first_group`
+    /// 
+    /// Each handle is `constants::RAYTRACE_HITGROUP_HANDLE_SIZE` bytes
+    pub fn get_raytracing_shader_group_handles(&self, pipeline: &PipelineHandle, first_group: u32, count: u32) -> Result<Vec<u8>> {
+        scoped_alloc!(self.cpu_alloc);
+        unsafe { self.handle.get_raytracing_shader_group_handles(pipeline, first_group, count) }
+    }
+
     /// Create a descriptor table layout
     pub fn create_descriptor_table_layout(&self, desc: &DescriptorTableDesc) -> Result<DescriptorTableLayoutHandle> {
         scoped_alloc!(self.cpu_alloc);
@@ -186,7 +550,7 @@ impl DeviceHandle {
     /// 
     /// Primarily prefer using fences, or secondarily queue specific flushes when possible
     pub fn flush(&self) -> Result<()> {
-        unsafe { self.handle.flush(&self.command_queues) }
+        self.track_device_lost(unsafe { self.handle.flush(&self.command_queues) })
     }
 
     /// Allocate a GPU heap
@@ -194,14 +558,191 @@ impl DeviceHandle {
         scoped_alloc!(self.cpu_alloc);
         let alignment = if msaa_support { constants::MIN_MSAA_ALLOCATION_ALIGN } else  { constants::MIN_ALLOCATION_ALIGN };
         let heap = self.handle.allocate_heap(size, alignment.alignment(), memory_type, mem_info)?;
-        Ok(MemoryHeapHandle::new(MemoryHeap::new(heap, msaa_support)))
+        let heap = MemoryHeap::new(heap, size, memory_type, msaa_support);
+        self.gpu_allocator.track_heap_alloc(&heap);
+        Ok(MemoryHeapHandle::new(heap))
     }
 
     /// Free a GPU heap
     pub unsafe fn free_heap(&self, heap: MemoryHeapHandle) {
+        self.gpu_allocator.track_heap_free(&heap);
         self.handle.free_heap(heap)
     }
 
+    /// Get the current GPU allocator statistics
+    pub fn get_allocator_stats(&self) -> GpuAllocatorStats {
+        self.gpu_allocator.stats()
+    }
+
+    /// Get the current OS memory budget
+    pub fn get_memory_budget(&self) -> Result<MemoryBudgetInfo> {
+        self.phys_dev.handle.get_memory_budget_info()
+    }
+
+    /// Query the OS memory budget and notify all budget listeners when it has changed
+    /// 
+    /// The OS does not push budget changes to the RAL, so this should be called regularly, e.g. once per frame
+    pub fn update_memory_budget(&self) -> Result<()> {
+        let budget = self.get_memory_budget()?;
+
+        let old = {
+            let mut memory_budget = self.memory_budget.lock();
+            let old = *memory_budget;
+            *memory_budget = Some(budget);
+            old
+        };
+
+        if old.map_or(true, |old| old.has_budget_changed(&budget)) {
+            self.budget_listeners.lock().notify(&MemoryBudgetEvent { old, new: budget });
+        }
+        Ok(())
+    }
+
+    /// Register a listener that is notified when the memory budget changes
+    pub fn register_memory_budget_listener(&self, listener: DynEventListenerRef<MemoryBudgetEvent>) {
+        self.budget_listeners.lock().push(listener);
+    }
+
+    /// Unregister a memory budget listener
+    pub fn unregister_memory_budget_listener(&self, listener: &DynEventListenerRef<MemoryBudgetEvent>) {
+        self.budget_listeners.lock().remove(listener);
+    }
+
+    /// Begin a programmatic GPU capture
+    /// 
+    /// When RenderDoc is attached, it will be used to make the capture, otherwise the API specific capture tool will be used (e.g. PIX for DX12)
+    /// 
+    /// # Errors
+    /// 
+    /// Returns an error if no capture tool is attached
+    pub fn begin_capture(&self) -> Result<()> {
+        if let Some(renderdoc) = &self.renderdoc {
+            renderdoc.start_frame_capture();
+            return Ok(());
+        }
+
+        if unsafe { self.handle.begin_capture() } {
+            Ok(())
+        } else {
+            Err(Error::MissingFeature("GPU capture tool"))
+        }
+    }
+
+    /// End a programmatic GPU capture
+    /// 
+    /// # Errors
+    /// 
+    /// Returns an error if no capture tool is attached, or if RenderDoc failed to make the capture
+    pub fn end_capture(&self) -> Result<()> {
+        if let Some(renderdoc) = &self.renderdoc {
+            if !renderdoc.is_frame_capturing() {
+                return Err(Error::Other("Trying to end a GPU capture, but no capture is in progress".to_string()));
+            }
+            return if renderdoc.end_frame_capture() {
+                Ok(())
+            } else {
+                Err(Error::Other("RenderDoc failed to make a capture".to_string()))
+            };
+        }
+
+        if unsafe { self.handle.end_capture() } {
+            Ok(())
+        } else {
+            Err(Error::MissingFeature("GPU capture tool"))
+        }
+    }
+
+    /// Capture the next presented frame
+    /// 
+    /// # Errors
+    /// 
+    /// Returns an error if RenderDoc is not attached, as this is not supported by other capture tools
+    pub fn trigger_capture(&self) -> Result<()> {
+        match &self.renderdoc {
+            Some(renderdoc) => {
+                renderdoc.trigger_capture();
+                Ok(())
+            },
+            None => Err(Error::MissingFeature("RenderDoc")),
+        }
+    }
+
+    /// Check if the device was lost
+    pub fn is_lost(&self) -> bool {
+        self.lost.load(Ordering::Acquire)
+    }
+
+    /// Register a listener that is notified when the device is lost or recreated
+    pub fn register_device_lost_listener(&self, listener: DynEventListenerRef<DeviceLostEvent>) {
+        self.lost_listeners.lock().push(listener);
+    }
+
+    /// Unregister a device lost listener
+    pub fn unregister_device_lost_listener(&self, listener: &DynEventListenerRef<DeviceLostEvent>) {
+        self.lost_listeners.lock().remove(listener);
+    }
+
+    /// Recreate a swap chain for this device, using the same window and settings as a swap chain of a lost device
+    /// 
+    /// The swap chain needs to be the last reference to it, as most APIs only allow a single swap chain per window
+    pub fn recreate_swap_chain(&self, swap_chain: SwapChainHandle) -> Result<SwapChainHandle> {
+        let queue_index = swap_chain.queue().index;
+        let queue = self.command_queues.iter()
+            .flatten()
+            .find(|queue| queue.index == queue_index)
+            .ok_or(Error::InvalidParameter(format!("Device has no queue matching {queue_index} to present the swap chain on")))?
+            .clone();
+
+        let desc = swap_chain.get_recreate_desc(queue);
+        let hdr_metadata = swap_chain.hdr_metadata();
+        drop(swap_chain);
+
+        let swap_chain = self.create_swap_chain(desc)?;
+        if let Some(metadata) = &hdr_metadata {
+            swap_chain.set_hdr_metadata(Some(metadata))?;
+        }
+        Ok(swap_chain)
+    }
+
+    /// Track if a result indicates that the device was lost
+    /// 
+    /// The first time the device is lost, the reason is logged and all device lost listeners are notified
+    pub(crate) fn track_device_lost<T>(&self, result: Result<T>) -> Result<T> {
+        if let Err(Error::DeviceLost) = &result {
+            if !self.lost.swap(true, Ordering::AcqRel) {
+                let info = unsafe { self.handle.get_device_lost_info() };
+                log_error!(LOG_CAT, "Device lost: {}", info.reason);
+                for detail in &info.details {
+                    log_error!(LOG_CAT, "{detail}");
+                }
+                self.notify_device_lost_listeners(&DeviceLostEvent::Lost(info));
+            }
+        }
+        result
+    }
+
+    /// Move all device lost listeners to the recreated device and notify them
+    pub(crate) fn transfer_device_lost_listeners(&self, new_device: &DeviceHandle) {
+        let listeners = core::mem::replace(&mut *self.lost_listeners.lock(), DynEventListenerArray::new());
+        {
+            let mut new_listeners = new_device.lost_listeners.lock();
+            for listener in listeners {
+                new_listeners.push(listener);
+            }
+        }
+        new_device.notify_device_lost_listeners(&DeviceLostEvent::Recreated(new_device.clone()));
+    }
+
+    /// Notify all device lost listeners
+    /// 
+    /// The listeners are copied out of the lock first, so listeners can register or unregister listeners while they are notified
+    fn notify_device_lost_listeners(&self, event: &DeviceLostEvent) {
+        let listeners = self.lost_listeners.lock().to_vec();
+        for listener in &listeners {
+            listener.lock().notify(event);
+        }
+    }
+
     /// Get the allocator the device uses for internal memory allocations
     pub fn allocator(&self) -> AllocId {
         self.cpu_alloc
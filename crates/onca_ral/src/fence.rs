@@ -2,7 +2,7 @@ use onca_common::time::Duration;
 
 use crate::{
     handle::{InterfaceHandle, HandleImpl, create_ral_handle},
-    Result, Handle
+    Result, Handle, WeakHandle, Device, track_device_lost,
 };
 
 
@@ -19,42 +19,46 @@ pub trait FenceInterface {
 
     /// `self` should not be used, `self` is only present to be able to dynamically dispatch this function
     unsafe fn wait_multiple(&self, fences: &[(Handle<Fence>, u64)], wait_for_all: bool, timeout: Duration) -> Result<bool>;
+
+    /// Set the debug name of the fence
+    unsafe fn set_debug_name(&self, name: &str);
 }
 
 pub type FenceInterfaceHandle = InterfaceHandle<dyn FenceInterface>;
 
 pub struct Fence {
     handle: FenceInterfaceHandle,
+    device: WeakHandle<Device>,
     // TODO
     //value:  u64,
 }
 create_ral_handle!(FenceHandle, Fence, FenceInterfaceHandle);
 
 impl FenceHandle {
-    pub(crate) fn create(handle: FenceInterfaceHandle) -> Self {
-        Self::new(Fence { handle })
+    pub(crate) fn create(handle: FenceInterfaceHandle, device: WeakHandle<Device>) -> Self {
+        Self::new(Fence { handle, device })
     }
 
     pub fn get_value(&self) -> Result<u64> {
-        unsafe { self.handle.get_value() }
+        track_device_lost(&self.device, unsafe { self.handle.get_value() })
     }
 
     /// Singal the fence using a given value
     pub fn signal(&self, value: u64) -> Result<()> {
-        unsafe { self.handle.signal(value) }
+        track_device_lost(&self.device, unsafe { self.handle.signal(value) })
     }
     
     /// Wait for a given fence value to be present
     /// 
     /// Returns result with `Ok(true)` when the fence was signelled, and `Ok(false)` if the wait hit a timeout
     pub fn wait(&self, value: u64, timeout: Duration) -> Result<bool> {
-        unsafe { self.handle.wait(value, timeout) }
+        track_device_lost(&self.device, unsafe { self.handle.wait(value, timeout) })
     }
     
     /// Wait for multiple fences, until 1 or all match the given fence values
     /// 
     /// Returns result with `Ok(true)` when the fence was signelled, and `Ok(false)` if the wait hit a timeout
     pub fn wait_multiple(fences: &[(Handle<Fence>, u64)], wait_for_all: bool, timeout: Duration) -> Result<bool> {
-        unsafe { fences[0].0.handle.wait_multiple(fences, wait_for_all, timeout) }
+        track_device_lost(&fences[0].0.device, unsafe { fences[0].0.handle.wait_multiple(fences, wait_for_all, timeout) })
     }
 }
\ No newline at end of file
@@ -0,0 +1,520 @@
+//! Render graph
+//!
+//! A render graph is a layer over the RAL, where passes declare which resources they read and write, and in which state they expect them to be.
+//! The graph uses this information to derive all barriers between passes, so passes only need to record their own commands.
+//!
+//! Passes are executed in the order they were added, which means that a pass reading a resource will always see the result of all passes writing to it that were added before it.
+//!
+//! # Transient resources
+//!
+//! Besides importing existing resources, textures that only live during the execution of the graph can be created as transient textures.
+//! When the graph is compiled, the lifetime of each transient texture is calculated, and all transient textures are placed in a single heap,
+//! where textures that are never used at the same time share the same memory.
+//!
+//! The content of a transient texture is undefined when it is first used in the graph.
+
+use core::fmt;
+
+use onca_common::prelude::*;
+
+use crate::*;
+
+/// Handle to a resource in a render graph
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct GraphResourceId(u32);
+
+impl GraphResourceId {
+    /// Get the index of the resource in the graph
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl fmt::Display for GraphResourceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("graph-resource {}", self.0))
+    }
+}
+
+/// Resource used by a render graph
+#[derive(Clone)]
+pub enum GraphResource {
+    /// Texture resource
+    Texture(TextureHandle),
+    /// Buffer resource
+    Buffer(BufferHandle),
+}
+
+/// Lifetime of a resource in a compiled graph, as indices into the order the passes will be executed in
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct GraphResourceLifetime {
+    /// Index of the first pass using the resource
+    pub first_pass: usize,
+    /// Index of the last pass using the resource
+    pub last_pass:  usize,
+}
+
+impl GraphResourceLifetime {
+    fn overlaps(self, other: GraphResourceLifetime) -> bool {
+        self.first_pass <= other.last_pass && other.first_pass <= self.last_pass
+    }
+}
+
+enum GraphResourceSource {
+    /// Resource imported into the graph
+    Imported(GraphResource),
+    /// Transient texture, which is created when the graph is compiled
+    Transient(TextureDesc),
+}
+
+struct GraphResourceEntry {
+    name:          String,
+    source:        GraphResourceSource,
+    initial_state: ResourceState,
+    final_state:   Option<ResourceState>,
+}
+
+#[derive(Clone, Copy)]
+struct GraphResourceAccess {
+    resource: GraphResourceId,
+    state:    ResourceState,
+}
+
+type GraphPassExecuteFn<'a> = Box<dyn FnOnce(&GraphicsCommandList, &GraphResources) + 'a>;
+
+struct GraphPass<'a> {
+    name:     String,
+    accesses: Vec<GraphResourceAccess>,
+    execute:  Option<GraphPassExecuteFn<'a>>,
+}
+
+/// Render graph
+///
+/// `'a` is the lifetime of any data captured by the pass' execute closures
+pub struct RenderGraph<'a> {
+    resources: Vec<GraphResourceEntry>,
+    passes:    Vec<GraphPass<'a>>,
+}
+
+impl<'a> RenderGraph<'a> {
+    /// Create an empty render graph
+    pub fn new() -> Self {
+        Self {
+            resources: Vec::new(),
+            passes: Vec::new(),
+        }
+    }
+
+    /// Import a texture into the graph
+    ///
+    /// `initial_state` is the state the texture is in before the graph is executed, and if `final_state` is set, the texture will be transitioned into that state after all passes have executed
+    pub fn import_texture(&mut self, name: &str, texture: TextureHandle, initial_state: ResourceState, final_state: Option<ResourceState>) -> GraphResourceId {
+        self.add_resource(name, GraphResourceSource::Imported(GraphResource::Texture(texture)), initial_state, final_state)
+    }
+
+    /// Import a buffer into the graph
+    ///
+    /// `initial_state` is the state the buffer is in before the graph is executed, and if `final_state` is set, the buffer will be transitioned into that state after all passes have executed
+    pub fn import_buffer(&mut self, name: &str, buffer: BufferHandle, initial_state: ResourceState, final_state: Option<ResourceState>) -> GraphResourceId {
+        self.add_resource(name, GraphResourceSource::Imported(GraphResource::Buffer(buffer)), initial_state, final_state)
+    }
+
+    /// Create a transient texture, which only exists while the graph is executed
+    ///
+    /// The texture is created when the graph is compiled, and may share its memory with other transient textures that are not used at the same time,
+    /// so its content is undefined when it is first used. The memory type in `desc` is ignored, as transient textures are always placed in GPU memory.
+    pub fn create_texture(&mut self, name: &str, desc: &TextureDesc) -> GraphResourceId {
+        self.add_resource(name, GraphResourceSource::Transient(*desc), ResourceState::UNDEFINED_TEX, None)
+    }
+
+    /// Get an imported resource, transient resources are only available once the graph is compiled
+    pub fn get_resource(&self, id: GraphResourceId) -> Option<&GraphResource> {
+        self.resources.get(id.index()).and_then(|entry| match &entry.source {
+            GraphResourceSource::Imported(resource) => Some(resource),
+            GraphResourceSource::Transient(_) => None,
+        })
+    }
+
+    /// Add a new pass to the graph
+    ///
+    /// The pass is only added to the graph once `PassBuilder::execute` is called
+    pub fn add_pass<'g>(&'g mut self, name: &str) -> PassBuilder<'g, 'a> {
+        PassBuilder {
+            graph: self,
+            name: name.to_string(),
+            accesses: Vec::new(),
+        }
+    }
+
+    /// Compile the graph, this will calculate all barriers and resource lifetimes, and create all transient textures that are used by the passes
+    pub fn compile(self, device: &DeviceHandle) -> Result<CompiledRenderGraph<'a>> {
+        scoped_alloc!(AllocId::TlsTemp);
+
+        let mut lifetimes : Vec<Option<GraphResourceLifetime>> = vec![None; self.resources.len()];
+        let mut merged_passes = Vec::with_capacity(self.passes.len());
+
+        for (pass_idx, pass) in self.passes.into_iter().enumerate() {
+            // Merge all accesses to the same resource in a pass, as a resource can only be in a single state at a time
+            let mut merged : Vec<GraphResourceAccess> = Vec::with_capacity(pass.accesses.len());
+            for access in pass.accesses {
+                let entry = &self.resources[access.resource.index()];
+                let is_texture = !matches!(entry.source, GraphResourceSource::Imported(GraphResource::Buffer(_)));
+                if is_texture && access.state.layout.is_none() {
+                    return Err(Error::InvalidParameter(format!("Pass '{}' accesses texture '{}' without a texture layout", pass.name, entry.name)));
+                }
+
+                match merged.iter_mut().find(|merged| merged.resource == access.resource) {
+                    Some(merged) => {
+                        if let (Some(merged_layout), Some(layout)) = (merged.state.layout, access.state.layout) {
+                            if merged_layout != layout {
+                                return Err(Error::InvalidParameter(format!("Pass '{}' accesses texture '{}' with multiple layouts: {merged_layout} and {layout}", pass.name, entry.name)));
+                            }
+                        }
+                        merged.state |= access.state;
+                    },
+                    None => merged.push(access),
+                }
+            }
+
+            for access in &merged {
+                let idx = access.resource.index();
+                lifetimes[idx] = Some(match lifetimes[idx] {
+                    Some(lifetime) => GraphResourceLifetime { first_pass: lifetime.first_pass, last_pass: pass_idx },
+                    None => GraphResourceLifetime { first_pass: pass_idx, last_pass: pass_idx },
+                });
+            }
+
+            merged_passes.push((pass.name, merged, pass.execute));
+        }
+
+        let mut initial_states : Vec<ResourceState> = self.resources.iter().map(|entry| entry.initial_state).collect();
+        let (resources, transients) = Self::create_transient_textures(device, &self.resources, &lifetimes, &merged_passes, &mut initial_states)?;
+
+        let mut cur_states : Vec<(ResourceState, bool)> = initial_states.into_iter().map(|state| (state, false)).collect();
+        let mut passes = Vec::with_capacity(merged_passes.len());
+        for (name, merged, execute) in merged_passes {
+            let mut barriers = Vec::new();
+            for access in &merged {
+                let idx = access.resource.index();
+                let (cur_state, cur_is_write) = cur_states[idx];
+                let is_write = is_write_access(access.state.access);
+
+                // A barrier is needed when the state changes, or to make writes visible to subsequent writes
+                if cur_state != access.state || (cur_is_write && is_write) {
+                    barriers.push(GraphBarrier { resource: access.resource, before: cur_state, after: access.state });
+                }
+                cur_states[idx] = (access.state, is_write);
+            }
+
+            passes.push(CompiledGraphPass {
+                name,
+                barriers,
+                execute,
+            });
+        }
+
+        let mut final_barriers = Vec::new();
+        for (idx, entry) in self.resources.iter().enumerate() {
+            match entry.final_state {
+                Some(final_state) if cur_states[idx].0 != final_state =>
+                    final_barriers.push(GraphBarrier { resource: GraphResourceId(idx as u32), before: cur_states[idx].0, after: final_state }),
+                _ => {},
+            }
+        }
+
+        Ok(CompiledRenderGraph {
+            resources,
+            passes,
+            final_barriers,
+            lifetimes,
+            transients,
+        })
+    }
+
+    /// Place all used transient textures in a single heap and create them, transient textures whose lifetimes don't overlap can share memory
+    ///
+    /// Returns the resources of the graph, with `None` for transient textures that are never used, and the transient textures with the heap they are placed in
+    fn create_transient_textures(
+        device: &DeviceHandle,
+        entries: &[GraphResourceEntry],
+        lifetimes: &[Option<GraphResourceLifetime>],
+        passes: &[(String, Vec<GraphResourceAccess>, Option<GraphPassExecuteFn<'a>>)],
+        initial_states: &mut [ResourceState]
+    ) -> Result<(Vec<Option<GraphResource>>, GraphTransientResources)> {
+        struct Placement {
+            idx:      usize,
+            desc:     TextureDesc,
+            offset:   u64,
+            size:     u64,
+            lifetime: GraphResourceLifetime,
+        }
+
+        let mut transients = Vec::new();
+        for (idx, entry) in entries.iter().enumerate() {
+            if let (GraphResourceSource::Transient(desc), Some(lifetime)) = (&entry.source, lifetimes[idx]) {
+                transients.push((idx, *desc, device.get_texture_allocation_info(desc), lifetime));
+            }
+        }
+        // Place the largest textures first, as smaller textures are more likely to fit in the gaps between them
+        transients.sort_by(|a, b| b.2.size.cmp(&a.2.size));
+
+        let mut placements : Vec<Placement> = Vec::with_capacity(transients.len());
+        for (idx, desc, alloc_info, lifetime) in transients {
+            // Find the lowest offset at which the texture doesn't overlap the memory of any texture that is used at the same time
+            let mut candidate = 0;
+            let offset = loop {
+                let aligned = candidate.next_multiple_of(alloc_info.alignment);
+                let conflict_end = placements.iter()
+                    .filter(|placed| placed.lifetime.overlaps(lifetime) && placed.offset < aligned + alloc_info.size && aligned < placed.offset + placed.size)
+                    .map(|placed| placed.offset + placed.size)
+                    .max();
+                match conflict_end {
+                    Some(end) => candidate = end,
+                    None => break aligned,
+                }
+            };
+            placements.push(Placement { idx, desc, offset, size: alloc_info.size, lifetime });
+        }
+
+        let mut resources : Vec<Option<GraphResource>> = entries.iter().map(|entry| match &entry.source {
+            GraphResourceSource::Imported(resource) => Some(resource.clone()),
+            GraphResourceSource::Transient(_) => None,
+        }).collect();
+
+        let mut transients = GraphTransientResources { device: device.clone(), textures: Vec::with_capacity(placements.len()), heap: None };
+        let heap_size = placements.iter().map(|placed| placed.offset + placed.size).max().unwrap_or(0);
+        if heap_size == 0 {
+            return Ok((resources, transients));
+        }
+
+        let msaa_support = placements.iter().any(|placed| placed.desc.samples.is_multisampled());
+        let heap = unsafe { device.allocate_heap(heap_size, msaa_support, MemoryType::Gpu, device.gpu_allocator().memory_info())? };
+        heap.set_debug_name("render graph transient heap");
+        // When creating a texture fails, dropping the transient resources will free the heap
+        transients.heap = Some(heap.clone());
+
+        for placed in &placements {
+            // SAFETY: Textures sharing memory never have overlapping lifetimes, and the first barrier of each texture transitions it from the undefined layout
+            let texture = unsafe { device.create_placed_texture(&placed.desc, &heap, placed.offset)? };
+            texture.set_debug_name(&entries[placed.idx].name);
+            transients.textures.push(texture.clone());
+            resources[placed.idx] = Some(GraphResource::Texture(texture));
+
+            // The texture can only start using its memory once all earlier textures that used the same memory are done with it
+            let sync_point = placements.iter()
+                .filter(|other| other.lifetime.last_pass < placed.lifetime.first_pass && other.offset < placed.offset + placed.size && placed.offset < other.offset + other.size)
+                .filter_map(|other| passes[other.lifetime.last_pass].1.iter().find(|access| access.resource.index() == other.idx))
+                .fold(SyncPoint::None, |sync_point, access| sync_point | access.state.sync_point);
+            if !sync_point.is_none() {
+                initial_states[placed.idx] = ResourceState::new_tex(Access::None, sync_point, TextureLayout::Undefined);
+            }
+        }
+
+        Ok((resources, transients))
+    }
+
+    fn add_resource(&mut self, name: &str, source: GraphResourceSource, initial_state: ResourceState, final_state: Option<ResourceState>) -> GraphResourceId {
+        let id = GraphResourceId(self.resources.len() as u32);
+        self.resources.push(GraphResourceEntry {
+            name: name.to_string(),
+            source,
+            initial_state,
+            final_state,
+        });
+        id
+    }
+}
+
+/// Builder used to declare the resources used by a pass
+pub struct PassBuilder<'g, 'a> {
+    graph:    &'g mut RenderGraph<'a>,
+    name:     String,
+    accesses: Vec<GraphResourceAccess>,
+}
+
+impl<'g, 'a> PassBuilder<'g, 'a> {
+    /// Declare that the pass reads from `resource`, while it is in the given state
+    pub fn read(self, resource: GraphResourceId, state: ResourceState) -> Self {
+        self.access(resource, state)
+    }
+
+    /// Declare that the pass writes to `resource`, while it is in the given state
+    pub fn write(self, resource: GraphResourceId, state: ResourceState) -> Self {
+        self.access(resource, state)
+    }
+
+    /// Finish the pass declaration and add it to the graph, with a closure that will record the pass' commands
+    ///
+    /// The closure can get the resources used by the pass, including transient textures, from the `GraphResources` it is passed
+    pub fn execute<F: FnOnce(&GraphicsCommandList, &GraphResources) + 'a>(self, execute: F) {
+        self.graph.passes.push(GraphPass {
+            name: self.name,
+            accesses: self.accesses,
+            execute: Some(Box::new(execute)),
+        });
+    }
+
+    fn access(mut self, resource: GraphResourceId, state: ResourceState) -> Self {
+        assert!(resource.index() < self.graph.resources.len(), "Pass '{}' uses a resource that is not part of the graph: {resource}", self.name);
+        self.accesses.push(GraphResourceAccess { resource, state });
+        self
+    }
+}
+
+/// Resources of a compiled render graph, passed to the passes when they are executed
+pub struct GraphResources<'r> {
+    resources: &'r [Option<GraphResource>],
+}
+
+impl GraphResources<'_> {
+    /// Get a resource, `None` if the resource is a transient texture that is not used by any pass
+    pub fn get(&self, id: GraphResourceId) -> Option<&GraphResource> {
+        self.resources.get(id.index()).and_then(|resource| resource.as_ref())
+    }
+
+    /// Get a texture, `None` if the resource is not a texture, or it's a transient texture that is not used by any pass
+    pub fn texture(&self, id: GraphResourceId) -> Option<&TextureHandle> {
+        match self.get(id) {
+            Some(GraphResource::Texture(texture)) => Some(texture),
+            _ => None,
+        }
+    }
+
+    /// Get a buffer, `None` if the resource is not a buffer
+    pub fn buffer(&self, id: GraphResourceId) -> Option<&BufferHandle> {
+        match self.get(id) {
+            Some(GraphResource::Buffer(buffer)) => Some(buffer),
+            _ => None,
+        }
+    }
+}
+
+//==============================================================================================================================
+
+struct GraphBarrier {
+    resource: GraphResourceId,
+    before:   ResourceState,
+    after:    ResourceState,
+}
+
+struct CompiledGraphPass<'a> {
+    name:     String,
+    barriers: Vec<GraphBarrier>,
+    execute:  Option<GraphPassExecuteFn<'a>>,
+}
+
+/// Compiled render graph, ready to be recorded into a command list
+pub struct CompiledRenderGraph<'a> {
+    resources:      Vec<Option<GraphResource>>,
+    passes:         Vec<CompiledGraphPass<'a>>,
+    final_barriers: Vec<GraphBarrier>,
+    lifetimes:      Vec<Option<GraphResourceLifetime>>,
+    // Declared after `resources`, so the handles to the transient textures are released before the heap they are placed in is freed
+    transients:     GraphTransientResources,
+}
+
+impl<'a> CompiledRenderGraph<'a> {
+    /// Get the names of the passes, in the order they will be executed
+    pub fn pass_names(&self) -> impl Iterator<Item = &str> {
+        self.passes.iter().map(|pass| pass.name.as_str())
+    }
+
+    /// Get the lifetime of a resource, `None` if the resource isn't used by any pass
+    pub fn resource_lifetime(&self, id: GraphResourceId) -> Option<GraphResourceLifetime> {
+        self.lifetimes.get(id.index()).copied().flatten()
+    }
+
+    /// Get a resource, including transient textures, `None` if the resource is a transient texture that is not used by any pass
+    pub fn get_resource(&self, id: GraphResourceId) -> Option<&GraphResource> {
+        self.resources.get(id.index()).and_then(|resource| resource.as_ref())
+    }
+
+    /// Get the size of the heap the transient textures are placed in, which is 0 when the graph doesn't use any transient textures
+    pub fn transient_heap_size(&self) -> u64 {
+        self.transients.heap.as_ref().map_or(0, |heap| heap.size())
+    }
+
+    /// Get the number of barriers the graph will record
+    pub fn barrier_count(&self) -> usize {
+        self.passes.iter().map(|pass| pass.barriers.len()).sum::<usize>() + self.final_barriers.len()
+    }
+
+    /// Record all passes and their barriers into the command list
+    ///
+    /// The returned transient resources need to be kept alive until the command list has finished executing on the GPU
+    #[must_use]
+    pub fn execute(self, command_list: &GraphicsCommandList) -> GraphTransientResources {
+        let Self { resources, mut passes, final_barriers, transients, .. } = self;
+        scoped_alloc!(AllocId::TlsTemp);
+
+        let graph_resources = GraphResources { resources: &resources };
+        for pass in &mut passes {
+            if !pass.barriers.is_empty() {
+                let barriers = Self::create_barriers(&resources, &pass.barriers);
+                command_list.barrier(&barriers);
+            }
+
+            if let Some(execute) = pass.execute.take() {
+                execute(command_list, &graph_resources);
+            }
+        }
+
+        if !final_barriers.is_empty() {
+            let barriers = Self::create_barriers(&resources, &final_barriers);
+            command_list.barrier(&barriers);
+        }
+
+        transients
+    }
+
+    fn create_barriers(resources: &[Option<GraphResource>], graph_barriers: &[GraphBarrier]) -> Vec<Barrier> {
+        graph_barriers.iter().map(|barrier| match resources[barrier.resource.index()].as_ref().expect("Barriers are only created for used resources") {
+            GraphResource::Texture(texture) => Barrier::new_basic_texture(barrier.before, barrier.after, texture.clone()),
+            GraphResource::Buffer(buffer) => Barrier::Buffer {
+                before: barrier.before,
+                after: barrier.after,
+                buffer: buffer.clone(),
+                offset: 0,
+                size: buffer.size(),
+                queue_transfer_op: BarrierQueueTransferOp::None,
+            },
+        }).collect()
+    }
+}
+
+/// Transient textures of an executed render graph, and the heap they are placed in
+///
+/// These need to be kept alive until the command list the graph was recorded into has finished executing on the GPU, the heap is freed when this is dropped.
+/// Handles to transient textures should not be kept around after this is dropped, as the memory they are placed in is no longer valid.
+pub struct GraphTransientResources {
+    device:   DeviceHandle,
+    textures: Vec<TextureHandle>,
+    heap:     Option<MemoryHeapHandle>,
+}
+
+impl Drop for GraphTransientResources {
+    fn drop(&mut self) {
+        // Textures need to be destroyed before the memory they are placed in is freed
+        self.textures.clear();
+        if let Some(heap) = self.heap.take() {
+            unsafe { self.device.free_heap(heap) };
+        }
+    }
+}
+
+fn is_write_access(access: Access) -> bool {
+    access.intersects(
+        Access::RenderTargetWrite |
+        Access::DepthStencilWrite |
+        Access::StorageWrite |
+        Access::ShaderWrite |
+        Access::AccelerationStructureWrite |
+        Access::CopyWrite |
+        Access::ResolveWrite |
+        Access::HostWrite |
+        Access::MemoryWrite |
+        Access::VideoDecodeWrite |
+        Access::VideoProcessWrite |
+        Access::VideoEncodeWrite
+    )
+}
@@ -78,6 +78,38 @@ macro_rules! create_ral_handle {
                 &self.handle
             }
         }
+
+        impl $ty {
+            /// Set the debug name, which will show up in graphics debuggers and validation messages
+            pub fn set_debug_name(&self, name: &str) {
+                unsafe { self.handle.set_debug_name(name) }
+            }
+        }
+    };
+    // Variant for types which keep a copy of their debug name in a `debug_name: RwLock<String>` field, so the RAL's own validation can refer to them by name
+    ($iden:ident, $ty:ty, $iface:ident, debug_name) => {
+        pub type $iden = Handle<$ty>;
+
+        impl HandleImpl for $ty {
+            type InterfaceHandle = $iface;
+
+            unsafe fn interface(&self) -> &Self::InterfaceHandle {
+                &self.handle
+            }
+        }
+
+        impl $ty {
+            /// Set the debug name, which will show up in graphics debuggers and validation messages
+            pub fn set_debug_name(&self, name: &str) {
+                *self.debug_name.write() = name.to_string();
+                unsafe { self.handle.set_debug_name(name) }
+            }
+
+            /// Get the debug name
+            pub fn debug_name(&self) -> String {
+                self.debug_name.read().clone()
+            }
+        }
     };
 }
 pub(crate) use create_ral_handle;
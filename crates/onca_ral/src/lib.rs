@@ -45,8 +45,18 @@ mod pipeline;
 mod buffer;
 mod memory;
 mod sampler;
+mod query;
+mod upload;
+mod raytracing;
+mod bindless;
+mod pipeline_cache;
+mod capture;
+mod sparse;
+#[cfg(feature = "validation")]
+mod validation;
 
 pub mod api;
+pub mod graph;
 
 pub use common::*;
 use onca_logging::LogCategory;
@@ -66,6 +76,14 @@ pub use pipeline::*;
 pub use buffer::*;
 pub use memory::*;
 pub use sampler::*;
+pub use query::*;
+pub use upload::*;
+pub use raytracing::*;
+pub use bindless::*;
+pub use pipeline_cache::*;
+pub use sparse::*;
+#[cfg(feature = "validation")]
+pub use validation::is_validation_layer_enabled;
 
 pub const LOG_CAT : LogCategory = LogCategory::new("RAL");
 
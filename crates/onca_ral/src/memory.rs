@@ -1,7 +1,7 @@
-use onca_common::prelude::*;
+use onca_common::{prelude::*, sync::Mutex};
 use onca_common_macros::flags;
 
-use crate::{Result, handle::InterfaceHandle, HandleImpl, Handle, MemoryType, WeakHandle, Device, MemoryInfo, Error, DeviceHandle, MemAlign};
+use crate::{Result, handle::InterfaceHandle, HandleImpl, Handle, MemoryType, WeakHandle, Device, MemoryInfo, Error, DeviceHandle, MemAlign, MemoryHeapType, MemoryBudgetInfo};
 
 
 #[flags]
@@ -33,6 +33,15 @@ pub struct ApiMemoryRequest {
     pub memory_types:      Vec<MemoryType>,
 }
 
+/// Memory needed to place a resource in a memory heap
+#[derive(Clone, Copy, Debug)]
+pub struct ResourceAllocationInfo {
+    /// Size of the resource in memory
+    pub size:      u64,
+    /// Required alignment of the offset the resource is placed at
+    pub alignment: u64,
+}
+
 //==============================================================================================================================
 
 #[derive(Clone, Copy, Debug)]
@@ -58,26 +67,46 @@ impl GpuAddress {
 
 //==============================================================================================================================
 
-pub trait MemoryHeapInterface {}
+pub trait MemoryHeapInterface {
+    /// Set the debug name of the memory heap
+    unsafe fn set_debug_name(&self, name: &str);
+}
 pub type MemoryHeapInterfaceHandle = InterfaceHandle<dyn MemoryHeapInterface>;
 
 /// Memory heap
 pub struct MemoryHeap {
     handle:       MemoryHeapInterfaceHandle,
+    size:         u64,
+    memory_type:  MemoryType,
     msaa_support: bool,
 }
 
 pub type MemoryHeapHandle = Handle<MemoryHeap>;
 
 impl MemoryHeap {
-    pub(crate) fn new(handle: MemoryHeapInterfaceHandle, msaa_support: bool) -> Self {
-        Self { handle, msaa_support }
+    pub(crate) fn new(handle: MemoryHeapInterfaceHandle, size: u64, memory_type: MemoryType, msaa_support: bool) -> Self {
+        Self { handle, size, memory_type, msaa_support }
+    }
+
+    /// Get the size of the memory heap
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Get the memory type of the memory heap
+    pub fn memory_type(&self) -> MemoryType {
+        self.memory_type
     }
 
     /// Check if te memory heap supports MSAA
     pub fn has_msaa_support(&self) -> bool {
         self.msaa_support
     }
+
+    /// Set the debug name, which will show up in graphics debuggers and validation messages
+    pub fn set_debug_name(&self, name: &str) {
+        unsafe { self.handle.set_debug_name(name) }
+    }
 }
 
 impl HandleImpl for MemoryHeap {
@@ -149,6 +178,8 @@ impl GpuAllocation {
 pub trait GpuAllocatorInterface {
     unsafe fn alloc(&self, device: &DeviceHandle, mem_info: &MemoryInfo, size: u64, desc: GpuAllocationDesc, api_req: ApiMemoryRequest) -> Result<GpuAllocation>;
     unsafe fn free(&self, device: &DeviceHandle, allocation: GpuAllocation);
+    /// Get the size of the largest free block that can be allocated from without allocating a new heap
+    fn largest_free_block(&self, heap_type: MemoryHeapType) -> u64;
 }
 
 pub enum GpuAllocatorImpl {
@@ -158,12 +189,48 @@ pub enum GpuAllocatorImpl {
     Custom(Box<dyn GpuAllocatorInterface>),
 }
 
+/// GPU allocator statistics for a single memory heap type
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GpuHeapStats {
+    /// Total size of all memory heaps
+    pub total_size:         u64,
+    /// Size of all memory allocated from the memory heaps
+    pub used_size:          u64,
+    /// Size of the largest free block in the memory heaps
+    pub largest_free_block: u64,
+    /// Number of memory heaps
+    pub num_heaps:          u32,
+    /// Number of allocations
+    pub num_allocations:    u32,
+}
+
+/// GPU allocator statistics
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GpuAllocatorStats {
+    /// Statistics per memory heap type
+    pub heaps: [GpuHeapStats; MemoryHeapType::COUNT],
+    /// Combined statistics of all memory heap types
+    pub total: GpuHeapStats,
+}
+
+/// Event send when the OS memory budget changes
+/// 
+/// This event is send when the budget of any heap changes, or when the memory in use on a heap goes over or back under its budget
+#[derive(Clone, Copy, Debug)]
+pub struct MemoryBudgetEvent {
+    /// Previous memory budget, `None` if this is the first time the budget is queried
+    pub old: Option<MemoryBudgetInfo>,
+    /// New memory budget
+    pub new: MemoryBudgetInfo,
+}
+
 /// Wrapper around the chosen GPU alloctor
 pub struct GpuAllocator {
     device:     WeakHandle<Device>,
     mem_info:   MemoryInfo,
     alloc_impl: GpuAllocatorImpl,
-    def_alloc:  DefaultGpuAllocator
+    def_alloc:  DefaultGpuAllocator,
+    stats:      Mutex<GpuAllocatorStats>,
 }
 
 impl GpuAllocator {
@@ -174,6 +241,7 @@ impl GpuAllocator {
             mem_info,
             alloc_impl,
             def_alloc: DefaultGpuAllocator {  },
+            stats: Mutex::new(GpuAllocatorStats::default()),
         }
     }
 
@@ -190,21 +258,78 @@ impl GpuAllocator {
 
         let device = WeakHandle::upgrade(&self.device).ok_or(Error::UseAfterDeviceDropped)?;
 
-        match &self.alloc_impl {
+        let allocation = match &self.alloc_impl {
             GpuAllocatorImpl::Default => self.def_alloc.alloc(&device, &self.mem_info, size, desc, api_req),
             GpuAllocatorImpl::Custom(alloc) => alloc.alloc(&device, &self.mem_info, size, desc, api_req),
-        }
+        }?;
+
+        let mut stats = self.stats.lock();
+        let heap_stats = &mut stats.heaps[self.get_heap_type(allocation.heap.memory_type()) as usize];
+        heap_stats.used_size += allocation.size;
+        heap_stats.num_allocations += 1;
+
+        Ok(allocation)
     }
 
     /// Free memory on the GPU
     pub unsafe fn free(&self, allocation: GpuAllocation) {
         let device = WeakHandle::upgrade(&self.device).unwrap();
 
+        {
+            let mut stats = self.stats.lock();
+            let heap_stats = &mut stats.heaps[self.get_heap_type(allocation.heap.memory_type()) as usize];
+            heap_stats.used_size -= allocation.size;
+            heap_stats.num_allocations -= 1;
+        }
+
         match &self.alloc_impl {
             GpuAllocatorImpl::Default => self.def_alloc.free(&device, allocation),
             GpuAllocatorImpl::Custom(alloc) => alloc.free(&device, allocation),
         }
     }
+
+    /// Get the current allocator statistics
+    pub fn stats(&self) -> GpuAllocatorStats {
+        let mut stats = *self.stats.lock();
+        for (idx, heap_stats) in stats.heaps.iter_mut().enumerate() {
+            let heap_type = self.mem_info.heaps[idx].heap_type;
+            heap_stats.largest_free_block = match &self.alloc_impl {
+                GpuAllocatorImpl::Default => self.def_alloc.largest_free_block(heap_type),
+                GpuAllocatorImpl::Custom(alloc) => alloc.largest_free_block(heap_type),
+            };
+        }
+
+        let mut total = GpuHeapStats::default();
+        for heap_stats in &stats.heaps {
+            total.total_size += heap_stats.total_size;
+            total.used_size += heap_stats.used_size;
+            total.largest_free_block = total.largest_free_block.max(heap_stats.largest_free_block);
+            total.num_heaps += heap_stats.num_heaps;
+            total.num_allocations += heap_stats.num_allocations;
+        }
+        stats.total = total;
+        stats
+    }
+
+    /// Track a newly allocated memory heap
+    pub(crate) fn track_heap_alloc(&self, heap: &MemoryHeap) {
+        let mut stats = self.stats.lock();
+        let heap_stats = &mut stats.heaps[self.get_heap_type(heap.memory_type) as usize];
+        heap_stats.total_size += heap.size;
+        heap_stats.num_heaps += 1;
+    }
+
+    /// Track a freed memory heap
+    pub(crate) fn track_heap_free(&self, heap: &MemoryHeap) {
+        let mut stats = self.stats.lock();
+        let heap_stats = &mut stats.heaps[self.get_heap_type(heap.memory_type) as usize];
+        heap_stats.total_size -= heap.size;
+        heap_stats.num_heaps -= 1;
+    }
+
+    fn get_heap_type(&self, memory_type: MemoryType) -> MemoryHeapType {
+        self.mem_info.mem_types[memory_type as usize].heap_type
+    }
 }
 
 
@@ -241,6 +366,11 @@ impl GpuAllocatorInterface for DefaultGpuAllocator {
             unimplemented!("We don't handle freeing of non-dedicated allocations yet")
         }
     }
+
+    fn largest_free_block(&self, _heap_type: MemoryHeapType) -> u64 {
+        // All allocations are currently dedicated, so heaps never have any free blocks
+        0
+    }
 }
 
 //==============================================================================================================================
@@ -34,6 +34,10 @@ pub enum Capabilities {
 	/// Can a scalar [0; 1] be used to define a minimum number of shaders that should be invoked per pixel, relative to the sample count?
 	// NOTE: Can this be done using DX12 force samples?
 	MinSampleShading,
+	/// Are ASTC (LDR) compressed formats supported?
+	///
+	/// DX12 does not support ASTC, so this is only available on Vulkan
+	TextureCompressionASTC,
 }
 
 /// Shader caching support by the current driver
@@ -135,6 +139,10 @@ pub struct Properties {
 	pub graphics_preempt: GraphicsPreemptionGranularity,
 	/// Compute preemption granularity
 	pub compure_preempt:  ComputePreemptionGranularity,
+	/// Maximum total size of all constant ranges in a pipeline layout, in bytes
+	/// 
+	/// This is at least `constants::MAX_PIPELINE_PUSH_CONSTANT_SIZE`
+	pub max_pipeline_constant_size: u32,
 }
 
 //==============================================================================================================================
@@ -206,6 +214,8 @@ pub enum RaytracingSupportFlags {
 	IndirectBuild,
 	/// Support for invocation reordering / shader execution reordering (SER)
 	InvocationReordering,
+	/// Support for inline raytracing using ray queries in any shader stage
+	RayQuery,
 }
 
 /// Raytracing support
@@ -215,6 +225,8 @@ pub struct RaytracingSupport {
 	pub flags:                                          RaytracingSupportFlags,
 	/// Hint inidicating the actual reordering
 	pub invocation_reorder_mode:                        InvocationReorderMode,
+	/// Maximum recursion depth of `trace_ray()` calls in a raytracing pipeline
+	pub max_recursion_depth:                            u32,
 }
 
 //==============================================================================================================================
@@ -222,6 +234,7 @@ pub struct RaytracingSupport {
 //==============================================================================================================================
 
 /// Maximum support variable shading rate tile size
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum VariableRateShadingAttachmentTileSize { 
     /// 8x8 tile size
     Tile8x8,
@@ -230,6 +243,9 @@ pub enum VariableRateShadingAttachmentTileSize {
 }
 
 /// Variable rate shading (VRS) support
+/// 
+/// Per-draw, per-primitive, and attachment based shading rates are required to be supported
+#[derive(Clone, Copy, Debug)]
 pub struct VariableRateShadingSupport {
     /// Size of the attachment tiles
     pub attachment_tile_size          : VariableRateShadingAttachmentTileSize,
@@ -237,6 +253,22 @@ pub struct VariableRateShadingSupport {
     pub large_shading_rates_supported : bool,
 }
 
+impl VariableRateShadingSupport {
+    /// Check if a shading rate is supported
+    pub fn is_shading_rate_supported(&self, rate: ShadingRate) -> bool {
+        !rate.is_large() || self.large_shading_rates_supported
+    }
+
+    /// Get the size of a shading rate attachment needed to cover a render area of `width` x `height`
+    pub fn attachment_size(&self, width: u32, height: u32) -> (u32, u32) {
+        let tile_size = match self.attachment_tile_size {
+            VariableRateShadingAttachmentTileSize::Tile8x8 => 8,
+            VariableRateShadingAttachmentTileSize::Tile16x16 => 16,
+        };
+        (width.div_ceil(tile_size), height.div_ceil(tile_size))
+    }
+}
+
 //==============================================================================================================================
 // SAMPLER FEEDBACK
 //==============================================================================================================================
@@ -326,6 +358,8 @@ pub trait PhysicalDeviceInterface {
 	fn get_memory_budget_info(&self) -> crate::Result<MemoryBudgetInfo>;
 	/// Request an amount of memory to be reserved for the device
 	fn reserve_memory(&self, heap_idx: u8, bytes: u64) -> crate::Result<()>;
+	/// Get the sample counts a 2D texture with the given format supports when used as a render target or depth stencil
+	fn get_supported_sample_counts(&self, format: Format) -> SampleCountSupport;
 
 	// TODO: Get sparse properties for a texture from its format, usages, sample counts, type, tiling, etc
 	// Probably via device, not physical device
@@ -363,3 +397,15 @@ pub struct PhysicalDevice {
     /// Queue info
     pub queue_infos:            [QueueInfo; QueueType::COUNT],
 }
+
+impl PhysicalDevice {
+	/// Get the sample counts a 2D texture with the given format supports when used as a render target or depth stencil
+	/// 
+	/// Formats that cannot be used as a render target or depth stencil only support `SampleCountSupport::Sample1`
+	pub fn get_supported_sample_counts(&self, format: Format) -> SampleCountSupport {
+		if !format.get_support().intersects(FormatSupport::RenderTarget | FormatSupport::DepthStencil) {
+			return SampleCountSupport::Sample1;
+		}
+		self.handle.get_supported_sample_counts(format)
+	}
+}
@@ -3,7 +3,8 @@ use onca_common_macros::flags;
 
 use crate::{
     handle::{InterfaceHandle, create_ral_handle},
-    Handle, HandleImpl, Result, StaticSamplerHandle, ShaderVisibility, Error, constants, InlineDescriptorDesc, DescriptorTableLayoutHandle,
+    Handle, HandleImpl, Result, PhysicalDevice, StaticSamplerHandle, ShaderVisibility, Error, constants, InlineDescriptorDesc, DescriptorTableLayoutHandle,
+    check_invalid_parameter,
 };
 
 
@@ -14,11 +15,22 @@ pub enum PipelineLayoutFlags {
     /// 
     /// On certain hardware, this can allow space to be saved in the pipeline layout
     ContainsInputLayout,
+    /// Shaders can directly index the bound descriptor heaps using `ResourceIndex`es from a `BindlessTable`
+    /// 
+    /// Both a resource and sampler descriptor heap need to be bound when binding a pipeline layout with this flag
+    /// 
+    /// - DX12: Heaps are accessed using `ResourceDescriptorHeap` and `SamplerDescriptorHeap`
+    /// - Vulkan: Heaps are accessed using 2 additional descriptor sets, each containing a single unbounded array, which come after all other entries
+    Bindless,
 }
 
+/// Range of 32-bit constants which can be set directly on a command list, without needing a constant buffer
+/// 
+/// - DX12: Root constants
+/// - Vulkan: Push constants
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct PipelineConstantRange {
-    /// Number of constants
+    /// Number of 32-bit constants
     pub count:      u8,
     /// Shader visibility
     pub visibility: ShaderVisibility,
@@ -33,6 +45,7 @@ pub struct PipelineConstantRange {
 /// - Inline descriptors
 /// - Constants
 /// - Static samplers
+/// - Bindless resource and sampler heaps (Vulkan only, see `PipelineLayoutFlags::Bindless`)
 /// 
 /// With the index of an 'entry' here referring to:
 /// - DX12: A register space
@@ -60,13 +73,14 @@ pub struct PipelineLayoutDesc {
 impl PipelineLayoutDesc {
     const MAX_SLOTS: u32 = 64;
 
-    pub fn validate(&self) -> Result<()> {
+    pub fn validate(&self, phys_dev: &PhysicalDevice) -> Result<()> {
         #[cfg(feature = "validation")]
         {
             let num_tables = self.descriptor_tables.as_ref().map_or(0, |arr| arr.len() as u32);
             let num_inline = self.inline_descriptors.as_ref().map_or(0, |arr| arr.len() as u32);
             let has_static_samplers = self.static_samplers.is_some();
-            let num_entries = num_tables + num_inline + has_static_samplers as u32;
+            let num_bindless = if self.flags.contains(PipelineLayoutFlags::Bindless) { 2 } else { 0 };
+            let num_entries = num_tables + num_inline + has_static_samplers as u32 + num_bindless;
 
             if num_entries + has_static_samplers as u32 > constants::MAX_PIPELINE_BOUND_DESCRIPTORS {
                 return Err(Error::InvalidParameter(format!(
@@ -78,10 +92,14 @@ impl PipelineLayoutDesc {
             
             let mut num_constants = 0;
             if let Some(constants) = &self.constant_ranges {   
-                for constant_range in constants {
+                for (idx, constant_range) in constants.iter().enumerate() {
+                    check_invalid_parameter!(constant_range.count > 0, "Constant range {idx} does not contain any constants");
                     num_constants += constant_range.count as u32;
                 }
             }
+            let max_constant_size = phys_dev.properties.max_pipeline_constant_size;
+            check_invalid_parameter!(num_constants * 4 <= max_constant_size,
+                "Total size of all constant ranges ({} bytes) exceeds the maximum of {} bytes supported by the device", num_constants * 4, max_constant_size);
             
 
             let used_slots = num_tables + num_inline * 2 + num_constants + has_static_samplers as u32;
@@ -122,12 +140,31 @@ impl PipelineLayoutDesc {
         }
         Ok(())
     }
+
+    /// Get the offset of the constant range at `index` (in 32-bit values), relative to the start of all constants in the layout
+    pub fn constant_range_offset(&self, index: u32) -> u32 {
+        self.constant_ranges.as_ref().map_or(0, |ranges| ranges.iter().take(index as usize).map(|range| range.count as u32).sum())
+    }
+
+    /// Validate that `count` constants can be written to the constant range at `index`, starting at `offset` (in 32-bit values)
+    #[cfg(feature = "validation")]
+    pub(crate) fn validate_constants(&self, index: u32, offset: u32, count: usize) -> Result<()> {
+        let range = match self.constant_ranges.as_ref().and_then(|ranges| ranges.get(index as usize)) {
+            Some(range) => range,
+            None => return Err(Error::InvalidParameter(format!("The bound pipeline layout does not have a constant range at index {index}"))),
+        };
+        check_invalid_parameter!(count > 0, "Cannot set 0 constants");
+        check_invalid_parameter!(offset as u64 + count as u64 <= range.count as u64,
+            "Constants go out of bounds of constant range {index}, offset + count: {}, range size: {}", offset as u64 + count as u64, range.count);
+        Ok(())
+    }
 }
 
 //==============================================================================================================================
 
 pub trait PipelineLayoutInterface {
-
+    /// Set the debug name of the pipeline layout
+    unsafe fn set_debug_name(&self, name: &str);
 }
 
 pub type PipelineLayoutInterfaceHandle = InterfaceHandle<dyn PipelineLayoutInterface>;
@@ -170,7 +207,8 @@ impl PipelineLayoutHandle {
 //==============================================================================================================================
 
 pub trait PipelineInterface {
-
+    /// Set the debug name of the pipeline
+    unsafe fn set_debug_name(&self, name: &str);
 }
 
 pub type PipelineInterfaceHandle = InterfaceHandle<dyn PipelineInterface>;
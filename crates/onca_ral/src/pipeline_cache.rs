@@ -0,0 +1,200 @@
+use core::task::Poll;
+
+use onca_common::{prelude::*, io::{self, AsyncIOResult, AsyncRead, Write}};
+use onca_fs::{File, FileAccessFlags, FileCreateFlags, OpenMode, Path, Permission, AsyncReadResult};
+use onca_logging::log_warning;
+
+use crate::{
+    handle::{InterfaceHandle, create_ral_handle},
+    *,
+};
+
+/// Magic identifying serialized pipeline cache data
+const PIPELINE_CACHE_MAGIC: [u8; 4] = *b"OPCD";
+/// Version of the serialized pipeline cache header
+const PIPELINE_CACHE_VERSION: u32 = 1;
+/// Size of the serialized pipeline cache header
+const PIPELINE_CACHE_HEADER_SIZE: usize = 40;
+
+/// Key identifying the device and driver a pipeline cache was created for
+/// 
+/// Driver pipeline caches can only be reused by the same device and driver, so when the key of serialized cache data does not match the current device, the data will be discarded
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PipelineCacheKey {
+    /// Vendor ID
+    pub vendor_id:      u32,
+    /// Product ID
+    pub product_id:     u32,
+    /// Driver version
+    pub driver_version: Version,
+    /// API version
+    pub api_version:    Version,
+}
+
+impl PipelineCacheKey {
+    /// Get the pipeline cache key for a physical device
+    pub fn from_physical_device(phys_dev: &PhysicalDevice) -> Self {
+        Self {
+            vendor_id: phys_dev.properties.vendor_id,
+            product_id: phys_dev.properties.product_id,
+            driver_version: phys_dev.properties.driver_version,
+            api_version: phys_dev.properties.api_version,
+        }
+    }
+
+    fn write_header(&self, data_size: u64, dst: &mut Vec<u8>) {
+        dst.extend_from_slice(&PIPELINE_CACHE_MAGIC);
+        dst.extend_from_slice(&PIPELINE_CACHE_VERSION.to_le_bytes());
+        dst.extend_from_slice(&self.vendor_id.to_le_bytes());
+        dst.extend_from_slice(&self.product_id.to_le_bytes());
+        for version in [self.driver_version, self.api_version] {
+            dst.extend_from_slice(&version.major.to_le_bytes());
+            dst.extend_from_slice(&version.minor.to_le_bytes());
+            dst.extend_from_slice(&version.patch.to_le_bytes());
+        }
+        // Padding to keep the driver data 8-byte aligned
+        dst.extend_from_slice(&[0; 4]);
+        dst.extend_from_slice(&data_size.to_le_bytes());
+    }
+
+    /// Get the driver data from serialized pipeline cache data, or `None` if the data was not created for a device and driver matching this key
+    pub(crate) fn get_driver_data<'a>(&self, data: &'a [u8]) -> Option<&'a [u8]> {
+        if data.len() < PIPELINE_CACHE_HEADER_SIZE {
+            return None;
+        }
+
+        let mut header = Vec::with_capacity(PIPELINE_CACHE_HEADER_SIZE);
+        let data_size = u64::from_le_bytes(data[32..40].try_into().unwrap());
+        self.write_header(data_size, &mut header);
+
+        let driver_data = &data[PIPELINE_CACHE_HEADER_SIZE..];
+        if header != data[..PIPELINE_CACHE_HEADER_SIZE] || driver_data.len() as u64 != data_size {
+            return None;
+        }
+        Some(driver_data)
+    }
+}
+
+//==============================================================================================================================
+
+pub trait PipelineCacheInterface {
+    /// Get the driver data of the pipeline cache
+    unsafe fn get_data(&self) -> Result<Vec<u8>>;
+
+    /// Set the debug name of the pipeline cache
+    unsafe fn set_debug_name(&self, name: &str);
+}
+
+pub type PipelineCacheInterfaceHandle = InterfaceHandle<dyn PipelineCacheInterface>;
+
+/// Pipeline cache
+/// 
+/// A pipeline cache stores driver compiled pipelines, so they can be reused across runs of the application, removing the cost of compiling pipelines on first use.
+/// 
+/// - DX12: `ID3D12PipelineLibrary`
+/// - Vulkan: `VkPipelineCache`
+pub struct PipelineCache {
+    handle: PipelineCacheInterfaceHandle,
+    key:    PipelineCacheKey,
+}
+create_ral_handle!(PipelineCacheHandle, PipelineCache, PipelineCacheInterfaceHandle);
+
+impl PipelineCacheHandle {
+    pub(crate) fn create(handle: PipelineCacheInterfaceHandle, key: PipelineCacheKey) -> Self {
+        Self::new(PipelineCache {
+            handle,
+            key,
+        })
+    }
+
+    /// Get the key of the device and driver the pipeline cache was created for
+    pub fn key(&self) -> PipelineCacheKey {
+        self.key
+    }
+
+    /// Serialize the pipeline cache, so it can be used to create a pipeline cache during a later run
+    pub fn serialize(&self) -> Result<Vec<u8>> {
+        let driver_data = unsafe { self.handle.get_data()? };
+
+        let mut data = Vec::with_capacity(PIPELINE_CACHE_HEADER_SIZE + driver_data.len());
+        self.key.write_header(driver_data.len() as u64, &mut data);
+        data.extend_from_slice(&driver_data);
+        Ok(data)
+    }
+
+    /// Serialize the pipeline cache and write it to a file
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let data = self.serialize()?;
+        let mut file = File::create(path, OpenMode::CreateAlways, Permission::Write, Permission::None, FileCreateFlags::None, FileAccessFlags::None)
+            .map_err(|err| Error::Other(format!("Failed to create pipeline cache file '{path}': {err}")))?;
+        file.write_all(&data).map_err(|err| Error::Other(format!("Failed to write pipeline cache file '{path}': {err}")))
+    }
+}
+
+//==============================================================================================================================
+
+enum PipelineCacheLoadState {
+    Reading(AsyncReadResult),
+    Done(Option<Vec<u8>>),
+}
+
+/// Asynchronous pipeline cache loader
+/// 
+/// Allows the pipeline cache to be read from disk in the background while the rest of the application boots.
+/// When the cache file does not exist or cannot be read, an empty pipeline cache will be created instead.
+pub struct PipelineCacheLoader {
+    state: PipelineCacheLoadState,
+}
+
+impl PipelineCacheLoader {
+    /// Start loading a pipeline cache from a file
+    pub fn load(path: &Path) -> Self {
+        let read = File::open(path, Permission::Read, Permission::None, FileAccessFlags::SupportAsync | FileAccessFlags::SequentialAccess)
+            .and_then(|mut file| {
+                let size = file.get_metadata()?.file_size;
+                file.read_async(size)
+            });
+
+        let state = match read {
+            Ok(read) => PipelineCacheLoadState::Reading(read),
+            Err(err) => {
+                log_warning!(LOG_CAT, "Failed to read pipeline cache file '{path}', starting with an empty cache: {err}");
+                PipelineCacheLoadState::Done(None)
+            },
+        };
+        Self { state }
+    }
+
+    /// Check if the pipeline cache file has been read
+    pub fn is_loaded(&mut self) -> bool {
+        if let PipelineCacheLoadState::Reading(read) = &mut self.state {
+            if let Poll::Ready(res) = read.poll() {
+                self.state = PipelineCacheLoadState::Done(Self::get_data(res));
+            }
+        }
+        matches!(self.state, PipelineCacheLoadState::Done(_))
+    }
+
+    /// Wait until the pipeline cache file has been read and create the pipeline cache
+    pub fn finish(self, device: &DeviceHandle) -> Result<PipelineCacheHandle> {
+        let data = match self.state {
+            PipelineCacheLoadState::Reading(mut read) => loop {
+                if let Poll::Ready(res) = read.wait(u32::MAX) {
+                    break Self::get_data(res);
+                }
+            },
+            PipelineCacheLoadState::Done(data) => data,
+        };
+        device.create_pipeline_cache(data.as_deref())
+    }
+
+    fn get_data(res: io::Result<Vec<u8>>) -> Option<Vec<u8>> {
+        match res {
+            Ok(data) => Some(data),
+            Err(err) => {
+                log_warning!(LOG_CAT, "Failed to read pipeline cache file, starting with an empty cache: {err}");
+                None
+            },
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use core::time::Duration;
+
+use onca_common_macros::EnumDisplay;
+use crate::{handle::{InterfaceHandle, create_ral_handle}, Handle, HandleImpl};
+
+/// Query type
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, EnumDisplay)]
+pub enum QueryType {
+    /// GPU timestamp, written using `write_timestamp` on any command list, except for a `CopyCommandList`
+    /// 
+    /// Each query resolves to a single `u64` tick value, see `TimestampCalibration` for converting ticks to time
+    Timestamp,
+    /// GPU timestamp written on a copy queue, written using `write_timestamp` on a `CopyCommandList`
+    /// 
+    /// Each query resolves to a single `u64` tick value, use the timestamp calibration of the copy queue to convert ticks to time
+    CopyQueueTimestamp,
+    /// Pipeline statistics, collected between `begin_query` and `end_query`
+    /// 
+    /// Each query resolves to a `PipelineStatistics`
+    PipelineStatistics,
+    /// Occlusion query, collected between `begin_query` and `end_query`
+    /// 
+    /// Each query resolves to a `u64` containing the exact number of samples that passed the depth and stencil tests
+    Occlusion,
+    /// Binary occlusion query, collected between `begin_query` and `end_query`
+    /// 
+    /// Each query resolves to a `u64` that is 0 when no samples passed the depth and stencil tests, and non-zero otherwise.
+    /// This can be cheaper than an `Occlusion` query when only the visibility is needed, e.g. when used for predication
+    BinaryOcclusion,
+}
+
+impl QueryType {
+    /// Get the size of a single resolved query of this type
+    pub fn result_size(self) -> u64 {
+        match self {
+            QueryType::Timestamp          => core::mem::size_of::<u64>() as u64,
+            QueryType::CopyQueueTimestamp => core::mem::size_of::<u64>() as u64,
+            QueryType::PipelineStatistics => core::mem::size_of::<PipelineStatistics>() as u64,
+            QueryType::Occlusion          => core::mem::size_of::<u64>() as u64,
+            QueryType::BinaryOcclusion    => core::mem::size_of::<u64>() as u64,
+        }
+    }
+
+    /// Check if the query type is a timestamp query
+    pub fn is_timestamp(self) -> bool {
+        matches!(self, QueryType::Timestamp | QueryType::CopyQueueTimestamp)
+    }
+
+    /// Check if the query type is an occlusion query
+    pub fn is_occlusion(self) -> bool {
+        matches!(self, QueryType::Occlusion | QueryType::BinaryOcclusion)
+    }
+}
+
+/// Predication operation, determines when commands are skipped while predication is active
+/// 
+/// The predicate is read as a 64-bit value, on vulkan only the lower 32 bits of the value are used.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, EnumDisplay)]
+pub enum PredicationOp {
+    /// Skip commands when the predicate is 0, e.g. skip rendering when an occlusion query found the object to be invisible
+    EqualZero,
+    /// Skip commands when the predicate is not 0
+    NotEqualZero,
+}
+
+/// Query pool description
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct QueryPoolDesc {
+    /// Type of queries in the pool
+    pub query_type: QueryType,
+    /// Number of queries in the pool
+    pub count:      u32,
+}
+
+/// Resolved pipeline statistics
+/// 
+/// The layout of this structure matches the layout the statistics will be resolved to in a buffer
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+#[repr(C)]
+pub struct PipelineStatistics {
+    /// Number of vertices read by the input assembler
+    pub input_assembler_vertices:    u64,
+    /// Number of primitives read by the input assembler
+    pub input_assembler_primitives:  u64,
+    /// Number of vertex shader invocations
+    pub vertex_shader_invocations:   u64,
+    /// Number of geometry shader invocations
+    pub geometry_shader_invocations: u64,
+    /// Number of primitives output by the geometry shader
+    pub geometry_shader_primitives:  u64,
+    /// Number of primitives sent to the rasterizer
+    pub clipper_invocations:         u64,
+    /// Number of primitives rendered by the rasterizer
+    pub clipper_primitives:          u64,
+    /// Number of pixel shader invocations
+    pub pixel_shader_invocations:    u64,
+    /// Number of hull shader invocations
+    pub hull_shader_invocations:     u64,
+    /// Number of domain shader invocations
+    pub domain_shader_invocations:   u64,
+    /// Number of compute shader invocations
+    pub compute_shader_invocations:  u64,
+}
+
+/// Correlated CPU and GPU timestamps, used to convert GPU timestamps to CPU time
+/// 
+/// The CPU timestamp is in the platform's high resolution clock, i.e. `QueryPerformanceCounter` on windows and `CLOCK_MONOTONIC` on other platforms
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TimestampCalibration {
+    /// GPU timestamp in ticks
+    pub gpu_timestamp: u64,
+    /// GPU timestamp frequency in ticks per second
+    pub gpu_frequency: u64,
+    /// CPU timestamp in ticks
+    pub cpu_timestamp: u64,
+    /// CPU timestamp frequency in ticks per second
+    pub cpu_frequency: u64,
+}
+
+impl TimestampCalibration {
+    /// Convert a number of GPU ticks to a duration
+    pub fn gpu_ticks_to_duration(&self, ticks: u64) -> Duration {
+        ticks_to_duration(ticks, self.gpu_frequency)
+    }
+
+    /// Get the duration between 2 GPU timestamps
+    pub fn gpu_elapsed(&self, begin: u64, end: u64) -> Duration {
+        self.gpu_ticks_to_duration(end.saturating_sub(begin))
+    }
+
+    /// Convert a GPU timestamp to a CPU timestamp
+    pub fn gpu_to_cpu_timestamp(&self, gpu_timestamp: u64) -> u64 {
+        let gpu_delta = gpu_timestamp as i128 - self.gpu_timestamp as i128;
+        let cpu_delta = gpu_delta * self.cpu_frequency as i128 / self.gpu_frequency as i128;
+        (self.cpu_timestamp as i128 + cpu_delta).max(0) as u64
+    }
+
+    /// Convert a CPU timestamp to a GPU timestamp
+    pub fn cpu_to_gpu_timestamp(&self, cpu_timestamp: u64) -> u64 {
+        let cpu_delta = cpu_timestamp as i128 - self.cpu_timestamp as i128;
+        let gpu_delta = cpu_delta * self.gpu_frequency as i128 / self.cpu_frequency as i128;
+        (self.gpu_timestamp as i128 + gpu_delta).max(0) as u64
+    }
+}
+
+fn ticks_to_duration(ticks: u64, frequency: u64) -> Duration {
+    let secs = ticks / frequency;
+    let nanos = (ticks % frequency) as u128 * 1_000_000_000 / frequency as u128;
+    Duration::new(secs, nanos as u32)
+}
+
+//==============================================================================================================================
+
+pub trait QueryPoolInterface {
+    /// Set the debug name of the query pool
+    unsafe fn set_debug_name(&self, name: &str);
+}
+
+pub type QueryPoolInterfaceHandle = InterfaceHandle<dyn QueryPoolInterface>;
+
+pub struct QueryPool {
+    handle: QueryPoolInterfaceHandle,
+    desc:   QueryPoolDesc,
+}
+create_ral_handle!(QueryPoolHandle, QueryPool, QueryPoolInterfaceHandle);
+
+impl QueryPoolHandle {
+    pub(crate) fn create(handle: QueryPoolInterfaceHandle, desc: QueryPoolDesc) -> Self {
+        Self::new(QueryPool { handle, desc })
+    }
+
+    /// Get the query pool description
+    pub fn desc(&self) -> &QueryPoolDesc {
+        &self.desc
+    }
+
+    /// Get the type of queries in the pool
+    pub fn query_type(&self) -> QueryType {
+        self.desc.query_type
+    }
+
+    /// Get the number of queries in the pool
+    pub fn count(&self) -> u32 {
+        self.desc.count
+    }
+}
@@ -1,5 +1,5 @@
 use core::{
-    mem::{ManuallyDrop, MaybeUninit},
+    mem::ManuallyDrop,
     fmt,
 };
 use onca_common::{
@@ -7,10 +7,10 @@ use onca_common::{
     dynlib::DynLib,
     mem::MemoryManager,
 };
-use onca_logging::{log_error, LogCategory, LogLevel, Logger};
+use onca_logging::{log_error, log_info, LogCategory, LogLevel, Logger};
 use onca_toml::{self as toml, Toml};
 
-use crate::{PhysicalDevice, Result, Error, DeviceInterfaceHandle, CommandQueue, QueuePriority, QueueType, CommandQueueInterfaceHandle, handle::Handle, DeviceHandle, QueueIndex, CommandQueueHandle, GpuAllocatorImpl};
+use crate::{PhysicalDevice, Result, Error, DeviceInterfaceHandle, CommandQueueInterfaceHandle, DeviceHandle, QueueIndex, GpuAllocatorImpl};
 
 const LOG_CAT : LogCategory = LogCategory::new("Graphics RAL");
 
@@ -60,6 +60,10 @@ pub enum RalApiSpecificSettings {
 pub struct Settings {
     /// RAL api
     pub api                      : RalApi,
+    /// Run without any window or swap-chain, e.g. for automated tests or servers without a display
+    /// 
+    /// Swap-chains cannot be created in headless mode, rendering needs to happen to textures created by the user
+    pub headless                 : bool,
 
     /// Debug enable
     pub debug_enabled            : bool,
@@ -75,6 +79,10 @@ pub struct Settings {
     pub debug_dcqs               : bool,
     /// Automatically name GPU objects
     pub debug_auto_naming        : bool,
+    /// RAL validation layer, tracks resource states and lifetimes while recording command lists, independent of the API's debug layers
+    /// 
+    /// Requires the `validation` feature
+    pub debug_ral_validation     : bool,
     /// Debug log level
     pub debug_log_level          : LogLevel,
 
@@ -105,6 +113,9 @@ impl Settings {
                 log_error!(LOG_CAT, "No api specified");
                 return None;
             }
+            if let Some(toml::Item::Boolean(true)) = common.get_item("headless") {
+                settings.headless = true;
+            }
         }
 
         if let Some(toml::Item::Table(debug_table)) = toml.get("debug") {
@@ -129,6 +140,9 @@ impl Settings {
             if let Some(toml::Item::Boolean(true)) = debug_table.get_item("auto-naming") {
                 settings.debug_auto_naming = true;
             }
+            if let Some(toml::Item::Boolean(true)) = debug_table.get_item("ral-validation") {
+                settings.debug_ral_validation = true;
+            }
             if let Some(toml::Item::String(level)) = debug_table.get_item("log-level") {
                 settings.debug_log_level = match level.as_str() {
                     "verbose" => LogLevel::Verbose,
@@ -157,6 +171,7 @@ impl Default for Settings {
     fn default() -> Self {
         Self {
             api: RalApi::Vulkan, // < Value doesn't really matter here, as the setting will be loaded from the toml
+            headless: false,
             debug_enabled: false,
             debug_validation: false,
             debug_performance: false,
@@ -164,6 +179,7 @@ impl Default for Settings {
             debug_gbv_state_tracking: false,
             debug_dcqs: false,
             debug_auto_naming: false,
+            debug_ral_validation: false,
             debug_log_level: LogLevel::Error,
             api_specific: Toml::new()
         }
@@ -207,6 +223,9 @@ impl Ral {
             Some(func) => func,
             None => return Err(Error::LoadFunction("create_ral")),
         };
+
+        #[cfg(feature = "validation")]
+        crate::validation::set_validation_layer_enabled(settings.debug_ral_validation);
         
         let ral = create_ral(memory_manager, logger, alloc, settings);
         ral.map(|ral| Self { dynlib, ral: Some(ral), alloc })
@@ -226,15 +245,31 @@ impl Ral {
 
     pub fn create_device(&self, phys_dev: PhysicalDevice, gpu_alloc_impl: GpuAllocatorImpl) -> Result<DeviceHandle> {
         let (handle, command_queue_handles) = unsafe { self.get().create_device(&phys_dev)? };
-        let mut command_queues = MaybeUninit::<[[CommandQueueHandle; QueuePriority::COUNT]; QueueType::COUNT]>::uninit();
+        Ok(DeviceHandle::create(handle, phys_dev, command_queue_handles, gpu_alloc_impl, self.settings().headless, self.alloc))
+    }
 
-        for (x, arr) in command_queue_handles.into_iter().enumerate() {
-            for (y, (handle, index)) in arr.into_iter().enumerate() {
-                unsafe { core::ptr::write(&mut (&mut *command_queues.as_mut_ptr())[x][y], Handle::new(CommandQueue { handle, index })) };
-            }
+    /// Recreate a device after it was lost
+    /// 
+    /// The new device will be created on the same physical device if it is still available, otherwise the first available physical device will be used.
+    /// All device lost listeners registered on the lost device are moved to the new device and notified.
+    /// 
+    /// Objects created from the lost device need to be released and recreated from the new device, swap chains can be recreated using `Device::recreate_swap_chain`
+    pub fn recreate_device(&self, lost_device: &DeviceHandle, gpu_alloc_impl: GpuAllocatorImpl) -> Result<DeviceHandle> {
+        let lost_props = &lost_device.get_physical_device().properties;
+
+        let mut phys_devs = self.get_physical_devices()?;
+        let idx = phys_devs.iter()
+            .position(|phys_dev| phys_dev.properties.vendor_id == lost_props.vendor_id && phys_dev.properties.product_id == lost_props.product_id)
+            .unwrap_or(0);
+        if phys_devs.is_empty() {
+            return Err(Error::Other("No physical devices are available to recreate the device".to_string()));
         }
-        let command_queues = unsafe { command_queues.assume_init() };
-        Ok(DeviceHandle::create(handle, phys_dev, command_queues, gpu_alloc_impl, self.alloc))
+        let phys_dev = phys_devs.swap_remove(idx);
+
+        log_info!(LOG_CAT, "Recreating lost device on '{}'", phys_dev.properties.description);
+        let device = self.create_device(phys_dev, gpu_alloc_impl)?;
+        lost_device.transfer_device_lost_listeners(&device);
+        Ok(device)
     }    
 
     fn drop_impl(&mut self) {
@@ -0,0 +1,678 @@
+//! Hardware raytracing
+//! 
+//! This module contains:
+//! - Acceleration structures (BLAS/TLAS), which can be built, updated, copied, and compacted using a command list
+//! - Raytracing pipeline descriptions
+//! - Shader binding tables, which map raygen, miss, hit group, and callable shaders to the records used by `trace_rays`
+//! 
+//! Raytracing pipelines share the compute pipeline layout and its bound descriptors,
+//! so `bind_compute_pipeline_layout` and `set_compute_descriptor_table` are used to bind resources for `trace_rays`
+use onca_common_macros::flags;
+
+use crate::{
+    *,
+    handle::{InterfaceHandle, create_ral_handle},
+};
+
+//==============================================================================================================================
+// ACCELERATION STRUCTURES
+//==============================================================================================================================
+
+/// Acceleration structure type
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AccelerationStructureType {
+    /// Bottom level acceleration structure (BLAS), contains geometry
+    BottomLevel,
+    /// Top level acceleration structure (TLAS), contains instances of BLASes
+    TopLevel,
+}
+
+/// Acceleration structure build flags
+#[flags]
+pub enum AccelerationStructureBuildFlags {
+    /// The acceleration structure can be updated using `update_acceleration_structure`
+    AllowUpdate,
+    /// The acceleration structure can be compacted using `copy_acceleration_structure` with `AccelerationStructureCopyMode::Compact`
+    AllowCompaction,
+    /// Prefer a higher trace performance at the cost of a longer build time
+    PreferFastTrace,
+    /// Prefer a shorter build time at the cost of trace performance
+    PreferFastBuild,
+    /// Minimize the memory used by the acceleration structure and its scratch memory, at the cost of build time and trace performance
+    MinimizeMemory,
+}
+
+/// Raytracing geometry flags
+#[flags]
+pub enum RaytracingGeometryFlags {
+    /// The geometry does not invoke any-hit shaders
+    Opaque,
+    /// The any-hit shader of the geometry will be invoked at most once per primitive
+    NoDuplicateAnyHit,
+}
+
+/// Raytracing instance flags
+/// 
+/// The bits of these flags match the bits used by the underlying APIs
+#[flags]
+pub enum RaytracingInstanceFlags {
+    /// Disable face culling for the instance
+    TriangleCullDisable,
+    /// Counter clockwise triangles are considered front-facing
+    TriangleFrontCounterClockwise,
+    /// Treat all geometry in the instance as opaque
+    ForceOpaque,
+    /// Treat all geometry in the instance as non-opaque
+    ForceNonOpaque,
+}
+
+/// Index data used by raytracing triangle geometry
+#[derive(Clone)]
+pub struct RaytracingIndices {
+    /// Buffer containing the indices
+    pub buffer: BufferHandle,
+    /// Offset of the first index in the buffer
+    pub offset: u64,
+    /// Index format
+    pub format: IndexFormat,
+    /// Number of indices
+    pub count:  u32,
+}
+
+/// Triangle geometry
+#[derive(Clone)]
+pub struct RaytracingTriangles {
+    /// Buffer containing the vertex positions
+    pub vertex_buffer: BufferHandle,
+    /// Offset of the first vertex in the buffer
+    pub vertex_offset: u64,
+    /// Stride between vertices
+    pub vertex_stride: u64,
+    /// Number of vertices
+    pub vertex_count:  u32,
+    /// Format of the vertex positions
+    pub vertex_format: Format,
+    /// Optional indices, if no indices are provided, each 3 consecutive vertices form a triangle
+    pub indices:       Option<RaytracingIndices>,
+    /// Optional buffer and offset to a row-major 3x4 `f32` transform matrix, that is applied to the vertices
+    pub transform:     Option<(BufferHandle, u64)>,
+}
+
+/// Axis-aligned bounding box geometry, used for procedural geometry
+/// 
+/// Each AABB is stored as 6 `f32`s: `min_x`, `min_y`, `min_z`, `max_x`, `max_y`, `max_z`
+#[derive(Clone)]
+pub struct RaytracingAabbs {
+    /// Buffer containing the AABBs
+    pub buffer: BufferHandle,
+    /// Offset of the first AABB in the buffer
+    pub offset: u64,
+    /// Stride between AABBs
+    pub stride: u64,
+    /// Number of AABBs
+    pub count:  u32,
+}
+
+/// Raytracing geometry data
+#[derive(Clone)]
+pub enum RaytracingGeometryData {
+    /// Triangles
+    Triangles(RaytracingTriangles),
+    /// Axis-aligned bounding boxes
+    Aabbs(RaytracingAabbs),
+}
+
+/// Raytracing geometry, used to build a BLAS
+#[derive(Clone)]
+pub struct RaytracingGeometry {
+    /// Geometry flags
+    pub flags: RaytracingGeometryFlags,
+    /// Geometry data
+    pub data:  RaytracingGeometryData,
+}
+
+impl RaytracingGeometry {
+    /// Get the number of primitives in the geometry
+    pub fn primitive_count(&self) -> u32 {
+        match &self.data {
+            RaytracingGeometryData::Triangles(triangles) => triangles.indices.as_ref().map_or(triangles.vertex_count, |indices| indices.count) / 3,
+            RaytracingGeometryData::Aabbs(aabbs) => aabbs.count,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        #[cfg(feature = "validation")]
+        {
+            match &self.data {
+                RaytracingGeometryData::Triangles(triangles) => {
+                    if !triangles.vertex_buffer.usages().contains(BufferUsage::AccelerationStructureInput) {
+                        return Err(Error::InvalidParameter("Raytracing vertex buffer requires the `BufferUsage::AccelerationStructureInput` usage".to_string()));
+                    }
+                    let vertex_end = triangles.vertex_offset + triangles.vertex_stride * triangles.vertex_count as u64;
+                    if vertex_end > triangles.vertex_buffer.size() {
+                        return Err(Error::InvalidParameter(format!("Raytracing vertices go out of the range of the vertex buffer, end: {vertex_end}, buffer size: {}", triangles.vertex_buffer.size())));
+                    }
+
+                    if let Some(indices) = &triangles.indices {
+                        if !indices.buffer.usages().contains(BufferUsage::AccelerationStructureInput) {
+                            return Err(Error::InvalidParameter("Raytracing index buffer requires the `BufferUsage::AccelerationStructureInput` usage".to_string()));
+                        }
+                        if indices.count % 3 != 0 {
+                            return Err(Error::InvalidParameter(format!("Raytracing index count needs to be a multiple of 3, found {}", indices.count)));
+                        }
+                        let index_size = match indices.format {
+                            IndexFormat::U16 => 2,
+                            IndexFormat::U32 => 4,
+                        };
+                        let index_end = indices.offset + index_size * indices.count as u64;
+                        if index_end > indices.buffer.size() {
+                            return Err(Error::InvalidParameter(format!("Raytracing indices go out of the range of the index buffer, end: {index_end}, buffer size: {}", indices.buffer.size())));
+                        }
+                    } else if triangles.vertex_count % 3 != 0 {
+                        return Err(Error::InvalidParameter(format!("Raytracing vertex count needs to be a multiple of 3 when no indices are provided, found {}", triangles.vertex_count)));
+                    }
+
+                    if let Some((transform, offset)) = &triangles.transform {
+                        if !transform.usages().contains(BufferUsage::AccelerationStructureInput) {
+                            return Err(Error::InvalidParameter("Raytracing transform buffer requires the `BufferUsage::AccelerationStructureInput` usage".to_string()));
+                        }
+                        if offset % 16 != 0 {
+                            return Err(Error::InvalidParameter(format!("Raytracing transform offset needs to be a multiple of 16, found {offset}")));
+                        }
+                    }
+                },
+                RaytracingGeometryData::Aabbs(aabbs) => {
+                    if !aabbs.buffer.usages().contains(BufferUsage::AccelerationStructureInput) {
+                        return Err(Error::InvalidParameter("Raytracing AABB buffer requires the `BufferUsage::AccelerationStructureInput` usage".to_string()));
+                    }
+                    if aabbs.stride % 8 != 0 || aabbs.stride < 24 {
+                        return Err(Error::InvalidParameter(format!("Raytracing AABB stride needs to be a multiple of 8, and at least 24 bytes, found {}", aabbs.stride)));
+                    }
+                    let aabb_end = aabbs.offset + aabbs.stride * aabbs.count as u64;
+                    if aabb_end > aabbs.buffer.size() {
+                        return Err(Error::InvalidParameter(format!("Raytracing AABBs go out of the range of the AABB buffer, end: {aabb_end}, buffer size: {}", aabbs.buffer.size())));
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Raytracing instance, as it is laid out in the instance buffer used to build a TLAS
+/// 
+/// The layout matches the layout expected by both DX12 and Vulkan
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct RaytracingInstance {
+    /// Row-major 3x4 transform matrix
+    pub transform:              [f32; 12],
+    instance_id_and_mask:       u32,
+    hit_group_offset_and_flags: u32,
+    acceleration_structure:     u64,
+}
+
+impl RaytracingInstance {
+    /// Create a new instance of a BLAS
+    /// 
+    /// Only the lower 24-bits of `instance_id` and `hit_group_offset` are used
+    pub fn new(transform: [f32; 12], instance_id: u32, mask: u8, hit_group_offset: u32, flags: RaytracingInstanceFlags, blas: &AccelerationStructureHandle) -> Self {
+        Self {
+            transform,
+            instance_id_and_mask: (instance_id & 0x00FF_FFFF) | ((mask as u32) << 24),
+            hit_group_offset_and_flags: (hit_group_offset & 0x00FF_FFFF) | ((flags.bits() as u32) << 24),
+            acceleration_structure: blas.gpu_address().as_raw(),
+        }
+    }
+
+    /// Get the instance id
+    pub fn instance_id(&self) -> u32 {
+        self.instance_id_and_mask & 0x00FF_FFFF
+    }
+
+    /// Get the instance mask
+    pub fn mask(&self) -> u8 {
+        (self.instance_id_and_mask >> 24) as u8
+    }
+
+    /// Get the offset into the hit group table
+    pub fn hit_group_offset(&self) -> u32 {
+        self.hit_group_offset_and_flags & 0x00FF_FFFF
+    }
+}
+
+/// Inputs used to build an acceleration structure
+#[derive(Clone, Copy)]
+pub enum AccelerationStructureInputs<'a> {
+    /// Geometry used to build a BLAS
+    BottomLevel(&'a [RaytracingGeometry]),
+    /// Instances used to build a TLAS, stored as `RaytracingInstance`s in `instances` at `offset`
+    TopLevel {
+        instances: &'a BufferHandle,
+        offset:    u64,
+        count:     u32,
+    },
+}
+
+impl AccelerationStructureInputs<'_> {
+    /// Get the type of acceleration structure these inputs will build
+    pub fn ty(&self) -> AccelerationStructureType {
+        match self {
+            AccelerationStructureInputs::BottomLevel(_)  => AccelerationStructureType::BottomLevel,
+            AccelerationStructureInputs::TopLevel { .. } => AccelerationStructureType::TopLevel,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        #[cfg(feature = "validation")]
+        {
+            match self {
+                AccelerationStructureInputs::BottomLevel(geometry) => {
+                    if geometry.is_empty() {
+                        return Err(Error::InvalidParameter("A BLAS requires at least 1 geometry".to_string()));
+                    }
+                    if geometry.len() as u64 > constants::MAX_RAYTRACE_ACCELERATION_STRUCTURE_GEOMETRY_COUNT {
+                        return Err(Error::InvalidParameter(format!("Too many geometries in BLAS ({}), only {} are allowed", geometry.len(), constants::MAX_RAYTRACE_ACCELERATION_STRUCTURE_GEOMETRY_COUNT)));
+                    }
+
+                    let is_triangles = matches!(geometry[0].data, RaytracingGeometryData::Triangles(_));
+                    let mut primitive_count = 0;
+                    for geo in *geometry {
+                        if matches!(geo.data, RaytracingGeometryData::Triangles(_)) != is_triangles {
+                            return Err(Error::InvalidParameter("A BLAS can only contain either triangles or AABBs, not both".to_string()));
+                        }
+                        geo.validate()?;
+                        primitive_count += geo.primitive_count() as u64;
+                    }
+
+                    if primitive_count > constants::MAX_RAYTRACE_ACCELERATION_STRUCTURE_PRIMITIVE_COUNT {
+                        return Err(Error::InvalidParameter(format!("Too many primitives in BLAS ({primitive_count}), only {} are allowed", constants::MAX_RAYTRACE_ACCELERATION_STRUCTURE_PRIMITIVE_COUNT)));
+                    }
+                },
+                AccelerationStructureInputs::TopLevel { instances, offset, count } => {
+                    if !instances.usages().contains(BufferUsage::AccelerationStructureInput) {
+                        return Err(Error::InvalidParameter("Raytracing instance buffer requires the `BufferUsage::AccelerationStructureInput` usage".to_string()));
+                    }
+                    let align = constants::RAYTRACE_INSTANCE_ALIGNMENT.alignment();
+                    if offset % align != 0 {
+                        return Err(Error::InvalidParameter(format!("Raytracing instance offset ({offset}) needs to be a multiple of {align}")));
+                    }
+                    if *count as u64 > constants::MAX_RAYTRACE_ACCELERATION_STRUCTURE_INSTANCE_COUNT {
+                        return Err(Error::InvalidParameter(format!("Too many instances in TLAS ({count}), only {} are allowed", constants::MAX_RAYTRACE_ACCELERATION_STRUCTURE_INSTANCE_COUNT)));
+                    }
+                    let end = offset + core::mem::size_of::<RaytracingInstance>() as u64 * *count as u64;
+                    if end > instances.size() {
+                        return Err(Error::InvalidParameter(format!("Raytracing instances go out of the range of the instance buffer, end: {end}, buffer size: {}", instances.size())));
+                    }
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Memory requirements to build an acceleration structure
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct AccelerationStructureBuildSizes {
+    /// Size needed to store the acceleration structure
+    pub size:                u64,
+    /// Size of the scratch memory needed to build the acceleration structure
+    pub build_scratch_size:  u64,
+    /// Size of the scratch memory needed to update the acceleration structure
+    pub update_scratch_size: u64,
+}
+
+/// Acceleration structure build info
+#[derive(Clone, Copy)]
+pub struct AccelerationStructureBuildInfo<'a> {
+    /// Inputs to build the acceleration structure from
+    pub inputs:         AccelerationStructureInputs<'a>,
+    /// Build flags
+    /// 
+    /// When updating an acceleration structure, these flags need to match the flags it was originally built with
+    pub flags:          AccelerationStructureBuildFlags,
+    /// Acceleration structure to build
+    pub dst:            &'a AccelerationStructureHandle,
+    /// Buffer used as scratch memory during the build, requires the `BufferUsage::StorageBuffer` usage
+    pub scratch:        &'a BufferHandle,
+    /// Offset into the scratch buffer
+    pub scratch_offset: u64,
+}
+
+impl AccelerationStructureBuildInfo<'_> {
+    pub fn validate(&self) -> Result<()> {
+        #[cfg(feature = "validation")]
+        {
+            self.inputs.validate()?;
+
+            if self.inputs.ty() != self.dst.ty() {
+                return Err(Error::InvalidParameter(format!("Acceleration structure inputs are for a {:?} acceleration structure, but the destination is a {:?} acceleration structure", self.inputs.ty(), self.dst.ty())));
+            }
+            if !self.scratch.usages().contains(BufferUsage::StorageBuffer) {
+                return Err(Error::InvalidParameter("Acceleration structure scratch buffer requires the `BufferUsage::StorageBuffer` usage".to_string()));
+            }
+            let align = constants::MIN_RAYTRACE_ACCELERATION_STRUCTURE_SCRATCH_ALIGNMENT.alignment();
+            if self.scratch.gpu_address().at(self.scratch_offset).as_raw() % align != 0 {
+                return Err(Error::InvalidParameter(format!("Acceleration structure scratch memory needs to be aligned to {align} bytes")));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Acceleration structure copy mode
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AccelerationStructureCopyMode {
+    /// Copy the acceleration structure as is, the destination needs to be at least the size of the source
+    Clone,
+    /// Compact the acceleration structure into the destination, the source needs to be built with `AccelerationStructureBuildFlags::AllowCompaction`
+    /// 
+    /// The required size of the destination can be retrieved using `write_acceleration_structure_compacted_size`
+    Compact,
+}
+
+/// Acceleration structure description
+#[derive(Clone)]
+pub struct AccelerationStructureDesc {
+    /// Acceleration structure type
+    pub ty:     AccelerationStructureType,
+    /// Buffer the acceleration structure is stored in, requires the `BufferUsage::AccelerationStructure` usage
+    pub buffer: BufferHandle,
+    /// Offset in the buffer
+    pub offset: u64,
+    /// Size of the acceleration structure
+    pub size:   u64,
+}
+
+impl AccelerationStructureDesc {
+    pub fn validate(&self) -> Result<()> {
+        #[cfg(feature = "validation")]
+        {
+            if !self.buffer.usages().contains(BufferUsage::AccelerationStructure) {
+                return Err(Error::InvalidParameter("Acceleration structure buffer requires the `BufferUsage::AccelerationStructure` usage".to_string()));
+            }
+            let align = constants::RAYTRACE_ACCELERATION_STRUCTURE_OFFSET_ALIGNMENT.alignment();
+            if self.offset % align != 0 {
+                return Err(Error::InvalidParameter(format!("Acceleration structure offset ({}) needs to be a multiple of {align}", self.offset)));
+            }
+            if self.offset + self.size > self.buffer.size() {
+                return Err(Error::InvalidParameter(format!("Acceleration structure goes out of the range of its buffer, end: {}, buffer size: {}", self.offset + self.size, self.buffer.size())));
+            }
+        }
+        Ok(())
+    }
+}
+
+//==============================================================================================================================
+
+pub trait AccelerationStructureInterface {
+    /// Set the debug name of the acceleration structure
+    unsafe fn set_debug_name(&self, name: &str);
+}
+
+pub type AccelerationStructureInterfaceHandle = InterfaceHandle<dyn AccelerationStructureInterface>;
+
+/// Acceleration structure
+pub struct AccelerationStructure {
+    handle:  AccelerationStructureInterfaceHandle,
+    desc:    AccelerationStructureDesc,
+    address: GpuAddress,
+}
+create_ral_handle!(AccelerationStructureHandle, AccelerationStructure, AccelerationStructureInterfaceHandle);
+
+impl AccelerationStructureHandle {
+    pub(crate) fn create(handle: AccelerationStructureInterfaceHandle, desc: &AccelerationStructureDesc, address: GpuAddress) -> Self {
+        Self::new(AccelerationStructure {
+            handle,
+            desc: desc.clone(),
+            address,
+        })
+    }
+
+    /// Get the acceleration structure description
+    pub fn desc(&self) -> &AccelerationStructureDesc {
+        &self.desc
+    }
+
+    /// Get the acceleration structure type
+    pub fn ty(&self) -> AccelerationStructureType {
+        self.desc.ty
+    }
+
+    /// Get the buffer the acceleration structure is stored in
+    pub fn buffer(&self) -> &BufferHandle {
+        &self.desc.buffer
+    }
+
+    /// Get the size of the acceleration structure
+    pub fn size(&self) -> u64 {
+        self.desc.size
+    }
+
+    /// Get the GPU address of the acceleration structure
+    pub fn gpu_address(&self) -> GpuAddress {
+        self.address
+    }
+}
+
+//==============================================================================================================================
+// PIPELINES
+//==============================================================================================================================
+
+/// Raytracing shader group
+/// 
+/// Indices refer to the shaders in `RaytracingPipelineDesc::shaders`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RaytracingShaderGroup {
+    /// Raygen, miss, or callable shader
+    General(u32),
+    /// Hit group for triangle geometry
+    TrianglesHitGroup {
+        closest_hit: Option<u32>,
+        any_hit:     Option<u32>,
+    },
+    /// Hit group for procedural (AABB) geometry
+    ProceduralHitGroup {
+        closest_hit:  Option<u32>,
+        any_hit:      Option<u32>,
+        intersection: u32,
+    },
+}
+
+/// Raytracing pipeline description
+#[derive(Clone)]
+pub struct RaytracingPipelineDesc {
+    /// Shaders used by the pipeline
+    pub shaders:             Vec<ShaderHandle>,
+    /// Shader groups, the index of a group is used to identify it in a shader binding table
+    pub groups:              Vec<RaytracingShaderGroup>,
+    /// Maximum recursion depth of `trace_ray()` calls
+    pub max_recursion_depth: u32,
+    /// Maximum size of a ray payload
+    pub max_payload_size:    u32,
+    /// Maximum size of the hit attributes
+    pub max_attribute_size:  u32,
+    /// Pipeline layout
+    pub pipeline_layout:     PipelineLayoutHandle,
+}
+
+impl RaytracingPipelineDesc {
+    pub fn validate(&self) -> Result<()> {
+        #[cfg(feature = "validation")]
+        {
+            if self.groups.is_empty() {
+                return Err(Error::InvalidParameter("Raytracing pipeline requires at least 1 shader group".to_string()));
+            }
+            if self.max_attribute_size > constants::MAX_RAYTRACE_HIT_ATTRIBUTE_SIZE {
+                return Err(Error::InvalidParameter(format!("Raytracing hit attribute size ({}) exceeds the maximum of {}", self.max_attribute_size, constants::MAX_RAYTRACE_HIT_ATTRIBUTE_SIZE)));
+            }
+
+            let check_shader = |idx: u32, types: &[ShaderType], name: &str| -> Result<()> {
+                let shader = match self.shaders.get(idx as usize) {
+                    Some(shader) => shader,
+                    None => return Err(Error::InvalidParameter(format!("Raytracing {name} shader index {idx} is out of range, only {} shaders are available", self.shaders.len()))),
+                };
+                let shader_type = shader.shader_type();
+                if !types.contains(&shader_type) {
+                    return Err(Error::InvalidParameter(format!("Raytracing {name} shader at index {idx} has an invalid shader type: '{shader_type:?}'")));
+                }
+                Ok(())
+            };
+
+            for group in &self.groups {
+                match *group {
+                    RaytracingShaderGroup::General(idx) => check_shader(idx, &[ShaderType::RayGen, ShaderType::Miss, ShaderType::Callable], "general")?,
+                    RaytracingShaderGroup::TrianglesHitGroup { closest_hit, any_hit } => {
+                        if let Some(idx) = closest_hit {
+                            check_shader(idx, &[ShaderType::ClosestHit], "closest hit")?;
+                        }
+                        if let Some(idx) = any_hit {
+                            check_shader(idx, &[ShaderType::AnyHit], "any hit")?;
+                        }
+                    },
+                    RaytracingShaderGroup::ProceduralHitGroup { closest_hit, any_hit, intersection } => {
+                        if let Some(idx) = closest_hit {
+                            check_shader(idx, &[ShaderType::ClosestHit], "closest hit")?;
+                        }
+                        if let Some(idx) = any_hit {
+                            check_shader(idx, &[ShaderType::AnyHit], "any hit")?;
+                        }
+                        check_shader(intersection, &[ShaderType::Intersection], "intersection")?;
+                    },
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+//==============================================================================================================================
+// SHADER BINDING TABLE
+//==============================================================================================================================
+
+/// Region of a shader binding table
+#[derive(Clone, Copy, Debug)]
+pub struct ShaderTableRegion {
+    /// GPU address of the first record
+    pub address: GpuAddress,
+    /// Size of the region
+    pub size:    u64,
+    /// Stride between records
+    pub stride:  u64,
+}
+
+impl ShaderTableRegion {
+    /// Create an empty region
+    pub fn empty() -> Self {
+        Self { address: GpuAddress::new(0), size: 0, stride: 0 }
+    }
+}
+
+/// Shader binding table regions used by `trace_rays`
+#[derive(Clone, Copy, Debug)]
+pub struct ShaderBindingTableRegions {
+    /// Raygen record, only a single record is used
+    pub raygen:    ShaderTableRegion,
+    /// Miss records
+    pub miss:      ShaderTableRegion,
+    /// Hit group records
+    pub hit_group: ShaderTableRegion,
+    /// Callable records
+    pub callable:  ShaderTableRegion,
+}
+
+/// Shader binding table description
+/// 
+/// Indices refer to the shader groups in the raytracing pipeline
+#[derive(Clone, Copy, Debug)]
+pub struct ShaderBindingTableDesc<'a> {
+    /// Raygen shader group
+    pub raygen:     u32,
+    /// Miss shader groups
+    pub miss:       &'a [u32],
+    /// Hit groups
+    pub hit_groups: &'a [u32],
+    /// Callable shader groups
+    pub callable:   &'a [u32],
+}
+
+/// Shader binding table
+/// 
+/// The table is stored in upload memory, and is written once on creation
+pub struct ShaderBindingTable {
+    buffer:  BufferHandle,
+    regions: ShaderBindingTableRegions,
+}
+
+impl ShaderBindingTable {
+    /// Create a shader binding table for a raytracing pipeline
+    pub fn new(device: &DeviceHandle, pipeline: &PipelineHandle, desc: &ShaderBindingTableDesc) -> Result<Self> {
+        let group_count = desc.miss.iter()
+            .chain(desc.hit_groups)
+            .chain(desc.callable)
+            .fold(desc.raygen, |max, idx| max.max(*idx)) + 1;
+        let handles = device.get_raytracing_shader_group_handles(pipeline, 0, group_count)?;
+
+        let handle_size = constants::RAYTRACE_HITGROUP_HANDLE_SIZE as u64;
+        let stride = handle_size.next_multiple_of(constants::MIN_RAYTRACE_HITGROUP_HANDLE_ALIGNMENT.alignment());
+        let base_align = constants::MIN_RAYTRACE_HITGROUP_BASE_ALIGNMENT.alignment();
+
+        let raygen_offset = 0;
+        let miss_offset = (raygen_offset + stride).next_multiple_of(base_align);
+        let hit_group_offset = (miss_offset + stride * desc.miss.len() as u64).next_multiple_of(base_align);
+        let callable_offset = (hit_group_offset + stride * desc.hit_groups.len() as u64).next_multiple_of(base_align);
+        let size = callable_offset + stride * desc.callable.len() as u64;
+
+        let buffer = device.create_buffer(&BufferDesc {
+            size,
+            usage: BufferUsage::ShaderBindingTable,
+            alloc_desc: GpuAllocationDesc {
+                memory_type: MemoryType::Upload,
+                flags: MemoryAllocationFlags::None,
+            },
+        })?;
+
+        let mut data = vec![0u8; size as usize];
+        let mut write_records = |offset: u64, groups: &[u32]| {
+            for (idx, group) in groups.iter().enumerate() {
+                let src = *group as usize * handle_size as usize;
+                let dst = (offset + stride * idx as u64) as usize;
+                data[dst..dst + handle_size as usize].copy_from_slice(&handles[src..src + handle_size as usize]);
+            }
+        };
+        write_records(raygen_offset, &[desc.raygen]);
+        write_records(miss_offset, desc.miss);
+        write_records(hit_group_offset, desc.hit_groups);
+        write_records(callable_offset, desc.callable);
+
+        let mut mapped = buffer.map(0, size)?;
+        mapped.write(&data);
+        buffer.unmap(mapped);
+
+        let address = buffer.gpu_address();
+        let region = |offset: u64, count: usize| if count == 0 {
+            ShaderTableRegion::empty()
+        } else {
+            ShaderTableRegion { address: address.at(offset), size: stride * count as u64, stride }
+        };
+
+        let regions = ShaderBindingTableRegions {
+            raygen: region(raygen_offset, 1),
+            miss: region(miss_offset, desc.miss.len()),
+            hit_group: region(hit_group_offset, desc.hit_groups.len()),
+            callable: region(callable_offset, desc.callable.len()),
+        };
+
+        Ok(Self { buffer, regions })
+    }
+
+    /// Get the buffer containing the shader binding table
+    pub fn buffer(&self) -> &BufferHandle {
+        &self.buffer
+    }
+
+    /// Get the regions of the shader binding table, to be passed to `trace_rays`
+    pub fn regions(&self) -> &ShaderBindingTableRegions {
+        &self.regions
+    }
+}
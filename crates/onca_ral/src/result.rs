@@ -1,7 +1,7 @@
 use core::fmt;
 use onca_common::prelude::*;
 
-use crate::{Format, CommandListType, TextureLayout, ClearColor, QueueIndex};
+use crate::{Format, CommandListType, TextureLayout, ClearColor, QueueIndex, SwapChainColorSpace};
 
 /// RAL error
 #[derive(Clone, PartialEq, Debug)]
@@ -21,6 +21,8 @@ pub enum Error {
     /// Out of device memory
     OutOfDeviceMemory,
     /// Device lost
+    /// 
+    /// Once returned, the device and all objects created from it are invalid, and the device needs to be recreated using `Ral::recreate_device`
     DeviceLost,
     /// Format error
     Format(String),
@@ -28,6 +30,8 @@ pub enum Error {
     UnsupportedSwapchainFormats(Vec<Format>),
     /// Unsupported format
     UnsupportedFormat(Format),
+    /// Unsupported swap-chain color space
+    UnsupportedColorSpace(SwapChainColorSpace),
     /// Unsupported format for a given view
     UnsupportedViewFormat{ texture: Format, view: Format },
     /// Unsupported memory topology
@@ -87,6 +91,7 @@ impl fmt::Display for Error {
                 Ok(())
             },
             Error::UnsupportedFormat(format)                  => f.write_fmt(format_args!("Unsupported format: {format}")),
+            Error::UnsupportedColorSpace(color_space)         => f.write_fmt(format_args!("Unsupported swap-chain color space: {color_space}")),
             Error::UnsupportedViewFormat { texture, view }    => f.write_fmt(format_args!("Unsupported view format {view} for texture format {texture}, formats must be in the same component family")),
             Error::UnsupportedMemoryTopology(info)            => f.write_fmt(format_args!("Unsupported memory topologies: {info}")),
             Error::UseAfterDeviceDropped                      => f.write_str("Tried to use the device after it was dropped"),
@@ -173,6 +173,8 @@ pub struct SamplerDesc {
 //==============================================================================================================================
 
 pub trait StaticSamplerInterface {
+    /// Set the debug name of the static sampler
+    unsafe fn set_debug_name(&self, name: &str);
 }
 pub type StaticSamplerInterfaceHandle = InterfaceHandle<dyn StaticSamplerInterface>;
 
@@ -195,6 +197,8 @@ impl StaticSamplerHandle {
 //==============================================================================================================================
 
 pub trait SamplerInterface {
+    /// Set the debug name of the sampler
+    unsafe fn set_debug_name(&self, name: &str);
 }
 pub type SamplerInterfaceHandle = InterfaceHandle<dyn SamplerInterface>;
 
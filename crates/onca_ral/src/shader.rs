@@ -1,7 +1,20 @@
-use crate::{handle::{InterfaceHandle, create_ral_handle}, Handle, HandleImpl, ShaderType};
+use core::num::NonZeroU32;
+
+use crate::{
+    handle::{InterfaceHandle, create_ral_handle}, Handle, HandleImpl,
+    ShaderType, DescriptorType, DescriptorTableDesc, DescriptorCount, PipelineLayoutDesc, PipelineLayoutFlags,
+    InputLayout, InputLayoutElement, InputLayoutStepRate, VertexComponents, VertexDataType, VertexFormat,
+    Result, Error,
+};
 
 pub trait ShaderInterface {
+    /// Reflect the shader code
+    /// 
+    /// Vertex inputs are only expected to be reflected when `shader_type` is `ShaderType::Vertex`
+    fn reflect(&self, shader_type: ShaderType) -> Result<ShaderReflection>;
 
+    /// Set the debug name of the shader
+    unsafe fn set_debug_name(&self, name: &str);
 }
 
 pub type ShaderInterfaceHandle = InterfaceHandle<dyn ShaderInterface>;
@@ -27,4 +40,277 @@ impl ShaderHandle {
     pub fn shader_type(&self) -> ShaderType {
         self.shader_type
     }
-}
\ No newline at end of file
+
+    /// Reflect the shader
+    /// 
+    /// The reflection data is extracted from the shader code on each call, so the result should be kept around when it is needed multiple times.
+    pub fn reflect(&self) -> Result<ShaderReflection> {
+        self.handle.reflect(self.shader_type)
+    }
+}
+
+//==============================================================================================================================
+
+/// Reflected shader binding type
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShaderBindingType {
+    /// Constant buffer
+    ConstantBuffer,
+    /// Sampled texture
+    SampledTexture,
+    /// Storage texture
+    StorageTexture,
+    /// Read-only texel buffer
+    ConstantTexelBuffer,
+    /// Read/write texel buffer
+    StorageTexelBuffer,
+    /// Read-only structured or raw buffer
+    ReadOnlyStorageBuffer,
+    /// Read/write structured or raw buffer
+    StorageBuffer,
+    /// Sampler
+    Sampler,
+    /// Raytracing acceleration structure
+    AccelerationStructure,
+}
+
+impl ShaderBindingType {
+    /// Get the descriptor type of a binding that can be bound in a resource descriptor table
+    /// 
+    /// Returns `None` for samplers, read-only storage buffers and acceleration structures, as these don't have a matching `DescriptorType`.
+    pub fn descriptor_type(self) -> Option<DescriptorType> {
+        match self {
+            ShaderBindingType::ConstantBuffer        => Some(DescriptorType::ConstantBuffer),
+            ShaderBindingType::SampledTexture        => Some(DescriptorType::SampledTexture),
+            ShaderBindingType::StorageTexture        => Some(DescriptorType::StorageTexture),
+            ShaderBindingType::ConstantTexelBuffer   => Some(DescriptorType::ConstantTexelBuffer),
+            ShaderBindingType::StorageTexelBuffer    => Some(DescriptorType::StorageTexelBuffer),
+            ShaderBindingType::StorageBuffer         => Some(DescriptorType::StorageBuffer),
+            ShaderBindingType::ReadOnlyStorageBuffer |
+            ShaderBindingType::Sampler               |
+            ShaderBindingType::AccelerationStructure => None,
+        }
+    }
+}
+
+/// Reflected shader binding
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ShaderBinding {
+    /// Name of the binding, `None` if the name was stripped from the shader
+    pub name:         Option<String>,
+    /// Binding type
+    pub binding_type: ShaderBindingType,
+    /// Binding index
+    /// 
+    /// - DX12: Register
+    /// - Vulkan: Binding
+    pub binding:      u32,
+    /// Number of descriptors, `None` for an unbounded array
+    pub count:        Option<NonZeroU32>,
+}
+
+/// Reflected set of shader bindings
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ShaderBindingSet {
+    /// Set index
+    /// 
+    /// - DX12: Register space
+    /// - Vulkan: Descriptor set
+    pub set:      u32,
+    /// Bindings, sorted by binding index
+    pub bindings: Vec<ShaderBinding>,
+}
+
+/// Reflected push constant range
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ShaderPushConstantRange {
+    /// Offset of the range (in bytes)
+    pub offset: u32,
+    /// Size of the range (in bytes)
+    pub size:   u32,
+}
+
+/// Reflected vertex input
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ShaderVertexInput {
+    /// Upper-case semantic name, without the semantic index
+    /// 
+    /// Empty if the semantic could not be recovered, e.g. for SPIR-V without semantic decorations or debug names
+    pub semantic:       String,
+    /// Semantic index
+    pub semantic_index: u8,
+    /// Input location
+    /// 
+    /// - DX12: Input register
+    /// - Vulkan: Location
+    pub location:       u32,
+    /// Number of components
+    pub components:     u8,
+    /// Data type the shader reads the input as, either `SFloat`, `SInt` or `UInt`
+    pub data_type:      VertexDataType,
+}
+
+impl ShaderVertexInput {
+    /// Check if a vertex format can be read by the vertex input
+    /// 
+    /// Formats that are converted to floats (normalized and float formats) are compatible with float inputs, integer formats need to have the same signedness
+    pub fn is_compatible_format(&self, format: VertexFormat) -> bool {
+        let (_, data_type) = format.to_components_and_data_type();
+        match self.data_type {
+            VertexDataType::SFloat |
+            VertexDataType::UFloat => matches!(data_type, VertexDataType::SFloat | VertexDataType::UFloat | VertexDataType::SNorm | VertexDataType::UNorm),
+            VertexDataType::SInt   => data_type == VertexDataType::SInt,
+            VertexDataType::Uint   => data_type == VertexDataType::Uint,
+            VertexDataType::SNorm  |
+            VertexDataType::UNorm  => false,
+        }
+    }
+
+    /// Get the 32-bit vertex format matching the input
+    pub fn default_format(&self) -> Option<VertexFormat> {
+        let components = match self.components {
+            1 => VertexComponents::X32,
+            2 => VertexComponents::X32Y32,
+            3 => VertexComponents::X32Y32Z32,
+            4 => VertexComponents::X32Y32Z32W32,
+            _ => return None,
+        };
+        VertexFormat::from_components_and_data_type(components, self.data_type)
+    }
+}
+
+/// Backend-agnostic shader reflection
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ShaderReflection {
+    /// Shader type
+    pub shader_type:       ShaderType,
+    /// Binding sets, sorted by set index
+    pub binding_sets:      Vec<ShaderBindingSet>,
+    /// Push constant ranges
+    /// 
+    /// DX12 does not differentiate between root constants and constant buffers in shader code, so root constants will be reflected as a `ConstantBuffer` binding
+    pub push_constants:    Vec<ShaderPushConstantRange>,
+    /// Vertex inputs, sorted by location, only available for vertex shaders
+    pub vertex_inputs:     Vec<ShaderVertexInput>,
+    /// Thread group size, only available for compute, task and mesh shaders
+    pub thread_group_size: Option<[u32; 3]>,
+}
+
+impl ShaderReflection {
+    /// Create an empty shader reflection
+    pub fn new(shader_type: ShaderType) -> Self {
+        Self {
+            shader_type,
+            binding_sets: Vec::new(),
+            push_constants: Vec::new(),
+            vertex_inputs: Vec::new(),
+            thread_group_size: None,
+        }
+    }
+
+    /// Add a binding to the set with the given index, used by the backends when building the reflection
+    pub fn add_binding(&mut self, set: u32, binding: ShaderBinding) {
+        let set_idx = match self.binding_sets.binary_search_by_key(&set, |binding_set| binding_set.set) {
+            Ok(idx) => idx,
+            Err(idx) => {
+                self.binding_sets.insert(idx, ShaderBindingSet { set, bindings: Vec::new() });
+                idx
+            },
+        };
+
+        let bindings = &mut self.binding_sets[set_idx].bindings;
+        let binding_idx = bindings.partition_point(|val| val.binding < binding.binding);
+        bindings.insert(binding_idx, binding);
+    }
+
+    /// Split a semantic into its name and index, e.g. `TEXCOORD3` into `("TEXCOORD", 3)`
+    pub fn split_semantic(semantic: &str) -> (String, u8) {
+        let name = semantic.trim_end_matches(|c: char| c.is_ascii_digit());
+        let index = semantic[name.len()..].parse().unwrap_or(0);
+        (name.to_ascii_uppercase(), index)
+    }
+
+    /// Generate an input layout from the vertex inputs
+    /// 
+    /// All elements will be tightly packed in input slot 0, using the 32-bit format matching the vertex input.
+    pub fn generate_input_layout(&self) -> Result<InputLayout> {
+        let mut input_layout = InputLayout::new();
+        let mut offset = 0;
+        for input in &self.vertex_inputs {
+            let format = input.default_format().ok_or_else(|| Error::InvalidParameter(format!("Cannot generate a vertex format for input `{}{}`", input.semantic, input.semantic_index)))?;
+            input_layout.push(InputLayoutElement::new(input.semantic.clone(), input.semantic_index, 0, format, offset, InputLayoutStepRate::PerVertex));
+            offset += format.byte_size() as u16;
+        }
+        Ok(input_layout)
+    }
+
+    /// Validate that an input layout provides all vertex inputs with a compatible format
+    /// 
+    /// Vertex inputs without a semantic cannot be matched to an element, so they are skipped.
+    pub fn validate_input_layout(&self, input_layout: &InputLayout) -> Result<()> {
+        for input in &self.vertex_inputs {
+            if input.semantic.is_empty() {
+                continue;
+            }
+
+            let element = input_layout.elements.iter().find(|elem| 
+                elem.semantic_index == input.semantic_index &&
+                elem.semantic().trim_end_matches('\0').eq_ignore_ascii_case(&input.semantic)
+            ).ok_or_else(|| Error::InvalidParameter(format!("Input layout is missing vertex input `{}{}`", input.semantic, input.semantic_index)))?;
+
+            if !input.is_compatible_format(element.format) {
+                return Err(Error::InvalidParameter(format!("Vertex input `{}{}` cannot be read from format `{}`", input.semantic, input.semantic_index, element.format)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate that all bindings used by the shader are covered by a pipeline layout
+    /// 
+    /// Sets are matched to pipeline layout entries in the order described in [`PipelineLayoutDesc`].
+    pub fn validate_pipeline_layout(&self, desc: &PipelineLayoutDesc) -> Result<()> {
+        let tables = desc.descriptor_tables.as_ref().map_or(&[][..], |tables| &tables[..]);
+        let inline_descriptors = desc.inline_descriptors.as_ref().map_or(&[][..], |inline| &inline[..]);
+        let num_constants = desc.constant_ranges.as_ref().map_or(0, |ranges| ranges.len());
+        let has_static_samplers = desc.static_samplers.is_some();
+
+        let inline_start = tables.len();
+        let constants_start = inline_start + inline_descriptors.len();
+        let static_sampler_idx = constants_start + num_constants;
+        let bindless_start = static_sampler_idx + has_static_samplers as usize;
+
+        for set in &self.binding_sets {
+            let set_idx = set.set as usize;
+            for binding in &set.bindings {
+                let binding_name = binding.name.as_ref().map_or(String::new(), |name| format!(" `{name}`"));
+
+                let valid = if set_idx < inline_start {
+                    let table = &tables[set_idx];
+                    let count = binding.count.map_or(table.num_descriptors(), |count| count.get());
+                    let in_range = binding.binding + count <= table.num_descriptors();
+                    let matches_type = match table.desc() {
+                        DescriptorTableDesc::Resource { ranges, .. } => binding.binding_type != ShaderBindingType::Sampler &&
+                            (binding.count.is_some() || ranges.last().map_or(false, |range| matches!(range.count, DescriptorCount::Unbounded(_)))),
+                        DescriptorTableDesc::Sampler { .. } => binding.binding_type == ShaderBindingType::Sampler,
+                    };
+                    in_range && matches_type
+                } else if set_idx < constants_start {
+                    let inline = &inline_descriptors[set_idx - inline_start];
+                    binding.binding == 0 && binding.count.map_or(false, |count| count.get() == 1) &&
+                        binding.binding_type.descriptor_type().map_or(binding.binding_type == ShaderBindingType::ReadOnlyStorageBuffer, |ty| ty == inline.descriptor_type)
+                } else if set_idx < static_sampler_idx {
+                    binding.binding_type == ShaderBindingType::ConstantBuffer
+                } else if set_idx == static_sampler_idx && has_static_samplers {
+                    binding.binding_type == ShaderBindingType::Sampler
+                } else {
+                    desc.flags.contains(PipelineLayoutFlags::Bindless) && set_idx < bindless_start + 2
+                };
+
+                if !valid {
+                    return Err(Error::InvalidParameter(format!("Shader binding{binding_name} ({:?}) at set {}, binding {} is not compatible with the pipeline layout", binding.binding_type, set.set, binding.binding)));
+                }
+            }
+        }
+        Ok(())
+    }
+}
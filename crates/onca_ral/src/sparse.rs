@@ -0,0 +1,286 @@
+use core::num::NonZeroU8;
+use std::collections::HashSet;
+
+use onca_common::sync::RwLock;
+
+use crate::*;
+
+/// Sparse (reserved) texture description
+///
+/// A sparse texture only reserves virtual address space on creation, memory is mapped to it per tile using `CommandQueueHandle::update_tile_mappings`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SparseTextureDesc {
+    /// Size of the texture
+    pub size:       TextureSize,
+    /// Format of the texture
+    pub format:     Format,
+    /// Number of mip levels
+    pub mip_levels: NonZeroU8,
+    /// Texture usages
+    pub usage:      TextureUsage,
+}
+
+impl SparseTextureDesc {
+    pub fn validate(&self, phys_dev: &PhysicalDevice) -> Result<()> {
+        #[cfg(feature = "validation")]
+        {
+            if phys_dev.sparse_resources.is_none() {
+                return Err(Error::MissingFeature("Sparse resources"));
+            }
+            check_invalid_parameter!(!self.format.components().is_planar(), "Sparse textures cannot use planar formats, found {}", self.format);
+            check_invalid_parameter!(!self.usage.contains(TextureUsage::ShadingRate), "Sparse textures cannot be used as shading rate attachments");
+            if let TextureSize::Size3D { .. } = self.size {
+                check_invalid_parameter!(phys_dev.sparse_resources.contains(SparseResourceSupport::Standard3DBlockShape), "3D sparse textures require `SparseResourceSupport::Standard3DBlockShape`");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Shape of a single sparse tile in texels
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SparseTileShape {
+    /// Width of a tile in texels
+    pub width:  u32,
+    /// Height of a tile in texels
+    pub height: u32,
+    /// Depth of a tile in texels
+    pub depth:  u32,
+}
+
+/// Tiling information of a sparse texture
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SparseTextureInfo {
+    /// Shape of a single tile
+    pub tile_shape:                SparseTileShape,
+    /// Number of mips that are stored using standard tiles, all mips starting at this index are stored in the packed mip tail
+    pub num_standard_mips:         u8,
+    /// Number of mips that are packed in the mip tail
+    pub num_packed_mips:           u8,
+    /// Number of tiles needed to store the packed mips of a single layer
+    pub num_tiles_for_packed_mips: u32,
+    /// Total number of tiles needed to make the whole texture resident
+    pub total_tiles:               u32,
+}
+
+impl SparseTextureInfo {
+    /// Get the number of tiles in each dimension for a given mip level
+    pub fn get_mip_tile_count(&self, size: TextureSize, mip: u8) -> (u32, u32, u32) {
+        let width = (size.width() as u32 >> mip).max(1);
+        let height = (size.height() as u32 >> mip).max(1);
+        let depth = (size.depth() as u32 >> mip).max(1);
+        (
+            width.div_ceil(self.tile_shape.width),
+            height.div_ceil(self.tile_shape.height),
+            depth.div_ceil(self.tile_shape.depth),
+        )
+    }
+}
+
+/// Region of tiles in a sparse texture
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SparseTileRegion {
+    /// Box of standard tiles in a single subresource
+    Tiles {
+        /// Subresource containing the tiles, this must be a mip stored using standard tiles
+        subresource: TextureSubresourceIndex,
+        /// Offset of the first tile (in tiles)
+        offset:      TextureOffset,
+        /// Number of tiles in each dimension
+        extent:      TextureExtent,
+    },
+    /// Packed mip tail of a layer
+    PackedMips {
+        /// Array layer, 0 for non-array textures
+        layer: u16,
+    },
+}
+
+/// Mapping of a region of tiles to memory
+#[derive(Clone)]
+pub struct SparseTileMapping {
+    /// Region of the texture to map
+    pub region:      SparseTileRegion,
+    /// Memory heap to map the tiles to, `None` unmaps the tiles
+    pub heap:        Option<MemoryHeapHandle>,
+    /// Offset into the heap (in tiles), tiles in the region will be mapped to consecutive tiles in the heap
+    pub heap_offset: u32,
+}
+
+impl SparseTileMapping {
+    pub fn validate(&self, texture: &SparseTexture) -> Result<()> {
+        #[cfg(feature = "validation")]
+        {
+            let info = texture.info();
+            match self.region {
+                SparseTileRegion::Tiles { subresource, offset, extent } => {
+                    let (mip, layer) = subresource_mip_and_layer(subresource);
+                    check_invalid_parameter!(mip < info.num_standard_mips, "Mip {mip} is part of the packed mips, use `SparseTileRegion::PackedMips` to map it");
+                    check_invalid_parameter!(layer < texture.size().layers(), "Layer {layer} is out of range, texture has {} layers", texture.size().layers());
+
+                    let (width, height, depth) = info.get_mip_tile_count(texture.size(), mip);
+                    check_invalid_parameter!(offset.x as u32 + extent.width.get() as u32 <= width, "Tile region exceeds the width of mip {mip} ({width} tiles)");
+                    check_invalid_parameter!(offset.y as u32 + extent.height.get() as u32 <= height, "Tile region exceeds the height of mip {mip} ({height} tiles)");
+                    check_invalid_parameter!(offset.z as u32 + extent.depth.get() as u32 <= depth, "Tile region exceeds the depth of mip {mip} ({depth} tiles)");
+                },
+                SparseTileRegion::PackedMips { layer } => {
+                    check_invalid_parameter!(info.num_packed_mips > 0, "Texture does not have any packed mips");
+                    check_invalid_parameter!(layer < texture.size().layers(), "Layer {layer} is out of range, texture has {} layers", texture.size().layers());
+                },
+            }
+
+            if let Some(heap) = &self.heap {
+                let end = (self.heap_offset as u64 + self.num_tiles(info) as u64) * constants::SPARSE_TILE_SIZE;
+                check_invalid_parameter!(end <= heap.size(), "Mapped tiles exceed the size of the heap, needed {end} bytes, heap has {} bytes", heap.size());
+                check_invalid_parameter!(heap.memory_type() == MemoryType::Gpu, "Sparse tiles can only be mapped to GPU memory");
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the number of tiles mapped by the mapping
+    pub fn num_tiles(&self, info: &SparseTextureInfo) -> u32 {
+        match self.region {
+            SparseTileRegion::Tiles { extent, .. } => extent.width.get() as u32 * extent.height.get() as u32 * extent.depth.get() as u32,
+            SparseTileRegion::PackedMips { .. }    => info.num_tiles_for_packed_mips,
+        }
+    }
+}
+
+/// Get the mip level and array layer of a subresource
+pub fn subresource_mip_and_layer(subresource: TextureSubresourceIndex) -> (u8, u16) {
+    match subresource {
+        TextureSubresourceIndex::Texture { mip_level, .. }      => (mip_level, 0),
+        TextureSubresourceIndex::Array { mip_level, layer, .. } => (mip_level, layer),
+    }
+}
+
+//==============================================================================================================================
+
+/// Location of a single standard tile in a sparse texture
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct SparseTileCoord {
+    /// Mip level
+    pub mip:   u8,
+    /// Array layer
+    pub layer: u16,
+    /// X coordinate in tiles
+    pub x:     u32,
+    /// Y coordinate in tiles
+    pub y:     u32,
+    /// Z coordinate in tiles
+    pub z:     u32,
+}
+
+#[derive(Default)]
+struct SparseResidency {
+    tiles:       HashSet<SparseTileCoord>,
+    packed_mips: HashSet<u16>,
+}
+
+/// Sparse (reserved) texture
+///
+/// The residency of the texture is tracked on the CPU whenever its tile mappings are updated, which allows a virtual texturing system to query which tiles are currently backed by memory
+pub struct SparseTexture {
+    texture:   TextureHandle,
+    info:      SparseTextureInfo,
+    residency: RwLock<SparseResidency>,
+}
+
+impl SparseTexture {
+    pub(crate) fn new(texture: TextureHandle, info: SparseTextureInfo) -> Self {
+        Self { texture, info, residency: RwLock::new(SparseResidency::default()) }
+    }
+
+    /// Get the underlying texture
+    pub fn texture(&self) -> &TextureHandle {
+        &self.texture
+    }
+
+    /// Get the tiling info of the texture
+    pub fn info(&self) -> &SparseTextureInfo {
+        &self.info
+    }
+
+    /// Get the texture size
+    pub fn size(&self) -> TextureSize {
+        self.texture.size()
+    }
+
+    /// Check if a tile is currently mapped to memory
+    pub fn is_tile_resident(&self, coord: SparseTileCoord) -> bool {
+        self.residency.read().tiles.contains(&coord)
+    }
+
+    /// Check if the packed mips of a layer are currently mapped to memory
+    pub fn are_packed_mips_resident(&self, layer: u16) -> bool {
+        self.residency.read().packed_mips.contains(&layer)
+    }
+
+    /// Get the number of tiles that are currently mapped to memory, including those of the packed mips
+    pub fn resident_tile_count(&self) -> u32 {
+        let residency = self.residency.read();
+        residency.tiles.len() as u32 + residency.packed_mips.len() as u32 * self.info.num_tiles_for_packed_mips
+    }
+
+    /// Get the most detailed mip level at which a texel is fully resident, i.e. the tile containing it in that and all less detailed mips are mapped
+    ///
+    /// Returns `None` if not even the least detailed mip is resident
+    pub fn get_min_resident_mip(&self, layer: u16, x: u32, y: u32, z: u32) -> Option<u8> {
+        let residency = self.residency.read();
+        let mip_levels = self.texture.mip_levels();
+
+        let mut min_mip = None;
+        if self.info.num_packed_mips > 0 {
+            if !residency.packed_mips.contains(&layer) {
+                return None;
+            }
+            min_mip = Some(self.info.num_standard_mips);
+        }
+
+        for mip in (0..self.info.num_standard_mips.min(mip_levels)).rev() {
+            let coord = SparseTileCoord {
+                mip,
+                layer,
+                x: (x >> mip) / self.info.tile_shape.width,
+                y: (y >> mip) / self.info.tile_shape.height,
+                z: (z >> mip) / self.info.tile_shape.depth,
+            };
+            if !residency.tiles.contains(&coord) {
+                break;
+            }
+            min_mip = Some(mip);
+        }
+        min_mip
+    }
+
+    /// Update the CPU-side residency after the tile mappings were updated on the GPU
+    pub(crate) fn update_residency(&self, mappings: &[SparseTileMapping]) {
+        let mut residency = self.residency.write();
+        for mapping in mappings {
+            let resident = mapping.heap.is_some();
+            match mapping.region {
+                SparseTileRegion::Tiles { subresource, offset, extent } => {
+                    let (mip, layer) = subresource_mip_and_layer(subresource);
+                    for z in offset.z as u32..offset.z as u32 + extent.depth.get() as u32 {
+                        for y in offset.y as u32..offset.y as u32 + extent.height.get() as u32 {
+                            for x in offset.x as u32..offset.x as u32 + extent.width.get() as u32 {
+                                let coord = SparseTileCoord { mip, layer, x, y, z };
+                                if resident {
+                                    residency.tiles.insert(coord);
+                                } else {
+                                    residency.tiles.remove(&coord);
+                                }
+                            }
+                        }
+                    }
+                },
+                SparseTileRegion::PackedMips { layer } => if resident {
+                    residency.packed_mips.insert(layer);
+                } else {
+                    residency.packed_mips.remove(&layer);
+                },
+            }
+        }
+    }
+}
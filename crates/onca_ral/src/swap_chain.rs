@@ -1,14 +1,15 @@
 use onca_common::{
     prelude::*,
     sys::{AppHandle, get_app_handle}, sync::{RwLock, RwLockReadGuard, MappedRwLockReadGuard},
+    time::Duration,
 };
-use onca_window::{OSWindowHandle, Window};
+use onca_window::{OSWindowHandle, Window, WindowId, WindowManager, Monitor};
 
 use crate::{
     common::*,
     handle::{InterfaceHandle, HandleImpl},
     api,
-    Handle, TextureHandle, Result, Error, CommandQueueHandle, WeakHandle, Device,
+    Handle, TextureHandle, Result, Error, CommandQueueHandle, WeakHandle, Device, track_device_lost,
     TextureUsage, TextureSize, TextureFlags, RenderTargetViewDesc, RenderTargetViewType, TextureAspect, RenderTargetViewHandle, DeviceHandle,
 };
 
@@ -28,6 +29,10 @@ pub struct SwapChainDesc {
     pub num_backbuffers:        u8,
     /// Array with format, order from most preferred to least preferred, the first supported format will be used
     pub formats:                Vec<Format>,
+    /// Array with color spaces, order from most preferred to least preferred.
+    /// 
+    /// The first color space that is supported by the output in combination with any of the formats will be used, if none are supported, `SwapChainColorSpace::SrgbNonLinear` will be used.
+    pub color_spaces:           Vec<SwapChainColorSpace>,
     /// Usages to try and create the backbuffers with
     pub usages:                 TextureUsage,
     /// Present mode to use
@@ -46,6 +51,7 @@ impl SwapChainDesc {
     /// The following values will be set to a default value:
     /// - `preserve_after_present`
     /// - `alpha_mode`
+    /// - `color_spaces`
     pub fn from_window(window: &Window, num_backbuffers: u8, formats: Vec<Format>, usages: TextureUsage, present_mode: PresentMode, queue: CommandQueueHandle) -> Self {
         let window_settings = window.settings();
         Self {
@@ -55,6 +61,7 @@ impl SwapChainDesc {
             height: window_settings.size().height,
             num_backbuffers,
             formats,
+            color_spaces: vec![SwapChainColorSpace::SrgbNonLinear],
             usages,
             present_mode,
             preserve_after_present: false,
@@ -63,6 +70,17 @@ impl SwapChainDesc {
         }
     }
 
+    /// Create swapchain info for each window managed by the window manager, starting with the main window.
+    /// 
+    /// Each window needs its own swap-chain, which should be destroyed when the window sends a `WindowEvent::Closed` event.
+    /// 
+    /// The same default values as with [`SwapChainDesc::from_window`] will be used.
+    pub fn from_windows(window_manager: &WindowManager, num_backbuffers: u8, formats: &[Format], usages: TextureUsage, present_mode: PresentMode, queue: &CommandQueueHandle) -> Vec<(WindowId, Self)> {
+        window_manager.windows()
+            .map(|window| (window.id(), Self::from_window(window, num_backbuffers, formats.to_vec(), usages, present_mode, queue.clone())))
+            .collect()
+    }
+
     pub fn validate(&self) -> Result<()> {
         #[cfg(feature = "validation")]
         {
@@ -98,6 +116,29 @@ pub trait SwapChainInterface {
     unsafe fn recreate_swapchain(&self, device: &DeviceHandle, params: api::SwapChainChangeParams) -> Result<api::SwapChainResultInfo>;
     /// Resize the size of the swap-chain
     unsafe fn resize(&self, device: &DeviceHandle, params: api::SwapChainChangeParams) -> Result<api::SwapChainResizeResultInfo>;
+    /// Change the color space of the swap-chain, this recreates the backbuffers using the first format in `formats` that supports the color space
+    /// 
+    /// If the color space is not supported with any of the formats, `Error::UnsupportedColorSpace` is returned
+    unsafe fn change_color_space(&self, device: &DeviceHandle, color_space: SwapChainColorSpace, formats: &[Format], params: api::SwapChainChangeParams) -> Result<api::SwapChainResultInfo>;
+    /// Check if the color space is supported with any of the formats in `formats`, without changing the swap-chain
+    unsafe fn supports_color_space(&self, color_space: SwapChainColorSpace, formats: &[Format]) -> Result<bool>;
+    /// Set the HDR metadata of the swap-chain, `None` clears the metadata
+    unsafe fn set_hdr_metadata(&self, metadata: Option<&HdrMetadata>) -> Result<()>;
+    /// Get the capabilities of the output the swap-chain is currently presented to
+    unsafe fn get_output_info(&self) -> Result<SwapChainOutputInfo>;
+    /// Get the present statistics of the swap-chain
+    /// 
+    /// If the underlying API cannot report present statistics, this function is allowed to return `Error::MissingFeature`
+    unsafe fn get_present_statistics(&self) -> Result<api::PresentStatisticsResultInfo>;
+    /// Set the maximum number of frames that can be queued for presentation
+    unsafe fn set_max_frame_latency(&self, latency: u8) -> Result<()>;
+    /// Wait until the swap-chain is ready to accept a new frame, respecting the maximum frame latency
+    /// 
+    /// Returns `Ok(true)` when the swap-chain is ready, and `Ok(false)` if the wait hit a timeout
+    unsafe fn wait_for_present_ready(&self, timeout: Duration) -> Result<bool>;
+
+    /// Set the debug name of the swap chain
+    unsafe fn set_debug_name(&self, name: &str);
 }
 
 pub type SwapChainInterfaceHandle = InterfaceHandle<dyn SwapChainInterface>;
@@ -106,30 +147,47 @@ struct SwapChainDynamic {
     width:         u16,
     height:        u16,
     present_mode:  PresentMode,
+    format:        Format,
+    color_space:   SwapChainColorSpace,
+    hdr_metadata:  Option<HdrMetadata>,
     backbuffers:   Vec<(TextureHandle, RenderTargetViewHandle)>,
-    current_index: u8
+    current_index: u8,
+    max_frame_latency:   u8,
+    last_present_counts: Option<(u32, u32)>,
+    dropped_frames:      u32,
 }
 
 impl SwapChainDynamic {
-    pub fn new(width: u16, height: u16, present_mode: PresentMode, backbuffers: Vec<(TextureHandle, RenderTargetViewHandle)>) -> Self {
+    pub fn new(width: u16, height: u16, present_mode: PresentMode, format: Format, color_space: SwapChainColorSpace, backbuffers: Vec<(TextureHandle, RenderTargetViewHandle)>) -> Self {
         Self {
             width,
             height,
             present_mode,
+            format,
+            color_space,
+            hdr_metadata: None,
             backbuffers,
             current_index: 0,
+            max_frame_latency: 1,
+            last_present_counts: None,
+            dropped_frames: 0,
         }
     }
+
+    fn reset_present_statistics(&mut self) {
+        self.last_present_counts = None;
+        self.dropped_frames = 0;
+    }
 }
 
 /// Swap chain
 // TODO: Stereo support
 pub struct SwapChain {
     handle:                 InterfaceHandle<dyn SwapChainInterface>,
-    _app_handle:            AppHandle,
-    _window_handle:         OSWindowHandle,
+    app_handle:             AppHandle,
+    window_handle:          OSWindowHandle,
     num_backbuffers:        u8,
-    format:                 Format,
+    formats:                Vec<Format>,
     backbuffer_usages:      TextureUsage,
     alpha_mode:             SwapChainAlphaMode,
     preserve_after_present: bool,
@@ -158,13 +216,13 @@ impl SwapChain {
             }
         }
 
-        let dynamic = RwLock::new(SwapChainDynamic::new(result_info.width, result_info.height, result_info.present_mode, backbuffers));
+        let dynamic = RwLock::new(SwapChainDynamic::new(result_info.width, result_info.height, result_info.present_mode, result_info.format, result_info.color_space, backbuffers));
         Ok(Handle::new(Self {
             handle,
-            _app_handle: desc.app_handle,
-            _window_handle: desc.window_handle,
+            app_handle: desc.app_handle,
+            window_handle: desc.window_handle,
             num_backbuffers: result_info.num_backbuffers,
-            format: result_info.format,
+            formats: desc.formats,
             backbuffer_usages: result_info.backbuffer_usages,
             alpha_mode: desc.alpha_mode,
             preserve_after_present: desc.preserve_after_present,
@@ -186,12 +244,12 @@ impl SwapChain {
         }
 
         let dynamic = self.dynamic.read();
-        unsafe { self.handle.present(dynamic.present_mode, dynamic.current_index as u32, &self.queue, present_info) }
+        track_device_lost(&self.device, unsafe { self.handle.present(dynamic.present_mode, dynamic.current_index as u32, &self.queue, present_info) })
     }
 
     /// Acquire the next backbuffer
     pub fn acquire_next_backbuffer(&self) -> Result<()> {
-        let index = unsafe { self.handle.acquire_next_backbuffer()? };
+        let index = track_device_lost(&self.device, unsafe { self.handle.acquire_next_backbuffer() })?;
         self.dynamic.write().current_index = index;
         Ok(())
     }
@@ -199,29 +257,152 @@ impl SwapChain {
     /// Change the current present mode of the swapchain
     pub fn change_present_mode(&self, present_mode: PresentMode) -> Result<()> {
         if self.handle.needs_present_mode_recreate() {
-            let params = api::SwapChainChangeParams {
-                width: *self.width(),
-                height: *self.height(),
-                num_backbuffers: self.num_backbuffers,
-                format: self.format,
-                backbuffer_usages: self.backbuffer_usages,
-                present_mode,
-                alpha_mode: self.alpha_mode,
-                queue: self.queue.clone(),
-            };
-            self.recreate_swapchain(params)?;
+            let mut params = self.get_change_params();
+            params.present_mode = present_mode;
+            self.recreate_swapchain(|device| unsafe { self.handle.recreate_swapchain(device, params) })?;
         }
         self.dynamic.write().present_mode = present_mode;
         Ok(())
     }
 
-    fn recreate_swapchain(&self, params: api::SwapChainChangeParams) -> Result<()> {
+    /// Change the color space of the swap-chain
+    /// 
+    /// The backbuffers will be recreated using the first format the swap-chain was created with that supports the color space.
+    /// Any HDR metadata that was set will be cleared when changing the color space.
+    pub fn change_color_space(&self, color_space: SwapChainColorSpace) -> Result<()> {
+        if *self.color_space() == color_space {
+            return Ok(());
+        }
+
+        // Check support first, as the backbuffers are released before the swap-chain is recreated, which would leave it without backbuffers if the color space is unsupported
+        if !track_device_lost(&self.device, unsafe { self.handle.supports_color_space(color_space, &self.formats) })? {
+            return Err(Error::UnsupportedColorSpace(color_space));
+        }
+
+        let params = self.get_change_params();
+        self.recreate_swapchain(|device| track_device_lost(&self.device, unsafe { self.handle.change_color_space(device, color_space, &self.formats, params) }))?;
+        self.dynamic.write().hdr_metadata = None;
+        Ok(())
+    }
+
+    /// Set the HDR metadata used by the output to tone-map the presented content, `None` clears the metadata
+    /// 
+    /// HDR metadata can only be set when the swap-chain uses a HDR color space
+    pub fn set_hdr_metadata(&self, metadata: Option<&HdrMetadata>) -> Result<()> {
+        #[cfg(feature = "validation")]
+        {
+            let color_space = *self.color_space();
+            if metadata.is_some() && !color_space.is_hdr() {
+                return Err(Error::InvalidParameter(format!("SwapChain::set_hdr_metadata(): HDR metadata can only be set for a HDR color space, current color space is {color_space}")));
+            }
+        }
+
+        track_device_lost(&self.device, unsafe { self.handle.set_hdr_metadata(metadata) })?;
+        self.dynamic.write().hdr_metadata = metadata.copied();
+        Ok(())
+    }
+
+    /// Get the capabilities of the output (monitor) the swap-chain is currently presented to
+    /// 
+    /// As the window can move between outputs, this should be re-queried when the window has moved to a different monitor
+    pub fn get_output_info(&self) -> Result<SwapChainOutputInfo> {
+        track_device_lost(&self.device, unsafe { self.handle.get_output_info() })
+    }
+
+    /// Get the present statistics of the swap-chain
+    /// 
+    /// Dropped frames are detected by comparing the number of vertical blanks that passed with the number of frames that were presented since the last call,
+    /// so this should be called once per frame to get accurate results.
+    /// 
+    /// If the underlying API cannot report present statistics, `Error::MissingFeature` is returned
+    pub fn get_present_statistics(&self) -> Result<PresentStatistics> {
+        let result_info = track_device_lost(&self.device, unsafe { self.handle.get_present_statistics() })?;
+
+        let mut dynamic = self.dynamic.write();
+        let sync_interval = match dynamic.present_mode {
+            PresentMode::Immediate => 0,
+            _ => 1,
+        };
+
+        if let Some((last_present_count, last_refresh_count)) = dynamic.last_present_counts {
+            let present_delta = result_info.present_count.wrapping_sub(last_present_count);
+            let refresh_delta = result_info.present_refresh_count.wrapping_sub(last_refresh_count);
+            if sync_interval > 0 {
+                dynamic.dropped_frames += refresh_delta.saturating_sub(present_delta * sync_interval);
+            }
+        }
+        dynamic.last_present_counts = Some((result_info.present_count, result_info.present_refresh_count));
+
+        Ok(PresentStatistics {
+            present_count: result_info.present_count,
+            present_refresh_count: result_info.present_refresh_count,
+            present_time: result_info.present_time,
+            sync_interval,
+            dropped_frames: dynamic.dropped_frames,
+        })
+    }
+
+    /// Set the maximum number of frames that can be queued for presentation, this defaults to 1
+    /// 
+    /// A lower latency reduces input latency, while a higher latency allows the CPU to run further ahead of the GPU.
+    /// 
+    /// A validation error will occur when `latency` is 0 or exceeds `constants::MAX_FRAME_LATENCY`
+    pub fn set_max_frame_latency(&self, latency: u8) -> Result<()> {
+        #[cfg(feature = "validation")]
+        {
+            if latency == 0 || latency > crate::constants::MAX_FRAME_LATENCY {
+                return Err(Error::InvalidParameter(format!("SwapChain::set_max_frame_latency(): `latency` needs to be in the range [1, {}], found {latency}", crate::constants::MAX_FRAME_LATENCY)));
+            }
+        }
+
+        track_device_lost(&self.device, unsafe { self.handle.set_max_frame_latency(latency) })?;
+        self.dynamic.write().max_frame_latency = latency;
+        Ok(())
+    }
+
+    /// Get the maximum number of frames that can be queued for presentation
+    pub fn max_frame_latency(&self) -> u8 {
+        self.dynamic.read().max_frame_latency
+    }
+
+    /// Wait until the swap-chain is ready to accept a new frame
+    /// 
+    /// This should be called at the start of a frame, before any input is processed, so the frame can target a stable frame time with the lowest possible latency.
+    /// 
+    /// Returns result with `Ok(true)` when the swap-chain is ready, and `Ok(false)` if the wait hit a timeout
+    pub fn wait_for_present_ready(&self, timeout: Duration) -> Result<bool> {
+        track_device_lost(&self.device, unsafe { self.handle.wait_for_present_ready(timeout) })
+    }
+
+    /// Get the monitor the swap-chain's window currently overlaps the most, if it can be detected
+    pub fn get_monitor(&self) -> Option<Monitor> {
+        Monitor::from_window_handle(self.window_handle)
+    }
+
+    fn get_change_params(&self) -> api::SwapChainChangeParams {
+        let dynamic = self.dynamic.read();
+        api::SwapChainChangeParams {
+            width: dynamic.width,
+            height: dynamic.height,
+            num_backbuffers: self.num_backbuffers,
+            format: dynamic.format,
+            backbuffer_usages: self.backbuffer_usages,
+            present_mode: dynamic.present_mode,
+            alpha_mode: self.alpha_mode,
+            color_space: dynamic.color_space,
+            queue: self.queue.clone(),
+        }
+    }
+
+    fn recreate_swapchain<F>(&self, recreate: F) -> Result<()> where
+        F: FnOnce(&DeviceHandle) -> Result<api::SwapChainResultInfo>
+    {
         let mut dynamic = self.dynamic.write();
         // Should handle cleanup for backbuffers
         dynamic.backbuffers.clear();
 
         let device = WeakHandle::upgrade(&self.device).unwrap();
-        let result_info = unsafe { self.handle.recreate_swapchain(&device, params)? };
+        let result_info = recreate(&device)?;
 
         let texture_size = TextureSize::new_2d(result_info.width, result_info.height, 1).unwrap();
         let rtv_desc = RenderTargetViewDesc {
@@ -238,10 +419,24 @@ impl SwapChain {
         dynamic.width = result_info.width;
         dynamic.height = result_info.height;
         dynamic.present_mode = result_info.present_mode;
+        dynamic.format = result_info.format;
+        dynamic.color_space = result_info.color_space;
+        dynamic.reset_present_statistics();
 
         Ok(())
     }
 
+    /// Resize the swapchain to the size of the window's client area, e.g. after a `WindowEvent::Resized` event.
+    /// 
+    /// A minimized window has no client area, in which case the swapchain will keep its current size.
+    pub fn resize_to_window(&self, window: &Window) -> Result<()> {
+        let size = window.settings().size();
+        if size.width == 0 || size.height == 0 {
+            return Ok(());
+        }
+        self.resize(size.width, size.height)
+    }
+
     /// Resize the swapchain
     pub fn resize(&self, width: u16, height: u16) -> Result<()> {
         debug_assert!(width != 0);
@@ -258,26 +453,28 @@ impl SwapChain {
                 width,
                 height,
                 num_backbuffers: self.num_backbuffers,
-                format: self.format,
+                format: dynamic.format,
                 backbuffer_usages: self.backbuffer_usages,
                 present_mode: dynamic.present_mode,
                 alpha_mode: self.alpha_mode,
+                color_space: dynamic.color_space,
                 queue: self.queue.clone(),
             };
 
             let result_info = unsafe { self.handle.resize(&device, params)? };
             dynamic.width = result_info.width;
             dynamic.height = result_info.height;
+            dynamic.reset_present_statistics();
 
             let texture_size = TextureSize::new_2d(result_info.width, result_info.height, 1).unwrap();
             let rtv_desc = RenderTargetViewDesc {
                 view_type: RenderTargetViewType::View2D { mip_slice: 0, aspect: TextureAspect::Color },
-                format: self.format,
+                format: dynamic.format,
             };
 
             for handle in result_info.backbuffers {
                 unsafe {
-                    let texture_handle = TextureHandle::create(self.device.clone(), handle, TextureFlags::None, texture_size, self.format, 1, self.backbuffer_usages);
+                    let texture_handle = TextureHandle::create(self.device.clone(), handle, TextureFlags::None, texture_size, dynamic.format, 1, self.backbuffer_usages);
                     let rtv = texture_handle.get_or_create_render_target_view(&rtv_desc)?;
                     dynamic.backbuffers.push((texture_handle, rtv));
                 }
@@ -303,7 +500,17 @@ impl SwapChain {
 
     /// Get the backbuffer format
     pub fn backbuffer_format(&self) -> Format {
-        self.format
+        self.dynamic.read().format
+    }
+
+    /// Get the swap-chain color space
+    pub fn color_space(&self) -> MappedRwLockReadGuard<SwapChainColorSpace> {
+        RwLockReadGuard::map(self.dynamic.read(), |dy| &dy.color_space)
+    }
+
+    /// Get the HDR metadata that is currently set
+    pub fn hdr_metadata(&self) -> Option<HdrMetadata> {
+        self.dynamic.read().hdr_metadata
     }
 
     /// Get the backbuffer usages
@@ -333,6 +540,41 @@ impl SwapChain {
     pub fn preserve_after_present(&self) -> bool {
         self.preserve_after_present
     }
+
+    /// Set the debug name, which will show up in graphics debuggers and validation messages
+    pub fn set_debug_name(&self, name: &str) {
+        unsafe { self.handle.set_debug_name(name) }
+    }
+
+    /// Get the queue the swap chain is associated with
+    pub(crate) fn queue(&self) -> &CommandQueueHandle {
+        &self.queue
+    }
+
+    /// Get a description to recreate the swap chain with the same settings, but associated with a different queue
+    pub(crate) fn get_recreate_desc(&self, queue: CommandQueueHandle) -> SwapChainDesc {
+        let dynamic = self.dynamic.read();
+
+        // Prefer the current format, but keep the other formats around for any later color space changes
+        let mut formats = Vec::with_capacity(self.formats.len() + 1);
+        formats.push(dynamic.format);
+        formats.extend(self.formats.iter().copied().filter(|format| *format != dynamic.format));
+
+        SwapChainDesc {
+            app_handle: self.app_handle,
+            window_handle: self.window_handle,
+            width: dynamic.width,
+            height: dynamic.height,
+            num_backbuffers: self.num_backbuffers,
+            formats,
+            color_spaces: vec![dynamic.color_space],
+            usages: self.backbuffer_usages,
+            present_mode: dynamic.present_mode,
+            preserve_after_present: self.preserve_after_present,
+            alpha_mode: self.alpha_mode,
+            queue,
+        }
+    }
 }
 
 impl HandleImpl for SwapChain {
@@ -1,11 +1,15 @@
 use core::{
-    num::{NonZeroU8, NonZeroU16},
+    num::{NonZeroU8, NonZeroU16, NonZeroU64},
     hash::Hash,
     fmt,
+    mem::ManuallyDrop,
 };
 use std::collections::HashMap;
 
-use onca_common::sync::{RwLock, RwLockUpgradableReadGuard};
+use onca_common::{
+    sync::{RwLock, RwLockUpgradableReadGuard},
+    time::Duration,
+};
 use onca_common_macros::{flags, EnumDisplay};
 
 use crate::{*, handle::{InterfaceHandle, create_ral_handle}};
@@ -31,6 +35,8 @@ pub enum TextureUsage {
     ColorAttachment,
     /// Texture can be used as a depth/stencil attachment
     DepthStencilAttachment,
+    /// Texture can be used as a shading rate attachment
+    ShadingRate,
 }
 
 // TODO: DX12 does planes as indices, not aspects
@@ -347,6 +353,8 @@ impl TextureSize {
 /// Texture flags
 #[flags]
 pub enum TextureFlags {
+    /// Texture is a sparse (reserved) texture, memory is mapped to it per tile
+    Sparse,
 }
 
 /// Offset into a texture
@@ -415,10 +423,52 @@ impl TextureExtent {
 // TEXTURES
 //==============================================================================================================================
 
+/// Texture description
+#[derive(Clone, Copy, Debug)]
+pub struct TextureDesc {
+    /// Size of the texture
+    pub size:       TextureSize,
+    /// Format of the texture
+    pub format:     Format,
+    /// Number of mip levels
+    pub mip_levels: NonZeroU8,
+    /// Number of samples per pixel
+    /// 
+    /// Multisampled textures need to be 2D textures with a single mip level, use `PhysicalDevice::get_supported_sample_counts` to check which sample counts a format supports
+    pub samples:    SampleCount,
+    /// Texture usages
+    pub usage:      TextureUsage,
+    /// Allocation description
+    pub alloc_desc: GpuAllocationDesc,
+}
+
+impl TextureDesc {
+    pub fn validate(&self) -> Result<()> {
+        #[cfg(feature = "validation")]
+        {
+            let (width, height, depth, _) = self.size.as_tuple();
+            let max_mips = 16 - width.max(height).max(depth).leading_zeros() as u8;
+            check_invalid_parameter!(self.mip_levels.get() <= max_mips, "Texture of size {width}x{height}x{depth} can have at most {max_mips} mip levels, found {}", self.mip_levels);
+            check_invalid_parameter!(!self.usage.is_none(), "A texture needs at least 1 usage");
+            check_invalid_parameter!(self.alloc_desc.memory_type == MemoryType::Gpu, "Textures can only be allocated in GPU memory, use a readback buffer to access texture data on the CPU");
+            if self.samples.is_multisampled() {
+                check_invalid_parameter!(matches!(self.size, TextureSize::Size2D { .. }), "Only 2D textures can be multisampled");
+                check_invalid_parameter!(self.mip_levels.get() == 1, "Multisampled textures can only have 1 mip level, found {}", self.mip_levels);
+                check_invalid_parameter!(self.usage.intersects(TextureUsage::ColorAttachment | TextureUsage::DepthStencilAttachment), "Multisampled textures need either the `ColorAttachment` or `DepthStencilAttachment` usage");
+                check_invalid_parameter!(!self.usage.contains(TextureUsage::ShadingRate), "Multisampled textures cannot be used as a shading rate attachment");
+            }
+        }
+        Ok(())
+    }
+}
+
 pub trait TextureInterface {
     unsafe fn create_sampled_texture_view(&self, texture: &TextureHandle, desc: &SampledTextureViewDesc) -> Result<SampledTextureViewInterfaceHandle>;
     unsafe fn create_storage_texture_view(&self, texture: &TextureHandle, desc: &StorageTextureViewDesc) -> Result<StorageTextureViewInterfaceHandle>;
     unsafe fn create_render_texture_view(&self, device: &DeviceHandle, texture: &TextureHandle, desc: &RenderTargetViewDesc) -> Result<RenderTargetViewInterfaceHandle>;
+
+    /// Set the debug name of the texture
+    unsafe fn set_debug_name(&self, name: &str);
 }
 
 pub type TextureInterfaceHandle = InterfaceHandle<dyn TextureInterface>;
@@ -443,31 +493,80 @@ impl TextureDynamic {
 /// Texture
 pub struct Texture {
     device:     WeakHandle<Device>,
-    handle:     TextureInterfaceHandle,
+    handle:     ManuallyDrop<TextureInterfaceHandle>,
+    /// Allocation owned by the texture, `None` for swap-chain, sparse and placed textures
+    allocation: Option<GpuAllocation>,
+    /// Heap a placed texture lives in, which is kept alive for as long as the texture exists
+    heap:       Option<MemoryHeapHandle>,
     flags:      TextureFlags,
     size:       TextureSize,
     format:     Format,
     num_mips:   u8,
+    samples:    SampleCount,
     usage:      TextureUsage,
 
     pub(crate) dynamic: RwLock<TextureDynamic>,
+    debug_name:         RwLock<String>,
 }
-create_ral_handle!(TextureHandle, Texture, TextureInterfaceHandle);
+create_ral_handle!(TextureHandle, Texture, TextureInterfaceHandle, debug_name);
 
 impl TextureHandle {
     pub(crate) unsafe fn create(device: WeakHandle<Device>, handle: TextureInterfaceHandle, flags: TextureFlags, size: TextureSize, format: Format, num_mips: u8, usage: TextureUsage) -> Self {
         Self::new(Texture {
             device,
-            handle,
+            handle: ManuallyDrop::new(handle),
+            allocation: None,
+            heap: None,
             size,
             flags,
             format,
             num_mips,
+            samples: SampleCount::Sample1,
             usage,
             dynamic: RwLock::new(TextureDynamic::new()),
+            debug_name: RwLock::new(String::new()),
+        })
+    }
+
+    pub(crate) unsafe fn create_allocated(device: &DeviceHandle, handle: TextureInterfaceHandle, allocation: GpuAllocation, desc: &TextureDesc) -> Self {
+        Self::new(Texture {
+            device: Handle::downgrade(device),
+            handle: ManuallyDrop::new(handle),
+            allocation: Some(allocation),
+            heap: None,
+            size: desc.size,
+            flags: TextureFlags::None,
+            format: desc.format,
+            num_mips: desc.mip_levels.get(),
+            samples: desc.samples,
+            usage: desc.usage,
+            dynamic: RwLock::new(TextureDynamic::new()),
+            debug_name: RwLock::new(String::new()),
+        })
+    }
+
+    pub(crate) unsafe fn create_placed(device: &DeviceHandle, handle: TextureInterfaceHandle, heap: MemoryHeapHandle, desc: &TextureDesc) -> Self {
+        Self::new(Texture {
+            device: Handle::downgrade(device),
+            handle: ManuallyDrop::new(handle),
+            allocation: None,
+            heap: Some(heap),
+            size: desc.size,
+            flags: TextureFlags::None,
+            format: desc.format,
+            num_mips: desc.mip_levels.get(),
+            samples: desc.samples,
+            usage: desc.usage,
+            dynamic: RwLock::new(TextureDynamic::new()),
+            debug_name: RwLock::new(String::new()),
         })
     }
     
+    /// Get the texture flags
+    pub fn flags(&self) -> TextureFlags {
+        self.flags
+    }
+
     /// Get the texture size
     pub fn size(&self) -> TextureSize {
         self.size
@@ -487,6 +586,107 @@ impl TextureHandle {
     pub fn mip_levels(&self) -> u8 {
         self.num_mips
     }
+
+    /// Get the number of samples per pixel
+    pub fn samples(&self) -> SampleCount {
+        self.samples
+    }
+
+    /// Get the heap the texture is placed in, `None` if the texture is not a placed texture
+    pub fn heap(&self) -> Option<&MemoryHeapHandle> {
+        self.heap.as_ref()
+    }
+
+    /// Read back the content of a single subresource to the CPU
+    /// 
+    /// The copy is recorded and submitted on the graphics queue, after which this call waits until it has finished executing,
+    /// so this is meant for screenshots, automated tests and offline tools, and should not be used every frame.
+    /// 
+    /// The texture needs the `TextureUsage::CopySrc` usage, and all its subresources need to be in `state` when the copy is executed, they will be returned to `state` afterwards.
+    /// 
+    /// The returned data contains the tightly packed rows of the subresource.
+    pub fn read_back(&self, subresource: TextureSubresourceIndex, state: ResourceState) -> Result<Vec<u8>> {
+        #[cfg(feature = "validation")]
+        {
+            check_invalid_parameter!(self.usage.contains(TextureUsage::CopySrc), "Texture needs the `TextureUsage::CopySrc` usage to be read back");
+            check_invalid_parameter!(state.layout.is_some(), "Texture state needs to contain a layout to read back a texture");
+            check_invalid_parameter!(!self.samples.is_multisampled(), "Multisampled textures cannot be read back, resolve the texture first");
+        }
+
+        let device = WeakHandle::upgrade(&self.device).ok_or(Error::UseAfterDeviceDropped)?;
+
+        let (mip, _) = subresource_mip_and_layer(subresource);
+        let (width, height, depth, _) = self.size.as_tuple();
+        let width = (width >> mip).max(1);
+        let height = (height >> mip).max(1);
+        let depth = (depth >> mip).max(1);
+
+        let (block_width, block_height) = self.format.min_mip_size();
+        let (block_width, block_height) = (block_width as u64, block_height as u64);
+        let unit_size = self.format.unit_byte_size() as u64;
+
+        let row_length = (width as u64).div_ceil(block_width);
+        let row_count = (height as u64).div_ceil(block_height) * depth as u64;
+        let row_size = row_length * unit_size;
+        let row_pitch = row_size.next_multiple_of(constants::OPTIMAL_COPY_ROW_PITCH_ALIGNMENT.alignment());
+        let buffer_size = row_pitch * row_count;
+
+        let buffer = device.create_buffer(&BufferDesc {
+            size: buffer_size,
+            usage: BufferUsage::CopyDst,
+            alloc_desc: GpuAllocationDesc {
+                memory_type: MemoryType::Readback,
+                flags: MemoryAllocationFlags::None,
+            },
+        })?;
+
+        let command_pool = device.create_graphics_command_pool(CommandPoolFlags::Transient)?;
+        let command_list = command_pool.allocate()?;
+        command_list.begin(CommandListBeginFlags::OneTimeSubmit)?;
+
+        command_list.barrier(&[Barrier::new_basic_texture(state, ResourceState::COPY_READ_TEX, self.clone())]);
+        command_list.as_ref().copy_texture_to_buffer(self, &buffer, &[BufferTextureRegion {
+            buffer_offset: 0,
+            buffer_row_length_and_height: Some((
+                NonZeroU64::new(row_pitch / unit_size * block_width).unwrap(),
+                NonZeroU64::new((height as u64).div_ceil(block_height) * block_height).unwrap()
+            )),
+            texture_view: TextureCopyView {
+                subresource,
+                offset: TextureOffset::new_3d(0, 0, 0),
+                extent: TextureExtent::new_3d(NonZeroU16::new(width).unwrap(), NonZeroU16::new(height).unwrap(), NonZeroU16::new(depth).unwrap()),
+            },
+        }]);
+        command_list.barrier(&[Barrier::new_basic_texture(ResourceState::COPY_READ_TEX, state, self.clone())]);
+        command_list.close()?;
+
+        let fence = device.create_fence()?;
+        let fence_value = fence.get_value()? + 1;
+        let queue = device.get_queue(QueueType::Graphics, QueuePriority::Normal);
+        queue.submit(&CommandListSubmitInfo {
+            command_lists: &[command_list],
+            wait_fences: None,
+            signal_fences: Some(&[FenceSignalSubmitInfo { fence: fence.clone(), value: fence_value, sync_point: SyncPoint::All }]),
+        })?;
+        if !fence.wait(fence_value, Duration::MAX)? {
+            return Err(Error::Timeout);
+        }
+
+        let mapped = buffer.map(0, buffer_size)?;
+        let mut padded = vec![0; buffer_size as usize];
+        mapped.read(&mut padded);
+        buffer.unmap(mapped);
+
+        if row_pitch == row_size {
+            return Ok(padded);
+        }
+
+        let mut data = Vec::with_capacity((row_size * row_count) as usize);
+        for row in padded.chunks_exact(row_pitch as usize) {
+            data.extend_from_slice(&row[..row_size as usize]);
+        }
+        Ok(data)
+    }
     
     /// Create a sampled view to this texture
     pub fn get_or_create_sampled_view(&self, desc: &SampledTextureViewDesc) -> Result<SampledTextureViewHandle> {
@@ -565,9 +765,24 @@ impl TextureHandle {
     }
 }
 
+impl Drop for Texture {
+    fn drop(&mut self) {
+        unsafe {
+            ManuallyDrop::drop(&mut self.handle);
+
+            // Free memory after dropping resource, as we can't free it before we destroy the texture using it
+            if let Some(allocation) = self.allocation.take() {
+                let device = WeakHandle::upgrade(&self.device).unwrap();
+                device.gpu_allocator().free(allocation);
+            }
+        }
+    }
+}
+
 impl fmt::Debug for Texture {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Texture")
+            .field("debug_name", &*self.debug_name.read())
             .field("handle", &self.handle)
             .field("flags", &self.flags)
             .field("size", &self.size)
@@ -657,6 +872,9 @@ impl RenderTargetViewDesc {
                 return Err(Error::UnsupportedViewFormat { texture: texture.format, view: self.format });
             }
 
+            let is_multisampled_view = matches!(self.view_type, RenderTargetViewType::View2DMS | RenderTargetViewType::View2DMSArray { .. });
+            validate_view_sample_count(is_multisampled_view, texture)?;
+
             match self.view_type {
                 RenderTargetViewType::View1D { mip_slice } => {
                     if mip_slice >= texture.mip_levels() {
@@ -722,7 +940,22 @@ impl RenderTargetViewDesc {
     }
 }
 
+/// Check that multisampled views are only created for multisampled textures, and the other way around.
+#[cfg(feature = "validation")]
+fn validate_view_sample_count(is_multisampled_view: bool, texture: &TextureHandle) -> Result<()> {
+    let is_multisampled_texture = texture.samples().is_multisampled();
+    if is_multisampled_view && !is_multisampled_texture {
+        return Err(Error::InvalidParameter("A multisampled view can only be created for a multisampled texture".to_string()));
+    }
+    if !is_multisampled_view && is_multisampled_texture {
+        return Err(Error::InvalidParameter(format!("A texture with {} samples can only be viewed using a multisampled view", texture.samples().get())));
+    }
+    Ok(())
+}
+
 pub trait RenderTargetViewInterface {
+    /// Set the debug name of the render target view
+    unsafe fn set_debug_name(&self, name: &str);
 }
 
 pub type RenderTargetViewInterfaceHandle = InterfaceHandle<dyn RenderTargetViewInterface>;
@@ -966,6 +1199,9 @@ impl SampledTextureViewDesc {
                 return Err(Error::UnsupportedViewFormat { texture: texture.format, view: self.format });
             }
 
+            let is_multisampled_view = matches!(self.view_type, SampledTextureViewType::View2DMS | SampledTextureViewType::View2DMSArray { .. });
+            validate_view_sample_count(is_multisampled_view, texture)?;
+
             let mip_info = match self.view_type {
                 SampledTextureViewType::View1D { min_lod, mip_levels } => if let Some(mip_levels) = mip_levels {
                         Some((min_lod, mip_levels.get()))
@@ -1066,6 +1302,8 @@ impl SampledTextureViewDesc {
 }
 
 pub trait SampledTextureViewInterface {
+    /// Set the debug name of the sampled texture view
+    unsafe fn set_debug_name(&self, name: &str);
 }
 
 pub type SampledTextureViewInterfaceHandle = InterfaceHandle<dyn SampledTextureViewInterface>;
@@ -1181,6 +1419,8 @@ impl StorageTextureViewDesc {
 }
 
 pub trait StorageTextureViewInterface {
+    /// Set the debug name of the storage texture view
+    unsafe fn set_debug_name(&self, name: &str);
 }
 
 pub type StorageTextureViewInterfaceHandle = InterfaceHandle<dyn StorageTextureViewInterface>;
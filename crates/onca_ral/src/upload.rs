@@ -0,0 +1,514 @@
+use core::{
+    marker::PhantomData,
+    num::{NonZeroU8, NonZeroU16, NonZeroU64},
+};
+use std::collections::VecDeque;
+
+use onca_common::{prelude::*, time::Duration};
+
+use crate::*;
+
+/// Allocation in an upload context's ring buffer
+/// 
+/// The memory is only valid until the frame it was allocated in has finished executing on the GPU.
+/// The allocation borrows the upload context, as the mapped memory is unmapped when the context is dropped.
+pub struct UploadAllocation<'a> {
+    /// Upload buffer containing the allocation
+    pub buffer: BufferHandle,
+    /// Offset of the allocation in the upload buffer
+    pub offset: u64,
+    /// Size of the allocation
+    pub size:   u64,
+    ptr:        *mut u8,
+    _phantom:   PhantomData<&'a mut [u8]>,
+}
+
+impl UploadAllocation<'_> {
+    /// Get the CPU mapped memory of the allocation
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut(self.ptr, self.size as usize) }
+    }
+
+    /// Write data to the start of the allocation
+    /// 
+    /// Returns the number of bytes written
+    pub fn write(&mut self, data: &[u8]) -> u64 {
+        let len = data.len().min(self.size as usize);
+        unsafe { core::ptr::copy_nonoverlapping(data.as_ptr(), self.ptr, len) };
+        len as u64
+    }
+}
+
+struct UploadFrame {
+    fence_value: u64,
+    end:         u64,
+}
+
+/// Upload context
+/// 
+/// The upload context suballocates dynamic data from a persistently mapped ring buffer in upload memory.
+/// 
+/// At the end of each frame, `end_frame` needs to be called and the returned fence signal needs to be passed when submitting the command lists using the uploaded data,
+/// this allows the context to reuse memory once the GPU has finished with it.
+/// When the ring buffer is full, allocating will wait for the oldest in-flight frame to finish.
+pub struct UploadContext {
+    buffer:      BufferHandle,
+    mapped:      Option<MappedMemory>,
+    size:        u64,
+    /// Total number of bytes allocated, the actual offset in the buffer is `head % size`
+    head:        u64,
+    /// Total number of bytes released
+    tail:        u64,
+    fence:       FenceHandle,
+    fence_value: u64,
+    frames:      VecDeque<UploadFrame>,
+}
+
+impl UploadContext {
+    /// Create a new upload context with a ring buffer of `size` bytes
+    pub fn new(device: &DeviceHandle, size: u64) -> Result<Self> {
+        let buffer = device.create_buffer(&BufferDesc {
+            size,
+            usage: BufferUsage::CopySrc | BufferUsage::ConstantBuffer | BufferUsage::VertexBuffer | BufferUsage::IndexBuffer,
+            alloc_desc: GpuAllocationDesc {
+                memory_type: MemoryType::Upload,
+                flags: MemoryAllocationFlags::None,
+            },
+        })?;
+        let mapped = buffer.map(0, size)?;
+        let fence = device.create_fence()?;
+        let fence_value = fence.get_value()?;
+
+        Ok(Self {
+            buffer,
+            mapped: Some(mapped),
+            size,
+            head: 0,
+            tail: 0,
+            fence,
+            fence_value,
+            frames: VecDeque::new(),
+        })
+    }
+
+    /// Get the upload buffer
+    pub fn buffer(&self) -> &BufferHandle {
+        &self.buffer
+    }
+
+    /// Get the size of the ring buffer
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Get the number of bytes currently in use, including the memory still in use by in-flight frames
+    pub fn used(&self) -> u64 {
+        self.head - self.tail
+    }
+
+    /// Allocate `size` bytes from the ring buffer, with the given alignment
+    /// 
+    /// If there is not enough space available, this will wait until enough in-flight frames have finished
+    /// 
+    /// ## Error
+    /// 
+    /// Allocating will fail if the allocation can never fit in the ring buffer, i.e. it is bigger than the ring buffer, or there is not enough space left for the current frame
+    pub fn allocate(&mut self, size: u64, align: MemAlign) -> Result<UploadAllocation<'_>> {
+        if size == 0 || size > self.size {
+            return Err(Error::InvalidParameter(format!("Upload allocation size needs to be between 1 and the upload buffer size ({}), size: {size}", self.size)));
+        }
+
+        self.retire_finished_frames()?;
+
+        let align = align.alignment();
+        let offset = loop {
+            let mut offset = self.head.next_multiple_of(align);
+            // Allocations can't wrap around the end of the buffer, so skip to the start of the buffer
+            if offset % self.size + size > self.size {
+                offset = offset.next_multiple_of(self.size);
+            }
+
+            if offset + size - self.tail <= self.size {
+                break offset;
+            }
+
+            match self.frames.front() {
+                Some(frame) => {
+                    let fence_value = frame.fence_value;
+                    if !self.fence.wait(fence_value, Duration::MAX)? {
+                        return Err(Error::Timeout);
+                    }
+                    self.retire_finished_frames()?;
+                },
+                None => return Err(Error::InvalidParameter(format!("Not enough space left in the upload buffer for the current frame, requested size: {size}, available: {}", self.size - self.used()))),
+            }
+        };
+
+        self.head = offset + size;
+
+        let buffer_offset = offset % self.size;
+        let ptr = unsafe { self.mapped.as_ref().unwrap().ptr().add(buffer_offset as usize) as *mut u8 };
+        Ok(UploadAllocation {
+            buffer: self.buffer.clone(),
+            offset: buffer_offset,
+            size,
+            ptr,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Upload data to a buffer, the copy will be recorded into `command_list`
+    pub fn upload_buffer<T: AsRef<Handle<CommandList>>>(&mut self, command_list: &T, dst: &BufferHandle, dst_offset: u64, data: &[u8]) -> Result<()> {
+        let mut allocation = self.allocate(data.len() as u64, MemAlign::new(4))?;
+        allocation.write(data);
+
+        command_list.as_ref().copy_buffer_region(&allocation.buffer, dst, BufferCopyRegion {
+            src_offset: allocation.offset,
+            dst_offset,
+            size: allocation.size,
+        });
+        Ok(())
+    }
+
+    /// Upload data to a texture, the copy will be recorded into `command_list`
+    /// 
+    /// `data` is expected to contain tightly packed rows for the region described by `texture_view`, and the texture needs to be in the `CopyDst` layout
+    pub fn upload_texture<T: AsRef<Handle<CommandList>>>(&mut self, command_list: &T, dst: &TextureHandle, texture_view: TextureCopyView, data: &[u8]) -> Result<()> {
+        let format = dst.format();
+        let (block_width, block_height) = format.min_mip_size();
+        let (block_width, block_height) = (block_width as u64, block_height as u64);
+        let unit_size = format.unit_byte_size() as u64;
+
+        let row_length = (texture_view.extent.width.get() as u64).div_ceil(block_width);
+        let row_count = (texture_view.extent.height.get() as u64).div_ceil(block_height);
+        let depth = texture_view.extent.depth.get() as u64;
+
+        let row_size = row_length * unit_size;
+        let row_pitch = row_size.next_multiple_of(constants::OPTIMAL_COPY_ROW_PITCH_ALIGNMENT.alignment());
+
+        let data_size = row_size * row_count * depth;
+        if (data.len() as u64) < data_size {
+            return Err(Error::InvalidParameter(format!("Not enough data to upload to the texture, expected {data_size} bytes, found {} bytes", data.len())));
+        }
+
+        let mut allocation = self.allocate(row_pitch * row_count * depth, constants::OPTIMAL_COPY_OFFSET_ALIGNMENT)?;
+        let mapped = allocation.as_mut_slice();
+        for row in 0..(row_count * depth) {
+            let src = &data[(row * row_size) as usize..][..row_size as usize];
+            mapped[(row * row_pitch) as usize..][..row_size as usize].copy_from_slice(src);
+        }
+
+        let region = BufferTextureRegion {
+            buffer_offset: allocation.offset,
+            buffer_row_length_and_height: Some((
+                NonZeroU64::new(row_pitch / unit_size * block_width).unwrap(),
+                NonZeroU64::new(row_count * block_height).unwrap()
+            )),
+            texture_view,
+        };
+        command_list.as_ref().copy_buffer_to_texture(&allocation.buffer, dst, &[region]);
+        Ok(())
+    }
+
+    /// End the current frame
+    /// 
+    /// The returned fence signal needs to be submitted after the last command list using memory allocated in this frame
+    pub fn end_frame(&mut self) -> FenceSignalSubmitInfo {
+        self.fence_value += 1;
+        self.frames.push_back(UploadFrame { fence_value: self.fence_value, end: self.head });
+
+        FenceSignalSubmitInfo {
+            fence: self.fence.clone(),
+            value: self.fence_value,
+            sync_point: SyncPoint::All,
+        }
+    }
+
+    fn retire_finished_frames(&mut self) -> Result<()> {
+        if self.frames.is_empty() {
+            return Ok(());
+        }
+
+        let completed = self.fence.get_value()?;
+        while let Some(frame) = self.frames.front() {
+            if frame.fence_value > completed {
+                break;
+            }
+            self.tail = frame.end;
+            self.frames.pop_front();
+        }
+        Ok(())
+    }
+}
+
+impl Drop for UploadContext {
+    fn drop(&mut self) {
+        if let Some(mapped) = self.mapped.take() {
+            self.buffer.unmap(mapped);
+        }
+    }
+}
+
+//==============================================================================================================================
+
+/// Initial data of a texture
+pub struct TextureInitData<'a> {
+    /// Tightly packed data of all provided subresources, ordered by array layer, and then by mip level (i.e. the layout used by DDS files)
+    pub data:       &'a [u8],
+    /// Number of mip levels contained in `data`, starting at mip 0
+    /// 
+    /// When the texture has more mips, the remaining mips will be generated if a `MipGenerator` is provided, otherwise their content is undefined
+    pub mip_levels: NonZeroU8,
+}
+
+/// Copy of a single subresource from the initial data to the upload buffer
+pub(crate) struct TextureInitDataCopy {
+    /// Offset in the tightly packed data
+    pub data_offset: u64,
+    /// Size of a single row of texels or blocks
+    pub row_size:    u64,
+    /// Pitch of a row in the upload buffer
+    pub row_pitch:   u64,
+    /// Number of rows, including the rows of all depth slices
+    pub num_rows:    u64,
+    /// Copy region in the upload buffer
+    pub region:      BufferTextureRegion,
+}
+
+impl TextureInitData<'_> {
+    /// Get the copies needed to upload all subresources contained in the data, and the size of the upload buffer they are copied from
+    /// 
+    /// Rows in the upload buffer are aligned to `constants::OPTIMAL_COPY_ROW_PITCH_ALIGNMENT`, and each subresource is aligned to `constants::OPTIMAL_COPY_OFFSET_ALIGNMENT`
+    pub(crate) fn get_subresource_copies(&self, desc: &TextureDesc) -> Result<(Vec<TextureInitDataCopy>, u64)> {
+        let (width, height, depth, layers) = desc.size.as_tuple();
+        let (block_width, block_height) = desc.format.min_mip_size();
+        let (block_width, block_height) = (block_width as u64, block_height as u64);
+        let unit_size = desc.format.unit_byte_size() as u64;
+
+        let mut copies = Vec::with_capacity(layers as usize * self.mip_levels.get() as usize);
+        let mut data_offset = 0;
+        let mut buffer_offset = 0;
+        for layer in 0..layers {
+            for mip in 0..self.mip_levels.get() {
+                let mip_width = (width >> mip).max(1);
+                let mip_height = (height >> mip).max(1);
+                let mip_depth = (depth >> mip).max(1);
+
+                let row_length = (mip_width as u64).div_ceil(block_width);
+                let row_count = (mip_height as u64).div_ceil(block_height);
+                let row_size = row_length * unit_size;
+                let row_pitch = row_size.next_multiple_of(constants::OPTIMAL_COPY_ROW_PITCH_ALIGNMENT.alignment());
+                let num_rows = row_count * mip_depth as u64;
+
+                let subresource = match desc.size {
+                    TextureSize::Size3D { .. } => TextureSubresourceIndex::Texture { aspect: TextureAspect::Color, mip_level: mip },
+                    _                          => TextureSubresourceIndex::Array { aspect: TextureAspect::Color, mip_level: mip, layer },
+                };
+
+                buffer_offset = buffer_offset.next_multiple_of(constants::OPTIMAL_COPY_OFFSET_ALIGNMENT.alignment());
+                // The extent is the size of the mip, which is not a multiple of the block size for the smallest mips of a block-compressed texture.
+                // Vulkan requires the extent to stay within the mip, while DX12 requires whole blocks, so the DX12 backend rounds it up to the block size
+                copies.push(TextureInitDataCopy {
+                    data_offset,
+                    row_size,
+                    row_pitch,
+                    num_rows,
+                    region: BufferTextureRegion {
+                        buffer_offset,
+                        buffer_row_length_and_height: Some((
+                            NonZeroU64::new(row_pitch / unit_size * block_width).unwrap(),
+                            NonZeroU64::new(row_count * block_height).unwrap()
+                        )),
+                        texture_view: TextureCopyView {
+                            subresource,
+                            offset: TextureOffset::new_3d(0, 0, 0),
+                            extent: TextureExtent::new_3d(NonZeroU16::new(mip_width).unwrap(), NonZeroU16::new(mip_height).unwrap(), NonZeroU16::new(mip_depth).unwrap()),
+                        },
+                    },
+                });
+
+                data_offset += row_size * num_rows;
+                buffer_offset += row_pitch * num_rows;
+            }
+        }
+
+        if (self.data.len() as u64) < data_offset {
+            return Err(Error::InvalidParameter(format!("Not enough initial data for the texture, expected {data_offset} bytes, found {} bytes", self.data.len())));
+        }
+        Ok((copies, buffer_offset))
+    }
+}
+
+//==============================================================================================================================
+
+/// Resources used by a recorded mip generation pass
+/// 
+/// These need to be kept alive until the command list the pass was recorded in has finished executing
+pub struct MipGenerationResources {
+    _heap:          DescriptorHeapHandle,
+    _sampled_views: Vec<SampledTextureViewHandle>,
+    _storage_views: Vec<StorageTextureViewHandle>,
+}
+
+/// GPU mip chain generator
+/// 
+/// Generates each mip by downsampling the previous mip in a compute pass, using a compute shader provided by the user, as the RAL does not compile shaders itself.
+/// 
+/// The compute shader is expected to:
+/// - use a thread group size of `THREAD_GROUP_SIZE` x `THREAD_GROUP_SIZE` x 1, with each thread writing a single texel, and `z` being the array layer
+/// - use a single descriptor table, containing a sampled 2D array texture (the previous mip), followed by a storage 2D array texture (the mip to generate)
+/// - sample the source texture using the linear clamp static sampler at lod 0, as the sampled view's min lod is clamped to the previous mip
+/// 
+/// Mips can only be generated for 2D (array) textures with a format that supports storage textures, so sRGB textures need to be generated using a UNorm texture
+pub struct MipGenerator {
+    pipeline_layout: PipelineLayoutHandle,
+    pipeline:        PipelineHandle,
+}
+
+impl MipGenerator {
+    /// Size of a thread group in both the x and y dimension
+    pub const THREAD_GROUP_SIZE: u32 = 8;
+    /// State the texture needs to be in when recording a mip generation pass, the texture will be in the same state after the pass
+    pub const TEXTURE_STATE: ResourceState = ResourceState::new_tex(Access::SampledRead, SyncPoint::Compute, TextureLayout::ShaderRead);
+    /// State of the mip being generated
+    const GENERATE_STATE: ResourceState = ResourceState::new_tex(Access::StorageWrite, SyncPoint::Compute, TextureLayout::ShaderWrite);
+
+    /// Create a mip generator using the given compute shader
+    pub fn new(device: &DeviceHandle, shader: &ShaderHandle) -> Result<Self> {
+        let table_layout = device.create_descriptor_table_layout(&DescriptorTableDesc::Resource {
+            ranges: vec![
+                DescriptorRange {
+                    range_type: DescriptorType::SampledTexture,
+                    count: DescriptorCount::new_bounded(1).unwrap(),
+                    descriptor_access: DescriptorAccess::Static,
+                    data_access: DescriptorDataAccess::Default,
+                },
+                DescriptorRange {
+                    range_type: DescriptorType::StorageTexture,
+                    count: DescriptorCount::new_bounded(1).unwrap(),
+                    descriptor_access: DescriptorAccess::Static,
+                    data_access: DescriptorDataAccess::Default,
+                },
+            ],
+            visibility: ShaderVisibility::All,
+        })?;
+
+        let sampler = device.create_static_sampler(&StaticSamplerDesc {
+            min_filter: Filter::Linear,
+            mag_filter: Filter::Linear,
+            mipmap_mode: MipmapMode::Point,
+            reduction: FilterReductionMode::WeightedAverage,
+            address_mode_u: SamplerAddressMode::Clamp,
+            address_mode_v: SamplerAddressMode::Clamp,
+            address_mode_w: SamplerAddressMode::Clamp,
+            anisotropy: None,
+            comparison: None,
+            mip_lod_bias: 0.0,
+            min_lod: None,
+            max_lod: None,
+            border_color: StaticBorderColor::FloatTransparentBlack,
+            visibility: ShaderVisibility::All,
+        })?;
+
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDesc {
+            flags: PipelineLayoutFlags::None,
+            descriptor_tables: Some(vec![table_layout]),
+            inline_descriptors: None,
+            constant_ranges: None,
+            static_samplers: Some(vec![sampler]),
+        })?;
+
+        let pipeline = device.create_compute_pipeline(&ComputePipelineDesc {
+            compute_shader: shader.clone(),
+            pipeline_layout: pipeline_layout.clone(),
+        })?;
+
+        Ok(Self { pipeline_layout, pipeline })
+    }
+
+    /// Record a pass generating all mips of the texture, starting from `first_mip`, which is generated from `first_mip - 1`
+    /// 
+    /// The texture needs to be in the `MipGenerator::TEXTURE_STATE` state, and the returned resources need to be kept alive until the command list has finished executing
+    pub fn record(&self, device: &DeviceHandle, command_list: &GraphicsCommandList, texture: &TextureHandle, first_mip: u8) -> Result<MipGenerationResources> {
+        #[cfg(feature = "validation")]
+        {
+            check_invalid_parameter!(first_mip > 0, "Mip 0 cannot be generated");
+            check_invalid_parameter!(first_mip < texture.mip_levels(), "First mip to generate ({first_mip}) is out of range, texture has {} mips", texture.mip_levels());
+            check_invalid_parameter!(matches!(texture.size(), TextureSize::Size2D { .. }), "Mips can only be generated for 2D textures");
+            check_invalid_parameter!(texture.usages().contains(TextureUsage::Sampled | TextureUsage::Storage), "Texture needs the `TextureUsage::Sampled` and `TextureUsage::Storage` usages to generate mips");
+            check_invalid_parameter!(texture.format().get_support().contains(FormatSupport::Storage), "Mips can only be generated for formats that support storage textures, found {}", texture.format());
+        }
+
+        let mip_levels = texture.mip_levels();
+        let (width, height, _, layers) = texture.size().as_tuple();
+        let format = texture.format();
+
+        let heap = device.create_descriptor_heap(&DescriptorHeapDesc {
+            heap_type: DescriptorHeapType::Resources,
+            max_descriptors: (mip_levels - first_mip) as u32 * constants::MIN_DESCRIPTOR_TABLE_OFFSET_ALIGNMENT,
+            shader_visible: true,
+        })?;
+
+        command_list.bind_descriptor_heaps(Some(&heap), None);
+        command_list.bind_compute_pipeline_layout(&self.pipeline_layout);
+        command_list.bind_compute_pipeline(&self.pipeline);
+
+        let mut sampled_views = Vec::with_capacity((mip_levels - first_mip) as usize);
+        let mut storage_views = Vec::with_capacity((mip_levels - first_mip) as usize);
+        for mip in first_mip..mip_levels {
+            // Only include the previous mips in the sampled view, so it doesn't overlap the mip being written
+            let sampled_view = texture.get_or_create_sampled_view(&SampledTextureViewDesc {
+                view_type: SampledTextureViewType::View2DArray {
+                    min_lod: (mip - 1) as f32,
+                    mip_levels: NonZeroU8::new(mip),
+                    first_slice: 0,
+                    array_size: layers,
+                    aspect: TextureAspect::Color,
+                },
+                format,
+                components: TextureComponentMapping::default(),
+            })?;
+            let storage_view = texture.get_or_create_storage_view(&StorageTextureViewDesc {
+                view_type: StorageTextureViewType::View2DArray { first_slice: 0, array_size: layers, aspect: TextureAspect::Color },
+                mip_slice: mip,
+                format,
+            })?;
+
+            let index = (mip - first_mip) as u32 * constants::MIN_DESCRIPTOR_TABLE_OFFSET_ALIGNMENT;
+            heap.write_sampled_texture(index, &sampled_view)?;
+            heap.write_storage_texture(index + 1, &storage_view)?;
+
+            let subresource_range = TextureSubresourceRange::Texture { aspect: TextureAspect::Color, base_mip: mip, mip_levels: NonZeroU8::new(1) };
+            command_list.barrier(&[Barrier::Texture {
+                before: Self::TEXTURE_STATE,
+                after: Self::GENERATE_STATE,
+                texture: texture.clone(),
+                subresource_range: Some(subresource_range),
+                queue_transfer_op: BarrierQueueTransferOp::None,
+            }]);
+
+            command_list.set_compute_descriptor_table(0, heap.get_gpu_descriptor(index)?);
+
+            let mip_width = (width as u32 >> mip).max(1);
+            let mip_height = (height as u32 >> mip).max(1);
+            command_list.dispatch(mip_width.div_ceil(Self::THREAD_GROUP_SIZE), mip_height.div_ceil(Self::THREAD_GROUP_SIZE), layers as u32);
+
+            command_list.barrier(&[Barrier::Texture {
+                before: Self::GENERATE_STATE,
+                after: Self::TEXTURE_STATE,
+                texture: texture.clone(),
+                subresource_range: Some(subresource_range),
+                queue_transfer_op: BarrierQueueTransferOp::None,
+            }]);
+
+            sampled_views.push(sampled_view);
+            storage_views.push(storage_view);
+        }
+
+        Ok(MipGenerationResources {
+            _heap: heap,
+            _sampled_views: sampled_views,
+            _storage_views: storage_views,
+        })
+    }
+}
@@ -0,0 +1,191 @@
+//! RAL validation layer
+//!
+//! The validation layer tracks the state of the resources used while recording a command list and checks each use against the recorded barriers.
+//! It also keeps track of all resources referenced by a command list, so resources that were dropped before the command list was submitted can be detected.
+//!
+//! This is independent of the API's debug layers, and all issues are reported to the log, using the debug names of the command list and resources involved.
+//! The layer is enabled using the `[debug] ral-validation` setting, and requires the `validation` feature.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::collections::HashMap;
+
+use onca_common::prelude::*;
+
+use crate::{
+    Handle, HandleImpl, WeakHandle, Buffer, BufferHandle, Texture, TextureHandle, DescriptorHeap, DescriptorHeapHandle,
+    Access, Barrier, BarrierQueueTransferOp, ResourceState, TextureLayout,
+};
+
+static VALIDATION_LAYER_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Enable or disable the validation layer, this only affects command lists that are allocated or reset afterwards
+pub(crate) fn set_validation_layer_enabled(enabled: bool) {
+    VALIDATION_LAYER_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Check if the validation layer is enabled
+pub fn is_validation_layer_enabled() -> bool {
+    VALIDATION_LAYER_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Result of a tracked operation, contains the message to report on failure
+pub(crate) type TrackResult = core::result::Result<(), String>;
+
+/// Get the name to display for a debug name
+pub(crate) fn display_name(name: &str) -> &str {
+    if name.is_empty() { "<unnamed>" } else { name }
+}
+
+/// Get a key identifying a resource, this is unique as long as a (weak) handle to the resource exists
+fn resource_key<T: HandleImpl>(handle: &Handle<T>) -> usize {
+    &**handle as *const T as usize
+}
+
+struct TrackedResource<T: HandleImpl> {
+    handle: WeakHandle<T>,
+    /// Debug name when the resource was first used, so it can still be reported after the resource was dropped
+    name:   String,
+    /// Last known state, `None` if the state is unknown
+    state:  Option<ResourceState>,
+}
+
+impl<T: HandleImpl> TrackedResource<T> {
+    fn track<'a>(resources: &'a mut HashMap<usize, TrackedResource<T>>, handle: &Handle<T>, get_name: impl FnOnce(&T) -> String) -> &'a mut Self {
+        resources.entry(resource_key(handle)).or_insert_with(|| TrackedResource {
+            handle: Handle::downgrade(handle),
+            name: get_name(handle),
+            state: None,
+        })
+    }
+
+    fn check_before_state(&self, kind: &str, before: &ResourceState) -> TrackResult {
+        let state = match &self.state {
+            Some(state) => state,
+            None => return Ok(()),
+        };
+
+        if state.layout.is_some() && before.layout.is_some() && state.layout != before.layout {
+            return Err(format!("{kind} '{}' is expected to be in the {} layout by the barrier, but it was last transitioned to the {} layout",
+                display_name(&self.name), before.layout.unwrap(), state.layout.unwrap()));
+        }
+        // Any access not included in the before state will not be made available, `MemoryRead` and `MemoryWrite` cover all accesses
+        if !before.access.contains(state.access) && !before.access.contains(Access::MemoryRead | Access::MemoryWrite) {
+            return Err(format!("{kind} '{}' is expected to be used with {} access by the barrier, but it was last transitioned to {} access",
+                display_name(&self.name), before.access, state.access));
+        }
+        Ok(())
+    }
+
+    fn check_access(&self, kind: &str, allowed_access: Access) -> TrackResult {
+        match &self.state {
+            Some(state) if !state.access.intersects(allowed_access) => Err(format!("{kind} '{}' is used with {allowed_access} access, but it was last transitioned to {} access",
+                display_name(&self.name), state.access)),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_alive(&self, kind: &str) -> TrackResult {
+        if WeakHandle::strong_count(&self.handle) == 0 {
+            Err(format!("{kind} '{}' was dropped before the command list using it was submitted", display_name(&self.name)))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Get the state of a resource after a barrier, partial transitions and queue releases make the state unknown, as states are tracked for the whole resource
+fn get_after_state(after: ResourceState, is_full_resource: bool, queue_transfer_op: BarrierQueueTransferOp) -> Option<ResourceState> {
+    match queue_transfer_op {
+        BarrierQueueTransferOp::To(_) => None,
+        _ if !is_full_resource        => None,
+        _                             => Some(after),
+    }
+}
+
+/// Resource tracker of a single command list
+///
+/// The state of a resource is only known after it was transitioned in the command list, as its state at the start depends on the work executed before it.
+pub(crate) struct ResourceTracker {
+    enabled:  bool,
+    buffers:  HashMap<usize, TrackedResource<Buffer>>,
+    textures: HashMap<usize, TrackedResource<Texture>>,
+    heaps:    HashMap<usize, TrackedResource<DescriptorHeap>>,
+}
+
+impl ResourceTracker {
+    pub fn new() -> Self {
+        Self {
+            enabled: is_validation_layer_enabled(),
+            buffers: HashMap::new(),
+            textures: HashMap::new(),
+            heaps: HashMap::new(),
+        }
+    }
+
+    /// Track a barrier, and check its before state against the last known state of the resource
+    pub fn barrier(&mut self, barrier: &Barrier) -> TrackResult {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        match barrier {
+            Barrier::Global { .. } => Ok(()),
+            Barrier::Buffer { before, after, buffer, offset, size, queue_transfer_op } => {
+                let tracked = TrackedResource::track(&mut self.buffers, buffer, |buffer| buffer.debug_name());
+                let res = tracked.check_before_state("Buffer", before);
+                tracked.state = get_after_state(*after, *offset == 0 && *size >= buffer.size(), *queue_transfer_op);
+                res
+            },
+            Barrier::Texture { before, after, texture, subresource_range, queue_transfer_op } => {
+                let tracked = TrackedResource::track(&mut self.textures, texture, |texture| texture.debug_name());
+                let res = tracked.check_before_state("Texture", before);
+                tracked.state = get_after_state(*after, subresource_range.is_none(), *queue_transfer_op);
+                res
+            },
+        }
+    }
+
+    /// Track the use of a buffer, and check that its last known state allows any of the given accesses
+    pub fn use_buffer(&mut self, buffer: &BufferHandle, allowed_access: Access) -> TrackResult {
+        if !self.enabled {
+            return Ok(());
+        }
+        TrackedResource::track(&mut self.buffers, buffer, |buffer| buffer.debug_name()).check_access("Buffer", allowed_access)
+    }
+
+    /// Track the use of a texture, and check that its last known state allows any of the given accesses, and is in one of the given layouts
+    pub fn use_texture(&mut self, texture: &TextureHandle, allowed_access: Access, allowed_layouts: &[TextureLayout]) -> TrackResult {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let tracked = TrackedResource::track(&mut self.textures, texture, |texture| texture.debug_name());
+        tracked.check_access("Texture", allowed_access)?;
+        match tracked.state.and_then(|state| state.layout) {
+            Some(layout) if !allowed_layouts.contains(&layout) => Err(format!("Texture '{}' is used in the {} layout, which is not valid for this operation",
+                display_name(&tracked.name), layout)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Track the use of a descriptor heap
+    pub fn use_descriptor_heap(&mut self, heap: &DescriptorHeapHandle) {
+        if self.enabled {
+            TrackedResource::track(&mut self.heaps, heap, |heap| heap.debug_name());
+        }
+    }
+
+    /// Check that all resources used by the command list are still alive, this should be called when the command list is submitted
+    pub fn check_lifetimes(&self) -> TrackResult {
+        for buffer in self.buffers.values() {
+            buffer.check_alive("Buffer")?;
+        }
+        for texture in self.textures.values() {
+            texture.check_alive("Texture")?;
+        }
+        for heap in self.heaps.values() {
+            heap.check_alive("Descriptor heap")?;
+        }
+        Ok(())
+    }
+}
@@ -13,7 +13,7 @@ pub struct Buffer {
 impl Buffer {
     pub unsafe fn new(device: &Device, desc: &ral::BufferDesc, alloc: &ral::GpuAllocator) -> ral::Result<(ral::BufferInterfaceHandle, ral::GpuAllocation, ral::GpuAddress)> {
         let mut buffer_flags = D3D12_RESOURCE_FLAGS(0);
-        if desc.usage.intersects(ral::BufferUsage::StorageTexelBuffer | ral::BufferUsage::StorageBuffer) {
+        if desc.usage.intersects(ral::BufferUsage::StorageTexelBuffer | ral::BufferUsage::StorageBuffer | ral::BufferUsage::AccelerationStructure) {
             buffer_flags  |= D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS;
         }
 
@@ -79,9 +79,14 @@ impl Buffer {
             None => (tex_extend.width.get() as u32, tex_extend.height.get() as u32),
         };
 
-        let unit_width = format.min_mip_size().0 as u32;
+        let (unit_width, unit_height) = format.min_mip_size();
+        let (unit_width, unit_height) = (unit_width as u32, unit_height as u32);
         let row_length = (row_length + unit_width - 1) / unit_width;
-        
+
+        // The footprint of a block-compressed format needs to cover whole blocks, while the extent of the copy may end at the edge of a mip that is not a multiple of the block size
+        let width = (tex_extend.width.get() as u32).next_multiple_of(unit_width);
+        let height = height.next_multiple_of(unit_height);
+
         D3D12_TEXTURE_COPY_LOCATION {
             pResource: ManuallyDrop::new(Some(core::ptr::read(&resource))),
             Type: D3D12_TEXTURE_COPY_TYPE_PLACED_FOOTPRINT,
@@ -90,7 +95,7 @@ impl Buffer {
                     Offset: offset,
                     Footprint: D3D12_SUBRESOURCE_FOOTPRINT {
                         Format: format.to_dx(),
-                        Width: tex_extend.width.get() as u32,
+                        Width: width,
                         Height: height,
                         Depth: tex_extend.depth.get() as u32,
                         RowPitch: row_length * format.unit_byte_size() as u32,
@@ -121,4 +126,8 @@ impl ral::BufferInterface for Buffer {
         };
         self.resource.Unmap(0, Some(&range));
     }
+
+    unsafe fn set_debug_name(&self, name: &str) {
+        set_object_name(&self.resource, name);
+    }
 }
\ No newline at end of file
@@ -1,14 +1,21 @@
-use core::mem::ManuallyDrop;
+use core::{ffi::c_void, mem::ManuallyDrop};
 use std::ptr;
 
 use onca_common::prelude::*;
 use onca_ral as ral;
 use ral::{CommandListInterfaceHandle, CommandListType, HandleImpl};
 use windows::{Win32::Graphics::Direct3D12::*, core::ComInterface};
-use crate::{utils::*, device::Device, texture::{texture_layout_to_dx, Texture, RenderTargetView}, pipeline::{PipelineLayout, Pipeline}, buffer::Buffer, descriptors::DescriptorHeap};
+use crate::{utils::*, device::Device, texture::{texture_layout_to_dx, Texture, RenderTargetView}, pipeline::{PipelineLayout, Pipeline}, buffer::Buffer, descriptors::DescriptorHeap, query::QueryPool, raytracing::{AccelerationStructure, RaytracingPipeline, BuildInputs}};
+
+/// Event metadata for a null-terminated wide string, as understood by PIX
+const PIX_EVENT_UNICODE_VERSION: u32 = 0;
 
 pub struct CommandPool {
-    pub alloc:     ID3D12CommandAllocator,
+    pub alloc:                   ID3D12CommandAllocator,
+    pub dispatch_signature:      ID3D12CommandSignature,
+    pub dispatch_mesh_signature: ID3D12CommandSignature,
+    pub draw_signature:          ID3D12CommandSignature,
+    pub draw_indexed_signature:  ID3D12CommandSignature,
 }
 
 impl CommandPool {
@@ -23,6 +30,10 @@ impl CommandPool {
         let alloc = device.device.CreateCommandAllocator(dx_type).map_err(|err| err.to_ral_error())?;
         Ok(ral::CommandPoolInterfaceHandle::new(CommandPool {
             alloc,
+            dispatch_signature: device.dispatch_signature.clone(),
+            dispatch_mesh_signature: device.dispatch_mesh_signature.clone(),
+            draw_signature: device.draw_signature.clone(),
+            draw_indexed_signature: device.draw_indexed_signature.clone(),
         }))
     }
 }
@@ -49,18 +60,30 @@ impl ral::CommandPoolInterface for CommandPool {
             list,
             alloc: self.alloc.clone(),
             list_type,
+            dispatch_signature: self.dispatch_signature.clone(),
+            dispatch_mesh_signature: self.dispatch_mesh_signature.clone(),
+            draw_signature: self.draw_signature.clone(),
+            draw_indexed_signature: self.draw_indexed_signature.clone(),
         }))
     }
 
     unsafe fn free(&self, _list: &CommandListInterfaceHandle) {
         // Nothing to do, dropping the handle will this for us
-    }   
+    }
+
+    unsafe fn set_debug_name(&self, name: &str) {
+        set_object_name(&self.alloc, name);
+    }
 }
 
 pub struct CommandList {
-    pub list:      ID3D12GraphicsCommandList9,
-    pub alloc:     ID3D12CommandAllocator,
-    pub list_type: ral::CommandListType,
+    pub list:                    ID3D12GraphicsCommandList9,
+    pub alloc:                   ID3D12CommandAllocator,
+    pub list_type:               ral::CommandListType,
+    pub dispatch_signature:      ID3D12CommandSignature,
+    pub dispatch_mesh_signature: ID3D12CommandSignature,
+    pub draw_signature:          ID3D12CommandSignature,
+    pub draw_indexed_signature:  ID3D12CommandSignature,
 }
 
 impl ral::CommandListInterface for CommandList {
@@ -85,6 +108,23 @@ impl ral::CommandListInterface for CommandList {
 
     //==============================================================================================================================
 
+    unsafe fn begin_event(&self, name: &str, _color: [f32; 4]) {
+        // Without the PIX event runtime, only the unicode event format is available, which doesn't support colors
+        let name = to_wide(name);
+        self.list.BeginEvent(PIX_EVENT_UNICODE_VERSION, Some(name.as_ptr() as *const c_void), (name.len() * 2) as u32);
+    }
+
+    unsafe fn end_event(&self) {
+        self.list.EndEvent();
+    }
+
+    unsafe fn set_marker(&self, name: &str, _color: [f32; 4]) {
+        let name = to_wide(name);
+        self.list.SetMarker(PIX_EVENT_UNICODE_VERSION, Some(name.as_ptr() as *const c_void), (name.len() * 2) as u32);
+    }
+
+    //==============================================================================================================================
+
     unsafe fn barrier(&self, barriers: &[ral::Barrier], _cur_queue_index: ral::QueueIndex) {
         scoped_alloc!(AllocId::TlsTemp);
 
@@ -310,12 +350,14 @@ impl ral::CommandListInterface for CommandList {
 
             let tex_offset = region.texture_view.offset;
             let tex_extent = region.texture_view.extent;
+            // Copies of block-compressed formats need to cover whole blocks, even at the edge of a mip that is not a multiple of the block size
+            let (block_width, block_height) = src.format().min_mip_size();
             let src_box = D3D12_BOX {
                 left:   tex_offset.x as u32,
                 top:    tex_offset.y as u32,
                 front:  tex_offset.z as u32,
-                right:  tex_offset.x as u32 + tex_extent.width.get() as u32,
-                bottom: tex_offset.y as u32 + tex_extent.height.get() as u32,
+                right:  tex_offset.x as u32 + (tex_extent.width.get() as u32).next_multiple_of(block_width as u32),
+                bottom: tex_offset.y as u32 + (tex_extent.height.get() as u32).next_multiple_of(block_height as u32),
                 back:   tex_offset.z as u32 + tex_extent.depth.get() as u32,
             };
 
@@ -367,6 +409,79 @@ impl ral::CommandListInterface for CommandList {
         self.list.SetComputeRootDescriptorTable(index, gpu_descriptor);
     }
 
+    unsafe fn set_compute_constants(&self, index: u32, offset: u32, data: &[u32], layout: &ral::PipelineLayoutHandle) {
+        let root_index = get_constants_root_index(index, layout);
+        self.list.SetComputeRoot32BitConstants(root_index, data.len() as u32, data.as_ptr() as *const c_void, offset);
+    }
+
+    unsafe fn dispatch(&self, group_count_x: u32, group_count_y: u32, group_count_z: u32) {
+        self.list.Dispatch(group_count_x, group_count_y, group_count_z);
+    }
+
+    unsafe fn dispatch_indirect(&self, buffer: &ral::BufferHandle, offset: u64) {
+        let resource = &buffer.interface().as_concrete_type::<Buffer>().resource;
+        self.list.ExecuteIndirect(&self.dispatch_signature, 1, resource, offset, None, 0);
+    }
+
+    //==============================================================================================================================
+
+    unsafe fn build_acceleration_structure(&self, info: &ral::AccelerationStructureBuildInfo, src: Option<&ral::AccelerationStructureHandle>) {
+        let build_inputs = BuildInputs::new(&info.inputs, info.flags, src.is_some());
+        let desc = D3D12_BUILD_RAYTRACING_ACCELERATION_STRUCTURE_DESC {
+            DestAccelerationStructureData: info.dst.interface().as_concrete_type::<AccelerationStructure>().address,
+            Inputs: build_inputs.inputs,
+            SourceAccelerationStructureData: src.map_or(0, |src| src.interface().as_concrete_type::<AccelerationStructure>().address),
+            ScratchAccelerationStructureData: info.scratch.gpu_address().at(info.scratch_offset).as_raw(),
+        };
+        self.list.BuildRaytracingAccelerationStructure(&desc, None);
+    }
+
+    unsafe fn copy_acceleration_structure(&self, dst: &ral::AccelerationStructureHandle, src: &ral::AccelerationStructureHandle, mode: ral::AccelerationStructureCopyMode) {
+        let dx_mode = match mode {
+            ral::AccelerationStructureCopyMode::Clone   => D3D12_RAYTRACING_ACCELERATION_STRUCTURE_COPY_MODE_CLONE,
+            ral::AccelerationStructureCopyMode::Compact => D3D12_RAYTRACING_ACCELERATION_STRUCTURE_COPY_MODE_COMPACT,
+        };
+        let dst_address = dst.interface().as_concrete_type::<AccelerationStructure>().address;
+        let src_address = src.interface().as_concrete_type::<AccelerationStructure>().address;
+        self.list.CopyRaytracingAccelerationStructure(dst_address, src_address, dx_mode);
+    }
+
+    unsafe fn write_acceleration_structure_compacted_size(&self, src: &ral::AccelerationStructureHandle, dst: &ral::BufferHandle, dst_offset: u64) {
+        let desc = D3D12_RAYTRACING_ACCELERATION_STRUCTURE_POSTBUILD_INFO_DESC {
+            DestBuffer: dst.gpu_address().at(dst_offset).as_raw(),
+            InfoType: D3D12_RAYTRACING_ACCELERATION_STRUCTURE_POSTBUILD_INFO_COMPACTED_SIZE,
+        };
+        let src_address = src.interface().as_concrete_type::<AccelerationStructure>().address;
+        self.list.EmitRaytracingAccelerationStructurePostbuildInfo(&desc, &[src_address]);
+    }
+
+    unsafe fn bind_raytracing_pipeline(&self, pipeline: &ral::PipelineHandle) {
+        let state_object = &pipeline.interface().as_concrete_type::<RaytracingPipeline>().state_object;
+        self.list.SetPipelineState1(state_object);
+    }
+
+    unsafe fn trace_rays(&self, regions: &ral::ShaderBindingTableRegions, width: u32, height: u32, depth: u32) {
+        let to_range = |region: &ral::ShaderTableRegion| D3D12_GPU_VIRTUAL_ADDRESS_RANGE_AND_STRIDE {
+            StartAddress: region.address.as_raw(),
+            SizeInBytes: region.size,
+            StrideInBytes: region.stride,
+        };
+
+        let desc = D3D12_DISPATCH_RAYS_DESC {
+            RayGenerationShaderRecord: D3D12_GPU_VIRTUAL_ADDRESS_RANGE {
+                StartAddress: regions.raygen.address.as_raw(),
+                SizeInBytes: regions.raygen.size,
+            },
+            MissShaderTable: to_range(&regions.miss),
+            HitGroupTable: to_range(&regions.hit_group),
+            CallableShaderTable: to_range(&regions.callable),
+            Width: width,
+            Height: height,
+            Depth: depth,
+        };
+        self.list.DispatchRays(&desc);
+    }
+
     //==============================================================================================================================
 
     unsafe fn bind_graphics_pipeline_layout(&self, pipeline_layout: &ral::PipelineLayoutHandle) {
@@ -388,6 +503,11 @@ impl ral::CommandListInterface for CommandList {
         self.list.SetGraphicsRootDescriptorTable(index, gpu_descriptor);
     }
 
+    unsafe fn set_graphics_constants(&self, index: u32, offset: u32, data: &[u32], layout: &ral::PipelineLayoutHandle) {
+        let root_index = get_constants_root_index(index, layout);
+        self.list.SetGraphicsRoot32BitConstants(root_index, data.len() as u32, data.as_ptr() as *const c_void, offset);
+    }
+
     unsafe fn bind_vertex_buffer(&self, view: ral::VertexBufferView) {
         let buffer = view.buffer.interface().as_concrete_type::<Buffer>();
 
@@ -570,6 +690,12 @@ impl ral::CommandListInterface for CommandList {
             flags |= D3D12_RENDER_PASS_FLAG_ALLOW_UAV_WRITES;
         }
 
+        // The shading rate image is command list state in DX12, so always set it to make sure no image from a previous render pass is used
+        let shading_rate_image = rendering_info.shading_rate.as_ref()
+            .and_then(|shading_rate| ral::WeakHandle::upgrade(shading_rate.view.texture()))
+            .and_then(|texture| texture.interface().as_concrete_type::<Texture>().resource.cast::<ID3D12Resource>().ok());
+        self.list.RSSetShadingRateImage(shading_rate_image.as_ref());
+
         self.list.BeginRenderPass(opt_dx_rts, depth_stencil_ptr, flags);
     }
 
@@ -577,6 +703,19 @@ impl ral::CommandListInterface for CommandList {
         self.list.EndRenderPass();
     }
 
+    unsafe fn resolve_texture(&self, src: &ral::TextureHandle, src_subresource: ral::TextureSubresourceIndex, dst: &ral::TextureHandle, dst_subresource: ral::TextureSubresourceIndex) {
+        let src_texture = &src.interface().as_concrete_type::<Texture>().resource;
+        let dst_texture = &dst.interface().as_concrete_type::<Texture>().resource;
+
+        let (src_mip, src_layer) = ral::subresource_mip_and_layer(src_subresource);
+        let (dst_mip, dst_layer) = ral::subresource_mip_and_layer(dst_subresource);
+
+        let src_subresource_idx = calculate_subresource(src_mip as u32, src_layer as u32, 0, src.mip_levels() as u32, src.size().layers() as u32);
+        let dst_subresource_idx = calculate_subresource(dst_mip as u32, dst_layer as u32, 0, dst.mip_levels() as u32, dst.size().layers() as u32);
+
+        self.list.ResolveSubresource(dst_texture, dst_subresource_idx, src_texture, src_subresource_idx, src.format().to_dx());
+    }
+
     unsafe fn set_viewports(&self, viewports: &[ral::Viewport]) {
         const MAX_VIEWPORTS: usize = ral::constants::MAX_VIEWPORT_COUNT as usize;
 
@@ -606,6 +745,11 @@ impl ral::CommandListInterface for CommandList {
         self.list.IASetPrimitiveTopology(topology.to_dx());
     }
 
+    unsafe fn set_shading_rate(&self, rate: ral::ShadingRate, combiners: [ral::ShadingRateCombiner; 2]) {
+        let combiners = [combiners[0].to_dx(), combiners[1].to_dx()];
+        self.list.RSSetShadingRate(rate.to_dx(), Some(&combiners as *const _));
+    }
+
     unsafe fn draw_instanced(&self, vertex_count: u32, instance_count: u32, start_vertex: u32, start_instance: u32) {
         self.list.DrawInstanced(vertex_count, instance_count, start_vertex, start_instance);
     }
@@ -614,7 +758,84 @@ impl ral::CommandListInterface for CommandList {
         self.list.DrawIndexedInstanced(index_count, instance_count, start_index, vertex_offset, start_instance);
     }
 
-    
+    unsafe fn draw_indirect(&self, buffer: &ral::BufferHandle, offset: u64, draw_count: u32) {
+        let resource = &buffer.interface().as_concrete_type::<Buffer>().resource;
+        self.list.ExecuteIndirect(&self.draw_signature, draw_count, resource, offset, None, 0);
+    }
+
+    unsafe fn draw_indexed_indirect(&self, buffer: &ral::BufferHandle, offset: u64, draw_count: u32) {
+        let resource = &buffer.interface().as_concrete_type::<Buffer>().resource;
+        self.list.ExecuteIndirect(&self.draw_indexed_signature, draw_count, resource, offset, None, 0);
+    }
+
+    unsafe fn draw_indirect_count(&self, buffer: &ral::BufferHandle, offset: u64, count_buffer: &ral::BufferHandle, count_offset: u64, max_draw_count: u32) {
+        let resource = &buffer.interface().as_concrete_type::<Buffer>().resource;
+        let count_resource = &count_buffer.interface().as_concrete_type::<Buffer>().resource;
+        self.list.ExecuteIndirect(&self.draw_signature, max_draw_count, resource, offset, count_resource, count_offset);
+    }
+
+    unsafe fn draw_indexed_indirect_count(&self, buffer: &ral::BufferHandle, offset: u64, count_buffer: &ral::BufferHandle, count_offset: u64, max_draw_count: u32) {
+        let resource = &buffer.interface().as_concrete_type::<Buffer>().resource;
+        let count_resource = &count_buffer.interface().as_concrete_type::<Buffer>().resource;
+        self.list.ExecuteIndirect(&self.draw_indexed_signature, max_draw_count, resource, offset, count_resource, count_offset);
+    }
+
+    unsafe fn dispatch_mesh(&self, group_count_x: u32, group_count_y: u32, group_count_z: u32) {
+        self.list.DispatchMesh(group_count_x, group_count_y, group_count_z);
+    }
+
+    unsafe fn dispatch_mesh_indirect(&self, buffer: &ral::BufferHandle, offset: u64) {
+        let resource = &buffer.interface().as_concrete_type::<Buffer>().resource;
+        self.list.ExecuteIndirect(&self.dispatch_mesh_signature, 1, resource, offset, None, 0);
+    }
+
+    //==============================================================================================================================
+
+    unsafe fn write_timestamp(&self, query_pool: &ral::QueryPoolHandle, index: u32) {
+        let dx_query_pool = query_pool.interface().as_concrete_type::<QueryPool>();
+        self.list.EndQuery(&dx_query_pool.heap, dx_query_pool.query_type, index);
+    }
+
+    unsafe fn begin_query(&self, query_pool: &ral::QueryPoolHandle, index: u32) {
+        let dx_query_pool = query_pool.interface().as_concrete_type::<QueryPool>();
+        self.list.BeginQuery(&dx_query_pool.heap, dx_query_pool.query_type, index);
+    }
+
+    unsafe fn end_query(&self, query_pool: &ral::QueryPoolHandle, index: u32) {
+        let dx_query_pool = query_pool.interface().as_concrete_type::<QueryPool>();
+        self.list.EndQuery(&dx_query_pool.heap, dx_query_pool.query_type, index);
+    }
+
+    unsafe fn reset_queries(&self, _query_pool: &ral::QueryPoolHandle, _first_query: u32, _count: u32) {
+        // DX12 query heaps don't need to be reset
+    }
+
+    unsafe fn resolve_queries(&self, query_pool: &ral::QueryPoolHandle, first_query: u32, count: u32, dst: &ral::BufferHandle, dst_offset: u64) {
+        let dx_query_pool = query_pool.interface().as_concrete_type::<QueryPool>();
+        let dst_buffer = &dst.interface().as_concrete_type::<Buffer>().resource;
+        self.list.ResolveQueryData(&dx_query_pool.heap, dx_query_pool.query_type, first_query, count, dst_buffer, dst_offset);
+    }
+
+    unsafe fn begin_predication(&self, buffer: &ral::BufferHandle, offset: u64, op: ral::PredicationOp) {
+        let resource = &buffer.interface().as_concrete_type::<Buffer>().resource;
+        self.list.SetPredication(resource, offset, op.to_dx());
+    }
+
+    unsafe fn end_predication(&self) {
+        self.list.SetPredication(None, 0, D3D12_PREDICATION_OP_EQUAL_ZERO);
+    }
+
+    unsafe fn set_debug_name(&self, name: &str) {
+        set_object_name(&self.list, name);
+    }
+}
+
+/// Constant ranges come after all descriptor tables and inline descriptors in the root signature
+fn get_constants_root_index(index: u32, layout: &ral::PipelineLayoutHandle) -> u32 {
+    let desc = layout.desc();
+    let num_tables = desc.descriptor_tables.as_ref().map_or(0, |tables| tables.len() as u32);
+    let num_inline = desc.inline_descriptors.as_ref().map_or(0, |inlines| inlines.len() as u32);
+    num_tables + num_inline + index
 }
 
 pub fn load_op_to_dx(load_op: ral::AttachmentLoadOp<ral::ClearColor>, format: ral::Format) -> D3D12_RENDER_PASS_BEGINNING_ACCESS {
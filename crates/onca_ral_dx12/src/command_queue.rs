@@ -3,9 +3,9 @@ use core::sync::atomic::{AtomicU64, Ordering};
 use onca_common::time::Duration;
 use onca_ral as ral;
 use ral::{HandleImpl, FenceInterface};
-use windows::{Win32::Graphics::Direct3D12::*, core::ComInterface};
+use windows::{Win32::{Graphics::Direct3D12::*, System::Performance::QueryPerformanceFrequency}, core::ComInterface};
 
-use crate::{fence::Fence, utils::ToRalError, command_list::CommandList};
+use crate::{fence::Fence, utils::{ToRalError, set_object_name, calculate_subresource}, command_list::CommandList, texture::Texture, memory::MemoryHeap};
 
 pub struct CommandQueue {
     pub queue:       ID3D12CommandQueue,
@@ -59,4 +59,93 @@ impl ral::CommandQueueInterface for CommandQueue {
 
         Ok(())
     }
+
+    unsafe fn update_tile_mappings(&self, texture: &ral::SparseTexture, mappings: &[ral::SparseTileMapping]) -> ral::Result<()> {
+        let resource = &texture.texture().interface().as_concrete_type::<Texture>().resource;
+        let info = texture.info();
+        let mip_levels = texture.texture().mip_levels() as u32;
+        let layers = texture.size().layers() as u32;
+
+        // Each mapping can reference a different heap, so they each need their own call
+        for mapping in mappings {
+            let num_tiles = mapping.num_tiles(info);
+            let (coord, size) = match mapping.region {
+                ral::SparseTileRegion::Tiles { subresource, offset, extent } => {
+                    let (mip, layer) = ral::subresource_mip_and_layer(subresource);
+                    (
+                        D3D12_TILED_RESOURCE_COORDINATE {
+                            X: offset.x as u32,
+                            Y: offset.y as u32,
+                            Z: offset.z as u32,
+                            Subresource: calculate_subresource(mip as u32, layer as u32, 0, mip_levels, layers),
+                        },
+                        D3D12_TILE_REGION_SIZE {
+                            NumTiles: num_tiles,
+                            UseBox: true.into(),
+                            Width: extent.width.get() as u32,
+                            Height: extent.height.get(),
+                            Depth: extent.depth.get(),
+                        }
+                    )
+                },
+                // The packed mips are addressed using the subresource of the first packed mip
+                ral::SparseTileRegion::PackedMips { layer } => (
+                    D3D12_TILED_RESOURCE_COORDINATE {
+                        X: 0,
+                        Y: 0,
+                        Z: 0,
+                        Subresource: calculate_subresource(info.num_standard_mips as u32, layer as u32, 0, mip_levels, layers),
+                    },
+                    D3D12_TILE_REGION_SIZE {
+                        NumTiles: num_tiles,
+                        UseBox: false.into(),
+                        Width: 0,
+                        Height: 0,
+                        Depth: 0,
+                    }
+                ),
+            };
+
+            let (heap, range_flags) = match &mapping.heap {
+                Some(heap) => (Some(&heap.interface().as_concrete_type::<MemoryHeap>().heap), D3D12_TILE_RANGE_FLAG_NONE),
+                None       => (None, D3D12_TILE_RANGE_FLAG_NULL),
+            };
+
+            self.queue.UpdateTileMappings(
+                resource,
+                1,
+                Some(&coord),
+                Some(&size),
+                heap,
+                1,
+                Some(&range_flags),
+                Some(&mapping.heap_offset),
+                Some(&num_tiles),
+                D3D12_TILE_MAPPING_FLAG_NONE
+            );
+        }
+        Ok(())
+    }
+
+    unsafe fn get_timestamp_calibration(&self) -> ral::Result<ral::TimestampCalibration> {
+        let gpu_frequency = self.queue.GetTimestampFrequency().map_err(|err| err.to_ral_error())?;
+
+        let mut gpu_timestamp = 0;
+        let mut cpu_timestamp = 0;
+        self.queue.GetClockCalibration(&mut gpu_timestamp, &mut cpu_timestamp).map_err(|err| err.to_ral_error())?;
+
+        let mut cpu_frequency = 0;
+        QueryPerformanceFrequency(&mut cpu_frequency).map_err(|err| err.to_ral_error())?;
+
+        Ok(ral::TimestampCalibration {
+            gpu_timestamp,
+            gpu_frequency,
+            cpu_timestamp,
+            cpu_frequency: cpu_frequency as u64,
+        })
+    }
+
+    unsafe fn set_debug_name(&self, name: &str) {
+        set_object_name(&self.queue, name);
+    }
 }
\ No newline at end of file
@@ -4,13 +4,25 @@ use windows::Win32::Graphics::Direct3D12::*;
 use crate::utils::*;
 
 pub struct Dx12Debug {
-    _debug: Option<ID3D12Debug5>
+    _debug: Option<ID3D12Debug5>,
+    _dred:  Option<ID3D12DeviceRemovedExtendedDataSettings1>,
 }
 
 impl Dx12Debug {
     pub fn new(settings: &Settings) -> Result<Self> {
+        // DRED (Device Removed Extended Data) needs to be enabled before any device is created, so we can get breadcrumbs and page fault info when a device is lost.
+        // This is enabled regardless of the debug layer, as device lost info is also needed in release builds
+        let mut dred : Option<ID3D12DeviceRemovedExtendedDataSettings1> = None;
+        unsafe {
+            if D3D12GetDebugInterface(&mut dred).is_ok() {
+                let dred = dred.as_ref().unwrap();
+                dred.SetAutoBreadcrumbsEnablement(D3D12_DRED_ENABLEMENT_FORCED_ON);
+                dred.SetPageFaultEnablement(D3D12_DRED_ENABLEMENT_FORCED_ON);
+            }
+        }
+
         if !settings.debug_enabled {
-            return Ok(Self{ _debug: None });
+            return Ok(Self{ _debug: None, _dred: dred });
         }
         
         let mut debug : Option<ID3D12Debug5> = None;
@@ -30,6 +42,7 @@ impl Dx12Debug {
             }
             debug.SetGPUBasedValidationFlags(gbv_flags);
         };
-        Ok(Self{ _debug: debug })
+
+        Ok(Self{ _debug: debug, _dred: dred })
     }
 }
\ No newline at end of file
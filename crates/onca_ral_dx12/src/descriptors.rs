@@ -213,7 +213,9 @@ impl DescriptorTableLayout {
 }
 
 impl ral::DescriptorTableLayoutInterface for DescriptorTableLayout {
-
+    unsafe fn set_debug_name(&self, _name: &str) {
+        // Descriptor table layouts only exist as part of a root signature, so there is no D3D12 object to name
+    }
 }
 
 //==============================================================================================================================
@@ -503,4 +505,7 @@ impl ral::DescriptorHeapInterface for DescriptorHeap {
         self.write_uav(index, resource, None, &desc);
     }
 
+    unsafe fn set_debug_name(&self, name: &str) {
+        set_object_name(&self.heap, name);
+    }
 }
\ No newline at end of file
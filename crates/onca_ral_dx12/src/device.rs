@@ -3,11 +3,16 @@ use std::sync::Arc;
 
 use onca_common::prelude::*;
 use onca_ral as ral;
+use ral::HandleImpl;
 
 use ral::constants::{MAX_RENDER_TARGET_VIEWS, MAX_DEPTH_STENCIL_VIEWS};
-use windows::Win32::Graphics::{
-    Direct3D::*,
-    Direct3D12::*,
+use windows::{
+    core::ComInterface,
+    Win32::Graphics::{
+        Direct3D::*,
+        Direct3D12::*,
+        Dxgi::{DXGIGetDebugInterface1, IDXGraphicsAnalysis},
+    },
 };
 
 use crate::{
@@ -18,7 +23,9 @@ use crate::{
     descriptors::{RTVAndDSVDescriptorHeap, DescriptorHeap, DescriptorTableLayout},
     swap_chain::SwapChain,
     command_list::CommandPool,
-    fence::Fence, shader::Shader, pipeline::{Pipeline, PipelineLayout}, buffer::Buffer, memory::MemoryHeap, sampler::{StaticSampler, Sampler},
+    fence::Fence, shader::Shader, pipeline::{Pipeline, PipelineLayout, PipelineCache}, buffer::Buffer, memory::MemoryHeap, sampler::{StaticSampler, Sampler}, query::QueryPool,
+    raytracing::{AccelerationStructure, RaytracingPipeline, get_build_sizes},
+    texture::Texture,
 };
 
 pub struct Device {
@@ -27,6 +34,12 @@ pub struct Device {
     pub dsv_heap:                 Arc<RTVAndDSVDescriptorHeap>,
     pub resource_descriptor_size: u32,
     pub sampler_descriptor_size:  u32,
+    pub dispatch_signature:       ID3D12CommandSignature,
+    pub dispatch_mesh_signature:  ID3D12CommandSignature,
+    pub draw_signature:           ID3D12CommandSignature,
+    pub draw_indexed_signature:   ID3D12CommandSignature,
+    /// PIX programmatic capture interface, only available when the application is run under PIX
+    pub graphics_analysis:        Option<IDXGraphicsAnalysis>,
 }
 
 impl Device {
@@ -62,6 +75,13 @@ impl Device {
 
         let resource_descriptor_size = device.GetDescriptorHandleIncrementSize(D3D12_DESCRIPTOR_HEAP_TYPE_CBV_SRV_UAV);
         let sampler_descriptor_size = device.GetDescriptorHandleIncrementSize(D3D12_DESCRIPTOR_HEAP_TYPE_SAMPLER);
+
+        let dispatch_signature = Self::create_command_signature::<ral::DispatchIndirectArgs>(&device, D3D12_INDIRECT_ARGUMENT_TYPE_DISPATCH)?;
+        let dispatch_mesh_signature = Self::create_command_signature::<ral::DispatchIndirectArgs>(&device, D3D12_INDIRECT_ARGUMENT_TYPE_DISPATCH_MESH)?;
+        let draw_signature = Self::create_command_signature::<ral::DrawIndirectArgs>(&device, D3D12_INDIRECT_ARGUMENT_TYPE_DRAW)?;
+        let draw_indexed_signature = Self::create_command_signature::<ral::DrawIndexedIndirectArgs>(&device, D3D12_INDIRECT_ARGUMENT_TYPE_DRAW_INDEXED)?;
+
+        let graphics_analysis = DXGIGetDebugInterface1::<IDXGraphicsAnalysis>(0).ok();
     
         Ok((ral::DeviceInterfaceHandle::new(Device {
                 device,
@@ -69,10 +89,34 @@ impl Device {
                 dsv_heap: Arc::new(dsv_heap),
                 resource_descriptor_size,
                 sampler_descriptor_size,
+                dispatch_signature,
+                dispatch_mesh_signature,
+                draw_signature,
+                draw_indexed_signature,
+                graphics_analysis,
             }),
             command_queues.assume_init()
         ))
     }
+
+    /// Create a command signature for a single indirect argument, with `T` being the argument layout
+    unsafe fn create_command_signature<T>(device: &ID3D12Device10, argument_type: D3D12_INDIRECT_ARGUMENT_TYPE) -> ral::Result<ID3D12CommandSignature> {
+        let argument_desc = D3D12_INDIRECT_ARGUMENT_DESC {
+            Type: argument_type,
+            ..Default::default()
+        };
+
+        let desc = D3D12_COMMAND_SIGNATURE_DESC {
+            ByteStride: core::mem::size_of::<T>() as u32,
+            NumArgumentDescs: 1,
+            pArgumentDescs: &argument_desc,
+            NodeMask: 0,
+        };
+
+        let mut signature : Option<ID3D12CommandSignature> = None;
+        device.CreateCommandSignature(&desc, None, &mut signature).map_err(|err| err.to_ral_error())?;
+        Ok(signature.unwrap_unchecked())
+    }
 }
 
 impl ral::DeviceInterface for Device {
@@ -92,6 +136,22 @@ impl ral::DeviceInterface for Device {
         Buffer::new(self, desc, alloc)
     }
 
+    unsafe fn create_texture(&self, desc: &ral::TextureDesc, alloc: &ral::GpuAllocator) -> ral::Result<(ral::TextureInterfaceHandle, ral::GpuAllocation)> {
+        Texture::new(self, desc, alloc)
+    }
+
+    unsafe fn create_sparse_texture(&self, desc: &ral::SparseTextureDesc) -> ral::Result<(ral::TextureInterfaceHandle, ral::SparseTextureInfo)> {
+        Texture::new_sparse(self, desc)
+    }
+
+    unsafe fn create_placed_texture(&self, desc: &ral::TextureDesc, heap: &ral::MemoryHeapHandle, offset: u64) -> ral::Result<ral::TextureInterfaceHandle> {
+        Texture::new_placed(self, desc, heap, offset)
+    }
+
+    unsafe fn get_texture_allocation_info(&self, desc: &ral::TextureDesc) -> ral::ResourceAllocationInfo {
+        Texture::get_allocation_info(self, desc)
+    }
+
     unsafe fn create_shader(&self, code: &[u8], _shader_type: ral::ShaderType) -> ral::Result<ral::ShaderInterfaceHandle> {
         Shader::new(code)
     }
@@ -104,8 +164,41 @@ impl ral::DeviceInterface for Device {
         Ok(Sampler::new(desc))
     }
 
-    unsafe fn create_graphics_pipeline(&self, desc: &ral::GraphicsPipelineDesc) -> ral::Result<ral::PipelineInterfaceHandle> {
-        Pipeline::new_graphics(self, desc)
+    unsafe fn create_graphics_pipeline(&self, desc: &ral::GraphicsPipelineDesc, cache: Option<&ral::PipelineCacheHandle>) -> ral::Result<ral::PipelineInterfaceHandle> {
+        Pipeline::new_graphics(self, desc, cache)
+    }
+
+    unsafe fn create_compute_pipeline(&self, desc: &ral::ComputePipelineDesc, cache: Option<&ral::PipelineCacheHandle>) -> ral::Result<ral::PipelineInterfaceHandle> {
+        Pipeline::new_compute(self, desc, cache)
+    }
+
+    unsafe fn create_mesh_pipeline(&self, desc: &ral::MeshPipelineDesc, cache: Option<&ral::PipelineCacheHandle>) -> ral::Result<ral::PipelineInterfaceHandle> {
+        Pipeline::new_mesh(self, desc, cache)
+    }
+
+    unsafe fn create_pipeline_cache(&self, data: &[u8]) -> ral::Result<ral::PipelineCacheInterfaceHandle> {
+        PipelineCache::new(self, data)
+    }
+
+    unsafe fn create_query_pool(&self, desc: &ral::QueryPoolDesc) -> ral::Result<ral::QueryPoolInterfaceHandle> {
+        QueryPool::new(self, desc)
+    }
+
+    unsafe fn create_acceleration_structure(&self, desc: &ral::AccelerationStructureDesc) -> ral::Result<(ral::AccelerationStructureInterfaceHandle, ral::GpuAddress)> {
+        AccelerationStructure::new(desc)
+    }
+
+    unsafe fn get_acceleration_structure_build_sizes(&self, inputs: &ral::AccelerationStructureInputs, flags: ral::AccelerationStructureBuildFlags) -> ral::Result<ral::AccelerationStructureBuildSizes> {
+        get_build_sizes(self, inputs, flags)
+    }
+
+    unsafe fn create_raytracing_pipeline(&self, desc: &ral::RaytracingPipelineDesc, _cache: Option<&ral::PipelineCacheHandle>) -> ral::Result<ral::PipelineInterfaceHandle> {
+        // Pipeline libraries can only store pipeline state streams, not state objects
+        RaytracingPipeline::new(self, desc)
+    }
+
+    unsafe fn get_raytracing_shader_group_handles(&self, pipeline: &ral::PipelineHandle, first_group: u32, count: u32) -> ral::Result<Vec<u8>> {
+        pipeline.interface().as_concrete_type::<RaytracingPipeline>().get_shader_group_handles(first_group, count)
     }
 
     unsafe fn create_pipeline_layout(&self, desc: &ral::PipelineLayoutDesc) -> ral::Result<ral::PipelineLayoutInterfaceHandle> {
@@ -128,6 +221,72 @@ impl ral::DeviceInterface for Device {
         // Nothing to do, dropping the heap will handle this
     }
 
+    unsafe fn begin_capture(&self) -> bool {
+        match &self.graphics_analysis {
+            Some(graphics_analysis) => {
+                graphics_analysis.BeginCapture();
+                true
+            },
+            None => false,
+        }
+    }
+
+    unsafe fn end_capture(&self) -> bool {
+        match &self.graphics_analysis {
+            Some(graphics_analysis) => {
+                graphics_analysis.EndCapture();
+                true
+            },
+            None => false,
+        }
+    }
+
+    unsafe fn get_device_lost_info(&self) -> ral::DeviceLostInfo {
+        let reason = match self.device.GetDeviceRemovedReason() {
+            Ok(_) => "Device was not removed".to_string(),
+            Err(err) => format!("{} ({:X})", err.message(), err.code().0),
+        };
+
+        let mut details = Vec::new();
+        if let Ok(dred) = self.device.cast::<ID3D12DeviceRemovedExtendedData1>() {
+            if let Ok(breadcrumbs) = dred.GetAutoBreadcrumbsOutput1() {
+                let mut node = breadcrumbs.pHeadAutoBreadcrumbNode;
+                while let Some(cur) = node.as_ref() {
+                    let last_value = if cur.pLastBreadcrumbValue.is_null() { 0 } else { *cur.pLastBreadcrumbValue };
+                    details.push(format!("Breadcrumbs for command list '{}' on queue '{}': {last_value}/{} operations completed",
+                        from_wide(cur.pCommandListDebugNameW), from_wide(cur.pCommandQueueDebugNameW), cur.BreadcrumbCount));
+
+                    if !cur.pCommandHistory.is_null() {
+                        let history = core::slice::from_raw_parts(cur.pCommandHistory, cur.BreadcrumbCount as usize);
+                        for (idx, op) in history.iter().enumerate() {
+                            let marker = if idx as u32 == last_value { " <- last completed" } else { "" };
+                            details.push(format!("    [{idx}] {op:?}{marker}"));
+                        }
+                    }
+                    node = cur.pNext;
+                }
+            }
+
+            if let Ok(page_fault) = dred.GetPageFaultAllocationOutput1() {
+                details.push(format!("Page fault at GPU virtual address {:#X}", page_fault.PageFaultVA));
+
+                let mut node = page_fault.pHeadExistingAllocationNode;
+                while let Some(cur) = node.as_ref() {
+                    details.push(format!("    Existing allocation: '{}' ({:?})", from_wide(cur.ObjectNameW), cur.AllocationType));
+                    node = cur.pNext;
+                }
+
+                let mut node = page_fault.pHeadRecentFreedAllocationNode;
+                while let Some(cur) = node.as_ref() {
+                    details.push(format!("    Recently freed allocation: '{}' ({:?})", from_wide(cur.ObjectNameW), cur.AllocationType));
+                    node = cur.pNext;
+                }
+            }
+        }
+
+        ral::DeviceLostInfo { reason, details }
+    }
+
     unsafe fn flush(&self, queues: &[[ral::CommandQueueHandle; ral::QueuePriority::COUNT]; ral::QueueType::COUNT]) -> ral::Result<()> {
         // There is no function for this, so just flush all queues
         for arr in queues {
@@ -137,5 +296,9 @@ impl ral::DeviceInterface for Device {
         }
         Ok(())
     }
+
+    unsafe fn set_debug_name(&self, name: &str) {
+        set_object_name(&self.device, name);
+    }
 }
 
@@ -6,7 +6,7 @@ use windows::Win32::{
     Graphics::Direct3D12::{ID3D12Fence, ID3D12Device10, D3D12_FENCE_FLAG_NONE}, System::Threading::{WaitForSingleObject, WaitForMultipleObjects, CreateEventA},
 };
 
-use crate::utils::ToRalError;
+use crate::utils::{ToRalError, set_object_name};
 
 pub struct Fence {
     pub fence: ID3D12Fence,
@@ -60,7 +60,9 @@ impl ral::FenceInterface for Fence {
         }
     }
 
-    
+    unsafe fn set_debug_name(&self, name: &str) {
+        set_object_name(&self.fence, name);
+    }
 }
 
 impl Drop for Fence {
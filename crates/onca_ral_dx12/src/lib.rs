@@ -35,6 +35,8 @@ mod pipeline;
 mod buffer;
 mod memory;
 mod sampler;
+mod query;
+mod raytracing;
 
 use dx12_ral::Dx12Ral;
 
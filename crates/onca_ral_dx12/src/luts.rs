@@ -22,6 +22,7 @@ pub(crate) const DX12_QUEUE_PRIORITIES : [D3D12_COMMAND_QUEUE_PRIORITY; QueuePri
     D3D12_COMMAND_QUEUE_PRIORITY_HIGH,
 ];
 
+// ASTC formats are not supported by DX12, so they map to `DXGI_FORMAT_UNKNOWN`
 pub(crate) const DX12_FORMATS : [DXGI_FORMAT; Format::COUNT] = [
     /* R32G32B32A32Typeless           */ DXGI_FORMAT_R32G32B32A32_TYPELESS,
     /* R32G32B32A32SFloat             */ DXGI_FORMAT_R32G32B32A32_FLOAT,
@@ -101,6 +102,34 @@ pub(crate) const DX12_FORMATS : [DXGI_FORMAT; Format::COUNT] = [
     /* BC7Typeless                    */ DXGI_FORMAT_BC7_TYPELESS,
     /* BC7UNorm                       */ DXGI_FORMAT_BC7_UNORM,
     /* BC7Srgb                        */ DXGI_FORMAT_BC7_UNORM_SRGB,
+    /* ASTC4x4UNorm                   */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC4x4Srgb                    */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC5x4UNorm                   */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC5x4Srgb                    */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC5x5UNorm                   */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC5x5Srgb                    */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC6x5UNorm                   */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC6x5Srgb                    */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC6x6UNorm                   */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC6x6Srgb                    */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC8x5UNorm                   */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC8x5Srgb                    */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC8x6UNorm                   */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC8x6Srgb                    */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC8x8UNorm                   */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC8x8Srgb                    */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC10x5UNorm                  */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC10x5Srgb                   */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC10x6UNorm                  */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC10x6Srgb                   */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC10x8UNorm                  */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC10x8Srgb                   */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC10x10UNorm                 */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC10x10Srgb                  */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC12x10UNorm                 */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC12x10Srgb                  */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC12x12UNorm                 */ DXGI_FORMAT_UNKNOWN,
+    /* ASTC12x12Srgb                  */ DXGI_FORMAT_UNKNOWN,
     /* SamplerFeedbackMinMipOpaque    */ DXGI_FORMAT_SAMPLER_FEEDBACK_MIN_MIP_OPAQUE,
     /* SamplerFeedbackMipRegionOpaque */ DXGI_FORMAT_SAMPLER_FEEDBACK_MIP_REGION_USED_OPAQUE,
 ];
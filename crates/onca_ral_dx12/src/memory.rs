@@ -1,7 +1,7 @@
 use onca_ral as ral;
 use windows::Win32::Graphics::Direct3D12::*;
 
-use crate::{device::Device, utils::ToRalError};
+use crate::{device::Device, utils::{ToRalError, set_object_name}};
 
 // TODO
 // pub struct GpuAllocator {
@@ -69,7 +69,9 @@ impl MemoryHeap {
 }
 
 impl ral::MemoryHeapInterface for MemoryHeap {
-
+    unsafe fn set_debug_name(&self, name: &str) {
+        set_object_name(&self.heap, name);
+    }
 }
 
 
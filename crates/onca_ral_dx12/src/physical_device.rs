@@ -88,6 +88,7 @@ pub struct PhysicalDevice {
     pub shader_model:       D3D_SHADER_MODEL,
     pub root_signature_ver: D3D_ROOT_SIGNATURE_VERSION,
     pub options:            D3DOptions,
+    pub sample_counts:      [SampleCountSupport; Format::COUNT],
 }
 
 impl PhysicalDevice {
@@ -100,7 +101,7 @@ impl PhysicalDevice {
 
         let non_local_info = unsafe {
             let mut query_info = MaybeUninit::uninit();
-            self.adapter.QueryVideoMemoryInfo(0, DXGI_MEMORY_SEGMENT_GROUP_LOCAL, query_info.as_mut_ptr()).map_err(|err| err.to_ral_error())?;
+            self.adapter.QueryVideoMemoryInfo(0, DXGI_MEMORY_SEGMENT_GROUP_NON_LOCAL, query_info.as_mut_ptr()).map_err(|err| err.to_ral_error())?;
             query_info.assume_init()
         };
 
@@ -149,6 +150,10 @@ impl PhysicalDeviceInterface for PhysicalDevice {
     fn reserve_memory(&self, heap_idx: u8, bytes: u64) -> ral::Result<()> {
         self._reserve_memory(heap_idx, bytes).map_err(|err| err.into())
     }
+
+    fn get_supported_sample_counts(&self, format: Format) -> SampleCountSupport {
+        self.sample_counts[format as usize]
+    }
 }
 
 pub fn get_physical_devices(factory: &IDXGIFactory7) -> Result<Vec<ral::PhysicalDevice>> {
@@ -248,7 +253,8 @@ fn get_device(factory: &IDXGIFactory7, adapter: IDXGIAdapter4) -> ral::Result<ra
         dev_type: get_device_type(desc.Flags, desc.VendorId, desc.DeviceId),
         graphics_preempt: get_graphics_preemption(desc.GraphicsPreemptionGranularity),
         compure_preempt: get_compute_preemption(desc.ComputePreemptionGranularity),
-        
+        // Root constants take up 1 DWORD of the 64 DWORD root signature budget per constant, the remaining budget is validated together with the other root parameters
+        max_pipeline_constant_size: 64 * 4,
     };
 
 
@@ -290,6 +296,7 @@ fn get_device(factory: &IDXGIFactory7, adapter: IDXGIAdapter4) -> ral::Result<ra
         shader_model: shader_model.HighestShaderModel,
         root_signature_ver: root_signature.HighestVersion,
         options,
+        sample_counts: get_sample_count_support(&dummy_device),
     });
 
     let queue_infos = [
@@ -493,6 +500,10 @@ fn get_sparse_resource_support() -> SparseResourceSupport {
 fn check_format_properties(device: &ID3D12Device) -> ral::Result<()> {
     let mut res = Ok(());
     Format::for_each(|format| {
+        // ASTC is not supported by DX12
+        if format.is_astc() {
+            return;
+        }
         if let Err(err) = get_format_properties_for_single(device, format) && res.is_ok() {
             res = Err(err);
         }
@@ -500,6 +511,30 @@ fn check_format_properties(device: &ID3D12Device) -> ral::Result<()> {
     res
 }
 
+fn get_sample_count_support(device: &ID3D12Device) -> [SampleCountSupport; Format::COUNT] {
+    let mut sample_counts = [SampleCountSupport::Sample1; Format::COUNT];
+    Format::for_each(|format| {
+        // ASTC is not supported by DX12
+        if format.is_astc() {
+            return;
+        }
+
+        let support = &mut sample_counts[format as usize];
+        for (count, flag) in [(2, SampleCountSupport::Sample2), (4, SampleCountSupport::Sample4), (8, SampleCountSupport::Sample8), (16, SampleCountSupport::Sample16)] {
+            let mut quality_levels = D3D12_FEATURE_DATA_MULTISAMPLE_QUALITY_LEVELS {
+                Format: format.to_dx(),
+                SampleCount: count,
+                Flags: D3D12_MULTISAMPLE_QUALITY_LEVELS_FLAG_NONE,
+                NumQualityLevels: 0,
+            };
+            if query_dx12_feature_support(device, D3D12_FEATURE_MULTISAMPLE_QUALITY_LEVELS, &mut quality_levels).is_ok() && quality_levels.NumQualityLevels > 0 {
+                support.enable(flag);
+            }
+        }
+    });
+    sample_counts
+}
+
 // Only need to check optional support, all other features are guaranteed by the DX12 spec for at min DX12.1
 fn get_format_properties_for_single(device: &ID3D12Device, format: Format) -> Result<()> {
     // NOTE: Currently not handled:
@@ -772,9 +807,11 @@ fn get_raytracing_support(_options: &D3DOptions) -> ral::Result<RaytracingSuppor
 
     // TODO: invocation reordering via NVAPI
 
+    // Raytracing tier 1.1 is required, which always supports indirect builds (via `ExecuteIndirect`) and inline raytracing
     Ok(RaytracingSupport {
-        flags: RaytracingSupportFlags::None,
+        flags: RaytracingSupportFlags::IndirectBuild | RaytracingSupportFlags::RayQuery,
         invocation_reorder_mode: InvocationReorderMode::None,
+        max_recursion_depth: D3D12_RAYTRACING_MAX_DECLARABLE_TRACE_RECURSION_DEPTH,
     })
 }
 
@@ -1,17 +1,22 @@
-use core::{ffi::c_void, mem::ManuallyDrop};
+use core::{ffi::c_void, mem::ManuallyDrop, hash::Hasher};
 
-use onca_common::collections::ByteBuffer;
+use onca_common::{collections::ByteBuffer, hashing::FNVa64};
 use onca_ral as ral;
 use ral::HandleImpl;
-use windows::Win32::Graphics::{
-    Direct3D12::*,
-    Dxgi::Common::{DXGI_FORMAT_UNKNOWN, DXGI_SAMPLE_DESC, DXGI_FORMAT}
+use windows::{
+    core::{PCSTR, PCWSTR},
+    Win32::Graphics::{
+        Direct3D12::*,
+        Dxgi::Common::{DXGI_FORMAT_UNKNOWN, DXGI_SAMPLE_DESC, DXGI_FORMAT}
+    },
 };
 
-use crate::{device::Device, shader::Shader, utils::{ToDx, ToRalError, get_root_parameter_type}, sampler::StaticSampler, descriptors::DescriptorTableLayout};
+use crate::{device::Device, shader::Shader, utils::{ToDx, ToRalError, get_root_parameter_type, to_wide}, sampler::StaticSampler, descriptors::DescriptorTableLayout};
 
 pub struct PipelineLayout {
-    pub root_sig: ID3D12RootSignature
+    pub root_sig:      ID3D12RootSignature,
+    /// Hash of the serialized root signature, used to identify pipelines in a pipeline library
+    pub root_sig_hash: u64,
 }
 
 impl PipelineLayout {
@@ -24,6 +29,9 @@ impl PipelineLayout {
         if desc.flags.contains(ral::PipelineLayoutFlags::ContainsInputLayout) {
             flags |= D3D12_ROOT_SIGNATURE_FLAG_ALLOW_INPUT_ASSEMBLER_INPUT_LAYOUT;
         }
+        if desc.flags.contains(ral::PipelineLayoutFlags::Bindless) {
+            flags |= D3D12_ROOT_SIGNATURE_FLAG_CBV_SRV_UAV_HEAP_DIRECTLY_INDEXED | D3D12_ROOT_SIGNATURE_FLAG_SAMPLER_HEAP_DIRECTLY_INDEXED;
+        }
 
         let mut parameters = Vec::new();
         let mut all_ranges = Vec::new();
@@ -111,14 +119,20 @@ impl PipelineLayout {
 
         let root_sig = device.device.CreateRootSignature(0, serialized_data).map_err(|err| err.to_ral_error())?;
 
+        let mut hasher = FNVa64::new();
+        hasher.write(serialized_data);
+
         Ok(ral::PipelineLayoutInterfaceHandle::new(PipelineLayout {
-            root_sig
+            root_sig,
+            root_sig_hash: hasher.finish(),
         }))
     }
 }
 
 impl ral::PipelineLayoutInterface for PipelineLayout {
-
+    unsafe fn set_debug_name(&self, name: &str) {
+        set_object_name(&self.root_sig, name);
+    }
 }
 
 //==============================================================================================================================
@@ -128,14 +142,15 @@ pub struct Pipeline {
 }
 
 impl Pipeline {
-    pub unsafe fn new_graphics(device: &Device, desc: &ral::GraphicsPipelineDesc) -> ral::Result<ral::PipelineInterfaceHandle> {
+    pub unsafe fn new_graphics(device: &Device, desc: &ral::GraphicsPipelineDesc, cache: Option<&ral::PipelineCacheHandle>) -> ral::Result<ral::PipelineInterfaceHandle> {
         let mut pipeline_stream = PipelineStream::default();
 
         pipeline_stream.set_root_signature(&desc.pipeline_layout);
         pipeline_stream.set_vertex_shader(&desc.vertex_shader);
         pipeline_stream.set_pixel_shader(&desc.pixel_shader);
-        pipeline_stream.set_blend_desc(&desc.blend_state);
-        pipeline_stream.set_raster_desc(&desc.rasterizer_state);
+        pipeline_stream.set_blend_desc(&desc.blend_state, desc.multisample_state.alpha_to_coverage);
+        pipeline_stream.set_raster_desc(&desc.rasterizer_state, desc.multisample_state.samples);
+        pipeline_stream.set_multisample_state(&desc.multisample_state);
         pipeline_stream.set_strip_cut(desc.primitive_restart.to_dx());
         pipeline_stream.set_topology_type(desc.topology.get_type().to_dx());
         pipeline_stream.set_depth_stencil_state(&desc.depth_stencil_state);
@@ -153,23 +168,125 @@ impl Pipeline {
             }
             pipeline_stream.set_input_layout(&dx_input_layout);
         }
-        
+
+        let pso = Self::create_pipeline_state(device, pipeline_stream, cache)?;
+
+        Ok(ral::PipelineInterfaceHandle::new(Self {
+            pso
+        }))
+    }
+
+    pub unsafe fn new_compute(device: &Device, desc: &ral::ComputePipelineDesc, cache: Option<&ral::PipelineCacheHandle>) -> ral::Result<ral::PipelineInterfaceHandle> {
+        let mut pipeline_stream = PipelineStream::default();
+
+        pipeline_stream.set_root_signature(&desc.pipeline_layout);
+        pipeline_stream.set_compute_shader(&desc.compute_shader);
+
+        let pso = Self::create_pipeline_state(device, pipeline_stream, cache)?;
+
+        Ok(ral::PipelineInterfaceHandle::new(Self {
+            pso
+        }))
+    }
+
+    pub unsafe fn new_mesh(device: &Device, desc: &ral::MeshPipelineDesc, cache: Option<&ral::PipelineCacheHandle>) -> ral::Result<ral::PipelineInterfaceHandle> {
+        let mut pipeline_stream = PipelineStream::default();
+
+        pipeline_stream.set_root_signature(&desc.pipeline_layout);
+        if let Some(task_shader) = &desc.task_shader {
+            pipeline_stream.set_amplification_shader(task_shader);
+        }
+        pipeline_stream.set_mesh_shader(&desc.mesh_shader);
+        pipeline_stream.set_pixel_shader(&desc.pixel_shader);
+        pipeline_stream.set_blend_desc(&desc.blend_state, desc.multisample_state.alpha_to_coverage);
+        pipeline_stream.set_raster_desc(&desc.rasterizer_state, desc.multisample_state.samples);
+        pipeline_stream.set_multisample_state(&desc.multisample_state);
+        pipeline_stream.set_depth_stencil_state(&desc.depth_stencil_state);
+        pipeline_stream.set_render_target_formats(desc.rendertarget_formats);
+
+        if let Some(format) = desc.depth_stencil_format {
+            pipeline_stream.set_depth_stencil_format(format.to_dx());
+        }
+
+        let pso = Self::create_pipeline_state(device, pipeline_stream, cache)?;
+
+        Ok(ral::PipelineInterfaceHandle::new(Self {
+            pso
+        }))
+    }
+
+    unsafe fn create_pipeline_state(device: &Device, pipeline_stream: PipelineStream, cache: Option<&ral::PipelineCacheHandle>) -> ral::Result<ID3D12PipelineState> {
+        // The key needs to be calculated before building the stream, as building consumes the sub-objects
+        let key = if cache.is_some() { pipeline_stream.cache_key() } else { 0 };
+
         let mut stream = pipeline_stream.build();
         let dx_desc = D3D12_PIPELINE_STATE_STREAM_DESC {
             SizeInBytes: stream.len(),
             pPipelineStateSubobjectStream: stream.as_mut_ptr() as *mut c_void,
         };
 
-        let pso = device.device.CreatePipelineState(&dx_desc).map_err(|err| err.to_ral_error())?;
-        
-        Ok(ral::PipelineInterfaceHandle::new(Self {
-            pso
-        }))
+        match cache {
+            Some(cache) => cache.interface().as_concrete_type::<PipelineCache>().load_or_create(device, key, &dx_desc),
+            None => device.device.CreatePipelineState(&dx_desc).map_err(|err| err.to_ral_error()),
+        }
     }
 }
 
 impl ral::PipelineInterface for Pipeline {
+    unsafe fn set_debug_name(&self, name: &str) {
+        set_object_name(&self.pso, name);
+    }
+}
+
+//==============================================================================================================================
+
+pub struct PipelineCache {
+    pub library: ID3D12PipelineLibrary1,
+    /// The pipeline library references the data it was created from, so it needs to stay alive as long as the library
+    _data:       Vec<u8>,
+}
+
+impl PipelineCache {
+    pub unsafe fn new(device: &Device, data: &[u8]) -> ral::Result<ral::PipelineCacheInterfaceHandle> {
+        let data = data.to_vec();
+        let (library, data) = match device.device.CreatePipelineLibrary(data.as_ptr() as *const c_void, data.len()) {
+            Ok(library) => (library, data),
+            // The data was created by an incompatible driver or device, so start with an empty library
+            Err(_) if !data.is_empty() => (device.device.CreatePipelineLibrary(core::ptr::null(), 0).map_err(|err| err.to_ral_error())?, Vec::new()),
+            Err(err) => return Err(err.to_ral_error()),
+        };
+
+        Ok(ral::PipelineCacheInterfaceHandle::new(PipelineCache {
+            library,
+            _data: data,
+        }))
+    }
+
+    /// Load a pipeline from the library, or create and store it when it's not in the library yet
+    unsafe fn load_or_create(&self, device: &Device, key: u64, desc: &D3D12_PIPELINE_STATE_STREAM_DESC) -> ral::Result<ID3D12PipelineState> {
+        let name = to_wide(&format!("{key:016X}"));
+        if let Ok(pso) = self.library.LoadPipeline(PCWSTR(name.as_ptr()), desc) {
+            return Ok(pso);
+        }
+
+        let pso: ID3D12PipelineState = device.device.CreatePipelineState(desc).map_err(|err| err.to_ral_error())?;
+        // Storing fails when the same pipeline was stored in the meantime, in which case the library already contains it
+        let _ = self.library.StorePipeline(PCWSTR(name.as_ptr()), &pso);
+        Ok(pso)
+    }
+}
+
+impl ral::PipelineCacheInterface for PipelineCache {
+    unsafe fn get_data(&self) -> ral::Result<Vec<u8>> {
+        let size = self.library.GetSerializedSize();
+        let mut data = vec![0; size];
+        self.library.Serialize(data.as_mut_ptr() as *mut c_void, size).map_err(|err| err.to_ral_error())?;
+        Ok(data)
+    }
 
+    unsafe fn set_debug_name(&self, name: &str) {
+        set_object_name(&self.library, name);
+    }
 }
 
 //==============================================================================================================================
@@ -185,9 +302,13 @@ struct PipelineSubObject<T: Copy> {
 
 #[derive(Default)]
 pub struct PipelineStream {
+    root_sig_hash:  u64,
     root_signature: Option<PipelineSubObject<*const ID3D12RootSignature>>,
     vs_shader:      Option<PipelineSubObject<D3D12_SHADER_BYTECODE>>,
     ps_shader:      Option<PipelineSubObject<D3D12_SHADER_BYTECODE>>,
+    cs_shader:      Option<PipelineSubObject<D3D12_SHADER_BYTECODE>>,
+    as_shader:      Option<PipelineSubObject<D3D12_SHADER_BYTECODE>>,
+    ms_shader:      Option<PipelineSubObject<D3D12_SHADER_BYTECODE>>,
     blend_desc:     Option<PipelineSubObject<D3D12_BLEND_DESC>>,
     raster_desc:    Option<PipelineSubObject<D3D12_RASTERIZER_DESC1>>,
     strip_cut:      Option<PipelineSubObject<D3D12_INDEX_BUFFER_STRIP_CUT_VALUE>>,
@@ -195,6 +316,7 @@ pub struct PipelineStream {
     rt_formats:     Option<PipelineSubObject<D3D12_RT_FORMAT_ARRAY>>,
     dsv_format:     Option<PipelineSubObject<DXGI_FORMAT>>,
     sample_desc:    Option<PipelineSubObject<DXGI_SAMPLE_DESC>>,
+    sample_mask:    Option<PipelineSubObject<u32>>,
     depth_stecnil:  Option<PipelineSubObject<D3D12_DEPTH_STENCIL_DESC2>>,
     input_layout:   Option<PipelineSubObject<D3D12_INPUT_LAYOUT_DESC>>,
 }
@@ -205,6 +327,7 @@ impl PipelineStream {
             let pipeline_layout = pipeline_layout.interface().as_concrete_type::<PipelineLayout>();
             let root_sig = ManuallyDrop::new(core::ptr::read(&pipeline_layout.root_sig));
             let root_sig_ptr : *const ID3D12RootSignature = core::mem::transmute_copy(&root_sig);
+            self.root_sig_hash = pipeline_layout.root_sig_hash;
             self.root_signature = Some(PipelineSubObject {
                 subobject: D3D12_PIPELINE_STATE_SUBOBJECT_TYPE_ROOT_SIGNATURE,
                 data: root_sig_ptr,
@@ -228,14 +351,41 @@ impl PipelineStream {
         });
     }
 
-    fn set_blend_desc(&mut self, blend_state: &ral::BlendState) {
+    fn set_compute_shader(&mut self, shader: &ral::ShaderHandle) {
+        let bytecode = unsafe { shader.interface().as_concrete_type::<Shader>().get_dx_bytecode() };
+        self.cs_shader = Some(PipelineSubObject {
+            subobject: D3D12_PIPELINE_STATE_SUBOBJECT_TYPE_CS,
+            data: bytecode
+        });
+    }
+
+    fn set_amplification_shader(&mut self, shader: &ral::ShaderHandle) {
+        let bytecode = unsafe { shader.interface().as_concrete_type::<Shader>().get_dx_bytecode() };
+        self.as_shader = Some(PipelineSubObject {
+            subobject: D3D12_PIPELINE_STATE_SUBOBJECT_TYPE_AS,
+            data: bytecode
+        });
+    }
+
+    fn set_mesh_shader(&mut self, shader: &ral::ShaderHandle) {
+        let bytecode = unsafe { shader.interface().as_concrete_type::<Shader>().get_dx_bytecode() };
+        self.ms_shader = Some(PipelineSubObject {
+            subobject: D3D12_PIPELINE_STATE_SUBOBJECT_TYPE_MS,
+            data: bytecode
+        });
+    }
+
+    fn set_blend_desc(&mut self, blend_state: &ral::BlendState, alpha_to_coverage: bool) {
+        let mut blend_desc = blend_state.to_dx();
+        blend_desc.AlphaToCoverageEnable = alpha_to_coverage.into();
+
         self.blend_desc = Some(PipelineSubObject {
             subobject: D3D12_PIPELINE_STATE_SUBOBJECT_TYPE_BLEND,
-            data: blend_state.to_dx()
+            data: blend_desc
         });
     }
 
-    fn set_raster_desc(&mut self, raster_state: &ral::RasterizerState) {
+    fn set_raster_desc(&mut self, raster_state: &ral::RasterizerState, samples: ral::SampleCount) {
         let (depth_bias, depth_slope, depth_clamp) = raster_state.depth_bias.map_or((0.0, 0.0, 0.0), |bias| (bias.scale, bias.slope, bias.clamp));
 
         let raster_desc = D3D12_RASTERIZER_DESC1 {
@@ -246,7 +396,7 @@ impl PipelineStream {
             DepthBiasClamp: depth_clamp,
             SlopeScaledDepthBias: depth_slope,
             DepthClipEnable: raster_state.depth_clip_enable.into(),
-            MultisampleEnable: false.into(),
+            MultisampleEnable: samples.is_multisampled().into(),
             AntialiasedLineEnable: false.into(),
             ForcedSampleCount: 0,
             ConservativeRaster: raster_state.conservative_raster.to_dx(),
@@ -258,6 +408,17 @@ impl PipelineStream {
         });
     }
 
+    fn set_multisample_state(&mut self, multisample_state: &ral::MultisampleState) {
+        self.sample_desc = Some(PipelineSubObject {
+            subobject: D3D12_PIPELINE_STATE_SUBOBJECT_TYPE_SAMPLE_DESC,
+            data: DXGI_SAMPLE_DESC { Count: multisample_state.samples.get() as u32, Quality: 0 }
+        });
+        self.sample_mask = Some(PipelineSubObject {
+            subobject: D3D12_PIPELINE_STATE_SUBOBJECT_TYPE_SAMPLE_MASK,
+            data: multisample_state.sample_mask
+        });
+    }
+
     fn set_strip_cut(&mut self, strip_cut: D3D12_INDEX_BUFFER_STRIP_CUT_VALUE) {
         self.strip_cut = Some(PipelineSubObject {
             subobject: D3D12_PIPELINE_STATE_SUBOBJECT_TYPE_IB_STRIP_CUT_VALUE,
@@ -322,11 +483,59 @@ impl PipelineStream {
         })
     }
 
+    /// Calculate the key used to identify the pipeline in a pipeline library
+    /// 
+    /// Sub-objects referencing other data are hashed using the data they reference, as the pointers differ between runs
+    unsafe fn cache_key(&self) -> u64 {
+        let mut hasher = FNVa64::new();
+        hasher.write_u64(self.root_sig_hash);
+        for shader in [&self.vs_shader, &self.ps_shader, &self.cs_shader, &self.as_shader, &self.ms_shader] {
+            match shader {
+                Some(shader) => hasher.write(core::slice::from_raw_parts(shader.data.pShaderBytecode as *const u8, shader.data.BytecodeLength)),
+                None => hasher.write_u8(0),
+            }
+        }
+        Self::hash_sub_object(&mut hasher, &self.blend_desc);
+        Self::hash_sub_object(&mut hasher, &self.raster_desc);
+        Self::hash_sub_object(&mut hasher, &self.strip_cut);
+        Self::hash_sub_object(&mut hasher, &self.topology_type);
+        Self::hash_sub_object(&mut hasher, &self.rt_formats);
+        Self::hash_sub_object(&mut hasher, &self.dsv_format);
+        Self::hash_sub_object(&mut hasher, &self.sample_desc);
+        Self::hash_sub_object(&mut hasher, &self.sample_mask);
+        Self::hash_sub_object(&mut hasher, &self.depth_stecnil);
+        if let Some(input_layout) = &self.input_layout {
+            let elements = core::slice::from_raw_parts(input_layout.data.pInputElementDescs, input_layout.data.NumElements as usize);
+            for element in elements {
+                hasher.write(element.SemanticName.as_bytes());
+
+                let mut element = *element;
+                element.SemanticName = PCSTR::null();
+                Self::hash_raw(&mut hasher, &element);
+            }
+        }
+        hasher.finish()
+    }
+
+    unsafe fn hash_sub_object<T: Copy>(hasher: &mut FNVa64, subobject: &Option<PipelineSubObject<T>>) {
+        match subobject {
+            Some(subobject) => Self::hash_raw(hasher, &subobject.data),
+            None => hasher.write_u8(0),
+        }
+    }
+
+    unsafe fn hash_raw<T: Copy>(hasher: &mut FNVa64, value: &T) {
+        hasher.write(core::slice::from_raw_parts(value as *const T as *const u8, core::mem::size_of::<T>()));
+    }
+
     fn build(self) -> ByteBuffer {
         let mut buffer = ByteBuffer::new();
         Self::write_sub_object(&mut buffer, self.root_signature);
         Self::write_sub_object(&mut buffer, self.vs_shader);
         Self::write_sub_object(&mut buffer, self.ps_shader);
+        Self::write_sub_object(&mut buffer, self.cs_shader);
+        Self::write_sub_object(&mut buffer, self.as_shader);
+        Self::write_sub_object(&mut buffer, self.ms_shader);
         Self::write_sub_object(&mut buffer, self.blend_desc);
         Self::write_sub_object(&mut buffer, self.raster_desc);
         Self::write_sub_object(&mut buffer, self.strip_cut);
@@ -334,6 +543,7 @@ impl PipelineStream {
         Self::write_sub_object(&mut buffer, self.rt_formats);
         Self::write_sub_object(&mut buffer, self.dsv_format);
         Self::write_sub_object(&mut buffer, self.sample_desc);
+        Self::write_sub_object(&mut buffer, self.sample_mask);
         Self::write_sub_object(&mut buffer, self.depth_stecnil);
         Self::write_sub_object(&mut buffer, self.input_layout);
         buffer
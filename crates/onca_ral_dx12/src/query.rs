@@ -0,0 +1,41 @@
+use onca_ral as ral;
+use windows::Win32::Graphics::Direct3D12::*;
+
+use crate::{device::Device, utils::{ToRalError, set_object_name}};
+
+pub struct QueryPool {
+    pub heap:       ID3D12QueryHeap,
+    pub query_type: D3D12_QUERY_TYPE,
+}
+
+impl QueryPool {
+    pub unsafe fn new(device: &Device, desc: &ral::QueryPoolDesc) -> ral::Result<ral::QueryPoolInterfaceHandle> {
+        let (heap_type, query_type) = match desc.query_type {
+            ral::QueryType::Timestamp          => (D3D12_QUERY_HEAP_TYPE_TIMESTAMP, D3D12_QUERY_TYPE_TIMESTAMP),
+            ral::QueryType::CopyQueueTimestamp => (D3D12_QUERY_HEAP_TYPE_COPY_QUEUE_TIMESTAMP, D3D12_QUERY_TYPE_TIMESTAMP),
+            ral::QueryType::PipelineStatistics => (D3D12_QUERY_HEAP_TYPE_PIPELINE_STATISTICS, D3D12_QUERY_TYPE_PIPELINE_STATISTICS),
+            ral::QueryType::Occlusion          => (D3D12_QUERY_HEAP_TYPE_OCCLUSION, D3D12_QUERY_TYPE_OCCLUSION),
+            ral::QueryType::BinaryOcclusion    => (D3D12_QUERY_HEAP_TYPE_OCCLUSION, D3D12_QUERY_TYPE_BINARY_OCCLUSION),
+        };
+
+        let heap_desc = D3D12_QUERY_HEAP_DESC {
+            Type: heap_type,
+            Count: desc.count,
+            NodeMask: 0,
+        };
+
+        let mut heap = None;
+        device.device.CreateQueryHeap(&heap_desc, &mut heap).map_err(|err| err.to_ral_error())?;
+
+        Ok(ral::QueryPoolInterfaceHandle::new(QueryPool {
+            heap: heap.unwrap_unchecked(),
+            query_type,
+        }))
+    }
+}
+
+impl ral::QueryPoolInterface for QueryPool {
+    unsafe fn set_debug_name(&self, name: &str) {
+        set_object_name(&self.heap, name);
+    }
+}
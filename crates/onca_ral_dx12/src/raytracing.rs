@@ -0,0 +1,249 @@
+use core::{ffi::c_void, mem::ManuallyDrop};
+
+use onca_ral as ral;
+use ral::HandleImpl;
+use windows::{
+    core::{PCWSTR, ComInterface},
+    Win32::Graphics::{Direct3D12::*, Dxgi::Common::DXGI_FORMAT_UNKNOWN},
+};
+
+use crate::{device::Device, shader::Shader, pipeline::PipelineLayout, utils::{ToDx, ToRalError, to_wide}};
+
+pub struct AccelerationStructure {
+    pub address: u64,
+}
+
+impl AccelerationStructure {
+    pub unsafe fn new(desc: &ral::AccelerationStructureDesc) -> ral::Result<(ral::AccelerationStructureInterfaceHandle, ral::GpuAddress)> {
+        // DX12 acceleration structures are just a location in a buffer
+        let address = desc.buffer.gpu_address().at(desc.offset);
+        Ok((ral::AccelerationStructureInterfaceHandle::new(AccelerationStructure { address: address.as_raw() }), address))
+    }
+}
+
+impl ral::AccelerationStructureInterface for AccelerationStructure {
+    unsafe fn set_debug_name(&self, _name: &str) {
+        // Acceleration structures live inside of a buffer, which can be named instead
+    }
+}
+
+/// Acceleration structure build inputs
+/// 
+/// Owns the geometry descriptions referenced by the inputs, so they need to be kept alive while the inputs are used
+pub struct BuildInputs {
+    pub inputs: D3D12_BUILD_RAYTRACING_ACCELERATION_STRUCTURE_INPUTS,
+    _geometry:  Vec<D3D12_RAYTRACING_GEOMETRY_DESC>,
+}
+
+impl BuildInputs {
+    pub fn new(inputs: &ral::AccelerationStructureInputs, flags: ral::AccelerationStructureBuildFlags, is_update: bool) -> Self {
+        let mut dx_flags = flags.to_dx();
+        if is_update {
+            dx_flags |= D3D12_RAYTRACING_ACCELERATION_STRUCTURE_BUILD_FLAG_PERFORM_UPDATE;
+        }
+
+        match inputs {
+            ral::AccelerationStructureInputs::BottomLevel(geometry) => {
+                let geometry = geometry.iter().map(get_geometry_desc).collect::<Vec<_>>();
+                Self {
+                    inputs: D3D12_BUILD_RAYTRACING_ACCELERATION_STRUCTURE_INPUTS {
+                        Type: D3D12_RAYTRACING_ACCELERATION_STRUCTURE_TYPE_BOTTOM_LEVEL,
+                        Flags: dx_flags,
+                        NumDescs: geometry.len() as u32,
+                        DescsLayout: D3D12_ELEMENTS_LAYOUT_ARRAY,
+                        Anonymous: D3D12_BUILD_RAYTRACING_ACCELERATION_STRUCTURE_INPUTS_0 { pGeometryDescs: geometry.as_ptr() },
+                    },
+                    _geometry: geometry,
+                }
+            },
+            ral::AccelerationStructureInputs::TopLevel { instances, offset, count } => Self {
+                inputs: D3D12_BUILD_RAYTRACING_ACCELERATION_STRUCTURE_INPUTS {
+                    Type: D3D12_RAYTRACING_ACCELERATION_STRUCTURE_TYPE_TOP_LEVEL,
+                    Flags: dx_flags,
+                    NumDescs: *count,
+                    DescsLayout: D3D12_ELEMENTS_LAYOUT_ARRAY,
+                    Anonymous: D3D12_BUILD_RAYTRACING_ACCELERATION_STRUCTURE_INPUTS_0 { InstanceDescs: instances.gpu_address().at(*offset).as_raw() },
+                },
+                _geometry: Vec::new(),
+            },
+        }
+    }
+}
+
+fn get_geometry_desc(geometry: &ral::RaytracingGeometry) -> D3D12_RAYTRACING_GEOMETRY_DESC {
+    match &geometry.data {
+        ral::RaytracingGeometryData::Triangles(triangles) => {
+            let (index_format, index_count, index_buffer) = match &triangles.indices {
+                Some(indices) => (indices.format.to_dx(), indices.count, indices.buffer.gpu_address().at(indices.offset).as_raw()),
+                None => (DXGI_FORMAT_UNKNOWN, 0, 0),
+            };
+
+            D3D12_RAYTRACING_GEOMETRY_DESC {
+                Type: D3D12_RAYTRACING_GEOMETRY_TYPE_TRIANGLES,
+                Flags: geometry.flags.to_dx(),
+                Anonymous: D3D12_RAYTRACING_GEOMETRY_DESC_0 { Triangles: D3D12_RAYTRACING_GEOMETRY_TRIANGLES_DESC {
+                    Transform3x4: triangles.transform.as_ref().map_or(0, |(buffer, offset)| buffer.gpu_address().at(*offset).as_raw()),
+                    IndexFormat: index_format,
+                    VertexFormat: triangles.vertex_format.to_dx(),
+                    IndexCount: index_count,
+                    VertexCount: triangles.vertex_count,
+                    IndexBuffer: index_buffer,
+                    VertexBuffer: D3D12_GPU_VIRTUAL_ADDRESS_AND_STRIDE {
+                        StartAddress: triangles.vertex_buffer.gpu_address().at(triangles.vertex_offset).as_raw(),
+                        StrideInBytes: triangles.vertex_stride,
+                    },
+                } },
+            }
+        },
+        ral::RaytracingGeometryData::Aabbs(aabbs) => D3D12_RAYTRACING_GEOMETRY_DESC {
+            Type: D3D12_RAYTRACING_GEOMETRY_TYPE_PROCEDURAL_PRIMITIVE_AABBS,
+            Flags: geometry.flags.to_dx(),
+            Anonymous: D3D12_RAYTRACING_GEOMETRY_DESC_0 { AABBs: D3D12_RAYTRACING_GEOMETRY_AABBS_DESC {
+                AABBCount: aabbs.count as u64,
+                AABBs: D3D12_GPU_VIRTUAL_ADDRESS_AND_STRIDE {
+                    StartAddress: aabbs.buffer.gpu_address().at(aabbs.offset).as_raw(),
+                    StrideInBytes: aabbs.stride,
+                },
+            } },
+        },
+    }
+}
+
+pub unsafe fn get_build_sizes(device: &Device, inputs: &ral::AccelerationStructureInputs, flags: ral::AccelerationStructureBuildFlags) -> ral::Result<ral::AccelerationStructureBuildSizes> {
+    let build_inputs = BuildInputs::new(inputs, flags, false);
+    let mut prebuild_info = D3D12_RAYTRACING_ACCELERATION_STRUCTURE_PREBUILD_INFO::default();
+    device.device.GetRaytracingAccelerationStructurePrebuildInfo(&build_inputs.inputs, &mut prebuild_info);
+
+    Ok(ral::AccelerationStructureBuildSizes {
+        size: prebuild_info.ResultDataMaxSizeInBytes,
+        build_scratch_size: prebuild_info.ScratchDataSizeInBytes,
+        update_scratch_size: prebuild_info.UpdateScratchDataSizeInBytes,
+    })
+}
+
+//==============================================================================================================================
+
+pub struct RaytracingPipeline {
+    pub state_object: ID3D12StateObject,
+    pub properties:   ID3D12StateObjectProperties,
+    /// Null-terminated export names used to identify each shader group
+    pub group_names:  Vec<Vec<u16>>,
+}
+
+impl RaytracingPipeline {
+    pub unsafe fn new(device: &Device, desc: &ral::RaytracingPipelineDesc) -> ral::Result<ral::PipelineInterfaceHandle> {
+        // All shaders use `main` as entry point, so each library renames its export to a unique name
+        let entry_point = to_wide("main");
+        let shader_names = (0..desc.shaders.len()).map(|idx| to_wide(&format!("Shader{idx}"))).collect::<Vec<_>>();
+
+        let exports = shader_names.iter().map(|name| D3D12_EXPORT_DESC {
+            Name: PCWSTR(name.as_ptr()),
+            ExportToRename: PCWSTR(entry_point.as_ptr()),
+            Flags: D3D12_EXPORT_FLAG_NONE,
+        }).collect::<Vec<_>>();
+
+        let libraries = desc.shaders.iter().zip(exports.iter()).map(|(shader, export)| D3D12_DXIL_LIBRARY_DESC {
+            DXILLibrary: shader.interface().as_concrete_type::<Shader>().get_dx_bytecode(),
+            NumExports: 1,
+            pExports: export as *const _ as *mut _,
+        }).collect::<Vec<_>>();
+
+        let shader_name_ptr = |idx: Option<u32>| match idx {
+            Some(idx) => PCWSTR(shader_names[idx as usize].as_ptr()),
+            None => PCWSTR::null(),
+        };
+
+        let mut group_names = Vec::with_capacity(desc.groups.len());
+        let mut hit_groups = Vec::new();
+        for (idx, group) in desc.groups.iter().enumerate() {
+            match *group {
+                ral::RaytracingShaderGroup::General(shader) => group_names.push(shader_names[shader as usize].clone()),
+                ral::RaytracingShaderGroup::TrianglesHitGroup { closest_hit, any_hit } => {
+                    group_names.push(to_wide(&format!("Group{idx}")));
+                    hit_groups.push(D3D12_HIT_GROUP_DESC {
+                        HitGroupExport: PCWSTR(group_names[idx].as_ptr()),
+                        Type: D3D12_HIT_GROUP_TYPE_TRIANGLES,
+                        AnyHitShaderImport: shader_name_ptr(any_hit),
+                        ClosestHitShaderImport: shader_name_ptr(closest_hit),
+                        IntersectionShaderImport: PCWSTR::null(),
+                    });
+                },
+                ral::RaytracingShaderGroup::ProceduralHitGroup { closest_hit, any_hit, intersection } => {
+                    group_names.push(to_wide(&format!("Group{idx}")));
+                    hit_groups.push(D3D12_HIT_GROUP_DESC {
+                        HitGroupExport: PCWSTR(group_names[idx].as_ptr()),
+                        Type: D3D12_HIT_GROUP_TYPE_PROCEDURAL_PRIMITIVE,
+                        AnyHitShaderImport: shader_name_ptr(any_hit),
+                        ClosestHitShaderImport: shader_name_ptr(closest_hit),
+                        IntersectionShaderImport: shader_name_ptr(Some(intersection)),
+                    });
+                },
+            }
+        }
+
+        let shader_config = D3D12_RAYTRACING_SHADER_CONFIG {
+            MaxPayloadSizeInBytes: desc.max_payload_size,
+            MaxAttributeSizeInBytes: desc.max_attribute_size,
+        };
+        let pipeline_config = D3D12_RAYTRACING_PIPELINE_CONFIG {
+            MaxTraceRecursionDepth: desc.max_recursion_depth,
+        };
+
+        let pipeline_layout = desc.pipeline_layout.interface().as_concrete_type::<PipelineLayout>();
+        let root_signature = D3D12_GLOBAL_ROOT_SIGNATURE {
+            pGlobalRootSignature: ManuallyDrop::new(Some(pipeline_layout.root_sig.clone())),
+        };
+
+        let mut subobjects = Vec::with_capacity(libraries.len() + hit_groups.len() + 3);
+        for library in &libraries {
+            subobjects.push(D3D12_STATE_SUBOBJECT { Type: D3D12_STATE_SUBOBJECT_TYPE_DXIL_LIBRARY, pDesc: library as *const _ as *const c_void });
+        }
+        for hit_group in &hit_groups {
+            subobjects.push(D3D12_STATE_SUBOBJECT { Type: D3D12_STATE_SUBOBJECT_TYPE_HIT_GROUP, pDesc: hit_group as *const _ as *const c_void });
+        }
+        subobjects.push(D3D12_STATE_SUBOBJECT { Type: D3D12_STATE_SUBOBJECT_TYPE_RAYTRACING_SHADER_CONFIG, pDesc: &shader_config as *const _ as *const c_void });
+        subobjects.push(D3D12_STATE_SUBOBJECT { Type: D3D12_STATE_SUBOBJECT_TYPE_RAYTRACING_PIPELINE_CONFIG, pDesc: &pipeline_config as *const _ as *const c_void });
+        subobjects.push(D3D12_STATE_SUBOBJECT { Type: D3D12_STATE_SUBOBJECT_TYPE_GLOBAL_ROOT_SIGNATURE, pDesc: &root_signature as *const _ as *const c_void });
+
+        let state_object_desc = D3D12_STATE_OBJECT_DESC {
+            Type: D3D12_STATE_OBJECT_TYPE_RAYTRACING_PIPELINE,
+            NumSubobjects: subobjects.len() as u32,
+            pSubobjects: subobjects.as_ptr(),
+        };
+
+        let state_object = device.device.CreateStateObject::<ID3D12StateObject>(&state_object_desc);
+        // Release the reference to the root signature held by the subobject
+        drop(ManuallyDrop::into_inner(root_signature.pGlobalRootSignature));
+        let state_object = state_object.map_err(|err| err.to_ral_error())?;
+        let properties = state_object.cast::<ID3D12StateObjectProperties>().map_err(|err| err.to_ral_error())?;
+
+        Ok(ral::PipelineInterfaceHandle::new(RaytracingPipeline {
+            state_object,
+            properties,
+            group_names,
+        }))
+    }
+
+    pub unsafe fn get_shader_group_handles(&self, first_group: u32, count: u32) -> ral::Result<Vec<u8>> {
+        let handle_size = ral::constants::RAYTRACE_HITGROUP_HANDLE_SIZE as usize;
+        let mut handles = Vec::with_capacity(count as usize * handle_size);
+        for idx in first_group..first_group + count {
+            let name = match self.group_names.get(idx as usize) {
+                Some(name) => name,
+                None => return Err(ral::Error::InvalidParameter(format!("Raytracing shader group index {idx} is out of range"))),
+            };
+            let identifier = self.properties.GetShaderIdentifier(PCWSTR(name.as_ptr()));
+            if identifier.is_null() {
+                return Err(ral::Error::Other(format!("Failed to get the shader identifier for raytracing shader group {idx}")));
+            }
+            handles.extend_from_slice(core::slice::from_raw_parts(identifier as *const u8, handle_size));
+        }
+        Ok(handles)
+    }
+}
+
+impl ral::PipelineInterface for RaytracingPipeline {
+    unsafe fn set_debug_name(&self, name: &str) {
+        set_object_name(&self.state_object, name);
+    }
+}
@@ -40,6 +40,9 @@ impl StaticSampler {
 }
 
 impl ral::StaticSamplerInterface for StaticSampler {
+    unsafe fn set_debug_name(&self, _name: &str) {
+        // Static samplers are part of a root signature, so there is no D3D12 object to name
+    }
 }
 
 //==============================================================================================================================
@@ -129,6 +132,9 @@ impl Sampler {
 }
 
 impl ral::SamplerInterface for Sampler {
+    unsafe fn set_debug_name(&self, _name: &str) {
+        // Only a descriptor description is stored, so there is no D3D12 object to name
+    }
 }
 
 
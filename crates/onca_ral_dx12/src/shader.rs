@@ -1,4 +1,4 @@
-use core::ffi::c_void;
+use core::{ffi::c_void, num::NonZeroU32};
 
 use onca_ral as ral;
 use windows::Win32::Graphics::Direct3D12::D3D12_SHADER_BYTECODE;
@@ -27,5 +27,184 @@ impl Shader {
 }
 
 impl ral::ShaderInterface for Shader {
-    
+    fn reflect(&self, shader_type: ral::ShaderType) -> ral::Result<ral::ShaderReflection> {
+        let container = DxilContainer::new(&self.blob)?;
+        let mut reflection = ral::ShaderReflection::new(shader_type);
+
+        // Bindings and thread group size are retrieved from the pipeline state validation part, as this doesn't get stripped with the reflection data
+        let psv = container.get_part(DXIL_PART_PSV0).ok_or(ral::Error::InvalidShaderCode("DXIL container does not contain pipeline state validation data"))?;
+        parse_psv(psv, shader_type, &mut reflection)?;
+
+        if shader_type == ral::ShaderType::Vertex {
+            if let Some(signature) = container.get_part(DXIL_PART_ISG1) {
+                parse_input_signature(signature, DXIL_SIG1_ELEMENT_SIZE, &mut reflection)?;
+            } else if let Some(signature) = container.get_part(DXIL_PART_ISGN) {
+                parse_input_signature(signature, DXIL_SIG_ELEMENT_SIZE, &mut reflection)?;
+            }
+        }
+
+        Ok(reflection)
+    }
+
+    unsafe fn set_debug_name(&self, _name: &str) {
+        // Shaders only exist as bytecode until they are used in a pipeline, so there is no D3D12 object to name
+    }
+}
+
+//==============================================================================================================================
+// DXIL container parsing
+//==============================================================================================================================
+
+const DXIL_CONTAINER_MAGIC : u32 = u32::from_le_bytes(*b"DXBC");
+const DXIL_CONTAINER_HEADER_SIZE : usize = 32;
+const DXIL_PART_PSV0 : u32 = u32::from_le_bytes(*b"PSV0");
+const DXIL_PART_ISG1 : u32 = u32::from_le_bytes(*b"ISG1");
+const DXIL_PART_ISGN : u32 = u32::from_le_bytes(*b"ISGN");
+
+const DXIL_SIG_ELEMENT_SIZE : usize = 24;
+const DXIL_SIG1_ELEMENT_SIZE : usize = 32;
+
+// Size of `PSVRuntimeInfo2`, which is the first version containing the number of threads
+const PSV_RUNTIME_INFO2_SIZE : usize = 48;
+const PSV_RUNTIME_INFO2_NUM_THREADS_OFFSET : usize = 36;
+// Size of `PSVResourceBindInfo1`, which is the first version containing the resource kind
+const PSV_RESOURCE_BIND_INFO1_SIZE : usize = 24;
+
+fn read_u32(data: &[u8], offset: usize) -> ral::Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .ok_or(ral::Error::InvalidShaderCode("Unexpected end of DXIL container data"))
+}
+
+struct DxilContainer<'a> {
+    data:      &'a [u8],
+    num_parts: usize,
+}
+
+impl<'a> DxilContainer<'a> {
+    fn new(data: &'a [u8]) -> ral::Result<Self> {
+        if read_u32(data, 0)? != DXIL_CONTAINER_MAGIC {
+            return Err(ral::Error::InvalidShaderCode("Shader code is not a DXIL container"));
+        }
+        let num_parts = read_u32(data, 28)? as usize;
+        Ok(Self { data, num_parts })
+    }
+
+    fn get_part(&self, fourcc: u32) -> Option<&'a [u8]> {
+        for i in 0..self.num_parts {
+            let offset = read_u32(self.data, DXIL_CONTAINER_HEADER_SIZE + i * 4).ok()? as usize;
+            if read_u32(self.data, offset).ok()? == fourcc {
+                let size = read_u32(self.data, offset + 4).ok()? as usize;
+                return self.data.get(offset + 8..offset + 8 + size);
+            }
+        }
+        None
+    }
+}
+
+fn parse_psv(data: &[u8], shader_type: ral::ShaderType, reflection: &mut ral::ShaderReflection) -> ral::Result<()> {
+    let runtime_info_size = read_u32(data, 0)? as usize;
+    let runtime_info = data.get(4..4 + runtime_info_size).ok_or(ral::Error::InvalidShaderCode("Unexpected end of DXIL container data"))?;
+
+    if runtime_info_size >= PSV_RUNTIME_INFO2_SIZE && matches!(shader_type, ral::ShaderType::Compute | ral::ShaderType::Task | ral::ShaderType::Mesh) {
+        reflection.thread_group_size = Some([
+            read_u32(runtime_info, PSV_RUNTIME_INFO2_NUM_THREADS_OFFSET)?,
+            read_u32(runtime_info, PSV_RUNTIME_INFO2_NUM_THREADS_OFFSET + 4)?,
+            read_u32(runtime_info, PSV_RUNTIME_INFO2_NUM_THREADS_OFFSET + 8)?,
+        ]);
+    }
+
+    let mut offset = 4 + runtime_info_size;
+    let num_resources = read_u32(data, offset)? as usize;
+    if num_resources == 0 {
+        return Ok(());
+    }
+    let bind_info_size = read_u32(data, offset + 4)? as usize;
+    offset += 8;
+
+    for _ in 0..num_resources {
+        let res_type = read_u32(data, offset)?;
+        let space = read_u32(data, offset + 4)?;
+        let lower_bound = read_u32(data, offset + 8)?;
+        let upper_bound = read_u32(data, offset + 12)?;
+        let res_kind = if bind_info_size >= PSV_RESOURCE_BIND_INFO1_SIZE { read_u32(data, offset + 16)? } else { 0 };
+        offset += bind_info_size;
+
+        let binding_type = get_binding_type(res_type, res_kind)?;
+        let count = if upper_bound == u32::MAX { None } else { NonZeroU32::new(upper_bound - lower_bound + 1) };
+
+        reflection.add_binding(space, ral::ShaderBinding {
+            name: None,
+            binding_type,
+            binding: lower_bound,
+            count,
+        });
+    }
+    Ok(())
+}
+
+fn get_binding_type(res_type: u32, res_kind: u32) -> ral::Result<ral::ShaderBindingType> {
+    // `PSVResourceKind`
+    const KIND_TYPED_BUFFER : u32 = 10;
+    const KIND_RT_ACCELERATION_STRUCTURE : u32 = 16;
+
+    // `PSVResourceType`
+    Ok(match res_type {
+        1 => ral::ShaderBindingType::Sampler,
+        2 => ral::ShaderBindingType::ConstantBuffer,
+        3 if res_kind == KIND_TYPED_BUFFER => ral::ShaderBindingType::ConstantTexelBuffer,
+        3 => ral::ShaderBindingType::SampledTexture,
+        4 if res_kind == KIND_RT_ACCELERATION_STRUCTURE => ral::ShaderBindingType::AccelerationStructure,
+        4 | 5 => ral::ShaderBindingType::ReadOnlyStorageBuffer,
+        6 if res_kind == KIND_TYPED_BUFFER => ral::ShaderBindingType::StorageTexelBuffer,
+        6 => ral::ShaderBindingType::StorageTexture,
+        7 | 8 | 9 => ral::ShaderBindingType::StorageBuffer,
+        _ => return Err(ral::Error::InvalidShaderCode("Unknown DXIL resource type")),
+    })
+}
+
+fn parse_input_signature(data: &[u8], element_size: usize, reflection: &mut ral::ShaderReflection) -> ral::Result<()> {
+    let num_elements = read_u32(data, 0)? as usize;
+    let elements_offset = read_u32(data, 4)? as usize;
+
+    // Signature v1 elements are prefixed by the stream index
+    let base = if element_size == DXIL_SIG1_ELEMENT_SIZE { 4 } else { 0 };
+
+    for i in 0..num_elements {
+        let offset = elements_offset + i * element_size + base;
+        let name_offset = read_u32(data, offset)? as usize;
+        let semantic_index = read_u32(data, offset + 4)?;
+        let system_value = read_u32(data, offset + 8)?;
+        let component_type = read_u32(data, offset + 12)?;
+        let register = read_u32(data, offset + 16)?;
+        let mask = *data.get(offset + 20).ok_or(ral::Error::InvalidShaderCode("Unexpected end of DXIL container data"))?;
+
+        // System values, like `SV_VertexID`, are not provided by vertex buffers
+        if system_value != 0 {
+            continue;
+        }
+
+        let name_bytes = data.get(name_offset..).ok_or(ral::Error::InvalidShaderCode("Unexpected end of DXIL container data"))?;
+        let name_len = name_bytes.iter().position(|&c| c == 0).unwrap_or(name_bytes.len());
+        let semantic = String::from_utf8_lossy(&name_bytes[..name_len]).to_ascii_uppercase();
+
+        // `DxilProgramSigCompType`
+        let data_type = match component_type {
+            1 | 4 | 7 => ral::VertexDataType::Uint,
+            2 | 5 | 8 => ral::VertexDataType::SInt,
+            3 | 6 | 9 => ral::VertexDataType::SFloat,
+            _ => return Err(ral::Error::InvalidShaderCode("Unknown DXIL signature component type")),
+        };
+
+        reflection.vertex_inputs.push(ral::ShaderVertexInput {
+            semantic,
+            semantic_index: semantic_index as u8,
+            location: register,
+            components: (8 - mask.leading_zeros()) as u8,
+            data_type,
+        });
+    }
+
+    reflection.vertex_inputs.sort_by_key(|input| input.location);
+    Ok(())
 }
@@ -1,9 +1,16 @@
-use core::{ptr::null_mut, time::Duration};
+use core::{ptr::null_mut, time::Duration, ffi::c_void, mem::size_of};
 
 use onca_common::{prelude::*, sync::Mutex};
 use onca_ral as ral;
 use ral::{FenceInterface, HandleImpl};
-use windows::{Win32::{Graphics::Dxgi::{*, Common::DXGI_SAMPLE_DESC}, Foundation::{RECT, POINT, FALSE}}, core::ComInterface};
+use windows::{
+    Win32::{
+        Graphics::{Dxgi::{*, Common::DXGI_SAMPLE_DESC}, Direct3D::WKPDID_D3DDebugObjectName},
+        Foundation::{RECT, POINT, FALSE, HANDLE, CloseHandle, WAIT_TIMEOUT, WAIT_FAILED, GetLastError},
+        System::Threading::{WaitForSingleObject, INFINITE},
+    },
+    core::ComInterface
+};
 
 use crate::{utils::*, device::Device, physical_device::PhysicalDevice, texture::Texture, fence::Fence, command_queue::CommandQueue};
 
@@ -13,13 +20,14 @@ pub struct SwapchainDynamic {
 }
 
 pub struct SwapChain {
-    pub swap_chain:  IDXGISwapChain3,
-    pub fence:       Fence,
-    pub dynamic:     Mutex<SwapchainDynamic>
+    pub swap_chain:       IDXGISwapChain3,
+    pub fence:            Fence,
+    pub latency_waitable: HANDLE,
+    pub dynamic:          Mutex<SwapchainDynamic>
 }
 
 impl SwapChain {
-    const FLAGS: u32 = DXGI_SWAP_CHAIN_FLAG_ALLOW_TEARING.0 as u32;
+    const FLAGS: u32 = DXGI_SWAP_CHAIN_FLAG_ALLOW_TEARING.0 as u32 | DXGI_SWAP_CHAIN_FLAG_FRAME_LATENCY_WAITABLE_OBJECT.0 as u32;
 
     pub unsafe fn new(device: &Device, phys_dev: &ral::PhysicalDevice, create_info: &ral::SwapChainDesc) -> ral::Result<(ral::SwapChainInterfaceHandle, ral::api::SwapChainResultInfo)> {
         let dx_phys_dev = phys_dev.handle.as_concrete_type::<PhysicalDevice>();
@@ -46,6 +54,19 @@ impl SwapChain {
         // Disable Alt + Tab, exclusive fullscreen is not really needed with the flip model on a modern version of windows
         dx_phys_dev.factory.MakeWindowAssociation(create_info.window_handle.hwnd(), DXGI_MWA_NO_ALT_ENTER).map_err(|err| err.to_ral_error())?;
 
+        // Color space support can only be queried on an existing swap-chain, so switch the format afterwards if the preferred color space needs it
+        let (format, color_space) = create_info.color_spaces.iter()
+            .find_map(|color_space| Self::find_color_space_format(&swap_chain, *color_space, &create_info.formats).map(|format| (format, *color_space)))
+            .unwrap_or((format, ral::SwapChainColorSpace::SrgbNonLinear));
+        if format != create_info.formats[0] {
+            swap_chain.ResizeBuffers(create_info.num_backbuffers as u32, create_info.width as u32, create_info.height as u32, format.to_dx(), Self::FLAGS).map_err(|err| err.to_ral_error())?;
+        }
+        swap_chain.SetColorSpace1(color_space.to_dx()).map_err(|err| err.to_ral_error())?;
+
+        // The latency of a swap-chain created with a waitable object is controlled by the swap-chain itself, instead of the device
+        swap_chain.SetMaximumFrameLatency(1).map_err(|err| err.to_ral_error())?;
+        let latency_waitable = swap_chain.GetFrameLatencyWaitableObject();
+
         let mut backbuffers = Vec::with_capacity(create_info.num_backbuffers as usize);
 
         for i in 0..create_info.num_backbuffers as u32 {
@@ -70,6 +91,7 @@ impl SwapChain {
         Ok((ral::SwapChainInterfaceHandle::new(SwapChain{
                 swap_chain,
                 fence,
+                latency_waitable,
                 dynamic,
             }),
             ral::api::SwapChainResultInfo {
@@ -80,9 +102,50 @@ impl SwapChain {
                 format: format,
                 backbuffer_usages: usages,
                 present_mode: create_info.present_mode,
+                color_space,
             }
         ))
     }
+
+    /// Find the first format that can be used to present using the given color space
+    unsafe fn find_color_space_format(swap_chain: &IDXGISwapChain3, color_space: ral::SwapChainColorSpace, formats: &[ral::Format]) -> Option<ral::Format> {
+        if !Self::supports_color_space(swap_chain, color_space) {
+            return None;
+        }
+
+        formats.iter().copied().find(|format| match color_space {
+            ral::SwapChainColorSpace::SrgbNonLinear      => true,
+            ral::SwapChainColorSpace::ExtendedSrgbLinear => *format == ral::Format::R16G16B16A16SFloat,
+            ral::SwapChainColorSpace::Hdr10St2084        => *format == ral::Format::R10G10B10A2UNorm,
+        })
+    }
+
+    unsafe fn supports_color_space(swap_chain: &IDXGISwapChain3, color_space: ral::SwapChainColorSpace) -> bool {
+        match swap_chain.CheckColorSpaceSupport(color_space.to_dx()) {
+            Ok(support) => support & DXGI_SWAP_CHAIN_COLOR_SPACE_SUPPORT_FLAG_PRESENT.0 as u32 != 0,
+            Err(_) => false,
+        }
+    }
+
+    unsafe fn resize_buffers(&self, num_backbuffers: u8, width: u16, height: u16, format: ral::Format) -> ral::Result<Vec<ral::TextureInterfaceHandle>> {
+        let mut dynamic = self.dynamic.lock();
+        // Set wait value for all backbuffers
+        let cur_fence_value = *dynamic.frame_values.iter().reduce(|a, b| a.max(b)).unwrap();
+        for fence_value in &mut dynamic.frame_values {
+            *fence_value = cur_fence_value;
+        }
+
+        self.swap_chain.ResizeBuffers(num_backbuffers as u32, width as u32, height as u32, format.to_dx(), Self::FLAGS).map_err(|err| err.to_ral_error())?;
+
+        let mut backbuffers = Vec::with_capacity(num_backbuffers as usize);
+        for i in 0..num_backbuffers as u32 {
+            let resource = self.swap_chain.GetBuffer(i).map_err(|err| err.to_ral_error())?;
+            backbuffers.push(ral::TextureInterfaceHandle::new(Texture {
+                resource
+            }));
+        }
+        Ok(backbuffers)
+    }
 }
 
 impl ral::SwapChainInterface for SwapChain {
@@ -177,27 +240,126 @@ impl ral::SwapChainInterface for SwapChain {
     }
     
     unsafe fn resize(&self, _device: &ral::DeviceHandle, params: ral::api::SwapChainChangeParams) -> ral::Result<ral::api::SwapChainResizeResultInfo> {
-        let mut dynamic = self.dynamic.lock();
-        // Set wait value for all backbuffers
-        let cur_fence_value = *dynamic.frame_values.iter().reduce(|a, b| a.max(b)).unwrap();
-        for fence_value in &mut dynamic.frame_values {
-            *fence_value = cur_fence_value;
-        }
+        let backbuffers = self.resize_buffers(params.num_backbuffers, params.width, params.height, params.format)?;
+        Ok(ral::api::SwapChainResizeResultInfo {
+            backbuffers,
+            width: params.width,
+            height: params.height,
+        })
+    }
 
-        self.swap_chain.ResizeBuffers(params.num_backbuffers as u32, params.width as u32, params.height as u32, params.format.to_dx(), Self::FLAGS).map_err(|err| err.to_ral_error())?;
+    unsafe fn change_color_space(&self, _device: &ral::DeviceHandle, color_space: ral::SwapChainColorSpace, formats: &[ral::Format], params: ral::api::SwapChainChangeParams) -> ral::Result<ral::api::SwapChainResultInfo> {
+        let format = Self::find_color_space_format(&self.swap_chain, color_space, formats).ok_or(ral::Error::UnsupportedColorSpace(color_space))?;
 
-        let mut backbuffers = Vec::with_capacity(params.num_backbuffers as usize);
-        for i in 0..params.num_backbuffers as u32 {
-            let resource = self.swap_chain.GetBuffer(i).map_err(|err| err.to_ral_error())?;
-            backbuffers.push(ral::TextureInterfaceHandle::new(Texture {
-                resource
-            }));
-        }
+        let backbuffers = self.resize_buffers(params.num_backbuffers, params.width, params.height, format)?;
+        self.swap_chain.SetColorSpace1(color_space.to_dx()).map_err(|err| err.to_ral_error())?;
+        // Metadata belongs to the previous color space
+        _ = ral::SwapChainInterface::set_hdr_metadata(self, None);
 
-        Ok(ral::api::SwapChainResizeResultInfo {
+        Ok(ral::api::SwapChainResultInfo {
             backbuffers,
             width: params.width,
             height: params.height,
+            num_backbuffers: params.num_backbuffers,
+            format,
+            backbuffer_usages: params.backbuffer_usages,
+            present_mode: params.present_mode,
+            color_space,
+        })
+    }
+
+    unsafe fn supports_color_space(&self, color_space: ral::SwapChainColorSpace, formats: &[ral::Format]) -> ral::Result<bool> {
+        Ok(Self::find_color_space_format(&self.swap_chain, color_space, formats).is_some())
+    }
+
+    unsafe fn set_hdr_metadata(&self, metadata: Option<&ral::HdrMetadata>) -> ral::Result<()> {
+        let swap_chain = self.swap_chain.cast::<IDXGISwapChain4>().map_err(|err| err.to_ral_error())?;
+        let result = match metadata {
+            Some(metadata) => {
+                // Chromaticities are stored in increments of 0.00002, the minimum luminance in increments of 0.0001 nits
+                let to_chromaticity = |xy: [f32; 2]| [(xy[0] * 50000.0) as u16, (xy[1] * 50000.0) as u16];
+                let dx_metadata = DXGI_HDR_METADATA_HDR10 {
+                    RedPrimary: to_chromaticity(metadata.red_primary),
+                    GreenPrimary: to_chromaticity(metadata.green_primary),
+                    BluePrimary: to_chromaticity(metadata.blue_primary),
+                    WhitePoint: to_chromaticity(metadata.white_point),
+                    MaxMasteringLuminance: metadata.max_mastering_luminance as u32,
+                    MinMasteringLuminance: (metadata.min_mastering_luminance * 10000.0) as u32,
+                    MaxContentLightLevel: metadata.max_content_light_level as u16,
+                    MaxFrameAverageLightLevel: metadata.max_frame_average_light_level as u16,
+                };
+                swap_chain.SetHDRMetaData(DXGI_HDR_METADATA_TYPE_HDR10, size_of::<DXGI_HDR_METADATA_HDR10>() as u32, Some(&dx_metadata as *const _ as *const c_void))
+            },
+            None => swap_chain.SetHDRMetaData(DXGI_HDR_METADATA_TYPE_NONE, 0, None),
+        };
+        result.map_err(|err| err.to_ral_error())
+    }
+
+    unsafe fn get_output_info(&self) -> ral::Result<ral::SwapChainOutputInfo> {
+        let output = self.swap_chain.GetContainingOutput().map_err(|err| err.to_ral_error())?;
+        let output = output.cast::<IDXGIOutput6>().map_err(|err| err.to_ral_error())?;
+        let desc = output.GetDesc1().map_err(|err| err.to_ral_error())?;
+
+        let color_spaces = [ral::SwapChainColorSpace::SrgbNonLinear, ral::SwapChainColorSpace::ExtendedSrgbLinear, ral::SwapChainColorSpace::Hdr10St2084]
+            .into_iter()
+            .filter(|color_space| Self::supports_color_space(&self.swap_chain, *color_space))
+            .collect();
+
+        // The output only reports its color space as HDR10 when HDR is enabled in the OS
+        let hdr = if desc.ColorSpace == DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020 {
+            Some(ral::OutputHdrInfo {
+                red_primary: desc.RedPrimary,
+                green_primary: desc.GreenPrimary,
+                blue_primary: desc.BluePrimary,
+                white_point: desc.WhitePoint,
+                min_luminance: desc.MinLuminance,
+                max_luminance: desc.MaxLuminance,
+                max_full_frame_luminance: desc.MaxFullFrameLuminance,
+            })
+        } else {
+            None
+        };
+
+        Ok(ral::SwapChainOutputInfo {
+            color_spaces,
+            bits_per_color: desc.BitsPerColor as u8,
+            hdr,
         })
     }
+
+    unsafe fn get_present_statistics(&self) -> ral::Result<ral::api::PresentStatisticsResultInfo> {
+        let mut stats = DXGI_FRAME_STATISTICS::default();
+        self.swap_chain.GetFrameStatistics(&mut stats).map_err(|err| err.to_ral_error())?;
+
+        Ok(ral::api::PresentStatisticsResultInfo {
+            present_count: stats.PresentCount,
+            present_refresh_count: stats.PresentRefreshCount,
+            present_time: stats.SyncQPCTime as u64,
+        })
+    }
+
+    unsafe fn set_max_frame_latency(&self, latency: u8) -> ral::Result<()> {
+        self.swap_chain.SetMaximumFrameLatency(latency as u32).map_err(|err| err.to_ral_error())
+    }
+
+    unsafe fn wait_for_present_ready(&self, timeout: Duration) -> ral::Result<bool> {
+        // Timeouts that don't fit in a u32 would wrap around to a short timeout, so clamp them to an infinite wait
+        let timeout = timeout.as_millis().min(INFINITE as u128) as u32;
+        match WaitForSingleObject(self.latency_waitable, timeout) {
+            WAIT_FAILED => Err(ral::Error::Other(GetLastError().unwrap_err().to_string())),
+            WAIT_TIMEOUT => Ok(false),
+            _ => Ok(true),
+        }
+    }
+
+    unsafe fn set_debug_name(&self, name: &str) {
+        // DXGI objects don't have `SetName`, so the name is stored as private data, which expects a non-null-terminated narrow string
+        _ = self.swap_chain.SetPrivateData(&WKPDID_D3DDebugObjectName, name.len() as u32, name.as_ptr() as *const _);
+    }
+}
+
+impl Drop for SwapChain {
+    fn drop(&mut self) {
+        _ = unsafe { CloseHandle(self.latency_waitable) };
+    }
 }
\ No newline at end of file
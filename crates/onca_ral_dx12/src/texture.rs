@@ -2,10 +2,10 @@ use core::mem::ManuallyDrop;
 use std::sync::Arc;
 
 use onca_ral as ral;
-use ral::HandleImpl;
-use windows::{Win32::Graphics::Direct3D12::*, core::ComInterface};
+use ral::{ApiMemoryRequest, HandleImpl};
+use windows::{Win32::Graphics::{Direct3D12::*, Dxgi::Common::DXGI_SAMPLE_DESC}, core::ComInterface};
 
-use crate::{descriptors::RTVAndDSVDescriptorHeap, utils::{calculate_subresource, ToDx}, device::Device};
+use crate::{descriptors::RTVAndDSVDescriptorHeap, utils::{calculate_subresource, ToDx, ToRalError}, device::Device, memory::MemoryHeap};
 
 
 //==============================================================================================================================
@@ -18,11 +18,146 @@ pub struct Texture {
 }
 
 impl Texture {
+    pub unsafe fn new(device: &Device, desc: &ral::TextureDesc, alloc: &ral::GpuAllocator) -> ral::Result<(ral::TextureInterfaceHandle, ral::GpuAllocation)> {
+        let resource_desc = Self::get_resource_desc(desc);
+        let alloc_info = device.device.GetResourceAllocationInfo2(0, 1, &resource_desc, None);
+
+        // Render targets and depth stencils are commonly large and long lived, so give them their own memory when possible
+        let api_req = ApiMemoryRequest {
+            prefer_dedicated: desc.usage.intersects(ral::TextureUsage::ColorAttachment | ral::TextureUsage::DepthStencilAttachment),
+            require_dedicated: false,
+            alignment: alloc_info.Alignment,
+            memory_types: vec![ral::MemoryType::Gpu],
+        };
+
+        let allocation = alloc.alloc(alloc_info.SizeInBytes, desc.alloc_desc, api_req)?;
+        let resource = Self::create_placed_resource(device, &resource_desc, allocation.heap(), allocation.offset())?;
+        Ok((ral::TextureInterfaceHandle::new(Texture { resource }), allocation))
+    }
+
+    pub unsafe fn new_placed(device: &Device, desc: &ral::TextureDesc, heap: &ral::MemoryHeapHandle, offset: u64) -> ral::Result<ral::TextureInterfaceHandle> {
+        let resource_desc = Self::get_resource_desc(desc);
+        let resource = Self::create_placed_resource(device, &resource_desc, heap, offset)?;
+        Ok(ral::TextureInterfaceHandle::new(Texture { resource }))
+    }
+
+    pub unsafe fn get_allocation_info(device: &Device, desc: &ral::TextureDesc) -> ral::ResourceAllocationInfo {
+        let resource_desc = Self::get_resource_desc(desc);
+        let alloc_info = device.device.GetResourceAllocationInfo2(0, 1, &resource_desc, None);
+        ral::ResourceAllocationInfo { size: alloc_info.SizeInBytes, alignment: alloc_info.Alignment }
+    }
+
+    fn get_resource_desc(desc: &ral::TextureDesc) -> D3D12_RESOURCE_DESC1 {
+        let (dimension, width, height, depth_or_layers) = Self::get_dimension_and_size(desc.size);
+        let flags = Self::get_resource_flags(desc.usage);
+
+        D3D12_RESOURCE_DESC1 {
+            Dimension: dimension,
+            Alignment: 0,
+            Width: width as u64,
+            Height: height as u32,
+            DepthOrArraySize: depth_or_layers,
+            MipLevels: desc.mip_levels.get() as u16,
+            Format: desc.format.to_dx(),
+            SampleDesc: DXGI_SAMPLE_DESC { Count: desc.samples.get() as u32, Quality: 0 },
+            Layout: D3D12_TEXTURE_LAYOUT_UNKNOWN,
+            Flags: flags,
+            SamplerFeedbackMipRegion: D3D12_MIP_REGION { Width: 0, Height: 0, Depth: 0 },
+        }
+    }
 
+    unsafe fn create_placed_resource(device: &Device, resource_desc: &D3D12_RESOURCE_DESC1, heap: &ral::MemoryHeapHandle, offset: u64) -> ral::Result<ID3D12Resource2> {
+        let heap = heap.interface().as_concrete_type::<MemoryHeap>();
+
+        let mut resource : Option<ID3D12Resource2> = None;
+        device.device.CreatePlacedResource2(
+            &heap.heap, offset,
+            resource_desc,
+            D3D12_BARRIER_LAYOUT_UNDEFINED,
+            None,
+            None,
+            &mut resource
+        ).map_err(|err| err.to_ral_error())?;
+        // SAFETY: if we get here, the optional contains a valid resource
+        Ok(resource.unwrap_unchecked())
+    }
 
+    pub unsafe fn new_sparse(device: &Device, desc: &ral::SparseTextureDesc) -> ral::Result<(ral::TextureInterfaceHandle, ral::SparseTextureInfo)> {
+        let (dimension, width, height, depth_or_layers) = Self::get_dimension_and_size(desc.size);
+        let flags = Self::get_resource_flags(desc.usage);
+
+        // Reserved resources always need to use the 64KiB undefined swizzle layout
+        let resource_desc = D3D12_RESOURCE_DESC {
+            Dimension: dimension,
+            Alignment: D3D12_DEFAULT_RESOURCE_PLACEMENT_ALIGNMENT as u64,
+            Width: width as u64,
+            Height: height as u32,
+            DepthOrArraySize: depth_or_layers,
+            MipLevels: desc.mip_levels.get() as u16,
+            Format: desc.format.to_dx(),
+            SampleDesc: DXGI_SAMPLE_DESC { Count: 1, Quality: 0 },
+            Layout: D3D12_TEXTURE_LAYOUT_64KB_UNDEFINED_SWIZZLE,
+            Flags: flags,
+        };
+
+        let mut resource : Option<ID3D12Resource2> = None;
+        device.device.CreateReservedResource2(
+            &resource_desc,
+            D3D12_BARRIER_LAYOUT_UNDEFINED,
+            None,
+            None,
+            None,
+            &mut resource
+        ).map_err(|err| err.to_ral_error())?;
+        // SAFETY: if we get here, the optional contains a valid resource
+        let resource = resource.unwrap_unchecked();
+
+        let mut total_tiles = 0;
+        let mut packed_mip_info = D3D12_PACKED_MIP_INFO::default();
+        let mut tile_shape = D3D12_TILE_SHAPE::default();
+        device.device.GetResourceTiling(&resource, Some(&mut total_tiles), Some(&mut packed_mip_info), Some(&mut tile_shape), None, 0, core::ptr::null_mut());
+
+        let info = ral::SparseTextureInfo {
+            tile_shape: ral::SparseTileShape {
+                width: tile_shape.WidthInTexels,
+                height: tile_shape.HeightInTexels,
+                depth: tile_shape.DepthInTexels,
+            },
+            num_standard_mips: packed_mip_info.NumStandardMips,
+            num_packed_mips: packed_mip_info.NumPackedMips,
+            num_tiles_for_packed_mips: packed_mip_info.NumTilesForPackedMips,
+            total_tiles,
+        };
+        Ok((ral::TextureInterfaceHandle::new(Texture { resource }), info))
+    }
     
     // Helpers
 
+    fn get_dimension_and_size(size: ral::TextureSize) -> (D3D12_RESOURCE_DIMENSION, u16, u16, u16) {
+        match size {
+            ral::TextureSize::Size1D { width, layers }         => (D3D12_RESOURCE_DIMENSION_TEXTURE1D, width.get(), 1           , layers.get()),
+            ral::TextureSize::Size2D { width, height, layers } => (D3D12_RESOURCE_DIMENSION_TEXTURE2D, width.get(), height.get(), layers.get()),
+            ral::TextureSize::Size3D { width, height, depth }  => (D3D12_RESOURCE_DIMENSION_TEXTURE3D, width.get(), height.get(), depth.get()),
+        }
+    }
+
+    fn get_resource_flags(usage: ral::TextureUsage) -> D3D12_RESOURCE_FLAGS {
+        let mut flags = D3D12_RESOURCE_FLAG_NONE;
+        if usage.contains(ral::TextureUsage::ColorAttachment) {
+            flags |= D3D12_RESOURCE_FLAG_ALLOW_RENDER_TARGET;
+        }
+        if usage.contains(ral::TextureUsage::DepthStencilAttachment) {
+            flags |= D3D12_RESOURCE_FLAG_ALLOW_DEPTH_STENCIL;
+            if !usage.contains(ral::TextureUsage::Sampled) {
+                flags |= D3D12_RESOURCE_FLAG_DENY_SHADER_RESOURCE;
+            }
+        }
+        if usage.contains(ral::TextureUsage::Storage) {
+            flags |= D3D12_RESOURCE_FLAG_ALLOW_UNORDERED_ACCESS;
+        }
+        flags
+    }
+
     pub unsafe fn get_texture_copy_location(&self, format: ral::Format, layers: u16, mips: u8, subresource_index: ral::TextureSubresourceIndex) -> D3D12_TEXTURE_COPY_LOCATION {
         // TODO: layer from aspect
         let (aspect, mip, layer) = match subresource_index {
@@ -56,6 +191,10 @@ impl ral::TextureInterface for Texture {
     unsafe fn create_render_texture_view(&self, device: &ral::DeviceHandle, texture: &ral::TextureHandle, desc: &ral::RenderTargetViewDesc) -> ral::Result<ral::RenderTargetViewInterfaceHandle> {
         RenderTargetView::new(device, texture, desc)
     }
+
+    unsafe fn set_debug_name(&self, name: &str) {
+        set_object_name(&self.resource, name);
+    }
 }
 
 //==============================================================================================================================
@@ -93,7 +232,7 @@ impl RenderTargetView {
                 }
             ),
             ral::RenderTargetViewType::View2DMS => (
-                D3D12_RTV_DIMENSION_TEXTURE2D,
+                D3D12_RTV_DIMENSION_TEXTURE2DMS,
                 D3D12_RENDER_TARGET_VIEW_DESC_0 {
                     Texture2DMS: D3D12_TEX2DMS_RTV { UnusedField_NothingToDefine: 0 }
                 }
@@ -155,7 +294,11 @@ impl RenderTargetView {
     }
 }
 
-impl ral::RenderTargetViewInterface for RenderTargetView {}
+impl ral::RenderTargetViewInterface for RenderTargetView {
+    unsafe fn set_debug_name(&self, _name: &str) {
+        // Render target views are descriptors in a shared heap, so there is no D3D12 object to name
+    }
+}
 
 impl Drop for RenderTargetView {
     fn drop(&mut self) {
@@ -280,7 +423,11 @@ impl SampledTextureView {
     }
 }
 
-impl ral::SampledTextureViewInterface for SampledTextureView {}
+impl ral::SampledTextureViewInterface for SampledTextureView {
+    unsafe fn set_debug_name(&self, _name: &str) {
+        // Only a descriptor description is stored, so there is no D3D12 object to name
+    }
+}
 
 //--------------------------------------------------------------
 
@@ -350,7 +497,9 @@ impl StorageTextureView {
 }
 
 impl ral::StorageTextureViewInterface for StorageTextureView {
-
+    unsafe fn set_debug_name(&self, _name: &str) {
+        // Only a descriptor description is stored, so there is no D3D12 object to name
+    }
 }
 
 //==============================================================================================================================
@@ -1,5 +1,5 @@
 use windows::{
-    core::{Error as WinError, HRESULT, PCSTR},
+    core::{Error as WinError, HRESULT, PCSTR, PCWSTR},
     Win32::{Graphics::{
         Direct3D::*,
         Dxgi::{*, Common::*},
@@ -39,6 +39,10 @@ impl MakeDx12Version for ral::Version {
 
 pub fn d3d_error_to_ral_error(err: &WinError) -> ral::Error {
     match err.code() {
+        DXGI_ERROR_DEVICE_REMOVED |
+        DXGI_ERROR_DEVICE_HUNG |
+        DXGI_ERROR_DEVICE_RESET |
+        DXGI_ERROR_DRIVER_INTERNAL_ERROR => ral::Error::DeviceLost,
         // TODO
 
         _ => ral::Error::Unknown,
@@ -241,6 +245,18 @@ impl ToDx for ral::SwapChainAlphaMode {
     }
 }
 
+impl ToDx for ral::SwapChainColorSpace {
+    type DxType = DXGI_COLOR_SPACE_TYPE;
+
+    fn to_dx(&self) -> Self::DxType {
+        match self {
+            ral::SwapChainColorSpace::SrgbNonLinear      => DXGI_COLOR_SPACE_RGB_FULL_G22_NONE_P709,
+            ral::SwapChainColorSpace::ExtendedSrgbLinear => DXGI_COLOR_SPACE_RGB_FULL_G10_NONE_P709,
+            ral::SwapChainColorSpace::Hdr10St2084        => DXGI_COLOR_SPACE_RGB_FULL_G2084_NONE_P2020,
+        }
+    }
+}
+
 impl ToDx for ral::LogicOp {
     type DxType = D3D12_LOGIC_OP;
 
@@ -391,6 +407,47 @@ impl ToDx for ral::PrimitiveTopology {
     }
 }
 
+impl ToDx for ral::ShadingRate {
+    type DxType = D3D12_SHADING_RATE;
+
+    fn to_dx(&self) -> Self::DxType {
+        match self {
+            ral::ShadingRate::Rate1x1 => D3D12_SHADING_RATE_1X1,
+            ral::ShadingRate::Rate1x2 => D3D12_SHADING_RATE_1X2,
+            ral::ShadingRate::Rate2x1 => D3D12_SHADING_RATE_2X1,
+            ral::ShadingRate::Rate2x2 => D3D12_SHADING_RATE_2X2,
+            ral::ShadingRate::Rate2x4 => D3D12_SHADING_RATE_2X4,
+            ral::ShadingRate::Rate4x2 => D3D12_SHADING_RATE_4X2,
+            ral::ShadingRate::Rate4x4 => D3D12_SHADING_RATE_4X4,
+        }
+    }
+}
+
+impl ToDx for ral::ShadingRateCombiner {
+    type DxType = D3D12_SHADING_RATE_COMBINER;
+
+    fn to_dx(&self) -> Self::DxType {
+        match self {
+            ral::ShadingRateCombiner::Passthrough => D3D12_SHADING_RATE_COMBINER_PASSTHROUGH,
+            ral::ShadingRateCombiner::Override    => D3D12_SHADING_RATE_COMBINER_OVERRIDE,
+            ral::ShadingRateCombiner::Min         => D3D12_SHADING_RATE_COMBINER_MIN,
+            ral::ShadingRateCombiner::Max         => D3D12_SHADING_RATE_COMBINER_MAX,
+            ral::ShadingRateCombiner::Sum         => D3D12_SHADING_RATE_COMBINER_SUM,
+        }
+    }
+}
+
+impl ToDx for ral::PredicationOp {
+    type DxType = D3D12_PREDICATION_OP;
+
+    fn to_dx(&self) -> Self::DxType {
+        match self {
+            ral::PredicationOp::EqualZero    => D3D12_PREDICATION_OP_EQUAL_ZERO,
+            ral::PredicationOp::NotEqualZero => D3D12_PREDICATION_OP_NOT_EQUAL_ZERO,
+        }
+    }
+}
+
 impl ToDx for ral::CompareOp {
     type DxType = D3D12_COMPARISON_FUNC;
 
@@ -575,6 +632,45 @@ impl ToDx for ral::IndexFormat {
     }
 }
 
+impl ToDx for ral::AccelerationStructureBuildFlags {
+    type DxType = D3D12_RAYTRACING_ACCELERATION_STRUCTURE_BUILD_FLAGS;
+
+    fn to_dx(&self) -> Self::DxType {
+        let mut flags = D3D12_RAYTRACING_ACCELERATION_STRUCTURE_BUILD_FLAG_NONE;
+        if self.contains(ral::AccelerationStructureBuildFlags::AllowUpdate) {
+            flags |= D3D12_RAYTRACING_ACCELERATION_STRUCTURE_BUILD_FLAG_ALLOW_UPDATE;
+        }
+        if self.contains(ral::AccelerationStructureBuildFlags::AllowCompaction) {
+            flags |= D3D12_RAYTRACING_ACCELERATION_STRUCTURE_BUILD_FLAG_ALLOW_COMPACTION;
+        }
+        if self.contains(ral::AccelerationStructureBuildFlags::PreferFastTrace) {
+            flags |= D3D12_RAYTRACING_ACCELERATION_STRUCTURE_BUILD_FLAG_PREFER_FAST_TRACE;
+        }
+        if self.contains(ral::AccelerationStructureBuildFlags::PreferFastBuild) {
+            flags |= D3D12_RAYTRACING_ACCELERATION_STRUCTURE_BUILD_FLAG_PREFER_FAST_BUILD;
+        }
+        if self.contains(ral::AccelerationStructureBuildFlags::MinimizeMemory) {
+            flags |= D3D12_RAYTRACING_ACCELERATION_STRUCTURE_BUILD_FLAG_MINIMIZE_MEMORY;
+        }
+        flags
+    }
+}
+
+impl ToDx for ral::RaytracingGeometryFlags {
+    type DxType = D3D12_RAYTRACING_GEOMETRY_FLAGS;
+
+    fn to_dx(&self) -> Self::DxType {
+        let mut flags = D3D12_RAYTRACING_GEOMETRY_FLAG_NONE;
+        if self.contains(ral::RaytracingGeometryFlags::Opaque) {
+            flags |= D3D12_RAYTRACING_GEOMETRY_FLAG_OPAQUE;
+        }
+        if self.contains(ral::RaytracingGeometryFlags::NoDuplicateAnyHit) {
+            flags |= D3D12_RAYTRACING_GEOMETRY_FLAG_NO_DUPLICATE_ANYHIT_INVOCATION;
+        }
+        flags
+    }
+}
+
 impl ToDx for ral::ShaderVisibility {
     type DxType = D3D12_SHADER_VISIBILITY;
 
@@ -633,6 +729,27 @@ impl ToDx for ral::TextureComponentMapping {
 
 //==============================================================================================================================
 
+/// Convert a string to a null-terminated wide string
+pub fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(core::iter::once(0)).collect()
+}
+
+/// Convert a null-terminated wide string to a string, returns an empty string for a null pointer
+pub unsafe fn from_wide(s: PCWSTR) -> String {
+    if s.is_null() {
+        String::new()
+    } else {
+        String::from_utf16_lossy(s.as_wide())
+    }
+}
+
+/// Set the debug name of a D3D12 object
+pub unsafe fn set_object_name(object: &ID3D12Object, name: &str) {
+    let name = to_wide(name);
+    // Debug names are only informative, so failing to set one is not an error
+    _ = object.SetName(PCWSTR(name.as_ptr()));
+}
+
 pub fn sync_point_to_dx(sync_point: ral::SyncPoint, access: ral::Access) -> D3D12_BARRIER_SYNC {
     if sync_point.intersects(ral::SyncPoint::Top | ral::SyncPoint::Bottom | ral::SyncPoint::All) {
         return D3D12_BARRIER_SYNC_ALL;
@@ -699,6 +816,9 @@ pub fn sync_point_to_dx(sync_point: ral::SyncPoint, access: ral::Access) -> D3D1
     if sync_point.contains(ral::SyncPoint::AccelerationStructureQuery) {
         barrier_sync |= D3D12_BARRIER_SYNC_EMIT_RAYTRACING_ACCELERATION_STRUCTURE_POSTBUILD_INFO;
     }
+    if sync_point.contains(ral::SyncPoint::Conditional) {
+        barrier_sync |= D3D12_BARRIER_SYNC_PREDICATION;
+    }
     if sync_point.contains(ral::SyncPoint::VideoDecode) {
         barrier_sync |= D3D12_BARRIER_SYNC_VIDEO_DECODE;
     }
@@ -4,7 +4,7 @@ use onca_ral as ral;
 use ash::vk;
 use ral::{HandleImpl, GpuAddress};
 
-use crate::{vulkan::AllocationCallbacks, device::Device, utils::ToRalError, memory::{create_api_memory_request, MemoryHeap}};
+use crate::{vulkan::AllocationCallbacks, device::Device, utils::ToRalError, memory::{create_api_memory_request, MemoryHeap}, debug};
 
 pub struct Buffer {
     pub buffer:          vk::Buffer,
@@ -77,6 +77,10 @@ impl ral::BufferInterface for Buffer {
         let heap = allocation.heap().interface().as_concrete_type::<MemoryHeap>();
         device.unmap_memory(heap.memory());
     }
+
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.buffer, name);
+    }
 }
 
 impl Drop for Buffer {
@@ -2,12 +2,14 @@ use std::sync::{Weak, Arc};
 
 use onca_common::prelude::*;
 use onca_ral as ral;
-use ash::{vk, extensions::ext};
+use ash::{vk, extensions::{ext, khr}};
 use ral::{CommandListType, CommandListBeginFlags, HandleImpl};
 
 use crate::{
     vulkan::AllocationCallbacks,
-    utils::*, texture::{texture_layout_to_vk, Texture, RenderTargetView}, device::Device, pipeline::{Pipeline, PipelineLayout}, buffer::Buffer, descriptor::{DescriptorHeap, DescriptorHeapBuffer},
+    utils::*, texture::{texture_layout_to_vk, Texture, RenderTargetView, SampledTextureView}, device::Device, pipeline::{Pipeline, PipelineLayout}, buffer::Buffer, descriptor::{DescriptorHeap, DescriptorHeapBuffer}, query::QueryPool,
+    raytracing::{AccelerationStructure, BuildGeometry},
+    debug,
 };
 
 
@@ -16,7 +18,13 @@ pub struct CommandPool {
     pub device: Weak<ash::Device>,
     pub alloc_callbacks: AllocationCallbacks,
 
-    pub descriptor_buffer: ext::DescriptorBuffer,
+    pub descriptor_buffer:      ext::DescriptorBuffer,
+    pub mesh_shader:            ext::MeshShader,
+    pub acceleration_structure: khr::AccelerationStructure,
+    pub ray_tracing_pipeline:   khr::RayTracingPipeline,
+    pub fragment_shading_rate:  khr::FragmentShadingRate,
+    pub conditional_rendering:  vk::ExtConditionalRenderingFn,
+    pub vrs_texel_size:         vk::Extent2D,
 }
 
 impl CommandPool {
@@ -48,6 +56,12 @@ impl CommandPool {
             device: Arc::downgrade(&device.device),
             alloc_callbacks: device.alloc_callbacks.clone(),
             descriptor_buffer: device.descriptor_buffer.clone(),
+            mesh_shader: device.mesh_shader.clone(),
+            acceleration_structure: device.acceleration_structure.clone(),
+            ray_tracing_pipeline: device.ray_tracing_pipeline.clone(),
+            fragment_shading_rate: device.fragment_shading_rate.clone(),
+            conditional_rendering: device.conditional_rendering.clone(),
+            vrs_texel_size: device.vrs_texel_size,
         }))
     }
 }
@@ -72,6 +86,12 @@ impl ral::CommandPoolInterface for CommandPool {
             buffer: buffer[0],
             device: self.device.clone(),
             descriptor_buffer: self.descriptor_buffer.clone(),
+            mesh_shader: self.mesh_shader.clone(),
+            acceleration_structure: self.acceleration_structure.clone(),
+            ray_tracing_pipeline: self.ray_tracing_pipeline.clone(),
+            fragment_shading_rate: self.fragment_shading_rate.clone(),
+            conditional_rendering: self.conditional_rendering.clone(),
+            vrs_texel_size: self.vrs_texel_size,
          }))
     }
 
@@ -82,7 +102,9 @@ impl ral::CommandPoolInterface for CommandPool {
         }
     }
 
-    
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.pool, name);
+    }
 }
 
 impl Drop for CommandPool {
@@ -98,7 +120,37 @@ pub struct CommandList {
     pub buffer: vk::CommandBuffer,
     pub device: Weak<ash::Device>,
 
-    pub descriptor_buffer: ext::DescriptorBuffer,
+    pub descriptor_buffer:      ext::DescriptorBuffer,
+    pub mesh_shader:            ext::MeshShader,
+    pub acceleration_structure: khr::AccelerationStructure,
+    pub ray_tracing_pipeline:   khr::RayTracingPipeline,
+    pub fragment_shading_rate:  khr::FragmentShadingRate,
+    pub conditional_rendering:  vk::ExtConditionalRenderingFn,
+    pub vrs_texel_size:         vk::Extent2D,
+}
+
+impl CommandList {
+    /// Point the bindless descriptor sets to the start of the bound resource (buffer 0) and sampler (buffer 1) heaps
+    unsafe fn set_bindless_descriptor_offsets(&self, bind_point: vk::PipelineBindPoint, pipeline_layout: &ral::PipelineLayoutHandle) {
+        let pipeline_layout = pipeline_layout.interface().as_concrete_type::<PipelineLayout>();
+        let Some(bindless_set) = pipeline_layout.bindless_set else { return; };
+
+        self.descriptor_buffer.cmd_set_descriptor_buffer_offsets(self.buffer, bind_point, pipeline_layout.layout, bindless_set, &[0], &[0]);
+        self.descriptor_buffer.cmd_set_descriptor_buffer_offsets(self.buffer, bind_point, pipeline_layout.layout, bindless_set + 1, &[1], &[0]);
+    }
+
+    /// Push constants into the range at `index`, push constants are not tied to a bind point, so this is shared by compute and graphics
+    unsafe fn push_constants(&self, index: u32, offset: u32, data: &[u32], layout: &ral::PipelineLayoutHandle) {
+        let desc = layout.desc();
+        let Some(range) = desc.constant_ranges.as_ref().and_then(|ranges| ranges.get(index as usize)) else { return; };
+
+        let pipeline_layout = layout.interface().as_concrete_type::<PipelineLayout>().layout;
+        let byte_offset = (desc.constant_range_offset(index) + offset) * 4;
+        let bytes = core::slice::from_raw_parts(data.as_ptr() as *const u8, data.len() * 4);
+
+        let device = Weak::upgrade(&self.device).expect("Device was deleted while recoding a command list");
+        device.cmd_push_constants(self.buffer, pipeline_layout, range.visibility.to_vulkan(), byte_offset, bytes);
+    }
 }
 
 impl ral::CommandListInterface for CommandList {
@@ -129,6 +181,22 @@ impl ral::CommandListInterface for CommandList {
         device.end_command_buffer(self.buffer).map_err(|err| err.to_ral_error())
     }
 
+    //==============================================================================================================================
+
+    unsafe fn begin_event(&self, name: &str, color: [f32; 4]) {
+        debug::cmd_begin_label(self.buffer, name, color);
+    }
+
+    unsafe fn end_event(&self) {
+        debug::cmd_end_label(self.buffer);
+    }
+
+    unsafe fn set_marker(&self, name: &str, color: [f32; 4]) {
+        debug::cmd_insert_label(self.buffer, name, color);
+    }
+
+    //==============================================================================================================================
+
     unsafe fn barrier(&self, barriers: &[ral::Barrier], cur_queue_idx: ral::QueueIndex) {
         scoped_alloc!(AllocId::TlsTemp);
         
@@ -466,8 +534,9 @@ impl ral::CommandListInterface for CommandList {
 
     //==============================================================================================================================
     
-    unsafe fn bind_compute_pipeline_layout(&self, _pipeline_layout: &ral::PipelineLayoutHandle) {
-        // Nothing to do here for now
+    unsafe fn bind_compute_pipeline_layout(&self, pipeline_layout: &ral::PipelineLayoutHandle) {
+        self.set_bindless_descriptor_offsets(vk::PipelineBindPoint::COMPUTE, pipeline_layout);
+        self.set_bindless_descriptor_offsets(vk::PipelineBindPoint::RAY_TRACING_KHR, pipeline_layout);
     }
 
     unsafe fn bind_compute_pipeline(&self, pipeline: &ral::PipelineHandle) {
@@ -493,7 +562,16 @@ impl ral::CommandListInterface for CommandList {
 
         self.descriptor_buffer.cmd_set_descriptor_buffer_offsets(
             self.buffer,
-            vk::PipelineBindPoint::GRAPHICS,
+            vk::PipelineBindPoint::COMPUTE,
+            pipeline_layout,
+            index,
+            &buffer_indices,
+            &offsets
+        );
+        // Raytracing pipelines share the compute pipeline layout, but have their own bind point
+        self.descriptor_buffer.cmd_set_descriptor_buffer_offsets(
+            self.buffer,
+            vk::PipelineBindPoint::RAY_TRACING_KHR,
             pipeline_layout,
             index,
             &buffer_indices,
@@ -501,9 +579,96 @@ impl ral::CommandListInterface for CommandList {
         );
     }
 
+    unsafe fn set_compute_constants(&self, index: u32, offset: u32, data: &[u32], layout: &ral::PipelineLayoutHandle) {
+        self.push_constants(index, offset, data, layout);
+    }
+
+    unsafe fn dispatch(&self, group_count_x: u32, group_count_y: u32, group_count_z: u32) {
+        let device = Weak::upgrade(&self.device).expect("Device was deleted while recoding a command list");
+        device.cmd_dispatch(self.buffer, group_count_x, group_count_y, group_count_z)
+    }
+
+    unsafe fn dispatch_indirect(&self, buffer: &ral::BufferHandle, offset: u64) {
+        let device = Weak::upgrade(&self.device).expect("Device was deleted while recoding a command list");
+        let buffer = buffer.interface().as_concrete_type::<Buffer>().buffer;
+        device.cmd_dispatch_indirect(self.buffer, buffer, offset)
+    }
+
+    //==============================================================================================================================
+
+    unsafe fn build_acceleration_structure(&self, info: &ral::AccelerationStructureBuildInfo, src: Option<&ral::AccelerationStructureHandle>) {
+        let geometry = BuildGeometry::new(&info.inputs);
+        let mut build_info = geometry.get_build_info(info.inputs.ty(), info.flags);
+        build_info.dst_acceleration_structure = info.dst.interface().as_concrete_type::<AccelerationStructure>().acceleration_structure;
+        build_info.scratch_data = vk::DeviceOrHostAddressKHR { device_address: info.scratch.gpu_address().at(info.scratch_offset).as_raw() };
+        if let Some(src) = src {
+            build_info.mode = vk::BuildAccelerationStructureModeKHR::UPDATE;
+            build_info.src_acceleration_structure = src.interface().as_concrete_type::<AccelerationStructure>().acceleration_structure;
+        }
+
+        self.acceleration_structure.cmd_build_acceleration_structures(self.buffer, &[build_info], &[&geometry.ranges]);
+    }
+
+    unsafe fn copy_acceleration_structure(&self, dst: &ral::AccelerationStructureHandle, src: &ral::AccelerationStructureHandle, mode: ral::AccelerationStructureCopyMode) {
+        let vk_mode = match mode {
+            ral::AccelerationStructureCopyMode::Clone   => vk::CopyAccelerationStructureModeKHR::CLONE,
+            ral::AccelerationStructureCopyMode::Compact => vk::CopyAccelerationStructureModeKHR::COMPACT,
+        };
+        let copy_info = vk::CopyAccelerationStructureInfoKHR::builder()
+            .src(src.interface().as_concrete_type::<AccelerationStructure>().acceleration_structure)
+            .dst(dst.interface().as_concrete_type::<AccelerationStructure>().acceleration_structure)
+            .mode(vk_mode);
+
+        self.acceleration_structure.cmd_copy_acceleration_structure(self.buffer, &copy_info);
+    }
+
+    unsafe fn write_acceleration_structure_compacted_size(&self, src: &ral::AccelerationStructureHandle, dst: &ral::BufferHandle, dst_offset: u64) {
+        let device = Weak::upgrade(&self.device).expect("Device was deleted while recoding a command list");
+        let vk_src = src.interface().as_concrete_type::<AccelerationStructure>();
+        let dst_buffer = dst.interface().as_concrete_type::<Buffer>().buffer;
+
+        // Vulkan can only write the compacted size to a query, so write it to the acceleration structure's query pool and copy it to the destination
+        device.cmd_reset_query_pool(self.buffer, vk_src.compacted_size_pool, 0, 1);
+        self.acceleration_structure.cmd_write_acceleration_structures_properties(self.buffer, &[vk_src.acceleration_structure], vk::QueryType::ACCELERATION_STRUCTURE_COMPACTED_SIZE_KHR, vk_src.compacted_size_pool, 0);
+        device.cmd_copy_query_pool_results(self.buffer, vk_src.compacted_size_pool, 0, 1, dst_buffer, dst_offset, 8, vk::QueryResultFlags::TYPE_64 | vk::QueryResultFlags::WAIT);
+    }
+
+    unsafe fn bind_raytracing_pipeline(&self, pipeline: &ral::PipelineHandle) {
+        let pipeline = pipeline.interface().as_concrete_type::<Pipeline>().pipeline;
+
+        let device = Weak::upgrade(&self.device).expect("Device was deleted while recoding a command list");
+        device.cmd_bind_pipeline(self.buffer, vk::PipelineBindPoint::RAY_TRACING_KHR, pipeline);
+    }
+
+    unsafe fn trace_rays(&self, regions: &ral::ShaderBindingTableRegions, width: u32, height: u32, depth: u32) {
+        let to_region = |region: &ral::ShaderTableRegion| vk::StridedDeviceAddressRegionKHR {
+            device_address: region.address.as_raw(),
+            stride: region.stride,
+            size: region.size,
+        };
+
+        // Vulkan requires the raygen region's stride to match its size
+        let raygen = vk::StridedDeviceAddressRegionKHR {
+            device_address: regions.raygen.address.as_raw(),
+            stride: regions.raygen.size,
+            size: regions.raygen.size,
+        };
+
+        self.ray_tracing_pipeline.cmd_trace_rays(
+            self.buffer,
+            &raygen,
+            &to_region(&regions.miss),
+            &to_region(&regions.hit_group),
+            &to_region(&regions.callable),
+            width,
+            height,
+            depth
+        );
+    }
+
     //==============================================================================================================================
-    unsafe fn bind_graphics_pipeline_layout(&self, _pipeline_layout: &ral::PipelineLayoutHandle) {
-        // Nothing to do here for now
+    unsafe fn bind_graphics_pipeline_layout(&self, pipeline_layout: &ral::PipelineLayoutHandle) {
+        self.set_bindless_descriptor_offsets(vk::PipelineBindPoint::GRAPHICS, pipeline_layout);
     }
 
     unsafe fn bind_graphics_pipeline(&self, pipeline: &ral::PipelineHandle) {
@@ -537,6 +702,10 @@ impl ral::CommandListInterface for CommandList {
         );
     }
 
+    unsafe fn set_graphics_constants(&self, index: u32, offset: u32, data: &[u32], layout: &ral::PipelineLayoutHandle) {
+        self.push_constants(index, offset, data, layout);
+    }
+
     unsafe fn bind_vertex_buffer(&self, view: ral::VertexBufferView) {
         let device = Weak::upgrade(&self.device).expect("Device was deleted while recoding a command list");
 
@@ -643,6 +812,14 @@ impl ral::CommandListInterface for CommandList {
 
 
 
+        let mut shading_rate_attachment = vk::RenderingFragmentShadingRateAttachmentInfoKHR::default();
+        if let Some(shading_rate) = &rendering_info.shading_rate {
+            shading_rate_attachment.image_view = shading_rate.view.interface().as_concrete_type::<SampledTextureView>().view;
+            shading_rate_attachment.image_layout = texture_layout_to_vk(shading_rate.layout);
+            shading_rate_attachment.shading_rate_attachment_texel_size = self.vrs_texel_size;
+            info = info.push_next(&mut shading_rate_attachment);
+        }
+
         let device = Weak::upgrade(&self.device).expect("Device was deleted while recoding a command list");
         device.cmd_begin_rendering(self.buffer, &info);
     }
@@ -652,6 +829,44 @@ impl ral::CommandListInterface for CommandList {
         device.cmd_end_rendering(self.buffer);
     }
 
+    unsafe fn resolve_texture(&self, src: &ral::TextureHandle, src_subresource: ral::TextureSubresourceIndex, dst: &ral::TextureHandle, dst_subresource: ral::TextureSubresourceIndex) {
+        let src_image = src.interface().as_concrete_type::<Texture>().image;
+        let dst_image = dst.interface().as_concrete_type::<Texture>().image;
+
+        let (src_mip, src_layer) = ral::subresource_mip_and_layer(src_subresource);
+        let (dst_mip, dst_layer) = ral::subresource_mip_and_layer(dst_subresource);
+
+        // Multisampled textures only have a single mip, so the size of the resolve is the full size of the source
+        let (width, height, _, _) = src.size().as_tuple();
+        let aspect = src.format().aspect().to_vulkan();
+
+        let region = vk::ImageResolve2::builder()
+            .src_subresource(vk::ImageSubresourceLayers {
+                aspect_mask: aspect,
+                mip_level: src_mip as u32,
+                base_array_layer: src_layer as u32,
+                layer_count: 1,
+            })
+            .dst_subresource(vk::ImageSubresourceLayers {
+                aspect_mask: aspect,
+                mip_level: dst_mip as u32,
+                base_array_layer: dst_layer as u32,
+                layer_count: 1,
+            })
+            .extent(vk::Extent3D { width: width as u32, height: height as u32, depth: 1 })
+            .build();
+
+        let resolve_info = vk::ResolveImageInfo2::builder()
+            .src_image(src_image)
+            .src_image_layout(vk::ImageLayout::TRANSFER_SRC_OPTIMAL)
+            .dst_image(dst_image)
+            .dst_image_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+            .regions(core::slice::from_ref(&region));
+
+        let device = Weak::upgrade(&self.device).expect("Device was deleted while recoding a command list");
+        device.cmd_resolve_image2(self.buffer, &resolve_info);
+    }
+
     unsafe fn set_viewports(&self, viewports: &[ral::Viewport]) {
         const MAX_VIEWPORTS: usize = ral::constants::MAX_VIEWPORT_COUNT as usize;
         let mut vk_viewports = Vec::with_capacity(MAX_VIEWPORTS);
@@ -679,6 +894,10 @@ impl ral::CommandListInterface for CommandList {
         device.cmd_set_primitive_topology(self.buffer, topology.to_vulkan());
     }
 
+    unsafe fn set_shading_rate(&self, rate: ral::ShadingRate, combiners: [ral::ShadingRateCombiner; 2]) {
+        self.fragment_shading_rate.cmd_set_fragment_shading_rate(self.buffer, &rate.to_vulkan(), &[combiners[0].to_vulkan(), combiners[1].to_vulkan()]);
+    }
+
     unsafe fn draw_instanced(&self, vertex_count: u32, instance_count: u32, start_vertex: u32, start_instance: u32) {
         let device = Weak::upgrade(&self.device).expect("Device was deleted while recoding a command list");
         device.cmd_draw(self.buffer, vertex_count, instance_count, start_vertex, start_instance);
@@ -689,4 +908,101 @@ impl ral::CommandListInterface for CommandList {
         device.cmd_draw_indexed(self.buffer, index_count, instance_count, start_index, vertex_offset, start_instance)
     }
 
+    unsafe fn draw_indirect(&self, buffer: &ral::BufferHandle, offset: u64, draw_count: u32) {
+        let device = Weak::upgrade(&self.device).expect("Device was deleted while recoding a command list");
+        let buffer = buffer.interface().as_concrete_type::<Buffer>().buffer;
+        let stride = core::mem::size_of::<ral::DrawIndirectArgs>() as u32;
+        device.cmd_draw_indirect(self.buffer, buffer, offset, draw_count, stride)
+    }
+
+    unsafe fn draw_indexed_indirect(&self, buffer: &ral::BufferHandle, offset: u64, draw_count: u32) {
+        let device = Weak::upgrade(&self.device).expect("Device was deleted while recoding a command list");
+        let buffer = buffer.interface().as_concrete_type::<Buffer>().buffer;
+        let stride = core::mem::size_of::<ral::DrawIndexedIndirectArgs>() as u32;
+        device.cmd_draw_indexed_indirect(self.buffer, buffer, offset, draw_count, stride)
+    }
+
+    unsafe fn draw_indirect_count(&self, buffer: &ral::BufferHandle, offset: u64, count_buffer: &ral::BufferHandle, count_offset: u64, max_draw_count: u32) {
+        let device = Weak::upgrade(&self.device).expect("Device was deleted while recoding a command list");
+        let buffer = buffer.interface().as_concrete_type::<Buffer>().buffer;
+        let count_buffer = count_buffer.interface().as_concrete_type::<Buffer>().buffer;
+        let stride = core::mem::size_of::<ral::DrawIndirectArgs>() as u32;
+        device.cmd_draw_indirect_count(self.buffer, buffer, offset, count_buffer, count_offset, max_draw_count, stride)
+    }
+
+    unsafe fn draw_indexed_indirect_count(&self, buffer: &ral::BufferHandle, offset: u64, count_buffer: &ral::BufferHandle, count_offset: u64, max_draw_count: u32) {
+        let device = Weak::upgrade(&self.device).expect("Device was deleted while recoding a command list");
+        let buffer = buffer.interface().as_concrete_type::<Buffer>().buffer;
+        let count_buffer = count_buffer.interface().as_concrete_type::<Buffer>().buffer;
+        let stride = core::mem::size_of::<ral::DrawIndexedIndirectArgs>() as u32;
+        device.cmd_draw_indexed_indirect_count(self.buffer, buffer, offset, count_buffer, count_offset, max_draw_count, stride)
+    }
+
+    unsafe fn dispatch_mesh(&self, group_count_x: u32, group_count_y: u32, group_count_z: u32) {
+        self.mesh_shader.cmd_draw_mesh_tasks(self.buffer, group_count_x, group_count_y, group_count_z)
+    }
+
+    unsafe fn dispatch_mesh_indirect(&self, buffer: &ral::BufferHandle, offset: u64) {
+        let buffer = buffer.interface().as_concrete_type::<Buffer>().buffer;
+        let stride = core::mem::size_of::<ral::DispatchIndirectArgs>() as u32;
+        self.mesh_shader.cmd_draw_mesh_tasks_indirect(self.buffer, buffer, offset, 1, stride)
+    }
+
+    //==============================================================================================================================
+
+    unsafe fn write_timestamp(&self, query_pool: &ral::QueryPoolHandle, index: u32) {
+        let device = Weak::upgrade(&self.device).expect("Device was deleted while recoding a command list");
+        let pool = query_pool.interface().as_concrete_type::<QueryPool>().pool;
+        device.cmd_write_timestamp2(self.buffer, vk::PipelineStageFlags2::ALL_COMMANDS, pool, index)
+    }
+
+    unsafe fn begin_query(&self, query_pool: &ral::QueryPoolHandle, index: u32) {
+        let device = Weak::upgrade(&self.device).expect("Device was deleted while recoding a command list");
+        let pool = query_pool.interface().as_concrete_type::<QueryPool>().pool;
+        let flags = if query_pool.query_type() == ral::QueryType::Occlusion { vk::QueryControlFlags::PRECISE } else { vk::QueryControlFlags::empty() };
+        device.cmd_begin_query(self.buffer, pool, index, flags)
+    }
+
+    unsafe fn end_query(&self, query_pool: &ral::QueryPoolHandle, index: u32) {
+        let device = Weak::upgrade(&self.device).expect("Device was deleted while recoding a command list");
+        let pool = query_pool.interface().as_concrete_type::<QueryPool>().pool;
+        device.cmd_end_query(self.buffer, pool, index)
+    }
+
+    unsafe fn reset_queries(&self, query_pool: &ral::QueryPoolHandle, first_query: u32, count: u32) {
+        let device = Weak::upgrade(&self.device).expect("Device was deleted while recoding a command list");
+        let pool = query_pool.interface().as_concrete_type::<QueryPool>().pool;
+        device.cmd_reset_query_pool(self.buffer, pool, first_query, count)
+    }
+
+    unsafe fn resolve_queries(&self, query_pool: &ral::QueryPoolHandle, first_query: u32, count: u32, dst: &ral::BufferHandle, dst_offset: u64) {
+        let device = Weak::upgrade(&self.device).expect("Device was deleted while recoding a command list");
+        let pool = query_pool.interface().as_concrete_type::<QueryPool>().pool;
+        let dst_buffer = dst.interface().as_concrete_type::<Buffer>().buffer;
+        let stride = query_pool.query_type().result_size();
+        device.cmd_copy_query_pool_results(self.buffer, pool, first_query, count, dst_buffer, dst_offset, stride, vk::QueryResultFlags::TYPE_64 | vk::QueryResultFlags::WAIT)
+    }
+
+    unsafe fn begin_predication(&self, buffer: &ral::BufferHandle, offset: u64, op: ral::PredicationOp) {
+        let vk_buffer = buffer.interface().as_concrete_type::<Buffer>().buffer;
+        // Vulkan skips commands when the 32-bit predicate is 0, unless the condition is inverted
+        let flags = match op {
+            ral::PredicationOp::EqualZero    => vk::ConditionalRenderingFlagsEXT::empty(),
+            ral::PredicationOp::NotEqualZero => vk::ConditionalRenderingFlagsEXT::INVERTED,
+        };
+
+        let begin_info = vk::ConditionalRenderingBeginInfoEXT::builder()
+            .buffer(vk_buffer)
+            .offset(offset)
+            .flags(flags);
+        (self.conditional_rendering.cmd_begin_conditional_rendering_ext)(self.buffer, &*begin_info);
+    }
+
+    unsafe fn end_predication(&self) {
+        (self.conditional_rendering.cmd_end_conditional_rendering_ext)(self.buffer);
+    }
+
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.buffer, name);
+    }
 }
\ No newline at end of file
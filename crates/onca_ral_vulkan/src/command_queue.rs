@@ -1,15 +1,61 @@
-use std::sync::Weak;
+use std::sync::{Arc, Weak};
 
-use onca_common::prelude::*;
-use ash::vk;
+use onca_common::{prelude::*, sync::Mutex};
+use ash::{vk, extensions::ext};
 use onca_ral as ral;
 use ral::HandleImpl;
 
-use crate::{utils::{ToRalError, ToVulkan}, command_list::CommandList, fence::Fence};
+use crate::{utils::{ToRalError, ToVulkan}, command_list::CommandList, fence::Fence, texture::{Texture, get_sparse_requirements}, memory::MemoryHeap, vulkan::AllocationCallbacks, debug};
+
+/// Timeline semaphore used to order sparse binding operations with other work submitted to a queue
+/// 
+/// Sparse binding operations are not implicitly ordered with command buffer submissions, so each submission signals the semaphore,
+/// sparse binding operations wait for all previous submissions, and submissions wait for the last sparse binding operation.
+/// 
+/// This is shared by all `CommandQueue`s that use the same vulkan queue.
+pub struct QueueOrdering {
+    semaphore:       vk::Semaphore,
+    /// Last value that was signalled by an operation submitted to the queue
+    value:           u64,
+    /// Value signalled by the last sparse binding operation, 0 if no sparse binding operation was submitted to the queue yet
+    last_bind_value: u64,
+    device:          Weak<ash::Device>,
+    alloc_callbacks: AllocationCallbacks,
+}
+
+impl QueueOrdering {
+    pub unsafe fn new(device: &Arc<ash::Device>, alloc_callbacks: &AllocationCallbacks) -> ral::Result<Arc<Mutex<QueueOrdering>>> {
+        let mut typed_create_info = vk::SemaphoreTypeCreateInfo::builder()
+            .semaphore_type(vk::SemaphoreType::TIMELINE);
+
+        let create_info = vk::SemaphoreCreateInfo::builder()
+            .push_next(&mut typed_create_info);
+
+        let semaphore = device.create_semaphore(&create_info, alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.to_ral_error())?;
+        Ok(Arc::new(Mutex::new(QueueOrdering {
+            semaphore,
+            value: 0,
+            last_bind_value: 0,
+            device: Arc::downgrade(device),
+            alloc_callbacks: alloc_callbacks.clone(),
+        })))
+    }
+}
+
+impl Drop for QueueOrdering {
+    fn drop(&mut self) {
+        let device = Weak::upgrade(&self.device).unwrap();
+        unsafe { device.destroy_semaphore(self.semaphore, self.alloc_callbacks.get_some_vk_callbacks()) };
+    }
+}
 
 pub struct CommandQueue {
     pub queue: vk::Queue,
     pub device: Weak<ash::Device>,
+    /// Number of nanoseconds per timestamp tick
+    pub timestamp_period: f32,
+    pub calibrated_timestamps: Option<ext::CalibratedTimestamps>,
+    pub ordering: Arc<Mutex<QueueOrdering>>,
 }
 
 impl ral::CommandQueueInterface for CommandQueue {
@@ -55,6 +101,28 @@ impl ral::CommandQueueInterface for CommandQueue {
             vk_data.push((command_buffer_infos, wait_semaphores, signal_semaphores));
         }
 
+        // The lock is held until the work is submitted, so the ordering values are signalled in submission order
+        let mut ordering = self.ordering.lock();
+        let signal_value = ordering.value + 1;
+        if let Some(first) = vk_data.first_mut() {
+            if ordering.last_bind_value != 0 {
+                first.1.push(vk::SemaphoreSubmitInfo::builder()
+                    .semaphore(ordering.semaphore)
+                    .value(ordering.last_bind_value)
+                    .stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+                    .build()
+                );
+            }
+        }
+        if let Some(last) = vk_data.last_mut() {
+            last.2.push(vk::SemaphoreSubmitInfo::builder()
+                .semaphore(ordering.semaphore)
+                .value(signal_value)
+                .stage_mask(vk::PipelineStageFlags2::ALL_COMMANDS)
+                .build()
+            );
+        }
+
         // Create batches referencing the previously created data
         let mut vk_batches = Vec::with_capacity(batches.len());
         for data in &vk_data {
@@ -70,8 +138,156 @@ impl ral::CommandQueueInterface for CommandQueue {
         let device = Weak::upgrade(&self.device).ok_or(ral::Error::UseAfterDeviceDropped)?;
 
         // Currently we don't use the fence, but check if it could be used for something via the RAL
-        device.queue_submit2(self.queue, &vk_batches, vk::Fence::default()).map_err(|err| err.to_ral_error())
+        device.queue_submit2(self.queue, &vk_batches, vk::Fence::default()).map_err(|err| err.to_ral_error())?;
+        if !vk_batches.is_empty() {
+            ordering.value = signal_value;
+        }
+        Ok(())
     }
 
-    
+    unsafe fn update_tile_mappings(&self, texture: &ral::SparseTexture, mappings: &[ral::SparseTileMapping]) -> ral::Result<()> {
+        scoped_alloc!(AllocId::TlsTemp);
+
+        let device = Weak::upgrade(&self.device).ok_or(ral::Error::UseAfterDeviceDropped)?;
+        let image = texture.texture().interface().as_concrete_type::<Texture>().image;
+        let aspect = texture.texture().format().aspect().to_vulkan();
+        let info = texture.info();
+        let size = texture.size();
+        let sparse_requirements = get_sparse_requirements(&device, image, aspect)?;
+
+        let mut image_binds = Vec::new();
+        let mut opaque_binds = Vec::new();
+        for mapping in mappings {
+            let (memory, memory_offset) = match &mapping.heap {
+                Some(heap) => (heap.interface().as_concrete_type::<MemoryHeap>().memory(), mapping.heap_offset as u64 * ral::constants::SPARSE_TILE_SIZE),
+                None       => (vk::DeviceMemory::null(), 0),
+            };
+
+            match mapping.region {
+                ral::SparseTileRegion::Tiles { subresource, offset, extent } => {
+                    let (mip, layer) = ral::subresource_mip_and_layer(subresource);
+                    let tile_shape = info.tile_shape;
+
+                    let x = offset.x as u32 * tile_shape.width;
+                    let y = offset.y as u32 * tile_shape.height;
+                    let z = offset.z as u32 * tile_shape.depth;
+
+                    // Vulkan expects the extent in texels, which need to be clamped at the edge of the mip
+                    let mip_width = (size.width() as u32 >> mip).max(1);
+                    let mip_height = (size.height() as u32 >> mip).max(1);
+                    let mip_depth = (size.depth() as u32 >> mip).max(1);
+
+                    image_binds.push(vk::SparseImageMemoryBind::builder()
+                        .subresource(vk::ImageSubresource { aspect_mask: aspect, mip_level: mip as u32, array_layer: layer as u32 })
+                        .offset(vk::Offset3D { x: x as i32, y: y as i32, z: z as i32 })
+                        .extent(vk::Extent3D {
+                            width: (extent.width.get() as u32 * tile_shape.width).min(mip_width - x),
+                            height: (extent.height.get() as u32 * tile_shape.height).min(mip_height - y),
+                            depth: (extent.depth.get() as u32 * tile_shape.depth).min(mip_depth - z),
+                        })
+                        .memory(memory)
+                        .memory_offset(memory_offset)
+                        .build()
+                    );
+                },
+                ral::SparseTileRegion::PackedMips { layer } => {
+                    opaque_binds.push(vk::SparseMemoryBind::builder()
+                        .resource_offset(sparse_requirements.image_mip_tail_offset + layer as u64 * sparse_requirements.image_mip_tail_stride)
+                        .size(sparse_requirements.image_mip_tail_size)
+                        .memory(memory)
+                        .memory_offset(memory_offset)
+                        .build()
+                    );
+                },
+            }
+        }
+
+        let image_bind_infos = [vk::SparseImageMemoryBindInfo::builder()
+            .image(image)
+            .binds(&image_binds)
+            .build()
+        ];
+        let opaque_bind_infos = [vk::SparseImageOpaqueMemoryBindInfo::builder()
+            .image(image)
+            .binds(&opaque_binds)
+            .build()
+        ];
+
+        // Sparse binding is not implicitly ordered with other work on the queue, so wait for all previously submitted work,
+        // and signal a value that later submissions will wait on, see `QueueOrdering`.
+        // The lock is held until the bind is submitted, so the ordering values are signalled in submission order
+        let mut ordering = self.ordering.lock();
+        let semaphores = [ordering.semaphore];
+        let wait_values = [ordering.value];
+        let signal_values = [ordering.value + 1];
+        let mut timeline_info = vk::TimelineSemaphoreSubmitInfo::builder()
+            .wait_semaphore_values(&wait_values)
+            .signal_semaphore_values(&signal_values);
+
+        let mut bind_info = vk::BindSparseInfo::builder()
+            .wait_semaphores(&semaphores)
+            .signal_semaphores(&semaphores)
+            .push_next(&mut timeline_info);
+        if !image_binds.is_empty() {
+            bind_info = bind_info.image_binds(&image_bind_infos);
+        }
+        if !opaque_binds.is_empty() {
+            bind_info = bind_info.image_opaque_binds(&opaque_bind_infos);
+        }
+
+        device.queue_bind_sparse(self.queue, &[bind_info.build()], vk::Fence::null()).map_err(|err| err.to_ral_error())?;
+        ordering.value = signal_values[0];
+        ordering.last_bind_value = signal_values[0];
+        Ok(())
+    }
+
+    unsafe fn get_timestamp_calibration(&self) -> ral::Result<ral::TimestampCalibration> {
+        let calibrated_timestamps = self.calibrated_timestamps.as_ref().ok_or(ral::Error::MissingFeature("VK_EXT_calibrated_timestamps"))?;
+        let device = Weak::upgrade(&self.device).ok_or(ral::Error::UseAfterDeviceDropped)?;
+
+        let timestamp_infos = [
+            vk::CalibratedTimestampInfoEXT::builder()
+                .time_domain(vk::TimeDomainEXT::DEVICE)
+                .build(),
+            vk::CalibratedTimestampInfoEXT::builder()
+                .time_domain(CPU_TIME_DOMAIN)
+                .build(),
+        ];
+
+        let (timestamps, _) = calibrated_timestamps.get_calibrated_timestamps(device.handle(), &timestamp_infos).map_err(|err| err.to_ral_error())?;
+
+        Ok(ral::TimestampCalibration {
+            gpu_timestamp: timestamps[0],
+            gpu_frequency: (1_000_000_000.0 / self.timestamp_period as f64) as u64,
+            cpu_timestamp: timestamps[1],
+            cpu_frequency: get_cpu_timestamp_frequency(),
+        })
+    }
+
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.queue, name);
+    }
+}
+
+#[cfg(windows)]
+const CPU_TIME_DOMAIN: vk::TimeDomainEXT = vk::TimeDomainEXT::QUERY_PERFORMANCE_COUNTER;
+#[cfg(not(windows))]
+const CPU_TIME_DOMAIN: vk::TimeDomainEXT = vk::TimeDomainEXT::CLOCK_MONOTONIC;
+
+#[cfg(windows)]
+pub(crate) fn get_cpu_timestamp_frequency() -> u64 {
+    extern "system" {
+        fn QueryPerformanceFrequency(frequency: *mut i64) -> i32;
+    }
+
+    let mut frequency = 0;
+    // Can't fail on windows XP and later
+    unsafe { QueryPerformanceFrequency(&mut frequency) };
+    frequency as u64
+}
+
+#[cfg(not(windows))]
+pub(crate) fn get_cpu_timestamp_frequency() -> u64 {
+    // `CLOCK_MONOTONIC` is in nanoseconds
+    1_000_000_000
 }
\ No newline at end of file
@@ -0,0 +1,70 @@
+use core::ffi::CStr;
+use std::sync::Weak;
+
+use onca_common::{
+    prelude::*,
+    sync::{RwLock, const_rwlock},
+};
+use ash::{vk, extensions::ext as vk_ext};
+
+/// Debug utils used for debug names and labels
+/// 
+/// `VK_EXT_debug_utils` is an instance extension, but objects only keep track of their device, so the loaded functions are stored globally
+static DEBUG_UTILS: RwLock<Option<vk_ext::DebugUtils>> = const_rwlock(None);
+
+/// Set the debug utils used for debug names and labels, `None` disables them
+pub fn set_debug_utils(debug_utils: Option<vk_ext::DebugUtils>) {
+    *DEBUG_UTILS.write() = debug_utils;
+}
+
+/// Set the debug name of a vulkan object
+pub fn set_object_name<T: vk::Handle>(device: &Weak<ash::Device>, object: T, name: &str) {
+    let debug_utils = DEBUG_UTILS.read();
+    let Some(debug_utils) = &*debug_utils else { return; };
+    let Some(device) = Weak::upgrade(device) else { return; };
+
+    scoped_alloc!(AllocId::TlsTemp);
+    let mut name = name.to_string();
+    name.null_terminate();
+
+    let name_info = vk::DebugUtilsObjectNameInfoEXT::builder()
+        .object_type(T::TYPE)
+        .object_handle(object.as_raw())
+        .object_name(unsafe { CStr::from_ptr(name.as_ptr() as *const _) });
+
+    // Debug names are only informative, so failing to set one is not an error
+    _ = unsafe { debug_utils.set_debug_utils_object_name(device.handle(), &name_info) };
+}
+
+/// Begin a debug label in a command buffer
+pub unsafe fn cmd_begin_label(command_buffer: vk::CommandBuffer, name: &str, color: [f32; 4]) {
+    if let Some(debug_utils) = &*DEBUG_UTILS.read() {
+        scoped_alloc!(AllocId::TlsTemp);
+        let mut name = name.to_string();
+        name.null_terminate();
+        let label = vk::DebugUtilsLabelEXT::builder()
+            .label_name(CStr::from_ptr(name.as_ptr() as *const _))
+            .color(color);
+        debug_utils.cmd_begin_debug_utils_label(command_buffer, &label);
+    }
+}
+
+/// End the last debug label in a command buffer
+pub unsafe fn cmd_end_label(command_buffer: vk::CommandBuffer) {
+    if let Some(debug_utils) = &*DEBUG_UTILS.read() {
+        debug_utils.cmd_end_debug_utils_label(command_buffer);
+    }
+}
+
+/// Insert a debug label in a command buffer
+pub unsafe fn cmd_insert_label(command_buffer: vk::CommandBuffer, name: &str, color: [f32; 4]) {
+    if let Some(debug_utils) = &*DEBUG_UTILS.read() {
+        scoped_alloc!(AllocId::TlsTemp);
+        let mut name = name.to_string();
+        name.null_terminate();
+        let label = vk::DebugUtilsLabelEXT::builder()
+            .label_name(CStr::from_ptr(name.as_ptr() as *const _))
+            .color(color);
+        debug_utils.cmd_insert_debug_utils_label(command_buffer, &label);
+    }
+}
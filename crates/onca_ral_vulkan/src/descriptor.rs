@@ -6,7 +6,7 @@ use onca_ral as ral;
 use ash::{vk, extensions::ext};
 use ral::{HandleImpl, BufferInterface, GpuAddress};
 
-use crate::{device::Device, buffer::Buffer, sampler::Sampler, texture::{SampledTextureView, StorageTextureView}, vulkan::AllocationCallbacks, utils::{ToVulkan, ToRalError}};
+use crate::{device::Device, buffer::Buffer, sampler::Sampler, texture::{SampledTextureView, StorageTextureView}, vulkan::AllocationCallbacks, utils::{ToVulkan, ToRalError}, debug};
 
 pub const MUTABLE_DESCRIPTOR_TYPES : [vk::DescriptorType; ral::DescriptorType::COUNT] = [
     vk::DescriptorType::SAMPLED_IMAGE,
@@ -115,7 +115,9 @@ impl DescriptorTableLayout {
 }
 
 impl ral::DescriptorTableLayoutInterface for DescriptorTableLayout {
-
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.handle, name);
+    }
 }
 
 impl Drop for DescriptorTableLayout {
@@ -419,6 +421,13 @@ impl ral::DescriptorHeapInterface for DescriptorHeap {
     unsafe fn write_rw_texel_buffer(&self, index: u32, buffer: &ral::BufferHandle, desc: ral::TexelBufferViewDesc) {
         self.write_buffer(index, buffer, desc.offset(), desc.size(), vk::DescriptorType::STORAGE_TEXEL_BUFFER);
     }
+
+    unsafe fn set_debug_name(&self, name: &str) {
+        // CPU-only heaps are not backed by a vulkan object
+        if let DescriptorHeapBuffer::Gpu { buffer, .. } = &self.buffer {
+            debug::set_object_name(&buffer.device, buffer.buffer, name);
+        }
+    }
 }
 
 impl Drop for DescriptorHeap {
@@ -1,27 +1,35 @@
-use core::mem::MaybeUninit;
+use core::{mem::MaybeUninit, ffi::CStr};
 use std::sync::{Arc, Weak};
 
-use onca_common::prelude::*;
+use onca_common::{prelude::*, sync::Mutex};
 use onca_common_macros::flags;
 use onca_ral as ral;
-use ash::{vk, extensions::ext};
+use ash::{vk, extensions::{ext, khr}};
 use ral::HandleImpl;
 
 use crate::{
     utils::*,
     physical_device::PhysicalDevice,
-    command_queue::CommandQueue,
+    command_queue::{CommandQueue, QueueOrdering},
     instance::Instance,
     vulkan::*,
     swap_chain::SwapChain,
     command_list::CommandPool,
-    fence::Fence, shader::Shader, pipeline::{Pipeline, PipelineLayout}, buffer::Buffer, descriptor::{DescriptorHeap, DescriptorTableLayout}, memory::MemoryHeap, sampler::{StaticSampler, Sampler},
+    fence::Fence, shader::Shader, pipeline::{Pipeline, PipelineLayout, PipelineCache}, buffer::Buffer, descriptor::{DescriptorHeap, DescriptorTableLayout}, memory::MemoryHeap, sampler::{StaticSampler, Sampler}, query::QueryPool,
+    raytracing::{AccelerationStructure, get_build_sizes},
+    texture::Texture,
+    debug,
 };
 
 #[flags]
 pub enum SupportedExtensions {
     SwapChainIncremental,
     SwapChainMaintenance1,
+    CalibratedTimestamps,
+    DeviceFault,
+    HdrMetadata,
+    PresentWait,
+    DisplayTiming,
 }
 
 pub struct Device {
@@ -39,10 +47,22 @@ pub struct Device {
 
     // Extensions
     pub descriptor_buffer:        ext::DescriptorBuffer,
+    pub mesh_shader:              ext::MeshShader,
+    pub acceleration_structure:   khr::AccelerationStructure,
+    pub ray_tracing_pipeline:     khr::RayTracingPipeline,
+    pub fragment_shading_rate:    khr::FragmentShadingRate,
+    pub conditional_rendering:    vk::ExtConditionalRenderingFn,
+    /// Texel size of shading rate attachments
+    pub vrs_texel_size:           vk::Extent2D,
+    pub device_fault:             Option<vk::ExtDeviceFaultFn>,
+    pub hdr_metadata:             Option<vk::ExtHdrMetadataFn>,
+    pub present_wait:             Option<vk::KhrPresentWaitFn>,
+    pub display_timing:           Option<vk::GoogleDisplayTimingFn>,
 }
 
 impl Device {
     pub const REQUIRED_EXTENSIONS : [&'static str; 16] = [
+        VK_EXT_CONDITIONAL_RENDERING,
         VK_EXT_CUSTOM_BORDER_COLOR,
         VK_EXT_CONSERVATIVE_RASTERIZATION,
         VK_EXT_DESCRIPTOR_BUFFER,
@@ -58,6 +78,10 @@ impl Device {
         VK_KHR_FRAGMENT_SHADING_RATE,
         VK_KHR_RAY_TRACING_PIPELINE,
         VK_KHR_RAY_QUERY,
+    ];
+
+    /// Extensions that are only required when the device can present, i.e. when it is not headless
+    pub const PRESENT_EXTENSIONS : [&'static str; 1] = [
         VK_KHR_SWAPCHAIN,
     ];
 
@@ -103,6 +127,9 @@ impl Device {
             .shader_float64(true)
             .shader_int64(true)
             .shader_int16(true)
+            // Texture compression
+            .texture_compression_bc(true)
+            .texture_compression_astc_ldr(vk_phys_dev.options.feats.texture_compression_astc_ldr.as_bool())
             // Sparse binding
             .sparse_binding(true)
             .sparse_residency_buffer(true)
@@ -113,6 +140,12 @@ impl Device {
         let mut features1_2 = vk::PhysicalDeviceVulkan12Features::builder()
             .descriptor_binding_partially_bound(true)
             .descriptor_binding_variable_descriptor_count(true)
+            .descriptor_indexing(true)
+            .draw_indirect_count(true)
+            .shader_sampled_image_array_non_uniform_indexing(true)
+            .shader_storage_image_array_non_uniform_indexing(true)
+            .shader_storage_buffer_array_non_uniform_indexing(true)
+            .shader_uniform_buffer_array_non_uniform_indexing(true)
             .runtime_descriptor_array(true)
             .timeline_semaphore(true)
             .buffer_device_address(true);
@@ -142,19 +175,86 @@ impl Device {
         let mut image_view_min_lod = vk::PhysicalDeviceImageViewMinLodFeaturesEXT::builder()
             .min_lod(true);
 
+        let mut mesh_shader_features = vk::PhysicalDeviceMeshShaderFeaturesEXT::builder()
+            .task_shader(true)
+            .mesh_shader(true)
+            .multiview_mesh_shader(true)
+            .primitive_fragment_shading_rate_mesh_shader(true)
+            .mesh_shader_queries(vk_phys_dev.options.mesh_shader_feats.mesh_shader_queries.as_bool());
+
+        let mut acceleration_structure_features = vk::PhysicalDeviceAccelerationStructureFeaturesKHR::builder()
+            .acceleration_structure(true);
+
+        let mut ray_tracing_pipeline_features = vk::PhysicalDeviceRayTracingPipelineFeaturesKHR::builder()
+            .ray_tracing_pipeline(true)
+            .ray_traversal_primitive_culling(vk_phys_dev.options.rt_pipeline_feats.ray_traversal_primitive_culling.as_bool());
+
+        let mut ray_query_features = vk::PhysicalDeviceRayQueryFeaturesKHR::builder()
+            .ray_query(true);
+
+        let mut fragment_shading_rate_features = vk::PhysicalDeviceFragmentShadingRateFeaturesKHR::builder()
+            .pipeline_fragment_shading_rate(true)
+            .primitive_fragment_shading_rate(true)
+            .attachment_fragment_shading_rate(true);
+
+        let mut conditional_rendering_features = vk::PhysicalDeviceConditionalRenderingFeaturesEXT::builder()
+            .conditional_rendering(true)
+            .inherited_conditional_rendering(vk_phys_dev.options.cond_rendering_feats.inherited_conditional_rendering.as_bool());
+
+        let mut device_fault_features = vk::PhysicalDeviceFaultFeaturesEXT::builder()
+            .device_fault(true);
+
+        let mut present_id_features = vk::PhysicalDevicePresentIdFeaturesKHR::builder()
+            .present_id(true);
+        let mut present_wait_features = vk::PhysicalDevicePresentWaitFeaturesKHR::builder()
+            .present_wait(true);
+
+        let instance = match vk_phys_dev.instance.upgrade() {
+            Some(instance) => instance,
+            None => return Err(ral::Error::Other("Vulkan instance has been destroyed before the device could be created".to_string())),
+        };
+
         let mut extensions : Vec<&str> = Self::REQUIRED_EXTENSIONS.into_iter().collect();
+        if !instance.headless {
+            extensions.extend(Self::PRESENT_EXTENSIONS);
+        }
         if vk_phys_dev.options.is_extension_supported(VK_KHR_RAY_TRACING_MAINTENANCE1) {
             extensions.push(VK_KHR_RAY_TRACING_MAINTENANCE1);
         }
 
         let mut supported_extensions = SupportedExtensions::None;
-        if vk_phys_dev.options.is_extension_supported(VK_KHR_INCREMENTAL_PRESENT) {
-            extensions.push(VK_KHR_INCREMENTAL_PRESENT);
-            supported_extensions.enable(SupportedExtensions::SwapChainIncremental)        
+        // Present related extensions depend on `VK_KHR_swapchain`, which is not enabled for headless devices
+        if !instance.headless {
+            if vk_phys_dev.options.is_extension_supported(VK_KHR_INCREMENTAL_PRESENT) {
+                extensions.push(VK_KHR_INCREMENTAL_PRESENT);
+                supported_extensions.enable(SupportedExtensions::SwapChainIncremental)        
+            }
+            if vk_phys_dev.options.is_extension_supported(VK_EXT_SWAPCHAIN_MAINTENANCE1) {
+                extensions.push(VK_EXT_SWAPCHAIN_MAINTENANCE1);
+                supported_extensions.enable(SupportedExtensions::SwapChainIncremental)        
+            }
+            if vk_phys_dev.options.is_extension_supported(VK_EXT_HDR_METADATA) {
+                extensions.push(VK_EXT_HDR_METADATA);
+                supported_extensions.enable(SupportedExtensions::HdrMetadata);
+            }
+            // `VK_KHR_present_wait` requires `VK_KHR_present_id`, and is only useful in combination with it
+            if vk_phys_dev.options.is_extension_supported(VK_KHR_PRESENT_ID) && vk_phys_dev.options.is_extension_supported(VK_KHR_PRESENT_WAIT) {
+                extensions.push(VK_KHR_PRESENT_ID);
+                extensions.push(VK_KHR_PRESENT_WAIT);
+                supported_extensions.enable(SupportedExtensions::PresentWait);
+            }
+            if vk_phys_dev.options.is_extension_supported(VK_GOOGLE_DISPLAY_TIMING) {
+                extensions.push(VK_GOOGLE_DISPLAY_TIMING);
+                supported_extensions.enable(SupportedExtensions::DisplayTiming);
+            }
+        }
+        if vk_phys_dev.options.is_extension_supported(VK_EXT_CALIBRATED_TIMESTAMPS) {
+            extensions.push(VK_EXT_CALIBRATED_TIMESTAMPS);
+            supported_extensions.enable(SupportedExtensions::CalibratedTimestamps);
         }
-        if vk_phys_dev.options.is_extension_supported(VK_EXT_SWAPCHAIN_MAINTENANCE1) {
-            extensions.push(VK_EXT_SWAPCHAIN_MAINTENANCE1);
-            supported_extensions.enable(SupportedExtensions::SwapChainIncremental)        
+        if vk_phys_dev.options.is_extension_supported(VK_EXT_DEVICE_FAULT) {
+            extensions.push(VK_EXT_DEVICE_FAULT);
+            supported_extensions.enable(SupportedExtensions::DeviceFault);
         }
 
         let extensions_i8 = extensions.iter().map(|s| s.as_ptr() as *const i8).collect::<Vec<_>>();
@@ -202,18 +302,39 @@ impl Device {
             .push_next(&mut vertex_attribure_divisor)
             .push_next(&mut mutable_descriptor_type)
             .push_next(&mut descriptor_buffer)
-            .push_next(&mut image_view_min_lod);
-
-        let instance = match vk_phys_dev.instance.upgrade() {
-            Some(instance) => instance,
-            None => return Err(ral::Error::Other("Vulkan instance has been destroyed before the device could be created".to_string())),
+            .push_next(&mut image_view_min_lod)
+            .push_next(&mut mesh_shader_features)
+            .push_next(&mut acceleration_structure_features)
+            .push_next(&mut ray_tracing_pipeline_features)
+            .push_next(&mut ray_query_features)
+            .push_next(&mut fragment_shading_rate_features)
+            .push_next(&mut conditional_rendering_features);
+        let create_info = if supported_extensions.contains(SupportedExtensions::DeviceFault) {
+            create_info.push_next(&mut device_fault_features)
+        } else {
+            create_info
+        };
+        let create_info = if supported_extensions.contains(SupportedExtensions::PresentWait) {
+            create_info.push_next(&mut present_id_features)
+                .push_next(&mut present_wait_features)
+        } else {
+            create_info
         };
 
         let device = unsafe { instance.instance.create_device(vk_phys_dev.phys_dev, &create_info, instance.alloc_callbacks.get_some_vk_callbacks()) }.map_err(|err| err.to_ral_error())?;
         let device = Arc::new(device);
 
+        let calibrated_timestamps = if supported_extensions.contains(SupportedExtensions::CalibratedTimestamps) {
+            Some(ext::CalibratedTimestamps::new(&instance.entry, &instance.instance))
+        } else {
+            None
+        };
+        let timestamp_period = vk_phys_dev.options.props.limits.timestamp_period;
+
         let mut queues = MaybeUninit::<[[(ral::CommandQueueInterfaceHandle, ral::QueueIndex); ral::QueuePriority::COUNT]; ral::QueueType::COUNT]>::uninit();
         for (queue_idx, queue_info) in queue_create_infos.iter().enumerate() {
+            // Queues with a different priority can map to the same vulkan queue, which then need to share their ordering
+            let mut orderings : [Option<Arc<Mutex<QueueOrdering>>>; 2] = [None, None];
             for i in 0..ral::QueuePriority::COUNT {
                 // map priority to index
                 let idx = if queue_info.queue_count == 1 {
@@ -228,7 +349,21 @@ impl Device {
                 };
 
                 let queue = device.get_device_queue(queue_info.queue_family_index, idx);
-                core::ptr::write(&mut (&mut *queues.as_mut_ptr())[queue_idx][i], (ral::CommandQueueInterfaceHandle::new(CommandQueue { queue, device: Arc::downgrade(&device) }), ral::QueueIndex::new(queue_idx as u8)));
+                let ordering = match &orderings[idx as usize] {
+                    Some(ordering) => ordering.clone(),
+                    None => {
+                        let ordering = QueueOrdering::new(&device, &instance.alloc_callbacks)?;
+                        orderings[idx as usize] = Some(ordering.clone());
+                        ordering
+                    },
+                };
+                core::ptr::write(&mut (&mut *queues.as_mut_ptr())[queue_idx][i], (ral::CommandQueueInterfaceHandle::new(CommandQueue {
+                    queue,
+                    device: Arc::downgrade(&device),
+                    timestamp_period,
+                    calibrated_timestamps: calibrated_timestamps.clone(),
+                    ordering,
+                }), ral::QueueIndex::new(queue_idx as u8)));
             }
         }
 
@@ -262,6 +397,31 @@ impl Device {
 
         // Extensions
         let descriptor_buffer = ext::DescriptorBuffer::new(&instance.instance, &device);
+        let mesh_shader = ext::MeshShader::new(&instance.instance, &device);
+        let acceleration_structure = khr::AccelerationStructure::new(&instance.instance, &device);
+        let ray_tracing_pipeline = khr::RayTracingPipeline::new(&instance.instance, &device);
+        let fragment_shading_rate = khr::FragmentShadingRate::new(&instance.instance, &device);
+        let conditional_rendering = vk::ExtConditionalRenderingFn::load(|name| core::mem::transmute(instance.instance.get_device_proc_addr(device.handle(), name.as_ptr())));
+        let device_fault = if supported_extensions.contains(SupportedExtensions::DeviceFault) {
+            Some(vk::ExtDeviceFaultFn::load(|name| core::mem::transmute(instance.instance.get_device_proc_addr(device.handle(), name.as_ptr()))))
+        } else {
+            None
+        };
+        let hdr_metadata = if supported_extensions.contains(SupportedExtensions::HdrMetadata) {
+            Some(vk::ExtHdrMetadataFn::load(|name| core::mem::transmute(instance.instance.get_device_proc_addr(device.handle(), name.as_ptr()))))
+        } else {
+            None
+        };
+        let present_wait = if supported_extensions.contains(SupportedExtensions::PresentWait) {
+            Some(vk::KhrPresentWaitFn::load(|name| core::mem::transmute(instance.instance.get_device_proc_addr(device.handle(), name.as_ptr()))))
+        } else {
+            None
+        };
+        let display_timing = if supported_extensions.contains(SupportedExtensions::DisplayTiming) {
+            Some(vk::GoogleDisplayTimingFn::load(|name| core::mem::transmute(instance.instance.get_device_proc_addr(device.handle(), name.as_ptr()))))
+        } else {
+            None
+        };
 
         Ok((ral::DeviceInterfaceHandle::new(Device {
                 device: device,
@@ -274,6 +434,16 @@ impl Device {
                 descriptor_sizes,
                 sampler_descriptor_size: vk_phys_dev.options.descriptor_buffer_props.sampler_descriptor_size as u32,
                 descriptor_buffer,
+                mesh_shader,
+                acceleration_structure,
+                ray_tracing_pipeline,
+                fragment_shading_rate,
+                conditional_rendering,
+                vrs_texel_size: vk_phys_dev.options.vrs_props.max_fragment_shading_rate_attachment_texel_size,
+                device_fault,
+                hdr_metadata,
+                present_wait,
+                display_timing,
             }),
             queues.assume_init()))
     }
@@ -296,6 +466,22 @@ impl ral::DeviceInterface for Device {
         Buffer::new(self, desc, alloc, desc.usage.to_vulkan())
     }
 
+    unsafe fn create_texture(&self, desc: &ral::TextureDesc, alloc: &ral::GpuAllocator) -> ral::Result<(ral::TextureInterfaceHandle, ral::GpuAllocation)> {
+        Texture::new(self, desc, alloc)
+    }
+
+    unsafe fn create_sparse_texture(&self, desc: &ral::SparseTextureDesc) -> ral::Result<(ral::TextureInterfaceHandle, ral::SparseTextureInfo)> {
+        Texture::new_sparse(self, desc)
+    }
+
+    unsafe fn create_placed_texture(&self, desc: &ral::TextureDesc, heap: &ral::MemoryHeapHandle, offset: u64) -> ral::Result<ral::TextureInterfaceHandle> {
+        Texture::new_placed(self, desc, heap, offset)
+    }
+
+    unsafe fn get_texture_allocation_info(&self, desc: &ral::TextureDesc) -> ral::ResourceAllocationInfo {
+        Texture::get_allocation_info(self, desc)
+    }
+
     unsafe fn create_shader(&self, code: &[u8], _shader_type: ral::ShaderType) -> ral::Result<ral::ShaderInterfaceHandle> {
         Shader::new(self, code)
     }
@@ -312,8 +498,42 @@ impl ral::DeviceInterface for Device {
         PipelineLayout::new(self, desc)
     }
 
-    unsafe fn create_graphics_pipeline(&self, desc: &ral::GraphicsPipelineDesc) -> ral::Result<ral::PipelineInterfaceHandle> {
-        Pipeline::new_graphics(self, desc)
+    unsafe fn create_graphics_pipeline(&self, desc: &ral::GraphicsPipelineDesc, cache: Option<&ral::PipelineCacheHandle>) -> ral::Result<ral::PipelineInterfaceHandle> {
+        Pipeline::new_graphics(self, desc, cache)
+    }
+
+    unsafe fn create_compute_pipeline(&self, desc: &ral::ComputePipelineDesc, cache: Option<&ral::PipelineCacheHandle>) -> ral::Result<ral::PipelineInterfaceHandle> {
+        Pipeline::new_compute(self, desc, cache)
+    }
+
+    unsafe fn create_mesh_pipeline(&self, desc: &ral::MeshPipelineDesc, cache: Option<&ral::PipelineCacheHandle>) -> ral::Result<ral::PipelineInterfaceHandle> {
+        Pipeline::new_mesh(self, desc, cache)
+    }
+
+    unsafe fn create_pipeline_cache(&self, data: &[u8]) -> ral::Result<ral::PipelineCacheInterfaceHandle> {
+        PipelineCache::new(self, data)
+    }
+
+    unsafe fn create_query_pool(&self, desc: &ral::QueryPoolDesc) -> ral::Result<ral::QueryPoolInterfaceHandle> {
+        QueryPool::new(self, desc)
+    }
+
+    unsafe fn create_acceleration_structure(&self, desc: &ral::AccelerationStructureDesc) -> ral::Result<(ral::AccelerationStructureInterfaceHandle, ral::GpuAddress)> {
+        AccelerationStructure::new(self, desc)
+    }
+
+    unsafe fn get_acceleration_structure_build_sizes(&self, inputs: &ral::AccelerationStructureInputs, flags: ral::AccelerationStructureBuildFlags) -> ral::Result<ral::AccelerationStructureBuildSizes> {
+        get_build_sizes(self, inputs, flags)
+    }
+
+    unsafe fn create_raytracing_pipeline(&self, desc: &ral::RaytracingPipelineDesc, cache: Option<&ral::PipelineCacheHandle>) -> ral::Result<ral::PipelineInterfaceHandle> {
+        Pipeline::new_raytracing(self, desc, cache)
+    }
+
+    unsafe fn get_raytracing_shader_group_handles(&self, pipeline: &ral::PipelineHandle, first_group: u32, count: u32) -> ral::Result<Vec<u8>> {
+        let pipeline = pipeline.interface().as_concrete_type::<Pipeline>().pipeline;
+        let data_size = count as usize * ral::constants::RAYTRACE_HITGROUP_HANDLE_SIZE as usize;
+        self.ray_tracing_pipeline.get_ray_tracing_shader_group_handles(pipeline, first_group, count, data_size).map_err(|err| err.to_ral_error())
     }
 
     unsafe fn create_descriptor_table_layout(&self, desc: &ral::DescriptorTableDesc) -> ral::Result<(ral::DescriptorTableLayoutInterfaceHandle, u32, u32)> {
@@ -337,7 +557,60 @@ impl ral::DeviceInterface for Device {
         heap.free(self)
     }
 
-    
+    unsafe fn begin_capture(&self) -> bool {
+        // Vulkan has no API specific capture tool, RenderDoc is handled by the RAL itself
+        false
+    }
+
+    unsafe fn end_capture(&self) -> bool {
+        false
+    }
+
+    unsafe fn get_device_lost_info(&self) -> ral::DeviceLostInfo {
+        let device_fault = match &self.device_fault {
+            Some(device_fault) => device_fault,
+            None => return ral::DeviceLostInfo {
+                reason: "Device lost, VK_EXT_device_fault is unsupported, so no additional info is available".to_string(),
+                details: Vec::new(),
+            },
+        };
+
+        let mut counts = vk::DeviceFaultCountsEXT::default();
+        if (device_fault.get_device_fault_info_ext)(self.device.handle(), &mut counts, core::ptr::null_mut()) != vk::Result::SUCCESS {
+            return ral::DeviceLostInfo { reason: "Device lost, failed to query device fault info".to_string(), details: Vec::new() };
+        }
+
+        let mut address_infos = vec![vk::DeviceFaultAddressInfoEXT::default(); counts.address_info_count as usize];
+        let mut vendor_infos = vec![vk::DeviceFaultVendorInfoEXT::default(); counts.vendor_info_count as usize];
+        // We don't do anything with the vendor binary data, so don't request it
+        counts.vendor_binary_size = 0;
+
+        let mut fault_info = vk::DeviceFaultInfoEXT {
+            p_address_infos: address_infos.as_mut_ptr(),
+            p_vendor_infos: vendor_infos.as_mut_ptr(),
+            ..Default::default()
+        };
+        if (device_fault.get_device_fault_info_ext)(self.device.handle(), &mut counts, &mut fault_info) != vk::Result::SUCCESS {
+            return ral::DeviceLostInfo { reason: "Device lost, failed to query device fault info".to_string(), details: Vec::new() };
+        }
+
+        let reason = CStr::from_ptr(fault_info.description.as_ptr()).to_string_lossy().into_owned();
+
+        let mut details = Vec::with_capacity(address_infos.len() + vendor_infos.len());
+        for info in &address_infos[..counts.address_info_count as usize] {
+            details.push(format!("Faulting address {:#X} (precision {:#X}): {:?}", info.reported_address, info.address_precision, info.address_type));
+        }
+        for info in &vendor_infos[..counts.vendor_info_count as usize] {
+            let description = CStr::from_ptr(info.description.as_ptr()).to_string_lossy();
+            details.push(format!("Vendor fault {:#X} (data {:#X}): {description}", info.vendor_fault_code, info.vendor_fault_data));
+        }
+
+        ral::DeviceLostInfo { reason, details }
+    }
+
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&Arc::downgrade(&self.device), self.device.handle(), name);
+    }
 }
 
 impl Drop for Device {
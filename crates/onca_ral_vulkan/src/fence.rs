@@ -5,7 +5,7 @@ use onca_ral as ral;
 use ash::vk;
 use ral::HandleImpl;
 
-use crate::{utils::ToRalError, device::Device, vulkan::AllocationCallbacks};
+use crate::{utils::ToRalError, device::Device, vulkan::AllocationCallbacks, debug};
 
 pub struct Fence {
     pub semaphore:       vk::Semaphore,
@@ -87,7 +87,11 @@ impl ral::FenceInterface for Fence {
             Err(err) if err == vk::Result::TIMEOUT => Ok(false),
             Err(err) => Err(err.to_ral_error()),
         }
-    }  
+    }
+
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.semaphore, name);
+    }
 }
 
 impl Drop for Fence {
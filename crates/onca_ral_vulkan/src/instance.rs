@@ -17,6 +17,7 @@ use ral::Version;
 
 use crate::{
     vulkan::*,
+    debug,
     LOG_CAT
 };
 
@@ -25,7 +26,11 @@ pub struct Instance {
     pub instance        : ash::Instance,
     pub debug_utils     : vk_ext::DebugUtils,
     pub debug_messenger : vk::DebugUtilsMessengerEXT,
-    pub alloc_callbacks : AllocationCallbacks
+    pub alloc_callbacks : AllocationCallbacks,
+    /// Is the instance created without any surface support, i.e. it can only be used for offscreen rendering
+    pub headless        : bool,
+    /// Is `VK_KHR_wayland_surface` enabled, without it, only X11 windows can be presented to on Linux
+    pub wayland_surface : bool,
 }
 
 impl Instance {
@@ -65,7 +70,16 @@ impl Instance {
 
         if settings.debug_enabled {
             layers.push(String::from("VK_LAYER_KHRONOS_validation"));
-            extensions.push(String::from("VK_EXT_debug_utils"));
+        }
+        // Debug utils are also used for debug names and labels, which are picked up by graphics debuggers, even when validation is disabled
+        extensions.push(String::from("VK_EXT_debug_utils"));
+        // Needed for any swap-chain color space other than sRGB
+        if !settings.headless {
+            extensions.push(String::from("VK_EXT_swapchain_colorspace"));
+            // Windows use X11 when there is no Wayland compositor, so Wayland surface support is optional
+            if cfg!(target_os = "linux") {
+                extensions.push(String::from("VK_KHR_wayland_surface"));
+            }
         }
 
         // Filter out unavailable optional layers an extensions
@@ -77,14 +91,21 @@ impl Instance {
             layers.iter().any(|layer| available_layers.iter().find(|val| val.0.name == *layer).map_or(false, |val| val.1.iter().any(|val| val.name == *extension)))
         });
 
-        // Required extensions        
-        extensions.push(String::from("VK_KHR_surface"));
+        let debug_utils_enabled = extensions.iter().any(|extension| extension == "VK_EXT_debug_utils");
+        let wayland_surface = extensions.iter().any(|extension| extension == "VK_KHR_wayland_surface");
+
+        // Required extensions, a headless instance never presents, so it doesn't need any surface extensions
+        if !settings.headless {
+            extensions.push(String::from("VK_KHR_surface"));
 
-        if cfg!(windows) {
-            extensions.push(String::from("VK_KHR_win32_surface"));
-        } else {
-            log_error!(LOG_CAT, "No platfrom specific surface extension is found");
-            return Err(vk::Result::ERROR_EXTENSION_NOT_PRESENT);
+            if cfg!(windows) {
+                extensions.push(String::from("VK_KHR_win32_surface"));
+            } else if cfg!(target_os = "linux") {
+                extensions.push(String::from("VK_KHR_xlib_surface"));
+            } else {
+                log_error!(LOG_CAT, "No platfrom specific surface extension is found");
+                return Err(vk::Result::ERROR_EXTENSION_NOT_PRESENT);
+            }
         }
 
 
@@ -134,6 +155,9 @@ impl Instance {
 
         let instance = unsafe { entry.create_instance(&create_info, alloc_callbacks.get_some_vk_callbacks())? };
         let debug_utils = vk_ext::DebugUtils::new(&entry, &instance);
+        if debug_utils_enabled {
+            debug::set_debug_utils(Some(debug_utils.clone()));
+        }
 
         let mut res = Self {
             entry,
@@ -141,9 +165,11 @@ impl Instance {
             debug_utils,
             debug_messenger: vk::DebugUtilsMessengerEXT::null(),
             alloc_callbacks,
+            headless: settings.headless,
+            wayland_surface,
         };
 
-        if settings.debug_enabled {
+        if settings.debug_enabled && debug_utils_enabled {
             res.setup_debug(settings)?;
         }
         Ok(Arc::new(res))
@@ -198,6 +224,7 @@ impl Instance {
 
 impl Drop for Instance {
     fn drop(&mut self) {
+        debug::set_debug_utils(None);
         if self.debug_messenger != vk::DebugUtilsMessengerEXT::null() {
             unsafe { self.debug_utils.destroy_debug_utils_messenger(self.debug_messenger, self.alloc_callbacks.get_some_vk_callbacks()) };
         }
@@ -35,6 +35,9 @@ mod buffer;
 mod descriptor;
 mod memory;
 mod sampler;
+mod query;
+mod raytracing;
+mod debug;
 
 
 #[no_mangle]
@@ -90,6 +90,34 @@ pub(crate) const VULKAN_FORMATS : [vk::Format; Format::COUNT] = [
     /* BC7Typeless                    */ vk::Format::UNDEFINED,
     /* BC7UNorm                       */ vk::Format::BC7_UNORM_BLOCK,
     /* BC7Srgb                        */ vk::Format::BC7_SRGB_BLOCK,
+    /* ASTC4x4UNorm                   */ vk::Format::ASTC_4X4_UNORM_BLOCK,
+    /* ASTC4x4Srgb                    */ vk::Format::ASTC_4X4_SRGB_BLOCK,
+    /* ASTC5x4UNorm                   */ vk::Format::ASTC_5X4_UNORM_BLOCK,
+    /* ASTC5x4Srgb                    */ vk::Format::ASTC_5X4_SRGB_BLOCK,
+    /* ASTC5x5UNorm                   */ vk::Format::ASTC_5X5_UNORM_BLOCK,
+    /* ASTC5x5Srgb                    */ vk::Format::ASTC_5X5_SRGB_BLOCK,
+    /* ASTC6x5UNorm                   */ vk::Format::ASTC_6X5_UNORM_BLOCK,
+    /* ASTC6x5Srgb                    */ vk::Format::ASTC_6X5_SRGB_BLOCK,
+    /* ASTC6x6UNorm                   */ vk::Format::ASTC_6X6_UNORM_BLOCK,
+    /* ASTC6x6Srgb                    */ vk::Format::ASTC_6X6_SRGB_BLOCK,
+    /* ASTC8x5UNorm                   */ vk::Format::ASTC_8X5_UNORM_BLOCK,
+    /* ASTC8x5Srgb                    */ vk::Format::ASTC_8X5_SRGB_BLOCK,
+    /* ASTC8x6UNorm                   */ vk::Format::ASTC_8X6_UNORM_BLOCK,
+    /* ASTC8x6Srgb                    */ vk::Format::ASTC_8X6_SRGB_BLOCK,
+    /* ASTC8x8UNorm                   */ vk::Format::ASTC_8X8_UNORM_BLOCK,
+    /* ASTC8x8Srgb                    */ vk::Format::ASTC_8X8_SRGB_BLOCK,
+    /* ASTC10x5UNorm                  */ vk::Format::ASTC_10X5_UNORM_BLOCK,
+    /* ASTC10x5Srgb                   */ vk::Format::ASTC_10X5_SRGB_BLOCK,
+    /* ASTC10x6UNorm                  */ vk::Format::ASTC_10X6_UNORM_BLOCK,
+    /* ASTC10x6Srgb                   */ vk::Format::ASTC_10X6_SRGB_BLOCK,
+    /* ASTC10x8UNorm                  */ vk::Format::ASTC_10X8_UNORM_BLOCK,
+    /* ASTC10x8Srgb                   */ vk::Format::ASTC_10X8_SRGB_BLOCK,
+    /* ASTC10x10UNorm                 */ vk::Format::ASTC_10X10_UNORM_BLOCK,
+    /* ASTC10x10Srgb                  */ vk::Format::ASTC_10X10_SRGB_BLOCK,
+    /* ASTC12x10UNorm                 */ vk::Format::ASTC_12X10_UNORM_BLOCK,
+    /* ASTC12x10Srgb                  */ vk::Format::ASTC_12X10_SRGB_BLOCK,
+    /* ASTC12x12UNorm                 */ vk::Format::ASTC_12X12_UNORM_BLOCK,
+    /* ASTC12x12Srgb                  */ vk::Format::ASTC_12X12_SRGB_BLOCK,
     /* SamplerFeedbackMinMipOpaque    */ vk::Format::UNDEFINED,
     /* SamplerFeedbackMipRegionOpaque */ vk::Format::UNDEFINED,
 ];
@@ -1,11 +1,14 @@
+use std::sync::{Arc, Weak};
+
 use onca_ral as ral;
 use ash::vk;
 use ral::{MemoryHeapInterfaceHandle, ApiMemoryRequest};
 
-use crate::{device::Device, utils::ToRalError, vulkan::VkBoolToBool};
+use crate::{device::Device, utils::ToRalError, vulkan::VkBoolToBool, debug};
 
 pub struct MemoryHeap {
-    memory: vk::DeviceMemory
+    memory: vk::DeviceMemory,
+    device: Weak<ash::Device>,
 }
 
 impl MemoryHeap {
@@ -22,7 +25,7 @@ impl MemoryHeap {
             .push_next(&mut memory_flags_info);
 
         let memory = device.device.allocate_memory(&alloc_info, device.alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.to_ral_error())?;
-        Ok(ral::MemoryHeapInterfaceHandle::new(MemoryHeap{ memory }))
+        Ok(ral::MemoryHeapInterfaceHandle::new(MemoryHeap{ memory, device: Arc::downgrade(&device.device) }))
     }
 
     pub unsafe fn free(&self, device: &Device) {
@@ -35,7 +38,9 @@ impl MemoryHeap {
 }
 
 impl ral::MemoryHeapInterface for MemoryHeap {
-
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.memory, name);
+    }
 }
 
 pub fn create_api_memory_request(mem_info: &ral::MemoryInfo, mem_reqs: &vk::MemoryRequirements, dedicated: &vk::MemoryDedicatedRequirements) -> ApiMemoryRequest {
@@ -102,14 +102,18 @@ impl ral::PhysicalDeviceInterface for PhysicalDevice {
 
         unsafe { instance.instance.get_physical_device_memory_properties2(self.phys_dev, &mut mem_props) };
 
+        let vk_heaps = &mem_props.memory_properties.memory_heaps[..mem_props.memory_properties.memory_heap_count as usize];
+        let vk_types = &mem_props.memory_properties.memory_types[..mem_props.memory_properties.memory_type_count as usize];
+
         let mut budgets = [MemoryBudgetValue::default(); MemoryHeapType::COUNT];
         let mut total = MemoryBudgetValue::default();
-        for i in 0..MemoryHeapType::COUNT {
-            budgets[i].budget = mem_budget_props.heap_budget[i];
-            budgets[i].in_use = mem_budget_props.heap_usage[i];
+        for (heap_idx, vk_heap) in vk_heaps.iter().enumerate() {
+            let heap_type = get_heap_type(heap_idx, vk_heap, vk_types);
+            budgets[heap_type as usize].budget += mem_budget_props.heap_budget[heap_idx];
+            budgets[heap_type as usize].in_use += mem_budget_props.heap_usage[heap_idx];
 
-            total.budget += mem_budget_props.heap_budget[i];
-            total.in_use += mem_budget_props.heap_usage[i];
+            total.budget += mem_budget_props.heap_budget[heap_idx];
+            total.in_use += mem_budget_props.heap_usage[heap_idx];
         }
 
         Ok(MemoryBudgetInfo { budgets, total })
@@ -119,6 +123,31 @@ impl ral::PhysicalDeviceInterface for PhysicalDevice {
         // NO-OP
         Ok(())
     }
+
+    fn get_supported_sample_counts(&self, format: Format) -> SampleCountSupport {
+        let instance = match self.instance.upgrade() {
+            None => return SampleCountSupport::Sample1,
+            Some(instance) => instance
+        };
+
+        let usage = if format.aspect().intersects(ral::TextureAspect::Depth | ral::TextureAspect::Stencil) {
+            vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT
+        } else {
+            vk::ImageUsageFlags::COLOR_ATTACHMENT
+        };
+
+        let props = match unsafe { instance.instance.get_physical_device_image_format_properties(self.phys_dev, format.to_vulkan(), vk::ImageType::TYPE_2D, vk::ImageTiling::OPTIMAL, usage, vk::ImageCreateFlags::empty()) } {
+            Ok(props) => props,
+            Err(_) => return SampleCountSupport::Sample1,
+        };
+
+        let mut support = SampleCountSupport::Sample1;
+        support.set(SampleCountSupport::Sample2, props.sample_counts.contains(vk::SampleCountFlags::TYPE_2));
+        support.set(SampleCountSupport::Sample4, props.sample_counts.contains(vk::SampleCountFlags::TYPE_4));
+        support.set(SampleCountSupport::Sample8, props.sample_counts.contains(vk::SampleCountFlags::TYPE_8));
+        support.set(SampleCountSupport::Sample16, props.sample_counts.contains(vk::SampleCountFlags::TYPE_16));
+        support
+    }
 }
 
 // For multi-adapter, look into VkPhsyicalDeviceGroup...
@@ -137,7 +166,7 @@ pub fn get_physical_devices(instance: &Arc<Instance>) -> ral::Result<Vec<ral::Ph
 
 fn get_device(instance: &Arc<Instance>, phys_dev: vk::PhysicalDevice) -> ral::Result<ral::PhysicalDevice> {
     let vk_options = VulkanOptions::get(&instance, phys_dev)?;
-    check_required_extensions(&vk_options)?;
+    check_required_extensions(&vk_options, instance.headless)?;
     
     let driver_version = if vk_options.props.vendor_id == 0x10DE {
         // Nvidia driver version encoding
@@ -168,6 +197,7 @@ fn get_device(instance: &Arc<Instance>, phys_dev: vk::PhysicalDevice) -> ral::Re
         // Currently don't know of a way to get these on vulkan
         graphics_preempt: GraphicsPreemptionGranularity::Unknown,
         compure_preempt: ComputePreemptionGranularity::Unknown,
+        max_pipeline_constant_size: vk_options.props.limits.max_push_constants_size,
     };
 
     let memory_props = get_vk_memory_props(&instance, phys_dev);
@@ -281,7 +311,9 @@ fn get_device(instance: &Arc<Instance>, phys_dev: vk::PhysicalDevice) -> ral::Re
     let raytracing = get_raytracing_support(&vk_options)?;
     let vrs = get_vrs_support(&vk_options)?;
     let queue_infos = get_queue_infos(&instance, phys_dev)?;
-    
+
+    let mut capabilities = Capabilities::MinSampleShading;
+    capabilities.set(Capabilities::TextureCompressionASTC, vk_options.feats.texture_compression_astc_ldr.as_bool());
     
     let handle = ral::PhysicalDeviceInterfaceHandle::new(PhysicalDevice{
         instance: Arc::downgrade(instance),
@@ -293,7 +325,7 @@ fn get_device(instance: &Arc<Instance>, phys_dev: vk::PhysicalDevice) -> ral::Re
         handle,
         properties,
         memory_info,
-        capabilities: Capabilities::MinSampleShading,
+        capabilities,
         shader,
         pipeline_cache_support: PipelineCacheSupport::Single | PipelineCacheSupport::Library,
         render_pass_tier: RenderpassTier::Tier2,
@@ -336,8 +368,6 @@ fn parse_memory_info(mem_props: vk::PhysicalDeviceMemoryProperties) -> ral::Resu
                                                 vk::MemoryPropertyFlags::DEVICE_UNCACHED_AMD |
                                                 vk::MemoryPropertyFlags::RDMA_CAPABLE_NV;
 
-    const NON_REBAR_UPLOAD_SIZE: u64 = MiB(256) as u64;
-
     let vk_heaps = &mem_props.memory_heaps[..mem_props.memory_heap_count as usize];
     let vk_types = &mem_props.memory_types[..mem_props.memory_type_count as usize];
 
@@ -345,22 +375,7 @@ fn parse_memory_info(mem_props: vk::PhysicalDeviceMemoryProperties) -> ral::Resu
     let mut mem_types = MemoryTypeInfo::create_empty_heap_arr();
 
     for (heap_idx, vk_heap) in vk_heaps.iter().enumerate() {
-        let has_upload_heap_size = vk_heap.size <= NON_REBAR_UPLOAD_SIZE;
-        let heap_type = if vk_heap.flags.contains(vk::MemoryHeapFlags::DEVICE_LOCAL) {
-            // We have an upload heap when the heap is <= 256MiB, and has no DEVICE_LOCAL only memory type associated with it
-            if  has_upload_heap_size && 
-                vk_types.iter().find(|mem| mem.heap_index == heap_idx as u32 &&
-                    mem.property_flags.contains(vk::MemoryPropertyFlags::DEVICE_LOCAL) &&
-                    !mem.property_flags.contains(vk::MemoryPropertyFlags::HOST_VISIBLE))
-                .is_some()
-            {
-                ral::MemoryHeapType::UploadHeap
-            } else {
-                ral::MemoryHeapType::Gpu
-            }
-        } else {
-            ral::MemoryHeapType::System
-        };
+        let heap_type = get_heap_type(heap_idx, vk_heap, vk_types);
 
         let ral_heap = &mut heaps[heap_type as usize];
         ral_heap.multi_instance = vk_heap.flags.contains(vk::MemoryHeapFlags::MULTI_INSTANCE);
@@ -411,6 +426,28 @@ fn parse_memory_info(mem_props: vk::PhysicalDeviceMemoryProperties) -> ral::Resu
     Ok(MemoryInfo { heaps, mem_types })
 }
 
+/// Get the RAL heap type of a vulkan memory heap
+fn get_heap_type(heap_idx: usize, vk_heap: &vk::MemoryHeap, vk_types: &[vk::MemoryType]) -> ral::MemoryHeapType {
+    const NON_REBAR_UPLOAD_SIZE: u64 = MiB(256) as u64;
+
+    let has_upload_heap_size = vk_heap.size <= NON_REBAR_UPLOAD_SIZE;
+    if vk_heap.flags.contains(vk::MemoryHeapFlags::DEVICE_LOCAL) {
+        // We have an upload heap when the heap is <= 256MiB, and has no DEVICE_LOCAL only memory type associated with it
+        if  has_upload_heap_size && 
+            vk_types.iter().find(|mem| mem.heap_index == heap_idx as u32 &&
+                mem.property_flags.contains(vk::MemoryPropertyFlags::DEVICE_LOCAL) &&
+                !mem.property_flags.contains(vk::MemoryPropertyFlags::HOST_VISIBLE))
+            .is_some()
+        {
+            ral::MemoryHeapType::UploadHeap
+        } else {
+            ral::MemoryHeapType::Gpu
+        }
+    } else {
+        ral::MemoryHeapType::System
+    }
+}
+
 /// Converts flags to a memory type
 /// 
 /// - Gpu: Device local, but not host visible
@@ -432,10 +469,15 @@ fn get_memory_type_from_flags(flags: vk::MemoryPropertyFlags) -> Option<ral::Mem
     }
 }
 
-fn check_required_extensions(vk_options: &VulkanOptions) -> ral::Result<()> {
+fn check_required_extensions(vk_options: &VulkanOptions, headless: bool) -> ral::Result<()> {
     for req_ext in Device::REQUIRED_EXTENSIONS {
         vk_options.check_required_extension(req_ext)?;
     }
+    if !headless {
+        for req_ext in Device::PRESENT_EXTENSIONS {
+            vk_options.check_required_extension(req_ext)?;
+        }
+    }
     Ok(())
 }
 
@@ -455,9 +497,13 @@ fn get_sparse_resource_support(vk_options: &VulkanOptions) -> ral::Result<Sparse
     Ok(sparse_resources)
 }
 
-fn check_format_properties(instance: &Instance, phys_dev: vk::PhysicalDevice, _vk_options: &VulkanOptions) -> ral::Result<()> {
+fn check_format_properties(instance: &Instance, phys_dev: vk::PhysicalDevice, vk_options: &VulkanOptions) -> ral::Result<()> {
     let mut res = Ok(());
     Format::for_each(|format| {
+        // ASTC formats are optional
+        if format.is_astc() && !vk_options.feats.texture_compression_astc_ldr.as_bool() {
+            return;
+        }
         if let Err(err) = check_format_properties_for_single(instance, phys_dev, format) && res.is_ok() {
             res = Err(err);
         }
@@ -662,6 +708,7 @@ fn get_raytracing_support(vk_options: &VulkanOptions) -> ral::Result<RaytracingS
     let mut flags = RaytracingSupportFlags::None;
     flags.set(RaytracingSupportFlags::IndirectBuild, vk_options.accel_struct_feats.acceleration_structure_indirect_build.as_bool());
     flags.set(RaytracingSupportFlags::InvocationReordering, vk_options.rt_reorder_feats.ray_tracing_invocation_reorder.as_bool());
+    flags.set(RaytracingSupportFlags::RayQuery, vk_options.ray_query_feats.ray_query.as_bool());
 
 
     let invocation_reorder_mode = if vk_options.rt_reorder_props.ray_tracing_invocation_reorder_reordering_hint == vk::RayTracingInvocationReorderModeNV::REORDER {
@@ -673,6 +720,7 @@ fn get_raytracing_support(vk_options: &VulkanOptions) -> ral::Result<RaytracingS
     Ok(RaytracingSupport {
         flags,
         invocation_reorder_mode,
+        max_recursion_depth: vk_options.rt_pipeline_props.max_ray_recursion_depth,
     })
 }
 
@@ -690,6 +738,12 @@ cfg_if::cfg_if!{
             let win32_surface = ash::extensions::khr::Win32Surface::new(&instance.entry, &instance.instance);
             unsafe { win32_surface.get_physical_device_win32_presentation_support(phys_dev, queue_idx) }
         }
+    } else if #[cfg(target_os = "linux")] {
+        fn check_present_queue_support(_instance: &Instance, _phys_dev: vk::PhysicalDevice, _queue_idx: u32) -> bool {
+            // Presentation support depends on whether windows use X11 or Wayland, which is only known once a window exists,
+            // so the swap-chain checks support for its surface using `vkGetPhysicalDeviceSurfaceSupportKHR` when it's created
+            true
+        }
     }
 }
 
@@ -794,6 +848,7 @@ pub struct VulkanOptions {
     pub mut_descriptor_type_feats: vk::PhysicalDeviceMutableDescriptorTypeFeaturesEXT,
     pub custom_border_color_feats: vk::PhysicalDeviceCustomBorderColorFeaturesEXT,
     pub image_view_min_lod_feats:  vk::PhysicalDeviceImageViewMinLodFeaturesEXT,
+    pub cond_rendering_feats:      vk::PhysicalDeviceConditionalRenderingFeaturesEXT,
 
     /// Extensions and Layers
     pub extensions          : Vec<ExtensionProperties>,
@@ -849,6 +904,7 @@ impl VulkanOptions {
         let mut mut_descriptor_type_feats = vk::PhysicalDeviceMutableDescriptorTypeFeaturesEXT::default();
         let mut custom_border_color_feats = vk::PhysicalDeviceCustomBorderColorFeaturesEXT::default();
         let mut image_view_min_lod_feats = vk::PhysicalDeviceImageViewMinLodFeaturesEXT::default();
+        let mut cond_rendering_feats = vk::PhysicalDeviceConditionalRenderingFeaturesEXT::default();
 
         let mut feats = vk::PhysicalDeviceFeatures2::builder()
             .push_next(&mut feats11)
@@ -867,7 +923,8 @@ impl VulkanOptions {
             .push_next(&mut descriptor_buffer_feats)
             .push_next(&mut mut_descriptor_type_feats)
             .push_next(&mut custom_border_color_feats)
-            .push_next(&mut image_view_min_lod_feats);
+            .push_next(&mut image_view_min_lod_feats)
+            .push_next(&mut cond_rendering_feats);
         unsafe { instance.instance.get_physical_device_features2(phys_dev, &mut feats) }
 
         let vk_extensions = unsafe { instance.instance.enumerate_device_extension_properties(phys_dev) }.map_err(|err| err.to_ral_error())?;
@@ -924,6 +981,7 @@ impl VulkanOptions {
             mut_descriptor_type_feats,
             custom_border_color_feats,
             image_view_min_lod_feats,
+            cond_rendering_feats,
 
             extensions,
             layers
@@ -1028,6 +1086,11 @@ impl VulkanOptions {
         check_required_feature!(self.feats13, robust_image_access);
         check_required_feature!(self.feats13, maintenance4);
         
+        // --------
+        // VK_EXT_conditional_rendering
+        self.check_required_extension(VK_EXT_CONDITIONAL_RENDERING)?;
+        check_required_feature!(self.cond_rendering_feats, conditional_rendering);
+
         // --------
         // VK_EXT_custom_bofer_color
         self.check_required_extension(VK_EXT_CUSTOM_BORDER_COLOR)?;
@@ -1933,6 +1996,9 @@ impl VulkanOptions {
         log_verbose!(LOG_CAT, "| - storageTexelBufferOffsetSingleTexelAlignment                                  | {:>VALUE_COLUMN_WIDTH$} |", get_bool(self.props13.storage_texel_buffer_offset_single_texel_alignment));
         log_verbose!(LOG_CAT, "| - uniformTexelBufferAlignmentOffset                                             | {:>VALUE_COLUMN_WIDTH$} |", self.props13.uniform_texel_buffer_offset_alignment_bytes);
         log_verbose!(LOG_CAT, "| - uniformTexelBufferOffsetSingleTexelAlignment                                  | {:>VALUE_COLUMN_WIDTH$} |", get_bool(self.props13.uniform_texel_buffer_offset_single_texel_alignment));
+        log_verbose!(LOG_CAT, "|-[VK_EXT_conditional_rendering] - - - - - - - - - - - - - - - - - - - - - - - - -+- - - - - - - -{} |", get_extension_value(VK_EXT_CONDITIONAL_RENDERING));
+        log_verbose!(LOG_CAT, "| - conditionalRendering                                                          | {:>VALUE_COLUMN_WIDTH$} |", get_bool(self.cond_rendering_feats.conditional_rendering));
+        log_verbose!(LOG_CAT, "| - inheritedConditionalRendering                                                 | {:>VALUE_COLUMN_WIDTH$} |", get_bool(self.cond_rendering_feats.inherited_conditional_rendering));
         log_verbose!(LOG_CAT, "|-[VK_EXT_custom_border_color] - - - - - - - - - - - - - - - - - - - - - - - - - -+- - - - - - - -{} |", get_extension_value(VK_EXT_CUSTOM_BORDER_COLOR));
         log_verbose!(LOG_CAT, "| Features                                                                        +                             |");
         log_verbose!(LOG_CAT, "| - customBorderColors                                                            | {:>VALUE_COLUMN_WIDTH$} |", get_bool(self.custom_border_color_feats.custom_border_colors));
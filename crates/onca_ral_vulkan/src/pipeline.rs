@@ -6,11 +6,13 @@ use onca_ral as ral;
 use ash::vk;
 use ral::{HandleImpl, TextureAspect};
 
-use crate::{device::Device, utils::{ToRalError, ToVulkan}, vulkan::AllocationCallbacks, shader::Shader, sampler::Sampler, descriptor::{MUTABLE_DESCRIPTOR_TYPES, DescriptorTableLayout}};
+use crate::{device::Device, utils::{ToRalError, ToVulkan}, vulkan::AllocationCallbacks, shader::Shader, sampler::Sampler, descriptor::{MUTABLE_DESCRIPTOR_TYPES, DescriptorTableLayout}, debug};
 
 
 pub struct PipelineLayout {
     pub layout:          vk::PipelineLayout,
+    /// Index of the first of the 2 bindless descriptor sets (resources, samplers), if the layout is bindless
+    pub bindless_set:    Option<u32>,
     pub device:          Weak<ash::Device>,
     pub alloc_callbacks: AllocationCallbacks,
 }
@@ -95,6 +97,15 @@ impl PipelineLayout {
             None => {},
         }
 
+        let bindless_set = if desc.flags.contains(ral::PipelineLayoutFlags::Bindless) {
+            let bindless_set = layouts.len() as u32;
+            layouts.push(Self::create_bindless_set_layout(device, vk::DescriptorType::MUTABLE_EXT, ral::constants::MAX_BINDLESS_RESOURCE_DESCRIPTORS)?);
+            layouts.push(Self::create_bindless_set_layout(device, vk::DescriptorType::SAMPLER, ral::constants::MAX_BINDLESS_SAMPLER_DESCRIPTORS)?);
+            Some(bindless_set)
+        } else {
+            None
+        };
+
         let create_info = vk::PipelineLayoutCreateInfo::builder()
             .set_layouts(&layouts)
             .push_constant_ranges(&push_constants);
@@ -104,15 +115,48 @@ impl PipelineLayout {
         Ok(ral::PipelineLayoutInterfaceHandle::new(
             PipelineLayout {
                 layout,
+                bindless_set,
                 device: Arc::downgrade(&device.device),
                 alloc_callbacks: device.alloc_callbacks.clone(),
             }
         ))
     }
+
+    unsafe fn create_bindless_set_layout(device: &Device, descriptor_type: vk::DescriptorType, count: u32) -> ral::Result<vk::DescriptorSetLayout> {
+        let binding = [vk::DescriptorSetLayoutBinding::builder()
+            .binding(0)
+            .descriptor_type(descriptor_type)
+            .descriptor_count(count)
+            .stage_flags(vk::ShaderStageFlags::ALL)
+            .build()
+        ];
+
+        let binding_flags = [vk::DescriptorBindingFlags::PARTIALLY_BOUND | vk::DescriptorBindingFlags::UPDATE_AFTER_BIND | vk::DescriptorBindingFlags::VARIABLE_DESCRIPTOR_COUNT];
+        let mut flags_create_info = vk::DescriptorSetLayoutBindingFlagsCreateInfo::builder()
+            .binding_flags(&binding_flags);
+
+        let mutable_type_list = [vk::MutableDescriptorTypeListEXT::builder()
+            .descriptor_types(&MUTABLE_DESCRIPTOR_TYPES)
+            .build()];
+        let mut mutable_types = vk::MutableDescriptorTypeCreateInfoEXT::builder()
+            .mutable_descriptor_type_lists(&mutable_type_list);
+
+        let mut create_info = vk::DescriptorSetLayoutCreateInfo::builder()
+            .push_next(&mut flags_create_info)
+            .flags(vk::DescriptorSetLayoutCreateFlags::DESCRIPTOR_BUFFER_EXT)
+            .bindings(&binding);
+        if descriptor_type == vk::DescriptorType::MUTABLE_EXT {
+            create_info = create_info.push_next(&mut mutable_types);
+        }
+
+        device.device.create_descriptor_set_layout(&create_info, device.alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.to_ral_error())
+    }
 }
 
 impl ral::PipelineLayoutInterface for PipelineLayout {
-
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.layout, name);
+    }
 }
 
 impl Drop for PipelineLayout {
@@ -133,7 +177,7 @@ pub struct Pipeline {
 // TODO: Look into more flags: https://registry.khronos.org/vulkan/specs/1.3-extensions/man/html/VkPipelineCreateFlagBits.html
 
 impl Pipeline {
-    pub unsafe fn new_graphics(device: &Device, desc: &ral::GraphicsPipelineDesc) -> ral::Result<ral::PipelineInterfaceHandle> {
+    pub unsafe fn new_graphics(device: &Device, desc: &ral::GraphicsPipelineDesc, cache: Option<&ral::PipelineCacheHandle>) -> ral::Result<ral::PipelineInterfaceHandle> {
         scoped_alloc!(AllocId::TlsTemp);
 
         let vertex_shader = desc.vertex_shader.interface().as_concrete_type::<Shader>();
@@ -156,15 +200,21 @@ impl Pipeline {
             let mut location_counters = [0; ral::constants::MAX_VERTEX_INPUT_BUFFERS as usize];
             let mut bindings_created  = [false; ral::constants::MAX_VERTEX_INPUT_BUFFERS as usize];
 
+            // Locations are matched to the vertex shader inputs by semantic, if the shader doesn't have a matching input, fall back to the order of the vertex attributes
+            let vertex_inputs = desc.vertex_shader.reflect().map_or(Vec::new(), |reflection| reflection.vertex_inputs);
+
             let strides = input_layout.calculate_strides();
             for element in &input_layout.elements {
                 let step_rate = element.step_rate.to_vulkan();
 
-                let location = location_counters[element.input_slot as usize];
+                let fallback_location = location_counters[element.input_slot as usize];
                 location_counters[element.input_slot as usize] += 1;
+
+                let location = vertex_inputs.iter()
+                    .find(|input| input.semantic_index == element.semantic_index && element.semantic().trim_end_matches('\0').eq_ignore_ascii_case(&input.semantic))
+                    .map_or(fallback_location, |input| input.location);
                 
                 vertex_attributes.push(vk::VertexInputAttributeDescription::builder()
-                // TODO: Currently we just expect vulkan locations to match the order of vertex attributes, but in the future this should be handled by either shader reflection or a custom shader system (shaders written in rust)
                 .location(location)
                 .binding(element.input_slot as u32)
                 .format(element.format.to_vulkan())
@@ -258,6 +308,12 @@ impl Pipeline {
         let depth_format = desc.depth_stencil_format.map_or(vk::Format::UNDEFINED, |format| if format.aspect().contains(TextureAspect::Depth) { format.to_vulkan() } else { vk::Format::UNDEFINED });
         let stencil_format = desc.depth_stencil_format.map_or(vk::Format::UNDEFINED, |format| if format.aspect().contains(TextureAspect::Stencil) { format.to_vulkan() } else { vk::Format::UNDEFINED });
 
+        let sample_mask = [desc.multisample_state.sample_mask];
+        let multisample_state = vk::PipelineMultisampleStateCreateInfo::builder()
+            .rasterization_samples(desc.multisample_state.samples.to_vulkan())
+            .sample_mask(&sample_mask)
+            .alpha_to_coverage_enable(desc.multisample_state.alpha_to_coverage);
+
         let mut blend_attachments = Vec::new();
         let blend_state = match desc.blend_state {
             ral::BlendState::None => vk::PipelineColorBlendStateCreateInfo::default(),
@@ -285,6 +341,8 @@ impl Pipeline {
             vk::DynamicState::STENCIL_REFERENCE,
             vk::DynamicState::PRIMITIVE_TOPOLOGY,
             vk::DynamicState::VERTEX_INPUT_BINDING_STRIDE,
+            // Set using `set_shading_rate`
+            vk::DynamicState::FRAGMENT_SHADING_RATE_KHR,
         ];
     
         let dynamic_state_info = vk::PipelineDynamicStateCreateInfo::builder()
@@ -306,13 +364,217 @@ impl Pipeline {
             .input_assembly_state(&input_assembly_state)
             .viewport_state(&viewport_state)
             .rasterization_state(&rasterizer_state)
+            .multisample_state(&multisample_state)
+            .depth_stencil_state(&depth_stencil_state)
+            .color_blend_state(&blend_state)
+            .dynamic_state(&dynamic_state_info)
+            .layout(layout)
+            .build();
+
+        let pipeline = device.device.create_graphics_pipelines(PipelineCache::get_vk_cache(cache), &[create_info], device.alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.1.to_ral_error())?[0];
+
+        Ok(ral::PipelineInterfaceHandle::new( Pipeline {
+            pipeline,
+            device: Arc::downgrade(&device.device),
+            alloc_callbacks: device.alloc_callbacks.clone(),
+        }))
+    }
+
+    pub unsafe fn new_compute(device: &Device, desc: &ral::ComputePipelineDesc, cache: Option<&ral::PipelineCacheHandle>) -> ral::Result<ral::PipelineInterfaceHandle> {
+        let compute_shader = desc.compute_shader.interface().as_concrete_type::<Shader>();
+        let layout = desc.pipeline_layout.interface().as_concrete_type::<PipelineLayout>().layout;
+
+        let create_info = vk::ComputePipelineCreateInfo::builder()
+            .flags(vk::PipelineCreateFlags::DESCRIPTOR_BUFFER_EXT)
+            .stage(compute_shader.get_shader_stage_info(ral::ShaderType::Compute))
+            .layout(layout)
+            .build();
+
+        let pipeline = device.device.create_compute_pipelines(PipelineCache::get_vk_cache(cache), &[create_info], device.alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.1.to_ral_error())?[0];
+
+        Ok(ral::PipelineInterfaceHandle::new( Pipeline {
+            pipeline,
+            device: Arc::downgrade(&device.device),
+            alloc_callbacks: device.alloc_callbacks.clone(),
+        }))
+    }
+
+    pub unsafe fn new_raytracing(device: &Device, desc: &ral::RaytracingPipelineDesc, cache: Option<&ral::PipelineCacheHandle>) -> ral::Result<ral::PipelineInterfaceHandle> {
+        scoped_alloc!(AllocId::TlsTemp);
+
+        let shader_stages = desc.shaders.iter().map(|shader| {
+            let shader_type = shader.shader_type();
+            shader.interface().as_concrete_type::<Shader>().get_shader_stage_info(shader_type)
+        }).collect::<Vec<_>>();
+
+        let shader_idx = |idx: Option<u32>| idx.unwrap_or(vk::SHADER_UNUSED_KHR);
+        let groups = desc.groups.iter().map(|group| match *group {
+            ral::RaytracingShaderGroup::General(shader) => vk::RayTracingShaderGroupCreateInfoKHR::builder()
+                .ty(vk::RayTracingShaderGroupTypeKHR::GENERAL)
+                .general_shader(shader)
+                .closest_hit_shader(vk::SHADER_UNUSED_KHR)
+                .any_hit_shader(vk::SHADER_UNUSED_KHR)
+                .intersection_shader(vk::SHADER_UNUSED_KHR)
+                .build(),
+            ral::RaytracingShaderGroup::TrianglesHitGroup { closest_hit, any_hit } => vk::RayTracingShaderGroupCreateInfoKHR::builder()
+                .ty(vk::RayTracingShaderGroupTypeKHR::TRIANGLES_HIT_GROUP)
+                .general_shader(vk::SHADER_UNUSED_KHR)
+                .closest_hit_shader(shader_idx(closest_hit))
+                .any_hit_shader(shader_idx(any_hit))
+                .intersection_shader(vk::SHADER_UNUSED_KHR)
+                .build(),
+            ral::RaytracingShaderGroup::ProceduralHitGroup { closest_hit, any_hit, intersection } => vk::RayTracingShaderGroupCreateInfoKHR::builder()
+                .ty(vk::RayTracingShaderGroupTypeKHR::PROCEDURAL_HIT_GROUP)
+                .general_shader(vk::SHADER_UNUSED_KHR)
+                .closest_hit_shader(shader_idx(closest_hit))
+                .any_hit_shader(shader_idx(any_hit))
+                .intersection_shader(intersection)
+                .build(),
+        }).collect::<Vec<_>>();
+
+        let layout = desc.pipeline_layout.interface().as_concrete_type::<PipelineLayout>().layout;
+
+        // NOTE: Payload and attribute sizes are defined by the shaders in vulkan
+        let create_info = vk::RayTracingPipelineCreateInfoKHR::builder()
+            .flags(vk::PipelineCreateFlags::DESCRIPTOR_BUFFER_EXT)
+            .stages(&shader_stages)
+            .groups(&groups)
+            .max_pipeline_ray_recursion_depth(desc.max_recursion_depth)
+            .layout(layout)
+            .build();
+
+        let pipeline = device.ray_tracing_pipeline.create_ray_tracing_pipelines(vk::DeferredOperationKHR::null(), PipelineCache::get_vk_cache(cache), &[create_info], device.alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.to_ral_error())?[0];
+
+        Ok(ral::PipelineInterfaceHandle::new( Pipeline {
+            pipeline,
+            device: Arc::downgrade(&device.device),
+            alloc_callbacks: device.alloc_callbacks.clone(),
+        }))
+    }
+
+    pub unsafe fn new_mesh(device: &Device, desc: &ral::MeshPipelineDesc, cache: Option<&ral::PipelineCacheHandle>) -> ral::Result<ral::PipelineInterfaceHandle> {
+        scoped_alloc!(AllocId::TlsTemp);
+
+        let mesh_shader = desc.mesh_shader.interface().as_concrete_type::<Shader>();
+        let pixel_shader = desc.pixel_shader.interface().as_concrete_type::<Shader>();
+
+        let mut shader_stages = Vec::with_capacity(3);
+        if let Some(task_shader) = &desc.task_shader {
+            let task_shader = task_shader.interface().as_concrete_type::<Shader>();
+            shader_stages.push(task_shader.get_shader_stage_info(ral::ShaderType::Task));
+        }
+        shader_stages.push(mesh_shader.get_shader_stage_info(ral::ShaderType::Mesh));
+        shader_stages.push(pixel_shader.get_shader_stage_info(ral::ShaderType::Pixel));
+
+        // We handle viewport dynamically, but we still need to pass this struct, so just create the default one
+        let viewport_state = vk::PipelineViewportStateCreateInfo::default();
+
+        let (depth_bias_enable, depth_bias, depth_bias_slope, depth_bias_clamp) = match desc.rasterizer_state.depth_bias {
+            Some(bias) => (true, bias.scale, bias.slope, bias.clamp),
+            None => (false, 0.0, 0.0, 0.0),
+        };
+
+        let mut conservative_rasterization_state = vk::PipelineRasterizationConservativeStateCreateInfoEXT::builder()
+            .conservative_rasterization_mode(desc.rasterizer_state.conservative_raster.to_vulkan())
+            .extra_primitive_overestimation_size(1.0 / 256.0); // This matches Tier 3 for DX12
+
+        let mut clip_enable_state = vk::PipelineRasterizationDepthClipStateCreateInfoEXT::builder()
+            .depth_clip_enable(desc.rasterizer_state.depth_clip_enable);
+
+        // We don't use line stipple
+        let mut line_raster_state = vk::PipelineRasterizationLineStateCreateInfoEXT::builder()
+            .line_rasterization_mode(desc.rasterizer_state.line_raster_mode.to_vulkan());
+
+        let rasterizer_state = vk::PipelineRasterizationStateCreateInfo::builder()
+            .depth_clamp_enable(true) // Is always enabled to match the functionality of other APIs
+            .rasterizer_discard_enable(false)
+            .polygon_mode(desc.rasterizer_state.fill_mode.to_vulkan())
+            .cull_mode(desc.rasterizer_state.cull_mode.to_vulkan())
+            .front_face(desc.rasterizer_state.winding_order.to_vulkan())
+            .depth_bias_enable(depth_bias_enable)
+            .depth_bias_constant_factor(depth_bias)
+            .depth_bias_slope_factor(depth_bias_slope)
+            .depth_bias_clamp(depth_bias_clamp)
+            .line_width(if desc.rasterizer_state.line_raster_mode == ral::LineRasterizationMode::RectangularWide { 1.4 } else { 1.0 })
+            .push_next(&mut conservative_rasterization_state)
+            .push_next(&mut clip_enable_state)
+            .push_next(&mut line_raster_state);
+
+        let depth_stencil_state = desc.depth_stencil_state.to_vulkan();
+
+        let mut rendertarget_formats = [vk::Format::UNDEFINED; ral::constants::MAX_RENDERTARGETS as usize];
+        let mut rendertarget_count = ral::constants::MAX_RENDERTARGETS as usize;
+
+        for (idx, format_opt) in desc.rendertarget_formats.iter().enumerate() {
+            if let Some(format) = format_opt {
+                rendertarget_formats[idx] = format.to_vulkan();
+                rendertarget_count = idx + 1;
+            }
+        }
+
+        let depth_format = desc.depth_stencil_format.map_or(vk::Format::UNDEFINED, |format| if format.aspect().contains(TextureAspect::Depth) { format.to_vulkan() } else { vk::Format::UNDEFINED });
+        let stencil_format = desc.depth_stencil_format.map_or(vk::Format::UNDEFINED, |format| if format.aspect().contains(TextureAspect::Stencil) { format.to_vulkan() } else { vk::Format::UNDEFINED });
+
+        let sample_mask = [desc.multisample_state.sample_mask];
+        let multisample_state = vk::PipelineMultisampleStateCreateInfo::builder()
+            .rasterization_samples(desc.multisample_state.samples.to_vulkan())
+            .sample_mask(&sample_mask)
+            .alpha_to_coverage_enable(desc.multisample_state.alpha_to_coverage);
+
+        let mut blend_attachments = Vec::new();
+        let blend_state = match desc.blend_state {
+            ral::BlendState::None => vk::PipelineColorBlendStateCreateInfo::default(),
+            ral::BlendState::LogicOp(logic_op) => vk::PipelineColorBlendStateCreateInfo::builder()
+                .logic_op_enable(true)
+                .logic_op(logic_op.to_vulkan())
+                .build(),
+            ral::BlendState::Blend(blend_states) => {
+                for state in blend_states.iter() {
+                    blend_attachments.push(state.to_vulkan());
+                }
+
+                vk::PipelineColorBlendStateCreateInfo::builder()
+                    .attachments(&blend_attachments[..rendertarget_count])
+                    .build()
+            },
+        };
+
+        // Mesh pipelines have no input assembly, so topology and vertex input state are not dynamic
+        let dynamic_states = [
+            vk::DynamicState::VIEWPORT_WITH_COUNT,
+            vk::DynamicState::SCISSOR_WITH_COUNT,
+            vk::DynamicState::DEPTH_BIAS,
+            vk::DynamicState::BLEND_CONSTANTS,
+            vk::DynamicState::DEPTH_BOUNDS,
+            vk::DynamicState::STENCIL_REFERENCE,
+            vk::DynamicState::FRAGMENT_SHADING_RATE_KHR,
+        ];
+    
+        let dynamic_state_info = vk::PipelineDynamicStateCreateInfo::builder()
+            .dynamic_states(&dynamic_states);
+
+        let layout = desc.pipeline_layout.interface().as_concrete_type::<PipelineLayout>().layout;
+
+        let mut rendering_create_info = vk::PipelineRenderingCreateInfo::builder()
+            .color_attachment_formats(&rendertarget_formats[..rendertarget_count])
+            .depth_attachment_format(depth_format)
+            .stencil_attachment_format(stencil_format)
+            .view_mask(desc.view_mask.unwrap_or_default() as u32);
+    
+        let create_info = vk::GraphicsPipelineCreateInfo::builder()
+            .push_next(&mut rendering_create_info)
+            .flags(vk::PipelineCreateFlags::DESCRIPTOR_BUFFER_EXT)
+            .stages(&shader_stages)
+            .viewport_state(&viewport_state)
+            .rasterization_state(&rasterizer_state)
+            .multisample_state(&multisample_state)
             .depth_stencil_state(&depth_stencil_state)
             .color_blend_state(&blend_state)
             .dynamic_state(&dynamic_state_info)
             .layout(layout)
             .build();
 
-        let pipeline = device.device.create_graphics_pipelines(vk::PipelineCache::null(), &[create_info], device.alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.1.to_ral_error())?[0];
+        let pipeline = device.device.create_graphics_pipelines(PipelineCache::get_vk_cache(cache), &[create_info], device.alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.1.to_ral_error())?[0];
 
         Ok(ral::PipelineInterfaceHandle::new( Pipeline {
             pipeline,
@@ -323,7 +585,9 @@ impl Pipeline {
 }
 
 impl ral::PipelineInterface for Pipeline {
-
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.pipeline, name);
+    }
 }
 
 impl Drop for Pipeline {
@@ -331,4 +595,49 @@ impl Drop for Pipeline {
         let device = Weak::upgrade(&self.device).unwrap();
         unsafe { device.destroy_pipeline(self.pipeline, self.alloc_callbacks.get_some_vk_callbacks()) };
     }
-}
\ No newline at end of file
+}
+//==============================================================================================================================
+
+pub struct PipelineCache {
+    pub cache:           vk::PipelineCache,
+    pub device:          Weak<ash::Device>,
+    pub alloc_callbacks: AllocationCallbacks,
+}
+
+impl PipelineCache {
+    pub unsafe fn new(device: &Device, data: &[u8]) -> ral::Result<ral::PipelineCacheInterfaceHandle> {
+        let create_info = vk::PipelineCacheCreateInfo::builder()
+            .initial_data(data);
+
+        // The driver validates the data against its own header, if it does not match, the data is ignored and an empty cache is created
+        let cache = device.device.create_pipeline_cache(&create_info, device.alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.to_ral_error())?;
+
+        Ok(ral::PipelineCacheInterfaceHandle::new(PipelineCache {
+            cache,
+            device: Arc::downgrade(&device.device),
+            alloc_callbacks: device.alloc_callbacks.clone(),
+        }))
+    }
+
+    pub fn get_vk_cache(cache: Option<&ral::PipelineCacheHandle>) -> vk::PipelineCache {
+        cache.map_or(vk::PipelineCache::null(), |cache| unsafe { cache.interface().as_concrete_type::<PipelineCache>().cache })
+    }
+}
+
+impl ral::PipelineCacheInterface for PipelineCache {
+    unsafe fn get_data(&self) -> ral::Result<Vec<u8>> {
+        let device = Weak::upgrade(&self.device).ok_or(ral::Error::UseAfterDeviceDropped)?;
+        device.get_pipeline_cache_data(self.cache).map_err(|err| err.to_ral_error())
+    }
+
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.cache, name);
+    }
+}
+
+impl Drop for PipelineCache {
+    fn drop(&mut self) {
+        let device = Weak::upgrade(&self.device).unwrap();
+        unsafe { device.destroy_pipeline_cache(self.cache, self.alloc_callbacks.get_some_vk_callbacks()) };
+    }
+}
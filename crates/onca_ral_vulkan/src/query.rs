@@ -0,0 +1,64 @@
+use std::sync::{Arc, Weak};
+
+use onca_ral as ral;
+use ash::vk;
+
+use crate::{utils::ToRalError, device::Device, vulkan::AllocationCallbacks, debug};
+
+pub struct QueryPool {
+    pub pool:            vk::QueryPool,
+    pub device:          Weak<ash::Device>,
+    pub alloc_callbacks: AllocationCallbacks,
+}
+
+impl QueryPool {
+    pub unsafe fn new(device: &Device, desc: &ral::QueryPoolDesc) -> ral::Result<ral::QueryPoolInterfaceHandle> {
+        let (query_type, pipeline_statistics) = match desc.query_type {
+            ral::QueryType::Timestamp          => (vk::QueryType::TIMESTAMP, vk::QueryPipelineStatisticFlags::empty()),
+            ral::QueryType::CopyQueueTimestamp => (vk::QueryType::TIMESTAMP, vk::QueryPipelineStatisticFlags::empty()),
+            // Flags are written out in the same order as the fields in `ral::PipelineStatistics`
+            ral::QueryType::PipelineStatistics => (vk::QueryType::PIPELINE_STATISTICS, 
+                vk::QueryPipelineStatisticFlags::INPUT_ASSEMBLY_VERTICES |
+                vk::QueryPipelineStatisticFlags::INPUT_ASSEMBLY_PRIMITIVES |
+                vk::QueryPipelineStatisticFlags::VERTEX_SHADER_INVOCATIONS |
+                vk::QueryPipelineStatisticFlags::GEOMETRY_SHADER_INVOCATIONS |
+                vk::QueryPipelineStatisticFlags::GEOMETRY_SHADER_PRIMITIVES |
+                vk::QueryPipelineStatisticFlags::CLIPPING_INVOCATIONS |
+                vk::QueryPipelineStatisticFlags::CLIPPING_PRIMITIVES |
+                vk::QueryPipelineStatisticFlags::FRAGMENT_SHADER_INVOCATIONS |
+                vk::QueryPipelineStatisticFlags::TESSELLATION_CONTROL_SHADER_PATCHES |
+                vk::QueryPipelineStatisticFlags::TESSELLATION_EVALUATION_SHADER_INVOCATIONS |
+                vk::QueryPipelineStatisticFlags::COMPUTE_SHADER_INVOCATIONS
+            ),
+            // Binary occlusion queries are occlusion queries without `vk::QueryControlFlags::PRECISE`
+            ral::QueryType::Occlusion |
+            ral::QueryType::BinaryOcclusion    => (vk::QueryType::OCCLUSION, vk::QueryPipelineStatisticFlags::empty()),
+        };
+
+        let create_info = vk::QueryPoolCreateInfo::builder()
+            .query_type(query_type)
+            .query_count(desc.count)
+            .pipeline_statistics(pipeline_statistics);
+
+        let pool = device.device.create_query_pool(&create_info, device.alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.to_ral_error())?;
+
+        Ok(ral::QueryPoolInterfaceHandle::new(QueryPool {
+            pool,
+            device: Arc::downgrade(&device.device),
+            alloc_callbacks: device.alloc_callbacks.clone(),
+        }))
+    }
+}
+
+impl ral::QueryPoolInterface for QueryPool {
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.pool, name);
+    }
+}
+
+impl Drop for QueryPool {
+    fn drop(&mut self) {
+        let device = Weak::upgrade(&self.device).unwrap();
+        unsafe { device.destroy_query_pool(self.pool, self.alloc_callbacks.get_some_vk_callbacks()) };
+    }
+}
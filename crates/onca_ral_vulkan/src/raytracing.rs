@@ -0,0 +1,176 @@
+use std::sync::{Arc, Weak};
+
+use onca_ral as ral;
+use ash::{vk, extensions::khr};
+use ral::HandleImpl;
+
+use crate::{utils::{ToRalError, ToVulkan}, device::Device, buffer::Buffer, vulkan::AllocationCallbacks, debug};
+
+pub struct AccelerationStructure {
+    pub acceleration_structure: vk::AccelerationStructureKHR,
+    /// Query pool used to retrieve the compacted size of the acceleration structure
+    pub compacted_size_pool:    vk::QueryPool,
+    pub device:                 Weak<ash::Device>,
+    pub loader:                 khr::AccelerationStructure,
+    pub alloc_callbacks:        AllocationCallbacks,
+}
+
+impl AccelerationStructure {
+    pub unsafe fn new(device: &Device, desc: &ral::AccelerationStructureDesc) -> ral::Result<(ral::AccelerationStructureInterfaceHandle, ral::GpuAddress)> {
+        let buffer = desc.buffer.interface().as_concrete_type::<Buffer>().buffer;
+
+        let create_info = vk::AccelerationStructureCreateInfoKHR::builder()
+            .buffer(buffer)
+            .offset(desc.offset)
+            .size(desc.size)
+            .ty(desc.ty.to_vulkan());
+
+        let acceleration_structure = device.acceleration_structure.create_acceleration_structure(&create_info, device.alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.to_ral_error())?;
+
+        let address_info = vk::AccelerationStructureDeviceAddressInfoKHR::builder()
+            .acceleration_structure(acceleration_structure);
+        let address = device.acceleration_structure.get_acceleration_structure_device_address(&address_info);
+
+        let query_create_info = vk::QueryPoolCreateInfo::builder()
+            .query_type(vk::QueryType::ACCELERATION_STRUCTURE_COMPACTED_SIZE_KHR)
+            .query_count(1);
+        let compacted_size_pool = match device.device.create_query_pool(&query_create_info, device.alloc_callbacks.get_some_vk_callbacks()) {
+            Ok(pool) => pool,
+            Err(err) => {
+                device.acceleration_structure.destroy_acceleration_structure(acceleration_structure, device.alloc_callbacks.get_some_vk_callbacks());
+                return Err(err.to_ral_error());
+            },
+        };
+
+        Ok((ral::AccelerationStructureInterfaceHandle::new(AccelerationStructure {
+                acceleration_structure,
+                compacted_size_pool,
+                device: Arc::downgrade(&device.device),
+                loader: device.acceleration_structure.clone(),
+                alloc_callbacks: device.alloc_callbacks.clone(),
+            }),
+            ral::GpuAddress::new(address)
+        ))
+    }
+}
+
+impl ral::AccelerationStructureInterface for AccelerationStructure {
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.acceleration_structure, name);
+    }
+}
+
+impl Drop for AccelerationStructure {
+    fn drop(&mut self) {
+        let device = Weak::upgrade(&self.device).unwrap();
+        unsafe {
+            device.destroy_query_pool(self.compacted_size_pool, self.alloc_callbacks.get_some_vk_callbacks());
+            self.loader.destroy_acceleration_structure(self.acceleration_structure, self.alloc_callbacks.get_some_vk_callbacks());
+        }
+    }
+}
+
+/// Acceleration structure build geometry and the build range for each geometry
+pub struct BuildGeometry {
+    pub geometry: Vec<vk::AccelerationStructureGeometryKHR>,
+    pub ranges:   Vec<vk::AccelerationStructureBuildRangeInfoKHR>,
+}
+
+impl BuildGeometry {
+    pub fn new(inputs: &ral::AccelerationStructureInputs) -> Self {
+        match inputs {
+            ral::AccelerationStructureInputs::BottomLevel(geometry) => Self {
+                geometry: geometry.iter().map(get_geometry).collect(),
+                ranges: geometry.iter().map(|geometry| vk::AccelerationStructureBuildRangeInfoKHR {
+                    primitive_count: geometry.primitive_count(),
+                    primitive_offset: 0,
+                    first_vertex: 0,
+                    transform_offset: 0,
+                }).collect(),
+            },
+            ral::AccelerationStructureInputs::TopLevel { instances, offset, count } => {
+                let instances = vk::AccelerationStructureGeometryInstancesDataKHR::builder()
+                    .array_of_pointers(false)
+                    .data(vk::DeviceOrHostAddressConstKHR { device_address: instances.gpu_address().at(*offset).as_raw() })
+                    .build();
+
+                Self {
+                    geometry: vec![vk::AccelerationStructureGeometryKHR::builder()
+                        .geometry_type(vk::GeometryTypeKHR::INSTANCES)
+                        .geometry(vk::AccelerationStructureGeometryDataKHR { instances })
+                        .build()
+                    ],
+                    ranges: vec![vk::AccelerationStructureBuildRangeInfoKHR {
+                        primitive_count: *count,
+                        primitive_offset: 0,
+                        first_vertex: 0,
+                        transform_offset: 0,
+                    }],
+                }
+            },
+        }
+    }
+
+    /// Get the build info for the geometry, the build info references the geometry, so it needs to be kept alive while the build info is used
+    pub fn get_build_info(&self, ty: ral::AccelerationStructureType, flags: ral::AccelerationStructureBuildFlags) -> vk::AccelerationStructureBuildGeometryInfoKHR {
+        vk::AccelerationStructureBuildGeometryInfoKHR::builder()
+            .ty(ty.to_vulkan())
+            .flags(flags.to_vulkan())
+            .mode(vk::BuildAccelerationStructureModeKHR::BUILD)
+            .geometries(&self.geometry)
+            .build()
+    }
+}
+
+fn get_geometry(geometry: &ral::RaytracingGeometry) -> vk::AccelerationStructureGeometryKHR {
+    match &geometry.data {
+        ral::RaytracingGeometryData::Triangles(triangles) => {
+            let (index_type, index_address) = match &triangles.indices {
+                Some(indices) => (indices.format.to_vulkan(), indices.buffer.gpu_address().at(indices.offset).as_raw()),
+                None => (vk::IndexType::NONE_KHR, 0),
+            };
+            let transform_address = triangles.transform.as_ref().map_or(0, |(buffer, offset)| buffer.gpu_address().at(*offset).as_raw());
+
+            let triangles = vk::AccelerationStructureGeometryTrianglesDataKHR::builder()
+                .vertex_format(triangles.vertex_format.to_vulkan())
+                .vertex_data(vk::DeviceOrHostAddressConstKHR { device_address: triangles.vertex_buffer.gpu_address().at(triangles.vertex_offset).as_raw() })
+                .vertex_stride(triangles.vertex_stride)
+                .max_vertex(triangles.vertex_count.saturating_sub(1))
+                .index_type(index_type)
+                .index_data(vk::DeviceOrHostAddressConstKHR { device_address: index_address })
+                .transform_data(vk::DeviceOrHostAddressConstKHR { device_address: transform_address })
+                .build();
+
+            vk::AccelerationStructureGeometryKHR::builder()
+                .geometry_type(vk::GeometryTypeKHR::TRIANGLES)
+                .geometry(vk::AccelerationStructureGeometryDataKHR { triangles })
+                .flags(geometry.flags.to_vulkan())
+                .build()
+        },
+        ral::RaytracingGeometryData::Aabbs(aabbs) => {
+            let aabbs = vk::AccelerationStructureGeometryAabbsDataKHR::builder()
+                .data(vk::DeviceOrHostAddressConstKHR { device_address: aabbs.buffer.gpu_address().at(aabbs.offset).as_raw() })
+                .stride(aabbs.stride)
+                .build();
+
+            vk::AccelerationStructureGeometryKHR::builder()
+                .geometry_type(vk::GeometryTypeKHR::AABBS)
+                .geometry(vk::AccelerationStructureGeometryDataKHR { aabbs })
+                .flags(geometry.flags.to_vulkan())
+                .build()
+        },
+    }
+}
+
+pub unsafe fn get_build_sizes(device: &Device, inputs: &ral::AccelerationStructureInputs, flags: ral::AccelerationStructureBuildFlags) -> ral::Result<ral::AccelerationStructureBuildSizes> {
+    let geometry = BuildGeometry::new(inputs);
+    let build_info = geometry.get_build_info(inputs.ty(), flags);
+    let max_primitive_counts = geometry.ranges.iter().map(|range| range.primitive_count).collect::<Vec<_>>();
+
+    let sizes = device.acceleration_structure.get_acceleration_structure_build_sizes(vk::AccelerationStructureBuildTypeKHR::DEVICE, &build_info, &max_primitive_counts);
+    Ok(ral::AccelerationStructureBuildSizes {
+        size: sizes.acceleration_structure_size,
+        build_scratch_size: sizes.build_scratch_size,
+        update_scratch_size: sizes.update_scratch_size,
+    })
+}
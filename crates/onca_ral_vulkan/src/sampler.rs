@@ -1,10 +1,13 @@
+use std::sync::{Arc, Weak};
+
 use onca_ral as ral;
 use ash::vk;
 
-use crate::{device::Device, utils::{ToRalError, ToVulkan}};
+use crate::{device::Device, utils::{ToRalError, ToVulkan}, debug};
 
 pub struct StaticSampler {
-    pub sampler: vk::Sampler
+    pub sampler: vk::Sampler,
+    pub device:  Weak<ash::Device>,
 }
 
 impl StaticSampler {
@@ -28,17 +31,21 @@ impl StaticSampler {
             .build();
 
         let sampler = device.device.create_sampler(&create_info, device.alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.to_ral_error())?;
-        Ok(ral::StaticSamplerInterfaceHandle::new(StaticSampler { sampler }))
+        Ok(ral::StaticSamplerInterfaceHandle::new(StaticSampler { sampler, device: Arc::downgrade(&device.device) }))
     }
 }
 
 impl ral::StaticSamplerInterface for StaticSampler {
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.sampler, name);
+    }
 }
 
 //==============================================================================================================================
 
 pub struct Sampler {
-    pub sampler: vk::Sampler
+    pub sampler: vk::Sampler,
+    pub device:  Weak<ash::Device>,
 }
 
 impl Sampler {
@@ -82,9 +89,12 @@ impl Sampler {
         }
 
         let sampler = device.device.create_sampler(&create_info, device.alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.to_ral_error())?;
-        Ok(ral::SamplerInterfaceHandle::new(Sampler { sampler }))
+        Ok(ral::SamplerInterfaceHandle::new(Sampler { sampler, device: Arc::downgrade(&device.device) }))
     }
 }
 
 impl ral::SamplerInterface for Sampler {
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.sampler, name);
+    }
 }
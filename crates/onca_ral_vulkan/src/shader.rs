@@ -1,15 +1,20 @@
-use core::ffi::CStr;
-use std::sync::{Arc, Weak};
+use core::{ffi::CStr, num::NonZeroU32};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Weak},
+};
 
 use onca_ral as ral;
 use ash::vk;
 
-use crate::{device::Device, utils::{ToRalError, ToVulkan}, vulkan::AllocationCallbacks};
+use crate::{device::Device, utils::{ToRalError, ToVulkan}, vulkan::AllocationCallbacks, debug};
 
 
 /// Shader modules get deprecated in 'VK_KHR_maintenance5'
 pub struct Shader {
     pub shader: vk::ShaderModule,
+    /// SPIR-V code, kept around for reflection
+    pub code: Vec<u32>,
     pub alloc_callbacks: AllocationCallbacks,
     pub device: Weak<ash::Device>,
 }
@@ -29,6 +34,7 @@ impl Shader {
         
         Ok(ral::ShaderInterfaceHandle::new(Self {
             shader,
+            code: code_u32.to_vec(),
             alloc_callbacks: device.alloc_callbacks.clone(),
             device: Arc::downgrade(&device.device),
         }))
@@ -48,7 +54,13 @@ impl Shader {
 }
 
 impl ral::ShaderInterface for Shader {
+    fn reflect(&self, shader_type: ral::ShaderType) -> ral::Result<ral::ShaderReflection> {
+        SpirvReflector::new(&self.code)?.reflect(shader_type)
+    }
 
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.shader, name);
+    }
 }
 
 impl Drop for Shader {
@@ -56,4 +68,411 @@ impl Drop for Shader {
         let device = Weak::upgrade(&self.device).unwrap();
         unsafe { device.destroy_shader_module(self.shader, self.alloc_callbacks.get_some_vk_callbacks()) };
     }
-}
\ No newline at end of file
+}
+
+//==============================================================================================================================
+// SPIR-V reflection
+//==============================================================================================================================
+
+mod spirv {
+    pub const MAGIC : u32 = 0x07230203;
+    pub const HEADER_SIZE : usize = 5;
+
+    pub const OP_NAME : u32 = 5;
+    pub const OP_ENTRY_POINT : u32 = 15;
+    pub const OP_EXECUTION_MODE : u32 = 16;
+    pub const OP_TYPE_INT : u32 = 21;
+    pub const OP_TYPE_FLOAT : u32 = 22;
+    pub const OP_TYPE_VECTOR : u32 = 23;
+    pub const OP_TYPE_MATRIX : u32 = 24;
+    pub const OP_TYPE_IMAGE : u32 = 25;
+    pub const OP_TYPE_SAMPLER : u32 = 26;
+    pub const OP_TYPE_SAMPLED_IMAGE : u32 = 27;
+    pub const OP_TYPE_ARRAY : u32 = 28;
+    pub const OP_TYPE_RUNTIME_ARRAY : u32 = 29;
+    pub const OP_TYPE_STRUCT : u32 = 30;
+    pub const OP_TYPE_POINTER : u32 = 32;
+    pub const OP_CONSTANT : u32 = 43;
+    pub const OP_VARIABLE : u32 = 59;
+    pub const OP_DECORATE : u32 = 71;
+    pub const OP_MEMBER_DECORATE : u32 = 72;
+    pub const OP_EXECUTION_MODE_ID : u32 = 331;
+    pub const OP_TYPE_ACCELERATION_STRUCTURE : u32 = 5341;
+    pub const OP_DECORATE_STRING : u32 = 5632;
+
+    pub const DECORATION_BLOCK : u32 = 2;
+    pub const DECORATION_BUFFER_BLOCK : u32 = 3;
+    pub const DECORATION_ARRAY_STRIDE : u32 = 6;
+    pub const DECORATION_MATRIX_STRIDE : u32 = 7;
+    pub const DECORATION_BUILT_IN : u32 = 11;
+    pub const DECORATION_NON_WRITABLE : u32 = 24;
+    pub const DECORATION_LOCATION : u32 = 30;
+    pub const DECORATION_BINDING : u32 = 33;
+    pub const DECORATION_DESCRIPTOR_SET : u32 = 34;
+    pub const DECORATION_OFFSET : u32 = 35;
+    pub const DECORATION_USER_SEMANTIC : u32 = 5635;
+
+    pub const STORAGE_CLASS_INPUT : u32 = 1;
+    pub const STORAGE_CLASS_UNIFORM : u32 = 2;
+    pub const STORAGE_CLASS_PUSH_CONSTANT : u32 = 9;
+    pub const STORAGE_CLASS_STORAGE_BUFFER : u32 = 12;
+
+    pub const EXECUTION_MODE_LOCAL_SIZE : u32 = 17;
+    pub const EXECUTION_MODE_LOCAL_SIZE_ID : u32 = 38;
+
+    pub const DIM_BUFFER : u32 = 5;
+    pub const IMAGE_SAMPLED_STORAGE : u32 = 2;
+}
+
+enum SpirvType {
+    Int { width: u32, signed: bool },
+    Float { width: u32 },
+    Vector { component: u32, count: u32 },
+    Matrix { column: u32, count: u32 },
+    Image { dim: u32, sampled: u32 },
+    Sampler,
+    SampledImage,
+    Array { element: u32, length: u32 },
+    RuntimeArray { element: u32 },
+    Struct { members: Vec<u32> },
+    Pointer { pointee: u32 },
+    AccelerationStructure,
+}
+
+#[derive(Default)]
+struct SpirvDecorations {
+    set:          Option<u32>,
+    binding:      Option<u32>,
+    location:     Option<u32>,
+    built_in:     bool,
+    block:        bool,
+    buffer_block: bool,
+    non_writable: bool,
+    array_stride: Option<u32>,
+    semantic:     Option<String>,
+}
+
+#[derive(Default)]
+struct SpirvMemberDecorations {
+    offset:        u32,
+    matrix_stride: Option<u32>,
+    non_writable:  bool,
+}
+
+struct SpirvVariable {
+    id:            u32,
+    type_id:       u32,
+    storage_class: u32,
+}
+
+/// Minimal SPIR-V parser, only extracting the info needed for `ral::ShaderReflection`
+#[derive(Default)]
+struct SpirvReflector {
+    names:              HashMap<u32, String>,
+    types:              HashMap<u32, SpirvType>,
+    constants:          HashMap<u32, u32>,
+    decorations:        HashMap<u32, SpirvDecorations>,
+    member_decorations: HashMap<(u32, u32), SpirvMemberDecorations>,
+    variables:          Vec<SpirvVariable>,
+    entry_point:        Option<u32>,
+    interface:          Vec<u32>,
+    local_size:         Option<[u32; 3]>,
+    local_size_ids:     Option<[u32; 3]>,
+}
+
+impl SpirvReflector {
+    fn new(code: &[u32]) -> ral::Result<Self> {
+        if code.len() < spirv::HEADER_SIZE || code[0] != spirv::MAGIC {
+            return Err(ral::Error::InvalidShaderCode("Shader code is not valid SPIR-V"));
+        }
+
+        let mut reflector = Self::default();
+        let mut offset = spirv::HEADER_SIZE;
+        while offset < code.len() {
+            let word_count = (code[offset] >> 16) as usize;
+            let opcode = code[offset] & 0xFFFF;
+            if word_count == 0 || offset + word_count > code.len() {
+                return Err(ral::Error::InvalidShaderCode("Invalid SPIR-V instruction"));
+            }
+            reflector.parse_instruction(opcode, &code[offset + 1..offset + word_count]);
+            offset += word_count;
+        }
+        Ok(reflector)
+    }
+
+    fn parse_instruction(&mut self, opcode: u32, ops: &[u32]) {
+        match opcode {
+            spirv::OP_NAME if ops.len() >= 2 => {
+                self.names.insert(ops[0], read_string(&ops[1..]));
+            },
+            // Only a single entry point is supported, so take the first one
+            spirv::OP_ENTRY_POINT if ops.len() >= 3 && self.entry_point.is_none() => {
+                self.entry_point = Some(ops[1]);
+                let name_len = (read_string(&ops[2..]).len() + 4) / 4;
+                self.interface = ops.get(2 + name_len..).map_or(Vec::new(), |ids| ids.to_vec());
+            },
+            spirv::OP_EXECUTION_MODE if ops.len() >= 5 && Some(ops[0]) == self.entry_point && ops[1] == spirv::EXECUTION_MODE_LOCAL_SIZE => {
+                self.local_size = Some([ops[2], ops[3], ops[4]]);
+            },
+            spirv::OP_EXECUTION_MODE_ID if ops.len() >= 5 && Some(ops[0]) == self.entry_point && ops[1] == spirv::EXECUTION_MODE_LOCAL_SIZE_ID => {
+                self.local_size_ids = Some([ops[2], ops[3], ops[4]]);
+            },
+            spirv::OP_TYPE_INT if ops.len() >= 3 => {
+                self.types.insert(ops[0], SpirvType::Int { width: ops[1], signed: ops[2] != 0 });
+            },
+            spirv::OP_TYPE_FLOAT if ops.len() >= 2 => {
+                self.types.insert(ops[0], SpirvType::Float { width: ops[1] });
+            },
+            spirv::OP_TYPE_VECTOR if ops.len() >= 3 => {
+                self.types.insert(ops[0], SpirvType::Vector { component: ops[1], count: ops[2] });
+            },
+            spirv::OP_TYPE_MATRIX if ops.len() >= 3 => {
+                self.types.insert(ops[0], SpirvType::Matrix { column: ops[1], count: ops[2] });
+            },
+            spirv::OP_TYPE_IMAGE if ops.len() >= 7 => {
+                self.types.insert(ops[0], SpirvType::Image { dim: ops[2], sampled: ops[6] });
+            },
+            spirv::OP_TYPE_SAMPLER if ops.len() >= 1 => {
+                self.types.insert(ops[0], SpirvType::Sampler);
+            },
+            spirv::OP_TYPE_SAMPLED_IMAGE if ops.len() >= 1 => {
+                self.types.insert(ops[0], SpirvType::SampledImage);
+            },
+            spirv::OP_TYPE_ARRAY if ops.len() >= 3 => {
+                self.types.insert(ops[0], SpirvType::Array { element: ops[1], length: ops[2] });
+            },
+            spirv::OP_TYPE_RUNTIME_ARRAY if ops.len() >= 2 => {
+                self.types.insert(ops[0], SpirvType::RuntimeArray { element: ops[1] });
+            },
+            spirv::OP_TYPE_STRUCT if ops.len() >= 1 => {
+                self.types.insert(ops[0], SpirvType::Struct { members: ops[1..].to_vec() });
+            },
+            spirv::OP_TYPE_POINTER if ops.len() >= 3 => {
+                self.types.insert(ops[0], SpirvType::Pointer { pointee: ops[2] });
+            },
+            spirv::OP_TYPE_ACCELERATION_STRUCTURE if ops.len() >= 1 => {
+                self.types.insert(ops[0], SpirvType::AccelerationStructure);
+            },
+            // Only 32-bit constants are needed, as these are only used for array lengths and local sizes
+            spirv::OP_CONSTANT if ops.len() >= 3 => {
+                self.constants.insert(ops[1], ops[2]);
+            },
+            spirv::OP_VARIABLE if ops.len() >= 3 => {
+                self.variables.push(SpirvVariable { id: ops[1], type_id: ops[0], storage_class: ops[2] });
+            },
+            spirv::OP_DECORATE if ops.len() >= 2 => {
+                let decorations = self.decorations.entry(ops[0]).or_default();
+                let literal = ops.get(2).copied();
+                match ops[1] {
+                    spirv::DECORATION_BLOCK          => decorations.block = true,
+                    spirv::DECORATION_BUFFER_BLOCK   => decorations.buffer_block = true,
+                    spirv::DECORATION_ARRAY_STRIDE   => decorations.array_stride = literal,
+                    spirv::DECORATION_BUILT_IN       => decorations.built_in = true,
+                    spirv::DECORATION_NON_WRITABLE   => decorations.non_writable = true,
+                    spirv::DECORATION_LOCATION       => decorations.location = literal,
+                    spirv::DECORATION_BINDING        => decorations.binding = literal,
+                    spirv::DECORATION_DESCRIPTOR_SET => decorations.set = literal,
+                    _ => {},
+                }
+            },
+            spirv::OP_DECORATE_STRING if ops.len() >= 3 && ops[1] == spirv::DECORATION_USER_SEMANTIC => {
+                self.decorations.entry(ops[0]).or_default().semantic = Some(read_string(&ops[2..]));
+            },
+            spirv::OP_MEMBER_DECORATE if ops.len() >= 3 => {
+                let decorations = self.member_decorations.entry((ops[0], ops[1])).or_default();
+                let literal = ops.get(3).copied();
+                match ops[2] {
+                    spirv::DECORATION_OFFSET         => decorations.offset = literal.unwrap_or(0),
+                    spirv::DECORATION_MATRIX_STRIDE  => decorations.matrix_stride = literal,
+                    spirv::DECORATION_NON_WRITABLE   => decorations.non_writable = true,
+                    _ => {},
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn reflect(&self, shader_type: ral::ShaderType) -> ral::Result<ral::ShaderReflection> {
+        let mut reflection = ral::ShaderReflection::new(shader_type);
+
+        for var in &self.variables {
+            let Some(SpirvType::Pointer { pointee }) = self.types.get(&var.type_id) else { continue };
+            let decorations = self.decorations.get(&var.id);
+
+            match var.storage_class {
+                spirv::STORAGE_CLASS_PUSH_CONSTANT => {
+                    if let Some(range) = self.get_push_constant_range(*pointee) {
+                        reflection.push_constants.push(range);
+                    }
+                },
+                spirv::STORAGE_CLASS_INPUT => {
+                    if shader_type != ral::ShaderType::Vertex || !self.interface.contains(&var.id) {
+                        continue;
+                    }
+                    let Some(decorations) = decorations else { continue };
+                    if decorations.built_in {
+                        continue;
+                    }
+                    let Some(location) = decorations.location else { continue };
+
+                    // DXC names inputs as 'in.var.<SEMANTIC>' when no explicit semantic decoration is available
+                    let semantic = decorations.semantic.as_ref()
+                        .or_else(|| self.names.get(&var.id))
+                        .map_or("", |name| name.strip_prefix("in.var.").unwrap_or(name));
+                    let (semantic, semantic_index) = ral::ShaderReflection::split_semantic(semantic);
+                    let (components, data_type) = self.get_input_type(*pointee).ok_or(ral::Error::InvalidShaderCode("Unsupported SPIR-V vertex input type"))?;
+
+                    reflection.vertex_inputs.push(ral::ShaderVertexInput {
+                        semantic,
+                        semantic_index,
+                        location,
+                        components,
+                        data_type,
+                    });
+                },
+                _ => {
+                    let Some(decorations) = decorations else { continue };
+                    let (Some(set), Some(binding)) = (decorations.set, decorations.binding) else { continue };
+                    let (binding_type, count) = self.get_binding_type(*pointee, var.storage_class, decorations.non_writable)
+                        .ok_or(ral::Error::InvalidShaderCode("Unsupported SPIR-V resource type"))?;
+
+                    reflection.add_binding(set, ral::ShaderBinding {
+                        name: self.names.get(&var.id).filter(|name| !name.is_empty()).cloned(),
+                        binding_type,
+                        binding,
+                        count,
+                    });
+                },
+            }
+        }
+
+        reflection.vertex_inputs.sort_by_key(|input| input.location);
+
+        if matches!(shader_type, ral::ShaderType::Compute | ral::ShaderType::Task | ral::ShaderType::Mesh) {
+            reflection.thread_group_size = match (self.local_size, self.local_size_ids) {
+                (Some(size), _) => Some(size),
+                (None, Some(ids)) => Some([
+                    self.constants.get(&ids[0]).copied().unwrap_or(1),
+                    self.constants.get(&ids[1]).copied().unwrap_or(1),
+                    self.constants.get(&ids[2]).copied().unwrap_or(1),
+                ]),
+                (None, None) => None,
+            };
+        }
+
+        Ok(reflection)
+    }
+
+    fn get_binding_type(&self, type_id: u32, storage_class: u32, non_writable: bool) -> Option<(ral::ShaderBindingType, Option<NonZeroU32>)> {
+        let (type_id, count) = match self.types.get(&type_id)? {
+            SpirvType::Array { element, length } => (*element, NonZeroU32::new(*self.constants.get(length)?)),
+            SpirvType::RuntimeArray { element } => (*element, None),
+            _ => (type_id, NonZeroU32::new(1)),
+        };
+
+        let binding_type = match self.types.get(&type_id)? {
+            SpirvType::Sampler => ral::ShaderBindingType::Sampler,
+            SpirvType::Image { dim, sampled } => match (*dim == spirv::DIM_BUFFER, *sampled == spirv::IMAGE_SAMPLED_STORAGE) {
+                (true, true)   => ral::ShaderBindingType::StorageTexelBuffer,
+                (true, false)  => ral::ShaderBindingType::ConstantTexelBuffer,
+                (false, true)  => ral::ShaderBindingType::StorageTexture,
+                (false, false) => ral::ShaderBindingType::SampledTexture,
+            },
+            SpirvType::SampledImage => ral::ShaderBindingType::SampledTexture,
+            SpirvType::AccelerationStructure => ral::ShaderBindingType::AccelerationStructure,
+            SpirvType::Struct { members } => {
+                let decorations = self.decorations.get(&type_id);
+                let is_block = decorations.map_or(false, |dec| dec.block);
+                let is_buffer_block = decorations.map_or(false, |dec| dec.buffer_block);
+
+                if storage_class == spirv::STORAGE_CLASS_UNIFORM && is_block && !is_buffer_block {
+                    ral::ShaderBindingType::ConstantBuffer
+                } else if storage_class == spirv::STORAGE_CLASS_UNIFORM || storage_class == spirv::STORAGE_CLASS_STORAGE_BUFFER {
+                    let read_only = non_writable || (0..members.len() as u32).all(|idx|
+                        self.member_decorations.get(&(type_id, idx)).map_or(false, |dec| dec.non_writable)
+                    );
+                    if read_only {
+                        ral::ShaderBindingType::ReadOnlyStorageBuffer
+                    } else {
+                        ral::ShaderBindingType::StorageBuffer
+                    }
+                } else {
+                    return None;
+                }
+            },
+            _ => return None,
+        };
+        Some((binding_type, count))
+    }
+
+    fn get_push_constant_range(&self, type_id: u32) -> Option<ral::ShaderPushConstantRange> {
+        let SpirvType::Struct { members } = self.types.get(&type_id)? else { return None };
+
+        let mut start = u32::MAX;
+        let mut end = 0;
+        for (idx, member) in members.iter().enumerate() {
+            let decorations = self.member_decorations.get(&(type_id, idx as u32));
+            let offset = decorations.map_or(0, |dec| dec.offset);
+            let size = self.get_type_size(*member, decorations.and_then(|dec| dec.matrix_stride))?;
+            start = start.min(offset);
+            end = end.max(offset + size);
+        }
+
+        if start >= end {
+            None
+        } else {
+            Some(ral::ShaderPushConstantRange { offset: start, size: end - start })
+        }
+    }
+
+    fn get_type_size(&self, type_id: u32, matrix_stride: Option<u32>) -> Option<u32> {
+        match self.types.get(&type_id)? {
+            SpirvType::Int { width, .. } |
+            SpirvType::Float { width }   => Some(width / 8),
+            SpirvType::Vector { component, count } => Some(self.get_type_size(*component, None)? * count),
+            SpirvType::Matrix { column, count } => Some(matrix_stride.map_or_else(|| self.get_type_size(*column, None), |stride| Some(stride))? * count),
+            SpirvType::Array { element, length } => {
+                let length = *self.constants.get(length)?;
+                let stride = self.decorations.get(&type_id).and_then(|dec| dec.array_stride);
+                Some(stride.map_or_else(|| self.get_type_size(*element, matrix_stride), |stride| Some(stride))? * length)
+            },
+            SpirvType::Struct { members } => {
+                let mut size = 0;
+                for (idx, member) in members.iter().enumerate() {
+                    let decorations = self.member_decorations.get(&(type_id, idx as u32));
+                    let offset = decorations.map_or(0, |dec| dec.offset);
+                    size = size.max(offset + self.get_type_size(*member, decorations.and_then(|dec| dec.matrix_stride))?);
+                }
+                Some(size)
+            },
+            _ => None,
+        }
+    }
+
+    fn get_input_type(&self, type_id: u32) -> Option<(u8, ral::VertexDataType)> {
+        match self.types.get(&type_id)? {
+            SpirvType::Float { .. } => Some((1, ral::VertexDataType::SFloat)),
+            SpirvType::Int { signed: true, .. } => Some((1, ral::VertexDataType::SInt)),
+            SpirvType::Int { signed: false, .. } => Some((1, ral::VertexDataType::Uint)),
+            SpirvType::Vector { component, count } => {
+                let (_, data_type) = self.get_input_type(*component)?;
+                Some((*count as u8, data_type))
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Read a null-terminated literal string, packed into 32-bit words
+fn read_string(words: &[u32]) -> String {
+    let mut bytes = Vec::new();
+    'outer: for word in words {
+        for byte in word.to_le_bytes() {
+            if byte == 0 {
+                break 'outer;
+            }
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
@@ -1,4 +1,4 @@
-use core::{ffi::c_void, cell::Cell};
+use core::{ffi::c_void, cell::Cell, time::Duration, ptr::null_mut};
 use std::sync::{Weak, Arc};
 
 use cfg_if::cfg_if;
@@ -7,21 +7,33 @@ use onca_common::{collections::BitSet, prelude::*,};
 use onca_ral as ral;
 use ash::{vk, extensions::khr};
 use ral::{HandleImpl, CommandQueueHandle};
+#[cfg(target_os = "linux")]
+use onca_window::{RawWindowHandle, RawDisplayHandle};
 
-use crate::{vulkan::AllocationCallbacks, utils::{ToVulkan, ToRalError, vulkan_to_texture_usage}, fence::Fence, command_queue::CommandQueue, device::{Device, SupportedExtensions}, texture::Texture, physical_device::PhysicalDevice};
+use crate::{vulkan::AllocationCallbacks, utils::{ToVulkan, ToRalError, vulkan_to_texture_usage, vulkan_to_color_space}, fence::Fence, command_queue::{CommandQueue, get_cpu_timestamp_frequency}, device::{Device, SupportedExtensions}, texture::Texture, physical_device::PhysicalDevice, debug};
 
 const NUM_VULKAN_PRESENT_MODES : usize = 6;
 
+/// Surface extension used to create the surface, which depends on the display server the window is created on
+#[cfg(target_os = "linux")]
+pub enum LinuxSurface {
+    Xlib(khr::XlibSurface),
+    Wayland(khr::WaylandSurface),
+}
+
 pub struct SwapChain {
     pub surface:                 vk::SurfaceKHR,
     pub swapchain:               Cell<vk::SwapchainKHR>,
 
     pub device:                  Weak<ash::Device>,
+    pub phys_dev:                vk::PhysicalDevice,
 
     pub ash_surface:             khr::Surface,
     pub ash_swapchain:           khr::Swapchain,
     #[cfg(windows)]
     pub os_surface:              khr::Win32Surface,
+    #[cfg(target_os = "linux")]
+    pub os_surface:              LinuxSurface,
 
     pub supported_present_modes: BitSet<NUM_VULKAN_PRESENT_MODES>,
     pub alloc_callbacks:         AllocationCallbacks,
@@ -33,6 +45,18 @@ pub struct SwapChain {
     pub support_maintenance1:    bool,
 
     pub resize_command_pool:     vk::CommandPool,
+
+    pub hdr_metadata_fn:         Option<vk::ExtHdrMetadataFn>,
+    /// HDR metadata needs to be re-applied when the swap-chain gets recreated
+    pub hdr_metadata:            Cell<Option<ral::HdrMetadata>>,
+
+    pub present_wait_fn:         Option<vk::KhrPresentWaitFn>,
+    pub display_timing_fn:       Option<vk::GoogleDisplayTimingFn>,
+    /// Id of the last present, ids restart at 1 when the swap-chain gets recreated
+    pub present_id:              Cell<u64>,
+    pub max_frame_latency:       Cell<u8>,
+    /// Number of presents that were displayed and the time at which the last present was displayed, retrieved using `VK_GOOGLE_display_timing`
+    pub present_timing:          Cell<(u32, u64)>,
 }
 
 impl SwapChain {
@@ -42,6 +66,14 @@ impl SwapChain {
         let instance = device.get_instance()?;
         let (os_surface, surface) = Self::create_surface(device, &desc)?;
         let ash_surface = khr::Surface::new(&instance.entry, &instance.instance);
+
+        // Whether a queue can present depends on the window system, which is only known once the surface exists
+        let queue_index = desc.queue.index.get() as u32;
+        let present_supported = ash_surface.get_physical_device_surface_support(vk_phys_dev.phys_dev, queue_index, surface).unwrap_or(false);
+        if !present_supported {
+            ash_surface.destroy_surface(surface, instance.alloc_callbacks.get_some_vk_callbacks());
+            return Err(ral::Error::Other(format!("Queue family {queue_index} can't present to the window's surface")));
+        }
     
         let capabilities = ash_surface.get_physical_device_surface_capabilities(vk_phys_dev.phys_dev, surface).map_err(|err| err.to_ral_error())?;
         
@@ -59,21 +91,15 @@ impl SwapChain {
             ral::PresentMode::Fifo
         };
 
-        // TODO: color space
-        // Get best format
-        let formats = ash_surface.get_physical_device_surface_formats(vk_phys_dev.phys_dev, surface).map_err(|err| err.to_ral_error())?;
+        // Get best format and color space, falling back to nonlinear SRGB if no preferred color space is supported
+        let surface_formats = ash_surface.get_physical_device_surface_formats(vk_phys_dev.phys_dev, surface).map_err(|err| err.to_ral_error())?;
 
-        let mut swapchain_format = None;
-        for format in &desc.formats {
-            let vk_format = format.to_vulkan();
-            // for now, we will require nonlinear SRGB color spaces
-            if formats.iter().any(|surface_format| surface_format.format == vk_format && surface_format.color_space == vk::ColorSpaceKHR::SRGB_NONLINEAR) {
-                swapchain_format = Some(*format);
-                break;
-            }
-        }
-        let swapchain_format = match swapchain_format {
-            Some(format) => format,
+        let (swapchain_format, color_space) = match desc.color_spaces.iter()
+            .copied()
+            .chain(core::iter::once(ral::SwapChainColorSpace::SrgbNonLinear))
+            .find_map(|color_space| Self::find_surface_format(&surface_formats, color_space, &desc.formats).map(|format| (format, color_space)))
+        {
+            Some(format_and_color_space) => format_and_color_space,
             None => return Err(ral::Error::UnsupportedSwapchainFormats(desc.formats.clone())),
         };
 
@@ -88,7 +114,6 @@ impl SwapChain {
         let backbuffer_usages = desc.usages & supported_usages;
 
         let ash_swapchain = ash::extensions::khr::Swapchain::new(&instance.instance, &device.device);
-        let queue_index = desc.queue.index.get() as u32;
 
         let pool_info = vk::CommandPoolCreateInfo::builder()
             .queue_family_index(queue_index);
@@ -102,6 +127,7 @@ impl SwapChain {
             width, height,
             num_backbuffers,
             swapchain_format.to_vulkan(),
+            color_space.to_vulkan(),
             backbuffer_usages.to_vulkan(),
             present_mode.to_vulkan(),
             capabilities.current_transform,
@@ -120,6 +146,7 @@ impl SwapChain {
             surface,
             swapchain: Cell::new(swapchain),
             device: Arc::downgrade(&device.device),
+            phys_dev: vk_phys_dev.phys_dev,
             ash_surface,
             ash_swapchain,
             os_surface,
@@ -130,6 +157,13 @@ impl SwapChain {
             support_incremental: device.supported_extensions.contains(SupportedExtensions::SwapChainIncremental),
             support_maintenance1: device.supported_extensions.contains(SupportedExtensions::SwapChainMaintenance1),
             resize_command_pool,
+            hdr_metadata_fn: device.hdr_metadata.clone(),
+            hdr_metadata: Cell::new(None),
+            present_wait_fn: device.present_wait.clone(),
+            display_timing_fn: device.display_timing.clone(),
+            present_id: Cell::new(0),
+            max_frame_latency: Cell::new(1),
+            present_timing: Cell::new((0, 0)),
         });
 
         Ok((handle, ral::api::SwapChainResultInfo { 
@@ -139,10 +173,41 @@ impl SwapChain {
             format: swapchain_format,
             backbuffer_usages,
             present_mode,
+            color_space,
             backbuffers,
         }))
     }
 
+    /// Find the first format that the surface supports in combination with the given color space
+    fn find_surface_format(surface_formats: &[vk::SurfaceFormatKHR], color_space: ral::SwapChainColorSpace, formats: &[ral::Format]) -> Option<ral::Format> {
+        let vk_color_space = color_space.to_vulkan();
+        formats.iter().copied().find(|format| {
+            let vk_format = format.to_vulkan();
+            surface_formats.iter().any(|surface_format| surface_format.format == vk_format && surface_format.color_space == vk_color_space)
+        })
+    }
+
+    unsafe fn apply_hdr_metadata(&self, metadata: &ral::HdrMetadata) -> ral::Result<()> {
+        let hdr_metadata_fn = self.hdr_metadata_fn.as_ref().ok_or(ral::Error::MissingFeature("VK_EXT_hdr_metadata"))?;
+        let device = Weak::upgrade(&self.device).ok_or(ral::Error::UseAfterDeviceDropped)?;
+
+        let to_xy = |xy: [f32; 2]| vk::XYColorEXT { x: xy[0], y: xy[1] };
+        let vk_metadata = vk::HdrMetadataEXT::builder()
+            .display_primary_red(to_xy(metadata.red_primary))
+            .display_primary_green(to_xy(metadata.green_primary))
+            .display_primary_blue(to_xy(metadata.blue_primary))
+            .white_point(to_xy(metadata.white_point))
+            .max_luminance(metadata.max_mastering_luminance)
+            .min_luminance(metadata.min_mastering_luminance)
+            .max_content_light_level(metadata.max_content_light_level)
+            .max_frame_average_light_level(metadata.max_frame_average_light_level)
+            .build();
+
+        let swapchain = self.swapchain.get();
+        (hdr_metadata_fn.set_hdr_metadata_ext)(device.handle(), 1, &swapchain, &vk_metadata);
+        Ok(())
+    }
+
     unsafe fn create_swapchain(
         device: &Arc<ash::Device>,
         ash_swapchain: &khr::Swapchain,
@@ -151,6 +216,7 @@ impl SwapChain {
         width: u32, height: u32,
         num_backbuffers: u32,
         swapchain_format: vk::Format,
+        color_space: vk::ColorSpaceKHR,
         backbuffer_usages: vk::ImageUsageFlags,
         present_mode: vk::PresentModeKHR,
         current_transform: vk::SurfaceTransformFlagsKHR,
@@ -166,7 +232,7 @@ impl SwapChain {
             .image_array_layers(1)
             .min_image_count(num_backbuffers)
             .image_format(swapchain_format)
-            .image_color_space(vk::ColorSpaceKHR::SRGB_NONLINEAR)
+            .image_color_space(color_space)
             .image_usage(backbuffer_usages)
             .image_sharing_mode(vk::SharingMode::EXCLUSIVE)
             .present_mode(present_mode)
@@ -278,6 +344,35 @@ impl SwapChain {
                 let surface = win32_surface.create_win32_surface(&surface_create_info, instance.alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.to_ral_error())?;
                 Ok((win32_surface, surface))
             }
+        } else if #[cfg(target_os = "linux")] {
+            unsafe fn create_surface(device: &Device, desc: &ral::SwapChainDesc) -> ral::Result<(LinuxSurface, vk::SurfaceKHR)> {
+                let instance = match device.instance.upgrade() {
+                    Some(instance) => instance,
+                    None => return Err(ral::Error::Other("Vulkan instance has been destroyed before the device could be created".to_string())),
+                };
+
+                if let (RawWindowHandle::Wayland { surface }, RawDisplayHandle::Wayland { display }) = (desc.window_handle.raw_window_handle(), desc.window_handle.raw_display_handle()) {
+                    if !instance.wayland_surface {
+                        return Err(ral::Error::Other("Cannot create a surface for a Wayland window, 'VK_KHR_wayland_surface' is not supported".to_string()));
+                    }
+
+                    let surface_create_info = vk::WaylandSurfaceCreateInfoKHR::builder()
+                        .display(display.as_ptr() as *mut vk::wl_display)
+                        .surface(surface.as_ptr() as *mut vk::wl_surface);
+
+                    let wayland_surface = khr::WaylandSurface::new(&instance.entry, &instance.instance);
+                    let surface = wayland_surface.create_wayland_surface(&surface_create_info, instance.alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.to_ral_error())?;
+                    return Ok((LinuxSurface::Wayland(wayland_surface), surface));
+                }
+
+                let surface_create_info = vk::XlibSurfaceCreateInfoKHR::builder()
+                    .dpy(desc.window_handle.xlib_display() as *mut vk::Display)
+                    .window(desc.window_handle.xlib_window() as vk::Window);
+
+                let xlib_surface = khr::XlibSurface::new(&instance.entry, &instance.instance);
+                let surface = xlib_surface.create_xlib_surface(&surface_create_info, instance.alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.to_ral_error())?;
+                Ok((LinuxSurface::Xlib(xlib_surface), surface))
+            }
         }
     }
 }
@@ -354,6 +449,31 @@ impl ral::SwapChainInterface for SwapChain {
             vk_present_info = vk_present_info.push_next(&mut present_mode_info);
         }
 
+        let present_id = self.present_id.get() + 1;
+        self.present_id.set(present_id);
+
+        let mut present_id_info;
+        let vk_present_id;
+        if self.present_wait_fn.is_some() {
+            vk_present_id = [present_id];
+            present_id_info = vk::PresentIdKHR::builder()
+                .present_ids(&vk_present_id);
+            vk_present_info = vk_present_info.push_next(&mut present_id_info);
+        }
+
+        // Presentation timing is only reported for presents that pass in a present time
+        let mut present_times_info;
+        let vk_present_time;
+        if self.display_timing_fn.is_some() {
+            vk_present_time = [vk::PresentTimeGOOGLE {
+                present_id: present_id as u32,
+                desired_present_time: 0,
+            }];
+            present_times_info = vk::PresentTimesInfoGOOGLE::builder()
+                .times(&vk_present_time);
+            vk_present_info = vk_present_info.push_next(&mut present_times_info);
+        }
+
         match self.ash_swapchain.queue_present(queue, &vk_present_info) {
             Ok(_) => Ok(()),
             Err(err) => Err(err.to_ral_error()),
@@ -418,6 +538,7 @@ impl ral::SwapChainInterface for SwapChain {
             width, height,
             num_backbuffers,
             params.format.to_vulkan(),
+            params.color_space.to_vulkan(),
             backbuffer_usages.to_vulkan(),
             params.present_mode.to_vulkan(),
             capabilities.current_transform,
@@ -428,6 +549,12 @@ impl ral::SwapChainInterface for SwapChain {
         )?;
         
         self.swapchain.set(swapchain);
+        self.present_id.set(0);
+        self.present_timing.set((0, 0));
+
+        if let Some(metadata) = self.hdr_metadata.get() {
+            self.apply_hdr_metadata(&metadata)?;
+        }
 
         Ok(ral::api::SwapChainResultInfo {
             backbuffers,
@@ -437,7 +564,7 @@ impl ral::SwapChainInterface for SwapChain {
             format: params.format,
             backbuffer_usages,
             present_mode: params.present_mode,
-            
+            color_space: params.color_space,
         })
     }
 
@@ -448,6 +575,127 @@ impl ral::SwapChainInterface for SwapChain {
             height: info.height,
         })
     }
+
+    unsafe fn change_color_space(&self, device: &ral::DeviceHandle, color_space: ral::SwapChainColorSpace, formats: &[ral::Format], mut params: ral::api::SwapChainChangeParams) -> ral::Result<ral::api::SwapChainResultInfo> {
+        let surface_formats = self.ash_surface.get_physical_device_surface_formats(self.phys_dev, self.surface).map_err(|err| err.to_ral_error())?;
+        params.format = Self::find_surface_format(&surface_formats, color_space, formats).ok_or(ral::Error::UnsupportedColorSpace(color_space))?;
+        params.color_space = color_space;
+
+        // Metadata belongs to the previous color space
+        self.hdr_metadata.set(None);
+        self.recreate_swapchain(device, params)
+    }
+
+    unsafe fn supports_color_space(&self, color_space: ral::SwapChainColorSpace, formats: &[ral::Format]) -> ral::Result<bool> {
+        let surface_formats = self.ash_surface.get_physical_device_surface_formats(self.phys_dev, self.surface).map_err(|err| err.to_ral_error())?;
+        Ok(Self::find_surface_format(&surface_formats, color_space, formats).is_some())
+    }
+
+    unsafe fn set_hdr_metadata(&self, metadata: Option<&ral::HdrMetadata>) -> ral::Result<()> {
+        // Vulkan has no way to clear the metadata, but it will be dropped when the swap-chain is recreated
+        if let Some(metadata) = metadata {
+            self.apply_hdr_metadata(metadata)?;
+        }
+        self.hdr_metadata.set(metadata.copied());
+        Ok(())
+    }
+
+    unsafe fn get_output_info(&self) -> ral::Result<ral::SwapChainOutputInfo> {
+        let surface_formats = self.ash_surface.get_physical_device_surface_formats(self.phys_dev, self.surface).map_err(|err| err.to_ral_error())?;
+
+        let mut color_spaces = Vec::new();
+        for surface_format in surface_formats {
+            if let Some(color_space) = vulkan_to_color_space(surface_format.color_space) && !color_spaces.contains(&color_space) {
+                color_spaces.push(color_space);
+            }
+        }
+
+        // Vulkan does not expose the capabilities of the display the surface is presented on
+        Ok(ral::SwapChainOutputInfo {
+            color_spaces,
+            bits_per_color: 0,
+            hdr: None,
+        })
+    }
+
+    unsafe fn get_present_statistics(&self) -> ral::Result<ral::api::PresentStatisticsResultInfo> {
+        let display_timing_fn = self.display_timing_fn.as_ref().ok_or(ral::Error::MissingFeature("VK_GOOGLE_display_timing"))?;
+        let device = Weak::upgrade(&self.device).ok_or(ral::Error::UseAfterDeviceDropped)?;
+        let swapchain = self.swapchain.get();
+
+        let mut refresh_cycle = vk::RefreshCycleDurationGOOGLE::default();
+        let vk_res = (display_timing_fn.get_refresh_cycle_duration_google)(device.handle(), swapchain, &mut refresh_cycle);
+        if vk_res != vk::Result::SUCCESS {
+            return Err(vk_res.to_ral_error());
+        }
+
+        scoped_alloc!(AllocId::TlsTemp);
+        let mut count = 0;
+        let vk_res = (display_timing_fn.get_past_presentation_timing_google)(device.handle(), swapchain, &mut count, null_mut());
+        if vk_res != vk::Result::SUCCESS {
+            return Err(vk_res.to_ral_error());
+        }
+
+        let mut timings = Vec::with_capacity(count as usize);
+        let vk_res = (display_timing_fn.get_past_presentation_timing_google)(device.handle(), swapchain, &mut count, timings.as_mut_ptr());
+        if vk_res != vk::Result::SUCCESS && vk_res != vk::Result::INCOMPLETE {
+            return Err(vk_res.to_ral_error());
+        }
+        timings.set_len(count as usize);
+
+        // Timings are only returned once, so accumulate them
+        let (mut present_count, mut present_time) = self.present_timing.get();
+        present_count += timings.len() as u32;
+        if let Some(timing) = timings.last() {
+            present_time = timing.actual_present_time;
+        }
+        self.present_timing.set((present_count, present_time));
+
+        // Vulkan has no vertical blank counter, so derive it from the time at which the last present was displayed, only the difference between counts is meaningful
+        let present_refresh_count = if refresh_cycle.refresh_duration != 0 {
+            (present_time / refresh_cycle.refresh_duration) as u32
+        } else {
+            0
+        };
+
+        // The present time is reported in nanoseconds, while the RAL reports it in ticks of the CPU's high resolution clock, like DXGI does
+        let present_time = (present_time as u128 * get_cpu_timestamp_frequency() as u128 / 1_000_000_000) as u64;
+
+        Ok(ral::api::PresentStatisticsResultInfo {
+            present_count,
+            present_refresh_count,
+            present_time,
+        })
+    }
+
+    unsafe fn set_max_frame_latency(&self, latency: u8) -> ral::Result<()> {
+        self.max_frame_latency.set(latency);
+        Ok(())
+    }
+
+    unsafe fn wait_for_present_ready(&self, timeout: Duration) -> ral::Result<bool> {
+        // Without `VK_KHR_present_wait`, latency is only limited by acquiring the next backbuffer
+        let Some(present_wait_fn) = &self.present_wait_fn else { return Ok(true) };
+        let device = Weak::upgrade(&self.device).ok_or(ral::Error::UseAfterDeviceDropped)?;
+
+        // Wait until at most `max_frame_latency - 1` presents are still queued, so the next frame fits within the latency
+        let latency = self.max_frame_latency.get() as u64;
+        let present_id = self.present_id.get();
+        if present_id < latency {
+            return Ok(true);
+        }
+
+        let timeout = timeout.as_nanos().min(u64::MAX as u128) as u64;
+        match (present_wait_fn.wait_for_present_khr)(device.handle(), self.swapchain.get(), present_id + 1 - latency, timeout) {
+            vk::Result::SUCCESS | vk::Result::SUBOPTIMAL_KHR => Ok(true),
+            vk::Result::TIMEOUT => Ok(false),
+            err => Err(err.to_ral_error()),
+        }
+    }
+
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.swapchain.get(), name);
+    }
 }
 
 impl Drop for SwapChain {
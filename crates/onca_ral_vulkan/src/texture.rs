@@ -4,7 +4,7 @@ use onca_ral as ral;
 use ash::vk;
 use ral::HandleImpl;
 
-use crate::{vulkan::AllocationCallbacks, utils::{ToRalError, ToVulkan}};
+use crate::{vulkan::AllocationCallbacks, utils::{ToRalError, ToVulkan}, device::Device, memory::{create_api_memory_request, MemoryHeap}, debug};
 
 
 //==============================================================================================================================
@@ -20,6 +20,158 @@ pub struct Texture {
     pub is_swap_chain_image: bool
 }
 
+impl Texture {
+    pub unsafe fn new(device: &Device, desc: &ral::TextureDesc, alloc: &ral::GpuAllocator) -> ral::Result<(ral::TextureInterfaceHandle, ral::GpuAllocation)> {
+        let (image_type, extent, layers) = get_image_type_and_extent(desc.size);
+        let create_info = Self::get_create_info(desc, image_type, extent, layers);
+        let image = device.device.create_image(&create_info, device.alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.to_ral_error())?;
+
+        let texture = Texture {
+            image,
+            device: Arc::downgrade(&device.device),
+            alloc_callbacks: device.alloc_callbacks.clone(),
+            is_swap_chain_image: false,
+        };
+
+        let memory_info = vk::ImageMemoryRequirementsInfo2::builder()
+            .image(image);
+        let mut memory_dedicated_requirements = vk::MemoryDedicatedRequirements::default();
+        let mut memory_requirements = vk::MemoryRequirements2::builder()
+            .push_next(&mut memory_dedicated_requirements)
+            .build();
+        device.device.get_image_memory_requirements2(&memory_info, &mut memory_requirements);
+
+        let api_req = create_api_memory_request(alloc.memory_info(), &memory_requirements.memory_requirements, &memory_dedicated_requirements);
+        let memory = alloc.alloc(memory_requirements.memory_requirements.size, desc.alloc_desc, api_req)?;
+
+        let vk_mem = memory.heap().interface().as_concrete_type::<MemoryHeap>();
+
+        let bind_image_mem = vk::BindImageMemoryInfo::builder()
+            .image(image)
+            .memory(vk_mem.memory())
+            .memory_offset(memory.offset())
+            .build();
+        device.device.bind_image_memory2(&[bind_image_mem]).map_err(|err| err.to_ral_error())?;
+
+        Ok((ral::TextureInterfaceHandle::new(texture), memory))
+    }
+
+    pub unsafe fn new_placed(device: &Device, desc: &ral::TextureDesc, heap: &ral::MemoryHeapHandle, offset: u64) -> ral::Result<ral::TextureInterfaceHandle> {
+        let (image_type, extent, layers) = get_image_type_and_extent(desc.size);
+        let create_info = Self::get_create_info(desc, image_type, extent, layers);
+        let image = device.device.create_image(&create_info, device.alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.to_ral_error())?;
+
+        let texture = Texture {
+            image,
+            device: Arc::downgrade(&device.device),
+            alloc_callbacks: device.alloc_callbacks.clone(),
+            is_swap_chain_image: false,
+        };
+
+        let vk_mem = heap.interface().as_concrete_type::<MemoryHeap>();
+        let bind_image_mem = vk::BindImageMemoryInfo::builder()
+            .image(image)
+            .memory(vk_mem.memory())
+            .memory_offset(offset)
+            .build();
+        device.device.bind_image_memory2(&[bind_image_mem]).map_err(|err| err.to_ral_error())?;
+
+        Ok(ral::TextureInterfaceHandle::new(texture))
+    }
+
+    pub unsafe fn get_allocation_info(device: &Device, desc: &ral::TextureDesc) -> ral::ResourceAllocationInfo {
+        let (image_type, extent, layers) = get_image_type_and_extent(desc.size);
+        let create_info = Self::get_create_info(desc, image_type, extent, layers);
+
+        // Query the requirements without having to create the image
+        let requirements_info = vk::DeviceImageMemoryRequirements::builder()
+            .create_info(&create_info);
+        let mut memory_requirements = vk::MemoryRequirements2::default();
+        device.device.get_device_image_memory_requirements(&requirements_info, &mut memory_requirements);
+
+        ral::ResourceAllocationInfo {
+            size: memory_requirements.memory_requirements.size,
+            alignment: memory_requirements.memory_requirements.alignment,
+        }
+    }
+
+    fn get_create_info(desc: &ral::TextureDesc, image_type: vk::ImageType, extent: vk::Extent3D, layers: u32) -> vk::ImageCreateInfo {
+        vk::ImageCreateInfo::builder()
+            .image_type(image_type)
+            .format(desc.format.to_vulkan())
+            .extent(extent)
+            .mip_levels(desc.mip_levels.get() as u32)
+            .array_layers(layers)
+            .samples(desc.samples.to_vulkan())
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(desc.usage.to_vulkan())
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .build()
+    }
+
+    pub unsafe fn new_sparse(device: &Device, desc: &ral::SparseTextureDesc) -> ral::Result<(ral::TextureInterfaceHandle, ral::SparseTextureInfo)> {
+        let (image_type, extent, layers) = get_image_type_and_extent(desc.size);
+
+        let create_info = vk::ImageCreateInfo::builder()
+            .flags(vk::ImageCreateFlags::SPARSE_BINDING | vk::ImageCreateFlags::SPARSE_RESIDENCY)
+            .image_type(image_type)
+            .format(desc.format.to_vulkan())
+            .extent(extent)
+            .mip_levels(desc.mip_levels.get() as u32)
+            .array_layers(layers)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(desc.usage.to_vulkan())
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .initial_layout(vk::ImageLayout::UNDEFINED);
+
+        let image = device.device.create_image(&create_info, device.alloc_callbacks.get_some_vk_callbacks()).map_err(|err| err.to_ral_error())?;
+
+        let texture = Texture {
+            image,
+            device: Arc::downgrade(&device.device),
+            alloc_callbacks: device.alloc_callbacks.clone(),
+            is_swap_chain_image: false,
+        };
+
+        let memory_requirements = device.device.get_image_memory_requirements(image);
+        let sparse_requirements = get_sparse_requirements(&device.device, image, desc.format.aspect().to_vulkan())?;
+
+        let mip_tail_first_lod = sparse_requirements.image_mip_tail_first_lod.min(desc.mip_levels.get() as u32) as u8;
+        let granularity = sparse_requirements.format_properties.image_granularity;
+        let info = ral::SparseTextureInfo {
+            tile_shape: ral::SparseTileShape {
+                width: granularity.width,
+                height: granularity.height,
+                depth: granularity.depth,
+            },
+            num_standard_mips: mip_tail_first_lod,
+            num_packed_mips: desc.mip_levels.get() - mip_tail_first_lod,
+            num_tiles_for_packed_mips: sparse_requirements.image_mip_tail_size.div_ceil(ral::constants::SPARSE_TILE_SIZE) as u32,
+            total_tiles: memory_requirements.size.div_ceil(ral::constants::SPARSE_TILE_SIZE) as u32,
+        };
+        Ok((ral::TextureInterfaceHandle::new(texture), info))
+    }
+}
+
+/// Get the image type, extent and number of array layers for a texture size
+fn get_image_type_and_extent(size: ral::TextureSize) -> (vk::ImageType, vk::Extent3D, u32) {
+    match size {
+        ral::TextureSize::Size1D { width, layers }         => (vk::ImageType::TYPE_1D, vk::Extent3D { width: width.get() as u32, height: 1                  , depth: 1                 }, layers.get() as u32),
+        ral::TextureSize::Size2D { width, height, layers } => (vk::ImageType::TYPE_2D, vk::Extent3D { width: width.get() as u32, height: height.get() as u32, depth: 1                 }, layers.get() as u32),
+        ral::TextureSize::Size3D { width, height, depth }  => (vk::ImageType::TYPE_3D, vk::Extent3D { width: width.get() as u32, height: height.get() as u32, depth: depth.get() as u32 }, 1),
+    }
+}
+
+/// Get the sparse memory requirements of an image for the given aspect
+pub unsafe fn get_sparse_requirements(device: &ash::Device, image: vk::Image, aspect: vk::ImageAspectFlags) -> ral::Result<vk::SparseImageMemoryRequirements> {
+    device.get_image_sparse_memory_requirements(image)
+        .into_iter()
+        .find(|reqs| reqs.format_properties.aspect_mask.intersects(aspect))
+        .ok_or(ral::Error::Other("Image does not have any sparse memory requirements for its aspect".to_string()))
+}
+
 impl ral::TextureInterface for Texture {
     unsafe fn create_sampled_texture_view(&self, texture: &ral::TextureHandle, desc: &ral::SampledTextureViewDesc) -> ral::Result<ral::SampledTextureViewInterfaceHandle> {
         let device = Weak::upgrade(&self.device).ok_or(ral::Error::UseAfterDeviceDropped)?;
@@ -35,6 +187,10 @@ impl ral::TextureInterface for Texture {
         let device = Weak::upgrade(&self.device).ok_or(ral::Error::UseAfterDeviceDropped)?;
         RenderTargetView::new(device, self.alloc_callbacks.clone(), desc, texture)
     }
+
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.image, name);
+    }
 }
 
 impl Drop for Texture {
@@ -159,7 +315,9 @@ impl RenderTargetView {
 }
 
 impl ral::RenderTargetViewInterface for RenderTargetView {
-
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.view, name);
+    }
 }
 
 impl Drop for RenderTargetView {
@@ -302,6 +460,9 @@ impl SampledTextureView {
 }
 
 impl ral::SampledTextureViewInterface for SampledTextureView {
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.view, name);
+    }
 }
 
 impl Drop for SampledTextureView {
@@ -404,6 +565,9 @@ impl StorageTextureView {
 }
 
 impl ral::StorageTextureViewInterface for StorageTextureView {
+    unsafe fn set_debug_name(&self, name: &str) {
+        debug::set_object_name(&self.device, self.view, name);
+    }
 }
 
 impl Drop for StorageTextureView {
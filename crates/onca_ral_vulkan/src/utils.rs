@@ -54,9 +54,19 @@ pub fn vulkan_to_texture_usage(vk_usage: vk::ImageUsageFlags) -> ral::TextureUsa
     usage.set(ral::TextureUsage::Storage               , is_flag_set(vk_usage, vk::ImageUsageFlags::STORAGE));
     usage.set(ral::TextureUsage::ColorAttachment       , is_flag_set(vk_usage, vk::ImageUsageFlags::COLOR_ATTACHMENT));
     usage.set(ral::TextureUsage::DepthStencilAttachment, is_flag_set(vk_usage, vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT));
+    usage.set(ral::TextureUsage::ShadingRate           , is_flag_set(vk_usage, vk::ImageUsageFlags::FRAGMENT_SHADING_RATE_ATTACHMENT_KHR));
     usage
 }
 
+pub fn vulkan_to_color_space(vk_color_space: vk::ColorSpaceKHR) -> Option<ral::SwapChainColorSpace> {
+    match vk_color_space {
+        vk::ColorSpaceKHR::SRGB_NONLINEAR           => Some(ral::SwapChainColorSpace::SrgbNonLinear),
+        vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT => Some(ral::SwapChainColorSpace::ExtendedSrgbLinear),
+        vk::ColorSpaceKHR::HDR10_ST2084_EXT         => Some(ral::SwapChainColorSpace::Hdr10St2084),
+        _                                           => None,
+    }
+}
+
 pub trait ToVulkan {
     type VkType;
 
@@ -102,10 +112,21 @@ impl ToVulkan for ral::TextureUsage {
     if self.contains(ral::TextureUsage::DepthStencilAttachment) {
         vk_usage |= vk::ImageUsageFlags::DEPTH_STENCIL_ATTACHMENT;
     }
+    if self.contains(ral::TextureUsage::ShadingRate) {
+        vk_usage |= vk::ImageUsageFlags::FRAGMENT_SHADING_RATE_ATTACHMENT_KHR;
+    }
     vk_usage
     }
 }
 
+impl ToVulkan for ral::SampleCount {
+    type VkType = vk::SampleCountFlags;
+
+    fn to_vulkan(&self) -> Self::VkType {
+        vk::SampleCountFlags::from_raw(self.get() as u32)
+    }
+}
+
 impl ToVulkan for ral::PresentMode {
     type VkType = vk::PresentModeKHR;
 
@@ -420,6 +441,18 @@ impl ToVulkan for ral::SwapChainAlphaMode {
     }
 }
 
+impl ToVulkan for ral::SwapChainColorSpace {
+    type VkType = vk::ColorSpaceKHR;
+
+    fn to_vulkan(&self) -> Self::VkType {
+        match self {
+            ral::SwapChainColorSpace::SrgbNonLinear      => vk::ColorSpaceKHR::SRGB_NONLINEAR,
+            ral::SwapChainColorSpace::ExtendedSrgbLinear => vk::ColorSpaceKHR::EXTENDED_SRGB_LINEAR_EXT,
+            ral::SwapChainColorSpace::Hdr10St2084        => vk::ColorSpaceKHR::HDR10_ST2084_EXT,
+        }
+    }
+}
+
 impl ToVulkan for ral::ShaderType {
     type VkType = vk::ShaderStageFlags;
 
@@ -427,6 +460,7 @@ impl ToVulkan for ral::ShaderType {
         match self {
             ral::ShaderType::Vertex       => vk::ShaderStageFlags::VERTEX,
             ral::ShaderType::Pixel        => vk::ShaderStageFlags::FRAGMENT,
+            ral::ShaderType::Compute      => vk::ShaderStageFlags::COMPUTE,
             ral::ShaderType::Task         => vk::ShaderStageFlags::TASK_EXT,
             ral::ShaderType::Mesh         => vk::ShaderStageFlags::MESH_EXT,
             ral::ShaderType::RayGen       => vk::ShaderStageFlags::RAYGEN_KHR,
@@ -524,6 +558,29 @@ impl ToVulkan for ral::LineRasterizationMode {
     }
 }
 
+impl ToVulkan for ral::ShadingRate {
+    type VkType = vk::Extent2D;
+
+    fn to_vulkan(&self) -> Self::VkType {
+        let (width, height) = self.coarse_pixel_size();
+        vk::Extent2D { width, height }
+    }
+}
+
+impl ToVulkan for ral::ShadingRateCombiner {
+    type VkType = vk::FragmentShadingRateCombinerOpKHR;
+
+    fn to_vulkan(&self) -> Self::VkType {
+        match self {
+            ral::ShadingRateCombiner::Passthrough => vk::FragmentShadingRateCombinerOpKHR::KEEP,
+            ral::ShadingRateCombiner::Override    => vk::FragmentShadingRateCombinerOpKHR::REPLACE,
+            ral::ShadingRateCombiner::Min         => vk::FragmentShadingRateCombinerOpKHR::MIN,
+            ral::ShadingRateCombiner::Max         => vk::FragmentShadingRateCombinerOpKHR::MAX,
+            ral::ShadingRateCombiner::Sum         => vk::FragmentShadingRateCombinerOpKHR::MUL,
+        }
+    }
+}
+
 impl ToVulkan for ral::CompareOp {
     type VkType = vk::CompareOp;
 
@@ -750,15 +807,74 @@ impl ToVulkan for ral::BufferUsage {
             flags |= vk::BufferUsageFlags::VERTEX_BUFFER;
         }
         if self.contains(ral::BufferUsage::IndirectBuffer) {
-            flags |= vk::BufferUsageFlags::INDEX_BUFFER;
+            flags |= vk::BufferUsageFlags::INDIRECT_BUFFER;
         }
         if self.contains(ral::BufferUsage::ConditionalRendering) {
             flags |= vk::BufferUsageFlags::CONDITIONAL_RENDERING_EXT;
         }
+        if self.contains(ral::BufferUsage::AccelerationStructure) {
+            flags |= vk::BufferUsageFlags::ACCELERATION_STRUCTURE_STORAGE_KHR;
+        }
+        if self.contains(ral::BufferUsage::AccelerationStructureInput) {
+            flags |= vk::BufferUsageFlags::ACCELERATION_STRUCTURE_BUILD_INPUT_READ_ONLY_KHR;
+        }
+        if self.contains(ral::BufferUsage::ShaderBindingTable) {
+            flags |= vk::BufferUsageFlags::SHADER_BINDING_TABLE_KHR;
+        }
         flags
     }
 }
 
+impl ToVulkan for ral::AccelerationStructureBuildFlags {
+    type VkType = vk::BuildAccelerationStructureFlagsKHR;
+
+    fn to_vulkan(&self) -> Self::VkType {
+        let mut flags = vk::BuildAccelerationStructureFlagsKHR::empty();
+        if self.contains(ral::AccelerationStructureBuildFlags::AllowUpdate) {
+            flags |= vk::BuildAccelerationStructureFlagsKHR::ALLOW_UPDATE;
+        }
+        if self.contains(ral::AccelerationStructureBuildFlags::AllowCompaction) {
+            flags |= vk::BuildAccelerationStructureFlagsKHR::ALLOW_COMPACTION;
+        }
+        if self.contains(ral::AccelerationStructureBuildFlags::PreferFastTrace) {
+            flags |= vk::BuildAccelerationStructureFlagsKHR::PREFER_FAST_TRACE;
+        }
+        if self.contains(ral::AccelerationStructureBuildFlags::PreferFastBuild) {
+            flags |= vk::BuildAccelerationStructureFlagsKHR::PREFER_FAST_BUILD;
+        }
+        if self.contains(ral::AccelerationStructureBuildFlags::MinimizeMemory) {
+            flags |= vk::BuildAccelerationStructureFlagsKHR::LOW_MEMORY;
+        }
+        flags
+    }
+}
+
+impl ToVulkan for ral::RaytracingGeometryFlags {
+    type VkType = vk::GeometryFlagsKHR;
+
+    fn to_vulkan(&self) -> Self::VkType {
+        let mut flags = vk::GeometryFlagsKHR::empty();
+        if self.contains(ral::RaytracingGeometryFlags::Opaque) {
+            flags |= vk::GeometryFlagsKHR::OPAQUE;
+        }
+        if self.contains(ral::RaytracingGeometryFlags::NoDuplicateAnyHit) {
+            flags |= vk::GeometryFlagsKHR::NO_DUPLICATE_ANY_HIT_INVOCATION;
+        }
+        flags
+    }
+}
+
+impl ToVulkan for ral::AccelerationStructureType {
+    type VkType = vk::AccelerationStructureTypeKHR;
+
+    fn to_vulkan(&self) -> Self::VkType {
+        match self {
+            ral::AccelerationStructureType::BottomLevel => vk::AccelerationStructureTypeKHR::BOTTOM_LEVEL,
+            ral::AccelerationStructureType::TopLevel    => vk::AccelerationStructureTypeKHR::TOP_LEVEL,
+        }
+    }
+}
+
 impl ToVulkan for ral::IndexFormat {
     type VkType = vk::IndexType;
 
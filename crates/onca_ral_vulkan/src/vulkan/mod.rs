@@ -19,9 +19,13 @@ impl VkBoolToBool for vk::Bool32 {
     }
 }
 
+pub const VK_EXT_CALIBRATED_TIMESTAMPS:         &str = "VK_EXT_calibrated_timestamps\0";
+pub const VK_EXT_CONDITIONAL_RENDERING:         &str = "VK_EXT_conditional_rendering\0";
 pub const VK_EXT_CUSTOM_BORDER_COLOR:           &str = "VK_EXT_custom_border_color\0";
 pub const VK_EXT_CONSERVATIVE_RASTERIZATION:    &str = "VK_EXT_conservative_rasterization\0";
 pub const VK_EXT_DESCRIPTOR_BUFFER:             &str = "VK_EXT_descriptor_buffer\0";
+pub const VK_EXT_DEVICE_FAULT:                  &str = "VK_EXT_device_fault\0";
+pub const VK_EXT_HDR_METADATA:                  &str = "VK_EXT_hdr_metadata\0";
 pub const VK_EXT_IMAGE_VIEW_MIN_LOD:            &str = "VK_EXT_image_view_min_lod\0";
 pub const VK_EXT_MEMORY_BUDGET:                 &str = "VK_EXT_memory_budget\0";
 pub const VK_EXT_MESH_SHADER:                   &str = "VK_EXT_mesh_shader\0";
@@ -30,10 +34,13 @@ pub const VK_EXT_LINE_RASTERIZATION:            &str = "VK_EXT_line_rasterizatio
 pub const VK_EXT_SAMPLE_LOCATIONS:              &str = "VK_EXT_sample_locations\0";
 pub const VK_EXT_SWAPCHAIN_MAINTENANCE1:        &str = "VK_EXT_swapchain_maintenance1\0";
 pub const VK_EXT_VERTEX_ATTRIBUTE_DIVISOR:      &str = "VK_EXT_vertex_attribute_divisor\0";
+pub const VK_GOOGLE_DISPLAY_TIMING:             &str = "VK_GOOGLE_display_timing\0";
 pub const VK_KHR_ACCELERATION_STRUCTURE  :      &str = "VK_KHR_acceleration_structure\0";
 pub const VK_KHR_DEFERRED_HOST_OPERATIONS:      &str = "VK_KHR_deferred_host_operations\0";
 pub const VK_KHR_FRAGMENT_SHADING_RATE:         &str = "VK_KHR_fragment_shading_rate\0";
 pub const VK_KHR_INCREMENTAL_PRESENT:           &str = "VK_KHR_incremental_present\0";
+pub const VK_KHR_PRESENT_ID:                    &str = "VK_KHR_present_id\0";
+pub const VK_KHR_PRESENT_WAIT:                  &str = "VK_KHR_present_wait\0";
 pub const VK_KHR_RAY_TRACING_MAINTENANCE1:      &str = "VK_KHR_ray_tracing_maintenance1\0";
 pub const VK_KHR_RAY_TRACING_PIPELINE:          &str = "VK_KHR_ray_tracing_pipeline\0";
 pub const VK_KHR_RAY_QUERY:                     &str = "VK_KHR_ray_query\0";
@@ -0,0 +1,194 @@
+use crate::*;
+
+/// Separator between the segments of a path.
+const SEPARATOR: char = '/';
+
+/// Parser translating glob syntax into a regex node tree.
+///
+/// Supported syntax:
+/// - `?`: any single character, except for a path separator
+/// - `*`: any number of characters, except for a path separator
+/// - `**`: when used as a full path segment, any number of path segments, i.e. `a/**/b` matches `a/b` and `a/x/y/b`, while a trailing `/**` matches anything in a directory
+/// - `[abc]`, `[a-z]`: any character in the class, `[!a-z]` and `[^a-z]` match any character not in the class, except for a path separator
+/// - `{a,b}`: any of the comma separated alternatives, which can themselves contain glob syntax, including nested braces
+/// - `\`: escapes the next character, so it is matched literally
+pub(crate) struct GlobParser<'a> {
+	orig:   &'a str,
+	cursor: &'a str,
+	idx:    usize,
+}
+
+impl<'a> GlobParser<'a> {
+	pub fn new(glob: &'a str) -> Self {
+		Self { orig: glob, cursor: glob, idx: 0 }
+	}
+
+	/// Parse the glob into a node, which needs to match the complete subject.
+	pub fn parse(mut self) -> Result<RegexNode, RegexError> {
+		let mut nodes = vec![RegexNode::SubjectStart];
+		self.parse_sequence(&mut nodes, false)?;
+		nodes.push(RegexNode::SubjectEndOnly);
+		Ok(RegexNode::Unit(nodes))
+	}
+
+	fn consume_count(&mut self, count: usize) {
+		self.idx += count;
+		self.cursor = &self.cursor[count..];
+	}
+
+	fn next_char(&mut self) -> Option<char> {
+		let ch = self.cursor.chars().next()?;
+		self.consume_count(ch.len_utf8());
+		Some(ch)
+	}
+
+	fn is_segment_start(&self) -> bool {
+		self.idx == 0 || self.orig[..self.idx].ends_with(SEPARATOR)
+	}
+
+	/// Parse nodes until the end of the glob, or until the end of the current alternative when inside of braces.
+	fn parse_sequence(&mut self, nodes: &mut Vec<RegexNode>, in_braces: bool) -> Result<(), RegexError> {
+		let mut literal = String::new();
+		while let Some(ch) = self.cursor.chars().next() {
+			if in_braces && (ch == ',' || ch == '}') {
+				break;
+			}
+
+			let node = match ch {
+				'?' => {
+					self.consume_count(1);
+					Self::any_in_segment()
+				},
+				'*' => self.parse_star(),
+				'[' => self.parse_class()?,
+				'{' => self.parse_braces()?,
+				'\\' => {
+					self.consume_count(1);
+					match self.next_char() {
+						Some(ch) => literal.push(ch),
+						None => return Err(RegexError::new_str("Expected a character to escape after '\\'", self.idx - 1, self.idx)),
+					}
+					continue;
+				},
+				_ => {
+					self.consume_count(ch.len_utf8());
+					literal.push(ch);
+					continue;
+				},
+			};
+
+			Self::flush_literal(nodes, &mut literal);
+			nodes.push(node);
+		}
+		Self::flush_literal(nodes, &mut literal);
+		Ok(())
+	}
+
+	fn flush_literal(nodes: &mut Vec<RegexNode>, literal: &mut String) {
+		let mut chars = literal.chars();
+		match (chars.next(), chars.next()) {
+			(None, _) => {},
+			(Some(ch), None) => nodes.push(RegexNode::LiteralChar(ch)),
+			_ => nodes.push(RegexNode::Literal(core::mem::take(literal))),
+		}
+		literal.clear();
+	}
+
+	fn any_in_segment() -> RegexNode {
+		RegexNode::ClassDef(vec![SEPARATOR], Vec::new(), Vec::new(), false)
+	}
+
+	fn repeat_any(node: RegexNode) -> RegexNode {
+		RegexNode::Repetition(Box::new(node), Vec::new(), RepetitionMode::AtLeast(0), RepetitionStrategy::Greedy)
+	}
+
+	fn parse_star(&mut self) -> RegexNode {
+		let is_segment_start = self.is_segment_start();
+		let star_count = self.cursor.bytes().take_while(|b| *b == b'*').count();
+		self.consume_count(star_count);
+
+		if star_count == 1 || !is_segment_start {
+			return Self::repeat_any(Self::any_in_segment());
+		}
+
+		if self.cursor.starts_with(SEPARATOR) {
+			// `**/`: any number of complete path segments, including none
+			self.consume_count(1);
+			let segment = RegexNode::Unit(vec![Self::repeat_any(Self::any_in_segment()), RegexNode::LiteralChar(SEPARATOR)]);
+			Self::repeat_any(RegexNode::Group { capture_idx: None, sub_node: Box::new(segment), atomic: false })
+		} else if self.cursor.is_empty() {
+			// Trailing `**`: anything, including separators
+			Self::repeat_any(RegexNode::ClassDef(Vec::new(), Vec::new(), Vec::new(), false))
+		} else {
+			Self::repeat_any(Self::any_in_segment())
+		}
+	}
+
+	fn parse_class(&mut self) -> Result<RegexNode, RegexError> {
+		let start = self.idx;
+		self.consume_count(1);
+
+		let expected = !(self.cursor.starts_with('!') || self.cursor.starts_with('^'));
+		if !expected {
+			self.consume_count(1);
+		}
+
+		let mut chars = Vec::new();
+		let mut ranges = Vec::new();
+		let mut first = true;
+		loop {
+			let ch = match self.next_char() {
+				// A `]` directly after the opening bracket is part of the class
+				Some(']') if !first => break,
+				Some('\\') => self.next_char(),
+				ch => ch,
+			};
+			let Some(ch) = ch else {
+				return Err(RegexError::new_str("Unclosed character class, expected ']'", start, self.idx));
+			};
+			first = false;
+
+			if self.cursor.len() > 1 && self.cursor.starts_with('-') && !self.cursor[1..].starts_with(']') {
+				self.consume_count(1);
+				let end = match self.next_char() {
+					Some('\\') => self.next_char(),
+					end => end,
+				};
+				let Some(end) = end else {
+					return Err(RegexError::new_str("Unclosed character class, expected ']'", start, self.idx));
+				};
+				if end < ch {
+					return Err(RegexError::new(format!("Invalid character range '{ch}-{end}', the start of the range must not come after its end"), start, self.idx));
+				}
+				ranges.push((ch, end));
+			} else {
+				chars.push(ch);
+			}
+		}
+
+		// A negated class should never cross a path segment
+		if !expected {
+			chars.push(SEPARATOR);
+		}
+		Ok(RegexNode::ClassDef(chars, ranges, Vec::new(), expected))
+	}
+
+	fn parse_braces(&mut self) -> Result<RegexNode, RegexError> {
+		let start = self.idx;
+		self.consume_count(1);
+
+		let mut alternatives = Vec::new();
+		loop {
+			let mut nodes = Vec::new();
+			self.parse_sequence(&mut nodes, true)?;
+			alternatives.push(nodes);
+
+			match self.next_char() {
+				Some(',') => {},
+				Some('}') => break,
+				_ => return Err(RegexError::new_str("Unclosed brace expansion, expected '}'", start, self.idx)),
+			}
+		}
+		Ok(RegexNode::Alternation(alternatives))
+	}
+}
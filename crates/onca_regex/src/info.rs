@@ -0,0 +1,208 @@
+use crate::{*, matcher::CaseFold};
+
+/// Information about a capture group of a regex.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CaptureInfo<'a> {
+	/// Index of the capture, index 0 represents the whole match.
+	pub index:    u16,
+	/// Name of the capture, if it is named.
+	pub name:     Option<&'a str>,
+	/// Minimum length of the captured string in bytes.
+	pub min_len:  usize,
+	/// Maximum length of the captured string in bytes, `None` if the length is unbounded.
+	pub max_len:  Option<usize>,
+	/// Whether a successful match can occur without the capture participating in it, e.g. when the group is inside an optional repetition or an alternation.
+	pub optional: bool,
+}
+
+impl CaptureInfo<'_> {
+	/// Get the length of the captured string in bytes, if it is always the same.
+	pub fn fixed_length(&self) -> Option<usize> {
+		if self.max_len == Some(self.min_len) {
+			Some(self.min_len)
+		} else {
+			None
+		}
+	}
+
+	/// Check if the capture can capture an empty string.
+	pub fn is_nullable(&self) -> bool {
+		self.min_len == 0
+	}
+}
+
+/// Length bounds in bytes of the string a node can match.
+#[derive(Clone, Copy)]
+pub(crate) struct LengthBounds {
+	pub min: usize,
+	pub max: Option<usize>,
+}
+
+impl LengthBounds {
+	const EMPTY: Self = Self::exact(0);
+	const UNBOUNDED: Self = Self { min: 0, max: None };
+	const SINGLE_CHAR: Self = Self { min: 1, max: Some(4) };
+
+	const fn exact(len: usize) -> Self {
+		Self { min: len, max: Some(len) }
+	}
+
+	/// Bounds of a node followed by another node.
+	fn then(self, other: Self) -> Self {
+		Self { min: self.min + other.min, max: self.max.zip(other.max).map(|(a, b)| a + b) }
+	}
+
+	/// Bounds of a node that matches either of the nodes.
+	fn either(self, other: Self) -> Self {
+		Self { min: self.min.min(other.min), max: self.max.zip(other.max).map(|(a, b)| a.max(b)) }
+	}
+
+	fn repeat(self, min: u16, max: u16) -> Self {
+		let max = if max == u16::MAX {
+			match self.max {
+				Some(0) => Some(0),
+				_ => None,
+			}
+		} else {
+			self.max.map(|len| len * max as usize)
+		};
+		Self { min: self.min * min as usize, max }
+	}
+}
+
+/// Capture info without the name, collected from the node tree.
+#[derive(Clone, Copy)]
+pub(crate) struct CaptureMeta {
+	pub bounds:   LengthBounds,
+	pub optional: bool,
+}
+
+/// Collect the metadata of the whole match and all captures in a regex, the returned vector is indexed by capture index.
+pub(crate) fn collect_capture_meta(node: &RegexNode, flags: RegexFlags) -> Vec<Option<CaptureMeta>> {
+	let mut captures = vec![None];
+	let bounds = collect(node, flags, false, &mut captures);
+	captures[0] = Some(CaptureMeta { bounds, optional: false });
+	captures
+}
+
+fn collect_nodes(nodes: &[RegexNode], flags: RegexFlags, optional: bool, captures: &mut Vec<Option<CaptureMeta>>) -> LengthBounds {
+	nodes.iter().fold(LengthBounds::EMPTY, |bounds, node| bounds.then(collect(node, flags, optional, captures)))
+}
+
+fn literal_bounds(flags: RegexFlags, lit: &str) -> LengthBounds {
+	if !flags.contains(RegexFlags::Caseless) || flags.contains(RegexFlags::AsciiCaseless) {
+		return LengthBounds::exact(lit.len());
+	}
+
+	// A caseless match can have a different encoded length than the literal itself,
+	// each matched character is encoded in at most 4 bytes, and is case folded into at most 3 characters
+	let num_folded = lit.chars().flat_map(|ch| CaseFold::new(flags, ch)).count();
+	LengthBounds { min: num_folded.div_ceil(3), max: Some(num_folded * 4) }
+}
+
+/// Calculate the length bounds of a node, while collecting the metadata of all captures within it.
+fn collect(node: &RegexNode, flags: RegexFlags, optional: bool, captures: &mut Vec<Option<CaptureMeta>>) -> LengthBounds {
+	match node {
+		RegexNode::Unit(nodes) => collect_nodes(nodes, flags, optional, captures),
+		RegexNode::Literal(lit) => literal_bounds(flags, lit),
+		RegexNode::LiteralChar(ch) => literal_bounds(flags, ch.encode_utf8(&mut [0; 4])),
+		RegexNode::CharacterClassChar(ch) => LengthBounds::exact(ch.len_utf8()),
+		RegexNode::Dot => LengthBounds::SINGLE_CHAR,
+		RegexNode::CharacterClass(CharacterClass::ExtendedGraphemeCluster, _) => LengthBounds { min: 1, max: None },
+		RegexNode::CharacterClass(..) => LengthBounds::SINGLE_CHAR,
+		RegexNode::ClassDef(chars, ranges, nodes, expected) => {
+			if !*expected || !nodes.is_empty() {
+				return LengthBounds::SINGLE_CHAR;
+			}
+
+			// The encoded length of a char grows with its value, so only the range ends are relevant
+			chars.iter().map(|ch| LengthBounds::exact(ch.len_utf8()))
+				.chain(ranges.iter().map(|(begin, end)| LengthBounds { min: begin.len_utf8(), max: Some(end.len_utf8()) }))
+				.reduce(LengthBounds::either)
+				.unwrap_or(LengthBounds::SINGLE_CHAR)
+		},
+		RegexNode::Alternation(options) => {
+			let optional = optional || options.len() > 1;
+			options.iter()
+				.map(|nodes| collect_nodes(nodes, flags, optional, captures))
+				.reduce(LengthBounds::either)
+				.unwrap_or(LengthBounds::EMPTY)
+		},
+		RegexNode::Repetition(sub, tail, mode, _) => {
+			let (min, max) = match mode {
+				RepetitionMode::Exactly(n)          => (*n, *n),
+				RepetitionMode::AtLeast(n)          => (*n, u16::MAX),
+				RepetitionMode::AtLeastAtMost(n, m) => (*n, *m),
+			};
+
+			let sub_bounds = collect(sub, flags, optional || min == 0, captures).repeat(min, max);
+			sub_bounds.then(collect_nodes(tail, flags, optional, captures))
+		},
+		RegexNode::Group { capture_idx, sub_node, .. } => {
+			let bounds = collect(sub_node, flags, optional, captures);
+			if let Some(idx) = capture_idx {
+				let idx = *idx as usize;
+				if captures.len() <= idx {
+					captures.resize(idx + 1, None);
+				}
+
+				// Multiple groups can share the same index, so merge their metadata
+				captures[idx] = Some(match captures[idx] {
+					Some(meta) => CaptureMeta { bounds: meta.bounds.either(bounds), optional: meta.optional || optional },
+					None       => CaptureMeta { bounds, optional },
+				});
+			}
+			bounds
+		},
+		// Captures are never stored inside lookarounds
+		RegexNode::Lookahead(inner, _) => {
+			collect(inner, flags, true, captures);
+			LengthBounds::EMPTY
+		},
+		RegexNode::Lookbehind(nodes, _, _) => {
+			collect_nodes(nodes, flags, true, captures);
+			LengthBounds::EMPTY
+		},
+		RegexNode::AbsConditional(_, yes, no) |
+		RegexNode::NamedConditional(_, yes, no) => {
+			let yes = collect(yes, flags, true, captures);
+			match no {
+				Some(no) => yes.either(collect(no, flags, true, captures)),
+				None     => yes,
+			}
+		},
+		RegexNode::RecursiveConditional(_, yes, no) |
+		RegexNode::NamedRecursiveConditional(_, yes, no) => {
+			collect(yes, flags, true, captures);
+			if let Some(no) = no {
+				collect(no, flags, true, captures);
+			}
+			LengthBounds::UNBOUNDED
+		},
+		RegexNode::AssertConditional(cond, yes, no) => {
+			collect(cond, flags, true, captures);
+			let yes = collect(yes, flags, true, captures);
+			match no {
+				Some(no) => yes.either(collect(no, flags, true, captures)),
+				None     => yes,
+			}
+		},
+		RegexNode::DefineConditional(inner) => {
+			collect(inner, flags, true, captures);
+			LengthBounds::EMPTY
+		},
+		RegexNode::AbsBackRef(_) |
+		RegexNode::NamedBackRef(_) => LengthBounds::UNBOUNDED,
+		RegexNode::None |
+		RegexNode::StartOfString |
+		RegexNode::EndOfString |
+		RegexNode::InternalOptionSetting(_) |
+		RegexNode::MatchStartReset |
+		RegexNode::WordBoundary(_) |
+		RegexNode::SubjectStart |
+		RegexNode::SubjectEndOrNewline |
+		RegexNode::SubjectEndOnly |
+		RegexNode::FirstMatchPos => LengthBounds::EMPTY,
+		RegexNode::ParsedGroup(..) => unreachable!("Parsed groups should have been optimized out"),
+	}
+}
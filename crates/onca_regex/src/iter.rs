@@ -0,0 +1,180 @@
+use crate::*;
+
+/// A single match of a regex in a string.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Match<'a> {
+	s:     &'a str,
+	begin: usize,
+	end:   usize,
+}
+
+impl<'a> Match<'a> {
+	pub(crate) fn new(s: &'a str, begin: usize, end: usize) -> Self {
+		Self { s, begin, end }
+	}
+
+	/// Get the byte index of the start of the match.
+	pub fn start(&self) -> usize {
+		self.begin
+	}
+
+	/// Get the byte index of the end of the match.
+	pub fn end(&self) -> usize {
+		self.end
+	}
+
+	/// Get the byte range of the match.
+	pub fn range(&self) -> Range<usize> {
+		self.begin..self.end
+	}
+
+	/// Check if the match is empty.
+	pub fn is_empty(&self) -> bool {
+		self.begin == self.end
+	}
+
+	/// Get the matched string.
+	pub fn as_str(&self) -> &'a str {
+		&self.s[self.begin..self.end]
+	}
+}
+
+/// Iterator over all successive non-overlapping matches of a regex in a string, including their captures.
+///
+/// When an empty match is found, the next search will start at the next character,
+/// and an empty match directly following a previous match will be skipped.
+pub struct CapturesIter<'r, 's> {
+	regex:          &'r Regex,
+	s:              &'s str,
+	search_start:   usize,
+	last_match_end: Option<usize>,
+}
+
+impl<'r, 's> CapturesIter<'r, 's> {
+	pub(crate) fn new(regex: &'r Regex, s: &'s str) -> Self {
+		Self { regex, s, search_start: 0, last_match_end: None }
+	}
+}
+
+impl<'r: 's, 's> Iterator for CapturesIter<'r, 's> {
+	type Item = MatchResult<'s>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		while self.search_start <= self.s.len() {
+			let match_res = self.regex.find_from(self.s, self.search_start)?;
+			let whole = match_res.whole;
+
+			if whole.is_empty() {
+				// Always advance past an empty match, so we don't get stuck matching it forever
+				self.search_start = whole.end + self.s[whole.end..].chars().next().map_or(1, |ch| ch.len_utf8());
+
+				// Don't allow an empty match directly after the previous match
+				if self.last_match_end == Some(whole.end) {
+					continue;
+				}
+			} else {
+				self.search_start = whole.end;
+			}
+
+			self.last_match_end = Some(whole.end);
+			return Some(match_res);
+		}
+		None
+	}
+}
+
+/// Iterator over all successive non-overlapping matches of a regex in a string.
+///
+/// See [`CapturesIter`] for how empty matches are handled.
+pub struct FindIter<'r, 's> {
+	iter: CapturesIter<'r, 's>,
+}
+
+impl<'r, 's> FindIter<'r, 's> {
+	pub(crate) fn new(regex: &'r Regex, s: &'s str) -> Self {
+		Self { iter: CapturesIter::new(regex, s) }
+	}
+}
+
+impl<'r: 's, 's> Iterator for FindIter<'r, 's> {
+	type Item = Match<'s>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.iter.next().map(|res| Match::new(res.s, res.whole.begin as usize, res.whole.end))
+	}
+}
+
+/// Iterator over substrings of a string, separated by the matches of a regex.
+pub struct Split<'r, 's> {
+	finder:    FindIter<'r, 's>,
+	s:         &'s str,
+	last:      usize,
+	finished:  bool,
+}
+
+impl<'r, 's> Split<'r, 's> {
+	pub(crate) fn new(regex: &'r Regex, s: &'s str) -> Self {
+		Self { finder: FindIter::new(regex, s), s, last: 0, finished: false }
+	}
+
+	/// Return the remainder of the string that has not been split yet.
+	fn remainder(&mut self) -> Option<&'s str> {
+		if self.finished {
+			None
+		} else {
+			self.finished = true;
+			Some(&self.s[self.last..])
+		}
+	}
+}
+
+impl<'r: 's, 's> Iterator for Split<'r, 's> {
+	type Item = &'s str;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.finished {
+			return None;
+		}
+
+		match self.finder.next() {
+			Some(delim) => {
+				let res = &self.s[self.last..delim.start()];
+				self.last = delim.end();
+				Some(res)
+			},
+			None => self.remainder(),
+		}
+	}
+}
+
+/// Iterator over at most `n` substrings of a string, separated by the matches of a regex.
+/// 
+/// The last substring will contain the remainder of the string.
+pub struct SplitN<'r, 's> {
+	split: Split<'r, 's>,
+	n:     usize,
+}
+
+impl<'r, 's> SplitN<'r, 's> {
+	pub(crate) fn new(regex: &'r Regex, s: &'s str, n: usize) -> Self {
+		Self { split: Split::new(regex, s), n }
+	}
+}
+
+impl<'r: 's, 's> Iterator for SplitN<'r, 's> {
+	type Item = &'s str;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		match self.n {
+			0 => None,
+			1 => {
+				self.n = 0;
+				self.split.remainder()
+			},
+			_ => {
+				self.n -= 1;
+				self.split.next()
+			}
+		}
+	}
+}
@@ -2,12 +2,16 @@
 //! 
 //! This library supports a combined version of both PCRE and Javascript regex syntax.
 //! 
+//! # Newlines
+//! 
+//! By default, any Unicode newline is recognized by `^`, `$`, `\Z`, `.`, and `\R`, i.e. CR, LF, CRLF, VT, FF, NEL, LS, and PS.
+//! This can be restricted to only LF or only CRLF by creating the regex with a [`NewlineMode`] using [`Regex::with_options`].
+//! 
 //! # Limitations
 //! 
 //! - Only supports UTF-8, so no UTF-16, UTF-32, or EBCDIC support
 //! - Unicode property support is always enabled
 //! - No control of optimization
-//! - Always uses any new line, meaning: CR, LF, CRLF,  VT (Vertical Tab, U+000B), FF (Form Feed, U+000C), NEL (NExt Line, U+0085), LS (Line Separator, U+2028), and PS (Paragraph Separator, U+2029)
 //! - No support for PCRE options, i.e. '(*UTF)'
 //! - In PCRE Mode, the \C escape sequence is not suppported
 //! - No support for \cx
@@ -26,16 +30,37 @@ use onca_common::prelude::*;
 use onca_common_macros::flags;
 use onca_unicode_info as unicode;
 use opt_process::RegexProcessor;
+use info::CaptureMeta;
+use prefilter::Prefilter;
 use parse::Parser;
+use glob::GlobParser;
+use pikevm::PikeProgram;
+use serialize::{RegexDeserializer, RegexSerializer};
 
 mod parse;
 mod opt_process;
 mod matcher;
+mod pikevm;
+mod prefilter;
+mod serialize;
+mod glob;
+
+mod replace;
+pub use replace::*;
+
+mod info;
+pub use info::CaptureInfo;
+
+mod stream;
+pub use stream::*;
+
+mod iter;
+pub use iter::*;
 
 /// Regex flags
 #[flags]
 pub enum RegexFlags {
-	/// Case is ignored when matching literals.
+	/// Case is ignored when matching literals, using full Unicode case folding, meaning that a single character can match multiple characters, e.g. 'ß' matches "ss".
 	Caseless,
 	/// Support multi line regexes.
 	Multiline,
@@ -47,39 +72,56 @@ pub enum RegexFlags {
 	DuplicateNames,
 	/// If a regex tries to match an empty regex, it will pass and be treated as if the back reference doesn't exist
 	AllowEmtpyBackRefs,
+	/// The regex can only match at the position a search is started at, instead of at any position after it.
+	/// 
+	/// When iterating over matches, this means every match needs to directly follow the previous one.
+	Anchored,
+	/// When combined with [`RegexFlags::Caseless`], only ASCII characters are matched caselessly, which is faster than full Unicode case folding.
+	AsciiCaseless,
 }
 
+/// Sequences that are recognized as a newline by `^`, `$`, `\Z`, `.`, and `\R`.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum NewlineMode {
+	/// Any Unicode newline: CR, LF, CRLF, VT (Vertical Tab, U+000B), FF (Form Feed, U+000C), NEL (NExt Line, U+0085), LS (Line Separator, U+2028), and PS (Paragraph Separator, U+2029).
+	/// 
+	/// A CRLF is always treated as a single newline.
+	#[default]
+	AnyUnicode,
+	/// Only LF.
+	LfOnly,
+	/// Only CRLF, a lone CR or LF is treated as a normal character.
+	CrLf,
+}
 
 #[derive(Clone, Copy, Default)]
 struct RegexRange {
-	pub begin: u16,
-	pub end:   u16
+	pub begin: usize,
+	pub end:   usize
 }
 
 impl RegexRange {
 	pub fn to_range(self) -> Range<usize> {
-		Range { start: self.begin as usize, end: self.end as usize }
-	}
-
-	pub fn is_empty(self) -> bool {
-		self.begin == self.end
+		Range { start: self.begin, end: self.end }
 	}
 }
 
 
+#[derive(Clone)]
 enum RepetitionMode {
 	Exactly(u16),
 	AtLeast(u16),
 	AtLeastAtMost(u16, u16),
 }
 
+#[derive(Clone)]
 enum RepetitionStrategy {
 	Greedy,
 	Possessive,
 	Lazy,
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
 enum CharacterClass {
 	HorizontalWhitespace,
 	VerticalWhitespace,
@@ -110,7 +152,8 @@ enum RegexFlagChange {
 	ExtendedOff,
 	ExtendedOn,
 }
- 
+
+#[derive(Clone)]
 enum RegexNode {
 	// Special node that does nothing, used to handle things like \Q and \E
 	None,
@@ -257,19 +300,31 @@ impl RegexError {
 
 // TODO: Match and Recursion limits
 // TODO: Composable regexes (need to change parsing to only store relative capture indices)
+#[derive(Clone, Copy, Default)]
 pub struct RegexOptions {
 	pub flags:      RegexFlags,
-
+	/// Sequences that are recognized as a newline.
+	pub newline:    NewlineMode,
 }
 
 pub struct Regex {
 	node:          RegexNode,
 	capture_names: HashMap<String, Vec<u16>>,
 	flags:         RegexFlags,
+	newline:       NewlineMode,
+	program:       Option<PikeProgram>,
+	capture_meta:  Vec<Option<CaptureMeta>>,
+	prefilter:     Option<Prefilter>,
 }
 
 impl Regex {
 	pub fn new(regex: &str, flags: RegexFlags) -> Result<Self, RegexError> {
+		Self::with_options(regex, RegexOptions { flags, ..Default::default() })
+	}
+
+	/// Create a regex with additional options, like the newline mode.
+	pub fn with_options(regex: &str, options: RegexOptions) -> Result<Self, RegexError> {
+		let RegexOptions { flags, newline } = options;
 		let parser = Parser::new(regex, flags);
 		let (mut node, capture_names) = match parser.parse() {
 			Ok(tup) => tup,
@@ -285,49 +340,290 @@ impl Regex {
 			return Err(err);
 		}
 
-		Ok(Self { node, capture_names, flags })
+		Ok(Self::from_parts(node, capture_names, flags, newline))
+	}
+
+	/// Create a regex from a glob pattern, e.g. `assets/**/*.png`, which matches complete paths using `/` as the separator.
+	/// 
+	/// Supports `?`, `*`, `**` segments, character classes like `[a-z]` and `[!a-z]`, brace expansion like `{png,jpg}`, and `\\` to escape characters.
+	pub fn from_glob(glob: &str) -> Result<Self, RegexError> {
+		let mut node = match GlobParser::new(glob).parse() {
+			Ok(node) => node,
+			Err(mut err) => {
+				err.regex = glob.to_string();
+				return Err(err);
+			}
+		};
+
+		let processor = RegexProcessor::new();
+		if let Err(mut err) = processor.process_and_optimize(&mut node) {
+			err.regex = glob.to_string();
+			return Err(err);
+		}
+
+		Ok(Self::from_parts(node, HashMap::new(), RegexFlags::None, NewlineMode::default()))
+	}
+
+	/// Create a regex from a processed node tree.
+	/// 
+	/// When the regex does not need any backtracking features, i.e. back references, lookarounds, conditionals, or atomic groups,
+	/// it will be matched using a Pike VM, which guarantees a matching time linear in the size of the subject.
+	fn from_parts(node: RegexNode, capture_names: HashMap<String, Vec<u16>>, flags: RegexFlags, newline: NewlineMode) -> Self {
+		let program = PikeProgram::compile(&node, flags, newline);
+		let capture_meta = info::collect_capture_meta(&node, flags);
+		let prefilter = RegexProcessor::new().extract_literals(&node, flags).map(Prefilter::new);
+		Self { node, capture_names, flags, newline, program, capture_meta, prefilter }
+	}
+
+	/// Get the sequences that are recognized as a newline by the regex.
+	pub fn newline_mode(&self) -> NewlineMode {
+		self.newline
+	}
+
+	/// Check if the regex is matched using the linear time engine, instead of the backtracking matcher.
+	pub fn is_linear_time(&self) -> bool {
+		self.program.is_some()
+	}
+
+	/// Get the number of capture groups in the regex, not including the whole match.
+	pub fn capture_count(&self) -> u16 {
+		(self.capture_meta.len() - 1) as u16
+	}
+
+	/// Get an iterator over the index and name of each capture group, not including the whole match.
+	pub fn capture_names(&self) -> impl Iterator<Item = (u16, Option<&str>)> {
+		(1..=self.capture_count()).map(|idx| (idx, self.capture_name(idx)))
+	}
+
+	/// Get the name of a capture group, if it is named.
+	pub fn capture_name(&self, idx: u16) -> Option<&str> {
+		self.capture_names.iter()
+			.find(|(_, indices)| indices.contains(&idx))
+			.map(|(name, _)| name.as_str())
+	}
+
+	/// Get information about a capture group, index 0 will return the information about the whole match.
+	pub fn capture_info(&self, idx: u16) -> Option<CaptureInfo<'_>> {
+		let meta = self.capture_meta.get(idx as usize).copied().flatten()?;
+		Some(CaptureInfo {
+			index: idx,
+			name: self.capture_name(idx),
+			min_len: meta.bounds.min,
+			max_len: meta.bounds.max,
+			optional: meta.optional,
+		})
+	}
+
+	/// Serialize the compiled regex to bytes.
+	/// 
+	/// This allows regexes to be compiled offline and loaded using [`Regex::from_bytes`], without needing to parse and process the regex again.
+	pub fn to_bytes(&self) -> Vec<u8> {
+		RegexSerializer::new().serialize(self)
+	}
+
+	/// Deserialize a compiled regex from bytes created with [`Regex::to_bytes`].
+	/// 
+	/// Returns an error when the data is malformed, or was serialized by an incompatible version.
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self, RegexError> {
+		RegexDeserializer::new(bytes).deserialize()
 	}
 
 	/// Check if a string matches the regex entirely, if so, return a result with the captures.
 	pub fn is_match<'a>(&'a self, s: &'a str) -> Option<MatchResult<'a>> {
-		let mut matcher = Matcher::new(s, self.flags, &self.capture_names, true);
-		if let Some(captures) = matcher.find(&self.node) && matcher.is_empty() {
-			return Some(MatchResult{
-				regex: self,
-				s,
-				captures,
-			})
+		self.is_match_at(s, 0)
+	}
+
+	/// Check if the remainder of a string starting at byte index `start` matches the regex entirely, if so, return a result with the captures.
+	/// 
+	/// Unlike matching a slice of the string, the part of the string before `start` is still visible to assertions like `\b` and lookbehinds,
+	/// and `^` will only match at `start` if it is the start of the string or of a line in multiline mode.
+	/// 
+	/// Returns `None` if `start` is not on a character boundary.
+	pub fn is_match_at<'a>(&'a self, s: &'a str, start: usize) -> Option<MatchResult<'a>> {
+		if !s.is_char_boundary(start) {
+			return None;
+		}
+		if let Some(prefilter) = &self.prefilter && !prefilter.could_match_exactly(s, start) {
+			return None;
+		}
+
+		let res = self.find_single(s, start)?;
+		if res.whole.end == s.len() {
+			Some(res)
 		} else {
 			None
 		}
 	}
 
 	/// Check if a string contains the regex, if so, return the byte index into the string and a result with the captures.
+	/// 
+	/// If the regex was created with [`RegexFlags::Anchored`], the match needs to start at the start of the string.
 	pub fn contains<'a>(&'a self, s: &'a str) -> Option<(usize, MatchResult<'a>)> {
-		for (idx, _) in s.char_indices() {
-			let mut matcher = Matcher::new(&s[idx..], self.flags, &self.capture_names, idx == 0);
-			if let Some(captures) = matcher.find(&self.node) {
-				return Some((idx, MatchResult{
-    			    regex: self,
-    			    s,
-    			    captures,
-    			}))
+		self.find_at(s, 0)
+	}
+
+	/// Find the first match in a string starting at or after byte index `start`, if found, return the byte index into the string and a result with the captures.
+	/// 
+	/// If the regex was created with [`RegexFlags::Anchored`], the match needs to start at `start`.
+	/// 
+	/// Unlike matching a slice of the string, the part of the string before `start` is still visible to assertions like `\b` and lookbehinds,
+	/// and `^` will only match at `start` if it is the start of the string or of a line in multiline mode. `\G` matches at `start`.
+	/// 
+	/// Returns `None` if `start` is not on a character boundary.
+	pub fn find_at<'a>(&'a self, s: &'a str, start: usize) -> Option<(usize, MatchResult<'a>)> {
+		if !s.is_char_boundary(start) {
+			return None;
+		}
+		self.find_from(s, start).map(|res| (res.whole.begin, res))
+	}
+
+	/// Replace the first match in a string using the given replacer.
+	/// 
+	/// A `&str` or `String` replacer is interpreted as a replacement template, see [`Replacer`] for the syntax.
+	pub fn replace<R: Replacer>(&self, s: &str, replacer: R) -> String {
+		self.replacen(s, 1, replacer)
+	}
+
+	/// Replace all non-overlapping matches in a string using the given replacer.
+	/// 
+	/// A `&str` or `String` replacer is interpreted as a replacement template, see [`Replacer`] for the syntax.
+	pub fn replace_all<R: Replacer>(&self, s: &str, replacer: R) -> String {
+		self.replacen(s, 0, replacer)
+	}
+
+	/// Replace at most `limit` non-overlapping matches in a string using the given replacer, when `limit` is 0, all matches will be replaced.
+	/// 
+	/// A `&str` or `String` replacer is interpreted as a replacement template, see [`Replacer`] for the syntax.
+	pub fn replacen<R: Replacer>(&self, s: &str, limit: usize, mut replacer: R) -> String {
+		let mut res = String::with_capacity(s.len());
+		let mut last_copied = 0;
+
+		let limit = if limit == 0 { usize::MAX } else { limit };
+		for match_res in self.captures_iter(s).take(limit) {
+			let whole = match_res.whole.to_range();
+			res.push_str(&s[last_copied..whole.start]);
+			replacer.replace_append(&match_res, &mut res);
+			last_copied = whole.end;
+		}
+
+		res.push_str(&s[last_copied..]);
+		res
+	}
+
+	/// Get an iterator over all successive non-overlapping matches in a string.
+	pub fn find_iter<'r, 's>(&'r self, s: &'s str) -> FindIter<'r, 's> {
+		FindIter::new(self, s)
+	}
+
+	/// Get an iterator over all successive non-overlapping matches in a string, including their captures.
+	pub fn captures_iter<'r, 's>(&'r self, s: &'s str) -> CapturesIter<'r, 's> {
+		CapturesIter::new(self, s)
+	}
+
+	/// Get an iterator over the substrings of a string, separated by the matches of the regex.
+	/// 
+	/// If the regex matches at the start or end of the string, an empty substring will be returned at that side.
+	pub fn split<'r, 's>(&'r self, s: &'s str) -> Split<'r, 's> {
+		Split::new(self, s)
+	}
+
+	/// Get an iterator over at most `n` substrings of a string, separated by the matches of the regex.
+	/// 
+	/// The last substring returned will contain the remainder of the string.
+	pub fn splitn<'r, 's>(&'r self, s: &'s str, n: usize) -> SplitN<'r, 's> {
+		SplitN::new(self, s, n)
+	}
+
+	/// Find the first match starting at or after `start`, or only at `start` if the regex is anchored.
+	pub(crate) fn find_from<'a>(&'a self, s: &'a str, start: usize) -> Option<MatchResult<'a>> {
+		let mut start = start;
+		if let Some(prefilter) = &self.prefilter {
+			// Any match needs to contain the required literal, so if it's missing, we don't need to try matching at all
+			if !prefilter.could_contain(s, start) {
+				return None;
 			}
+
+			if prefilter.has_prefix() && !self.flags.contains(RegexFlags::Anchored) {
+				start = prefilter.next_candidate(s, start)?;
+				if self.program.is_none() {
+					return self.find_from_candidates(prefilter, s, start);
+				}
+			}
+		}
+
+		if self.flags.contains(RegexFlags::Anchored) {
+			return self.find_single(s, start);
+		}
+
+		if let Some(program) = &self.program {
+			let slots = program.search(s, start, false)?;
+			return Some(self.match_from_slots(s, &slots));
+		}
+
+		for (idx, _) in s[start..].char_indices() {
+			if let Some(res) = self.find_single(s, start + idx) {
+				return Some(res);
+			}
+		}
+		// An empty regex can still match at the end of the string
+		self.find_single(s, s.len())
+	}
+
+	/// Find the first match by only trying to match at the positions where the prefix of the regex occurs.
+	fn find_from_candidates<'a>(&'a self, prefilter: &Prefilter, s: &'a str, mut candidate: usize) -> Option<MatchResult<'a>> {
+		loop {
+			if let Some(res) = self.find_single(s, candidate) {
+				return Some(res);
+			}
+			let next = candidate + s[candidate..].chars().next().map_or(1, |ch| ch.len_utf8());
+			candidate = prefilter.next_candidate(s, next)?;
+		}
+	}
+
+	/// Try to match the regex exactly at `idx`.
+	fn find_single<'a>(&'a self, s: &'a str, idx: usize) -> Option<MatchResult<'a>> {
+		if let Some(program) = &self.program {
+			let slots = program.search(s, idx, true)?;
+			return Some(self.match_from_slots(s, &slots));
+		}
+
+		let mut matcher = Matcher::new(s, idx, idx, self.flags, self.newline, &self.capture_names);
+		let captures = matcher.find(&self.node)?;
+		Some(MatchResult {
+			regex: self,
+			s,
+			whole: RegexRange { begin: idx, end: matcher.index() },
+			captures,
+		})
+	}
+
+	/// Create a match result from the slots returned by the Pike VM.
+	fn match_from_slots<'a>(&'a self, s: &'a str, slots: &[Option<usize>]) -> MatchResult<'a> {
+		let captures = slots.chunks_exact(2).enumerate().map(|(idx, slots)| match (slots[0], slots[1]) {
+			(Some(begin), Some(end)) if idx != 0 => Some(RegexRange { begin, end }),
+			_ => None,
+		}).collect();
+
+		MatchResult {
+			regex: self,
+			s,
+			whole: RegexRange { begin: slots[0].unwrap_or_default(), end: slots[1].unwrap_or_default() },
+			captures,
 		}
-		None
 	}
 }
 
 pub struct MatchResult<'a> {
 	regex:    &'a Regex,
 	s:        &'a str,
-	captures: Vec<RegexRange>,
+	whole:    RegexRange,
+	/// Range of each capture, `None` if the capture did not participate in the match.
+	captures: Vec<Option<RegexRange>>,
 }
 
 impl MatchResult<'_> {
 	pub fn has_capture(&self, idx: u16) -> bool {
-		let idx = idx as usize;
-		idx < self.captures.len() && !self.captures[idx].is_empty()
+		self.captures.get(idx as usize).map_or(false, Option::is_some)
 	}
 
 	pub fn has_capture_by_name(&self, name: &str) -> bool {
@@ -340,27 +636,36 @@ impl MatchResult<'_> {
 		false
 	}
 
+	/// Get a capture, if it participated in the match.
 	pub fn get_capture(&self, idx: u16) -> Option<&str> {
-		let idx = idx as usize;
-		if idx < self.captures.len() && self.captures[idx].is_empty() {
-			let range = self.captures[idx];
-			Some(&self.s[range.to_range()])
-		} else {
-			None
-		}
+		self.get_capture_range(idx).map(|range| &self.s[range])
+	}
 
-		// let range = self.captures.get(&idx)?;
-		// Some(&self.s[range.to_range()])
+	/// Get the byte range of a capture in the matched string, if it participated in the match.
+	pub fn get_capture_range(&self, idx: u16) -> Option<Range<usize>> {
+		self.captures.get(idx as usize).copied().flatten().map(RegexRange::to_range)
 	}
 
 	pub fn get_capture_by_name(&self, name: &str) -> Option<&str> {
+		self.get_capture_range_by_name(name).map(|range| &self.s[range])
+	}
+
+	/// Get the byte range of a named capture in the matched string, if it participated in the match.
+	/// 
+	/// When multiple captures share the same name, the last one that participated in the match is returned.
+	pub fn get_capture_range_by_name(&self, name: &str) -> Option<Range<usize>> {
 		let indices = self.regex.capture_names.get(name)?;
-		for idx in indices.iter().rev() {
-			if let Some(s) = self.get_capture(*idx) {
-				return Some(s);
-			}
-		}
-		None
+		indices.iter().rev().find_map(|idx| self.get_capture_range(*idx))
+	}
+
+	/// Get the whole matched string.
+	pub fn whole_match(&self) -> &str {
+		&self.s[self.whole.to_range()]
+	}
+
+	/// Get the byte range of the whole match in the matched string.
+	pub fn whole_match_range(&self) -> Range<usize> {
+		self.whole.to_range()
 	}
 }
 
@@ -71,54 +71,276 @@ const NEWLINE_CHARS: [char; 7] = [
 	'\u{2029}',
 ];
 
-const NEWLINE_CHARS_STR: [&'static str; 8] = [
-	"\r",
-	"\n",
-	"\r\n",
-	"\u{000b}",
-	"\u{000c}",
-	"\u{0085}",
-	"\u{2028}",
-	"\u{2029}",
-];
+/// Check if a character is part of a character class.
+pub(crate) fn class_matches(class: &CharacterClass, ch: char) -> bool {
+	match class {
+		CharacterClass::HorizontalWhitespace => HORIZONTAL_WHITESPACE_CHARS.contains(&ch),
+		CharacterClass::VerticalWhitespace   => VERTICAL_WHITESPACE_CHARS.contains(&ch),
+		CharacterClass::Whitespace           => WHITESPACE_CHARS.contains(&ch),
+		CharacterClass::Word                 => ch == '_' || ch.is_alphanumeric(),
+		CharacterClass::NonNewLine           => !NEWLINE_CHARS.contains(&ch),
+		CharacterClass::Category(cat)        => unicode::get_category(ch as u32).is_some_and(|val| val.intersects(*cat)),
+		CharacterClass::Script(script)       => unicode::get_script(ch).map_or(false, |val| val == *script) ||
+												unicode::get_script_extensions(ch).is_some_and(|val| val.contains(script)),
+		CharacterClass::PosixSpace           => ch == '\u{0C}' || unicode::get_category(ch as u32).is_some_and(|val| val.intersects(unicode::Category::Separator)),
+		CharacterClass::UNC                  => ch == '$' || ch == '@'|| ch == '`' || (ch as u32 >= 0xA0 && ((ch as u32) < 0xD800 || ch as u32 > 0xDFFF )),
+		CharacterClass::PosixAscii           => (ch as u32) <= 127,
+		CharacterClass::PosixGraph           |
+		CharacterClass::PosixPrint           => ch != '\u{061C}' &&
+			ch  != '\u{180E}' &&
+			!(ch >= '\u{2066}' && ch <= '\u{2069}') &&
+			unicode::get_category(ch as u32).is_some_and(|val| val.intersects(
+				unicode::Category::Letter |
+				unicode::Category::Mark |
+				unicode::Category::Number |
+				unicode::Category::Punctuation |
+				unicode::Category::Symbol |
+				unicode::Category::Format |
+				if *class == CharacterClass::PosixPrint { unicode::Category::SpaceSeparator } else { unicode::Category::None }
+			)),
+		CharacterClass::PosixXDigit          => (ch >= '0' && ch <= '9') || (ch >= 'a' && ch <= 'f') || (ch >= 'A' && ch <= 'F'),
+		CharacterClass::Any                  => true,
+		_ => todo!(),
+	}
+}
 
-const LINE_WRAPS: [&'static str; 2] = [
-	"\n",
-	"\r\n"
-];
+/// Iterator over the case folded representation of a character.
+/// 
+/// When the regex is not caseless, this only returns the character itself.
+#[derive(Clone, Copy)]
+pub(crate) struct CaseFold {
+	chars: [char; 3],
+	len:   u8,
+	idx:   u8,
+}
+
+impl CaseFold {
+	pub fn new(flags: RegexFlags, ch: char) -> Self {
+		if !flags.contains(RegexFlags::Caseless) {
+			return Self::single(ch);
+		}
+		if flags.contains(RegexFlags::AsciiCaseless) {
+			return Self::single(ch.to_ascii_lowercase());
+		}
+
+		match unicode::to_casefold(ch) {
+			unicode::Casing::Simple(folded) => Self::single(folded),
+			unicode::Casing::Complex(folded) => {
+				let mut chars = ['\0'; 3];
+				chars[..folded.len()].copy_from_slice(folded);
+				Self { chars, len: folded.len() as u8, idx: 0 }
+			},
+			unicode::Casing::Conditional(_) => Self::single(ch),
+		}
+	}
+
+	fn single(ch: char) -> Self {
+		Self { chars: [ch, '\0', '\0'], len: 1, idx: 0 }
+	}
+
+	/// Get the remaining case folded characters.
+	pub fn as_slice(&self) -> &[char] {
+		&self.chars[self.idx as usize..self.len as usize]
+	}
+}
+
+impl Iterator for CaseFold {
+	type Item = char;
+
+	fn next(&mut self) -> Option<char> {
+		if self.idx < self.len {
+			self.idx += 1;
+			Some(self.chars[self.idx as usize - 1])
+		} else {
+			None
+		}
+	}
+}
+
+/// Get the number of bytes at the start of the cursor that match the literal, taking into account caseless matching.
+/// 
+/// When caseless, both are compared using their case folded representation, so a single character can match multiple characters, e.g. 'ß' matches "ss".
+pub(crate) fn literal_match_len(flags: RegexFlags, literal: &str, cursor: &str) -> Option<usize> {
+	if !flags.contains(RegexFlags::Caseless) {
+		return if cursor.starts_with(literal) { Some(literal.len()) } else { None };
+	}
+
+	let mut expected = literal.chars().flat_map(|ch| CaseFold::new(flags, ch)).peekable();
+	if expected.peek().is_none() {
+		return Some(0);
+	}
+
+	let mut len = 0;
+	for ch in cursor.chars() {
+		for folded in CaseFold::new(flags, ch) {
+			if expected.next() != Some(folded) {
+				return None;
+			}
+		}
+
+		len += ch.len_utf8();
+		if expected.peek().is_none() {
+			return Some(len);
+		}
+	}
+	None
+}
+
+/// Check if a character matches a class definition, i.e. `[...]`, without taking into account if the class is negated.
+/// 
+/// When caseless, the class matches if any case variant of the character is part of the explicit characters or ranges, e.g. `[a-z]` matches 'A'.
+pub(crate) fn class_def_matches(flags: RegexFlags, chars: &[char], ranges: &[(char, char)], nodes: &[RegexNode], ch: char) -> bool {
+	let matches_char = |ch: char| chars.contains(&ch) || ranges.iter().any(|(begin, end)| *begin <= ch && ch <= *end);
+
+	let char_matches = if !flags.contains(RegexFlags::Caseless) {
+		matches_char(ch)
+	} else if flags.contains(RegexFlags::AsciiCaseless) {
+		matches_char(ch) || matches_char(ch.to_ascii_lowercase()) || matches_char(ch.to_ascii_uppercase())
+	} else {
+		// Only single character case mappings are considered, as a class only ever matches a single character
+		let folded = match unicode::to_casefold(ch) {
+			unicode::Casing::Simple(folded) => folded,
+			_ => ch,
+		};
+		fn single_char(mut iter: impl Iterator<Item = char>) -> Option<char> {
+			let ch = iter.next()?;
+			iter.next().is_none().then_some(ch)
+		}
+
+		matches_char(ch) ||
+			matches_char(folded) ||
+			[ch, folded].into_iter().any(|ch| {
+				single_char(ch.to_lowercase()).is_some_and(matches_char) ||
+				single_char(ch.to_uppercase()).is_some_and(matches_char)
+			})
+	};
+
+	char_matches ||
+		nodes.iter().any(|node| match node {
+			RegexNode::CharacterClass(class, expected) => class_matches(class, ch) == *expected,
+			_ => false,
+		})
+}
+
+/// Get the length of the newline at the start of the cursor, if there is one.
+pub(crate) fn newline_len(newline: NewlineMode, cursor: &str) -> Option<usize> {
+	match newline {
+		NewlineMode::AnyUnicode => if cursor.starts_with("\r\n") {
+			Some(2)
+		} else {
+			cursor.chars().next().filter(|ch| NEWLINE_CHARS.contains(ch)).map(char::len_utf8)
+		},
+		NewlineMode::LfOnly => cursor.starts_with('\n').then_some(1),
+		NewlineMode::CrLf => cursor.starts_with("\r\n").then_some(2),
+	}
+}
+
+/// Check if the string ends with a newline.
+fn ends_with_newline(newline: NewlineMode, s: &str) -> bool {
+	match newline {
+		NewlineMode::AnyUnicode => s.chars().next_back().is_some_and(|ch| NEWLINE_CHARS.contains(&ch)),
+		NewlineMode::LfOnly => s.ends_with('\n'),
+		NewlineMode::CrLf => s.ends_with("\r\n"),
+	}
+}
+
+/// Check if the given byte index is in between the CR and LF of a CRLF that is treated as a single newline.
+fn is_inside_crlf(newline: NewlineMode, s: &str, idx: usize) -> bool {
+	newline == NewlineMode::AnyUnicode && s[..idx].ends_with('\r') && s[idx..].starts_with('\n')
+}
+
+/// Check if the given byte index is directly before a newline that ends the subject.
+fn is_before_final_newline(newline: NewlineMode, s: &str, idx: usize) -> bool {
+	let cursor = &s[idx..];
+	newline_len(newline, cursor) == Some(cursor.len()) && !is_inside_crlf(newline, s, idx)
+}
+
+/// Check if a dot can match the start of the cursor.
+pub(crate) fn dot_matches(flags: RegexFlags, newline: NewlineMode, cursor: &str) -> bool {
+	!cursor.is_empty() && (flags.contains(RegexFlags::DotAll) || newline_len(newline, cursor).is_none())
+}
+
+fn is_word_char(ch: char) -> bool {
+	ch == '_' || ch.is_alphanumeric()
+}
+
+/// Check if there is a word boundary at the given byte index.
+pub(crate) fn is_word_boundary(s: &str, idx: usize) -> bool {
+	let is_prev_word = s[..idx].chars().next_back().is_some_and(is_word_char);
+	let is_next_word = s[idx..].chars().next().is_some_and(is_word_char);
+	is_prev_word != is_next_word
+}
+
+/// Check if the given byte index is at the start of the subject, or at the start of a line when in multiline mode.
+pub(crate) fn is_at_start_boundary(flags: RegexFlags, newline: NewlineMode, s: &str, idx: usize) -> bool {
+	if idx == 0 {
+		return true;
+	}
+	if !flags.contains(RegexFlags::Multiline) || idx == s.len() {
+		return false;
+	}
+
+	ends_with_newline(newline, &s[..idx]) && !is_inside_crlf(newline, s, idx)
+}
+
+/// Check if the given byte index is at the end of the subject, or before a newline at the end of the subject.
+/// When in multiline mode, this will match before any newline.
+pub(crate) fn is_at_end_boundary(flags: RegexFlags, newline: NewlineMode, s: &str, idx: usize) -> bool {
+	if idx == s.len() {
+		return true;
+	}
+
+	if flags.contains(RegexFlags::Multiline) {
+		newline_len(newline, &s[idx..]).is_some() && !is_inside_crlf(newline, s, idx)
+	} else {
+		!flags.contains(RegexFlags::DollarEndOnly) && is_before_final_newline(newline, s, idx)
+	}
+}
+
+/// Check if the given byte index is at the end of the subject, or before a newline at the end of the subject.
+pub(crate) fn is_at_subject_end_or_newline(newline: NewlineMode, s: &str, idx: usize) -> bool {
+	idx == s.len() || is_before_final_newline(newline, s, idx)
+}
 
 pub(crate) struct Matcher<'a> {
     flags:          RegexFlags,
+    newline:        NewlineMode,
     orig:           &'a str,
     cursor:         &'a str,
     index:          usize,
 	atomic_index:   usize,
-	captures:       Vec<RegexRange>,
+	captures:       Vec<Option<RegexRange>>,
 	capture_names:  &'a HashMap<String, Vec<u16>>,
 	enable_capture: bool,
-    start_from_0:   bool,
+	first_match_pos: usize,
 }
 
 impl<'a> Matcher<'a> {
-    pub fn new(s: &'a str, flags: RegexFlags, capture_names: &'a HashMap<String, Vec<u16>>, start_from_0: bool) -> Self {
+    /// Create a matcher that starts matching at byte index `start`.
+    /// 
+    /// `first_match_pos` is the position matched by `\G`, i.e. the position the search was started at.
+    pub fn new(s: &'a str, start: usize, first_match_pos: usize, flags: RegexFlags, newline: NewlineMode, capture_names: &'a HashMap<String, Vec<u16>>) -> Self {
         Self {
 		    flags,
+		    newline,
 		    orig: s,
-		    cursor: s,
-		    index: 0,
+		    cursor: &s[start..],
+		    index: start,
 			atomic_index: 0,
 		    captures: Vec::new(),
 			capture_names,
 			enable_capture: true,
-            start_from_0,
+			first_match_pos,
 		}
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.cursor.is_empty()
+    /// Get the byte index the matcher is currently at.
+    pub fn index(&self) -> usize {
+        self.index
     }
 
-    pub fn find(&mut self, node: &RegexNode) -> Option<Vec<RegexRange>> {
+    /// Find a match, returning the range of each capture, or `None` for captures that did not participate in the match.
+    pub fn find(&mut self, node: &RegexNode) -> Option<Vec<Option<RegexRange>>> {
         if self.find_and_match(node) {
             Some(core::mem::take(&mut self.captures))
         } else {
@@ -137,80 +359,29 @@ impl<'a> Matcher<'a> {
 				}
 				true
 			}
-		    RegexNode::Literal(literal) => if self.flags.contains(RegexFlags::Caseless) {
-                let lower_lit = literal.to_lowercase();
-                let lower_cursor = self.cursor.to_lowercase();
-
-                if lower_cursor.starts_with(&lower_lit) {
-                    self.move_equivalent(literal)
-                } else {
-                    false
-                }
-            } else {
-                if self.cursor.starts_with(literal) {
-		    		self.move_cursor(literal.len())
-		    	} else {
-		    		false
-		    	}
-            },
-			RegexNode::LiteralChar(ch) => if self.flags.contains(RegexFlags::Caseless) {
-                let lower_ch = ch.to_lowercase();
-                let Some(first_ch) = self.cursor.chars().next() else { return false; };
-                let first_ch = first_ch.to_lowercase();
-
-                let res = lower_ch.clone().len() == first_ch.clone().len() && lower_ch.zip(first_ch).all(|(a, b)| a == b);
-
-                if res {
-                    self.move_cursor(ch.len_utf8())
-                } else {
-                    false
-                }
-            } else {
-                if self.cursor.starts_with(*ch) {
-			    	self.move_cursor(ch.len_utf8())
-			    } else {
-			    	false
-			    }
-            },
-			RegexNode::Dot => if self.flags.contains(RegexFlags::DotAll) || LINE_WRAPS.iter().any(|s| !self.cursor.starts_with(s)) {
+		    RegexNode::Literal(literal) => match literal_match_len(self.flags, literal, self.cursor) {
+				Some(len) => self.move_cursor(len),
+				None => false,
+			},
+			RegexNode::LiteralChar(ch) => match literal_match_len(self.flags, ch.encode_utf8(&mut [0; 4]), self.cursor) {
+				Some(len) => self.move_cursor(len),
+				None => false,
+			},
+			RegexNode::Dot => if dot_matches(self.flags, self.newline, self.cursor) {
 				let mut chars = self.cursor.chars();
 				let Some(ch) = chars.next() else { return false; };
 				self.move_cursor(ch.len_utf8())
 			} else {
 				false
 			},
+			RegexNode::CharacterClass(CharacterClass::AtomicNewLine, _) => match newline_len(self.newline, self.cursor) {
+				Some(len) => self.move_cursor(len),
+				None => false,
+			},
 			RegexNode::CharacterClass(class, expected) => {
 				let mut chars = self.cursor.chars();
 				let Some(ch) = chars.next() else { return false; };
-				let res = match class {
-        			CharacterClass::HorizontalWhitespace => HORIZONTAL_WHITESPACE_CHARS.contains(&ch),
-        			CharacterClass::VerticalWhitespace   => VERTICAL_WHITESPACE_CHARS.contains(&ch),
-        			CharacterClass::Whitespace           => WHITESPACE_CHARS.contains(&ch),
-        			CharacterClass::Word                 => ch == '_' || ch.is_alphanumeric(),
-					CharacterClass::NonNewLine           => !NEWLINE_CHARS.contains(&ch),
-					CharacterClass::Category(cat)        => unicode::get_category(ch as u32).is_some_and(|val| val.intersects(*cat)),
-					CharacterClass::Script(script)       => unicode::get_script(ch).map_or(false, |val| val == *script) ||
-						                                    unicode::get_script_extensions(ch).is_some_and(|val| val.contains(script)),
-					CharacterClass::PosixSpace           => ch == '\u{0C}' || unicode::get_category(ch as u32).is_some_and(|val| val.intersects(unicode::Category::Separator)),
-					CharacterClass::UNC                  => ch == '$' || ch == '@'|| ch == '`' || (ch as u32 >= 0xA0 && ((ch as u32) < 0xD800 || ch as u32 > 0xDFFF )),
-					CharacterClass::PosixAscii           => (ch as u32) <= 127,
-					CharacterClass::PosixGraph           |
-					CharacterClass::PosixPrint           => ch != '\u{061C}' &&
-						ch  != '\u{180E}' &&
-						!(ch >= '\u{2066}' && ch <= '\u{2069}') &&
-						unicode::get_category(ch as u32).is_some_and(|val| val.intersects(
-							unicode::Category::Letter |
-							unicode::Category::Mark |
-							unicode::Category::Number |
-							unicode::Category::Punctuation |
-							unicode::Category::Symbol |
-							unicode::Category::Format |
-							if *class == CharacterClass::PosixPrint { unicode::Category::SpaceSeparator } else { unicode::Category::None }
-						)),
-					CharacterClass::PosixXDigit          => (ch >= '0' && ch <= '9') || (ch >= 'a' && ch <= 'f') || (ch >= 'A' && ch <= 'F'),
-					CharacterClass::Any                  => true,
-					_ => todo!(),
-    			};
+				let res = class_matches(class, ch);
 
 				// Either value needs to be true, i.e. (false, true) or (true, false) only
 				if res == *expected {
@@ -350,9 +521,9 @@ impl<'a> Matcher<'a> {
 				if self.enable_capture {
 					if let Some(capture_idx) = capture_idx {
 						if self.captures.len() <= *capture_idx as usize {
-							self.captures.resize(*capture_idx as usize + 1, RegexRange::default());
+							self.captures.resize(*capture_idx as usize + 1, None);
 						}
-						self.captures[*capture_idx as usize] = RegexRange{ begin: start_idx as u16, end: self.index as u16 };
+						self.captures[*capture_idx as usize] = Some(RegexRange{ begin: start_idx, end: self.index });
 					}
 				}	
 
@@ -365,30 +536,15 @@ impl<'a> Matcher<'a> {
 			},
 			RegexNode::ClassDef(chars, ranges, nodes, expected) => {
 				let Some(ch) = self.cursor.chars().next() else { return false };
-				let start_idx = self.index;
-				if chars.contains(&ch) ||
-					ranges.iter().any(|(begin, end)| *begin <= ch && ch <= *end) ||
-					nodes.iter().any(|node| self.find_and_match(node))
-				{
-					if *expected {
-						if self.index == start_idx {
-							self.move_cursor(ch.len_utf8());
-						}
-						return true;
-					} else {
-						return false;
-					}
-				}
-
-				if *expected {
-					false
-				} else {
+				if class_def_matches(self.flags, chars, ranges, nodes, ch) == *expected {
 					self.move_cursor(ch.len_utf8())
+				} else {
+					false
 				}
 			},
 			RegexNode::CharacterClassChar(_) => panic!("A CharacterClassChar should never appear in a compiled regex"),
-			RegexNode::StartOfString => self.is_at_start_boundary(),
-			RegexNode::EndOfString => self.is_at_end_boundary(),
+			RegexNode::StartOfString => is_at_start_boundary(self.flags, self.newline, self.orig, self.index),
+			RegexNode::EndOfString => is_at_end_boundary(self.flags, self.newline, self.orig, self.index),
 			RegexNode::InternalOptionSetting(flag_change) => {
                 if flag_change.contains(RegexFlagChange::CaselessOff) {
                     self.flags &= !RegexFlags::Caseless;
@@ -410,65 +566,29 @@ impl<'a> Matcher<'a> {
 
 				true
 			},
-			RegexNode::WordBoundary(expected) => {
-				let is_prev_word = if self.index == 0 {
-					true
-				} else {
-					let Some(ch) = self.orig[self.index..].chars().next() else { return false };
-					ch == '_' || ch.is_alphanumeric()
-				};
-
-				let is_next_char = if let Some(ch) = self.cursor.chars().next() {
-					ch == '_' || ch.is_alphanumeric()
-				} else {
-					return false;
-				};
-
-				let same = is_prev_word == is_next_char;
-				same == *expected
-			},
+			RegexNode::WordBoundary(expected) => is_word_boundary(self.orig, self.index) == *expected,
 			RegexNode::SubjectStart => self.index == 0,
-			RegexNode::SubjectEndOrNewline => {
-				self.cursor.is_empty() ||
-					NEWLINE_CHARS_STR.iter().any(|wrap| self.cursor == *wrap)
-			}
+			RegexNode::SubjectEndOrNewline => is_at_subject_end_or_newline(self.newline, self.orig, self.index),
 			RegexNode::SubjectEndOnly => self.cursor.is_empty(),
 			RegexNode::AbsBackRef(idx) => {
-				let idx = *idx as usize;
-				if idx >= self.captures.len() {
+				let Some(capture) = self.captures.get(*idx as usize).copied().flatten() else {
 					return self.flags.contains(RegexFlags::AllowEmtpyBackRefs);
-				}
-
-				let capture = self.captures[idx];
-				if capture.is_empty() {
-					return self.flags.contains(RegexFlags::AllowEmtpyBackRefs);
-				}
+				};
 
 				let capture_s = &self.orig[capture.to_range()];
-				if self.cursor.starts_with(capture_s) {
-					self.move_cursor(capture_s.len());
-					true
-				} else {
-					false
+				match literal_match_len(self.flags, capture_s, self.cursor) {
+					Some(len) => self.move_cursor(len),
+					None => false,
 				}
 			},
 			RegexNode::NamedBackRef(name) => {
 				let Some(indices) = self.capture_names.get(name) else { return false };
 				for idx in indices.iter().rev() {
-					let idx = *idx as usize;
-					if idx >= self.captures.len() {
-						continue;
-					}
-
-					let capture = self.captures[idx];
-					if capture.is_empty() {
-						continue;
-					}
+					let Some(capture) = self.captures.get(*idx as usize).copied().flatten() else { continue };
 
 					let capture_s = &self.orig[capture.to_range()];
-					if self.cursor.starts_with(capture_s) {
-						self.move_cursor(capture_s.len());
-						return true;
+					if let Some(len) = literal_match_len(self.flags, capture_s, self.cursor) {
+						return self.move_cursor(len);
 					}
 				}
 				self.flags.contains(RegexFlags::AllowEmtpyBackRefs)
@@ -509,7 +629,7 @@ impl<'a> Matcher<'a> {
 				res
 			},
 			RegexNode::AbsConditional(capture, yes, no) => {
-				if self.captures.get(*capture as usize).map_or(false, |range| !range.is_empty()) {
+				if self.captures.get(*capture as usize).map_or(false, Option::is_some) {
 					self.find_and_match(yes)
 				} else if let Some(no) = no {
 					self.find_and_match(no)
@@ -521,7 +641,7 @@ impl<'a> Matcher<'a> {
 				let Some(indices) = self.capture_names.get(capture) else { return false; };
 				let mut cond = false;
 				for idx in indices {
-					 if self.captures.get(*idx as usize).map_or(false, |range| !range.is_empty()) {
+					 if self.captures.get(*idx as usize).map_or(false, Option::is_some) {
 						cond = true;
 						break;
 					 }
@@ -559,7 +679,7 @@ impl<'a> Matcher<'a> {
 					false
 				}
 			},
-			RegexNode::FirstMatchPos => self.start_from_0 && self.index == 0,
+			RegexNode::FirstMatchPos => self.index == self.first_match_pos,
 			RegexNode::DefineConditional(_) => true,
 			RegexNode::MatchStartReset => unreachable!("Match start reset should have been optimized out"),
 			RegexNode::ParsedGroup(..) => unreachable!("Parsed groups should have been optimized out"),
@@ -577,39 +697,17 @@ impl<'a> Matcher<'a> {
 		}
 	}
 
-    fn move_equivalent(&mut self, s: &str) -> bool {
-        let num_chars = s.chars().count();
-        let Some((index, _)) = self.cursor.char_indices().nth(num_chars) else { return false; };
-        self.move_cursor(index)
-    }
-
 	fn reset(&mut self, idx: usize) {
 		self.index = idx;
 		self.cursor = &self.orig[self.index..];
 	}
 
 	fn reset_to_char_boundary(&mut self, idx: usize) -> bool {
-		if self.cursor.is_char_boundary(idx) {
+		if self.orig.is_char_boundary(idx) {
 			self.reset(idx);
 			true
 		} else {
 			false
 		}
 	}
-
-	fn is_at_start_boundary(&self) -> bool {
-		self.index == 0 ||
-			(self.flags.contains(RegexFlags::Multiline) && 
-			 NEWLINE_CHARS_STR.iter().any(|wrap| self.cursor.starts_with(wrap)) &&
-			 self.cursor.len() != 1 &&
-			 self.cursor != "\r\n"
-			)
-	}
-
-	fn is_at_end_boundary(&self) -> bool {
-		self.cursor.is_empty() ||
-		NEWLINE_CHARS_STR.iter().any(|wrap| self.cursor == *wrap) ||
-			(!self.flags.contains(RegexFlags::DollarEndOnly) && 
-				LINE_WRAPS.iter().any(|wrap| self.cursor.starts_with(wrap)))
-	}
 }
\ No newline at end of file
@@ -103,6 +103,75 @@ impl RegexProcessor {
 			_ => Ok(()),
 		}
     }
+
+	/// Extract the literals that need to be present in any string matched by the node, these can be used to quickly skip over parts of a string that can't contain a match.
+	/// 
+	/// Returns `None` if no literals can be extracted, i.e. when the regex is (partially) caseless.
+	pub fn extract_literals(&self, node: &RegexNode, flags: RegexFlags) -> Option<RequiredLiterals> {
+		if flags.contains(RegexFlags::Caseless) {
+			return None;
+		}
+		let literals = Self::extract_node_literals(node)?;
+		if literals.inner.is_empty() {
+			None
+		} else {
+			Some(literals)
+		}
+	}
+
+	fn extract_nodes_literals(nodes: &[RegexNode]) -> Option<RequiredLiterals> {
+		nodes.iter().try_fold(RequiredLiterals::exact(String::new()), |literals, node| Some(literals.then(Self::extract_node_literals(node)?)))
+	}
+
+	fn extract_node_literals(node: &RegexNode) -> Option<RequiredLiterals> {
+		Some(match node {
+			RegexNode::Unit(nodes) => Self::extract_nodes_literals(nodes)?,
+			RegexNode::Literal(lit) => RequiredLiterals::exact(lit.clone()),
+			RegexNode::LiteralChar(ch) => RequiredLiterals::exact(ch.to_string()),
+			RegexNode::Alternation(alterations) => {
+				let mut literals = None;
+				for nodes in alterations {
+					let alt_literals = Self::extract_nodes_literals(nodes)?;
+					literals = Some(match literals {
+						None => alt_literals,
+						Some(literals) => RequiredLiterals::either(literals, alt_literals),
+					});
+				}
+				literals.unwrap_or_default()
+			},
+			RegexNode::Repetition(inner, tail, mode, _) => {
+				let (min, max) = match mode {
+					RepetitionMode::Exactly(n)          => (*n, *n),
+					RepetitionMode::AtLeast(n)          => (*n, u16::MAX),
+					RepetitionMode::AtLeastAtMost(n, m) => (*n, *m),
+				};
+				let literals = Self::extract_node_literals(inner)?.repeat(min, max);
+				literals.then(Self::extract_nodes_literals(tail)?)
+			},
+			RegexNode::Group { sub_node, .. } => Self::extract_node_literals(sub_node)?,
+			// An option setting can make the remainder of the regex caseless
+			RegexNode::InternalOptionSetting(change) => if change.contains(RegexFlagChange::CaselessOn) {
+				return None;
+			} else {
+				RequiredLiterals::exact(String::new())
+			},
+			// `\G` depends on where the search starts, so skipping ahead would change its meaning
+			RegexNode::FirstMatchPos => return None,
+			// Zero-width assertions don't consume any characters, so literals around them are still contiguous
+			RegexNode::None |
+			RegexNode::StartOfString |
+			RegexNode::EndOfString |
+			RegexNode::MatchStartReset |
+			RegexNode::WordBoundary(_) |
+			RegexNode::SubjectStart |
+			RegexNode::SubjectEndOrNewline |
+			RegexNode::SubjectEndOnly |
+			RegexNode::Lookahead(..) |
+			RegexNode::Lookbehind(..) => RequiredLiterals::exact(String::new()),
+			RegexNode::ParsedGroup(..) => unreachable!("Parsed groups should have been optimized out"),
+			_ => RequiredLiterals::default(),
+		})
+	}
 }
 
 struct LiteralCombinePass;
@@ -120,7 +189,7 @@ impl OptProcessPass for LiteralCombinePass {
 		let mut buffer = String::with_capacity(1);
 
 		let process_lit_node = |nodes: &mut Vec<RegexNode>, buffer: &mut String, idx: usize, i: usize| if !buffer.is_empty() && idx + 1 < i {
-			if buffer.chars().nth(2).is_some() {
+			if buffer.chars().nth(1).is_some() {
 				nodes[idx] = RegexNode::Literal(buffer.clone());
 			} else {
 				nodes[idx] = RegexNode::LiteralChar(buffer.chars().next().unwrap());
@@ -237,7 +306,7 @@ impl OptProcessPass for ResolveRepetitionTail {
             self.process_single(processor, node)?;
         }
 
-        for i in (0..nodes.len() - 1).rev() {
+        for i in (0..nodes.len().saturating_sub(1)).rev() {
             if matches!(nodes[i], RegexNode::Repetition(..)) {
                 let tail = nodes.split_off(i + 1);
                 let RegexNode::Repetition(_, tail_ref, _, _) = &mut nodes[i] else { unreachable!() };
@@ -271,4 +340,80 @@ impl OptProcessPass for  MatchRestartSplitter {
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Maximum length of an extracted literal, to prevent large repetitions from creating huge literals.
+const MAX_LITERAL_LEN: usize = 256;
+
+/// Literals that need to be present in any string matched by a node.
+#[derive(Clone, Default, Debug)]
+pub(crate) struct RequiredLiterals {
+	/// Literal any match starts with.
+	pub prefix: String,
+	/// Literal any match ends with.
+	pub suffix: String,
+	/// Longest literal any match contains, this is at least as long as the prefix and suffix.
+	pub inner:  String,
+	/// Whether the node only matches the literal, in which case the prefix, suffix, and inner literal are the same.
+	pub exact:  bool,
+}
+
+impl RequiredLiterals {
+	fn exact(lit: String) -> Self {
+		if lit.len() > MAX_LITERAL_LEN {
+			let prefix = lit[..lit.floor_char_boundary(MAX_LITERAL_LEN)].to_string();
+			let suffix = lit[lit.ceil_char_boundary(lit.len() - MAX_LITERAL_LEN)..].to_string();
+			Self { inner: prefix.clone(), prefix, suffix, exact: false }
+		} else {
+			Self { prefix: lit.clone(), suffix: lit.clone(), inner: lit, exact: true }
+		}
+	}
+
+	/// Literals of a node followed by another node.
+	fn then(self, other: Self) -> Self {
+		if self.exact && other.exact {
+			return Self::exact(self.prefix + &other.prefix);
+		}
+
+		// The literals at the end of the first node and the start of the second node are contiguous
+		let middle = Self::exact(self.suffix + &other.prefix);
+		let prefix = if self.exact { middle.prefix.clone() } else { self.prefix };
+		let suffix = if other.exact { middle.suffix.clone() } else { other.suffix };
+		let inner = [self.inner, other.inner, middle.inner, prefix.clone(), suffix.clone()].into_iter()
+			.max_by_key(|lit| lit.len())
+			.unwrap_or_default();
+		Self { prefix, suffix, inner, exact: false }
+	}
+
+	/// Literals of a node that matches either of the nodes.
+	fn either(self, other: Self) -> Self {
+		if self.exact && other.exact && self.prefix == other.prefix {
+			return self;
+		}
+
+		let prefix_len = self.prefix.bytes().zip(other.prefix.bytes()).take_while(|(a, b)| a == b).count();
+		let prefix = self.prefix[..self.prefix.floor_char_boundary(prefix_len)].to_string();
+
+		let suffix_len = self.suffix.bytes().rev().zip(other.suffix.bytes().rev()).take_while(|(a, b)| a == b).count();
+		let suffix = self.suffix[self.suffix.ceil_char_boundary(self.suffix.len() - suffix_len)..].to_string();
+
+		let inner = if prefix.len() >= suffix.len() { prefix.clone() } else { suffix.clone() };
+		Self { prefix, suffix, inner, exact: false }
+	}
+
+	/// Literals of a node repeated at least `min` and at most `max` times.
+	fn repeat(self, min: u16, max: u16) -> Self {
+		if min == 0 {
+			return if max == 0 { Self::exact(String::new()) } else { Self::default() };
+		}
+
+		if self.exact {
+			let count = (min as usize).min(MAX_LITERAL_LEN / self.prefix.len().max(1) + 1);
+			let mut literals = Self::exact(self.prefix.repeat(count));
+			literals.exact &= min == max;
+			literals
+		} else {
+			self
+		}
+	}
+}
@@ -1,6 +1,6 @@
 use crate::*;
 
-const PARSE_SPECIAL_CHARS: [char; 12] = [
+const PARSE_SPECIAL_CHARS: [char; 13] = [
 	'\\',
 	'(',
 	')',
@@ -13,6 +13,7 @@ const PARSE_SPECIAL_CHARS: [char; 12] = [
     ']',
 	'^',
 	'$',
+	'.',
 ];
 
 #[derive(PartialEq, Eq)]
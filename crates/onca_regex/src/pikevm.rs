@@ -0,0 +1,357 @@
+//! Pike VM matching engine.
+//!
+//! The Pike VM simulates all possible paths through the regex in lockstep, so it runs in `O(n * m)` time,
+//! where `n` is the length of the subject and `m` is the size of the program.
+//! Threads are kept in priority order, so the result is identical to the first match a backtracker would find.
+//!
+//! Regexes using features that require backtracking, i.e. back references, lookarounds, conditionals and atomic groups, cannot be compiled into a program.
+
+use crate::{*, matcher::*};
+
+/// Maximum number of instructions in a program, regexes which need more instructions will use the backtracking matcher.
+const MAX_PROGRAM_LEN: usize = 8192;
+
+#[derive(Clone, Copy)]
+enum Assertion {
+	StartOfString,
+	EndOfString,
+	WordBoundary(bool),
+	SubjectStart,
+	SubjectEndOrNewline,
+	SubjectEndOnly,
+	FirstMatchPos,
+}
+
+enum Inst {
+	/// Match a single character, when caseless, this is a case folded character.
+	Char(char),
+	/// Match any character a `.` can match.
+	Dot,
+	/// Match a single character in a character class.
+	Class(CharacterClass, bool),
+	/// Match a single character in a class definition.
+	ClassDef(Vec<char>, Vec<(char, char)>, Vec<RegexNode>, bool),
+	/// Continue at both instructions, the first one has a higher priority.
+	Split(usize, usize),
+	/// Continue at the given instruction.
+	Jmp(usize),
+	/// Store the current position in a slot.
+	Save(usize),
+	/// Only continue when the assertion holds at the current position.
+	Assert(Assertion),
+	/// The regex matched.
+	Match,
+}
+
+pub(crate) struct PikeProgram {
+	insts:     Vec<Inst>,
+	flags:     RegexFlags,
+	newline:   NewlineMode,
+	num_slots: usize,
+}
+
+impl PikeProgram {
+	/// Try to compile a regex into a program, returns `None` if the regex requires the backtracking matcher.
+	pub fn compile(node: &RegexNode, flags: RegexFlags, newline: NewlineMode) -> Option<Self> {
+		let mut compiler = Compiler { insts: Vec::new(), flags, num_slots: 2 };
+		compiler.push(Inst::Save(0))?;
+		compiler.compile(node)?;
+		compiler.push(Inst::Save(1))?;
+		compiler.push(Inst::Match)?;
+
+		Some(Self { insts: compiler.insts, flags, newline, num_slots: compiler.num_slots })
+	}
+
+	/// Search for the first match starting at or after byte index `start`, or only at `start` when `anchored` is set.
+	///
+	/// Returns the slots of the match, slots `2 * n` and `2 * n + 1` contain the range of capture `n`, with capture 0 being the whole match.
+	pub fn search(&self, s: &str, start: usize, anchored: bool) -> Option<Vec<Option<usize>>> {
+		let mut clist = ThreadList::new(self.insts.len());
+		let mut nlist = ThreadList::new(self.insts.len());
+		let mut matched = None;
+
+		let mut pos = start;
+		loop {
+			// Starting a new thread at each position gives it the lowest priority, so earlier starts are always preferred
+			if matched.is_none() && (!anchored || pos == start) {
+				self.add_thread(&mut clist, 0, vec![None; self.num_slots], s, pos, start);
+			}
+			if clist.threads.is_empty() && (matched.is_some() || anchored) {
+				break;
+			}
+
+			let cursor = &s[pos..];
+			let ch = cursor.chars().next();
+			let folded = ch.map(|ch| CaseFold::new(self.flags, ch));
+			for Thread { pc, slots } in clist.threads.drain(..) {
+				let next_pc = match &self.insts[pc] {
+					Inst::Match => {
+						// Any remaining thread has a lower priority than this match, so they can be discarded
+						matched = Some(slots);
+						break;
+					},
+					Inst::Char(_)                                  => folded.and_then(|folded| self.match_folded(pc, folded.as_slice())),
+					Inst::Dot                                      => dot_matches(self.flags, self.newline, cursor).then_some(pc + 1),
+					Inst::Class(class, expected)                   => ch.is_some_and(|ch| class_matches(class, ch) == *expected).then_some(pc + 1),
+					Inst::ClassDef(chars, ranges, nodes, expected) => ch.is_some_and(|ch| class_def_matches(self.flags, chars, ranges, nodes, ch) == *expected).then_some(pc + 1),
+					_ => unreachable!("Only character matching instructions and matches can be stored in a thread list"),
+				};
+
+				if let Some(next_pc) = next_pc && let Some(ch) = ch {
+					self.add_thread(&mut nlist, next_pc, slots, s, pos + ch.len_utf8(), start);
+				}
+			}
+
+			let Some(ch) = ch else { break };
+			pos += ch.len_utf8();
+
+			clist.clear();
+			core::mem::swap(&mut clist, &mut nlist);
+		}
+		matched
+	}
+
+	/// Match the case folded representation of a character against consecutive `Char` instructions, returns the instruction following them.
+	/// 
+	/// When caseless, a single character can be folded into multiple characters, e.g. 'ß' into "ss", so it can match multiple instructions at once.
+	fn match_folded(&self, mut pc: usize, folded: &[char]) -> Option<usize> {
+		for ch in folded {
+			match self.insts[pc] {
+				Inst::Char(expected) if expected == *ch => pc += 1,
+				_ => return None,
+			}
+		}
+		Some(pc)
+	}
+
+	/// Add a thread and follow all instructions which don't consume a character.
+	fn add_thread(&self, list: &mut ThreadList, pc: usize, mut slots: Vec<Option<usize>>, s: &str, pos: usize, search_start: usize) {
+		let mut stack = core::mem::take(&mut list.stack);
+		stack.push(Frame::Explore(pc));
+		while let Some(frame) = stack.pop() {
+			let pc = match frame {
+				Frame::Explore(pc) => pc,
+				Frame::RestoreSlot(slot, val) => {
+					slots[slot] = val;
+					continue;
+				},
+			};
+
+			if !list.insert(pc) {
+				continue;
+			}
+
+			match &self.insts[pc] {
+				Inst::Jmp(target) => stack.push(Frame::Explore(*target)),
+				Inst::Split(first, second) => {
+					// The second branch is pushed first, so the first branch is fully explored before it, giving it the higher priority
+					stack.push(Frame::Explore(*second));
+					stack.push(Frame::Explore(*first));
+				},
+				Inst::Save(slot) => {
+					stack.push(Frame::RestoreSlot(*slot, slots[*slot]));
+					slots[*slot] = Some(pos);
+					stack.push(Frame::Explore(pc + 1));
+				},
+				Inst::Assert(assertion) => if self.check_assertion(*assertion, s, pos, search_start) {
+					stack.push(Frame::Explore(pc + 1));
+				},
+				_ => list.threads.push(Thread { pc, slots: slots.clone() }),
+			}
+		}
+		list.stack = stack;
+	}
+
+	fn check_assertion(&self, assertion: Assertion, s: &str, pos: usize, search_start: usize) -> bool {
+		match assertion {
+			Assertion::StartOfString        => is_at_start_boundary(self.flags, self.newline, s, pos),
+			Assertion::EndOfString          => is_at_end_boundary(self.flags, self.newline, s, pos),
+			Assertion::WordBoundary(expected) => is_word_boundary(s, pos) == expected,
+			Assertion::SubjectStart         => pos == 0,
+			Assertion::SubjectEndOrNewline  => is_at_subject_end_or_newline(self.newline, s, pos),
+			Assertion::SubjectEndOnly       => pos == s.len(),
+			Assertion::FirstMatchPos        => pos == search_start,
+		}
+	}
+}
+
+struct Compiler {
+	insts:     Vec<Inst>,
+	flags:     RegexFlags,
+	num_slots: usize,
+}
+
+impl Compiler {
+	fn push(&mut self, inst: Inst) -> Option<usize> {
+		if self.insts.len() >= MAX_PROGRAM_LEN {
+			return None;
+		}
+
+		self.insts.push(inst);
+		Some(self.insts.len() - 1)
+	}
+
+	fn compile_char(&mut self, ch: char) -> Option<()> {
+		for folded in CaseFold::new(self.flags, ch) {
+			self.push(Inst::Char(folded))?;
+		}
+		Some(())
+	}
+
+	fn compile_nodes(&mut self, nodes: &[RegexNode]) -> Option<()> {
+		for node in nodes {
+			self.compile(node)?;
+		}
+		Some(())
+	}
+
+	fn compile(&mut self, node: &RegexNode) -> Option<()> {
+		match node {
+			RegexNode::None => {},
+			RegexNode::Unit(nodes) => self.compile_nodes(nodes)?,
+			RegexNode::Literal(literal) => for ch in literal.chars() {
+				self.compile_char(ch)?;
+			},
+			RegexNode::LiteralChar(ch) => self.compile_char(*ch)?,
+			RegexNode::Dot => { self.push(Inst::Dot)?; },
+			RegexNode::CharacterClass(class, expected) => match class {
+				CharacterClass::AtomicNewLine |
+				CharacterClass::ExtendedGraphemeCluster => return None,
+				_ => { self.push(Inst::Class(class.clone(), *expected))?; },
+			},
+			RegexNode::ClassDef(chars, ranges, nodes, expected) => { self.push(Inst::ClassDef(chars.clone(), ranges.clone(), nodes.clone(), *expected))?; },
+			RegexNode::Alternation(options) => {
+				let mut jumps = Vec::new();
+				for (idx, nodes) in options.iter().enumerate() {
+					if idx == options.len() - 1 {
+						self.compile_nodes(nodes)?;
+					} else {
+						let split = self.push(Inst::Split(0, 0))?;
+						self.compile_nodes(nodes)?;
+						jumps.push(self.push(Inst::Jmp(0))?);
+						self.insts[split] = Inst::Split(split + 1, self.insts.len());
+					}
+				}
+
+				let end = self.insts.len();
+				for jump in jumps {
+					self.insts[jump] = Inst::Jmp(end);
+				}
+			},
+			RegexNode::Repetition(sub, tail, mode, strategy) => {
+				let greedy = match strategy {
+					RepetitionStrategy::Greedy     => true,
+					RepetitionStrategy::Lazy       => false,
+					RepetitionStrategy::Possessive => return None,
+				};
+				let (min, max) = match mode {
+					RepetitionMode::Exactly(n)          => (*n, *n),
+					RepetitionMode::AtLeast(n)          => (*n, u16::MAX),
+					RepetitionMode::AtLeastAtMost(n, m) => (*n, *m),
+				};
+
+				for _ in 0..min {
+					self.compile(sub)?;
+				}
+
+				if max == u16::MAX {
+					let split = self.push(Inst::Split(0, 0))?;
+					self.compile(sub)?;
+					self.push(Inst::Jmp(split))?;
+					self.insts[split] = Self::split(greedy, split + 1, self.insts.len());
+				} else {
+					let mut splits = Vec::new();
+					for _ in min..max {
+						splits.push(self.push(Inst::Split(0, 0))?);
+						self.compile(sub)?;
+					}
+
+					let end = self.insts.len();
+					for split in splits {
+						self.insts[split] = Self::split(greedy, split + 1, end);
+					}
+				}
+
+				self.compile_nodes(tail)?;
+			},
+			RegexNode::Group { capture_idx, sub_node, atomic } => {
+				if *atomic {
+					return None;
+				}
+
+				match capture_idx {
+					Some(idx) => {
+						let slot = *idx as usize * 2;
+						self.num_slots = self.num_slots.max(slot + 2);
+
+						self.push(Inst::Save(slot))?;
+						self.compile(sub_node)?;
+						self.push(Inst::Save(slot + 1))?;
+					},
+					None => self.compile(sub_node)?,
+				}
+			},
+			RegexNode::StartOfString       => { self.push(Inst::Assert(Assertion::StartOfString))?; },
+			RegexNode::EndOfString         => { self.push(Inst::Assert(Assertion::EndOfString))?; },
+			RegexNode::WordBoundary(expected) => { self.push(Inst::Assert(Assertion::WordBoundary(*expected)))?; },
+			RegexNode::SubjectStart        => { self.push(Inst::Assert(Assertion::SubjectStart))?; },
+			RegexNode::SubjectEndOrNewline => { self.push(Inst::Assert(Assertion::SubjectEndOrNewline))?; },
+			RegexNode::SubjectEndOnly      => { self.push(Inst::Assert(Assertion::SubjectEndOnly))?; },
+			RegexNode::FirstMatchPos       => { self.push(Inst::Assert(Assertion::FirstMatchPos))?; },
+			RegexNode::DefineConditional(_) => {},
+			// Back references, lookarounds, conditionals and changing options mid-regex require the backtracking matcher
+			_ => return None,
+		}
+		Some(())
+	}
+
+	fn split(greedy: bool, body: usize, exit: usize) -> Inst {
+		if greedy {
+			Inst::Split(body, exit)
+		} else {
+			Inst::Split(exit, body)
+		}
+	}
+}
+
+struct Thread {
+	pc:    usize,
+	slots: Vec<Option<usize>>,
+}
+
+enum Frame {
+	Explore(usize),
+	RestoreSlot(usize, Option<usize>),
+}
+
+/// List of threads, which keeps track of the visited instructions using a sparse set, so every instruction is only visited once per position.
+struct ThreadList {
+	dense:   Vec<usize>,
+	sparse:  Vec<usize>,
+	threads: Vec<Thread>,
+	// Reused stack to follow instructions when adding a thread
+	stack:   Vec<Frame>,
+}
+
+impl ThreadList {
+	fn new(len: usize) -> Self {
+		Self { dense: Vec::with_capacity(len), sparse: vec![0; len], threads: Vec::new(), stack: Vec::new() }
+	}
+
+	/// Mark an instruction as visited, returns `false` if it was already visited.
+	fn insert(&mut self, pc: usize) -> bool {
+		let idx = self.sparse[pc];
+		if idx < self.dense.len() && self.dense[idx] == pc {
+			return false;
+		}
+
+		self.sparse[pc] = self.dense.len();
+		self.dense.push(pc);
+		true
+	}
+
+	fn clear(&mut self) {
+		self.dense.clear();
+		self.threads.clear();
+	}
+}
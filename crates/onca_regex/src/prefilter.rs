@@ -0,0 +1,83 @@
+use crate::opt_process::RequiredLiterals;
+
+const WORD_SIZE: usize = core::mem::size_of::<usize>();
+const LO_BITS: usize = usize::from_ne_bytes([0x01; WORD_SIZE]);
+const HI_BITS: usize = usize::from_ne_bytes([0x80; WORD_SIZE]);
+
+/// Check if any byte in the word is 0.
+fn contains_zero_byte(word: usize) -> bool {
+	word.wrapping_sub(LO_BITS) & !word & HI_BITS != 0
+}
+
+/// Find the first occurrence of a byte in the haystack, checking a full word at a time.
+pub(crate) fn memchr(needle: u8, haystack: &[u8]) -> Option<usize> {
+	let splat = usize::from_ne_bytes([needle; WORD_SIZE]);
+
+	// SAFETY: Any bit pattern is a valid usize
+	let (head, words, _) = unsafe { haystack.align_to::<usize>() };
+	if let Some(idx) = head.iter().position(|b| *b == needle) {
+		return Some(idx);
+	}
+
+	let mut offset = head.len();
+	for word in words {
+		if contains_zero_byte(*word ^ splat) {
+			break;
+		}
+		offset += WORD_SIZE;
+	}
+	haystack[offset..].iter().position(|b| *b == needle).map(|idx| offset + idx)
+}
+
+/// Find the first occurrence of a literal in the haystack.
+///
+/// Candidates are found by scanning for the last byte of the literal, which is less likely to be a common leading character, like a space or a delimiter.
+pub(crate) fn find_literal(literal: &[u8], haystack: &[u8]) -> Option<usize> {
+	let Some((&last, _)) = literal.split_last() else { return Some(0) };
+	let last_offset = literal.len() - 1;
+
+	let mut pos = last_offset;
+	while pos < haystack.len() {
+		let idx = pos + memchr(last, &haystack[pos..])?;
+		let begin = idx - last_offset;
+		if &haystack[begin..=idx] == literal {
+			return Some(begin);
+		}
+		pos = idx + 1;
+	}
+	None
+}
+
+/// Prefilter to quickly skip over parts of a string that can't contain a match.
+pub(crate) struct Prefilter {
+	prefix:   String,
+	suffix:   String,
+	required: String,
+}
+
+impl Prefilter {
+	pub fn new(literals: RequiredLiterals) -> Self {
+		Self { prefix: literals.prefix, suffix: literals.suffix, required: literals.inner }
+	}
+
+	/// Check if a match could be found starting at or after `start`.
+	pub fn could_contain(&self, s: &str, start: usize) -> bool {
+		s.as_bytes().get(start..).is_some_and(|rest| find_literal(self.required.as_bytes(), rest).is_some())
+	}
+
+	/// Check if a match could start at `start` and span the remainder of the string.
+	pub fn could_match_exactly(&self, s: &str, start: usize) -> bool {
+		let rest = &s[start..];
+		rest.starts_with(&self.prefix) && rest.ends_with(&self.suffix)
+	}
+
+	/// Check if every match starts with a known literal, if so, only the positions returned by [`Prefilter::next_candidate`] need to be checked.
+	pub fn has_prefix(&self) -> bool {
+		!self.prefix.is_empty()
+	}
+
+	/// Find the next position at or after `start` where a match could start.
+	pub fn next_candidate(&self, s: &str, start: usize) -> Option<usize> {
+		find_literal(self.prefix.as_bytes(), s.as_bytes().get(start..)?).map(|idx| start + idx)
+	}
+}
@@ -0,0 +1,99 @@
+use crate::*;
+
+/// Trait for types that can be used to replace a match found by a [`Regex`].
+///
+/// # Replacement templates
+///
+/// When a `&str` or `String` is used as a replacer, it is interpreted as a template, which supports the following syntax:
+/// - `$n` or `${n}`: Insert the capture with index `n`, `$0` inserts the whole match.
+/// - `$name` or `${name}`: Insert the capture with the given name.
+/// - `$$`: Insert a literal `$`.
+///
+/// When using the unbraced form, the longest sequence of alphanumeric characters and `_` is used as the name or index,
+/// meaning that `$1a` will be interpreted as a capture named `1a`, use `${1}a` instead.
+///
+/// Captures that did not participate in the match, or that do not exist, are replaced by an empty string.
+///
+/// A closure taking a [`MatchResult`] and returning a `String` can be used to calculate the replacement manually.
+pub trait Replacer {
+	/// Append the replacement for a match to `dst`.
+	fn replace_append(&mut self, match_res: &MatchResult, dst: &mut String);
+}
+
+impl Replacer for &str {
+	fn replace_append(&mut self, match_res: &MatchResult, dst: &mut String) {
+		expand_template(self, match_res, dst);
+	}
+}
+
+impl Replacer for &String {
+	fn replace_append(&mut self, match_res: &MatchResult, dst: &mut String) {
+		expand_template(self, match_res, dst);
+	}
+}
+
+impl Replacer for String {
+	fn replace_append(&mut self, match_res: &MatchResult, dst: &mut String) {
+		expand_template(self, match_res, dst);
+	}
+}
+
+impl<F> Replacer for F where
+	F: FnMut(&MatchResult) -> String
+{
+	fn replace_append(&mut self, match_res: &MatchResult, dst: &mut String) {
+		dst.push_str(&self(match_res));
+	}
+}
+
+/// Replacer that inserts a string without interpreting it as a template.
+pub struct NoExpand<'a>(pub &'a str);
+
+impl Replacer for NoExpand<'_> {
+	fn replace_append(&mut self, _match_res: &MatchResult, dst: &mut String) {
+		dst.push_str(self.0);
+	}
+}
+
+/// Expand a replacement template and append it to `dst`.
+fn expand_template(template: &str, match_res: &MatchResult, dst: &mut String) {
+	let mut cursor = template;
+	while let Some(dollar) = cursor.find('$') {
+		dst.push_str(&cursor[..dollar]);
+		cursor = &cursor[dollar + 1..];
+
+		if let Some(rest) = cursor.strip_prefix('$') {
+			dst.push('$');
+			cursor = rest;
+			continue;
+		}
+
+		let (name, rest) = if let Some(braced) = cursor.strip_prefix('{') {
+			match braced.find('}') {
+				Some(end) => (&braced[..end], &braced[end + 1..]),
+				// Unclosed brace, so treat the `$` as a literal
+				None => ("", cursor),
+			}
+		} else {
+			let end = cursor.find(|ch: char| !(ch.is_alphanumeric() || ch == '_')).unwrap_or(cursor.len());
+			(&cursor[..end], &cursor[end..])
+		};
+
+		if name.is_empty() {
+			dst.push('$');
+			cursor = rest;
+			continue;
+		}
+
+		let capture = match name.parse::<u16>() {
+			Ok(0) => Some(match_res.whole_match()),
+			Ok(idx) => match_res.get_capture(idx),
+			Err(_) => match_res.get_capture_by_name(name),
+		};
+		if let Some(capture) = capture {
+			dst.push_str(capture);
+		}
+		cursor = rest;
+	}
+	dst.push_str(cursor);
+}
@@ -0,0 +1,559 @@
+use crate::*;
+
+/// Magic number at the start of a serialized regex.
+const SERIALIZE_MAGIC: [u8; 4] = *b"ORGX";
+/// Version of the serialized regex format, needs to be incremented each time the format or any node changes.
+const SERIALIZE_VERSION: u16 = 2;
+
+/// Create flags from their bits, using the flags' `bits` function to infer the underlying type.
+fn flags_from_bits<B: TryFrom<u64>, F: From<B>>(_bits_fn: fn(&F) -> B, bits: u64) -> Option<F> {
+	B::try_from(bits).ok().map(F::from)
+}
+
+pub(crate) struct RegexSerializer {
+	buffer: Vec<u8>,
+}
+
+impl RegexSerializer {
+	pub fn new() -> Self {
+		Self { buffer: Vec::new() }
+	}
+
+	pub fn serialize(mut self, regex: &Regex) -> Vec<u8> {
+		self.buffer.extend_from_slice(&SERIALIZE_MAGIC);
+		self.write_u16(SERIALIZE_VERSION);
+		self.write_u64(regex.flags.bits() as u64);
+		self.write_u8(match regex.newline {
+			NewlineMode::AnyUnicode => 0,
+			NewlineMode::LfOnly     => 1,
+			NewlineMode::CrLf       => 2,
+		});
+
+		// Sort the names, so the output is deterministic
+		let mut names = regex.capture_names.iter().collect::<Vec<_>>();
+		names.sort_by(|a, b| a.0.cmp(b.0));
+
+		self.write_len(names.len());
+		for (name, indices) in names {
+			self.write_str(name);
+			self.write_len(indices.len());
+			for idx in indices {
+				self.write_u16(*idx);
+			}
+		}
+
+		self.write_node(&regex.node);
+		self.buffer
+	}
+
+	fn write_u8(&mut self, val: u8) {
+		self.buffer.push(val);
+	}
+
+	fn write_bool(&mut self, val: bool) {
+		self.buffer.push(val as u8);
+	}
+
+	fn write_u16(&mut self, val: u16) {
+		self.buffer.extend_from_slice(&val.to_le_bytes());
+	}
+
+	fn write_u32(&mut self, val: u32) {
+		self.buffer.extend_from_slice(&val.to_le_bytes());
+	}
+
+	fn write_u64(&mut self, val: u64) {
+		self.buffer.extend_from_slice(&val.to_le_bytes());
+	}
+
+	fn write_len(&mut self, len: usize) {
+		self.write_u32(len as u32);
+	}
+
+	fn write_char(&mut self, ch: char) {
+		self.write_u32(ch as u32);
+	}
+
+	fn write_str(&mut self, s: &str) {
+		self.write_len(s.len());
+		self.buffer.extend_from_slice(s.as_bytes());
+	}
+
+	fn write_nodes(&mut self, nodes: &[RegexNode]) {
+		self.write_len(nodes.len());
+		for node in nodes {
+			self.write_node(node);
+		}
+	}
+
+	fn write_opt_node(&mut self, node: &Option<Box<RegexNode>>) {
+		match node {
+			Some(node) => {
+				self.write_bool(true);
+				self.write_node(node);
+			},
+			None => self.write_bool(false),
+		}
+	}
+
+	fn write_class(&mut self, class: &CharacterClass) {
+		match class {
+			CharacterClass::HorizontalWhitespace    => self.write_u8(0),
+			CharacterClass::VerticalWhitespace      => self.write_u8(1),
+			CharacterClass::Whitespace              => self.write_u8(2),
+			CharacterClass::Word                    => self.write_u8(3),
+			CharacterClass::NonNewLine              => self.write_u8(4),
+			CharacterClass::Category(cat)           => {
+				self.write_u8(5);
+				self.write_u64(cat.bits() as u64);
+			},
+			CharacterClass::Script(script)          => {
+				self.write_u8(6);
+				self.write_str(&format!("{script:?}"));
+			},
+			CharacterClass::PosixSpace              => self.write_u8(7),
+			CharacterClass::UNC                     => self.write_u8(8),
+			CharacterClass::PosixAscii              => self.write_u8(9),
+			CharacterClass::PosixGraph              => self.write_u8(10),
+			CharacterClass::PosixPrint              => self.write_u8(11),
+			CharacterClass::PosixXDigit             => self.write_u8(12),
+			CharacterClass::AtomicNewLine           => self.write_u8(13),
+			CharacterClass::ExtendedGraphemeCluster => self.write_u8(14),
+			CharacterClass::Any                     => self.write_u8(15),
+		}
+	}
+
+	fn write_node(&mut self, node: &RegexNode) {
+		match node {
+			RegexNode::None => self.write_u8(0),
+			RegexNode::Unit(nodes) => {
+				self.write_u8(1);
+				self.write_nodes(nodes);
+			},
+			RegexNode::Literal(lit) => {
+				self.write_u8(2);
+				self.write_str(lit);
+			},
+			RegexNode::LiteralChar(ch) => {
+				self.write_u8(3);
+				self.write_char(*ch);
+			},
+			RegexNode::Dot => self.write_u8(4),
+			RegexNode::CharacterClass(class, expected) => {
+				self.write_u8(5);
+				self.write_class(class);
+				self.write_bool(*expected);
+			},
+			RegexNode::CharacterClassChar(ch) => {
+				self.write_u8(6);
+				self.write_char(*ch);
+			},
+			RegexNode::Alternation(alternations) => {
+				self.write_u8(7);
+				self.write_len(alternations.len());
+				for nodes in alternations {
+					self.write_nodes(nodes);
+				}
+			},
+			RegexNode::Repetition(sub, tail, mode, strategy) => {
+				self.write_u8(8);
+				self.write_node(sub);
+				self.write_nodes(tail);
+				match mode {
+					RepetitionMode::Exactly(n) => {
+						self.write_u8(0);
+						self.write_u16(*n);
+					},
+					RepetitionMode::AtLeast(n) => {
+						self.write_u8(1);
+						self.write_u16(*n);
+					},
+					RepetitionMode::AtLeastAtMost(n, m) => {
+						self.write_u8(2);
+						self.write_u16(*n);
+						self.write_u16(*m);
+					},
+				}
+				self.write_u8(match strategy {
+					RepetitionStrategy::Greedy     => 0,
+					RepetitionStrategy::Possessive => 1,
+					RepetitionStrategy::Lazy       => 2,
+				});
+			},
+			RegexNode::StartOfString => self.write_u8(9),
+			RegexNode::EndOfString => self.write_u8(10),
+			RegexNode::InternalOptionSetting(change) => {
+				self.write_u8(11);
+				self.write_u64(change.bits() as u64);
+			},
+			RegexNode::MatchStartReset => self.write_u8(12),
+			RegexNode::WordBoundary(expected) => {
+				self.write_u8(13);
+				self.write_bool(*expected);
+			},
+			RegexNode::SubjectStart => self.write_u8(14),
+			RegexNode::SubjectEndOrNewline => self.write_u8(15),
+			RegexNode::SubjectEndOnly => self.write_u8(16),
+			RegexNode::FirstMatchPos => self.write_u8(17),
+			RegexNode::AbsBackRef(idx) => {
+				self.write_u8(18);
+				self.write_u16(*idx);
+			},
+			RegexNode::NamedBackRef(name) => {
+				self.write_u8(19);
+				self.write_str(name);
+			},
+			RegexNode::Lookahead(inner, expected) => {
+				self.write_u8(20);
+				self.write_node(inner);
+				self.write_bool(*expected);
+			},
+			RegexNode::Lookbehind(nodes, lengths, expected) => {
+				self.write_u8(21);
+				self.write_nodes(nodes);
+				self.write_len(lengths.len());
+				for len in lengths {
+					self.write_u16(*len);
+				}
+				self.write_bool(*expected);
+			},
+			RegexNode::AbsConditional(idx, yes, no) => {
+				self.write_u8(22);
+				self.write_u16(*idx);
+				self.write_node(yes);
+				self.write_opt_node(no);
+			},
+			RegexNode::NamedConditional(name, yes, no) => {
+				self.write_u8(23);
+				self.write_str(name);
+				self.write_node(yes);
+				self.write_opt_node(no);
+			},
+			RegexNode::RecursiveConditional(idx, yes, no) => {
+				self.write_u8(24);
+				self.write_u16(*idx);
+				self.write_node(yes);
+				self.write_opt_node(no);
+			},
+			RegexNode::NamedRecursiveConditional(name, yes, no) => {
+				self.write_u8(25);
+				self.write_str(name);
+				self.write_node(yes);
+				self.write_opt_node(no);
+			},
+			RegexNode::DefineConditional(inner) => {
+				self.write_u8(26);
+				self.write_node(inner);
+			},
+			RegexNode::AssertConditional(cond, yes, no) => {
+				self.write_u8(27);
+				self.write_node(cond);
+				self.write_node(yes);
+				self.write_opt_node(no);
+			},
+			RegexNode::ParsedGroup(..) => unreachable!("Parsed groups should have been processed before serializing"),
+			RegexNode::Group { capture_idx, sub_node, atomic } => {
+				self.write_u8(28);
+				match capture_idx {
+					Some(idx) => {
+						self.write_bool(true);
+						self.write_u16(*idx);
+					},
+					None => self.write_bool(false),
+				}
+				self.write_node(sub_node);
+				self.write_bool(*atomic);
+			},
+			RegexNode::ClassDef(chars, ranges, nodes, expected) => {
+				self.write_u8(29);
+				self.write_len(chars.len());
+				for ch in chars {
+					self.write_char(*ch);
+				}
+				self.write_len(ranges.len());
+				for (begin, end) in ranges {
+					self.write_char(*begin);
+					self.write_char(*end);
+				}
+				self.write_nodes(nodes);
+				self.write_bool(*expected);
+			},
+		}
+	}
+}
+
+pub(crate) struct RegexDeserializer<'a> {
+	bytes: &'a [u8],
+	idx:   usize,
+}
+
+impl<'a> RegexDeserializer<'a> {
+	pub fn new(bytes: &'a [u8]) -> Self {
+		Self { bytes, idx: 0 }
+	}
+
+	pub fn deserialize(mut self) -> Result<Regex, RegexError> {
+		if self.read_bytes(4)? != SERIALIZE_MAGIC {
+			return Err(RegexError::new_str("Invalid serialized regex: magic number does not match", 0, 4));
+		}
+		let version = self.read_u16()?;
+		if version != SERIALIZE_VERSION {
+			return Err(RegexError::new(format!("Unsupported serialized regex version: {version}, expected {SERIALIZE_VERSION}"), 4, 6));
+		}
+
+		let flags_bits = self.read_u64()?;
+		let flags = flags_from_bits(RegexFlags::bits, flags_bits).ok_or_else(|| self.error("Invalid regex flags"))?;
+		let newline = match self.read_u8()? {
+			0 => NewlineMode::AnyUnicode,
+			1 => NewlineMode::LfOnly,
+			2 => NewlineMode::CrLf,
+			_ => return Err(self.error("Invalid newline mode")),
+		};
+
+		let name_count = self.read_len()?;
+		let mut capture_names = HashMap::with_capacity(name_count);
+		for _ in 0..name_count {
+			let name = self.read_string()?;
+			let index_count = self.read_len()?;
+			let mut indices = Vec::with_capacity(index_count);
+			for _ in 0..index_count {
+				indices.push(self.read_u16()?);
+			}
+			capture_names.insert(name, indices);
+		}
+
+		let node = self.read_node()?;
+		if self.idx != self.bytes.len() {
+			return Err(self.error("Unexpected trailing data"));
+		}
+
+		Ok(Regex::from_parts(node, capture_names, flags, newline))
+	}
+
+	fn error(&self, msg: &str) -> RegexError {
+		RegexError::new(format!("Invalid serialized regex: {msg}"), self.idx, self.idx)
+	}
+
+	fn read_bytes(&mut self, count: usize) -> Result<&'a [u8], RegexError> {
+		let end = self.idx + count;
+		if end > self.bytes.len() {
+			return Err(self.error("Unexpected end of data"));
+		}
+		let bytes = &self.bytes[self.idx..end];
+		self.idx = end;
+		Ok(bytes)
+	}
+
+	fn read_u8(&mut self) -> Result<u8, RegexError> {
+		Ok(self.read_bytes(1)?[0])
+	}
+
+	fn read_bool(&mut self) -> Result<bool, RegexError> {
+		match self.read_u8()? {
+			0 => Ok(false),
+			1 => Ok(true),
+			_ => Err(self.error("Invalid boolean")),
+		}
+	}
+
+	fn read_u16(&mut self) -> Result<u16, RegexError> {
+		Ok(u16::from_le_bytes(self.read_bytes(2)?.try_into().unwrap()))
+	}
+
+	fn read_u32(&mut self) -> Result<u32, RegexError> {
+		Ok(u32::from_le_bytes(self.read_bytes(4)?.try_into().unwrap()))
+	}
+
+	fn read_u64(&mut self) -> Result<u64, RegexError> {
+		Ok(u64::from_le_bytes(self.read_bytes(8)?.try_into().unwrap()))
+	}
+
+	fn read_len(&mut self) -> Result<usize, RegexError> {
+		let len = self.read_u32()? as usize;
+		// Every element takes up at least 1 byte, so this protects against huge allocations for corrupted data
+		if len > self.bytes.len() - self.idx {
+			return Err(self.error("Length is out of range"));
+		}
+		Ok(len)
+	}
+
+	fn read_char(&mut self) -> Result<char, RegexError> {
+		let val = self.read_u32()?;
+		char::from_u32(val).ok_or_else(|| self.error("Invalid character"))
+	}
+
+	fn read_string(&mut self) -> Result<String, RegexError> {
+		let len = self.read_len()?;
+		let bytes = self.read_bytes(len)?;
+		match core::str::from_utf8(bytes) {
+			Ok(s) => Ok(s.to_string()),
+			Err(_) => Err(self.error("Invalid UTF-8 string")),
+		}
+	}
+
+	fn read_nodes(&mut self) -> Result<Vec<RegexNode>, RegexError> {
+		let len = self.read_len()?;
+		let mut nodes = Vec::with_capacity(len);
+		for _ in 0..len {
+			nodes.push(self.read_node()?);
+		}
+		Ok(nodes)
+	}
+
+	fn read_box_node(&mut self) -> Result<Box<RegexNode>, RegexError> {
+		Ok(Box::new(self.read_node()?))
+	}
+
+	fn read_opt_node(&mut self) -> Result<Option<Box<RegexNode>>, RegexError> {
+		if self.read_bool()? {
+			Ok(Some(self.read_box_node()?))
+		} else {
+			Ok(None)
+		}
+	}
+
+	fn read_class(&mut self) -> Result<CharacterClass, RegexError> {
+		Ok(match self.read_u8()? {
+			0  => CharacterClass::HorizontalWhitespace,
+			1  => CharacterClass::VerticalWhitespace,
+			2  => CharacterClass::Whitespace,
+			3  => CharacterClass::Word,
+			4  => CharacterClass::NonNewLine,
+			5  => {
+				let bits = self.read_u64()?;
+				CharacterClass::Category(flags_from_bits(unicode::Category::bits, bits).ok_or_else(|| self.error("Invalid unicode category"))?)
+			},
+			6  => {
+				let name = self.read_string()?;
+				CharacterClass::Script(unicode::Script::parse(&name).ok_or_else(|| self.error("Invalid unicode script"))?)
+			},
+			7  => CharacterClass::PosixSpace,
+			8  => CharacterClass::UNC,
+			9  => CharacterClass::PosixAscii,
+			10 => CharacterClass::PosixGraph,
+			11 => CharacterClass::PosixPrint,
+			12 => CharacterClass::PosixXDigit,
+			13 => CharacterClass::AtomicNewLine,
+			14 => CharacterClass::ExtendedGraphemeCluster,
+			15 => CharacterClass::Any,
+			_  => return Err(self.error("Invalid character class")),
+		})
+	}
+
+	fn read_node(&mut self) -> Result<RegexNode, RegexError> {
+		Ok(match self.read_u8()? {
+			0  => RegexNode::None,
+			1  => RegexNode::Unit(self.read_nodes()?),
+			2  => RegexNode::Literal(self.read_string()?),
+			3  => RegexNode::LiteralChar(self.read_char()?),
+			4  => RegexNode::Dot,
+			5  => {
+				let class = self.read_class()?;
+				RegexNode::CharacterClass(class, self.read_bool()?)
+			},
+			6  => RegexNode::CharacterClassChar(self.read_char()?),
+			7  => {
+				let len = self.read_len()?;
+				let mut alternations = Vec::with_capacity(len);
+				for _ in 0..len {
+					alternations.push(self.read_nodes()?);
+				}
+				RegexNode::Alternation(alternations)
+			},
+			8  => {
+				let sub = self.read_box_node()?;
+				let tail = self.read_nodes()?;
+				let mode = match self.read_u8()? {
+					0 => RepetitionMode::Exactly(self.read_u16()?),
+					1 => RepetitionMode::AtLeast(self.read_u16()?),
+					2 => {
+						let n = self.read_u16()?;
+						RepetitionMode::AtLeastAtMost(n, self.read_u16()?)
+					},
+					_ => return Err(self.error("Invalid repetition mode")),
+				};
+				let strategy = match self.read_u8()? {
+					0 => RepetitionStrategy::Greedy,
+					1 => RepetitionStrategy::Possessive,
+					2 => RepetitionStrategy::Lazy,
+					_ => return Err(self.error("Invalid repetition strategy")),
+				};
+				RegexNode::Repetition(sub, tail, mode, strategy)
+			},
+			9  => RegexNode::StartOfString,
+			10 => RegexNode::EndOfString,
+			11 => {
+				let bits = self.read_u64()?;
+				RegexNode::InternalOptionSetting(flags_from_bits(RegexFlagChange::bits, bits).ok_or_else(|| self.error("Invalid option setting"))?)
+			},
+			12 => RegexNode::MatchStartReset,
+			13 => RegexNode::WordBoundary(self.read_bool()?),
+			14 => RegexNode::SubjectStart,
+			15 => RegexNode::SubjectEndOrNewline,
+			16 => RegexNode::SubjectEndOnly,
+			17 => RegexNode::FirstMatchPos,
+			18 => RegexNode::AbsBackRef(self.read_u16()?),
+			19 => RegexNode::NamedBackRef(self.read_string()?),
+			20 => {
+				let inner = self.read_box_node()?;
+				RegexNode::Lookahead(inner, self.read_bool()?)
+			},
+			21 => {
+				let nodes = self.read_nodes()?;
+				let len = self.read_len()?;
+				let mut lengths = Vec::with_capacity(len);
+				for _ in 0..len {
+					lengths.push(self.read_u16()?);
+				}
+				RegexNode::Lookbehind(nodes, lengths, self.read_bool()?)
+			},
+			22 => {
+				let idx = self.read_u16()?;
+				let yes = self.read_box_node()?;
+				RegexNode::AbsConditional(idx, yes, self.read_opt_node()?)
+			},
+			23 => {
+				let name = self.read_string()?;
+				let yes = self.read_box_node()?;
+				RegexNode::NamedConditional(name, yes, self.read_opt_node()?)
+			},
+			24 => {
+				let idx = self.read_u16()?;
+				let yes = self.read_box_node()?;
+				RegexNode::RecursiveConditional(idx, yes, self.read_opt_node()?)
+			},
+			25 => {
+				let name = self.read_string()?;
+				let yes = self.read_box_node()?;
+				RegexNode::NamedRecursiveConditional(name, yes, self.read_opt_node()?)
+			},
+			26 => RegexNode::DefineConditional(self.read_box_node()?),
+			27 => {
+				let cond = self.read_box_node()?;
+				let yes = self.read_box_node()?;
+				RegexNode::AssertConditional(cond, yes, self.read_opt_node()?)
+			},
+			28 => {
+				let capture_idx = if self.read_bool()? { Some(self.read_u16()?) } else { None };
+				let sub_node = self.read_box_node()?;
+				RegexNode::Group { capture_idx, sub_node, atomic: self.read_bool()? }
+			},
+			29 => {
+				let len = self.read_len()?;
+				let mut chars = Vec::with_capacity(len);
+				for _ in 0..len {
+					chars.push(self.read_char()?);
+				}
+				let len = self.read_len()?;
+				let mut ranges = Vec::with_capacity(len);
+				for _ in 0..len {
+					let begin = self.read_char()?;
+					ranges.push((begin, self.read_char()?));
+				}
+				let nodes = self.read_nodes()?;
+				RegexNode::ClassDef(chars, ranges, nodes, self.read_bool()?)
+			},
+			_  => return Err(self.error("Invalid node")),
+		})
+	}
+}
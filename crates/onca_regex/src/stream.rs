@@ -0,0 +1,185 @@
+use std::io;
+
+use crate::*;
+
+/// Number of additional bytes that need to be available after the longest possible match before a match is reported,
+/// so assertions after the match, like `\b` or `$`, can see the next character.
+const ASSERTION_MARGIN: usize = 4;
+
+/// Size of the chunks read by [`StreamMatcher::scan`].
+const SCAN_CHUNK_SIZE: usize = 4096;
+
+/// A match found by a [`StreamMatcher`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct StreamMatch {
+	begin: usize,
+	end:   usize,
+	text:  String,
+}
+
+impl StreamMatch {
+	/// Get the absolute byte offset of the start of the match in the stream.
+	pub fn start(&self) -> usize {
+		self.begin
+	}
+
+	/// Get the absolute byte offset of the end of the match in the stream.
+	pub fn end(&self) -> usize {
+		self.end
+	}
+
+	/// Get the absolute byte range of the match in the stream.
+	pub fn range(&self) -> Range<usize> {
+		self.begin..self.end
+	}
+
+	/// Get the matched string.
+	pub fn as_str(&self) -> &str {
+		&self.text
+	}
+}
+
+/// Matcher that finds all successive non-overlapping matches of a regex in UTF-8 data that is provided chunk by chunk.
+///
+/// Only a bounded window of the data is kept in memory. A match is only reported once enough data is available to know it can't change anymore,
+/// i.e. when the longest possible match of the regex fits in the buffered data, or when the window is full.
+/// This means that:
+/// - Matches longer than the window may be cut short.
+/// - Lookarounds can't see further than the window before or after the current position.
+///
+/// Empty matches are handled the same as in [`Regex::find_iter`].
+pub struct StreamMatcher<'r> {
+	regex:          &'r Regex,
+	window:         usize,
+	buffer:         String,
+	incomplete:     Vec<u8>,
+	base:           usize,
+	search_pos:     usize,
+	last_match_end: Option<usize>,
+}
+
+impl<'r> StreamMatcher<'r> {
+	/// Create a new stream matcher, which keeps at most around `window` bytes before and after the current search position in memory.
+	pub fn new(regex: &'r Regex, window: usize) -> Self {
+		Self {
+			regex,
+			window: window.max(1),
+			buffer: String::new(),
+			incomplete: Vec::new(),
+			base: 0,
+			search_pos: 0,
+			last_match_end: None,
+		}
+	}
+
+	/// Get the total number of bytes that have been fed to the matcher.
+	pub fn stream_len(&self) -> usize {
+		self.base + self.buffer.len() + self.incomplete.len()
+	}
+
+	/// Feed the next chunk of data to the matcher, calling `on_match` for every match that has been found.
+	///
+	/// The chunk does not need to end on a character boundary. Returns an error with [`io::ErrorKind::InvalidData`] if the data is not valid UTF-8.
+	pub fn feed(&mut self, data: &[u8], on_match: impl FnMut(StreamMatch)) -> io::Result<()> {
+		self.incomplete.extend_from_slice(data);
+		let valid_len = match core::str::from_utf8(&self.incomplete) {
+			Ok(s) => s.len(),
+			// Incomplete character at the end of the chunk, which will be completed by the next chunk
+			Err(err) if err.error_len().is_none() => err.valid_up_to(),
+			Err(err) => return Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+		};
+
+		// SAFETY: The bytes were validated above
+		self.buffer.push_str(unsafe { core::str::from_utf8_unchecked(&self.incomplete[..valid_len]) });
+		self.incomplete.drain(..valid_len);
+
+		self.process(false, on_match);
+		Ok(())
+	}
+
+	/// Signal the end of the stream, calling `on_match` for all remaining matches.
+	///
+	/// Afterwards, the matcher is reset and can be used for a new stream.
+	/// Returns an error with [`io::ErrorKind::InvalidData`] if the stream ended with an incomplete character.
+	pub fn finish(&mut self, on_match: impl FnMut(StreamMatch)) -> io::Result<()> {
+		let res = if self.incomplete.is_empty() {
+			self.process(true, on_match);
+			Ok(())
+		} else {
+			Err(io::Error::new(io::ErrorKind::InvalidData, "Stream ended with an incomplete UTF-8 character"))
+		};
+
+		self.buffer.clear();
+		self.incomplete.clear();
+		self.base = 0;
+		self.search_pos = 0;
+		self.last_match_end = None;
+		res
+	}
+
+	/// Read all data from a reader, calling `on_match` for every match that has been found, and finish the stream.
+	pub fn scan<R: io::Read>(&mut self, mut reader: R, mut on_match: impl FnMut(StreamMatch)) -> io::Result<()> {
+		let mut chunk = [0u8; SCAN_CHUNK_SIZE];
+		loop {
+			let read = match reader.read(&mut chunk) {
+				Ok(0) => break,
+				Ok(read) => read,
+				Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+				Err(err) => return Err(err),
+			};
+			self.feed(&chunk[..read], &mut on_match)?;
+		}
+		self.finish(on_match)
+	}
+
+	/// Get the number of bytes after the start of a match that need to be available before the match can be reported.
+	fn horizon(&self) -> usize {
+		match self.regex.capture_info(0).and_then(|info| info.max_len) {
+			Some(max_len) => (max_len + ASSERTION_MARGIN).min(self.window),
+			None => self.window,
+		}
+	}
+
+	fn process(&mut self, at_end: bool, mut on_match: impl FnMut(StreamMatch)) {
+		let horizon = self.horizon();
+		// No match can start before this position when more data arrives
+		let decided_pos = self.buffer.floor_char_boundary(self.buffer.len().saturating_sub(horizon));
+
+		while self.search_pos <= self.buffer.len() {
+			let Some(res) = self.regex.find_from(&self.buffer, self.search_pos) else {
+				self.search_pos = if at_end { self.buffer.len() + 1 } else { self.search_pos.max(decided_pos) };
+				break;
+			};
+
+			let whole = res.whole.to_range();
+			if !at_end && whole.start + horizon > self.buffer.len() {
+				self.search_pos = self.search_pos.max(whole.start.min(decided_pos));
+				break;
+			}
+
+			let abs_end = self.base + whole.end;
+			if whole.is_empty() {
+				// Always advance past an empty match, so we don't get stuck matching it forever
+				self.search_pos = whole.end + self.buffer[whole.end..].chars().next().map_or(1, |ch| ch.len_utf8());
+
+				// Don't allow an empty match directly after the previous match
+				if self.last_match_end == Some(abs_end) {
+					continue;
+				}
+			} else {
+				self.search_pos = whole.end;
+			}
+
+			self.last_match_end = Some(abs_end);
+			on_match(StreamMatch { begin: self.base + whole.start, end: abs_end, text: self.buffer[whole].to_string() });
+		}
+
+		// Only keep a window of data before the search position, so lookbehinds and assertions still have some context
+		let discard_len = self.buffer.floor_char_boundary(self.search_pos.min(self.buffer.len()).saturating_sub(self.window));
+		if discard_len > 0 {
+			self.buffer.drain(..discard_len);
+			self.base += discard_len;
+			self.search_pos -= discard_len;
+		}
+	}
+}
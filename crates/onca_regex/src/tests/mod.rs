@@ -230,6 +230,351 @@ fn test_conditional() {
 	check_matches(r"(?(DEFINE)b)", RegexFlags::None, &[""], &["a", "b", "c"])
 }
 
+#[test]
+fn test_replace() {
+	let regex = Regex::new(r"(\w+)@(?<domain>\w+)", RegexFlags::None).unwrap();
+	assert_eq!(regex.replace("a@b c@d", "$2:$1"), "b:a c@d");
+	assert_eq!(regex.replace_all("a@b c@d", "${domain}-$0"), "b-a@b d-c@d");
+	assert_eq!(regex.replacen("a@b c@d e@f", 2, "$$"), "$ $ e@f");
+	assert_eq!(regex.replace_all("a@b", NoExpand("$1")), "$1");
+	assert_eq!(regex.replace_all("a@b", |res: &MatchResult| if res.has_capture_by_name("domain") { "named".to_string() } else { String::new() }), "named");
+
+	let regex = Regex::new("a*", RegexFlags::None).unwrap();
+	assert_eq!(regex.replace_all("baaac", "-"), "-b-c-");
+}
+
+#[test]
+fn test_find_iter() {
+	let regex = Regex::new(r"\d+", RegexFlags::None).unwrap();
+	let matches: Vec<_> = regex.find_iter("a1 22 333b").map(|m| (m.start(), m.as_str())).collect();
+	assert_eq!(matches, [(1, "1"), (3, "22"), (6, "333")]);
+
+	let regex = Regex::new("a*", RegexFlags::None).unwrap();
+	let matches: Vec<_> = regex.find_iter("baaac").map(|m| m.range()).collect();
+	assert_eq!(matches, [0..0, 1..4, 5..5]);
+
+	let regex = Regex::new(r"(\w)=(\w)", RegexFlags::None).unwrap();
+	assert_eq!(regex.captures_iter("a=b, c=d").count(), 2);
+}
+
+#[test]
+fn test_split() {
+	let regex = Regex::new(r"\h*,\h*", RegexFlags::None).unwrap();
+	assert_eq!(regex.split("a, b ,c").collect::<Vec<_>>(), ["a", "b", "c"]);
+	assert_eq!(regex.split(",a,").collect::<Vec<_>>(), ["", "a", ""]);
+	assert_eq!(regex.splitn("a, b, c", 2).collect::<Vec<_>>(), ["a", "b, c"]);
+	assert_eq!(regex.splitn("a, b, c", 0).count(), 0);
+
+	let regex = Regex::new(".", RegexFlags::DotAll).unwrap();
+	assert_eq!(regex.split("\n").collect::<Vec<_>>(), ["", ""]);
+}
+
+#[test]
+fn test_serialize() {
+	let regex = Regex::new(r"(?<name>a|b)+\d{2,3}?[x-z]\w?(?<=y|z)$", RegexFlags::Caseless).unwrap();
+	let bytes = regex.to_bytes();
+	let deserialized = Regex::from_bytes(&bytes).unwrap();
+	assert_eq!(deserialized.to_bytes(), bytes);
+
+	for s in ["ab12x", "ab12y", "A123zz", "ba1y", ""] {
+		assert_eq!(regex.is_match(s).is_some(), deserialized.is_match(s).is_some(), "Mismatch for '{s}'");
+	}
+
+	assert!(Regex::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+	assert!(Regex::from_bytes(b"ORGX").is_err());
+}
+
+#[test]
+fn test_linear_engine() {
+	// Would take exponential time using backtracking
+	let regex = Regex::new(r"(a|aa)*b", RegexFlags::None).unwrap();
+	assert!(regex.is_linear_time());
+	let s = "a".repeat(64);
+	assert!(regex.contains(&s).is_none());
+	assert!(regex.is_match(&(s + "b")).is_some());
+
+	let regex = Regex::new(r"(\w+)@(?<domain>\w+)", RegexFlags::None).unwrap();
+	assert!(regex.is_linear_time());
+	assert_eq!(regex.replace("to: a@b", "${domain}"), "to: b");
+
+	check_matches(r"a*?b|a+", RegexFlags::None, &["aab", "aa"], &["", "ba"]);
+	check_matches(r"\bfoo\b", RegexFlags::Caseless, &["FOO"], &["foobar"]);
+
+	assert!(!Regex::new(r"(a)\g1", RegexFlags::None).unwrap().is_linear_time());
+	assert!(!Regex::new(r"a(?=b)", RegexFlags::None).unwrap().is_linear_time());
+	assert!(!Regex::new(r"a++", RegexFlags::None).unwrap().is_linear_time());
+}
+
+#[test]
+fn test_find_at() {
+	let regex = Regex::new(r"\bb\w", RegexFlags::None).unwrap();
+	assert_eq!(regex.find_at("ab bc", 1).map(|(idx, _)| idx), Some(3));
+	assert!(regex.is_match_at("abc", 1).is_none());
+	assert!(regex.is_match_at("a bc", 2).is_some());
+
+	let regex = Regex::new(r"^a", RegexFlags::None).unwrap();
+	assert!(regex.find_at("aa", 1).is_none());
+	let regex = Regex::new(r"^a", RegexFlags::Multiline).unwrap();
+	assert_eq!(regex.find_at("a\na", 1).map(|(idx, _)| idx), Some(2));
+
+	let regex = Regex::new(r"\Ga", RegexFlags::None).unwrap();
+	assert_eq!(regex.find_at("ba", 1).map(|(idx, _)| idx), Some(1));
+	assert!(regex.find_at("bba", 1).is_none());
+
+	assert!(regex.find_at("\u{e9}a", 1).is_none());
+}
+
+#[test]
+fn test_anchored() {
+	let regex = Regex::new(r"\d+,?", RegexFlags::Anchored).unwrap();
+	assert!(regex.contains("a1").is_none());
+	assert_eq!(regex.find_at("a1", 1).map(|(idx, _)| idx), Some(1));
+
+	let tokens: Vec<_> = regex.find_iter("1,22,3 4").map(|m| m.as_str()).collect();
+	assert_eq!(tokens, ["1,", "22,", "3"]);
+}
+
+#[test]
+fn test_capture_info() {
+	let regex = Regex::new(r"(?<key>\w+)=([0-9]{2})(?:,(?<extra>a|bc))?", RegexFlags::None).unwrap();
+	assert_eq!(regex.capture_count(), 3);
+	assert_eq!(regex.capture_names().collect::<Vec<_>>(), [(1, Some("key")), (2, None), (3, Some("extra"))]);
+
+	let key = regex.capture_info(1).unwrap();
+	assert_eq!((key.min_len, key.max_len, key.optional), (1, None, false));
+	assert!(!key.is_nullable());
+
+	let digits = regex.capture_info(2).unwrap();
+	assert_eq!(digits.fixed_length(), Some(2));
+
+	let extra = regex.capture_info(3).unwrap();
+	assert_eq!((extra.name, extra.min_len, extra.max_len, extra.optional), (Some("extra"), 1, Some(2), true));
+
+	let whole = regex.capture_info(0).unwrap();
+	assert_eq!((whole.min_len, whole.max_len), (4, None));
+	assert!(regex.capture_info(4).is_none());
+
+	let regex = Regex::new(r"a(b*)", RegexFlags::None).unwrap();
+	assert!(regex.capture_info(1).unwrap().is_nullable());
+}
+
+#[test]
+fn test_stream_matcher() {
+	let regex = Regex::new(r"\d+", RegexFlags::None).unwrap();
+	let mut matcher = StreamMatcher::new(&regex, 4);
+	let mut matches = Vec::new();
+	for chunk in ["ab12", "34 5", "6"] {
+		matcher.feed(chunk.as_bytes(), |m| matches.push((m.start(), m.as_str().to_string()))).unwrap();
+	}
+	assert_eq!(matches, [(2, "1234".to_string())]);
+	matcher.finish(|m| matches.push((m.start(), m.as_str().to_string()))).unwrap();
+	assert_eq!(matches, [(2, "1234".to_string()), (7, "56".to_string())]);
+
+	// Characters split across chunks
+	let regex = Regex::new("\u{e9}b", RegexFlags::None).unwrap();
+	let data = "a\u{e9}b \u{e9}b".as_bytes();
+	let mut matcher = StreamMatcher::new(&regex, 8);
+	let mut matches = Vec::new();
+	for chunk in data.chunks(1) {
+		matcher.feed(chunk, |m| matches.push(m.range())).unwrap();
+	}
+	matcher.finish(|m| matches.push(m.range())).unwrap();
+	assert_eq!(matches, [1..4, 5..8]);
+
+	let mut matches = Vec::new();
+	matcher.scan(data, |m| matches.push(m.range())).unwrap();
+	assert_eq!(matches, [1..4, 5..8]);
+
+	let mut matcher = StreamMatcher::new(&regex, 8);
+	assert!(matcher.feed(&[0xFF], |_| {}).is_err());
+	let mut matcher = StreamMatcher::new(&regex, 8);
+	matcher.feed(&data[..2], |_| {}).unwrap();
+	assert!(matcher.finish(|_| {}).is_err());
+}
+
+#[test]
+fn test_caseless() {
+	check_matches("stra\u{df}e", RegexFlags::Caseless, &["STRASSE", "strasse", "Stra\u{df}e", "STRA\u{1e9e}E"], &["strase"]);
+	check_matches("strasse", RegexFlags::Caseless, &["stra\u{df}e"], &["stras"]);
+	check_matches("\u{3a3}", RegexFlags::Caseless, &["\u{3c3}", "\u{3c2}"], &["s"]);
+	check_matches("k+", RegexFlags::Caseless, &["kK\u{212a}"], &[]);
+
+	check_matches("stra\u{df}e", RegexFlags::Caseless | RegexFlags::AsciiCaseless, &["STRA\u{df}E"], &["strasse"]);
+	check_matches("k+", RegexFlags::Caseless | RegexFlags::AsciiCaseless, &["kK"], &["\u{212a}"]);
+
+	// Backtracking matcher
+	check_matches("(a)stra\u{df}e\\g1", RegexFlags::Caseless, &["ASTRASSEa"], &["astrasea"]);
+}
+
+#[test]
+fn test_caseless_class_def() {
+	check_matches("^[a-z]+$", RegexFlags::Caseless, &["abc", "ABC", "aBc", "\u{212a}"], &["1", "a1"]);
+	check_matches("^[A-Z]+$", RegexFlags::Caseless, &["abc", "ABC"], &["1"]);
+	check_matches("^[xyz]$", RegexFlags::Caseless, &["x", "Y"], &["a"]);
+	check_matches("^[\u{3c3}]$", RegexFlags::Caseless, &["\u{3a3}", "\u{3c2}"], &["s"]);
+	check_matches("^[^a-z]$", RegexFlags::Caseless, &["1"], &["a", "A"]);
+	check_matches("^[^Q]$", RegexFlags::Caseless, &["a"], &["q", "Q"]);
+	check_matches("^[a-z]$", RegexFlags::None, &["a"], &["A"]);
+
+	check_matches("^[a-z]+$", RegexFlags::Caseless | RegexFlags::AsciiCaseless, &["abc", "ABC"], &["\u{212a}"]);
+	check_matches("^[^A-Z]$", RegexFlags::Caseless | RegexFlags::AsciiCaseless, &["1"], &["a", "A"]);
+
+	// Backtracking matcher
+	check_matches("^([a-z])\\g1$", RegexFlags::Caseless, &["Aa", "bB"], &["ab"]);
+	check_matches("^(a)[^b]\\g1$", RegexFlags::Caseless, &["aca"], &["aBa", "aba"]);
+}
+
+#[test]
+fn test_match_ranges() {
+	let regex = Regex::new(r"(?<key>\w+)=(\w*)(x)?", RegexFlags::None).unwrap();
+	let (_, res) = regex.contains("  ab=cd").unwrap();
+	assert_eq!(res.whole_match_range(), 2..7);
+	assert_eq!(res.whole_match(), "ab=cd");
+	assert_eq!(res.get_capture_range(1), Some(2..4));
+	assert_eq!(res.get_capture_range_by_name("key"), Some(2..4));
+	assert_eq!(res.get_capture(2), Some("cd"));
+	assert_eq!(res.get_capture_by_name("key"), Some("ab"));
+	assert_eq!(res.get_capture(3), None);
+	assert_eq!(res.get_capture_range(4), None);
+
+	// Offsets past 64KiB
+	let s = " ".repeat(70_000) + "ab=cd";
+	let (idx, res) = regex.contains(&s).unwrap();
+	assert_eq!(idx, 70_000);
+	assert_eq!(res.get_capture_range(2), Some(70_003..70_005));
+}
+
+#[test]
+fn test_empty_capture() {
+	// A capture that matched an empty string still participated in the match
+	let regex = Regex::new("(a*)", RegexFlags::None).unwrap();
+	let (_, res) = regex.contains("b").unwrap();
+	assert!(res.has_capture(1));
+	assert_eq!(res.get_capture_range(1), Some(0..0));
+	assert_eq!(res.get_capture(1), Some(""));
+
+	// Backtracking matcher
+	let regex = Regex::new(r"b(a*)\g1(?(1)c|d)", RegexFlags::None).unwrap();
+	let (_, res) = regex.contains("bc").unwrap();
+	assert_eq!(res.get_capture_range(1), Some(1..1));
+
+	let regex = Regex::new("b(a)?", RegexFlags::None).unwrap();
+	let (_, res) = regex.contains("b").unwrap();
+	assert!(!res.has_capture(1));
+	assert_eq!(res.get_capture_range(1), None);
+}
+
+#[test]
+fn test_glob() {
+	let check_glob = |glob: &str, valid: &[&str], invalid: &[&str]| {
+		let regex = Regex::from_glob(glob).unwrap();
+		for val in valid {
+			assert!(regex.is_match(val).is_some(), "Failed to match glob '{glob}' with value '{val}'");
+		}
+		for val in invalid {
+			assert!(regex.is_match(val).is_none(), "Should not match glob '{glob}' with value '{val}'");
+		}
+	};
+
+	check_glob("*.png", &["a.png", ".png"], &["a.jpg", "dir/a.png", "a.png.bak"]);
+	check_glob("file?.txt", &["file1.txt", "fileA.txt"], &["file.txt", "file/.txt", "file12.txt"]);
+	check_glob("assets/**/*.png", &["assets/a.png", "assets/x/a.png", "assets/x/y/a.png"], &["assets.png", "other/a.png", "assets/x/a.jpg"]);
+	check_glob("assets/**", &["assets/", "assets/a", "assets/x/y.png"], &["assets", "other/a"]);
+	check_glob("**/*.rs", &["lib.rs", "src/lib.rs", "a/b/c.rs"], &["lib.rsx"]);
+	check_glob("a**b", &["ab", "axxb"], &["a/b"]);
+	check_glob("[a-c]x[!0-9]", &["axy", "cx_"], &["dxy", "ax1", "ax/"]);
+	check_glob("[]!]", &["]", "!"], &["a"]);
+	check_glob("*.{png,jp{e,}g}", &["a.png", "a.jpeg", "a.jpg"], &["a.gif", "a.jpeeg"]);
+	check_glob("{,dir/}file", &["file", "dir/file"], &["dirfile"]);
+	check_glob(r"\*\?", &["*?"], &["a?", "*a"]);
+	check_glob("a,b", &["a,b"], &["a"]);
+
+	assert!(Regex::from_glob("[abc").is_err());
+	assert!(Regex::from_glob("{a,b").is_err());
+	assert!(Regex::from_glob("[z-a]").is_err());
+	assert!(Regex::from_glob("a\\").is_err());
+}
+
+#[test]
+fn test_literal_prefilter() {
+	let find_all = |regex: &str, s: &str| -> Vec<(usize, String)> {
+		let regex = Regex::new(regex, RegexFlags::None).unwrap();
+		regex.find_iter(s).map(|m| (m.start(), m.as_str().to_string())).collect()
+	};
+
+	// Required prefix, both for the linear engine and the backtracker
+	assert_eq!(find_all(r"error: \w+", "ok error: disk warn error: net"), [(3, "error: disk".to_string()), (20, "error: net".to_string())]);
+	assert_eq!(find_all(r"(e)rror: \g{1}\w*", "error: x error: eek"), [(9, "error: eek".to_string())]);
+	// Candidates that don't match, multibyte characters around candidates
+	assert_eq!(find_all(r"a(?:b)+c", "ababé abbbc abc"), [(7, "abbbc".to_string()), (13, "abc".to_string())]);
+	assert_eq!(find_all(r"é\d", "éaé1"), [(3, "é1".to_string())]);
+	// Required suffix and inner literals
+	assert_eq!(find_all(r"\w+[.]png", "a.jpg b.png"), [(6, "b.png".to_string())]);
+	assert_eq!(find_all(r"\d+-(?:ab|cb)-\d", "1-ab-x 2-cb-3"), [(7, "2-cb-3".to_string())]);
+	assert!(find_all(r"\d+mid\d+", "1mi2 3md4").is_empty());
+	// Alternations only share a common prefix
+	assert_eq!(find_all(r"foo(?:bar|baz)", "foobaz foobar"), [(0, "foobaz".to_string()), (7, "foobar".to_string())]);
+	assert_eq!(find_all(r"cat|dog", "a dog and a cat"), [(2, "dog".to_string()), (12, "cat".to_string())]);
+	// Optional literals are not required
+	assert_eq!(find_all(r"x(?:abc)?y", "xy xabcy"), [(0, "xy".to_string()), (3, "xabcy".to_string())]);
+	assert_eq!(find_all(r"(?:ab){2,}", "ab abab ababab"), [(3, "abab".to_string()), (8, "ababab".to_string())]);
+	// Lookarounds still see the text before the candidate
+	assert_eq!(find_all(r"(?<=x)\d+", "5 x10"), [(3, "10".to_string())]);
+	// Caseless matching and `\G` disable skipping ahead
+	let regex = Regex::new(r"abc", RegexFlags::Caseless).unwrap();
+	assert_eq!(regex.contains("x ABC").map(|(idx, _)| idx), Some(2));
+	assert!(find_all(r"\Gfoo", "xfoo").is_empty());
+
+	let regex = Regex::new(r"key=\w+;", RegexFlags::None).unwrap();
+	assert!(regex.is_match("key=value;").is_some());
+	assert!(regex.is_match("key=value").is_none());
+	assert!(regex.is_match("xkey=value;").is_none());
+
+	let s = "x".repeat(100_000) + "needle";
+	let regex = Regex::new(r"need[a-z]+", RegexFlags::None).unwrap();
+	assert_eq!(regex.contains(&s).map(|(idx, _)| idx), Some(100_000));
+}
+
+#[test]
+fn test_newline_mode() {
+	let find_all = |regex: &str, flags: RegexFlags, newline: NewlineMode, s: &str| -> Vec<String> {
+		let regex = Regex::with_options(regex, RegexOptions { flags, newline }).unwrap();
+		regex.find_iter(s).map(|m| m.as_str().to_string()).collect()
+	};
+
+	let s = "a\r\nb\nc\u{2028}d";
+	assert_eq!(find_all(r"^\w", RegexFlags::Multiline, NewlineMode::AnyUnicode, s), ["a", "b", "c", "d"]);
+	assert_eq!(find_all(r"^\w", RegexFlags::Multiline, NewlineMode::LfOnly, s), ["a", "b", "c"]);
+	assert_eq!(find_all(r"^\w", RegexFlags::Multiline, NewlineMode::CrLf, s), ["a", "b"]);
+
+	assert_eq!(find_all(r"\w$", RegexFlags::Multiline, NewlineMode::AnyUnicode, s), ["a", "b", "c", "d"]);
+	assert_eq!(find_all(r"\w$", RegexFlags::Multiline, NewlineMode::LfOnly, s), ["b", "d"]);
+	assert_eq!(find_all(r"\w$", RegexFlags::Multiline, NewlineMode::CrLf, s), ["a", "d"]);
+
+	// A dot never matches a newline, but does match characters that are not a newline in the current mode
+	assert_eq!(find_all(r"a.", RegexFlags::None, NewlineMode::AnyUnicode, "a\u{85}"), Vec::<String>::new());
+	assert_eq!(find_all(r"a.", RegexFlags::None, NewlineMode::LfOnly, "a\u{85}"), ["a\u{85}"]);
+	assert_eq!(find_all(r"a.", RegexFlags::None, NewlineMode::CrLf, "a\r\n"), Vec::<String>::new());
+	assert_eq!(find_all(r"a.", RegexFlags::None, NewlineMode::CrLf, "a\n"), ["a\n"]);
+
+	// `\R` matches a full newline
+	assert_eq!(find_all(r"a\R", RegexFlags::None, NewlineMode::AnyUnicode, "a\r\n a\r a\u{2029}"), ["a\r\n", "a\r", "a\u{2029}"]);
+	assert_eq!(find_all(r"a\R", RegexFlags::None, NewlineMode::LfOnly, "a\r\n a\n"), ["a\n"]);
+	assert_eq!(find_all(r"a\R", RegexFlags::None, NewlineMode::CrLf, "a\r\n a\n"), ["a\r\n"]);
+
+	// `$` and `\Z` match before a newline at the end of the subject, but never in the middle of a CRLF
+	assert_eq!(find_all(r"a$", RegexFlags::None, NewlineMode::AnyUnicode, "a\r\n"), ["a"]);
+	assert_eq!(find_all(r"a\r$", RegexFlags::None, NewlineMode::AnyUnicode, "a\r\n"), Vec::<String>::new());
+	assert_eq!(find_all(r"a\r$", RegexFlags::None, NewlineMode::LfOnly, "a\r\n"), ["a\r"]);
+	assert_eq!(find_all(r"a\Z", RegexFlags::None, NewlineMode::CrLf, "a\n"), Vec::<String>::new());
+	assert_eq!(find_all(r"a\Z", RegexFlags::None, NewlineMode::CrLf, "a\r\n"), ["a"]);
+
+	let regex = Regex::with_options(r"^\w", RegexOptions { flags: RegexFlags::Multiline, newline: NewlineMode::LfOnly }).unwrap();
+	let regex = Regex::from_bytes(&regex.to_bytes()).unwrap();
+	assert_eq!(regex.newline_mode(), NewlineMode::LfOnly);
+	assert_eq!(regex.find_iter(s).count(), 3);
+}
+
 fn check_matches(regex_s: &str, flags: RegexFlags, valid: &[&str], invalid: &[&str]) {
 	let regex = Regex::new(regex_s, flags).unwrap();
 	for val in valid {
@@ -0,0 +1,126 @@
+use onca_common::io::Read;
+use onca_fs::{File, FileAccessFlags, Path, Permission};
+use onca_ral as ral;
+
+use crate::{
+    os::os_imp,
+    CompiledShader, ShaderCompileDesc, ShaderCompileError, ShaderCompileFlags, ShaderModel, ShaderTarget,
+};
+
+/// Shader compiler
+///
+/// # Note
+///
+/// For DXIL to be signed, `dxil.dll` (`libdxil.so` on linux) needs to be located next to the compiler library, otherwise DX12 will refuse to create the shader.
+pub struct ShaderCompiler {
+    dxc: os_imp::Dxc,
+}
+
+impl ShaderCompiler {
+    /// Create a new shader compiler by loading the DXC library at the given path.
+    pub fn new(library_path: &str) -> Result<Self, ShaderCompileError> {
+        Ok(Self { dxc: os_imp::Dxc::load(library_path)? })
+    }
+
+    /// Compile HLSL source code.
+    pub fn compile(&self, source: &str, desc: &ShaderCompileDesc) -> Result<CompiledShader, ShaderCompileError> {
+        let args = get_arguments(desc);
+        // Reflection is only returned separately for DXIL
+        self.dxc.compile(source, &args, desc.target == ShaderTarget::Dxil)
+    }
+
+    /// Compile an HLSL source file.
+    ///
+    /// If `desc.source_name` is empty, the path will be used as the source name.
+    pub fn compile_file(&self, path: &Path, desc: &ShaderCompileDesc) -> Result<CompiledShader, ShaderCompileError> {
+        let mut file = File::open(path, Permission::Read, Permission::None, FileAccessFlags::SequentialAccess)?;
+        let mut source = String::new();
+        file.read_to_string(&mut source)?;
+
+        if desc.source_name.is_empty() {
+            let source_name = path.to_string();
+            let desc = ShaderCompileDesc { source_name: &source_name, ..desc.clone() };
+            self.compile(&source, &desc)
+        } else {
+            self.compile(&source, desc)
+        }
+    }
+}
+
+fn get_target_profile(shader_type: ral::ShaderType, shader_model: ShaderModel) -> String {
+    let prefix = match shader_type {
+        ral::ShaderType::Vertex       => "vs",
+        ral::ShaderType::Pixel        => "ps",
+        ral::ShaderType::Compute      => "cs",
+        ral::ShaderType::Task         => "as",
+        ral::ShaderType::Mesh         => "ms",
+        ral::ShaderType::RayGen       |
+        ral::ShaderType::Intersection |
+        ral::ShaderType::AnyHit       |
+        ral::ShaderType::ClosestHit   |
+        ral::ShaderType::Miss         |
+        ral::ShaderType::Callable     => "lib",
+    };
+    format!("{prefix}_{}_{}", shader_model.major, shader_model.minor)
+}
+
+fn get_arguments(desc: &ShaderCompileDesc) -> Vec<String> {
+    let mut args = Vec::new();
+
+    // Source name is passed as a positional argument, so DXC can resolve includes relative to it
+    if !desc.source_name.is_empty() {
+        args.push(desc.source_name.to_string());
+    }
+
+    let profile = get_target_profile(desc.shader_type, desc.shader_model);
+    // Libraries export all their entry points
+    if !profile.starts_with("lib_") {
+        args.push("-E".to_string());
+        args.push(desc.entry_point.to_string());
+    }
+    args.push("-T".to_string());
+    args.push(profile);
+    // Matrices are row-major in the engine
+    args.push("-Zpr".to_string());
+
+    for define in desc.defines {
+        args.push("-D".to_string());
+        match &define.value {
+            Some(value) => args.push(format!("{}={value}", define.name)),
+            None => args.push(define.name.clone()),
+        }
+    }
+
+    for dir in desc.include_dirs {
+        args.push("-I".to_string());
+        args.push(dir.to_string());
+    }
+
+    if desc.flags.contains(ShaderCompileFlags::Debug) {
+        args.push("-Zi".to_string());
+        args.push("-Qembed_debug".to_string());
+    }
+    if desc.flags.contains(ShaderCompileFlags::SkipOptimization) {
+        args.push("-Od".to_string());
+    }
+    if desc.flags.contains(ShaderCompileFlags::WarningsAsErrors) {
+        args.push("-WX".to_string());
+    }
+
+    match desc.target {
+        ShaderTarget::Dxil => {
+            // Reflection is returned separately
+            args.push("-Qstrip_reflect".to_string());
+        },
+        ShaderTarget::Spirv => {
+            args.push("-spirv".to_string());
+            args.push("-fspv-target-env=vulkan1.3".to_string());
+            args.push("-fspv-reflect".to_string());
+            if matches!(desc.shader_type, ral::ShaderType::Vertex | ral::ShaderType::Mesh) {
+                args.push("-fvk-invert-y".to_string());
+            }
+        },
+    }
+
+    args
+}
@@ -0,0 +1,32 @@
+use core::fmt;
+use onca_common::io;
+
+/// Shader compiler error
+#[derive(Debug)]
+pub enum ShaderCompileError {
+    /// The DXC library could not be loaded.
+    LoadLibrary(String),
+    /// The DXC library could not be initialized.
+    Initialize(String),
+    /// An I/O error occured.
+    IO(io::Error),
+    /// The shader failed to compile, contains the compiler output.
+    Compilation(String),
+}
+
+impl From<io::Error> for ShaderCompileError {
+    fn from(err: io::Error) -> Self {
+        Self::IO(err)
+    }
+}
+
+impl fmt::Display for ShaderCompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShaderCompileError::LoadLibrary(path) => f.write_fmt(format_args!("Failed to load the shader compiler library: '{path}'")),
+            ShaderCompileError::Initialize(err)   => f.write_fmt(format_args!("Failed to initialize the shader compiler: {err}")),
+            ShaderCompileError::IO(err)           => f.write_fmt(format_args!("I/O error: {err}")),
+            ShaderCompileError::Compilation(msg)  => f.write_fmt(format_args!("Failed to compile shader:\n{msg}")),
+        }
+    }
+}
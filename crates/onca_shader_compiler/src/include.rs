@@ -0,0 +1,15 @@
+use onca_common::io::Read;
+use onca_fs::{File, FileAccessFlags, Path, Permission};
+
+/// Load an included file using `onca_fs`
+///
+/// DXC resolves includes relative to the including file and the include directories, and calls into the include handler for each candidate path until one can be loaded.
+pub(crate) fn load_include(path: &str) -> Option<Vec<u8>> {
+    let path = path.strip_prefix("./").unwrap_or(path);
+    let path = Path::new(path).ok()?;
+    let mut file = File::open(path, Permission::Read, Permission::None, FileAccessFlags::SequentialAccess).ok()?;
+
+    let mut data = Vec::new();
+    file.read_to_end(&mut data).ok()?;
+    Some(data)
+}
@@ -0,0 +1,127 @@
+//! Runtime shader compiler.
+//!
+//! Compiles HLSL source code to DXIL or SPIR-V using DXC, loaded at runtime from the `dxcompiler` library.
+//!
+//! Includes are resolved using `onca_fs`, relative to the including file and the include directories passed in the compile description.
+
+use onca_common_macros::flags;
+use onca_ral as ral;
+
+mod error;
+pub use error::*;
+
+mod include;
+mod os;
+
+mod compiler;
+pub use compiler::*;
+
+/// Shader compile flags
+#[flags]
+pub enum ShaderCompileFlags {
+    /// Embed debug info into the compiled shader.
+    Debug,
+    /// Skip shader optimization.
+    SkipOptimization,
+    /// Treat warnings as errors.
+    WarningsAsErrors,
+}
+
+/// Shader compile target
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShaderTarget {
+    /// DXIL, used by DX12.
+    Dxil,
+    /// SPIR-V, used by Vulkan.
+    Spirv,
+}
+
+impl ShaderTarget {
+    /// Get the shader target required by a RAL api, returns `None` if the api does not consume DXIL or SPIR-V.
+    pub fn from_api(api: &ral::RalApi) -> Option<Self> {
+        match api {
+            ral::RalApi::DX12   => Some(Self::Dxil),
+            ral::RalApi::Vulkan => Some(Self::Spirv),
+            _                   => None,
+        }
+    }
+}
+
+/// Shader model
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct ShaderModel {
+    /// Major version.
+    pub major: u8,
+    /// Minor version.
+    pub minor: u8,
+}
+
+impl ShaderModel {
+    /// Shader model 6.6
+    pub const SM_6_6: ShaderModel = ShaderModel { major: 6, minor: 6 };
+    /// Shader model 6.7
+    pub const SM_6_7: ShaderModel = ShaderModel { major: 6, minor: 7 };
+}
+
+impl Default for ShaderModel {
+    fn default() -> Self {
+        Self::SM_6_7
+    }
+}
+
+/// Shader define
+#[derive(Clone, Debug)]
+pub struct ShaderDefine {
+    /// Name of the define.
+    pub name:  String,
+    /// Optional value of the define.
+    pub value: Option<String>,
+}
+
+impl ShaderDefine {
+    /// Create a define without a value.
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), value: None }
+    }
+
+    /// Create a define with a value.
+    pub fn with_value(name: &str, value: &str) -> Self {
+        Self { name: name.to_string(), value: Some(value.to_string()) }
+    }
+}
+
+/// Shader compile description
+#[derive(Clone, Debug)]
+pub struct ShaderCompileDesc<'a> {
+    /// Name of the source, used to resolve relative includes and in compiler messages.
+    pub source_name:  &'a str,
+    /// Entry point of the shader.
+    ///
+    /// Ignored for raytracing shaders, as these are compiled as libraries.
+    pub entry_point:  &'a str,
+    /// Shader type.
+    pub shader_type:  ral::ShaderType,
+    /// Compile target.
+    pub target:       ShaderTarget,
+    /// Shader model.
+    pub shader_model: ShaderModel,
+    /// Defines.
+    pub defines:      &'a [ShaderDefine],
+    /// Additional include directories.
+    pub include_dirs: &'a [&'a str],
+    /// Compile flags.
+    pub flags:        ShaderCompileFlags,
+}
+
+/// Compiled shader
+#[derive(Clone, Debug)]
+pub struct CompiledShader {
+    /// Compiled shader code, can be passed to `Device::create_shader`.
+    pub code:       Vec<u8>,
+    /// Reflection data.
+    ///
+    /// For DXIL this is the stripped reflection container, for SPIR-V the reflection info is embedded in the code.
+    pub reflection: Option<Vec<u8>>,
+    /// Compiler warnings, if any were emitted.
+    pub warnings:   Option<String>,
+}
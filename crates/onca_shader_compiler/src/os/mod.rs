@@ -0,0 +1,11 @@
+use cfg_if::cfg_if;
+
+cfg_if!{
+    if #[cfg(windows)] {
+        pub(crate) mod windows;
+        pub(crate) use self::windows as os_imp;
+    } else {
+        pub(crate) mod posix;
+        pub(crate) use self::posix as os_imp;
+    }
+}
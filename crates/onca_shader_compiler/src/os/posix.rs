@@ -0,0 +1,442 @@
+//! DXC bindings for posix platforms.
+//!
+//! The `windows` crate is only available on windows, so the COM interfaces used by the compiler are declared here.
+//! Only the vtable entries up to the last one that is called are declared, unused entries are declared as `usize`.
+//!
+//! On posix platforms, `wchar_t` is 32-bits, so DXC expects wide strings to be UTF-32.
+//!
+//! Outside of windows, DXC's `IUnknown` (WinAdapter.h) has a virtual destructor, which adds 2 entries to every vtable after `Release`.
+
+use core::{
+    ffi::{c_char, c_void},
+    marker::PhantomData,
+    ptr::{null_mut, NonNull},
+    slice,
+    sync::atomic::{AtomicU32, Ordering, fence},
+};
+use std::ffi::CString;
+
+use crate::{include::load_include, CompiledShader, ShaderCompileError};
+
+type HResult = i32;
+type Bool = i32;
+type WChar = u32;
+
+const S_OK          : HResult = 0;
+const E_NOINTERFACE : HResult = 0x80004002u32 as i32;
+const E_POINTER     : HResult = 0x80004003u32 as i32;
+const E_FAIL        : HResult = 0x80004005u32 as i32;
+
+const DXC_CP_UTF8        : u32 = 65001;
+const DXC_OUT_OBJECT     : u32 = 1;
+const DXC_OUT_ERRORS     : u32 = 2;
+const DXC_OUT_REFLECTION : u32 = 8;
+
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Guid {
+    data1: u32,
+    data2: u16,
+    data3: u16,
+    data4: [u8; 8],
+}
+
+const fn guid(data1: u32, data2: u16, data3: u16, data4: [u8; 8]) -> Guid {
+    Guid { data1, data2, data3, data4 }
+}
+
+const CLSID_DXC_COMPILER       : Guid = guid(0x73e22d93, 0xe6ce, 0x47f3, [0xb5, 0xbf, 0xf0, 0x66, 0x4f, 0x39, 0xc1, 0xb0]);
+const CLSID_DXC_UTILS          : Guid = guid(0x6245d6af, 0x66e0, 0x48fd, [0x80, 0xb4, 0x4d, 0x27, 0x17, 0x96, 0x74, 0x8c]);
+const IID_IUNKNOWN             : Guid = guid(0x00000000, 0x0000, 0x0000, [0xc0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46]);
+const IID_IDXC_BLOB            : Guid = guid(0x8ba5fb08, 0x5195, 0x40e2, [0xac, 0x58, 0x0d, 0x98, 0x9c, 0x3a, 0x01, 0x02]);
+const IID_IDXC_BLOB_UTF8       : Guid = guid(0x3da636c9, 0xba71, 0x4024, [0xa3, 0x01, 0x30, 0xcb, 0xf1, 0x25, 0x30, 0x5b]);
+const IID_IDXC_INCLUDE_HANDLER : Guid = guid(0x7f61fc7d, 0x950d, 0x467f, [0xb3, 0xe3, 0x3c, 0x02, 0xfb, 0x49, 0x18, 0x7c]);
+const IID_IDXC_RESULT          : Guid = guid(0x58346cda, 0xdde7, 0x4497, [0x94, 0x61, 0x6f, 0x87, 0xaf, 0x5e, 0x06, 0x59]);
+const IID_IDXC_COMPILER3       : Guid = guid(0x228b4687, 0x5a6a, 0x4730, [0x90, 0x0c, 0x97, 0x02, 0xb2, 0x20, 0x3f, 0x54]);
+const IID_IDXC_UTILS           : Guid = guid(0x4605c4cb, 0x2019, 0x492a, [0xad, 0xa4, 0x65, 0xf2, 0x0b, 0xb7, 0xd6, 0x7f]);
+
+type FnDxcCreateInstance = unsafe extern "system" fn(rclsid: *const Guid, riid: *const Guid, ppv: *mut *mut c_void) -> HResult;
+
+#[repr(C)]
+struct DxcBuffer {
+    ptr:      *const c_void,
+    size:     usize,
+    encoding: u32,
+}
+
+//--------------------------------------------------------------
+// INTERFACES
+//--------------------------------------------------------------
+
+#[repr(C)]
+struct IUnknownVtbl {
+    query_interface: unsafe extern "system" fn(this: *mut c_void, riid: *const Guid, ppv: *mut *mut c_void) -> HResult,
+    add_ref:         unsafe extern "system" fn(this: *mut c_void) -> u32,
+    release:         unsafe extern "system" fn(this: *mut c_void) -> u32,
+    // Itanium ABI complete and deleting destructors of `virtual ~IUnknown()`
+    #[cfg(not(windows))]
+    complete_dtor:   unsafe extern "system" fn(this: *mut c_void),
+    #[cfg(not(windows))]
+    deleting_dtor:   unsafe extern "system" fn(this: *mut c_void),
+}
+
+#[repr(C)]
+struct IDxcBlobVtbl {
+    base:               IUnknownVtbl,
+    get_buffer_pointer: unsafe extern "system" fn(this: *mut c_void) -> *mut c_void,
+    get_buffer_size:    unsafe extern "system" fn(this: *mut c_void) -> usize,
+}
+
+#[repr(C)]
+struct IDxcBlobUtf8Vtbl {
+    base:               IDxcBlobVtbl,
+    // IDxcBlobEncoding
+    _get_encoding:      usize,
+    // IDxcBlobUtf8
+    get_string_pointer: unsafe extern "system" fn(this: *mut c_void) -> *const c_char,
+    get_string_length:  unsafe extern "system" fn(this: *mut c_void) -> usize,
+}
+
+#[repr(C)]
+struct IDxcResultVtbl {
+    base:              IUnknownVtbl,
+    // IDxcOperationResult
+    get_status:        unsafe extern "system" fn(this: *mut c_void, status: *mut HResult) -> HResult,
+    _get_result:       usize,
+    _get_error_buffer: usize,
+    // IDxcResult
+    has_output:        unsafe extern "system" fn(this: *mut c_void, kind: u32) -> Bool,
+    get_output:        unsafe extern "system" fn(this: *mut c_void, kind: u32, riid: *const Guid, ppv: *mut *mut c_void, output_name: *mut *mut c_void) -> HResult,
+}
+
+#[repr(C)]
+struct IDxcCompiler3Vtbl {
+    base:    IUnknownVtbl,
+    compile: unsafe extern "system" fn(this: *mut c_void, source: *const DxcBuffer, arguments: *const *const WChar, arg_count: u32, include_handler: *mut c_void, riid: *const Guid, result: *mut *mut c_void) -> HResult,
+}
+
+#[repr(C)]
+struct IDxcUtilsVtbl {
+    base:                     IUnknownVtbl,
+    _create_blob_from_blob:   usize,
+    _create_blob_from_pinned: usize,
+    _move_to_blob:            usize,
+    create_blob:              unsafe extern "system" fn(this: *mut c_void, data: *const c_void, size: u32, code_page: u32, blob: *mut *mut c_void) -> HResult,
+}
+
+#[repr(C)]
+struct IDxcIncludeHandlerVtbl {
+    base:        IUnknownVtbl,
+    load_source: unsafe extern "system" fn(this: *mut c_void, filename: *const WChar, include_source: *mut *mut c_void) -> HResult,
+}
+
+/// Vtable of a COM interface
+trait ComVtbl {
+    fn unknown(&self) -> &IUnknownVtbl;
+}
+
+macro_rules! impl_com_vtbl {
+    ($($vtbl:ty),*) => {
+        $(
+            impl ComVtbl for $vtbl {
+                fn unknown(&self) -> &IUnknownVtbl {
+                    &self.base
+                }
+            }
+        )*
+    };
+}
+impl_com_vtbl!(IDxcBlobVtbl, IDxcResultVtbl, IDxcCompiler3Vtbl, IDxcUtilsVtbl, IDxcIncludeHandlerVtbl);
+
+impl ComVtbl for IDxcBlobUtf8Vtbl {
+    fn unknown(&self) -> &IUnknownVtbl {
+        self.base.unknown()
+    }
+}
+
+/// Owned reference to a COM object, the reference is released when dropped
+struct ComPtr<V: ComVtbl> {
+    ptr:      NonNull<*const V>,
+    _phantom: PhantomData<V>,
+}
+
+impl<V: ComVtbl> ComPtr<V> {
+    /// Take ownership of a reference to a COM object, returns `None` if `ptr` is null.
+    unsafe fn from_raw(ptr: *mut c_void) -> Option<Self> {
+        NonNull::new(ptr as *mut *const V).map(|ptr| Self { ptr, _phantom: PhantomData })
+    }
+
+    /// Take ownership of a reference returned via an out-pointer.
+    unsafe fn from_result(hr: HResult, ptr: *mut c_void) -> Result<Self, HResult> {
+        if hr < 0 {
+            return Err(hr);
+        }
+        Self::from_raw(ptr).ok_or(E_POINTER)
+    }
+
+    fn as_raw(&self) -> *mut c_void {
+        self.ptr.as_ptr() as *mut c_void
+    }
+
+    fn vtbl(&self) -> &V {
+        unsafe { &**self.ptr.as_ptr() }
+    }
+}
+
+impl<V: ComVtbl> Clone for ComPtr<V> {
+    fn clone(&self) -> Self {
+        unsafe { (self.vtbl().unknown().add_ref)(self.as_raw()) };
+        Self { ptr: self.ptr, _phantom: PhantomData }
+    }
+}
+
+impl<V: ComVtbl> Drop for ComPtr<V> {
+    fn drop(&mut self) {
+        unsafe { (self.vtbl().unknown().release)(self.as_raw()) };
+    }
+}
+
+//--------------------------------------------------------------
+// LIBRARY
+//--------------------------------------------------------------
+
+/// Dynamic library loaded using `dlopen`
+struct Library(NonNull<c_void>);
+
+impl Library {
+    fn load(path: &str) -> Option<Self> {
+        let path = CString::new(path).ok()?;
+        NonNull::new(unsafe { libc::dlopen(path.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL) }).map(Self)
+    }
+
+    /// Get the address of a symbol, `name` needs to be null-terminated.
+    fn get(&self, name: &[u8]) -> Option<NonNull<c_void>> {
+        NonNull::new(unsafe { libc::dlsym(self.0.as_ptr(), name.as_ptr() as *const c_char) })
+    }
+}
+
+impl Drop for Library {
+    fn drop(&mut self) {
+        unsafe { libc::dlclose(self.0.as_ptr()) };
+    }
+}
+
+//--------------------------------------------------------------
+// COMPILER
+//--------------------------------------------------------------
+
+pub(crate) struct Dxc {
+    utils:    ComPtr<IDxcUtilsVtbl>,
+    compiler: ComPtr<IDxcCompiler3Vtbl>,
+    // Needs to be dropped last, as the interfaces above live in the library
+    _library: Library,
+}
+
+impl Dxc {
+    pub(crate) fn load(library_path: &str) -> Result<Self, ShaderCompileError> {
+        let library = Library::load(library_path).ok_or_else(|| ShaderCompileError::LoadLibrary(library_path.to_string()))?;
+        let create_instance = library.get(b"DxcCreateInstance\0")
+            .ok_or_else(|| ShaderCompileError::LoadLibrary(format!("{library_path} (missing 'DxcCreateInstance')")))?;
+        let create_instance = unsafe { core::mem::transmute::<*mut c_void, FnDxcCreateInstance>(create_instance.as_ptr()) };
+
+        let utils = unsafe { create_dxc_instance::<IDxcUtilsVtbl>(create_instance, &CLSID_DXC_UTILS, &IID_IDXC_UTILS)? };
+        let compiler = unsafe { create_dxc_instance::<IDxcCompiler3Vtbl>(create_instance, &CLSID_DXC_COMPILER, &IID_IDXC_COMPILER3)? };
+
+        Ok(Self {
+            utils,
+            compiler,
+            _library: library,
+        })
+    }
+
+    pub(crate) fn compile(&self, source: &str, args: &[String], get_reflection: bool) -> Result<CompiledShader, ShaderCompileError> {
+        let wide_args = args.iter().map(|arg| to_wide(arg)).collect::<Vec<_>>();
+        let arg_ptrs = wide_args.iter().map(|arg| arg.as_ptr()).collect::<Vec<_>>();
+
+        let buffer = DxcBuffer {
+            ptr: source.as_ptr() as *const c_void,
+            size: source.len(),
+            encoding: DXC_CP_UTF8,
+        };
+
+        let include_handler = IncludeHandler::create(self.utils.clone());
+
+        unsafe {
+            let mut result = null_mut();
+            let hr = (self.compiler.vtbl().compile)(self.compiler.as_raw(), &buffer, arg_ptrs.as_ptr(), arg_ptrs.len() as u32, include_handler.as_raw(), &IID_IDXC_RESULT, &mut result);
+            let result = ComPtr::<IDxcResultVtbl>::from_result(hr, result)
+                .map_err(|hr| ShaderCompileError::Compilation(hresult_message(hr)))?;
+
+            let messages = get_messages(&result);
+            let mut status = S_OK;
+            let hr = (result.vtbl().get_status)(result.as_raw(), &mut status);
+            if hr < 0 {
+                return Err(ShaderCompileError::Compilation(hresult_message(hr)));
+            }
+            if status < 0 {
+                return Err(ShaderCompileError::Compilation(messages.unwrap_or_else(|| format!("Unknown error ({status:#010X})"))));
+            }
+
+            let object = get_output::<IDxcBlobVtbl>(&result, DXC_OUT_OBJECT, &IID_IDXC_BLOB)
+                .map_err(|hr| ShaderCompileError::Compilation(hresult_message(hr)))?;
+            let code = blob_to_vec(&object);
+
+            let reflection = if get_reflection && (result.vtbl().has_output)(result.as_raw(), DXC_OUT_REFLECTION) != 0 {
+                get_output::<IDxcBlobVtbl>(&result, DXC_OUT_REFLECTION, &IID_IDXC_BLOB).ok().map(|blob| blob_to_vec(&blob))
+            } else {
+                None
+            };
+
+            Ok(CompiledShader {
+                code,
+                reflection,
+                warnings: messages,
+            })
+        }
+    }
+}
+
+unsafe fn create_dxc_instance<V: ComVtbl>(create_instance: FnDxcCreateInstance, clsid: &Guid, iid: &Guid) -> Result<ComPtr<V>, ShaderCompileError> {
+    let mut instance = null_mut();
+    let hr = create_instance(clsid, iid, &mut instance);
+    ComPtr::from_result(hr, instance).map_err(|hr| ShaderCompileError::Initialize(hresult_message(hr)))
+}
+
+unsafe fn get_output<V: ComVtbl>(result: &ComPtr<IDxcResultVtbl>, kind: u32, iid: &Guid) -> Result<ComPtr<V>, HResult> {
+    let mut output = null_mut();
+    let hr = (result.vtbl().get_output)(result.as_raw(), kind, iid, &mut output, null_mut());
+    ComPtr::from_result(hr, output)
+}
+
+fn hresult_message(hr: HResult) -> String {
+    format!("DXC call failed with HRESULT {hr:#010X}")
+}
+
+fn to_wide(s: &str) -> Vec<WChar> {
+    s.chars().map(|c| c as WChar).chain(core::iter::once(0)).collect()
+}
+
+unsafe fn from_wide(s: *const WChar) -> Option<String> {
+    if s.is_null() {
+        return None;
+    }
+
+    let mut len = 0;
+    while *s.add(len) != 0 {
+        len += 1;
+    }
+    slice::from_raw_parts(s, len).iter().map(|c| char::from_u32(*c)).collect()
+}
+
+unsafe fn get_messages(result: &ComPtr<IDxcResultVtbl>) -> Option<String> {
+    if (result.vtbl().has_output)(result.as_raw(), DXC_OUT_ERRORS) == 0 {
+        return None;
+    }
+
+    let errors = get_output::<IDxcBlobUtf8Vtbl>(result, DXC_OUT_ERRORS, &IID_IDXC_BLOB_UTF8).ok()?;
+    let len = (errors.vtbl().get_string_length)(errors.as_raw());
+    if len == 0 {
+        return None;
+    }
+
+    let bytes = slice::from_raw_parts((errors.vtbl().get_string_pointer)(errors.as_raw()) as *const u8, len);
+    Some(String::from_utf8_lossy(bytes).into_owned())
+}
+
+unsafe fn blob_to_vec(blob: &ComPtr<IDxcBlobVtbl>) -> Vec<u8> {
+    slice::from_raw_parts((blob.vtbl().get_buffer_pointer)(blob.as_raw()) as *const u8, (blob.vtbl().get_buffer_size)(blob.as_raw())).to_vec()
+}
+
+//--------------------------------------------------------------
+// INCLUDE HANDLER
+//--------------------------------------------------------------
+
+/// Include handler that loads included files using `onca_fs`
+#[repr(C)]
+struct IncludeHandler {
+    // Needs to be the first field, as the object is passed to DXC as an `IDxcIncludeHandler`
+    vtbl:      *const IDxcIncludeHandlerVtbl,
+    ref_count: AtomicU32,
+    utils:     ComPtr<IDxcUtilsVtbl>,
+}
+
+static INCLUDE_HANDLER_VTBL: IDxcIncludeHandlerVtbl = IDxcIncludeHandlerVtbl {
+    base: IUnknownVtbl {
+        query_interface: IncludeHandler::query_interface,
+        add_ref: IncludeHandler::add_ref,
+        release: IncludeHandler::release,
+        #[cfg(not(windows))]
+        complete_dtor: IncludeHandler::complete_dtor,
+        #[cfg(not(windows))]
+        deleting_dtor: IncludeHandler::deleting_dtor,
+    },
+    load_source: IncludeHandler::load_source,
+};
+
+impl IncludeHandler {
+    fn create(utils: ComPtr<IDxcUtilsVtbl>) -> ComPtr<IDxcIncludeHandlerVtbl> {
+        let handler = Box::new(Self {
+            vtbl: &INCLUDE_HANDLER_VTBL,
+            ref_count: AtomicU32::new(1),
+            utils,
+        });
+        unsafe { ComPtr::from_raw(Box::into_raw(handler) as *mut c_void).unwrap_unchecked() }
+    }
+
+    unsafe extern "system" fn query_interface(this: *mut c_void, riid: *const Guid, ppv: *mut *mut c_void) -> HResult {
+        if riid.is_null() || ppv.is_null() {
+            return E_POINTER;
+        }
+
+        if *riid == IID_IUNKNOWN || *riid == IID_IDXC_INCLUDE_HANDLER {
+            Self::add_ref(this);
+            *ppv = this;
+            S_OK
+        } else {
+            *ppv = null_mut();
+            E_NOINTERFACE
+        }
+    }
+
+    unsafe extern "system" fn add_ref(this: *mut c_void) -> u32 {
+        let handler = &*(this as *const Self);
+        handler.ref_count.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    unsafe extern "system" fn release(this: *mut c_void) -> u32 {
+        let handler = &*(this as *const Self);
+        let ref_count = handler.ref_count.fetch_sub(1, Ordering::Release) - 1;
+        if ref_count == 0 {
+            fence(Ordering::Acquire);
+            drop(Box::from_raw(this as *mut Self));
+        }
+        ref_count
+    }
+
+    /// Destroy the handler without freeing its memory.
+    #[cfg(not(windows))]
+    unsafe extern "system" fn complete_dtor(this: *mut c_void) {
+        core::ptr::drop_in_place(this as *mut Self);
+    }
+
+    /// Destroy the handler and free its memory.
+    #[cfg(not(windows))]
+    unsafe extern "system" fn deleting_dtor(this: *mut c_void) {
+        drop(Box::from_raw(this as *mut Self));
+    }
+
+    unsafe extern "system" fn load_source(this: *mut c_void, filename: *const WChar, include_source: *mut *mut c_void) -> HResult {
+        if include_source.is_null() {
+            return E_POINTER;
+        }
+        *include_source = null_mut();
+
+        let handler = &*(this as *const Self);
+        let Some(path) = from_wide(filename) else { return E_FAIL; };
+        let Some(data) = load_include(&path) else { return E_FAIL; };
+
+        // `CreateBlob` copies the data, so it does not need to outlive this call.
+        // The returned `IDxcBlobEncoding` derives from `IDxcBlob`, so it can be returned as is.
+        (handler.utils.vtbl().create_blob)(handler.utils.as_raw(), data.as_ptr() as *const c_void, data.len() as u32, DXC_CP_UTF8, include_source)
+    }
+}
@@ -0,0 +1,130 @@
+use core::{ffi::c_void, ptr::null_mut, slice};
+
+use onca_common::dynlib::DynLib;
+use windows::{
+    core::{implement, ComInterface, Error as WinError, Interface, GUID, HRESULT, PCWSTR},
+    Win32::{
+        Foundation::E_FAIL,
+        Graphics::Direct3D::Dxc::*,
+    },
+};
+
+use crate::{include::load_include, CompiledShader, ShaderCompileError};
+
+type FnDxcCreateInstance = unsafe extern "system" fn(rclsid: *const GUID, riid: *const GUID, ppv: *mut *mut c_void) -> HRESULT;
+
+pub(crate) struct Dxc {
+    utils:    IDxcUtils,
+    compiler: IDxcCompiler3,
+    // Needs to be dropped last, as the interfaces above live in the library
+    _dynlib:  DynLib,
+}
+
+impl Dxc {
+    pub(crate) fn load(library_path: &str) -> Result<Self, ShaderCompileError> {
+        let dynlib = DynLib::load(library_path).map_err(|_| ShaderCompileError::LoadLibrary(library_path.to_string()))?;
+        let create_instance = dynlib.get::<FnDxcCreateInstance>("DxcCreateInstance")
+            .ok_or_else(|| ShaderCompileError::LoadLibrary(format!("{library_path} (missing 'DxcCreateInstance')")))?;
+
+        let utils = unsafe { create_dxc_instance::<IDxcUtils>(create_instance, &CLSID_DxcUtils)? };
+        let compiler = unsafe { create_dxc_instance::<IDxcCompiler3>(create_instance, &CLSID_DxcCompiler)? };
+
+        Ok(Self {
+            utils,
+            compiler,
+            _dynlib: dynlib,
+        })
+    }
+
+    pub(crate) fn compile(&self, source: &str, args: &[String], get_reflection: bool) -> Result<CompiledShader, ShaderCompileError> {
+        let wide_args = args.iter().map(|arg| to_wide(arg)).collect::<Vec<_>>();
+        let arg_ptrs = wide_args.iter().map(|arg| PCWSTR(arg.as_ptr())).collect::<Vec<_>>();
+
+        let buffer = DxcBuffer {
+            Ptr: source.as_ptr() as *const c_void,
+            Size: source.len(),
+            Encoding: DXC_CP_UTF8.0,
+        };
+
+        let include_handler : IDxcIncludeHandler = IncludeHandler::new(self.utils.clone()).into();
+
+        unsafe {
+            let result : IDxcResult = self.compiler.Compile(&buffer, Some(&arg_ptrs), &include_handler)
+                .map_err(|err| ShaderCompileError::Compilation(err.message().to_string()))?;
+
+            let messages = get_messages(&result);
+            let status = result.GetStatus().map_err(|err| ShaderCompileError::Compilation(err.message().to_string()))?;
+            if status.is_err() {
+                return Err(ShaderCompileError::Compilation(messages.unwrap_or_else(|| format!("Unknown error ({status})"))));
+            }
+
+            let object : IDxcBlob = result.GetOutput(DXC_OUT_OBJECT, null_mut())
+                .map_err(|err| ShaderCompileError::Compilation(err.message().to_string()))?;
+            let code = blob_to_vec(&object);
+
+            let reflection = if get_reflection && result.HasOutput(DXC_OUT_REFLECTION).as_bool() {
+                result.GetOutput::<IDxcBlob>(DXC_OUT_REFLECTION, null_mut()).ok().map(|blob| blob_to_vec(&blob))
+            } else {
+                None
+            };
+
+            Ok(CompiledShader {
+                code,
+                reflection,
+                warnings: messages,
+            })
+        }
+    }
+}
+
+unsafe fn create_dxc_instance<T: ComInterface>(create_instance: FnDxcCreateInstance, clsid: &GUID) -> Result<T, ShaderCompileError> {
+    let mut instance = null_mut();
+    create_instance(clsid, &T::IID, &mut instance).ok().map_err(|err| ShaderCompileError::Initialize(err.message().to_string()))?;
+    Ok(T::from_raw(instance))
+}
+
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(core::iter::once(0)).collect()
+}
+
+unsafe fn get_messages(result: &IDxcResult) -> Option<String> {
+    if !result.HasOutput(DXC_OUT_ERRORS).as_bool() {
+        return None;
+    }
+
+    let errors : IDxcBlobUtf8 = result.GetOutput(DXC_OUT_ERRORS, null_mut()).ok()?;
+    let len = errors.GetStringLength();
+    if len == 0 {
+        return None;
+    }
+
+    let bytes = slice::from_raw_parts(errors.GetStringPointer().0, len);
+    Some(String::from_utf8_lossy(bytes).into_owned())
+}
+
+unsafe fn blob_to_vec(blob: &IDxcBlob) -> Vec<u8> {
+    slice::from_raw_parts(blob.GetBufferPointer() as *const u8, blob.GetBufferSize()).to_vec()
+}
+
+/// Include handler that loads included files using `onca_fs`
+#[implement(IDxcIncludeHandler)]
+struct IncludeHandler {
+    utils: IDxcUtils,
+}
+
+impl IncludeHandler {
+    fn new(utils: IDxcUtils) -> Self {
+        Self { utils }
+    }
+}
+
+impl IDxcIncludeHandler_Impl for IncludeHandler {
+    fn LoadSource(&self, pfilename: &PCWSTR) -> windows::core::Result<IDxcBlob> {
+        let path = unsafe { pfilename.to_string() }.map_err(|_| WinError::from(E_FAIL))?;
+        let data = load_include(&path).ok_or_else(|| WinError::from(E_FAIL))?;
+
+        // `CreateBlob` copies the data, so it does not need to outlive this call
+        let blob = unsafe { self.utils.CreateBlob(data.as_ptr() as *const c_void, data.len() as u32, DXC_CP_UTF8)? };
+        blob.cast()
+    }
+}
@@ -0,0 +1,263 @@
+use std::io::Write;
+use onca_common::io;
+
+use crate::{Terminal, TerminalColor, TextFormatting};
+
+/// Style of a cell, a color of `None` uses the default color of the terminal
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CellStyle {
+    /// Foreground color
+    pub fore:       Option<TerminalColor>,
+    /// Background color
+    pub back:       Option<TerminalColor>,
+    /// Formatting
+    pub formatting: TextFormatting,
+}
+
+impl CellStyle {
+    /// Create a cell style with the given colors and no formatting
+    pub fn new(fore: Option<TerminalColor>, back: Option<TerminalColor>) -> Self {
+        Self { fore, back, formatting: TextFormatting::None }
+    }
+}
+
+impl Default for CellStyle {
+    fn default() -> Self {
+        Self { fore: None, back: None, formatting: TextFormatting::None }
+    }
+}
+
+/// Single character cell of a terminal canvas
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Cell {
+    /// Character
+    pub ch:    char,
+    /// Style
+    pub style: CellStyle,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self { ch: ' ', style: CellStyle::default() }
+    }
+}
+
+/// Style of the lines of a box
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BoxStyle {
+    /// `┌─┐`
+    Single,
+    /// `╔═╗`
+    Double,
+    /// `╭─╮`
+    Rounded,
+    /// `┏━┓`
+    Heavy,
+    /// `+-+`, for terminals without box drawing characters
+    Ascii,
+}
+
+impl BoxStyle {
+    /// Get the characters: top-left, top-right, bottom-left, bottom-right, horizontal, vertical
+    fn chars(self) -> [char; 6] {
+        match self {
+            BoxStyle::Single  => ['┌', '┐', '└', '┘', '─', '│'],
+            BoxStyle::Double  => ['╔', '╗', '╚', '╝', '═', '║'],
+            BoxStyle::Rounded => ['╭', '╮', '╰', '╯', '─', '│'],
+            BoxStyle::Heavy   => ['┏', '┓', '┗', '┛', '━', '┃'],
+            BoxStyle::Ascii   => ['+', '+', '+', '+', '-', '|'],
+        }
+    }
+}
+
+/// Double-buffered grid of cells.
+/// 
+/// All drawing is done into the back buffer, when presenting, only the cells that differ from what is currently on screen are written,
+/// using as few escape codes as possible, which avoids flickering and reduces the number of bytes written compared to redrawing everything.
+/// 
+/// Coordinates are 0-based, with (0, 0) being the top-left of the terminal, anything drawn outside of the canvas is clipped.
+pub struct TerminalCanvas {
+    width:        u16,
+    height:       u16,
+    front:        Vec<Cell>,
+    back:         Vec<Cell>,
+    full_redraw:  bool,
+}
+
+impl TerminalCanvas {
+    /// Create a canvas with the given size
+    pub fn new(width: u16, height: u16) -> Self {
+        let size = width as usize * height as usize;
+        Self {
+            width,
+            height,
+            front: vec![Cell::default(); size],
+            back: vec![Cell::default(); size],
+            full_redraw: true,
+        }
+    }
+
+    /// Create a canvas with the size of the terminal
+    pub fn with_terminal_size() -> io::Result<Self> {
+        let (width, height) = Terminal::size()?;
+        Ok(Self::new(width, height))
+    }
+
+    /// Get the width of the canvas
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// Get the height of the canvas
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Resize the canvas, the content is cleared and everything will be redrawn on the next present
+    pub fn resize(&mut self, width: u16, height: u16) {
+        let size = width as usize * height as usize;
+        self.width = width;
+        self.height = height;
+        self.front = vec![Cell::default(); size];
+        self.back = vec![Cell::default(); size];
+        self.full_redraw = true;
+    }
+
+    /// Force everything to be redrawn on the next present, e.g. when something else has written to the terminal
+    pub fn invalidate(&mut self) {
+        self.full_redraw = true;
+    }
+
+    /// Clear the back buffer
+    pub fn clear(&mut self) {
+        self.back.fill(Cell::default());
+    }
+
+    /// Get a cell from the back buffer
+    pub fn get(&self, x: u16, y: u16) -> Option<&Cell> {
+        self.index(x, y).map(|idx| &self.back[idx])
+    }
+
+    /// Set a cell in the back buffer
+    pub fn set(&mut self, x: u16, y: u16, cell: Cell) {
+        if let Some(idx) = self.index(x, y) {
+            self.back[idx] = cell;
+        }
+    }
+
+    /// Draw text starting at the given position, the text does not wrap, returns the number of characters that were drawn
+    pub fn draw_text(&mut self, x: u16, y: u16, text: &str, style: CellStyle) -> u16 {
+        let mut drawn = 0;
+        for (offset, ch) in text.chars().filter(|ch| !ch.is_control()).enumerate() {
+            let Some(cur_x) = x.checked_add(offset as u16) else { break };
+            if cur_x >= self.width {
+                break;
+            }
+            self.set(cur_x, y, Cell { ch, style });
+            drawn += 1;
+        }
+        drawn
+    }
+
+    /// Fill a region with the given cell
+    pub fn fill(&mut self, x: u16, y: u16, width: u16, height: u16, cell: Cell) {
+        let end_x = x.saturating_add(width).min(self.width);
+        let end_y = y.saturating_add(height).min(self.height);
+        for cur_y in y..end_y {
+            for cur_x in x..end_x {
+                self.set(cur_x, cur_y, cell);
+            }
+        }
+    }
+
+    /// Draw the outline of a box, the box includes the given position and size
+    pub fn draw_box(&mut self, x: u16, y: u16, width: u16, height: u16, box_style: BoxStyle, style: CellStyle) {
+        if width < 2 || height < 2 {
+            return;
+        }
+        let [top_left, top_right, bottom_left, bottom_right, horizontal, vertical] = box_style.chars();
+        let right = x.saturating_add(width - 1);
+        let bottom = y.saturating_add(height - 1);
+
+        for cur_x in x + 1..right {
+            self.set(cur_x, y, Cell { ch: horizontal, style });
+            self.set(cur_x, bottom, Cell { ch: horizontal, style });
+        }
+        for cur_y in y + 1..bottom {
+            self.set(x, cur_y, Cell { ch: vertical, style });
+            self.set(right, cur_y, Cell { ch: vertical, style });
+        }
+        self.set(x, y, Cell { ch: top_left, style });
+        self.set(right, y, Cell { ch: top_right, style });
+        self.set(x, bottom, Cell { ch: bottom_left, style });
+        self.set(right, bottom, Cell { ch: bottom_right, style });
+    }
+
+    /// Write the differences between the back buffer and the screen to the terminal, returns the number of bytes written
+    pub fn present(&mut self) -> io::Result<usize> {
+        let mut buf = Vec::new();
+        self.render_diff(&mut buf);
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        Terminal::write_bytes(&buf)
+    }
+
+    /// Write the escape codes and text needed to update the screen to the back buffer into `buf`, and mark the back buffer as presented
+    pub fn render_diff(&mut self, buf: &mut Vec<u8>) {
+        let mut cursor: Option<(u16, u16)> = None;
+        let mut cur_style: Option<CellStyle> = None;
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let idx = y as usize * self.width as usize + x as usize;
+                let cell = self.back[idx];
+                if !self.full_redraw && self.front[idx] == cell {
+                    continue;
+                }
+
+                if cursor != Some((x, y)) {
+                    _ = write!(buf, "\x1B[{};{}H", y + 1, x + 1);
+                }
+                Self::write_style_change(buf, cur_style, cell.style);
+                cur_style = Some(cell.style);
+
+                let mut utf8 = [0u8; 4];
+                buf.extend_from_slice(cell.ch.encode_utf8(&mut utf8).as_bytes());
+                cursor = Some((x + 1, y));
+            }
+        }
+
+        if cur_style.is_some() {
+            _ = write!(buf, "\x1B[0m");
+        }
+        self.front.copy_from_slice(&self.back);
+        self.full_redraw = false;
+    }
+
+    fn write_style_change(buf: &mut Vec<u8>, prev: Option<CellStyle>, style: CellStyle) {
+        if prev.map_or(true, |prev| prev.fore != style.fore) {
+            match style.fore {
+                Some(color) => _ = color.write_fore_escape_code(buf),
+                None        => _ = write!(buf, "\x1B[39m"),
+            }
+        }
+        if prev.map_or(true, |prev| prev.back != style.back) {
+            match style.back {
+                Some(color) => _ = color.write_back_escape_code(buf),
+                None        => _ = write!(buf, "\x1B[49m"),
+            }
+        }
+        if prev.map_or(true, |prev| prev.formatting != style.formatting) {
+            _ = style.formatting.write_escape_code(buf);
+        }
+    }
+
+    fn index(&self, x: u16, y: u16) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(y as usize * self.width as usize + x as usize)
+        } else {
+            None
+        }
+    }
+}
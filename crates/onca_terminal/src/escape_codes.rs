@@ -142,22 +142,22 @@ impl CursorMove {
     /// Get an escape code representing the terminal cursor move
     pub fn to_escape_code(&self) -> String {
         match self {
-            CursorMove::Up(n)          => format!("\0x1B[{n}A"),
-            CursorMove::Down(n)        => format!("\0x1B[{n}B"),
-            CursorMove::Forward(n)     => format!("\0x1B[{n}C"),
-            CursorMove::Backward(n)    => format!("\0x1B[{n}D"),
-            CursorMove::Position(x, y) => format!("\0x1B[{y};{x}H"),
+            CursorMove::Up(n)          => format!("\x1B[{n}A"),
+            CursorMove::Down(n)        => format!("\x1B[{n}B"),
+            CursorMove::Forward(n)     => format!("\x1B[{n}C"),
+            CursorMove::Backward(n)    => format!("\x1B[{n}D"),
+            CursorMove::Position(x, y) => format!("\x1B[{y};{x}H"),
         }
     }
 
     /// Write the terminal cursor move escape code to an `io::Write`
     pub fn write_escape_code(&self, writer: &mut dyn io::Write) -> io::Result<()> {
         match self {
-            CursorMove::Up(n)          => write!(writer, "\0x1B[{n}A"),
-            CursorMove::Down(n)        => write!(writer, "\0x1B[{n}B"),
-            CursorMove::Forward(n)     => write!(writer, "\0x1B[{n}C"),
-            CursorMove::Backward(n)    => write!(writer, "\0x1B[{n}D"),
-            CursorMove::Position(x, y) => write!(writer, "\0x1B[{y};{x}H"),
+            CursorMove::Up(n)          => write!(writer, "\x1B[{n}A"),
+            CursorMove::Down(n)        => write!(writer, "\x1B[{n}B"),
+            CursorMove::Forward(n)     => write!(writer, "\x1B[{n}C"),
+            CursorMove::Backward(n)    => write!(writer, "\x1B[{n}D"),
+            CursorMove::Position(x, y) => write!(writer, "\x1B[{y};{x}H"),
         }
     }
 }
@@ -216,6 +216,10 @@ pub enum CursorAction {
     Show(bool),
     /// Does the cursor blink
     Blink(bool),
+    /// Save the cursor position and formatting
+    Save,
+    /// Restore the cursor position and formatting that was last saved
+    Restore,
 }
 
 impl CursorAction {
@@ -232,6 +236,8 @@ impl CursorAction {
             } else {
                 "\x1B[?12l"
             },
+            CursorAction::Save    => "\x1B7",
+            CursorAction::Restore => "\x1B8",
         }
     }
 
@@ -248,6 +254,8 @@ impl CursorAction {
             } else {
                 write!(writer, "\x1B[?12l")
             },
+            CursorAction::Save    => write!(writer, "\x1B7"),
+            CursorAction::Restore => write!(writer, "\x1B8"),
         }
     }
 }
@@ -345,6 +353,33 @@ impl TextFormatting {
     }
 }
 
+/// Screen buffer
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScreenBuffer {
+    /// Main screen buffer, containing the scrollback of the shell
+    Main,
+    /// Alternate screen buffer, without scrollback, used by full-screen applications
+    Alternate,
+}
+
+impl ScreenBuffer {
+    /// Get an escape code to switch to the screen buffer
+    pub fn to_escape_code(&self) -> &str {
+        match self {
+            ScreenBuffer::Main      => "\x1B[?1049l",
+            ScreenBuffer::Alternate => "\x1B[?1049h",
+        }
+    }
+
+    /// Write the escape code to switch to the screen buffer to an `io::Write`
+    pub fn write_escape_code(&self, writer: &mut dyn io::Write) -> io::Result<()> {
+        match self {
+            ScreenBuffer::Main      => write!(writer, "\x1B[?1049l"),
+            ScreenBuffer::Alternate => write!(writer, "\x1B[?1049h"),
+        }
+    }
+}
+
 /// Cursor blinking
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum CursorBlink {
@@ -371,3 +406,45 @@ impl CursorBlink {
         }
     }
 }
+
+/// Hyperlink (OSC 8), terminals without support for hyperlinks only display the text
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Hyperlink<'a> {
+    /// Text to display
+    pub text: &'a str,
+    /// URI the text links to, e.g. `file:///path/to/asset.png`
+    pub uri:  &'a str,
+}
+
+impl Hyperlink<'_> {
+    /// Get an escape code representing the hyperlink, including its text
+    pub fn to_escape_code(&self) -> String {
+        format!("\x1B]8;;{}\x1B\\{}\x1B]8;;\x1B\\", strip_control_chars(self.uri), self.text)
+    }
+
+    /// Write the hyperlink escape code, including its text, to an `io::Write`
+    pub fn write_escape_code(&self, writer: &mut dyn io::Write) -> io::Result<()> {
+        write!(writer, "\x1B]8;;{}\x1B\\{}\x1B]8;;\x1B\\", strip_control_chars(self.uri), self.text)
+    }
+}
+
+/// Title of the terminal window (OSC 2)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct WindowTitle<'a>(pub &'a str);
+
+impl WindowTitle<'_> {
+    /// Get an escape code to set the window title
+    pub fn to_escape_code(&self) -> String {
+        format!("\x1B]2;{}\x1B\\", strip_control_chars(self.0))
+    }
+
+    /// Write the escape code to set the window title to an `io::Write`
+    pub fn write_escape_code(&self, writer: &mut dyn io::Write) -> io::Result<()> {
+        write!(writer, "\x1B]2;{}\x1B\\", strip_control_chars(self.0))
+    }
+}
+
+/// Remove control characters, as they would terminate an OSC sequence early
+fn strip_control_chars(s: &str) -> String {
+    s.chars().filter(|c| !c.is_control()).collect()
+}
@@ -0,0 +1,68 @@
+use std::sync::{
+    Once,
+    atomic::{AtomicBool, Ordering},
+};
+use onca_common::io;
+
+use crate::{Terminal, CursorAction};
+
+/// Is the alternate screen currently active, used by the panic hook to know if the main screen needs to be restored
+static ALTERNATE_SCREEN_ACTIVE: AtomicBool = AtomicBool::new(false);
+static PANIC_HOOK: Once = Once::new();
+
+pub(crate) fn set_alternate_screen_active(active: bool) {
+    ALTERNATE_SCREEN_ACTIVE.store(active, Ordering::Release);
+}
+
+/// Install a panic hook that restores the main screen before the panic message is written, so the message does not get lost when the alternate screen is left
+fn install_panic_hook() {
+    PANIC_HOOK.call_once(|| {
+        let prev_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            if ALTERNATE_SCREEN_ACTIVE.load(Ordering::Acquire) {
+                restore_terminal();
+            }
+            prev_hook(info);
+        }));
+    });
+}
+
+fn restore_terminal() {
+    _ = Terminal::set_raw_mode(false);
+    Terminal::cursor_action(CursorAction::Show(true));
+    Terminal::reset_color_and_formatting();
+    Terminal::leave_alternate_screen();
+}
+
+/// Guard for a full-screen terminal application.
+/// 
+/// Switches to the alternate screen buffer when created and restores the main screen, cursor and input mode when dropped, including when a panic occurs,
+/// so the user's shell is never left in a corrupted state.
+pub struct FullScreenGuard {
+    was_raw: bool,
+}
+
+impl FullScreenGuard {
+    /// Enter full-screen mode, optionally enabling raw mode for immediate key input.
+    pub fn new(raw_mode: bool) -> io::Result<Self> {
+        install_panic_hook();
+
+        let was_raw = Terminal::is_raw_mode();
+        if raw_mode && !was_raw {
+            Terminal::set_raw_mode(true)?;
+        }
+        Terminal::enter_alternate_screen();
+        Ok(Self { was_raw })
+    }
+}
+
+impl Drop for FullScreenGuard {
+    fn drop(&mut self) {
+        Terminal::cursor_action(CursorAction::Show(true));
+        Terminal::reset_color_and_formatting();
+        Terminal::leave_alternate_screen();
+        if !self.was_raw {
+            _ = Terminal::set_raw_mode(false);
+        }
+    }
+}
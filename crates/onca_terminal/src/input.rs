@@ -0,0 +1,246 @@
+use onca_common::sync::Mutex;
+use onca_common_macros::flags;
+
+/// Key read from the terminal
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Key {
+    /// Printable character
+    Char(char),
+    Enter,
+    Tab,
+    Backspace,
+    Escape,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Insert,
+    Delete,
+    /// Function key, e.g. `F(1)` is F1
+    F(u8),
+}
+
+/// Key modifiers
+#[flags]
+pub enum KeyModifiers {
+    Shift,
+    Alt,
+    Ctrl,
+}
+
+/// Key event read from the terminal
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KeyEvent {
+    /// Key
+    pub key:       Key,
+    /// Modifiers held while the key was pressed
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyEvent {
+    /// Create a key event without any modifiers
+    pub fn new(key: Key) -> Self {
+        Self { key, modifiers: KeyModifiers::None }
+    }
+
+    /// Create a key event with the given modifiers
+    pub fn with_modifiers(key: Key, modifiers: KeyModifiers) -> Self {
+        Self { key, modifiers }
+    }
+
+    /// Check if the key event is Ctrl+C
+    pub fn is_ctrl_c(&self) -> bool {
+        self.key == Key::Char('c') && self.modifiers.contains(KeyModifiers::Ctrl)
+    }
+}
+
+/// How to read input from the terminal
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReadMode {
+    /// Wait until input is available
+    Blocking,
+    /// Return immediately when no input is available
+    NonBlocking,
+}
+
+/// Interrupt sent to the terminal
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InterruptKind {
+    /// Ctrl+C was pressed
+    CtrlC,
+    /// Ctrl+Break was pressed
+    CtrlBreak,
+}
+
+/// Interrupt handler, returns `true` when the interrupt was handled and the process should not be terminated
+pub type InterruptHandler = Box<dyn Fn(InterruptKind) -> bool + Send + Sync>;
+
+static INTERRUPT_HANDLER: Mutex<Option<InterruptHandler>> = Mutex::new(None);
+
+pub(crate) fn set_interrupt_handler(handler: Option<InterruptHandler>) {
+    *INTERRUPT_HANDLER.lock() = handler;
+}
+
+/// Called by the OS backend when an interrupt is received.
+/// 
+/// This locks the handler and runs user code, so it must never be called from within a signal handler.
+pub(crate) fn dispatch_interrupt(kind: InterruptKind) -> bool {
+    match &*INTERRUPT_HANDLER.lock() {
+        Some(handler) => handler(kind),
+        None => false,
+    }
+}
+
+//==============================================================================================================================
+
+/// Decoder for VT input sequences
+pub(crate) struct VtDecoder {
+    pending: Vec<u8>,
+}
+
+impl VtDecoder {
+    pub(crate) const fn new() -> Self {
+        Self { pending: Vec::new() }
+    }
+
+    /// Add bytes read from the terminal
+    pub(crate) fn push(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+    }
+
+    /// Decode the next key.
+    /// 
+    /// Returns `None` if there are not enough bytes to decode a key.
+    /// When `flush` is set, no more input is expected, so an incomplete escape sequence is returned as a lone `Escape` key.
+    pub(crate) fn next(&mut self, flush: bool) -> Option<KeyEvent> {
+        loop {
+            let (consumed, event) = Self::decode(&self.pending, flush)?;
+            self.pending.drain(..consumed);
+            if event.is_some() {
+                return event;
+            }
+        }
+    }
+
+    /// Decode a key from the start of `bytes`, returns the number of consumed bytes and the key, the key is `None` for unknown sequences that should be skipped
+    fn decode(bytes: &[u8], flush: bool) -> Option<(usize, Option<KeyEvent>)> {
+        let first = *bytes.first()?;
+        if first != 0x1B {
+            return Self::decode_char(bytes, flush).map(|(len, event)| (len, Some(event)));
+        }
+
+        match bytes.get(1) {
+            None => flush.then_some((1, Some(KeyEvent::new(Key::Escape)))),
+            Some(b'[') => match Self::decode_csi(&bytes[2..]) {
+                Some((len, event)) => Some((len + 2, event)),
+                None => flush.then_some((1, Some(KeyEvent::new(Key::Escape)))),
+            },
+            Some(b'O') => match bytes.get(2) {
+                Some(b) => Some((3, Self::decode_ss3(*b).map(KeyEvent::new))),
+                None => flush.then_some((1, Some(KeyEvent::new(Key::Escape)))),
+            },
+            // Escape followed by a key is sent when alt is held
+            Some(_) => match Self::decode_char(&bytes[1..], flush) {
+                Some((len, mut event)) => {
+                    event.modifiers |= KeyModifiers::Alt;
+                    Some((len + 1, Some(event)))
+                },
+                None => flush.then_some((1, Some(KeyEvent::new(Key::Escape)))),
+            },
+        }
+    }
+
+    fn decode_char(bytes: &[u8], flush: bool) -> Option<(usize, KeyEvent)> {
+        let first = bytes[0];
+        let event = match first {
+            b'\r' | b'\n'  => KeyEvent::new(Key::Enter),
+            b'\t'          => KeyEvent::new(Key::Tab),
+            0x7F | 0x08    => KeyEvent::new(Key::Backspace),
+            0x1B           => KeyEvent::new(Key::Escape),
+            0x00           => KeyEvent::with_modifiers(Key::Char(' '), KeyModifiers::Ctrl),
+            0x01..=0x1A    => KeyEvent::with_modifiers(Key::Char((b'a' + first - 1) as char), KeyModifiers::Ctrl),
+            0x1C..=0x1F    => KeyEvent::with_modifiers(Key::Char((b'\\' + first - 0x1C) as char), KeyModifiers::Ctrl),
+            _ => {
+                let len = match first {
+                    0x00..=0x7F => 1,
+                    0xC0..=0xDF => 2,
+                    0xE0..=0xEF => 3,
+                    0xF0..=0xF7 => 4,
+                    // Invalid leading byte, skip it
+                    _ => return Some((1, KeyEvent::new(Key::Char(char::REPLACEMENT_CHARACTER)))),
+                };
+                if bytes.len() < len {
+                    return flush.then_some((bytes.len(), KeyEvent::new(Key::Char(char::REPLACEMENT_CHARACTER))));
+                }
+                let ch = core::str::from_utf8(&bytes[..len]).ok().and_then(|s| s.chars().next()).unwrap_or(char::REPLACEMENT_CHARACTER);
+                return Some((len, KeyEvent::new(Key::Char(ch))));
+            }
+        };
+        Some((1, event))
+    }
+
+    /// Decode a control sequence (after `ESC [`), returns `None` if the sequence is incomplete
+    fn decode_csi(bytes: &[u8]) -> Option<(usize, Option<KeyEvent>)> {
+        let final_idx = bytes.iter().position(|b| (0x40..=0x7E).contains(b))?;
+        let final_byte = bytes[final_idx];
+
+        let params = core::str::from_utf8(&bytes[..final_idx]).unwrap_or("");
+        let mut params = params.split(';').map(|param| param.parse::<u8>().unwrap_or(1));
+        let first = params.next().unwrap_or(1);
+        let modifiers = Self::decode_modifiers(params.next().unwrap_or(1));
+
+        let key = match final_byte {
+            b'~' => match first {
+                1 | 7   => Some(Key::Home),
+                2       => Some(Key::Insert),
+                3       => Some(Key::Delete),
+                4 | 8   => Some(Key::End),
+                5       => Some(Key::PageUp),
+                6       => Some(Key::PageDown),
+                11..=15 => Some(Key::F(first - 10)),
+                17..=21 => Some(Key::F(first - 11)),
+                23 | 24 => Some(Key::F(first - 12)),
+                _       => None,
+            },
+            b'Z' => return Some((final_idx + 1, Some(KeyEvent::with_modifiers(Key::Tab, modifiers | KeyModifiers::Shift)))),
+            b => Self::decode_ss3(b),
+        };
+        Some((final_idx + 1, key.map(|key| KeyEvent::with_modifiers(key, modifiers))))
+    }
+
+    /// Decode the final byte of a cursor key or `ESC O` sequence
+    fn decode_ss3(b: u8) -> Option<Key> {
+        match b {
+            b'A' => Some(Key::Up),
+            b'B' => Some(Key::Down),
+            b'C' => Some(Key::Right),
+            b'D' => Some(Key::Left),
+            b'H' => Some(Key::Home),
+            b'F' => Some(Key::End),
+            b'P' => Some(Key::F(1)),
+            b'Q' => Some(Key::F(2)),
+            b'R' => Some(Key::F(3)),
+            b'S' => Some(Key::F(4)),
+            _    => None,
+        }
+    }
+
+    fn decode_modifiers(param: u8) -> KeyModifiers {
+        let bits = param.saturating_sub(1);
+        let mut modifiers = KeyModifiers::None;
+        if bits & 1 != 0 {
+            modifiers |= KeyModifiers::Shift;
+        }
+        if bits & 2 != 0 {
+            modifiers |= KeyModifiers::Alt;
+        }
+        if bits & 4 != 0 {
+            modifiers |= KeyModifiers::Ctrl;
+        }
+        modifiers
+    }
+}
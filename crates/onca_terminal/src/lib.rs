@@ -1,22 +1,48 @@
 use std::{
     cell::RefCell,
     io::Write,
+    sync::atomic::{AtomicBool, Ordering},
 };
 use onca_common::{
     prelude::*,
     io,
+    sync::Mutex,
 };
 
 mod escape_codes;
 pub use escape_codes::*;
 
+mod input;
+pub use input::*;
+
+mod resize;
+pub use resize::*;
+
+mod full_screen;
+pub use full_screen::*;
+
+mod canvas;
+pub use canvas::*;
+
+mod strip;
+pub use strip::*;
+
+mod progress;
+pub use progress::*;
+
+mod writer;
+pub use writer::*;
+
 mod os;
 use os::os_imp;
 
-/// Terminal I/O (currently only write is supported)
+/// Terminal I/O
 pub struct Terminal;
 pub type TerminalIOHandle = os_imp::IOHandle;
 
+static INPUT_DECODER: Mutex<VtDecoder> = Mutex::new(VtDecoder::new());
+static RAW_MODE: AtomicBool = AtomicBool::new(false);
+
 impl Terminal {
     /// Initialize the terminal, if it isn't initialized yet
     pub fn init() -> io::Result<()> {
@@ -86,6 +112,48 @@ impl Terminal {
         })
     }
 
+    /// Save the cursor position and formatting
+    pub fn save_cursor() {
+        Self::cursor_action(CursorAction::Save)
+    }
+
+    /// Restore the cursor position and formatting that was last saved
+    pub fn restore_cursor() {
+        Self::cursor_action(CursorAction::Restore)
+    }
+
+    /// Switch to the alternate screen buffer, which leaves the content of the main screen untouched.
+    /// 
+    /// Prefer using a [`FullScreenGuard`], which also restores the main screen when a panic occurs.
+    pub fn enter_alternate_screen() {
+        Self::exec_terminal_sequence(|buf| {
+            _ = ScreenBuffer::Alternate.write_escape_code(buf);
+        });
+        full_screen::set_alternate_screen_active(true);
+    }
+
+    /// Switch back to the main screen buffer
+    pub fn leave_alternate_screen() {
+        full_screen::set_alternate_screen_active(false);
+        Self::exec_terminal_sequence(|buf| {
+            _ = ScreenBuffer::Main.write_escape_code(buf);
+        });
+    }
+
+    /// Write text that links to `uri`, e.g. a source file or asset path.
+    /// 
+    /// Terminals that don't support hyperlinks only display the text.
+    pub fn write_link(text: &str, uri: &str) -> io::Result<usize> {
+        Self::write(&Hyperlink { text, uri }.to_escape_code())
+    }
+
+    /// Set the title of the terminal window
+    pub fn set_title(title: &str) {
+        Self::exec_terminal_sequence(|buf| {
+            _ = WindowTitle(title).write_escape_code(buf);
+        })
+    }
+
     /// Set the cursor shape
     pub fn set_cursor_shape(shape: CursorShape) {
         Self::exec_terminal_sequence(|buf| {
@@ -160,6 +228,173 @@ impl Terminal {
     pub fn get_output_handle(&self) -> TerminalIOHandle {
         os_imp::Terminal::get_output_handle()
     }
+
+    /// Enable or disable raw mode.
+    /// 
+    /// In raw mode, input is not echoed and is available immediately instead of after a line was entered, Ctrl+C is also passed on as a key instead of as an interrupt.
+    pub fn set_raw_mode(enabled: bool) -> io::Result<()> {
+        os_imp::Terminal::set_raw_mode(enabled)?;
+        RAW_MODE.store(enabled, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Check if the terminal is in raw mode
+    pub fn is_raw_mode() -> bool {
+        RAW_MODE.load(Ordering::Relaxed)
+    }
+
+    /// Read a key from the terminal.
+    /// 
+    /// Outside of raw mode, keys only become available after a line was entered.
+    /// Returns `None` if no key is available when reading non-blocking, or when the end of the input has been reached.
+    pub fn read_key(mode: ReadMode) -> io::Result<Option<KeyEvent>> {
+        Self::poll_resize();
+
+        let mut decoder = INPUT_DECODER.lock();
+        loop {
+            let has_input = os_imp::Terminal::has_input()?;
+            if let Some(event) = decoder.next(!has_input) {
+                return Ok(Some(event));
+            }
+            if !has_input && mode == ReadMode::NonBlocking {
+                return Ok(None);
+            }
+
+            let mut buf = [0u8; 64];
+            let bytes_read = os_imp::Terminal::read_bytes(&mut buf)?;
+            if bytes_read == 0 {
+                return Ok(None);
+            }
+            decoder.push(&buf[..bytes_read]);
+        }
+    }
+
+    /// Read a line from the terminal, with basic line editing (cursor movement, backspace and delete).
+    /// 
+    /// Returns `None` when the line was cancelled using Ctrl+C, or when the end of the input has been reached before anything was entered.
+    pub fn read_line() -> io::Result<Option<String>> {
+        let was_raw = Self::is_raw_mode();
+        if !was_raw {
+            Self::set_raw_mode(true)?;
+        }
+        let res = Self::read_line_raw();
+        if !was_raw {
+            Self::set_raw_mode(false)?;
+        }
+        res
+    }
+
+    fn read_line_raw() -> io::Result<Option<String>> {
+        let mut line = Vec::<char>::new();
+        let mut cursor = 0;
+        loop {
+            let Some(event) = Self::read_key(ReadMode::Blocking)? else {
+                return Ok((!line.is_empty()).then(|| line.into_iter().collect()));
+            };
+
+            match event.key {
+                _ if event.is_ctrl_c() => {
+                    Self::write("^C\r\n")?;
+                    return Ok(None);
+                },
+                Key::Enter => {
+                    Self::write("\r\n")?;
+                    return Ok(Some(line.into_iter().collect()));
+                },
+                Key::Backspace if cursor > 0 => {
+                    cursor -= 1;
+                    line.remove(cursor);
+                    Self::move_cursor(CursorMove::Backward(1));
+                    Self::redraw_line_tail(&line[cursor..], 0)?;
+                },
+                Key::Delete if cursor < line.len() => {
+                    line.remove(cursor);
+                    Self::redraw_line_tail(&line[cursor..], 0)?;
+                },
+                Key::Left if cursor > 0 => {
+                    cursor -= 1;
+                    Self::move_cursor(CursorMove::Backward(1));
+                },
+                Key::Right if cursor < line.len() => {
+                    cursor += 1;
+                    Self::move_cursor(CursorMove::Forward(1));
+                },
+                Key::Home if cursor > 0 => {
+                    Self::move_cursor(CursorMove::Backward(cursor as u32));
+                    cursor = 0;
+                },
+                Key::End if cursor < line.len() => {
+                    Self::move_cursor(CursorMove::Forward((line.len() - cursor) as u32));
+                    cursor = line.len();
+                },
+                Key::Char(ch) if !event.modifiers.contains(KeyModifiers::Ctrl) && !event.modifiers.contains(KeyModifiers::Alt) => {
+                    line.insert(cursor, ch);
+                    Self::redraw_line_tail(&line[cursor..], 1)?;
+                    cursor += 1;
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /// Redraw the line from the cursor onwards, and move the cursor back, keeping it `advance` characters further
+    fn redraw_line_tail(tail: &[char], advance: usize) -> io::Result<()> {
+        let mut text: String = tail.iter().collect();
+        // Overwrite the character that was removed
+        text.push(' ');
+        Self::write(&text)?;
+        Self::move_cursor(CursorMove::Backward((tail.len() + 1 - advance) as u32));
+        Ok(())
+    }
+
+    /// Get the size of the terminal, as the number of columns and rows
+    pub fn size() -> io::Result<(u16, u16)> {
+        os_imp::Terminal::size()
+    }
+
+    /// Register a listener that is notified when the terminal is resized.
+    /// 
+    /// Listeners are notified from [`Terminal::poll_resize`], which is also called when reading input.
+    pub fn register_resize_listener(listener: ResizeListenerRef) {
+        if let Ok(size) = Self::size() {
+            resize::update_size(size);
+        }
+        resize::register_resize_listener(listener);
+    }
+
+    /// Unregister a resize listener
+    pub fn unregister_resize_listener(listener: &ResizeListenerRef) {
+        resize::unregister_resize_listener(listener);
+    }
+
+    /// Check if the terminal was resized since the last poll, and notify the resize listeners if it was.
+    /// 
+    /// Tools with an update loop should call this each iteration, so resizes are noticed without needing to read input.
+    pub fn poll_resize() {
+        if !os_imp::Terminal::take_resized() {
+            return;
+        }
+        if let Ok(size) = Self::size() {
+            resize::update_size(size);
+        }
+    }
+
+    /// Set the handler that is called when Ctrl+C or Ctrl+Break is pressed while the terminal is not in raw mode.
+    /// 
+    /// When the handler returns `true`, the interrupt is handled and the process will not be terminated.
+    /// The handler is called from a separate thread, not from the thread that is reading input.
+    pub fn set_interrupt_handler<F>(handler: F) -> io::Result<()> where
+        F: Fn(InterruptKind) -> bool + Send + Sync + 'static
+    {
+        input::set_interrupt_handler(Some(Box::new(handler)));
+        os_imp::Terminal::register_interrupt_handler(true)
+    }
+
+    /// Remove the interrupt handler, restoring the default behavior of terminating the process
+    pub fn clear_interrupt_handler() -> io::Result<()> {
+        input::set_interrupt_handler(None);
+        os_imp::Terminal::register_interrupt_handler(false)
+    }
 }
 
 impl io::Write for Terminal {
@@ -0,0 +1,222 @@
+use std::{
+    sync::atomic::{AtomicBool, AtomicI32, Ordering},
+    thread,
+};
+use onca_common::{io, sync::Mutex};
+
+use crate::InterruptKind;
+
+/// Terminal settings before raw mode was enabled
+static ORIGINAL_TERMIOS: Mutex<Option<libc::termios>> = Mutex::new(None);
+/// Was a SIGWINCH received since the last check
+static RESIZED: AtomicBool = AtomicBool::new(false);
+/// Is the SIGWINCH handler installed
+static RESIZE_HANDLER_REGISTERED: AtomicBool = AtomicBool::new(false);
+/// Are the interrupt signal handlers installed
+static INTERRUPT_HANDLER_REGISTERED: AtomicBool = AtomicBool::new(false);
+/// Write end of the pipe the interrupt signal handler passes received signals through, -1 until the interrupt thread is started
+static INTERRUPT_PIPE: AtomicI32 = AtomicI32::new(-1);
+
+pub struct Terminal;
+
+pub type IOHandle = libc::c_int;
+
+impl Terminal {
+    pub(crate) fn init() -> io::Result<()> {
+        // Posix terminals understand VT sequences out of the box, so only the resize handler needs to be set up
+        if !RESIZE_HANDLER_REGISTERED.swap(true, Ordering::AcqRel) {
+            set_signal_handler(libc::SIGWINCH, resize_handler as libc::sighandler_t)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn write(text: &str) -> io::Result<usize> {
+        Terminal::write_bytes(text.as_bytes())
+    }
+
+    pub(crate) fn write_bytes(bytes: &[u8]) -> io::Result<usize> {
+        let mut written = 0;
+        while written < bytes.len() {
+            let res = unsafe { libc::write(libc::STDOUT_FILENO, bytes[written..].as_ptr() as *const libc::c_void, bytes.len() - written) };
+            if res < 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(err);
+            }
+            written += res as usize;
+        }
+        Ok(written)
+    }
+
+    pub(crate) fn get_output_handle() -> IOHandle {
+        libc::STDOUT_FILENO
+    }
+
+    pub(crate) fn set_raw_mode(enabled: bool) -> io::Result<()> {
+        let mut original = ORIGINAL_TERMIOS.lock();
+        if enabled {
+            let mut termios = unsafe { core::mem::zeroed::<libc::termios>() };
+            check(unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) })?;
+            if original.is_none() {
+                *original = Some(termios);
+            }
+
+            // Output processing is left enabled, so '\n' still moves to the start of the next line
+            termios.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN);
+            termios.c_iflag &= !(libc::IXON | libc::ICRNL | libc::BRKINT | libc::INPCK | libc::ISTRIP);
+            termios.c_cflag |= libc::CS8;
+            termios.c_cc[libc::VMIN] = 1;
+            termios.c_cc[libc::VTIME] = 0;
+            check(unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) })
+        } else if let Some(termios) = original.take() {
+            check(unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) })
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn has_input() -> io::Result<bool> {
+        let mut fd = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+        loop {
+            let res = unsafe { libc::poll(&mut fd, 1, 0) };
+            if res < 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(err);
+            }
+            // A hang-up is reported as readable, so the following read can report the end of the input
+            return Ok(res > 0 && fd.revents & (libc::POLLIN | libc::POLLHUP) != 0);
+        }
+    }
+
+    pub(crate) fn read_bytes(buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let res = unsafe { libc::read(libc::STDIN_FILENO, buf.as_mut_ptr() as *mut libc::c_void, buf.len()) };
+            if res < 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(err);
+            }
+            return Ok(res as usize);
+        }
+    }
+
+    pub(crate) fn size() -> io::Result<(u16, u16)> {
+        let mut size = unsafe { core::mem::zeroed::<libc::winsize>() };
+        check(unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) })?;
+        Ok((size.ws_col, size.ws_row))
+    }
+
+    /// Check if the terminal was resized since the last call, based on whether a SIGWINCH was received.
+    pub(crate) fn take_resized() -> bool {
+        // Without the handler, resizes can't be detected, so let the caller compare sizes
+        !RESIZE_HANDLER_REGISTERED.load(Ordering::Acquire) || RESIZED.swap(false, Ordering::AcqRel)
+    }
+
+    pub(crate) fn register_interrupt_handler(enabled: bool) -> io::Result<()> {
+        if INTERRUPT_HANDLER_REGISTERED.swap(enabled, Ordering::AcqRel) == enabled {
+            return Ok(());
+        }
+        if enabled && INTERRUPT_PIPE.load(Ordering::Acquire) < 0 {
+            if let Err(err) = start_interrupt_thread() {
+                INTERRUPT_HANDLER_REGISTERED.store(false, Ordering::Release);
+                return Err(err);
+            }
+        }
+        let handler = if enabled { interrupt_handler as libc::sighandler_t } else { libc::SIG_DFL };
+        set_signal_handler(libc::SIGINT, handler)?;
+        set_signal_handler(libc::SIGQUIT, handler)
+    }
+}
+
+fn check(res: libc::c_int) -> io::Result<()> {
+    if res < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+fn set_signal_handler(signal: libc::c_int, handler: libc::sighandler_t) -> io::Result<()> {
+    unsafe {
+        let mut action = core::mem::zeroed::<libc::sigaction>();
+        action.sa_sigaction = handler;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        check(libc::sigaction(signal, &action, core::ptr::null_mut()))
+    }
+}
+
+extern "C" fn resize_handler(_signal: libc::c_int) {
+    RESIZED.store(true, Ordering::Release);
+}
+
+/// Start the thread that runs the interrupt handler.
+/// 
+/// Only async-signal-safe functions can be called from a signal handler, so it can neither lock the interrupt handler nor run it.
+/// Instead, the signal handler writes the signal to a pipe, which is read by this thread.
+fn start_interrupt_thread() -> io::Result<()> {
+    let mut fds = [0; 2];
+    check(unsafe { libc::pipe(fds.as_mut_ptr()) })?;
+    let [read_fd, write_fd] = fds;
+    unsafe {
+        // When the pipe is full, an interrupt is already pending, so the signal handler should drop the signal instead of blocking
+        libc::fcntl(write_fd, libc::F_SETFL, libc::fcntl(write_fd, libc::F_GETFL) | libc::O_NONBLOCK);
+        libc::fcntl(read_fd, libc::F_SETFD, libc::FD_CLOEXEC);
+        libc::fcntl(write_fd, libc::F_SETFD, libc::FD_CLOEXEC);
+    }
+
+    let res = thread::Builder::new()
+        .name("onca_terminal interrupt".to_string())
+        .spawn(move || interrupt_thread(read_fd));
+    if let Err(err) = res {
+        unsafe {
+            libc::close(read_fd);
+            libc::close(write_fd);
+        }
+        return Err(err);
+    }
+
+    INTERRUPT_PIPE.store(write_fd, Ordering::Release);
+    Ok(())
+}
+
+fn interrupt_thread(read_fd: libc::c_int) {
+    loop {
+        let mut signal = 0u8;
+        let res = unsafe { libc::read(read_fd, &mut signal as *mut u8 as *mut libc::c_void, 1) };
+        if res < 0 {
+            if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return;
+        }
+        if res == 0 {
+            return;
+        }
+
+        let signal = signal as libc::c_int;
+        // Ctrl+\ (SIGQUIT) is the closest equivalent to Ctrl+Break
+        let kind = if signal == libc::SIGQUIT { InterruptKind::CtrlBreak } else { InterruptKind::CtrlC };
+        if !crate::input::dispatch_interrupt(kind) {
+            // Not handled, so fall back to the default behavior, which terminates the process
+            unsafe {
+                libc::signal(signal, libc::SIG_DFL);
+                libc::kill(libc::getpid(), signal);
+            }
+        }
+    }
+}
+
+extern "C" fn interrupt_handler(signal: libc::c_int) {
+    // Only pass the signal on to the interrupt thread, as `write` is async-signal-safe
+    let fd = INTERRUPT_PIPE.load(Ordering::Acquire);
+    let signal = signal as u8;
+    unsafe { libc::write(fd, &signal as *const u8 as *const libc::c_void, 1) };
+}
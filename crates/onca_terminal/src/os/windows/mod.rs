@@ -1,17 +1,30 @@
-use onca_common::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use onca_common::{io, sync::Mutex};
 use windows::Win32::{
-    Foundation::HANDLE, 
+    Foundation::{HANDLE, BOOL, FALSE}, 
     System::Console::{
-        WriteConsoleA, AllocConsole, GetStdHandle, SetConsoleMode, GetConsoleMode,
-        STD_OUTPUT_HANDLE, STD_HANDLE,
-        ENABLE_WRAP_AT_EOL_OUTPUT, ENABLE_VIRTUAL_TERMINAL_PROCESSING,
-    }, Storage::FileSystem::WriteFile,
+        WriteConsoleA, AllocConsole, GetStdHandle, SetConsoleMode, GetConsoleMode, PeekConsoleInputW, ReadConsoleInputW, SetConsoleCtrlHandler,
+        GetConsoleScreenBufferInfo, CONSOLE_SCREEN_BUFFER_INFO,
+        STD_OUTPUT_HANDLE, STD_INPUT_HANDLE, STD_HANDLE, CONSOLE_MODE, INPUT_RECORD, KEY_EVENT, CTRL_C_EVENT, CTRL_BREAK_EVENT,
+        ENABLE_WRAP_AT_EOL_OUTPUT, ENABLE_VIRTUAL_TERMINAL_PROCESSING, ENABLE_VIRTUAL_TERMINAL_INPUT, ENABLE_LINE_INPUT, ENABLE_ECHO_INPUT, ENABLE_PROCESSED_INPUT,
+    }, Storage::FileSystem::{WriteFile, ReadFile},
 };
 
+use crate::InterruptKind;
+
 fn get_std_handle(handle: STD_HANDLE) -> io::Result<HANDLE> {
     unsafe { GetStdHandle(handle) }.map_err(|err| io::Error::from_raw_os_error(err.code().0))
 }
 
+fn to_io_error(err: windows::core::Error) -> io::Error {
+    io::Error::from_raw_os_error(err.code().0)
+}
+
+/// Input mode before raw mode was enabled
+static ORIGINAL_INPUT_MODE: Mutex<Option<CONSOLE_MODE>> = Mutex::new(None);
+/// Is the ctrl handler registered
+static CTRL_HANDLER_REGISTERED: AtomicBool = AtomicBool::new(false);
+
 // The terminal code expects codepage 65001 (UTF-8) to be set, otherwise it may cause some weird glitches
 pub struct Terminal;
 
@@ -68,6 +81,93 @@ impl Terminal {
     pub(crate) fn get_output_handle() -> IOHandle {
         get_std_handle(STD_OUTPUT_HANDLE).unwrap_or_default()
     }
+
+    pub(crate) fn set_raw_mode(enabled: bool) -> io::Result<()> {
+        let input = get_std_handle(STD_INPUT_HANDLE)?;
+        let mut original = ORIGINAL_INPUT_MODE.lock();
+        if enabled {
+            let mut mode = CONSOLE_MODE::default();
+            unsafe { GetConsoleMode(input, &mut mode) }.map_err(to_io_error)?;
+            if original.is_none() {
+                *original = Some(mode);
+            }
+
+            // Input is passed on as VT sequences, so all keys can be decoded the same way on all platforms
+            let raw_mode = (mode & !(ENABLE_LINE_INPUT | ENABLE_ECHO_INPUT | ENABLE_PROCESSED_INPUT)) | ENABLE_VIRTUAL_TERMINAL_INPUT;
+            unsafe { SetConsoleMode(input, raw_mode) }.map_err(to_io_error)
+        } else if let Some(mode) = original.take() {
+            unsafe { SetConsoleMode(input, mode) }.map_err(to_io_error)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn has_input() -> io::Result<bool> {
+        let input = get_std_handle(STD_INPUT_HANDLE)?;
+        // Redirected input can always be read, until the end of the input is reached
+        if !is_terminal(input) {
+            return Ok(true);
+        }
+
+        loop {
+            let mut record = [INPUT_RECORD::default()];
+            let mut count = 0;
+            unsafe { PeekConsoleInputW(input, &mut record, &mut count) }.map_err(to_io_error)?;
+            if count == 0 {
+                return Ok(false);
+            }
+
+            let is_char_input = record[0].EventType as u32 == KEY_EVENT && unsafe {
+                let key_event = &record[0].Event.KeyEvent;
+                key_event.bKeyDown.as_bool() && key_event.uChar.UnicodeChar != 0
+            };
+            if is_char_input {
+                return Ok(true);
+            }
+
+            // Discard events that don't result in any input, e.g. key releases or focus events, as they would block a read
+            unsafe { ReadConsoleInputW(input, &mut record, &mut count) }.map_err(to_io_error)?;
+        }
+    }
+
+    pub(crate) fn read_bytes(buf: &mut [u8]) -> io::Result<usize> {
+        let input = get_std_handle(STD_INPUT_HANDLE)?;
+        let mut bytes_read = 0;
+        unsafe { ReadFile(input, Some(buf), Some(&mut bytes_read), None) }.map_or_else(|err| Err(to_io_error(err)), |_| Ok(bytes_read as usize))
+    }
+
+    pub(crate) fn size() -> io::Result<(u16, u16)> {
+        let output = get_std_handle(STD_OUTPUT_HANDLE)?;
+        let mut info = CONSOLE_SCREEN_BUFFER_INFO::default();
+        unsafe { GetConsoleScreenBufferInfo(output, &mut info) }.map_err(to_io_error)?;
+        let width = info.srWindow.Right - info.srWindow.Left + 1;
+        let height = info.srWindow.Bottom - info.srWindow.Top + 1;
+        Ok((width.max(0) as u16, height.max(0) as u16))
+    }
+
+    /// Check if the terminal might have been resized since the last call.
+    /// 
+    /// The console only sends resize events as input records, which would be consumed by reads, so always report a possible resize and let the caller compare sizes.
+    pub(crate) fn take_resized() -> bool {
+        true
+    }
+
+    pub(crate) fn register_interrupt_handler(enabled: bool) -> io::Result<()> {
+        // The handler should only be added once, otherwise it would be called multiple times
+        if CTRL_HANDLER_REGISTERED.swap(enabled, Ordering::AcqRel) == enabled {
+            return Ok(());
+        }
+        unsafe { SetConsoleCtrlHandler(Some(ctrl_handler), BOOL::from(enabled)) }.map_err(to_io_error)
+    }
+}
+
+unsafe extern "system" fn ctrl_handler(ctrl_type: u32) -> BOOL {
+    let kind = match ctrl_type {
+        CTRL_C_EVENT     => InterruptKind::CtrlC,
+        CTRL_BREAK_EVENT => InterruptKind::CtrlBreak,
+        _                => return FALSE,
+    };
+    BOOL::from(crate::input::dispatch_interrupt(kind))
 }
 
 fn is_terminal(handle: HANDLE) -> bool {
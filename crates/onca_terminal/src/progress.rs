@@ -0,0 +1,387 @@
+use std::{
+    fmt::Write as _,
+    time::{Duration, Instant},
+};
+use onca_common::io;
+
+use crate::{Terminal, TerminalColor};
+
+/// Progress bar with an estimated time remaining
+pub struct ProgressBar {
+    label:   String,
+    current: u64,
+    total:   u64,
+    start:   Instant,
+    end:     Option<Instant>,
+    width:   u16,
+    color:   Option<TerminalColor>,
+    done:    bool,
+}
+
+impl ProgressBar {
+    /// Default width of the bar itself, in characters
+    pub const DEFAULT_WIDTH: u16 = 30;
+
+    /// Create a new progress bar
+    pub fn new(label: &str, total: u64) -> Self {
+        Self {
+            label: label.to_string(),
+            current: 0,
+            total,
+            start: Instant::now(),
+            end: None,
+            width: Self::DEFAULT_WIDTH,
+            color: None,
+            done: false,
+        }
+    }
+
+    /// Set the width of the bar itself, in characters
+    pub fn with_width(mut self, width: u16) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Set the color of the filled part of the bar
+    pub fn with_color(mut self, color: TerminalColor) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Set the label
+    pub fn set_label(&mut self, label: &str) {
+        self.label = label.to_string();
+    }
+
+    /// Set the current progress
+    pub fn set(&mut self, current: u64) {
+        self.current = current.min(self.total);
+        self.update_end();
+    }
+
+    /// Increment the current progress
+    pub fn inc(&mut self, amount: u64) {
+        self.set(self.current.saturating_add(amount));
+    }
+
+    /// Set the total
+    pub fn set_total(&mut self, total: u64) {
+        self.total = total;
+        self.current = self.current.min(total);
+        self.update_end();
+    }
+
+    /// Mark the progress bar as finished
+    pub fn finish(&mut self) {
+        self.current = self.total;
+        self.done = true;
+        self.update_end();
+    }
+
+    /// Check if the progress bar is finished
+    pub fn is_finished(&self) -> bool {
+        self.done || self.current >= self.total
+    }
+
+    /// Get the time it took to finish the progress bar, or the time since it was created if it is not yet finished
+    pub fn elapsed(&self) -> Duration {
+        match self.end {
+            Some(end) => end.duration_since(self.start),
+            None      => self.start.elapsed(),
+        }
+    }
+
+    /// Get the progress as a fraction between 0 and 1
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.current as f32 / self.total as f32
+        }
+    }
+
+    /// Get the estimated time remaining, based on the average speed since the bar was created
+    pub fn eta(&self) -> Option<Duration> {
+        if self.current == 0 {
+            return None;
+        }
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let remaining = (self.total - self.current) as f64;
+        Some(Duration::from_secs_f64(elapsed * remaining / self.current as f64))
+    }
+
+    /// Render the progress bar to a single line
+    pub fn render(&self, out: &mut String) {
+        let filled = ((self.fraction() * self.width as f32) as u16).min(self.width);
+
+        if !self.label.is_empty() {
+            _ = write!(out, "{} ", self.label);
+        }
+        out.push('[');
+        if let Some(color) = self.color {
+            out.push_str(&color.fore_to_escape_code());
+        }
+        out.extend(core::iter::repeat('█').take(filled as usize));
+        if self.color.is_some() {
+            out.push_str("\x1B[39m");
+        }
+        out.extend(core::iter::repeat('░').take((self.width - filled) as usize));
+        _ = write!(out, "] {:>3}% {}/{}", (self.fraction() * 100.0) as u32, self.current, self.total);
+
+        if self.is_finished() {
+            _ = write!(out, " done in {}", format_duration(self.elapsed()));
+        } else if let Some(eta) = self.eta() {
+            _ = write!(out, " ETA {}", format_duration(eta));
+        }
+    }
+
+    // Remember when the bar finished, so the 'done in' time does not keep increasing while the bar is still drawn
+    fn update_end(&mut self) {
+        if self.is_finished() {
+            self.end.get_or_insert_with(Instant::now);
+        } else {
+            self.end = None;
+        }
+    }
+}
+
+/// Spinner for tasks without a known amount of work
+pub struct Spinner {
+    label:      String,
+    frames:     &'static [char],
+    frame:      usize,
+    interval:   Duration,
+    last_frame: Instant,
+    done:       bool,
+}
+
+impl Spinner {
+    /// Braille dot spinner frames
+    pub const DOTS: &'static [char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+    /// ASCII spinner frames, for terminals without unicode support
+    pub const ASCII: &'static [char] = &['|', '/', '-', '\\'];
+
+    /// Create a new spinner
+    pub fn new(label: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            frames: Self::DOTS,
+            frame: 0,
+            interval: Duration::from_millis(80),
+            last_frame: Instant::now(),
+            done: false,
+        }
+    }
+
+    /// Set the frames of the spinner
+    pub fn with_frames(mut self, frames: &'static [char]) -> Self {
+        self.frames = frames;
+        self
+    }
+
+    /// Set the label
+    pub fn set_label(&mut self, label: &str) {
+        self.label = label.to_string();
+    }
+
+    /// Advance the spinner if enough time has passed since the last frame
+    pub fn tick(&mut self) {
+        if self.last_frame.elapsed() >= self.interval && !self.frames.is_empty() {
+            self.frame = (self.frame + 1) % self.frames.len();
+            self.last_frame = Instant::now();
+        }
+    }
+
+    /// Mark the spinner as finished
+    pub fn finish(&mut self) {
+        self.done = true;
+    }
+
+    /// Check if the spinner is finished
+    pub fn is_finished(&self) -> bool {
+        self.done
+    }
+
+    /// Render the spinner to a single line
+    pub fn render(&self, out: &mut String) {
+        let frame = if self.done { '✓' } else { self.frames.get(self.frame).copied().unwrap_or(' ') };
+        _ = write!(out, "{frame} {}", self.label);
+    }
+}
+
+/// Widget in a progress stack
+pub enum ProgressWidget {
+    Bar(ProgressBar),
+    Spinner(Spinner),
+}
+
+impl ProgressWidget {
+    fn render(&self, out: &mut String) {
+        match self {
+            ProgressWidget::Bar(bar)         => bar.render(out),
+            ProgressWidget::Spinner(spinner) => spinner.render(out),
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        match self {
+            ProgressWidget::Bar(bar)         => bar.is_finished(),
+            ProgressWidget::Spinner(spinner) => spinner.is_finished(),
+        }
+    }
+}
+
+/// Handle to a widget in a progress stack
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ProgressHandle(usize);
+
+/// Stack of progress widgets, drawn below any other terminal output.
+/// 
+/// The widgets are redrawn in place, while text written via [`ProgressStack::println`] is written above the stack, so log output and progress can be interleaved without corrupting each other.
+pub struct ProgressStack {
+    widgets:     Vec<Option<ProgressWidget>>,
+    drawn_lines: u16,
+}
+
+impl ProgressStack {
+    /// Create an empty progress stack
+    pub fn new() -> Self {
+        Self { widgets: Vec::new(), drawn_lines: 0 }
+    }
+
+    /// Add a progress bar to the stack
+    pub fn add_bar(&mut self, bar: ProgressBar) -> ProgressHandle {
+        self.add(ProgressWidget::Bar(bar))
+    }
+
+    /// Add a spinner to the stack
+    pub fn add_spinner(&mut self, spinner: Spinner) -> ProgressHandle {
+        self.add(ProgressWidget::Spinner(spinner))
+    }
+
+    fn add(&mut self, widget: ProgressWidget) -> ProgressHandle {
+        self.widgets.push(Some(widget));
+        ProgressHandle(self.widgets.len() - 1)
+    }
+
+    /// Remove a widget from the stack
+    pub fn remove(&mut self, handle: ProgressHandle) -> Option<ProgressWidget> {
+        self.widgets.get_mut(handle.0).and_then(Option::take)
+    }
+
+    /// Get a progress bar in the stack
+    pub fn bar_mut(&mut self, handle: ProgressHandle) -> Option<&mut ProgressBar> {
+        match self.widgets.get_mut(handle.0) {
+            Some(Some(ProgressWidget::Bar(bar))) => Some(bar),
+            _ => None,
+        }
+    }
+
+    /// Get a spinner in the stack
+    pub fn spinner_mut(&mut self, handle: ProgressHandle) -> Option<&mut Spinner> {
+        match self.widgets.get_mut(handle.0) {
+            Some(Some(ProgressWidget::Spinner(spinner))) => Some(spinner),
+            _ => None,
+        }
+    }
+
+    /// Check if all widgets are finished
+    pub fn is_finished(&self) -> bool {
+        self.widgets.iter().flatten().all(ProgressWidget::is_finished)
+    }
+
+    /// Advance all spinners and redraw the stack
+    pub fn draw(&mut self) -> io::Result<()> {
+        for widget in self.widgets.iter_mut().flatten() {
+            if let ProgressWidget::Spinner(spinner) = widget {
+                spinner.tick();
+            }
+        }
+
+        let mut out = String::new();
+        self.write_clear(&mut out);
+        self.write_widgets(&mut out);
+        Terminal::write(&out).map(|_| ())
+    }
+
+    /// Write a line of text above the stack, and redraw the stack below it
+    pub fn println(&mut self, text: &str) -> io::Result<()> {
+        let mut out = String::new();
+        self.write_clear(&mut out);
+        out.push_str(text);
+        out.push_str("\r\n");
+        self.write_widgets(&mut out);
+        Terminal::write(&out).map(|_| ())
+    }
+
+    /// Remove the stack from the terminal, e.g. before writing output that does not go through the stack, [`ProgressStack::draw`] will draw it again
+    pub fn clear(&mut self) -> io::Result<()> {
+        let mut out = String::new();
+        self.write_clear(&mut out);
+        Terminal::write(&out).map(|_| ())
+    }
+
+    fn write_clear(&mut self, out: &mut String) {
+        if self.drawn_lines > 0 {
+            // Move to the start of the first line of the stack and clear everything below it
+            _ = write!(out, "\r\x1B[{}A\x1B[0J", self.drawn_lines);
+            self.drawn_lines = 0;
+        }
+    }
+
+    fn write_widgets(&mut self, out: &mut String) {
+        // Lines that are wider than the terminal would wrap, which would break moving back to the top of the stack when clearing it
+        let max_width = Terminal::size().map_or(u16::MAX, |(width, _)| width);
+
+        let mut line = String::new();
+        for widget in self.widgets.iter().flatten() {
+            line.clear();
+            widget.render(&mut line);
+            write_truncated(&line, max_width, out);
+            out.push_str("\x1B[0K\r\n");
+            self.drawn_lines += 1;
+        }
+    }
+}
+
+impl Default for ProgressStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Write a line, truncated to `max_width` visible characters, escape sequences are always written, as they take up no space
+fn write_truncated(line: &str, max_width: u16, out: &mut String) {
+    let mut visible = 0;
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1B' {
+            out.push(c);
+            match chars.next() {
+                Some('[') => {
+                    out.push('[');
+                    for c in chars.by_ref() {
+                        out.push(c);
+                        if ('\x40'..='\x7E').contains(&c) {
+                            break;
+                        }
+                    }
+                },
+                Some(c) => out.push(c),
+                None => {},
+            }
+        } else if visible < max_width {
+            out.push(c);
+            visible += 1;
+        }
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, (secs / 60) % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
@@ -0,0 +1,55 @@
+use onca_common::{
+    sync::Mutex,
+    event_listener::{EventListener, EventListenerArray, EventListenerRef},
+};
+
+/// Terminal resize event
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TerminalResizeEvent {
+    /// Number of columns
+    pub width:  u16,
+    /// Number of rows
+    pub height: u16,
+}
+
+/// Resize listener reference, the listeners are shared between threads, so they need to be `Send`
+pub type ResizeListenerRef = EventListenerRef<dyn EventListener<TerminalResizeEvent> + Send>;
+
+struct ResizeListeners {
+    listeners: EventListenerArray<dyn EventListener<TerminalResizeEvent> + Send>,
+    last_size: Option<(u16, u16)>,
+}
+
+static RESIZE_LISTENERS: Mutex<Option<ResizeListeners>> = Mutex::new(None);
+
+fn with_listeners<R, F: FnOnce(&mut ResizeListeners) -> R>(f: F) -> R {
+    let mut listeners = RESIZE_LISTENERS.lock();
+    f(listeners.get_or_insert_with(|| ResizeListeners { listeners: EventListenerArray::new(), last_size: None }))
+}
+
+pub(crate) fn register_resize_listener(listener: ResizeListenerRef) {
+    with_listeners(|listeners| listeners.listeners.push(listener));
+}
+
+pub(crate) fn unregister_resize_listener(listener: &ResizeListenerRef) {
+    with_listeners(|listeners| listeners.listeners.remove(listener));
+}
+
+/// Notify the listeners if the size is different from the last known size
+pub(crate) fn update_size(size: (u16, u16)) {
+    let listeners = with_listeners(|listeners| {
+        if listeners.last_size == Some(size) {
+            return Vec::new();
+        }
+        // The initial size is not a resize
+        let notify = listeners.last_size.is_some();
+        listeners.last_size = Some(size);
+        if notify { listeners.listeners.to_vec() } else { Vec::new() }
+    });
+
+    // Listeners are notified outside of the lock, so they can (un)register listeners or query the size
+    let event = TerminalResizeEvent { width: size.0, height: size.1 };
+    for listener in listeners {
+        listener.lock().notify(&event);
+    }
+}
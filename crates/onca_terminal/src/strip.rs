@@ -0,0 +1,135 @@
+use onca_common::io;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum StripState {
+    Text,
+    /// After `ESC`
+    Escape,
+    /// Control sequence, after `ESC [`
+    Csi,
+    /// Operating system command, after `ESC ]`, e.g. hyperlinks and window titles
+    Osc,
+    /// `ESC` inside of an operating system command, which is the start of the string terminator `ESC \`
+    OscEscape,
+}
+
+/// State machine removing escape sequences from text.
+/// 
+/// The state is kept between calls, so escape sequences that are split across multiple writes are still removed.
+#[derive(Clone, Copy, Debug)]
+pub struct AnsiStripper {
+    state: StripState,
+}
+
+impl AnsiStripper {
+    /// Create a new stripper
+    pub fn new() -> Self {
+        Self { state: StripState::Text }
+    }
+
+    /// Remove all escape sequences from `bytes` and append the remaining text to `out`
+    pub fn strip(&mut self, bytes: &[u8], out: &mut Vec<u8>) {
+        for &b in bytes {
+            self.state = match self.state {
+                StripState::Text => if b == 0x1B {
+                    StripState::Escape
+                } else {
+                    out.push(b);
+                    StripState::Text
+                },
+                StripState::Escape => match b {
+                    b'[' => StripState::Csi,
+                    b']' => StripState::Osc,
+                    _    => StripState::Text,
+                },
+                StripState::Csi => if (0x40..=0x7E).contains(&b) {
+                    StripState::Text
+                } else {
+                    StripState::Csi
+                },
+                StripState::Osc => match b {
+                    0x07 => StripState::Text,
+                    0x1B => StripState::OscEscape,
+                    _    => StripState::Osc,
+                },
+                StripState::OscEscape => StripState::Text,
+            }
+        }
+    }
+}
+
+impl Default for AnsiStripper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Remove all escape sequences (colors, formatting, cursor movement, hyperlinks, etc) from a string
+pub fn strip_ansi(text: &str) -> String {
+    let mut out = Vec::with_capacity(text.len());
+    AnsiStripper::new().strip(text.as_bytes(), &mut out);
+    // Escape sequences only consist of ASCII, so removing them cannot result in invalid UTF-8
+    String::from_utf8(out).unwrap_or_default()
+}
+
+/// Writer wrapper that removes all escape sequences before passing the text on to the inner writer.
+/// 
+/// This allows the same colored output to be written to the terminal, while only plain text ends up in e.g. a log file.
+pub struct AnsiStripWriter<W: io::Write> {
+    inner:    W,
+    stripper: AnsiStripper,
+    buffer:   Vec<u8>,
+    enabled:  bool,
+}
+
+impl<W: io::Write> AnsiStripWriter<W> {
+    /// Create a new writer that strips escape sequences
+    pub fn new(inner: W) -> Self {
+        Self { inner, stripper: AnsiStripper::new(), buffer: Vec::new(), enabled: true }
+    }
+
+    /// Enable or disable stripping, when disabled, all text is passed through unchanged
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.stripper = AnsiStripper::new();
+        }
+    }
+
+    /// Check if stripping is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Get the inner writer
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    /// Get the inner writer mutably
+    pub fn inner_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Take the inner writer out of the wrapper
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: io::Write> io::Write for AnsiStripWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.enabled {
+            return self.inner.write(buf);
+        }
+
+        self.buffer.clear();
+        self.stripper.strip(buf, &mut self.buffer);
+        self.inner.write_all(&self.buffer)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
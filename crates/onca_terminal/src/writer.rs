@@ -0,0 +1,147 @@
+use onca_common::{io, sync::Mutex};
+
+use crate::os::os_imp;
+
+/// When a [`TerminalWriter`] flushes its buffer
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TerminalBufferMode {
+    /// Only flush when the buffer is full, or when explicitly flushed
+    Full,
+    /// Flush each completed line, partial lines stay buffered
+    Line,
+}
+
+struct WriterState {
+    buffer: Vec<u8>,
+    mode:   TerminalBufferMode,
+}
+
+impl WriterState {
+    fn flush_to(&mut self, len: usize) -> io::Result<()> {
+        if len == 0 {
+            return Ok(());
+        }
+        let res = os_imp::Terminal::write_bytes(&self.buffer[..len]);
+        self.buffer.drain(..len);
+        res.map(|_| ())
+    }
+}
+
+/// Buffered writer to the terminal, which can be shared between threads.
+/// 
+/// Output is collected in a buffer and written to the terminal in batches, instead of going to the OS on each call like [`Terminal::write`](crate::Terminal::write).
+/// Each write is added to the buffer as a whole, so output of multiple threads is never interleaved within a single write.
+pub struct TerminalWriter {
+    state:    Mutex<WriterState>,
+    capacity: usize,
+}
+
+impl TerminalWriter {
+    /// Default buffer capacity in bytes
+    pub const DEFAULT_CAPACITY: usize = 8192;
+
+    /// Create a new writer with the default capacity
+    pub fn new(mode: TerminalBufferMode) -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY, mode)
+    }
+
+    /// Create a new writer, which flushes when the buffer reaches `capacity` bytes
+    pub fn with_capacity(capacity: usize, mode: TerminalBufferMode) -> Self {
+        Self {
+            state: Mutex::new(WriterState { buffer: Vec::with_capacity(capacity), mode }),
+            capacity,
+        }
+    }
+
+    /// Get the buffer mode
+    pub fn mode(&self) -> TerminalBufferMode {
+        self.state.lock().mode
+    }
+
+    /// Set the buffer mode, switching to line buffering flushes any completed lines
+    pub fn set_mode(&self, mode: TerminalBufferMode) -> io::Result<()> {
+        let mut state = self.state.lock();
+        state.mode = mode;
+        if mode == TerminalBufferMode::Line {
+            let len = Self::completed_lines_len(&state.buffer);
+            state.flush_to(len)?;
+        }
+        Ok(())
+    }
+
+    /// Get the number of bytes that are currently buffered
+    pub fn buffered_len(&self) -> usize {
+        self.state.lock().buffer.len()
+    }
+
+    /// Add bytes to the buffer, flushing when needed
+    pub fn write_bytes(&self, bytes: &[u8]) -> io::Result<()> {
+        let mut state = self.state.lock();
+
+        // Large writes don't fit in the buffer, so write them directly after what was already buffered
+        if state.buffer.len() + bytes.len() > self.capacity {
+            let len = state.buffer.len();
+            state.flush_to(len)?;
+            if bytes.len() > self.capacity {
+                return os_imp::Terminal::write_bytes(bytes).map(|_| ());
+            }
+        }
+
+        state.buffer.extend_from_slice(bytes);
+        if state.mode == TerminalBufferMode::Line && bytes.contains(&b'\n') {
+            let len = Self::completed_lines_len(&state.buffer);
+            state.flush_to(len)?;
+        }
+        Ok(())
+    }
+
+    /// Add text to the buffer, flushing when needed
+    pub fn write_str(&self, text: &str) -> io::Result<()> {
+        self.write_bytes(text.as_bytes())
+    }
+
+    /// Write everything in the buffer to the terminal
+    pub fn flush(&self) -> io::Result<()> {
+        let mut state = self.state.lock();
+        let len = state.buffer.len();
+        state.flush_to(len)
+    }
+
+    fn completed_lines_len(buffer: &[u8]) -> usize {
+        buffer.iter().rposition(|&b| b == b'\n').map_or(0, |idx| idx + 1)
+    }
+}
+
+impl Default for TerminalWriter {
+    fn default() -> Self {
+        Self::new(TerminalBufferMode::Line)
+    }
+}
+
+impl Drop for TerminalWriter {
+    fn drop(&mut self) {
+        _ = TerminalWriter::flush(self);
+    }
+}
+
+impl io::Write for &TerminalWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_bytes(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        TerminalWriter::flush(self)
+    }
+}
+
+impl io::Write for TerminalWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_bytes(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        TerminalWriter::flush(self)
+    }
+}
@@ -2688,6 +2688,31 @@ pub fn generate_unicode_data() {
 	to_upper.sort_unstable_by_key(|val| val.0);
 	to_title.sort_unstable_by_key(|val| val.0);
 
+	let mut case_folding = Vec::new();
+	parse_file("CaseFolding.txt", |line| {
+		let elems = line.split(';').map(|s| s.trim()).collect::<Vec<_>>();
+
+		// Only use the common and full case foldings
+		if elems[1] != "C" && elems[1] != "F" {
+			return;
+		}
+
+		let id = u32::from_str_radix(elems[0], 16).unwrap();
+		let id = char::from_u32(id).unwrap();
+
+		let mut folded = Vec::new();
+		for val in elems[2].split(' ') {
+			folded.push(char::from_u32(u32::from_str_radix(val, 16).unwrap()).unwrap());
+		}
+
+		if folded.len() == 1 {
+			case_folding.push((id, BuildCasing::Simple(folded[0])));
+		} else {
+			case_folding.push((id, BuildCasing::Complex(folded)));
+		}
+	});
+	case_folding.sort_unstable_by_key(|val| val.0);
+
 	parse_file("NameAliases.txt", |line| {
 		let elems = line.split(';').map(|s| s.trim()).collect::<Vec<_>>();
 		if elems[2] == "correction" || elems[2] == "control" {
@@ -2807,6 +2832,7 @@ use crate::*;
 	write_arr_char(&mut writer, "TO_LOWER", "Casing", to_lower, false);
 	write_arr_char(&mut writer, "TO_UPPER", "Casing", to_upper, false);
 	write_arr_char(&mut writer, "TO_TITLE", "Casing", to_title, false);
+	write_arr_char(&mut writer, "CASE_FOLDING", "Casing", case_folding, false);
 	write_arr_index(&mut writer, "DERIVED_AGE", "Age", uni_age, true);
 	write_arr_index(&mut writer, "NUMERIC_VALUES", "u8", numeric_values, false);
 	write_arr_index(&mut writer, "DIGIT_VALUES", "u8", digit_values, false);
@@ -2190,6 +2190,13 @@ pub fn to_title(ch: char) -> Casing {
 	from_key_or(ch, &unicode::TO_TITLE, Casing::Simple(ch))
 }
 
+/// Get the full case folded representation of a character, used for caseless comparisons, if the character does not change when case folded, the character will be returned.
+/// 
+/// The result is never [`Casing::Conditional`], as the Turkic specific case foldings are not included.
+pub fn to_casefold(ch: char) -> Casing {
+	from_key_or(ch, &unicode::CASE_FOLDING, Casing::Simple(ch))
+}
+
 /// Get the joining info for a codepoint, or `None` if there is no joining info for the codepoint.
 pub fn get_joining_info(codepoint: u32) -> Option<JoiningInfo> {
 	from_index(codepoint, &unicode::JOINING_INFO)
@@ -83555,6 +83555,1539 @@ pub(crate) const TO_TITLE: [(char, Casing); 1556] = [
 	('𞥃', Casing::Simple('𞤡')),
 ];
 
+pub(crate) const CASE_FOLDING: [(char, Casing); 1530] = [
+	('A', Casing::Simple('a')),
+	('B', Casing::Simple('b')),
+	('C', Casing::Simple('c')),
+	('D', Casing::Simple('d')),
+	('E', Casing::Simple('e')),
+	('F', Casing::Simple('f')),
+	('G', Casing::Simple('g')),
+	('H', Casing::Simple('h')),
+	('I', Casing::Simple('i')),
+	('J', Casing::Simple('j')),
+	('K', Casing::Simple('k')),
+	('L', Casing::Simple('l')),
+	('M', Casing::Simple('m')),
+	('N', Casing::Simple('n')),
+	('O', Casing::Simple('o')),
+	('P', Casing::Simple('p')),
+	('Q', Casing::Simple('q')),
+	('R', Casing::Simple('r')),
+	('S', Casing::Simple('s')),
+	('T', Casing::Simple('t')),
+	('U', Casing::Simple('u')),
+	('V', Casing::Simple('v')),
+	('W', Casing::Simple('w')),
+	('X', Casing::Simple('x')),
+	('Y', Casing::Simple('y')),
+	('Z', Casing::Simple('z')),
+	('µ', Casing::Simple('μ')),
+	('À', Casing::Simple('à')),
+	('Á', Casing::Simple('á')),
+	('Â', Casing::Simple('â')),
+	('Ã', Casing::Simple('ã')),
+	('Ä', Casing::Simple('ä')),
+	('Å', Casing::Simple('å')),
+	('Æ', Casing::Simple('æ')),
+	('Ç', Casing::Simple('ç')),
+	('È', Casing::Simple('è')),
+	('É', Casing::Simple('é')),
+	('Ê', Casing::Simple('ê')),
+	('Ë', Casing::Simple('ë')),
+	('Ì', Casing::Simple('ì')),
+	('Í', Casing::Simple('í')),
+	('Î', Casing::Simple('î')),
+	('Ï', Casing::Simple('ï')),
+	('Ð', Casing::Simple('ð')),
+	('Ñ', Casing::Simple('ñ')),
+	('Ò', Casing::Simple('ò')),
+	('Ó', Casing::Simple('ó')),
+	('Ô', Casing::Simple('ô')),
+	('Õ', Casing::Simple('õ')),
+	('Ö', Casing::Simple('ö')),
+	('Ø', Casing::Simple('ø')),
+	('Ù', Casing::Simple('ù')),
+	('Ú', Casing::Simple('ú')),
+	('Û', Casing::Simple('û')),
+	('Ü', Casing::Simple('ü')),
+	('Ý', Casing::Simple('ý')),
+	('Þ', Casing::Simple('þ')),
+	('ß', Casing::Complex(&['s', 's'])),
+	('Ā', Casing::Simple('ā')),
+	('Ă', Casing::Simple('ă')),
+	('Ą', Casing::Simple('ą')),
+	('Ć', Casing::Simple('ć')),
+	('Ĉ', Casing::Simple('ĉ')),
+	('Ċ', Casing::Simple('ċ')),
+	('Č', Casing::Simple('č')),
+	('Ď', Casing::Simple('ď')),
+	('Đ', Casing::Simple('đ')),
+	('Ē', Casing::Simple('ē')),
+	('Ĕ', Casing::Simple('ĕ')),
+	('Ė', Casing::Simple('ė')),
+	('Ę', Casing::Simple('ę')),
+	('Ě', Casing::Simple('ě')),
+	('Ĝ', Casing::Simple('ĝ')),
+	('Ğ', Casing::Simple('ğ')),
+	('Ġ', Casing::Simple('ġ')),
+	('Ģ', Casing::Simple('ģ')),
+	('Ĥ', Casing::Simple('ĥ')),
+	('Ħ', Casing::Simple('ħ')),
+	('Ĩ', Casing::Simple('ĩ')),
+	('Ī', Casing::Simple('ī')),
+	('Ĭ', Casing::Simple('ĭ')),
+	('Į', Casing::Simple('į')),
+	('İ', Casing::Complex(&['i', '\u{307}'])),
+	('Ĳ', Casing::Simple('ĳ')),
+	('Ĵ', Casing::Simple('ĵ')),
+	('Ķ', Casing::Simple('ķ')),
+	('Ĺ', Casing::Simple('ĺ')),
+	('Ļ', Casing::Simple('ļ')),
+	('Ľ', Casing::Simple('ľ')),
+	('Ŀ', Casing::Simple('ŀ')),
+	('Ł', Casing::Simple('ł')),
+	('Ń', Casing::Simple('ń')),
+	('Ņ', Casing::Simple('ņ')),
+	('Ň', Casing::Simple('ň')),
+	('ŉ', Casing::Complex(&['ʼ', 'n'])),
+	('Ŋ', Casing::Simple('ŋ')),
+	('Ō', Casing::Simple('ō')),
+	('Ŏ', Casing::Simple('ŏ')),
+	('Ő', Casing::Simple('ő')),
+	('Œ', Casing::Simple('œ')),
+	('Ŕ', Casing::Simple('ŕ')),
+	('Ŗ', Casing::Simple('ŗ')),
+	('Ř', Casing::Simple('ř')),
+	('Ś', Casing::Simple('ś')),
+	('Ŝ', Casing::Simple('ŝ')),
+	('Ş', Casing::Simple('ş')),
+	('Š', Casing::Simple('š')),
+	('Ţ', Casing::Simple('ţ')),
+	('Ť', Casing::Simple('ť')),
+	('Ŧ', Casing::Simple('ŧ')),
+	('Ũ', Casing::Simple('ũ')),
+	('Ū', Casing::Simple('ū')),
+	('Ŭ', Casing::Simple('ŭ')),
+	('Ů', Casing::Simple('ů')),
+	('Ű', Casing::Simple('ű')),
+	('Ų', Casing::Simple('ų')),
+	('Ŵ', Casing::Simple('ŵ')),
+	('Ŷ', Casing::Simple('ŷ')),
+	('Ÿ', Casing::Simple('ÿ')),
+	('Ź', Casing::Simple('ź')),
+	('Ż', Casing::Simple('ż')),
+	('Ž', Casing::Simple('ž')),
+	('ſ', Casing::Simple('s')),
+	('Ɓ', Casing::Simple('ɓ')),
+	('Ƃ', Casing::Simple('ƃ')),
+	('Ƅ', Casing::Simple('ƅ')),
+	('Ɔ', Casing::Simple('ɔ')),
+	('Ƈ', Casing::Simple('ƈ')),
+	('Ɖ', Casing::Simple('ɖ')),
+	('Ɗ', Casing::Simple('ɗ')),
+	('Ƌ', Casing::Simple('ƌ')),
+	('Ǝ', Casing::Simple('ǝ')),
+	('Ə', Casing::Simple('ə')),
+	('Ɛ', Casing::Simple('ɛ')),
+	('Ƒ', Casing::Simple('ƒ')),
+	('Ɠ', Casing::Simple('ɠ')),
+	('Ɣ', Casing::Simple('ɣ')),
+	('Ɩ', Casing::Simple('ɩ')),
+	('Ɨ', Casing::Simple('ɨ')),
+	('Ƙ', Casing::Simple('ƙ')),
+	('Ɯ', Casing::Simple('ɯ')),
+	('Ɲ', Casing::Simple('ɲ')),
+	('Ɵ', Casing::Simple('ɵ')),
+	('Ơ', Casing::Simple('ơ')),
+	('Ƣ', Casing::Simple('ƣ')),
+	('Ƥ', Casing::Simple('ƥ')),
+	('Ʀ', Casing::Simple('ʀ')),
+	('Ƨ', Casing::Simple('ƨ')),
+	('Ʃ', Casing::Simple('ʃ')),
+	('Ƭ', Casing::Simple('ƭ')),
+	('Ʈ', Casing::Simple('ʈ')),
+	('Ư', Casing::Simple('ư')),
+	('Ʊ', Casing::Simple('ʊ')),
+	('Ʋ', Casing::Simple('ʋ')),
+	('Ƴ', Casing::Simple('ƴ')),
+	('Ƶ', Casing::Simple('ƶ')),
+	('Ʒ', Casing::Simple('ʒ')),
+	('Ƹ', Casing::Simple('ƹ')),
+	('Ƽ', Casing::Simple('ƽ')),
+	('Ǆ', Casing::Simple('ǆ')),
+	('ǅ', Casing::Simple('ǆ')),
+	('Ǉ', Casing::Simple('ǉ')),
+	('ǈ', Casing::Simple('ǉ')),
+	('Ǌ', Casing::Simple('ǌ')),
+	('ǋ', Casing::Simple('ǌ')),
+	('Ǎ', Casing::Simple('ǎ')),
+	('Ǐ', Casing::Simple('ǐ')),
+	('Ǒ', Casing::Simple('ǒ')),
+	('Ǔ', Casing::Simple('ǔ')),
+	('Ǖ', Casing::Simple('ǖ')),
+	('Ǘ', Casing::Simple('ǘ')),
+	('Ǚ', Casing::Simple('ǚ')),
+	('Ǜ', Casing::Simple('ǜ')),
+	('Ǟ', Casing::Simple('ǟ')),
+	('Ǡ', Casing::Simple('ǡ')),
+	('Ǣ', Casing::Simple('ǣ')),
+	('Ǥ', Casing::Simple('ǥ')),
+	('Ǧ', Casing::Simple('ǧ')),
+	('Ǩ', Casing::Simple('ǩ')),
+	('Ǫ', Casing::Simple('ǫ')),
+	('Ǭ', Casing::Simple('ǭ')),
+	('Ǯ', Casing::Simple('ǯ')),
+	('ǰ', Casing::Complex(&['j', '\u{30c}'])),
+	('Ǳ', Casing::Simple('ǳ')),
+	('ǲ', Casing::Simple('ǳ')),
+	('Ǵ', Casing::Simple('ǵ')),
+	('Ƕ', Casing::Simple('ƕ')),
+	('Ƿ', Casing::Simple('ƿ')),
+	('Ǹ', Casing::Simple('ǹ')),
+	('Ǻ', Casing::Simple('ǻ')),
+	('Ǽ', Casing::Simple('ǽ')),
+	('Ǿ', Casing::Simple('ǿ')),
+	('Ȁ', Casing::Simple('ȁ')),
+	('Ȃ', Casing::Simple('ȃ')),
+	('Ȅ', Casing::Simple('ȅ')),
+	('Ȇ', Casing::Simple('ȇ')),
+	('Ȉ', Casing::Simple('ȉ')),
+	('Ȋ', Casing::Simple('ȋ')),
+	('Ȍ', Casing::Simple('ȍ')),
+	('Ȏ', Casing::Simple('ȏ')),
+	('Ȑ', Casing::Simple('ȑ')),
+	('Ȓ', Casing::Simple('ȓ')),
+	('Ȕ', Casing::Simple('ȕ')),
+	('Ȗ', Casing::Simple('ȗ')),
+	('Ș', Casing::Simple('ș')),
+	('Ț', Casing::Simple('ț')),
+	('Ȝ', Casing::Simple('ȝ')),
+	('Ȟ', Casing::Simple('ȟ')),
+	('Ƞ', Casing::Simple('ƞ')),
+	('Ȣ', Casing::Simple('ȣ')),
+	('Ȥ', Casing::Simple('ȥ')),
+	('Ȧ', Casing::Simple('ȧ')),
+	('Ȩ', Casing::Simple('ȩ')),
+	('Ȫ', Casing::Simple('ȫ')),
+	('Ȭ', Casing::Simple('ȭ')),
+	('Ȯ', Casing::Simple('ȯ')),
+	('Ȱ', Casing::Simple('ȱ')),
+	('Ȳ', Casing::Simple('ȳ')),
+	('Ⱥ', Casing::Simple('ⱥ')),
+	('Ȼ', Casing::Simple('ȼ')),
+	('Ƚ', Casing::Simple('ƚ')),
+	('Ⱦ', Casing::Simple('ⱦ')),
+	('Ɂ', Casing::Simple('ɂ')),
+	('Ƀ', Casing::Simple('ƀ')),
+	('Ʉ', Casing::Simple('ʉ')),
+	('Ʌ', Casing::Simple('ʌ')),
+	('Ɇ', Casing::Simple('ɇ')),
+	('Ɉ', Casing::Simple('ɉ')),
+	('Ɋ', Casing::Simple('ɋ')),
+	('Ɍ', Casing::Simple('ɍ')),
+	('Ɏ', Casing::Simple('ɏ')),
+	('\u{345}', Casing::Simple('ι')),
+	('Ͱ', Casing::Simple('ͱ')),
+	('Ͳ', Casing::Simple('ͳ')),
+	('Ͷ', Casing::Simple('ͷ')),
+	('Ϳ', Casing::Simple('ϳ')),
+	('Ά', Casing::Simple('ά')),
+	('Έ', Casing::Simple('έ')),
+	('Ή', Casing::Simple('ή')),
+	('Ί', Casing::Simple('ί')),
+	('Ό', Casing::Simple('ό')),
+	('Ύ', Casing::Simple('ύ')),
+	('Ώ', Casing::Simple('ώ')),
+	('ΐ', Casing::Complex(&['ι', '\u{308}', '\u{301}'])),
+	('Α', Casing::Simple('α')),
+	('Β', Casing::Simple('β')),
+	('Γ', Casing::Simple('γ')),
+	('Δ', Casing::Simple('δ')),
+	('Ε', Casing::Simple('ε')),
+	('Ζ', Casing::Simple('ζ')),
+	('Η', Casing::Simple('η')),
+	('Θ', Casing::Simple('θ')),
+	('Ι', Casing::Simple('ι')),
+	('Κ', Casing::Simple('κ')),
+	('Λ', Casing::Simple('λ')),
+	('Μ', Casing::Simple('μ')),
+	('Ν', Casing::Simple('ν')),
+	('Ξ', Casing::Simple('ξ')),
+	('Ο', Casing::Simple('ο')),
+	('Π', Casing::Simple('π')),
+	('Ρ', Casing::Simple('ρ')),
+	('Σ', Casing::Simple('σ')),
+	('Τ', Casing::Simple('τ')),
+	('Υ', Casing::Simple('υ')),
+	('Φ', Casing::Simple('φ')),
+	('Χ', Casing::Simple('χ')),
+	('Ψ', Casing::Simple('ψ')),
+	('Ω', Casing::Simple('ω')),
+	('Ϊ', Casing::Simple('ϊ')),
+	('Ϋ', Casing::Simple('ϋ')),
+	('ΰ', Casing::Complex(&['υ', '\u{308}', '\u{301}'])),
+	('ς', Casing::Simple('σ')),
+	('Ϗ', Casing::Simple('ϗ')),
+	('ϐ', Casing::Simple('β')),
+	('ϑ', Casing::Simple('θ')),
+	('ϕ', Casing::Simple('φ')),
+	('ϖ', Casing::Simple('π')),
+	('Ϙ', Casing::Simple('ϙ')),
+	('Ϛ', Casing::Simple('ϛ')),
+	('Ϝ', Casing::Simple('ϝ')),
+	('Ϟ', Casing::Simple('ϟ')),
+	('Ϡ', Casing::Simple('ϡ')),
+	('Ϣ', Casing::Simple('ϣ')),
+	('Ϥ', Casing::Simple('ϥ')),
+	('Ϧ', Casing::Simple('ϧ')),
+	('Ϩ', Casing::Simple('ϩ')),
+	('Ϫ', Casing::Simple('ϫ')),
+	('Ϭ', Casing::Simple('ϭ')),
+	('Ϯ', Casing::Simple('ϯ')),
+	('ϰ', Casing::Simple('κ')),
+	('ϱ', Casing::Simple('ρ')),
+	('ϴ', Casing::Simple('θ')),
+	('ϵ', Casing::Simple('ε')),
+	('Ϸ', Casing::Simple('ϸ')),
+	('Ϲ', Casing::Simple('ϲ')),
+	('Ϻ', Casing::Simple('ϻ')),
+	('Ͻ', Casing::Simple('ͻ')),
+	('Ͼ', Casing::Simple('ͼ')),
+	('Ͽ', Casing::Simple('ͽ')),
+	('Ѐ', Casing::Simple('ѐ')),
+	('Ё', Casing::Simple('ё')),
+	('Ђ', Casing::Simple('ђ')),
+	('Ѓ', Casing::Simple('ѓ')),
+	('Є', Casing::Simple('є')),
+	('Ѕ', Casing::Simple('ѕ')),
+	('І', Casing::Simple('і')),
+	('Ї', Casing::Simple('ї')),
+	('Ј', Casing::Simple('ј')),
+	('Љ', Casing::Simple('љ')),
+	('Њ', Casing::Simple('њ')),
+	('Ћ', Casing::Simple('ћ')),
+	('Ќ', Casing::Simple('ќ')),
+	('Ѝ', Casing::Simple('ѝ')),
+	('Ў', Casing::Simple('ў')),
+	('Џ', Casing::Simple('џ')),
+	('А', Casing::Simple('а')),
+	('Б', Casing::Simple('б')),
+	('В', Casing::Simple('в')),
+	('Г', Casing::Simple('г')),
+	('Д', Casing::Simple('д')),
+	('Е', Casing::Simple('е')),
+	('Ж', Casing::Simple('ж')),
+	('З', Casing::Simple('з')),
+	('И', Casing::Simple('и')),
+	('Й', Casing::Simple('й')),
+	('К', Casing::Simple('к')),
+	('Л', Casing::Simple('л')),
+	('М', Casing::Simple('м')),
+	('Н', Casing::Simple('н')),
+	('О', Casing::Simple('о')),
+	('П', Casing::Simple('п')),
+	('Р', Casing::Simple('р')),
+	('С', Casing::Simple('с')),
+	('Т', Casing::Simple('т')),
+	('У', Casing::Simple('у')),
+	('Ф', Casing::Simple('ф')),
+	('Х', Casing::Simple('х')),
+	('Ц', Casing::Simple('ц')),
+	('Ч', Casing::Simple('ч')),
+	('Ш', Casing::Simple('ш')),
+	('Щ', Casing::Simple('щ')),
+	('Ъ', Casing::Simple('ъ')),
+	('Ы', Casing::Simple('ы')),
+	('Ь', Casing::Simple('ь')),
+	('Э', Casing::Simple('э')),
+	('Ю', Casing::Simple('ю')),
+	('Я', Casing::Simple('я')),
+	('Ѡ', Casing::Simple('ѡ')),
+	('Ѣ', Casing::Simple('ѣ')),
+	('Ѥ', Casing::Simple('ѥ')),
+	('Ѧ', Casing::Simple('ѧ')),
+	('Ѩ', Casing::Simple('ѩ')),
+	('Ѫ', Casing::Simple('ѫ')),
+	('Ѭ', Casing::Simple('ѭ')),
+	('Ѯ', Casing::Simple('ѯ')),
+	('Ѱ', Casing::Simple('ѱ')),
+	('Ѳ', Casing::Simple('ѳ')),
+	('Ѵ', Casing::Simple('ѵ')),
+	('Ѷ', Casing::Simple('ѷ')),
+	('Ѹ', Casing::Simple('ѹ')),
+	('Ѻ', Casing::Simple('ѻ')),
+	('Ѽ', Casing::Simple('ѽ')),
+	('Ѿ', Casing::Simple('ѿ')),
+	('Ҁ', Casing::Simple('ҁ')),
+	('Ҋ', Casing::Simple('ҋ')),
+	('Ҍ', Casing::Simple('ҍ')),
+	('Ҏ', Casing::Simple('ҏ')),
+	('Ґ', Casing::Simple('ґ')),
+	('Ғ', Casing::Simple('ғ')),
+	('Ҕ', Casing::Simple('ҕ')),
+	('Җ', Casing::Simple('җ')),
+	('Ҙ', Casing::Simple('ҙ')),
+	('Қ', Casing::Simple('қ')),
+	('Ҝ', Casing::Simple('ҝ')),
+	('Ҟ', Casing::Simple('ҟ')),
+	('Ҡ', Casing::Simple('ҡ')),
+	('Ң', Casing::Simple('ң')),
+	('Ҥ', Casing::Simple('ҥ')),
+	('Ҧ', Casing::Simple('ҧ')),
+	('Ҩ', Casing::Simple('ҩ')),
+	('Ҫ', Casing::Simple('ҫ')),
+	('Ҭ', Casing::Simple('ҭ')),
+	('Ү', Casing::Simple('ү')),
+	('Ұ', Casing::Simple('ұ')),
+	('Ҳ', Casing::Simple('ҳ')),
+	('Ҵ', Casing::Simple('ҵ')),
+	('Ҷ', Casing::Simple('ҷ')),
+	('Ҹ', Casing::Simple('ҹ')),
+	('Һ', Casing::Simple('һ')),
+	('Ҽ', Casing::Simple('ҽ')),
+	('Ҿ', Casing::Simple('ҿ')),
+	('Ӏ', Casing::Simple('ӏ')),
+	('Ӂ', Casing::Simple('ӂ')),
+	('Ӄ', Casing::Simple('ӄ')),
+	('Ӆ', Casing::Simple('ӆ')),
+	('Ӈ', Casing::Simple('ӈ')),
+	('Ӊ', Casing::Simple('ӊ')),
+	('Ӌ', Casing::Simple('ӌ')),
+	('Ӎ', Casing::Simple('ӎ')),
+	('Ӑ', Casing::Simple('ӑ')),
+	('Ӓ', Casing::Simple('ӓ')),
+	('Ӕ', Casing::Simple('ӕ')),
+	('Ӗ', Casing::Simple('ӗ')),
+	('Ә', Casing::Simple('ә')),
+	('Ӛ', Casing::Simple('ӛ')),
+	('Ӝ', Casing::Simple('ӝ')),
+	('Ӟ', Casing::Simple('ӟ')),
+	('Ӡ', Casing::Simple('ӡ')),
+	('Ӣ', Casing::Simple('ӣ')),
+	('Ӥ', Casing::Simple('ӥ')),
+	('Ӧ', Casing::Simple('ӧ')),
+	('Ө', Casing::Simple('ө')),
+	('Ӫ', Casing::Simple('ӫ')),
+	('Ӭ', Casing::Simple('ӭ')),
+	('Ӯ', Casing::Simple('ӯ')),
+	('Ӱ', Casing::Simple('ӱ')),
+	('Ӳ', Casing::Simple('ӳ')),
+	('Ӵ', Casing::Simple('ӵ')),
+	('Ӷ', Casing::Simple('ӷ')),
+	('Ӹ', Casing::Simple('ӹ')),
+	('Ӻ', Casing::Simple('ӻ')),
+	('Ӽ', Casing::Simple('ӽ')),
+	('Ӿ', Casing::Simple('ӿ')),
+	('Ԁ', Casing::Simple('ԁ')),
+	('Ԃ', Casing::Simple('ԃ')),
+	('Ԅ', Casing::Simple('ԅ')),
+	('Ԇ', Casing::Simple('ԇ')),
+	('Ԉ', Casing::Simple('ԉ')),
+	('Ԋ', Casing::Simple('ԋ')),
+	('Ԍ', Casing::Simple('ԍ')),
+	('Ԏ', Casing::Simple('ԏ')),
+	('Ԑ', Casing::Simple('ԑ')),
+	('Ԓ', Casing::Simple('ԓ')),
+	('Ԕ', Casing::Simple('ԕ')),
+	('Ԗ', Casing::Simple('ԗ')),
+	('Ԙ', Casing::Simple('ԙ')),
+	('Ԛ', Casing::Simple('ԛ')),
+	('Ԝ', Casing::Simple('ԝ')),
+	('Ԟ', Casing::Simple('ԟ')),
+	('Ԡ', Casing::Simple('ԡ')),
+	('Ԣ', Casing::Simple('ԣ')),
+	('Ԥ', Casing::Simple('ԥ')),
+	('Ԧ', Casing::Simple('ԧ')),
+	('Ԩ', Casing::Simple('ԩ')),
+	('Ԫ', Casing::Simple('ԫ')),
+	('Ԭ', Casing::Simple('ԭ')),
+	('Ԯ', Casing::Simple('ԯ')),
+	('Ա', Casing::Simple('ա')),
+	('Բ', Casing::Simple('բ')),
+	('Գ', Casing::Simple('գ')),
+	('Դ', Casing::Simple('դ')),
+	('Ե', Casing::Simple('ե')),
+	('Զ', Casing::Simple('զ')),
+	('Է', Casing::Simple('է')),
+	('Ը', Casing::Simple('ը')),
+	('Թ', Casing::Simple('թ')),
+	('Ժ', Casing::Simple('ժ')),
+	('Ի', Casing::Simple('ի')),
+	('Լ', Casing::Simple('լ')),
+	('Խ', Casing::Simple('խ')),
+	('Ծ', Casing::Simple('ծ')),
+	('Կ', Casing::Simple('կ')),
+	('Հ', Casing::Simple('հ')),
+	('Ձ', Casing::Simple('ձ')),
+	('Ղ', Casing::Simple('ղ')),
+	('Ճ', Casing::Simple('ճ')),
+	('Մ', Casing::Simple('մ')),
+	('Յ', Casing::Simple('յ')),
+	('Ն', Casing::Simple('ն')),
+	('Շ', Casing::Simple('շ')),
+	('Ո', Casing::Simple('ո')),
+	('Չ', Casing::Simple('չ')),
+	('Պ', Casing::Simple('պ')),
+	('Ջ', Casing::Simple('ջ')),
+	('Ռ', Casing::Simple('ռ')),
+	('Ս', Casing::Simple('ս')),
+	('Վ', Casing::Simple('վ')),
+	('Տ', Casing::Simple('տ')),
+	('Ր', Casing::Simple('ր')),
+	('Ց', Casing::Simple('ց')),
+	('Ւ', Casing::Simple('ւ')),
+	('Փ', Casing::Simple('փ')),
+	('Ք', Casing::Simple('ք')),
+	('Օ', Casing::Simple('օ')),
+	('Ֆ', Casing::Simple('ֆ')),
+	('և', Casing::Complex(&['ե', 'ւ'])),
+	('Ⴀ', Casing::Simple('ⴀ')),
+	('Ⴁ', Casing::Simple('ⴁ')),
+	('Ⴂ', Casing::Simple('ⴂ')),
+	('Ⴃ', Casing::Simple('ⴃ')),
+	('Ⴄ', Casing::Simple('ⴄ')),
+	('Ⴅ', Casing::Simple('ⴅ')),
+	('Ⴆ', Casing::Simple('ⴆ')),
+	('Ⴇ', Casing::Simple('ⴇ')),
+	('Ⴈ', Casing::Simple('ⴈ')),
+	('Ⴉ', Casing::Simple('ⴉ')),
+	('Ⴊ', Casing::Simple('ⴊ')),
+	('Ⴋ', Casing::Simple('ⴋ')),
+	('Ⴌ', Casing::Simple('ⴌ')),
+	('Ⴍ', Casing::Simple('ⴍ')),
+	('Ⴎ', Casing::Simple('ⴎ')),
+	('Ⴏ', Casing::Simple('ⴏ')),
+	('Ⴐ', Casing::Simple('ⴐ')),
+	('Ⴑ', Casing::Simple('ⴑ')),
+	('Ⴒ', Casing::Simple('ⴒ')),
+	('Ⴓ', Casing::Simple('ⴓ')),
+	('Ⴔ', Casing::Simple('ⴔ')),
+	('Ⴕ', Casing::Simple('ⴕ')),
+	('Ⴖ', Casing::Simple('ⴖ')),
+	('Ⴗ', Casing::Simple('ⴗ')),
+	('Ⴘ', Casing::Simple('ⴘ')),
+	('Ⴙ', Casing::Simple('ⴙ')),
+	('Ⴚ', Casing::Simple('ⴚ')),
+	('Ⴛ', Casing::Simple('ⴛ')),
+	('Ⴜ', Casing::Simple('ⴜ')),
+	('Ⴝ', Casing::Simple('ⴝ')),
+	('Ⴞ', Casing::Simple('ⴞ')),
+	('Ⴟ', Casing::Simple('ⴟ')),
+	('Ⴠ', Casing::Simple('ⴠ')),
+	('Ⴡ', Casing::Simple('ⴡ')),
+	('Ⴢ', Casing::Simple('ⴢ')),
+	('Ⴣ', Casing::Simple('ⴣ')),
+	('Ⴤ', Casing::Simple('ⴤ')),
+	('Ⴥ', Casing::Simple('ⴥ')),
+	('Ⴧ', Casing::Simple('ⴧ')),
+	('Ⴭ', Casing::Simple('ⴭ')),
+	('ᏸ', Casing::Simple('Ᏸ')),
+	('ᏹ', Casing::Simple('Ᏹ')),
+	('ᏺ', Casing::Simple('Ᏺ')),
+	('ᏻ', Casing::Simple('Ᏻ')),
+	('ᏼ', Casing::Simple('Ᏼ')),
+	('ᏽ', Casing::Simple('Ᏽ')),
+	('ᲀ', Casing::Simple('в')),
+	('ᲁ', Casing::Simple('д')),
+	('ᲂ', Casing::Simple('о')),
+	('ᲃ', Casing::Simple('с')),
+	('ᲄ', Casing::Simple('т')),
+	('ᲅ', Casing::Simple('т')),
+	('ᲆ', Casing::Simple('ъ')),
+	('ᲇ', Casing::Simple('ѣ')),
+	('ᲈ', Casing::Simple('ꙋ')),
+	('Ა', Casing::Simple('ა')),
+	('Ბ', Casing::Simple('ბ')),
+	('Გ', Casing::Simple('გ')),
+	('Დ', Casing::Simple('დ')),
+	('Ე', Casing::Simple('ე')),
+	('Ვ', Casing::Simple('ვ')),
+	('Ზ', Casing::Simple('ზ')),
+	('Თ', Casing::Simple('თ')),
+	('Ი', Casing::Simple('ი')),
+	('Კ', Casing::Simple('კ')),
+	('Ლ', Casing::Simple('ლ')),
+	('Მ', Casing::Simple('მ')),
+	('Ნ', Casing::Simple('ნ')),
+	('Ო', Casing::Simple('ო')),
+	('Პ', Casing::Simple('პ')),
+	('Ჟ', Casing::Simple('ჟ')),
+	('Რ', Casing::Simple('რ')),
+	('Ს', Casing::Simple('ს')),
+	('Ტ', Casing::Simple('ტ')),
+	('Უ', Casing::Simple('უ')),
+	('Ფ', Casing::Simple('ფ')),
+	('Ქ', Casing::Simple('ქ')),
+	('Ღ', Casing::Simple('ღ')),
+	('Ყ', Casing::Simple('ყ')),
+	('Შ', Casing::Simple('შ')),
+	('Ჩ', Casing::Simple('ჩ')),
+	('Ც', Casing::Simple('ც')),
+	('Ძ', Casing::Simple('ძ')),
+	('Წ', Casing::Simple('წ')),
+	('Ჭ', Casing::Simple('ჭ')),
+	('Ხ', Casing::Simple('ხ')),
+	('Ჯ', Casing::Simple('ჯ')),
+	('Ჰ', Casing::Simple('ჰ')),
+	('Ჱ', Casing::Simple('ჱ')),
+	('Ჲ', Casing::Simple('ჲ')),
+	('Ჳ', Casing::Simple('ჳ')),
+	('Ჴ', Casing::Simple('ჴ')),
+	('Ჵ', Casing::Simple('ჵ')),
+	('Ჶ', Casing::Simple('ჶ')),
+	('Ჷ', Casing::Simple('ჷ')),
+	('Ჸ', Casing::Simple('ჸ')),
+	('Ჹ', Casing::Simple('ჹ')),
+	('Ჺ', Casing::Simple('ჺ')),
+	('Ჽ', Casing::Simple('ჽ')),
+	('Ჾ', Casing::Simple('ჾ')),
+	('Ჿ', Casing::Simple('ჿ')),
+	('Ḁ', Casing::Simple('ḁ')),
+	('Ḃ', Casing::Simple('ḃ')),
+	('Ḅ', Casing::Simple('ḅ')),
+	('Ḇ', Casing::Simple('ḇ')),
+	('Ḉ', Casing::Simple('ḉ')),
+	('Ḋ', Casing::Simple('ḋ')),
+	('Ḍ', Casing::Simple('ḍ')),
+	('Ḏ', Casing::Simple('ḏ')),
+	('Ḑ', Casing::Simple('ḑ')),
+	('Ḓ', Casing::Simple('ḓ')),
+	('Ḕ', Casing::Simple('ḕ')),
+	('Ḗ', Casing::Simple('ḗ')),
+	('Ḙ', Casing::Simple('ḙ')),
+	('Ḛ', Casing::Simple('ḛ')),
+	('Ḝ', Casing::Simple('ḝ')),
+	('Ḟ', Casing::Simple('ḟ')),
+	('Ḡ', Casing::Simple('ḡ')),
+	('Ḣ', Casing::Simple('ḣ')),
+	('Ḥ', Casing::Simple('ḥ')),
+	('Ḧ', Casing::Simple('ḧ')),
+	('Ḩ', Casing::Simple('ḩ')),
+	('Ḫ', Casing::Simple('ḫ')),
+	('Ḭ', Casing::Simple('ḭ')),
+	('Ḯ', Casing::Simple('ḯ')),
+	('Ḱ', Casing::Simple('ḱ')),
+	('Ḳ', Casing::Simple('ḳ')),
+	('Ḵ', Casing::Simple('ḵ')),
+	('Ḷ', Casing::Simple('ḷ')),
+	('Ḹ', Casing::Simple('ḹ')),
+	('Ḻ', Casing::Simple('ḻ')),
+	('Ḽ', Casing::Simple('ḽ')),
+	('Ḿ', Casing::Simple('ḿ')),
+	('Ṁ', Casing::Simple('ṁ')),
+	('Ṃ', Casing::Simple('ṃ')),
+	('Ṅ', Casing::Simple('ṅ')),
+	('Ṇ', Casing::Simple('ṇ')),
+	('Ṉ', Casing::Simple('ṉ')),
+	('Ṋ', Casing::Simple('ṋ')),
+	('Ṍ', Casing::Simple('ṍ')),
+	('Ṏ', Casing::Simple('ṏ')),
+	('Ṑ', Casing::Simple('ṑ')),
+	('Ṓ', Casing::Simple('ṓ')),
+	('Ṕ', Casing::Simple('ṕ')),
+	('Ṗ', Casing::Simple('ṗ')),
+	('Ṙ', Casing::Simple('ṙ')),
+	('Ṛ', Casing::Simple('ṛ')),
+	('Ṝ', Casing::Simple('ṝ')),
+	('Ṟ', Casing::Simple('ṟ')),
+	('Ṡ', Casing::Simple('ṡ')),
+	('Ṣ', Casing::Simple('ṣ')),
+	('Ṥ', Casing::Simple('ṥ')),
+	('Ṧ', Casing::Simple('ṧ')),
+	('Ṩ', Casing::Simple('ṩ')),
+	('Ṫ', Casing::Simple('ṫ')),
+	('Ṭ', Casing::Simple('ṭ')),
+	('Ṯ', Casing::Simple('ṯ')),
+	('Ṱ', Casing::Simple('ṱ')),
+	('Ṳ', Casing::Simple('ṳ')),
+	('Ṵ', Casing::Simple('ṵ')),
+	('Ṷ', Casing::Simple('ṷ')),
+	('Ṹ', Casing::Simple('ṹ')),
+	('Ṻ', Casing::Simple('ṻ')),
+	('Ṽ', Casing::Simple('ṽ')),
+	('Ṿ', Casing::Simple('ṿ')),
+	('Ẁ', Casing::Simple('ẁ')),
+	('Ẃ', Casing::Simple('ẃ')),
+	('Ẅ', Casing::Simple('ẅ')),
+	('Ẇ', Casing::Simple('ẇ')),
+	('Ẉ', Casing::Simple('ẉ')),
+	('Ẋ', Casing::Simple('ẋ')),
+	('Ẍ', Casing::Simple('ẍ')),
+	('Ẏ', Casing::Simple('ẏ')),
+	('Ẑ', Casing::Simple('ẑ')),
+	('Ẓ', Casing::Simple('ẓ')),
+	('Ẕ', Casing::Simple('ẕ')),
+	('ẖ', Casing::Complex(&['h', '\u{331}'])),
+	('ẗ', Casing::Complex(&['t', '\u{308}'])),
+	('ẘ', Casing::Complex(&['w', '\u{30a}'])),
+	('ẙ', Casing::Complex(&['y', '\u{30a}'])),
+	('ẚ', Casing::Complex(&['a', 'ʾ'])),
+	('ẛ', Casing::Simple('ṡ')),
+	('ẞ', Casing::Complex(&['s', 's'])),
+	('Ạ', Casing::Simple('ạ')),
+	('Ả', Casing::Simple('ả')),
+	('Ấ', Casing::Simple('ấ')),
+	('Ầ', Casing::Simple('ầ')),
+	('Ẩ', Casing::Simple('ẩ')),
+	('Ẫ', Casing::Simple('ẫ')),
+	('Ậ', Casing::Simple('ậ')),
+	('Ắ', Casing::Simple('ắ')),
+	('Ằ', Casing::Simple('ằ')),
+	('Ẳ', Casing::Simple('ẳ')),
+	('Ẵ', Casing::Simple('ẵ')),
+	('Ặ', Casing::Simple('ặ')),
+	('Ẹ', Casing::Simple('ẹ')),
+	('Ẻ', Casing::Simple('ẻ')),
+	('Ẽ', Casing::Simple('ẽ')),
+	('Ế', Casing::Simple('ế')),
+	('Ề', Casing::Simple('ề')),
+	('Ể', Casing::Simple('ể')),
+	('Ễ', Casing::Simple('ễ')),
+	('Ệ', Casing::Simple('ệ')),
+	('Ỉ', Casing::Simple('ỉ')),
+	('Ị', Casing::Simple('ị')),
+	('Ọ', Casing::Simple('ọ')),
+	('Ỏ', Casing::Simple('ỏ')),
+	('Ố', Casing::Simple('ố')),
+	('Ồ', Casing::Simple('ồ')),
+	('Ổ', Casing::Simple('ổ')),
+	('Ỗ', Casing::Simple('ỗ')),
+	('Ộ', Casing::Simple('ộ')),
+	('Ớ', Casing::Simple('ớ')),
+	('Ờ', Casing::Simple('ờ')),
+	('Ở', Casing::Simple('ở')),
+	('Ỡ', Casing::Simple('ỡ')),
+	('Ợ', Casing::Simple('ợ')),
+	('Ụ', Casing::Simple('ụ')),
+	('Ủ', Casing::Simple('ủ')),
+	('Ứ', Casing::Simple('ứ')),
+	('Ừ', Casing::Simple('ừ')),
+	('Ử', Casing::Simple('ử')),
+	('Ữ', Casing::Simple('ữ')),
+	('Ự', Casing::Simple('ự')),
+	('Ỳ', Casing::Simple('ỳ')),
+	('Ỵ', Casing::Simple('ỵ')),
+	('Ỷ', Casing::Simple('ỷ')),
+	('Ỹ', Casing::Simple('ỹ')),
+	('Ỻ', Casing::Simple('ỻ')),
+	('Ỽ', Casing::Simple('ỽ')),
+	('Ỿ', Casing::Simple('ỿ')),
+	('Ἀ', Casing::Simple('ἀ')),
+	('Ἁ', Casing::Simple('ἁ')),
+	('Ἂ', Casing::Simple('ἂ')),
+	('Ἃ', Casing::Simple('ἃ')),
+	('Ἄ', Casing::Simple('ἄ')),
+	('Ἅ', Casing::Simple('ἅ')),
+	('Ἆ', Casing::Simple('ἆ')),
+	('Ἇ', Casing::Simple('ἇ')),
+	('Ἐ', Casing::Simple('ἐ')),
+	('Ἑ', Casing::Simple('ἑ')),
+	('Ἒ', Casing::Simple('ἒ')),
+	('Ἓ', Casing::Simple('ἓ')),
+	('Ἔ', Casing::Simple('ἔ')),
+	('Ἕ', Casing::Simple('ἕ')),
+	('Ἠ', Casing::Simple('ἠ')),
+	('Ἡ', Casing::Simple('ἡ')),
+	('Ἢ', Casing::Simple('ἢ')),
+	('Ἣ', Casing::Simple('ἣ')),
+	('Ἤ', Casing::Simple('ἤ')),
+	('Ἥ', Casing::Simple('ἥ')),
+	('Ἦ', Casing::Simple('ἦ')),
+	('Ἧ', Casing::Simple('ἧ')),
+	('Ἰ', Casing::Simple('ἰ')),
+	('Ἱ', Casing::Simple('ἱ')),
+	('Ἲ', Casing::Simple('ἲ')),
+	('Ἳ', Casing::Simple('ἳ')),
+	('Ἴ', Casing::Simple('ἴ')),
+	('Ἵ', Casing::Simple('ἵ')),
+	('Ἶ', Casing::Simple('ἶ')),
+	('Ἷ', Casing::Simple('ἷ')),
+	('Ὀ', Casing::Simple('ὀ')),
+	('Ὁ', Casing::Simple('ὁ')),
+	('Ὂ', Casing::Simple('ὂ')),
+	('Ὃ', Casing::Simple('ὃ')),
+	('Ὄ', Casing::Simple('ὄ')),
+	('Ὅ', Casing::Simple('ὅ')),
+	('ὐ', Casing::Complex(&['υ', '\u{313}'])),
+	('ὒ', Casing::Complex(&['υ', '\u{313}', '\u{300}'])),
+	('ὔ', Casing::Complex(&['υ', '\u{313}', '\u{301}'])),
+	('ὖ', Casing::Complex(&['υ', '\u{313}', '\u{342}'])),
+	('Ὑ', Casing::Simple('ὑ')),
+	('Ὓ', Casing::Simple('ὓ')),
+	('Ὕ', Casing::Simple('ὕ')),
+	('Ὗ', Casing::Simple('ὗ')),
+	('Ὠ', Casing::Simple('ὠ')),
+	('Ὡ', Casing::Simple('ὡ')),
+	('Ὢ', Casing::Simple('ὢ')),
+	('Ὣ', Casing::Simple('ὣ')),
+	('Ὤ', Casing::Simple('ὤ')),
+	('Ὥ', Casing::Simple('ὥ')),
+	('Ὦ', Casing::Simple('ὦ')),
+	('Ὧ', Casing::Simple('ὧ')),
+	('ᾀ', Casing::Complex(&['ἀ', 'ι'])),
+	('ᾁ', Casing::Complex(&['ἁ', 'ι'])),
+	('ᾂ', Casing::Complex(&['ἂ', 'ι'])),
+	('ᾃ', Casing::Complex(&['ἃ', 'ι'])),
+	('ᾄ', Casing::Complex(&['ἄ', 'ι'])),
+	('ᾅ', Casing::Complex(&['ἅ', 'ι'])),
+	('ᾆ', Casing::Complex(&['ἆ', 'ι'])),
+	('ᾇ', Casing::Complex(&['ἇ', 'ι'])),
+	('ᾈ', Casing::Complex(&['ἀ', 'ι'])),
+	('ᾉ', Casing::Complex(&['ἁ', 'ι'])),
+	('ᾊ', Casing::Complex(&['ἂ', 'ι'])),
+	('ᾋ', Casing::Complex(&['ἃ', 'ι'])),
+	('ᾌ', Casing::Complex(&['ἄ', 'ι'])),
+	('ᾍ', Casing::Complex(&['ἅ', 'ι'])),
+	('ᾎ', Casing::Complex(&['ἆ', 'ι'])),
+	('ᾏ', Casing::Complex(&['ἇ', 'ι'])),
+	('ᾐ', Casing::Complex(&['ἠ', 'ι'])),
+	('ᾑ', Casing::Complex(&['ἡ', 'ι'])),
+	('ᾒ', Casing::Complex(&['ἢ', 'ι'])),
+	('ᾓ', Casing::Complex(&['ἣ', 'ι'])),
+	('ᾔ', Casing::Complex(&['ἤ', 'ι'])),
+	('ᾕ', Casing::Complex(&['ἥ', 'ι'])),
+	('ᾖ', Casing::Complex(&['ἦ', 'ι'])),
+	('ᾗ', Casing::Complex(&['ἧ', 'ι'])),
+	('ᾘ', Casing::Complex(&['ἠ', 'ι'])),
+	('ᾙ', Casing::Complex(&['ἡ', 'ι'])),
+	('ᾚ', Casing::Complex(&['ἢ', 'ι'])),
+	('ᾛ', Casing::Complex(&['ἣ', 'ι'])),
+	('ᾜ', Casing::Complex(&['ἤ', 'ι'])),
+	('ᾝ', Casing::Complex(&['ἥ', 'ι'])),
+	('ᾞ', Casing::Complex(&['ἦ', 'ι'])),
+	('ᾟ', Casing::Complex(&['ἧ', 'ι'])),
+	('ᾠ', Casing::Complex(&['ὠ', 'ι'])),
+	('ᾡ', Casing::Complex(&['ὡ', 'ι'])),
+	('ᾢ', Casing::Complex(&['ὢ', 'ι'])),
+	('ᾣ', Casing::Complex(&['ὣ', 'ι'])),
+	('ᾤ', Casing::Complex(&['ὤ', 'ι'])),
+	('ᾥ', Casing::Complex(&['ὥ', 'ι'])),
+	('ᾦ', Casing::Complex(&['ὦ', 'ι'])),
+	('ᾧ', Casing::Complex(&['ὧ', 'ι'])),
+	('ᾨ', Casing::Complex(&['ὠ', 'ι'])),
+	('ᾩ', Casing::Complex(&['ὡ', 'ι'])),
+	('ᾪ', Casing::Complex(&['ὢ', 'ι'])),
+	('ᾫ', Casing::Complex(&['ὣ', 'ι'])),
+	('ᾬ', Casing::Complex(&['ὤ', 'ι'])),
+	('ᾭ', Casing::Complex(&['ὥ', 'ι'])),
+	('ᾮ', Casing::Complex(&['ὦ', 'ι'])),
+	('ᾯ', Casing::Complex(&['ὧ', 'ι'])),
+	('ᾲ', Casing::Complex(&['ὰ', 'ι'])),
+	('ᾳ', Casing::Complex(&['α', 'ι'])),
+	('ᾴ', Casing::Complex(&['ά', 'ι'])),
+	('ᾶ', Casing::Complex(&['α', '\u{342}'])),
+	('ᾷ', Casing::Complex(&['α', '\u{342}', 'ι'])),
+	('Ᾰ', Casing::Simple('ᾰ')),
+	('Ᾱ', Casing::Simple('ᾱ')),
+	('Ὰ', Casing::Simple('ὰ')),
+	('Ά', Casing::Simple('ά')),
+	('ᾼ', Casing::Complex(&['α', 'ι'])),
+	('ι', Casing::Simple('ι')),
+	('ῂ', Casing::Complex(&['ὴ', 'ι'])),
+	('ῃ', Casing::Complex(&['η', 'ι'])),
+	('ῄ', Casing::Complex(&['ή', 'ι'])),
+	('ῆ', Casing::Complex(&['η', '\u{342}'])),
+	('ῇ', Casing::Complex(&['η', '\u{342}', 'ι'])),
+	('Ὲ', Casing::Simple('ὲ')),
+	('Έ', Casing::Simple('έ')),
+	('Ὴ', Casing::Simple('ὴ')),
+	('Ή', Casing::Simple('ή')),
+	('ῌ', Casing::Complex(&['η', 'ι'])),
+	('ῒ', Casing::Complex(&['ι', '\u{308}', '\u{300}'])),
+	('ΐ', Casing::Complex(&['ι', '\u{308}', '\u{301}'])),
+	('ῖ', Casing::Complex(&['ι', '\u{342}'])),
+	('ῗ', Casing::Complex(&['ι', '\u{308}', '\u{342}'])),
+	('Ῐ', Casing::Simple('ῐ')),
+	('Ῑ', Casing::Simple('ῑ')),
+	('Ὶ', Casing::Simple('ὶ')),
+	('Ί', Casing::Simple('ί')),
+	('ῢ', Casing::Complex(&['υ', '\u{308}', '\u{300}'])),
+	('ΰ', Casing::Complex(&['υ', '\u{308}', '\u{301}'])),
+	('ῤ', Casing::Complex(&['ρ', '\u{313}'])),
+	('ῦ', Casing::Complex(&['υ', '\u{342}'])),
+	('ῧ', Casing::Complex(&['υ', '\u{308}', '\u{342}'])),
+	('Ῠ', Casing::Simple('ῠ')),
+	('Ῡ', Casing::Simple('ῡ')),
+	('Ὺ', Casing::Simple('ὺ')),
+	('Ύ', Casing::Simple('ύ')),
+	('Ῥ', Casing::Simple('ῥ')),
+	('ῲ', Casing::Complex(&['ὼ', 'ι'])),
+	('ῳ', Casing::Complex(&['ω', 'ι'])),
+	('ῴ', Casing::Complex(&['ώ', 'ι'])),
+	('ῶ', Casing::Complex(&['ω', '\u{342}'])),
+	('ῷ', Casing::Complex(&['ω', '\u{342}', 'ι'])),
+	('Ὸ', Casing::Simple('ὸ')),
+	('Ό', Casing::Simple('ό')),
+	('Ὼ', Casing::Simple('ὼ')),
+	('Ώ', Casing::Simple('ώ')),
+	('ῼ', Casing::Complex(&['ω', 'ι'])),
+	('Ω', Casing::Simple('ω')),
+	('K', Casing::Simple('k')),
+	('Å', Casing::Simple('å')),
+	('Ⅎ', Casing::Simple('ⅎ')),
+	('Ⅰ', Casing::Simple('ⅰ')),
+	('Ⅱ', Casing::Simple('ⅱ')),
+	('Ⅲ', Casing::Simple('ⅲ')),
+	('Ⅳ', Casing::Simple('ⅳ')),
+	('Ⅴ', Casing::Simple('ⅴ')),
+	('Ⅵ', Casing::Simple('ⅵ')),
+	('Ⅶ', Casing::Simple('ⅶ')),
+	('Ⅷ', Casing::Simple('ⅷ')),
+	('Ⅸ', Casing::Simple('ⅸ')),
+	('Ⅹ', Casing::Simple('ⅹ')),
+	('Ⅺ', Casing::Simple('ⅺ')),
+	('Ⅻ', Casing::Simple('ⅻ')),
+	('Ⅼ', Casing::Simple('ⅼ')),
+	('Ⅽ', Casing::Simple('ⅽ')),
+	('Ⅾ', Casing::Simple('ⅾ')),
+	('Ⅿ', Casing::Simple('ⅿ')),
+	('Ↄ', Casing::Simple('ↄ')),
+	('Ⓐ', Casing::Simple('ⓐ')),
+	('Ⓑ', Casing::Simple('ⓑ')),
+	('Ⓒ', Casing::Simple('ⓒ')),
+	('Ⓓ', Casing::Simple('ⓓ')),
+	('Ⓔ', Casing::Simple('ⓔ')),
+	('Ⓕ', Casing::Simple('ⓕ')),
+	('Ⓖ', Casing::Simple('ⓖ')),
+	('Ⓗ', Casing::Simple('ⓗ')),
+	('Ⓘ', Casing::Simple('ⓘ')),
+	('Ⓙ', Casing::Simple('ⓙ')),
+	('Ⓚ', Casing::Simple('ⓚ')),
+	('Ⓛ', Casing::Simple('ⓛ')),
+	('Ⓜ', Casing::Simple('ⓜ')),
+	('Ⓝ', Casing::Simple('ⓝ')),
+	('Ⓞ', Casing::Simple('ⓞ')),
+	('Ⓟ', Casing::Simple('ⓟ')),
+	('Ⓠ', Casing::Simple('ⓠ')),
+	('Ⓡ', Casing::Simple('ⓡ')),
+	('Ⓢ', Casing::Simple('ⓢ')),
+	('Ⓣ', Casing::Simple('ⓣ')),
+	('Ⓤ', Casing::Simple('ⓤ')),
+	('Ⓥ', Casing::Simple('ⓥ')),
+	('Ⓦ', Casing::Simple('ⓦ')),
+	('Ⓧ', Casing::Simple('ⓧ')),
+	('Ⓨ', Casing::Simple('ⓨ')),
+	('Ⓩ', Casing::Simple('ⓩ')),
+	('Ⰰ', Casing::Simple('ⰰ')),
+	('Ⰱ', Casing::Simple('ⰱ')),
+	('Ⰲ', Casing::Simple('ⰲ')),
+	('Ⰳ', Casing::Simple('ⰳ')),
+	('Ⰴ', Casing::Simple('ⰴ')),
+	('Ⰵ', Casing::Simple('ⰵ')),
+	('Ⰶ', Casing::Simple('ⰶ')),
+	('Ⰷ', Casing::Simple('ⰷ')),
+	('Ⰸ', Casing::Simple('ⰸ')),
+	('Ⰹ', Casing::Simple('ⰹ')),
+	('Ⰺ', Casing::Simple('ⰺ')),
+	('Ⰻ', Casing::Simple('ⰻ')),
+	('Ⰼ', Casing::Simple('ⰼ')),
+	('Ⰽ', Casing::Simple('ⰽ')),
+	('Ⰾ', Casing::Simple('ⰾ')),
+	('Ⰿ', Casing::Simple('ⰿ')),
+	('Ⱀ', Casing::Simple('ⱀ')),
+	('Ⱁ', Casing::Simple('ⱁ')),
+	('Ⱂ', Casing::Simple('ⱂ')),
+	('Ⱃ', Casing::Simple('ⱃ')),
+	('Ⱄ', Casing::Simple('ⱄ')),
+	('Ⱅ', Casing::Simple('ⱅ')),
+	('Ⱆ', Casing::Simple('ⱆ')),
+	('Ⱇ', Casing::Simple('ⱇ')),
+	('Ⱈ', Casing::Simple('ⱈ')),
+	('Ⱉ', Casing::Simple('ⱉ')),
+	('Ⱊ', Casing::Simple('ⱊ')),
+	('Ⱋ', Casing::Simple('ⱋ')),
+	('Ⱌ', Casing::Simple('ⱌ')),
+	('Ⱍ', Casing::Simple('ⱍ')),
+	('Ⱎ', Casing::Simple('ⱎ')),
+	('Ⱏ', Casing::Simple('ⱏ')),
+	('Ⱐ', Casing::Simple('ⱐ')),
+	('Ⱑ', Casing::Simple('ⱑ')),
+	('Ⱒ', Casing::Simple('ⱒ')),
+	('Ⱓ', Casing::Simple('ⱓ')),
+	('Ⱔ', Casing::Simple('ⱔ')),
+	('Ⱕ', Casing::Simple('ⱕ')),
+	('Ⱖ', Casing::Simple('ⱖ')),
+	('Ⱗ', Casing::Simple('ⱗ')),
+	('Ⱘ', Casing::Simple('ⱘ')),
+	('Ⱙ', Casing::Simple('ⱙ')),
+	('Ⱚ', Casing::Simple('ⱚ')),
+	('Ⱛ', Casing::Simple('ⱛ')),
+	('Ⱜ', Casing::Simple('ⱜ')),
+	('Ⱝ', Casing::Simple('ⱝ')),
+	('Ⱞ', Casing::Simple('ⱞ')),
+	('Ⱟ', Casing::Simple('ⱟ')),
+	('Ⱡ', Casing::Simple('ⱡ')),
+	('Ɫ', Casing::Simple('ɫ')),
+	('Ᵽ', Casing::Simple('ᵽ')),
+	('Ɽ', Casing::Simple('ɽ')),
+	('Ⱨ', Casing::Simple('ⱨ')),
+	('Ⱪ', Casing::Simple('ⱪ')),
+	('Ⱬ', Casing::Simple('ⱬ')),
+	('Ɑ', Casing::Simple('ɑ')),
+	('Ɱ', Casing::Simple('ɱ')),
+	('Ɐ', Casing::Simple('ɐ')),
+	('Ɒ', Casing::Simple('ɒ')),
+	('Ⱳ', Casing::Simple('ⱳ')),
+	('Ⱶ', Casing::Simple('ⱶ')),
+	('Ȿ', Casing::Simple('ȿ')),
+	('Ɀ', Casing::Simple('ɀ')),
+	('Ⲁ', Casing::Simple('ⲁ')),
+	('Ⲃ', Casing::Simple('ⲃ')),
+	('Ⲅ', Casing::Simple('ⲅ')),
+	('Ⲇ', Casing::Simple('ⲇ')),
+	('Ⲉ', Casing::Simple('ⲉ')),
+	('Ⲋ', Casing::Simple('ⲋ')),
+	('Ⲍ', Casing::Simple('ⲍ')),
+	('Ⲏ', Casing::Simple('ⲏ')),
+	('Ⲑ', Casing::Simple('ⲑ')),
+	('Ⲓ', Casing::Simple('ⲓ')),
+	('Ⲕ', Casing::Simple('ⲕ')),
+	('Ⲗ', Casing::Simple('ⲗ')),
+	('Ⲙ', Casing::Simple('ⲙ')),
+	('Ⲛ', Casing::Simple('ⲛ')),
+	('Ⲝ', Casing::Simple('ⲝ')),
+	('Ⲟ', Casing::Simple('ⲟ')),
+	('Ⲡ', Casing::Simple('ⲡ')),
+	('Ⲣ', Casing::Simple('ⲣ')),
+	('Ⲥ', Casing::Simple('ⲥ')),
+	('Ⲧ', Casing::Simple('ⲧ')),
+	('Ⲩ', Casing::Simple('ⲩ')),
+	('Ⲫ', Casing::Simple('ⲫ')),
+	('Ⲭ', Casing::Simple('ⲭ')),
+	('Ⲯ', Casing::Simple('ⲯ')),
+	('Ⲱ', Casing::Simple('ⲱ')),
+	('Ⲳ', Casing::Simple('ⲳ')),
+	('Ⲵ', Casing::Simple('ⲵ')),
+	('Ⲷ', Casing::Simple('ⲷ')),
+	('Ⲹ', Casing::Simple('ⲹ')),
+	('Ⲻ', Casing::Simple('ⲻ')),
+	('Ⲽ', Casing::Simple('ⲽ')),
+	('Ⲿ', Casing::Simple('ⲿ')),
+	('Ⳁ', Casing::Simple('ⳁ')),
+	('Ⳃ', Casing::Simple('ⳃ')),
+	('Ⳅ', Casing::Simple('ⳅ')),
+	('Ⳇ', Casing::Simple('ⳇ')),
+	('Ⳉ', Casing::Simple('ⳉ')),
+	('Ⳋ', Casing::Simple('ⳋ')),
+	('Ⳍ', Casing::Simple('ⳍ')),
+	('Ⳏ', Casing::Simple('ⳏ')),
+	('Ⳑ', Casing::Simple('ⳑ')),
+	('Ⳓ', Casing::Simple('ⳓ')),
+	('Ⳕ', Casing::Simple('ⳕ')),
+	('Ⳗ', Casing::Simple('ⳗ')),
+	('Ⳙ', Casing::Simple('ⳙ')),
+	('Ⳛ', Casing::Simple('ⳛ')),
+	('Ⳝ', Casing::Simple('ⳝ')),
+	('Ⳟ', Casing::Simple('ⳟ')),
+	('Ⳡ', Casing::Simple('ⳡ')),
+	('Ⳣ', Casing::Simple('ⳣ')),
+	('Ⳬ', Casing::Simple('ⳬ')),
+	('Ⳮ', Casing::Simple('ⳮ')),
+	('Ⳳ', Casing::Simple('ⳳ')),
+	('Ꙁ', Casing::Simple('ꙁ')),
+	('Ꙃ', Casing::Simple('ꙃ')),
+	('Ꙅ', Casing::Simple('ꙅ')),
+	('Ꙇ', Casing::Simple('ꙇ')),
+	('Ꙉ', Casing::Simple('ꙉ')),
+	('Ꙋ', Casing::Simple('ꙋ')),
+	('Ꙍ', Casing::Simple('ꙍ')),
+	('Ꙏ', Casing::Simple('ꙏ')),
+	('Ꙑ', Casing::Simple('ꙑ')),
+	('Ꙓ', Casing::Simple('ꙓ')),
+	('Ꙕ', Casing::Simple('ꙕ')),
+	('Ꙗ', Casing::Simple('ꙗ')),
+	('Ꙙ', Casing::Simple('ꙙ')),
+	('Ꙛ', Casing::Simple('ꙛ')),
+	('Ꙝ', Casing::Simple('ꙝ')),
+	('Ꙟ', Casing::Simple('ꙟ')),
+	('Ꙡ', Casing::Simple('ꙡ')),
+	('Ꙣ', Casing::Simple('ꙣ')),
+	('Ꙥ', Casing::Simple('ꙥ')),
+	('Ꙧ', Casing::Simple('ꙧ')),
+	('Ꙩ', Casing::Simple('ꙩ')),
+	('Ꙫ', Casing::Simple('ꙫ')),
+	('Ꙭ', Casing::Simple('ꙭ')),
+	('Ꚁ', Casing::Simple('ꚁ')),
+	('Ꚃ', Casing::Simple('ꚃ')),
+	('Ꚅ', Casing::Simple('ꚅ')),
+	('Ꚇ', Casing::Simple('ꚇ')),
+	('Ꚉ', Casing::Simple('ꚉ')),
+	('Ꚋ', Casing::Simple('ꚋ')),
+	('Ꚍ', Casing::Simple('ꚍ')),
+	('Ꚏ', Casing::Simple('ꚏ')),
+	('Ꚑ', Casing::Simple('ꚑ')),
+	('Ꚓ', Casing::Simple('ꚓ')),
+	('Ꚕ', Casing::Simple('ꚕ')),
+	('Ꚗ', Casing::Simple('ꚗ')),
+	('Ꚙ', Casing::Simple('ꚙ')),
+	('Ꚛ', Casing::Simple('ꚛ')),
+	('Ꜣ', Casing::Simple('ꜣ')),
+	('Ꜥ', Casing::Simple('ꜥ')),
+	('Ꜧ', Casing::Simple('ꜧ')),
+	('Ꜩ', Casing::Simple('ꜩ')),
+	('Ꜫ', Casing::Simple('ꜫ')),
+	('Ꜭ', Casing::Simple('ꜭ')),
+	('Ꜯ', Casing::Simple('ꜯ')),
+	('Ꜳ', Casing::Simple('ꜳ')),
+	('Ꜵ', Casing::Simple('ꜵ')),
+	('Ꜷ', Casing::Simple('ꜷ')),
+	('Ꜹ', Casing::Simple('ꜹ')),
+	('Ꜻ', Casing::Simple('ꜻ')),
+	('Ꜽ', Casing::Simple('ꜽ')),
+	('Ꜿ', Casing::Simple('ꜿ')),
+	('Ꝁ', Casing::Simple('ꝁ')),
+	('Ꝃ', Casing::Simple('ꝃ')),
+	('Ꝅ', Casing::Simple('ꝅ')),
+	('Ꝇ', Casing::Simple('ꝇ')),
+	('Ꝉ', Casing::Simple('ꝉ')),
+	('Ꝋ', Casing::Simple('ꝋ')),
+	('Ꝍ', Casing::Simple('ꝍ')),
+	('Ꝏ', Casing::Simple('ꝏ')),
+	('Ꝑ', Casing::Simple('ꝑ')),
+	('Ꝓ', Casing::Simple('ꝓ')),
+	('Ꝕ', Casing::Simple('ꝕ')),
+	('Ꝗ', Casing::Simple('ꝗ')),
+	('Ꝙ', Casing::Simple('ꝙ')),
+	('Ꝛ', Casing::Simple('ꝛ')),
+	('Ꝝ', Casing::Simple('ꝝ')),
+	('Ꝟ', Casing::Simple('ꝟ')),
+	('Ꝡ', Casing::Simple('ꝡ')),
+	('Ꝣ', Casing::Simple('ꝣ')),
+	('Ꝥ', Casing::Simple('ꝥ')),
+	('Ꝧ', Casing::Simple('ꝧ')),
+	('Ꝩ', Casing::Simple('ꝩ')),
+	('Ꝫ', Casing::Simple('ꝫ')),
+	('Ꝭ', Casing::Simple('ꝭ')),
+	('Ꝯ', Casing::Simple('ꝯ')),
+	('Ꝺ', Casing::Simple('ꝺ')),
+	('Ꝼ', Casing::Simple('ꝼ')),
+	('Ᵹ', Casing::Simple('ᵹ')),
+	('Ꝿ', Casing::Simple('ꝿ')),
+	('Ꞁ', Casing::Simple('ꞁ')),
+	('Ꞃ', Casing::Simple('ꞃ')),
+	('Ꞅ', Casing::Simple('ꞅ')),
+	('Ꞇ', Casing::Simple('ꞇ')),
+	('Ꞌ', Casing::Simple('ꞌ')),
+	('Ɥ', Casing::Simple('ɥ')),
+	('Ꞑ', Casing::Simple('ꞑ')),
+	('Ꞓ', Casing::Simple('ꞓ')),
+	('Ꞗ', Casing::Simple('ꞗ')),
+	('Ꞙ', Casing::Simple('ꞙ')),
+	('Ꞛ', Casing::Simple('ꞛ')),
+	('Ꞝ', Casing::Simple('ꞝ')),
+	('Ꞟ', Casing::Simple('ꞟ')),
+	('Ꞡ', Casing::Simple('ꞡ')),
+	('Ꞣ', Casing::Simple('ꞣ')),
+	('Ꞥ', Casing::Simple('ꞥ')),
+	('Ꞧ', Casing::Simple('ꞧ')),
+	('Ꞩ', Casing::Simple('ꞩ')),
+	('Ɦ', Casing::Simple('ɦ')),
+	('Ɜ', Casing::Simple('ɜ')),
+	('Ɡ', Casing::Simple('ɡ')),
+	('Ɬ', Casing::Simple('ɬ')),
+	('Ɪ', Casing::Simple('ɪ')),
+	('Ʞ', Casing::Simple('ʞ')),
+	('Ʇ', Casing::Simple('ʇ')),
+	('Ʝ', Casing::Simple('ʝ')),
+	('Ꭓ', Casing::Simple('ꭓ')),
+	('Ꞵ', Casing::Simple('ꞵ')),
+	('Ꞷ', Casing::Simple('ꞷ')),
+	('Ꞹ', Casing::Simple('ꞹ')),
+	('Ꞻ', Casing::Simple('ꞻ')),
+	('Ꞽ', Casing::Simple('ꞽ')),
+	('Ꞿ', Casing::Simple('ꞿ')),
+	('Ꟁ', Casing::Simple('ꟁ')),
+	('Ꟃ', Casing::Simple('ꟃ')),
+	('Ꞔ', Casing::Simple('ꞔ')),
+	('Ʂ', Casing::Simple('ʂ')),
+	('Ᶎ', Casing::Simple('ᶎ')),
+	('Ꟈ', Casing::Simple('ꟈ')),
+	('Ꟊ', Casing::Simple('ꟊ')),
+	('Ꟑ', Casing::Simple('ꟑ')),
+	('Ꟗ', Casing::Simple('ꟗ')),
+	('Ꟙ', Casing::Simple('ꟙ')),
+	('Ꟶ', Casing::Simple('ꟶ')),
+	('ꭰ', Casing::Simple('Ꭰ')),
+	('ꭱ', Casing::Simple('Ꭱ')),
+	('ꭲ', Casing::Simple('Ꭲ')),
+	('ꭳ', Casing::Simple('Ꭳ')),
+	('ꭴ', Casing::Simple('Ꭴ')),
+	('ꭵ', Casing::Simple('Ꭵ')),
+	('ꭶ', Casing::Simple('Ꭶ')),
+	('ꭷ', Casing::Simple('Ꭷ')),
+	('ꭸ', Casing::Simple('Ꭸ')),
+	('ꭹ', Casing::Simple('Ꭹ')),
+	('ꭺ', Casing::Simple('Ꭺ')),
+	('ꭻ', Casing::Simple('Ꭻ')),
+	('ꭼ', Casing::Simple('Ꭼ')),
+	('ꭽ', Casing::Simple('Ꭽ')),
+	('ꭾ', Casing::Simple('Ꭾ')),
+	('ꭿ', Casing::Simple('Ꭿ')),
+	('ꮀ', Casing::Simple('Ꮀ')),
+	('ꮁ', Casing::Simple('Ꮁ')),
+	('ꮂ', Casing::Simple('Ꮂ')),
+	('ꮃ', Casing::Simple('Ꮃ')),
+	('ꮄ', Casing::Simple('Ꮄ')),
+	('ꮅ', Casing::Simple('Ꮅ')),
+	('ꮆ', Casing::Simple('Ꮆ')),
+	('ꮇ', Casing::Simple('Ꮇ')),
+	('ꮈ', Casing::Simple('Ꮈ')),
+	('ꮉ', Casing::Simple('Ꮉ')),
+	('ꮊ', Casing::Simple('Ꮊ')),
+	('ꮋ', Casing::Simple('Ꮋ')),
+	('ꮌ', Casing::Simple('Ꮌ')),
+	('ꮍ', Casing::Simple('Ꮍ')),
+	('ꮎ', Casing::Simple('Ꮎ')),
+	('ꮏ', Casing::Simple('Ꮏ')),
+	('ꮐ', Casing::Simple('Ꮐ')),
+	('ꮑ', Casing::Simple('Ꮑ')),
+	('ꮒ', Casing::Simple('Ꮒ')),
+	('ꮓ', Casing::Simple('Ꮓ')),
+	('ꮔ', Casing::Simple('Ꮔ')),
+	('ꮕ', Casing::Simple('Ꮕ')),
+	('ꮖ', Casing::Simple('Ꮖ')),
+	('ꮗ', Casing::Simple('Ꮗ')),
+	('ꮘ', Casing::Simple('Ꮘ')),
+	('ꮙ', Casing::Simple('Ꮙ')),
+	('ꮚ', Casing::Simple('Ꮚ')),
+	('ꮛ', Casing::Simple('Ꮛ')),
+	('ꮜ', Casing::Simple('Ꮜ')),
+	('ꮝ', Casing::Simple('Ꮝ')),
+	('ꮞ', Casing::Simple('Ꮞ')),
+	('ꮟ', Casing::Simple('Ꮟ')),
+	('ꮠ', Casing::Simple('Ꮠ')),
+	('ꮡ', Casing::Simple('Ꮡ')),
+	('ꮢ', Casing::Simple('Ꮢ')),
+	('ꮣ', Casing::Simple('Ꮣ')),
+	('ꮤ', Casing::Simple('Ꮤ')),
+	('ꮥ', Casing::Simple('Ꮥ')),
+	('ꮦ', Casing::Simple('Ꮦ')),
+	('ꮧ', Casing::Simple('Ꮧ')),
+	('ꮨ', Casing::Simple('Ꮨ')),
+	('ꮩ', Casing::Simple('Ꮩ')),
+	('ꮪ', Casing::Simple('Ꮪ')),
+	('ꮫ', Casing::Simple('Ꮫ')),
+	('ꮬ', Casing::Simple('Ꮬ')),
+	('ꮭ', Casing::Simple('Ꮭ')),
+	('ꮮ', Casing::Simple('Ꮮ')),
+	('ꮯ', Casing::Simple('Ꮯ')),
+	('ꮰ', Casing::Simple('Ꮰ')),
+	('ꮱ', Casing::Simple('Ꮱ')),
+	('ꮲ', Casing::Simple('Ꮲ')),
+	('ꮳ', Casing::Simple('Ꮳ')),
+	('ꮴ', Casing::Simple('Ꮴ')),
+	('ꮵ', Casing::Simple('Ꮵ')),
+	('ꮶ', Casing::Simple('Ꮶ')),
+	('ꮷ', Casing::Simple('Ꮷ')),
+	('ꮸ', Casing::Simple('Ꮸ')),
+	('ꮹ', Casing::Simple('Ꮹ')),
+	('ꮺ', Casing::Simple('Ꮺ')),
+	('ꮻ', Casing::Simple('Ꮻ')),
+	('ꮼ', Casing::Simple('Ꮼ')),
+	('ꮽ', Casing::Simple('Ꮽ')),
+	('ꮾ', Casing::Simple('Ꮾ')),
+	('ꮿ', Casing::Simple('Ꮿ')),
+	('ﬀ', Casing::Complex(&['f', 'f'])),
+	('ﬁ', Casing::Complex(&['f', 'i'])),
+	('ﬂ', Casing::Complex(&['f', 'l'])),
+	('ﬃ', Casing::Complex(&['f', 'f', 'i'])),
+	('ﬄ', Casing::Complex(&['f', 'f', 'l'])),
+	('ﬅ', Casing::Complex(&['s', 't'])),
+	('ﬆ', Casing::Complex(&['s', 't'])),
+	('ﬓ', Casing::Complex(&['մ', 'ն'])),
+	('ﬔ', Casing::Complex(&['մ', 'ե'])),
+	('ﬕ', Casing::Complex(&['մ', 'ի'])),
+	('ﬖ', Casing::Complex(&['վ', 'ն'])),
+	('ﬗ', Casing::Complex(&['մ', 'խ'])),
+	('Ａ', Casing::Simple('ａ')),
+	('Ｂ', Casing::Simple('ｂ')),
+	('Ｃ', Casing::Simple('ｃ')),
+	('Ｄ', Casing::Simple('ｄ')),
+	('Ｅ', Casing::Simple('ｅ')),
+	('Ｆ', Casing::Simple('ｆ')),
+	('Ｇ', Casing::Simple('ｇ')),
+	('Ｈ', Casing::Simple('ｈ')),
+	('Ｉ', Casing::Simple('ｉ')),
+	('Ｊ', Casing::Simple('ｊ')),
+	('Ｋ', Casing::Simple('ｋ')),
+	('Ｌ', Casing::Simple('ｌ')),
+	('Ｍ', Casing::Simple('ｍ')),
+	('Ｎ', Casing::Simple('ｎ')),
+	('Ｏ', Casing::Simple('ｏ')),
+	('Ｐ', Casing::Simple('ｐ')),
+	('Ｑ', Casing::Simple('ｑ')),
+	('Ｒ', Casing::Simple('ｒ')),
+	('Ｓ', Casing::Simple('ｓ')),
+	('Ｔ', Casing::Simple('ｔ')),
+	('Ｕ', Casing::Simple('ｕ')),
+	('Ｖ', Casing::Simple('ｖ')),
+	('Ｗ', Casing::Simple('ｗ')),
+	('Ｘ', Casing::Simple('ｘ')),
+	('Ｙ', Casing::Simple('ｙ')),
+	('Ｚ', Casing::Simple('ｚ')),
+	('𐐀', Casing::Simple('𐐨')),
+	('𐐁', Casing::Simple('𐐩')),
+	('𐐂', Casing::Simple('𐐪')),
+	('𐐃', Casing::Simple('𐐫')),
+	('𐐄', Casing::Simple('𐐬')),
+	('𐐅', Casing::Simple('𐐭')),
+	('𐐆', Casing::Simple('𐐮')),
+	('𐐇', Casing::Simple('𐐯')),
+	('𐐈', Casing::Simple('𐐰')),
+	('𐐉', Casing::Simple('𐐱')),
+	('𐐊', Casing::Simple('𐐲')),
+	('𐐋', Casing::Simple('𐐳')),
+	('𐐌', Casing::Simple('𐐴')),
+	('𐐍', Casing::Simple('𐐵')),
+	('𐐎', Casing::Simple('𐐶')),
+	('𐐏', Casing::Simple('𐐷')),
+	('𐐐', Casing::Simple('𐐸')),
+	('𐐑', Casing::Simple('𐐹')),
+	('𐐒', Casing::Simple('𐐺')),
+	('𐐓', Casing::Simple('𐐻')),
+	('𐐔', Casing::Simple('𐐼')),
+	('𐐕', Casing::Simple('𐐽')),
+	('𐐖', Casing::Simple('𐐾')),
+	('𐐗', Casing::Simple('𐐿')),
+	('𐐘', Casing::Simple('𐑀')),
+	('𐐙', Casing::Simple('𐑁')),
+	('𐐚', Casing::Simple('𐑂')),
+	('𐐛', Casing::Simple('𐑃')),
+	('𐐜', Casing::Simple('𐑄')),
+	('𐐝', Casing::Simple('𐑅')),
+	('𐐞', Casing::Simple('𐑆')),
+	('𐐟', Casing::Simple('𐑇')),
+	('𐐠', Casing::Simple('𐑈')),
+	('𐐡', Casing::Simple('𐑉')),
+	('𐐢', Casing::Simple('𐑊')),
+	('𐐣', Casing::Simple('𐑋')),
+	('𐐤', Casing::Simple('𐑌')),
+	('𐐥', Casing::Simple('𐑍')),
+	('𐐦', Casing::Simple('𐑎')),
+	('𐐧', Casing::Simple('𐑏')),
+	('𐒰', Casing::Simple('𐓘')),
+	('𐒱', Casing::Simple('𐓙')),
+	('𐒲', Casing::Simple('𐓚')),
+	('𐒳', Casing::Simple('𐓛')),
+	('𐒴', Casing::Simple('𐓜')),
+	('𐒵', Casing::Simple('𐓝')),
+	('𐒶', Casing::Simple('𐓞')),
+	('𐒷', Casing::Simple('𐓟')),
+	('𐒸', Casing::Simple('𐓠')),
+	('𐒹', Casing::Simple('𐓡')),
+	('𐒺', Casing::Simple('𐓢')),
+	('𐒻', Casing::Simple('𐓣')),
+	('𐒼', Casing::Simple('𐓤')),
+	('𐒽', Casing::Simple('𐓥')),
+	('𐒾', Casing::Simple('𐓦')),
+	('𐒿', Casing::Simple('𐓧')),
+	('𐓀', Casing::Simple('𐓨')),
+	('𐓁', Casing::Simple('𐓩')),
+	('𐓂', Casing::Simple('𐓪')),
+	('𐓃', Casing::Simple('𐓫')),
+	('𐓄', Casing::Simple('𐓬')),
+	('𐓅', Casing::Simple('𐓭')),
+	('𐓆', Casing::Simple('𐓮')),
+	('𐓇', Casing::Simple('𐓯')),
+	('𐓈', Casing::Simple('𐓰')),
+	('𐓉', Casing::Simple('𐓱')),
+	('𐓊', Casing::Simple('𐓲')),
+	('𐓋', Casing::Simple('𐓳')),
+	('𐓌', Casing::Simple('𐓴')),
+	('𐓍', Casing::Simple('𐓵')),
+	('𐓎', Casing::Simple('𐓶')),
+	('𐓏', Casing::Simple('𐓷')),
+	('𐓐', Casing::Simple('𐓸')),
+	('𐓑', Casing::Simple('𐓹')),
+	('𐓒', Casing::Simple('𐓺')),
+	('𐓓', Casing::Simple('𐓻')),
+	('𐕰', Casing::Simple('𐖗')),
+	('𐕱', Casing::Simple('𐖘')),
+	('𐕲', Casing::Simple('𐖙')),
+	('𐕳', Casing::Simple('𐖚')),
+	('𐕴', Casing::Simple('𐖛')),
+	('𐕵', Casing::Simple('𐖜')),
+	('𐕶', Casing::Simple('𐖝')),
+	('𐕷', Casing::Simple('𐖞')),
+	('𐕸', Casing::Simple('𐖟')),
+	('𐕹', Casing::Simple('𐖠')),
+	('𐕺', Casing::Simple('𐖡')),
+	('𐕼', Casing::Simple('𐖣')),
+	('𐕽', Casing::Simple('𐖤')),
+	('𐕾', Casing::Simple('𐖥')),
+	('𐕿', Casing::Simple('𐖦')),
+	('𐖀', Casing::Simple('𐖧')),
+	('𐖁', Casing::Simple('𐖨')),
+	('𐖂', Casing::Simple('𐖩')),
+	('𐖃', Casing::Simple('𐖪')),
+	('𐖄', Casing::Simple('𐖫')),
+	('𐖅', Casing::Simple('𐖬')),
+	('𐖆', Casing::Simple('𐖭')),
+	('𐖇', Casing::Simple('𐖮')),
+	('𐖈', Casing::Simple('𐖯')),
+	('𐖉', Casing::Simple('𐖰')),
+	('𐖊', Casing::Simple('𐖱')),
+	('𐖌', Casing::Simple('𐖳')),
+	('𐖍', Casing::Simple('𐖴')),
+	('𐖎', Casing::Simple('𐖵')),
+	('𐖏', Casing::Simple('𐖶')),
+	('𐖐', Casing::Simple('𐖷')),
+	('𐖑', Casing::Simple('𐖸')),
+	('𐖒', Casing::Simple('𐖹')),
+	('𐖔', Casing::Simple('𐖻')),
+	('𐖕', Casing::Simple('𐖼')),
+	('𐲀', Casing::Simple('𐳀')),
+	('𐲁', Casing::Simple('𐳁')),
+	('𐲂', Casing::Simple('𐳂')),
+	('𐲃', Casing::Simple('𐳃')),
+	('𐲄', Casing::Simple('𐳄')),
+	('𐲅', Casing::Simple('𐳅')),
+	('𐲆', Casing::Simple('𐳆')),
+	('𐲇', Casing::Simple('𐳇')),
+	('𐲈', Casing::Simple('𐳈')),
+	('𐲉', Casing::Simple('𐳉')),
+	('𐲊', Casing::Simple('𐳊')),
+	('𐲋', Casing::Simple('𐳋')),
+	('𐲌', Casing::Simple('𐳌')),
+	('𐲍', Casing::Simple('𐳍')),
+	('𐲎', Casing::Simple('𐳎')),
+	('𐲏', Casing::Simple('𐳏')),
+	('𐲐', Casing::Simple('𐳐')),
+	('𐲑', Casing::Simple('𐳑')),
+	('𐲒', Casing::Simple('𐳒')),
+	('𐲓', Casing::Simple('𐳓')),
+	('𐲔', Casing::Simple('𐳔')),
+	('𐲕', Casing::Simple('𐳕')),
+	('𐲖', Casing::Simple('𐳖')),
+	('𐲗', Casing::Simple('𐳗')),
+	('𐲘', Casing::Simple('𐳘')),
+	('𐲙', Casing::Simple('𐳙')),
+	('𐲚', Casing::Simple('𐳚')),
+	('𐲛', Casing::Simple('𐳛')),
+	('𐲜', Casing::Simple('𐳜')),
+	('𐲝', Casing::Simple('𐳝')),
+	('𐲞', Casing::Simple('𐳞')),
+	('𐲟', Casing::Simple('𐳟')),
+	('𐲠', Casing::Simple('𐳠')),
+	('𐲡', Casing::Simple('𐳡')),
+	('𐲢', Casing::Simple('𐳢')),
+	('𐲣', Casing::Simple('𐳣')),
+	('𐲤', Casing::Simple('𐳤')),
+	('𐲥', Casing::Simple('𐳥')),
+	('𐲦', Casing::Simple('𐳦')),
+	('𐲧', Casing::Simple('𐳧')),
+	('𐲨', Casing::Simple('𐳨')),
+	('𐲩', Casing::Simple('𐳩')),
+	('𐲪', Casing::Simple('𐳪')),
+	('𐲫', Casing::Simple('𐳫')),
+	('𐲬', Casing::Simple('𐳬')),
+	('𐲭', Casing::Simple('𐳭')),
+	('𐲮', Casing::Simple('𐳮')),
+	('𐲯', Casing::Simple('𐳯')),
+	('𐲰', Casing::Simple('𐳰')),
+	('𐲱', Casing::Simple('𐳱')),
+	('𐲲', Casing::Simple('𐳲')),
+	('𑢠', Casing::Simple('𑣀')),
+	('𑢡', Casing::Simple('𑣁')),
+	('𑢢', Casing::Simple('𑣂')),
+	('𑢣', Casing::Simple('𑣃')),
+	('𑢤', Casing::Simple('𑣄')),
+	('𑢥', Casing::Simple('𑣅')),
+	('𑢦', Casing::Simple('𑣆')),
+	('𑢧', Casing::Simple('𑣇')),
+	('𑢨', Casing::Simple('𑣈')),
+	('𑢩', Casing::Simple('𑣉')),
+	('𑢪', Casing::Simple('𑣊')),
+	('𑢫', Casing::Simple('𑣋')),
+	('𑢬', Casing::Simple('𑣌')),
+	('𑢭', Casing::Simple('𑣍')),
+	('𑢮', Casing::Simple('𑣎')),
+	('𑢯', Casing::Simple('𑣏')),
+	('𑢰', Casing::Simple('𑣐')),
+	('𑢱', Casing::Simple('𑣑')),
+	('𑢲', Casing::Simple('𑣒')),
+	('𑢳', Casing::Simple('𑣓')),
+	('𑢴', Casing::Simple('𑣔')),
+	('𑢵', Casing::Simple('𑣕')),
+	('𑢶', Casing::Simple('𑣖')),
+	('𑢷', Casing::Simple('𑣗')),
+	('𑢸', Casing::Simple('𑣘')),
+	('𑢹', Casing::Simple('𑣙')),
+	('𑢺', Casing::Simple('𑣚')),
+	('𑢻', Casing::Simple('𑣛')),
+	('𑢼', Casing::Simple('𑣜')),
+	('𑢽', Casing::Simple('𑣝')),
+	('𑢾', Casing::Simple('𑣞')),
+	('𑢿', Casing::Simple('𑣟')),
+	('𖹀', Casing::Simple('𖹠')),
+	('𖹁', Casing::Simple('𖹡')),
+	('𖹂', Casing::Simple('𖹢')),
+	('𖹃', Casing::Simple('𖹣')),
+	('𖹄', Casing::Simple('𖹤')),
+	('𖹅', Casing::Simple('𖹥')),
+	('𖹆', Casing::Simple('𖹦')),
+	('𖹇', Casing::Simple('𖹧')),
+	('𖹈', Casing::Simple('𖹨')),
+	('𖹉', Casing::Simple('𖹩')),
+	('𖹊', Casing::Simple('𖹪')),
+	('𖹋', Casing::Simple('𖹫')),
+	('𖹌', Casing::Simple('𖹬')),
+	('𖹍', Casing::Simple('𖹭')),
+	('𖹎', Casing::Simple('𖹮')),
+	('𖹏', Casing::Simple('𖹯')),
+	('𖹐', Casing::Simple('𖹰')),
+	('𖹑', Casing::Simple('𖹱')),
+	('𖹒', Casing::Simple('𖹲')),
+	('𖹓', Casing::Simple('𖹳')),
+	('𖹔', Casing::Simple('𖹴')),
+	('𖹕', Casing::Simple('𖹵')),
+	('𖹖', Casing::Simple('𖹶')),
+	('𖹗', Casing::Simple('𖹷')),
+	('𖹘', Casing::Simple('𖹸')),
+	('𖹙', Casing::Simple('𖹹')),
+	('𖹚', Casing::Simple('𖹺')),
+	('𖹛', Casing::Simple('𖹻')),
+	('𖹜', Casing::Simple('𖹼')),
+	('𖹝', Casing::Simple('𖹽')),
+	('𖹞', Casing::Simple('𖹾')),
+	('𖹟', Casing::Simple('𖹿')),
+	('𞤀', Casing::Simple('𞤢')),
+	('𞤁', Casing::Simple('𞤣')),
+	('𞤂', Casing::Simple('𞤤')),
+	('𞤃', Casing::Simple('𞤥')),
+	('𞤄', Casing::Simple('𞤦')),
+	('𞤅', Casing::Simple('𞤧')),
+	('𞤆', Casing::Simple('𞤨')),
+	('𞤇', Casing::Simple('𞤩')),
+	('𞤈', Casing::Simple('𞤪')),
+	('𞤉', Casing::Simple('𞤫')),
+	('𞤊', Casing::Simple('𞤬')),
+	('𞤋', Casing::Simple('𞤭')),
+	('𞤌', Casing::Simple('𞤮')),
+	('𞤍', Casing::Simple('𞤯')),
+	('𞤎', Casing::Simple('𞤰')),
+	('𞤏', Casing::Simple('𞤱')),
+	('𞤐', Casing::Simple('𞤲')),
+	('𞤑', Casing::Simple('𞤳')),
+	('𞤒', Casing::Simple('𞤴')),
+	('𞤓', Casing::Simple('𞤵')),
+	('𞤔', Casing::Simple('𞤶')),
+	('𞤕', Casing::Simple('𞤷')),
+	('𞤖', Casing::Simple('𞤸')),
+	('𞤗', Casing::Simple('𞤹')),
+	('𞤘', Casing::Simple('𞤺')),
+	('𞤙', Casing::Simple('𞤻')),
+	('𞤚', Casing::Simple('𞤼')),
+	('𞤛', Casing::Simple('𞤽')),
+	('𞤜', Casing::Simple('𞤾')),
+	('𞤝', Casing::Simple('𞤿')),
+	('𞤞', Casing::Simple('𞥀')),
+	('𞤟', Casing::Simple('𞥁')),
+	('𞤠', Casing::Simple('𞥂')),
+	('𞤡', Casing::Simple('𞥃')),
+];
+
 pub(crate) const DERIVED_AGE: [(UnicodeIndex, Age); 1694] = [
 	(UnicodeIndex::Range (0x00000, 0x001F5), Age::Version1_1),
 	(UnicodeIndex::Range (0x001F6, 0x001F9), Age::Version3_0),
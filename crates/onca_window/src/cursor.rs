@@ -0,0 +1,64 @@
+use crate::{os, PhysicalSize};
+
+/// System cursor shape
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CursorShape {
+    /// Default arrow cursor
+    Arrow,
+    /// Text selection cursor
+    IBeam,
+    /// Crosshair, e.g. for precise selection
+    Crosshair,
+    /// Pointing hand, e.g. for links
+    Hand,
+    /// Open hand, indicating something can be grabbed
+    Grab,
+    /// Closed hand, indicating something is being grabbed
+    ///
+    /// On windows, this is the same as `Grab`
+    Grabbing,
+    /// Horizontal resize, from the left to the right
+    ResizeHorizontal,
+    /// Vertical resize, from the top to the bottom
+    ResizeVertical,
+    /// Diagonal resize, from the top-left to the bottom-right
+    ResizeNwSe,
+    /// Diagonal resize, from the top-right to the bottom-left
+    ResizeNeSw,
+    /// Move in any direction
+    Move,
+    /// The action is not allowed
+    NotAllowed,
+    /// The application is busy and cannot be interacted with
+    Wait,
+    /// The application is busy, but can still be interacted with
+    Progress,
+    /// Help is available
+    Help,
+}
+
+/// Custom cursor
+pub struct Cursor {
+    os_cursor : os::OSCursor,
+}
+
+impl Cursor {
+    /// Create a cursor from RGBA pixels, stored from top to bottom, with 8 bits per channel.
+    ///
+    /// The hotspot is the location of the pixel that is used as the position of the cursor.
+    pub fn from_rgba(pixels: &[u8], size: PhysicalSize, hotspot: (u16, u16)) -> Cursor {
+        assert_eq!(pixels.len(), size.width as usize * size.height as usize * 4, "Cursor pixel data should contain width * height RGBA pixels");
+        assert!(hotspot.0 < size.width && hotspot.1 < size.height, "Cursor hotspot should be inside of the cursor");
+        Cursor { os_cursor: os::OSCursor::from_rgba(pixels, size, hotspot) }
+    }
+
+    pub(crate) fn get_os_cursor(&self) -> &os::OSCursor {
+        &self.os_cursor
+    }
+}
+
+/// Cursor shown while the cursor is over the client area of a window
+pub(crate) enum WindowCursor {
+    Shape(CursorShape),
+    Custom(Cursor),
+}
\ No newline at end of file
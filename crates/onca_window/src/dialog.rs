@@ -0,0 +1,153 @@
+use std::path::{Path, PathBuf};
+use onca_common::sys::is_on_main_thread;
+
+use crate::os;
+
+/// Buttons shown in a message box
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MessageBoxButtons {
+    /// An 'OK' button
+    Ok,
+    /// 'OK' and 'Cancel' buttons
+    OkCancel,
+    /// 'Yes' and 'No' buttons
+    YesNo,
+    /// 'Yes', 'No' and 'Cancel' buttons
+    YesNoCancel,
+    /// 'Retry' and 'Cancel' buttons
+    RetryCancel,
+    /// 'Abort', 'Retry' and 'Ignore' buttons
+    AbortRetryIgnore,
+}
+
+impl MessageBoxButtons {
+    /// Get the result when the message box is closed without pressing a button, or when it could not be shown.
+    pub(crate) fn dismiss_result(self) -> MessageBoxResult {
+        match self {
+            MessageBoxButtons::Ok => MessageBoxResult::Ok,
+            MessageBoxButtons::OkCancel => MessageBoxResult::Cancel,
+            MessageBoxButtons::YesNo => MessageBoxResult::No,
+            MessageBoxButtons::YesNoCancel => MessageBoxResult::Cancel,
+            MessageBoxButtons::RetryCancel => MessageBoxResult::Cancel,
+            MessageBoxButtons::AbortRetryIgnore => MessageBoxResult::Abort,
+        }
+    }
+}
+
+/// Severity of a message box, which defines the icon shown in it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MessageBoxSeverity {
+    /// Informational message
+    Info,
+    /// Warning message
+    Warning,
+    /// Error message
+    Error,
+}
+
+/// Button pressed to close a message box
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MessageBoxResult {
+    Ok,
+    Cancel,
+    Yes,
+    No,
+    Retry,
+    Abort,
+    Ignore,
+}
+
+/// Show a modal message box, blocking the calling thread until the user has closed it.
+///
+/// This does not depend on any window, so it can be used to report fatal errors, even before a window is created.
+/// If the message box cannot be shown, the result of dismissing the message box is returned, e.g. `Cancel` for `OkCancel`.
+pub fn message_box(title: &str, text: &str, buttons: MessageBoxButtons, severity: MessageBoxSeverity) -> MessageBoxResult {
+    os::dialog::message_box(title, text, buttons, severity)
+}
+
+/// File type filter of a file dialog
+pub(crate) struct FileFilter {
+    pub(crate) name       : String,
+    pub(crate) extensions : Vec<String>,
+}
+
+/// Native file dialog
+///
+/// Works as a builder, so functions can be chained to set any setting.
+pub struct FileDialog {
+    pub(crate) title        : Option<String>,
+    pub(crate) filters      : Vec<FileFilter>,
+    pub(crate) directory    : Option<PathBuf>,
+    pub(crate) file_name    : Option<String>,
+    pub(crate) multi_select : bool,
+}
+
+impl FileDialog {
+    /// Create a file dialog with the default settings.
+    pub fn new() -> FileDialog {
+        FileDialog {
+            title: None,
+            filters: Vec::new(),
+            directory: None,
+            file_name: None,
+            multi_select: false,
+        }
+    }
+
+    /// Set the title of the dialog.
+    pub fn with_title(mut self, title: &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// Add a file type filter, with the extensions it contains, without a leading '.', e.g. `with_filter("Images", &["png", "jpg"])`.
+    ///
+    /// The first filter will be selected when the dialog is shown.
+    pub fn with_filter(mut self, name: &str, extensions: &[&str]) -> Self {
+        self.filters.push(FileFilter {
+            name: name.to_string(),
+            extensions: extensions.iter().map(|ext| ext.to_string()).collect(),
+        });
+        self
+    }
+
+    /// Set the directory the dialog starts in.
+    pub fn with_directory<P: AsRef<Path>>(mut self, directory: P) -> Self {
+        self.directory = Some(directory.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set the file name that is initially filled in.
+    pub fn with_file_name(mut self, file_name: &str) -> Self {
+        self.file_name = Some(file_name.to_string());
+        self
+    }
+
+    /// Set whether multiple files can be selected when opening files.
+    pub fn multi_select(mut self, multi_select: bool) -> Self {
+        self.multi_select = multi_select;
+        self
+    }
+
+    /// Show a dialog to open one or more files, blocking until the user has closed it.
+    ///
+    /// Returns the selected files, or an empty array if the dialog was cancelled.
+    pub fn open(&self) -> Vec<PathBuf> {
+        assert!(is_on_main_thread(), "A file dialog should only be shown on the main thread");
+        os::dialog::open_file_dialog(self)
+    }
+
+    /// Show a dialog to select the location to save a file to, blocking until the user has closed it.
+    ///
+    /// Returns the selected location, or `None` if the dialog was cancelled.
+    pub fn save(&self) -> Option<PathBuf> {
+        assert!(is_on_main_thread(), "A file dialog should only be shown on the main thread");
+        os::dialog::save_file_dialog(self)
+    }
+}
+
+impl Default for FileDialog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
\ No newline at end of file
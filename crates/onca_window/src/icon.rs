@@ -11,6 +11,12 @@ impl Icon {
         Icon { os_icon: os::OSIcon::from_path(path, size) }
     }
 
+    /// Create an icon from RGBA pixels, stored from top to bottom, with 8 bits per channel.
+    pub fn from_rgba(pixels: &[u8], size: PhysicalSize) -> Icon {
+        assert_eq!(pixels.len(), size.width as usize * size.height as usize * 4, "Icon pixel data should contain width * height RGBA pixels");
+        Icon { os_icon: os::OSIcon::from_rgba(pixels, size) }
+    }
+
     pub(crate) fn get_os_icon(&self) -> &os::OSIcon {
         &self.os_icon
     }
@@ -5,6 +5,12 @@ mod os;
 mod icon;
 pub use icon::*;
 
+mod cursor;
+pub use cursor::*;
+
+mod dialog;
+pub use dialog::*;
+
 mod monitor;
 pub use monitor::*;
 
@@ -17,6 +23,9 @@ pub use window::*;
 mod window_manager;
 pub use window_manager::*;
 
+mod raw_handle;
+pub use raw_handle::*;
+
 
 
 pub const LOG_CAT : LogCategory = LogCategory::new("Windowing");
@@ -1,10 +1,10 @@
-use onca_common::utils;
-use crate::{os, Window, PhysicalSize};
+use onca_common::{utils, event_listener::EventListener};
+use crate::{os, Window, WindowSettings, PhysicalSize, OSWindowHandle};
 
 pub type OSMonitorHandle = os::MonitorHandle;
 
 /// Monitor rect
-#[derive(Clone, Copy, Default, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
 pub struct MonitorRect {
     pub x      : i32,
     pub y      : i32,
@@ -33,6 +33,22 @@ pub struct Monitor {
     pub(crate) modes        : Vec<MonitorMode>,
 }
 
+/// Monitor configuration change
+/// 
+/// Monitors are identified by their device name, see `Monitor::dev_name()`.
+pub enum MonitorEvent<'a> {
+    /// A monitor has been connected
+    Added(&'a Monitor),
+    /// A monitor has been disconnected, the monitor contains the last known info of the monitor
+    /// 
+    /// Any window or swap-chain on the monitor should be moved to another monitor, as the output is no longer available.
+    Removed(&'a Monitor),
+    /// The display mode, position, DPI, or primary monitor has changed
+    ModeChanged(&'a Monitor),
+}
+
+pub type MonitorEventListener = dyn for<'a> EventListener<MonitorEvent<'a>>;
+
 impl Monitor {
     /// Enumerate over all attached monitors and return a array of them.
     pub fn enumerate_monitors() -> Vec<Monitor> {
@@ -45,8 +61,15 @@ impl Monitor {
     }
 
     /// Get the monitor with the largest overlap with a window. If a monitor cannot be detected, return `None`.
-    pub fn from_window(window: Window) -> Option<Monitor> {
-        os::monitor::get_monitor_from_hwnd(window.os_handle().hwnd())
+    pub fn from_window(window: &Window) -> Option<Monitor> {
+        os::monitor::get_monitor_from_handle(window.os_handle())
+    }
+
+    /// Get the monitor with the largest overlap with the window referenced by an OS window handle. If a monitor cannot be detected, return `None`.
+    /// 
+    /// This can be used by systems that only store the OS handle of a window, e.g. a swap-chain.
+    pub fn from_window_handle(handle: OSWindowHandle) -> Option<Monitor> {
+        os::monitor::get_monitor_from_handle(handle)
     }
 
     /// Get the monitor with the largest overlap of the given rect. If the rect does not overlap a monitor, return `None`.
@@ -104,6 +127,11 @@ impl Monitor {
         self.dpi
     }
 
+    /// Get the scale to convert from logical pixels to physical pixels on this monitor.
+    pub fn scale_factor(&self) -> f32 {
+        self.dpi as f32 / WindowSettings::DEFAULT_DPI as f32
+    }
+
     /// Check if the monitor is the primary monitor.
     pub fn is_primary(&self) -> bool {
         self.primary
@@ -120,9 +148,35 @@ impl Monitor {
     }
 
     /// Get all available monitor modes
+    /// 
+    /// The modes are sorted from the smallest to the largest resolution, and within the same resolution, from the lowest to the highest refresh rate and bits per pixel.
     pub fn modes(&self) -> &Vec<MonitorMode> {
         &self.modes
     }
+
+    /// Find the monitor mode with the given resolution and refresh rate.
+    /// 
+    /// If no refresh rate is given, the mode with the highest refresh rate will be returned.
+    /// When multiple modes match, the one with the highest bits per pixel will be returned.
+    pub fn find_mode(&self, size: PhysicalSize, refresh_rate: Option<u32>) -> Option<MonitorMode> {
+        self.modes.iter().rev()
+            .find(|mode| mode.size == size && refresh_rate.map_or(true, |refresh_rate| mode.refesh_rate == refresh_rate))
+            .copied()
+    }
+
+    /// Get the mode with the highest resolution, refresh rate and bits per pixel.
+    pub fn highest_mode(&self) -> Option<MonitorMode> {
+        self.modes.last().copied()
+    }
+
+    /// Check if the configuration of the monitor differs from the configuration in `other`, which should be the same monitor.
+    pub(crate) fn has_config_changed(&self, other: &Monitor) -> bool {
+        self.mon_rect != other.mon_rect ||
+        self.work_rect != other.work_rect ||
+        self.dpi != other.dpi ||
+        self.refresh_rate != other.refresh_rate ||
+        self.primary != other.primary
+    }
 }
 
 
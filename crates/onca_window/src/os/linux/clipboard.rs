@@ -0,0 +1,15 @@
+use super::{wayland, x11, WindowManagerData};
+
+pub(crate) fn get_text(data: &mut WindowManagerData) -> Option<String> {
+    match data {
+        WindowManagerData::X11(data) => x11::clipboard::get_text(data),
+        WindowManagerData::Wayland(data) => wayland::clipboard::get_text(data),
+    }
+}
+
+pub(crate) fn set_text(data: &mut WindowManagerData, text: &str) -> bool {
+    match data {
+        WindowManagerData::X11(data) => x11::clipboard::set_text(data, text),
+        WindowManagerData::Wayland(data) => wayland::clipboard::set_text(data, text),
+    }
+}
\ No newline at end of file
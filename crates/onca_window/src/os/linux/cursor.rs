@@ -0,0 +1,34 @@
+use crate::PhysicalSize;
+use super::{backend, wayland::cursor::WaylandCursor, x11::cursor::X11Cursor, Backend};
+
+enum CursorKind {
+    X11(X11Cursor),
+    Wayland(WaylandCursor),
+}
+
+pub struct OSCursor(CursorKind);
+
+impl OSCursor {
+    pub(crate) fn from_rgba(pixels: &[u8], size: PhysicalSize, hotspot: (u16, u16)) -> OSCursor {
+        match backend() {
+            Some(Backend::Wayland) => OSCursor(CursorKind::Wayland(WaylandCursor::from_rgba(pixels, size, hotspot))),
+            _ => OSCursor(CursorKind::X11(X11Cursor::from_rgba(pixels, size, hotspot))),
+        }
+    }
+
+    /// Get the cursor if it was created by the X11 backend
+    pub(crate) fn x11(&self) -> Option<&X11Cursor> {
+        match &self.0 {
+            CursorKind::X11(cursor) => Some(cursor),
+            CursorKind::Wayland(_) => None,
+        }
+    }
+
+    /// Get the cursor if it was created by the Wayland backend
+    pub(crate) fn wayland(&self) -> Option<&WaylandCursor> {
+        match &self.0 {
+            CursorKind::Wayland(cursor) => Some(cursor),
+            CursorKind::X11(_) => None,
+        }
+    }
+}
\ No newline at end of file
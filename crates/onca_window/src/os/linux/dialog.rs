@@ -0,0 +1,138 @@
+// TODO(jel): Use the desktop portal over D-Bus, so dialogs are native to the desktop environment, also when zenity is not installed
+use std::{
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+use onca_logging::log_error;
+
+use crate::{FileDialog, MessageBoxButtons, MessageBoxResult, MessageBoxSeverity, LOG_CAT};
+
+/// Separator between the selected files, which cannot be part of a path
+const FILE_SEPARATOR: &str = "\n";
+
+/// Run zenity, which is used to show GTK dialogs from outside of a GTK application
+fn run_zenity(args: &[String]) -> Option<Output> {
+    match Command::new("zenity").args(args).output() {
+        Ok(output) => Some(output),
+        Err(err) => {
+            log_error!(LOG_CAT, "Failed to show a dialog, zenity could not be run ({err})");
+            None
+        },
+    }
+}
+
+pub(crate) fn message_box(title: &str, text: &str, buttons: MessageBoxButtons, severity: MessageBoxSeverity) -> MessageBoxResult {
+    let icon = match severity {
+        MessageBoxSeverity::Info => "dialog-information",
+        MessageBoxSeverity::Warning => "dialog-warning",
+        MessageBoxSeverity::Error => "dialog-error",
+    };
+
+    let mut args = vec![format!("--title={title}"), format!("--text={text}"), "--no-markup".to_string()];
+    // A question only has 2 buttons, with an optional extra button, which prints its label when pressed
+    let (ok, cancel, extra) = match buttons {
+        MessageBoxButtons::Ok => {
+            let kind = match severity {
+                MessageBoxSeverity::Info => "--info",
+                MessageBoxSeverity::Warning => "--warning",
+                MessageBoxSeverity::Error => "--error",
+            };
+            args.push(kind.to_string());
+            (MessageBoxResult::Ok, MessageBoxResult::Ok, None)
+        },
+        MessageBoxButtons::OkCancel => (MessageBoxResult::Ok, MessageBoxResult::Cancel, None),
+        MessageBoxButtons::YesNo => (MessageBoxResult::Yes, MessageBoxResult::No, None),
+        MessageBoxButtons::YesNoCancel => (MessageBoxResult::Yes, MessageBoxResult::No, Some(MessageBoxResult::Cancel)),
+        MessageBoxButtons::RetryCancel => (MessageBoxResult::Retry, MessageBoxResult::Cancel, None),
+        MessageBoxButtons::AbortRetryIgnore => (MessageBoxResult::Retry, MessageBoxResult::Abort, Some(MessageBoxResult::Ignore)),
+    };
+    if buttons != MessageBoxButtons::Ok {
+        args.push("--question".to_string());
+        args.push(format!("--icon-name={icon}"));
+        args.push(format!("--ok-label={}", button_label(ok)));
+        args.push(format!("--cancel-label={}", button_label(cancel)));
+        if let Some(extra) = extra {
+            args.push(format!("--extra-button={}", button_label(extra)));
+        }
+    }
+
+    let Some(output) = run_zenity(&args) else {
+        log_error!(LOG_CAT, "Failed to show message box '{title}': {text}");
+        return buttons.dismiss_result();
+    };
+
+    if output.status.success() {
+        return ok;
+    }
+    match extra {
+        Some(extra) if String::from_utf8_lossy(&output.stdout).trim_end() == button_label(extra) => extra,
+        _ => cancel,
+    }
+}
+
+fn button_label(result: MessageBoxResult) -> &'static str {
+    match result {
+        MessageBoxResult::Ok => "OK",
+        MessageBoxResult::Cancel => "Cancel",
+        MessageBoxResult::Yes => "Yes",
+        MessageBoxResult::No => "No",
+        MessageBoxResult::Retry => "Retry",
+        MessageBoxResult::Abort => "Abort",
+        MessageBoxResult::Ignore => "Ignore",
+    }
+}
+
+pub(crate) fn open_file_dialog(settings: &FileDialog) -> Vec<PathBuf> {
+    let mut args = get_file_selection_args(settings);
+    if settings.multi_select {
+        args.push("--multiple".to_string());
+        args.push(format!("--separator={FILE_SEPARATOR}"));
+    }
+
+    // A non-zero exit code means the dialog was cancelled
+    match run_zenity(&args) {
+        Some(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .trim_end_matches('\n')
+            .split(FILE_SEPARATOR)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+pub(crate) fn save_file_dialog(settings: &FileDialog) -> Option<PathBuf> {
+    let mut args = get_file_selection_args(settings);
+    args.push("--save".to_string());
+    args.push("--confirm-overwrite".to_string());
+
+    let output = run_zenity(&args)?;
+    if !output.status.success() {
+        return None;
+    }
+    let path = String::from_utf8_lossy(&output.stdout).trim_end_matches('\n').to_string();
+    (!path.is_empty()).then(|| PathBuf::from(path))
+}
+
+/// Get the arguments shared by the open and save dialogs
+fn get_file_selection_args(settings: &FileDialog) -> Vec<String> {
+    let mut args = vec!["--file-selection".to_string()];
+    if let Some(title) = &settings.title {
+        args.push(format!("--title={title}"));
+    }
+
+    for filter in &settings.filters {
+        let patterns = filter.extensions.iter().map(|ext| format!("*.{ext}")).collect::<Vec<_>>().join(" ");
+        args.push(format!("--file-filter={} | {patterns}", filter.name));
+    }
+
+    // The initial directory is given as part of the file name, where a trailing separator selects only the directory
+    let file_name = settings.file_name.as_deref().unwrap_or_default();
+    match &settings.directory {
+        Some(directory) => args.push(format!("--filename={}", directory.join(file_name).display())),
+        None if !file_name.is_empty() => args.push(format!("--filename={file_name}")),
+        None => (),
+    }
+    args
+}
\ No newline at end of file
@@ -0,0 +1,112 @@
+use onca_logging::log_warning;
+
+use crate::{PhysicalSize, LOG_CAT};
+
+/// Icon stored as ARGB pixels, which is the format used by `_NET_WM_ICON`
+#[derive(Clone, Default)]
+pub struct OSIcon {
+    width:  u32,
+    height: u32,
+    argb:   Vec<u32>,
+}
+
+impl OSIcon {
+    /// Load an icon from a bitmap file.
+    ///
+    /// The window manager scales the icon to the size it needs, so the requested size is ignored.
+    pub(crate) fn from_path(path: &str, _size: Option<PhysicalSize>) -> OSIcon {
+        let data = match std::fs::read(path) {
+            Ok(data) => data,
+            Err(err) => {
+                log_warning!(LOG_CAT, "Failed to load icon '{path}'. ({err})");
+                return OSIcon::default();
+            },
+        };
+
+        match decode_bitmap(&data) {
+            Some((width, height, argb)) => OSIcon { width, height, argb },
+            None => {
+                log_warning!(LOG_CAT, "Failed to load icon '{path}', only uncompressed 24-bit and 32-bit bitmaps are supported");
+                OSIcon::default()
+            },
+        }
+    }
+
+    pub(crate) fn from_rgba(pixels: &[u8], size: PhysicalSize) -> OSIcon {
+        let argb = pixels.chunks_exact(4).map(|pixel| u32::from_be_bytes([pixel[3], pixel[0], pixel[1], pixel[2]])).collect();
+        OSIcon { width: size.width as u32, height: size.height as u32, argb }
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        !self.argb.is_empty()
+    }
+
+    /// Get the size of the icon and its ARGB pixels, which are not premultiplied.
+    pub(crate) fn argb(&self) -> (u32, u32, &[u32]) {
+        (self.width, self.height, &self.argb)
+    }
+
+    /// Get the icon in the layout of a `_NET_WM_ICON` property: width, height, followed by the pixels.
+    pub(crate) fn net_wm_icon_data(&self) -> Vec<core::ffi::c_ulong> {
+        let mut data = Vec::with_capacity(self.argb.len() + 2);
+        data.push(self.width as _);
+        data.push(self.height as _);
+        data.extend(self.argb.iter().map(|pixel| *pixel as core::ffi::c_ulong));
+        data
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2).map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4).map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Decode an uncompressed 24-bit or 32-bit bitmap into ARGB pixels, stored from top to bottom.
+fn decode_bitmap(data: &[u8]) -> Option<(u32, u32, Vec<u32>)> {
+    if data.get(0..2)? != b"BM" {
+        return None;
+    }
+
+    const BI_RGB: u32 = 0;
+    const BI_BITFIELDS: u32 = 3;
+
+    let pixel_offset = read_u32(data, 10)? as usize;
+    let width = read_u32(data, 18)? as i32;
+    let height = read_u32(data, 22)? as i32;
+    let bit_count = read_u16(data, 28)?;
+    let compression = read_u32(data, 30)?;
+    if width <= 0 || height == 0 || !(compression == BI_RGB || (compression == BI_BITFIELDS && bit_count == 32)) {
+        return None;
+    }
+
+    // A positive height means the rows are stored from bottom to top
+    let bottom_up = height > 0;
+    let (width, height) = (width as usize, height.unsigned_abs() as usize);
+    let bytes_per_pixel = match bit_count {
+        24 => 3,
+        32 => 4,
+        _ => return None,
+    };
+    let stride = (width * bytes_per_pixel + 3) & !3;
+
+    let mut argb = Vec::with_capacity(width * height);
+    let mut has_alpha = false;
+    for row in 0..height {
+        let src_row = if bottom_up { height - 1 - row } else { row };
+        let row_data = data.get(pixel_offset + src_row * stride..pixel_offset + src_row * stride + width * bytes_per_pixel)?;
+        for pixel in row_data.chunks_exact(bytes_per_pixel) {
+            let alpha = if bytes_per_pixel == 4 { pixel[3] } else { 0xFF };
+            has_alpha |= alpha != 0;
+            argb.push(u32::from_be_bytes([alpha, pixel[2], pixel[1], pixel[0]]));
+        }
+    }
+
+    // Most 32-bit bitmaps don't use the alpha channel, which would result in a fully transparent icon
+    if !has_alpha {
+        argb.iter_mut().for_each(|pixel| *pixel |= 0xFF00_0000);
+    }
+    Some((width as u32, height as u32, argb))
+}
\ No newline at end of file
@@ -0,0 +1,78 @@
+use std::{env, sync::OnceLock};
+
+use onca_logging::{log_debug, log_error, log_warning};
+
+use crate::LOG_CAT;
+
+pub(crate) mod wayland;
+pub(crate) mod x11;
+
+pub(crate) mod clipboard;
+
+pub(crate) mod cursor;
+pub(crate) use cursor::OSCursor;
+
+pub(crate) mod dialog;
+
+pub(crate) mod icon;
+pub(crate) use icon::OSIcon;
+
+pub(crate) mod monitor;
+pub(crate) use monitor::MonitorHandle;
+
+pub(crate) mod uri_list;
+
+pub(crate) mod window;
+pub(crate) use window::{OSWindowHandle, OSWindowData, raw_display_handle};
+
+pub(crate) mod window_manager;
+pub(crate) use window_manager::WindowManagerData;
+
+/// Environment variable that can be used to force a backend, either `wayland` or `x11`
+const BACKEND_ENV_VAR: &str = "ONCA_WINDOW_BACKEND";
+
+/// Display server protocol used by all windows of the process
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Backend {
+    Wayland,
+    X11,
+}
+
+static BACKEND: OnceLock<Option<Backend>> = OnceLock::new();
+
+/// Get the backend used to talk to the display server, connecting to it the first time this is called.
+///
+/// Wayland is preferred when the session has a compositor, falling back to X11, which in a Wayland session goes through XWayland.
+/// Returns `None` if neither a Wayland compositor nor an X server can be reached.
+pub(crate) fn backend() -> Option<Backend> {
+    *BACKEND.get_or_init(select_backend)
+}
+
+fn select_backend() -> Option<Backend> {
+    let forced = env::var(BACKEND_ENV_VAR).ok().and_then(|name| match name.to_ascii_lowercase().as_str() {
+        "wayland" => Some(Backend::Wayland),
+        "x11" => Some(Backend::X11),
+        _ => {
+            log_warning!(LOG_CAT, "Unknown window backend '{name}' in {BACKEND_ENV_VAR}, expected 'wayland' or 'x11'");
+            None
+        },
+    });
+
+    let has_wayland_session = ["WAYLAND_DISPLAY", "WAYLAND_SOCKET"].into_iter()
+        .any(|var| env::var_os(var).is_some_and(|value| !value.is_empty()));
+    if forced == Some(Backend::Wayland) || (forced.is_none() && has_wayland_session) {
+        if wayland::connection().is_some() {
+            log_debug!(LOG_CAT, "Using the Wayland window backend");
+            return Some(Backend::Wayland);
+        }
+        log_warning!(LOG_CAT, "Failed to connect to the Wayland compositor, falling back to X11");
+    }
+
+    if x11::connection().is_some() {
+        log_debug!(LOG_CAT, "Using the X11 window backend");
+        return Some(Backend::X11);
+    }
+
+    log_error!(LOG_CAT, "Failed to connect to a Wayland compositor or an X server, no windows can be created");
+    None
+}
\ No newline at end of file
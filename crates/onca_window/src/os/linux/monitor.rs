@@ -0,0 +1,69 @@
+use x11_dl::xrandr;
+
+use crate::{Monitor, MonitorRect};
+use super::{backend, wayland, x11, Backend, OSWindowHandle, window::WindowHandle};
+
+/// Identifier of a monitor, as used by the backend that enumerated it
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum MonitorId {
+    /// XRandR output
+    X11(xrandr::RROutput),
+    /// Name of the `wl_output` global
+    Wayland(u32),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct MonitorHandle(pub(crate) MonitorId);
+
+impl MonitorHandle {
+    /// Get the XRandR output of the monitor, or 0 if the monitor was not enumerated by the X11 backend
+    pub fn output(&self) -> u64 {
+        match self.0 {
+            MonitorId::X11(output) => output as u64,
+            MonitorId::Wayland(_) => 0,
+        }
+    }
+}
+
+fn is_wayland() -> bool {
+    backend() == Some(Backend::Wayland)
+}
+
+pub(crate) fn enumerate_monitors() -> Vec<Monitor> {
+    if is_wayland() {
+        wayland::monitor::enumerate_monitors()
+    } else {
+        x11::monitor::enumerate_monitors()
+    }
+}
+
+pub(crate) fn primary_monitor() -> Option<Monitor> {
+    if is_wayland() {
+        wayland::monitor::primary_monitor()
+    } else {
+        x11::monitor::primary_monitor()
+    }
+}
+
+pub(crate) fn get_monitor_from_handle(handle: OSWindowHandle) -> Option<Monitor> {
+    match &handle.0 {
+        WindowHandle::X11(handle) => x11::monitor::get_monitor_from_handle(handle),
+        WindowHandle::Wayland(handle) => wayland::monitor::get_monitor_from_handle(handle),
+    }
+}
+
+pub(crate) fn get_monitor_at(x: i32, y: i32) -> Option<Monitor> {
+    if is_wayland() {
+        wayland::monitor::get_monitor_at(x, y)
+    } else {
+        x11::monitor::get_monitor_at(x, y)
+    }
+}
+
+pub(crate) fn get_monitor_from_largest_overlap(rect: MonitorRect) -> Option<Monitor> {
+    if is_wayland() {
+        wayland::monitor::get_monitor_from_largest_overlap(rect)
+    } else {
+        x11::monitor::get_monitor_from_largest_overlap(rect)
+    }
+}
\ No newline at end of file
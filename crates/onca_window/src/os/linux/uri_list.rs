@@ -0,0 +1,36 @@
+use std::{
+    ffi::OsString,
+    os::unix::ffi::OsStringExt,
+    path::PathBuf,
+};
+
+/// Get the paths of the local files in a `text/uri-list`, any other URIs are ignored
+pub(crate) fn parse_uri_list(data: &[u8]) -> Vec<PathBuf> {
+    String::from_utf8_lossy(data)
+        .lines()
+        .map(str::trim)
+        // Lines starting with a '#' are comments
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|uri| uri.strip_prefix("file://"))
+        // The host is either empty, `localhost`, or the name of this machine, so it can be skipped
+        .filter_map(|uri| uri.find('/').map(|idx| &uri[idx..]))
+        .filter_map(percent_decode)
+        .map(|path| PathBuf::from(OsString::from_vec(path)))
+        .collect()
+}
+
+/// Decode a percent-encoded URI path, returns `None` if the encoding is invalid
+fn percent_decode(path: &str) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(path.len());
+    let mut bytes = path.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte == b'%' {
+            let high = (bytes.next()? as char).to_digit(16)?;
+            let low = (bytes.next()? as char).to_digit(16)?;
+            decoded.push(((high << 4) | low) as u8);
+        } else {
+            decoded.push(byte);
+        }
+    }
+    Some(decoded)
+}
\ No newline at end of file
@@ -0,0 +1,256 @@
+use core::cell::Cell;
+use std::{
+    fs::File,
+    io::Write,
+    os::fd::AsFd,
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use onca_logging::{log_debug, log_error, log_warning};
+use wayland_client::{
+    event_created_child,
+    protocol::{
+        wl_data_device::{self, WlDataDevice},
+        wl_data_device_manager::DndAction,
+        wl_data_offer::{self, WlDataOffer},
+        wl_data_source::{self, WlDataSource},
+    },
+    Connection as WlConnection, Dispatch, Proxy, QueueHandle,
+};
+
+use crate::{DropEffect, PhysicalPosition, WindowEvent, LOG_CAT};
+use crate::os::linux::uri_list::parse_uri_list;
+use super::{connection, create_pipe, read_pipe, shared, window_manager::{WaylandState, WaylandWindowManagerData}};
+
+/// Mime types used for text, from most to least preferred
+const TEXT_MIME_TYPES: [&str; 3] = ["text/plain;charset=utf-8", "UTF8_STRING", "text/plain"];
+/// Mime type used for dragged files
+const URI_LIST_MIME_TYPE: &str = "text/uri-list";
+
+/// Mime types offered by another client, which are sent before the offer is used
+type OfferedMimeTypes = Mutex<Vec<String>>;
+
+/// Files being dragged over a window
+pub(crate) struct DragState {
+    offer:  WlDataOffer,
+    /// Window the files are dragged over
+    window: u32,
+    /// Serial of the event in which the drag entered the window, which is needed to accept the drag
+    serial: u32,
+    files:  Option<Vec<PathBuf>>,
+    effect: DropEffect,
+    pos:    PhysicalPosition,
+}
+
+impl DragState {
+    /// Tell the source if the files are accepted and what will be done with them
+    fn update_status(&self) {
+        if self.files.is_some() && self.effect != DropEffect::None {
+            // Wayland has no action to create links, so links are made from copies
+            let action = if self.effect == DropEffect::Move { DndAction::Move } else { DndAction::Copy };
+            self.offer.accept(self.serial, Some(URI_LIST_MIME_TYPE.to_string()));
+            self.offer.set_actions(DndAction::Copy | DndAction::Move, action);
+        } else {
+            self.offer.accept(self.serial, None);
+            self.offer.set_actions(DndAction::empty(), DndAction::empty());
+        }
+    }
+}
+
+/// Check if another client offers a mime type
+fn offers_mime_type(offer: &WlDataOffer, mime_type: &str) -> bool {
+    offer.data::<OfferedMimeTypes>().is_some_and(|mime_types| {
+        mime_types.lock().unwrap_or_else(|err| err.into_inner()).iter().any(|offered| offered == mime_type)
+    })
+}
+
+/// Receive the data of an offer in the given mime type
+fn receive(offer: &WlDataOffer, mime_type: &str) -> Option<Vec<u8>> {
+    let conn = connection()?;
+    let (read, write) = match create_pipe() {
+        Ok(pipe) => pipe,
+        Err(err) => {
+            log_error!(LOG_CAT, "Failed to create a pipe to receive '{mime_type}' from another application ({err})");
+            return None;
+        },
+    };
+
+    offer.receive(mime_type.to_string(), write.as_fd());
+    // The other client closes its end of the pipe when it's done writing, which only ends the read when this end is closed too
+    drop(write);
+    conn.flush();
+    read_pipe(read)
+}
+
+/// Convert a position in surface coordinates to a position in the window
+fn to_physical(id: u32, x: f64, y: f64) -> PhysicalPosition {
+    let scale = shared().windows.get(&id).map_or(1, |proxies| proxies.scale) as f64;
+    PhysicalPosition::new((x * scale) as i32, (y * scale) as i32)
+}
+
+pub(crate) fn get_text(data: &mut WaylandWindowManagerData) -> Option<String> {
+    // The compositor doesn't send our own selection back, so use the text directly
+    if let Some((_, text)) = &data.state.clipboard_source {
+        return Some(text.clone());
+    }
+
+    let offer = data.state.selection.as_ref()?;
+    let mime_type = TEXT_MIME_TYPES.iter().find(|mime_type| offers_mime_type(offer, mime_type))?;
+    let text = receive(offer, mime_type)?;
+    Some(String::from_utf8_lossy(&text).into_owned())
+}
+
+pub(crate) fn set_text(data: &mut WaylandWindowManagerData, text: &str) -> bool {
+    let Some(conn) = connection() else { return false };
+    let (Some(manager), Some(data_device)) = (&conn.data_device_manager, &data.state.data_device) else {
+        log_warning!(LOG_CAT, "Failed to set the clipboard text, the Wayland compositor doesn't support the clipboard");
+        return false;
+    };
+
+    let source = manager.create_data_source(&conn.qh, ());
+    for mime_type in TEXT_MIME_TYPES {
+        source.offer(mime_type.to_string());
+    }
+    // The compositor only allows the clipboard to be set in response to user input
+    data_device.set_selection(Some(&source), shared().input_serial);
+    if let Some((old_source, _)) = data.state.clipboard_source.replace((source, text.to_string())) {
+        old_source.destroy();
+    }
+    conn.flush();
+    true
+}
+
+impl Dispatch<WlDataDevice, ()> for WaylandState {
+    fn event(state: &mut Self, _: &WlDataDevice, event: wl_data_device::Event, _: &(), _: &WlConnection, _: &QueueHandle<Self>) {
+        match event {
+            wl_data_device::Event::Enter { serial, surface, x, y, id } => {
+                let Some(offer) = id else { return };
+                let window_id = surface.id().protocol_id();
+                // A new drag can only start when the previous one has ended, so the previous one has gone away without leaving
+                end_drag(state);
+
+                let Some(window) = state.window(window_id) else {
+                    offer.destroy();
+                    return;
+                };
+
+                let files = if window.settings().does_accept_files() && offers_mime_type(&offer, URI_LIST_MIME_TYPE) {
+                    receive(&offer, URI_LIST_MIME_TYPE)
+                        .map(|data| parse_uri_list(&data))
+                        .filter(|files| !files.is_empty())
+                } else {
+                    None
+                };
+
+                let mut drag = DragState { offer, window: window_id, serial, files, effect: window.drop_effect, pos: to_physical(window_id, x, y) };
+                if let Some(files) = &drag.files {
+                    let drop_effect = Cell::new(drag.effect);
+                    let set_effect = |new_effect| drop_effect.set(new_effect);
+                    log_debug!(LOG_CAT, "Started hovering {} files over window {} at location ({}, {})", files.len(), window.id(), drag.pos.x, drag.pos.y);
+                    window.send_window_event(WindowEvent::FilesHoverStarted{ files, pos: drag.pos, set_effect: &set_effect });
+                    drag.effect = drop_effect.get();
+                }
+                drag.update_status();
+                state.drag = Some(drag);
+            },
+            wl_data_device::Event::Motion { x, y, .. } => {
+                let Some(drag) = &mut state.drag else { return };
+                drag.pos = to_physical(drag.window, x, y);
+                if drag.files.is_none() {
+                    return;
+                }
+
+                let window_id = drag.window;
+                let Some(window) = state.window(window_id) else { return };
+                let Some(drag) = &mut state.drag else { return };
+                let drop_effect = Cell::new(drag.effect);
+                let set_effect = |new_effect| drop_effect.set(new_effect);
+                window.send_window_event(WindowEvent::FilesHoverMoved{ pos: drag.pos, set_effect: &set_effect });
+                drag.effect = drop_effect.get();
+                drag.update_status();
+            },
+            wl_data_device::Event::Leave => end_drag(state),
+            wl_data_device::Event::Drop => {
+                let Some(drag) = state.drag.take() else { return };
+                let accepted = drag.files.is_some() && drag.effect != DropEffect::None;
+
+                if let Some(window) = state.window(drag.window) {
+                    match drag.files {
+                        Some(files) if accepted => {
+                            log_debug!(LOG_CAT, "Dropped {} files over window {} at location ({}, {})", files.len(), window.id(), drag.pos.x, drag.pos.y);
+                            window.send_window_event(WindowEvent::FilesDropped(files, drag.pos));
+                        },
+                        Some(_) => {
+                            log_debug!(LOG_CAT, "Rejected files dropped over window {}", window.id());
+                            window.send_window_event(WindowEvent::FilesHoverEnded);
+                        },
+                        None => (),
+                    }
+                }
+
+                // Only an accepted drop can be finished, otherwise the source is told the drop was cancelled
+                if accepted {
+                    drag.offer.finish();
+                }
+                drag.offer.destroy();
+            },
+            wl_data_device::Event::Selection { id } => {
+                if let Some(old_offer) = state.selection.take() {
+                    old_offer.destroy();
+                }
+                state.selection = id;
+            },
+            _ => (),
+        }
+    }
+
+    event_created_child!(WaylandState, WlDataDevice, [
+        wl_data_device::EVT_DATA_OFFER_OPCODE => (WlDataOffer, OfferedMimeTypes::default()),
+    ]);
+}
+
+/// End the current drag, after it has left the window
+fn end_drag(state: &mut WaylandState) {
+    let Some(drag) = state.drag.take() else { return };
+    if drag.files.is_some() {
+        if let Some(window) = state.window(drag.window) {
+            log_debug!(LOG_CAT, "Stopped hovering files over window {}", window.id());
+            window.send_window_event(WindowEvent::FilesHoverEnded);
+        }
+    }
+    drag.offer.destroy();
+}
+
+impl Dispatch<WlDataOffer, OfferedMimeTypes> for WaylandState {
+    fn event(_: &mut Self, _: &WlDataOffer, event: wl_data_offer::Event, mime_types: &OfferedMimeTypes, _: &WlConnection, _: &QueueHandle<Self>) {
+        if let wl_data_offer::Event::Offer { mime_type } = event {
+            mime_types.lock().unwrap_or_else(|err| err.into_inner()).push(mime_type);
+        }
+    }
+}
+
+impl Dispatch<WlDataSource, ()> for WaylandState {
+    fn event(state: &mut Self, source: &WlDataSource, event: wl_data_source::Event, _: &(), _: &WlConnection, _: &QueueHandle<Self>) {
+        let text = match &state.clipboard_source {
+            Some((clipboard_source, text)) if clipboard_source == source => text,
+            // Sources are destroyed when they're replaced, so any remaining events are stale
+            _ => return,
+        };
+
+        match event {
+            wl_data_source::Event::Send { fd, .. } => {
+                // All offered mime types are text, which is always sent as UTF-8
+                if let Err(err) = File::from(fd).write_all(text.as_bytes()) {
+                    log_error!(LOG_CAT, "Failed to send the clipboard text to another application ({err})");
+                }
+            },
+            wl_data_source::Event::Cancelled => {
+                // Another client has set the clipboard
+                state.clipboard_source = None;
+                source.destroy();
+            },
+            _ => (),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,154 @@
+use std::env;
+
+use onca_logging::log_warning;
+use wayland_client::protocol::{wl_buffer::WlBuffer, wl_pointer::WlPointer, wl_surface::WlSurface};
+use wayland_cursor::CursorTheme;
+
+use crate::{CursorShape, PhysicalSize, WindowCursor, LOG_CAT};
+use super::{connection, premultiply, shared, Connection};
+
+/// Size of the cursor when `XCURSOR_SIZE` is not set, at a scale of 1
+const DEFAULT_CURSOR_SIZE: u32 = 24;
+
+pub(crate) struct WaylandCursor {
+    buffer:  Option<WlBuffer>,
+    hotspot: (i32, i32),
+}
+
+impl WaylandCursor {
+    pub(crate) fn from_rgba(pixels: &[u8], size: PhysicalSize, hotspot: (u16, u16)) -> WaylandCursor {
+        let buffer = connection().and_then(|conn| {
+            // Buffers use premultiplied ARGB pixels
+            let argb = pixels.chunks_exact(4)
+                .map(|pixel| premultiply(u32::from_be_bytes([pixel[3], pixel[0], pixel[1], pixel[2]])))
+                .collect::<Vec<_>>();
+            conn.create_shm_buffer(size.width as u32, size.height as u32, &argb)
+        });
+        if buffer.is_none() {
+            log_warning!(LOG_CAT, "Failed to create a {}x{} cursor from pixels", size.width, size.height);
+        }
+        WaylandCursor { buffer, hotspot: (hotspot.0 as i32, hotspot.1 as i32) }
+    }
+}
+
+impl Drop for WaylandCursor {
+    fn drop(&mut self) {
+        if let Some(buffer) = self.buffer.take() {
+            buffer.destroy();
+        }
+    }
+}
+
+/// State of the pointer of the seat, the cursor is set for the pointer instead of for a window
+pub(crate) struct PointerState {
+    pub(crate) pointer:      WlPointer,
+    /// Surface the cursor is drawn to
+    surface:                 WlSurface,
+    /// Cursor theme, loaded for the scale of the window the pointer is over
+    theme:                   Option<(i32, CursorTheme)>,
+    /// Window the pointer is over
+    pub(crate) focus:        Option<u32>,
+    /// Serial of the event in which the pointer entered the window, which is needed to change the cursor
+    pub(crate) enter_serial: u32,
+}
+
+impl PointerState {
+    pub(crate) fn new(conn: &Connection, pointer: WlPointer) -> PointerState {
+        PointerState {
+            pointer,
+            surface: conn.compositor.create_surface(&conn.qh, ()),
+            theme: None,
+            focus: None,
+            enter_serial: 0,
+        }
+    }
+
+    /// Load the cursor theme for the given scale, when it's not loaded for that scale yet
+    fn theme(&mut self, conn: &Connection, scale: i32) -> Option<&mut CursorTheme> {
+        if self.theme.as_ref().is_none_or(|(theme_scale, _)| *theme_scale != scale) {
+            let name = env::var("XCURSOR_THEME").ok().filter(|name| !name.is_empty()).unwrap_or_else(|| "default".to_string());
+            let size = env::var("XCURSOR_SIZE").ok().and_then(|size| size.parse().ok()).unwrap_or(DEFAULT_CURSOR_SIZE);
+            match CursorTheme::load_from_name(&conn.conn, conn.shm.clone(), &name, size * scale as u32) {
+                Ok(theme) => self.theme = Some((scale, theme)),
+                Err(err) => {
+                    log_warning!(LOG_CAT, "Failed to load cursor theme '{name}' ({err})");
+                    return None;
+                },
+            }
+        }
+        self.theme.as_mut().map(|(_, theme)| theme)
+    }
+
+    pub(crate) fn destroy(self) {
+        self.surface.destroy();
+        self.pointer.release();
+    }
+}
+
+/// Show the cursor of a window, as long as the pointer is over the window
+pub(crate) fn update_cursor(id: u32, cursor: &WindowCursor, visible: bool) {
+    let Some(conn) = connection() else { return };
+    let mut shared = shared();
+    let shared = &mut *shared;
+
+    let scale = shared.windows.get(&id).map_or(1, |proxies| proxies.scale);
+    let Some(pointer) = &mut shared.pointer else { return };
+    if pointer.focus == Some(id) {
+        apply_cursor(conn, pointer, cursor, visible, scale);
+        conn.flush();
+    }
+}
+
+/// Show a cursor for the pointer, the scale is the scale of the window the pointer is over
+pub(crate) fn apply_cursor(conn: &Connection, pointer: &mut PointerState, cursor: &WindowCursor, visible: bool, scale: i32) {
+    if !visible {
+        pointer.pointer.set_cursor(pointer.enter_serial, None, 0, 0);
+        return;
+    }
+
+    let (buffer, hotspot, buffer_scale) = match cursor {
+        WindowCursor::Shape(shape) => {
+            let Some(theme) = pointer.theme(conn, scale) else { return };
+            let Some(name) = shape_names(*shape).iter().find(|name| theme.get_cursor(name).is_some()) else {
+                log_warning!(LOG_CAT, "The cursor theme doesn't have a cursor for {shape:?}");
+                return;
+            };
+            let Some(cursor) = theme.get_cursor(name) else { return };
+            let image = &cursor[0];
+            let (x, y) = image.hotspot();
+            // The hotspot is in surface coordinates, while the cursor is drawn at the scale of the window
+            ((**image).clone(), (x as i32 / scale, y as i32 / scale), scale)
+        },
+        WindowCursor::Custom(cursor) => {
+            let Some(WaylandCursor { buffer: Some(buffer), hotspot }) = cursor.get_os_cursor().wayland() else { return };
+            (buffer.clone(), *hotspot, 1)
+        },
+    };
+
+    pointer.surface.set_buffer_scale(buffer_scale);
+    pointer.surface.attach(Some(&buffer), 0, 0);
+    pointer.surface.damage(0, 0, i32::MAX, i32::MAX);
+    pointer.surface.commit();
+    pointer.pointer.set_cursor(pointer.enter_serial, Some(&pointer.surface), hotspot.0, hotspot.1);
+}
+
+/// Get the names of the cursor in a cursor theme, from the name used by the CSS cursor names to the name used by older themes
+fn shape_names(shape: CursorShape) -> &'static [&'static str] {
+    match shape {
+        CursorShape::Arrow => &["default", "left_ptr"],
+        CursorShape::IBeam => &["text", "xterm"],
+        CursorShape::Crosshair => &["crosshair", "cross"],
+        CursorShape::Hand => &["pointer", "hand2"],
+        CursorShape::Grab => &["grab", "openhand", "hand1"],
+        CursorShape::Grabbing => &["grabbing", "closedhand", "fleur"],
+        CursorShape::ResizeHorizontal => &["ew-resize", "sb_h_double_arrow"],
+        CursorShape::ResizeVertical => &["ns-resize", "sb_v_double_arrow"],
+        CursorShape::ResizeNwSe => &["nwse-resize", "bottom_right_corner"],
+        CursorShape::ResizeNeSw => &["nesw-resize", "bottom_left_corner"],
+        CursorShape::Move => &["move", "fleur"],
+        CursorShape::NotAllowed => &["not-allowed", "crossed_circle"],
+        CursorShape::Wait => &["wait", "watch"],
+        CursorShape::Progress => &["progress", "left_ptr_watch"],
+        CursorShape::Help => &["help", "question_arrow"],
+    }
+}
\ No newline at end of file
@@ -0,0 +1,385 @@
+pub(crate) mod clipboard;
+pub(crate) mod cursor;
+pub(crate) mod monitor;
+pub(crate) mod seat;
+pub(crate) mod window;
+pub(crate) mod window_manager;
+
+use core::ffi::c_void;
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, Read, Write},
+    os::fd::{AsFd, AsRawFd, OwnedFd},
+    sync::{Mutex, MutexGuard, OnceLock},
+    time::{Duration, Instant},
+};
+
+use onca_logging::{log_debug, log_error, log_warning};
+use wayland_client::{
+    globals::{registry_queue_init, GlobalList, GlobalListContents},
+    protocol::{
+        wl_buffer::WlBuffer,
+        wl_callback::WlCallback,
+        wl_compositor::WlCompositor,
+        wl_data_device_manager::WlDataDeviceManager,
+        wl_output::WlOutput,
+        wl_region::WlRegion,
+        wl_registry::{self, WlRegistry},
+        wl_seat::WlSeat,
+        wl_shm::{self, WlShm},
+        wl_shm_pool::WlShmPool,
+    },
+    delegate_noop, Connection as WlConnection, Dispatch, EventQueue, Proxy, QueueHandle,
+};
+use wayland_protocols::{
+    wp::pointer_constraints::zv1::client::{
+        zwp_confined_pointer_v1::ZwpConfinedPointerV1,
+        zwp_locked_pointer_v1::ZwpLockedPointerV1,
+        zwp_pointer_constraints_v1::ZwpPointerConstraintsV1,
+    },
+    xdg::{
+        activation::v1::client::xdg_activation_v1::XdgActivationV1,
+        decoration::zv1::client::zxdg_decoration_manager_v1::ZxdgDecorationManagerV1,
+        shell::client::xdg_wm_base::{self, XdgWmBase},
+        toplevel_icon::v1::client::{
+            xdg_toplevel_icon_manager_v1::XdgToplevelIconManagerV1,
+            xdg_toplevel_icon_v1::XdgToplevelIconV1,
+        },
+    },
+};
+use xkbcommon_dl::{xkbcommon_option, XkbCommon};
+
+use crate::LOG_CAT;
+use self::{
+    monitor::OutputInfo,
+    cursor::PointerState,
+    window::{PendingEvent, WindowProxies},
+    window_manager::WaylandState,
+};
+
+/// Time to wait for another client to send the content of the clipboard or of a drag
+const TRANSFER_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Connection to the Wayland compositor, shared by all windows, with the globals it provides
+pub(crate) struct Connection {
+    pub(crate) conn:                WlConnection,
+    pub(crate) qh:                  QueueHandle<WaylandState>,
+    pub(crate) compositor:          WlCompositor,
+    pub(crate) shm:                 WlShm,
+    pub(crate) wm_base:             XdgWmBase,
+    pub(crate) seat:                Option<WlSeat>,
+    /// Used for the clipboard and dropping files
+    pub(crate) data_device_manager: Option<WlDataDeviceManager>,
+    /// Lets the compositor draw the window frame, without it windows don't have a frame, e.g. on GNOME
+    pub(crate) decoration_manager:  Option<ZxdgDecorationManagerV1>,
+    pub(crate) pointer_constraints: Option<ZwpPointerConstraintsV1>,
+    /// Used to activate windows and to request the user's attention
+    pub(crate) activation:          Option<XdgActivationV1>,
+    pub(crate) icon_manager:        Option<XdgToplevelIconManagerV1>,
+    /// xkbcommon is needed to translate key presses to text
+    pub(crate) xkb:                 Option<&'static XkbCommon>,
+    /// Event queue and the state events are dispatched to, until they are taken by the window manager
+    queue:                          Mutex<Option<(EventQueue<WaylandState>, WaylandState)>>,
+}
+
+// SAFETY: The event queue and its state are only accessed by the window manager, which runs on the main thread
+unsafe impl Send for Connection {}
+unsafe impl Sync for Connection {}
+
+static CONNECTION: OnceLock<Option<Connection>> = OnceLock::new();
+
+/// State that is needed by window handles, which don't have access to the window manager.
+///
+/// Event handlers only lock this for as long as they need to update it, as window listeners may use the window handle.
+pub(crate) struct SharedState {
+    /// Objects of each window, mapped by the protocol id of its surface
+    pub(crate) windows:      BTreeMap<u32, WindowProxies>,
+    pub(crate) outputs:      Vec<OutputInfo>,
+    pub(crate) pointer:      Option<PointerState>,
+    /// Serial of the last input event, which is needed to move, resize or activate a window, or to set the clipboard
+    pub(crate) input_serial: u32,
+    /// Requests that are processed by the window manager during its next tick, like X11 messages a window sends to itself
+    pub(crate) pending:      Vec<(u32, PendingEvent)>,
+}
+
+// SAFETY: The cursor theme in the pointer state is only used on the main thread
+unsafe impl Send for SharedState {}
+
+static SHARED: Mutex<SharedState> = Mutex::new(SharedState {
+    windows: BTreeMap::new(),
+    outputs: Vec::new(),
+    pointer: None,
+    input_serial: 0,
+    pending: Vec::new(),
+});
+
+/// Get the connection to the Wayland compositor, connecting to it the first time this is called.
+///
+/// Returns `None` if no compositor can be reached, or if it's missing globals that are required to create windows.
+pub(crate) fn connection() -> Option<&'static Connection> {
+    CONNECTION.get_or_init(Connection::open).as_ref()
+}
+
+/// Lock the state shared with the window handles
+pub(crate) fn shared() -> MutexGuard<'static, SharedState> {
+    SHARED.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+impl Connection {
+    fn open() -> Option<Connection> {
+        let conn = match WlConnection::connect_to_env() {
+            Ok(conn) => conn,
+            Err(err) => {
+                log_warning!(LOG_CAT, "Failed to connect to the Wayland compositor ({err})");
+                return None;
+            },
+        };
+
+        let (globals, mut queue) = match registry_queue_init::<WaylandState>(&conn) {
+            Ok(res) => res,
+            Err(err) => {
+                log_error!(LOG_CAT, "Failed to retrieve the globals of the Wayland compositor ({err})");
+                return None;
+            },
+        };
+        let qh = queue.handle();
+
+        // Version 3 is needed to set the buffer scale, and version 6 lets the compositor tell which scale it prefers
+        let compositor = bind_required::<WlCompositor>(&globals, &qh, 3, 6)?;
+        let shm = bind_required::<WlShm>(&globals, &qh, 1, 1)?;
+        let wm_base = bind_required::<XdgWmBase>(&globals, &qh, 1, 5)?;
+
+        let seat = bind_optional::<WlSeat>(&globals, &qh, 1, 7, "input");
+        // Version 3 is needed to select the action when dropping files
+        let data_device_manager = bind_optional::<WlDataDeviceManager>(&globals, &qh, 3, 3, "the clipboard and dropping files");
+        let decoration_manager = globals.bind(&qh, 1..=1, ()).ok();
+        if decoration_manager.is_none() {
+            log_debug!(LOG_CAT, "The Wayland compositor doesn't draw window decorations, windows won't have a frame");
+        }
+        let pointer_constraints = bind_optional::<ZwpPointerConstraintsV1>(&globals, &qh, 1, 1, "grabbing the cursor");
+        let activation = bind_optional::<XdgActivationV1>(&globals, &qh, 1, 1, "activating windows");
+        let icon_manager = globals.bind(&qh, 1..=1, ()).ok();
+
+        // There is a global for each monitor, and monitors may be added or removed at any time
+        let outputs = globals.contents().with_list(|list| list.iter()
+            .filter(|global| global.interface == WlOutput::interface().name)
+            .map(|global| (global.name, global.version))
+            .collect::<Vec<_>>()
+        );
+        for (name, version) in outputs {
+            monitor::bind_output(globals.registry(), &qh, name, version);
+        }
+
+        let xkb = xkbcommon_option();
+        if xkb.is_none() {
+            log_warning!(LOG_CAT, "Failed to load xkbcommon, text input will not be available");
+        }
+
+        // Receive the info of the outputs and the capabilities of the seat
+        let mut state = WaylandState::new();
+        if let Err(err) = queue.roundtrip(&mut state) {
+            log_error!(LOG_CAT, "Failed to communicate with the Wayland compositor ({err})");
+            return None;
+        }
+
+        log_debug!(LOG_CAT, "Opened connection to the Wayland compositor");
+        Some(Connection {
+            conn,
+            qh,
+            compositor,
+            shm,
+            wm_base,
+            seat,
+            data_device_manager,
+            decoration_manager,
+            pointer_constraints,
+            activation,
+            icon_manager,
+            xkb,
+            queue: Mutex::new(Some((queue, state))),
+        })
+    }
+
+    /// Take the event queue and the state it dispatches to, which can only be done once
+    pub(crate) fn take_queue(&self) -> Option<(EventQueue<WaylandState>, WaylandState)> {
+        self.queue.lock().unwrap_or_else(|err| err.into_inner()).take()
+    }
+
+    /// Get the `wl_display` of the connection
+    pub(crate) fn display_ptr(&self) -> *mut c_void {
+        self.conn.backend().display_ptr() as *mut c_void
+    }
+
+    /// Send all requests made since the last time the connection was flushed
+    pub(crate) fn flush(&self) {
+        if let Err(err) = self.conn.flush() {
+            log_error!(LOG_CAT, "Failed to send requests to the Wayland compositor ({err})");
+        }
+    }
+
+    /// Create a buffer from premultiplied ARGB pixels, which are copied into shared memory.
+    pub(crate) fn create_shm_buffer(&self, width: u32, height: u32, argb: &[u32]) -> Option<WlBuffer> {
+        let len = width as usize * height as usize * 4;
+        let mut file = match create_shm_file(len) {
+            Ok(file) => file,
+            Err(err) => {
+                log_error!(LOG_CAT, "Failed to create shared memory for a {width}x{height} image ({err})");
+                return None;
+            },
+        };
+
+        let bytes = argb.iter().flat_map(|pixel| pixel.to_ne_bytes()).collect::<Vec<_>>();
+        if let Err(err) = file.write_all(&bytes[..len.min(bytes.len())]) {
+            log_error!(LOG_CAT, "Failed to write a {width}x{height} image to shared memory ({err})");
+            return None;
+        }
+
+        // The buffer keeps the memory alive, so the pool can be destroyed immediately
+        let pool = self.shm.create_pool(file.as_fd(), len as i32, &self.qh, ());
+        let buffer = pool.create_buffer(0, width as i32, height as i32, width as i32 * 4, wl_shm::Format::Argb8888, &self.qh, ());
+        pool.destroy();
+        Some(buffer)
+    }
+
+    /// Create a region that doesn't contain anything
+    pub(crate) fn create_empty_region(&self) -> WlRegion {
+        self.compositor.create_region(&self.qh, ())
+    }
+}
+
+fn bind_required<I>(globals: &GlobalList, qh: &QueueHandle<WaylandState>, min_version: u32, max_version: u32) -> Option<I> where
+    I: Proxy + 'static,
+    WaylandState: Dispatch<I, ()>,
+{
+    match globals.bind(qh, min_version..=max_version, ()) {
+        Ok(global) => Some(global),
+        Err(err) => {
+            log_error!(LOG_CAT, "The Wayland compositor doesn't support '{}', which is required to create windows ({err})", I::interface().name);
+            None
+        },
+    }
+}
+
+fn bind_optional<I>(globals: &GlobalList, qh: &QueueHandle<WaylandState>, min_version: u32, max_version: u32, feature: &str) -> Option<I> where
+    I: Proxy + 'static,
+    WaylandState: Dispatch<I, ()>,
+{
+    match globals.bind(qh, min_version..=max_version, ()) {
+        Ok(global) => Some(global),
+        Err(err) => {
+            log_warning!(LOG_CAT, "The Wayland compositor doesn't support '{}', {feature} will not be available ({err})", I::interface().name);
+            None
+        },
+    }
+}
+
+/// Create an anonymous file in memory, which can be shared with the compositor
+fn create_shm_file(len: usize) -> io::Result<File> {
+    let fd = unsafe { libc::memfd_create(b"onca-window-shm\0".as_ptr() as *const _, libc::MFD_CLOEXEC) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: The file descriptor was just created and isn't owned by anything else
+    let file = unsafe { <File as std::os::fd::FromRawFd>::from_raw_fd(fd) };
+    file.set_len(len as u64)?;
+    Ok(file)
+}
+
+/// Premultiply the color of an ARGB pixel with its alpha, which is the format used by shared memory buffers
+pub(crate) fn premultiply(argb: u32) -> u32 {
+    let alpha = argb >> 24;
+    let premultiply = |shift: u32| ((((argb >> shift) & 0xFF) * alpha + 127) / 255) << shift;
+    (alpha << 24) | premultiply(16) | premultiply(8) | premultiply(0)
+}
+
+/// Create a pipe to receive data from another client, the write end is sent to the other client
+pub(crate) fn create_pipe() -> io::Result<(File, OwnedFd)> {
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // SAFETY: Both file descriptors were just created and aren't owned by anything else
+    unsafe {
+        Ok((<File as std::os::fd::FromRawFd>::from_raw_fd(fds[0]), <OwnedFd as std::os::fd::FromRawFd>::from_raw_fd(fds[1])))
+    }
+}
+
+/// Read all data that another client writes to a pipe, until it closes its end of the pipe.
+///
+/// Returns `None` if the other client didn't send all data in time.
+pub(crate) fn read_pipe(mut file: File) -> Option<Vec<u8>> {
+    let start = Instant::now();
+    let mut data = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let remaining = TRANSFER_TIMEOUT.saturating_sub(start.elapsed());
+        let mut poll_fd = libc::pollfd { fd: file.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+        let res = unsafe { libc::poll(&mut poll_fd, 1, remaining.as_millis() as i32) };
+        if res == 0 {
+            log_error!(LOG_CAT, "Failed to receive data from another application, it did not respond");
+            return None;
+        }
+        if res < 0 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            log_error!(LOG_CAT, "Failed to receive data from another application ({err})");
+            return None;
+        }
+
+        match file.read(&mut buf) {
+            Ok(0) => return Some(data),
+            Ok(len) => data.extend_from_slice(&buf[..len]),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+            Err(err) => {
+                log_error!(LOG_CAT, "Failed to receive data from another application ({err})");
+                return None;
+            },
+        }
+    }
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for WaylandState {
+    fn event(state: &mut Self, registry: &WlRegistry, event: wl_registry::Event, _: &GlobalListContents, _: &WlConnection, qh: &QueueHandle<Self>) {
+        match event {
+            wl_registry::Event::Global { name, interface, version } if interface == WlOutput::interface().name => {
+                monitor::bind_output(registry, qh, name, version);
+            },
+            wl_registry::Event::GlobalRemove { name } => {
+                let mut shared = shared();
+                if let Some(idx) = shared.outputs.iter().position(|output| output.name == name) {
+                    let output = shared.outputs.remove(idx);
+                    output.output.release();
+                    state.monitors_changed = true;
+                }
+            },
+            _ => (),
+        }
+    }
+}
+
+impl Dispatch<XdgWmBase, ()> for WaylandState {
+    fn event(_: &mut Self, wm_base: &XdgWmBase, event: xdg_wm_base::Event, _: &(), _: &WlConnection, _: &QueueHandle<Self>) {
+        // The compositor checks if the application is still responding
+        if let xdg_wm_base::Event::Ping { serial } = event {
+            wm_base.pong(serial);
+        }
+    }
+}
+
+delegate_noop!(WaylandState: WlCompositor);
+delegate_noop!(WaylandState: WlRegion);
+delegate_noop!(WaylandState: ignore WlShm);
+delegate_noop!(WaylandState: WlShmPool);
+delegate_noop!(WaylandState: ignore WlBuffer);
+delegate_noop!(WaylandState: ignore WlCallback);
+delegate_noop!(WaylandState: WlDataDeviceManager);
+delegate_noop!(WaylandState: ZxdgDecorationManagerV1);
+delegate_noop!(WaylandState: ZwpPointerConstraintsV1);
+delegate_noop!(WaylandState: ignore ZwpLockedPointerV1);
+delegate_noop!(WaylandState: ignore ZwpConfinedPointerV1);
+delegate_noop!(WaylandState: XdgActivationV1);
+delegate_noop!(WaylandState: ignore XdgToplevelIconManagerV1);
+delegate_noop!(WaylandState: XdgToplevelIconV1);
\ No newline at end of file
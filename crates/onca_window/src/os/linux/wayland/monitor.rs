@@ -0,0 +1,155 @@
+use std::collections::BTreeSet;
+
+use wayland_client::{
+    protocol::{wl_output::{self, WlOutput}, wl_registry::WlRegistry},
+    Connection as WlConnection, Dispatch, QueueHandle, WEnum,
+};
+
+use crate::{Monitor, MonitorMode, MonitorModeOrdWrapper, MonitorRect, PhysicalSize, WindowSettings};
+use crate::os::linux::monitor::{MonitorHandle, MonitorId};
+use super::{shared, window::WaylandWindowHandle, window_manager::WaylandState};
+
+/// Info of a `wl_output`, which is sent by the compositor in multiple events
+pub(crate) struct OutputInfo {
+    /// Name of the global, which identifies the output
+    pub(crate) name:        u32,
+    pub(crate) output:      WlOutput,
+    /// Position in the compositor's global space
+    pub(crate) x:           i32,
+    pub(crate) y:           i32,
+    pub(crate) mode:        MonitorMode,
+    pub(crate) modes:       BTreeSet<MonitorModeOrdWrapper>,
+    pub(crate) scale:       i32,
+    /// Name of the connector, e.g. 'DP-1'
+    pub(crate) connector:   String,
+    pub(crate) description: String,
+    /// Has the compositor sent all info of the output
+    pub(crate) done:        bool,
+}
+
+pub(crate) fn bind_output(registry: &WlRegistry, qh: &QueueHandle<WaylandState>, name: u32, version: u32) {
+    // Version 2 added the scale, and version 4 the name of the output
+    let output = registry.bind::<WlOutput, _, _>(name, version.min(4), qh, name);
+    shared().outputs.push(OutputInfo {
+        name,
+        output,
+        x: 0,
+        y: 0,
+        mode: MonitorMode { bits_per_pixel: 32, refesh_rate: 0, size: PhysicalSize::new(0, 0) },
+        modes: BTreeSet::new(),
+        scale: 1,
+        connector: String::new(),
+        description: String::new(),
+        done: false,
+    });
+}
+
+impl Dispatch<WlOutput, u32> for WaylandState {
+    fn event(state: &mut Self, _: &WlOutput, event: wl_output::Event, name: &u32, _: &WlConnection, _: &QueueHandle<Self>) {
+        let mut shared = shared();
+        let Some(info) = shared.outputs.iter_mut().find(|output| output.name == *name) else { return };
+
+        match event {
+            wl_output::Event::Geometry { x, y, make, model, .. } => {
+                info.x = x;
+                info.y = y;
+                // Replaced by the description when the compositor supports version 4
+                if info.description.is_empty() {
+                    info.description = format!("{make} {model}");
+                }
+            },
+            wl_output::Event::Mode { flags, width, height, refresh } => {
+                let mode = MonitorMode {
+                    bits_per_pixel: 32,
+                    // The refresh rate is in mHz
+                    refesh_rate: (refresh as f32 / 1000.0).round() as u32,
+                    size: PhysicalSize::new(width as u16, height as u16),
+                };
+                if let WEnum::Value(flags) = flags {
+                    if flags.contains(wl_output::Mode::Current) {
+                        info.mode = mode;
+                    }
+                }
+                info.modes.insert(MonitorModeOrdWrapper(mode));
+            },
+            wl_output::Event::Scale { factor } => info.scale = factor.max(1),
+            wl_output::Event::Name { name } => info.connector = name,
+            wl_output::Event::Description { description } => info.description = description,
+            wl_output::Event::Done => {
+                info.done = true;
+                state.monitors_changed = true;
+                // The scale of the windows on the output may have changed
+                state.scale_changed = true;
+            },
+            _ => (),
+        }
+    }
+}
+
+fn get_monitor(info: &OutputInfo, primary: bool) -> Monitor {
+    let mut dev_name = [0u8; 32];
+    let len = info.connector.len().min(dev_name.len() - 1);
+    dev_name[..len].copy_from_slice(&info.connector.as_bytes()[..len]);
+
+    let mut name = [0u8; 128];
+    let len = info.description.len().min(name.len() - 1);
+    name[..len].copy_from_slice(&info.description.as_bytes()[..len]);
+
+    let mon_rect = MonitorRect { x: info.x, y: info.y, width: info.mode.size.width, height: info.mode.size.height };
+    Monitor {
+        os_handle: MonitorHandle(MonitorId::Wayland(info.name)),
+        mon_rect,
+        // Wayland doesn't expose the area that isn't covered by panels
+        work_rect: mon_rect,
+        refresh_rate: info.mode.refesh_rate as f32,
+        dpi: WindowSettings::DEFAULT_DPI * info.scale as u16,
+        primary,
+        dev_name,
+        name,
+        modes: info.modes.iter().map(|mode| mode.0).collect(),
+    }
+}
+
+fn overlap(a: &MonitorRect, b: &MonitorRect) -> i64 {
+    let width = (a.x + a.width as i32).min(b.x + b.width as i32) - a.x.max(b.x);
+    let height = (a.y + a.height as i32).min(b.y + b.height as i32) - a.y.max(b.y);
+    if width <= 0 || height <= 0 {
+        0
+    } else {
+        width as i64 * height as i64
+    }
+}
+
+pub(crate) fn enumerate_monitors() -> Vec<Monitor> {
+    // Wayland has no concept of a primary monitor, so like most applications, use the first one
+    shared().outputs.iter()
+        .filter(|info| info.done)
+        .enumerate()
+        .map(|(idx, info)| get_monitor(info, idx == 0))
+        .collect()
+}
+
+pub(crate) fn primary_monitor() -> Option<Monitor> {
+    enumerate_monitors().into_iter().next()
+}
+
+pub(crate) fn get_monitor_from_handle(handle: &WaylandWindowHandle) -> Option<Monitor> {
+    // A window can only find out which outputs it's shown on, so use the first one it entered
+    let output = shared().windows.get(&handle.id())?.outputs.first().copied()?;
+    enumerate_monitors().into_iter().find(|monitor| monitor.os_handle.0 == MonitorId::Wayland(output))
+}
+
+pub(crate) fn get_monitor_at(x: i32, y: i32) -> Option<Monitor> {
+    enumerate_monitors().into_iter().find(|monitor| {
+        let rect = monitor.monitor_rect();
+        x >= rect.x && x < rect.x + rect.width as i32 && y >= rect.y && y < rect.y + rect.height as i32
+    })
+}
+
+pub(crate) fn get_monitor_from_largest_overlap(rect: MonitorRect) -> Option<Monitor> {
+    enumerate_monitors().into_iter()
+        .map(|monitor| (overlap(monitor.monitor_rect(), &rect), monitor))
+        .filter(|(overlap, _)| *overlap > 0)
+        .max_by_key(|(overlap, _)| *overlap)
+        .map(|(_, monitor)| monitor)
+}
\ No newline at end of file
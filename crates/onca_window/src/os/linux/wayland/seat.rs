@@ -0,0 +1,378 @@
+use core::{
+    ffi::{c_char, c_int},
+    ptr,
+};
+use std::{
+    env,
+    ffi::CString,
+    os::fd::{AsRawFd, OwnedFd},
+    time::{Duration, Instant},
+};
+
+use onca_logging::{log_debug, log_error, log_warning};
+use wayland_client::{
+    protocol::{
+        wl_keyboard::{self, WlKeyboard},
+        wl_pointer::{self, WlPointer},
+        wl_seat::{self, WlSeat},
+    },
+    Connection as WlConnection, Dispatch, Proxy, QueueHandle, WEnum,
+};
+use xkbcommon_dl::{
+    xkb_compose_compile_flags, xkb_compose_state, xkb_compose_state_flags, xkb_compose_status, xkb_compose_table,
+    xkb_context, xkb_context_flags, xkb_keymap, xkb_keymap_compile_flags, xkb_keymap_format, xkb_state,
+    xkbcommon_compose_option, XkbCommon, XkbCommonCompose,
+};
+
+use crate::{Flags, Window, WindowEvent, LOG_CAT, LOG_MSG_CAT};
+use super::{
+    connection, shared, Connection,
+    cursor::{self, PointerState},
+    window_manager::WaylandState,
+};
+
+/// Offset between the evdev keycodes sent by the compositor and the keycodes used by xkbcommon
+const EVDEV_KEYCODE_OFFSET: u32 = 8;
+
+/// Keymap of the keyboard, which translates key presses to text
+struct XkbKeymap {
+    xkb:           &'static XkbCommon,
+    context:       *mut xkb_context,
+    keymap:        *mut xkb_keymap,
+    state:         *mut xkb_state,
+    /// Dead keys and compose sequences, which are only available when the compose table for the locale is found
+    compose:       Option<(&'static XkbCommonCompose, *mut xkb_compose_table, *mut xkb_compose_state)>,
+}
+
+impl XkbKeymap {
+    /// Create the keymap from the file descriptor the compositor sent, which contains a null-terminated keymap
+    unsafe fn new(xkb: &'static XkbCommon, fd: &OwnedFd, size: u32) -> Option<XkbKeymap> {
+        let map = libc::mmap(ptr::null_mut(), size as usize, libc::PROT_READ, libc::MAP_PRIVATE, fd.as_raw_fd(), 0);
+        if map == libc::MAP_FAILED {
+            log_error!(LOG_CAT, "Failed to map the keymap of the keyboard ({})", std::io::Error::last_os_error());
+            return None;
+        }
+
+        let context = (xkb.xkb_context_new)(xkb_context_flags::XKB_CONTEXT_NO_FLAGS);
+        let keymap = if context.is_null() {
+            ptr::null_mut()
+        } else {
+            (xkb.xkb_keymap_new_from_string)(context, map as *const c_char, xkb_keymap_format::XKB_KEYMAP_FORMAT_TEXT_V1, xkb_keymap_compile_flags::XKB_KEYMAP_COMPILE_NO_FLAGS)
+        };
+        libc::munmap(map, size as usize);
+
+        if keymap.is_null() {
+            log_error!(LOG_CAT, "Failed to compile the keymap of the keyboard, text input will not be available");
+            if !context.is_null() {
+                (xkb.xkb_context_unref)(context);
+            }
+            return None;
+        }
+        let state = (xkb.xkb_state_new)(keymap);
+
+        let compose = xkbcommon_compose_option().and_then(|compose| {
+            // The compose table depends on the locale used for text, which is the first locale variable that is set
+            let locale = ["LC_ALL", "LC_CTYPE", "LANG"].iter()
+                .filter_map(|var| env::var(var).ok())
+                .find(|locale| !locale.is_empty())
+                .unwrap_or_else(|| "C".to_string());
+            let locale = CString::new(locale).ok()?;
+
+            let table = (compose.xkb_compose_table_new_from_locale)(context, locale.as_ptr(), xkb_compose_compile_flags::XKB_COMPOSE_COMPILE_NO_FLAGS);
+            if table.is_null() {
+                log_debug!(LOG_CAT, "No compose table was found for locale '{}', dead keys will not be available", locale.to_string_lossy());
+                return None;
+            }
+            let compose_state = (compose.xkb_compose_state_new)(table, xkb_compose_state_flags::XKB_COMPOSE_STATE_NO_FLAGS);
+            Some((compose, table, compose_state))
+        });
+
+        Some(XkbKeymap { xkb, context, keymap, state, compose })
+    }
+
+    fn update_modifiers(&mut self, depressed: u32, latched: u32, locked: u32, group: u32) {
+        unsafe { (self.xkb.xkb_state_update_mask)(self.state, depressed, latched, locked, 0, 0, group) };
+    }
+
+    fn key_repeats(&self, keycode: u32) -> bool {
+        unsafe { (self.xkb.xkb_keymap_key_repeats)(self.keymap, keycode) != 0 }
+    }
+
+    /// Get the text of a key press, dead keys are combined with the next key when `compose` is set
+    fn key_text(&mut self, keycode: u32, compose: bool) -> Option<String> {
+        unsafe {
+            if let (Some((compose_lib, _, compose_state)), true) = (self.compose, compose) {
+                let keysym = (self.xkb.xkb_state_key_get_one_sym)(self.state, keycode);
+                (compose_lib.xkb_compose_state_feed)(compose_state, keysym);
+                match (compose_lib.xkb_compose_state_get_status)(compose_state) {
+                    xkb_compose_status::XKB_COMPOSE_COMPOSING => return None,
+                    xkb_compose_status::XKB_COMPOSE_COMPOSED => {
+                        let text = read_utf8(|buf, len| (compose_lib.xkb_compose_state_get_utf8)(compose_state, buf, len));
+                        (compose_lib.xkb_compose_state_reset)(compose_state);
+                        return text;
+                    },
+                    xkb_compose_status::XKB_COMPOSE_CANCELLED => {
+                        (compose_lib.xkb_compose_state_reset)(compose_state);
+                        return None;
+                    },
+                    xkb_compose_status::XKB_COMPOSE_NOTHING => (),
+                }
+            }
+            read_utf8(|buf, len| (self.xkb.xkb_state_key_get_utf8)(self.state, keycode, buf, len))
+        }
+    }
+}
+
+impl Drop for XkbKeymap {
+    fn drop(&mut self) {
+        unsafe {
+            if let Some((compose, table, compose_state)) = self.compose {
+                (compose.xkb_compose_state_unref)(compose_state);
+                (compose.xkb_compose_table_unref)(table);
+            }
+            (self.xkb.xkb_state_unref)(self.state);
+            (self.xkb.xkb_keymap_unref)(self.keymap);
+            (self.xkb.xkb_context_unref)(self.context);
+        }
+    }
+}
+
+/// Read a UTF-8 string from xkbcommon, which returns the length of the string when the buffer is too small
+fn read_utf8<F: Fn(*mut c_char, usize) -> c_int>(f: F) -> Option<String> {
+    let len = f(ptr::null_mut(), 0);
+    if len <= 0 {
+        return None;
+    }
+
+    // The string is always null-terminated
+    let mut buf = vec![0u8; len as usize + 1];
+    f(buf.as_mut_ptr() as *mut c_char, buf.len());
+    buf.truncate(len as usize);
+    String::from_utf8(buf).ok()
+}
+
+pub(crate) struct KeyboardState {
+    keyboard:     WlKeyboard,
+    keymap:       Option<XkbKeymap>,
+    /// Window that has keyboard focus
+    focus:        Option<u32>,
+    /// Number of repeated key presses per second, 0 disables key repeat
+    repeat_rate:  i32,
+    /// Delay before a held key starts repeating
+    repeat_delay: Duration,
+    /// Key that is being held, with the time at which it's repeated next
+    repeat:       Option<(u32, Instant)>,
+}
+
+impl KeyboardState {
+    fn new(keyboard: WlKeyboard) -> KeyboardState {
+        // The compositor sends the actual repeat info when the keyboard is created, these are common defaults
+        KeyboardState { keyboard, keymap: None, focus: None, repeat_rate: 25, repeat_delay: Duration::from_millis(600), repeat: None }
+    }
+}
+
+/// Create or destroy the pointer and keyboard after the capabilities of the seat have changed
+pub(crate) fn update_capabilities(state: &mut WaylandState, conn: &Connection) {
+    let Some(seat) = &conn.seat else { return };
+    let capabilities = state.seat_capabilities;
+
+    let mut shared = shared();
+    if capabilities.contains(wl_seat::Capability::Pointer) {
+        if shared.pointer.is_none() {
+            shared.pointer = Some(PointerState::new(conn, seat.get_pointer(&conn.qh, ())));
+        }
+    } else if let Some(pointer) = shared.pointer.take() {
+        pointer.destroy();
+    }
+    drop(shared);
+
+    if capabilities.contains(wl_seat::Capability::Keyboard) {
+        if state.keyboard.is_none() {
+            state.keyboard = Some(KeyboardState::new(seat.get_keyboard(&conn.qh, ())));
+        }
+    } else if let Some(keyboard) = state.keyboard.take() {
+        // Releasing was only added in version 3, older keyboards are kept alive by the compositor
+        if keyboard.keyboard.version() >= 3 {
+            keyboard.keyboard.release();
+        }
+    }
+    conn.flush();
+}
+
+/// Repeat the text of the key that is being held
+pub(crate) fn repeat_key(state: &mut WaylandState) {
+    let Some(keyboard) = &mut state.keyboard else { return };
+    let Some((keycode, mut next)) = keyboard.repeat else { return };
+    let now = Instant::now();
+    if next > now {
+        return;
+    }
+
+    let interval = Duration::from_secs(1) / keyboard.repeat_rate as u32;
+    let mut text = String::new();
+    while next <= now {
+        // Dead keys are not repeated, as the compose sequence already ended when the key was pressed
+        if let Some(key_text) = keyboard.keymap.as_mut().and_then(|keymap| keymap.key_text(keycode, false)) {
+            text.push_str(&key_text);
+        }
+        next += interval;
+    }
+    keyboard.repeat = Some((keycode, next));
+
+    let focus = keyboard.focus;
+    if let Some(window) = focus.and_then(|id| state.window(id)) {
+        send_text(window, &text);
+    }
+}
+
+/// Send the text of a key press to a window, without any control characters
+fn send_text(window: &mut Window, text: &str) {
+    if !window.settings.does_accept_input() {
+        return;
+    }
+
+    let text = text.chars().filter(|ch| !ch.is_control()).collect::<String>();
+    if !text.is_empty() {
+        window.send_window_event(WindowEvent::Text(&text));
+    }
+}
+
+impl Dispatch<WlSeat, ()> for WaylandState {
+    fn event(state: &mut Self, _: &WlSeat, event: wl_seat::Event, _: &(), _: &WlConnection, _: &QueueHandle<Self>) {
+        // The pointer and keyboard are created by the window manager, as the connection isn't available while it's being opened
+        if let wl_seat::Event::Capabilities { capabilities: WEnum::Value(capabilities) } = event {
+            state.seat_capabilities = capabilities;
+            state.seat_changed = true;
+        }
+    }
+}
+
+impl Dispatch<WlPointer, ()> for WaylandState {
+    fn event(state: &mut Self, _: &WlPointer, event: wl_pointer::Event, _: &(), _: &WlConnection, _: &QueueHandle<Self>) {
+        match event {
+            wl_pointer::Event::Enter { serial, surface, .. } => {
+                let id = surface.id().protocol_id();
+                let Some(window) = state.window(id) else { return };
+
+                {
+                    let mut shared = shared();
+                    let shared = &mut *shared;
+                    let scale = shared.windows.get(&id).map_or(1, |proxies| proxies.scale);
+                    let Some(pointer) = &mut shared.pointer else { return };
+                    pointer.focus = Some(id);
+                    pointer.enter_serial = serial;
+
+                    // The cursor needs to be set each time the pointer enters a window
+                    if let Some(conn) = connection() {
+                        cursor::apply_cursor(conn, pointer, &window.cursor, window.cursor_visible, scale);
+                    }
+                }
+
+                // The pointer is given back to the window when the compositor is done moving or resizing the window
+                window.settings.flags.set(Flags::DraggingWindow | Flags::SizingWindow, false);
+
+                if !window.settings().is_mouse_in_window() {
+                    log_debug!(LOG_MSG_CAT, "mouse has entered window {}", window.id);
+                    window.settings.flags.set(Flags::MouseInWindow, true);
+                    window.send_window_event(WindowEvent::MouseEnter);
+                }
+            },
+            wl_pointer::Event::Leave { surface, .. } => {
+                let id = surface.id().protocol_id();
+                if let Some(pointer) = &mut shared().pointer {
+                    pointer.focus = None;
+                }
+
+                let Some(window) = state.window(id) else { return };
+                log_debug!(LOG_MSG_CAT, "mouse has left window {}", window.id);
+                window.settings.flags.set(Flags::MouseInWindow, false);
+                window.send_window_event(WindowEvent::MouseLeave);
+            },
+            wl_pointer::Event::Button { serial, state: button_state, .. } => {
+                let focus = {
+                    let mut shared = shared();
+                    shared.input_serial = serial;
+                    shared.pointer.as_ref().and_then(|pointer| pointer.focus)
+                };
+
+                if button_state == WEnum::Value(wl_pointer::ButtonState::Released) {
+                    if let Some(window) = focus.and_then(|id| state.window(id)) {
+                        window.settings.flags.set(Flags::DraggingWindow | Flags::SizingWindow, false);
+                    }
+                }
+            },
+            _ => (),
+        }
+    }
+}
+
+impl Dispatch<WlKeyboard, ()> for WaylandState {
+    fn event(state: &mut Self, _: &WlKeyboard, event: wl_keyboard::Event, _: &(), _: &WlConnection, _: &QueueHandle<Self>) {
+        let Some(keyboard) = &mut state.keyboard else { return };
+
+        match event {
+            wl_keyboard::Event::Keymap { format, fd, size } => {
+                keyboard.keymap = None;
+                if format != WEnum::Value(wl_keyboard::KeymapFormat::XkbV1) {
+                    log_warning!(LOG_CAT, "The keyboard uses an unsupported keymap format, text input will not be available");
+                    return;
+                }
+                let Some(xkb) = connection().and_then(|conn| conn.xkb) else { return };
+                keyboard.keymap = unsafe { XkbKeymap::new(xkb, &fd, size) };
+            },
+            wl_keyboard::Event::Enter { serial, surface, .. } => {
+                let id = surface.id().protocol_id();
+                keyboard.focus = Some(id);
+                shared().input_serial = serial;
+
+                let Some(window) = state.window(id) else { return };
+                log_debug!(LOG_MSG_CAT, "input has been focused for window {}", window.id);
+                window.send_window_event(WindowEvent::InputFocused);
+            },
+            wl_keyboard::Event::Leave { surface, .. } => {
+                keyboard.focus = None;
+                keyboard.repeat = None;
+
+                let Some(window) = state.window(surface.id().protocol_id()) else { return };
+                log_debug!(LOG_MSG_CAT, "input has been unfocused for window {}", window.id);
+                window.send_window_event(WindowEvent::InputUnfocused);
+            },
+            wl_keyboard::Event::Key { serial, key, state: key_state, .. } => {
+                shared().input_serial = serial;
+                let keycode = key + EVDEV_KEYCODE_OFFSET;
+
+                if key_state != WEnum::Value(wl_keyboard::KeyState::Pressed) {
+                    if keyboard.repeat.is_some_and(|(repeat_key, _)| repeat_key == keycode) {
+                        keyboard.repeat = None;
+                    }
+                    return;
+                }
+
+                let Some(keymap) = &mut keyboard.keymap else { return };
+                let text = keymap.key_text(keycode, true);
+                // Modifiers don't repeat, so holding them doesn't stop the previous key from repeating
+                if keymap.key_repeats(keycode) && keyboard.repeat_rate > 0 {
+                    keyboard.repeat = Some((keycode, Instant::now() + keyboard.repeat_delay));
+                }
+
+                let focus = keyboard.focus;
+                if let (Some(window), Some(text)) = (focus.and_then(|id| state.window(id)), text) {
+                    send_text(window, &text);
+                }
+            },
+            wl_keyboard::Event::Modifiers { mods_depressed, mods_latched, mods_locked, group, .. } => {
+                if let Some(keymap) = &mut keyboard.keymap {
+                    keymap.update_modifiers(mods_depressed, mods_latched, mods_locked, group);
+                }
+            },
+            wl_keyboard::Event::RepeatInfo { rate, delay } => {
+                keyboard.repeat_rate = rate.max(0);
+                keyboard.repeat_delay = Duration::from_millis(delay.max(0) as u64);
+                if rate <= 0 {
+                    keyboard.repeat = None;
+                }
+            },
+            _ => (),
+        }
+    }
+}
\ No newline at end of file
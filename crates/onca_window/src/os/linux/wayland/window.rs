@@ -0,0 +1,805 @@
+use crate::*;
+use core::{
+    cell::Cell,
+    ffi::c_void,
+    ptr::NonNull,
+};
+use onca_common::{
+    sync::Mutex,
+    event_listener::EventListenerArray,
+};
+use onca_logging::{log_debug, log_error, log_warning};
+use wayland_client::{
+    protocol::wl_surface::{self, WlSurface},
+    Connection as WlConnection, Dispatch, Proxy, QueueHandle, WEnum,
+};
+use wayland_protocols::{
+    wp::pointer_constraints::zv1::client::{
+        zwp_confined_pointer_v1::ZwpConfinedPointerV1,
+        zwp_locked_pointer_v1::ZwpLockedPointerV1,
+        zwp_pointer_constraints_v1::Lifetime,
+    },
+    xdg::{
+        activation::v1::client::xdg_activation_token_v1::{self, XdgActivationTokenV1},
+        decoration::zv1::client::zxdg_toplevel_decoration_v1::{self, ZxdgToplevelDecorationV1},
+        shell::client::{
+            xdg_surface::{self, XdgSurface},
+            xdg_toplevel::{self, XdgToplevel},
+        },
+    },
+};
+
+use super::{connection, cursor, premultiply, shared, Connection, window_manager::WaylandState};
+use crate::os::linux::{
+    window::{process_dpi_change, OSWindowData, OSWindowHandle, WindowHandle},
+    WindowManagerData,
+};
+
+/// Objects of a window, which are kept in the shared state, as the window handle needs to be copyable
+pub(crate) struct WindowProxies {
+    pub(crate) surface:  WlSurface,
+    xdg_surface:         XdgSurface,
+    toplevel:            XdgToplevel,
+    decoration:          Option<ZxdgToplevelDecorationV1>,
+    locked_pointer:      Option<ZwpLockedPointerV1>,
+    confined_pointer:    Option<ZwpConfinedPointerV1>,
+    /// Outputs the window is shown on, by the name of their global, in the order they were entered
+    pub(crate) outputs:  Vec<u32>,
+    /// Scale of the buffers drawn to the window
+    pub(crate) scale:    i32,
+}
+
+/// Requests made using a window handle, which are processed during the next tick of the window manager, so listeners are notified the same way as on other platforms
+pub(crate) enum PendingEvent {
+    Close,
+    EnableInput(bool),
+    /// Wayland windows choose their own size, so resizing just changes the size the window is drawn at
+    Resize(PhysicalSize),
+    /// The compositor doesn't tell when a window is minimized
+    Minimized,
+    Destroyed,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct WaylandWindowHandle {
+    /// Protocol id of the window's surface, which identifies the window
+    id:      u32,
+    /// `wl_surface` of the window, which is used by renderers to create a surface for the window
+    surface: NonNull<c_void>,
+}
+
+impl WaylandWindowHandle {
+    pub(crate) fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Get the raw platform handle of the window
+    pub(crate) fn raw_window_handle(&self) -> RawWindowHandle {
+        RawWindowHandle::Wayland { surface: self.surface }
+    }
+
+    /// Call `f` with the objects of the window, as long as the window hasn't been destroyed
+    fn with_proxies<R, F: FnOnce(&Connection, &mut WindowProxies) -> R>(&self, f: F) -> Option<R> {
+        let conn = connection()?;
+        let res = f(conn, shared().windows.get_mut(&self.id)?);
+        conn.flush();
+        Some(res)
+    }
+
+    fn push_event(&self, event: PendingEvent) {
+        shared().pending.push((self.id, event));
+    }
+
+    pub(crate) fn close(&mut self) -> bool {
+        // Closing goes through the same path as the close button, so listeners are still able to cancel it
+        self.push_event(PendingEvent::Close);
+        true
+    }
+
+    pub(crate) fn move_to(&mut self, window_id: WindowId, _settings: &WindowSettings, x: i32, y: i32) {
+        log_warning!(LOG_CAT, "Failed to move window '{window_id}' to ({x}, {y}), Wayland doesn't allow applications to position their windows.");
+    }
+
+    pub(crate) fn resize(&mut self, _window_id: WindowId, _settings: &WindowSettings, width: u16, height: u16) {
+        self.push_event(PendingEvent::Resize(PhysicalSize::new(width.max(1), height.max(1))));
+    }
+
+    pub(crate) fn minimize(&mut self, _window_id: WindowId) {
+        if self.with_proxies(|_, proxies| proxies.toplevel.set_minimized()).is_some() {
+            self.push_event(PendingEvent::Minimized);
+        }
+    }
+
+    pub(crate) fn maximize(&mut self, _window_id: WindowId) {
+        self.with_proxies(|_, proxies| proxies.toplevel.set_maximized());
+    }
+
+    pub(crate) fn restore(&mut self, _window_id: WindowId) {
+        self.with_proxies(|_, proxies| proxies.toplevel.unset_maximized());
+        // A minimized window can only be brought back by activating it
+        self.request_activation(true);
+    }
+
+    /// Set the fullscreen mode of the window, returning the mode that was applied.
+    pub(crate) fn set_fullscreen(
+        &mut self,
+        window_id: WindowId,
+        old_mode: FullscreenMode,
+        mode: FullscreenMode,
+        settings: &mut WindowSettings,
+    ) -> FullscreenMode {
+        let mode = match mode {
+            FullscreenMode::Exclusive(_) => {
+                log_warning!(LOG_CAT, "Failed to switch window '{window_id}' to exclusive fullscreen, falling back to borderless fullscreen, as Wayland doesn't allow changing the display mode");
+                FullscreenMode::Borderless
+            },
+            mode => mode,
+        };
+
+        let fullscreen = mode != FullscreenMode::Windowed;
+        let applied = self.with_proxies(|_, proxies| {
+            settings.flags.set(Flags::Fullscreen, fullscreen);
+            if fullscreen {
+                proxies.toplevel.set_fullscreen(None);
+            } else {
+                proxies.toplevel.unset_fullscreen();
+            }
+            update_size_limits(proxies, settings);
+            update_decorations(proxies, settings);
+        });
+        if applied.is_some() { mode } else { old_mode }
+    }
+
+    pub(crate) fn bring_to_front(&mut self, _window_id: WindowId) {
+        self.request_activation(true);
+    }
+
+    pub(crate) fn set_topmost(&mut self, window_id: WindowId, topmost: bool) {
+        if topmost {
+            log_warning!(LOG_CAT, "Failed to make window '{window_id}' topmost, Wayland doesn't allow applications to keep their windows on top");
+        }
+    }
+
+    pub(crate) fn enable_input(&mut self, _window_id: WindowId, enable: bool) {
+        // Wayland has no concept of disabled windows, so input is ignored while the window is disabled
+        self.push_event(PendingEvent::EnableInput(enable));
+    }
+
+    pub(crate) fn set_visible(&mut self, window_id: WindowId, _settings: &WindowSettings, visible: bool) {
+        // A window is shown once it's drawn to, and it can only be hidden by destroying it
+        if !visible {
+            log_warning!(LOG_CAT, "Failed to hide window '{window_id}', Wayland doesn't allow hiding a window without destroying it");
+        }
+    }
+
+    pub(crate) fn set_active(&mut self, _window_id: WindowId, _settings: &WindowSettings, active: bool) {
+        // Only the compositor can deactivate a window, by activating another one
+        if active {
+            self.request_activation(true);
+        }
+    }
+
+    /// Only a single bit is allowed to be set with this function
+    pub(crate) fn set_flag(
+        &self,
+        settings: &WindowSettings,
+        flag: Flags,
+        _set: bool,
+    ) -> Result<(), u32> {
+        let res = self.with_proxies(|conn, proxies| {
+            match flag {
+                Flags::Resizable => update_size_limits(proxies, settings),
+                Flags::ClickThrough => update_input_region(conn, proxies, settings),
+                // Wayland has no window types, and the compositor decides which buttons are shown
+                _ => (),
+            }
+            // The state is double-buffered, so it's only applied once the surface is committed
+            proxies.surface.commit();
+        });
+        res.ok_or(0)
+    }
+
+    pub(crate) fn set_opacity(&mut self, window_id: WindowId, settings: &mut WindowSettings, opacity: f32) {
+        settings.opacity = opacity;
+        if opacity < 1.0 {
+            log_warning!(LOG_CAT, "Failed to set the opacity of window '{window_id}', Wayland only supports transparency using the alpha of the window's pixels");
+        }
+    }
+
+    pub(crate) fn set_border_style(&mut self, _window_id: WindowId, settings: &mut WindowSettings, border_style: BorderStyle) {
+        settings.border = border_style;
+        self.with_proxies(|_, proxies| update_decorations(proxies, settings));
+    }
+
+    pub(crate) fn begin_drag(&mut self) -> bool {
+        let serial = shared().input_serial;
+        self.with_proxies(|conn, proxies| match &conn.seat {
+            Some(seat) => {
+                proxies.toplevel._move(seat, serial);
+                true
+            },
+            None => false,
+        }).unwrap_or(false)
+    }
+
+    pub(crate) fn begin_sizing(&mut self, dir: ResizeDir) -> bool {
+        // Matches the edges used by `_NET_WM_MOVERESIZE` on X11
+        let edge = match dir {
+            ResizeDir::North => xdg_toplevel::ResizeEdge::Top,
+            ResizeDir::NorthWest => xdg_toplevel::ResizeEdge::TopRight,
+            ResizeDir::West => xdg_toplevel::ResizeEdge::Right,
+            ResizeDir::SouthWest => xdg_toplevel::ResizeEdge::BottomRight,
+            ResizeDir::South => xdg_toplevel::ResizeEdge::Bottom,
+            ResizeDir::SouthEast => xdg_toplevel::ResizeEdge::BottomLeft,
+            ResizeDir::East => xdg_toplevel::ResizeEdge::Left,
+            ResizeDir::NorthEast => xdg_toplevel::ResizeEdge::TopLeft,
+        };
+
+        let serial = shared().input_serial;
+        self.with_proxies(|conn, proxies| match &conn.seat {
+            Some(seat) => {
+                proxies.toplevel.resize(seat, serial, edge);
+                true
+            },
+            None => false,
+        }).unwrap_or(false)
+    }
+
+    pub(crate) fn notify_user(&mut self, _window_id: WindowId, attention: AttentionType) {
+        // Activation requests that don't come from user input make the compositor ask for the user's attention instead, how this is shown is up to the compositor
+        if !matches!(attention, AttentionType::None) {
+            self.request_activation(false);
+        }
+    }
+
+    pub(crate) fn set_icons(&mut self, _window_id: WindowId, icon: Option<&Icon>, small_icon: Option<&Icon>) {
+        self.with_proxies(|conn, proxies| set_icons(conn, proxies, icon, small_icon));
+    }
+
+    pub(crate) fn set_taskbar_progress(&mut self, _window_id: WindowId, _manager: &WindowManagerData, _progress: TaskbarProgress) {
+        // Wayland has no standard way of showing progress on a taskbar
+        // TODO(jel): Support the Unity launcher API over D-Bus, which is also supported by KDE and some GNOME extensions
+    }
+
+    pub(crate) fn set_ime_allowed(&mut self, _window_id: WindowId, _allowed: bool) {
+        // TODO(jel): Support input methods using the text-input protocol
+    }
+
+    pub(crate) fn set_ime_candidate_rect(&mut self, _window_id: WindowId, _pos: PhysicalPosition, _size: PhysicalSize) {
+        // TODO(jel): Support input methods using the text-input protocol
+    }
+
+    pub(crate) fn apply_cursor_grab(&mut self, window_id: WindowId, mode: CursorGrabMode) {
+        let Some(conn) = connection() else { return };
+        let mut shared = shared();
+        let pointer = shared.pointer.as_ref().map(|pointer| pointer.pointer.clone());
+        let Some(proxies) = shared.windows.get_mut(&self.id) else { return };
+
+        if let Some(locked_pointer) = proxies.locked_pointer.take() {
+            locked_pointer.destroy();
+        }
+        if let Some(confined_pointer) = proxies.confined_pointer.take() {
+            confined_pointer.destroy();
+        }
+
+        if mode != CursorGrabMode::None {
+            let (Some(constraints), Some(pointer)) = (&conn.pointer_constraints, pointer) else {
+                log_warning!(LOG_CAT, "Failed to set the cursor grab of window '{window_id}' to {mode:?}, pointer constraints are not available");
+                return;
+            };
+
+            // Persistent constraints are reactivated by the compositor each time the window gets focus
+            if mode == CursorGrabMode::Locked {
+                proxies.locked_pointer = Some(constraints.lock_pointer(&proxies.surface, &pointer, None, Lifetime::Persistent, &conn.qh, ()));
+            } else {
+                proxies.confined_pointer = Some(constraints.confine_pointer(&proxies.surface, &pointer, None, Lifetime::Persistent, &conn.qh, ()));
+            }
+        }
+        drop(shared);
+        conn.flush();
+    }
+
+    pub(crate) fn release_cursor_grab(&mut self) {
+        // Pointer constraints are only active while the window has focus, so they don't need to be released when the window loses focus
+    }
+
+    pub(crate) fn update_cursor(&mut self, cursor: &WindowCursor, visible: bool) {
+        cursor::update_cursor(self.id, cursor, visible);
+    }
+
+    pub(crate) unsafe fn destroy(&mut self) {
+        let Some(conn) = connection() else { return };
+        // Make sure to only destroy the window once, as the window manager only notifies listeners during its next tick
+        let Some(proxies) = shared().windows.remove(&self.id) else { return };
+
+        if let Some(locked_pointer) = proxies.locked_pointer {
+            locked_pointer.destroy();
+        }
+        if let Some(confined_pointer) = proxies.confined_pointer {
+            confined_pointer.destroy();
+        }
+        if let Some(decoration) = proxies.decoration {
+            decoration.destroy();
+        }
+        proxies.toplevel.destroy();
+        proxies.xdg_surface.destroy();
+        proxies.surface.destroy();
+        conn.flush();
+
+        self.push_event(PendingEvent::Destroyed);
+    }
+
+    /// Ask the compositor to activate the window, passing the serial of the last input event lets the compositor know the request comes from the user
+    fn request_activation(&self, from_input: bool) {
+        let Some(conn) = connection() else { return };
+        let Some(activation) = &conn.activation else { return };
+
+        let shared = shared();
+        let Some(proxies) = shared.windows.get(&self.id) else { return };
+        let token = activation.get_activation_token(&conn.qh, proxies.surface.clone());
+        if let (Some(seat), true) = (&conn.seat, from_input && shared.input_serial != 0) {
+            token.set_serial(shared.input_serial, seat);
+        }
+        token.set_surface(&proxies.surface);
+        token.commit();
+        drop(shared);
+        conn.flush();
+    }
+}
+
+/// State sent by an `xdg_toplevel.configure`, which is only applied once the compositor is done sending all state
+struct ToplevelConfigure {
+    /// Size in surface coordinates, 0 when the window can choose its own size
+    width:  i32,
+    height: i32,
+    states: Vec<xdg_toplevel::State>,
+}
+
+#[derive(Default)]
+pub(crate) struct WaylandWindowData {
+    pending_configure: Option<ToplevelConfigure>,
+    /// Has the first configure been received, which is needed before anything can be drawn to the window
+    configured:        bool,
+    /// Scale the compositor prefers, which takes priority over the scale of the outputs the window is on
+    preferred_scale:   Option<i32>,
+}
+
+impl WaylandWindowData {
+    pub(crate) fn set_accept_files(window: &mut Window) {
+        // The compositor sends drags to any window, so the data device ignores drags over windows that don't accept files
+        if !window.settings().does_accept_files() {
+            log_debug!(LOG_CAT, "Window {} no longer accepts files", window.id());
+        }
+    }
+}
+
+/// Close a window after the close button was pressed or the window was asked to close, unless a listener cancels it
+fn request_close(window: &mut Window) {
+    log_debug!(LOG_MSG_CAT, "received close request for window {}", window.id);
+    // The close button can still be used while the window is closing
+    if window.is_closing() {
+        return;
+    }
+
+    let close = Cell::new(true);
+    let cancel = || close.set(false);
+    window.send_window_event(WindowEvent::CloseRequested{ cancel: &cancel });
+    if close.get() {
+        window.is_closing = true;
+        window.send_window_event(WindowEvent::Closed);
+    }
+}
+
+/// Process a request made using the window handle, see `PendingEvent`
+pub(crate) fn process_pending_event(window: &mut Window, event: PendingEvent) {
+    match event {
+        PendingEvent::Close => request_close(window),
+        PendingEvent::EnableInput(enabled) => {
+            log_debug!(LOG_MSG_CAT, "received input {} for window {}", if enabled { "enable" } else { "disable" }, window.id);
+            window.settings.flags.set(Flags::AcceptsInput, enabled);
+            window.send_window_event(if enabled { WindowEvent::EnabledInput } else { WindowEvent::DisabledInput });
+        },
+        PendingEvent::Resize(size) => {
+            if size != window.settings.size() {
+                window.settings.size = size.into();
+                log_debug!(LOG_MSG_CAT, "Window {} has been resized to {}x{}", window.id, size.width, size.height);
+                window.send_window_event(WindowEvent::Resized(size));
+            }
+        },
+        PendingEvent::Minimized => set_minmax_state(window, Flags::Minimized),
+        PendingEvent::Destroyed => {
+            log_debug!(LOG_MSG_CAT, "Window {} has been destroyed", window.id);
+            window.notify_destroyed();
+        },
+    }
+}
+
+fn set_minmax_state(window: &mut Window, minmax_state: Flags) {
+    let old_state = window.settings.set_minmax_state(minmax_state);
+    if old_state == minmax_state {
+        return;
+    }
+
+    if minmax_state == Flags::Minimized {
+        log_debug!(LOG_MSG_CAT, "Window {} has been minimized", window.id);
+        window.send_window_event(WindowEvent::Minimized);
+    } else if minmax_state == Flags::Maximized {
+        log_debug!(LOG_MSG_CAT, "Window {} has been maximized", window.id);
+        window.send_window_event(WindowEvent::Maximized);
+    } else {
+        log_debug!(LOG_MSG_CAT, "Window {} has been restored", window.id);
+        window.send_window_event(WindowEvent::Restored);
+    }
+    // The new size will be sent by the next configure if it has changed, but a `Resized` event is still expected to follow
+    let size = window.settings.size();
+    window.send_window_event(WindowEvent::Resized(size));
+}
+
+/// Apply the state the compositor sent in a configure sequence
+fn apply_configure(window: &mut Window, configure: ToplevelConfigure) {
+    let id = window.os_handle.wayland().id;
+    let scale = shared().windows.get(&id).map_or(1, |proxies| proxies.scale);
+
+    let has_state = |state: xdg_toplevel::State| configure.states.contains(&state);
+    let maximized = has_state(xdg_toplevel::State::Maximized);
+    let activated = has_state(xdg_toplevel::State::Activated);
+
+    if configure.width > 0 && configure.height > 0 {
+        let size = PhysicalSize::new((configure.width * scale) as u16, (configure.height * scale) as u16);
+        if size != window.settings.size() {
+            window.settings.size = size.into();
+            log_debug!(LOG_MSG_CAT, "Window {} has been resized to {}x{}", window.id, size.width, size.height);
+            window.send_window_event(WindowEvent::Resized(size));
+        }
+    }
+
+    // A minimized window can only be brought back by activating it
+    let minmax_state = if maximized {
+        Flags::Maximized
+    } else if window.settings.is_minimized() && !activated {
+        Flags::Minimized
+    } else {
+        Flags::None
+    };
+    set_minmax_state(window, minmax_state);
+
+    if activated != window.settings.is_active() {
+        log_debug!(LOG_MSG_CAT, "Window {} has been {}", window.id, if activated { "activated" } else { "deactivated" });
+        window.settings.flags.set(Flags::Active, activated);
+        if activated {
+            let minimized = window.settings.is_minimized();
+            window.send_window_event(WindowEvent::Focused(minimized));
+        } else {
+            window.send_window_event(WindowEvent::Unfocused);
+        }
+    }
+
+    // The window is shown as soon as something is drawn to it, which can only be done after the first configure
+    let data = window.os_data.wayland();
+    if !data.configured {
+        data.configured = true;
+        window.settings.flags.set(Flags::Visible, true);
+        window.send_window_event(WindowEvent::Visible);
+    }
+}
+
+/// Update the buffer scale of a window, after the outputs the window is on or the scale preferred by the compositor have changed
+pub(crate) fn update_scale(window: &mut Window) {
+    let id = window.os_handle.wayland().id;
+    let preferred_scale = window.os_data.wayland().preferred_scale;
+
+    let scale = {
+        let mut shared = shared();
+        let shared = &mut *shared;
+        let Some(proxies) = shared.windows.get_mut(&id) else { return };
+
+        // Windows that aren't DPI aware are scaled by the compositor
+        let scale = if !window.settings.is_dpi_aware() {
+            1
+        } else if let Some(scale) = preferred_scale {
+            scale
+        } else {
+            // Use the highest scale, so the window is sharp on every output it's shown on
+            proxies.outputs.iter()
+                .filter_map(|name| shared.outputs.iter().find(|output| output.name == *name))
+                .map(|output| output.scale)
+                .max()
+                .unwrap_or(proxies.scale)
+        };
+
+        if scale == proxies.scale {
+            return;
+        }
+        proxies.scale = scale;
+        proxies.surface.set_buffer_scale(scale);
+        update_size_limits(proxies, &window.settings);
+        scale
+    };
+
+    process_dpi_change(window, WindowSettings::DEFAULT_DPI * scale as u16);
+}
+
+/// Update the minimum and maximum size of the window, which are in surface coordinates
+fn update_size_limits(proxies: &WindowProxies, settings: &WindowSettings) {
+    let to_surface = |size: PhysicalSize| (size.width as i32 / proxies.scale, size.height as i32 / proxies.scale);
+
+    // Compositors may refuse to make a window fullscreen when its size is limited
+    let (min_size, max_size) = if settings.is_fullscreen() {
+        (None, None)
+    } else if settings.is_resizable() {
+        let dpi_scale = settings.dpi_scale();
+        (settings.min_size.map(|s| s.to_physical(dpi_scale)), settings.max_size.map(|s| s.to_physical(dpi_scale)))
+    } else {
+        (Some(settings.size()), Some(settings.size()))
+    };
+
+    // A size of 0 means there is no limit
+    let (min_width, min_height) = min_size.map_or((0, 0), to_surface);
+    let (max_width, max_height) = max_size.map_or((0, 0), to_surface);
+    proxies.toplevel.set_min_size(min_width, min_height);
+    proxies.toplevel.set_max_size(max_width, max_height);
+}
+
+/// Update who draws the frame of the window, the compositor doesn't distinguish between the different border styles
+fn update_decorations(proxies: &WindowProxies, settings: &WindowSettings) {
+    let Some(decoration) = &proxies.decoration else { return };
+
+    // A fullscreen window never has a border, but keeps its border style for when it returns to windowed mode
+    // Client-side decorations are never drawn, so they result in a window without a frame
+    if settings.is_fullscreen() || settings.border_style() == BorderStyle::Borderless {
+        decoration.set_mode(zxdg_toplevel_decoration_v1::Mode::ClientSide);
+    } else {
+        decoration.set_mode(zxdg_toplevel_decoration_v1::Mode::ServerSide);
+    }
+}
+
+/// Update the input region of the window, an empty input region lets all mouse input pass through to whatever is below the window
+fn update_input_region(conn: &Connection, proxies: &WindowProxies, settings: &WindowSettings) {
+    if settings.is_click_through() {
+        let region = conn.create_empty_region();
+        proxies.surface.set_input_region(Some(&region));
+        region.destroy();
+    } else {
+        // Without a region, the whole surface receives input
+        proxies.surface.set_input_region(None);
+    }
+}
+
+fn set_icons(conn: &Connection, proxies: &WindowProxies, icon: Option<&Icon>, small_icon: Option<&Icon>) {
+    let Some(icon_manager) = &conn.icon_manager else { return };
+
+    let buffers = [icon, small_icon].into_iter()
+        .flatten()
+        .map(|icon| icon.get_os_icon())
+        .filter(|icon| icon.is_valid())
+        .filter_map(|icon| {
+            let (width, height, argb) = icon.argb();
+            if width != height {
+                log_warning!(LOG_CAT, "Ignoring a {width}x{height} window icon, Wayland only supports square icons");
+                return None;
+            }
+            let premultiplied = argb.iter().map(|pixel| premultiply(*pixel)).collect::<Vec<_>>();
+            conn.create_shm_buffer(width, height, &premultiplied)
+        })
+        .collect::<Vec<_>>();
+
+    if buffers.is_empty() {
+        // Without an icon, the compositor uses the icon from the application's desktop entry
+        icon_manager.set_icon(&proxies.toplevel, None);
+        return;
+    }
+
+    let toplevel_icon = icon_manager.create_icon(&conn.qh, ());
+    for buffer in &buffers {
+        toplevel_icon.add_buffer(buffer, 1);
+    }
+    icon_manager.set_icon(&proxies.toplevel, Some(&toplevel_icon));
+    // The window keeps its icon once it's set, after which the icon and its buffers are no longer needed
+    toplevel_icon.destroy();
+    for buffer in buffers {
+        buffer.destroy();
+    }
+}
+
+impl Dispatch<WlSurface, ()> for WaylandState {
+    fn event(state: &mut Self, surface: &WlSurface, event: wl_surface::Event, _: &(), _: &WlConnection, _: &QueueHandle<Self>) {
+        let id = surface.id().protocol_id();
+        let Some(window) = state.window(id) else { return };
+
+        match event {
+            wl_surface::Event::Enter { output } => {
+                let Some(name) = output.data::<u32>().copied() else { return };
+                if let Some(proxies) = shared().windows.get_mut(&id) {
+                    proxies.outputs.push(name);
+                }
+                update_scale(window);
+            },
+            wl_surface::Event::Leave { output } => {
+                let Some(name) = output.data::<u32>().copied() else { return };
+                if let Some(proxies) = shared().windows.get_mut(&id) {
+                    proxies.outputs.retain(|output| *output != name);
+                }
+                update_scale(window);
+            },
+            wl_surface::Event::PreferredBufferScale { factor } => {
+                window.os_data.wayland().preferred_scale = Some(factor.max(1));
+                update_scale(window);
+            },
+            _ => (),
+        }
+    }
+}
+
+impl Dispatch<XdgSurface, u32> for WaylandState {
+    fn event(state: &mut Self, xdg_surface: &XdgSurface, event: xdg_surface::Event, id: &u32, _: &WlConnection, _: &QueueHandle<Self>) {
+        let xdg_surface::Event::Configure { serial } = event else { return };
+        xdg_surface.ack_configure(serial);
+
+        let Some(window) = state.window(*id) else { return };
+        if let Some(configure) = window.os_data.wayland().pending_configure.take() {
+            apply_configure(window, configure);
+        }
+    }
+}
+
+impl Dispatch<XdgToplevel, u32> for WaylandState {
+    fn event(state: &mut Self, _: &XdgToplevel, event: xdg_toplevel::Event, id: &u32, _: &WlConnection, _: &QueueHandle<Self>) {
+        let Some(window) = state.window(*id) else { return };
+
+        match event {
+            xdg_toplevel::Event::Configure { width, height, states } => {
+                // The states are sent as an array of 32-bit values
+                let states = states.chunks_exact(4)
+                    .filter_map(|state| xdg_toplevel::State::try_from(u32::from_ne_bytes([state[0], state[1], state[2], state[3]])).ok())
+                    .collect();
+                window.os_data.wayland().pending_configure = Some(ToplevelConfigure { width, height, states });
+            },
+            xdg_toplevel::Event::Close => request_close(window),
+            _ => (),
+        }
+    }
+}
+
+impl Dispatch<ZxdgToplevelDecorationV1, u32> for WaylandState {
+    fn event(_: &mut Self, _: &ZxdgToplevelDecorationV1, event: zxdg_toplevel_decoration_v1::Event, id: &u32, _: &WlConnection, _: &QueueHandle<Self>) {
+        // The compositor may ignore the requested mode, in which case it's up to the compositor if the window has a frame
+        if let zxdg_toplevel_decoration_v1::Event::Configure { mode: WEnum::Value(mode) } = event {
+            log_debug!(LOG_MSG_CAT, "Decoration mode of the surface {id} has changed to {mode:?}");
+        }
+    }
+}
+
+impl Dispatch<XdgActivationTokenV1, WlSurface> for WaylandState {
+    fn event(_: &mut Self, token: &XdgActivationTokenV1, event: xdg_activation_token_v1::Event, surface: &WlSurface, _: &WlConnection, _: &QueueHandle<Self>) {
+        let xdg_activation_token_v1::Event::Done { token: token_str } = event else { return };
+        if let Some(activation) = connection().and_then(|conn| conn.activation.as_ref()) {
+            activation.activate(token_str, surface);
+        }
+        token.destroy();
+    }
+}
+
+pub(crate) fn raw_display_handle() -> RawDisplayHandle {
+    let display = connection().and_then(|conn| NonNull::new(conn.display_ptr()));
+    match display {
+        Some(display) => RawDisplayHandle::Wayland { display },
+        // The Wayland backend is only used when the connection to the compositor was opened
+        None => RawDisplayHandle::Xlib { display: None, screen: 0 },
+    }
+}
+
+/// Get the id the compositor uses to match windows with the desktop entry of the application, which is the name of the executable
+fn app_id() -> Option<String> {
+    let exe = std::env::current_exe().ok()?;
+    Some(exe.file_stem()?.to_string_lossy().into_owned())
+}
+
+pub(crate) fn create(
+    manager: &mut WindowManager,
+    mut settings: WindowSettings
+) -> Option<Box<Window>> {
+    let Some(conn) = connection() else {
+        log_error!(LOG_CAT, "Failed to create a window, there is no connection to the Wayland compositor");
+        return None;
+    };
+
+    settings.validate_dpi();
+    // The compositor only tells which outputs a window is on once it's shown, so start with the scale of the first output
+    let scale = if settings.is_dpi_aware() {
+        shared().outputs.first().map_or(1, |output| output.scale)
+    } else {
+        1
+    };
+    settings.dpi = WindowSettings::DEFAULT_DPI * scale as u16;
+    // The frame drawn by the compositor is outside of the surface, and its size isn't known
+    settings.margins = Margins { top: 0, left: 0, bottom: 0, right: 0 };
+
+    let surface = conn.compositor.create_surface(&conn.qh, ());
+    let id = surface.id().protocol_id();
+    let Some(surface_ptr) = NonNull::new(surface.id().as_ptr() as *mut c_void) else {
+        log_error!(LOG_CAT, "Failed to create a window, the surface has no proxy");
+        surface.destroy();
+        return None;
+    };
+
+    let xdg_surface = conn.wm_base.get_xdg_surface(&surface, &conn.qh, id);
+    let toplevel = xdg_surface.get_toplevel(&conn.qh, id);
+    if let Some(title) = &settings.title {
+        toplevel.set_title(title.clone());
+    }
+    if let Some(app_id) = app_id() {
+        toplevel.set_app_id(app_id);
+    }
+    if scale > 1 {
+        surface.set_buffer_scale(scale);
+    }
+
+    let decoration = conn.decoration_manager.as_ref().map(|manager| manager.get_toplevel_decoration(&toplevel, &conn.qh, id));
+    let proxies = WindowProxies {
+        surface: surface.clone(),
+        xdg_surface,
+        toplevel,
+        decoration,
+        locked_pointer: None,
+        confined_pointer: None,
+        outputs: Vec::new(),
+        scale,
+    };
+
+    update_size_limits(&proxies, &settings);
+    update_decorations(&proxies, &settings);
+    set_icons(conn, &proxies, settings.icon(), settings.small_icon());
+    if settings.is_click_through() {
+        update_input_region(conn, &proxies, &settings);
+    }
+    if settings.is_maximized() {
+        proxies.toplevel.set_maximized();
+    }
+    if settings.is_fullscreen() {
+        proxies.toplevel.set_fullscreen(None);
+    }
+    // A window is only mapped once it's drawn to, so it can't start minimized
+    if settings.is_minimized() {
+        settings.set_minmax_state(Flags::None);
+    }
+    if settings.is_top_most() {
+        log_warning!(LOG_CAT, "Wayland doesn't allow applications to keep their windows on top, the window will not be topmost");
+        settings.flags.set(Flags::TopMost, false);
+    }
+    shared().windows.insert(id, proxies);
+
+    let fullscreen_mode = if settings.is_fullscreen() { FullscreenMode::Borderless } else { FullscreenMode::Windowed };
+    let PhysicalSize { width, height } = settings.size();
+    // The window is only visible once the compositor has configured it
+    settings.flags.set(Flags::Visible | Flags::Active, false);
+
+    let window = Window {
+        os_handle: OSWindowHandle(WindowHandle::Wayland(WaylandWindowHandle { id, surface: surface_ptr })),
+        os_data: OSWindowData::Wayland(WaylandWindowData::default()),
+        id: WindowId(0),
+        settings,
+        manager: manager as *mut WindowManager,
+        listeners: Mutex::new(EventListenerArray::new()),
+        is_closing: false,
+        is_destroyed: false,
+        cursor_grab: CursorGrabMode::None,
+        cursor_visible: true,
+        cursor: WindowCursor::Shape(CursorShape::Arrow),
+        drop_effect: DropEffect::Copy,
+        fullscreen_mode,
+        taskbar_progress: TaskbarProgress::None,
+    };
+    let mut window_ptr = Box::new(window);
+
+    let data = manager.get_os_data().wayland();
+    data.register_window(id, &mut *window_ptr);
+
+    // Committing the surface without a buffer makes the compositor configure the window, which needs to be acknowledged before anything can be drawn to it
+    surface.commit();
+    data.roundtrip();
+
+    let title_str = window_ptr.settings().title().map_or("", |string| string);
+    log_debug!(LOG_CAT, "Created new window '{title_str}' with size {width}x{height}");
+
+    Some(window_ptr)
+}
\ No newline at end of file
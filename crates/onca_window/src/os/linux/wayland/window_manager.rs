@@ -0,0 +1,171 @@
+use core::mem;
+use std::{collections::HashMap, os::fd::AsRawFd};
+
+use onca_logging::{log_debug, log_error};
+use wayland_client::{
+    protocol::{
+        wl_data_device::WlDataDevice,
+        wl_data_offer::WlDataOffer,
+        wl_data_source::WlDataSource,
+        wl_seat,
+    },
+    EventQueue,
+};
+
+use crate::{Window, LOG_CAT};
+use super::{
+    clipboard::DragState,
+    connection, seat::{self, KeyboardState}, shared,
+    window::{self, PendingEvent},
+};
+
+/// State that Wayland events are dispatched to
+pub(crate) struct WaylandState {
+    /// Windows mapped by the protocol id of their surface, so events can be dispatched to them
+    windows:                       HashMap<u32, *mut Window>,
+    pub(crate) seat_capabilities:  wl_seat::Capability,
+    /// Have the capabilities of the seat changed since the last tick
+    pub(crate) seat_changed:       bool,
+    pub(crate) keyboard:           Option<KeyboardState>,
+    pub(crate) data_device:        Option<WlDataDevice>,
+    /// Files being dragged over a window
+    pub(crate) drag:               Option<DragState>,
+    /// Clipboard content offered by another client
+    pub(crate) selection:          Option<WlDataOffer>,
+    /// Clipboard content set by this application
+    pub(crate) clipboard_source:   Option<(WlDataSource, String)>,
+    /// Has the display configuration changed since the last tick
+    pub(crate) monitors_changed:   bool,
+    /// Has the scale of any output changed since the last tick
+    pub(crate) scale_changed:      bool,
+}
+
+// SAFETY: The windows are only accessed by the window manager, which runs on the main thread
+unsafe impl Send for WaylandState {}
+
+impl WaylandState {
+    pub(crate) fn new() -> Self {
+        Self {
+            windows: HashMap::new(),
+            seat_capabilities: wl_seat::Capability::empty(),
+            seat_changed: false,
+            keyboard: None,
+            data_device: None,
+            drag: None,
+            selection: None,
+            clipboard_source: None,
+            monitors_changed: false,
+            scale_changed: false,
+        }
+    }
+
+    /// Get a window from the protocol id of its surface
+    pub(crate) fn window<'a>(&self, id: u32) -> Option<&'a mut Window> {
+        // SAFETY: Windows are boxed, and are only removed from the window manager after they have been destroyed, which removes them from the map
+        self.windows.get(&id).map(|window| unsafe { &mut **window })
+    }
+}
+
+pub(crate) struct WaylandWindowManagerData {
+    /// Event queue of the connection, which is only missing when the connection to the compositor failed
+    queue:                 Option<EventQueue<WaylandState>>,
+    pub(crate) state:      WaylandState,
+    /// Has the loss of the connection already been reported
+    connection_lost:       bool,
+}
+
+impl WaylandWindowManagerData {
+    pub(crate) fn new() -> Self {
+        let Some((queue, mut state)) = connection().and_then(|conn| conn.take_queue()) else {
+            log_error!(LOG_CAT, "Failed to connect to the Wayland compositor, no windows can be created");
+            return Self { queue: None, state: WaylandState::new(), connection_lost: false };
+        };
+
+        if let Some(conn) = connection() {
+            seat::update_capabilities(&mut state, conn);
+            state.seat_changed = false;
+            if let (Some(manager), Some(seat)) = (&conn.data_device_manager, &conn.seat) {
+                state.data_device = Some(manager.get_data_device(seat, &conn.qh, ()));
+            }
+        }
+
+        let mut data = Self { queue: Some(queue), state, connection_lost: false };
+        // Receive the keymap and repeat info of the keyboard that was just created
+        data.roundtrip();
+        data
+    }
+
+    pub(crate) fn register_window(&mut self, id: u32, window: *mut Window) {
+        self.state.windows.insert(id, window);
+    }
+
+    /// Wait until the compositor has processed all requests, dispatching any events it sends back
+    pub(crate) fn roundtrip(&mut self) {
+        let Some(queue) = &mut self.queue else { return };
+        if let Err(err) = queue.roundtrip(&mut self.state) {
+            self.report_connection_lost(&err.to_string());
+        }
+    }
+
+    pub(crate) fn tick(&mut self) {
+        let Some(conn) = connection() else { return };
+        let Some(queue) = &mut self.queue else { return };
+
+        if let Err(err) = dispatch(queue, &mut self.state) {
+            self.report_connection_lost(&err);
+            return;
+        }
+
+        if mem::take(&mut self.state.seat_changed) {
+            seat::update_capabilities(&mut self.state, conn);
+        }
+
+        // Requests made using window handles, the list is taken first, as listeners may make new requests
+        let pending = mem::take(&mut shared().pending);
+        for (id, event) in pending {
+            let Some(window) = self.state.window(id) else { continue };
+            let destroyed = matches!(event, PendingEvent::Destroyed);
+            window::process_pending_event(window, event);
+            if destroyed {
+                self.state.windows.remove(&id);
+            }
+        }
+
+        if mem::take(&mut self.state.scale_changed) {
+            log_debug!(LOG_CAT, "The scale of a monitor has changed");
+            for window in self.state.windows.values() {
+                // SAFETY: See `WaylandState::window`
+                window::update_scale(unsafe { &mut **window });
+            }
+        }
+
+        seat::repeat_key(&mut self.state);
+        conn.flush();
+    }
+
+    pub(crate) fn take_monitors_changed(&mut self) -> bool {
+        mem::take(&mut self.state.monitors_changed)
+    }
+
+    fn report_connection_lost(&mut self, err: &str) {
+        // Every following tick fails the same way, so only report it once
+        if !mem::replace(&mut self.connection_lost, true) {
+            log_error!(LOG_CAT, "Lost the connection to the Wayland compositor ({err})");
+        }
+    }
+}
+
+/// Dispatch all events that have arrived, without blocking when there are none
+fn dispatch(queue: &mut EventQueue<WaylandState>, state: &mut WaylandState) -> Result<(), String> {
+    queue.dispatch_pending(state).map_err(|err| err.to_string())?;
+    queue.flush().map_err(|err| err.to_string())?;
+
+    if let Some(guard) = queue.prepare_read() {
+        let mut poll_fd = libc::pollfd { fd: guard.connection_fd().as_raw_fd(), events: libc::POLLIN, revents: 0 };
+        if unsafe { libc::poll(&mut poll_fd, 1, 0) } > 0 {
+            guard.read().map_err(|err| err.to_string())?;
+        }
+    }
+    queue.dispatch_pending(state).map_err(|err| err.to_string())?;
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,273 @@
+use crate::*;
+use core::ffi::c_void;
+
+use onca_logging::log_debug;
+
+use super::{
+    backend, Backend,
+    wayland::window::{WaylandWindowData, WaylandWindowHandle},
+    x11::window::{X11WindowData, X11WindowHandle},
+    WindowManagerData,
+};
+
+/// Forward a call to the handle of the backend the window was created with
+macro_rules! dispatch {
+    ($handle:expr, $func:ident($($arg:expr),*)) => {
+        match $handle {
+            WindowHandle::X11(handle) => handle.$func($($arg),*),
+            WindowHandle::Wayland(handle) => handle.$func($($arg),*),
+        }
+    };
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum WindowHandle {
+    X11(X11WindowHandle),
+    Wayland(WaylandWindowHandle),
+}
+
+#[derive(Clone, Copy)]
+pub struct OSWindowHandle(pub(crate) WindowHandle);
+
+impl OSWindowHandle {
+    /// Get the X11 window id from the handle, or 0 if the window is not an X11 window
+    pub fn xlib_window(&self) -> u64 {
+        match &self.0 {
+            WindowHandle::X11(handle) => handle.xlib_window(),
+            WindowHandle::Wayland(_) => 0,
+        }
+    }
+
+    /// Get the Xlib `Display` the window is created on, or null if the window is not an X11 window
+    pub fn xlib_display(&self) -> *mut c_void {
+        match &self.0 {
+            WindowHandle::X11(handle) => handle.xlib_display(),
+            WindowHandle::Wayland(_) => core::ptr::null_mut(),
+        }
+    }
+
+    /// Get the id of the visual used by the window, or 0 if the window is not an X11 window
+    pub fn xlib_visual_id(&self) -> u64 {
+        match &self.0 {
+            WindowHandle::X11(handle) => handle.xlib_visual_id(),
+            WindowHandle::Wayland(_) => 0,
+        }
+    }
+
+    /// Get the raw platform handle of the window
+    pub fn raw_window_handle(&self) -> RawWindowHandle {
+        dispatch!(&self.0, raw_window_handle())
+    }
+
+    /// Get the raw platform handle of the display server the window is connected to
+    pub fn raw_display_handle(&self) -> RawDisplayHandle {
+        match &self.0 {
+            WindowHandle::X11(_) => super::x11::window::raw_display_handle(),
+            WindowHandle::Wayland(_) => super::wayland::window::raw_display_handle(),
+        }
+    }
+
+    /// Get the X11 handle of a window created by the X11 backend
+    pub(crate) fn x11(&self) -> &X11WindowHandle {
+        match &self.0 {
+            WindowHandle::X11(handle) => handle,
+            WindowHandle::Wayland(_) => unreachable!("Window was not created by the X11 backend"),
+        }
+    }
+
+    /// Get the Wayland handle of a window created by the Wayland backend
+    pub(crate) fn wayland(&self) -> &WaylandWindowHandle {
+        match &self.0 {
+            WindowHandle::Wayland(handle) => handle,
+            WindowHandle::X11(_) => unreachable!("Window was not created by the Wayland backend"),
+        }
+    }
+
+    pub(crate) fn close(&mut self) -> bool {
+        dispatch!(&mut self.0, close())
+    }
+
+    pub(crate) fn move_to(&mut self, window_id: WindowId, settings: &WindowSettings, x: i32, y: i32) {
+        dispatch!(&mut self.0, move_to(window_id, settings, x, y))
+    }
+
+    pub(crate) fn resize(&mut self, window_id: WindowId, settings: &WindowSettings, width: u16, height: u16) {
+        dispatch!(&mut self.0, resize(window_id, settings, width, height))
+    }
+
+    pub(crate) fn minimize(&mut self, window_id: WindowId) {
+        dispatch!(&mut self.0, minimize(window_id))
+    }
+
+    pub(crate) fn maximize(&mut self, window_id: WindowId) {
+        dispatch!(&mut self.0, maximize(window_id))
+    }
+
+    pub(crate) fn restore(&mut self, window_id: WindowId) {
+        dispatch!(&mut self.0, restore(window_id))
+    }
+
+    /// Set the fullscreen mode of the window, returning the mode that was applied.
+    pub(crate) fn set_fullscreen(
+        &mut self,
+        window_id: WindowId,
+        old_mode: FullscreenMode,
+        mode: FullscreenMode,
+        os_data: &mut OSWindowData,
+        settings: &mut WindowSettings,
+    ) -> FullscreenMode {
+        match (&mut self.0, os_data) {
+            (WindowHandle::X11(handle), OSWindowData::X11(os_data)) => handle.set_fullscreen(window_id, old_mode, mode, os_data, settings),
+            (WindowHandle::Wayland(handle), OSWindowData::Wayland(_)) => handle.set_fullscreen(window_id, old_mode, mode, settings),
+            _ => unreachable!("The handle and data of a window are created by the same backend"),
+        }
+    }
+
+    pub(crate) fn bring_to_front(&mut self, window_id: WindowId) {
+        dispatch!(&mut self.0, bring_to_front(window_id))
+    }
+
+    pub(crate) fn set_topmost(&mut self, window_id: WindowId, topmost: bool) {
+        dispatch!(&mut self.0, set_topmost(window_id, topmost))
+    }
+
+    pub(crate) fn enable_input(&mut self, window_id: WindowId, enable: bool) {
+        dispatch!(&mut self.0, enable_input(window_id, enable))
+    }
+
+    pub(crate) fn set_visible(&mut self, window_id: WindowId, settings: &WindowSettings, visible: bool) {
+        dispatch!(&mut self.0, set_visible(window_id, settings, visible))
+    }
+
+    pub(crate) fn set_active(&mut self, window_id: WindowId, settings: &WindowSettings, active: bool) {
+        dispatch!(&mut self.0, set_active(window_id, settings, active))
+    }
+
+    /// Only a single bit is allowed to be set with this function
+    pub(crate) fn set_flag(&self, settings: &WindowSettings, flag: Flags, set: bool) -> Result<(), u32> {
+        dispatch!(&self.0, set_flag(settings, flag, set))
+    }
+
+    pub(crate) fn set_opacity(&mut self, window_id: WindowId, settings: &mut WindowSettings, opacity: f32) {
+        dispatch!(&mut self.0, set_opacity(window_id, settings, opacity))
+    }
+
+    pub(crate) fn set_border_style(&mut self, window_id: WindowId, settings: &mut WindowSettings, border_style: BorderStyle) {
+        dispatch!(&mut self.0, set_border_style(window_id, settings, border_style))
+    }
+
+    pub(crate) fn begin_drag(&mut self) -> bool {
+        dispatch!(&mut self.0, begin_drag())
+    }
+
+    pub(crate) fn begin_sizing(&mut self, dir: ResizeDir) -> bool {
+        dispatch!(&mut self.0, begin_sizing(dir))
+    }
+
+    pub(crate) fn notify_user(&mut self, window_id: WindowId, attention: AttentionType) {
+        dispatch!(&mut self.0, notify_user(window_id, attention))
+    }
+
+    pub(crate) fn set_icons(&mut self, window_id: WindowId, icon: Option<&Icon>, small_icon: Option<&Icon>) {
+        dispatch!(&mut self.0, set_icons(window_id, icon, small_icon))
+    }
+
+    pub(crate) fn set_taskbar_progress(&mut self, window_id: WindowId, manager: &WindowManagerData, progress: TaskbarProgress) {
+        dispatch!(&mut self.0, set_taskbar_progress(window_id, manager, progress))
+    }
+
+    pub(crate) fn set_ime_allowed(&mut self, window_id: WindowId, allowed: bool) {
+        dispatch!(&mut self.0, set_ime_allowed(window_id, allowed))
+    }
+
+    pub(crate) fn set_ime_candidate_rect(&mut self, window_id: WindowId, pos: PhysicalPosition, size: PhysicalSize) {
+        dispatch!(&mut self.0, set_ime_candidate_rect(window_id, pos, size))
+    }
+
+    pub(crate) fn apply_cursor_grab(&mut self, window_id: WindowId, mode: CursorGrabMode) {
+        dispatch!(&mut self.0, apply_cursor_grab(window_id, mode))
+    }
+
+    pub(crate) fn release_cursor_grab(&mut self) {
+        dispatch!(&mut self.0, release_cursor_grab())
+    }
+
+    pub(crate) fn update_cursor(&mut self, cursor: &WindowCursor, visible: bool) {
+        dispatch!(&mut self.0, update_cursor(cursor, visible))
+    }
+
+    pub(crate) unsafe fn destroy(&mut self) {
+        dispatch!(&mut self.0, destroy())
+    }
+}
+
+pub(crate) enum OSWindowData {
+    X11(X11WindowData),
+    Wayland(WaylandWindowData),
+}
+
+impl OSWindowData {
+    /// Get the data of a window created by the X11 backend
+    pub(crate) fn x11(&mut self) -> &mut X11WindowData {
+        match self {
+            OSWindowData::X11(data) => data,
+            OSWindowData::Wayland(_) => unreachable!("Window was not created by the X11 backend"),
+        }
+    }
+
+    /// Get the data of a window created by the Wayland backend
+    pub(crate) fn wayland(&mut self) -> &mut WaylandWindowData {
+        match self {
+            OSWindowData::Wayland(data) => data,
+            OSWindowData::X11(_) => unreachable!("Window was not created by the Wayland backend"),
+        }
+    }
+
+    pub(crate) fn set_accept_files(window: &mut Window) {
+        match window.os_data {
+            OSWindowData::X11(_) => X11WindowData::set_accept_files(window),
+            OSWindowData::Wayland(_) => WaylandWindowData::set_accept_files(window),
+        }
+    }
+}
+
+/// Update the DPI of a window, after the DPI of the display server or the monitor the window is on was changed
+pub(crate) fn process_dpi_change(window: &mut Window, dpi: u16) {
+    if !window.settings().is_dpi_aware() || window.settings.dpi == dpi {
+        return;
+    }
+    log_debug!(LOG_MSG_CAT, "Window {} has changed DPI to {dpi}", window.id);
+
+    // A logical size is scaled using the DPI, so when the window doesn't scale with the DPI, its physical size needs to be kept
+    let old_size = window.settings.size();
+    window.settings.dpi = dpi;
+    if window.settings().does_scale_with_dpi() {
+        let size = window.settings.size();
+        if size != old_size {
+            window.os_handle.resize(window.id, &window.settings, size.width, size.height);
+        }
+    } else {
+        window.settings.size = old_size.into();
+    }
+
+    let scale_factor = window.settings.dpi_scale();
+    let size = window.settings.size();
+    window.send_window_event(WindowEvent::ScaleFactorChanged{ dpi, scale_factor, size });
+}
+
+pub(crate) fn raw_display_handle() -> RawDisplayHandle {
+    match backend() {
+        Some(Backend::Wayland) => super::wayland::window::raw_display_handle(),
+        _ => super::x11::window::raw_display_handle(),
+    }
+}
+
+pub(crate) fn create(
+    manager: &mut WindowManager,
+    settings: WindowSettings
+) -> Option<Box<Window>> {
+    match backend() {
+        Some(Backend::Wayland) => super::wayland::window::create(manager, settings),
+        _ => super::x11::window::create(manager, settings),
+    }
+}
\ No newline at end of file
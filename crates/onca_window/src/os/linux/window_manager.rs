@@ -0,0 +1,49 @@
+use super::{
+    backend, Backend,
+    wayland::window_manager::WaylandWindowManagerData,
+    x11::window_manager::X11WindowManagerData,
+};
+
+pub(crate) enum WindowManagerData {
+    X11(X11WindowManagerData),
+    Wayland(WaylandWindowManagerData),
+}
+
+impl WindowManagerData {
+    pub(crate) fn new() -> Self {
+        match backend() {
+            Some(Backend::Wayland) => WindowManagerData::Wayland(WaylandWindowManagerData::new()),
+            _ => WindowManagerData::X11(X11WindowManagerData::new()),
+        }
+    }
+
+    /// Get the data of the X11 backend
+    pub(crate) fn x11(&mut self) -> &mut X11WindowManagerData {
+        match self {
+            WindowManagerData::X11(data) => data,
+            WindowManagerData::Wayland(_) => unreachable!("The window manager is not using the X11 backend"),
+        }
+    }
+
+    /// Get the data of the Wayland backend
+    pub(crate) fn wayland(&mut self) -> &mut WaylandWindowManagerData {
+        match self {
+            WindowManagerData::Wayland(data) => data,
+            WindowManagerData::X11(_) => unreachable!("The window manager is not using the Wayland backend"),
+        }
+    }
+
+    pub(crate) fn tick(&mut self) {
+        match self {
+            WindowManagerData::X11(data) => data.tick(),
+            WindowManagerData::Wayland(data) => data.tick(),
+        }
+    }
+
+    pub(crate) fn take_monitors_changed(&mut self) -> bool {
+        match self {
+            WindowManagerData::X11(data) => data.take_monitors_changed(),
+            WindowManagerData::Wayland(data) => data.take_monitors_changed(),
+        }
+    }
+}
\ No newline at end of file
@@ -0,0 +1,125 @@
+use core::ffi::c_int;
+
+use onca_logging::log_error;
+use x11_dl::xlib;
+
+use crate::LOG_CAT;
+use super::{connection, window_manager::X11WindowManagerData};
+
+/// X11 clipboard state
+///
+/// The content of the clipboard is owned by the application it was copied from, so the text needs to be kept around until another application takes ownership of the clipboard.
+pub(crate) struct ClipboardData {
+    /// Hidden window used to own the clipboard and receive its content
+    pub(crate) window: xlib::Window,
+    text:              Option<String>,
+}
+
+impl ClipboardData {
+    pub(crate) fn new() -> Self {
+        let window = connection().map_or(0, |conn| unsafe {
+            let window = (conn.xlib.XCreateSimpleWindow)(conn.display, conn.root, 0, 0, 1, 1, 0, 0, 0);
+            // Large content is received in chunks, which are signaled by property changes
+            (conn.xlib.XSelectInput)(conn.display, window, xlib::PropertyChangeMask);
+            window
+        });
+        Self { window, text: None }
+    }
+}
+
+pub(crate) unsafe fn process_event(data: &mut ClipboardData, event: &mut xlib::XEvent) {
+    let Some(conn) = connection() else { return };
+
+    match event.get_type() {
+        xlib::SelectionRequest => {
+            let request = event.selection_request;
+            let mut response = xlib::XSelectionEvent {
+                type_: xlib::SelectionNotify,
+                serial: 0,
+                send_event: xlib::True,
+                display: conn.display,
+                requestor: request.requestor,
+                selection: request.selection,
+                target: request.target,
+                // A property of 0 tells the requestor that the request was refused
+                property: 0,
+                time: request.time,
+            };
+
+            // Old clients don't set a property, in which case the target should be used
+            let property = if request.property == 0 { request.target } else { request.property };
+            let text = data.text.as_ref().filter(|_| request.selection == conn.atoms.CLIPBOARD);
+            if let Some(text) = text {
+                if request.target == conn.atoms.TARGETS {
+                    let targets = [conn.atoms.TARGETS, conn.atoms.UTF8_STRING, xlib::XA_STRING];
+                    (conn.xlib.XChangeProperty)(
+                        conn.display,
+                        request.requestor,
+                        property,
+                        xlib::XA_ATOM,
+                        32,
+                        xlib::PropModeReplace,
+                        targets.as_ptr() as *const u8,
+                        targets.len() as c_int,
+                    );
+                    response.property = property;
+                } else if request.target == conn.atoms.UTF8_STRING || request.target == xlib::XA_STRING {
+                    (conn.xlib.XChangeProperty)(
+                        conn.display,
+                        request.requestor,
+                        property,
+                        request.target,
+                        8,
+                        xlib::PropModeReplace,
+                        text.as_ptr(),
+                        text.len() as c_int,
+                    );
+                    response.property = property;
+                }
+            }
+
+            (conn.xlib.XSendEvent)(conn.display, request.requestor, xlib::False, xlib::NoEventMask, &mut response as *mut _ as *mut xlib::XEvent);
+            (conn.xlib.XFlush)(conn.display);
+        },
+        xlib::SelectionClear => {
+            // Another application has taken ownership of the clipboard
+            if event.selection_clear.selection == conn.atoms.CLIPBOARD {
+                data.text = None;
+            }
+        },
+        _ => (),
+    }
+}
+
+pub(crate) fn get_text(data: &mut X11WindowManagerData) -> Option<String> {
+    let conn = connection()?;
+    let clipboard = &mut data.clipboard;
+
+    let owner = unsafe { (conn.xlib.XGetSelectionOwner)(conn.display, conn.atoms.CLIPBOARD) };
+    if owner == 0 {
+        return None;
+    }
+    if owner == clipboard.window {
+        return clipboard.text.clone();
+    }
+
+    // The content is sent by the owner of the clipboard
+    let text = conn.read_selection(clipboard.window, conn.atoms.CLIPBOARD, conn.atoms.UTF8_STRING, conn.atoms._ONCA_CLIPBOARD, xlib::CurrentTime)?;
+    Some(String::from_utf8_lossy(&text).into_owned())
+}
+
+pub(crate) fn set_text(data: &mut X11WindowManagerData, text: &str) -> bool {
+    let Some(conn) = connection() else { return false };
+    let clipboard = &mut data.clipboard;
+
+    unsafe {
+        clipboard.text = Some(text.to_string());
+        (conn.xlib.XSetSelectionOwner)(conn.display, conn.atoms.CLIPBOARD, clipboard.window, xlib::CurrentTime);
+        if (conn.xlib.XGetSelectionOwner)(conn.display, conn.atoms.CLIPBOARD) != clipboard.window {
+            log_error!(LOG_CAT, "Failed to take ownership of the clipboard");
+            clipboard.text = None;
+            return false;
+        }
+        true
+    }
+}
\ No newline at end of file
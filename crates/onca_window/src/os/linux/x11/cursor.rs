@@ -0,0 +1,114 @@
+use core::ffi::{c_char, c_uint};
+
+use onca_logging::log_warning;
+use x11_dl::xlib;
+
+use crate::{CursorShape, PhysicalSize, WindowCursor, LOG_CAT};
+use super::{connection, Connection};
+
+/// Glyphs of the X cursor font, used when a cursor is missing from the cursor theme
+const XC_X_CURSOR: c_uint = 0;
+const XC_BOTTOM_LEFT_CORNER: c_uint = 12;
+const XC_BOTTOM_RIGHT_CORNER: c_uint = 14;
+const XC_CROSSHAIR: c_uint = 34;
+const XC_FLEUR: c_uint = 52;
+const XC_HAND1: c_uint = 58;
+const XC_HAND2: c_uint = 60;
+const XC_LEFT_PTR: c_uint = 68;
+const XC_QUESTION_ARROW: c_uint = 92;
+const XC_SB_H_DOUBLE_ARROW: c_uint = 108;
+const XC_SB_V_DOUBLE_ARROW: c_uint = 116;
+const XC_WATCH: c_uint = 150;
+const XC_XTERM: c_uint = 152;
+
+pub(crate) struct X11Cursor {
+    cursor: xlib::Cursor,
+}
+
+impl X11Cursor {
+    pub(crate) fn from_rgba(pixels: &[u8], size: PhysicalSize, hotspot: (u16, u16)) -> X11Cursor {
+        let Some(conn) = connection() else { return X11Cursor { cursor: 0 } };
+        let Some(xcursor) = &conn.xcursor else {
+            log_warning!(LOG_CAT, "Failed to create a {}x{} cursor from pixels, Xcursor is not available", size.width, size.height);
+            return X11Cursor { cursor: 0 };
+        };
+
+        unsafe {
+            let image = (xcursor.XcursorImageCreate)(size.width as i32, size.height as i32);
+            if image.is_null() {
+                log_warning!(LOG_CAT, "Failed to create a {}x{} cursor from pixels", size.width, size.height);
+                return X11Cursor { cursor: 0 };
+            }
+
+            let image_ref = &mut *image;
+            image_ref.xhot = hotspot.0 as u32;
+            image_ref.yhot = hotspot.1 as u32;
+
+            // Xcursor expects premultiplied ARGB pixels
+            let dst = core::slice::from_raw_parts_mut(image_ref.pixels, size.width as usize * size.height as usize);
+            for (dst, pixel) in dst.iter_mut().zip(pixels.chunks_exact(4)) {
+                let alpha = pixel[3] as u32;
+                let premultiply = |channel: u8| (channel as u32 * alpha + 127) / 255;
+                *dst = (alpha << 24) | (premultiply(pixel[0]) << 16) | (premultiply(pixel[1]) << 8) | premultiply(pixel[2]);
+            }
+
+            let cursor = (xcursor.XcursorImageLoadCursor)(conn.display, image);
+            (xcursor.XcursorImageDestroy)(image);
+            X11Cursor { cursor }
+        }
+    }
+}
+
+impl Drop for X11Cursor {
+    fn drop(&mut self) {
+        if self.cursor == 0 {
+            return;
+        }
+        if let Some(conn) = connection() {
+            unsafe { (conn.xlib.XFreeCursor)(conn.display, self.cursor) };
+        }
+    }
+}
+
+/// Define the cursor shown over the client area of a window
+pub(crate) unsafe fn define_cursor(conn: &Connection, xwindow: xlib::Window, cursor: &WindowCursor) {
+    match cursor {
+        WindowCursor::Shape(shape) => {
+            let cursor = load_shape_cursor(conn, *shape);
+            (conn.xlib.XDefineCursor)(conn.display, xwindow, cursor);
+            // The window keeps a reference to the cursor, so it can be freed immediately
+            (conn.xlib.XFreeCursor)(conn.display, cursor);
+        },
+        WindowCursor::Custom(cursor) => {
+            (conn.xlib.XDefineCursor)(conn.display, xwindow, cursor.get_os_cursor().x11().map_or(0, |cursor| cursor.cursor));
+        },
+    }
+}
+
+/// Load a cursor from the current cursor theme, falling back to the X cursor font
+unsafe fn load_shape_cursor(conn: &Connection, shape: CursorShape) -> xlib::Cursor {
+    let (name, glyph): (&[u8], c_uint) = match shape {
+        CursorShape::Arrow => (b"default\0", XC_LEFT_PTR),
+        CursorShape::IBeam => (b"text\0", XC_XTERM),
+        CursorShape::Crosshair => (b"crosshair\0", XC_CROSSHAIR),
+        CursorShape::Hand => (b"pointer\0", XC_HAND2),
+        CursorShape::Grab => (b"grab\0", XC_HAND1),
+        CursorShape::Grabbing => (b"grabbing\0", XC_FLEUR),
+        CursorShape::ResizeHorizontal => (b"ew-resize\0", XC_SB_H_DOUBLE_ARROW),
+        CursorShape::ResizeVertical => (b"ns-resize\0", XC_SB_V_DOUBLE_ARROW),
+        CursorShape::ResizeNwSe => (b"nwse-resize\0", XC_BOTTOM_RIGHT_CORNER),
+        CursorShape::ResizeNeSw => (b"nesw-resize\0", XC_BOTTOM_LEFT_CORNER),
+        CursorShape::Move => (b"move\0", XC_FLEUR),
+        CursorShape::NotAllowed => (b"not-allowed\0", XC_X_CURSOR),
+        CursorShape::Wait => (b"wait\0", XC_WATCH),
+        CursorShape::Progress => (b"progress\0", XC_WATCH),
+        CursorShape::Help => (b"help\0", XC_QUESTION_ARROW),
+    };
+
+    let cursor = conn.xcursor.as_ref().map_or(0, |xcursor| (xcursor.XcursorLibraryLoadCursor)(conn.display, name.as_ptr() as *const c_char));
+    if cursor != 0 {
+        cursor
+    } else {
+        (conn.xlib.XCreateFontCursor)(conn.display, glyph)
+    }
+}
\ No newline at end of file
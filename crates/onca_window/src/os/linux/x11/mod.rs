@@ -0,0 +1,466 @@
+pub(crate) mod clipboard;
+pub(crate) mod cursor;
+pub(crate) mod monitor;
+pub(crate) mod window;
+pub(crate) mod window_manager;
+
+use core::{
+    ffi::{c_char, c_int, c_long, c_uchar, c_ulong, CStr},
+    mem,
+    ptr::null_mut,
+};
+use std::{
+    sync::OnceLock,
+    thread,
+    time::{Duration, Instant},
+};
+
+use onca_logging::{log_debug, log_error, log_warning};
+use x11_dl::{
+    xlib::{self, Xlib},
+    xrandr::{self, Xrandr},
+    xcursor::Xcursor,
+    // The XFixes library is loaded through a struct that x11-dl names `Xlib`
+    xfixes::Xlib as XFixes,
+};
+
+use crate::{WindowSettings, LOG_CAT};
+
+macro_rules! define_atoms {
+    ($($name:ident $(= $atom_name:literal)?),* $(,)?) => {
+        /// Atoms that are interned when the connection to the X server is opened
+        #[allow(non_snake_case)]
+        pub(crate) struct Atoms {
+            $(pub(crate) $name: xlib::Atom,)*
+        }
+
+        impl Atoms {
+            unsafe fn new(xlib: &Xlib, display: *mut xlib::Display) -> Self {
+                Self {
+                    $($name: (xlib.XInternAtom)(display, define_atoms!(@name $name $($atom_name)?).as_ptr() as *const c_char, xlib::False),)*
+                }
+            }
+        }
+    };
+    // Atoms that aren't valid identifiers, like mime types, need their name to be given explicitly
+    (@name $name:ident) => { concat!(stringify!($name), "\0") };
+    (@name $name:ident $atom_name:literal) => { concat!($atom_name, "\0") };
+}
+
+define_atoms!{
+    WM_PROTOCOLS,
+    WM_DELETE_WINDOW,
+    WM_STATE,
+    UTF8_STRING,
+    CLIPBOARD,
+    TARGETS,
+    INCR,
+    _NET_WM_NAME,
+    _NET_WM_ICON,
+    _NET_WM_STATE,
+    _NET_WM_STATE_FULLSCREEN,
+    _NET_WM_STATE_MAXIMIZED_VERT,
+    _NET_WM_STATE_MAXIMIZED_HORZ,
+    _NET_WM_STATE_HIDDEN,
+    _NET_WM_STATE_ABOVE,
+    _NET_WM_STATE_DEMANDS_ATTENTION,
+    _NET_WM_STATE_SKIP_TASKBAR,
+    _NET_WM_WINDOW_TYPE,
+    _NET_WM_WINDOW_TYPE_NORMAL,
+    _NET_WM_WINDOW_TYPE_UTILITY,
+    _NET_WM_WINDOW_OPACITY,
+    _NET_WM_MOVERESIZE,
+    _NET_ACTIVE_WINDOW,
+    _NET_WORKAREA,
+    _NET_FRAME_EXTENTS,
+    _MOTIF_WM_HINTS,
+    XdndAware,
+    XdndEnter,
+    XdndPosition,
+    XdndStatus,
+    XdndLeave,
+    XdndDrop,
+    XdndFinished,
+    XdndSelection,
+    XdndTypeList,
+    XdndActionCopy,
+    XdndActionMove,
+    XdndActionLink,
+    TEXT_URI_LIST = "text/uri-list",
+    _ONCA_CLIPBOARD,
+    _ONCA_DROP,
+    _ONCA_ENABLE_INPUT,
+}
+
+/// `_NET_WM_STATE` client message actions
+pub(crate) const NET_WM_STATE_REMOVE: c_long = 0;
+pub(crate) const NET_WM_STATE_ADD: c_long = 1;
+
+/// `_MOTIF_WM_HINTS` flags and values
+pub(crate) const MWM_HINTS_FUNCTIONS: c_ulong = 1 << 0;
+pub(crate) const MWM_HINTS_DECORATIONS: c_ulong = 1 << 1;
+pub(crate) const MWM_FUNC_RESIZE: c_ulong = 1 << 1;
+pub(crate) const MWM_FUNC_MOVE: c_ulong = 1 << 2;
+pub(crate) const MWM_FUNC_MINIMIZE: c_ulong = 1 << 3;
+pub(crate) const MWM_FUNC_MAXIMIZE: c_ulong = 1 << 4;
+pub(crate) const MWM_FUNC_CLOSE: c_ulong = 1 << 5;
+pub(crate) const MWM_DECOR_BORDER: c_ulong = 1 << 1;
+pub(crate) const MWM_DECOR_RESIZEH: c_ulong = 1 << 2;
+pub(crate) const MWM_DECOR_TITLE: c_ulong = 1 << 3;
+pub(crate) const MWM_DECOR_MENU: c_ulong = 1 << 4;
+pub(crate) const MWM_DECOR_MINIMIZE: c_ulong = 1 << 5;
+pub(crate) const MWM_DECOR_MAXIMIZE: c_ulong = 1 << 6;
+
+/// Kind of shape that defines where a window receives input, from the X shape extension
+pub(crate) const SHAPE_INPUT: c_int = 2;
+
+/// Time to wait for the owner of a selection to respond to a request for its content
+const SELECTION_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Connection to the X server, shared by all windows
+pub(crate) struct Connection {
+    pub(crate) xlib:             Xlib,
+    /// XRandR is used for monitor info, it's only missing on very old X servers
+    pub(crate) xrandr:           Option<Xrandr>,
+    /// Xcursor is used for themed and custom cursors
+    pub(crate) xcursor:          Option<Xcursor>,
+    /// XFixes is used to change the input shape of click-through windows
+    pub(crate) xfixes:           Option<XFixes>,
+    pub(crate) display:          *mut xlib::Display,
+    pub(crate) screen:           c_int,
+    pub(crate) root:             xlib::Window,
+    /// Input method used to get composed text from key presses
+    pub(crate) xim:              xlib::XIM,
+    /// Cursor without any visible pixels, used to hide the cursor
+    pub(crate) blank_cursor:     xlib::Cursor,
+    /// First event id of the XRandR extension
+    pub(crate) randr_event_base: c_int,
+    pub(crate) atoms:            Atoms,
+}
+
+// SAFETY: Xlib is initialized with `XInitThreads`, and the window manager only accesses the connection on the main thread
+unsafe impl Send for Connection {}
+unsafe impl Sync for Connection {}
+
+static CONNECTION: OnceLock<Option<Connection>> = OnceLock::new();
+
+/// Get the connection to the X server, opening it the first time this is called.
+///
+/// Returns `None` if the X server cannot be reached.
+pub(crate) fn connection() -> Option<&'static Connection> {
+    CONNECTION.get_or_init(|| unsafe { Connection::open() }).as_ref()
+}
+
+impl Connection {
+    unsafe fn open() -> Option<Connection> {
+        let xlib = match Xlib::open() {
+            Ok(xlib) => xlib,
+            Err(err) => {
+                log_error!(LOG_CAT, "Failed to load Xlib ({err})");
+                return None;
+            },
+        };
+
+        (xlib.XInitThreads)();
+        (xlib.XSetErrorHandler)(Some(error_handler));
+
+        let display = (xlib.XOpenDisplay)(null_mut());
+        if display.is_null() {
+            log_error!(LOG_CAT, "Failed to open a connection to the X server");
+            return None;
+        }
+
+        let screen = (xlib.XDefaultScreen)(display);
+        let root = (xlib.XRootWindow)(display, screen);
+        let atoms = Atoms::new(&xlib, display);
+
+        let xrandr = match Xrandr::open() {
+            Ok(xrandr) => Some(xrandr),
+            Err(err) => {
+                log_warning!(LOG_CAT, "Failed to load XRandR, monitor info will not be available ({err})");
+                None
+            },
+        };
+
+        let xcursor = match Xcursor::open() {
+            Ok(xcursor) => Some(xcursor),
+            Err(err) => {
+                log_warning!(LOG_CAT, "Failed to load Xcursor, only cursors from the X cursor font will be available ({err})");
+                None
+            },
+        };
+
+        let xfixes = match XFixes::open() {
+            Ok(xfixes) => Some(xfixes),
+            Err(err) => {
+                log_warning!(LOG_CAT, "Failed to load XFixes, click-through windows will not be available ({err})");
+                None
+            },
+        };
+
+        let mut randr_event_base = 0;
+        if let Some(xrandr) = &xrandr {
+            let mut randr_error_base = 0;
+            if (xrandr.XRRQueryExtension)(display, &mut randr_event_base, &mut randr_error_base) != 0 {
+                // Screen change events are used to notify windows about resolution changes, while output and CRTC changes are used to detect monitor changes
+                (xrandr.XRRSelectInput)(display, root, xrandr::RRScreenChangeNotifyMask | xrandr::RROutputChangeNotifyMask | xrandr::RRCrtcChangeNotifyMask);
+            }
+        }
+
+        // Use the locale of the environment to get correctly composed text
+        libc::setlocale(libc::LC_CTYPE, b"\0".as_ptr() as *const c_char);
+        (xlib.XSetLocaleModifiers)(b"\0".as_ptr() as *const c_char);
+        let xim = (xlib.XOpenIM)(display, null_mut(), null_mut(), null_mut());
+        if xim.is_null() {
+            log_warning!(LOG_CAT, "Failed to open an X input method, text input will be limited to the current keyboard layout");
+        }
+
+        let blank_cursor = {
+            let data = [0 as c_char; 8];
+            let pixmap = (xlib.XCreateBitmapFromData)(display, root, data.as_ptr(), 8, 8);
+            let mut color: xlib::XColor = mem::zeroed();
+            let cursor = (xlib.XCreatePixmapCursor)(display, pixmap, pixmap, &mut color, &mut color, 0, 0);
+            (xlib.XFreePixmap)(display, pixmap);
+            cursor
+        };
+
+        log_debug!(LOG_CAT, "Opened connection to the X server");
+        Some(Connection { xlib, xrandr, xcursor, xfixes, display, screen, root, xim, blank_cursor, randr_event_base, atoms })
+    }
+
+    /// Get the DPI of the X server, as set by the desktop environment using the `Xft.dpi` resource
+    ///
+    /// The resources are read from the root window, as the copy Xlib stores when the connection is opened won't be updated when the DPI changes.
+    pub(crate) fn dpi(&self) -> u16 {
+        self.get_property_string(self.root, xlib::XA_RESOURCE_MANAGER, xlib::XA_STRING)
+            .and_then(|resources| resources.lines()
+                .find_map(|line| line.strip_prefix("Xft.dpi:"))
+                .and_then(|dpi| dpi.trim().parse::<f32>().ok())
+            )
+            .map_or(WindowSettings::DEFAULT_DPI, |dpi| dpi.round() as u16)
+    }
+
+    /// Send a client message to a window of another client, e.g. the source of a drag
+    pub(crate) fn send_message(&self, window: xlib::Window, message_type: xlib::Atom, data: [c_long; 5]) {
+        unsafe {
+            let mut event = xlib::XClientMessageEvent {
+                type_: xlib::ClientMessage,
+                serial: 0,
+                send_event: xlib::True,
+                display: self.display,
+                window,
+                message_type,
+                format: 32,
+                data: xlib::ClientMessageData::from(data),
+            };
+            // Without an event mask, the event is sent to the client that created the window
+            (self.xlib.XSendEvent)(self.display, window, xlib::False, xlib::NoEventMask, &mut event as *mut _ as *mut xlib::XEvent);
+            (self.xlib.XFlush)(self.display);
+        }
+    }
+
+    /// Send a client message to the root window, which is how EWMH requests are made to the window manager
+    pub(crate) fn send_root_message(&self, window: xlib::Window, message_type: xlib::Atom, data: [c_long; 5]) {
+        unsafe {
+            let mut event = xlib::XClientMessageEvent {
+                type_: xlib::ClientMessage,
+                serial: 0,
+                send_event: xlib::True,
+                display: self.display,
+                window,
+                message_type,
+                format: 32,
+                data: xlib::ClientMessageData::from(data),
+            };
+            (self.xlib.XSendEvent)(
+                self.display,
+                self.root,
+                xlib::False,
+                xlib::SubstructureRedirectMask | xlib::SubstructureNotifyMask,
+                &mut event as *mut _ as *mut xlib::XEvent,
+            );
+            (self.xlib.XFlush)(self.display);
+        }
+    }
+
+    /// Add or remove up to 2 `_NET_WM_STATE` atoms from a window
+    pub(crate) fn set_wm_state(&self, window: xlib::Window, add: bool, first: xlib::Atom, second: xlib::Atom) {
+        let action = if add { NET_WM_STATE_ADD } else { NET_WM_STATE_REMOVE };
+        // The source indication of 1 tells the window manager the request comes from a regular application
+        self.send_root_message(window, self.atoms._NET_WM_STATE, [action, first as c_long, second as c_long, 1, 0]);
+    }
+
+    /// Get a window property, passing its type, format and items to `f`, which is only called when the property exists.
+    ///
+    /// Items with a format of 32-bit are returned as `c_long`s by Xlib.
+    unsafe fn get_property<T, F>(&self, window: xlib::Window, property: xlib::Atom, ty: xlib::Atom, f: F) -> Option<T> where
+        F: FnOnce(xlib::Atom, c_int, *const c_uchar, usize) -> Option<T>
+    {
+        let mut actual_type = 0;
+        let mut actual_format = 0;
+        let mut num_items = 0;
+        let mut bytes_after = 0;
+        let mut data: *mut c_uchar = null_mut();
+
+        let res = (self.xlib.XGetWindowProperty)(
+            self.display,
+            window,
+            property,
+            0,
+            c_long::MAX / 4,
+            xlib::False,
+            ty,
+            &mut actual_type,
+            &mut actual_format,
+            &mut num_items,
+            &mut bytes_after,
+            &mut data,
+        );
+        if res != xlib::Success as c_int || data.is_null() {
+            return None;
+        }
+
+        let value = f(actual_type, actual_format, data, num_items as usize);
+        (self.xlib.XFree)(data as *mut _);
+        value
+    }
+
+    /// Get a window property, where the items have a format of 32-bit
+    pub(crate) fn get_property32(&self, window: xlib::Window, property: xlib::Atom, ty: xlib::Atom) -> Option<Vec<c_ulong>> {
+        unsafe {
+            self.get_property(window, property, ty, |_, format, data, num_items| {
+                (format == 32).then(|| core::slice::from_raw_parts(data as *const c_ulong, num_items).to_vec())
+            })
+        }
+    }
+
+    /// Get a window property, where the items have a format of 8-bit
+    ///
+    /// Returns `None` if the property is missing or has a different type.
+    pub(crate) fn get_property_bytes(&self, window: xlib::Window, property: xlib::Atom, ty: xlib::Atom) -> Option<Vec<u8>> {
+        unsafe {
+            self.get_property(window, property, xlib::AnyPropertyType as xlib::Atom, |actual_type, format, data, num_items| {
+                (actual_type == ty && format == 8).then(|| core::slice::from_raw_parts(data, num_items).to_vec())
+            })
+        }
+    }
+
+    /// Get a window property containing a string, where the items have a format of 8-bit
+    ///
+    /// Returns `None` if the property is missing or has a different type.
+    pub(crate) fn get_property_string(&self, window: xlib::Window, property: xlib::Atom, ty: xlib::Atom) -> Option<String> {
+        self.get_property_bytes(window, property, ty).map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Get the type of a window property, or `None` if the window doesn't have the property
+    pub(crate) fn get_property_type(&self, window: xlib::Window, property: xlib::Atom) -> Option<xlib::Atom> {
+        unsafe { self.get_property(window, property, xlib::AnyPropertyType as xlib::Atom, |actual_type, _, _, _| (actual_type != 0).then_some(actual_type)) }
+    }
+
+    /// Get the content of a selection, converted to `target`, waiting for the owner of the selection to send it.
+    ///
+    /// The content is received in `property` on the `requestor` window, which needs to have selected `PropertyChangeMask`, as large content is sent in chunks using incremental (INCR) transfers.
+    ///
+    /// Returns `None` if the owner refused the request, didn't respond in time, or sent the content using a type other than `target`.
+    pub(crate) fn read_selection(&self, requestor: xlib::Window, selection: xlib::Atom, target: xlib::Atom, property: xlib::Atom, time: xlib::Time) -> Option<Vec<u8>> {
+        unsafe {
+            (self.xlib.XConvertSelection)(self.display, selection, target, property, requestor, time);
+            (self.xlib.XFlush)(self.display);
+
+            let event = self.wait_for_selection_event(requestor, xlib::SelectionNotify, property)?;
+            if event.selection.property == 0 {
+                return None;
+            }
+
+            if self.get_property_type(requestor, property) != Some(self.atoms.INCR) {
+                let data = self.get_property_bytes(requestor, property, target);
+                (self.xlib.XDeleteProperty)(self.display, requestor, property);
+                return data;
+            }
+
+            // Deleting the `INCR` property starts the transfer, after which the owner sends the next chunk each time the previous one is deleted, ending with an empty chunk
+            let mut data = Vec::new();
+            (self.xlib.XDeleteProperty)(self.display, requestor, property);
+            (self.xlib.XFlush)(self.display);
+            loop {
+                self.wait_for_selection_event(requestor, xlib::PropertyNotify, property)?;
+                let chunk = self.get_property_bytes(requestor, property, target);
+                (self.xlib.XDeleteProperty)(self.display, requestor, property);
+                (self.xlib.XFlush)(self.display);
+
+                match chunk {
+                    Some(chunk) if chunk.is_empty() => return Some(data),
+                    Some(chunk) => data.extend_from_slice(&chunk),
+                    None => {
+                        log_warning!(LOG_CAT, "Incremental selection transfer was aborted, the owner sent a chunk with an unexpected type");
+                        return None;
+                    },
+                }
+            }
+        }
+    }
+
+    /// Wait for a `SelectionNotify` for `requestor`, or a `PropertyNotify` for a new value of `property` on `requestor`, without touching any other queued events
+    unsafe fn wait_for_selection_event(&self, requestor: xlib::Window, event_type: c_int, property: xlib::Atom) -> Option<xlib::XEvent> {
+        let mut filter = SelectionEventFilter { requestor, event_type, property };
+        let start = Instant::now();
+        let mut event: xlib::XEvent = mem::zeroed();
+        loop {
+            if (self.xlib.XCheckIfEvent)(self.display, &mut event, Some(is_selection_event), &mut filter as *mut _ as xlib::XPointer) != 0 {
+                return Some(event);
+            }
+            if start.elapsed() > SELECTION_TIMEOUT {
+                log_error!(LOG_CAT, "Failed to get the content of a selection, the owner of the selection did not respond");
+                return None;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Get the position of the client area of a window on the root window
+    pub(crate) fn client_position(&self, window: xlib::Window) -> (i32, i32) {
+        unsafe {
+            let mut x = 0;
+            let mut y = 0;
+            let mut child = 0;
+            (self.xlib.XTranslateCoordinates)(self.display, window, self.root, 0, 0, &mut x, &mut y, &mut child);
+            (x, y)
+        }
+    }
+}
+
+/// Event to wait for while reading a selection
+struct SelectionEventFilter {
+    requestor:  xlib::Window,
+    event_type: c_int,
+    property:   xlib::Atom,
+}
+
+unsafe extern "C" fn is_selection_event(_display: *mut xlib::Display, event: *mut xlib::XEvent, arg: xlib::XPointer) -> xlib::Bool {
+    let filter = &*(arg as *const SelectionEventFilter);
+    let event = &*event;
+    if event.any.window != filter.requestor || event.get_type() != filter.event_type {
+        return xlib::False;
+    }
+
+    let matches = match filter.event_type {
+        xlib::SelectionNotify => true,
+        // Deleting the property also sends a `PropertyNotify`, which should be ignored
+        xlib::PropertyNotify => event.property.atom == filter.property && event.property.state == xlib::PropertyNewValue,
+        _ => false,
+    };
+    matches as xlib::Bool
+}
+
+/// Xlib exits the process on any error by default, so log them instead, as errors are expected for e.g. requests on a window that has already been destroyed by the X server
+unsafe extern "C" fn error_handler(display: *mut xlib::Display, event: *mut xlib::XErrorEvent) -> c_int {
+    let Some(conn) = CONNECTION.get().and_then(|conn| conn.as_ref()) else {
+        return 0;
+    };
+
+    let event = &*event;
+    let mut buf = [0 as c_char; 256];
+    (conn.xlib.XGetErrorText)(display, event.error_code as c_int, buf.as_mut_ptr(), buf.len() as c_int);
+    log_warning!(LOG_CAT, "X11 error: {} (request: {}, resource: {:X})", CStr::from_ptr(buf.as_ptr()).to_string_lossy(), event.request_code, event.resourceid);
+    0
+}
\ No newline at end of file
@@ -0,0 +1,297 @@
+use core::ffi::{c_int, c_ulong};
+use std::{
+    collections::BTreeSet,
+    sync::Mutex,
+};
+
+use onca_logging::log_error;
+use x11_dl::{xlib, xrandr};
+
+use crate::{Monitor, LOG_CAT, MonitorRect, MonitorMode, PhysicalSize, MonitorModeOrdWrapper};
+use super::{connection, Connection, window::X11WindowHandle};
+use crate::os::linux::monitor::{MonitorHandle, MonitorId};
+
+/// Display modes of the monitors that were changed by an exclusive fullscreen window, so they can be restored
+static ORIGINAL_MODES: Mutex<Vec<(xrandr::RROutput, xrandr::RRMode)>> = Mutex::new(Vec::new());
+
+/// Screen resources of the X server, which are freed when dropped
+struct ScreenResources<'a> {
+    conn:      &'a Connection,
+    xrandr:    &'a xrandr::Xrandr,
+    resources: *mut xrandr::XRRScreenResources,
+}
+
+impl<'a> ScreenResources<'a> {
+    fn get(conn: &'a Connection) -> Option<Self> {
+        let xrandr = conn.xrandr.as_ref()?;
+        let resources = unsafe { (xrandr.XRRGetScreenResourcesCurrent)(conn.display, conn.root) };
+        if resources.is_null() {
+            log_error!(LOG_CAT, "Failed to retrieve XRandR screen resources");
+            return None;
+        }
+        Some(Self { conn, xrandr, resources })
+    }
+
+    fn outputs(&self) -> &[xrandr::RROutput] {
+        unsafe { slice_or_empty((*self.resources).outputs, (*self.resources).noutput) }
+    }
+
+    fn modes(&self) -> &[xrandr::XRRModeInfo] {
+        unsafe { slice_or_empty((*self.resources).modes, (*self.resources).nmode) }
+    }
+
+    fn mode_info(&self, mode: xrandr::RRMode) -> Option<&xrandr::XRRModeInfo> {
+        self.modes().iter().find(|info| info.id == mode)
+    }
+
+    /// Get the output info and the info of the CRTC driving it, outputs that are disconnected or turned off return `None`
+    unsafe fn output_info(&self, output: xrandr::RROutput) -> Option<(*mut xrandr::XRROutputInfo, *mut xrandr::XRRCrtcInfo)> {
+        let output_info = (self.xrandr.XRRGetOutputInfo)(self.conn.display, self.resources, output);
+        if output_info.is_null() {
+            return None;
+        }
+        if (*output_info).connection != xrandr::RR_Connected as u16 || (*output_info).crtc == 0 {
+            (self.xrandr.XRRFreeOutputInfo)(output_info);
+            return None;
+        }
+
+        let crtc_info = (self.xrandr.XRRGetCrtcInfo)(self.conn.display, self.resources, (*output_info).crtc);
+        if crtc_info.is_null() {
+            (self.xrandr.XRRFreeOutputInfo)(output_info);
+            return None;
+        }
+        Some((output_info, crtc_info))
+    }
+}
+
+impl Drop for ScreenResources<'_> {
+    fn drop(&mut self) {
+        unsafe { (self.xrandr.XRRFreeScreenResources)(self.resources) };
+    }
+}
+
+unsafe fn slice_or_empty<'a, T>(ptr: *const T, len: c_int) -> &'a [T] {
+    if ptr.is_null() || len <= 0 {
+        &[]
+    } else {
+        core::slice::from_raw_parts(ptr, len as usize)
+    }
+}
+
+fn refresh_rate(mode: &xrandr::XRRModeInfo) -> f32 {
+    let mut v_total = mode.vTotal as f32;
+    if mode.modeFlags & xrandr::RR_DoubleScan as c_ulong != 0 {
+        v_total *= 2.0;
+    }
+    if mode.modeFlags & xrandr::RR_Interlace as c_ulong != 0 {
+        v_total /= 2.0;
+    }
+
+    if mode.hTotal == 0 || v_total == 0.0 {
+        0.0
+    } else {
+        mode.dotClock as f32 / (mode.hTotal as f32 * v_total)
+    }
+}
+
+unsafe fn get_monitor(conn: &Connection, resources: &ScreenResources, output: xrandr::RROutput, primary_output: xrandr::RROutput) -> Option<Monitor> {
+    let (output_info, crtc_info) = resources.output_info(output)?;
+    let output_ref = &*output_info;
+    let crtc = &*crtc_info;
+
+    let mon_rect = MonitorRect { x: crtc.x, y: crtc.y, width: crtc.width as u16, height: crtc.height as u16 };
+    let work_rect = get_work_rect(conn, mon_rect);
+    let cur_refresh_rate = resources.mode_info(crtc.mode).map_or(0.0, refresh_rate);
+    let bits_per_pixel = (conn.xlib.XDefaultDepth)(conn.display, conn.screen) as u8;
+
+    // All available modes
+    let mut monitor_btree = BTreeSet::new();
+    for mode in slice_or_empty(output_ref.modes, output_ref.nmode) {
+        if let Some(info) = resources.mode_info(*mode) {
+            monitor_btree.insert(MonitorModeOrdWrapper(MonitorMode {
+                bits_per_pixel,
+                refesh_rate: refresh_rate(info).round() as u32,
+                size: PhysicalSize::new(info.width as u16, info.height as u16),
+            }));
+        }
+    }
+    let monitor_modes = Vec::from_iter(monitor_btree.into_iter().map(|val| val.0));
+
+    // The output name is the name of the connector, e.g. 'DP-1'
+    let mut dev_name = [0u8; 32];
+    let output_name = slice_or_empty(output_ref.name as *const u8, output_ref.nameLen);
+    let len = output_name.len().min(dev_name.len() - 1);
+    dev_name[..len].copy_from_slice(&output_name[..len]);
+
+    let mut name = [0u8; 128];
+    name[..len].copy_from_slice(&output_name[..len]);
+
+    (resources.xrandr.XRRFreeCrtcInfo)(crtc_info);
+    (resources.xrandr.XRRFreeOutputInfo)(output_info);
+
+    Some(Monitor {
+        os_handle: MonitorHandle(MonitorId::X11(output)),
+        mon_rect,
+        work_rect,
+        refresh_rate: cur_refresh_rate,
+        // Scaling is set for the entire X server, not per monitor
+        dpi: conn.dpi(),
+        primary: output == primary_output,
+        dev_name,
+        name,
+        modes: monitor_modes,
+    })
+}
+
+/// The work area is only available for the entire desktop, so clip it to the monitor
+unsafe fn get_work_rect(conn: &Connection, mon_rect: MonitorRect) -> MonitorRect {
+    let Some(work_area) = conn.get_property32(conn.root, conn.atoms._NET_WORKAREA, xlib::XA_CARDINAL) else {
+        return mon_rect;
+    };
+    if work_area.len() < 4 {
+        return mon_rect;
+    }
+
+    let left = (work_area[0] as i32).max(mon_rect.x);
+    let top = (work_area[1] as i32).max(mon_rect.y);
+    let right = (work_area[0] as i32 + work_area[2] as i32).min(mon_rect.x + mon_rect.width as i32);
+    let bottom = (work_area[1] as i32 + work_area[3] as i32).min(mon_rect.y + mon_rect.height as i32);
+    if right <= left || bottom <= top {
+        return mon_rect;
+    }
+    MonitorRect { x: left, y: top, width: (right - left) as u16, height: (bottom - top) as u16 }
+}
+
+fn find_monitor<F: FnMut(&Monitor) -> bool>(mut pred: F) -> Option<Monitor> {
+    enumerate_monitors().into_iter().find(|monitor| pred(monitor))
+}
+
+fn overlap(a: &MonitorRect, b: &MonitorRect) -> i64 {
+    let width = (a.x + a.width as i32).min(b.x + b.width as i32) - a.x.max(b.x);
+    let height = (a.y + a.height as i32).min(b.y + b.height as i32) - a.y.max(b.y);
+    if width <= 0 || height <= 0 {
+        0
+    } else {
+        width as i64 * height as i64
+    }
+}
+
+pub(crate) fn enumerate_monitors() -> Vec<Monitor> {
+    let Some(conn) = connection() else { return Vec::new() };
+    let Some(resources) = ScreenResources::get(conn) else { return Vec::new() };
+
+    unsafe {
+        let primary_output = (resources.xrandr.XRRGetOutputPrimary)(conn.display, conn.root);
+        resources.outputs().iter()
+            .filter_map(|output| get_monitor(conn, &resources, *output, primary_output))
+            .collect()
+    }
+}
+
+pub(crate) fn primary_monitor() -> Option<Monitor> {
+    // When no output is marked as primary, use the first output like most X11 applications do
+    let mut monitors = enumerate_monitors();
+    match monitors.iter().position(|monitor| monitor.primary) {
+        Some(idx) => Some(monitors.swap_remove(idx)),
+        None => monitors.into_iter().next(),
+    }
+}
+
+pub(crate) fn get_monitor_from_handle(handle: &X11WindowHandle) -> Option<Monitor> {
+    let conn = connection()?;
+    let rect = unsafe {
+        let mut attribs: xlib::XWindowAttributes = core::mem::zeroed();
+        if (conn.xlib.XGetWindowAttributes)(conn.display, handle.xlib_window() as xlib::Window, &mut attribs) == 0 {
+            return None;
+        }
+        let (x, y) = conn.client_position(handle.xlib_window() as xlib::Window);
+        MonitorRect { x, y, width: attribs.width as u16, height: attribs.height as u16 }
+    };
+    get_monitor_from_largest_overlap(rect)
+}
+
+pub(crate) fn get_monitor_at(x: i32, y: i32) -> Option<Monitor> {
+    find_monitor(|monitor| {
+        let rect = monitor.monitor_rect();
+        x >= rect.x && x < rect.x + rect.width as i32 && y >= rect.y && y < rect.y + rect.height as i32
+    })
+}
+
+pub(crate) fn get_monitor_from_largest_overlap(rect: MonitorRect) -> Option<Monitor> {
+    enumerate_monitors().into_iter()
+        .map(|monitor| (overlap(monitor.monitor_rect(), &rect), monitor))
+        .filter(|(overlap, _)| *overlap > 0)
+        .max_by_key(|(overlap, _)| *overlap)
+        .map(|(_, monitor)| monitor)
+}
+
+/// Change the display mode of a monitor, or restore its original display mode when `mode` is `None`.
+///
+/// Unlike on Windows, the X server does not restore the display mode when the process exits, so the mode needs to be restored before exiting.
+pub(crate) fn set_display_mode(monitor: MonitorHandle, mode: Option<MonitorMode>) -> bool {
+    let Some(conn) = connection() else { return false };
+    let Some(resources) = ScreenResources::get(conn) else { return false };
+    let MonitorId::X11(output) = monitor.0 else { return false };
+
+    unsafe {
+        let Some((output_info, crtc_info)) = resources.output_info(output) else {
+            log_error!(LOG_CAT, "Failed to retrieve monitor info to change its display mode");
+            return false;
+        };
+        let crtc = (*output_info).crtc;
+        let cur_mode = (*crtc_info).mode;
+
+        let mut original_modes = ORIGINAL_MODES.lock().unwrap_or_else(|err| err.into_inner());
+        let new_mode = match mode {
+            Some(mode) => {
+                let output_modes = slice_or_empty((*output_info).modes, (*output_info).nmode);
+                // A refresh rate of 0 means that the monitor can choose the value
+                let new_mode = output_modes.iter()
+                    .filter_map(|mode| resources.mode_info(*mode))
+                    .filter(|info| info.width as u16 == mode.size.width && info.height as u16 == mode.size.height)
+                    .filter(|info| mode.refesh_rate == 0 || refresh_rate(info).round() as u32 == mode.refesh_rate)
+                    .max_by(|a, b| refresh_rate(a).total_cmp(&refresh_rate(b)))
+                    .map(|info| info.id);
+                if new_mode.is_some() && !original_modes.iter().any(|(stored, _)| *stored == output) {
+                    original_modes.push((output, cur_mode));
+                }
+                new_mode
+            },
+            None => original_modes.iter().position(|(stored, _)| *stored == output).map(|idx| original_modes.swap_remove(idx).1),
+        };
+
+        let res = match new_mode {
+            Some(new_mode) if new_mode == cur_mode => true,
+            Some(new_mode) => {
+                let crtc_ref = &*crtc_info;
+                let res = (resources.xrandr.XRRSetCrtcConfig)(
+                    conn.display,
+                    resources.resources,
+                    crtc,
+                    xlib::CurrentTime,
+                    crtc_ref.x,
+                    crtc_ref.y,
+                    new_mode,
+                    crtc_ref.rotation,
+                    crtc_ref.outputs,
+                    crtc_ref.noutput,
+                );
+                res == xlib::Success as c_int
+            },
+            // Nothing to restore
+            None => mode.is_none(),
+        };
+
+        (resources.xrandr.XRRFreeCrtcInfo)(crtc_info);
+        (resources.xrandr.XRRFreeOutputInfo)(output_info);
+
+        if !res {
+            log_error!(LOG_CAT, "Failed to change the display mode of monitor with output '{output}'");
+        }
+        res
+    }
+}
+
+pub(crate) fn get_monitor_rect(monitor: MonitorHandle) -> Option<MonitorRect> {
+    find_monitor(|mon| mon.os_handle == monitor).map(|monitor| monitor.mon_rect)
+}
\ No newline at end of file
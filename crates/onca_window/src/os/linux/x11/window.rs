@@ -0,0 +1,1309 @@
+use crate::*;
+use core::{
+    ffi::{c_char, c_int, c_long, c_uint, c_ulong, c_void},
+    mem,
+    ptr::{null, null_mut, NonNull},
+};
+use std::{
+    cell::Cell,
+    path::PathBuf,
+};
+use onca_common::{
+    sync::Mutex,
+    event_listener::EventListenerArray,
+};
+use onca_logging::{log_debug, log_error, log_warning};
+use x11_dl::xlib;
+
+use super::{
+    self as x11,
+    cursor::define_cursor,
+    connection, Connection,
+};
+use crate::os::linux::{
+    uri_list::parse_uri_list,
+    window::{OSWindowData, OSWindowHandle, WindowHandle},
+    MonitorHandle, WindowManagerData,
+};
+
+/// Events every window listens to
+const EVENT_MASK: c_long = xlib::StructureNotifyMask |
+    xlib::PropertyChangeMask |
+    xlib::FocusChangeMask |
+    xlib::EnterWindowMask |
+    xlib::LeaveWindowMask |
+    xlib::KeyPressMask |
+    xlib::KeyReleaseMask |
+    xlib::ButtonPressMask |
+    xlib::ButtonReleaseMask |
+    xlib::PointerMotionMask;
+
+/// X input method attribute names and styles
+const XN_INPUT_STYLE: &[u8] = b"inputStyle\0";
+const XN_CLIENT_WINDOW: &[u8] = b"clientWindow\0";
+const XN_FOCUS_WINDOW: &[u8] = b"focusWindow\0";
+const XN_PREEDIT_ATTRIBUTES: &[u8] = b"preeditAttributes\0";
+const XN_SPOT_LOCATION: &[u8] = b"spotLocation\0";
+const XIM_PREEDIT_POSITION: c_ulong = 0x0004;
+const XIM_PREEDIT_NOTHING: c_ulong = 0x0008;
+const XIM_STATUS_NOTHING: c_ulong = 0x0400;
+
+/// `WM_STATE` and `WM_HINTS` initial state values, of a normal and a minimized window
+const NORMAL_STATE: c_int = 1;
+const ICONIC_STATE: c_int = 3;
+
+/// `_NET_WM_MOVERESIZE` direction to move the window
+const NET_WM_MOVERESIZE_MOVE: c_long = 8;
+
+/// Version of the XDND protocol that is supported
+const XDND_VERSION: c_long = 5;
+
+#[derive(Clone, Copy)]
+pub(crate) struct X11WindowHandle {
+    window:      xlib::Window,
+    /// Input context used to get composed text from key presses
+    xic:         xlib::XIC,
+    ime_allowed: bool,
+}
+
+impl X11WindowHandle {
+    /// Get the X11 window id from the handle
+    pub(crate) fn xlib_window(&self) -> u64 {
+        self.window as u64
+    }
+
+    /// Get the Xlib `Display` the window is created on
+    pub(crate) fn xlib_display(&self) -> *mut c_void {
+        connection().map_or(null_mut(), |conn| conn.display as *mut c_void)
+    }
+
+    /// Get the id of the visual used by the window
+    pub(crate) fn xlib_visual_id(&self) -> u64 {
+        connection().map_or(0, |conn| unsafe {
+            (conn.xlib.XVisualIDFromVisual)((conn.xlib.XDefaultVisual)(conn.display, conn.screen)) as u64
+        })
+    }
+
+    /// Get the raw platform handle of the window
+    pub(crate) fn raw_window_handle(&self) -> RawWindowHandle {
+        RawWindowHandle::Xlib {
+            window: self.xlib_window(),
+            visual_id: self.xlib_visual_id(),
+        }
+    }
+
+    pub(crate) fn close(&mut self) -> bool {
+        let Some(conn) = connection() else { return false };
+        // Closing goes through the same path as the close button, so listeners are still able to cancel it
+        let data = [conn.atoms.WM_DELETE_WINDOW as c_long, xlib::CurrentTime as c_long, 0, 0, 0];
+        send_message_to_self(conn, self.window, conn.atoms.WM_PROTOCOLS, data)
+    }
+
+    pub(crate) fn move_to(&mut self, window_id: WindowId, settings: &WindowSettings, x: i32, y: i32) {
+        let Some(conn) = connection() else { return };
+        unsafe {
+            // The window uses static gravity, so the position is the position of the client area
+            let res = (conn.xlib.XMoveWindow)(conn.display, self.window, x, y);
+            (conn.xlib.XFlush)(conn.display);
+            if res == 0 {
+                let old_pos = settings.position();
+                log_warning!(LOG_CAT, "Failed to move window '{window_id}' to ({x}, {y}). Window is still located at ({}, {}).", old_pos.x, old_pos.y);
+            }
+        }
+    }
+
+    pub(crate) fn resize(&mut self, window_id: WindowId, settings: &WindowSettings, width: u16, height: u16) {
+        let Some(conn) = connection() else { return };
+        unsafe {
+            let res = (conn.xlib.XResizeWindow)(conn.display, self.window, width.max(1) as c_uint, height.max(1) as c_uint);
+            (conn.xlib.XFlush)(conn.display);
+            if res == 0 {
+                let size = settings.size();
+                log_warning!(LOG_CAT, "Failed to resize window '{window_id}' to {width}x{height}. Window is still has size {}x{}.", size.width, size.height);
+            }
+        }
+    }
+
+    pub(crate) fn minimize(&mut self, window_id: WindowId) {
+        let Some(conn) = connection() else { return };
+        unsafe {
+            let res = (conn.xlib.XIconifyWindow)(conn.display, self.window, conn.screen);
+            (conn.xlib.XFlush)(conn.display);
+            if res == 0 {
+                log_warning!(LOG_CAT, "Failed to minimize window '{window_id}'.");
+            }
+        }
+    }
+
+    pub(crate) fn maximize(&mut self, _window_id: WindowId) {
+        let Some(conn) = connection() else { return };
+        conn.set_wm_state(self.window, true, conn.atoms._NET_WM_STATE_MAXIMIZED_VERT, conn.atoms._NET_WM_STATE_MAXIMIZED_HORZ);
+    }
+
+    pub(crate) fn restore(&mut self, _window_id: WindowId) {
+        let Some(conn) = connection() else { return };
+        conn.set_wm_state(self.window, false, conn.atoms._NET_WM_STATE_MAXIMIZED_VERT, conn.atoms._NET_WM_STATE_MAXIMIZED_HORZ);
+        // Mapping a minimized window restores it
+        unsafe {
+            (conn.xlib.XMapWindow)(conn.display, self.window);
+            (conn.xlib.XFlush)(conn.display);
+        }
+    }
+
+    /// Set the fullscreen mode of the window, returning the mode that was applied.
+    pub(crate) fn set_fullscreen(
+        &mut self,
+        window_id: WindowId,
+        old_mode: FullscreenMode,
+        mode: FullscreenMode,
+        os_data: &mut X11WindowData,
+        settings: &mut WindowSettings,
+    ) -> FullscreenMode {
+        let Some(conn) = connection() else { return old_mode };
+
+        // Restore the original display mode first, so it won't be left behind when switching to a different mode
+        if let Some(monitor) = os_data.exclusive_monitor.take() {
+            super::monitor::set_display_mode(monitor, None);
+        }
+
+        let mode = match mode {
+            FullscreenMode::Exclusive(monitor_mode) => {
+                let monitor = super::monitor::get_monitor_from_handle(self).map(|monitor| monitor.os_handle);
+                match monitor {
+                    Some(monitor) if super::monitor::set_display_mode(monitor, Some(monitor_mode)) => {
+                        os_data.exclusive_monitor = Some(monitor);
+                        mode
+                    },
+                    _ => {
+                        log_warning!(LOG_CAT, "Failed to switch window '{window_id}' to exclusive fullscreen, falling back to borderless fullscreen");
+                        FullscreenMode::Borderless
+                    },
+                }
+            },
+            mode => mode,
+        };
+
+        let fullscreen = mode != FullscreenMode::Windowed;
+        settings.flags.set(Flags::Fullscreen, fullscreen);
+        unsafe {
+            update_motif_hints(conn, self.window, settings);
+            update_size_hints(conn, self.window, settings, None);
+        }
+
+        // The window manager stores the windowed placement, and restores it when the window leaves fullscreen
+        conn.set_wm_state(self.window, fullscreen, conn.atoms._NET_WM_STATE_FULLSCREEN, 0);
+        mode
+    }
+
+    pub(crate) fn bring_to_front(&mut self, _window_id: WindowId) {
+        let Some(conn) = connection() else { return };
+        unsafe {
+            (conn.xlib.XRaiseWindow)(conn.display, self.window);
+            (conn.xlib.XFlush)(conn.display);
+        }
+    }
+
+    pub(crate) fn set_topmost(&mut self, _window_id: WindowId, topmost: bool) {
+        let Some(conn) = connection() else { return };
+        conn.set_wm_state(self.window, topmost, conn.atoms._NET_WM_STATE_ABOVE, 0);
+    }
+
+    pub(crate) fn enable_input(&mut self, window_id: WindowId, enable: bool) {
+        let Some(conn) = connection() else { return };
+        unsafe { set_wm_hints(conn, self.window, |hints| {
+            hints.flags |= xlib::InputHint;
+            hints.input = enable as c_int;
+        }) };
+
+        // X11 has no concept of disabled windows, so notify the window the same way as for other state changes, and ignore its input while it's disabled
+        if !send_message_to_self(conn, self.window, conn.atoms._ONCA_ENABLE_INPUT, [enable as c_long, 0, 0, 0, 0]) {
+            let enable_disable = if enable { "enable" } else { "disable" };
+            log_warning!(LOG_CAT, "Failed to {enable_disable} input for window '{}'.", window_id);
+        }
+    }
+
+    pub(crate) fn set_visible(&mut self, _window_id: WindowId, _settings: &WindowSettings, visible: bool) {
+        let Some(conn) = connection() else { return };
+        unsafe {
+            if visible {
+                (conn.xlib.XMapWindow)(conn.display, self.window);
+            } else {
+                (conn.xlib.XUnmapWindow)(conn.display, self.window);
+            }
+            (conn.xlib.XFlush)(conn.display);
+        }
+    }
+
+    pub(crate) fn set_active(&mut self, _window_id: WindowId, _settings: &WindowSettings, active: bool) {
+        // Only the window manager can deactivate a window, by activating another one
+        if active {
+            let Some(conn) = connection() else { return };
+            conn.send_root_message(self.window, conn.atoms._NET_ACTIVE_WINDOW, [1, xlib::CurrentTime as c_long, 0, 0, 0]);
+        }
+    }
+
+    /// Only a single bit is allowed to be set with this function
+    pub(crate) fn set_flag(
+        &self,
+        settings: &WindowSettings,
+        flag: Flags,
+        _set: bool,
+    ) -> Result<(), u32> {
+        let Some(conn) = connection() else { return Err(0) };
+        unsafe {
+            match flag {
+                Flags::MinimizeButton | Flags::MaximizeButton => update_motif_hints(conn, self.window, settings),
+                Flags::Resizable => {
+                    update_motif_hints(conn, self.window, settings);
+                    update_size_hints(conn, self.window, settings, None);
+                },
+                Flags::ToolWindow => update_window_type(conn, self.window, settings),
+                Flags::ClickThrough => update_input_shape(conn, self.window, settings),
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn set_opacity(&mut self, _window_id: WindowId, settings: &mut WindowSettings, opacity: f32) {
+        let Some(conn) = connection() else { return };
+        settings.opacity = opacity;
+        unsafe { update_opacity(conn, self.window, settings) };
+    }
+
+    pub(crate) fn set_border_style(&mut self, _window_id: WindowId, settings: &mut WindowSettings, border_style: BorderStyle) {
+        let Some(conn) = connection() else { return };
+        settings.border = border_style;
+        // The margins will be updated once the window manager has changed the frame, see `_NET_FRAME_EXTENTS`
+        unsafe { update_motif_hints(conn, self.window, settings) };
+    }
+
+    pub(crate) fn begin_drag(&mut self) -> bool {
+        self.begin_move_resize(NET_WM_MOVERESIZE_MOVE)
+    }
+
+    pub(crate) fn begin_sizing(&mut self, dir: ResizeDir) -> bool {
+        // `_NET_WM_MOVERESIZE` directions, starting at the top-left, going clockwise
+        let direction = match dir {
+            ResizeDir::North => 1,
+            ResizeDir::NorthWest => 2,
+            ResizeDir::West => 3,
+            ResizeDir::SouthWest => 4,
+            ResizeDir::South => 5,
+            ResizeDir::SouthEast => 6,
+            ResizeDir::East => 7,
+            ResizeDir::NorthEast => 0,
+        };
+        self.begin_move_resize(direction)
+    }
+
+    fn begin_move_resize(&mut self, direction: c_long) -> bool {
+        let Some(conn) = connection() else { return false };
+        unsafe {
+            let mut root = 0;
+            let mut child = 0;
+            let mut root_x = 0;
+            let mut root_y = 0;
+            let mut win_x = 0;
+            let mut win_y = 0;
+            let mut mask = 0;
+            if (conn.xlib.XQueryPointer)(conn.display, self.window, &mut root, &mut child, &mut root_x, &mut root_y, &mut win_x, &mut win_y, &mut mask) == 0 {
+                log_error!(LOG_CAT, "Failed getting the mouse's position before moving or sizing the window.");
+                return false;
+            }
+
+            // The window manager needs to be able to grab the pointer to move or resize the window
+            (conn.xlib.XUngrabPointer)(conn.display, xlib::CurrentTime);
+            conn.send_root_message(self.window, conn.atoms._NET_WM_MOVERESIZE, [root_x as c_long, root_y as c_long, direction, xlib::Button1 as c_long, 1]);
+            true
+        }
+    }
+
+    pub(crate) fn notify_user(&mut self, _window_id: WindowId, attention: AttentionType) {
+        let Some(conn) = connection() else { return };
+        let (demand_attention, urgent) = match attention {
+            AttentionType::None => (false, false),
+            AttentionType::Informative => (true, false),
+            AttentionType::Critical => (true, true),
+            // Window managers decide how a window demanding attention is shown, so it can't be flashed a number of times
+            AttentionType::Flash(_) => (true, false),
+        };
+
+        conn.set_wm_state(self.window, demand_attention, conn.atoms._NET_WM_STATE_DEMANDS_ATTENTION, 0);
+        // The urgency hint is shown more prominently by most window managers, and stays until it's explicitly removed
+        unsafe { set_wm_hints(conn, self.window, |hints| {
+            if urgent {
+                hints.flags |= xlib::XUrgencyHint;
+            } else {
+                hints.flags &= !xlib::XUrgencyHint;
+            }
+        }) };
+    }
+
+    pub(crate) fn set_icons(&mut self, _window_id: WindowId, icon: Option<&Icon>, small_icon: Option<&Icon>) {
+        let Some(conn) = connection() else { return };
+        unsafe { set_icons(conn, self.window, icon, small_icon) };
+    }
+
+    pub(crate) fn set_taskbar_progress(&mut self, _window_id: WindowId, _manager: &WindowManagerData, _progress: TaskbarProgress) {
+        // X11 has no standard way of showing progress on a taskbar
+        // TODO(jel): Support the Unity launcher API over D-Bus, which is also supported by KDE and some GNOME extensions
+    }
+
+    pub(crate) fn set_ime_allowed(&mut self, window_id: WindowId, allowed: bool) {
+        let Some(conn) = connection() else { return };
+        self.ime_allowed = allowed;
+        if self.xic.is_null() {
+            if allowed {
+                log_warning!(LOG_CAT, "Failed to enable the IME for window '{window_id}', no input method is available");
+            }
+            return;
+        }
+
+        unsafe {
+            if allowed {
+                (conn.xlib.XSetICFocus)(self.xic);
+            } else {
+                (conn.xlib.XUnsetICFocus)(self.xic);
+            }
+        }
+    }
+
+    pub(crate) fn set_ime_candidate_rect(&mut self, window_id: WindowId, pos: PhysicalPosition, size: PhysicalSize) {
+        let Some(conn) = connection() else { return };
+        if self.xic.is_null() {
+            return;
+        }
+
+        unsafe {
+            // The spot is the location of the cursor on the baseline of the text, so put it below the text, to not cover it
+            let mut spot = xlib::XPoint { x: pos.x as i16, y: (pos.y + size.height as i32) as i16 };
+            let attributes = (conn.xlib.XVaCreateNestedList)(0, XN_SPOT_LOCATION.as_ptr() as *const c_char, &mut spot as *mut xlib::XPoint, null::<c_void>());
+            if attributes.is_null() {
+                log_warning!(LOG_CAT, "Failed to set the IME candidate window location for window '{window_id}'");
+                return;
+            }
+            (conn.xlib.XSetICValues)(self.xic, XN_PREEDIT_ATTRIBUTES.as_ptr() as *const c_char, attributes, null::<c_void>());
+            (conn.xlib.XFree)(attributes);
+        }
+    }
+
+    pub(crate) fn apply_cursor_grab(&mut self, window_id: WindowId, mode: CursorGrabMode) {
+        let Some(conn) = connection() else { return };
+        unsafe {
+            match mode {
+                CursorGrabMode::None => {
+                    (conn.xlib.XUngrabPointer)(conn.display, xlib::CurrentTime);
+                },
+                CursorGrabMode::Confined | CursorGrabMode::Locked => {
+                    let res = (conn.xlib.XGrabPointer)(
+                        conn.display,
+                        self.window,
+                        xlib::True,
+                        (xlib::ButtonPressMask | xlib::ButtonReleaseMask | xlib::PointerMotionMask) as c_uint,
+                        xlib::GrabModeAsync,
+                        xlib::GrabModeAsync,
+                        self.window,
+                        0,
+                        xlib::CurrentTime,
+                    );
+                    if res != xlib::GrabSuccess {
+                        log_warning!(LOG_CAT, "Failed to set the cursor grab of window '{window_id}' to {mode:?} (err: {res})");
+                    } else if mode == CursorGrabMode::Locked {
+                        self.warp_cursor_to_center(conn);
+                    }
+                },
+            }
+            (conn.xlib.XFlush)(conn.display);
+        }
+    }
+
+    pub(crate) fn release_cursor_grab(&mut self) {
+        let Some(conn) = connection() else { return };
+        unsafe {
+            (conn.xlib.XUngrabPointer)(conn.display, xlib::CurrentTime);
+            (conn.xlib.XFlush)(conn.display);
+        }
+    }
+
+    /// X11 can't lock the cursor in place, so a locked cursor is moved back to the center each time it moves
+    unsafe fn warp_cursor_to_center(&self, conn: &Connection) {
+        let mut attribs: xlib::XWindowAttributes = mem::zeroed();
+        if (conn.xlib.XGetWindowAttributes)(conn.display, self.window, &mut attribs) != 0 {
+            (conn.xlib.XWarpPointer)(conn.display, 0, self.window, 0, 0, 0, 0, attribs.width / 2, attribs.height / 2);
+        }
+    }
+
+    pub(crate) fn update_cursor(&mut self, cursor: &WindowCursor, visible: bool) {
+        let Some(conn) = connection() else { return };
+        // The cursor is set per window, so it will only be hidden while it's over the client area
+        unsafe {
+            if visible {
+                define_cursor(conn, self.window, cursor);
+            } else {
+                (conn.xlib.XDefineCursor)(conn.display, self.window, conn.blank_cursor);
+            }
+            (conn.xlib.XFlush)(conn.display);
+        }
+    }
+
+    pub(crate) unsafe fn destroy(&mut self) {
+        // The window is only destroyed by the X server when processing the next events, so make sure to only destroy it once
+        if self.window == 0 {
+            return;
+        }
+        let Some(conn) = connection() else { return };
+
+        if !self.xic.is_null() {
+            (conn.xlib.XDestroyIC)(self.xic);
+            self.xic = null_mut();
+        }
+        (conn.xlib.XDestroyWindow)(conn.display, self.window);
+        (conn.xlib.XFlush)(conn.display);
+        self.window = 0;
+    }
+}
+
+/// Drag using the XDND protocol, which is currently over the window
+struct XdndDrag {
+    /// Window the drag originates from
+    source:       xlib::Window,
+    version:      c_long,
+    /// Can the dragged data be received as a list of files
+    has_uri_list: bool,
+    /// Have the files been requested, this can only be done once the first position is received, as the request needs its timestamp
+    requested:    bool,
+    /// Files being dragged, `None` when the drag doesn't contain any files
+    files:        Option<Vec<PathBuf>>,
+    effect:       DropEffect,
+    pos:          PhysicalPosition,
+}
+
+#[derive(Default)]
+pub(crate) struct X11WindowData {
+    /// Monitor of which the display mode was changed for exclusive fullscreen
+    exclusive_monitor: Option<MonitorHandle>,
+    /// Drag that is currently over the window
+    xdnd:              Option<XdndDrag>,
+}
+
+impl X11WindowData {
+    pub(crate) fn set_accept_files(window: &mut Window) {
+        let Some(conn) = connection() else { return };
+        let xwindow = window.os_handle.x11().window;
+
+        unsafe {
+            if window.settings().does_accept_files() {
+                // Drag sources only send XDND messages to windows that advertise the version of the protocol they support
+                let version = XDND_VERSION as xlib::Atom;
+                (conn.xlib.XChangeProperty)(
+                    conn.display,
+                    xwindow,
+                    conn.atoms.XdndAware,
+                    xlib::XA_ATOM,
+                    32,
+                    xlib::PropModeReplace,
+                    &version as *const xlib::Atom as *const u8,
+                    1,
+                );
+            } else {
+                (conn.xlib.XDeleteProperty)(conn.display, xwindow, conn.atoms.XdndAware);
+                window.os_data.x11().xdnd = None;
+            }
+            (conn.xlib.XFlush)(conn.display);
+        }
+    }
+}
+
+/// Process an event for a window, this is called by the window manager for each event of the window.
+pub(crate) unsafe fn process_event(window: &mut Window, event: &mut xlib::XEvent) {
+    let Some(conn) = connection() else { return };
+    // The handle is cleared when the window is destroyed, while events may still be queued for it
+    let xwindow = event.any.window;
+
+    match event.get_type() {
+        xlib::ConfigureNotify => {
+            let configure = event.configure;
+            let size = PhysicalSize::new(configure.width as u16, configure.height as u16);
+            // The position in the event is relative to the parent, which is the frame added by the window manager, so get the position on the root window instead
+            let (x, y) = conn.client_position(xwindow);
+            let pos = PhysicalPosition::new(x, y);
+
+            if size != window.settings.size() {
+                window.settings.size = size.into();
+                log_debug!(LOG_MSG_CAT, "Window {} has been resized to {}x{}", window.id, size.width, size.height);
+                window.send_window_event(WindowEvent::Resized(size));
+            }
+            if pos != window.settings.position() {
+                window.settings.position = Position::Client(pos.into());
+                log_debug!(LOG_MSG_CAT, "Window {} was moved to ({}, {})", window.id, pos.x, pos.y);
+                window.send_window_event(WindowEvent::Moved(pos));
+            }
+            reapply_cursor_grab(window);
+        },
+        xlib::PropertyNotify => {
+            let atom = event.property.atom;
+            if atom == conn.atoms._NET_WM_STATE {
+                update_state_from_wm(conn, window, xwindow);
+            } else if atom == conn.atoms._NET_FRAME_EXTENTS {
+                update_margins(conn, window, xwindow);
+            }
+        },
+        xlib::MapNotify => {
+            log_debug!(LOG_MSG_CAT, "received MapNotify for window {}", window.id);
+            if !window.settings.is_visible() {
+                window.settings.flags.set(Flags::Visible, true);
+                window.send_window_event(WindowEvent::Visible);
+            }
+        },
+        xlib::UnmapNotify => {
+            log_debug!(LOG_MSG_CAT, "received UnmapNotify for window {}", window.id);
+            // Minimized windows are unmapped, but are not considered to be hidden
+            let iconic = conn.get_property32(xwindow, conn.atoms.WM_STATE, conn.atoms.WM_STATE)
+                .is_some_and(|state| state.first() == Some(&(ICONIC_STATE as c_ulong)));
+            if !iconic && window.settings.is_visible() {
+                window.settings.flags.set(Flags::Visible, false);
+                window.send_window_event(WindowEvent::Hidden);
+            }
+        },
+        xlib::FocusIn | xlib::FocusOut => {
+            let focus = event.focus_change;
+            // Focus changes caused by grabs, e.g. while the window is being moved, don't change which window is active
+            if focus.mode == xlib::NotifyGrab || focus.mode == xlib::NotifyUngrab || focus.detail == xlib::NotifyPointer {
+                return;
+            }
+
+            let active = focus.type_ == xlib::FocusIn;
+            log_debug!(LOG_MSG_CAT, "received {} for window {}", if active { "FocusIn" } else { "FocusOut" }, window.id);
+            window.settings.flags.set(Flags::Active, active);
+
+            let X11WindowHandle { xic, ime_allowed, .. } = *window.os_handle.x11();
+            if !xic.is_null() && ime_allowed {
+                if active {
+                    (conn.xlib.XSetICFocus)(xic);
+                } else {
+                    (conn.xlib.XUnsetICFocus)(xic);
+                }
+            }
+
+            // Exclusive fullscreen should not keep the display mode changed while the user is working in another window
+            if let (FullscreenMode::Exclusive(mode), Some(monitor)) = (window.fullscreen_mode, window.os_data.x11().exclusive_monitor) {
+                if active {
+                    super::monitor::set_display_mode(monitor, Some(mode));
+                } else {
+                    super::monitor::set_display_mode(monitor, None);
+                    window.os_handle.minimize(window.id);
+                }
+            }
+
+            // The cursor grab is global, so it needs to be released when another window becomes active, and restored when the window is activated again
+            if window.cursor_grab != CursorGrabMode::None {
+                if active {
+                    window.os_handle.apply_cursor_grab(window.id, window.cursor_grab);
+                } else {
+                    window.os_handle.release_cursor_grab();
+                }
+            }
+
+            if active {
+                let minimized = window.settings.is_minimized();
+                window.send_window_event(WindowEvent::Focused(minimized));
+                window.send_window_event(WindowEvent::InputFocused);
+            } else {
+                window.send_window_event(WindowEvent::Unfocused);
+                window.send_window_event(WindowEvent::InputUnfocused);
+            }
+        },
+        xlib::EnterNotify => {
+            // The pointer is ungrabbed by the window manager when it's done moving or resizing the window
+            window.settings.flags.set(Flags::DraggingWindow | Flags::SizingWindow, false);
+
+            if !window.settings().is_mouse_in_window() {
+                log_debug!(LOG_MSG_CAT, "mouse has entered window {}", window.id);
+                window.settings.flags.set(Flags::MouseInWindow, true);
+                window.send_window_event(WindowEvent::MouseEnter);
+            }
+        },
+        xlib::LeaveNotify => {
+            // Grabs cause the pointer to temporarily leave the window, without it actually moving
+            if event.crossing.mode != xlib::NotifyNormal {
+                return;
+            }
+
+            log_debug!(LOG_MSG_CAT, "mouse has left window {}", window.id);
+            window.settings.flags.set(Flags::MouseInWindow, false);
+            window.send_window_event(WindowEvent::MouseLeave);
+        },
+        xlib::ButtonRelease => {
+            window.settings.flags.set(Flags::DraggingWindow | Flags::SizingWindow, false);
+        },
+        xlib::MotionNotify => {
+            if window.cursor_grab == CursorGrabMode::Locked && window.settings.is_active() {
+                let size = window.settings.size();
+                let motion = event.motion;
+                if motion.x != size.width as i32 / 2 || motion.y != size.height as i32 / 2 {
+                    window.os_handle.x11().warp_cursor_to_center(conn);
+                }
+            }
+        },
+        xlib::KeyPress => {
+            if !window.settings.does_accept_input() {
+                return;
+            }
+
+            if let Some(text) = lookup_text(conn, window.os_handle.x11().xic, &mut event.key) {
+                let text = text.chars().filter(|ch| !ch.is_control()).collect::<String>();
+                if !text.is_empty() {
+                    window.send_window_event(WindowEvent::Text(&text));
+                }
+            }
+        },
+        xlib::ClientMessage => {
+            let message = event.client_message;
+            if message.message_type == conn.atoms.WM_PROTOCOLS && message.data.get_long(0) as xlib::Atom == conn.atoms.WM_DELETE_WINDOW {
+                log_debug!(LOG_MSG_CAT, "received WM_DELETE_WINDOW for window {}", window.id);
+                // The close button can still be used while the window is closing
+                if window.is_closing() {
+                    return;
+                }
+
+                let close = Cell::new(true);
+                let cancel = || close.set(false);
+                window.send_window_event(WindowEvent::CloseRequested{ cancel: &cancel });
+                if close.get() {
+                    window.is_closing = true;
+                    window.send_window_event(WindowEvent::Closed);
+                }
+            } else if message.message_type == conn.atoms._ONCA_ENABLE_INPUT {
+                let enabled = message.data.get_long(0) != 0;
+                log_debug!(LOG_MSG_CAT, "received input {} for window {}", if enabled { "enable" } else { "disable" }, window.id);
+                window.settings.flags.set(Flags::AcceptsInput, enabled);
+                window.send_window_event(if enabled { WindowEvent::EnabledInput } else { WindowEvent::DisabledInput });
+            } else if window.settings().does_accept_files() {
+                process_xdnd_message(conn, window, xwindow, &message);
+            }
+        },
+        xlib::DestroyNotify => {
+            log_debug!(LOG_MSG_CAT, "received DestroyNotify for window {}", window.id);
+            if let Some(monitor) = window.os_data.x11().exclusive_monitor.take() {
+                super::monitor::set_display_mode(monitor, None);
+            }
+            window.notify_destroyed();
+        },
+        _ => (),
+    }
+}
+
+/// Process a client message that is part of the XDND protocol, as the target of the drag
+unsafe fn process_xdnd_message(conn: &Connection, window: &mut Window, xwindow: xlib::Window, message: &xlib::XClientMessageEvent) {
+    let source = message.data.get_long(0) as xlib::Window;
+    // Messages from any source other than the current drag are stale
+    let is_current_source = window.os_data.x11().xdnd.as_ref().is_some_and(|drag| drag.source == source);
+
+    if message.message_type == conn.atoms.XdndEnter {
+        let flags = message.data.get_long(1);
+        let version = flags >> 24;
+        if version > XDND_VERSION {
+            return;
+        }
+
+        // Only up to 3 types fit in the message, any more need to be read from the source
+        let types = if flags & 1 != 0 {
+            conn.get_property32(source, conn.atoms.XdndTypeList, xlib::XA_ATOM).unwrap_or_default()
+        } else {
+            (2..5).map(|idx| message.data.get_long(idx) as xlib::Atom).filter(|&ty| ty != 0).collect()
+        };
+
+        // A new drag can only start when the previous one has ended, so the previous source has gone away without finishing it
+        if window.os_data.x11().xdnd.take().is_some_and(|drag| drag.files.is_some()) {
+            window.send_window_event(WindowEvent::FilesHoverEnded);
+        }
+
+        window.os_data.x11().xdnd = Some(XdndDrag {
+            source,
+            version,
+            has_uri_list: types.contains(&conn.atoms.TEXT_URI_LIST),
+            requested: false,
+            files: None,
+            effect: window.drop_effect,
+            pos: PhysicalPosition::new(0, 0),
+        });
+    } else if message.message_type == conn.atoms.XdndPosition && is_current_source {
+        let Some(mut drag) = window.os_data.x11().xdnd.take() else { return };
+
+        let root_pos = message.data.get_long(2);
+        let (client_x, client_y) = conn.client_position(xwindow);
+        drag.pos = PhysicalPosition::new(((root_pos >> 16) & 0xFFFF) as i32 - client_x, (root_pos & 0xFFFF) as i32 - client_y);
+
+        let drop_effect = Cell::new(drag.effect);
+        let set_effect = |new_effect| drop_effect.set(new_effect);
+        if !drag.requested {
+            drag.requested = true;
+            if drag.has_uri_list {
+                // Version 0 doesn't send a timestamp
+                let time = if drag.version >= 1 { message.data.get_long(3) as xlib::Time } else { xlib::CurrentTime };
+                drag.files = conn.read_selection(xwindow, conn.atoms.XdndSelection, conn.atoms.TEXT_URI_LIST, conn.atoms._ONCA_DROP, time)
+                    .map(|data| parse_uri_list(&data))
+                    .filter(|files| !files.is_empty());
+            }
+
+            if let Some(files) = &drag.files {
+                log_debug!(LOG_CAT, "Started hovering {} files over window {} at location ({}, {})", files.len(), window.id(), drag.pos.x, drag.pos.y);
+                window.send_window_event(WindowEvent::FilesHoverStarted{ files, pos: drag.pos, set_effect: &set_effect });
+            }
+        } else if drag.files.is_some() {
+            window.send_window_event(WindowEvent::FilesHoverMoved{ pos: drag.pos, set_effect: &set_effect });
+        }
+        drag.effect = drop_effect.get();
+
+        let accept = drag.files.is_some() && drag.effect != DropEffect::None;
+        let action = if accept { xdnd_action(conn, drag.effect) } else { 0 };
+        // The 2nd flag asks for position messages to keep being sent, as the effect can be changed anywhere in the window
+        conn.send_message(source, conn.atoms.XdndStatus, [xwindow as c_long, accept as c_long | 2, 0, 0, action as c_long]);
+
+        window.os_data.x11().xdnd = Some(drag);
+    } else if message.message_type == conn.atoms.XdndLeave && is_current_source {
+        if window.os_data.x11().xdnd.take().is_some_and(|drag| drag.files.is_some()) {
+            log_debug!(LOG_CAT, "Stopped hovering files over window {}", window.id());
+            window.send_window_event(WindowEvent::FilesHoverEnded);
+        }
+    } else if message.message_type == conn.atoms.XdndDrop && is_current_source {
+        let Some(drag) = window.os_data.x11().xdnd.take() else { return };
+
+        let accepted = drag.files.is_some() && drag.effect != DropEffect::None;
+        match drag.files {
+            Some(files) if accepted => {
+                log_debug!(LOG_CAT, "Dropped {} files over window {} at location ({}, {})", files.len(), window.id(), drag.pos.x, drag.pos.y);
+                window.send_window_event(WindowEvent::FilesDropped(files, drag.pos));
+            },
+            Some(_) => {
+                log_debug!(LOG_CAT, "Rejected files dropped over window {}", window.id());
+                window.send_window_event(WindowEvent::FilesHoverEnded);
+            },
+            None => (),
+        }
+
+        // Sources before version 2 don't expect a response, and only version 5 added if the drop was accepted
+        if drag.version >= 2 {
+            let action = if accepted { xdnd_action(conn, drag.effect) } else { 0 };
+            let accepted = if drag.version >= 5 { accepted as c_long } else { 0 };
+            conn.send_message(source, conn.atoms.XdndFinished, [xwindow as c_long, accepted, action as c_long, 0, 0]);
+        }
+    }
+}
+
+/// Get the XDND action matching a drop effect
+fn xdnd_action(conn: &Connection, effect: DropEffect) -> xlib::Atom {
+    match effect {
+        DropEffect::None => 0,
+        DropEffect::Copy => conn.atoms.XdndActionCopy,
+        DropEffect::Move => conn.atoms.XdndActionMove,
+        DropEffect::Link => conn.atoms.XdndActionLink,
+    }
+}
+
+pub(crate) fn raw_display_handle() -> RawDisplayHandle {
+    let conn = connection();
+    RawDisplayHandle::Xlib {
+        display: conn.and_then(|conn| NonNull::new(conn.display as *mut c_void)),
+        screen: conn.map_or(0, |conn| conn.screen),
+    }
+}
+
+pub(crate) fn create(
+    manager: &mut WindowManager,
+    mut settings: WindowSettings
+) -> Option<Box<Window>> {
+    let Some(conn) = connection() else {
+        log_error!(LOG_CAT, "Failed to create a window, there is no connection to the X server");
+        return None;
+    };
+
+    unsafe {
+        settings.validate_dpi();
+        // The X server doesn't scale windows, so DPI aware windows use the DPI set by the desktop environment
+        if settings.is_dpi_aware() {
+            settings.dpi = conn.dpi();
+        }
+        // The size of the frame is only known once the window manager has added it, see `_NET_FRAME_EXTENTS`
+        settings.margins = Margins { top: 0, left: 0, bottom: 0, right: 0 };
+
+        let pos = match settings.position {
+            Position::Default => None,
+            _ => Some(settings.position()),
+        };
+        let PhysicalSize { width, height } = settings.size();
+
+        let mut attribs: xlib::XSetWindowAttributes = mem::zeroed();
+        attribs.background_pixel = (conn.xlib.XBlackPixel)(conn.display, conn.screen);
+        attribs.event_mask = EVENT_MASK;
+
+        let xwindow = (conn.xlib.XCreateWindow)(
+            conn.display,
+            conn.root,
+            pos.map_or(0, |pos| pos.x),
+            pos.map_or(0, |pos| pos.y),
+            width.max(1) as c_uint,
+            height.max(1) as c_uint,
+            0,
+            (conn.xlib.XDefaultDepth)(conn.display, conn.screen),
+            xlib::InputOutput as c_uint,
+            (conn.xlib.XDefaultVisual)(conn.display, conn.screen),
+            xlib::CWBackPixel | xlib::CWEventMask,
+            &mut attribs,
+        );
+        if xwindow == 0 {
+            log_error!(LOG_CAT, "Failed to create a window");
+            return None;
+        }
+
+        let mut protocols = [conn.atoms.WM_DELETE_WINDOW];
+        (conn.xlib.XSetWMProtocols)(conn.display, xwindow, protocols.as_mut_ptr(), protocols.len() as c_int);
+
+        if let Some(title) = &settings.title {
+            set_title(conn, xwindow, title);
+        }
+        set_icons(conn, xwindow, settings.icon(), settings.small_icon());
+        update_size_hints(conn, xwindow, &settings, pos);
+        update_motif_hints(conn, xwindow, &settings);
+        update_window_type(conn, xwindow, &settings);
+        set_initial_wm_state(conn, xwindow, &settings);
+        if settings.opacity() < 1.0 {
+            update_opacity(conn, xwindow, &settings);
+        }
+        if settings.is_click_through() {
+            update_input_shape(conn, xwindow, &settings);
+        }
+
+        let accepts_input = settings.does_accept_input();
+        let minimized = settings.is_minimized();
+        set_wm_hints(conn, xwindow, |hints| {
+            hints.flags |= xlib::InputHint | xlib::StateHint;
+            hints.input = accepts_input as c_int;
+            hints.initial_state = if minimized { ICONIC_STATE } else { NORMAL_STATE };
+        });
+
+        let xic = create_input_context(conn, xwindow);
+
+        let fullscreen_mode = if settings.is_fullscreen() { FullscreenMode::Borderless } else { FullscreenMode::Windowed };
+        let visible = settings.is_visible();
+        let accepts_files = settings.does_accept_files();
+        // The window is only visible once it's mapped, which will send a `MapNotify`
+        settings.flags.set(Flags::Visible, false);
+
+        let window = Window {
+            os_handle: OSWindowHandle(WindowHandle::X11(X11WindowHandle { window: xwindow, xic, ime_allowed: true })),
+            os_data: OSWindowData::X11(X11WindowData::default()),
+            id: WindowId(0),
+            settings,
+            manager: manager as *mut WindowManager,
+            listeners: Mutex::new(EventListenerArray::new()),
+            is_closing: false,
+            is_destroyed: false,
+            cursor_grab: CursorGrabMode::None,
+            cursor_visible: true,
+            cursor: WindowCursor::Shape(CursorShape::Arrow),
+            drop_effect: DropEffect::Copy,
+            fullscreen_mode,
+            taskbar_progress: TaskbarProgress::None,
+        };
+        let mut window_ptr = Box::new(window);
+        manager.get_os_data().x11().register_window(xwindow, &mut *window_ptr);
+
+        if accepts_files {
+            X11WindowData::set_accept_files(&mut window_ptr);
+        }
+
+        if visible {
+            (conn.xlib.XMapWindow)(conn.display, xwindow);
+        }
+        (conn.xlib.XFlush)(conn.display);
+
+        let title_str = window_ptr.settings().title().map_or("", |string| &string);
+        log_debug!(
+            LOG_CAT,
+            "Created new window '{title_str}' at ({}, {}) with size {width}x{height}",
+            pos.map_or(0, |pos| pos.x),
+            pos.map_or(0, |pos| pos.y)
+        );
+
+        Some(window_ptr)
+    }
+}
+
+unsafe fn set_title(conn: &Connection, xwindow: xlib::Window, title: &str) {
+    // `WM_NAME` only supports latin-1, so also set the UTF-8 `_NET_WM_NAME`, which is preferred by window managers
+    let mut latin_title = title.chars().map(|ch| if (ch as u32) < 0x100 { ch as u8 } else { b'?' }).collect::<Vec<_>>();
+    latin_title.push(0);
+    (conn.xlib.XStoreName)(conn.display, xwindow, latin_title.as_ptr() as *const c_char);
+    (conn.xlib.XChangeProperty)(
+        conn.display,
+        xwindow,
+        conn.atoms._NET_WM_NAME,
+        conn.atoms.UTF8_STRING,
+        8,
+        xlib::PropModeReplace,
+        title.as_ptr(),
+        title.len() as c_int,
+    );
+}
+
+unsafe fn set_icons(conn: &Connection, xwindow: xlib::Window, icon: Option<&Icon>, small_icon: Option<&Icon>) {
+    // Both icons are stored in the same property, the window manager picks the size it needs
+    let data = [icon, small_icon].into_iter()
+        .flatten()
+        .map(|icon| icon.get_os_icon())
+        .filter(|icon| icon.is_valid())
+        .flat_map(|icon| icon.net_wm_icon_data())
+        .collect::<Vec<_>>();
+    if data.is_empty() {
+        // Without the property, the window manager uses its default icon
+        (conn.xlib.XDeleteProperty)(conn.display, xwindow, conn.atoms._NET_WM_ICON);
+        (conn.xlib.XFlush)(conn.display);
+        return;
+    }
+
+    (conn.xlib.XChangeProperty)(
+        conn.display,
+        xwindow,
+        conn.atoms._NET_WM_ICON,
+        xlib::XA_CARDINAL,
+        32,
+        xlib::PropModeReplace,
+        data.as_ptr() as *const u8,
+        data.len() as c_int,
+    );
+    (conn.xlib.XFlush)(conn.display);
+}
+
+/// Update the `WM_HINTS` of a window
+unsafe fn set_wm_hints<F: FnOnce(&mut xlib::XWMHints)>(conn: &Connection, xwindow: xlib::Window, f: F) {
+    let mut hints = (conn.xlib.XGetWMHints)(conn.display, xwindow);
+    if hints.is_null() {
+        hints = (conn.xlib.XAllocWMHints)();
+        if hints.is_null() {
+            return;
+        }
+    }
+
+    f(&mut *hints);
+    (conn.xlib.XSetWMHints)(conn.display, xwindow, hints);
+    (conn.xlib.XFree)(hints as *mut c_void);
+    (conn.xlib.XFlush)(conn.display);
+}
+
+/// Update the size hints of the window, the position is only used when the window is first mapped
+unsafe fn update_size_hints(conn: &Connection, xwindow: xlib::Window, settings: &WindowSettings, pos: Option<PhysicalPosition>) {
+    let hints = (conn.xlib.XAllocSizeHints)();
+    if hints.is_null() {
+        return;
+    }
+    let hints_ref = &mut *hints;
+
+    // Static gravity makes the position of the window the position of its client area, instead of its frame
+    hints_ref.flags = xlib::PWinGravity;
+    hints_ref.win_gravity = xlib::StaticGravity;
+
+    if let Some(pos) = pos {
+        hints_ref.flags |= xlib::PPosition | xlib::USPosition;
+        hints_ref.x = pos.x;
+        hints_ref.y = pos.y;
+    }
+
+    // Window managers may refuse to make a window fullscreen when its size is limited
+    if !settings.is_fullscreen() {
+        let dpi_scale = settings.dpi_scale();
+        let (min_size, max_size) = if settings.is_resizable() {
+            (settings.min_size.map(|s| s.to_physical(dpi_scale)), settings.max_size.map(|s| s.to_physical(dpi_scale)))
+        } else {
+            (Some(settings.size()), Some(settings.size()))
+        };
+
+        if let Some(min_size) = min_size {
+            hints_ref.flags |= xlib::PMinSize;
+            hints_ref.min_width = min_size.width as c_int;
+            hints_ref.min_height = min_size.height as c_int;
+        }
+        if let Some(max_size) = max_size {
+            hints_ref.flags |= xlib::PMaxSize;
+            hints_ref.max_width = max_size.width as c_int;
+            hints_ref.max_height = max_size.height as c_int;
+        }
+    }
+
+    (conn.xlib.XSetWMNormalHints)(conn.display, xwindow, hints);
+    (conn.xlib.XFree)(hints as *mut c_void);
+    (conn.xlib.XFlush)(conn.display);
+}
+
+/// Update the decorations and functions the window manager provides for the window, using the Motif hints, which most window managers support
+unsafe fn update_motif_hints(conn: &Connection, xwindow: xlib::Window, settings: &WindowSettings) {
+    let flags = settings.flags();
+    let fullscreen = flags.contains(Flags::Fullscreen);
+
+    // A fullscreen window never has a border, but keeps its border style for when it returns to windowed mode
+    let border_style = if fullscreen { BorderStyle::Borderless } else { settings.border_style() };
+    let mut decorations = match border_style {
+        BorderStyle::Borderless => 0,
+        BorderStyle::ThinBorder => x11::MWM_DECOR_BORDER,
+        BorderStyle::Caption => x11::MWM_DECOR_BORDER | x11::MWM_DECOR_TITLE,
+        BorderStyle::FullCaption => x11::MWM_DECOR_BORDER | x11::MWM_DECOR_TITLE | x11::MWM_DECOR_MENU,
+    };
+    let mut functions = x11::MWM_FUNC_MOVE | x11::MWM_FUNC_CLOSE;
+
+    if flags.contains(Flags::Resizable) && !fullscreen {
+        if border_style == BorderStyle::Borderless {
+            log_warning!(LOG_CAT, "Cannot use Resizable with Borderless window style");
+        } else {
+            functions |= x11::MWM_FUNC_RESIZE;
+            decorations |= x11::MWM_DECOR_RESIZEH;
+        }
+    }
+    // Like on Windows, the buttons are only shown for windows with a full caption
+    if flags.contains(Flags::MinimizeButton) {
+        functions |= x11::MWM_FUNC_MINIMIZE;
+        if border_style == BorderStyle::FullCaption {
+            decorations |= x11::MWM_DECOR_MINIMIZE;
+        }
+    }
+    if flags.contains(Flags::MaximizeButton) {
+        functions |= x11::MWM_FUNC_MAXIMIZE;
+        if border_style == BorderStyle::FullCaption {
+            decorations |= x11::MWM_DECOR_MAXIMIZE;
+        }
+    }
+
+    // flags, functions, decorations, input mode, status
+    let hints: [c_ulong; 5] = [x11::MWM_HINTS_FUNCTIONS | x11::MWM_HINTS_DECORATIONS, functions, decorations, 0, 0];
+    (conn.xlib.XChangeProperty)(
+        conn.display,
+        xwindow,
+        conn.atoms._MOTIF_WM_HINTS,
+        conn.atoms._MOTIF_WM_HINTS,
+        32,
+        xlib::PropModeReplace,
+        hints.as_ptr() as *const u8,
+        hints.len() as c_int,
+    );
+    (conn.xlib.XFlush)(conn.display);
+}
+
+unsafe fn update_window_type(conn: &Connection, xwindow: xlib::Window, settings: &WindowSettings) {
+    let tool_window = settings.is_tool_window();
+    let window_type = if tool_window { conn.atoms._NET_WM_WINDOW_TYPE_UTILITY } else { conn.atoms._NET_WM_WINDOW_TYPE_NORMAL };
+    (conn.xlib.XChangeProperty)(
+        conn.display,
+        xwindow,
+        conn.atoms._NET_WM_WINDOW_TYPE,
+        xlib::XA_ATOM,
+        32,
+        xlib::PropModeReplace,
+        &window_type as *const xlib::Atom as *const u8,
+        1,
+    );
+    // Tool windows don't appear on the taskbar
+    conn.set_wm_state(xwindow, tool_window, conn.atoms._NET_WM_STATE_SKIP_TASKBAR, 0);
+}
+
+/// Update the opacity of the window, which is applied by the compositor, so it has no effect when no compositor is running
+unsafe fn update_opacity(conn: &Connection, xwindow: xlib::Window, settings: &WindowSettings) {
+    if settings.opacity() >= 1.0 {
+        (conn.xlib.XDeleteProperty)(conn.display, xwindow, conn.atoms._NET_WM_WINDOW_OPACITY);
+    } else {
+        // The opacity is stored as a fraction of the maximum 32-bit value, but format 32 properties are passed as longs
+        let opacity = (settings.opacity() as f64 * u32::MAX as f64).round() as c_ulong;
+        (conn.xlib.XChangeProperty)(
+            conn.display,
+            xwindow,
+            conn.atoms._NET_WM_WINDOW_OPACITY,
+            xlib::XA_CARDINAL,
+            32,
+            xlib::PropModeReplace,
+            &opacity as *const c_ulong as *const u8,
+            1,
+        );
+    }
+    (conn.xlib.XFlush)(conn.display);
+}
+
+/// Update the input shape of the window, an empty input shape lets all mouse input pass through to whatever is below the window
+unsafe fn update_input_shape(conn: &Connection, xwindow: xlib::Window, settings: &WindowSettings) {
+    let Some(xfixes) = &conn.xfixes else {
+        log_warning!(LOG_CAT, "Cannot change if a window is click-through, XFixes is not available");
+        return;
+    };
+
+    if settings.is_click_through() {
+        let region = (xfixes.XFixesCreateRegion)(conn.display, null_mut(), 0);
+        (xfixes.XFixesSetWindowShapeRegion)(conn.display, xwindow, x11::SHAPE_INPUT, 0, 0, region);
+        (xfixes.XFixesDestroyRegion)(conn.display, region);
+    } else {
+        // Without a region, the input shape is reset to cover the whole window
+        (xfixes.XFixesSetWindowShapeRegion)(conn.display, xwindow, x11::SHAPE_INPUT, 0, 0, 0);
+    }
+    (conn.xlib.XFlush)(conn.display);
+}
+
+/// Set the state of a window that has not been mapped yet, after which the window manager owns the property and it can only be changed using messages
+unsafe fn set_initial_wm_state(conn: &Connection, xwindow: xlib::Window, settings: &WindowSettings) {
+    let mut state = Vec::new();
+    if settings.is_maximized() {
+        state.push(conn.atoms._NET_WM_STATE_MAXIMIZED_VERT);
+        state.push(conn.atoms._NET_WM_STATE_MAXIMIZED_HORZ);
+    }
+    if settings.is_fullscreen() {
+        state.push(conn.atoms._NET_WM_STATE_FULLSCREEN);
+    }
+    if settings.is_top_most() {
+        state.push(conn.atoms._NET_WM_STATE_ABOVE);
+    }
+    if settings.is_tool_window() {
+        state.push(conn.atoms._NET_WM_STATE_SKIP_TASKBAR);
+    }
+    if state.is_empty() {
+        return;
+    }
+
+    (conn.xlib.XChangeProperty)(
+        conn.display,
+        xwindow,
+        conn.atoms._NET_WM_STATE,
+        xlib::XA_ATOM,
+        32,
+        xlib::PropModeReplace,
+        state.as_ptr() as *const u8,
+        state.len() as c_int,
+    );
+}
+
+unsafe fn create_input_context(conn: &Connection, xwindow: xlib::Window) -> xlib::XIC {
+    if conn.xim.is_null() {
+        return null_mut();
+    }
+
+    // Prefer letting the input method place its window at the text being composed, see `set_ime_candidate_rect`
+    for style in [XIM_PREEDIT_POSITION | XIM_STATUS_NOTHING, XIM_PREEDIT_NOTHING | XIM_STATUS_NOTHING] {
+        let xic = (conn.xlib.XCreateIC)(
+            conn.xim,
+            XN_INPUT_STYLE.as_ptr() as *const c_char,
+            style,
+            XN_CLIENT_WINDOW.as_ptr() as *const c_char,
+            xwindow,
+            XN_FOCUS_WINDOW.as_ptr() as *const c_char,
+            xwindow,
+            null::<c_void>(),
+        );
+        if !xic.is_null() {
+            return xic;
+        }
+    }
+
+    log_warning!(LOG_CAT, "Failed to create an X input context, text input will be limited to the current keyboard layout");
+    null_mut()
+}
+
+/// Get the text resulting from a key press
+unsafe fn lookup_text(conn: &Connection, xic: xlib::XIC, key: &mut xlib::XKeyEvent) -> Option<String> {
+    const X_BUFFER_OVERFLOW: c_int = -1;
+
+    let mut buffer = vec![0u8; 32];
+    let mut keysym = 0;
+    let len = if xic.is_null() {
+        (conn.xlib.XLookupString)(key, buffer.as_mut_ptr() as *mut c_char, buffer.len() as c_int, &mut keysym, null_mut())
+    } else {
+        let mut status = 0;
+        let mut len = (conn.xlib.Xutf8LookupString)(xic, key, buffer.as_mut_ptr() as *mut c_char, buffer.len() as c_int, &mut keysym, &mut status);
+        // Text committed by an input method can be longer than the buffer, in which case the required length is returned
+        if status == X_BUFFER_OVERFLOW {
+            buffer.resize(len as usize, 0);
+            len = (conn.xlib.Xutf8LookupString)(xic, key, buffer.as_mut_ptr() as *mut c_char, buffer.len() as c_int, &mut keysym, &mut status);
+        }
+        len
+    };
+
+    if len <= 0 {
+        return None;
+    }
+    buffer.truncate(len as usize);
+    Some(String::from_utf8_lossy(&buffer).into_owned())
+}
+
+/// Update the minimized, maximized and topmost state after the window manager changed `_NET_WM_STATE`
+unsafe fn update_state_from_wm(conn: &Connection, window: &mut Window, xwindow: xlib::Window) {
+    let state = conn.get_property32(xwindow, conn.atoms._NET_WM_STATE, xlib::XA_ATOM).unwrap_or_default();
+    let has_state = |atom: xlib::Atom| state.contains(&(atom as c_ulong));
+
+    let minmax_state = if has_state(conn.atoms._NET_WM_STATE_HIDDEN) {
+        Flags::Minimized
+    } else if has_state(conn.atoms._NET_WM_STATE_MAXIMIZED_VERT) && has_state(conn.atoms._NET_WM_STATE_MAXIMIZED_HORZ) {
+        Flags::Maximized
+    } else {
+        Flags::None
+    };
+
+    let old_state = window.settings.set_minmax_state(minmax_state);
+    if old_state != minmax_state {
+        let size = window.settings.size();
+        if minmax_state == Flags::Minimized {
+            log_debug!(LOG_MSG_CAT, "Window {} has been minimized", window.id);
+            window.send_window_event(WindowEvent::Minimized);
+        } else if minmax_state == Flags::Maximized {
+            log_debug!(LOG_MSG_CAT, "Window {} has been maximized", window.id);
+            window.send_window_event(WindowEvent::Maximized);
+        } else {
+            log_debug!(LOG_MSG_CAT, "Window {} has been restored", window.id);
+            window.send_window_event(WindowEvent::Restored);
+        }
+        // The new size will be sent by a `ConfigureNotify` if it has changed, but a `Resized` event is still expected to follow
+        window.send_window_event(WindowEvent::Resized(size));
+    }
+
+    let topmost = has_state(conn.atoms._NET_WM_STATE_ABOVE);
+    if topmost != window.settings.is_top_most() {
+        window.settings.flags.set(Flags::TopMost, topmost);
+        window.send_window_event(WindowEvent::TopMost(topmost));
+    }
+}
+
+/// Update the margins after the window manager has changed the frame of the window
+unsafe fn update_margins(conn: &Connection, window: &mut Window, xwindow: xlib::Window) {
+    // left, right, top, bottom
+    let Some(extents) = conn.get_property32(xwindow, conn.atoms._NET_FRAME_EXTENTS, xlib::XA_CARDINAL) else { return };
+    if extents.len() < 4 {
+        return;
+    }
+
+    window.settings.margins = Margins {
+        top: extents[2] as u16,
+        left: extents[0] as u16,
+        bottom: extents[3] as u16,
+        right: extents[1] as u16,
+    };
+}
+
+/// Send a client message to the window itself, which will be processed during the next tick of the window manager
+fn send_message_to_self(conn: &Connection, xwindow: xlib::Window, message_type: xlib::Atom, data: [c_long; 5]) -> bool {
+    unsafe {
+        let mut event = xlib::XClientMessageEvent {
+            type_: xlib::ClientMessage,
+            serial: 0,
+            send_event: xlib::True,
+            display: conn.display,
+            window: xwindow,
+            message_type,
+            format: 32,
+            data: xlib::ClientMessageData::from(data),
+        };
+        // Without an event mask, the event is sent to the client that created the window
+        let res = (conn.xlib.XSendEvent)(conn.display, xwindow, xlib::False, xlib::NoEventMask, &mut event as *mut _ as *mut xlib::XEvent);
+        (conn.xlib.XFlush)(conn.display);
+        res != 0
+    }
+}
+
+/// Re-apply the cursor grab after the client area of the window has moved or changed size.
+fn reapply_cursor_grab(window: &mut Window) {
+    if window.cursor_grab != CursorGrabMode::None && window.settings.is_active() {
+        window.os_handle.apply_cursor_grab(window.id, window.cursor_grab);
+    }
+}
\ No newline at end of file
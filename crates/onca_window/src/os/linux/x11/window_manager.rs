@@ -0,0 +1,99 @@
+use core::mem;
+use std::collections::HashMap;
+
+use onca_logging::{log_debug, log_error};
+use x11_dl::{xlib, xrandr};
+
+use crate::{Window, WindowEvent, LOG_CAT};
+use super::{
+    clipboard::{self, ClipboardData},
+    window,
+    connection,
+};
+use crate::os::linux::window::process_dpi_change;
+
+pub(crate) struct X11WindowManagerData {
+    /// Windows mapped by their X11 window id, so events can be dispatched to them
+    windows:              HashMap<xlib::Window, *mut Window>,
+    pub(crate) clipboard: ClipboardData,
+    /// Has the display configuration changed since the last tick
+    monitors_changed:     bool,
+}
+
+impl X11WindowManagerData {
+    pub(crate) fn new() -> Self {
+        match connection() {
+            Some(conn) => unsafe {
+                // Changes to the root window's resources are used to detect DPI changes
+                (conn.xlib.XSelectInput)(conn.display, conn.root, xlib::PropertyChangeMask);
+            },
+            None => log_error!(LOG_CAT, "Failed to connect to the X server, no windows can be created"),
+        }
+
+        Self { windows: HashMap::new(), clipboard: ClipboardData::new(), monitors_changed: false }
+    }
+
+    pub(crate) fn register_window(&mut self, xwindow: xlib::Window, window: *mut Window) {
+        self.windows.insert(xwindow, window);
+    }
+
+    pub(crate) fn tick(&mut self) {
+        let Some(conn) = connection() else { return };
+
+        unsafe {
+            while (conn.xlib.XPending)(conn.display) > 0 {
+                let mut event: xlib::XEvent = mem::zeroed();
+                (conn.xlib.XNextEvent)(conn.display, &mut event);
+
+                // Events used by the input method to compose text should not be processed by the window
+                if (conn.xlib.XFilterEvent)(&mut event, 0) != 0 {
+                    continue;
+                }
+
+                let xwindow = event.any.window;
+                if conn.xrandr.is_some() && event.get_type() == conn.randr_event_base + xrandr::RRScreenChangeNotify {
+                    self.process_screen_change(&mut event);
+                    self.monitors_changed = true;
+                } else if conn.xrandr.is_some() && event.get_type() == conn.randr_event_base + xrandr::RRNotify {
+                    // Outputs being connected or disconnected, or CRTCs changing their mode
+                    self.monitors_changed = true;
+                } else if xwindow == conn.root {
+                    if event.get_type() == xlib::PropertyNotify && event.property.atom == xlib::XA_RESOURCE_MANAGER {
+                        let dpi = conn.dpi();
+                        log_debug!(LOG_CAT, "X server DPI has changed to {dpi}");
+                        for window in self.windows.values() {
+                            process_dpi_change(&mut **window, dpi);
+                        }
+                    }
+                } else if xwindow == self.clipboard.window {
+                    clipboard::process_event(&mut self.clipboard, &mut event);
+                } else if let Some(window) = self.windows.get(&xwindow) {
+                    window::process_event(&mut **window, &mut event);
+                    if event.get_type() == xlib::DestroyNotify {
+                        self.windows.remove(&xwindow);
+                    }
+                }
+            }
+        }
+    }
+
+    pub(crate) fn take_monitors_changed(&mut self) -> bool {
+        mem::take(&mut self.monitors_changed)
+    }
+
+    unsafe fn process_screen_change(&mut self, event: &mut xlib::XEvent) {
+        let Some(conn) = connection() else { return };
+        let Some(xrandr) = &conn.xrandr else { return };
+
+        (xrandr.XRRUpdateConfiguration)(event);
+        let screen_change = &*(event as *mut xlib::XEvent as *const xrandr::XRRScreenChangeNotifyEvent);
+        let width = screen_change.width as u16;
+        let height = screen_change.height as u16;
+        let depth = (conn.xlib.XDefaultDepth)(conn.display, conn.screen) as u8;
+
+        log_debug!(LOG_CAT, "Display resolution has changed to {width}x{height} ({depth} bpp)");
+        for window in self.windows.values() {
+            (**window).send_window_event(WindowEvent::DisplayResolutionChanges(width, height, depth));
+        }
+    }
+}
\ No newline at end of file
@@ -4,5 +4,8 @@ cfg_if! {
     if #[cfg(windows)] {
         pub(crate) mod windows;
         pub(crate) use crate::os::windows::*;
+    } else if #[cfg(target_os = "linux")] {
+        pub(crate) mod linux;
+        pub(crate) use crate::os::linux::*;
     }
 }
\ No newline at end of file
@@ -0,0 +1,83 @@
+use core::ffi::c_void;
+use onca_logging::log_error;
+use windows::Win32::{
+    Foundation::{HANDLE, HGLOBAL, HWND},
+    System::{
+        DataExchange::{OpenClipboard, CloseClipboard, EmptyClipboard, GetClipboardData, SetClipboardData},
+        Memory::{GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock, GMEM_MOVEABLE},
+        Ole::CF_UNICODETEXT,
+    },
+};
+
+use crate::LOG_CAT;
+use super::WindowManagerData;
+
+pub(crate) fn get_text(_data: &mut WindowManagerData) -> Option<String> {
+    unsafe {
+        if let Err(err) = OpenClipboard(HWND(0)) {
+            log_error!(LOG_CAT, "Failed to open the clipboard ({err})");
+            return None;
+        }
+
+        // The clipboard does not contain any text when there is no data for the format
+        let text = GetClipboardData(CF_UNICODETEXT.0 as u32).ok().and_then(|handle| {
+            let hglobal = HGLOBAL(handle.0 as *mut c_void);
+            let data = GlobalLock(hglobal) as *const u16;
+            if data.is_null() {
+                return None;
+            }
+
+            let mut len = 0;
+            while *data.add(len) != 0 {
+                len += 1;
+            }
+            let text = String::from_utf16_lossy(core::slice::from_raw_parts(data, len));
+            let _ = GlobalUnlock(hglobal);
+            Some(text)
+        });
+
+        let _ = CloseClipboard();
+        text
+    }
+}
+
+pub(crate) fn set_text(_data: &mut WindowManagerData, text: &str) -> bool {
+    unsafe {
+        if let Err(err) = OpenClipboard(HWND(0)) {
+            log_error!(LOG_CAT, "Failed to open the clipboard ({err})");
+            return false;
+        }
+
+        let res = set_text_internal(text);
+        let _ = CloseClipboard();
+        match res {
+            Ok(_) => true,
+            Err(err) => {
+                log_error!(LOG_CAT, "Failed to set the clipboard text ({err})");
+                false
+            },
+        }
+    }
+}
+
+/// Set the clipboard text, the clipboard needs to be opened before this is called
+unsafe fn set_text_internal(text: &str) -> windows::core::Result<()> {
+    EmptyClipboard()?;
+
+    let utf16 = text.encode_utf16().chain(core::iter::once(0)).collect::<Vec<_>>();
+    let hglobal = GlobalAlloc(GMEM_MOVEABLE, utf16.len() * core::mem::size_of::<u16>())?;
+    let data = GlobalLock(hglobal) as *mut u16;
+    if data.is_null() {
+        let _ = GlobalFree(hglobal);
+        return Err(windows::core::Error::from_win32());
+    }
+    core::ptr::copy_nonoverlapping(utf16.as_ptr(), data, utf16.len());
+    let _ = GlobalUnlock(hglobal);
+
+    // The clipboard takes ownership of the memory, so it should only be freed when it fails
+    if let Err(err) = SetClipboardData(CF_UNICODETEXT.0 as u32, HANDLE(hglobal.0 as isize)) {
+        let _ = GlobalFree(hglobal);
+        return Err(err);
+    }
+    Ok(())
+}
\ No newline at end of file
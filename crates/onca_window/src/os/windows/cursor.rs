@@ -0,0 +1,103 @@
+use onca_common::prelude::*;
+use onca_logging::log_warning;
+use windows::{
+    Win32::{
+        Foundation::FALSE,
+        Graphics::Gdi::{CreateBitmap, DeleteObject},
+        UI::WindowsAndMessaging::{
+            CreateIconIndirect, DestroyCursor, LoadCursorW, HCURSOR, ICONINFO,
+            IDC_APPSTARTING, IDC_ARROW, IDC_CROSS, IDC_HAND, IDC_HELP, IDC_IBEAM, IDC_NO, IDC_SIZEALL, IDC_SIZENESW, IDC_SIZENS, IDC_SIZENWSE, IDC_SIZEWE, IDC_WAIT,
+        },
+    },
+};
+
+use crate::{CursorShape, PhysicalSize, WindowCursor, LOG_CAT};
+
+pub struct OSCursor {
+    hcursor: HCURSOR,
+}
+
+impl OSCursor {
+    pub(crate) fn from_rgba(pixels: &[u8], size: PhysicalSize, hotspot: (u16, u16)) -> OSCursor {
+        unsafe {
+            let _scope_alloc = ScopedAlloc::new(AllocId::TlsTemp);
+
+            // The color bitmap is stored as BGRA, its alpha channel is used for transparency, so the mask doesn't hide any pixels
+            let bgra = pixels.chunks_exact(4).flat_map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]]).collect::<Vec<_>>();
+            // Rows of the 1-bit mask are aligned to 16 bits
+            let mask_stride = (size.width as usize + 15) / 16 * 2;
+            let mask = vec![0u8; mask_stride * size.height as usize];
+
+            let hbm_color = CreateBitmap(size.width as i32, size.height as i32, 1, 32, Some(bgra.as_ptr() as *const _));
+            let hbm_mask = CreateBitmap(size.width as i32, size.height as i32, 1, 1, Some(mask.as_ptr() as *const _));
+
+            let icon_info = ICONINFO {
+                fIcon: FALSE,
+                xHotspot: hotspot.0 as u32,
+                yHotspot: hotspot.1 as u32,
+                hbmMask: hbm_mask,
+                hbmColor: hbm_color,
+            };
+            let hicon = CreateIconIndirect(&icon_info);
+
+            // The cursor has its own copy of the bitmaps
+            DeleteObject(hbm_color);
+            DeleteObject(hbm_mask);
+
+            match hicon {
+                Ok(hicon) => OSCursor { hcursor: HCURSOR(hicon.0) },
+                Err(err) => {
+                    log_warning!(LOG_CAT, "Failed to create a {}x{} cursor from pixels. (hresult: {:X})", size.width, size.height, err.code().0);
+                    OSCursor { hcursor: HCURSOR(0) }
+                },
+            }
+        }
+    }
+
+    pub(crate) fn hcursor(&self) -> HCURSOR {
+        self.hcursor
+    }
+}
+
+impl Drop for OSCursor {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.hcursor.is_invalid() {
+                let res = DestroyCursor(self.hcursor);
+                if let Err(err) = res {
+                    log_warning!(LOG_CAT, "Failed to destoy cursor with handle '{:X}' ({err})", self.hcursor.0);
+                }
+            }
+        }
+    }
+}
+
+/// Get the handle of the cursor that should be shown over the client area of a window
+pub(crate) fn get_hcursor(cursor: &WindowCursor) -> HCURSOR {
+    let shape = match cursor {
+        WindowCursor::Shape(shape) => *shape,
+        WindowCursor::Custom(cursor) => return cursor.get_os_cursor().hcursor(),
+    };
+
+    let idc = match shape {
+        CursorShape::Arrow => IDC_ARROW,
+        CursorShape::IBeam => IDC_IBEAM,
+        CursorShape::Crosshair => IDC_CROSS,
+        CursorShape::Hand => IDC_HAND,
+        // Windows doesn't have any grab cursors
+        CursorShape::Grab => IDC_HAND,
+        CursorShape::Grabbing => IDC_HAND,
+        CursorShape::ResizeHorizontal => IDC_SIZEWE,
+        CursorShape::ResizeVertical => IDC_SIZENS,
+        CursorShape::ResizeNwSe => IDC_SIZENWSE,
+        CursorShape::ResizeNeSw => IDC_SIZENESW,
+        CursorShape::Move => IDC_SIZEALL,
+        CursorShape::NotAllowed => IDC_NO,
+        CursorShape::Wait => IDC_WAIT,
+        CursorShape::Progress => IDC_APPSTARTING,
+        CursorShape::Help => IDC_HELP,
+    };
+
+    // System cursors are shared, so they don't need to be destroyed
+    unsafe { LoadCursorW(None, idc).unwrap_or_default() }
+}
\ No newline at end of file
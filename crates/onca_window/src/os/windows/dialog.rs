@@ -0,0 +1,158 @@
+use core::ffi::c_void;
+use std::path::PathBuf;
+use onca_logging::log_error;
+use windows::{
+    Win32::{
+        Foundation::HWND,
+        System::Com::{CoCreateInstance, CoTaskMemFree, CLSCTX_INPROC_SERVER},
+        UI::{
+            Shell::{
+                Common::COMDLG_FILTERSPEC,
+                FileOpenDialog, FileSaveDialog, IFileDialog, IFileOpenDialog, IFileSaveDialog, IShellItem, SHCreateItemFromParsingName,
+                FILEOPENDIALOGOPTIONS, FOS_ALLOWMULTISELECT, FOS_FORCEFILESYSTEM, FOS_OVERWRITEPROMPT, SIGDN_FILESYSPATH,
+            },
+            WindowsAndMessaging::*,
+        },
+    },
+    core::{HSTRING, PCWSTR},
+};
+
+use crate::{FileDialog, MessageBoxButtons, MessageBoxResult, MessageBoxSeverity, LOG_CAT};
+
+pub(crate) fn message_box(title: &str, text: &str, buttons: MessageBoxButtons, severity: MessageBoxSeverity) -> MessageBoxResult {
+    let buttons_style = match buttons {
+        MessageBoxButtons::Ok => MB_OK,
+        MessageBoxButtons::OkCancel => MB_OKCANCEL,
+        MessageBoxButtons::YesNo => MB_YESNO,
+        MessageBoxButtons::YesNoCancel => MB_YESNOCANCEL,
+        MessageBoxButtons::RetryCancel => MB_RETRYCANCEL,
+        MessageBoxButtons::AbortRetryIgnore => MB_ABORTRETRYIGNORE,
+    };
+    let icon_style = match severity {
+        MessageBoxSeverity::Info => MB_ICONINFORMATION,
+        MessageBoxSeverity::Warning => MB_ICONWARNING,
+        MessageBoxSeverity::Error => MB_ICONERROR,
+    };
+
+    // The message box has no owner, so make it modal to all windows of the thread, and make sure it's not hidden behind them
+    let res = unsafe { MessageBoxW(HWND(0), &HSTRING::from(text), &HSTRING::from(title), buttons_style | icon_style | MB_TASKMODAL | MB_SETFOREGROUND) };
+    match res {
+        IDOK => MessageBoxResult::Ok,
+        IDCANCEL => MessageBoxResult::Cancel,
+        IDYES => MessageBoxResult::Yes,
+        IDNO => MessageBoxResult::No,
+        IDRETRY => MessageBoxResult::Retry,
+        IDABORT => MessageBoxResult::Abort,
+        IDIGNORE => MessageBoxResult::Ignore,
+        _ => {
+            log_error!(LOG_CAT, "Failed to show message box '{title}': {text}");
+            buttons.dismiss_result()
+        },
+    }
+}
+
+pub(crate) fn open_file_dialog(settings: &FileDialog) -> Vec<PathBuf> {
+    unsafe {
+        let dialog: IFileOpenDialog = match CoCreateInstance(&FileOpenDialog, None, CLSCTX_INPROC_SERVER) {
+            Ok(dialog) => dialog,
+            Err(err) => {
+                log_error!(LOG_CAT, "Failed to create a file open dialog ({err})");
+                return Vec::new();
+            },
+        };
+
+        let mut options = FOS_FORCEFILESYSTEM;
+        if settings.multi_select {
+            options |= FOS_ALLOWMULTISELECT;
+        }
+        if let Err(err) = apply_settings(&dialog, settings, options) {
+            log_error!(LOG_CAT, "Failed to set up a file open dialog ({err})");
+            return Vec::new();
+        }
+
+        // An error is returned when the user cancels the dialog
+        if dialog.Show(HWND(0)).is_err() {
+            return Vec::new();
+        }
+
+        let items = match dialog.GetResults() {
+            Ok(items) => items,
+            Err(err) => {
+                log_error!(LOG_CAT, "Failed to get the files selected in a file open dialog ({err})");
+                return Vec::new();
+            },
+        };
+
+        let count = items.GetCount().unwrap_or(0);
+        (0..count).filter_map(|idx| items.GetItemAt(idx).ok())
+            .filter_map(|item| get_item_path(&item))
+            .collect()
+    }
+}
+
+pub(crate) fn save_file_dialog(settings: &FileDialog) -> Option<PathBuf> {
+    unsafe {
+        let dialog: IFileSaveDialog = match CoCreateInstance(&FileSaveDialog, None, CLSCTX_INPROC_SERVER) {
+            Ok(dialog) => dialog,
+            Err(err) => {
+                log_error!(LOG_CAT, "Failed to create a file save dialog ({err})");
+                return None;
+            },
+        };
+
+        if let Err(err) = apply_settings(&dialog, settings, FOS_FORCEFILESYSTEM | FOS_OVERWRITEPROMPT) {
+            log_error!(LOG_CAT, "Failed to set up a file save dialog ({err})");
+            return None;
+        }
+
+        // The extension of the selected filter will be added when the user doesn't type one
+        if let Some(extension) = settings.filters.first().and_then(|filter| filter.extensions.first()) {
+            let _ = dialog.SetDefaultExtension(&HSTRING::from(extension.as_str()));
+        }
+
+        // An error is returned when the user cancels the dialog
+        dialog.Show(HWND(0)).ok()?;
+        dialog.GetResult().ok().and_then(|item| get_item_path(&item))
+    }
+}
+
+/// Apply the settings shared by the open and save dialogs
+unsafe fn apply_settings(dialog: &IFileDialog, settings: &FileDialog, options: FILEOPENDIALOGOPTIONS) -> windows::core::Result<()> {
+    dialog.SetOptions(dialog.GetOptions()? | options)?;
+
+    if let Some(title) = &settings.title {
+        dialog.SetTitle(&HSTRING::from(title.as_str()))?;
+    }
+
+    if !settings.filters.is_empty() {
+        // The strings need to stay alive until the file types are set
+        let strings = settings.filters.iter().map(|filter| {
+            let spec = filter.extensions.iter().map(|ext| format!("*.{ext}")).collect::<Vec<_>>().join(";");
+            (HSTRING::from(filter.name.as_str()), HSTRING::from(spec))
+        }).collect::<Vec<_>>();
+        let file_types = strings.iter().map(|(name, spec)| COMDLG_FILTERSPEC {
+            pszName: PCWSTR(name.as_ptr()),
+            pszSpec: PCWSTR(spec.as_ptr()),
+        }).collect::<Vec<_>>();
+        dialog.SetFileTypes(&file_types)?;
+    }
+
+    if let Some(directory) = &settings.directory {
+        // The directory only needs to be set when it exists
+        if let Ok(folder) = SHCreateItemFromParsingName::<_, _, IShellItem>(&HSTRING::from(directory.as_path()), None) {
+            dialog.SetFolder(&folder)?;
+        }
+    }
+
+    if let Some(file_name) = &settings.file_name {
+        dialog.SetFileName(&HSTRING::from(file_name.as_str()))?;
+    }
+    Ok(())
+}
+
+unsafe fn get_item_path(item: &IShellItem) -> Option<PathBuf> {
+    let path = item.GetDisplayName(SIGDN_FILESYSPATH).ok()?;
+    let path_buf = path.to_string().ok().map(PathBuf::from);
+    CoTaskMemFree(Some(path.0 as *const c_void));
+    path_buf
+}
\ No newline at end of file
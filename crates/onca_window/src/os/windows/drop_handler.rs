@@ -1,8 +1,5 @@
 use core::ffi::c_void;
-use onca_common::{
-    prelude::*,
-    alloc::ScopedAlloc,
-};
+use std::{cell::Cell, ffi::OsString, os::windows::ffi::OsStringExt, path::PathBuf};
 use onca_logging::{log_warning, log_error, log_debug};
 use win_utils::com::ComInterface;
 use windows::{
@@ -11,12 +8,12 @@ use windows::{
         Ole::*,
         Com::*,
         SystemServices::*,
-    }, Foundation::{POINTL, DV_E_FORMATETC, S_OK}, UI::Shell::{HDROP, DragQueryFileA, DragFinish}}
+    }, Foundation::{POINT, POINTL, DV_E_FORMATETC, S_OK}, Graphics::Gdi::ScreenToClient, UI::Shell::{HDROP, DragQueryFileW}}
 };
 
 use onca_windows_utils as win_utils;
 
-use crate::{Window, WindowEvent, LOG_CAT};
+use crate::{DropEffect, PhysicalPosition, Window, WindowEvent, LOG_CAT};
 
 #[repr(C)]
 pub(crate) struct DropHandlerData {
@@ -25,7 +22,7 @@ pub(crate) struct DropHandlerData {
     ref_count  : win_utils::com::ComRefCount,
     window     : *mut Window,
     valid      : bool,
-    effect     : DROPEFFECT,
+    effect     : DropEffect,
 }
 
 pub const DROP_HANDLER_VTBL : IDropTarget_Vtbl = win_utils::ole::create_idroptarget_vtable(
@@ -50,8 +47,7 @@ impl DropHandler {
             ref_count: win_utils::com::ComRefCount::new(),
             window: window as *mut _,
             valid: false,
-            effect: DROPEFFECT_NONE
-
+            effect: DropEffect::None,
         });
 
         let data = &mut *handler_data as *mut DropHandlerData;
@@ -87,17 +83,19 @@ impl DropHandler {
     unsafe extern "system" fn DragEnter(this: &mut DropHandlerData, data_obj: ComInterface<IDataObject>, _: MODIFIERKEYS_FLAGS, pt: POINTL, effect: &mut DROPEFFECT) -> HRESULT {
         let window = &mut *this.window;
 
-        let hdrop = Self::iter_files(data_obj, |path| {
-            log_debug!(LOG_CAT, "Started hovering file '{}' over window {} at location({}, {})", &path, window.id(), pt.x, pt.y);
-            window.send_window_event(WindowEvent::HoverFileStarted(pt.x as u16, pt.y as u16, &path));
-        });
-        this.valid = hdrop.is_some();
-        if this.valid {
-            this.effect = DROPEFFECT_COPY
-        } else {
-            this.effect = DROPEFFECT_NONE
+        this.valid = false;
+        if let Some(files) = Self::get_files(data_obj) {
+            let pos = Self::client_pos(window, pt);
+            log_debug!(LOG_CAT, "Started hovering {} files over window {} at location ({}, {})", files.len(), window.id(), pos.x, pos.y);
+
+            let drop_effect = Cell::new(window.drop_effect());
+            let set_effect = |new_effect| drop_effect.set(new_effect);
+            window.send_window_event(WindowEvent::FilesHoverStarted{ files: &files, pos, set_effect: &set_effect });
+
+            this.valid = true;
+            this.effect = drop_effect.get();
         }
-        *effect = this.effect;
+        *effect = this.get_effect(*effect);
 
         S_OK
     }
@@ -107,9 +105,14 @@ impl DropHandler {
     unsafe extern "system" fn DragOver(this: &mut DropHandlerData, mod_keys: MODIFIERKEYS_FLAGS, pt: POINTL, effect: &mut DROPEFFECT) -> HRESULT {
         if this.valid {
             let window = &mut *this.window;
-            window.send_window_event(WindowEvent::HoverFileTick(pt.x as u16, pt.y as u16));
-            *effect = this.effect;
+            let pos = Self::client_pos(window, pt);
+
+            let drop_effect = Cell::new(this.effect);
+            let set_effect = |new_effect| drop_effect.set(new_effect);
+            window.send_window_event(WindowEvent::FilesHoverMoved{ pos, set_effect: &set_effect });
+            this.effect = drop_effect.get();
         }
+        *effect = this.get_effect(*effect);
 
         S_OK
     }
@@ -119,7 +122,7 @@ impl DropHandler {
         if this.valid {
             let window = &mut *this.window;
             log_debug!(LOG_CAT, "Stopped hovering files over window {}", window.id());
-            window.send_window_event(WindowEvent::HoverFileHoverEnded);
+            window.send_window_event(WindowEvent::FilesHoverEnded);
             this.valid = false;
         }
 
@@ -129,17 +132,26 @@ impl DropHandler {
     // We ignore the modifiers given here, and the user is responsible to handle any special action with modifiers using `onca_input`
     #[allow(unused, non_snake_case)]
     unsafe extern "system" fn Drop(this: &mut DropHandlerData, data_obj: ComInterface<IDataObject>, mod_keys: MODIFIERKEYS_FLAGS, pt: POINTL, effect: &mut DROPEFFECT) -> HRESULT {
-        let window = &mut *this.window;
-
-        let hdrop = Self::iter_files(data_obj, |path| {
-            log_debug!(LOG_CAT, "Dropped file '{}' over window {} at location({}, {})", &path, window.id(), pt.x, pt.y);
-            window.send_window_event(WindowEvent::DroppedFile(pt.x as u16, pt.y as u16, &path));
-        });
-        if let Some(hdrop) = hdrop {
-            DragFinish(hdrop);
+        *effect = this.get_effect(*effect);
+        if !this.valid {
+            return S_OK;
         }
         this.valid = false;
 
+        let window = &mut *this.window;
+        let files = if *effect != DROPEFFECT_NONE { Self::get_files(data_obj) } else { None };
+        match files {
+            Some(files) => {
+                let pos = Self::client_pos(window, pt);
+                log_debug!(LOG_CAT, "Dropped {} files over window {} at location ({}, {})", files.len(), window.id(), pos.x, pos.y);
+                window.send_window_event(WindowEvent::FilesDropped(files, pos));
+            },
+            None => {
+                log_debug!(LOG_CAT, "Rejected files dropped over window {}", window.id());
+                window.send_window_event(WindowEvent::FilesHoverEnded);
+            },
+        }
+
         S_OK
     }
 
@@ -147,28 +159,22 @@ impl DropHandler {
         win_utils::from_interface(this)
     }
 
-    unsafe fn iter_files<F: FnMut(String)>(data_obj: ComInterface<IDataObject>, mut callback: F) -> Option<HDROP> {
+    /// Convert the position of the cursor, which is in screen coordinates, to the client area of the window.
+    unsafe fn client_pos(window: &Window, pt: POINTL) -> PhysicalPosition {
+        let mut point = POINT { x: pt.x, y: pt.y };
+        ScreenToClient(window.os_handle().hwnd(), &mut point);
+        PhysicalPosition::new(point.x, point.y)
+    }
+
+    unsafe fn get_files(data_obj: ComInterface<IDataObject>) -> Option<Vec<PathBuf>> {
         let mut format = win_utils::ole::create_hdrop_formatetc();
         let res = data_obj.get().GetData(&mut format);
         match res {
-            Ok(medium) => {
-                let hdrop = HDROP(medium.u.hGlobal.0 as isize);
-
-                let num_files = DragQueryFileA(hdrop, 0xFFFF_FFFF, None);
-
-                for i in 0..num_files {
-                    let _scope_alloc = ScopedAlloc::new(AllocId::TlsTemp);
-
-                    let path_len = DragQueryFileA(hdrop, i, None);
-                    let mut buf = Vec::<u8>::new();
-                    buf.reserve(path_len as usize + 1);
-                    buf.set_len(path_len as usize + 1);
-
-                    let bytes_written = DragQueryFileA(hdrop, i, Some(&mut buf));
-                    buf.set_len(bytes_written as usize);
-                    callback(String::from_utf8_unchecked(buf));
-                }
-                Some(hdrop)
+            Ok(mut medium) => {
+                let files = query_dropped_files(HDROP(medium.u.hGlobal.0 as isize));
+                // The HDROP is owned by the medium, so it needs to be released with the medium, instead of using `DragFinish`
+                ReleaseStgMedium(&mut medium);
+                Some(files)
             },
             Err(err) => {
                 if err.code() == DV_E_FORMATETC {
@@ -183,4 +189,33 @@ impl DropHandler {
             },
         }
     }
+}
+
+impl DropHandlerData {
+    /// Get the win32 effect for the current drop effect, which needs to be one of the effects allowed by the source of the drag.
+    fn get_effect(&self, allowed: DROPEFFECT) -> DROPEFFECT {
+        let effect = match self.effect {
+            DropEffect::None => DROPEFFECT_NONE,
+            DropEffect::Copy => DROPEFFECT_COPY,
+            DropEffect::Move => DROPEFFECT_MOVE,
+            DropEffect::Link => DROPEFFECT_LINK,
+        };
+        if self.valid && allowed.0 & effect.0 != 0 {
+            effect
+        } else {
+            DROPEFFECT_NONE
+        }
+    }
+}
+
+/// Get the paths of all files stored in an `HDROP`.
+pub(crate) unsafe fn query_dropped_files(hdrop: HDROP) -> Vec<PathBuf> {
+    let num_files = DragQueryFileW(hdrop, 0xFFFF_FFFF, None);
+    (0..num_files).map(|i| {
+        // The returned length does not include the null-terminator, but it still needs space in the buffer
+        let path_len = DragQueryFileW(hdrop, i, None);
+        let mut buf = vec![0u16; path_len as usize + 1];
+        let chars_written = DragQueryFileW(hdrop, i, Some(&mut buf));
+        PathBuf::from(OsString::from_wide(&buf[..chars_written as usize]))
+    }).collect()
 }
\ No newline at end of file
@@ -3,9 +3,9 @@ use onca_logging::log_warning;
 use windows::{
     Win32::{
         UI::WindowsAndMessaging::{
-            HICON, LoadImageA, IMAGE_BITMAP, LR_LOADFROMFILE, DestroyIcon, CopyIcon
+            HICON, LoadImageA, IMAGE_BITMAP, LR_LOADFROMFILE, DestroyIcon, CopyIcon, CreateIcon
         },
-        Foundation::HMODULE,
+        Foundation::{HMODULE, HINSTANCE},
     },
     core::PCSTR
 };
@@ -41,6 +41,36 @@ impl OSIcon {
         }
     }
 
+    pub(crate) fn from_rgba(pixels: &[u8], size: PhysicalSize) -> OSIcon {
+        unsafe {
+            let _scope_alloc = ScopedAlloc::new(AllocId::TlsTemp);
+
+            // The color bitmap is stored as BGRA, its alpha channel is used for transparency, so the mask doesn't hide any pixels
+            let bgra = pixels.chunks_exact(4).flat_map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]]).collect::<Vec<_>>();
+            // Rows of the 1-bit mask are aligned to 16 bits
+            let mask_stride = (size.width as usize + 15) / 16 * 2;
+            let mask = vec![0u8; mask_stride * size.height as usize];
+
+            let hicon = CreateIcon(
+                HINSTANCE(0),
+                size.width as i32,
+                size.height as i32,
+                1,
+                32,
+                mask.as_ptr(),
+                bgra.as_ptr()
+            );
+
+            match hicon {
+                Ok(hicon) => OSIcon { hicon },
+                Err(err) => {
+                    log_warning!(LOG_CAT, "Failed to create a {}x{} icon from pixels. (hresult: {:X})", size.width, size.height, err.code().0);
+                    OSIcon { hicon: HICON(0) }
+                },
+            }
+        }
+    }
+
     pub(crate) fn hicon(&self) -> HICON {
         self.hicon
     }
@@ -1,3 +1,10 @@
+pub(crate) mod clipboard;
+
+pub(crate) mod cursor;
+pub(crate) use cursor::OSCursor;
+
+pub(crate) mod dialog;
+
 pub(crate) mod drop_handler;
 
 pub(crate) mod icon;
@@ -7,7 +14,7 @@ pub(crate) mod monitor;
 pub(crate) use monitor::MonitorHandle;
 
 pub(crate) mod window;
-pub(crate) use window::{OSWindowHandle, OSWindowData};
+pub(crate) use window::{OSWindowHandle, OSWindowData, raw_display_handle};
 
 pub(crate) mod window_manager;
 pub(crate) use window_manager::WindowManagerData;
\ No newline at end of file
@@ -12,6 +12,7 @@ use windows::{
             EnumDisplayMonitors, GetMonitorInfoA, MonitorFromWindow, MonitorFromPoint, MonitorFromRect, 
             HMONITOR, HDC, MONITORINFOEXA, MONITORINFO,
             MONITOR_DEFAULTTONULL, EnumDisplaySettingsExA, ENUM_CURRENT_SETTINGS, DEVMODEA, ENUM_DISPLAY_SETTINGS_MODE, DM_BITSPERPEL, DM_PELSWIDTH, DM_PELSHEIGHT, DM_DISPLAYFREQUENCY, EnumDisplayDevicesA, DISPLAY_DEVICEA, EDS_RAWMODE,
+            ChangeDisplaySettingsExA, CDS_FULLSCREEN, CDS_TYPE, DISP_CHANGE_SUCCESSFUL,
         },
         UI::{
             WindowsAndMessaging::MONITORINFOF_PRIMARY,
@@ -21,7 +22,7 @@ use windows::{
     core::PCSTR
 };
 
-use crate::{Monitor, LOG_CAT, WindowSettings, MonitorRect, MonitorMode, PhysicalSize, MonitorModeOrdWrapper};
+use crate::{Monitor, LOG_CAT, OSWindowHandle, WindowSettings, MonitorRect, MonitorMode, PhysicalSize, MonitorModeOrdWrapper};
 
 #[derive(Clone, Copy)]
 pub struct MonitorHandle(HMONITOR);
@@ -156,9 +157,9 @@ pub(crate) fn primary_monitor() -> Option<Monitor> {
     }
 }
 
-pub(crate) fn get_monitor_from_hwnd(hwnd: HWND) -> Option<Monitor> {
+pub(crate) fn get_monitor_from_handle(handle: OSWindowHandle) -> Option<Monitor> {
     unsafe {
-        let hmonitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONULL);
+        let hmonitor = MonitorFromWindow(handle.hwnd(), MONITOR_DEFAULTTONULL);
         if hmonitor.is_invalid() {
             None
         } else {
@@ -190,6 +191,49 @@ pub(crate) fn get_monitor_from_largest_overlap(rect: MonitorRect) -> Option<Moni
     }
 }
 
+/// Change the display mode of a monitor, or restore its original display mode when `mode` is `None`.
+/// 
+/// The display mode is changed temporarily, so it will automatically be restored when the process exits.
+pub(crate) fn set_display_mode(hmon: HMONITOR, mode: Option<MonitorMode>) -> bool {
+    unsafe {
+        let mut monitor_info = MONITORINFOEXA::default();
+        monitor_info.monitorInfo.cbSize = size_of::<MONITORINFOEXA>() as u32;
+        if hmon.is_invalid() || !GetMonitorInfoA(hmon, &mut monitor_info as *mut _ as *mut MONITORINFO).as_bool() {
+            log_error!(LOG_CAT, "Failed to retrieve monitor info to change its display mode");
+            return false;
+        }
+        let dev_name = PCSTR(monitor_info.szDevice.as_ptr() as *const u8);
+
+        let res = match mode {
+            Some(mode) => {
+                let mut dev_mode = DEVMODEA::default();
+                dev_mode.dmSize = size_of::<DEVMODEA>() as u16;
+                dev_mode.dmFields = DM_PELSWIDTH | DM_PELSHEIGHT;
+                dev_mode.dmPelsWidth = mode.size.width as u32;
+                dev_mode.dmPelsHeight = mode.size.height as u32;
+                // A value of 0 means that the monitor can choose the value
+                if mode.bits_per_pixel != 0 {
+                    dev_mode.dmFields |= DM_BITSPERPEL;
+                    dev_mode.dmBitsPerPel = mode.bits_per_pixel as u32;
+                }
+                if mode.refesh_rate != 0 {
+                    dev_mode.dmFields |= DM_DISPLAYFREQUENCY;
+                    dev_mode.dmDisplayFrequency = mode.refesh_rate;
+                }
+                ChangeDisplaySettingsExA(dev_name, Some(&dev_mode as *const DEVMODEA), HWND(0), CDS_FULLSCREEN, None)
+            },
+            // Passing no mode restores the mode stored in the registry
+            None => ChangeDisplaySettingsExA(dev_name, None, HWND(0), CDS_TYPE(0), None),
+        };
+
+        if res != DISP_CHANGE_SUCCESSFUL {
+            log_error!(LOG_CAT, "Failed to change the display mode of monitor '{}' (err: {})", utils::null_terminated_arr_to_str_unchecked(&*(&monitor_info.szDevice as *const _ as *const [u8; 32])), res.0);
+            return false;
+        }
+        true
+    }
+}
+
 pub(crate) fn get_monitor_rect(hmon: HMONITOR) -> Option<MonitorRect> {
     unsafe {
         if hmon.is_invalid() {
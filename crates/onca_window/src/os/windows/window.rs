@@ -1,5 +1,5 @@
 use crate::*;
-use core::{ffi::c_void, mem, ptr::null_mut};
+use core::{ffi::c_void, mem, num::NonZeroIsize, ptr::null_mut};
 use std::cell::Cell;
 use onca_common::{
     prelude::*,
@@ -13,21 +13,30 @@ use windows::{
     core::PCSTR,
     Win32::{
         Foundation::{
-            GetLastError, SetLastError, BOOL, ERROR_SUCCESS, HWND, LPARAM, LRESULT, POINT, POINTS,
+            GetLastError, SetLastError, BOOL, COLORREF, ERROR_SUCCESS, HWND, LPARAM, LRESULT, POINT, POINTS,
             RECT, WPARAM,
         },
-        Graphics::Gdi::{MonitorFromRect, MonitorFromWindow, MONITOR_DEFAULTTONULL},
-        System::Ole::RegisterDragDrop,
+        Globalization::HIMC,
+        Graphics::Gdi::{ClientToScreen, MonitorFromWindow, ScreenToClient, HMONITOR, MONITOR_DEFAULTTONEAREST},
+        System::Ole::{RegisterDragDrop, RevokeDragDrop},
         UI::{
-            HiDpi::GetDpiForWindow,
+            HiDpi::{AdjustWindowRectExForDpi, GetDpiForWindow, SetThreadDpiAwarenessContext, DPI_AWARENESS_CONTEXT_UNAWARE},
+            Input::Ime::{
+                ImmAssociateContextEx, ImmGetCompositionStringW, ImmGetContext, ImmReleaseContext, ImmSetCandidateWindow,
+                CANDIDATEFORM, CFS_EXCLUDE, GCS_COMPSTR, GCS_CURSORPOS, GCS_RESULTSTR, IACE_DEFAULT, IME_COMPOSITION_STRING,
+            },
             Input::KeyboardAndMouse::{EnableWindow, ReleaseCapture, TRACKMOUSEEVENT, TME_LEAVE, TrackMouseEvent},
-            Shell::{DragFinish, DragQueryFileA, DragQueryPoint, HDROP},
+            Shell::{DragFinish, DragQueryPoint, HDROP, TBPF_NOPROGRESS, TBPF_INDETERMINATE, TBPF_NORMAL, TBPF_PAUSED, TBPF_ERROR},
             WindowsAndMessaging::*, Controls::WM_MOUSELEAVE,
         },
     },
 };
 
-use super::drop_handler::DropHandler;
+use super::{
+    cursor::get_hcursor,
+    drop_handler::{query_dropped_files, DropHandler},
+    WindowManagerData,
+};
 
 #[derive(Clone, Copy)]
 pub struct OSWindowHandle {
@@ -40,6 +49,14 @@ impl OSWindowHandle {
         self.hwnd
     }
 
+    /// Get the raw platform handle of the window
+    pub fn raw_window_handle(&self) -> RawWindowHandle {
+        RawWindowHandle::Win32 {
+            hwnd: NonZeroIsize::new(self.hwnd.0).expect("The window has not been created yet"),
+            hinstance: NonZeroIsize::new(get_app_handle().hmodule().0),
+        }
+    }
+
     pub(self) fn null() -> OSWindowHandle {
         OSWindowHandle {
             hwnd: HWND::default(),
@@ -103,64 +120,81 @@ impl OSWindowHandle {
         unsafe { ShowWindow(self.hwnd, SW_RESTORE) };
     }
 
+    /// Set the fullscreen mode of the window, returning the mode that was applied.
     pub(crate) fn set_fullscreen(
         &mut self,
-        fullscreen: bool,
+        window_id: WindowId,
+        old_mode: FullscreenMode,
+        mode: FullscreenMode,
         os_data: &mut OSWindowData,
         settings: &mut WindowSettings,
-    ) {
+    ) -> FullscreenMode {
         unsafe {
-            let mut window_placement = WINDOWPLACEMENT::default();
-            window_placement.length = mem::size_of::<WINDOWPLACEMENT>() as u32;
-            let res = GetWindowPlacement(self.hwnd, &mut window_placement);
-            if let Err(err) = res {
-                log_warning!(LOG_CAT, "Failed to store pre-fullscreen window state ({err})");
+            // Store the window state, so it can be restored when returning to windowed mode
+            if old_mode == FullscreenMode::Windowed {
+                os_data.windowed_state.length = mem::size_of::<WINDOWPLACEMENT>() as u32;
+                let res = GetWindowPlacement(self.hwnd, &mut os_data.windowed_state);
+                if let Err(err) = res {
+                    log_warning!(LOG_CAT, "Failed to store pre-fullscreen window state of window '{window_id}' ({err})");
+                }
             }
 
-            let hmon = if fullscreen {
-                let cur_pos = settings.position();
-                let cur_size = settings.size();
-                let rect = RECT {
-                    left: cur_pos.x,
-                    top: cur_pos.y,
-                    right: cur_pos.x + cur_size.width as i32,
-                    bottom: cur_pos.y + cur_size.height as i32,
-                };
+            let hmon = MonitorFromWindow(self.hwnd, MONITOR_DEFAULTTONEAREST);
 
-                MonitorFromRect(&rect, MONITOR_DEFAULTTONULL)
-            } else {
-                MonitorFromWindow(self.hwnd, MONITOR_DEFAULTTONULL)
+            // Restore the original display mode first, so it won't be left behind when switching to a different mode
+            if let FullscreenMode::Exclusive(_) = old_mode {
+                super::monitor::set_display_mode(hmon, None);
+            }
+
+            let mode = match mode {
+                FullscreenMode::Exclusive(monitor_mode) if !super::monitor::set_display_mode(hmon, Some(monitor_mode)) => {
+                    log_warning!(LOG_CAT, "Failed to switch window '{window_id}' to exclusive fullscreen, falling back to borderless fullscreen");
+                    FullscreenMode::Borderless
+                },
+                mode => mode,
             };
 
+            let fullscreen = mode != FullscreenMode::Windowed;
             settings.flags.set(Flags::Fullscreen, fullscreen);
+            if let Err(err) = self.update_style_from_settings(settings) {
+                log_warning!(LOG_CAT, "Failed to update the style of window '{window_id}' for fullscreen (err: {err:X})");
+            }
+            let (style, ex_style) = get_win32_style(settings);
+            settings.margins = calculate_margins(style, ex_style, settings.dpi);
 
             if fullscreen {
-                let mon_rect = super::monitor::get_monitor_rect(hmon);
-                if let Some(rect) = mon_rect {
-                    let res = SetWindowPos(
-                        self.hwnd,
-                        HWND(0),
-                        rect.x,
-                        rect.y,
-                        rect.width as i32,
-                        rect.height as i32,
-                        SWP_NOZORDER,
-                    );
-                    if let Err(err) = res {
-                        settings.flags.set(Flags::Fullscreen, false);
-                        log_warning!(LOG_CAT, "Failed to set fullscreen position and size ({err})");
-                    }
-                } else {
-                    settings.flags.set(Flags::Fullscreen, false);
-                    log_warning!(LOG_CAT, "Failed to get monitor rect to set the fullscreen size and position");
-                }
+                self.fit_to_monitor(window_id, hmon);
             } else {
                 let res = SetWindowPlacement(self.hwnd, &os_data.windowed_state);
                 if let Err(err) = res {
-                    settings.flags.set(Flags::Fullscreen, false);
-                    log_warning!(LOG_CAT, "Failed to get monitor rect to set the fullscreen size and position ({err})");
+                    log_warning!(LOG_CAT, "Failed to restore the pre-fullscreen window state of window '{window_id}' ({err})");
                 }
             }
+            mode
+        }
+    }
+
+    /// Make the window cover the entire monitor.
+    pub(crate) fn fit_to_monitor(&mut self, window_id: WindowId, hmon: HMONITOR) {
+        unsafe {
+            // The monitor rect needs to be queried after the display mode has been changed, as it depends on the resolution
+            let Some(rect) = super::monitor::get_monitor_rect(hmon) else {
+                log_warning!(LOG_CAT, "Failed to get monitor rect to set the fullscreen size and position of window '{window_id}'");
+                return;
+            };
+
+            let res = SetWindowPos(
+                self.hwnd,
+                HWND_TOP,
+                rect.x,
+                rect.y,
+                rect.width as i32,
+                rect.height as i32,
+                SWP_NOOWNERZORDER | SWP_FRAMECHANGED,
+            );
+            if let Err(err) = res {
+                log_warning!(LOG_CAT, "Failed to set fullscreen position and size of window '{window_id}' ({err})");
+            }
         }
     }
 
@@ -233,26 +267,44 @@ impl OSWindowHandle {
             Flags::MinimizeButton | Flags::MaximizeButton => {
                 self.update_style_from_settings(settings)
             }
+            Flags::ClickThrough => {
+                self.update_style_from_settings(settings)?;
+                self.update_layered_attributes(settings)
+            }
             _ => Ok(()),
         }
     }
 
+    pub(crate) fn set_opacity(&mut self, window_id: WindowId, settings: &mut WindowSettings, opacity: f32) {
+        let old_opacity = settings.opacity;
+        settings.opacity = opacity;
+
+        // Changing the opacity can add or remove `WS_EX_LAYERED`, so the style needs to be updated before the alpha can be set
+        let res = self.update_style_from_settings(settings).and_then(|_| self.update_layered_attributes(settings));
+        if let Err(err) = res {
+            settings.opacity = old_opacity;
+            log_warning!(LOG_CAT, "Failed to set the opacity of window '{window_id}'. (err: {:X})", err);
+        }
+    }
+
+    /// Update the alpha of a layered window, a layered window is not drawn until this has been called
+    pub(crate) fn update_layered_attributes(&self, settings: &WindowSettings) -> Result<(), u32> {
+        if !is_layered(settings) {
+            return Ok(());
+        }
+
+        let alpha = (settings.opacity() * 255.0).round() as u8;
+        unsafe { SetLayeredWindowAttributes(self.hwnd, COLORREF(0), alpha, LWA_ALPHA) }.map_err(|err| err.code().0 as u32)
+    }
+
     pub(crate) fn set_border_style(&mut self, window_id: WindowId, settings: &mut WindowSettings, border_style: BorderStyle) {
         let old_style = settings.border_style();
         settings.border = border_style;
 
         match self.update_style_from_settings(settings) {
             Ok(_) => {
-                let mut margins = RECT::default();
                 let (style, style_ex) = get_win32_style(settings);
-                let res = unsafe { AdjustWindowRectEx(&mut margins, style, BOOL(0), style_ex) };
-                assert!(res.is_ok(), "This should not fail!");
-                settings.margins = Margins {
-                    top: (-margins.top) as u16,
-                    left: (-margins.left) as u16,
-                    bottom: margins.bottom as u16,
-                    right: margins.right as u16,
-                };
+                settings.margins = calculate_margins(style, style_ex, settings.dpi);
             }
             Err(err) =>
             {
@@ -389,6 +441,7 @@ impl OSWindowHandle {
                 // TODO(jel): How many times should we flash the window?
                 AttentionType::Informative => (FLASHW_TRAY | FLASHW_TIMERNOFG, 0),
                 AttentionType::Critical => (FLASHW_ALL | FLASHW_TIMERNOFG, u32::MAX),
+                AttentionType::Flash(count) => (FLASHW_ALL, count),
             };
 
             let flash_info = FLASHWINFO {
@@ -403,6 +456,130 @@ impl OSWindowHandle {
         }
     }
 
+    pub(crate) fn set_icons(&mut self, _window_id: WindowId, icon: Option<&Icon>, small_icon: Option<&Icon>) {
+        unsafe {
+            // A null icon makes the window fall back to the icon of its window class
+            let hicon = icon.map_or(HICON(0), |icon| icon.get_os_icon().hicon());
+            let hicon_sm = small_icon.map_or(HICON(0), |icon| icon.get_os_icon().hicon());
+            SendMessageA(self.hwnd, WM_SETICON, WPARAM(ICON_BIG as usize), LPARAM(hicon.0));
+            SendMessageA(self.hwnd, WM_SETICON, WPARAM(ICON_SMALL as usize), LPARAM(hicon_sm.0));
+        }
+    }
+
+    pub(crate) fn set_taskbar_progress(&mut self, window_id: WindowId, manager: &WindowManagerData, progress: TaskbarProgress) {
+        let Some(taskbar_list) = manager.taskbar_list() else { return };
+
+        unsafe {
+            // The progress value is a fraction, so use a large enough total to have a precision higher than a single percent
+            const PROGRESS_TOTAL: u64 = 10000;
+            let (state, value) = match progress {
+                TaskbarProgress::None => (TBPF_NOPROGRESS, None),
+                TaskbarProgress::Indeterminate => (TBPF_INDETERMINATE, None),
+                TaskbarProgress::Normal(percentage) => (TBPF_NORMAL, Some(percentage)),
+                TaskbarProgress::Paused(percentage) => (TBPF_PAUSED, Some(percentage)),
+                TaskbarProgress::Error(percentage) => (TBPF_ERROR, Some(percentage)),
+            };
+
+            let mut res = taskbar_list.SetProgressState(self.hwnd, state);
+            if let (Ok(_), Some(percentage)) = (&res, value) {
+                let completed = (percentage.clamp(0.0, 100.0) / 100.0 * PROGRESS_TOTAL as f32) as u64;
+                res = taskbar_list.SetProgressValue(self.hwnd, completed, PROGRESS_TOTAL);
+            }
+            if let Err(err) = res {
+                log_warning!(LOG_CAT, "Failed to set the taskbar progress of window '{window_id}' to {progress:?}. ({err})");
+            }
+        }
+    }
+
+    pub(crate) fn set_ime_allowed(&mut self, window_id: WindowId, allowed: bool) {
+        unsafe {
+            // Associating a null context disables the IME, while `IACE_DEFAULT` restores the default context of the window
+            let res = if allowed {
+                ImmAssociateContextEx(self.hwnd, HIMC::default(), IACE_DEFAULT)
+            } else {
+                ImmAssociateContextEx(self.hwnd, HIMC::default(), 0)
+            };
+            if !res.as_bool() {
+                log_warning!(LOG_CAT, "Failed to {} the IME for window '{window_id}'", if allowed { "enable" } else { "disable" });
+            }
+        }
+    }
+
+    pub(crate) fn set_ime_candidate_rect(&mut self, window_id: WindowId, pos: PhysicalPosition, size: PhysicalSize) {
+        unsafe {
+            let himc = ImmGetContext(self.hwnd);
+            if himc.is_invalid() {
+                return;
+            }
+
+            let candidate_form = CANDIDATEFORM {
+                dwIndex: 0,
+                dwStyle: CFS_EXCLUDE,
+                ptCurrentPos: POINT { x: pos.x, y: pos.y },
+                rcArea: RECT { left: pos.x, top: pos.y, right: pos.x + size.width as i32, bottom: pos.y + size.height as i32 },
+            };
+            if !ImmSetCandidateWindow(himc, &candidate_form).as_bool() {
+                log_warning!(LOG_CAT, "Failed to set the IME candidate window location for window '{window_id}'");
+            }
+            ImmReleaseContext(self.hwnd, himc);
+        }
+    }
+
+    pub(crate) fn apply_cursor_grab(&mut self, window_id: WindowId, mode: CursorGrabMode) {
+        unsafe {
+            let res = match mode {
+                CursorGrabMode::None => ClipCursor(None),
+                CursorGrabMode::Confined | CursorGrabMode::Locked => {
+                    let mut rect = RECT::default();
+                    if GetClientRect(self.hwnd, &mut rect).is_err() {
+                        log_warning!(LOG_CAT, "Failed to get the client area of window '{window_id}' to grab the cursor");
+                        return;
+                    }
+
+                    let mut top_left = POINT { x: rect.left, y: rect.top };
+                    let mut bottom_right = POINT { x: rect.right, y: rect.bottom };
+                    ClientToScreen(self.hwnd, &mut top_left);
+                    ClientToScreen(self.hwnd, &mut bottom_right);
+
+                    let clip_rect = if mode == CursorGrabMode::Locked {
+                        let center = POINT { x: (top_left.x + bottom_right.x) / 2, y: (top_left.y + bottom_right.y) / 2 };
+                        RECT { left: center.x, top: center.y, right: center.x + 1, bottom: center.y + 1 }
+                    } else {
+                        RECT { left: top_left.x, top: top_left.y, right: bottom_right.x, bottom: bottom_right.y }
+                    };
+                    ClipCursor(Some(&clip_rect as *const RECT))
+                },
+            };
+            if let Err(err) = res {
+                log_warning!(LOG_CAT, "Failed to set the cursor grab of window '{window_id}' to {mode:?} ({err})");
+            }
+        }
+    }
+
+    pub(crate) fn release_cursor_grab(&mut self) {
+        _ = unsafe { ClipCursor(None) };
+    }
+
+    pub(crate) fn update_cursor(&mut self, cursor: &WindowCursor, visible: bool) {
+        // Only update the cursor when it's over the client area, otherwise it will be updated on the next `WM_SETCURSOR`
+        unsafe {
+            let mut point = POINT::default();
+            if GetCursorPos(&mut point).is_err() || WindowFromPoint(point) != self.hwnd {
+                return;
+            }
+
+            let mut rect = RECT::default();
+            ScreenToClient(self.hwnd, &mut point);
+            if GetClientRect(self.hwnd, &mut rect).is_err() ||
+                point.x < rect.left || point.x >= rect.right || point.y < rect.top || point.y >= rect.bottom
+            {
+                return;
+            }
+
+            SetCursor(if visible { get_hcursor(cursor) } else { HCURSOR(0) });
+        }
+    }
+
     pub(crate) unsafe fn destroy(&mut self) {
         let res = DestroyWindow(self.hwnd);
         if let Err(err)= res {
@@ -419,6 +596,8 @@ pub(crate) struct OSWindowData {
     drop_handler: Option<DropHandler>,
     /// Window state before maximizing
     windowed_state: WINDOWPLACEMENT,
+    /// High surrogate of a UTF-16 character received via `WM_CHAR`, waiting for its low surrogate
+    high_surrogate: Option<u16>,
 }
 
 impl OSWindowData {
@@ -427,11 +606,13 @@ impl OSWindowData {
             Self {
                 drop_handler: Some(Self::create_and_register_drop_handler(window)),
                 windowed_state: WINDOWPLACEMENT::default(),
+                high_surrogate: None,
             }
         } else {
             Self {
                 drop_handler: None,
                 windowed_state: WINDOWPLACEMENT::default(),
+                high_surrogate: None,
             }
         }
     }
@@ -441,8 +622,16 @@ impl OSWindowData {
         if accepts_files {
             window.os_data.drop_handler =
                 Some(Self::create_and_register_drop_handler(window));
-        } else {
-            window.os_data.drop_handler = None;
+        } else if window.os_data.drop_handler.take().is_some() {
+            let res = unsafe { RevokeDragDrop(window.os_handle().hwnd()) };
+            if let Err(err) = res {
+                log_error!(
+                    LOG_CAT,
+                    "Failed to revoke drop handler for window {}. (HRESULT: {:X})",
+                    window.id(),
+                    err.code().0
+                );
+            }
         }
     }
 
@@ -469,6 +658,30 @@ impl OSWindowData {
     }
 }
 
+/// Get a composition string of the current IME composition, and the cursor position within the string, as a byte offset.
+unsafe fn get_ime_composition_string(hwnd: HWND, kind: IME_COMPOSITION_STRING) -> Option<(String, usize)> {
+    let himc = ImmGetContext(hwnd);
+    if himc.is_invalid() {
+        return None;
+    }
+
+    // The returned size is in bytes
+    let byte_len = ImmGetCompositionStringW(himc, kind, None, 0);
+    let res = if byte_len >= 0 {
+        let mut buffer = vec![0u16; byte_len as usize / 2];
+        ImmGetCompositionStringW(himc, kind, Some(buffer.as_mut_ptr() as *mut c_void), byte_len as u32);
+
+        // The cursor position is returned in UTF-16 code units
+        let cursor = ImmGetCompositionStringW(himc, GCS_CURSORPOS, None, 0).clamp(0, buffer.len() as i32) as usize;
+        let cursor = String::from_utf16_lossy(&buffer[..cursor]).len();
+        Some((String::from_utf16_lossy(&buffer), cursor))
+    } else {
+        None
+    };
+    ImmReleaseContext(hwnd, himc);
+    res
+}
+
 unsafe fn ok_or_last_error(res: bool) -> Result<(), u32> {
     if res {
         Ok(())
@@ -552,10 +765,9 @@ unsafe extern "system" fn wnd_proc(
             let x = (lparam.0 & 0xFFFF) as i16 as i32;
             let y = ((lparam.0 >> 16) & 0xFFFF) as i16 as i32;
 
-            window
-                .settings
-                .pos_to_physical_pos(PhysicalPosition::new(x, y).into());
+            window.settings.position = Position::Client(PhysicalPosition::new(x, y).into());
             window.settings.flags.set(Flags::HasMoved, true);
+            reapply_cursor_grab(window);
             PROCESSED
         }
         // While the win32 documentation says it's more efficient to handle this in WM_WINDOWPOSCHANGED, that message does not provide info a minimized/maximized
@@ -612,8 +824,18 @@ unsafe extern "system" fn wnd_proc(
                 }
                 _ => {}
             }
+            reapply_cursor_grab(window);
             PROCESSED
         }
+        WM_SETCURSOR => {
+            // The low word contains the hit-test result, only change the cursor when it's over the client area
+            if (lparam.0 & 0xFFFF) as u32 == HTCLIENT {
+                SetCursor(if window.cursor_visible { get_hcursor(&window.cursor) } else { HCURSOR(0) });
+                LRESULT(1)
+            } else {
+                DefWindowProcA(hwnd, msg, wparam, lparam)
+            }
+        }
         WM_ENTERSIZEMOVE => {
             log_debug!(
                 LOG_MSG_CAT,
@@ -696,6 +918,7 @@ unsafe extern "system" fn wnd_proc(
                 window.id
             );
             if window.settings().is_dpi_aware() {
+                // The X and Y DPI are always identical
                 let dpi = wparam.0 as u16;
                 log_debug!(
                     LOG_MSG_CAT,
@@ -703,16 +926,36 @@ unsafe extern "system" fn wnd_proc(
                     window.id
                 );
 
-                let old_dpi = window.settings.dpi;
+                // With per-monitor V2 awareness, the non-client area is scaled by the OS, so the margins need to be updated for the new DPI
                 window.settings.dpi = dpi;
-                if window.settings().does_scale_with_dpi() {
-                    let rect = &*(lparam.0 as *mut RECT);
-                    window.move_to(PhysicalPosition::new(rect.left, rect.top));
-                    window.resize(PhysicalSize::new(rect.bottom as u16, rect.right as u16));
+                let (style, ex_style) = get_win32_style(&window.settings);
+                window.settings.margins = calculate_margins(style, ex_style, dpi);
+
+                // The suggested rect keeps the window at the same relative location when it's moved to a different monitor, and is scaled to the new DPI.
+                // When the window doesn't scale with the DPI, only its border is resized, keeping the same client area
+                let rect = &*(lparam.0 as *const RECT);
+                let PhysicalSize { width, height } = if window.settings().does_scale_with_dpi() {
+                    PhysicalSize::new((rect.right - rect.left) as u16, (rect.bottom - rect.top) as u16)
+                } else {
+                    window.settings.size_with_borders()
+                };
+
+                let res = SetWindowPos(
+                    hwnd,
+                    HWND(0),
+                    rect.left,
+                    rect.top,
+                    width as i32,
+                    height as i32,
+                    SWP_NOZORDER | SWP_NOACTIVATE,
+                );
+                if let Err(err) = res {
+                    log_warning!(LOG_MSG_CAT, "Failed to resize window {} for its new DPI ({err})", window.id);
                 }
 
-                let scale = dpi as f32 / old_dpi as f32;
-                window.send_window_event(WindowEvent::DpiChanged(dpi, scale));
+                let scale_factor = window.settings.dpi_scale();
+                let size = window.settings.size();
+                window.send_window_event(WindowEvent::ScaleFactorChanged{ dpi, scale_factor, size });
             }
             PROCESSED
         }
@@ -755,6 +998,29 @@ unsafe extern "system" fn wnd_proc(
             );
 
             window.settings.flags.set(Flags::Active, active != 0);
+
+            // Exclusive fullscreen should not keep the display mode changed while the user is working in another window
+            if let FullscreenMode::Exclusive(mode) = window.fullscreen_mode {
+                let hmon = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+                if active != 0 {
+                    if super::monitor::set_display_mode(hmon, Some(mode)) {
+                        window.os_handle.fit_to_monitor(window.id, hmon);
+                    }
+                } else {
+                    super::monitor::set_display_mode(hmon, None);
+                    ShowWindow(hwnd, SW_SHOWMINNOACTIVE);
+                }
+            }
+
+            // The cursor grab is global, so it needs to be released when another window becomes active, and restored when the window is activated again
+            if window.cursor_grab != CursorGrabMode::None {
+                if active != 0 {
+                    window.os_handle.apply_cursor_grab(window.id, window.cursor_grab);
+                } else {
+                    window.os_handle.release_cursor_grab();
+                }
+            }
+
             match active as u32 {
                 WA_ACTIVE => window.send_window_event(WindowEvent::Focused(minimized)),
                 WA_CLICKACTIVE => window.send_window_event(WindowEvent::Focused(minimized)),
@@ -790,6 +1056,8 @@ unsafe extern "system" fn wnd_proc(
             let height = (lparam.0 >> 16) as u16;
             log_debug!(LOG_MSG_CAT, "received WM_DISPLAYCHANGE for window {} with size {width}x{height} and {bpp} bits per pixel", window.id);
             window.send_window_event(WindowEvent::DisplayResolutionChanges(width, height, bpp));
+            // The message is sent for any change to the display configuration, including monitors being connected or disconnected
+            manager.get_os_data().notify_monitors_changed();
             PROCESSED
         }
         WM_CLOSE => {
@@ -814,6 +1082,9 @@ unsafe extern "system" fn wnd_proc(
                 "received WM_NCDESTROY for window {}",
                 window.id
             );
+            if let FullscreenMode::Exclusive(_) = window.fullscreen_mode {
+                super::monitor::set_display_mode(MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST), None);
+            }
             window.notify_destroyed();
             PROCESSED
         }
@@ -836,39 +1107,18 @@ unsafe extern "system" fn wnd_proc(
                 DragFinish(hdrop);
                 return PROCESSED;
             }
-            let drop_x = drop_point.x as u16;
-            let drop_y = drop_point.y as u16;
+            let pos = PhysicalPosition::new(drop_point.x, drop_point.y);
 
-            let num_files = DragQueryFileA(hdrop, 0xFFFF_FFFF, None);
+            let files = query_dropped_files(hdrop);
             log_debug!(
                 LOG_MSG_CAT,
-                "Dropped {num_files} files in window {}",
+                "Dropped {} files in window {}",
+                files.len(),
                 window.id
             );
-
-            let _scope_alloc: ScopedAlloc = ScopedAlloc::new(AllocId::TlsTemp);
-            for i in 0..num_files {
-                let path_len = DragQueryFileA(hdrop, i, None);
-                let mut buf = Vec::<u8>::new();
-                buf.reserve(path_len as usize);
-                buf.set_len(path_len as usize);
-
-                let bytes_written = DragQueryFileA(hdrop, i, Some(&mut buf));
-                if bytes_written == path_len {
-                    let file = String::from_utf8_unchecked(buf);
-                    log_debug!(
-                        LOG_MSG_CAT,
-                        "Dropped file '{file}' at index {i} in window {}",
-                        window.id
-                    );
-                    window.send_window_event(WindowEvent::DroppedFile(drop_x, drop_y, &file));
-                } else {
-                    log_error!(
-                        LOG_MSG_CAT,
-                        "Failed to get path of file at index {i} for window {}",
-                        window.id
-                    );
-                }
+            // There is no hover feedback for these drops, so only the window's drop effect can reject them
+            if window.drop_effect != DropEffect::None {
+                window.send_window_event(WindowEvent::FilesDropped(files, pos));
             }
             DragFinish(hdrop);
 
@@ -899,6 +1149,50 @@ unsafe extern "system" fn wnd_proc(
             window.send_window_event(WindowEvent::MouseLeave);
             PROCESSED
         },
+        WM_CHAR => {
+            let code_unit = wparam.0 as u16;
+            let ch = if (0xD800..0xDC00).contains(&code_unit) {
+                window.os_data.high_surrogate = Some(code_unit);
+                None
+            } else if (0xDC00..0xE000).contains(&code_unit) {
+                window.os_data.high_surrogate.take()
+                    .and_then(|high| char::decode_utf16([high, code_unit]).next())
+                    .and_then(|res| res.ok())
+            } else {
+                window.os_data.high_surrogate = None;
+                char::from_u32(code_unit as u32)
+            };
+
+            if let Some(ch) = ch.filter(|ch| !ch.is_control()) {
+                let mut buf = [0u8; 4];
+                window.send_window_event(WindowEvent::Text(ch.encode_utf8(&mut buf)));
+            }
+            PROCESSED
+        },
+        WM_IME_STARTCOMPOSITION => {
+            window.send_window_event(WindowEvent::ImeCompositionStarted);
+            // Don't pass it to DefWindowProc, as the composition is expected to be drawn by the application
+            PROCESSED
+        },
+        WM_IME_COMPOSITION => {
+            let flags = lparam.0 as u32;
+            if is_flag_set(flags, GCS_RESULTSTR.0) {
+                if let Some((text, _)) = get_ime_composition_string(hwnd, GCS_RESULTSTR).filter(|(text, _)| !text.is_empty()) {
+                    window.send_window_event(WindowEvent::Text(&text));
+                }
+            }
+            if is_flag_set(flags, GCS_COMPSTR.0) {
+                if let Some((text, cursor)) = get_ime_composition_string(hwnd, GCS_COMPSTR) {
+                    window.send_window_event(WindowEvent::ImeComposition { text: &text, cursor });
+                }
+            }
+            // The result string has been handled, so don't let DefWindowProc generate `WM_IME_CHAR` messages for it
+            PROCESSED
+        },
+        WM_IME_ENDCOMPOSITION => {
+            window.send_window_event(WindowEvent::ImeCompositionEnded);
+            PROCESSED
+        },
         WM_INPUT => {
             let ptr = &(wparam, lparam) as *const _ as *const u8;
             manager.process_raw_input(RawInputEvent::Input(ptr));
@@ -910,10 +1204,22 @@ unsafe extern "system" fn wnd_proc(
             manager.process_raw_input(RawInputEvent::DeviceChanged(ptr));
             PROCESSED
         }
+        // The taskbar icon is (re)created after the window is first shown or explorer has restarted, so the progress needs to be set again
+        _ if msg == manager.get_os_data().taskbar_button_created_msg() => {
+            let progress = window.taskbar_progress;
+            if progress != TaskbarProgress::None {
+                window.os_handle.set_taskbar_progress(window.id, manager.get_os_data(), progress);
+            }
+            PROCESSED
+        },
         _ => DefWindowProcA(hwnd, msg, wparam, lparam),
     }
 }
 
+pub(crate) fn raw_display_handle() -> RawDisplayHandle {
+    RawDisplayHandle::Windows
+}
+
 pub(crate) fn create(
     manager: &mut WindowManager,
     mut settings: WindowSettings
@@ -930,7 +1236,7 @@ pub(crate) fn create(
         settings.validate_dpi();
 
         let (style, ex_style) = get_win32_style(&settings);
-        settings.margins = calculate_margins(style, ex_style);
+        settings.margins = calculate_margins(style, ex_style, settings.dpi);
         let pos = settings.outer_position();
         let PhysicalSize { width, height } = settings.size_with_borders();
 
@@ -945,6 +1251,7 @@ pub(crate) fn create(
 
         let is_dpi_aware = settings.is_dpi_aware();
         let dpi = settings.dpi;
+        let fullscreen_mode = if settings.is_fullscreen() { FullscreenMode::Borderless } else { FullscreenMode::Windowed };
 
         let window = Window {
             os_handle: OSWindowHandle::null(),
@@ -955,9 +1262,18 @@ pub(crate) fn create(
             listeners: Mutex::new(EventListenerArray::new()),
             is_closing: false,
             is_destroyed: false,
+            cursor_grab: CursorGrabMode::None,
+            cursor_visible: true,
+            cursor: WindowCursor::Shape(CursorShape::Arrow),
+            drop_effect: DropEffect::Copy,
+            fullscreen_mode,
+            taskbar_progress: TaskbarProgress::None,
         };
         let mut window_ptr = Box::new(window);
 
+        // The process is per-monitor DPI aware, so a DPI unaware window needs to be created while the thread is DPI unaware, so the OS will scale it
+        let prev_dpi_context = if is_dpi_aware { None } else { Some(SetThreadDpiAwarenessContext(DPI_AWARENESS_CONTEXT_UNAWARE)) };
+
         let hwnd = CreateWindowExA(
             ex_style,
             PCSTR(atom as usize as *const u8),
@@ -973,6 +1289,10 @@ pub(crate) fn create(
             Some(&*window_ptr as *const _ as *const c_void),
         );
 
+        if let Some(prev_dpi_context) = prev_dpi_context {
+            SetThreadDpiAwarenessContext(prev_dpi_context);
+        }
+
         if hwnd == HWND(0) {
             log_error!(
                 LOG_CAT,
@@ -984,11 +1304,16 @@ pub(crate) fn create(
         window_ptr.os_handle = OSWindowHandle { hwnd };
         window_ptr.os_data = OSWindowData::new(&mut window_ptr);
 
+        if let Err(err) = window_ptr.os_handle.update_layered_attributes(window_ptr.settings()) {
+            log_warning!(LOG_CAT, "Failed to set the opacity of a window (win32 err: {:X})", err);
+        }
+
         if is_dpi_aware {
             let window_dpi = GetDpiForWindow(hwnd) as u16;
             if window_dpi != dpi {
                 let old_size = window_ptr.settings().size();
                 window_ptr.settings.dpi = window_dpi;
+                window_ptr.settings.margins = calculate_margins(style, ex_style, window_dpi);
                 let PhysicalSize { width, height } = window_ptr.settings().size_with_borders();
 
                 let res = SetWindowPos(
@@ -1058,9 +1383,17 @@ fn get_win32_style(settings: &WindowSettings) -> (WINDOW_STYLE, WINDOW_EX_STYLE)
     if style.contains(Flags::TopMost) {
         win32_style_ex |= WS_EX_TOPMOST;
     }
+    if is_layered(settings) {
+        win32_style_ex |= WS_EX_LAYERED;
+    }
+    // Hit-testing only skips a transparent window when it's also layered
+    if style.contains(Flags::ClickThrough) {
+        win32_style_ex |= WS_EX_TRANSPARENT;
+    }
 
-    let border_style = settings.border_style();
-    if style.contains(Flags::Resizable) {
+    // A fullscreen window never has a border, but keeps its border style for when it returns to windowed mode
+    let border_style = if style.contains(Flags::Fullscreen) { BorderStyle::Borderless } else { settings.border_style() };
+    if style.contains(Flags::Resizable) && !style.contains(Flags::Fullscreen) {
         if border_style == BorderStyle::Borderless {
             log_warning!(LOG_CAT, "Cannot use Resizable with Borderless window style");
         } else {
@@ -1076,6 +1409,11 @@ fn get_win32_style(settings: &WindowSettings) -> (WINDOW_STYLE, WINDOW_EX_STYLE)
     (win32_style, win32_style_ex)
 }
 
+/// Check if the window needs to be a layered window, which is required for transparency and click-through
+fn is_layered(settings: &WindowSettings) -> bool {
+    settings.opacity() < 1.0 || settings.is_click_through()
+}
+
 fn get_border_style(border_style: BorderStyle) -> WINDOW_STYLE {
     match border_style {
         BorderStyle::Borderless => WS_POPUP,
@@ -1107,10 +1445,10 @@ fn get_show_window_cmd(visible: bool, active: bool, minimized: bool, maximized:
     }
 }
 
-fn calculate_margins(style: WINDOW_STYLE, ex_style: WINDOW_EX_STYLE) -> Margins {
+fn calculate_margins(style: WINDOW_STYLE, ex_style: WINDOW_EX_STYLE, dpi: u16) -> Margins {
     unsafe {
         let mut rect = RECT::default();
-        let res = AdjustWindowRectEx(&mut rect, style, BOOL(0), ex_style);
+        let res = AdjustWindowRectExForDpi(&mut rect, style, BOOL(0), ex_style, dpi as u32);
         if let Err(err) = res {
             log_warning!(LOG_CAT, "Failed to calculate window margins (win32 err: {err})");
         }
@@ -1124,6 +1462,13 @@ fn calculate_margins(style: WINDOW_STYLE, ex_style: WINDOW_EX_STYLE) -> Margins
     }
 }
 
+/// Re-apply the cursor grab after the client area of the window has moved or changed size.
+fn reapply_cursor_grab(window: &mut Window) {
+    if window.cursor_grab != CursorGrabMode::None && window.settings.is_active() {
+        window.os_handle.apply_cursor_grab(window.id, window.cursor_grab);
+    }
+}
+
 fn cursor_coords() -> Option<POINTS> {
     let mut points = POINT::default();
     let res = unsafe { GetCursorPos(&mut points) };
@@ -7,19 +7,22 @@ use onca_common::{
     sys::get_app_handle,
 };
 
-use onca_logging::{log_error, log_debug};
+use onca_logging::{log_error, log_debug, log_warning};
 use windows::{
     Win32::{
         UI::WindowsAndMessaging::{
-                RegisterClassExA, PeekMessageA, TranslateMessage, DispatchMessageA,
+                RegisterClassExA, PeekMessageA, TranslateMessage, DispatchMessageA, RegisterWindowMessageA,
                 WNDCLASSEXA, HICON, HCURSOR, MSG,
                 CS_HREDRAW, CS_VREDRAW,
                 PM_REMOVE, 
             },
-        Foundation::{WPARAM, LPARAM, HWND, LRESULT, GetLastError},
+        UI::HiDpi::{SetProcessDpiAwarenessContext, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2},
+        UI::Shell::{ITaskbarList3, TaskbarList},
+        System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER},
+        Foundation::{WPARAM, LPARAM, HWND, LRESULT, GetLastError, ERROR_ACCESS_DENIED},
         Graphics::Gdi::{COLOR_BACKGROUND, HBRUSH}
     },
-    core::PCSTR
+    core::{PCSTR, s}
 };
 
 use crate::{WindowSettings, LOG_CAT};
@@ -32,12 +35,55 @@ pub(crate) struct WndClassExKey {
 }
 
 pub(crate) struct WindowManagerData {
-    wnd_classes : HashMap<WndClassExKey, (u16, u16)>,
+    wnd_classes            : HashMap<WndClassExKey, (u16, u16)>,
+    /// Interface used to show progress on the taskbar icons of windows
+    taskbar_list           : Option<ITaskbarList3>,
+    /// Message sent to a window when its taskbar icon has been created
+    taskbar_button_created : u32,
+    /// Has the display configuration changed since the last tick
+    monitors_changed       : bool,
 }
 
 impl WindowManagerData {
     pub(crate) fn new() -> Self {
-        Self { wnd_classes: HashMap::new() }
+        // Per-monitor V2 awareness lets the OS scale the non-client area and notify each window when it is moved to a monitor with a different DPI.
+        // Windows that are not DPI aware will be created in a DPI unaware context, so the OS will scale them instead.
+        let res = unsafe { SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2) };
+        match res {
+            Ok(_) => log_debug!(LOG_CAT, "Set process DPI awareness to per-monitor V2"),
+            // The awareness has already been set, e.g. by the application manifest
+            Err(err) if err.code() == ERROR_ACCESS_DENIED.to_hresult() => log_debug!(LOG_CAT, "Process DPI awareness was already set"),
+            Err(err) => log_warning!(LOG_CAT, "Failed to set process DPI awareness to per-monitor V2, DPI changes may not be handled correctly ({err})"),
+        }
+
+        let taskbar_button_created = unsafe { RegisterWindowMessageA(s!("TaskbarButtonCreated")) };
+        let taskbar_list = unsafe { CoCreateInstance::<_, ITaskbarList3>(&TaskbarList, None, CLSCTX_INPROC_SERVER) }
+            .and_then(|taskbar_list| unsafe { taskbar_list.HrInit().map(|_| taskbar_list) });
+        let taskbar_list = match taskbar_list {
+            Ok(taskbar_list) => Some(taskbar_list),
+            Err(err) => {
+                log_warning!(LOG_CAT, "Failed to create the taskbar list, progress will not be shown on the taskbar ({err})");
+                None
+            },
+        };
+
+        Self { wnd_classes: HashMap::new(), taskbar_list, taskbar_button_created, monitors_changed: false }
+    }
+
+    pub(crate) fn taskbar_list(&self) -> Option<&ITaskbarList3> {
+        self.taskbar_list.as_ref()
+    }
+
+    pub(crate) fn taskbar_button_created_msg(&self) -> u32 {
+        self.taskbar_button_created
+    }
+
+    pub(crate) fn notify_monitors_changed(&mut self) {
+        self.monitors_changed = true;
+    }
+
+    pub(crate) fn take_monitors_changed(&mut self) -> bool {
+        core::mem::take(&mut self.monitors_changed)
     }
 
     pub(crate) fn register_wndclassex(&mut self, settings: &WindowSettings, wnd_proc: unsafe extern "system" fn(HWND, u32, WPARAM, LPARAM) -> LRESULT) -> Option<u16> {
@@ -0,0 +1,132 @@
+use core::{
+    ffi::c_void,
+    num::{NonZeroIsize, NonZeroU32},
+    ptr::NonNull,
+};
+
+use crate::{os, Window};
+
+/// Raw platform handle of a window.
+///
+/// This allows external libraries (e.g. video playback, overlays or alternative renderers) to interoperate with a window, without needing access to the OS specific code.
+/// The handle is only valid as long as the window has not been destroyed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum RawWindowHandle {
+    /// Win32 window.
+    Win32 {
+        /// Window handle (`HWND`).
+        hwnd:      NonZeroIsize,
+        /// Module handle of the application that created the window (`HINSTANCE`).
+        hinstance: Option<NonZeroIsize>,
+    },
+    /// X11 window, using Xlib.
+    Xlib {
+        /// X11 window id.
+        window:    u64,
+        /// Id of the visual used by the window, 0 if unknown.
+        visual_id: u64,
+    },
+    /// X11 window, using xcb.
+    Xcb {
+        /// X11 window id.
+        window:    NonZeroU32,
+        /// Id of the visual used by the window.
+        visual_id: Option<NonZeroU32>,
+    },
+    /// Wayland surface.
+    Wayland {
+        /// Pointer to the `wl_surface`.
+        surface: NonNull<c_void>,
+    },
+}
+
+/// Raw platform handle of the display server a window is connected to.
+///
+/// This is needed next to a [`RawWindowHandle`] on platforms where a window can't be used without a connection to its display server, e.g. to create a Vulkan surface on X11 or Wayland.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[non_exhaustive]
+pub enum RawDisplayHandle {
+    /// Windows, which does not need a display handle.
+    Windows,
+    /// X11 display, using Xlib.
+    Xlib {
+        /// Pointer to the Xlib `Display`.
+        display: Option<NonNull<c_void>>,
+        /// Screen number.
+        screen:  i32,
+    },
+    /// X11 display, using xcb.
+    Xcb {
+        /// Pointer to the `xcb_connection_t`.
+        connection: Option<NonNull<c_void>>,
+        /// Screen number.
+        screen:     i32,
+    },
+    /// Wayland display.
+    Wayland {
+        /// Pointer to the `wl_display`.
+        display: NonNull<c_void>,
+    },
+}
+
+impl Window {
+    /// Get the raw platform handle of the window.
+    pub fn raw_window_handle(&self) -> RawWindowHandle {
+        self.os_handle.raw_window_handle()
+    }
+
+    /// Get the raw platform handle of the display server the window is connected to.
+    pub fn raw_display_handle(&self) -> RawDisplayHandle {
+        os::raw_display_handle()
+    }
+}
+
+#[cfg(feature = "raw_window_handle")]
+mod raw_window_handle_impl {
+    use raw_window_handle as rwh;
+    use crate::Window;
+    use super::{RawWindowHandle, RawDisplayHandle};
+
+    impl rwh::HasWindowHandle for Window {
+        fn window_handle(&self) -> Result<rwh::WindowHandle<'_>, rwh::HandleError> {
+            if self.is_destroyed {
+                return Err(rwh::HandleError::Unavailable);
+            }
+
+            let handle = match self.raw_window_handle() {
+                RawWindowHandle::Win32 { hwnd, hinstance } => {
+                    let mut handle = rwh::Win32WindowHandle::new(hwnd);
+                    handle.hinstance = hinstance;
+                    rwh::RawWindowHandle::Win32(handle)
+                },
+                RawWindowHandle::Xlib { window, visual_id } => {
+                    let mut handle = rwh::XlibWindowHandle::new(window as _);
+                    handle.visual_id = visual_id as _;
+                    rwh::RawWindowHandle::Xlib(handle)
+                },
+                RawWindowHandle::Xcb { window, visual_id } => {
+                    let mut handle = rwh::XcbWindowHandle::new(window);
+                    handle.visual_id = visual_id;
+                    rwh::RawWindowHandle::Xcb(handle)
+                },
+                RawWindowHandle::Wayland { surface } => rwh::RawWindowHandle::Wayland(rwh::WaylandWindowHandle::new(surface)),
+            };
+            // SAFETY: The window can only be destroyed by the window manager, which needs mutable access to the window, so it stays valid while the handle is borrowed
+            Ok(unsafe { rwh::WindowHandle::borrow_raw(handle) })
+        }
+    }
+
+    impl rwh::HasDisplayHandle for Window {
+        fn display_handle(&self) -> Result<rwh::DisplayHandle<'_>, rwh::HandleError> {
+            let handle = match self.raw_display_handle() {
+                RawDisplayHandle::Windows => rwh::RawDisplayHandle::Windows(rwh::WindowsDisplayHandle::new()),
+                RawDisplayHandle::Xlib { display, screen } => rwh::RawDisplayHandle::Xlib(rwh::XlibDisplayHandle::new(display, screen)),
+                RawDisplayHandle::Xcb { connection, screen } => rwh::RawDisplayHandle::Xcb(rwh::XcbDisplayHandle::new(connection, screen)),
+                RawDisplayHandle::Wayland { display } => rwh::RawDisplayHandle::Wayland(rwh::WaylandDisplayHandle::new(display)),
+            };
+            // SAFETY: The display connection outlives all windows
+            Ok(unsafe { rwh::DisplayHandle::borrow_raw(handle) })
+        }
+    }
+}
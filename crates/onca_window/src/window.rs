@@ -1,6 +1,6 @@
 use crate::{
-    os, BorderStyle, Flags, PhysicalPosition, PhysicalSize, PixelPos, Size, WindowManager,
-    WindowSettings, LOG_CAT,
+    os, BorderStyle, Cursor, CursorShape, Flags, Icon, MonitorMode, PhysicalPosition, PhysicalSize, PixelPos, Size, WindowManager,
+    WindowCursor, WindowSettings, LOG_CAT,
 };
 use core::fmt;
 use onca_common::{
@@ -9,9 +9,10 @@ use onca_common::{
     sync::Mutex,
 };
 use onca_logging::log_warning;
+use std::path::PathBuf;
 
 /// Window handle
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
 pub struct WindowId(pub(crate) u32);
 
 impl fmt::Display for WindowId {
@@ -31,10 +32,11 @@ pub enum WindowEvent<'a> {
     Moved(PhysicalPosition),
     /// The window has been resized.
     Resized(PhysicalSize),
-    /// The DPI of the window has changed.
+    /// The DPI of the window has changed, e.g. because it was moved to a monitor with a different scale.
     ///
-    /// The event reports the new dpi value and the scaling factor to go from the old DPI to the new DPI.
-    DpiChanged(u16, f32),
+    /// The event reports the new DPI, the new scale factor to go from logical to physical pixels, and the new size of the client area.
+    /// If the window scales with the DPI, it has already been resized before this event is sent.
+    ScaleFactorChanged{ dpi: u16, scale_factor: f32, size: PhysicalSize },
     /// The window has been maximized.
     ///
     /// This event will be followed by a `Resized` event.
@@ -43,6 +45,10 @@ pub enum WindowEvent<'a> {
     ///
     /// This event will be followed by a `Resized` event.
     Minimized,
+    /// The fullscreen mode of the window has changed.
+    ///
+    /// The window has already been resized and a `Resized` event has been sent, which is where any swap-chain of the window should be resized.
+    FullscreenChanged(FullscreenMode),
     /// The window has been restored after being maximized ofr minimized.
     ///
     /// This event will be followed by a `Resized` event.
@@ -85,20 +91,26 @@ pub enum WindowEvent<'a> {
     ///
     /// The event reports the new resolution and bpp (bits per pixel) in the following order: (width, height, bpp).
     DisplayResolutionChanges(u16, u16, u8),
-    /// Hovering files have entered the window.
+    /// Files are being dragged into the window.
+    ///
+    /// This event provides the paths of the hovering files, the position of the cursor (in the client area) and a callback to set the feedback shown to the user.
+    /// If the callback is not called, the window's drop effect will be used, see [`Window::set_drop_effect`].
+    FilesHoverStarted{ files: &'a [PathBuf], pos: PhysicalPosition, set_effect: &'a dyn Fn(DropEffect) },
+    /// The files being dragged over the window have moved.
     ///
-    /// This event provides the window coordinates where the file is hovering (in the client area) and a path to the hovering file.
-    HoverFileStarted(u16, u16, &'a str),
-    /// Update the location where files are hovering above the window.
+    /// This event provides the position of the cursor (in the client area) and a callback to change the feedback shown to the user,
+    /// e.g. to only accept files while they are hovering over a specific panel.
+    /// If the callback is not called, the last effect will be kept.
+    FilesHoverMoved{ pos: PhysicalPosition, set_effect: &'a dyn Fn(DropEffect) },
+    /// The files being dragged over the window have left the window, or the drag was cancelled.
+    FilesHoverEnded,
+    /// Files have been dropped in the window.
     ///
-    /// This event provides the window coordinates where the file is hovering (in the client area).
-    HoverFileTick(u16, u16),
-    /// A files has been dropped in the window.
+    /// This event provides the paths of the dropped files and the position where they were dropped (in the client area).
+    /// This ends the hover, so it won't be followed by a `FilesHoverEnded` event.
     ///
-    /// This event provides the window coordinates where the file was dropped (in the client area) and a path to the dropped file.
-    DroppedFile(u16, u16, &'a str),
-    /// All files that were being hovered over the window are not hovering anymore.
-    HoverFileHoverEnded,
+    /// Files dropped while the effect was [`DropEffect::None`] are rejected, and will result in a `FilesHoverEnded` event instead.
+    FilesDropped(Vec<PathBuf>, PhysicalPosition),
     /// The window is requested to be closed and is checking callbacks to see if it is allowed to close.
     ///
     /// If `false` is returned, all subsequent callbacks will still be processed, and the event will notify the callback that the closing was interruped.
@@ -118,6 +130,21 @@ pub enum WindowEvent<'a> {
     ///
     /// This event will be preceeded with a `MouseMove` event containing the location where the mouse has left.
     MouseLeave,
+
+    /// Text has been entered in the window.
+    ///
+    /// This event contains the committed text, either typed directly or the result of an IME composition, control characters are not included.
+    Text(&'a str),
+    /// An IME composition has started.
+    ImeCompositionStarted,
+    /// The IME composition has been updated.
+    ///
+    /// The event contains the current composition string and the byte offset of the cursor within the composition string.
+    ImeComposition{ text: &'a str, cursor: usize },
+    /// The IME composition has ended.
+    ///
+    /// Any resulting text will be sent using a `Text` event.
+    ImeCompositionEnded,
 }
 
 /// Direction into which to resize the window
@@ -152,6 +179,65 @@ pub enum AttentionType {
     ///
     /// On windows, this will flash both the taskbar icon and the window itself
     Critical,
+    /// Flash the window and its taskbar icon a number of times
+    ///
+    /// Not all OSes support flashing a window a specific number of times, in which case this is the same as `Informative`
+    Flash(u32),
+}
+
+/// Progress shown on the taskbar icon of a window
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TaskbarProgress {
+    /// No progress is shown
+    None,
+    /// An operation is in progress, but the amount of progress is unknown
+    Indeterminate,
+    /// An operation is in progress, with a percentage in the range `[0, 100]`
+    Normal(f32),
+    /// An operation is paused, with a percentage in the range `[0, 100]`
+    Paused(f32),
+    /// An operation has failed, with the percentage at which it failed, in the range `[0, 100]`
+    Error(f32),
+}
+
+/// Defines how the cursor is grabbed by the window
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CursorGrabMode {
+    /// The cursor can freely move in and out of the window
+    None,
+    /// The cursor is confined to the client area of the window
+    Confined,
+    /// The cursor is locked in place, at the center of the client area of the window
+    ///
+    /// This is meant to be used together with raw relative mouse motion, e.g. for first person camera control
+    Locked,
+}
+
+/// Defines if and how the window covers its entire monitor
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FullscreenMode {
+    /// The window is a regular window
+    Windowed,
+    /// The window is a borderless window covering the entire monitor, without changing the display mode of the monitor
+    Borderless,
+    /// The window covers the entire monitor, which is switched to the given display mode
+    ///
+    /// The mode should be one of the modes supported by the monitor, see `Monitor::modes()` and `Monitor::find_mode()`.
+    /// The display mode is only applied while the window is active, the window is minimized and the original display mode restored when it's deactivated.
+    Exclusive(MonitorMode),
+}
+
+/// Feedback shown to the user while files are being dragged over the window
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DropEffect {
+    /// The files cannot be dropped in the window
+    None,
+    /// The files will be copied
+    Copy,
+    /// The files will be moved
+    Move,
+    /// A link to the files will be created
+    Link,
 }
 
 // NOTE(jel): We currently aren't supporting menus, as we will have a custom window border (including min/max buttons), but we may need to look into it for an OS with a global menu bar (like MacOS)
@@ -167,6 +253,12 @@ pub struct Window {
     pub(crate) listeners: Mutex<EventListenerArray<WindowEventListener>>,
     pub(crate) is_closing: bool,
     pub(crate) is_destroyed: bool,
+    pub(crate) cursor_grab: CursorGrabMode,
+    pub(crate) cursor_visible: bool,
+    pub(crate) cursor: WindowCursor,
+    pub(crate) drop_effect: DropEffect,
+    pub(crate) fullscreen_mode: FullscreenMode,
+    pub(crate) taskbar_progress: TaskbarProgress,
 }
 
 impl Window {
@@ -288,13 +380,28 @@ impl Window {
         self.os_handle.restore(self.id);
     }
 
-    /// Set the monitor to fullscreen
-    pub fn set_fullscreen(&mut self, fullscreen: bool) {
-        if fullscreen == self.settings.is_fullscreen() {
+    /// Set the fullscreen mode of the window
+    ///
+    /// The window will cover the monitor it currently overlaps the most, and its previous placement will be restored when it returns to windowed mode.
+    /// If the display mode cannot be changed for exclusive fullscreen, the window will fall back to borderless fullscreen.
+    ///
+    /// The window will be resized, sending a `Resized` event, followed by a `FullscreenChanged` event with the mode that was actually applied.
+    pub fn set_fullscreen(&mut self, mode: FullscreenMode) {
+        if mode == self.fullscreen_mode {
             return;
         }
 
-        self.os_handle.set_fullscreen(fullscreen, &mut self.os_data, &mut self.settings);
+        let old_mode = self.fullscreen_mode;
+        let new_mode = self.os_handle.set_fullscreen(self.id, old_mode, mode, &mut self.os_data, &mut self.settings);
+        self.fullscreen_mode = new_mode;
+        if new_mode != old_mode {
+            self.send_window_event(WindowEvent::FullscreenChanged(new_mode));
+        }
+    }
+
+    /// Get the fullscreen mode of the window
+    pub fn fullscreen_mode(&self) -> FullscreenMode {
+        self.fullscreen_mode
     }
 
     /// Try to put the window in focus
@@ -304,6 +411,7 @@ impl Window {
         self.os_handle.bring_to_front(self.id);
     }
 
+    /// Set whether the window is always shown above other windows
     pub fn set_topmost(&mut self, topmost: bool) {
         if self.settings().is_top_most() == topmost {
             return;
@@ -312,6 +420,29 @@ impl Window {
         self.os_handle.set_topmost(self.id, topmost);
     }
 
+    /// Set the opacity of the window, ranging from 0 (fully transparent) to 1 (fully opaque)
+    pub fn set_opacity(&mut self, opacity: f32) {
+        let opacity = opacity.clamp(0.0, 1.0);
+        if opacity == self.settings().opacity() {
+            return;
+        }
+
+        self.os_handle.set_opacity(self.id, &mut self.settings, opacity);
+    }
+
+    /// Set whether mouse input passes through the window, to whatever is below it
+    ///
+    /// Combined with `set_topmost` and `set_opacity`, this allows overlay windows, e.g. for debug HUDs.
+    pub fn set_click_through(&mut self, enable: bool) {
+        if enable == self.settings().is_click_through() {
+            return;
+        }
+
+        if !self.set_flag(Flags::ClickThrough, enable) {
+            log_warning!(LOG_CAT, "Failed to set if window '{}' is click-through", self.id);
+        }
+    }
+
     /// Enable or disable input to the window
     pub fn enable_input(&mut self, enable: bool) {
         if enable == self.settings().does_accept_input() {
@@ -397,12 +528,25 @@ impl Window {
             return;
         }
 
+        self.settings.flags.set(Flags::AcceptFiles, enable);
         scoped_alloc!(unsafe { (*self.manager).allocator_id() });
         os::OSWindowData::set_accept_files(self,)
     }
 
+    /// Set the feedback shown to the user while files are being dragged over the window.
+    ///
+    /// This is the effect used when a `FilesHoverStarted` listener doesn't set one, and defaults to [`DropEffect::Copy`].
+    pub fn set_drop_effect(&mut self, effect: DropEffect) {
+        self.drop_effect = effect;
+    }
+
+    /// Get the feedback shown to the user while files are being dragged over the window.
+    pub fn drop_effect(&self) -> DropEffect {
+        self.drop_effect
+    }
+
     fn set_flag(&mut self, flag: Flags, enable: bool) -> bool {
-        self.settings.flags.set(Flags::MinimizeButton, enable);
+        self.settings.flags.set(flag, enable);
         let res = self.os_handle.set_flag(self.settings(), flag, enable);
         match res {
             Ok(_) => true,
@@ -435,6 +579,90 @@ impl Window {
         self.os_handle.notify_user(self.id, attention);
     }
 
+    /// Set the window's icons, the previous icons will be replaced.
+    /// 
+    /// If no icon is given, the window will use the default icon of the OS.
+    pub fn set_icon(&mut self, icon: Option<Icon>, small_icon: Option<Icon>) {
+        self.os_handle.set_icons(self.id, icon.as_ref(), small_icon.as_ref());
+        // The old icons are only destroyed after the window has stopped using them
+        self.settings.icon = icon;
+        self.settings.icon_sm = small_icon;
+    }
+
+    /// Show the progress of an operation on the taskbar icon of the window.
+    /// 
+    /// This allows the progress of long operations to be seen, even when the window is minimized.
+    pub fn set_taskbar_progress(&mut self, progress: TaskbarProgress) {
+        self.taskbar_progress = progress;
+        let os_data = unsafe { (*self.manager).get_os_data() };
+        self.os_handle.set_taskbar_progress(self.id, os_data, progress);
+    }
+
+    /// Get the progress currently shown on the taskbar icon of the window.
+    pub fn taskbar_progress(&self) -> TaskbarProgress {
+        self.taskbar_progress
+    }
+
+    /// Set whether the window allows an IME (input method editor) to be used for text entry.
+    pub fn set_ime_allowed(&mut self, allowed: bool) {
+        self.os_handle.set_ime_allowed(self.id, allowed);
+    }
+
+    /// Set the area of the text being composed (in the client area), so the IME can place its candidate window next to it, without covering it.
+    pub fn set_ime_candidate_rect(&mut self, pos: PhysicalPosition, size: PhysicalSize) {
+        self.os_handle.set_ime_candidate_rect(self.id, pos, size);
+    }
+
+    /// Set how the cursor is grabbed by the window.
+    ///
+    /// The grab is only applied while the window is active, and is automatically restored when the window is activated again.
+    pub fn set_cursor_grab(&mut self, mode: CursorGrabMode) {
+        self.cursor_grab = mode;
+        if self.settings.is_active() {
+            self.os_handle.apply_cursor_grab(self.id, mode);
+        }
+    }
+
+    /// Get how the cursor is grabbed by the window.
+    pub fn cursor_grab(&self) -> CursorGrabMode {
+        self.cursor_grab
+    }
+
+    /// Set the system cursor shown while the cursor is over the client area of the window.
+    pub fn set_cursor_shape(&mut self, shape: CursorShape) {
+        let _old_cursor = core::mem::replace(&mut self.cursor, WindowCursor::Shape(shape));
+        self.os_handle.update_cursor(&self.cursor, self.cursor_visible);
+    }
+
+    /// Set a custom cursor shown while the cursor is over the client area of the window.
+    pub fn set_custom_cursor(&mut self, cursor: Cursor) {
+        // The old cursor is only destroyed after the window has stopped using it
+        let _old_cursor = core::mem::replace(&mut self.cursor, WindowCursor::Custom(cursor));
+        self.os_handle.update_cursor(&self.cursor, self.cursor_visible);
+    }
+
+    /// Get the system cursor shape used by the window, or `None` if the window uses a custom cursor.
+    pub fn cursor_shape(&self) -> Option<CursorShape> {
+        match self.cursor {
+            WindowCursor::Shape(shape) => Some(shape),
+            WindowCursor::Custom(_) => None,
+        }
+    }
+
+    /// Set whether the cursor is visible while it is over the client area of the window.
+    pub fn set_cursor_visible(&mut self, visible: bool) {
+        if self.cursor_visible == visible {
+            return;
+        }
+        self.cursor_visible = visible;
+        self.os_handle.update_cursor(&self.cursor, visible);
+    }
+
+    /// Check if the cursor is visible while it is over the client area of the window.
+    pub fn is_cursor_visible(&self) -> bool {
+        self.cursor_visible
+    }
+
     // Callbacks
 
     /// Register a window event callback.
@@ -3,9 +3,9 @@ use onca_common::{
     alloc::{get_active_alloc},
     sys::is_on_main_thread, sync::Mutex, event_listener::{EventListenerArray, EventListenerRef, EventListener},
 };
-use onca_logging::log_error;
+use onca_logging::{log_debug, log_error};
 
-use crate::{os, Window, WindowId, WindowSettings, LOG_CAT};
+use crate::{os, Monitor, MonitorEvent, MonitorEventListener, Window, WindowId, WindowSettings, LOG_CAT};
 
 /// Raw input data
 /// 
@@ -31,6 +31,8 @@ pub struct WindowManager {
     // Newly added callbacks that need to run during the next window manage tick
     new_callbacks:       Mutex<EventListenerArray<dyn EventListener<Window>>>,
     raw_input_callbacks: Mutex<EventListenerArray<dyn EventListener<RawInputEvent>>>,
+    monitors:            Vec<Monitor>,
+    monitor_listeners:   Mutex<EventListenerArray<MonitorEventListener>>,
 }
 
 impl WindowManager {
@@ -51,6 +53,8 @@ impl WindowManager {
             created_callbacks: Mutex::new(EventListenerArray::new()),
             new_callbacks: Mutex::new(EventListenerArray::new()),
             raw_input_callbacks: Mutex::new(EventListenerArray::new()),
+            monitors: Monitor::enumerate_monitors(),
+            monitor_listeners: Mutex::new(EventListenerArray::new()),
         })
     }
 
@@ -118,9 +122,17 @@ impl WindowManager {
             for window in &self.windows {
                 new_callbacks.notify(&window.1);
             }
+
+            // The callbacks now know about all existing windows, and will be notified of new windows using the created callbacks
+            new_callbacks.clear();
         }
 
-        self.os_data.tick()
+        self.os_data.tick();
+
+        // Multiple changes can be reported for a single configuration change, so only check for changes once all messages are processed
+        if self.os_data.take_monitors_changed() {
+            self.refresh_monitors();
+        }
     }
 
     /// Tick the window manager at the end of the frame, this will handle thing like destroying windows
@@ -176,11 +188,55 @@ impl WindowManager {
         }
     }
 
+    /// Get an iterator over all windows, starting with the main window.
+    /// 
+    /// This can be used to create the per-window resources of a system, e.g. a swap-chain for each window.
+    /// Any resources that depend on the OS window should be released when the window sends a `WindowEvent::Closed` event, as the window will be destroyed at the end of the frame.
+    pub fn windows(&self) -> impl Iterator<Item = &Window> {
+        self.main_window.as_deref().into_iter().chain(self.windows.iter().map(|(_, window)| &**window))
+    }
+
+    /// Get the handles of all windows, starting with the main window.
+    pub fn window_ids(&self) -> impl Iterator<Item = WindowId> + '_ {
+        self.windows().map(|window| window.id())
+    }
+
+    /// Get the number of windows, including the main window.
+    pub fn window_count(&self) -> usize {
+        self.main_window.is_some() as usize + self.windows.len()
+    }
+
+    /// Request all windows to close, starting with the main window.
+    /// 
+    /// Each window can still veto the request using the `cancel` callback of `WindowEvent::CloseRequested`, e.g. to ask the user to save unsaved changes.
+    pub fn request_close_all(&mut self) {
+        assert!(is_on_main_thread(), "Closing a window is only allowed on the main thread");
+
+        if let Some(main_window) = &mut self.main_window {
+            main_window.close();
+        }
+        for (_, window) in &mut self.windows {
+            window.close();
+        }
+    }
+
     /// Check if main window is still open.
     pub fn is_main_window_open(&self) -> bool {
         !self.main_window.as_ref().map_or(false, |window| window.is_closing())
     }
 
+    /// Get the text currently stored on the clipboard, or `None` if the clipboard does not contain any text.
+    pub fn clipboard_text(&mut self) -> Option<String> {
+        assert!(is_on_main_thread(), "The clipboard should only be accessed on the main thread");
+        os::clipboard::get_text(&mut self.os_data)
+    }
+
+    /// Store text on the clipboard, returns `false` if the text could not be stored.
+    pub fn set_clipboard_text(&mut self, text: &str) -> bool {
+        assert!(is_on_main_thread(), "The clipboard should only be accessed on the main thread");
+        os::clipboard::set_text(&mut self.os_data, text)
+    }
+
     /// Register a window created callback.
     /// 
     /// This callback is meant to allow the registration of callbacks on a window after it is created.
@@ -200,6 +256,7 @@ impl WindowManager {
     /// This function is thread-safe and can be called from any thread
     pub fn unregister_window_created_listener(&self, listener: &EventListenerRef<dyn EventListener<Window>>) {
         self.created_callbacks.lock().remove(listener);
+        self.new_callbacks.lock().remove(listener);
     }
 
     /// Register a raw input listener
@@ -220,12 +277,33 @@ impl WindowManager {
         self.raw_input_callbacks.lock().remove(listener);
     }
 
+    /// Get the monitors currently connected to the system.
+    /// 
+    /// The monitors are updated when the display configuration changes, see `register_monitor_listener`.
+    pub fn monitors(&self) -> &[Monitor] {
+        &self.monitors
+    }
+
+    /// Register a listener that is notified when a monitor is connected, disconnected, or has its configuration changed.
+    /// 
+    /// This function is thread-safe and can be called from any thread
+    pub fn register_monitor_listener(&self, listener: EventListenerRef<MonitorEventListener>) {
+        self.monitor_listeners.lock().push(listener);
+    }
+
+    /// Unregister a monitor listener
+    /// 
+    /// This function is thread-safe and can be called from any thread
+    pub fn unregister_monitor_listener(&self, listener: &EventListenerRef<MonitorEventListener>) {
+        self.monitor_listeners.lock().remove(listener);
+    }
+
     /// Enumerate over all existing windows and execute a callback
     /// 
     /// This function is meant to allow code to register callbacks on existing windows e.g. after creation of a new system
-    pub fn enumerate_window<F>(&mut self, callback: F) 
+    pub fn enumerate_window<F>(&mut self, mut callback: F) 
     where
-        F : Fn(&mut Window)
+        F : FnMut(&mut Window)
     {
         if let Some(main_window) = &mut self.main_window {
             callback(main_window);
@@ -249,6 +327,35 @@ impl WindowManager {
         self.raw_input_callbacks.lock().notify(&raw_input);
     }
 
+    /// Update the monitors and notify the monitor listeners of any monitor that was added, removed, or changed.
+    fn refresh_monitors(&mut self) {
+        let monitors = Monitor::enumerate_monitors();
+        let mut listeners = self.monitor_listeners.lock();
+
+        for old_monitor in &self.monitors {
+            if !monitors.iter().any(|monitor| monitor.dev_name() == old_monitor.dev_name()) {
+                log_debug!(LOG_CAT, "Monitor '{}' has been disconnected", old_monitor.dev_name());
+                listeners.notify(&MonitorEvent::Removed(old_monitor));
+            }
+        }
+
+        for monitor in &monitors {
+            match self.monitors.iter().find(|old_monitor| old_monitor.dev_name() == monitor.dev_name()) {
+                Some(old_monitor) => if monitor.has_config_changed(old_monitor) {
+                    log_debug!(LOG_CAT, "Monitor '{}' has changed its configuration", monitor.dev_name());
+                    listeners.notify(&MonitorEvent::ModeChanged(monitor));
+                },
+                None => {
+                    log_debug!(LOG_CAT, "Monitor '{}' has been connected", monitor.dev_name());
+                    listeners.notify(&MonitorEvent::Added(monitor));
+                },
+            }
+        }
+
+        drop(listeners);
+        self.monitors = monitors;
+    }
+
     fn notify_window_created(&self, window: &Window) {
         self.created_callbacks.lock().notify(&window)
     }
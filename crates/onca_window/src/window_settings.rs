@@ -64,6 +64,8 @@ pub enum Flags {
     AcceptFiles,
     /// Force top level.
     TopMost, 
+    /// Mouse input passes through the window, to whatever is below it.
+    ClickThrough,
 
     /// Window is DPI aware.
     DpiAware,
@@ -154,6 +156,7 @@ pub struct WindowSettings {
     pub(crate) read_order : ReadOrder,
     pub(crate) icon       : Option<Icon>,
     pub(crate) icon_sm    : Option<Icon>,
+    pub(crate) opacity    : f32,
     pub(crate) margins    : Margins,
 }
 
@@ -175,13 +178,14 @@ impl WindowSettings {
             read_order: ReadOrder::LeftToRight,
             icon: None,
             icon_sm: None,
+            opacity: 1.0,
             margins: Margins { top: 0, left: 0, bottom: 0, right: 0 }
         }
     }
 
     /// Create fullscreen window settings from a monitor.
     /// 
-    /// Fullscreen means windowed fullscreen/borderless windowed, exclusive fullscreen is not supported.
+    /// Fullscreen means windowed fullscreen/borderless windowed, exclusive fullscreen can be set after creation using `Window::set_fullscreen`.
     pub fn fullscreen_from_monitor(monitor: &Monitor) -> WindowSettings {
         let (x, y) = monitor.position();
         let (width, height) = monitor.size();
@@ -198,6 +202,7 @@ impl WindowSettings {
             read_order: ReadOrder::LeftToRight,
             icon: None,
             icon_sm: None,
+            opacity: 1.0,
             margins: Margins { top: 0, left: 0, bottom: 0, right: 0 }
         }
     }
@@ -347,6 +352,18 @@ impl WindowSettings {
         self
     }
 
+    /// Set if mouse input passes through the window, to whatever is below it
+    pub fn click_through(mut self, enable: bool) -> Self {
+        self.flags.set(Flags::ClickThrough, enable);
+        self
+    }
+
+    /// Set the opacity of the window, ranging from 0 (fully transparent) to 1 (fully opaque)
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
     /// Set if the window is active
     pub fn active(mut self, active: bool) -> Self {
         self.flags.set(Flags::Active, active);
@@ -390,39 +407,30 @@ impl WindowSettings {
     }
 
     /// Get the window client area size in physical pixels.
+    /// 
+    /// If the size was set in logical pixels, it is automatically scaled to the current DPI of the window.
     pub fn size(&self) -> PhysicalSize {
-        match self.size {
-            Size::Physical(size) => size,
-            Size::Logical(size) => {
-                let scale = self.dpi as f32 / Self::DEFAULT_DPI as f32;
-                size.to_physical(scale).cast()
-            },
-        }
+        self.to_physical_size(self.size)
     }
 
     /// Get the window area size in physical pixels.
     pub fn size_with_borders(&self) -> PhysicalSize {
-        let size = match self.size {
-            Size::Physical(size) => size,
-            Size::Logical(size) => {
-                let scale = self.dpi as f32 / Self::DEFAULT_DPI as f32;
-                size.to_physical(scale).cast()
-            },
-        };
-
-        size + self.margins.size()
+        self.size() + self.margins.size()
     }
 
     /// Get the window client area size in logical pixels.
     pub fn logical_size(&self) -> LogicalSize {
-        let scale = self.dpi as f32 / Self::DEFAULT_DPI as f32;
-        self.size.to_logical(scale)
+        self.to_logical_size(self.size)
     }
 
     /// Get the window area size in logical pixels.
     pub fn logical_size_with_borders(&self) -> LogicalSize {
-        let scale = self.dpi as f32 / Self::DEFAULT_DPI as f32;
-        self.size.to_logical(scale) + self.margins.size().cast().to_logical(scale)
+        self.to_logical_size(self.size) + self.to_logical_size(self.margins.size())
+    }
+
+    /// Get the window position (window's client area) in logical pixels.
+    pub fn logical_position(&self) -> LogicalPosition {
+        self.to_logical_position(self.position())
     }
 
     /// Get the window dpi
@@ -435,6 +443,26 @@ impl WindowSettings {
         self.dpi as f32 / Self::DEFAULT_DPI as f32
     }
 
+    /// Convert a size to physical pixels, using the current DPI of the window
+    pub fn to_physical_size<S: Into<Size>>(&self, size: S) -> PhysicalSize {
+        size.into().to_physical(self.dpi_scale())
+    }
+
+    /// Convert a size to logical pixels, using the current DPI of the window
+    pub fn to_logical_size<S: Into<Size>>(&self, size: S) -> LogicalSize {
+        size.into().to_logical(1.0 / self.dpi_scale())
+    }
+
+    /// Convert a position to physical pixels, using the current DPI of the window
+    pub fn to_physical_position<P: Into<PixelPos>>(&self, pos: P) -> PhysicalPosition {
+        pos.into().to_physical(self.dpi_scale())
+    }
+
+    /// Convert a position to logical pixels, using the current DPI of the window
+    pub fn to_logical_position<P: Into<PixelPos>>(&self, pos: P) -> LogicalPosition {
+        pos.into().to_logical(1.0 / self.dpi_scale())
+    }
+
     /// Get the window style
     pub fn flags(&self) -> Flags {
         self.flags
@@ -506,6 +534,16 @@ impl WindowSettings {
         self.flags.contains(Flags::TopMost)
     }
 
+    /// Check if mouse input passes through the window
+    pub fn is_click_through(&self) -> bool {
+        self.flags.contains(Flags::ClickThrough)
+    }
+
+    /// Get the opacity of the window
+    pub fn opacity(&self) -> f32 {
+        self.opacity
+    }
+
     /// Check if the window is active
     pub fn is_active(&self) -> bool {
         self.flags.contains(Flags::Active)
@@ -557,23 +595,11 @@ impl WindowSettings {
     }
 
     pub(crate) fn pos_to_physical_pos(&mut self, pos: PixelPos) -> PhysicalPosition {
-        match pos {
-            pixel::Position::Physical(pos) => pos,
-            pixel::Position::Logical(pos) => {
-                let scale = self.dpi_scale();
-                pos.to_physical(scale).cast() 
-            },
-        }
+        self.to_physical_position(pos)
     }
 
     pub(crate) fn size_to_physical_size(&mut self, size: Size) -> PhysicalSize {
-        match size {
-            Size::Physical(size) => size,
-            Size::Logical(size) => {
-                let scale = self.dpi_scale();
-                size.to_physical(scale).cast()
-            }
-        }
+        self.to_physical_size(size)
     }
 
     pub(crate) fn set_minmax_state(&mut self, flags: Flags) -> Flags {
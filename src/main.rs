@@ -18,13 +18,14 @@ use onca_common::{
     io::{self, Read},
     sys
 };
-use onca_logging::{log_debug, log_error, log_info, log_verbose, set_logger, LogCategory, LogLocation, Logger};
+use onca_logging::{log_debug, log_error, log_info, log_verbose, log_warning, set_logger, LogCategory, LogLocation, Logger};
 use onca_math::*;
 use onca_ral::{self as ral, define_ral_exports};
 use onca_terminal::*;
 use onca_toml::Toml;
 use onca_window::{WindowManager, WindowSettings, Flags, Monitor, PhysicalSize, WindowEvent, WindowId};
 use onca_input::{self as input, InputManager};
+use onca_shader_compiler::{ShaderCompiler, ShaderCompileDesc, ShaderCompileFlags, ShaderModel, ShaderTarget};
 use onca_fs as fs;
 use ral::{TextureUsage, CommandPoolFlags, Barrier, ResourceState, BarrierQueueTransferOp, RenderTargetAttachmentDesc, RenderingInfo, RenderingInfoFlags, CommandListBeginFlags, FenceHandle};
 
@@ -63,7 +64,8 @@ fn main() {
     _ = onca_logging::get_logger().add_writer(Box::new(Terminal));
 
     let output_file = onca_fs::File::create(Path::new("onca.log").unwrap(), fs::OpenMode::CreateAlways, fs::Permission::Write, fs::Permission::None, fs::FileCreateFlags::None, fs::FileAccessFlags::None).unwrap();
-    _ = onca_logging::get_logger().add_writer(Box::new(output_file));
+    // The log file should only contain plain text, not the colors that are written to the terminal
+    _ = onca_logging::get_logger().add_writer(Box::new(AnsiStripWriter::new(output_file)));
 
     _ = onca_common::sys::init_system().map_err(|s| panic!("{s}"));
     _ = Terminal::init();
@@ -139,6 +141,16 @@ fn actual_main(global_state: &GlobalState) {
         }
     };
     onca_logging::get_logger().flush();
+
+    // Create the shader compiler before any window listeners are registered, so we can still bail out here
+    let shader_compiler = match ShaderCompiler::new(get_dxc_library_path()) {
+        Ok(shader_compiler) => shader_compiler,
+        Err(err) => {
+            log_error!(LOG_CAT, "{err}");
+            onca_logging::get_logger().flush();
+            return;
+        }
+    };
     
     let mut phys_dev_to_use = None;
     let mut max_dedicated_memory = 0;
@@ -184,24 +196,38 @@ fn actual_main(global_state: &GlobalState) {
         None,
         None,
     ];
+    let mut graph_transients : [Option<ral::graph::GraphTransientResources>; 3] = [
+        None,
+        None,
+        None,
+    ];
 
-    let (vs_path, ps_path) = if ral.settings().api == ral::RalApi::DX12 {
-        (Path::new("data/shaders/dxil/tri.vs.dxil").unwrap(),
-         Path::new("data/shaders/dxil/tri.ps.dxil").unwrap())
-    } else {
-        (Path::new("data/shaders/spirv/tri.vs.spirv").unwrap(),
-         Path::new("data/shaders/spirv/tri.ps.spirv").unwrap())
-    };
+    let shader_target = ShaderTarget::from_api(&ral.settings().api).expect("The current RAL api does not support compiled HLSL shaders");
+
+    let compile_shader = |path: &str, shader_type: ral::ShaderType| {
+        let desc = ShaderCompileDesc {
+            source_name: "",
+            entry_point: "main",
+            shader_type,
+            target: shader_target,
+            shader_model: ShaderModel::SM_6_7,
+            defines: &[],
+            include_dirs: &["data/shaders"],
+            flags: ShaderCompileFlags::Debug | ShaderCompileFlags::WarningsAsErrors,
+        };
 
-    let mut vs_shader_file = fs::File::open(vs_path, fs::Permission::Read, fs::Permission::None, fs::FileAccessFlags::None).unwrap();
-    let mut vs_shader_code = Vec::new();
-    vs_shader_file.read_to_end(&mut vs_shader_code).unwrap();
-    let vs_shader_blob = device.create_shader(&vs_shader_code, ral::ShaderType::Vertex).unwrap();
+        let compiled = match shader_compiler.compile_file(Path::new(path).unwrap(), &desc) {
+            Ok(compiled) => compiled,
+            Err(err) => panic!("Failed to compile '{path}': {err}"),
+        };
+        if let Some(warnings) = &compiled.warnings {
+            log_warning!(LOG_CAT, "Shader compiler output for '{path}':\n{warnings}");
+        }
+        device.create_shader(&compiled.code, shader_type).unwrap()
+    };
 
-    let mut ps_shader_file = fs::File::open(ps_path, fs::Permission::Read, fs::Permission::None, fs::FileAccessFlags::None).unwrap();
-    let mut ps_shader_code = Vec::new();
-    ps_shader_file.read_to_end(&mut ps_shader_code).unwrap();
-    let ps_shader_blob = device.create_shader(&ps_shader_code, ral::ShaderType::Pixel).unwrap();
+    let vs_shader_blob = compile_shader("data/shaders/tri.vs.hlsl", ral::ShaderType::Vertex);
+    let ps_shader_blob = compile_shader("data/shaders/tri.ps.hlsl", ral::ShaderType::Pixel);
 
     let mut rendertarget_formats = [Default::default(); ral::constants::MAX_RENDERTARGETS as usize];
     rendertarget_formats[0] = Some(swapchain.backbuffer_format());
@@ -227,31 +253,27 @@ fn actual_main(global_state: &GlobalState) {
     let indices_size = core::mem::size_of_val(&indices);
     let indices_bytes = unsafe { core::slice::from_raw_parts(indices.as_ptr() as *const u8, indices_size) };
 
+    let mut upload_context = ral::UploadContext::new(&device, MiB(4) as u64).unwrap();
+
     let vertex_buffer_desc = ral::BufferDesc {
         size: vertices_size as u64,
-        usage: ral::BufferUsage::VertexBuffer,
+        usage: ral::BufferUsage::VertexBuffer | ral::BufferUsage::CopyDst,
         alloc_desc: ral::GpuAllocationDesc {
-            memory_type: ral::MemoryType::Upload,
+            memory_type: ral::MemoryType::Gpu,
             flags: ral::MemoryAllocationFlags::None,
         },
     };
     let vertex_buffer = device.create_buffer(&vertex_buffer_desc).unwrap();
-    let mut mapped_memory = vertex_buffer.map(0, u64::MAX).unwrap();
-    mapped_memory.write(vertices_bytes);
-    vertex_buffer.unmap(mapped_memory);
 
     let index_buffer_desc = ral::BufferDesc {
-        size: vertices_size as u64,
-        usage: ral::BufferUsage::IndexBuffer,
+        size: indices_size as u64,
+        usage: ral::BufferUsage::IndexBuffer | ral::BufferUsage::CopyDst,
         alloc_desc: ral::GpuAllocationDesc {
-            memory_type: ral::MemoryType::Upload,
+            memory_type: ral::MemoryType::Gpu,
             flags: ral::MemoryAllocationFlags::None,
         },
     };
     let index_buffer = device.create_buffer(&index_buffer_desc).unwrap();
-    let mut mapped_memory = index_buffer.map(0, u64::MAX).unwrap();
-    mapped_memory.write(&indices_bytes);
-    index_buffer.unmap(mapped_memory);
 
     let constant_buffer = (
         Mat4::<f32>::identity(),
@@ -264,16 +286,32 @@ fn actual_main(global_state: &GlobalState) {
 
     let constant_buffer_desc = ral::BufferDesc {
         size: constant_buffer_size as u64,
-        usage: ral::BufferUsage::ConstantBuffer,
+        usage: ral::BufferUsage::ConstantBuffer | ral::BufferUsage::CopyDst,
         alloc_desc: ral::GpuAllocationDesc {
-            memory_type: ral::MemoryType::Upload,
+            memory_type: ral::MemoryType::Gpu,
             flags: ral::MemoryAllocationFlags::None
         },
     };
     let constant_buffer = device.create_buffer(&constant_buffer_desc).unwrap();
-    let mut mapped_memory = constant_buffer.map(0, u64::MAX).unwrap();
-    mapped_memory.write(constant_buffer_bytes);
-    constant_buffer.unmap(mapped_memory);
+
+    let upload_command_list = command_allocators[0].allocate().unwrap();
+    upload_command_list.begin(CommandListBeginFlags::OneTimeSubmit).unwrap();
+    upload_context.upload_buffer(&upload_command_list, &vertex_buffer, 0, vertices_bytes).unwrap();
+    upload_context.upload_buffer(&upload_command_list, &index_buffer, 0, indices_bytes).unwrap();
+    upload_context.upload_buffer(&upload_command_list, &constant_buffer, 0, constant_buffer_bytes).unwrap();
+    upload_command_list.barrier(&[Barrier::Global {
+        before: ResourceState::COPY_WRITE,
+        after: ResourceState::VERTEX_INPUT | ResourceState::INDEX_INPUT | ResourceState::new(ral::Access::ConstantBuffer, ral::SyncPoint::Vertex),
+    }]);
+    upload_command_list.close().unwrap();
+
+    let upload_signal = [upload_context.end_frame()];
+    graphics_queue.submit(&ral::CommandListSubmitInfo {
+        command_lists: &[upload_command_list],
+        wait_fences: None,
+        signal_fences: Some(&upload_signal),
+    }).unwrap();
+    graphics_queue.flush().unwrap();
 
     //--------------
 
@@ -332,6 +370,7 @@ fn actual_main(global_state: &GlobalState) {
             ral::BlendOp::Add,
             ral::ColorWriteMask::all(),
         )]),
+        multisample_state: ral::MultisampleState::default(),
         input_layout: Some(input_layout),
         rendertarget_formats,
         depth_stencil_format: None,
@@ -393,82 +432,75 @@ fn actual_main(global_state: &GlobalState) {
 
         let back_buffer = swapchain.get_current_backbuffer();
 
-        let present_to_clear_barrier = Barrier::new_basic_texture(
-            ResourceState::PRESENT,
-            ResourceState::new_tex(ral::Access::RenderTargetWrite, ral::SyncPoint::All, ral::TextureLayout::RenderTarget),
-            back_buffer.0.clone()
-        );
-
-        command_list.barrier(&[present_to_clear_barrier]);
-        
-        let rendering_info = RenderingInfo {
-            flags: RenderingInfoFlags::None,
-            render_area: ral::Rect{ x: 0, y: 0, width: width as u32, height: height as u32 },
-            layers_or_view_mask: ral::RenderingInfoLayersOrViewMask::Layers(NonZeroU8::new(1).unwrap()),
-            render_targets: &[ral::RenderTargetAttachmentDesc {
-                rtv: back_buffer.1.clone(),
-                layout: ral::TextureLayout::RenderTarget,
-                load_op: ral::AttachmentLoadOp::Clear(ral::ClearColor::Float([0.4, 0.6, 0.9, 1.0])),
-                store_op: ral::AttachmentStoreOp::Store,
-            }],
-            depth_stencil: None,
-        };
-
-        let viewport = ral::Viewport {
-            x: 0.0,
-            y: 0.0,
-            width: width as f32,
-            height: height as f32,
-            min_depth: 0.0,
-            max_depth: 1.0,
-        };
-
-        let scissor = ral::ScissorRect {
-            x: 0,
-            y: 0,
-            width: width as u16,
-            height: height as u16,
-        };
-
-        command_list.begin_rendering(&rendering_info);
-
-        let vertex_buffer_view = ral::VertexBufferView {
-            input_slot: 0,
-            buffer: vertex_buffer.clone(),
-            offset: 0,
-            size: u64::MAX,
-            stride: core::mem::size_of::<Vertex>() as u16,
-        };
-        let index_buffer_view = ral::IndexBufferView {
-            buffer: index_buffer.clone(),
-            offset: 0,
-            size: u64::MAX,
-            index_format: ral::IndexFormat::U16,
-        };
-        
-        command_list.bind_graphics_pipeline_layout(&pipeline_layout);
-        command_list.bind_graphics_pipeline(&pipeline);
-
-        command_list.bind_descriptor_heaps(Some(&descriptor_heap), None);
-        command_list.set_graphics_descriptor_table(0, descriptor_heap.get_gpu_descriptor(0).unwrap());
-
-        command_list.set_scissors(&[scissor]);
-        command_list.set_viewport(&[viewport]);
-        command_list.set_primitive_topology(ral::PrimitiveTopology::TriangleList);
-
-        command_list.bind_vertex_buffer(vertex_buffer_view);
-        command_list.bind_index_buffer(index_buffer_view);
-        command_list.draw_indexed(6, 0, 0);
-        
-        command_list.end_rendering();
-        
-        
-        let clear_barrier_to_present = Barrier::new_basic_texture(
-            ResourceState::new_tex(ral::Access::RenderTargetWrite, ral::SyncPoint::All, ral::TextureLayout::RenderTarget),
-            ResourceState::PRESENT,
-            back_buffer.0.clone()
-        );
-        command_list.barrier(&[clear_barrier_to_present]);
+        let mut graph = ral::graph::RenderGraph::new();
+        let graph_back_buffer = graph.import_texture("backbuffer", back_buffer.0.clone(), ResourceState::PRESENT, Some(ResourceState::PRESENT));
+
+        graph.add_pass("main")
+            .write(graph_back_buffer, ResourceState::new_tex(ral::Access::RenderTargetWrite, ral::SyncPoint::All, ral::TextureLayout::RenderTarget))
+            .execute(|command_list, _| {
+                let rendering_info = RenderingInfo {
+                    flags: RenderingInfoFlags::None,
+                    render_area: ral::Rect{ x: 0, y: 0, width: width as u32, height: height as u32 },
+                    layers_or_view_mask: ral::RenderingInfoLayersOrViewMask::Layers(NonZeroU8::new(1).unwrap()),
+                    render_targets: &[ral::RenderTargetAttachmentDesc {
+                        rtv: back_buffer.1.clone(),
+                        layout: ral::TextureLayout::RenderTarget,
+                        load_op: ral::AttachmentLoadOp::Clear(ral::ClearColor::Float([0.4, 0.6, 0.9, 1.0])),
+                        store_op: ral::AttachmentStoreOp::Store,
+                    }],
+                    depth_stencil: None,
+                };
+
+                let viewport = ral::Viewport {
+                    x: 0.0,
+                    y: 0.0,
+                    width: width as f32,
+                    height: height as f32,
+                    min_depth: 0.0,
+                    max_depth: 1.0,
+                };
+
+                let scissor = ral::ScissorRect {
+                    x: 0,
+                    y: 0,
+                    width: width as u16,
+                    height: height as u16,
+                };
+
+                command_list.begin_rendering(&rendering_info);
+
+                let vertex_buffer_view = ral::VertexBufferView {
+                    input_slot: 0,
+                    buffer: vertex_buffer.clone(),
+                    offset: 0,
+                    size: u64::MAX,
+                    stride: core::mem::size_of::<Vertex>() as u16,
+                };
+                let index_buffer_view = ral::IndexBufferView {
+                    buffer: index_buffer.clone(),
+                    offset: 0,
+                    size: u64::MAX,
+                    index_format: ral::IndexFormat::U16,
+                };
+                
+                command_list.bind_graphics_pipeline_layout(&pipeline_layout);
+                command_list.bind_graphics_pipeline(&pipeline);
+
+                command_list.bind_descriptor_heaps(Some(&descriptor_heap), None);
+                command_list.set_graphics_descriptor_table(0, descriptor_heap.get_gpu_descriptor(0).unwrap());
+
+                command_list.set_scissors(&[scissor]);
+                command_list.set_viewport(&[viewport]);
+                command_list.set_primitive_topology(ral::PrimitiveTopology::TriangleList);
+
+                command_list.bind_vertex_buffer(vertex_buffer_view);
+                command_list.bind_index_buffer(index_buffer_view);
+                command_list.draw_indexed(6, 0, 0);
+                
+                command_list.end_rendering();
+            });
+
+        graph_transients[backbuffer_idx] = Some(graph.compile(&device).expect("Failed to compile render graph").execute(&command_list));
         
         command_list.close().unwrap();
 
@@ -521,6 +553,16 @@ fn actual_main(global_state: &GlobalState) {
     //device.flush().unwrap();
 }
 
+fn get_dxc_library_path() -> &'static str {
+    #[cfg(target_os = "windows")]
+    let path = "dxc/windows/dxc_2023_03_01/bin/x64/dxcompiler.dll";
+    #[cfg(target_os = "linux")]
+    let path = "dxc/linux/dxc_2023_03_01/lib/libdxcompiler.so";
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    let path = compile_error!("DXC is not available for the current platform");
+    path
+}
+
 fn is_device_suitable(phys_dev: &ral::PhysicalDevice) -> Option<u64> {
     if phys_dev.properties.dev_type == ral::physical_device::PhysicalDeviceType::Discrete {
         Some(phys_dev.memory_info.heaps[ral::MemoryHeapType::Gpu as usize].size)